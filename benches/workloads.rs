@@ -0,0 +1,159 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use extended_collections::avl_tree::AvlMap;
+use extended_collections::bench_support::{self, KeyDistribution, Op, Workload, WorkloadMap};
+use extended_collections::bp_tree::BpMap;
+use extended_collections::lsm_tree::compaction::{LeveledStrategy, SizeTieredStrategy};
+use extended_collections::lsm_tree::LsmMap;
+use extended_collections::red_black_tree::RedBlackMap;
+use extended_collections::skiplist::SkipMap;
+use extended_collections::splay_tree::SplayMap;
+use extended_collections::treap::TreapMap;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const KEY_COUNT: u64 = 10_000;
+const NUM_OF_OPERATIONS: usize = 1000;
+const RANGE_LEN: usize = 16;
+
+const DISTRIBUTIONS: [(&str, KeyDistribution); 3] = [
+    ("uniform", KeyDistribution::Uniform),
+    ("zipfian", KeyDistribution::Zipfian(0.99)),
+    ("sequential", KeyDistribution::Sequential),
+];
+
+static NEXT_PATH_ID: AtomicUsize = AtomicUsize::new(0);
+
+// A disk-resident map whose files are removed when it is dropped.
+struct TempMap<M> {
+    map: Option<M>,
+    path: PathBuf,
+}
+
+impl<M> TempMap<M> {
+    fn new<F>(name: &str, create: F) -> Self
+    where
+        F: FnOnce(&PathBuf) -> M,
+    {
+        let id = NEXT_PATH_ID.fetch_add(1, Ordering::Relaxed);
+        let path = PathBuf::from(format!("bench_{}_{}", name, id));
+        TempMap {
+            map: Some(create(&path)),
+            path,
+        }
+    }
+}
+
+impl<M> WorkloadMap for TempMap<M>
+where
+    M: WorkloadMap,
+{
+    fn insert(&mut self, key: u64, value: u64) {
+        self.map.as_mut().expect("Expected map.").insert(key, value);
+    }
+
+    fn get(&mut self, key: u64) -> Option<u64> {
+        self.map.as_mut().expect("Expected map.").get(key)
+    }
+
+    fn range(&mut self, start: u64, len: usize) -> u64 {
+        self.map.as_mut().expect("Expected map.").range(start, len)
+    }
+}
+
+impl<M> Drop for TempMap<M> {
+    fn drop(&mut self) {
+        drop(self.map.take());
+        if self.path.is_dir() {
+            fs::remove_dir_all(&self.path).ok();
+        } else {
+            fs::remove_file(&self.path).ok();
+        }
+    }
+}
+
+// Returns a map that contains every key of the workloads.
+fn populate<M, F>(create: &F) -> M
+where
+    M: WorkloadMap,
+    F: Fn() -> M,
+{
+    let mut map = create();
+    let ops: Vec<Op> = (0..KEY_COUNT).map(|key| Op::Insert(key, key)).collect();
+    bench_support::run(&mut map, &ops);
+    map
+}
+
+fn bench_map<M, F>(c: &mut Criterion, name: &str, create: F)
+where
+    M: WorkloadMap + 'static,
+    F: Fn() -> M + Copy + 'static,
+{
+    for &(distribution_name, distribution) in &DISTRIBUTIONS {
+        let ops = Workload::new(distribution, KEY_COUNT, 1, 0, 0, 0).generate(NUM_OF_OPERATIONS, 1);
+        c.bench_function(
+            &format!("bench {} insert {}", name, distribution_name),
+            move |b| {
+                b.iter_with_setup(create, |mut map| {
+                    black_box(bench_support::run(&mut map, &ops));
+                    map
+                })
+            },
+        );
+
+        let ops = Workload::new(distribution, KEY_COUNT, 0, 1, 0, 0).generate(NUM_OF_OPERATIONS, 2);
+        let mut map = populate(&create);
+        c.bench_function(
+            &format!("bench {} get {}", name, distribution_name),
+            move |b| b.iter(|| black_box(bench_support::run(&mut map, &ops))),
+        );
+
+        let ops = Workload::new(distribution, KEY_COUNT, 0, 0, 1, RANGE_LEN)
+            .generate(NUM_OF_OPERATIONS, 3);
+        let mut map = populate(&create);
+        c.bench_function(
+            &format!("bench {} range {}", name, distribution_name),
+            move |b| b.iter(|| black_box(bench_support::run(&mut map, &ops))),
+        );
+    }
+}
+
+fn bench_in_memory_maps(c: &mut Criterion) {
+    bench_map(c, "btreemap", BTreeMap::<u64, u64>::new);
+    bench_map(c, "avl_tree", AvlMap::<u64, u64>::new);
+    bench_map(c, "red_black_tree", RedBlackMap::<u64, u64>::new);
+    bench_map(c, "skiplist", SkipMap::<u64, u64>::new);
+    bench_map(c, "splay_tree", SplayMap::<u64, u64>::new);
+    bench_map(c, "treap", TreapMap::<u64, u64>::new);
+}
+
+fn bench_disk_maps(c: &mut Criterion) {
+    bench_map(c, "bp_tree", || {
+        TempMap::new("bp_tree", |path| {
+            BpMap::<u64, u64>::new(path, 8, 8).expect("Expected map.")
+        })
+    });
+    bench_map(c, "lsm_tree_size_tiered", || {
+        TempMap::new("lsm_tree_size_tiered", |path| {
+            let sts = SizeTieredStrategy::new(path, 16_000, 4, 64_000, 0.5, 1.5)
+                .expect("Expected strategy.");
+            LsmMap::<u64, u64, _>::new(sts)
+        })
+    });
+    bench_map(c, "lsm_tree_leveled", || {
+        TempMap::new("lsm_tree_leveled", |path| {
+            let ls =
+                LeveledStrategy::new(path, 16_000, 4, 64_000, 4, 10).expect("Expected strategy.");
+            LsmMap::<u64, u64, _>::new(ls)
+        })
+    });
+}
+
+criterion_group!(in_memory_benches, bench_in_memory_maps);
+criterion_group! {
+    name = disk_benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_disk_maps
+}
+criterion_main!(in_memory_benches, disk_benches);
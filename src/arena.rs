@@ -1,16 +1,53 @@
 //! Fast, but limited allocator.
 
-use std::mem;
-use std::ops::{Index, IndexMut};
-use std::vec::Vec;
+use alloc::vec::Vec;
+use core::iter::Enumerate;
+use core::mem;
+use core::ops::{Index, IndexMut};
+use core::slice;
+#[cfg(feature = "serde")]
+use serde::de::{Deserialize, Deserializer, Error as DeError};
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
 
-/// A struct representing an entry to `TypedArena<T>`
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// A struct representing an entry to `TypedArena<T>`.
+///
+/// An entry is the position of an object in the typed arena, so it can be converted to and from
+/// a `usize`. This allows entries to be stored compactly or used as keys of other collections.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::arena::{Entry, TypedArena};
+///
+/// let mut arena = TypedArena::new(1024);
+/// let x = arena.insert(0);
+/// let y = arena.insert(1);
+///
+/// assert_eq!(usize::from(y), 1);
+/// assert_eq!(arena.get(&Entry::from(0)), Some(&0));
+/// assert_eq!(Entry::from(usize::from(x)), x);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Entry {
-    chunk_index: usize,
-    block_index: usize,
+    index: usize,
+}
+
+impl From<usize> for Entry {
+    fn from(index: usize) -> Self {
+        Entry { index }
+    }
+}
+
+impl From<Entry> for usize {
+    fn from(entry: Entry) -> Self {
+        entry.index
+    }
 }
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 enum Block<T> {
     Occupied(T),
     Vacant(Option<Entry>),
@@ -24,6 +61,10 @@ enum Block<T> {
 /// the code itself is very simple and uses no unsafe code. When the typed arena is full, it will
 /// allocate another chunk of objects so no memory is reallocated.
 ///
+/// The typed arena can also be used as a slab: `insert` returns a stable `Entry` that remains
+/// valid until the object is removed, and the blocks of removed objects are reused by later
+/// insertions.
+///
 /// # Examples
 ///
 /// ```
@@ -39,6 +80,8 @@ enum Block<T> {
 ///
 /// assert_eq!(arena.free(&x), 2);
 /// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct TypedArena<T> {
     head: Option<Entry>,
     chunks: Vec<Vec<Block<T>>>,
@@ -47,14 +90,106 @@ pub struct TypedArena<T> {
     capacity: usize,
 }
 
+// the serialized fields of `TypedArena<T>`, which are checked before they are used
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(rename = "TypedArena")]
+struct TypedArenaFields<T> {
+    head: Option<Entry>,
+    chunks: Vec<Vec<Block<T>>>,
+    chunk_size: usize,
+    size: usize,
+    capacity: usize,
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> Deserialize<'de> for TypedArena<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields = TypedArenaFields::deserialize(deserializer)?;
+        let arena = TypedArena {
+            head: fields.head,
+            chunks: fields.chunks,
+            chunk_size: fields.chunk_size,
+            size: fields.size,
+            capacity: fields.capacity,
+        };
+        arena.check_invariants().map_err(D::Error::custom)?;
+        Ok(arena)
+    }
+}
+
 impl<T> TypedArena<T> {
+    #[cfg(feature = "serde")]
+    fn check_invariants(&self) -> Result<(), &'static str> {
+        if self.chunk_size == 0 {
+            return Err("chunk size must be positive");
+        }
+        if self.chunks.len().checked_mul(self.chunk_size) != Some(self.capacity) {
+            return Err("capacity does not match the number of chunks");
+        }
+        // objects are only appended to the last chunk, so every other chunk must be full
+        let chunk_count = self.chunks.len();
+        for (chunk_index, chunk) in self.chunks.iter().enumerate() {
+            if chunk.len() > self.chunk_size
+                || (chunk_index + 1 < chunk_count && chunk.len() != self.chunk_size)
+            {
+                return Err("chunk has an invalid number of blocks");
+            }
+        }
+
+        let block_count: usize = self.chunks.iter().map(Vec::len).sum();
+        let occupied_count = self
+            .chunks
+            .iter()
+            .flat_map(|chunk| chunk.iter())
+            .filter(|block| matches!(block, Block::Occupied(_)))
+            .count();
+        if occupied_count != self.size {
+            return Err("length does not match the number of objects");
+        }
+
+        // the free list must visit every vacant block exactly once
+        let vacant_count = block_count - occupied_count;
+        let mut visited_count = 0;
+        let mut curr = self.head;
+        while let Some(entry) = curr {
+            if visited_count == vacant_count || !self.is_valid_entry(&entry) {
+                return Err("free list is invalid");
+            }
+            let (chunk_index, block_index) = self.get_position(&entry);
+            match self.chunks[chunk_index][block_index] {
+                Block::Vacant(next_entry) => curr = next_entry,
+                Block::Occupied(_) => return Err("free list is invalid"),
+            }
+            visited_count += 1;
+        }
+        if visited_count != vacant_count {
+            return Err("free list is invalid");
+        }
+        Ok(())
+    }
+
+    fn get_position(&self, entry: &Entry) -> (usize, usize) {
+        (entry.index / self.chunk_size, entry.index % self.chunk_size)
+    }
+
     fn is_valid_entry(&self, entry: &Entry) -> bool {
-        entry.chunk_index < self.chunks.len()
-            && entry.block_index < self.chunks[entry.chunk_index].len()
+        let (chunk_index, block_index) = self.get_position(entry);
+        chunk_index < self.chunks.len() && block_index < self.chunks[chunk_index].len()
     }
 
     /// Constructs a new, empty `TypedArena<T>` with a specific number of objects per chunk.
     ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    ///
     /// # Examples
     ///
     /// ```
@@ -64,6 +199,7 @@ impl<T> TypedArena<T> {
     /// let arena: TypedArena<u32> = TypedArena::new(1024);
     /// ```
     pub fn new(chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "Error: chunk size must be positive.");
         TypedArena {
             head: None,
             chunks: Vec::new(),
@@ -97,13 +233,13 @@ impl<T> TypedArena<T> {
                 let last_chunk = &mut self.chunks[chunk_count - 1];
                 last_chunk.push(Block::Occupied(value));
                 Entry {
-                    chunk_index: chunk_count - 1,
-                    block_index: last_chunk.len() - 1,
+                    index: (chunk_count - 1) * self.chunk_size + last_chunk.len() - 1,
                 }
             }
             Some(entry) => {
+                let (chunk_index, block_index) = self.get_position(&entry);
                 let vacant_block = mem::replace(
-                    &mut self.chunks[entry.chunk_index][entry.block_index],
+                    &mut self.chunks[chunk_index][block_index],
                     Block::Occupied(value),
                 );
 
@@ -138,23 +274,114 @@ impl<T> TypedArena<T> {
         if !self.is_valid_entry(entry) {
             panic!("Error: attempting to free invalid block.");
         }
+        let (chunk_index, block_index) = self.get_position(entry);
         let old_block = mem::replace(
-            &mut self.chunks[entry.chunk_index][entry.block_index],
+            &mut self.chunks[chunk_index][block_index],
             Block::Vacant(self.head.take()),
         );
         match old_block {
             Block::Vacant(_) => panic!("Error: attempting to free vacant block."),
             Block::Occupied(value) => {
                 self.size -= 1;
-                self.head = Some(Entry {
-                    chunk_index: entry.chunk_index,
-                    block_index: entry.block_index,
-                });
+                self.head = Some(*entry);
                 value
             }
         }
     }
 
+    /// Inserts an object into the typed arena and returns its entry. The entry remains valid until
+    /// the object is removed. This method is identical to `allocate`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::TypedArena;
+    ///
+    /// let mut arena = TypedArena::new(1024);
+    /// let x = arena.insert(0);
+    /// assert_eq!(arena[x], 0);
+    /// ```
+    pub fn insert(&mut self, value: T) -> Entry {
+        self.allocate(value)
+    }
+
+    /// Removes an object from the typed arena and returns the object. Returns `None` if the entry
+    /// does not correspond to a valid object. Unlike `free`, this method does not panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::TypedArena;
+    ///
+    /// let mut arena = TypedArena::new(1024);
+    /// let x = arena.insert(0);
+    /// assert_eq!(arena.remove(&x), Some(0));
+    /// assert_eq!(arena.remove(&x), None);
+    /// ```
+    pub fn remove(&mut self, entry: &Entry) -> Option<T> {
+        if !self.contains(entry) {
+            return None;
+        }
+        Some(self.free(entry))
+    }
+
+    /// Returns `true` if the entry corresponds to a valid object in the typed arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::TypedArena;
+    ///
+    /// let mut arena = TypedArena::new(1024);
+    /// let x = arena.insert(0);
+    /// assert!(arena.contains(&x));
+    ///
+    /// arena.remove(&x);
+    /// assert!(!arena.contains(&x));
+    /// ```
+    pub fn contains(&self, entry: &Entry) -> bool {
+        self.get(entry).is_some()
+    }
+
+    /// Retains only the objects specified by the predicate. All objects for which `f` returns
+    /// `false` are removed from the typed arena, and the entries of the remaining objects are
+    /// unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::TypedArena;
+    ///
+    /// let mut arena = TypedArena::new(1024);
+    /// let x = arena.insert(0);
+    /// let y = arena.insert(1);
+    /// let z = arena.insert(2);
+    ///
+    /// arena.retain(|_, value| *value % 2 == 0);
+    /// assert!(arena.contains(&x));
+    /// assert!(!arena.contains(&y));
+    /// assert!(arena.contains(&z));
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Entry, &mut T) -> bool,
+    {
+        for chunk_index in 0..self.chunks.len() {
+            for block_index in 0..self.chunks[chunk_index].len() {
+                let entry = Entry {
+                    index: chunk_index * self.chunk_size + block_index,
+                };
+                let is_retained = match self.chunks[chunk_index][block_index] {
+                    Block::Occupied(ref mut value) => f(entry, value),
+                    Block::Vacant(_) => true,
+                };
+                if !is_retained {
+                    self.free(&entry);
+                }
+            }
+        }
+    }
+
     /// Returns an immutable reference to an object in the typed arena. Returns `None` if the entry
     /// does not correspond to a valid object.
     ///
@@ -171,7 +398,8 @@ impl<T> TypedArena<T> {
         if !self.is_valid_entry(entry) {
             return None;
         }
-        match self.chunks[entry.chunk_index][entry.block_index] {
+        let (chunk_index, block_index) = self.get_position(entry);
+        match self.chunks[chunk_index][block_index] {
             Block::Occupied(ref value) => Some(value),
             Block::Vacant(_) => None,
         }
@@ -193,11 +421,260 @@ impl<T> TypedArena<T> {
         if !self.is_valid_entry(entry) {
             return None;
         }
-        match self.chunks[entry.chunk_index][entry.block_index] {
+        let (chunk_index, block_index) = self.get_position(entry);
+        match self.chunks[chunk_index][block_index] {
             Block::Occupied(ref mut value) => Some(value),
             Block::Vacant(_) => None,
         }
     }
+
+    /// Swaps two objects in the typed arena.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either entry corresponds to an invalid or vacant value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::TypedArena;
+    ///
+    /// let mut arena = TypedArena::new(1024);
+    /// let x = arena.allocate(0);
+    /// let y = arena.allocate(1);
+    /// arena.swap(&x, &y);
+    /// assert_eq!(arena[x], 1);
+    /// assert_eq!(arena[y], 0);
+    /// ```
+    pub fn swap(&mut self, a: &Entry, b: &Entry) {
+        if self.get(a).is_none() || self.get(b).is_none() {
+            panic!("Error: attempting to swap invalid or vacant block.");
+        }
+        let (a_chunk_index, a_block_index) = self.get_position(a);
+        let (b_chunk_index, b_block_index) = self.get_position(b);
+        if a_chunk_index == b_chunk_index {
+            self.chunks[a_chunk_index].swap(a_block_index, b_block_index);
+        } else {
+            let (low, high) = if a_chunk_index < b_chunk_index {
+                (
+                    (a_chunk_index, a_block_index),
+                    (b_chunk_index, b_block_index),
+                )
+            } else {
+                (
+                    (b_chunk_index, b_block_index),
+                    (a_chunk_index, a_block_index),
+                )
+            };
+            let (low_chunks, high_chunks) = self.chunks.split_at_mut(high.0);
+            mem::swap(&mut low_chunks[low.0][low.1], &mut high_chunks[0][high.1]);
+        }
+    }
+
+    /// Returns the number of objects in the typed arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::TypedArena;
+    ///
+    /// let mut arena = TypedArena::new(1024);
+    /// let x = arena.allocate(0);
+    /// assert_eq!(arena.len(), 1);
+    ///
+    /// arena.free(&x);
+    /// assert_eq!(arena.len(), 0);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if the typed arena contains no objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::TypedArena;
+    ///
+    /// let mut arena = TypedArena::new(1024);
+    /// assert!(arena.is_empty());
+    ///
+    /// arena.allocate(0);
+    /// assert!(!arena.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the number of objects that the typed arena can hold without allocating another
+    /// chunk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::TypedArena;
+    ///
+    /// let mut arena = TypedArena::new(2);
+    /// assert_eq!(arena.capacity(), 0);
+    ///
+    /// arena.allocate(0);
+    /// assert_eq!(arena.capacity(), 2);
+    ///
+    /// arena.allocate(0);
+    /// arena.allocate(0);
+    /// assert_eq!(arena.capacity(), 4);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns an iterator over the objects in the typed arena. The iterator will yield the entry
+    /// of each object and an immutable reference to the object in order of their position in the
+    /// typed arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::TypedArena;
+    ///
+    /// let mut arena = TypedArena::new(1024);
+    /// let x = arena.allocate(0);
+    /// let y = arena.allocate(1);
+    /// let z = arena.allocate(2);
+    /// arena.free(&y);
+    ///
+    /// let mut iterator = arena.iter();
+    /// assert_eq!(iterator.next(), Some((x, &0)));
+    /// assert_eq!(iterator.next(), Some((z, &2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> TypedArenaIter<'_, T> {
+        TypedArenaIter {
+            chunks: self.chunks.iter().enumerate(),
+            curr_chunk: None,
+            chunk_size: self.chunk_size,
+        }
+    }
+
+    /// Returns a mutable iterator over the objects in the typed arena. The iterator will yield the
+    /// entry of each object and a mutable reference to the object in order of their position in
+    /// the typed arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::TypedArena;
+    ///
+    /// let mut arena = TypedArena::new(1024);
+    /// let x = arena.allocate(0);
+    /// let y = arena.allocate(1);
+    ///
+    /// for (_, value) in arena.iter_mut() {
+    ///     *value += 1;
+    /// }
+    ///
+    /// let mut iterator = arena.iter();
+    /// assert_eq!(iterator.next(), Some((x, &1)));
+    /// assert_eq!(iterator.next(), Some((y, &2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter_mut(&mut self) -> TypedArenaIterMut<'_, T> {
+        TypedArenaIterMut {
+            chunks: self.chunks.iter_mut().enumerate(),
+            curr_chunk: None,
+            chunk_size: self.chunk_size,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a TypedArena<T>
+where
+    T: 'a,
+{
+    type IntoIter = TypedArenaIter<'a, T>;
+    type Item = (Entry, &'a T);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut TypedArena<T>
+where
+    T: 'a,
+{
+    type IntoIter = TypedArenaIterMut<'a, T>;
+    type Item = (Entry, &'a mut T);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator for `TypedArena<T>`.
+///
+/// This iterator yields objects in order of their position in the typed arena.
+pub struct TypedArenaIter<'a, T> {
+    chunks: Enumerate<slice::Iter<'a, Vec<Block<T>>>>,
+    curr_chunk: Option<(usize, Enumerate<slice::Iter<'a, Block<T>>>)>,
+    chunk_size: usize,
+}
+
+impl<'a, T> Iterator for TypedArenaIter<'a, T>
+where
+    T: 'a,
+{
+    type Item = (Entry, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((chunk_index, ref mut blocks)) = self.curr_chunk {
+                for (block_index, block) in blocks {
+                    if let Block::Occupied(ref value) = block {
+                        let entry = Entry {
+                            index: chunk_index * self.chunk_size + block_index,
+                        };
+                        return Some((entry, value));
+                    }
+                }
+            }
+            let (chunk_index, chunk) = self.chunks.next()?;
+            self.curr_chunk = Some((chunk_index, chunk.iter().enumerate()));
+        }
+    }
+}
+
+/// A mutable iterator for `TypedArena<T>`.
+///
+/// This iterator yields objects in order of their position in the typed arena.
+pub struct TypedArenaIterMut<'a, T> {
+    chunks: Enumerate<slice::IterMut<'a, Vec<Block<T>>>>,
+    curr_chunk: Option<(usize, Enumerate<slice::IterMut<'a, Block<T>>>)>,
+    chunk_size: usize,
+}
+
+impl<'a, T> Iterator for TypedArenaIterMut<'a, T>
+where
+    T: 'a,
+{
+    type Item = (Entry, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((chunk_index, ref mut blocks)) = self.curr_chunk {
+                for (block_index, block) in blocks {
+                    if let Block::Occupied(ref mut value) = block {
+                        let entry = Entry {
+                            index: chunk_index * self.chunk_size + block_index,
+                        };
+                        return Some((entry, value));
+                    }
+                }
+            }
+            let (chunk_index, chunk) = self.chunks.next()?;
+            self.curr_chunk = Some((chunk_index, chunk.iter_mut().enumerate()));
+        }
+    }
 }
 
 impl<T> Index<Entry> for TypedArena<T> {
@@ -214,19 +691,375 @@ impl<T> IndexMut<Entry> for TypedArena<T> {
     }
 }
 
+/// A struct representing an entry to `GenerationalArena<T>`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct GenerationalEntry {
+    entry: Entry,
+    generation: usize,
+}
+
+/// A typed arena that detects stale entries.
+///
+/// Each block in the arena has a generation that is incremented whenever its object is
+/// deallocated, and each entry records the generation of the block when it was allocated. An entry
+/// whose object was deallocated is stale and will never refer to another object that reuses the
+/// same block.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::arena::GenerationalArena;
+///
+/// let mut arena = GenerationalArena::new(1024);
+///
+/// let x = arena.allocate(1);
+/// assert_eq!(arena[x], 1);
+/// assert_eq!(arena.free(&x), Some(1));
+///
+/// let y = arena.allocate(2);
+/// assert_eq!(arena.try_get(&x), None);
+/// assert_eq!(arena.try_get(&y), Some(&2));
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct GenerationalArena<T> {
+    arena: TypedArena<T>,
+    generations: Vec<usize>,
+}
+
+// the serialized fields of `GenerationalArena<T>`, which are checked before they are used
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(rename = "GenerationalArena")]
+struct GenerationalArenaFields<T> {
+    arena: TypedArena<T>,
+    generations: Vec<usize>,
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> Deserialize<'de> for GenerationalArena<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields = GenerationalArenaFields::deserialize(deserializer)?;
+        let block_count: usize = fields.arena.chunks.iter().map(Vec::len).sum();
+        if fields.generations.len() != block_count {
+            return Err(D::Error::custom(
+                "generations do not match the number of blocks",
+            ));
+        }
+        Ok(GenerationalArena {
+            arena: fields.arena,
+            generations: fields.generations,
+        })
+    }
+}
+
+impl<T> GenerationalArena<T> {
+    fn is_valid_entry(&self, entry: &GenerationalEntry) -> bool {
+        self.arena.is_valid_entry(&entry.entry)
+            && self.generations[entry.entry.index] == entry.generation
+    }
+
+    /// Constructs a new, empty `GenerationalArena<T>` with a specific number of objects per chunk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::GenerationalArena;
+    ///
+    /// // creates a new GenerationalArena<T> that contains a maximum of 1024 u32's per chunk
+    /// let arena: GenerationalArena<u32> = GenerationalArena::new(1024);
+    /// ```
+    pub fn new(chunk_size: usize) -> Self {
+        GenerationalArena {
+            arena: TypedArena::new(chunk_size),
+            generations: Vec::new(),
+        }
+    }
+
+    /// Allocates an object in the arena and returns a GenerationalEntry. The GenerationalEntry
+    /// can later be used to retrieve mutable and immutable references to the object, and
+    /// deallocate the object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::GenerationalArena;
+    ///
+    /// let mut arena = GenerationalArena::new(1024);
+    /// let x = arena.allocate(0);
+    /// ```
+    pub fn allocate(&mut self, value: T) -> GenerationalEntry {
+        let entry = self.arena.allocate(value);
+        if entry.index == self.generations.len() {
+            self.generations.push(0);
+        }
+        GenerationalEntry {
+            entry,
+            generation: self.generations[entry.index],
+        }
+    }
+
+    /// Deallocates an object in the arena and returns the object. Returns `None` if the entry is
+    /// stale or does not correspond to a valid object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::GenerationalArena;
+    ///
+    /// let mut arena = GenerationalArena::new(1024);
+    /// let x = arena.allocate(0);
+    /// assert_eq!(arena.free(&x), Some(0));
+    /// assert_eq!(arena.free(&x), None);
+    /// ```
+    pub fn free(&mut self, entry: &GenerationalEntry) -> Option<T> {
+        if !self.is_valid_entry(entry) {
+            return None;
+        }
+        self.generations[entry.entry.index] += 1;
+        Some(self.arena.free(&entry.entry))
+    }
+
+    /// Returns an immutable reference to an object in the arena. Returns `None` if the entry is
+    /// stale or does not correspond to a valid object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::GenerationalArena;
+    ///
+    /// let mut arena = GenerationalArena::new(1024);
+    /// let x = arena.allocate(0);
+    /// assert_eq!(arena.try_get(&x), Some(&0));
+    ///
+    /// arena.free(&x);
+    /// assert_eq!(arena.try_get(&x), None);
+    /// ```
+    pub fn try_get(&self, entry: &GenerationalEntry) -> Option<&T> {
+        if !self.is_valid_entry(entry) {
+            return None;
+        }
+        self.arena.get(&entry.entry)
+    }
+
+    /// Returns a mutable reference to an object in the arena. Returns `None` if the entry is stale
+    /// or does not correspond to a valid object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::GenerationalArena;
+    ///
+    /// let mut arena = GenerationalArena::new(1024);
+    /// let x = arena.allocate(0);
+    /// assert_eq!(arena.try_get_mut(&x), Some(&mut 0));
+    ///
+    /// arena.free(&x);
+    /// assert_eq!(arena.try_get_mut(&x), None);
+    /// ```
+    pub fn try_get_mut(&mut self, entry: &GenerationalEntry) -> Option<&mut T> {
+        if !self.is_valid_entry(entry) {
+            return None;
+        }
+        self.arena.get_mut(&entry.entry)
+    }
+
+    /// Returns the number of objects in the arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::GenerationalArena;
+    ///
+    /// let mut arena = GenerationalArena::new(1024);
+    /// arena.allocate(0);
+    /// assert_eq!(arena.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.arena.len()
+    }
+
+    /// Returns `true` if the arena contains no objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::GenerationalArena;
+    ///
+    /// let mut arena = GenerationalArena::new(1024);
+    /// assert!(arena.is_empty());
+    ///
+    /// arena.allocate(0);
+    /// assert!(!arena.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.arena.is_empty()
+    }
+
+    /// Returns the number of objects that the arena can hold without allocating another chunk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::GenerationalArena;
+    ///
+    /// let mut arena = GenerationalArena::new(2);
+    /// arena.allocate(0);
+    /// assert_eq!(arena.capacity(), 2);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.arena.capacity()
+    }
+
+    /// Returns an iterator over the objects in the arena. The iterator will yield the entry of
+    /// each object and an immutable reference to the object in order of their position in the
+    /// arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::GenerationalArena;
+    ///
+    /// let mut arena = GenerationalArena::new(1024);
+    /// let x = arena.allocate(0);
+    /// let y = arena.allocate(1);
+    ///
+    /// let mut iterator = arena.iter();
+    /// assert_eq!(iterator.next(), Some((x, &0)));
+    /// assert_eq!(iterator.next(), Some((y, &1)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> GenerationalArenaIter<'_, T> {
+        GenerationalArenaIter {
+            arena_iter: self.arena.iter(),
+            generations: &self.generations,
+        }
+    }
+
+    /// Returns a mutable iterator over the objects in the arena. The iterator will yield the entry
+    /// of each object and a mutable reference to the object in order of their position in the
+    /// arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::arena::GenerationalArena;
+    ///
+    /// let mut arena = GenerationalArena::new(1024);
+    /// let x = arena.allocate(0);
+    ///
+    /// for (_, value) in arena.iter_mut() {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(arena[x], 1);
+    /// ```
+    pub fn iter_mut(&mut self) -> GenerationalArenaIterMut<'_, T> {
+        GenerationalArenaIterMut {
+            arena_iter: self.arena.iter_mut(),
+            generations: &self.generations,
+        }
+    }
+}
+
+impl<T> Index<GenerationalEntry> for GenerationalArena<T> {
+    type Output = T;
+
+    fn index(&self, entry: GenerationalEntry) -> &Self::Output {
+        self.try_get(&entry)
+            .expect("Error: stale or invalid entry.")
+    }
+}
+
+impl<T> IndexMut<GenerationalEntry> for GenerationalArena<T> {
+    fn index_mut(&mut self, entry: GenerationalEntry) -> &mut Self::Output {
+        self.try_get_mut(&entry)
+            .expect("Error: stale or invalid entry.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a GenerationalArena<T>
+where
+    T: 'a,
+{
+    type IntoIter = GenerationalArenaIter<'a, T>;
+    type Item = (GenerationalEntry, &'a T);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut GenerationalArena<T>
+where
+    T: 'a,
+{
+    type IntoIter = GenerationalArenaIterMut<'a, T>;
+    type Item = (GenerationalEntry, &'a mut T);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator for `GenerationalArena<T>`.
+///
+/// This iterator yields objects in order of their position in the arena.
+pub struct GenerationalArenaIter<'a, T> {
+    arena_iter: TypedArenaIter<'a, T>,
+    generations: &'a [usize],
+}
+
+impl<'a, T> Iterator for GenerationalArenaIter<'a, T>
+where
+    T: 'a,
+{
+    type Item = (GenerationalEntry, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (entry, value) = self.arena_iter.next()?;
+        let generation = self.generations[entry.index];
+        Some((GenerationalEntry { entry, generation }, value))
+    }
+}
+
+/// A mutable iterator for `GenerationalArena<T>`.
+///
+/// This iterator yields objects in order of their position in the arena.
+pub struct GenerationalArenaIterMut<'a, T> {
+    arena_iter: TypedArenaIterMut<'a, T>,
+    generations: &'a [usize],
+}
+
+impl<'a, T> Iterator for GenerationalArenaIterMut<'a, T>
+where
+    T: 'a,
+{
+    type Item = (GenerationalEntry, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (entry, value) = self.arena_iter.next()?;
+        let generation = self.generations[entry.index];
+        Some((GenerationalEntry { entry, generation }, value))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Entry;
-    use super::TypedArena;
+    use super::{Block, GenerationalArena, TypedArena};
 
     #[test]
     #[should_panic]
     fn test_free_invalid_block() {
         let mut arena: TypedArena<u32> = TypedArena::new(1024);
-        arena.free(&Entry {
-            chunk_index: 0,
-            block_index: 0,
-        });
+        arena.free(&Entry::from(0));
     }
 
     #[test]
@@ -234,75 +1067,30 @@ mod tests {
     fn test_free_vacant_block() {
         let mut arena = TypedArena::new(1024);
         arena.allocate(0);
-        arena.free(&Entry {
-            chunk_index: 0,
-            block_index: 1,
-        });
+        arena.free(&Entry::from(1));
     }
 
     #[test]
     fn test_insert() {
         let mut pool = TypedArena::new(1024);
-        assert_eq!(
-            pool.allocate(0),
-            Entry {
-                chunk_index: 0,
-                block_index: 0
-            },
-        );
-        assert_eq!(
-            pool.allocate(0),
-            Entry {
-                chunk_index: 0,
-                block_index: 1
-            },
-        );
-        assert_eq!(
-            pool.allocate(0),
-            Entry {
-                chunk_index: 0,
-                block_index: 2
-            },
-        );
+        assert_eq!(pool.allocate(0), Entry::from(0));
+        assert_eq!(pool.allocate(0), Entry::from(1));
+        assert_eq!(pool.allocate(0), Entry::from(2));
     }
 
     #[test]
     fn test_insert_multiple_chunks() {
         let mut pool = TypedArena::new(2);
-        assert_eq!(
-            pool.allocate(0),
-            Entry {
-                chunk_index: 0,
-                block_index: 0
-            },
-        );
-        assert_eq!(
-            pool.allocate(0),
-            Entry {
-                chunk_index: 0,
-                block_index: 1
-            },
-        );
-        assert_eq!(
-            pool.allocate(0),
-            Entry {
-                chunk_index: 1,
-                block_index: 0
-            },
-        );
+        assert_eq!(pool.allocate(0), Entry::from(0));
+        assert_eq!(pool.allocate(0), Entry::from(1));
+        assert_eq!(pool.allocate(0), Entry::from(2));
     }
 
     #[test]
     fn test_free() {
         let mut pool = TypedArena::new(1024);
         let entry = pool.allocate(0);
-        assert_eq!(
-            entry,
-            Entry {
-                chunk_index: 0,
-                block_index: 0
-            },
-        );
+        assert_eq!(entry, Entry::from(0));
         assert_eq!(pool.free(&entry), 0);
         assert_eq!(pool.allocate(0), entry);
     }
@@ -317,26 +1105,14 @@ mod tests {
     #[test]
     fn test_get_invalid_block() {
         let pool: TypedArena<u32> = TypedArena::new(1024);
-        assert_eq!(
-            pool.get(&Entry {
-                chunk_index: 0,
-                block_index: 0
-            }),
-            None,
-        );
+        assert_eq!(pool.get(&Entry::from(0)), None);
     }
 
     #[test]
     fn test_get_vacant_block() {
         let mut pool = TypedArena::new(1024);
         pool.allocate(0);
-        assert_eq!(
-            pool.get(&Entry {
-                chunk_index: 0,
-                block_index: 1
-            }),
-            None,
-        );
+        assert_eq!(pool.get(&Entry::from(1)), None);
     }
 
     #[test]
@@ -350,25 +1126,238 @@ mod tests {
     #[test]
     fn test_get_mut_invalid_block() {
         let mut pool: TypedArena<u32> = TypedArena::new(1024);
-        assert_eq!(
-            pool.get_mut(&Entry {
-                chunk_index: 0,
-                block_index: 0
-            }),
-            None,
-        );
+        assert_eq!(pool.get_mut(&Entry::from(0)), None);
     }
 
     #[test]
     fn test_get_mut_vacant_block() {
         let mut pool = TypedArena::new(1024);
         pool.allocate(0);
+        assert_eq!(pool.get_mut(&Entry::from(1)), None);
+    }
+
+    #[test]
+    fn test_len_capacity() {
+        let mut pool = TypedArena::new(2);
+        let entries: Vec<Entry> = (0..3).map(|value| pool.allocate(value)).collect();
+        assert_eq!(pool.len(), 3);
+        assert_eq!(pool.capacity(), 4);
+
+        pool.free(&entries[1]);
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.capacity(), 4);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut pool = TypedArena::new(2);
+        let entries: Vec<Entry> = (0..5).map(|value| pool.allocate(value)).collect();
+        pool.free(&entries[1]);
+        pool.free(&entries[2]);
+
+        for (_, value) in &mut pool {
+            *value *= 2;
+        }
         assert_eq!(
-            pool.get_mut(&Entry {
-                chunk_index: 0,
-                block_index: 1
-            }),
-            None,
+            pool.iter().collect::<Vec<(Entry, &u32)>>(),
+            vec![(entries[0], &0), (entries[3], &6), (entries[4], &8)],
         );
     }
+
+    #[test]
+    fn test_generational_stale_entry() {
+        let mut pool = GenerationalArena::new(1024);
+        let x = pool.allocate(0);
+        assert_eq!(pool.free(&x), Some(0));
+
+        let y = pool.allocate(1);
+        assert_eq!(x.entry, y.entry);
+        assert_eq!(pool.try_get(&x), None);
+        assert_eq!(pool.try_get_mut(&x), None);
+        assert_eq!(pool.free(&x), None);
+        assert_eq!(pool.try_get(&y), Some(&1));
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_generational_index_stale_entry() {
+        let mut pool = GenerationalArena::new(1024);
+        let x = pool.allocate(0);
+        pool.free(&x);
+        pool.allocate(1);
+        let _ = pool[x];
+    }
+
+    #[test]
+    fn test_generational_iter() {
+        let mut pool = GenerationalArena::new(2);
+        let x = pool.allocate(0);
+        let y = pool.allocate(1);
+        let z = pool.allocate(2);
+        pool.free(&y);
+        let w = pool.allocate(3);
+
+        for (_, value) in &mut pool {
+            *value += 1;
+        }
+        assert_eq!(
+            pool.iter().collect::<Vec<_>>(),
+            vec![(x, &1), (w, &4), (z, &3)],
+        );
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut pool = TypedArena::new(2);
+        let entries: Vec<Entry> = (0..3).map(|value| pool.allocate(value)).collect();
+        pool.swap(&entries[0], &entries[1]);
+        pool.swap(&entries[2], &entries[0]);
+        pool.swap(&entries[1], &entries[1]);
+        assert_eq!(pool[entries[0]], 2);
+        assert_eq!(pool[entries[1]], 0);
+        assert_eq!(pool[entries[2]], 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_swap_vacant_block() {
+        let mut pool = TypedArena::new(1024);
+        let x = pool.allocate(0);
+        let y = pool.allocate(1);
+        pool.free(&y);
+        pool.swap(&x, &y);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut pool = TypedArena::new(2);
+        let entries: Vec<Entry> = (0..3).map(|value| pool.insert(value)).collect();
+        assert_eq!(pool.remove(&entries[1]), Some(1));
+        assert_eq!(pool.remove(&entries[1]), None);
+        assert_eq!(pool.remove(&Entry::from(4)), None);
+        assert!(!pool.contains(&entries[1]));
+        assert_eq!(pool.len(), 2);
+
+        assert_eq!(pool.insert(3), entries[1]);
+        assert_eq!(pool[entries[1]], 3);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut pool = TypedArena::new(2);
+        let entries: Vec<Entry> = (0..10).map(|value| pool.insert(value)).collect();
+        pool.retain(|entry, value| {
+            *value *= 2;
+            usize::from(entry) % 3 != 0
+        });
+        assert_eq!(pool.len(), 6);
+        assert_eq!(
+            pool.iter().map(|(entry, _)| entry).collect::<Vec<Entry>>(),
+            vec![entries[1], entries[2], entries[4], entries[5], entries[7], entries[8]],
+        );
+        assert_eq!(pool[entries[8]], 16);
+
+        let entry = pool.insert(10);
+        assert_eq!(usize::from(entry) % 3, 0);
+        assert_eq!(pool.len(), 7);
+    }
+
+    #[test]
+    fn test_entry_conversion() {
+        let mut pool = TypedArena::new(2);
+        for value in 0..5 {
+            let entry = pool.insert(value);
+            assert_eq!(usize::from(entry), value);
+            assert_eq!(Entry::from(value), entry);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut pool = TypedArena::new(2);
+        let entries: Vec<Entry> = (0..5).map(|value| pool.insert(value)).collect();
+        pool.remove(&entries[1]);
+        pool.remove(&entries[3]);
+
+        let mut deserialized: TypedArena<u32> =
+            bincode::deserialize(&bincode::serialize(&pool).unwrap()).unwrap();
+        assert_eq!(
+            deserialized.iter().collect::<Vec<_>>(),
+            pool.iter().collect::<Vec<_>>(),
+        );
+        assert_eq!(deserialized.insert(5), entries[3]);
+        assert_eq!(deserialized.insert(6), entries[1]);
+        assert_eq!(deserialized.len(), 5);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_invalid() {
+        fn deserialize<T>(value: &T) -> bincode::Result<TypedArena<u32>>
+        where
+            T: serde::Serialize,
+        {
+            bincode::deserialize(&bincode::serialize(value).unwrap())
+        }
+
+        let pool: TypedArena<u32> = TypedArena {
+            head: None,
+            chunks: Vec::new(),
+            chunk_size: 0,
+            size: 0,
+            capacity: 0,
+        };
+        assert!(deserialize(&pool).is_err());
+
+        let mut pool = TypedArena::new(2);
+        let entries: Vec<Entry> = (0..5).map(|value| pool.insert(value)).collect();
+        pool.remove(&entries[1]);
+        pool.remove(&entries[3]);
+        assert!(deserialize(&pool).is_ok());
+
+        let mut invalid = pool.clone();
+        invalid.size = 5;
+        assert!(deserialize(&invalid).is_err());
+
+        let mut invalid = pool.clone();
+        invalid.capacity = 4;
+        assert!(deserialize(&invalid).is_err());
+
+        let mut invalid = pool.clone();
+        invalid.chunks[0].pop();
+        invalid.size -= 1;
+        assert!(deserialize(&invalid).is_err());
+
+        let mut invalid = pool.clone();
+        invalid.head = Some(Entry::from(7));
+        assert!(deserialize(&invalid).is_err());
+
+        let mut invalid = pool.clone();
+        invalid.head = Some(Entry::from(0));
+        assert!(deserialize(&invalid).is_err());
+
+        // a free list that links the vacant block to itself
+        let mut invalid = pool.clone();
+        invalid.chunks[0][1] = Block::Vacant(Some(entries[1]));
+        invalid.head = Some(entries[1]);
+        assert!(deserialize(&invalid).is_err());
+
+        let mut pool = GenerationalArena::new(2);
+        pool.allocate(0);
+        pool.generations.push(0);
+        let bytes = bincode::serialize(&pool).unwrap();
+        assert!(bincode::deserialize::<GenerationalArena<u32>>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut pool = TypedArena::new(2);
+        let x = pool.insert(0);
+        let mut cloned = pool.clone();
+        cloned[x] = 1;
+        assert_eq!(pool[x], 0);
+        assert_eq!(cloned[x], 1);
+    }
 }
@@ -1,13 +1,22 @@
 use crate::avl_tree::node::Node;
 use crate::avl_tree::tree;
+use crate::compare::{Compare, Natural};
 use crate::entry::Entry;
-use std::borrow::Borrow;
-use std::ops::{Index, IndexMut};
+use crate::node_arena::{NodeArena, NodeRef};
+use crate::ordered::OrderedMap;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::{Bound, Index, IndexMut, RangeBounds};
 
 /// An ordered map implemented using an avl tree.
 ///
 /// An avl tree is a self-balancing binary search tree that maintains the invariant that the
-/// heights of two child subtrees of any node differ by at most one.
+/// heights of two child subtrees of any node differ by at most one. Each node is allocated in its
+/// own box unless the map is constructed with `with_arena`.
 ///
 /// # Examples
 ///
@@ -29,9 +38,11 @@ use std::ops::{Index, IndexMut};
 /// assert_eq!(map.remove(&0), Some((0, 2)));
 /// assert_eq!(map.remove(&1), None);
 /// ```
-pub struct AvlMap<T, U> {
-    tree: tree::Tree<T, U>,
+pub struct AvlMap<T, U, C = Natural> {
+    arena: tree::Arena<T, U>,
+    tree: tree::Tree,
     len: usize,
+    compare: C,
 }
 
 impl<T, U> AvlMap<T, U> {
@@ -45,7 +56,54 @@ impl<T, U> AvlMap<T, U> {
     /// let map: AvlMap<u32, u32> = AvlMap::new();
     /// ```
     pub fn new() -> Self {
-        AvlMap { tree: None, len: 0 }
+        Self::with_comparator(Natural)
+    }
+
+    /// Constructs a new, empty `AvlMap<T, U>` that allocates its nodes from a `TypedArena` to
+    /// improve cache locality. Because nodes cannot be shared between arenas, `split_off`,
+    /// `remove_range`, and the operations that combine two maps take linear time in the number of
+    /// nodes that are moved between maps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::with_arena();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// ```
+    pub fn with_arena() -> Self {
+        AvlMap {
+            arena: NodeArena::pooled(),
+            tree: None,
+            len: 0,
+            compare: Natural,
+        }
+    }
+}
+
+impl<T, U, C> AvlMap<T, U, C> {
+    /// Constructs a new, empty `AvlMap<T, U, C>` that uses `compare` to order keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::with_comparator(|a: &u32, b: &u32| b.cmp(a));
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.min(), Some(&2));
+    /// assert_eq!(map.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&2, &2), (&1, &1)]);
+    /// ```
+    pub fn with_comparator(compare: C) -> Self {
+        AvlMap {
+            arena: NodeArena::boxed(),
+            tree: None,
+            len: 0,
+            compare,
+        }
     }
 
     /// Inserts a key-value pair into the map. If the key already exists in the map, it will return
@@ -64,15 +122,17 @@ impl<T, U> AvlMap<T, U> {
     /// ```
     pub fn insert(&mut self, key: T, value: U) -> Option<(T, U)>
     where
-        T: Ord,
+        C: Compare<T>,
     {
         let AvlMap {
+            ref mut arena,
             ref mut tree,
             ref mut len,
+            ref compare,
         } = self;
         let new_node = Node::new(key, value);
         *len += 1;
-        tree::insert(tree, new_node).and_then(|entry| {
+        tree::insert(arena, tree, new_node, compare).and_then(|entry| {
             let Entry { key, value } = entry;
             *len -= 1;
             Some((key, value))
@@ -95,13 +155,16 @@ impl<T, U> AvlMap<T, U> {
     pub fn remove<V>(&mut self, key: &V) -> Option<(T, U)>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
         let AvlMap {
+            ref mut arena,
             ref mut tree,
             ref mut len,
+            ref compare,
         } = self;
-        tree::remove(tree, &key).and_then(|entry| {
+        tree::remove(arena, tree, key, compare).and_then(|entry| {
             let Entry { key, value } = entry;
             *len -= 1;
             Some((key, value))
@@ -123,7 +186,8 @@ impl<T, U> AvlMap<T, U> {
     pub fn contains_key<V>(&self, key: &V) -> bool
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
         self.get(key).is_some()
     }
@@ -144,9 +208,10 @@ impl<T, U> AvlMap<T, U> {
     pub fn get<V>(&self, key: &V) -> Option<&U>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        tree::get(&self.tree, key).map(|entry| &entry.value)
+        tree::get(&self.arena, self.tree, key, &self.compare).map(|entry| &entry.value)
     }
 
     /// Returns a mutable reference to the value associated with a particular key. Returns `None`
@@ -165,9 +230,10 @@ impl<T, U> AvlMap<T, U> {
     pub fn get_mut<V>(&mut self, key: &V) -> Option<&mut U>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        tree::get_mut(&mut self.tree, key).map(|entry| &mut entry.value)
+        tree::get_mut(&mut self.arena, self.tree, key, &self.compare).map(|entry| &mut entry.value)
     }
 
     /// Returns the number of elements in the map.
@@ -213,8 +279,61 @@ impl<T, U> AvlMap<T, U> {
     /// assert_eq!(map.is_empty(), true);
     /// ```
     pub fn clear(&mut self) {
-        self.tree = None;
+        self.arena.release(self.tree.take());
+        self.arena = self.arena.empty();
+        self.len = 0;
+    }
+
+    /// Retains only the key-value pairs that satisfy a predicate. The key-value pairs are visited
+    /// in ascending order of their keys. The tree is rebalanced by joining the retained subtrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.insert(3, 3);
+    ///
+    /// map.retain(|key, value| {
+    ///     *value += 1;
+    ///     key % 2 == 1
+    /// });
+    /// assert_eq!(map.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&1, &2), (&3, &4)]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T, &mut U) -> bool,
+    {
+        self.tree = tree::retain(&mut self.arena, self.tree, &mut f);
+        self.len = self.arena.len();
+    }
+
+    /// Clears the map, returning all key-value pairs as an iterator. The iterator yields the
+    /// key-value pairs in ascending order of their keys, and the map can be reused afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// assert_eq!(map.drain().collect::<Vec<(u32, u32)>>(), vec![(1, 1), (2, 2)]);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> AvlMapIntoIter<T, U> {
         self.len = 0;
+        let arena = self.arena.empty();
+        AvlMapIntoIter {
+            arena: mem::replace(&mut self.arena, arena),
+            current: self.tree.take(),
+            stack: Vec::new(),
+        }
     }
 
     /// Returns a key in the map that is less than or equal to a particular key. Returns `None` if
@@ -233,9 +352,10 @@ impl<T, U> AvlMap<T, U> {
     pub fn floor<V>(&self, key: &V) -> Option<&T>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        tree::floor(&self.tree, key).map(|entry| &entry.key)
+        tree::floor(&self.arena, self.tree, key, true, &self.compare).map(|entry| &entry.key)
     }
 
     /// Returns a key in the map that is greater than or equal to a particular key. Returns `None`
@@ -254,12 +374,14 @@ impl<T, U> AvlMap<T, U> {
     pub fn ceil<V>(&self, key: &V) -> Option<&T>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        tree::ceil(&self.tree, key).map(|entry| &entry.key)
+        tree::ceil(&self.arena, self.tree, key, true, &self.compare).map(|entry| &entry.key)
     }
 
-    /// Returns the minimum key of the map. Returns `None` if the map is empty.
+    /// Returns the key-value pair in the map with the greatest key that is less than or equal to a
+    /// particular key. Returns `None` if such a key does not exist.
     ///
     /// # Examples
     ///
@@ -268,17 +390,22 @@ impl<T, U> AvlMap<T, U> {
     ///
     /// let mut map = AvlMap::new();
     /// map.insert(1, 1);
-    /// map.insert(3, 3);
-    /// assert_eq!(map.min(), Some(&1));
+    /// assert_eq!(map.floor_entry(&0), None);
+    /// assert_eq!(map.floor_entry(&2), Some((&1, &1)));
     /// ```
-    pub fn min(&self) -> Option<&T>
+    pub fn floor_entry<V>(&self, key: &V) -> Option<(&T, &U)>
     where
-        T: Ord,
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        tree::min(&self.tree).map(|entry| &entry.key)
+        tree::floor(&self.arena, self.tree, key, true, &self.compare)
+            .map(|entry| (&entry.key, &entry.value))
     }
 
-    /// Returns the maximum key of the map. Returns `None` if the map is empty.
+    /// Returns the key and a mutable reference to the value of the key-value pair in the map with
+    /// the greatest key that is less than or equal to a particular key. Returns `None` if such a
+    /// key does not exist.
     ///
     /// # Examples
     ///
@@ -287,18 +414,23 @@ impl<T, U> AvlMap<T, U> {
     ///
     /// let mut map = AvlMap::new();
     /// map.insert(1, 1);
-    /// map.insert(3, 3);
-    /// assert_eq!(map.max(), Some(&3));
+    /// if let Some((_, value)) = map.floor_entry_mut(&2) {
+    ///     *value = 2;
+    /// }
+    /// assert_eq!(map.get(&1), Some(&2));
     /// ```
-    pub fn max(&self) -> Option<&T>
+    pub fn floor_entry_mut<V>(&mut self, key: &V) -> Option<(&T, &mut U)>
     where
-        T: Ord,
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        tree::max(&self.tree).map(|entry| &entry.key)
+        tree::floor_mut(&mut self.arena, self.tree, key, true, &self.compare)
+            .map(|entry| (&entry.key, &mut entry.value))
     }
 
-    /// Returns an iterator over the map. The iterator will yield key-value pairs using in-order
-    /// traversal.
+    /// Returns the key-value pair in the map with the least key that is greater than or equal to a
+    /// particular key. Returns `None` if such a key does not exist.
     ///
     /// # Examples
     ///
@@ -307,22 +439,22 @@ impl<T, U> AvlMap<T, U> {
     ///
     /// let mut map = AvlMap::new();
     /// map.insert(1, 1);
-    /// map.insert(2, 2);
-    ///
-    /// let mut iterator = map.iter();
-    /// assert_eq!(iterator.next(), Some((&1, &1)));
-    /// assert_eq!(iterator.next(), Some((&2, &2)));
-    /// assert_eq!(iterator.next(), None);
+    /// assert_eq!(map.ceil_entry(&2), None);
+    /// assert_eq!(map.ceil_entry(&0), Some((&1, &1)));
     /// ```
-    pub fn iter(&self) -> AvlMapIter<'_, T, U> {
-        AvlMapIter {
-            current: &self.tree,
-            stack: Vec::new(),
-        }
+    pub fn ceil_entry<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        tree::ceil(&self.arena, self.tree, key, true, &self.compare)
+            .map(|entry| (&entry.key, &entry.value))
     }
 
-    /// Returns a mutable iterator over the map. The iterator will yield key-value pairs using
-    /// in-order traversal.
+    /// Returns the key and a mutable reference to the value of the key-value pair in the map with
+    /// the least key that is greater than or equal to a particular key. Returns `None` if such a
+    /// key does not exist.
     ///
     /// # Examples
     ///
@@ -331,177 +463,908 @@ impl<T, U> AvlMap<T, U> {
     ///
     /// let mut map = AvlMap::new();
     /// map.insert(1, 1);
-    /// map.insert(2, 2);
-    ///
-    /// for (key, value) in &mut map {
-    ///     *value += 1;
+    /// if let Some((_, value)) = map.ceil_entry_mut(&0) {
+    ///     *value = 2;
     /// }
-    ///
-    /// let mut iterator = map.iter_mut();
-    /// assert_eq!(iterator.next(), Some((&1, &mut 2)));
-    /// assert_eq!(iterator.next(), Some((&2, &mut 3)));
-    /// assert_eq!(iterator.next(), None);
+    /// assert_eq!(map.get(&1), Some(&2));
     /// ```
-    pub fn iter_mut(&mut self) -> AvlMapIterMut<'_, T, U> {
-        AvlMapIterMut {
-            current: self.tree.as_mut().map(|node| &mut **node),
-            stack: Vec::new(),
-        }
+    pub fn ceil_entry_mut<V>(&mut self, key: &V) -> Option<(&T, &mut U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        tree::ceil_mut(&mut self.arena, self.tree, key, true, &self.compare)
+            .map(|entry| (&entry.key, &mut entry.value))
     }
-}
 
-impl<T, U> IntoIterator for AvlMap<T, U> {
-    type IntoIter = AvlMapIntoIter<T, U>;
-    type Item = (T, U);
-
-    fn into_iter(self) -> Self::IntoIter {
-        Self::IntoIter {
-            current: self.tree,
-            stack: Vec::new(),
-        }
+    /// Returns the key-value pair in the map with the greatest key that is less than a particular
+    /// key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.next_below(&1), None);
+    /// assert_eq!(map.next_below(&2), Some((&1, &1)));
+    /// ```
+    pub fn next_below<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        tree::floor(&self.arena, self.tree, key, false, &self.compare)
+            .map(|entry| (&entry.key, &entry.value))
     }
-}
-
-impl<'a, T, U> IntoIterator for &'a AvlMap<T, U>
-where
-    T: 'a,
-    U: 'a,
-{
-    type IntoIter = AvlMapIter<'a, T, U>;
-    type Item = (&'a T, &'a U);
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
+    /// Returns the key-value pair in the map with the least key that is greater than a particular
+    /// key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.next_above(&2), None);
+    /// assert_eq!(map.next_above(&1), Some((&2, &2)));
+    /// ```
+    pub fn next_above<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        tree::ceil(&self.arena, self.tree, key, false, &self.compare)
+            .map(|entry| (&entry.key, &entry.value))
     }
-}
-
-impl<'a, T, U> IntoIterator for &'a mut AvlMap<T, U>
-where
-    T: 'a,
-    U: 'a,
-{
-    type IntoIter = AvlMapIterMut<'a, T, U>;
-    type Item = (&'a T, &'a mut U);
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter_mut()
+    /// Returns the minimum key of the map. Returns `None` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.min(), Some(&1));
+    /// ```
+    pub fn min(&self) -> Option<&T> {
+        tree::min(&self.arena, self.tree).map(|entry| &entry.key)
     }
-}
-
-/// An owning iterator for `AvlMap<T, U>`.
-///
-/// This iterator traverses the elements of the map in-order and yields owned entries.
-pub struct AvlMapIntoIter<T, U> {
-    current: tree::Tree<T, U>,
-    stack: Vec<Node<T, U>>,
-}
 
-impl<T, U> Iterator for AvlMapIntoIter<T, U> {
-    type Item = (T, U);
+    /// Returns the maximum key of the map. Returns `None` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.max(), Some(&3));
+    /// ```
+    pub fn max(&self) -> Option<&T> {
+        tree::max(&self.arena, self.tree).map(|entry| &entry.key)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        while let Some(mut node) = self.current.take() {
-            self.current = node.left.take();
-            self.stack.push(*node);
-        }
-        self.stack.pop().map(|node| {
-            let Node {
-                entry: Entry { key, value },
-                right,
-                ..
-            } = node;
-            self.current = right;
+    /// Removes and returns the key-value pair with the minimum key in the map. Returns `None` if
+    /// the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.pop_min(), Some((1, 1)));
+    /// assert_eq!(map.pop_min(), Some((3, 3)));
+    /// assert_eq!(map.pop_min(), None);
+    /// ```
+    pub fn pop_min(&mut self) -> Option<(T, U)> {
+        let AvlMap {
+            ref mut arena,
+            ref mut tree,
+            ref mut len,
+            ..
+        } = self;
+        tree::pop_min(arena, tree).map(|entry| {
+            let Entry { key, value } = entry;
+            *len -= 1;
             (key, value)
         })
     }
-}
-
-/// An iterator for `AvlMap<T, U>`.
-///
-/// This iterator traverses the elements of the map in-order and yields immutable references.
-pub struct AvlMapIter<'a, T, U> {
-    current: &'a tree::Tree<T, U>,
-    stack: Vec<&'a Node<T, U>>,
-}
-
-impl<'a, T, U> Iterator for AvlMapIter<'a, T, U>
-where
-    T: 'a,
-    U: 'a,
-{
-    type Item = (&'a T, &'a U);
 
-    fn next(&mut self) -> Option<Self::Item> {
-        while let Some(ref node) = self.current {
-            self.current = &node.left;
-            self.stack.push(node);
-        }
-        self.stack.pop().map(|node| {
-            let Node {
-                entry: Entry { ref key, ref value },
-                ref right,
-                ..
-            } = node;
-            self.current = right;
+    /// Removes and returns the key-value pair with the maximum key in the map. Returns `None` if
+    /// the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.pop_max(), Some((3, 3)));
+    /// assert_eq!(map.pop_max(), Some((1, 1)));
+    /// assert_eq!(map.pop_max(), None);
+    /// ```
+    pub fn pop_max(&mut self) -> Option<(T, U)> {
+        let AvlMap {
+            ref mut arena,
+            ref mut tree,
+            ref mut len,
+            ..
+        } = self;
+        tree::pop_max(arena, tree).map(|entry| {
+            let Entry { key, value } = entry;
+            *len -= 1;
             (key, value)
         })
     }
-}
 
-type BorrowedIterEntryMut<'a, T, U> = Option<(&'a mut Entry<T, U>, BorrowedTreeMut<'a, T, U>)>;
-type BorrowedTreeMut<'a, T, U> = Option<&'a mut Node<T, U>>;
+    // Moves `tree` out of the storage of the map into a new map.
+    fn transfer(&mut self, tree: tree::Tree) -> Self
+    where
+        C: Clone,
+    {
+        let mut ret = AvlMap {
+            arena: self.arena.empty(),
+            tree: None,
+            len: 0,
+            compare: self.compare.clone(),
+        };
+        let (tree, len) = tree::transfer(&mut self.arena, &mut ret.arena, tree);
+        ret.tree = tree;
+        ret.len = len;
+        self.len -= len;
+        ret
+    }
+
+    /// Splits the map and returns the right part of the map. If `inclusive` is true, then the map
+    /// will retain the given key if it exists. Otherwise, the right part of the map will contain
+    /// the key if it exists. The map is split in logarithmic time. If the map allocates its nodes
+    /// from an arena, the nodes of the right part are also moved to the arena of the new map in
+    /// linear time in its length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.insert(3, 3);
+    ///
+    /// let split = map.split_off(&2, true);
+    /// assert_eq!(map[&1], 1);
+    /// assert_eq!(map[&2], 2);
+    /// assert_eq!(split[&3], 3);
+    /// ```
+    pub fn split_off<V>(&mut self, key: &V, inclusive: bool) -> Self
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V> + Clone,
+    {
+        let (left, right) = tree::split(&mut self.arena, self.tree, key, inclusive, &self.compare);
+        self.tree = left;
+        self.transfer(right)
+    }
+
+    /// Removes all entries whose keys are in `range` from the map and returns them as a new map.
+    /// The map is split and joined in logarithmic time. If the map allocates its nodes from an
+    /// arena, the removed nodes are also moved to the arena of the new map in linear time in their
+    /// number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// for key in 0..5 {
+    ///     map.insert(key, key);
+    /// }
+    ///
+    /// let removed = map.remove_range(1..3);
+    /// assert_eq!(removed.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&1, &1), (&2, &2)]);
+    /// assert_eq!(
+    ///     map.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&0, &0), (&3, &3), (&4, &4)],
+    /// );
+    /// ```
+    pub fn remove_range<V, R>(&mut self, range: R) -> Self
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V> + Clone,
+        R: RangeBounds<V>,
+    {
+        let AvlMap {
+            ref mut arena,
+            ref compare,
+            ..
+        } = self;
+        let (left, middle) = match range.start_bound() {
+            Bound::Included(start) => tree::split(arena, self.tree, start, false, compare),
+            Bound::Excluded(start) => tree::split(arena, self.tree, start, true, compare),
+            Bound::Unbounded => (None, self.tree),
+        };
+        let (middle, right) = match range.end_bound() {
+            Bound::Included(end) => tree::split(arena, middle, end, true, compare),
+            Bound::Excluded(end) => tree::split(arena, middle, end, false, compare),
+            Bound::Unbounded => (middle, None),
+        };
+        self.tree = tree::join_subtrees(arena, left, right);
+        self.transfer(middle)
+    }
+
+    // Combines the trees of two maps with `f`. The nodes of the smaller map are moved to the
+    // storage of the larger map, and `f` is called with the tree of the larger map first and
+    // whether the maps were swapped to do so. The returned map uses the comparator of `left`.
+    fn combine<F>(mut left: Self, mut right: Self, f: F) -> Self
+    where
+        F: FnOnce(&mut tree::Arena<T, U>, tree::Tree, tree::Tree, bool, &C) -> tree::Tree,
+    {
+        if left.len < right.len {
+            let (left_tree, _) =
+                tree::transfer(&mut left.arena, &mut right.arena, left.tree.take());
+            let right_tree = right.tree.take();
+            left.tree = f(&mut right.arena, right_tree, left_tree, true, &left.compare);
+            mem::swap(&mut left.arena, &mut right.arena);
+        } else {
+            let (right_tree, _) =
+                tree::transfer(&mut right.arena, &mut left.arena, right.tree.take());
+            let left_tree = left.tree.take();
+            left.tree = f(&mut left.arena, left_tree, right_tree, false, &left.compare);
+        }
+        left.len = left.arena.len();
+        left
+    }
+
+    /// Joins two maps. All keys in `left` must be less than all keys in `right`. The maps are
+    /// joined in logarithmic time. If either map allocates its nodes from an arena, the nodes of
+    /// the smaller map are also moved to the storage of the larger map in linear time in its
+    /// length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the maximum key in `left` is not less than the minimum key in `right`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut n = AvlMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = AvlMap::new();
+    /// m.insert(3, 3);
+    ///
+    /// let joined = AvlMap::join(n, m);
+    /// assert_eq!(
+    ///     joined.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1), (&2, &2), (&3, &3)],
+    /// );
+    /// ```
+    pub fn join(left: Self, right: Self) -> Self
+    where
+        C: Compare<T>,
+    {
+        if let (Some(max), Some(min)) = (left.max(), right.min()) {
+            assert!(
+                left.compare.compare(max, min) == Ordering::Less,
+                "Error: all keys in `left` must be less than all keys in `right`."
+            );
+        }
+        Self::combine(left, right, |arena, tree, other_tree, swapped, _| {
+            if swapped {
+                tree::join_subtrees(arena, other_tree, tree)
+            } else {
+                tree::join_subtrees(arena, tree, other_tree)
+            }
+        })
+    }
+
+    /// Returns the union of two maps. If there is a key that is found in both `left` and `right`,
+    /// the union will contain the value associated with the key in `left`. The maps are combined
+    /// by splitting and joining their trees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut n = AvlMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = AvlMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// let union = AvlMap::union(n, m);
+    /// assert_eq!(
+    ///     union.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1), (&2, &2), (&3, &3)],
+    /// );
+    /// ```
+    pub fn union(left: Self, right: Self) -> Self
+    where
+        C: Compare<T>,
+    {
+        Self::combine(left, right, tree::union)
+    }
+
+    /// Returns the intersection of two maps. If there is a key that is found in both `left` and
+    /// `right`, the intersection will contain the value associated with the key in `left`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut n = AvlMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = AvlMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// let intersection = AvlMap::intersection(n, m);
+    /// assert_eq!(
+    ///     intersection.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&2, &2)],
+    /// );
+    /// ```
+    pub fn intersection(left: Self, right: Self) -> Self
+    where
+        C: Compare<T>,
+    {
+        Self::combine(left, right, tree::intersection)
+    }
+
+    /// Returns the difference of `left` and `right`. The returned map will contain all entries
+    /// that do not have a key in `right`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut n = AvlMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = AvlMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// let difference = AvlMap::difference(n, m);
+    /// assert_eq!(
+    ///     difference.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1)],
+    /// );
+    /// ```
+    pub fn difference(left: Self, right: Self) -> Self
+    where
+        C: Compare<T>,
+    {
+        Self::combine(left, right, |arena, tree, other_tree, swapped, compare| {
+            tree::difference(arena, tree, other_tree, swapped, false, compare)
+        })
+    }
+
+    /// Returns the symmetric difference of `left` and `right`. The returned map will contain all
+    /// entries that exist in one map, but not both maps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut n = AvlMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = AvlMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// let symmetric_difference = AvlMap::symmetric_difference(n, m);
+    /// assert_eq!(
+    ///     symmetric_difference.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1), (&3, &3)],
+    /// );
+    /// ```
+    pub fn symmetric_difference(left: Self, right: Self) -> Self
+    where
+        C: Compare<T>,
+    {
+        Self::combine(left, right, |arena, tree, other_tree, swapped, compare| {
+            tree::difference(arena, tree, other_tree, swapped, true, compare)
+        })
+    }
+
+    /// Returns an iterator over the map. The iterator will yield key-value pairs using in-order
+    /// traversal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// let mut iterator = map.iter();
+    /// assert_eq!(iterator.next(), Some((&1, &1)));
+    /// assert_eq!(iterator.next(), Some((&2, &2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> AvlMapIter<'_, T, U> {
+        AvlMapIter {
+            arena: &self.arena,
+            current: self.tree,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Returns a mutable iterator over the map. The iterator will yield key-value pairs using
+    /// in-order traversal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// for (key, value) in &mut map {
+    ///     *value += 1;
+    /// }
+    ///
+    /// let mut iterator = map.iter_mut();
+    /// assert_eq!(iterator.next(), Some((&1, &mut 2)));
+    /// assert_eq!(iterator.next(), Some((&2, &mut 3)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter_mut(&mut self) -> AvlMapIterMut<'_, T, U> {
+        AvlMapIterMut {
+            arena: &mut self.arena,
+            current: self.tree,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Begins a bulk update session on the map. Inserts and removes applied through the returned
+    /// guard are buffered and leave the map unchanged until the guard is dropped. The buffered
+    /// updates are then sorted and merged with the entries of the map, and the tree is rebuilt
+    /// into a balanced tree once, which takes `O(m log m + n)` time for `m` updates to a map of
+    /// length `n`. If a key is updated several times, only the last update is applied.
+    ///
+    /// If the guard is leaked, the buffered updates are discarded and the map is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// {
+    ///     let mut bulk = map.begin_bulk();
+    ///     bulk.insert(2, 2);
+    ///     bulk.insert(3, 3);
+    ///     bulk.remove(1);
+    /// }
+    /// assert_eq!(map.get(&1), None);
+    /// assert_eq!(map.get(&2), Some(&2));
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn begin_bulk(&mut self) -> AvlMapBulkUpdate<'_, T, U, C>
+    where
+        C: Compare<T>,
+    {
+        AvlMapBulkUpdate {
+            map: self,
+            updates: Vec::new(),
+        }
+    }
+}
+
+impl<T, U, C> IntoIterator for AvlMap<T, U, C> {
+    type IntoIter = AvlMapIntoIter<T, U>;
+    type Item = (T, U);
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.drain()
+    }
+}
+
+impl<'a, T, U, C> IntoIterator for &'a AvlMap<T, U, C>
+where
+    T: 'a,
+    U: 'a,
+{
+    type IntoIter = AvlMapIter<'a, T, U>;
+    type Item = (&'a T, &'a U);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, U, C> IntoIterator for &'a mut AvlMap<T, U, C>
+where
+    T: 'a,
+    U: 'a,
+{
+    type IntoIter = AvlMapIterMut<'a, T, U>;
+    type Item = (&'a T, &'a mut U);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An owning iterator for `AvlMap<T, U>`.
+///
+/// This iterator traverses the elements of the map in-order and yields owned entries.
+pub struct AvlMapIntoIter<T, U> {
+    arena: tree::Arena<T, U>,
+    current: tree::Tree,
+    stack: Vec<NodeRef>,
+}
+
+impl<T, U> Iterator for AvlMapIntoIter<T, U> {
+    type Item = (T, U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.current {
+            self.current = self.arena[node].left;
+            self.stack.push(node);
+        }
+        self.stack.pop().map(|node| {
+            let Node {
+                entry: Entry { key, value },
+                right,
+                ..
+            } = self.arena.free(&node);
+            self.current = right;
+            (key, value)
+        })
+    }
+}
+
+impl<T, U> Drop for AvlMapIntoIter<T, U> {
+    fn drop(&mut self) {
+        while self.next().is_some() {}
+    }
+}
+
+/// An iterator for `AvlMap<T, U>`.
+///
+/// This iterator traverses the elements of the map in-order and yields immutable references.
+pub struct AvlMapIter<'a, T, U> {
+    arena: &'a tree::Arena<T, U>,
+    current: tree::Tree,
+    stack: Vec<NodeRef>,
+}
+
+impl<'a, T, U> Iterator for AvlMapIter<'a, T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type Item = (&'a T, &'a U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let arena = self.arena;
+        while let Some(node) = self.current {
+            self.current = arena[node].left;
+            self.stack.push(node);
+        }
+        self.stack.pop().map(|node| {
+            let Node {
+                entry: Entry { ref key, ref value },
+                right,
+                ..
+            } = arena[node];
+            self.current = right;
+            (key, value)
+        })
+    }
+}
+
+/// A mutable iterator for `AvlMap<T, U>`.
+///
+/// This iterator traverses the elements of the map in-order and yields mutable references.
+pub struct AvlMapIterMut<'a, T, U> {
+    arena: &'a mut tree::Arena<T, U>,
+    current: tree::Tree,
+    stack: Vec<NodeRef>,
+}
+
+impl<'a, T, U> Iterator for AvlMapIterMut<'a, T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type Item = (&'a T, &'a mut U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.current {
+            self.current = self.arena[node].left;
+            self.stack.push(node);
+        }
+        self.stack.pop().map(|node| {
+            // each node is yielded at most once, so the mutable references are disjoint
+            let node = unsafe { &mut *(&mut self.arena[node] as *mut Node<T, U>) };
+            let Node {
+                entry:
+                    Entry {
+                        ref key,
+                        ref mut value,
+                    },
+                right,
+                ..
+            } = node;
+            self.current = *right;
+            (key, value)
+        })
+    }
+}
+
+/// A guard for a bulk update session on an `AvlMap<T, U>`.
+///
+/// Inserts and removes applied through the guard are buffered and applied to the map at once when
+/// the guard is dropped, at which point the tree is rebuilt into a balanced tree.
+pub struct AvlMapBulkUpdate<'a, T, U, C = Natural>
+where
+    C: Compare<T>,
+{
+    map: &'a mut AvlMap<T, U, C>,
+    updates: Vec<(T, Option<U>)>,
+}
+
+impl<'a, T, U, C> AvlMapBulkUpdate<'a, T, U, C>
+where
+    C: Compare<T>,
+{
+    /// Buffers the insertion of a key-value pair into the map. If the key already exists in the
+    /// map when the guard is dropped, the old key-value pair will be replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// {
+    ///     let mut bulk = map.begin_bulk();
+    ///     bulk.insert(1, 2);
+    ///     bulk.insert(2, 2);
+    /// }
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// assert_eq!(map.get(&2), Some(&2));
+    /// ```
+    pub fn insert(&mut self, key: T, value: U) {
+        self.updates.push((key, Some(value)));
+    }
 
-/// A mutable iterator for `AvlMap<T, U>`.
-///
-/// This iterator traverses the elements of the map in-order and yields mutable references.
-pub struct AvlMapIterMut<'a, T, U> {
-    current: Option<&'a mut Node<T, U>>,
-    stack: Vec<BorrowedIterEntryMut<'a, T, U>>,
+    /// Buffers the removal of a key from the map. The key-value pair is dropped when the guard is
+    /// dropped if the key exists in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    ///
+    /// let mut map = AvlMap::new();
+    /// map.insert(1, 1);
+    /// {
+    ///     let mut bulk = map.begin_bulk();
+    ///     bulk.remove(1);
+    ///     bulk.remove(2);
+    /// }
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: T) {
+        self.updates.push((key, None));
+    }
 }
 
-impl<'a, T, U> Iterator for AvlMapIterMut<'a, T, U>
+impl<'a, T, U, C> Drop for AvlMapBulkUpdate<'a, T, U, C>
 where
-    T: 'a,
-    U: 'a,
+    C: Compare<T>,
 {
-    type Item = (&'a T, &'a mut U);
+    fn drop(&mut self) {
+        let AvlMap {
+            ref mut arena,
+            ref mut tree,
+            ref mut len,
+            ref compare,
+        } = self.map;
+        let updates = mem::take(&mut self.updates);
+        *len = tree::apply_sorted(arena, tree, updates, compare);
+    }
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let AvlMapIterMut {
-            ref mut current,
-            ref mut stack,
-        } = self;
-        while current.is_some() {
-            stack.push(current.take().map(|node| {
-                *current = node.left.as_mut().map(|node| &mut **node);
-                (&mut node.entry, node.right.as_mut().map(|node| &mut **node))
-            }));
+impl<T, U, C> OrderedMap<T, U> for AvlMap<T, U, C>
+where
+    C: Compare<T>,
+{
+    type Iter<'a>
+        = AvlMapIter<'a, T, U>
+    where
+        Self: 'a,
+        T: 'a,
+        U: 'a;
+
+    fn insert(&mut self, key: T, value: U) -> Option<(T, U)> {
+        AvlMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &T) -> Option<(T, U)> {
+        AvlMap::remove(self, key)
+    }
+
+    fn contains_key(&self, key: &T) -> bool {
+        AvlMap::contains_key(self, key)
+    }
+
+    fn get(&self, key: &T) -> Option<&U> {
+        AvlMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &T) -> Option<&mut U> {
+        AvlMap::get_mut(self, key)
+    }
+
+    fn len(&self) -> usize {
+        AvlMap::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        AvlMap::is_empty(self)
+    }
+
+    fn floor(&self, key: &T) -> Option<&T> {
+        AvlMap::floor(self, key)
+    }
+
+    fn ceil(&self, key: &T) -> Option<&T> {
+        AvlMap::ceil(self, key)
+    }
+
+    fn min(&self) -> Option<&T> {
+        AvlMap::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        AvlMap::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        AvlMap::iter(self)
+    }
+}
+
+impl<T, U, C> Clone for AvlMap<T, U, C>
+where
+    T: Clone,
+    U: Clone,
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        let (arena, tree) = self.arena.clone_tree(self.tree);
+        AvlMap {
+            arena,
+            tree,
+            len: self.len,
+            compare: self.compare.clone(),
         }
-        stack.pop().and_then(|pair_opt| match pair_opt {
-            Some(pair) => {
-                let (entry, right) = pair;
-                let Entry {
-                    ref key,
-                    ref mut value,
-                } = entry;
-                *current = right;
-                Some((key, value))
-            }
-            None => None,
-        })
     }
 }
 
-impl<T, U> Default for AvlMap<T, U> {
+impl<T, U, C> Drop for AvlMap<T, U, C> {
+    fn drop(&mut self) {
+        self.arena.release(self.tree.take());
+    }
+}
+
+impl<T, U, C> fmt::Debug for AvlMap<T, U, C>
+where
+    T: fmt::Debug,
+    U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, U, C> PartialEq for AvlMap<T, U, C>
+where
+    T: PartialEq,
+    U: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T, U, C> Eq for AvlMap<T, U, C>
+where
+    T: Eq,
+    U: Eq,
+{
+}
+
+impl<T, U, C> Default for AvlMap<T, U, C>
+where
+    C: Default,
+{
     fn default() -> Self {
-        Self::new()
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, U> FromIterator<(T, U)> for AvlMap<T, U>
+where
+    T: Ord,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (T, U)>,
+    {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<T, U, C> Extend<(T, U)> for AvlMap<T, U, C>
+where
+    C: Compare<T>,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (T, U)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
     }
 }
 
-impl<'a, T, U, V> Index<&'a V> for AvlMap<T, U>
+impl<'a, T, U, V, C> Index<&'a V> for AvlMap<T, U, C>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
     type Output = U;
 
@@ -510,10 +1373,11 @@ where
     }
 }
 
-impl<'a, T, U, V> IndexMut<&'a V> for AvlMap<T, U>
+impl<'a, T, U, V, C> IndexMut<&'a V> for AvlMap<T, U, C>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
     fn index_mut(&mut self, key: &V) -> &mut Self::Output {
         self.get_mut(key).expect("Error: key does not exist.")
@@ -523,6 +1387,9 @@ where
 #[cfg(test)]
 mod tests {
     use super::AvlMap;
+    use crate::avl_tree::tree;
+    use core::mem;
+    use core::ops::{Bound, RangeBounds};
 
     #[test]
     fn test_len_empty() {
@@ -607,6 +1474,67 @@ mod tests {
         assert_eq!(map.ceil(&6), None);
     }
 
+    #[test]
+    fn test_floor_ceil_entry() {
+        let mut map = AvlMap::new();
+        for key in (0..100u32).rev() {
+            map.insert(key * 2, key * 2);
+        }
+
+        let to_key = |entry: Option<(&u32, &u32)>| {
+            entry.map(|(key, value)| {
+                assert_eq!(key, value);
+                *key
+            })
+        };
+        for key in 0..200 {
+            assert_eq!(to_key(map.floor_entry(&key)), Some(key / 2 * 2));
+            assert_eq!(
+                to_key(map.ceil_entry(&key)),
+                Some(key.div_ceil(2) * 2).filter(|key| *key < 200),
+            );
+            assert_eq!(
+                to_key(map.next_below(&key)),
+                key.checked_sub(1).map(|key| key / 2 * 2),
+            );
+            assert_eq!(
+                to_key(map.next_above(&key)),
+                Some(key / 2 * 2 + 2).filter(|key| *key < 200),
+            );
+        }
+
+        *map.floor_entry_mut(&5).unwrap().1 = 0;
+        *map.ceil_entry_mut(&5).unwrap().1 = 0;
+        assert_eq!(map.get(&4), Some(&0));
+        assert_eq!(map.get(&6), Some(&0));
+        assert_eq!(map.floor_entry_mut(&200).map(|(key, _)| *key), Some(198));
+        assert!(map.ceil_entry_mut(&199).is_none());
+    }
+
+    #[test]
+    fn test_pop_min_max() {
+        let mut map = AvlMap::new();
+        for key in 0..1_000u32 {
+            map.insert(key * 7_919 % 1_000, key);
+        }
+
+        let (mut min, mut max) = (0, 999);
+        while min <= max {
+            let (key, _) = map.pop_min().unwrap();
+            assert_eq!(key, min);
+            min += 1;
+            let (key, _) = map.pop_max().unwrap();
+            assert_eq!(key, max);
+            max -= 1;
+            assert_eq!(map.len(), (max + 1 - min) as usize);
+            check_balanced(&map.arena, map.tree);
+            assert_eq!(map.arena.len(), map.len());
+        }
+        assert_eq!(map.pop_min(), None);
+        assert_eq!(map.pop_max(), None);
+        assert!(map.is_empty());
+    }
+
     #[test]
     fn test_into_iter() {
         let mut map = AvlMap::new();
@@ -649,4 +1577,371 @@ mod tests {
             vec![(&1, &3), (&3, &5), (&5, &7)],
         );
     }
+
+    #[test]
+    fn test_bulk_update() {
+        let mut map = AvlMap::new();
+        for key in (0..10_000u32).filter(|key| key % 5 == 0) {
+            map.insert(key, 0);
+        }
+        {
+            let mut bulk = map.begin_bulk();
+            for key in 0..10_000u32 {
+                bulk.insert(key, key);
+            }
+            for key in (0..10_000).filter(|key| key % 3 == 0) {
+                bulk.remove(key);
+            }
+            bulk.remove(10_000);
+            bulk.insert(1, 2);
+            bulk.insert(3, 3);
+        }
+        let expected: Vec<(u32, u32)> = (0..10_000)
+            .filter(|key| key % 3 != 0 || *key == 3)
+            .map(|key| if key == 1 { (key, 2) } else { (key, key) })
+            .collect();
+
+        assert_eq!(map.len(), expected.len());
+        assert_eq!(map.arena.len(), map.len());
+        check_balanced(&map.arena, map.tree);
+        assert_eq!(
+            map.iter()
+                .map(|(key, value)| (*key, *value))
+                .collect::<Vec<(u32, u32)>>(),
+            expected,
+        );
+
+        map.insert(10_000, 10_000);
+        assert_eq!(map.remove(&2), Some((2, 2)));
+        assert_eq!(map.len(), expected.len());
+    }
+
+    #[test]
+    fn test_bulk_update_sorted() {
+        let mut map = AvlMap::new();
+        {
+            let mut bulk = map.begin_bulk();
+            for key in 0..100_000u32 {
+                bulk.insert(key, key);
+            }
+        }
+        assert_eq!(map.len(), 100_000);
+        assert!(tree::height(&map.arena, map.tree) <= 17);
+        assert!(map.iter().map(|(key, _)| *key).eq(0..100_000));
+    }
+
+    #[test]
+    fn test_bulk_update_leaked() {
+        let mut map = AvlMap::new();
+        map.insert(1, 1);
+        {
+            let mut bulk = map.begin_bulk();
+            bulk.insert(2, 2);
+            bulk.remove(1);
+            mem::forget(bulk);
+        }
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(&1), Some(&1));
+        assert_eq!(map.insert(2, 2), None);
+        check_balanced(&map.arena, map.tree);
+    }
+
+    #[test]
+    fn test_clone_eq() {
+        let mut map = AvlMap::new();
+        map.insert(1, 2);
+        map.insert(5, 6);
+        map.insert(3, 4);
+
+        let mut cloned = map.clone();
+        assert_eq!(cloned, map);
+        cloned.insert(3, 5);
+        assert_ne!(cloned, map);
+        cloned.insert(3, 4);
+        cloned.insert(7, 8);
+        assert_ne!(cloned, map);
+        cloned.remove(&7);
+        assert_eq!(cloned, map);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut map = AvlMap::new();
+        map.insert(3, 4);
+        map.insert(1, 2);
+        assert_eq!(format!("{:?}", map), "{1: 2, 3: 4}");
+    }
+
+    fn check_balanced<T, U>(arena: &tree::Arena<T, U>, tree: tree::Tree) -> usize {
+        match tree {
+            None => 0,
+            Some(node) => {
+                let left_height = check_balanced(arena, arena[node].left);
+                let right_height = check_balanced(arena, arena[node].right);
+                assert!(left_height <= right_height + 1 && right_height <= left_height + 1);
+                assert_eq!(arena[node].height, left_height.max(right_height) + 1);
+                assert_eq!(
+                    arena[node].len,
+                    tree::len(arena, arena[node].left) + tree::len(arena, arena[node].right) + 1,
+                );
+                arena[node].height
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_off_inclusive() {
+        let mut map = AvlMap::new();
+        map.insert(1, 1);
+        map.insert(2, 2);
+        map.insert(3, 3);
+
+        let split = map.split_off(&2, true);
+        assert_eq!(
+            map.iter().collect::<Vec<(&u32, &u32)>>(),
+            vec![(&1, &1), (&2, &2)],
+        );
+        assert_eq!(split.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&3, &3)]);
+    }
+
+    #[test]
+    fn test_split_off_not_inclusive() {
+        let mut map = AvlMap::new();
+        map.insert(1, 1);
+        map.insert(2, 2);
+        map.insert(3, 3);
+
+        let split = map.split_off(&2, false);
+        assert_eq!(map.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&1, &1)]);
+        assert_eq!(
+            split.iter().collect::<Vec<(&u32, &u32)>>(),
+            vec![(&2, &2), (&3, &3)],
+        );
+    }
+
+    #[test]
+    fn test_remove_range() {
+        let ranges: [(Bound<u32>, Bound<u32>); 6] = [
+            (Bound::Included(20), Bound::Excluded(70)),
+            (Bound::Excluded(20), Bound::Included(70)),
+            (Bound::Unbounded, Bound::Excluded(35)),
+            (Bound::Included(35), Bound::Unbounded),
+            (Bound::Unbounded, Bound::Unbounded),
+            (Bound::Included(50), Bound::Excluded(50)),
+        ];
+        for range in ranges.iter() {
+            let mut map = AvlMap::new();
+            for key in (0..100).rev() {
+                map.insert(key, key);
+            }
+
+            let removed = map.remove_range(*range);
+            let is_removed = |key: &u32| range.contains(key);
+            assert_eq!(map.len() + removed.len(), 100);
+            assert!(map
+                .iter()
+                .map(|(key, _)| *key)
+                .eq((0..100).filter(|key| !is_removed(key))));
+            assert!(removed
+                .iter()
+                .map(|(key, _)| *key)
+                .eq((0..100).filter(is_removed)));
+            check_balanced(&map.arena, map.tree);
+            check_balanced(&removed.arena, removed.tree);
+            assert_eq!(map.arena.len(), map.len());
+        }
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut map = AvlMap::new();
+        for key in 0..10_007u32 {
+            map.insert(key * 7_919 % 10_007, key);
+        }
+
+        let is_retained = |key: &u32| !key.is_multiple_of(3) && (*key < 2_000 || *key > 6_000);
+        map.retain(|key, value| {
+            *value += 1;
+            is_retained(key)
+        });
+        let expected: Vec<u32> = (0..10_007).filter(is_retained).collect();
+        assert_eq!(map.len(), expected.len());
+        assert_eq!(
+            map.iter().map(|(key, _)| *key).collect::<Vec<u32>>(),
+            expected,
+        );
+        check_balanced(&map.arena, map.tree);
+
+        for key in expected.iter().filter(|key| *key % 2 == 0) {
+            assert!(map.remove(key).is_some());
+        }
+        map.insert(4_000, 0);
+        check_balanced(&map.arena, map.tree);
+        assert_eq!(
+            map.len(),
+            expected.iter().filter(|key| *key % 2 == 1).count() + 1
+        );
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut map = AvlMap::new();
+        map.insert(1, 2);
+        map.insert(5, 6);
+        map.insert(3, 4);
+
+        assert_eq!(
+            map.drain().collect::<Vec<(u32, u32)>>(),
+            vec![(1, 2), (3, 4), (5, 6)],
+        );
+        assert!(map.is_empty());
+        map.insert(1, 2);
+        assert_eq!(map.get(&1), Some(&2));
+    }
+
+    #[test]
+    fn test_comparator() {
+        let mut map = AvlMap::with_comparator(|a: &String, b: &String| {
+            a.to_lowercase().cmp(&b.to_lowercase())
+        });
+        map.insert(String::from("b"), 1);
+        map.insert(String::from("C"), 2);
+        assert_eq!(
+            map.insert(String::from("B"), 3),
+            Some((String::from("b"), 1))
+        );
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&String::from("c")), Some(&2));
+        assert_eq!(map.floor(&String::from("bb")), Some(&String::from("B")));
+        assert_eq!(map.ceil(&String::from("a")), Some(&String::from("B")));
+        assert_eq!(map.remove(&String::from("c")), Some((String::from("C"), 2)));
+        assert_eq!(
+            map.iter().collect::<Vec<(&String, &u32)>>(),
+            vec![(&String::from("B"), &3)],
+        );
+    }
+
+    #[test]
+    fn test_join() {
+        for &(left_len, right_len) in [(1_000, 10), (10, 1_000), (0, 10), (10, 0)].iter() {
+            let mut left = AvlMap::new();
+            for key in (0..left_len).rev() {
+                left.insert(key, key);
+            }
+            let mut right = AvlMap::new();
+            for key in left_len..left_len + right_len {
+                right.insert(key, key);
+            }
+
+            let mut map = AvlMap::join(left, right);
+            assert!(map
+                .iter()
+                .map(|(key, value)| (*key, *value))
+                .eq((0..left_len + right_len).map(|key| (key, key))));
+            check_balanced(&map.arena, map.tree);
+            assert_eq!(map.arena.len(), map.len());
+
+            map.insert(left_len + right_len, 0);
+            assert_eq!(map.remove(&0), Some((0, 0)));
+            check_balanced(&map.arena, map.tree);
+        }
+    }
+
+    #[test]
+    fn test_with_arena() {
+        let mut map = AvlMap::with_arena();
+        for key in 0..100 {
+            map.insert(key, key);
+        }
+        let mut split = map.split_off(&49, true);
+        let removed = split.remove_range(60..70);
+        assert_eq!(map.len(), 50);
+        assert_eq!(split.len(), 40);
+        assert_eq!(removed.len(), 10);
+        check_balanced(&split.arena, split.tree);
+        assert_eq!(split.arena.len(), split.len());
+
+        let mut boxed = AvlMap::new();
+        for key in 100..110 {
+            boxed.insert(key, key);
+        }
+        let map = AvlMap::join(AvlMap::union(map, split.clone()), boxed);
+        assert!(map.iter().map(|(key, _)| *key).eq((0..60).chain(70..110)));
+        check_balanced(&map.arena, map.tree);
+        assert_eq!(map.arena.len(), map.len());
+        assert_eq!(map.into_iter().take(5).count(), 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_join_overlapping() {
+        let mut left = AvlMap::new();
+        left.insert(2, 2);
+        let mut right = AvlMap::new();
+        right.insert(1, 1);
+        AvlMap::join(left, right);
+    }
+
+    fn check_set_operation<F, G>(f: F, is_included: G)
+    where
+        F: Fn(AvlMap<u32, u32>, AvlMap<u32, u32>) -> AvlMap<u32, u32>,
+        G: Fn(bool, bool) -> bool,
+    {
+        for &(left_len, right_len) in [(3_000, 300), (300, 3_000), (0, 300), (300, 0)].iter() {
+            let mut left = AvlMap::new();
+            for key in (0..left_len).filter(|key| key % 2 == 0) {
+                left.insert(key, 0);
+            }
+            let mut right = AvlMap::new();
+            for key in (0..right_len).rev().filter(|key| key % 3 == 0) {
+                right.insert(key, 1);
+            }
+
+            let map = f(left, right);
+            let expected: Vec<(u32, u32)> = (0..left_len.max(right_len))
+                .filter_map(|key| {
+                    let in_left = key < left_len && key % 2 == 0;
+                    let in_right = key < right_len && key % 3 == 0;
+                    if is_included(in_left, in_right) {
+                        Some((key, if in_left { 0 } else { 1 }))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            assert_eq!(
+                map.iter()
+                    .map(|(key, value)| (*key, *value))
+                    .collect::<Vec<(u32, u32)>>(),
+                expected,
+            );
+            check_balanced(&map.arena, map.tree);
+            assert_eq!(map.arena.len(), map.len());
+        }
+    }
+
+    #[test]
+    fn test_union() {
+        check_set_operation(AvlMap::union, |in_left, in_right| in_left || in_right);
+    }
+
+    #[test]
+    fn test_intersection() {
+        check_set_operation(AvlMap::intersection, |in_left, in_right| {
+            in_left && in_right
+        });
+    }
+
+    #[test]
+    fn test_difference() {
+        check_set_operation(AvlMap::difference, |in_left, in_right| in_left && !in_right);
+    }
+
+    #[test]
+    fn test_symmetric_difference() {
+        check_set_operation(AvlMap::symmetric_difference, |in_left, in_right| {
+            in_left != in_right
+        });
+    }
 }
@@ -1,10 +1,10 @@
 //! Self-balancing binary search tree where the heights of the two child subtrees of any node
 //! differ by at most one.
 
-mod map;
+pub(crate) mod map;
 mod node;
 mod set;
 mod tree;
 
-pub use self::map::AvlMap;
+pub use self::map::{AvlMap, AvlMapBulkUpdate};
 pub use self::set::AvlSet;
@@ -1,14 +1,16 @@
 use crate::avl_tree::tree;
 use crate::entry::Entry;
-use std::cmp;
-use std::mem;
+use crate::node_arena::{Links, NodeArena, NodeRef};
+use core::cmp;
 
 /// A struct representing an internal node of an avl tree.
+#[derive(Clone)]
 pub struct Node<T, U> {
     pub entry: Entry<T, U>,
     pub height: usize,
-    pub left: tree::Tree<T, U>,
-    pub right: tree::Tree<T, U>,
+    pub len: usize,
+    pub left: tree::Tree,
+    pub right: tree::Tree,
 }
 
 impl<T, U> Node<T, U> {
@@ -16,46 +18,55 @@ impl<T, U> Node<T, U> {
         Node {
             entry: Entry { key, value },
             height: 1,
+            len: 1,
             left: None,
             right: None,
         }
     }
 
-    pub fn update(&mut self) {
-        let Node {
-            ref mut height,
-            ref left,
-            ref right,
-            ..
-        } = self;
-        *height = cmp::max(tree::height(left), tree::height(right)) + 1;
+    pub fn update(arena: &mut NodeArena<Self>, node: NodeRef) {
+        let height = cmp::max(
+            tree::height(arena, arena[node].left),
+            tree::height(arena, arena[node].right),
+        ) + 1;
+        let len = tree::len(arena, arena[node].left) + tree::len(arena, arena[node].right) + 1;
+        arena[node].height = height;
+        arena[node].len = len;
     }
 
-    pub fn balance(&self) -> i32 {
-        (tree::height(&self.left) as i32) - (tree::height(&self.right) as i32)
+    pub fn balance(arena: &NodeArena<Self>, node: NodeRef) -> i32 {
+        (tree::height(arena, arena[node].left) as i32)
+            - (tree::height(arena, arena[node].right) as i32)
     }
 
-    pub fn rotate_left(&mut self) {
-        let mut child = self
+    // Rotations swap the contents of the nodes so that the root of the subtree keeps its handle.
+    pub fn rotate_left(arena: &mut NodeArena<Self>, node: NodeRef) {
+        let child = arena[node]
             .right
             .take()
             .expect("Expected right child node to be `Some`.");
-        self.right = child.left.take();
-        mem::swap(&mut *child, self);
-        child.update();
-        self.left = Some(child);
-        self.update();
+        arena[node].right = arena[child].left.take();
+        arena.swap(&node, &child);
+        Self::update(arena, child);
+        arena[node].left = Some(child);
+        Self::update(arena, node);
     }
 
-    pub fn rotate_right(&mut self) {
-        let mut child = self
+    pub fn rotate_right(arena: &mut NodeArena<Self>, node: NodeRef) {
+        let child = arena[node]
             .left
             .take()
             .expect("Expected left child node to be `Some`.");
-        self.left = child.right.take();
-        mem::swap(&mut *child, self);
-        child.update();
-        self.right = Some(child);
-        self.update();
+        arena[node].left = arena[child].right.take();
+        arena.swap(&node, &child);
+        Self::update(arena, child);
+        arena[node].right = Some(child);
+        Self::update(arena, node);
+    }
+}
+
+impl<T, U> Links for Node<T, U> {
+    fn links_mut(&mut self) -> (&mut tree::Tree, &mut tree::Tree) {
+        (&mut self.left, &mut self.right)
     }
 }
@@ -1,114 +1,446 @@
 use crate::avl_tree::node::Node;
+use crate::compare::Compare;
 use crate::entry::Entry;
-use std::borrow::Borrow;
-use std::cmp::Ordering;
-use std::mem;
+use crate::node_arena::{NodeArena, NodeRef};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::mem;
 
-pub type Tree<T, U> = Option<Box<Node<T, U>>>;
+pub type Tree = Option<NodeRef>;
 
-pub fn height<T, U>(tree: &Tree<T, U>) -> usize {
+pub type Arena<T, U> = NodeArena<Node<T, U>>;
+
+pub fn height<T, U>(arena: &Arena<T, U>, tree: Tree) -> usize {
+    match tree {
+        None => 0,
+        Some(node) => arena[node].height,
+    }
+}
+
+pub fn len<T, U>(arena: &Arena<T, U>, tree: Tree) -> usize {
     match tree {
         None => 0,
-        Some(ref node) => (**node).height,
+        Some(node) => arena[node].len,
     }
 }
 
-fn balance<T, U>(tree: &mut Tree<T, U>) {
-    let mut node = match tree.take() {
+fn balance<T, U>(arena: &mut Arena<T, U>, tree: Tree) {
+    let node = match tree {
         Some(node) => node,
         None => return,
     };
 
-    node.update();
+    Node::update(arena, node);
 
-    if node.balance() > 1 {
-        if let Some(ref mut child) = node.left {
-            if child.balance() < 0 {
-                child.rotate_left();
+    if Node::balance(arena, node) > 1 {
+        if let Some(child) = arena[node].left {
+            if Node::balance(arena, child) < 0 {
+                Node::rotate_left(arena, child);
             }
         }
-        node.rotate_right();
-    } else if node.balance() < -1 {
-        if let Some(ref mut child) = node.right {
-            if child.balance() > 0 {
-                child.rotate_right();
+        Node::rotate_right(arena, node);
+    } else if Node::balance(arena, node) < -1 {
+        if let Some(child) = arena[node].right {
+            if Node::balance(arena, child) > 0 {
+                Node::rotate_right(arena, child);
             }
         }
-        node.rotate_left();
+        Node::rotate_left(arena, node);
     }
-
-    *tree = Some(node);
 }
 
 // precondition: there exists a minimum node in the tree
-fn remove_min<T, U>(tree: &mut Tree<T, U>) -> Box<Node<T, U>> {
-    if let Some(ref mut node) = tree {
-        if node.left.is_some() {
-            return remove_min(&mut node.left);
+fn remove_min<T, U>(arena: &mut Arena<T, U>, tree: &mut Tree) -> NodeRef {
+    if let Some(node) = *tree {
+        if arena[node].left.is_some() {
+            let mut left = arena[node].left;
+            let ret = remove_min(arena, &mut left);
+            arena[node].left = left;
+            balance(arena, *tree);
+            return ret;
         }
     }
 
-    let mut node = tree.take().expect("Expected a non-empty tree.");
-    *tree = node.right.take();
+    let node = tree.take().expect("Expected a non-empty tree.");
+    *tree = arena[node].right.take();
     node
 }
 
-fn combine_subtrees<T, U>(left_tree: Tree<T, U>, mut right_tree: Tree<T, U>) -> Tree<T, U> {
-    let mut new_root = remove_min(&mut right_tree);
-    new_root.left = left_tree;
-    new_root.right = right_tree;
+fn remove_max<T, U>(arena: &mut Arena<T, U>, tree: &mut Tree) -> NodeRef {
+    if let Some(node) = *tree {
+        if arena[node].right.is_some() {
+            let mut right = arena[node].right;
+            let ret = remove_max(arena, &mut right);
+            arena[node].right = right;
+            balance(arena, *tree);
+            return ret;
+        }
+    }
+
+    let node = tree.take().expect("Expected a non-empty tree.");
+    *tree = arena[node].left.take();
+    node
+}
+
+fn combine_subtrees<T, U>(arena: &mut Arena<T, U>, left_tree: Tree, mut right_tree: Tree) -> Tree {
+    let new_root = remove_min(arena, &mut right_tree);
+    arena[new_root].left = left_tree;
+    arena[new_root].right = right_tree;
     Some(new_root)
 }
 
-pub fn insert<T, U>(tree: &mut Tree<T, U>, new_node: Node<T, U>) -> Option<Entry<T, U>>
+// Joins two trees with `node` as the middle key. All keys in `left` must be less than the key of
+// `node` and all keys in `right` must be greater than the key of `node`.
+fn join<T, U>(arena: &mut Arena<T, U>, left: Tree, node: NodeRef, right: Tree) -> NodeRef {
+    let left_height = height(arena, left);
+    let right_height = height(arena, right);
+    if left_height > right_height + 1 {
+        let left = left.expect("Expected a non-empty tree.");
+        let left_right = arena[left].right;
+        let child = join(arena, left_right, node, right);
+        arena[left].right = Some(child);
+        balance(arena, Some(left));
+        left
+    } else if right_height > left_height + 1 {
+        let right = right.expect("Expected a non-empty tree.");
+        let right_left = arena[right].left;
+        let child = join(arena, left, node, right_left);
+        arena[right].left = Some(child);
+        balance(arena, Some(right));
+        right
+    } else {
+        arena[node].left = left;
+        arena[node].right = right;
+        Node::update(arena, node);
+        node
+    }
+}
+
+// Joins two trees without a middle key. All keys in `left` must be less than all keys in `right`.
+pub fn join_subtrees<T, U>(arena: &mut Arena<T, U>, left: Tree, mut right: Tree) -> Tree {
+    if right.is_none() {
+        return left;
+    }
+    let node = remove_min(arena, &mut right);
+    Some(join(arena, left, node, right))
+}
+
+// Removes all entries whose key-value pairs do not satisfy `f` by joining the retained subtrees, so
+// the tree is rebalanced without being rebuilt. The entries are visited in ascending order.
+pub fn retain<T, U, F>(arena: &mut Arena<T, U>, tree: Tree, f: &mut F) -> Tree
 where
-    T: Ord,
+    F: FnMut(&T, &mut U) -> bool,
 {
-    let ret = match tree {
-        Some(ref mut node) => match new_node.entry.key.cmp(&node.entry.key) {
-            Ordering::Less => insert(&mut node.left, new_node),
-            Ordering::Greater => insert(&mut node.right, new_node),
-            Ordering::Equal => {
-                let Node { ref mut entry, .. } = &mut **node;
-                return Some(mem::replace(entry, new_node.entry));
+    let node = tree?;
+    let left = arena[node].left.take();
+    let left = retain(arena, left, f);
+    let keep = {
+        let Entry {
+            ref key,
+            ref mut value,
+        } = arena[node].entry;
+        f(key, value)
+    };
+    let right = arena[node].right.take();
+    let right = retain(arena, right, f);
+    if keep {
+        Some(join(arena, left, node, right))
+    } else {
+        arena.free(&node);
+        join_subtrees(arena, left, right)
+    }
+}
+
+// Splits a tree into the nodes whose keys are less than `key` and the remaining nodes by joining
+// the subtrees along the search path. A node whose key is equal to `key` is kept in the left tree
+// if `inclusive` is true.
+pub fn split<T, U, V, C>(
+    arena: &mut Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+    compare: &C,
+) -> (Tree, Tree)
+where
+    T: Borrow<V>,
+    V: ?Sized,
+    C: Compare<V>,
+{
+    let node = match tree {
+        Some(node) => node,
+        None => return (None, None),
+    };
+    let left = arena[node].left.take();
+    let right = arena[node].right.take();
+    let is_left = match compare.compare(arena[node].entry.key.borrow(), key) {
+        Ordering::Less => true,
+        Ordering::Equal => inclusive,
+        Ordering::Greater => false,
+    };
+    if is_left {
+        let (middle, right) = split(arena, right, key, inclusive, compare);
+        (Some(join(arena, left, node, middle)), right)
+    } else {
+        let (left, middle) = split(arena, left, key, inclusive, compare);
+        (left, Some(join(arena, middle, node, right)))
+    }
+}
+
+// Frees all nodes of a tree.
+pub fn free<T, U>(arena: &mut Arena<T, U>, tree: Tree) {
+    if let Some(node) = tree {
+        let Node { left, right, .. } = arena.free(&node);
+        free(arena, left);
+        free(arena, right);
+    }
+}
+
+// Splits a tree into the nodes whose keys are less than the key of `pivot`, the node whose key is
+// equal to the key of `pivot` if it exists, and the nodes whose keys are greater than the key of
+// `pivot`. `pivot` must not be in the tree.
+fn split_at<T, U, C>(
+    arena: &mut Arena<T, U>,
+    tree: Tree,
+    pivot: NodeRef,
+    compare: &C,
+) -> (Tree, Tree, Tree)
+where
+    C: Compare<T>,
+{
+    let node = match tree {
+        Some(node) => node,
+        None => return (None, None, None),
+    };
+    let left = arena[node].left.take();
+    let right = arena[node].right.take();
+    match compare.compare(&arena[node].entry.key, &arena[pivot].entry.key) {
+        Ordering::Less => {
+            let (middle, dup, right) = split_at(arena, right, pivot, compare);
+            (Some(join(arena, left, node, middle)), dup, right)
+        }
+        Ordering::Equal => (left, Some(node), right),
+        Ordering::Greater => {
+            let (left, dup, middle) = split_at(arena, left, pivot, compare);
+            (left, dup, Some(join(arena, middle, node, right)))
+        }
+    }
+}
+
+// Returns the union of two trees. If a key is in both trees, the entry in `left` is kept, or the
+// entry in `right` if `swapped` is true.
+pub fn union<T, U, C>(
+    arena: &mut Arena<T, U>,
+    left: Tree,
+    right: Tree,
+    swapped: bool,
+    compare: &C,
+) -> Tree
+where
+    C: Compare<T>,
+{
+    let node = match (left, right) {
+        (Some(node), Some(_)) => node,
+        (None, tree) | (tree, None) => return tree,
+    };
+    let left_left = arena[node].left.take();
+    let left_right = arena[node].right.take();
+    let (right_left, dup, right_right) = split_at(arena, right, node, compare);
+    let left = union(arena, left_left, right_left, swapped, compare);
+    let right = union(arena, left_right, right_right, swapped, compare);
+    let node = match dup {
+        Some(dup) if swapped => {
+            arena.free(&node);
+            dup
+        }
+        Some(dup) => {
+            arena.free(&dup);
+            node
+        }
+        None => node,
+    };
+    Some(join(arena, left, node, right))
+}
+
+// Returns the intersection of two trees. The entry in `left` is kept, or the entry in `right` if
+// `swapped` is true.
+pub fn intersection<T, U, C>(
+    arena: &mut Arena<T, U>,
+    left: Tree,
+    right: Tree,
+    swapped: bool,
+    compare: &C,
+) -> Tree
+where
+    C: Compare<T>,
+{
+    let node = match (left, right) {
+        (Some(node), Some(_)) => node,
+        _ => {
+            free(arena, left);
+            free(arena, right);
+            return None;
+        }
+    };
+    let left_left = arena[node].left.take();
+    let left_right = arena[node].right.take();
+    let (right_left, dup, right_right) = split_at(arena, right, node, compare);
+    let left = intersection(arena, left_left, right_left, swapped, compare);
+    let right = intersection(arena, left_right, right_right, swapped, compare);
+    match dup {
+        Some(dup) => {
+            let (node, dup) = if swapped { (dup, node) } else { (node, dup) };
+            arena.free(&dup);
+            Some(join(arena, left, node, right))
+        }
+        None => {
+            arena.free(&node);
+            join_subtrees(arena, left, right)
+        }
+    }
+}
+
+// Returns the entries of `left` whose keys are not in `right`, or the entries of `right` whose
+// keys are not in `left` if `swapped` is true. If `symmetric` is true, the entries of both trees
+// whose keys are not in the other tree are returned.
+pub fn difference<T, U, C>(
+    arena: &mut Arena<T, U>,
+    left: Tree,
+    right: Tree,
+    swapped: bool,
+    symmetric: bool,
+    compare: &C,
+) -> Tree
+where
+    C: Compare<T>,
+{
+    let keep_left = !swapped || symmetric;
+    let keep_right = swapped || symmetric;
+    let node = match (left, right) {
+        (Some(node), Some(_)) => node,
+        (left, None) => {
+            if keep_left {
+                return left;
+            }
+            free(arena, left);
+            return None;
+        }
+        (None, right) => {
+            if keep_right {
+                return right;
+            }
+            free(arena, right);
+            return None;
+        }
+    };
+    let left_left = arena[node].left.take();
+    let left_right = arena[node].right.take();
+    let (right_left, dup, right_right) = split_at(arena, right, node, compare);
+    let left = difference(arena, left_left, right_left, swapped, symmetric, compare);
+    let right = difference(arena, left_right, right_right, swapped, symmetric, compare);
+    match dup {
+        None if keep_left => Some(join(arena, left, node, right)),
+        _ => {
+            if let Some(dup) = dup {
+                arena.free(&dup);
+            }
+            arena.free(&node);
+            join_subtrees(arena, left, right)
+        }
+    }
+}
+
+// Moves a tree from `arena` to `new_arena` without changing its shape. Boxed nodes are moved in
+// constant time, and pooled nodes are moved one at a time. Returns the moved tree and its number
+// of nodes.
+pub fn transfer<T, U>(
+    arena: &mut Arena<T, U>,
+    new_arena: &mut Arena<T, U>,
+    tree: Tree,
+) -> (Tree, usize) {
+    let len = len(arena, tree);
+    if arena.try_move(new_arena, len) {
+        return (tree, len);
+    }
+    (move_nodes(arena, new_arena, tree), len)
+}
+
+fn move_nodes<T, U>(arena: &mut Arena<T, U>, new_arena: &mut Arena<T, U>, tree: Tree) -> Tree {
+    let mut node = arena.free(&tree?);
+    node.left = move_nodes(arena, new_arena, node.left.take());
+    node.right = move_nodes(arena, new_arena, node.right.take());
+    Some(new_arena.allocate(node))
+}
+
+pub fn insert<T, U, C>(
+    arena: &mut Arena<T, U>,
+    tree: &mut Tree,
+    new_node: Node<T, U>,
+    compare: &C,
+) -> Option<Entry<T, U>>
+where
+    C: Compare<T>,
+{
+    let ret = match *tree {
+        Some(node) => match compare.compare(&new_node.entry.key, &arena[node].entry.key) {
+            Ordering::Less => {
+                let mut left = arena[node].left;
+                let ret = insert(arena, &mut left, new_node, compare);
+                arena[node].left = left;
+                ret
             }
+            Ordering::Greater => {
+                let mut right = arena[node].right;
+                let ret = insert(arena, &mut right, new_node, compare);
+                arena[node].right = right;
+                ret
+            }
+            Ordering::Equal => return Some(mem::replace(&mut arena[node].entry, new_node.entry)),
         },
         None => {
-            *tree = Some(Box::new(new_node));
+            *tree = Some(arena.allocate(new_node));
             return None;
         }
     };
 
-    balance(tree);
+    balance(arena, *tree);
     ret
 }
 
-pub fn remove<T, U, V>(tree: &mut Tree<T, U>, key: &V) -> Option<Entry<T, U>>
+pub fn remove<T, U, V, C>(
+    arena: &mut Arena<T, U>,
+    tree: &mut Tree,
+    key: &V,
+    compare: &C,
+) -> Option<Entry<T, U>>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
-    let ret = match tree.take() {
-        Some(mut node) => match key.cmp(node.entry.key.borrow()) {
+    let ret = match *tree {
+        Some(node) => match compare.compare(key, arena[node].entry.key.borrow()) {
             Ordering::Less => {
-                let ret = remove(&mut node.left, key);
-                *tree = Some(node);
+                let mut left = arena[node].left;
+                let ret = remove(arena, &mut left, key, compare);
+                arena[node].left = left;
                 ret
             }
             Ordering::Greater => {
-                let ret = remove(&mut node.right, key);
-                *tree = Some(node);
+                let mut right = arena[node].right;
+                let ret = remove(arena, &mut right, key, compare);
+                arena[node].right = right;
                 ret
             }
             Ordering::Equal => {
-                let unboxed_node = *node;
                 let Node {
                     entry, left, right, ..
-                } = unboxed_node;
+                } = arena.free(&node);
                 match (left, right) {
                     (None, right) => *tree = right,
                     (left, None) => *tree = left,
-                    (left, right) => *tree = combine_subtrees(left, right),
+                    (left, right) => *tree = combine_subtrees(arena, left, right),
                 }
                 Some(entry)
             }
@@ -116,90 +448,292 @@ where
         None => return None,
     };
 
-    balance(tree);
+    balance(arena, *tree);
     ret
 }
 
-pub fn get<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V) -> Option<&'a Entry<T, U>>
+pub fn pop_min<T, U>(arena: &mut Arena<T, U>, tree: &mut Tree) -> Option<Entry<T, U>> {
+    if tree.is_none() {
+        return None;
+    }
+    let node = remove_min(arena, tree);
+    Some(arena.free(&node).entry)
+}
+
+pub fn pop_max<T, U>(arena: &mut Arena<T, U>, tree: &mut Tree) -> Option<Entry<T, U>> {
+    if tree.is_none() {
+        return None;
+    }
+    let node = remove_max(arena, tree);
+    Some(arena.free(&node).entry)
+}
+
+fn find<T, U, V, C>(arena: &Arena<T, U>, tree: Tree, key: &V, compare: &C) -> Tree
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
-    tree.as_ref()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Less => get(&node.left, key),
-            Ordering::Greater => get(&node.right, key),
-            Ordering::Equal => Some(&node.entry),
-        })
+    let mut curr = tree;
+    while let Some(node) = curr {
+        match compare.compare(key, arena[node].entry.key.borrow()) {
+            Ordering::Less => curr = arena[node].left,
+            Ordering::Greater => curr = arena[node].right,
+            Ordering::Equal => break,
+        }
+    }
+    curr
 }
 
-pub fn get_mut<'a, T, U, V>(tree: &'a mut Tree<T, U>, key: &V) -> Option<&'a mut Entry<T, U>>
+pub fn get<'a, T, U, V, C>(
+    arena: &'a Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    compare: &C,
+) -> Option<&'a Entry<T, U>>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
-    tree.as_mut()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Less => get_mut(&mut node.left, key),
-            Ordering::Greater => get_mut(&mut node.right, key),
-            Ordering::Equal => Some(&mut node.entry),
-        })
+    find(arena, tree, key, compare).map(|node| &arena[node].entry)
 }
 
-pub fn ceil<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V) -> Option<&'a Entry<T, U>>
+pub fn get_mut<'a, T, U, V, C>(
+    arena: &'a mut Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    compare: &C,
+) -> Option<&'a mut Entry<T, U>>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
-    tree.as_ref()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Greater => ceil(&node.right, key),
-            Ordering::Less => match ceil(&node.left, key) {
-                None => Some(&node.entry),
-                res => res,
-            },
-            Ordering::Equal => Some(&node.entry),
-        })
+    find(arena, tree, key, compare).map(move |node| &mut arena[node].entry)
 }
 
-pub fn floor<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V) -> Option<&'a Entry<T, U>>
+// Returns the node with the least key that is greater than a particular key, or equal to it if
+// `inclusive` is true.
+fn find_ceil<T, U, V, C>(
+    arena: &Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+    compare: &C,
+) -> Tree
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
-    tree.as_ref()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Less => floor(&node.left, key),
-            Ordering::Greater => match floor(&node.right, key) {
-                None => Some(&node.entry),
-                res => res,
-            },
-            Ordering::Equal => Some(&node.entry),
-        })
+    let mut curr = tree;
+    let mut ret = None;
+    while let Some(node) = curr {
+        match compare.compare(key, arena[node].entry.key.borrow()) {
+            Ordering::Less => {
+                ret = curr;
+                curr = arena[node].left;
+            }
+            Ordering::Equal if inclusive => return curr,
+            _ => curr = arena[node].right,
+        }
+    }
+    ret
 }
 
-pub fn min<T, U>(tree: &Tree<T, U>) -> Option<&Entry<T, U>>
+// Returns the node with the greatest key that is less than a particular key, or equal to it if
+// `inclusive` is true.
+fn find_floor<T, U, V, C>(
+    arena: &Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+    compare: &C,
+) -> Tree
 where
-    T: Ord,
+    T: Borrow<V>,
+    V: ?Sized,
+    C: Compare<V>,
 {
-    tree.as_ref().and_then(|node| {
-        let mut curr = node;
-        while let Some(ref left_node) = curr.left {
-            curr = left_node;
+    let mut curr = tree;
+    let mut ret = None;
+    while let Some(node) = curr {
+        match compare.compare(key, arena[node].entry.key.borrow()) {
+            Ordering::Greater => {
+                ret = curr;
+                curr = arena[node].right;
+            }
+            Ordering::Equal if inclusive => return curr,
+            _ => curr = arena[node].left,
         }
-        Some(&curr.entry)
-    })
+    }
+    ret
+}
+
+pub fn ceil<'a, T, U, V, C>(
+    arena: &'a Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+    compare: &C,
+) -> Option<&'a Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: ?Sized,
+    C: Compare<V>,
+{
+    find_ceil(arena, tree, key, inclusive, compare).map(|node| &arena[node].entry)
 }
 
-pub fn max<T, U>(tree: &Tree<T, U>) -> Option<&Entry<T, U>>
+pub fn ceil_mut<'a, T, U, V, C>(
+    arena: &'a mut Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+    compare: &C,
+) -> Option<&'a mut Entry<T, U>>
 where
-    T: Ord,
+    T: Borrow<V>,
+    V: ?Sized,
+    C: Compare<V>,
+{
+    find_ceil(arena, tree, key, inclusive, compare).map(move |node| &mut arena[node].entry)
+}
+
+pub fn floor<'a, T, U, V, C>(
+    arena: &'a Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+    compare: &C,
+) -> Option<&'a Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: ?Sized,
+    C: Compare<V>,
 {
-    tree.as_ref().and_then(|node| {
-        let mut curr = node;
-        while let Some(ref right_node) = curr.right {
-            curr = right_node;
+    find_floor(arena, tree, key, inclusive, compare).map(|node| &arena[node].entry)
+}
+
+pub fn floor_mut<'a, T, U, V, C>(
+    arena: &'a mut Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+    compare: &C,
+) -> Option<&'a mut Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: ?Sized,
+    C: Compare<V>,
+{
+    find_floor(arena, tree, key, inclusive, compare).map(move |node| &mut arena[node].entry)
+}
+
+pub fn min<T, U>(arena: &Arena<T, U>, tree: Tree) -> Option<&Entry<T, U>> {
+    tree.map(|node| {
+        let mut curr = &arena[node];
+        while let Some(left_node) = curr.left {
+            curr = &arena[left_node];
         }
-        Some(&curr.entry)
+        &curr.entry
     })
 }
+
+pub fn max<T, U>(arena: &Arena<T, U>, tree: Tree) -> Option<&Entry<T, U>> {
+    tree.map(|node| {
+        let mut curr = &arena[node];
+        while let Some(right_node) = curr.right {
+            curr = &arena[right_node];
+        }
+        &curr.entry
+    })
+}
+
+fn build<T, U>(arena: &mut Arena<T, U>, nodes: &mut vec::IntoIter<NodeRef>, len: usize) -> Tree {
+    if len == 0 {
+        return None;
+    }
+
+    let left_len = len / 2;
+    let left = build(arena, nodes, left_len);
+    let node = nodes.next().expect("Expected a non-empty iterator.");
+    let right = build(arena, nodes, len - left_len - 1);
+    arena[node].left = left;
+    arena[node].right = right;
+    Node::update(arena, node);
+    Some(node)
+}
+
+// Applies a batch of updates to a tree and rebuilds it into a perfectly balanced tree. An update
+// of `Some(value)` inserts or replaces the key and an update of `None` removes the key. If a key is
+// updated several times, only the last update is applied. The updates are sorted and merged with
+// the nodes of the tree in order, so it takes `O(m log m + n)` time for `m` updates and `n` nodes.
+// Returns the number of nodes in the rebuilt tree.
+pub fn apply_sorted<T, U, C>(
+    arena: &mut Arena<T, U>,
+    tree: &mut Tree,
+    mut updates: Vec<(T, Option<U>)>,
+    compare: &C,
+) -> usize
+where
+    C: Compare<T>,
+{
+    updates.sort_by(|a, b| compare.compare(&a.0, &b.0));
+    let mut updates = updates.into_iter().peekable();
+    let mut nodes = Vec::new();
+    let mut stack = Vec::new();
+    let mut curr = tree.take();
+    loop {
+        while let Some(node) = curr {
+            curr = arena[node].left.take();
+            stack.push(node);
+        }
+        let node = stack.pop();
+        if let Some(node) = node {
+            curr = arena[node].right.take();
+        }
+
+        // applies the updates whose keys are less than or equal to the key of `node`
+        let mut is_removed = false;
+        while let Some((key, _)) = updates.peek() {
+            let ordering = match node {
+                Some(node) => compare.compare(key, &arena[node].entry.key),
+                None => Ordering::Less,
+            };
+            if ordering == Ordering::Greater {
+                break;
+            }
+            let (key, value) = updates.next().expect("Expected a non-empty iterator.");
+            let is_last = match updates.peek() {
+                Some((next_key, _)) => compare.compare(&key, next_key) != Ordering::Equal,
+                None => true,
+            };
+            if !is_last {
+                continue;
+            }
+            if ordering == Ordering::Equal {
+                let node = node.expect("Expected a node with an equal key.");
+                match value {
+                    Some(value) => arena[node].entry = Entry { key, value },
+                    None => is_removed = true,
+                }
+            } else if let Some(value) = value {
+                nodes.push(arena.allocate(Node::new(key, value)));
+            }
+        }
+
+        match node {
+            Some(node) if is_removed => {
+                arena.free(&node);
+            }
+            Some(node) => nodes.push(node),
+            None => break,
+        }
+    }
+
+    let len = nodes.len();
+    *tree = build(arena, &mut nodes.into_iter(), len);
+    len
+}
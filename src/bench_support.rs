@@ -0,0 +1,449 @@
+//! Workload generators for benchmarking the maps of this crate.
+//!
+//! A `Workload` describes the distribution of keys and the mix of operations of a benchmark, and
+//! generates a deterministic sequence of `Op` from a seed. `WorkloadMap` is implemented for the
+//! ordered maps of this crate and for `BTreeMap`, so the same sequence of operations can be
+//! replayed against each of them using `run`.
+//!
+//! # Examples
+//!
+//! ```
+//! use extended_collections::avl_tree::AvlMap;
+//! use extended_collections::bench_support::{self, KeyDistribution, Workload};
+//! use std::collections::BTreeMap;
+//!
+//! let workload = Workload::new(KeyDistribution::Zipfian(1.0), 1000, 50, 40, 10, 16);
+//! let ops = workload.generate(10_000, 1);
+//!
+//! let mut map = AvlMap::new();
+//! let mut expected = BTreeMap::new();
+//! assert_eq!(bench_support::run(&mut map, &ops), bench_support::run(&mut expected, &ops));
+//! ```
+
+use crate::avl_tree::AvlMap;
+#[cfg(feature = "bp_tree")]
+use crate::bp_tree::BpMap;
+use crate::compare::Compare;
+#[cfg(feature = "lsm")]
+use crate::lsm_tree::compaction::CompactionStrategy;
+#[cfg(feature = "lsm")]
+use crate::lsm_tree::LsmMap;
+use crate::red_black_tree::RedBlackMap;
+use crate::skiplist::SkipMap;
+use crate::splay_tree::SplayMap;
+use crate::treap::TreapMap;
+use rand::{Rng, SeedableRng, XorShiftRng};
+use std::collections::BTreeMap;
+
+/// The distribution of the keys of a workload over `0..key_count`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyDistribution {
+    /// Every key is equally likely.
+    Uniform,
+    /// The probability of key `k` is proportional to `1 / (k + 1)^s` for the given exponent `s`,
+    /// so small keys are accessed much more often than large keys.
+    Zipfian(f64),
+    /// Keys are generated in increasing order, starting over at `0` after `key_count - 1`.
+    Sequential,
+}
+
+/// An infinite iterator of keys drawn from a `KeyDistribution`.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::bench_support::{KeyDistribution, KeyGenerator};
+///
+/// let keys: Vec<u64> = KeyGenerator::new(KeyDistribution::Sequential, 3, 1).take(5).collect();
+/// assert_eq!(keys, vec![0, 1, 2, 0, 1]);
+///
+/// let keys = KeyGenerator::new(KeyDistribution::Uniform, 3, 1).take(100);
+/// assert!(keys.into_iter().all(|key| key < 3));
+/// ```
+pub struct KeyGenerator {
+    distribution: KeyDistribution,
+    key_count: u64,
+    rng: XorShiftRng,
+    next_key: u64,
+    // The cumulative probabilities of the keys of a Zipfian distribution.
+    cumulative_probabilities: Vec<f64>,
+}
+
+impl KeyGenerator {
+    /// Constructs a new `KeyGenerator` that generates keys in `0..key_count` from a distribution,
+    /// seeded with `seed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key_count` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bench_support::{KeyDistribution, KeyGenerator};
+    ///
+    /// let mut keys = KeyGenerator::new(KeyDistribution::Zipfian(1.0), 100, 1);
+    /// assert!(keys.next().unwrap() < 100);
+    /// ```
+    pub fn new(distribution: KeyDistribution, key_count: u64, seed: u32) -> Self {
+        assert!(key_count > 0, "Error: key count must be positive.");
+        let cumulative_probabilities = match distribution {
+            KeyDistribution::Zipfian(exponent) => {
+                let mut sum = 0.0;
+                let mut cumulative_probabilities: Vec<f64> = (0..key_count)
+                    .map(|key| {
+                        sum += 1.0 / ((key + 1) as f64).powf(exponent);
+                        sum
+                    })
+                    .collect();
+                for probability in &mut cumulative_probabilities {
+                    *probability /= sum;
+                }
+                cumulative_probabilities
+            }
+            _ => Vec::new(),
+        };
+        KeyGenerator {
+            distribution,
+            key_count,
+            rng: XorShiftRng::from_seed([seed, seed ^ 0x9E37_79B9, 1, 1]),
+            next_key: 0,
+            cumulative_probabilities,
+        }
+    }
+}
+
+impl Iterator for KeyGenerator {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let key = match self.distribution {
+            KeyDistribution::Uniform => self.rng.gen_range(0, self.key_count),
+            KeyDistribution::Zipfian(_) => {
+                let probability = self.rng.gen::<f64>();
+                let index = match self.cumulative_probabilities.binary_search_by(|curr| {
+                    curr.partial_cmp(&probability).expect("Expected number.")
+                }) {
+                    Ok(index) | Err(index) => index,
+                };
+                (index as u64).min(self.key_count - 1)
+            }
+            KeyDistribution::Sequential => {
+                let key = self.next_key;
+                self.next_key = (self.next_key + 1) % self.key_count;
+                key
+            }
+        };
+        Some(key)
+    }
+}
+
+/// An operation of a workload.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Op {
+    /// Inserts a key-value pair.
+    Insert(u64, u64),
+    /// Looks up a key.
+    Get(u64),
+    /// Scans at most the given number of entries in ascending order, starting at the smallest key
+    /// greater than or equal to the given key.
+    Range(u64, usize),
+}
+
+/// The distribution of keys and the mix of operations of a benchmark.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::bench_support::{KeyDistribution, Op, Workload};
+///
+/// let workload = Workload::new(KeyDistribution::Sequential, 100, 1, 0, 0, 0);
+/// assert_eq!(workload.generate(2, 1)[0], Op::Insert(0, 0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Workload {
+    distribution: KeyDistribution,
+    key_count: u64,
+    insert_weight: u32,
+    get_weight: u32,
+    range_weight: u32,
+    range_len: usize,
+}
+
+impl Workload {
+    /// Constructs a new `Workload` whose keys are drawn from `distribution` over `0..key_count`.
+    /// Operations are inserts, gets, and range scans of `range_len` entries in proportion to their
+    /// weights.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key_count` is zero or if all the weights are zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bench_support::{KeyDistribution, Workload};
+    ///
+    /// let workload = Workload::new(KeyDistribution::Uniform, 1000, 50, 50, 0, 0);
+    /// ```
+    pub fn new(
+        distribution: KeyDistribution,
+        key_count: u64,
+        insert_weight: u32,
+        get_weight: u32,
+        range_weight: u32,
+        range_len: usize,
+    ) -> Self {
+        assert!(key_count > 0, "Error: key count must be positive.");
+        assert!(
+            insert_weight + get_weight + range_weight > 0,
+            "Error: at least one weight must be positive.",
+        );
+        Workload {
+            distribution,
+            key_count,
+            insert_weight,
+            get_weight,
+            range_weight,
+            range_len,
+        }
+    }
+
+    /// Generates `len` operations of the workload, seeded with `seed`. The same seed always
+    /// generates the same operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bench_support::{KeyDistribution, Op, Workload};
+    ///
+    /// let workload = Workload::new(KeyDistribution::Uniform, 1000, 0, 1, 0, 0);
+    /// let ops = workload.generate(100, 1);
+    /// assert_eq!(ops.len(), 100);
+    /// assert_eq!(ops, workload.generate(100, 1));
+    /// assert!(ops.iter().all(|op| match op {
+    ///     Op::Get(key) => *key < 1000,
+    ///     _ => false,
+    /// }));
+    /// ```
+    pub fn generate(&self, len: usize, seed: u32) -> Vec<Op> {
+        let mut keys = KeyGenerator::new(self.distribution, self.key_count, seed);
+        let mut rng = XorShiftRng::from_seed([seed ^ 0x9E37_79B9, seed, 1, 1]);
+        let total_weight = self.insert_weight + self.get_weight + self.range_weight;
+        (0..len)
+            .map(|_| {
+                let key = keys.next().expect("Expected key.");
+                let weight = rng.gen_range(0, total_weight);
+                if weight < self.insert_weight {
+                    Op::Insert(key, key)
+                } else if weight < self.insert_weight + self.get_weight {
+                    Op::Get(key)
+                } else {
+                    Op::Range(key, self.range_len)
+                }
+            })
+            .collect()
+    }
+}
+
+/// An ordered map with `u64` keys and values that workloads can be run against.
+///
+/// The disk-resident maps panic if an operation fails.
+pub trait WorkloadMap {
+    /// Inserts a key-value pair into the map.
+    fn insert(&mut self, key: u64, value: u64);
+
+    /// Returns the value associated with a key.
+    fn get(&mut self, key: u64) -> Option<u64>;
+
+    /// Scans at most `len` entries in ascending order, starting at the smallest key greater than
+    /// or equal to `start`, and returns the sum of their values.
+    fn range(&mut self, start: u64, len: usize) -> u64;
+}
+
+/// Runs operations against a map and returns a checksum of their results, which is the same for
+/// every map that the operations are run against.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::bench_support::{self, Op};
+/// use extended_collections::treap::TreapMap;
+///
+/// let mut map = TreapMap::new();
+/// let ops = [Op::Insert(1, 2), Op::Insert(3, 4), Op::Get(1), Op::Range(0, 2)];
+/// assert_eq!(bench_support::run(&mut map, &ops), 8);
+/// ```
+pub fn run<M>(map: &mut M, ops: &[Op]) -> u64
+where
+    M: WorkloadMap + ?Sized,
+{
+    ops.iter().fold(0, |checksum, op| match *op {
+        Op::Insert(key, value) => {
+            map.insert(key, value);
+            checksum
+        }
+        Op::Get(key) => checksum.wrapping_add(map.get(key).unwrap_or(0)),
+        Op::Range(start, len) => checksum.wrapping_add(map.range(start, len)),
+    })
+}
+
+impl WorkloadMap for BTreeMap<u64, u64> {
+    fn insert(&mut self, key: u64, value: u64) {
+        BTreeMap::insert(self, key, value);
+    }
+
+    fn get(&mut self, key: u64) -> Option<u64> {
+        BTreeMap::get(self, &key).cloned()
+    }
+
+    fn range(&mut self, start: u64, len: usize) -> u64 {
+        BTreeMap::range(self, start..)
+            .take(len)
+            .fold(0, |sum, (_, value)| sum.wrapping_add(*value))
+    }
+}
+
+// The in-memory maps do not have range iterators, so a range scan repeatedly finds the successor
+// of the previous key.
+fn successor_range<F>(start: u64, len: usize, mut ceil_entry: F) -> u64
+where
+    F: FnMut(u64) -> Option<(u64, u64)>,
+{
+    let mut sum = 0u64;
+    let mut next_key = Some(start);
+    for _ in 0..len {
+        let (key, value) = match next_key.and_then(&mut ceil_entry) {
+            Some(entry) => entry,
+            None => break,
+        };
+        sum = sum.wrapping_add(value);
+        next_key = key.checked_add(1);
+    }
+    sum
+}
+
+macro_rules! impl_workload_map {
+    ($($type:ty $(, $param:ident: $bound:path)?;)*) => {
+        $(
+            impl$(<$param: $bound>)? WorkloadMap for $type {
+                fn insert(&mut self, key: u64, value: u64) {
+                    <$type>::insert(self, key, value);
+                }
+
+                fn get(&mut self, key: u64) -> Option<u64> {
+                    <$type>::get(self, &key).cloned()
+                }
+
+                fn range(&mut self, start: u64, len: usize) -> u64 {
+                    successor_range(start, len, |key| {
+                        let key = *self.ceil(&key)?;
+                        <$type>::get(self, &key).map(|value| (key, *value))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_workload_map!(
+    AvlMap<u64, u64, C>, C: Compare<u64>;
+    RedBlackMap<u64, u64>;
+    SkipMap<u64, u64, C>, C: Compare<u64>;
+    SplayMap<u64, u64>;
+    TreapMap<u64, u64, C>, C: Compare<u64>;
+);
+
+#[cfg(feature = "bp_tree")]
+impl<C> WorkloadMap for BpMap<u64, u64, C>
+where
+    C: Compare<u64>,
+{
+    fn insert(&mut self, key: u64, value: u64) {
+        BpMap::insert(self, key, value).expect("Expected insert to succeed.");
+    }
+
+    fn get(&mut self, key: u64) -> Option<u64> {
+        BpMap::get(self, &key).expect("Expected get to succeed.")
+    }
+
+    // `BpMap` cannot seek to a key, so a range scan iterates from the smallest key.
+    fn range(&mut self, start: u64, len: usize) -> u64 {
+        self.iter_mut()
+            .expect("Expected iterator.")
+            .map(|entry| entry.expect("Expected entry."))
+            .skip_while(|(key, _)| *key < start)
+            .take(len)
+            .fold(0, |sum, (_, value)| sum.wrapping_add(value))
+    }
+}
+
+#[cfg(feature = "lsm")]
+impl<C> WorkloadMap for LsmMap<u64, u64, C>
+where
+    C: CompactionStrategy<u64, u64>,
+{
+    fn insert(&mut self, key: u64, value: u64) {
+        LsmMap::insert(self, key, value).expect("Expected insert to succeed.");
+    }
+
+    fn get(&mut self, key: u64) -> Option<u64> {
+        LsmMap::get(self, &key).expect("Expected get to succeed.")
+    }
+
+    fn range(&mut self, start: u64, len: usize) -> u64 {
+        self.iter_from(&start)
+            .expect("Expected iterator.")
+            .take(len)
+            .map(|entry| entry.expect("Expected entry."))
+            .fold(0, |sum, (_, value)| sum.wrapping_add(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, KeyDistribution, KeyGenerator, Op, Workload};
+    use crate::avl_tree::AvlMap;
+    use crate::red_black_tree::RedBlackMap;
+    use crate::skiplist::SkipMap;
+    use crate::splay_tree::SplayMap;
+    use crate::treap::TreapMap;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_zipfian() {
+        let mut counts = [0; 10];
+        for key in KeyGenerator::new(KeyDistribution::Zipfian(1.0), 10, 1).take(10_000) {
+            counts[key as usize] += 1;
+        }
+        // the probability of key 0 is about 0.34 and the probability of key 9 is about 0.03
+        assert!(counts[0] > 3000 && counts[0] < 3800);
+        assert!(counts[9] > 200 && counts[9] < 450);
+        assert!(counts.windows(2).all(|pair| pair[0] > pair[1]));
+    }
+
+    #[test]
+    fn test_weights() {
+        let ops = Workload::new(KeyDistribution::Uniform, 100, 1, 1, 2, 5).generate(10_000, 1);
+        let range_count = ops
+            .iter()
+            .filter(|op| match op {
+                Op::Range(_, len) => *len == 5,
+                _ => false,
+            })
+            .count();
+        assert!(range_count > 4500 && range_count < 5500);
+    }
+
+    #[test]
+    fn test_maps_agree() {
+        let workload = Workload::new(KeyDistribution::Uniform, 500, 50, 30, 20, 10);
+        let ops = workload.generate(5000, 1);
+        let expected = run(&mut BTreeMap::new(), &ops);
+        assert_eq!(run(&mut AvlMap::new(), &ops), expected);
+        assert_eq!(run(&mut RedBlackMap::new(), &ops), expected);
+        assert_eq!(run(&mut SkipMap::new(), &ops), expected);
+        assert_eq!(run(&mut SplayMap::new(), &ops), expected);
+        assert_eq!(run(&mut TreapMap::new(), &ops), expected);
+    }
+}
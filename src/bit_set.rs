@@ -0,0 +1,996 @@
+//! Sets of unsigned integers represented as bits.
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+use std::cmp;
+use std::fmt;
+use std::iter::FromIterator;
+
+const WORD_BITS: usize = 64;
+
+// Returns the index of the word that contains `value` and the mask of `value` within the word.
+fn word_position(value: usize) -> (usize, u64) {
+    (value / WORD_BITS, 1 << (value % WORD_BITS))
+}
+
+fn count_ones(words: &[u64]) -> usize {
+    words.iter().map(|word| word.count_ones() as usize).sum()
+}
+
+/// A set of unsigned integers implemented using a dense vector of bits.
+///
+/// The set stores a bit for every integer between zero and its largest member, packed into 64-bit
+/// words, so it is best suited for sets of small integers that are dense in their range. The
+/// vector grows as larger integers are inserted. Set operations combine the sets a word at a time.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::bit_set::BitSet;
+///
+/// let mut set = BitSet::new();
+/// set.insert(1);
+/// set.insert(100);
+///
+/// let mut other = BitSet::new();
+/// other.insert(100);
+/// other.insert(200);
+///
+/// set.union_with(&other);
+/// assert_eq!(set.iter().collect::<Vec<usize>>(), vec![1, 100, 200]);
+/// assert_eq!(set.rank(150), 2);
+///
+/// set.intersect_with(&other);
+/// assert_eq!(set.iter().collect::<Vec<usize>>(), vec![100, 200]);
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct BitSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    /// Constructs a new, empty `BitSet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let set = BitSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        BitSet {
+            words: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Constructs a new, empty `BitSet` with space for the integers less than `capacity` without
+    /// reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let set = BitSet::with_capacity(100);
+    /// assert!(set.capacity() >= 100);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        BitSet {
+            words: vec![0; capacity.div_ceil(WORD_BITS)],
+            len: 0,
+        }
+    }
+
+    /// Returns the number of integers that the set can hold without reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let set = BitSet::with_capacity(100);
+    /// assert_eq!(set.capacity(), 128);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.words.len() * WORD_BITS
+    }
+
+    /// Inserts an integer into the set. Returns `true` if the integer was not already in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let mut set = BitSet::new();
+    /// assert!(set.insert(3));
+    /// assert!(!set.insert(3));
+    /// ```
+    pub fn insert(&mut self, value: usize) -> bool {
+        let (index, mask) = word_position(value);
+        if index >= self.words.len() {
+            self.words.resize(index + 1, 0);
+        }
+        let is_new = self.words[index] & mask == 0;
+        if is_new {
+            self.words[index] |= mask;
+            self.len += 1;
+        }
+        is_new
+    }
+
+    /// Removes an integer from the set. Returns `true` if the integer was in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let mut set = BitSet::new();
+    /// set.insert(3);
+    /// assert!(set.remove(3));
+    /// assert!(!set.remove(3));
+    /// ```
+    pub fn remove(&mut self, value: usize) -> bool {
+        let (index, mask) = word_position(value);
+        let is_present = self.contains(value);
+        if is_present {
+            self.words[index] &= !mask;
+            self.len -= 1;
+        }
+        is_present
+    }
+
+    /// Checks if an integer exists in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let mut set = BitSet::new();
+    /// set.insert(3);
+    /// assert!(set.contains(3));
+    /// assert!(!set.contains(4));
+    /// ```
+    pub fn contains(&self, value: usize) -> bool {
+        let (index, mask) = word_position(value);
+        self.words.get(index).is_some_and(|word| word & mask != 0)
+    }
+
+    /// Returns the number of integers in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let mut set = BitSet::new();
+    /// set.insert(3);
+    /// set.insert(300);
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let set = BitSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the set, removing all integers. The capacity of the set is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let mut set = BitSet::new();
+    /// set.insert(3);
+    /// set.clear();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        for word in &mut self.words {
+            *word = 0;
+        }
+        self.len = 0;
+    }
+
+    /// Returns the number of integers in the set that are less than `value`. The time taken is
+    /// linear in `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let set: BitSet = vec![1, 5, 70].into_iter().collect();
+    /// assert_eq!(set.rank(0), 0);
+    /// assert_eq!(set.rank(5), 1);
+    /// assert_eq!(set.rank(6), 2);
+    /// assert_eq!(set.rank(1000), 3);
+    /// ```
+    pub fn rank(&self, value: usize) -> usize {
+        let (index, mask) = word_position(value);
+        if index >= self.words.len() {
+            return self.len;
+        }
+        count_ones(&self.words[..index]) + (self.words[index] & (mask - 1)).count_ones() as usize
+    }
+
+    /// Adds all integers in `other` to the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let mut set: BitSet = vec![1, 2].into_iter().collect();
+    /// let other: BitSet = vec![2, 3].into_iter().collect();
+    /// set.union_with(&other);
+    /// assert_eq!(set.iter().collect::<Vec<usize>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn union_with(&mut self, other: &BitSet) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, other_word) in self.words.iter_mut().zip(&other.words) {
+            *word |= other_word;
+        }
+        self.len = count_ones(&self.words);
+    }
+
+    /// Removes all integers that are not in `other` from the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let mut set: BitSet = vec![1, 2].into_iter().collect();
+    /// let other: BitSet = vec![2, 3].into_iter().collect();
+    /// set.intersect_with(&other);
+    /// assert_eq!(set.iter().collect::<Vec<usize>>(), vec![2]);
+    /// ```
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        let len = cmp::min(self.words.len(), other.words.len());
+        for (word, other_word) in self.words.iter_mut().zip(&other.words) {
+            *word &= other_word;
+        }
+        for word in &mut self.words[len..] {
+            *word = 0;
+        }
+        self.len = count_ones(&self.words);
+    }
+
+    /// Removes all integers that are in `other` from the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let mut set: BitSet = vec![1, 2].into_iter().collect();
+    /// let other: BitSet = vec![2, 3].into_iter().collect();
+    /// set.difference_with(&other);
+    /// assert_eq!(set.iter().collect::<Vec<usize>>(), vec![1]);
+    /// ```
+    pub fn difference_with(&mut self, other: &BitSet) {
+        for (word, other_word) in self.words.iter_mut().zip(&other.words) {
+            *word &= !other_word;
+        }
+        self.len = count_ones(&self.words);
+    }
+
+    /// Replaces the set with the integers that are in exactly one of the set and `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let mut set: BitSet = vec![1, 2].into_iter().collect();
+    /// let other: BitSet = vec![2, 3].into_iter().collect();
+    /// set.symmetric_difference_with(&other);
+    /// assert_eq!(set.iter().collect::<Vec<usize>>(), vec![1, 3]);
+    /// ```
+    pub fn symmetric_difference_with(&mut self, other: &BitSet) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, other_word) in self.words.iter_mut().zip(&other.words) {
+            *word ^= other_word;
+        }
+        self.len = count_ones(&self.words);
+    }
+
+    /// Returns `true` if every integer in the set is also in `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let set: BitSet = vec![1, 2].into_iter().collect();
+    /// let other: BitSet = vec![1, 2, 3].into_iter().collect();
+    /// assert!(set.is_subset(&other));
+    /// assert!(!other.is_subset(&set));
+    /// ```
+    pub fn is_subset(&self, other: &BitSet) -> bool {
+        self.words
+            .iter()
+            .enumerate()
+            .all(|(index, word)| word & !other.words.get(index).cloned().unwrap_or(0) == 0)
+    }
+
+    /// Returns `true` if the set and `other` have no integers in common.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let set: BitSet = vec![1, 2].into_iter().collect();
+    /// let other: BitSet = vec![3, 4].into_iter().collect();
+    /// assert!(set.is_disjoint(&other));
+    /// ```
+    pub fn is_disjoint(&self, other: &BitSet) -> bool {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .all(|(word, other_word)| word & other_word == 0)
+    }
+
+    /// Returns an iterator over the set. The iterator will yield integers in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::BitSet;
+    ///
+    /// let set: BitSet = vec![64, 3].into_iter().collect();
+    ///
+    /// let mut iterator = set.iter();
+    /// assert_eq!(iterator.next(), Some(3));
+    /// assert_eq!(iterator.next(), Some(64));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> BitSetIter<'_> {
+        BitSetIter {
+            words: self.words.iter().enumerate(),
+            offset: 0,
+            word: 0,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a BitSet {
+    type IntoIter = BitSetIter<'a>;
+    type Item = usize;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator for `BitSet`.
+///
+/// This iterator traverses the integers of the set in ascending order.
+pub struct BitSetIter<'a> {
+    words: std::iter::Enumerate<std::slice::Iter<'a, u64>>,
+    offset: usize,
+    word: u64,
+}
+
+impl<'a> Iterator for BitSetIter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.word == 0 {
+            let (index, word) = self.words.next()?;
+            self.offset = index * WORD_BITS;
+            self.word = *word;
+        }
+        let ret = self.offset + self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+        Some(ret)
+    }
+}
+
+impl Default for BitSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for BitSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for BitSet {
+    fn eq(&self, other: &BitSet) -> bool {
+        self.len == other.len && self.is_subset(other)
+    }
+}
+
+impl Eq for BitSet {}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut set = BitSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+/// A set of unsigned integers implemented using a sorted vector of non-empty 64-bit words.
+///
+/// Only the words that contain at least one member are stored alongside their index, so the space
+/// used is proportional to the number of members rather than the largest member. This makes the
+/// set suitable for sparse sets over a large range. Lookups binary search for the word containing
+/// an integer, and set operations merge the words of both sets.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::bit_set::SparseBitSet;
+///
+/// let mut set = SparseBitSet::new();
+/// set.insert(1);
+/// set.insert(1_000_000_000);
+///
+/// let mut other = SparseBitSet::new();
+/// other.insert(1_000_000_000);
+/// other.insert(2_000_000_000);
+///
+/// set.union_with(&other);
+/// assert_eq!(
+///     set.iter().collect::<Vec<usize>>(),
+///     vec![1, 1_000_000_000, 2_000_000_000],
+/// );
+/// assert_eq!(set.rank(1_500_000_000), 2);
+///
+/// set.intersect_with(&other);
+/// assert_eq!(
+///     set.iter().collect::<Vec<usize>>(),
+///     vec![1_000_000_000, 2_000_000_000],
+/// );
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct SparseBitSet {
+    // The index of each non-empty word paired with the word, sorted by index.
+    words: Vec<(usize, u64)>,
+    len: usize,
+}
+
+impl SparseBitSet {
+    /// Constructs a new, empty `SparseBitSet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::SparseBitSet;
+    ///
+    /// let set = SparseBitSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        SparseBitSet {
+            words: Vec::new(),
+            len: 0,
+        }
+    }
+
+    fn search(&self, index: usize) -> Result<usize, usize> {
+        self.words.binary_search_by_key(&index, |&(index, _)| index)
+    }
+
+    /// Inserts an integer into the set. Returns `true` if the integer was not already in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::SparseBitSet;
+    ///
+    /// let mut set = SparseBitSet::new();
+    /// assert!(set.insert(3));
+    /// assert!(!set.insert(3));
+    /// ```
+    pub fn insert(&mut self, value: usize) -> bool {
+        let (index, mask) = word_position(value);
+        let is_new = match self.search(index) {
+            Ok(pos) => {
+                let word = &mut self.words[pos].1;
+                let is_new = *word & mask == 0;
+                *word |= mask;
+                is_new
+            }
+            Err(pos) => {
+                self.words.insert(pos, (index, mask));
+                true
+            }
+        };
+        if is_new {
+            self.len += 1;
+        }
+        is_new
+    }
+
+    /// Removes an integer from the set. Returns `true` if the integer was in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::SparseBitSet;
+    ///
+    /// let mut set = SparseBitSet::new();
+    /// set.insert(3);
+    /// assert!(set.remove(3));
+    /// assert!(!set.remove(3));
+    /// ```
+    pub fn remove(&mut self, value: usize) -> bool {
+        let (index, mask) = word_position(value);
+        match self.search(index) {
+            Ok(pos) if self.words[pos].1 & mask != 0 => {
+                self.words[pos].1 &= !mask;
+                if self.words[pos].1 == 0 {
+                    self.words.remove(pos);
+                }
+                self.len -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks if an integer exists in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::SparseBitSet;
+    ///
+    /// let mut set = SparseBitSet::new();
+    /// set.insert(3);
+    /// assert!(set.contains(3));
+    /// assert!(!set.contains(4));
+    /// ```
+    pub fn contains(&self, value: usize) -> bool {
+        let (index, mask) = word_position(value);
+        match self.search(index) {
+            Ok(pos) => self.words[pos].1 & mask != 0,
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the number of integers in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::SparseBitSet;
+    ///
+    /// let mut set = SparseBitSet::new();
+    /// set.insert(3);
+    /// set.insert(300);
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::SparseBitSet;
+    ///
+    /// let set = SparseBitSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the set, removing all integers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::SparseBitSet;
+    ///
+    /// let mut set = SparseBitSet::new();
+    /// set.insert(3);
+    /// set.clear();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.words.clear();
+        self.len = 0;
+    }
+
+    /// Returns the number of integers in the set that are less than `value`. The time taken is
+    /// linear in the number of stored words that precede `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::SparseBitSet;
+    ///
+    /// let set: SparseBitSet = vec![1, 5, 70].into_iter().collect();
+    /// assert_eq!(set.rank(0), 0);
+    /// assert_eq!(set.rank(5), 1);
+    /// assert_eq!(set.rank(6), 2);
+    /// assert_eq!(set.rank(1000), 3);
+    /// ```
+    pub fn rank(&self, value: usize) -> usize {
+        let (index, mask) = word_position(value);
+        let (pos, partial) = match self.search(index) {
+            Ok(pos) => (pos, (self.words[pos].1 & (mask - 1)).count_ones() as usize),
+            Err(pos) => (pos, 0),
+        };
+        self.words[..pos]
+            .iter()
+            .map(|(_, word)| word.count_ones() as usize)
+            .sum::<usize>()
+            + partial
+    }
+
+    // Replaces the words of the set by combining the words of both sets with the same index
+    // using `f`. Words that only exist in this set are combined with zero if `keep_left` is false,
+    // and words that only exist in `other` are combined with zero if `keep_right` is false.
+    fn merge_with<F>(&mut self, other: &SparseBitSet, keep_left: bool, keep_right: bool, f: F)
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        let mut words = Vec::with_capacity(self.words.len() + other.words.len());
+        let mut left_iter = self.words.iter().peekable();
+        let mut right_iter = other.words.iter().peekable();
+        loop {
+            let (index, word) = match (left_iter.peek(), right_iter.peek()) {
+                (Some(&&(left_index, left_word)), Some(&&(right_index, right_word))) => {
+                    match left_index.cmp(&right_index) {
+                        cmp::Ordering::Less => {
+                            left_iter.next();
+                            (left_index, if keep_left { left_word } else { 0 })
+                        }
+                        cmp::Ordering::Greater => {
+                            right_iter.next();
+                            (right_index, if keep_right { right_word } else { 0 })
+                        }
+                        cmp::Ordering::Equal => {
+                            left_iter.next();
+                            right_iter.next();
+                            (left_index, f(left_word, right_word))
+                        }
+                    }
+                }
+                (Some(&&(left_index, left_word)), None) => {
+                    if !keep_left {
+                        break;
+                    }
+                    left_iter.next();
+                    (left_index, left_word)
+                }
+                (None, Some(&&(right_index, right_word))) => {
+                    if !keep_right {
+                        break;
+                    }
+                    right_iter.next();
+                    (right_index, right_word)
+                }
+                (None, None) => break,
+            };
+            if word != 0 {
+                words.push((index, word));
+            }
+        }
+        self.len = words
+            .iter()
+            .map(|(_, word)| word.count_ones() as usize)
+            .sum();
+        self.words = words;
+    }
+
+    /// Adds all integers in `other` to the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::SparseBitSet;
+    ///
+    /// let mut set: SparseBitSet = vec![1, 2].into_iter().collect();
+    /// let other: SparseBitSet = vec![2, 3].into_iter().collect();
+    /// set.union_with(&other);
+    /// assert_eq!(set.iter().collect::<Vec<usize>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn union_with(&mut self, other: &SparseBitSet) {
+        self.merge_with(other, true, true, |left, right| left | right);
+    }
+
+    /// Removes all integers that are not in `other` from the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::SparseBitSet;
+    ///
+    /// let mut set: SparseBitSet = vec![1, 2].into_iter().collect();
+    /// let other: SparseBitSet = vec![2, 3].into_iter().collect();
+    /// set.intersect_with(&other);
+    /// assert_eq!(set.iter().collect::<Vec<usize>>(), vec![2]);
+    /// ```
+    pub fn intersect_with(&mut self, other: &SparseBitSet) {
+        self.merge_with(other, false, false, |left, right| left & right);
+    }
+
+    /// Removes all integers that are in `other` from the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::SparseBitSet;
+    ///
+    /// let mut set: SparseBitSet = vec![1, 2].into_iter().collect();
+    /// let other: SparseBitSet = vec![2, 3].into_iter().collect();
+    /// set.difference_with(&other);
+    /// assert_eq!(set.iter().collect::<Vec<usize>>(), vec![1]);
+    /// ```
+    pub fn difference_with(&mut self, other: &SparseBitSet) {
+        self.merge_with(other, true, false, |left, right| left & !right);
+    }
+
+    /// Replaces the set with the integers that are in exactly one of the set and `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::SparseBitSet;
+    ///
+    /// let mut set: SparseBitSet = vec![1, 2].into_iter().collect();
+    /// let other: SparseBitSet = vec![2, 3].into_iter().collect();
+    /// set.symmetric_difference_with(&other);
+    /// assert_eq!(set.iter().collect::<Vec<usize>>(), vec![1, 3]);
+    /// ```
+    pub fn symmetric_difference_with(&mut self, other: &SparseBitSet) {
+        self.merge_with(other, true, true, |left, right| left ^ right);
+    }
+
+    /// Returns an iterator over the set. The iterator will yield integers in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_set::SparseBitSet;
+    ///
+    /// let set: SparseBitSet = vec![1 << 40, 3].into_iter().collect();
+    ///
+    /// let mut iterator = set.iter();
+    /// assert_eq!(iterator.next(), Some(3));
+    /// assert_eq!(iterator.next(), Some(1 << 40));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> SparseBitSetIter<'_> {
+        SparseBitSetIter {
+            words: self.words.iter(),
+            offset: 0,
+            word: 0,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a SparseBitSet {
+    type IntoIter = SparseBitSetIter<'a>;
+    type Item = usize;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator for `SparseBitSet`.
+///
+/// This iterator traverses the integers of the set in ascending order.
+pub struct SparseBitSetIter<'a> {
+    words: std::slice::Iter<'a, (usize, u64)>,
+    offset: usize,
+    word: u64,
+}
+
+impl<'a> Iterator for SparseBitSetIter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.word == 0 {
+            let &(index, word) = self.words.next()?;
+            self.offset = index * WORD_BITS;
+            self.word = word;
+        }
+        let ret = self.offset + self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+        Some(ret)
+    }
+}
+
+impl Default for SparseBitSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for SparseBitSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for SparseBitSet {
+    fn eq(&self, other: &SparseBitSet) -> bool {
+        self.words == other.words
+    }
+}
+
+impl Eq for SparseBitSet {}
+
+impl FromIterator<usize> for SparseBitSet {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut set = SparseBitSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<usize> for SparseBitSet {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitSet, SparseBitSet};
+    use rand::{Rng, SeedableRng, XorShiftRng};
+    use std::collections::BTreeSet;
+
+    fn random_values(rng: &mut XorShiftRng, len: usize, max: usize) -> BTreeSet<usize> {
+        (0..len).map(|_| rng.gen_range(0, max)).collect()
+    }
+
+    #[test]
+    fn test_bit_set_insert_remove() {
+        let mut set = BitSet::new();
+        assert!(set.insert(0));
+        assert!(set.insert(63));
+        assert!(set.insert(64));
+        assert!(!set.insert(64));
+        assert_eq!(set.len(), 3);
+        assert!(set.remove(63));
+        assert!(!set.remove(63));
+        assert!(!set.remove(1000));
+        assert_eq!(set.iter().collect::<Vec<usize>>(), vec![0, 64]);
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_bit_set_eq() {
+        let mut set = BitSet::with_capacity(1000);
+        set.insert(5);
+        let other: BitSet = vec![5].into_iter().collect();
+        assert_eq!(set, other);
+        set.insert(500);
+        assert_ne!(set, other);
+    }
+
+    #[test]
+    fn test_sparse_bit_set_insert_remove() {
+        let mut set = SparseBitSet::new();
+        assert!(set.insert(0));
+        assert!(set.insert(1 << 50));
+        assert!(!set.insert(1 << 50));
+        assert_eq!(set.len(), 2);
+        assert!(set.remove(1 << 50));
+        assert!(!set.remove(1 << 50));
+        assert_eq!(set.words.len(), 1);
+        assert_eq!(set.iter().collect::<Vec<usize>>(), vec![0]);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng: XorShiftRng = SeedableRng::from_seed([1, 1, 1, 1]);
+        for _ in 0..50 {
+            let left = random_values(&mut rng, 300, 2000);
+            let right = random_values(&mut rng, 300, 2000);
+            let left_dense: BitSet = left.iter().cloned().collect();
+            let right_dense: BitSet = right.iter().cloned().collect();
+            let left_sparse: SparseBitSet = left.iter().cloned().collect();
+            let right_sparse: SparseBitSet = right.iter().cloned().collect();
+
+            for value in 0..2100 {
+                let rank = left.range(..value).count();
+                assert_eq!(left_dense.rank(value), rank);
+                assert_eq!(left_sparse.rank(value), rank);
+            }
+
+            let expected: [Vec<usize>; 4] = [
+                left.union(&right).cloned().collect(),
+                left.intersection(&right).cloned().collect(),
+                left.difference(&right).cloned().collect(),
+                left.symmetric_difference(&right).cloned().collect(),
+            ];
+            for (op, expected) in expected.iter().enumerate() {
+                let mut dense = left_dense.clone();
+                let mut sparse = left_sparse.clone();
+                match op {
+                    0 => {
+                        dense.union_with(&right_dense);
+                        sparse.union_with(&right_sparse);
+                    }
+                    1 => {
+                        dense.intersect_with(&right_dense);
+                        sparse.intersect_with(&right_sparse);
+                    }
+                    2 => {
+                        dense.difference_with(&right_dense);
+                        sparse.difference_with(&right_sparse);
+                    }
+                    _ => {
+                        dense.symmetric_difference_with(&right_dense);
+                        sparse.symmetric_difference_with(&right_sparse);
+                    }
+                }
+                assert_eq!(&dense.iter().collect::<Vec<usize>>(), expected);
+                assert_eq!(dense.len(), expected.len());
+                assert_eq!(&sparse.iter().collect::<Vec<usize>>(), expected);
+                assert_eq!(sparse.len(), expected.len());
+            }
+
+            assert_eq!(
+                left_dense.is_disjoint(&right_dense),
+                left.is_disjoint(&right)
+            );
+            assert_eq!(left_dense.is_subset(&right_dense), left.is_subset(&right));
+        }
+    }
+}
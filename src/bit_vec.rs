@@ -0,0 +1,585 @@
+//! Growable bit vector that supports rank and select queries.
+
+use crate::fenwick_tree::FenwickTree;
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+use std::iter::FromIterator;
+
+const WORD_BITS: usize = 64;
+const BLOCK_WORDS: usize = 8;
+const BLOCK_BITS: usize = WORD_BITS * BLOCK_WORDS;
+
+// Returns the position of the `k`-th (zero-indexed) set bit of `word`.
+fn select_in_word(mut word: u64, k: usize) -> usize {
+    for _ in 0..k {
+        word &= word - 1;
+    }
+    word.trailing_zeros() as usize
+}
+
+/// A growable vector of bits that supports rank and select queries.
+///
+/// The bits are packed into 64-bit words. In addition to the bits, the vector maintains an
+/// auxiliary index that stores the number of ones in every block of 512 bits in a fenwick tree.
+/// Setting a bit updates the index in logarithmic time. Rank queries sum the counts of the blocks
+/// preceding a position using the index and count the remaining ones in at most eight words.
+/// Select queries search the index for the block that contains the requested bit and scan the
+/// words of that block.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::bit_vec::BitVec;
+///
+/// let mut bit_vec = BitVec::new(8);
+/// bit_vec.set(1, true);
+/// bit_vec.set(4, true);
+/// bit_vec.set(6, true);
+///
+/// assert_eq!(bit_vec.get(4), Some(true));
+/// assert_eq!(bit_vec.count_ones(), 3);
+///
+/// assert_eq!(bit_vec.rank1(5), 2);
+/// assert_eq!(bit_vec.rank0(5), 3);
+/// assert_eq!(bit_vec.select1(2), Some(6));
+/// assert_eq!(bit_vec.select0(2), Some(3));
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+    block_ones: FenwickTree<isize>,
+}
+
+impl BitVec {
+    /// Constructs a new `BitVec` with `len` bits that are all unset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let bit_vec = BitVec::new(10);
+    /// assert_eq!(bit_vec.len(), 10);
+    /// assert_eq!(bit_vec.count_ones(), 0);
+    /// ```
+    pub fn new(len: usize) -> Self {
+        Self::from_elem(len, false)
+    }
+
+    /// Constructs a new `BitVec` with `len` bits that are all equal to `bit`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let bit_vec = BitVec::from_elem(10, true);
+    /// assert_eq!(bit_vec.len(), 10);
+    /// assert_eq!(bit_vec.count_ones(), 10);
+    /// ```
+    pub fn from_elem(len: usize, bit: bool) -> Self {
+        let word: u64 = if bit { !0 } else { 0 };
+        let mut words = vec![word; len.div_ceil(WORD_BITS)];
+        if !len.is_multiple_of(WORD_BITS) {
+            if let Some(last) = words.last_mut() {
+                *last &= (1 << (len % WORD_BITS)) - 1;
+            }
+        }
+        Self::from_words(len, words).expect("Expected valid words.")
+    }
+
+    // Constructs a `BitVec` with `len` bits from the words that store them. Returns `None` if the
+    // number of words does not match `len` or if a bit past `len` is set.
+    pub(crate) fn from_words(len: usize, words: Vec<u64>) -> Option<Self> {
+        if words.len() != len.div_ceil(WORD_BITS) {
+            return None;
+        }
+        if !len.is_multiple_of(WORD_BITS) && words[words.len() - 1] >> (len % WORD_BITS) != 0 {
+            return None;
+        }
+        let block_ones = words
+            .chunks(BLOCK_WORDS)
+            .map(|block| block.iter().map(|word| word.count_ones() as isize).sum())
+            .collect::<Vec<isize>>();
+        Some(BitVec {
+            words,
+            len,
+            block_ones: FenwickTree::from(block_ones),
+        })
+    }
+
+    #[cfg(feature = "probabilistic")]
+    pub(crate) fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Returns the bit at a particular index, or `None` if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let mut bit_vec = BitVec::new(2);
+    /// bit_vec.set(0, true);
+    /// assert_eq!(bit_vec.get(0), Some(true));
+    /// assert_eq!(bit_vec.get(1), Some(false));
+    /// assert_eq!(bit_vec.get(2), None);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        Some(self.words[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0)
+    }
+
+    /// Sets the bit at a particular index to `bit`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let mut bit_vec = BitVec::new(2);
+    /// bit_vec.set(1, true);
+    /// assert_eq!(bit_vec.get(1), Some(true));
+    /// ```
+    pub fn set(&mut self, index: usize, bit: bool) {
+        assert!(index < self.len, "Error: index out of bounds.");
+        let word = &mut self.words[index / WORD_BITS];
+        let mask = 1 << (index % WORD_BITS);
+        if (*word & mask != 0) == bit {
+            return;
+        }
+        if bit {
+            *word |= mask;
+            self.block_ones.add(index / BLOCK_BITS, 1);
+        } else {
+            *word &= !mask;
+            self.block_ones.add(index / BLOCK_BITS, -1);
+        }
+    }
+
+    /// Appends a bit to the back of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let mut bit_vec = BitVec::new(0);
+    /// bit_vec.push(true);
+    /// assert_eq!(bit_vec.len(), 1);
+    /// assert_eq!(bit_vec.get(0), Some(true));
+    /// ```
+    pub fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(WORD_BITS) {
+            self.words.push(0);
+        }
+        if self.len.is_multiple_of(BLOCK_BITS) {
+            self.block_ones.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, bit);
+    }
+
+    /// Removes the last bit of the vector and returns it, or `None` if the vector is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let mut bit_vec = BitVec::new(0);
+    /// bit_vec.push(true);
+    /// assert_eq!(bit_vec.pop(), Some(true));
+    /// assert_eq!(bit_vec.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<bool> {
+        let bit = self.get(self.len.checked_sub(1)?)?;
+        self.set(self.len - 1, false);
+        self.len -= 1;
+        if self.len.is_multiple_of(WORD_BITS) {
+            self.words.pop();
+        }
+        if self.len.is_multiple_of(BLOCK_BITS) {
+            self.block_ones.pop();
+        }
+        Some(bit)
+    }
+
+    /// Returns the number of bits in the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let bit_vec = BitVec::new(10);
+    /// assert_eq!(bit_vec.len(), 10);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the vector is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let bit_vec = BitVec::new(0);
+    /// assert!(bit_vec.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of set bits in the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let bit_vec = BitVec::from_elem(10, true);
+    /// assert_eq!(bit_vec.count_ones(), 10);
+    /// ```
+    pub fn count_ones(&self) -> usize {
+        self.block_ones.prefix_sum(self.block_ones.len()) as usize
+    }
+
+    /// Returns the number of unset bits in the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let bit_vec = BitVec::new(10);
+    /// assert_eq!(bit_vec.count_zeros(), 10);
+    /// ```
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// Returns the number of set bits before a particular index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is greater than the length of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let bit_vec: BitVec = vec![true, false, true].into_iter().collect();
+    /// assert_eq!(bit_vec.rank1(0), 0);
+    /// assert_eq!(bit_vec.rank1(2), 1);
+    /// assert_eq!(bit_vec.rank1(3), 2);
+    /// ```
+    pub fn rank1(&self, index: usize) -> usize {
+        assert!(index <= self.len, "Error: index out of bounds.");
+        let block = index / BLOCK_BITS;
+        let word = index / WORD_BITS;
+        let mut ret = self.block_ones.prefix_sum(block) as usize;
+        ret += self.words[block * BLOCK_WORDS..word]
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum::<usize>();
+        if !index.is_multiple_of(WORD_BITS) {
+            ret += (self.words[word] & ((1 << (index % WORD_BITS)) - 1)).count_ones() as usize;
+        }
+        ret
+    }
+
+    /// Returns the number of unset bits before a particular index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is greater than the length of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let bit_vec: BitVec = vec![true, false, true].into_iter().collect();
+    /// assert_eq!(bit_vec.rank0(0), 0);
+    /// assert_eq!(bit_vec.rank0(2), 1);
+    /// assert_eq!(bit_vec.rank0(3), 1);
+    /// ```
+    pub fn rank0(&self, index: usize) -> usize {
+        index - self.rank1(index)
+    }
+
+    /// Returns the index of the `k`-th (zero-indexed) set bit, or `None` if there are at most `k`
+    /// set bits in the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let bit_vec: BitVec = vec![true, false, true].into_iter().collect();
+    /// assert_eq!(bit_vec.select1(0), Some(0));
+    /// assert_eq!(bit_vec.select1(1), Some(2));
+    /// assert_eq!(bit_vec.select1(2), None);
+    /// ```
+    pub fn select1(&self, k: usize) -> Option<usize> {
+        if k >= self.count_ones() {
+            return None;
+        }
+        let block = self.block_ones.lower_bound(&(k as isize + 1));
+        let mut remaining = k - self.block_ones.prefix_sum(block) as usize;
+        for (index, word) in self.words.iter().enumerate().skip(block * BLOCK_WORDS) {
+            let ones = word.count_ones() as usize;
+            if remaining < ones {
+                return Some(index * WORD_BITS + select_in_word(*word, remaining));
+            }
+            remaining -= ones;
+        }
+        unreachable!()
+    }
+
+    /// Returns the index of the `k`-th (zero-indexed) unset bit, or `None` if there are at most
+    /// `k` unset bits in the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let bit_vec: BitVec = vec![false, true, false].into_iter().collect();
+    /// assert_eq!(bit_vec.select0(0), Some(0));
+    /// assert_eq!(bit_vec.select0(1), Some(2));
+    /// assert_eq!(bit_vec.select0(2), None);
+    /// ```
+    pub fn select0(&self, k: usize) -> Option<usize> {
+        if k >= self.count_zeros() {
+            return None;
+        }
+        // The number of unset bits before a block is monotonic in the block, so the block that
+        // contains the requested bit is found by binary searching over the blocks.
+        let zeros_before =
+            |block: usize| block * BLOCK_BITS - self.block_ones.prefix_sum(block) as usize;
+        let mut lo = 0;
+        let mut hi = self.block_ones.len();
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if zeros_before(mid) <= k {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let mut remaining = k - zeros_before(lo);
+        for (index, word) in self.words.iter().enumerate().skip(lo * BLOCK_WORDS) {
+            let zeros = word.count_zeros() as usize;
+            if remaining < zeros {
+                return Some(index * WORD_BITS + select_in_word(!word, remaining));
+            }
+            remaining -= zeros;
+        }
+        unreachable!()
+    }
+
+    /// Returns an iterator over the bits of the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bit_vec::BitVec;
+    ///
+    /// let bit_vec: BitVec = vec![true, false].into_iter().collect();
+    ///
+    /// let mut iterator = bit_vec.iter();
+    /// assert_eq!(iterator.next(), Some(true));
+    /// assert_eq!(iterator.next(), Some(false));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> BitVecIter<'_> {
+        BitVecIter {
+            bit_vec: self,
+            index: 0,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a BitVec {
+    type IntoIter = BitVecIter<'a>;
+    type Item = bool;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator for `BitVec`.
+///
+/// This iterator traverses the bits of the vector in order.
+pub struct BitVecIter<'a> {
+    bit_vec: &'a BitVec,
+    index: usize,
+}
+
+impl<'a> Iterator for BitVecIter<'a> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ret = self.bit_vec.get(self.index);
+        if ret.is_some() {
+            self.index += 1;
+        }
+        ret
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.bit_vec.len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        let mut bit_vec = BitVec::new(0);
+        bit_vec.extend(iter);
+        bit_vec
+    }
+}
+
+impl Extend<bool> for BitVec {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        for bit in iter {
+            self.push(bit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitVec;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    fn random_bits(len: usize) -> Vec<bool> {
+        let mut rng: XorShiftRng = SeedableRng::from_seed([1, 1, 1, 1]);
+        (0..len).map(|_| rng.gen_weighted_bool(3)).collect()
+    }
+
+    #[test]
+    fn test_len_empty() {
+        let bit_vec = BitVec::new(0);
+        assert_eq!(bit_vec.len(), 0);
+        assert!(bit_vec.is_empty());
+        assert_eq!(bit_vec.rank1(0), 0);
+        assert_eq!(bit_vec.select1(0), None);
+        assert_eq!(bit_vec.select0(0), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_out_of_bounds() {
+        let mut bit_vec = BitVec::new(64);
+        bit_vec.set(64, true);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rank_out_of_bounds() {
+        let bit_vec = BitVec::new(64);
+        bit_vec.rank1(65);
+    }
+
+    #[test]
+    fn test_from_elem() {
+        let bit_vec = BitVec::from_elem(1000, true);
+        assert_eq!(bit_vec.count_ones(), 1000);
+        assert_eq!(bit_vec.count_zeros(), 0);
+        assert_eq!(bit_vec.rank1(1000), 1000);
+        assert_eq!(bit_vec.select1(999), Some(999));
+        assert_eq!(bit_vec.select1(1000), None);
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let bits = random_bits(2000);
+        let mut bit_vec = BitVec::new(0);
+        for (index, bit) in bits.iter().enumerate() {
+            bit_vec.push(*bit);
+            assert_eq!(bit_vec.len(), index + 1);
+            assert_eq!(
+                bit_vec.count_ones(),
+                bits[..=index].iter().filter(|bit| **bit).count()
+            );
+        }
+        for index in (0..bits.len()).rev() {
+            assert_eq!(bit_vec.pop(), Some(bits[index]));
+            assert_eq!(
+                bit_vec.count_ones(),
+                bits[..index].iter().filter(|bit| **bit).count()
+            );
+        }
+        assert_eq!(bit_vec.pop(), None);
+    }
+
+    #[test]
+    fn test_set() {
+        let mut bit_vec = BitVec::new(1000);
+        bit_vec.set(10, true);
+        bit_vec.set(600, true);
+        bit_vec.set(10, true);
+        assert_eq!(bit_vec.count_ones(), 2);
+        bit_vec.set(10, false);
+        assert_eq!(bit_vec.count_ones(), 1);
+        assert_eq!(bit_vec.select1(0), Some(600));
+    }
+
+    #[test]
+    fn test_rank_select() {
+        let bits = random_bits(5000);
+        let bit_vec: BitVec = bits.iter().cloned().collect();
+
+        let mut ones = Vec::new();
+        let mut zeros = Vec::new();
+        for (index, bit) in bits.iter().enumerate() {
+            assert_eq!(bit_vec.rank1(index), ones.len());
+            assert_eq!(bit_vec.rank0(index), zeros.len());
+            if *bit {
+                ones.push(index);
+            } else {
+                zeros.push(index);
+            }
+        }
+        assert_eq!(bit_vec.rank1(bits.len()), ones.len());
+
+        for (k, index) in ones.iter().enumerate() {
+            assert_eq!(bit_vec.select1(k), Some(*index));
+        }
+        assert_eq!(bit_vec.select1(ones.len()), None);
+        for (k, index) in zeros.iter().enumerate() {
+            assert_eq!(bit_vec.select0(k), Some(*index));
+        }
+        assert_eq!(bit_vec.select0(zeros.len()), None);
+    }
+
+    #[test]
+    fn test_iter() {
+        let bits = random_bits(100);
+        let bit_vec: BitVec = bits.iter().cloned().collect();
+        assert_eq!(bit_vec.iter().collect::<Vec<bool>>(), bits);
+    }
+}
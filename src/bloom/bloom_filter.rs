@@ -0,0 +1,523 @@
+use crate::bit_vec::BitVec;
+use crate::bloom::{format, hash, Error, Result, SipHashBuilder};
+use serde_derive::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::io;
+use std::marker::PhantomData;
+
+/// A space-efficient probabilistic data structure to test for membership in a set.
+///
+/// A bloom filter is a bit vector and a set of hash functions. Inserting an item sets the bits at
+/// the positions given by each hash function, and an item is reported to be in the filter if all
+/// of its bits are set. A bloom filter never has false negatives, but may have false positives.
+/// The hash functions are derived from the hashers built by a `BuildHasher`, which defaults to a
+/// `SipHashBuilder` with random keys.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::bloom::BloomFilter;
+///
+/// let mut filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+///
+/// assert!(!filter.contains("foo"));
+/// filter.insert("foo");
+/// assert!(filter.contains("foo"));
+///
+/// filter.clear();
+/// assert!(!filter.contains("foo"));
+///
+/// assert_eq!(filter.bit_count(), 96);
+/// assert_eq!(filter.hasher_count(), 7);
+/// ```
+#[derive(Clone, Deserialize, Serialize)]
+pub struct BloomFilter<T, S = SipHashBuilder> {
+    bit_vec: BitVec,
+    hasher_count: usize,
+    hash_builder: S,
+    #[serde(skip)]
+    _marker: PhantomData<T>,
+}
+
+impl<T> BloomFilter<T> {
+    /// Constructs a new, empty `BloomFilter<T>` with an estimated maximum of `item_count` items
+    /// and a desired false positive probability of `fpp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// ```
+    pub fn new(item_count: usize, fpp: f64) -> Self {
+        Self::from_bit_count(
+            hash::optimal_slot_count(item_count, fpp),
+            hash::optimal_hasher_count(fpp),
+        )
+    }
+
+    /// Constructs a new, empty `BloomFilter<T>` with `bit_count` bits and `hasher_count` hash
+    /// functions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_count` or `hasher_count` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::from_bit_count(100, 4);
+    /// assert_eq!(filter.bit_count(), 100);
+    /// assert_eq!(filter.hasher_count(), 4);
+    /// ```
+    pub fn from_bit_count(bit_count: usize, hasher_count: usize) -> Self {
+        Self::from_bit_vec(BitVec::new(bit_count), hasher_count, SipHashBuilder::new())
+    }
+
+    /// Constructs a new, empty `BloomFilter<T>` with an estimated maximum of `item_count` items
+    /// and a desired false positive probability of `fpp` whose hash functions are derived from
+    /// `seed`. Filters constructed with the same seed hash every item identically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::with_seed(10, 0.01, 0);
+    /// let other: BloomFilter<String> = BloomFilter::with_seed(10, 0.01, 0);
+    /// assert_eq!(filter.hasher(), other.hasher());
+    /// ```
+    pub fn with_seed(item_count: usize, fpp: f64, seed: u64) -> Self {
+        Self::with_hasher(item_count, fpp, SipHashBuilder::with_seed(seed))
+    }
+}
+
+impl<T, S> BloomFilter<T, S>
+where
+    S: BuildHasher,
+{
+    /// Constructs a new, empty `BloomFilter<T, S>` with an estimated maximum of `item_count` items
+    /// and a desired false positive probability of `fpp` that uses `hash_builder` to hash items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let filter: BloomFilter<String, RandomState> =
+    ///     BloomFilter::with_hasher(10, 0.01, RandomState::new());
+    /// ```
+    pub fn with_hasher(item_count: usize, fpp: f64, hash_builder: S) -> Self {
+        Self::from_bit_vec(
+            BitVec::new(hash::optimal_slot_count(item_count, fpp)),
+            hash::optimal_hasher_count(fpp),
+            hash_builder,
+        )
+    }
+
+    pub(crate) fn from_bit_vec(bit_vec: BitVec, hasher_count: usize, hash_builder: S) -> Self {
+        assert!(!bit_vec.is_empty(), "Error: bit count must be positive.");
+        assert!(hasher_count > 0, "Error: hasher count must be positive.");
+        BloomFilter {
+            bit_vec,
+            hasher_count,
+            hash_builder,
+            _marker: PhantomData,
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    pub(crate) fn into_parts(self) -> (BitVec, usize, S) {
+        (self.bit_vec, self.hasher_count, self.hash_builder)
+    }
+
+    /// Inserts an item into the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    ///
+    /// let mut filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn insert<U>(&mut self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        for slot in hash::slots(
+            &self.hash_builder,
+            item,
+            self.hasher_count,
+            self.bit_vec.len(),
+        ) {
+            self.bit_vec.set(slot, true);
+        }
+    }
+
+    /// Checks if an item is possibly in the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    ///
+    /// let mut filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// assert!(!filter.contains("foo"));
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        hash::slots(
+            &self.hash_builder,
+            item,
+            self.hasher_count,
+            self.bit_vec.len(),
+        )
+        .all(|slot| self.bit_vec.get(slot) == Some(true))
+    }
+
+    /// Inserts multiple items into the filter. The items are hashed in batches and the bits of
+    /// each batch are set in ascending order, which is faster than inserting the items one at a
+    /// time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    ///
+    /// let mut filter: BloomFilter<u32> = BloomFilter::new(100, 0.01);
+    /// filter.insert_all(&[1, 2, 3]);
+    /// assert!(filter.contains(&1));
+    /// assert!(filter.contains(&3));
+    /// ```
+    pub fn insert_all<'a, I, U>(&mut self, items: I)
+    where
+        T: Borrow<U>,
+        I: IntoIterator<Item = &'a U>,
+        U: Hash + ?Sized + 'a,
+    {
+        let mut items = items.into_iter();
+        let mut slots = Vec::with_capacity(hash::BATCH_SIZE * self.hasher_count);
+        loop {
+            slots.clear();
+            for item in items.by_ref().take(hash::BATCH_SIZE) {
+                slots.extend(hash::slots(
+                    &self.hash_builder,
+                    item,
+                    self.hasher_count,
+                    self.bit_vec.len(),
+                ));
+            }
+            if slots.is_empty() {
+                break;
+            }
+            slots.sort_unstable();
+            for slot in &slots {
+                self.bit_vec.set(*slot, true);
+            }
+        }
+    }
+
+    /// Checks if multiple items are possibly in the filter. Returns whether each item is possibly
+    /// in the filter in the order of the items. The items are hashed in batches and the bits of
+    /// each batch are probed in ascending order, which is faster than checking the items one at a
+    /// time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    ///
+    /// let mut filter: BloomFilter<u32> = BloomFilter::new(100, 0.01);
+    /// filter.insert(&1);
+    /// filter.insert(&3);
+    /// assert_eq!(filter.contains_all(&[1, 2, 3]), vec![true, false, true]);
+    /// ```
+    pub fn contains_all<'a, I, U>(&self, items: I) -> Vec<bool>
+    where
+        T: Borrow<U>,
+        I: IntoIterator<Item = &'a U>,
+        U: Hash + ?Sized + 'a,
+    {
+        let mut items = items.into_iter();
+        let mut ret = Vec::new();
+        let mut probes = Vec::with_capacity(hash::BATCH_SIZE * self.hasher_count);
+        loop {
+            probes.clear();
+            for item in items.by_ref().take(hash::BATCH_SIZE) {
+                let position = ret.len();
+                ret.push(true);
+                probes.extend(
+                    hash::slots(
+                        &self.hash_builder,
+                        item,
+                        self.hasher_count,
+                        self.bit_vec.len(),
+                    )
+                    .map(|slot| (slot, position)),
+                );
+            }
+            if probes.is_empty() {
+                break;
+            }
+            probes.sort_unstable();
+            for (slot, position) in &probes {
+                if self.bit_vec.get(*slot) != Some(true) {
+                    ret[*position] = false;
+                }
+            }
+        }
+        ret
+    }
+
+    /// Clears all items from the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    ///
+    /// let mut filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// filter.insert("foo");
+    /// filter.clear();
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn clear(&mut self) {
+        self.bit_vec = BitVec::new(self.bit_vec.len());
+    }
+
+    /// Returns the number of bits in the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// assert_eq!(filter.bit_count(), 96);
+    /// ```
+    pub fn bit_count(&self) -> usize {
+        self.bit_vec.len()
+    }
+
+    /// Returns the number of hash functions used by the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// assert_eq!(filter.hasher_count(), 7);
+    /// ```
+    pub fn hasher_count(&self) -> usize {
+        self.hasher_count
+    }
+
+    /// Returns a reference to the `BuildHasher` used by the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::{BloomFilter, SipHashBuilder};
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::with_seed(10, 0.01, 0);
+    /// assert_eq!(filter.hasher(), &SipHashBuilder::with_seed(0));
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns the estimated false positive probability of the filter based on the fraction of
+    /// bits that are set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    ///
+    /// let mut filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// assert_eq!(filter.estimate_fpp(), 0.0);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimate_fpp() > 0.0 && filter.estimate_fpp() < 0.01);
+    /// ```
+    pub fn estimate_fpp(&self) -> f64 {
+        hash::estimate_fpp(
+            self.bit_vec.count_ones(),
+            self.bit_vec.len(),
+            self.hasher_count,
+        )
+    }
+}
+
+impl<T> BloomFilter<T> {
+    /// Returns the filter serialized in a stable binary format that starts with a versioned
+    /// header. The keys of the hasher of the filter are serialized along with its bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    ///
+    /// let mut filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// filter.insert("foo");
+    ///
+    /// let bytes = filter.to_bytes();
+    /// let filter: BloomFilter<String> = BloomFilter::from_bytes(&bytes).unwrap();
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_bytes(&mut bytes)
+            .expect("Expected writing to a vector to succeed.");
+        bytes
+    }
+
+    fn write_bytes(&self, bytes: &mut Vec<u8>) -> io::Result<()> {
+        format::write_header(bytes, format::BLOOM_FILTER)?;
+        format::write_hash_builder(bytes, &self.hash_builder)?;
+        format::write_usize(bytes, self.hasher_count)?;
+        format::write_usize(bytes, self.bit_vec.len())?;
+        format::write_words(bytes, self.bit_vec.words())
+    }
+
+    /// Deserializes a filter from bytes produced by `to_bytes`. Returns an error if the bytes were
+    /// not produced by `to_bytes` for this kind of filter or if they were serialized with an
+    /// unsupported version of the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::BloomFilter;
+    ///
+    /// let mut filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// let filter: BloomFilter<String> = BloomFilter::from_bytes(&filter.to_bytes()).unwrap();
+    /// assert_eq!(filter.bit_count(), 96);
+    ///
+    /// assert!(BloomFilter::<String>::from_bytes(b"foo").is_err());
+    /// ```
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self> {
+        format::read_header(&mut bytes, format::BLOOM_FILTER)?;
+        let hash_builder = format::read_hash_builder(&mut bytes)?;
+        let hasher_count = format::read_usize(&mut bytes)?;
+        let bit_count = format::read_usize(&mut bytes)?;
+        let words = format::read_words(bytes)?;
+        match BitVec::from_words(bit_count, words) {
+            Some(bit_vec) if !bit_vec.is_empty() && hasher_count > 0 => {
+                Ok(Self::from_bit_vec(bit_vec, hasher_count, hash_builder))
+            }
+            _ => Err(Error::InvalidFormat),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BloomFilter;
+    use crate::bloom::Error;
+    use std::error;
+
+    #[test]
+    #[should_panic]
+    fn test_zero_hasher_count() {
+        let _filter: BloomFilter<u32> = BloomFilter::from_bit_count(100, 0);
+    }
+
+    #[test]
+    fn test_insert_contains() {
+        let mut filter: BloomFilter<u32> = BloomFilter::new(1000, 0.01);
+        for item in 0..1000u32 {
+            filter.insert(&item);
+        }
+        for item in 0..1000u32 {
+            assert!(filter.contains(&item));
+        }
+
+        let false_positives = (1000..11000u32)
+            .filter(|item| filter.contains(item))
+            .count();
+        assert!(false_positives < 200);
+        assert!(filter.estimate_fpp() < 0.02);
+    }
+
+    #[test]
+    fn test_insert_all_contains_all() {
+        let items: Vec<u32> = (0..1000).collect();
+        let mut filter: BloomFilter<u32> = BloomFilter::with_seed(1000, 0.01, 1);
+        let mut other: BloomFilter<u32> = BloomFilter::with_seed(1000, 0.01, 1);
+        filter.insert_all(&items);
+        for item in &items {
+            other.insert(item);
+        }
+        assert_eq!(filter.to_bytes(), other.to_bytes());
+
+        let queries: Vec<u32> = (500..1500).collect();
+        let expected: Vec<bool> = queries.iter().map(|item| filter.contains(item)).collect();
+        assert_eq!(filter.contains_all(&queries), expected);
+        assert!(filter.contains_all(&queries)[..500]
+            .iter()
+            .all(|found| *found));
+        assert!(filter.contains_all(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_with_seed() {
+        let mut filter: BloomFilter<u32> = BloomFilter::with_seed(100, 0.01, 1);
+        let mut other: BloomFilter<u32> = BloomFilter::with_seed(100, 0.01, 1);
+        let mut different: BloomFilter<u32> = BloomFilter::with_seed(100, 0.01, 2);
+        for item in 0..100u32 {
+            filter.insert(&item);
+            other.insert(&item);
+            different.insert(&item);
+        }
+        assert_eq!(filter.to_bytes(), other.to_bytes());
+        assert_ne!(filter.to_bytes(), different.to_bytes());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut filter: BloomFilter<u32> = BloomFilter::new(100, 0.01);
+        for item in 0..100u32 {
+            filter.insert(&item);
+        }
+
+        let bytes = filter.to_bytes();
+        assert_eq!(&bytes[..6], b"ECBF\x02\x00");
+        let deserialized: BloomFilter<u32> = BloomFilter::from_bytes(&bytes).unwrap();
+        assert_eq!(deserialized.to_bytes(), bytes);
+        assert_eq!(deserialized.hasher(), filter.hasher());
+        for item in 0..100u32 {
+            assert!(deserialized.contains(&item));
+        }
+
+        let deserialized: BloomFilter<u32> =
+            bincode::deserialize(&bincode::serialize(&filter).unwrap()).unwrap();
+        assert_eq!(deserialized.to_bytes(), bytes);
+
+        assert!(BloomFilter::<u32>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(BloomFilter::<u32>::from_bytes(&bytes[..bytes.len() - 8]).is_err());
+    }
+
+    #[test]
+    fn test_error_source() {
+        let filter: BloomFilter<u32> = BloomFilter::new(100, 0.01);
+        let bytes = filter.to_bytes();
+        match BloomFilter::<u32>::from_bytes(&bytes[..4]) {
+            Err(error @ Error::IOError(_)) => assert!(error::Error::source(&error).is_some()),
+            _ => panic!("Expected IOError."),
+        }
+    }
+}
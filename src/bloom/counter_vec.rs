@@ -0,0 +1,165 @@
+use serde_derive::{Deserialize, Serialize};
+
+const WORD_BITS: usize = 64;
+
+// A vector of saturating counters that are `width` bits wide and packed into 64-bit words. The
+// number of non-zero counters is maintained to estimate the false positive probability of filters.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct CounterVec {
+    words: Vec<u64>,
+    len: usize,
+    width: usize,
+    occupied_count: usize,
+}
+
+impl CounterVec {
+    pub fn new(len: usize, width: usize) -> Self {
+        assert!(
+            width > 0 && width <= 8 && WORD_BITS.is_multiple_of(width),
+            "Error: counter width must be 1, 2, 4, or 8 bits.",
+        );
+        let counters_per_word = WORD_BITS / width;
+        CounterVec {
+            words: vec![0; len.div_ceil(counters_per_word)],
+            len,
+            width,
+            occupied_count: 0,
+        }
+    }
+
+    // Constructs a `CounterVec` with `len` counters that are `width` bits wide from the words that
+    // store them. Returns `None` if the width is invalid, if the number of words does not match
+    // `len`, or if a bit past the last counter is set.
+    pub fn from_words(len: usize, width: usize, words: Vec<u64>) -> Option<Self> {
+        if width == 0 || width > 8 || !WORD_BITS.is_multiple_of(width) {
+            return None;
+        }
+        if words.len() != len.div_ceil(WORD_BITS / width) {
+            return None;
+        }
+        let bit_len = len * width;
+        if !bit_len.is_multiple_of(WORD_BITS)
+            && words[words.len() - 1] >> (bit_len % WORD_BITS) != 0
+        {
+            return None;
+        }
+        let mut ret = CounterVec {
+            words,
+            len,
+            width,
+            occupied_count: 0,
+        };
+        ret.occupied_count = (0..len).filter(|index| ret.get(*index) != 0).count();
+        Some(ret)
+    }
+
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn max_value(&self) -> u64 {
+        (1 << self.width) - 1
+    }
+
+    pub fn occupied_count(&self) -> usize {
+        self.occupied_count
+    }
+
+    pub fn get(&self, index: usize) -> u64 {
+        let bit_index = index * self.width;
+        (self.words[bit_index / WORD_BITS] >> (bit_index % WORD_BITS)) & self.max_value()
+    }
+
+    pub fn set(&mut self, index: usize, value: u64) {
+        let old_value = self.get(index);
+        if old_value == 0 && value != 0 {
+            self.occupied_count += 1;
+        } else if old_value != 0 && value == 0 {
+            self.occupied_count -= 1;
+        }
+        let max_value = self.max_value();
+        let bit_index = index * self.width;
+        let word = &mut self.words[bit_index / WORD_BITS];
+        *word &= !(max_value << (bit_index % WORD_BITS));
+        *word |= value.min(max_value) << (bit_index % WORD_BITS);
+    }
+
+    // Increments a counter unless it is saturated.
+    pub fn increment(&mut self, index: usize) {
+        let value = self.get(index);
+        if value < self.max_value() {
+            self.set(index, value + 1);
+        }
+    }
+
+    // Decrements a counter unless it is zero or saturated. A saturated counter may have been
+    // incremented more times than it can represent, so it is never decremented.
+    pub fn decrement(&mut self, index: usize) {
+        let value = self.get(index);
+        if value != 0 && value < self.max_value() {
+            self.set(index, value - 1);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for word in &mut self.words {
+            *word = 0;
+        }
+        self.occupied_count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CounterVec;
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_width() {
+        CounterVec::new(10, 3);
+    }
+
+    #[test]
+    fn test_increment_decrement() {
+        let mut counters = CounterVec::new(100, 4);
+        for _ in 0..20 {
+            counters.increment(17);
+        }
+        counters.increment(18);
+        assert_eq!(counters.get(16), 0);
+        assert_eq!(counters.get(17), 15);
+        assert_eq!(counters.get(18), 1);
+        assert_eq!(counters.occupied_count(), 2);
+
+        counters.decrement(17);
+        counters.decrement(18);
+        counters.decrement(18);
+        assert_eq!(counters.get(17), 15);
+        assert_eq!(counters.get(18), 0);
+        assert_eq!(counters.occupied_count(), 1);
+
+        counters.clear();
+        assert_eq!(counters.get(17), 0);
+        assert_eq!(counters.occupied_count(), 0);
+    }
+
+    #[test]
+    fn test_set() {
+        let mut counters = CounterVec::new(64, 8);
+        for index in 0..64 {
+            counters.set(index, index as u64);
+        }
+        for index in 0..64 {
+            assert_eq!(counters.get(index), index as u64);
+        }
+        assert_eq!(counters.occupied_count(), 63);
+    }
+}
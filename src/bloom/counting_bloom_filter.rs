@@ -0,0 +1,466 @@
+use crate::bit_vec::BitVec;
+use crate::bloom::counter_vec::CounterVec;
+use crate::bloom::{format, hash, BloomFilter, Error, Result, SipHashBuilder};
+use serde_derive::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::io;
+use std::marker::PhantomData;
+
+const COUNTER_WIDTH: usize = 4;
+
+/// A bloom filter that supports removing items.
+///
+/// A counting bloom filter replaces each bit of a bloom filter with a 4-bit counter. Inserting an
+/// item increments the counters at the positions given by each hash function, and removing an item
+/// decrements them. A counter that reaches its maximum value of 15 is never decremented, so the
+/// filter never has false negatives as long as only inserted items are removed.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::bloom::CountingBloomFilter;
+///
+/// let mut filter: CountingBloomFilter<String> = CountingBloomFilter::new(10, 0.01);
+///
+/// filter.insert("foo");
+/// filter.insert("bar");
+/// assert!(filter.contains("foo"));
+///
+/// assert!(filter.remove("foo"));
+/// assert!(!filter.contains("foo"));
+/// assert!(filter.contains("bar"));
+///
+/// let bloom_filter = filter.to_bloom_filter();
+/// assert!(bloom_filter.contains("bar"));
+/// ```
+#[derive(Clone, Deserialize, Serialize)]
+pub struct CountingBloomFilter<T, S = SipHashBuilder> {
+    counters: CounterVec,
+    hasher_count: usize,
+    hash_builder: S,
+    #[serde(skip)]
+    _marker: PhantomData<T>,
+}
+
+impl<T> CountingBloomFilter<T> {
+    /// Constructs a new, empty `CountingBloomFilter<T>` with an estimated maximum of `item_count`
+    /// items and a desired false positive probability of `fpp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    ///
+    /// let filter: CountingBloomFilter<String> = CountingBloomFilter::new(10, 0.01);
+    /// ```
+    pub fn new(item_count: usize, fpp: f64) -> Self {
+        Self::from_counter_count(
+            hash::optimal_slot_count(item_count, fpp),
+            hash::optimal_hasher_count(fpp),
+        )
+    }
+
+    /// Constructs a new, empty `CountingBloomFilter<T>` with `counter_count` counters and
+    /// `hasher_count` hash functions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `counter_count` or `hasher_count` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    ///
+    /// let filter: CountingBloomFilter<String> = CountingBloomFilter::from_counter_count(100, 4);
+    /// assert_eq!(filter.counter_count(), 100);
+    /// assert_eq!(filter.hasher_count(), 4);
+    /// ```
+    pub fn from_counter_count(counter_count: usize, hasher_count: usize) -> Self {
+        Self::from_parts(counter_count, hasher_count, SipHashBuilder::new())
+    }
+
+    /// Constructs a new, empty `CountingBloomFilter<T>` with an estimated maximum of `item_count`
+    /// items and a desired false positive probability of `fpp` whose hash functions are derived
+    /// from `seed`. Filters constructed with the same seed hash every item identically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    ///
+    /// let filter: CountingBloomFilter<String> = CountingBloomFilter::with_seed(10, 0.01, 0);
+    /// let other: CountingBloomFilter<String> = CountingBloomFilter::with_seed(10, 0.01, 0);
+    /// assert_eq!(filter.hasher(), other.hasher());
+    /// ```
+    pub fn with_seed(item_count: usize, fpp: f64, seed: u64) -> Self {
+        Self::with_hasher(item_count, fpp, SipHashBuilder::with_seed(seed))
+    }
+}
+
+impl<T, S> CountingBloomFilter<T, S>
+where
+    S: BuildHasher,
+{
+    /// Constructs a new, empty `CountingBloomFilter<T, S>` with an estimated maximum of
+    /// `item_count` items and a desired false positive probability of `fpp` that uses
+    /// `hash_builder` to hash items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let filter: CountingBloomFilter<String, RandomState> =
+    ///     CountingBloomFilter::with_hasher(10, 0.01, RandomState::new());
+    /// ```
+    pub fn with_hasher(item_count: usize, fpp: f64, hash_builder: S) -> Self {
+        Self::from_parts(
+            hash::optimal_slot_count(item_count, fpp),
+            hash::optimal_hasher_count(fpp),
+            hash_builder,
+        )
+    }
+
+    fn from_parts(counter_count: usize, hasher_count: usize, hash_builder: S) -> Self {
+        assert!(counter_count > 0, "Error: counter count must be positive.");
+        assert!(hasher_count > 0, "Error: hasher count must be positive.");
+        CountingBloomFilter {
+            counters: CounterVec::new(counter_count, COUNTER_WIDTH),
+            hasher_count,
+            hash_builder,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Inserts an item into the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter: CountingBloomFilter<String> = CountingBloomFilter::new(10, 0.01);
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn insert<U>(&mut self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        for slot in hash::slots(
+            &self.hash_builder,
+            item,
+            self.hasher_count,
+            self.counters.len(),
+        ) {
+            self.counters.increment(slot);
+        }
+    }
+
+    /// Removes an item from the filter. Returns `true` if the item was possibly in the filter.
+    /// Otherwise the filter is not modified and `false` is returned.
+    ///
+    /// Removing an item that was never inserted may introduce false negatives for the items that
+    /// share its counters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter: CountingBloomFilter<String> = CountingBloomFilter::new(10, 0.01);
+    /// filter.insert("foo");
+    /// assert!(filter.remove("foo"));
+    /// assert!(!filter.remove("foo"));
+    /// ```
+    pub fn remove<U>(&mut self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        if !self.contains(item) {
+            return false;
+        }
+        for slot in hash::slots(
+            &self.hash_builder,
+            item,
+            self.hasher_count,
+            self.counters.len(),
+        ) {
+            self.counters.decrement(slot);
+        }
+        true
+    }
+
+    /// Checks if an item is possibly in the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter: CountingBloomFilter<String> = CountingBloomFilter::new(10, 0.01);
+    /// assert!(!filter.contains("foo"));
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        hash::slots(
+            &self.hash_builder,
+            item,
+            self.hasher_count,
+            self.counters.len(),
+        )
+        .all(|slot| self.counters.get(slot) != 0)
+    }
+
+    /// Clears all items from the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter: CountingBloomFilter<String> = CountingBloomFilter::new(10, 0.01);
+    /// filter.insert("foo");
+    /// filter.clear();
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn clear(&mut self) {
+        self.counters.clear();
+    }
+
+    /// Returns the number of counters in the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    ///
+    /// let filter: CountingBloomFilter<String> = CountingBloomFilter::new(10, 0.01);
+    /// assert_eq!(filter.counter_count(), 96);
+    /// ```
+    pub fn counter_count(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Returns the number of hash functions used by the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    ///
+    /// let filter: CountingBloomFilter<String> = CountingBloomFilter::new(10, 0.01);
+    /// assert_eq!(filter.hasher_count(), 7);
+    /// ```
+    pub fn hasher_count(&self) -> usize {
+        self.hasher_count
+    }
+
+    /// Returns a reference to the `BuildHasher` used by the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::{CountingBloomFilter, SipHashBuilder};
+    ///
+    /// let filter: CountingBloomFilter<String> = CountingBloomFilter::with_seed(10, 0.01, 0);
+    /// assert_eq!(filter.hasher(), &SipHashBuilder::with_seed(0));
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns the estimated false positive probability of the filter based on the fraction of
+    /// counters that are non-zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter: CountingBloomFilter<String> = CountingBloomFilter::new(10, 0.01);
+    /// assert_eq!(filter.estimate_fpp(), 0.0);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimate_fpp() > 0.0 && filter.estimate_fpp() < 0.01);
+    /// ```
+    pub fn estimate_fpp(&self) -> f64 {
+        hash::estimate_fpp(
+            self.counters.occupied_count(),
+            self.counters.len(),
+            self.hasher_count,
+        )
+    }
+
+    /// Returns a `BloomFilter<T, S>` that contains the same items as the filter. The bloom filter
+    /// has a bit set for every non-zero counter and uses the same hash functions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter: CountingBloomFilter<String> = CountingBloomFilter::new(10, 0.01);
+    /// filter.insert("foo");
+    ///
+    /// let bloom_filter = filter.to_bloom_filter();
+    /// assert!(bloom_filter.contains("foo"));
+    /// assert_eq!(bloom_filter.bit_count(), filter.counter_count());
+    /// ```
+    pub fn to_bloom_filter(&self) -> BloomFilter<T, S>
+    where
+        S: Clone,
+    {
+        let bit_vec = (0..self.counters.len())
+            .map(|index| self.counters.get(index) != 0)
+            .collect::<BitVec>();
+        BloomFilter::from_bit_vec(bit_vec, self.hasher_count, self.hash_builder.clone())
+    }
+}
+
+impl<T> CountingBloomFilter<T> {
+    /// Returns the filter serialized in a stable binary format that starts with a versioned
+    /// header. The keys of the hasher of the filter are serialized along with its counters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter: CountingBloomFilter<String> = CountingBloomFilter::new(10, 0.01);
+    /// filter.insert("foo");
+    ///
+    /// let bytes = filter.to_bytes();
+    /// let filter: CountingBloomFilter<String> = CountingBloomFilter::from_bytes(&bytes).unwrap();
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_bytes(&mut bytes)
+            .expect("Expected writing to a vector to succeed.");
+        bytes
+    }
+
+    fn write_bytes(&self, bytes: &mut Vec<u8>) -> io::Result<()> {
+        format::write_header(bytes, format::COUNTING_BLOOM_FILTER)?;
+        format::write_hash_builder(bytes, &self.hash_builder)?;
+        format::write_usize(bytes, self.hasher_count)?;
+        format::write_usize(bytes, self.counters.len())?;
+        format::write_words(bytes, self.counters.words())
+    }
+
+    /// Deserializes a filter from bytes produced by `to_bytes`. Returns an error if the bytes were
+    /// not produced by `to_bytes` for this kind of filter or if they were serialized with an
+    /// unsupported version of the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter: CountingBloomFilter<String> = CountingBloomFilter::new(10, 0.01);
+    /// let filter: CountingBloomFilter<String> = CountingBloomFilter::from_bytes(&filter.to_bytes()).unwrap();
+    /// assert_eq!(filter.counter_count(), 96);
+    ///
+    /// assert!(CountingBloomFilter::<String>::from_bytes(b"foo").is_err());
+    /// ```
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self> {
+        format::read_header(&mut bytes, format::COUNTING_BLOOM_FILTER)?;
+        let hash_builder = format::read_hash_builder(&mut bytes)?;
+        let hasher_count = format::read_usize(&mut bytes)?;
+        let counter_count = format::read_usize(&mut bytes)?;
+        let words = format::read_words(bytes)?;
+        match CounterVec::from_words(counter_count, COUNTER_WIDTH, words) {
+            Some(counters) if counters.len() > 0 && hasher_count > 0 => Ok(CountingBloomFilter {
+                counters,
+                hasher_count,
+                hash_builder,
+                _marker: PhantomData,
+            }),
+            _ => Err(Error::InvalidFormat),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CountingBloomFilter;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut filter: CountingBloomFilter<u32> = CountingBloomFilter::new(1000, 0.01);
+        for item in 0..1000u32 {
+            filter.insert(&item);
+        }
+        for item in 0..500u32 {
+            assert!(filter.remove(&item));
+        }
+        for item in 500..1000u32 {
+            assert!(filter.contains(&item));
+        }
+
+        let false_positives = (0..500u32).filter(|item| filter.contains(item)).count();
+        assert!(false_positives < 50);
+        assert!(filter.estimate_fpp() < 0.01);
+    }
+
+    #[test]
+    fn test_saturated_counter() {
+        let mut filter: CountingBloomFilter<String> = CountingBloomFilter::from_counter_count(1, 1);
+        for _ in 0..20 {
+            filter.insert("foo");
+        }
+        for _ in 0..20 {
+            assert!(filter.remove("foo"));
+        }
+        assert!(filter.contains("foo"));
+    }
+
+    #[test]
+    fn test_to_bloom_filter() {
+        let mut filter: CountingBloomFilter<u32> = CountingBloomFilter::new(100, 0.01);
+        for item in 0..100u32 {
+            filter.insert(&item);
+        }
+        let bloom_filter = filter.to_bloom_filter();
+        for item in 0..100u32 {
+            assert!(bloom_filter.contains(&item));
+        }
+        assert_eq!(bloom_filter.estimate_fpp(), filter.estimate_fpp());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut filter: CountingBloomFilter<u32> = CountingBloomFilter::new(100, 0.01);
+        for item in 0..100u32 {
+            filter.insert(&item);
+        }
+
+        let bytes = filter.to_bytes();
+        assert_eq!(&bytes[..6], b"ECBF\x02\x01");
+        let mut deserialized: CountingBloomFilter<u32> =
+            CountingBloomFilter::from_bytes(&bytes).unwrap();
+        assert_eq!(deserialized.to_bytes(), bytes);
+        assert_eq!(deserialized.estimate_fpp(), filter.estimate_fpp());
+        for item in 0..100u32 {
+            assert!(deserialized.remove(&item));
+        }
+        assert_eq!(deserialized.estimate_fpp(), 0.0);
+
+        let deserialized: CountingBloomFilter<u32> =
+            bincode::deserialize(&bincode::serialize(&filter).unwrap()).unwrap();
+        assert_eq!(deserialized.to_bytes(), bytes);
+
+        assert!(crate::bloom::BloomFilter::<u32>::from_bytes(&bytes).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::bloom::{Error, Result, SipHashBuilder};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{self, Read, Write};
+
+// Every serialized filter starts with a header that consists of the magic bytes, the version of
+// the format, and the kind of the filter. All integers are stored in big-endian. Version 2 added
+// the keys of the hasher of the filter after the header.
+const MAGIC: &[u8; 4] = b"ECBF";
+const VERSION: u8 = 2;
+
+pub const BLOOM_FILTER: u8 = 0;
+pub const COUNTING_BLOOM_FILTER: u8 = 1;
+pub const STABLE_BLOOM_FILTER: u8 = 2;
+
+pub fn write_header<W>(writer: &mut W, kind: u8) -> io::Result<()>
+where
+    W: Write,
+{
+    writer.write_all(MAGIC)?;
+    writer.write_u8(VERSION)?;
+    writer.write_u8(kind)
+}
+
+pub fn read_header<R>(reader: &mut R, kind: u8) -> Result<()>
+where
+    R: Read,
+{
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(Error::InvalidMagic);
+    }
+    let version = reader.read_u8()?;
+    if version != VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    if reader.read_u8()? != kind {
+        return Err(Error::InvalidFormat);
+    }
+    Ok(())
+}
+
+pub fn write_usize<W>(writer: &mut W, value: usize) -> io::Result<()>
+where
+    W: Write,
+{
+    writer.write_u64::<BigEndian>(value as u64)
+}
+
+pub fn read_usize<R>(reader: &mut R) -> Result<usize>
+where
+    R: Read,
+{
+    Ok(reader.read_u64::<BigEndian>()? as usize)
+}
+
+pub fn write_hash_builder<W>(writer: &mut W, hash_builder: &SipHashBuilder) -> io::Result<()>
+where
+    W: Write,
+{
+    let (key_0, key_1) = hash_builder.keys();
+    writer.write_u64::<BigEndian>(key_0)?;
+    writer.write_u64::<BigEndian>(key_1)
+}
+
+pub fn read_hash_builder<R>(reader: &mut R) -> Result<SipHashBuilder>
+where
+    R: Read,
+{
+    let key_0 = reader.read_u64::<BigEndian>()?;
+    let key_1 = reader.read_u64::<BigEndian>()?;
+    Ok(SipHashBuilder::with_keys(key_0, key_1))
+}
+
+pub fn write_words<W>(writer: &mut W, words: &[u64]) -> io::Result<()>
+where
+    W: Write,
+{
+    for word in words {
+        writer.write_u64::<BigEndian>(*word)?;
+    }
+    Ok(())
+}
+
+// Reads the remaining words of a filter. The length of `bytes` must be a multiple of eight.
+pub fn read_words(bytes: &[u8]) -> Result<Vec<u64>> {
+    if !bytes.len().is_multiple_of(8) {
+        return Err(Error::InvalidFormat);
+    }
+    Ok(bytes
+        .chunks(8)
+        .map(|mut chunk| chunk.read_u64::<BigEndian>())
+        .collect::<io::Result<Vec<u64>>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_header, write_header, BLOOM_FILTER, COUNTING_BLOOM_FILTER};
+    use crate::bloom::Error;
+
+    #[test]
+    fn test_header() {
+        let mut bytes = Vec::new();
+        write_header(&mut bytes, BLOOM_FILTER).unwrap();
+        assert_eq!(bytes, b"ECBF\x02\x00".to_vec());
+        assert!(read_header(&mut bytes.as_slice(), BLOOM_FILTER).is_ok());
+
+        match read_header(&mut bytes.as_slice(), COUNTING_BLOOM_FILTER) {
+            Err(Error::InvalidFormat) => {}
+            _ => panic!("Expected invalid format error."),
+        }
+
+        bytes[4] = 1;
+        match read_header(&mut bytes.as_slice(), BLOOM_FILTER) {
+            Err(Error::UnsupportedVersion(1)) => {}
+            _ => panic!("Expected unsupported version error."),
+        }
+
+        bytes[0] = b'X';
+        match read_header(&mut bytes.as_slice(), BLOOM_FILTER) {
+            Err(Error::InvalidMagic) => {}
+            _ => panic!("Expected invalid magic error."),
+        }
+
+        match read_header(&mut &bytes[..3], BLOOM_FILTER) {
+            Err(Error::IOError(_)) => {}
+            _ => panic!("Expected IO error."),
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use crate::seed;
+use rand::Rng;
+use serde_derive::{Deserialize, Serialize};
+use std::f64::consts::LN_2;
+#[allow(deprecated)]
+use std::hash::SipHasher;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+/// A `BuildHasher` that builds SipHash-2-4 hashers with a pair of 128-bit keys.
+///
+/// Two filters only agree on the slots of an item if their hashers use the same keys, so the keys
+/// are serialized along with the filters that use them. The default keys are random, which makes
+/// it infeasible for an adversary to find items that collide in a filter.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::bloom::SipHashBuilder;
+/// use std::hash::{BuildHasher, Hasher};
+///
+/// let hash_builder = SipHashBuilder::with_seed(0);
+/// let mut hasher = hash_builder.build_hasher();
+/// hasher.write_u32(1);
+///
+/// let mut other = SipHashBuilder::with_seed(0).build_hasher();
+/// other.write_u32(1);
+/// assert_eq!(hasher.finish(), other.finish());
+/// ```
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SipHashBuilder {
+    key_0: u64,
+    key_1: u64,
+}
+
+impl SipHashBuilder {
+    /// Constructs a new `SipHashBuilder` with random keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::SipHashBuilder;
+    ///
+    /// assert_ne!(SipHashBuilder::new(), SipHashBuilder::new());
+    /// ```
+    pub fn new() -> Self {
+        let mut rng = rand::thread_rng();
+        Self::with_keys(rng.gen(), rng.gen())
+    }
+
+    /// Constructs a new `SipHashBuilder` with keys derived from `seed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::SipHashBuilder;
+    ///
+    /// assert_eq!(SipHashBuilder::with_seed(0), SipHashBuilder::with_seed(0));
+    /// assert_ne!(SipHashBuilder::with_seed(0), SipHashBuilder::with_seed(1));
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_keys(seed::split_mix(seed, 0), seed::split_mix(seed, 1))
+    }
+
+    /// Constructs a new `SipHashBuilder` with the keys `key_0` and `key_1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::SipHashBuilder;
+    ///
+    /// let hash_builder = SipHashBuilder::with_keys(1, 2);
+    /// assert_eq!(hash_builder.keys(), (1, 2));
+    /// ```
+    pub fn with_keys(key_0: u64, key_1: u64) -> Self {
+        SipHashBuilder { key_0, key_1 }
+    }
+
+    /// Returns the keys of the hashers built by the `SipHashBuilder`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::SipHashBuilder;
+    ///
+    /// let hash_builder = SipHashBuilder::with_keys(1, 2);
+    /// assert_eq!(hash_builder.keys(), (1, 2));
+    /// ```
+    pub fn keys(&self) -> (u64, u64) {
+        (self.key_0, self.key_1)
+    }
+}
+
+impl Default for SipHashBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl BuildHasher for SipHashBuilder {
+    type Hasher = SipHasher;
+
+    fn build_hasher(&self) -> SipHasher {
+        SipHasher::new_with_keys(self.key_0, self.key_1)
+    }
+}
+
+// Returns the number of slots that minimizes the size of a filter that contains `item_count` items
+// with a false positive probability of `fpp`.
+pub fn optimal_slot_count(item_count: usize, fpp: f64) -> usize {
+    let slot_count = -(item_count.max(1) as f64) * fpp.ln() / (LN_2 * LN_2);
+    (slot_count.ceil() as usize).max(1)
+}
+
+// Returns the number of hash functions that minimizes the false positive probability of a filter
+// with an optimal number of slots.
+pub fn optimal_hasher_count(fpp: f64) -> usize {
+    ((-fpp.log2()).ceil() as usize).max(1)
+}
+
+// The number of items that are hashed before their slots are probed by the batch operations of
+// the filters. The slots of a batch are probed in ascending order, so nearby slots are probed
+// together.
+pub const BATCH_SIZE: usize = 64;
+
+// Returns the `hasher_count` slots of an item in a filter with `slot_count` slots. The slots are
+// derived from two hashes of the item built by `hash_builder` using double hashing.
+pub fn slots<S, U>(
+    hash_builder: &S,
+    item: &U,
+    hasher_count: usize,
+    slot_count: usize,
+) -> impl Iterator<Item = usize>
+where
+    S: BuildHasher,
+    U: Hash + ?Sized,
+{
+    let mut hasher = hash_builder.build_hasher();
+    item.hash(&mut hasher);
+    let hash_1 = hasher.finish();
+    hasher.write_u8(0);
+    let hash_2 = hasher.finish();
+    (0..hasher_count as u64).map(move |index| {
+        (hash_1.wrapping_add(index.wrapping_mul(hash_2)) % slot_count as u64) as usize
+    })
+}
+
+// Returns the probability that every slot of an item that is not in a filter is occupied when
+// `occupied_count` of the `slot_count` slots are occupied.
+pub fn estimate_fpp(occupied_count: usize, slot_count: usize, hasher_count: usize) -> f64 {
+    (occupied_count as f64 / slot_count as f64).powi(hasher_count as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{optimal_hasher_count, optimal_slot_count, slots, SipHashBuilder};
+
+    #[test]
+    fn test_optimal_counts() {
+        assert_eq!(optimal_slot_count(1000, 0.01), 9586);
+        assert_eq!(optimal_hasher_count(0.01), 7);
+        assert_eq!(optimal_hasher_count(0.9), 1);
+    }
+
+    #[test]
+    fn test_slots() {
+        let hash_builder = SipHashBuilder::new();
+        let expected = slots(&hash_builder, "foo", 5, 100).collect::<Vec<usize>>();
+        assert_eq!(expected.len(), 5);
+        assert!(expected.iter().all(|slot| *slot < 100));
+        assert_eq!(
+            slots(&hash_builder, "foo", 5, 100).collect::<Vec<usize>>(),
+            expected
+        );
+
+        let other = SipHashBuilder::with_seed(0);
+        let slots_1 = slots(&other, "foo", 5, 1 << 32).collect::<Vec<usize>>();
+        let slots_2 =
+            slots(&SipHashBuilder::with_seed(1), "foo", 5, 1 << 32).collect::<Vec<usize>>();
+        assert_eq!(
+            slots(&other, "foo", 5, 1 << 32).collect::<Vec<usize>>(),
+            slots_1
+        );
+        assert_ne!(slots_1, slots_2);
+    }
+}
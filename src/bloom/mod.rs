@@ -0,0 +1,70 @@
+//! Space-efficient probabilistic data structures to test for membership in a set.
+//!
+//! Every filter implements `Serialize` and `Deserialize`, and can be converted to and from a
+//! stable binary format with `to_bytes` and `from_bytes`. The binary format starts with a
+//! versioned header so that filters built offline can be shipped to other processes.
+//!
+//! Filters hash items with a `BuildHasher`, which defaults to a `SipHashBuilder` with random
+//! keys. A filter constructed with `with_seed` derives its keys from the seed, so filters built
+//! with the same seed hash every item identically. A custom `BuildHasher` can be provided with
+//! `with_hasher`.
+
+mod bloom_filter;
+mod counter_vec;
+mod counting_bloom_filter;
+mod format;
+pub(crate) mod hash;
+mod stable_bloom_filter;
+
+pub use self::bloom_filter::BloomFilter;
+pub use self::counting_bloom_filter::CountingBloomFilter;
+pub use self::hash::SipHashBuilder;
+pub use self::stable_bloom_filter::StableBloomFilter;
+use std::error;
+use std::fmt;
+use std::io;
+use std::result;
+
+/// Convenience `Error` enum for `bloom`.
+#[derive(Debug)]
+pub enum Error {
+    /// An input or output error, such as unexpectedly reaching the end of the bytes.
+    IOError(io::Error),
+    /// The bytes do not start with the magic bytes of a serialized filter.
+    InvalidMagic,
+    /// The bytes were serialized with an unsupported version of the format.
+    UnsupportedVersion(u8),
+    /// The bytes do not describe a valid filter of the expected kind.
+    InvalidFormat,
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::IOError(err)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::IOError(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IOError(ref error) => write!(f, "{}", error),
+            Error::InvalidMagic => write!(f, "bytes are not a serialized filter"),
+            Error::UnsupportedVersion(version) => {
+                write!(f, "unsupported filter format version {}", version)
+            }
+            Error::InvalidFormat => write!(f, "bytes do not describe a valid filter"),
+        }
+    }
+}
+
+/// Convenience `Result` type for `bloom`.
+pub type Result<T> = result::Result<T, Error>;
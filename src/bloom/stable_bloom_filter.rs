@@ -0,0 +1,502 @@
+use crate::bloom::counter_vec::CounterVec;
+use crate::bloom::{format, hash, Error, Result, SipHashBuilder};
+use crate::seed;
+use rand::{Rng, XorShiftRng};
+use serde_derive::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::io;
+use std::marker::PhantomData;
+
+const DEFAULT_CELL_WIDTH: usize = 2;
+
+/// A bloom filter that evicts stale items to bound its false positive probability on an unbounded
+/// stream of items.
+///
+/// A stable bloom filter is an array of cells that are `cell_width` bits wide. Inserting an item
+/// first decrements `decrement_count` consecutive cells starting at a random cell, and then sets
+/// the cells at the positions given by each hash function to their maximum value. An item is
+/// reported to be in the filter if all of its cells are non-zero. Because cells are continuously
+/// decremented, the fraction of non-zero cells converges to a fixed point and the false positive
+/// probability stays bounded regardless of the number of items inserted. In exchange, the filter
+/// may have false negatives for items that have not been recently inserted.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::bloom::StableBloomFilter;
+///
+/// let mut filter: StableBloomFilter<String> = StableBloomFilter::new(1000, 0.01);
+///
+/// filter.insert("foo");
+/// assert!(filter.contains("foo"));
+///
+/// assert!(filter.stable_fpp() <= 0.01);
+/// ```
+#[derive(Clone, Deserialize, Serialize)]
+pub struct StableBloomFilter<T, S = SipHashBuilder> {
+    cells: CounterVec,
+    hasher_count: usize,
+    decrement_count: usize,
+    hash_builder: S,
+    #[serde(skip, default = "rand::weak_rng")]
+    rng: XorShiftRng,
+    #[serde(skip)]
+    _marker: PhantomData<T>,
+}
+
+impl<T> StableBloomFilter<T> {
+    /// Constructs a new, empty `StableBloomFilter<T>` with `cell_count` cells and a desired false
+    /// positive probability of `fpp` once the filter is stable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_count` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter: StableBloomFilter<String> = StableBloomFilter::new(1000, 0.01);
+    /// ```
+    pub fn new(cell_count: usize, fpp: f64) -> Self {
+        Self::with_hasher(cell_count, fpp, SipHashBuilder::new())
+    }
+
+    /// Constructs a new, empty `StableBloomFilter<T>` with `cell_count` cells and a desired false
+    /// positive probability of `fpp` once the filter is stable that uses `rng` to choose the cells
+    /// to decrement.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_count` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    /// use rand::{SeedableRng, XorShiftRng};
+    ///
+    /// let rng = XorShiftRng::from_seed([1, 1, 1, 1]);
+    /// let filter: StableBloomFilter<String> = StableBloomFilter::with_rng(1000, 0.01, rng);
+    /// ```
+    pub fn with_rng(cell_count: usize, fpp: f64, rng: XorShiftRng) -> Self {
+        let mut filter = Self::new(cell_count, fpp);
+        filter.rng = rng;
+        filter
+    }
+
+    /// Constructs a new, empty `StableBloomFilter<T>` with `cell_count` cells and a desired false
+    /// positive probability of `fpp` once the filter is stable whose hash functions and choices of
+    /// cells to decrement are derived from `seed`. Filters constructed with the same seed behave
+    /// identically.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_count` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    ///
+    /// let mut filter: StableBloomFilter<String> = StableBloomFilter::with_seed(1000, 0.01, 0);
+    /// let mut other: StableBloomFilter<String> = StableBloomFilter::with_seed(1000, 0.01, 0);
+    /// filter.insert("foo");
+    /// other.insert("foo");
+    /// assert_eq!(filter.to_bytes(), other.to_bytes());
+    /// ```
+    pub fn with_seed(cell_count: usize, fpp: f64, seed: u64) -> Self {
+        let mut filter = Self::with_hasher(cell_count, fpp, SipHashBuilder::with_seed(seed));
+        filter.rng = seed::rng_from_seed(seed);
+        filter
+    }
+
+    /// Constructs a new, empty `StableBloomFilter<T>` with `cell_count` cells that are
+    /// `cell_width` bits wide, `hasher_count` hash functions, and decrements `decrement_count`
+    /// cells on every insertion.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_count`, `hasher_count`, or `decrement_count` is zero, or if `cell_width` is
+    /// not 1, 2, 4, or 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter: StableBloomFilter<String> = StableBloomFilter::from_cell_count(1000, 2, 4, 10);
+    /// assert_eq!(filter.cell_count(), 1000);
+    /// assert_eq!(filter.hasher_count(), 4);
+    /// assert_eq!(filter.decrement_count(), 10);
+    /// ```
+    pub fn from_cell_count(
+        cell_count: usize,
+        cell_width: usize,
+        hasher_count: usize,
+        decrement_count: usize,
+    ) -> Self {
+        assert!(cell_count > 0, "Error: cell count must be positive.");
+        Self::from_parts(
+            CounterVec::new(cell_count, cell_width),
+            hasher_count,
+            decrement_count,
+            SipHashBuilder::new(),
+        )
+    }
+}
+
+impl<T, S> StableBloomFilter<T, S>
+where
+    S: BuildHasher,
+{
+    /// Constructs a new, empty `StableBloomFilter<T, S>` with `cell_count` cells and a desired
+    /// false positive probability of `fpp` once the filter is stable that uses `hash_builder` to
+    /// hash items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_count` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let filter: StableBloomFilter<String, RandomState> =
+    ///     StableBloomFilter::with_hasher(1000, 0.01, RandomState::new());
+    /// ```
+    pub fn with_hasher(cell_count: usize, fpp: f64, hash_builder: S) -> Self {
+        assert!(cell_count > 0, "Error: cell count must be positive.");
+        let hasher_count = hash::optimal_hasher_count(fpp);
+        let max_value = ((1 << DEFAULT_CELL_WIDTH) - 1) as f64;
+
+        // Solves for the number of cells to decrement such that the false positive probability at
+        // the fixed point is `fpp`. See "Approximately Detecting Duplicates for Streaming Data
+        // using Stable Bloom Filters" by Deng and Rafiei.
+        let base = (1.0 - fpp.powf(1.0 / hasher_count as f64)).powf(1.0 / max_value);
+        let decrement_count =
+            1.0 / ((1.0 / base - 1.0) * (1.0 / hasher_count as f64 - 1.0 / cell_count as f64));
+        let decrement_count = (decrement_count.ceil() as usize).max(1);
+
+        Self::from_parts(
+            CounterVec::new(cell_count, DEFAULT_CELL_WIDTH),
+            hasher_count,
+            decrement_count,
+            hash_builder,
+        )
+    }
+
+    fn from_parts(
+        cells: CounterVec,
+        hasher_count: usize,
+        decrement_count: usize,
+        hash_builder: S,
+    ) -> Self {
+        assert!(hasher_count > 0, "Error: hasher count must be positive.");
+        assert!(
+            decrement_count > 0,
+            "Error: decrement count must be positive."
+        );
+        StableBloomFilter {
+            cells,
+            hasher_count,
+            decrement_count,
+            hash_builder,
+            rng: rand::weak_rng(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Inserts an item into the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    ///
+    /// let mut filter: StableBloomFilter<String> = StableBloomFilter::new(1000, 0.01);
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn insert<U>(&mut self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let cell_count = self.cells.len();
+        let start = self.rng.gen_range(0, cell_count);
+        for offset in 0..self.decrement_count.min(cell_count) {
+            let cell = (start + offset) % cell_count;
+            let value = self.cells.get(cell);
+            if value != 0 {
+                self.cells.set(cell, value - 1);
+            }
+        }
+
+        let max_value = self.cells.max_value();
+        for slot in hash::slots(&self.hash_builder, item, self.hasher_count, cell_count) {
+            self.cells.set(slot, max_value);
+        }
+    }
+
+    /// Checks if an item is possibly in the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    ///
+    /// let mut filter: StableBloomFilter<String> = StableBloomFilter::new(1000, 0.01);
+    /// assert!(!filter.contains("foo"));
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        hash::slots(
+            &self.hash_builder,
+            item,
+            self.hasher_count,
+            self.cells.len(),
+        )
+        .all(|slot| self.cells.get(slot) != 0)
+    }
+
+    /// Clears all items from the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    ///
+    /// let mut filter: StableBloomFilter<String> = StableBloomFilter::new(1000, 0.01);
+    /// filter.insert("foo");
+    /// filter.clear();
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Returns the number of cells in the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter: StableBloomFilter<String> = StableBloomFilter::new(1000, 0.01);
+    /// assert_eq!(filter.cell_count(), 1000);
+    /// ```
+    pub fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns the number of hash functions used by the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter: StableBloomFilter<String> = StableBloomFilter::new(1000, 0.01);
+    /// assert_eq!(filter.hasher_count(), 7);
+    /// ```
+    pub fn hasher_count(&self) -> usize {
+        self.hasher_count
+    }
+
+    /// Returns a reference to the `BuildHasher` used by the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::{SipHashBuilder, StableBloomFilter};
+    ///
+    /// let filter: StableBloomFilter<String> = StableBloomFilter::with_seed(1000, 0.01, 0);
+    /// assert_eq!(filter.hasher(), &SipHashBuilder::with_seed(0));
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns the number of cells that are decremented on every insertion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter: StableBloomFilter<String> = StableBloomFilter::from_cell_count(1000, 2, 4, 10);
+    /// assert_eq!(filter.decrement_count(), 10);
+    /// ```
+    pub fn decrement_count(&self) -> usize {
+        self.decrement_count
+    }
+
+    /// Returns the estimated false positive probability of the filter based on the fraction of
+    /// cells that are non-zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    ///
+    /// let mut filter: StableBloomFilter<String> = StableBloomFilter::new(1000, 0.01);
+    /// assert_eq!(filter.estimate_fpp(), 0.0);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimate_fpp() > 0.0);
+    /// ```
+    pub fn estimate_fpp(&self) -> f64 {
+        hash::estimate_fpp(
+            self.cells.occupied_count(),
+            self.cells.len(),
+            self.hasher_count,
+        )
+    }
+
+    /// Returns the false positive probability of the filter once the fraction of non-zero cells
+    /// has converged to its fixed point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter: StableBloomFilter<String> = StableBloomFilter::new(1000, 0.01);
+    /// assert!(filter.stable_fpp() <= 0.01);
+    /// ```
+    pub fn stable_fpp(&self) -> f64 {
+        let hasher_count = self.hasher_count as f64;
+        let decrement_count = self.decrement_count as f64;
+        let cell_count = self.cells.len() as f64;
+        let max_value = self.cells.max_value() as f64;
+        let base = 1.0 / (1.0 + 1.0 / (decrement_count * (1.0 / hasher_count - 1.0 / cell_count)));
+        (1.0 - base.powf(max_value)).powf(hasher_count)
+    }
+}
+
+impl<T> StableBloomFilter<T> {
+    /// Returns the filter serialized in a stable binary format that starts with a versioned
+    /// header. The keys of the hasher of the filter are serialized along with its cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    ///
+    /// let mut filter: StableBloomFilter<String> = StableBloomFilter::new(1000, 0.01);
+    /// filter.insert("foo");
+    ///
+    /// let bytes = filter.to_bytes();
+    /// let filter: StableBloomFilter<String> = StableBloomFilter::from_bytes(&bytes).unwrap();
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_bytes(&mut bytes)
+            .expect("Expected writing to a vector to succeed.");
+        bytes
+    }
+
+    fn write_bytes(&self, bytes: &mut Vec<u8>) -> io::Result<()> {
+        format::write_header(bytes, format::STABLE_BLOOM_FILTER)?;
+        format::write_hash_builder(bytes, &self.hash_builder)?;
+        format::write_usize(bytes, self.hasher_count)?;
+        format::write_usize(bytes, self.decrement_count)?;
+        format::write_usize(bytes, self.cells.width())?;
+        format::write_usize(bytes, self.cells.len())?;
+        format::write_words(bytes, self.cells.words())
+    }
+
+    /// Deserializes a filter from bytes produced by `to_bytes`. Returns an error if the bytes were
+    /// not produced by `to_bytes` for this kind of filter or if they were serialized with an
+    /// unsupported version of the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::StableBloomFilter;
+    ///
+    /// let mut filter: StableBloomFilter<String> = StableBloomFilter::new(1000, 0.01);
+    /// let filter: StableBloomFilter<String> = StableBloomFilter::from_bytes(&filter.to_bytes()).unwrap();
+    /// assert_eq!(filter.cell_count(), 1000);
+    ///
+    /// assert!(StableBloomFilter::<String>::from_bytes(b"foo").is_err());
+    /// ```
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self> {
+        format::read_header(&mut bytes, format::STABLE_BLOOM_FILTER)?;
+        let hash_builder = format::read_hash_builder(&mut bytes)?;
+        let hasher_count = format::read_usize(&mut bytes)?;
+        let decrement_count = format::read_usize(&mut bytes)?;
+        let cell_width = format::read_usize(&mut bytes)?;
+        let cell_count = format::read_usize(&mut bytes)?;
+        let words = format::read_words(bytes)?;
+        match CounterVec::from_words(cell_count, cell_width, words) {
+            Some(cells) if cells.len() > 0 && hasher_count > 0 && decrement_count > 0 => Ok(
+                Self::from_parts(cells, hasher_count, decrement_count, hash_builder),
+            ),
+            _ => Err(Error::InvalidFormat),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StableBloomFilter;
+    use rand::{SeedableRng, XorShiftRng};
+
+    #[test]
+    fn test_decrement_count() {
+        let filter: StableBloomFilter<u32> = StableBloomFilter::new(10_000, 0.01);
+        assert!(filter.stable_fpp() <= 0.01);
+        assert!(filter.stable_fpp() > 0.005);
+    }
+
+    #[test]
+    fn test_unbounded_stream() {
+        let rng = XorShiftRng::from_seed([1, 1, 1, 1]);
+        let mut filter: StableBloomFilter<u32> = StableBloomFilter::with_rng(10_000, 0.01, rng);
+        for item in 0..100_000u32 {
+            filter.insert(&item);
+            assert!(filter.contains(&item));
+        }
+
+        let false_positives = (100_000..110_000u32)
+            .filter(|item| filter.contains(item))
+            .count();
+        assert!(false_positives < 200);
+        assert!(filter.estimate_fpp() < 0.02);
+
+        for item in 99_990..100_000u32 {
+            assert!(filter.contains(&item));
+        }
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut filter: StableBloomFilter<u32> = StableBloomFilter::from_cell_count(1000, 4, 3, 5);
+        for item in 0..100u32 {
+            filter.insert(&item);
+        }
+
+        let bytes = filter.to_bytes();
+        assert_eq!(&bytes[..6], b"ECBF\x02\x02");
+        let deserialized: StableBloomFilter<u32> = StableBloomFilter::from_bytes(&bytes).unwrap();
+        assert_eq!(deserialized.to_bytes(), bytes);
+        assert_eq!(deserialized.decrement_count(), 5);
+        assert_eq!(deserialized.stable_fpp(), filter.stable_fpp());
+
+        let deserialized: StableBloomFilter<u32> =
+            bincode::deserialize(&bincode::serialize(&filter).unwrap()).unwrap();
+        assert_eq!(deserialized.to_bytes(), bytes);
+    }
+}
@@ -1,11 +1,16 @@
-use crate::bp_tree::node::{InsertCases, InternalNode, LeafNode, Node, BLOCK_SIZE};
-use crate::bp_tree::pager::{Pager, Result};
+use crate::bp_tree::node::{InternalNode, LeafNode, Node, Value, DEFAULT_BLOCK_SIZE};
+use crate::bp_tree::pager::{Error, Pager, Result};
+use crate::compare::{Compare, Natural};
+use crate::durability::{SyncMode, SyncState};
 use crate::entry::Entry;
 use serde::de::DeserializeOwned;
 use serde::ser::Serialize;
 use std::borrow::Borrow;
+use std::fs::{File, OpenOptions};
+use std::io;
 use std::mem;
 use std::path::Path;
+use std::vec;
 
 // (page, node, index)
 type SearchHistory<T, U> = Vec<(usize, Node<T, U>, usize)>;
@@ -18,6 +23,10 @@ type SearchOutcome<T, U> = (usize, Node<T, U>, SearchHistory<T, U>);
 /// which each internal node contains keys and pointers to other nodes, and each leaf node
 /// contains keys and values.
 ///
+/// Nodes are stored in fixed-size pages. Leaf nodes store their entries in variable-length cells
+/// indexed by a slot directory, so short entries do not use more space than they need, and values
+/// that exceed the maximum inline value size are stored in a chain of overflow pages.
+///
 /// # Examples
 ///
 /// ```
@@ -43,13 +52,19 @@ type SearchOutcome<T, U> = (usize, Node<T, U>, SearchHistory<T, U>);
 /// # }
 /// # foo().unwrap();
 /// ```
-pub struct BpMap<T, U> {
+pub struct BpMap<T, U, C = Natural> {
     pager: Pager<T, U>,
+    sync_state: SyncState,
+    compare: C,
 }
 
 impl<T, U> BpMap<T, U> {
-    /// Constructs a new, empty `BpMap<T, U>` with maximum sizes for keys and values, and creates a
-    /// file for data persistence.
+    /// Constructs a new, empty `BpMap<T, U>` with a maximum size for keys and a maximum inline size
+    /// for values, and creates a file for data persistence. Values that are larger than the maximum
+    /// inline size are stored in overflow pages.
+    ///
+    /// Returns `Error::KeyTooLarge` if the maximum key size is too large for an entry to fit in a
+    /// leaf node.
     ///
     /// # Examples
     ///
@@ -59,7 +74,7 @@ impl<T, U> BpMap<T, U> {
     /// # use std::fs;
     /// use extended_collections::bp_tree::BpMap;
     ///
-    /// // keys have a maximum of 4 bytes and values have a maximum of 8 bytes
+    /// // keys have a maximum of 4 bytes and values of at most 8 bytes are stored inline
     /// let map: BpMap<u32, u64> = BpMap::new("example_bp_map_new", 4, 8)?;
     /// # fs::remove_file("example_bp_map_new")?;
     /// # Ok(())
@@ -72,20 +87,22 @@ impl<T, U> BpMap<T, U> {
         U: Serialize,
         P: AsRef<Path>,
     {
-        let leaf_degree = LeafNode::<T, U>::get_degree(key_size, value_size);
-        let internal_degree = InternalNode::<T, U>::get_degree(key_size);
+        let leaf_degree = LeafNode::<T, U>::get_degree(DEFAULT_BLOCK_SIZE);
+        let internal_degree = InternalNode::<T, U>::get_degree(DEFAULT_BLOCK_SIZE, key_size);
         Pager::new(
             file_path,
             key_size,
             value_size,
             leaf_degree,
             internal_degree,
+            DEFAULT_BLOCK_SIZE,
         )
-        .map(|pager| BpMap { pager })
+        .map(|pager| BpMap::from_pager(pager, Natural))
     }
 
-    /// Constructs a new, empty `BpMap<T, U>` with maximum sizes for keys and values and specific
-    /// sizes for leaf and internal nodes, and creates a file for data persistence.
+    /// Constructs a new, empty `BpMap<T, U>` with a maximum size for keys, a maximum inline size for
+    /// values, and a maximum number of entries for leaf and internal nodes, and creates a file for
+    /// data persistence.
     ///
     /// # Examples
     ///
@@ -113,16 +130,70 @@ impl<T, U> BpMap<T, U> {
         U: Serialize,
         P: AsRef<Path>,
     {
-        assert!(LeafNode::<T, U>::get_max_size(leaf_degree, key_size, value_size) <= BLOCK_SIZE);
-        assert!(InternalNode::<T, U>::get_max_size(internal_degree, key_size) <= BLOCK_SIZE);
+        assert!(
+            InternalNode::<T, U>::get_max_size(internal_degree, key_size) <= DEFAULT_BLOCK_SIZE
+        );
+        Pager::new(
+            file_path,
+            key_size,
+            value_size,
+            leaf_degree,
+            internal_degree,
+            DEFAULT_BLOCK_SIZE,
+        )
+        .map(|pager| BpMap::from_pager(pager, Natural))
+    }
+
+    /// Constructs a new, empty `BpMap<T, U>` with a maximum size for keys, a maximum inline size for
+    /// values, and a size for pages in bytes, and creates a file for data persistence. The block
+    /// size is stored in the file, so the map is opened with the same block size.
+    ///
+    /// Larger pages make the tree shallower and let larger values be stored inline, which reduces
+    /// the number of page accesses per operation on devices that read large blocks efficiently.
+    ///
+    /// Returns `Error::InvalidBlockSize` if the block size is not a power of two between 512 and
+    /// 65536 bytes, and `Error::KeyTooLarge` if the maximum key size is too large for an entry to
+    /// fit in a leaf node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let mut map: BpMap<u32, u64> =
+    ///     BpMap::with_block_size("example_bp_map_with_block_size", 4, 8, 16384)?;
+    /// map.insert(1, 1)?;
+    /// assert_eq!(map.block_size(), 16384);
+    /// # fs::remove_file("example_bp_map_with_block_size")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn with_block_size<P>(
+        file_path: P,
+        key_size: u64,
+        value_size: u64,
+        block_size: u64,
+    ) -> Result<BpMap<T, U>>
+    where
+        T: Serialize,
+        U: Serialize,
+        P: AsRef<Path>,
+    {
+        let leaf_degree = LeafNode::<T, U>::get_degree(block_size);
+        let internal_degree = InternalNode::<T, U>::get_degree(block_size, key_size);
         Pager::new(
             file_path,
             key_size,
             value_size,
             leaf_degree,
             internal_degree,
+            block_size,
         )
-        .map(|pager| BpMap { pager })
+        .map(|pager| BpMap::from_pager(pager, Natural))
     }
 
     /// Opens an existing `BpMap<T, U>` from a file.
@@ -143,14 +214,220 @@ impl<T, U> BpMap<T, U> {
     where
         P: AsRef<Path>,
     {
-        Pager::open(file_path).map(|pager| BpMap { pager })
+        Pager::open(file_path).map(|pager| BpMap::from_pager(pager, Natural))
+    }
+
+    /// Constructs a new, empty `BpMap<T, U>` with a maximum size for keys and a maximum inline size
+    /// for values, and creates a memory-mapped file for data persistence.
+    ///
+    /// Pages are accessed through a shared memory mapping of the file instead of seeks, reads, and
+    /// writes, which avoids a system call per page access and lets the operating system cache
+    /// pages. The file is grown geometrically, so it may be larger than the pages in use.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let mut map: BpMap<u32, u64> = BpMap::new_mmap("example_bp_map_new_mmap", 4, 8)?;
+    /// map.insert(0, 1)?;
+    /// assert_eq!(map.get(&0)?, Some(1));
+    /// # fs::remove_file("example_bp_map_new_mmap")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    #[cfg(unix)]
+    pub fn new_mmap<P>(file_path: P, key_size: u64, value_size: u64) -> Result<BpMap<T, U>>
+    where
+        T: Serialize,
+        U: Serialize,
+        P: AsRef<Path>,
+    {
+        let leaf_degree = LeafNode::<T, U>::get_degree(DEFAULT_BLOCK_SIZE);
+        let internal_degree = InternalNode::<T, U>::get_degree(DEFAULT_BLOCK_SIZE, key_size);
+        Pager::new_mmap(
+            file_path,
+            key_size,
+            value_size,
+            leaf_degree,
+            internal_degree,
+            DEFAULT_BLOCK_SIZE,
+        )
+        .map(|pager| BpMap::from_pager(pager, Natural))
+    }
+
+    /// Opens an existing `BpMap<T, U>` from a file and memory-maps the file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let map: BpMap<u32, u64> = BpMap::open_mmap("example_bp_map_open_mmap")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    #[cfg(unix)]
+    pub fn open_mmap<P>(file_path: P) -> Result<BpMap<T, U>>
+    where
+        P: AsRef<Path>,
+    {
+        Pager::open_mmap(file_path).map(|pager| BpMap::from_pager(pager, Natural))
+    }
+
+    /// Restores a checkpoint created by `checkpoint` into a new file at `file_path` and opens the
+    /// map from the new file. The checkpoint is left unmodified, so it can be restored again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let mut map: BpMap<u32, u64> = BpMap::new("example_bp_map_restore", 4, 8)?;
+    /// map.insert(1, 1)?;
+    /// map.checkpoint("example_bp_map_restore_backup")?;
+    /// map.clear()?;
+    ///
+    /// let mut map: BpMap<u32, u64> =
+    ///     BpMap::restore("example_bp_map_restore_backup", "example_bp_map_restore_restored")?;
+    /// assert_eq!(map.get(&1)?, Some(1));
+    /// # fs::remove_file("example_bp_map_restore")?;
+    /// # fs::remove_file("example_bp_map_restore_backup")?;
+    /// # fs::remove_file("example_bp_map_restore_restored")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn restore<P, Q>(checkpoint_path: P, file_path: Q) -> Result<BpMap<T, U>>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let checkpoint_path = checkpoint_path.as_ref();
+        let path = file_path.as_ref();
+        let mut checkpoint = File::open(checkpoint_path)
+            .map_err(|error| Error::file_io(checkpoint_path, "open", error))?;
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .map_err(|error| Error::file_io(path, "create", error))?;
+        io::copy(&mut checkpoint, &mut file)
+            .map_err(|error| Error::file_io(path, "copy", error))?;
+        file.sync_all()
+            .map_err(|error| Error::file_io(path, "sync", error))?;
+        Self::open(path)
+    }
+}
+
+impl<T, U, C> BpMap<T, U, C> {
+    fn from_pager(pager: Pager<T, U>, compare: C) -> Self {
+        BpMap {
+            pager,
+            sync_state: SyncState::new(SyncMode::default()),
+            compare,
+        }
+    }
+
+    // Records a write to the map and syncs the map if the sync mode requires it.
+    fn record_write(&mut self) -> Result<()> {
+        if self.sync_state.record_write() {
+            self.sync()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Constructs a new, empty `BpMap<T, U, C>` that uses `compare` to order keys, with a maximum
+    /// size for keys and a maximum inline size for values, and creates a file for data
+    /// persistence. The ordering is not stored in the file, so the map must be reopened with
+    /// `open_with_comparator` and the same ordering.
+    ///
+    /// Returns `Error::KeyTooLarge` if the maximum key size is too large for an entry to fit in a
+    /// leaf node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let compare = |a: &u32, b: &u32| b.cmp(a);
+    /// let mut map = BpMap::with_comparator("example_bp_map_with_comparator", 4, 8, compare)?;
+    /// map.insert(1, 1u64)?;
+    /// map.insert(2, 2)?;
+    /// assert_eq!(map.min()?, Some(2));
+    /// # fs::remove_file("example_bp_map_with_comparator")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn with_comparator<P>(
+        file_path: P,
+        key_size: u64,
+        value_size: u64,
+        compare: C,
+    ) -> Result<BpMap<T, U, C>>
+    where
+        T: Serialize,
+        U: Serialize,
+        P: AsRef<Path>,
+    {
+        let leaf_degree = LeafNode::<T, U>::get_degree(DEFAULT_BLOCK_SIZE);
+        let internal_degree = InternalNode::<T, U>::get_degree(DEFAULT_BLOCK_SIZE, key_size);
+        Pager::new(
+            file_path,
+            key_size,
+            value_size,
+            leaf_degree,
+            internal_degree,
+            DEFAULT_BLOCK_SIZE,
+        )
+        .map(|pager| BpMap::from_pager(pager, compare))
+    }
+
+    /// Opens an existing `BpMap<T, U, C>` from a file that uses `compare` to order keys. The
+    /// ordering must be the same as the ordering that the map was created with.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let compare = |a: &u32, b: &u32| b.cmp(a);
+    /// let map: BpMap<u32, u64, _> =
+    ///     BpMap::open_with_comparator("example_bp_map_open_with_comparator", compare)?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn open_with_comparator<P>(file_path: P, compare: C) -> Result<BpMap<T, U, C>>
+    where
+        P: AsRef<Path>,
+    {
+        Pager::open(file_path).map(|pager| BpMap::from_pager(pager, compare))
     }
 
     fn search_node<V>(&mut self, key: &V) -> Result<SearchOutcome<T, U>>
     where
         T: Borrow<V> + DeserializeOwned,
         U: DeserializeOwned,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
         let mut curr_page = self.pager.get_root_page();
         let mut curr_node = self.pager.get_page(curr_page)?;
@@ -158,7 +435,7 @@ impl<T, U> BpMap<T, U> {
         let mut stack = Vec::new();
 
         while let Node::Internal(node) = curr_node {
-            let next_index = node.search(key);
+            let next_index = node.search(key, &self.compare);
             let next_page = node.pointers[next_index];
             stack.push((curr_page, Node::Internal(node), next_index));
             curr_page = next_page;
@@ -170,10 +447,12 @@ impl<T, U> BpMap<T, U> {
     /// Inserts a key-value pair into the map. If the key already exists in the map, it will return
     /// and replace the old key-value pair.
     ///
-    /// # Panics
+    /// Values that exceed the maximum inline value size are written to overflow pages, so a value
+    /// of any size can be inserted as long as it can be read back into memory.
     ///
-    /// Panics if attempting to insert a key or value that exceeds the maximum key or value size
-    /// specified on creation.
+    /// Returns `Error::KeyTooLarge` if the key exceeds the maximum key size specified on creation,
+    /// and `Error::ValueTooLarge` if the serialized size of the value does not fit in a `usize`.
+    /// The map is left unchanged if either error is returned.
     ///
     /// # Examples
     ///
@@ -195,36 +474,32 @@ impl<T, U> BpMap<T, U> {
     /// ```
     pub fn insert(&mut self, key: T, value: U) -> Result<Option<(T, U)>>
     where
-        T: Clone + DeserializeOwned + Ord + Serialize,
+        T: Clone + DeserializeOwned + Serialize,
         U: DeserializeOwned + Serialize,
+        C: Compare<T>,
     {
         self.pager.validate_key(&key)?;
-        self.pager.validate_value(&value)?;
+        let value = self.pager.allocate_value(value)?;
         let (mut curr_page, mut curr_node, mut stack) = self.search_node(&key)?;
 
+        let layout = self.pager.get_page_layout();
         let mut split_node_entry = None;
+        let replaced_entry;
         match curr_node {
-            Node::Leaf(mut curr_leaf_node) => match curr_leaf_node.insert(Entry { key, value }) {
-                Some(InsertCases::Split {
-                    split_key,
-                    split_node,
-                }) => {
-                    let split_node_index = self.pager.allocate_node(&split_node)?;
+            Node::Leaf(mut curr_leaf_node) => {
+                replaced_entry = curr_leaf_node.insert(Entry { key, value }, &self.compare);
+
+                // a replaced value may be larger than the old value, so the node can overflow
+                if curr_leaf_node.is_overflow(self.pager.get_leaf_degree(), layout)? {
+                    let (split_key, split_node) = curr_leaf_node.split(layout)?;
+                    let split_node_index = self.pager.allocate_node(&Node::Leaf(split_node))?;
                     curr_leaf_node.next_leaf = Some(split_node_index);
                     split_node_entry = Some((split_key, split_node_index));
-                    self.pager
-                        .write_node(curr_page, &Node::Leaf(curr_leaf_node))?;
-                }
-                Some(InsertCases::Entry(entry)) => {
-                    self.pager
-                        .write_node(curr_page, &Node::Leaf(curr_leaf_node))?;
-                    return Ok(Some((entry.key, entry.value)));
                 }
-                None => self
-                    .pager
-                    .write_node(curr_page, &Node::Leaf(curr_leaf_node))?,
-            },
-            _ => panic!("Expected a leaf node."),
+                self.pager
+                    .write_node(curr_page, &Node::Leaf(curr_leaf_node))?;
+            }
+            _ => return Err(self.pager.corruption(curr_page)),
         }
 
         while let Some((split_key, split_pointer)) = split_node_entry {
@@ -232,7 +507,8 @@ impl<T, U> BpMap<T, U> {
                 Some((parent_page, mut parent_node, _)) => {
                     match parent_node {
                         Node::Internal(ref mut node) => {
-                            let split_node_opt = node.insert(split_key, split_pointer, true);
+                            let split_node_opt =
+                                node.insert(split_key, split_pointer, true, &self.compare);
                             if let Some((split_key, split_node)) = split_node_opt {
                                 let split_node_index = self.pager.allocate_node(&split_node)?;
                                 split_node_entry = Some((split_key, split_node_index));
@@ -258,9 +534,58 @@ impl<T, U> BpMap<T, U> {
                 }
             }
         }
-        let new_len = self.pager.get_len() + 1;
-        self.pager.set_len(new_len)?;
-        Ok(None)
+        let ret = match replaced_entry {
+            Some(entry) => {
+                let value = self.pager.take_value(entry.value)?;
+                Some((entry.key, value))
+            }
+            None => {
+                let new_len = self.pager.get_len() + 1;
+                self.pager.set_len(new_len)?;
+                None
+            }
+        };
+        self.record_write()?;
+        Ok(ret)
+    }
+
+    /// Inserts a key-value pair into the map if the key does not exist in the map. If the key
+    /// already exists in the map, the map is left unchanged and the key-value pair is returned.
+    /// Otherwise it will return `None`. The errors are the same as the errors of `insert`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let mut map: BpMap<u32, u64> = BpMap::new("example_bp_map_try_insert", 4, 8)?;
+    /// assert_eq!(map.try_insert(1, 1)?, None);
+    /// assert_eq!(map.try_insert(1, 2)?, Some((1, 2)));
+    /// assert_eq!(map.get(&1)?, Some(1));
+    /// # fs::remove_file("example_bp_map_try_insert")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn try_insert(&mut self, key: T, value: U) -> Result<Option<(T, U)>>
+    where
+        T: Clone + DeserializeOwned + Serialize,
+        U: DeserializeOwned + Serialize,
+        C: Compare<T>,
+    {
+        self.pager.validate_key(&key)?;
+        let (curr_page, curr_node, _) = self.search_node(&key)?;
+        let contains_key = match curr_node {
+            Node::Leaf(curr_leaf_node) => curr_leaf_node.search(&key, &self.compare).is_some(),
+            _ => return Err(self.pager.corruption(curr_page)),
+        };
+        if contains_key {
+            return Ok(Some((key, value)));
+        }
+        self.insert(key, value).map(|_| None)
     }
 
     /// Removes a key-value pair from the map. If the key exists in the map, it will return the
@@ -285,9 +610,10 @@ impl<T, U> BpMap<T, U> {
     /// ```
     pub fn remove<V>(&mut self, key: &V) -> Result<Option<(T, U)>>
     where
-        T: Borrow<V> + Clone + DeserializeOwned + Ord + Serialize,
+        T: Borrow<V> + Clone + DeserializeOwned + Serialize,
         U: DeserializeOwned + Serialize,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<T> + Compare<V>,
     {
         let (curr_page, curr_node, mut stack) = self.search_node(key)?;
         let mut delete_entry = None;
@@ -295,8 +621,13 @@ impl<T, U> BpMap<T, U> {
 
         match curr_node {
             Node::Leaf(mut curr_leaf_node) => {
-                ret = curr_leaf_node.remove(key);
-                let is_underflow = curr_leaf_node.len < (self.pager.get_leaf_degree() + 1) / 2;
+                ret = match curr_leaf_node.remove(key, &self.compare) {
+                    Some(entry) => entry,
+                    None => return Ok(None),
+                };
+                let leaf_degree = self.pager.get_leaf_degree();
+                let layout = self.pager.get_page_layout();
+                let is_underflow = curr_leaf_node.is_underflow(leaf_degree, layout)?;
                 if is_underflow && !stack.is_empty() {
                     if let Some((parent_page, parent_node, curr_index)) = stack.pop() {
                         let mut parent_node = {
@@ -316,12 +647,12 @@ impl<T, U> BpMap<T, U> {
                         let mut sibling_leaf_node = {
                             match self.pager.get_page(sibling_page)? {
                                 Node::Leaf(node) => node,
-                                _ => panic!("Expected a leaf node."),
+                                _ => return Err(self.pager.corruption(sibling_page)),
                             }
                         };
 
                         // merge
-                        if sibling_leaf_node.len == (self.pager.get_leaf_degree() + 1) / 2 {
+                        if curr_leaf_node.can_merge(&sibling_leaf_node, leaf_degree, layout)? {
                             if sibling_index == curr_index + 1 {
                                 curr_leaf_node.merge(&mut sibling_leaf_node);
                                 delete_entry = Some((curr_index, parent_page, parent_node));
@@ -338,38 +669,46 @@ impl<T, U> BpMap<T, U> {
                         }
                         // take one entry
                         else {
-                            if sibling_index == curr_index + 1 {
-                                let removed_entry = sibling_leaf_node.remove_at(0);
-                                let new_key = sibling_leaf_node.entries[0]
-                                    .as_ref()
-                                    .map(|entry| entry.key.clone())
-                                    .expect("Expected some entry.");
-                                parent_node.keys[curr_index] = Some(new_key);
-                                curr_leaf_node.insert(removed_entry);
+                            let borrow_index = if sibling_index == curr_index + 1 {
+                                0
                             } else {
-                                let remove_index = sibling_leaf_node.len - 1;
-                                let removed_entry = sibling_leaf_node.remove_at(remove_index);
-                                parent_node.keys[sibling_index] = Some(removed_entry.key.clone());
-                                curr_leaf_node.insert(removed_entry);
+                                sibling_leaf_node.entries.len() - 1
+                            };
+                            // a compressed node may not have room for an entry that does not
+                            // share its key prefix, in which case it is left underfull
+                            let can_borrow = sibling_leaf_node.entries.len() > 1
+                                && curr_leaf_node.can_insert(
+                                    &sibling_leaf_node.entries[borrow_index],
+                                    leaf_degree,
+                                    layout,
+                                )?;
+                            if can_borrow {
+                                let removed_entry = sibling_leaf_node.remove_at(borrow_index);
+                                if sibling_index == curr_index + 1 {
+                                    let new_key = sibling_leaf_node.entries[0].key.clone();
+                                    parent_node.keys[curr_index] = Some(new_key);
+                                } else {
+                                    parent_node.keys[sibling_index] =
+                                        Some(removed_entry.key.clone());
+                                }
+                                curr_leaf_node.insert(removed_entry, &self.compare);
+                                self.pager
+                                    .write_node(parent_page, &Node::Internal(parent_node))?;
+                                self.pager
+                                    .write_node(sibling_page, &Node::Leaf(sibling_leaf_node))?;
                             }
-                            self.pager
-                                .write_node(parent_page, &Node::Internal(parent_node))?;
-                            self.pager
-                                .write_node(sibling_page, &Node::Leaf(sibling_leaf_node))?;
                             self.pager
                                 .write_node(curr_page, &Node::Leaf(curr_leaf_node))?;
                         }
                     }
-                    let new_len = self.pager.get_len() - 1;
-                    self.pager.set_len(new_len)?;
-                } else if ret.is_some() {
-                    let new_len = self.pager.get_len() - 1;
-                    self.pager.set_len(new_len)?;
+                } else {
                     self.pager
                         .write_node(curr_page, &Node::Leaf(curr_leaf_node))?;
                 }
+                let new_len = self.pager.get_len() - 1;
+                self.pager.set_len(new_len)?;
             }
-            _ => panic!("Expected a leaf node."),
+            _ => return Err(self.pager.corruption(curr_page)),
         }
 
         while let Some((delete_index, curr_page, mut curr_node)) = delete_entry {
@@ -396,7 +735,7 @@ impl<T, U> BpMap<T, U> {
                     let mut sibling_node = {
                         match self.pager.get_page(sibling_page)? {
                             Node::Internal(node) => node,
-                            _ => panic!("Expected an internal node."),
+                            _ => return Err(self.pager.corruption(sibling_page)),
                         }
                     };
 
@@ -425,7 +764,7 @@ impl<T, U> BpMap<T, U> {
                         let removed_key =
                             mem::replace(&mut parent_node.keys[curr_index], Some(removed_key))
                                 .expect("Expected some key.");
-                        curr_node.insert(removed_key, removed_pointer, true);
+                        curr_node.insert(removed_key, removed_pointer, true, &self.compare);
                         self.pager
                             .write_node(parent_page, &Node::Internal(parent_node))?;
                         self.pager
@@ -439,7 +778,7 @@ impl<T, U> BpMap<T, U> {
                         let removed_key =
                             mem::replace(&mut parent_node.keys[sibling_index], Some(removed_key))
                                 .expect("Expected some key.");
-                        curr_node.insert(removed_key, removed_pointer, false);
+                        curr_node.insert(removed_key, removed_pointer, false, &self.compare);
                         self.pager
                             .write_node(parent_page, &Node::Internal(parent_node))?;
                         self.pager
@@ -459,7 +798,9 @@ impl<T, U> BpMap<T, U> {
                     .write_node(curr_page, &Node::Internal(curr_node))?;
             }
         }
-        Ok(ret.map(|entry| (entry.key, entry.value)))
+        let value = self.pager.take_value(ret.value)?;
+        self.record_write()?;
+        Ok(Some((ret.key, value)))
     }
 
     /// Checks if a key exists in the map.
@@ -485,7 +826,8 @@ impl<T, U> BpMap<T, U> {
     where
         T: Borrow<V> + DeserializeOwned,
         U: DeserializeOwned,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
         self.get(key).map(|value| value.is_some())
     }
@@ -514,14 +856,19 @@ impl<T, U> BpMap<T, U> {
     where
         T: Borrow<V> + DeserializeOwned,
         U: DeserializeOwned,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        let (_, curr_node, _) = self.search_node(key)?;
+        let (curr_page, curr_node, _) = self.search_node(key)?;
         match curr_node {
-            Node::Leaf(mut curr_leaf_node) => Ok(curr_leaf_node.search(key).and_then(|index| {
-                mem::replace(&mut curr_leaf_node.entries[index], None).map(|entry| entry.value)
-            })),
-            _ => panic!("Expected a leaf node."),
+            Node::Leaf(mut curr_leaf_node) => match curr_leaf_node.search(key, &self.compare) {
+                Some(index) => {
+                    let entry = curr_leaf_node.entries.swap_remove(index);
+                    self.pager.get_value(entry.value).map(Some)
+                }
+                None => Ok(None),
+            },
+            _ => Err(self.pager.corruption(curr_page)),
         }
     }
 
@@ -547,6 +894,27 @@ impl<T, U> BpMap<T, U> {
         self.pager.get_len()
     }
 
+    /// Returns the size of the pages of the map in bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let map: BpMap<u32, u64> = BpMap::new("example_bp_map_block_size", 4, 8)?;
+    /// assert_eq!(map.block_size(), 4096);
+    /// # fs::remove_file("example_bp_map_block_size")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn block_size(&self) -> u64 {
+        self.pager.get_page_layout().block_size
+    }
+
     /// Returns `true` if the map is empty.
     ///
     /// # Examples
@@ -593,7 +961,8 @@ impl<T, U> BpMap<T, U> {
         T: Serialize,
         U: Serialize,
     {
-        self.pager.clear()
+        self.pager.clear()?;
+        self.record_write()
     }
 
     /// Returns the minimum key of the map. Returns `None` if the map is empty.
@@ -629,10 +998,12 @@ impl<T, U> BpMap<T, U> {
         }
 
         match curr_node {
-            Node::Leaf(mut curr_leaf_node) => {
-                Ok(mem::replace(&mut curr_leaf_node.entries[0], None).map(|entry| entry.key))
-            }
-            _ => panic!("Expected a leaf node."),
+            Node::Leaf(curr_leaf_node) => Ok(curr_leaf_node
+                .entries
+                .into_iter()
+                .next()
+                .map(|entry| entry.key)),
+            _ => Err(self.pager.corruption(curr_page)),
         }
     }
 
@@ -670,15 +1041,9 @@ impl<T, U> BpMap<T, U> {
 
         match curr_node {
             Node::Leaf(mut curr_leaf_node) => {
-                if curr_leaf_node.len == 0 {
-                    Ok(None)
-                } else {
-                    let index = curr_leaf_node.len - 1;
-                    Ok(mem::replace(&mut curr_leaf_node.entries[index], None)
-                        .map(|entry| entry.key))
-                }
+                Ok(curr_leaf_node.entries.pop().map(|entry| entry.key))
             }
-            _ => panic!("Expected a leaf node."),
+            _ => Err(self.pager.corruption(curr_page)),
         }
     }
 
@@ -722,34 +1087,305 @@ impl<T, U> BpMap<T, U> {
         match curr_node {
             Node::Leaf(curr_leaf_node) => Ok(BpMapIterMut {
                 pager: &mut self.pager,
-                curr_node: curr_leaf_node,
-                curr_index: 0,
+                entries: curr_leaf_node.entries.into_iter(),
+                next_leaf: curr_leaf_node.next_leaf,
             }),
-            _ => panic!("Expected a leaf node."),
+            _ => Err(self.pager.corruption(curr_page)),
         }
     }
-}
-
-impl<'a, T, U> IntoIterator for &'a mut BpMap<T, U>
-where
-    T: 'a + DeserializeOwned,
-    U: 'a + DeserializeOwned,
-{
-    type IntoIter = BpMapIterMut<'a, T, U>;
-    type Item = Result<(T, U)>;
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter_mut().unwrap()
+    /// Creates a consistent point-in-time copy of the map in a new file at `file_path`. The map
+    /// remains open and can be modified while the copy is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let mut map: BpMap<u32, u64> = BpMap::new("example_bp_map_checkpoint", 4, 8)?;
+    /// map.insert(1, 1)?;
+    /// map.checkpoint("example_bp_map_checkpoint_backup")?;
+    /// map.insert(1, 2)?;
+    ///
+    /// let mut backup: BpMap<u32, u64> = BpMap::open("example_bp_map_checkpoint_backup")?;
+    /// assert_eq!(backup.get(&1)?, Some(1));
+    /// assert_eq!(map.get(&1)?, Some(2));
+    /// # fs::remove_file("example_bp_map_checkpoint")?;
+    /// # fs::remove_file("example_bp_map_checkpoint_backup")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn checkpoint<P>(&mut self, file_path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        self.pager.checkpoint(file_path)
     }
-}
 
-/// A mutable iterator for `BpMap<T, U>`.
-///
-/// This iterator traverses the elements of the map in ascending order and yields owned entries.
-pub struct BpMapIterMut<'a, T, U> {
-    pager: &'a mut Pager<T, U>,
-    curr_node: LeafNode<T, U>,
-    curr_index: usize,
+    /// Returns statistics of the pages of the map. Counting the free pages reads every free page,
+    /// so this takes time proportional to the number of free pages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let mut map: BpMap<u32, u64> = BpMap::with_degrees("example_bp_map_stats", 4, 8, 3, 3)?;
+    /// for key in 0..10 {
+    ///     map.insert(key, 0)?;
+    /// }
+    /// for key in 0..10 {
+    ///     map.remove(&key)?;
+    /// }
+    ///
+    /// let stats = map.stats()?;
+    /// assert_eq!(stats.block_size, 4096);
+    /// assert!(stats.free_page_count > 0);
+    /// assert_eq!(stats.free_size(), stats.free_page_count as u64 * 4096);
+    /// # fs::remove_file("example_bp_map_stats")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn stats(&mut self) -> Result<BpMapStats>
+    where
+        T: DeserializeOwned,
+        U: DeserializeOwned,
+    {
+        Ok(BpMapStats {
+            page_count: self.pager.get_page_count(),
+            free_page_count: self.pager.get_free_page_count()?,
+            block_size: self.pager.get_page_layout().block_size,
+        })
+    }
+
+    /// Compacts the file of the map by rewriting the pages that are in use into a new file without
+    /// free pages, and replacing the file of the map with the new file. The file does not shrink
+    /// when entries are removed, so compacting the map after removing many entries reclaims the
+    /// space of the free pages.
+    ///
+    /// The existing file is left intact until it is replaced, so the map is not lost if the
+    /// compaction is interrupted. A file at the path of the map with a `.compact` suffix is used
+    /// for the new file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let mut map: BpMap<u32, u64> = BpMap::with_degrees("example_bp_map_compact", 4, 8, 3, 3)?;
+    /// for key in 0..100 {
+    ///     map.insert(key, 0)?;
+    /// }
+    /// for key in 0..90 {
+    ///     map.remove(&key)?;
+    /// }
+    ///
+    /// let len = fs::metadata("example_bp_map_compact")?.len();
+    /// map.compact()?;
+    /// assert!(fs::metadata("example_bp_map_compact")?.len() < len);
+    /// assert_eq!(map.stats()?.free_page_count, 0);
+    /// assert_eq!(map.get(&95)?, Some(0));
+    /// # fs::remove_file("example_bp_map_compact")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn compact(&mut self) -> Result<()>
+    where
+        T: DeserializeOwned + Serialize,
+        U: DeserializeOwned + Serialize,
+    {
+        self.pager.compact()?;
+        self.sync_state.record_sync();
+        Ok(())
+    }
+
+    /// Syncs all data to disk before consuming the map. Unlike relying on the map being dropped,
+    /// any errors encountered while persisting the map are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let mut map: BpMap<u32, u64> = BpMap::new("example_bp_map_close", 4, 8)?;
+    /// map.insert(1, 1)?;
+    /// map.close()?;
+    ///
+    /// let map: BpMap<u32, u64> = BpMap::open("example_bp_map_close")?;
+    /// assert_eq!(map.len(), 1);
+    /// # fs::remove_file("example_bp_map_close")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn close(mut self) -> Result<()> {
+        self.sync()
+    }
+
+    /// Returns the sync mode of the map, which determines when writes are synced to disk. The
+    /// sync mode of a new or opened map is `SyncMode::Manual`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    /// use extended_collections::durability::SyncMode;
+    ///
+    /// let map: BpMap<u32, u64> = BpMap::new("example_bp_map_sync_mode", 4, 8)?;
+    /// assert_eq!(map.sync_mode(), SyncMode::Manual);
+    /// # fs::remove_file("example_bp_map_sync_mode")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn sync_mode(&self) -> SyncMode {
+        self.sync_state.mode()
+    }
+
+    /// Sets the sync mode of the map, which determines when writes are synced to disk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    /// use extended_collections::durability::SyncMode;
+    ///
+    /// let mut map: BpMap<u32, u64> = BpMap::new("example_bp_map_set_sync_mode", 4, 8)?;
+    /// map.set_sync_mode(SyncMode::Always);
+    /// assert_eq!(map.sync_mode(), SyncMode::Always);
+    /// # fs::remove_file("example_bp_map_set_sync_mode")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_sync_mode(&mut self, sync_mode: SyncMode) {
+        self.sync_state.set_mode(sync_mode);
+    }
+
+    /// Hands all outstanding writes to the operating system without waiting for them to reach the
+    /// disk. Flushed writes survive a crash of the process, but not a crash of the machine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let mut map: BpMap<u32, u64> = BpMap::new("example_bp_map_flush", 4, 8)?;
+    /// map.insert(1, 1)?;
+    /// map.flush()?;
+    /// # fs::remove_file("example_bp_map_flush")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn flush(&mut self) -> Result<()> {
+        self.pager.flush()
+    }
+
+    /// Syncs all writes to disk and waits until they reach the disk. Synced writes survive a
+    /// crash of the machine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::BpMap;
+    ///
+    /// let mut map: BpMap<u32, u64> = BpMap::new("example_bp_map_sync", 4, 8)?;
+    /// map.insert(1, 1)?;
+    /// map.sync()?;
+    ///
+    /// let map: BpMap<u32, u64> = BpMap::open("example_bp_map_sync")?;
+    /// assert_eq!(map.len(), 1);
+    /// # fs::remove_file("example_bp_map_sync")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn sync(&mut self) -> Result<()> {
+        self.pager.sync()?;
+        self.sync_state.record_sync();
+        Ok(())
+    }
+}
+
+impl<T, U, C> Drop for BpMap<T, U, C> {
+    fn drop(&mut self) {
+        // errors cannot be reported when the map is dropped, so `close` should be used instead if
+        // they need to be handled.
+        if self.sync_state.should_sync_on_drop() {
+            let _ = self.sync();
+        } else {
+            let _ = self.flush();
+        }
+    }
+}
+
+impl<'a, T, U, C> IntoIterator for &'a mut BpMap<T, U, C>
+where
+    T: 'a + DeserializeOwned,
+    U: 'a + DeserializeOwned,
+{
+    type IntoIter = BpMapIterMut<'a, T, U>;
+    type Item = Result<(T, U)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut().unwrap()
+    }
+}
+
+/// Statistics of the pages of a `BpMap`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BpMapStats {
+    /// The number of pages in the file, including free pages.
+    pub page_count: usize,
+    /// The number of free pages, which are reused by later insertions and removed by `compact`.
+    pub free_page_count: usize,
+    /// The size of a page in bytes.
+    pub block_size: u64,
+}
+
+impl BpMapStats {
+    /// Returns the number of bytes of free pages, which is the number of bytes that `compact`
+    /// would reclaim.
+    pub fn free_size(&self) -> u64 {
+        self.free_page_count as u64 * self.block_size
+    }
+}
+
+/// A mutable iterator for `BpMap<T, U>`.
+///
+/// This iterator traverses the elements of the map in ascending order and yields owned entries.
+pub struct BpMapIterMut<'a, T, U> {
+    pager: &'a mut Pager<T, U>,
+    entries: vec::IntoIter<Entry<T, Value<U>>>,
+    next_leaf: Option<usize>,
 }
 
 impl<'a, T, U> Iterator for BpMapIterMut<'a, T, U>
@@ -760,36 +1396,41 @@ where
     type Item = Result<(T, U)>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.curr_index >= self.curr_node.len {
-            match self.curr_node.next_leaf {
-                Some(next_page) => {
-                    self.curr_node = {
-                        match self.pager.get_page(next_page) {
-                            Ok(node) => match node {
-                                Node::Leaf(leaf_node) => leaf_node,
-                                _ => panic!("Expected a leaf node."),
-                            },
-                            Err(error) => return Some(Err(error)),
-                        }
-                    };
-                    self.curr_index = 0;
+        loop {
+            if let Some(entry) = self.entries.next() {
+                let key = entry.key;
+                return Some(self.pager.get_value(entry.value).map(|value| (key, value)));
+            }
+
+            let next_page = self.next_leaf?;
+            match self.pager.get_page(next_page) {
+                Ok(Node::Leaf(leaf_node)) => {
+                    self.entries = leaf_node.entries.into_iter();
+                    self.next_leaf = leaf_node.next_leaf;
+                }
+                Ok(_) => {
+                    self.next_leaf = None;
+                    return Some(Err(self.pager.corruption(next_page)));
+                }
+                Err(error) => {
+                    self.next_leaf = None;
+                    return Some(Err(error));
                 }
-                None => return None,
             }
         }
-
-        self.curr_index += 1;
-        self.curr_node.entries[self.curr_index - 1]
-            .take()
-            .map(|entry| Ok((entry.key, entry.value)))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{BpMap, Result};
-    use std::fs;
+    use super::{BpMap, Error, Result};
+    use crate::bp_tree::node::PageFormat;
+    use crate::durability::SyncMode;
+    use std::fs::{self, OpenOptions};
+    use std::io::{Seek, SeekFrom, Write};
     use std::panic;
+    use std::path::Path;
+    use std::time::Duration;
 
     fn teardown(test_name: &str) {
         fs::remove_file(test_name).ok();
@@ -846,6 +1487,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sync_mode() {
+        let test_name = "test_sync_mode";
+        run_test(
+            || {
+                let mut map: BpMap<u32, u64> = BpMap::new(test_name, 4, 8)?;
+                map.set_sync_mode(SyncMode::Always);
+                map.insert(1, 1)?;
+                assert!(!map.sync_state.is_dirty());
+
+                map.set_sync_mode(SyncMode::Interval(Duration::from_secs(3600)));
+                map.insert(2, 2)?;
+                assert!(map.sync_state.is_dirty());
+                map.sync()?;
+                assert!(!map.sync_state.is_dirty());
+
+                map.set_sync_mode(SyncMode::Manual);
+                map.remove(&1)?;
+                assert!(map.sync_state.is_dirty());
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_drop() {
+        let test_name = "test_drop";
+        run_test(
+            || {
+                let mut map: BpMap<u32, u64> = BpMap::new_mmap(test_name, 4, 8)?;
+                map.set_sync_mode(SyncMode::Interval(Duration::from_secs(3600)));
+                for key in 0..100 {
+                    map.insert(key, u64::from(key))?;
+                }
+                drop(map);
+
+                let mut map: BpMap<u32, u64> = BpMap::open(test_name)?;
+                assert_eq!(map.len(), 100);
+                assert_eq!(map.get(&99)?, Some(99));
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_close() {
+        let test_name = "test_close";
+        run_test(
+            || {
+                let mut map: BpMap<u32, u64> = BpMap::new(test_name, 4, 8)?;
+                map.insert(1, 1)?;
+                map.insert(2, 2)?;
+                map.close()?;
+
+                let mut map: BpMap<u32, u64> = BpMap::open(test_name)?;
+                assert_eq!(map.len(), 2);
+                assert_eq!(map.get(&1)?, Some(1));
+                assert_eq!(map.get(&2)?, Some(2));
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
     #[test]
     fn test_free_node() {
         let test_name = "test_free_node";
@@ -903,13 +1610,52 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
-    fn test_insert_panic() {
-        let test_name = "test_insert_panic";
+    fn test_insert_overflow_value() {
+        let test_name = "test_insert_overflow_value";
         run_test(
             || {
-                let mut map: BpMap<u32, Box<[u32]>> = BpMap::new(test_name, 4, 12)?;
-                map.insert(0, Box::new([0, 1]))?;
+                let mut map: BpMap<u32, Vec<u32>> = BpMap::new(test_name, 4, 12)?;
+                let large_value: Vec<u32> = (0..10_000).collect();
+                map.insert(0, vec![0, 1])?;
+                map.insert(1, large_value.clone())?;
+                assert_eq!(map.get(&0)?, Some(vec![0, 1]));
+                assert_eq!(map.get(&1)?, Some(large_value.clone()));
+                assert_eq!(
+                    map.iter_mut()?
+                        .map(|value| value.unwrap())
+                        .collect::<Vec<(u32, Vec<u32>)>>(),
+                    vec![(0, vec![0, 1]), (1, large_value.clone())],
+                );
+
+                let pages = map.pager.get_page_count();
+                assert_eq!(map.insert(1, vec![1])?, Some((1, large_value.clone())));
+                assert_eq!(map.insert(1, large_value.clone())?, Some((1, vec![1])));
+                assert_eq!(map.pager.get_page_count(), pages);
+                assert_eq!(map.remove(&1)?, Some((1, large_value)));
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_insert_variable_length_entries() {
+        let test_name = "test_insert_variable_length_entries";
+        run_test(
+            || {
+                let mut map: BpMap<u32, String> = BpMap::new(test_name, 4, 512)?;
+                let value = |key: u32| "a".repeat((key as usize * 37) % 600);
+                for key in 0..500 {
+                    map.insert(key, value(key))?;
+                }
+                for key in (0..500).filter(|key| key % 2 == 0) {
+                    assert_eq!(map.remove(&key)?, Some((key, value(key))));
+                }
+                assert_eq!(map.len(), 250);
+                for key in 0..500 {
+                    let expected = if key % 2 == 0 { None } else { Some(value(key)) };
+                    assert_eq!(map.get(&key)?, expected);
+                }
                 Ok(())
             },
             test_name,
@@ -947,6 +1693,100 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_try_insert() {
+        let test_name = "test_try_insert";
+        run_test(
+            || {
+                let mut map: BpMap<u32, u64> = BpMap::with_degrees(test_name, 4, 8, 3, 3)?;
+                for key in 0..100 {
+                    assert_eq!(map.try_insert(key, u64::from(key))?, None);
+                }
+                for key in 0..100 {
+                    assert_eq!(map.try_insert(key, 0)?, Some((key, 0)));
+                    assert_eq!(map.get(&key)?, Some(u64::from(key)));
+                }
+                assert_eq!(map.len(), 100);
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_comparator() {
+        let test_name = "test_comparator";
+        run_test(
+            || {
+                let compare = |a: &u32, b: &u32| b.cmp(a);
+                let mut map = BpMap::with_comparator(test_name, 4, 8, compare)?;
+                for key in 0..1000 {
+                    map.insert(key, u64::from(key))?;
+                }
+                for key in (0..1000).filter(|key| key % 2 == 0) {
+                    assert_eq!(map.remove(&key)?, Some((key, u64::from(key))));
+                }
+                assert_eq!(map.min()?, Some(999));
+                assert_eq!(map.max()?, Some(1));
+                map.close()?;
+
+                let mut map: BpMap<u32, u64, _> = BpMap::open_with_comparator(test_name, compare)?;
+                assert_eq!(map.len(), 500);
+                assert_eq!(map.get(&1)?, Some(1));
+                assert_eq!(map.get(&2)?, None);
+                let keys = map
+                    .iter_mut()?
+                    .map(|entry| entry.map(|(key, _)| key))
+                    .collect::<Result<Vec<u32>>>()?;
+                assert_eq!(
+                    keys,
+                    (0..1000)
+                        .rev()
+                        .filter(|key| key % 2 == 1)
+                        .collect::<Vec<u32>>()
+                );
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_insert_key_too_large() {
+        let test_name = "test_insert_key_too_large";
+        run_test(
+            || {
+                let mut map: BpMap<String, u64> = BpMap::new(test_name, 16, 8)?;
+                let key = String::from("a key that is larger than the maximum key size");
+                match map.insert(key.clone(), 1) {
+                    Err(Error::KeyTooLarge { max, actual }) => {
+                        assert_eq!(max, 16);
+                        assert_eq!(actual, key.len() as u64 + 8);
+                    }
+                    _ => panic!("Expected a key too large error."),
+                }
+                match map.try_insert(key, 1) {
+                    Err(Error::KeyTooLarge { .. }) => {}
+                    _ => panic!("Expected a key too large error."),
+                }
+                assert!(map.is_empty());
+                assert_eq!(map.pager.get_page_count(), 1);
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_new_key_size_too_large() {
+        let test_name = "test_new_key_size_too_large";
+        match BpMap::<u32, u64>::new(test_name, 4096, 8) {
+            Err(Error::KeyTooLarge { actual, .. }) => assert_eq!(actual, 4096),
+            _ => panic!("Expected a key too large error."),
+        }
+        assert!(fs::metadata(test_name).is_err());
+    }
+
     #[test]
     fn test_remove() {
         let test_name = "test_remove";
@@ -1005,4 +1845,429 @@ mod tests {
             test_name,
         );
     }
+
+    #[test]
+    fn test_mmap() {
+        let test_name = "test_mmap";
+        run_test(
+            || {
+                let mut map: BpMap<u32, u64> = BpMap::new_mmap(test_name, 4, 8)?;
+                for key in 0..1000 {
+                    assert_eq!(map.insert(key, u64::from(key) * 2)?, None);
+                }
+                for key in (0..1000).filter(|key| key % 3 == 0) {
+                    assert_eq!(map.remove(&key)?, Some((key, u64::from(key) * 2)));
+                }
+                assert_eq!(map.len(), 666);
+                assert_eq!(
+                    map.iter_mut()?
+                        .map(|value| value.unwrap())
+                        .collect::<Vec<(u32, u64)>>(),
+                    (0..1000)
+                        .filter(|key| key % 3 != 0)
+                        .map(|key| (key, u64::from(key) * 2))
+                        .collect::<Vec<(u32, u64)>>(),
+                );
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_mmap_close() {
+        let test_name = "test_mmap_close";
+        run_test(
+            || {
+                let mut map: BpMap<u32, u64> = BpMap::new_mmap(test_name, 4, 8)?;
+                for key in 0..100 {
+                    map.insert(key, u64::from(key))?;
+                }
+                map.close()?;
+
+                let mut map: BpMap<u32, u64> = BpMap::open(test_name)?;
+                assert_eq!(map.len(), 100);
+                assert_eq!(map.get(&50)?, Some(50));
+                map.insert(100, 100)?;
+                map.close()?;
+
+                let mut map: BpMap<u32, u64> = BpMap::open_mmap(test_name)?;
+                assert_eq!(map.len(), 101);
+                assert_eq!(map.get(&100)?, Some(100));
+                map.clear()?;
+                assert_eq!(map.min()?, None);
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_insert_replace_larger_values() {
+        let test_name = "test_insert_replace_larger_values";
+        run_test(
+            || {
+                let mut map: BpMap<u32, Vec<u8>> = BpMap::new(test_name, 4, 512)?;
+                for key in 0..100 {
+                    map.insert(key, vec![0; 8])?;
+                }
+                for key in 0..100 {
+                    assert_eq!(map.insert(key, vec![1; 500])?, Some((key, vec![0; 8])));
+                }
+                assert_eq!(map.len(), 100);
+                for key in 0..100 {
+                    assert_eq!(map.get(&key)?, Some(vec![1; 500]));
+                }
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_prefix_compression() {
+        let test_name = "test_prefix_compression";
+        run_test(
+            || {
+                let key = |index: u32| format!("users/profiles/{:06}", index);
+                let mut page_counts = Vec::new();
+                for format in &[PageFormat::Uncompressed, PageFormat::PrefixCompressed] {
+                    let mut map: BpMap<String, u64> = BpMap::new(test_name, 32, 8)?;
+                    map.pager.set_page_format(*format)?;
+                    for index in 0..2000 {
+                        map.insert(key(index), u64::from(index))?;
+                    }
+                    for index in (0..2000).filter(|index| index % 3 == 0) {
+                        assert_eq!(
+                            map.remove(&key(index))?,
+                            Some((key(index), u64::from(index)))
+                        );
+                    }
+                    for index in 0..2000 {
+                        let expected = if index % 3 == 0 {
+                            None
+                        } else {
+                            Some(u64::from(index))
+                        };
+                        assert_eq!(map.get(&key(index))?, expected);
+                    }
+                    page_counts.push(map.pager.get_page_count());
+                    map.close()?;
+
+                    let mut map: BpMap<String, u64> = BpMap::open(test_name)?;
+                    assert_eq!(map.pager.get_page_format(), *format);
+                    assert_eq!(map.get(&key(1))?, Some(1));
+                    assert_eq!(map.iter_mut()?.count(), 1333);
+                    fs::remove_file(test_name)?;
+                }
+                assert!(page_counts[1] < page_counts[0]);
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_open_unsupported_format() {
+        let test_name = "test_open_unsupported_format";
+        run_test(
+            || {
+                let map: BpMap<u32, u64> = BpMap::new(test_name, 4, 8)?;
+                map.close()?;
+
+                let mut file = OpenOptions::new().write(true).open(test_name)?;
+                file.seek(SeekFrom::Start(68))?;
+                file.write_all(&[0, 0, 0, 9])?;
+                drop(file);
+
+                match BpMap::<u32, u64>::open(test_name) {
+                    Err(Error::UnsupportedVersion { path, version }) => {
+                        assert_eq!(path, Path::new(test_name));
+                        assert_eq!(version, 9);
+                    }
+                    _ => panic!("Expected an unsupported version error."),
+                }
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_with_block_size() {
+        let test_name = "test_with_block_size";
+        run_test(
+            || {
+                let mut map: BpMap<u32, Vec<u8>> =
+                    BpMap::with_block_size(test_name, 4, 4096, 16384)?;
+                for key in 0..500 {
+                    map.insert(key, vec![key as u8; 3000])?;
+                }
+                assert_eq!(map.block_size(), 16384);
+                let pages = map.pager.get_page_count() as u64;
+                map.close()?;
+                assert_eq!(fs::metadata(test_name)?.len(), 72 + pages * 16384);
+
+                let mut map: BpMap<u32, Vec<u8>> = BpMap::open(test_name)?;
+                assert_eq!(map.block_size(), 16384);
+                assert_eq!(map.len(), 500);
+                for key in 0..500 {
+                    assert_eq!(map.get(&key)?, Some(vec![key as u8; 3000]));
+                }
+                for key in 0..250 {
+                    assert_eq!(map.remove(&key)?, Some((key, vec![key as u8; 3000])));
+                }
+                assert_eq!(map.min()?, Some(250));
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_with_block_size_invalid() {
+        let test_name = "test_with_block_size_invalid";
+        run_test(
+            || {
+                for block_size in &[0, 256, 5000, 131_072] {
+                    match BpMap::<u32, u64>::with_block_size(test_name, 4, 8, *block_size) {
+                        Err(Error::InvalidBlockSize { block_size: actual }) => {
+                            assert_eq!(actual, *block_size);
+                        }
+                        _ => panic!("Expected an invalid block size error."),
+                    }
+                }
+                match BpMap::<u32, u64>::with_block_size(test_name, 200, 8, 512) {
+                    Err(Error::KeyTooLarge { .. }) => {}
+                    _ => panic!("Expected a key too large error."),
+                }
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_open_invalid_block_size() {
+        let test_name = "test_open_invalid_block_size";
+        run_test(
+            || {
+                let map: BpMap<u32, u64> = BpMap::with_block_size(test_name, 4, 8, 8192)?;
+                map.close()?;
+
+                let mut file = OpenOptions::new().write(true).open(test_name)?;
+                file.seek(SeekFrom::Start(67))?;
+                file.write_all(&[20])?;
+                drop(file);
+
+                match BpMap::<u32, u64>::open(test_name) {
+                    Err(Error::Corruption { path, offset }) => {
+                        assert_eq!(path, Path::new(test_name));
+                        assert_eq!(offset, 67);
+                    }
+                    _ => panic!("Expected a corruption error."),
+                }
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_open_default_block_size() {
+        let test_name = "test_open_default_block_size";
+        run_test(
+            || {
+                let mut map: BpMap<u32, u64> = BpMap::new(test_name, 4, 8)?;
+                map.insert(1, 1)?;
+                map.close()?;
+
+                // files written before the block size was stored have a zero in its place
+                let mut file = OpenOptions::new().write(true).open(test_name)?;
+                file.seek(SeekFrom::Start(67))?;
+                file.write_all(&[0])?;
+                drop(file);
+
+                let mut map: BpMap<u32, u64> = BpMap::open(test_name)?;
+                assert_eq!(map.block_size(), 4096);
+                assert_eq!(map.get(&1)?, Some(1));
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let test_name = "test_stats";
+        run_test(
+            || {
+                let mut map: BpMap<u32, u64> = BpMap::with_degrees(test_name, 4, 8, 3, 3)?;
+                let stats = map.stats()?;
+                assert_eq!(stats.page_count, 1);
+                assert_eq!(stats.free_page_count, 0);
+                assert_eq!(stats.free_size(), 0);
+
+                for key in 0..100 {
+                    map.insert(key, 0)?;
+                }
+                let page_count = map.stats()?.page_count;
+                for key in 0..100 {
+                    map.remove(&key)?;
+                }
+                let stats = map.stats()?;
+                assert_eq!(stats.page_count, page_count);
+                assert_eq!(stats.free_page_count, page_count - 1);
+                assert_eq!(stats.free_size(), (page_count as u64 - 1) * 4096);
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    fn check_compact(map: &mut BpMap<u32, Vec<u8>>, test_name: &str) -> Result<()> {
+        let value = |key: u32| vec![key as u8; if key.is_multiple_of(10) { 10_000 } else { 4 }];
+        for key in 0..1000 {
+            map.insert(key, value(key))?;
+        }
+        for key in (0..1000).filter(|key: &u32| !key.is_multiple_of(4)) {
+            map.remove(&key)?;
+        }
+        let stats = map.stats()?;
+        assert!(stats.free_page_count > 0);
+
+        map.compact()?;
+        let compacted_stats = map.stats()?;
+        assert_eq!(compacted_stats.free_page_count, 0);
+        assert_eq!(
+            compacted_stats.page_count,
+            stats.page_count - stats.free_page_count
+        );
+        assert_eq!(
+            fs::metadata(test_name)?.len(),
+            72 + compacted_stats.page_count as u64 * 4096
+        );
+        assert!(!Path::new(&format!("{}.compact", test_name)).exists());
+
+        let entries = map.iter_mut()?.collect::<Result<Vec<_>>>()?;
+        let expected: Vec<_> = (0..1000)
+            .filter(|key: &u32| key.is_multiple_of(4))
+            .map(|key| (key, value(key)))
+            .collect();
+        assert_eq!(entries, expected);
+
+        // the compacted map remains writable
+        for key in (0..1000).filter(|key: &u32| !key.is_multiple_of(4)) {
+            map.insert(key, value(key))?;
+        }
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.get(&990)?, Some(value(990)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact() {
+        let test_name = "test_compact";
+        run_test(
+            || {
+                let mut map: BpMap<u32, Vec<u8>> = BpMap::new(test_name, 4, 16)?;
+                check_compact(&mut map, test_name)?;
+                map.close()?;
+
+                let mut map: BpMap<u32, Vec<u8>> = BpMap::open(test_name)?;
+                assert_eq!(map.len(), 1000);
+                assert_eq!(map.get(&10)?, Some(vec![10; 10_000]));
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_compact_mmap() {
+        let test_name = "test_compact_mmap";
+        run_test(
+            || {
+                let mut map: BpMap<u32, Vec<u8>> = BpMap::new_mmap(test_name, 4, 16)?;
+                check_compact(&mut map, test_name)
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_restore() {
+        let test_name = "test_checkpoint_restore";
+        let checkpoint_name = "test_checkpoint_restore_checkpoint";
+        let restored_name = "test_checkpoint_restore_restored";
+        run_test(
+            || {
+                let mut map: BpMap<u32, u64> = BpMap::new_mmap(test_name, 4, 8)?;
+                for key in 0..1000 {
+                    map.insert(key, u64::from(key))?;
+                }
+                map.checkpoint(checkpoint_name)?;
+                match map.checkpoint(checkpoint_name) {
+                    Err(Error::FileIOError {
+                        path, operation, ..
+                    }) => {
+                        assert_eq!(path, Path::new(checkpoint_name));
+                        assert_eq!(operation, "create");
+                    }
+                    _ => panic!("Expected a file error."),
+                }
+                for key in 0..500 {
+                    map.remove(&key)?;
+                }
+
+                let mut map: BpMap<u32, u64> = BpMap::restore(checkpoint_name, restored_name)?;
+                assert_eq!(map.len(), 1000);
+                for key in 0..1000 {
+                    assert_eq!(map.get(&key)?, Some(u64::from(key)));
+                }
+                Ok(())
+            },
+            test_name,
+        );
+        teardown(checkpoint_name);
+        teardown(restored_name);
+    }
+
+    #[test]
+    fn test_corruption() {
+        let test_name = "test_corruption";
+        run_test(
+            || {
+                let mut map: BpMap<u32, u64> = BpMap::new(test_name, 4, 8)?;
+                for key in 0..100 {
+                    map.insert(key, u64::from(key))?;
+                }
+                let root_page = map.pager.get_root_page();
+                let offset = match map.pager.corruption(root_page) {
+                    Error::Corruption { offset, .. } => offset,
+                    _ => panic!("Expected a corruption error."),
+                };
+                map.close()?;
+
+                let mut file = OpenOptions::new().write(true).open(test_name)?;
+                file.seek(SeekFrom::Start(offset))?;
+                file.write_all(&[255; 16])?;
+                drop(file);
+
+                let mut map: BpMap<u32, u64> = BpMap::open(test_name)?;
+                match map.get(&0) {
+                    Err(Error::Corruption {
+                        path,
+                        offset: corrupted_offset,
+                    }) => {
+                        assert_eq!(path, Path::new(test_name));
+                        assert_eq!(corrupted_offset, offset);
+                    }
+                    _ => panic!("Expected a corruption error."),
+                }
+                Ok(())
+            },
+            test_name,
+        );
+    }
 }
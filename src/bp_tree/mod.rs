@@ -1,4 +1,4 @@
-//! Disk-resident N-ary tree.
+//! Disk-resident N-ary tree and radix tree.
 
 macro_rules! init_array(
     ($ty:ty, $len:expr, $val:expr) => (
@@ -15,6 +15,9 @@ macro_rules! init_array(
 mod map;
 mod node;
 mod pager;
+mod radix;
+mod storage;
 
-pub use self::map::BpMap;
+pub use self::map::{BpMap, BpMapStats};
 pub use self::pager::{Error, Result};
+pub use self::radix::{RadixBpMap, RadixBpMapIterMut};
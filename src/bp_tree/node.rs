@@ -1,15 +1,329 @@
+use crate::compare::Compare;
 use crate::entry::Entry;
+use bincode::{self, deserialize, serialize, serialized_size};
+use byteorder::{BigEndian, ByteOrder};
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
 use serde_derive::{Deserialize, Serialize};
 use std::borrow::Borrow;
 use std::cmp::{self, Ordering};
+use std::io;
 use std::marker::PhantomData;
 use std::mem;
 
-const U64_SIZE: u64 = mem::size_of::<u64>() as u64;
-const OPT_U64_SIZE: u64 = mem::size_of::<Option<u64>>() as u64;
-pub const BLOCK_SIZE: u64 = 4096;
+// The size of a page of a tree that is created without specifying a block size, and of the pages
+// of files that were written before the block size was stored.
+pub const DEFAULT_BLOCK_SIZE: u64 = 4096;
+
+// The block size of a tree is a power of two between these bounds.
+pub const MIN_BLOCK_SIZE: u64 = 512;
+pub const MAX_BLOCK_SIZE: u64 = 65536;
+
+// Each page starts with a tag identifying the type of the node.
+const LEAF_TAG: u8 = 0;
+const INTERNAL_TAG: u8 = 1;
+const FREE_TAG: u8 = 2;
+const OVERFLOW_TAG: u8 = 3;
+
+// A page encodes a missing page index as `u64::MAX`.
+const NO_PAGE: u64 = u64::MAX;
+
+// A slot in the slot directory of a page is the offset and the length of a cell, each encoded as a
+// u32 (4 bytes).
+const SLOT_SIZE: u64 = 8;
+
+// tag (1 byte), length (4 bytes), and next leaf (8 bytes)
+const LEAF_HEADER_SIZE: u64 = 13;
+
+// tag (1 byte) and length (4 bytes)
+const INTERNAL_HEADER_SIZE: u64 = 5;
+
+// offset (1 byte) and length (2 bytes) of the common key prefix of a prefix-compressed page
+const PREFIX_HEADER_SIZE: u64 = 3;
+
+// tag (1 byte), next page (8 bytes), and length (4 bytes)
+const OVERFLOW_HEADER_SIZE: u64 = 13;
+
+// A `Value` is encoded with a u32 tag (4 bytes), and an overflow value additionally encodes its page
+// and length as two u64 (16 bytes).
+const VALUE_TAG_SIZE: u64 = 4;
+const OVERFLOW_VALUE_SIZE: u64 = VALUE_TAG_SIZE + 16;
+
+// The offsets in a serialized key at which the common key prefix of a page may start. bincode
+// encodes strings and vectors with a u64 length before their contents, so keys of different
+// lengths only share a prefix after the length.
+const PREFIX_OFFSETS: [usize; 2] = [0, 8];
+
+/// The format of the pages of a B+ tree. The format is stored in the header of the file so that
+/// files written in an older format can still be read and modified.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PageFormat {
+    /// Each cell stores its serialized key in its entirety.
+    Uncompressed,
+    /// The longest common prefix of the serialized keys of a page is stored once in the header of
+    /// the page and omitted from its cells.
+    PrefixCompressed,
+}
+
+impl PageFormat {
+    pub const LATEST: PageFormat = PageFormat::PrefixCompressed;
+
+    pub fn from_version(version: u32) -> Option<Self> {
+        match version {
+            0 => Some(PageFormat::Uncompressed),
+            1 => Some(PageFormat::PrefixCompressed),
+            _ => None,
+        }
+    }
+
+    pub fn version(self) -> u32 {
+        match self {
+            PageFormat::Uncompressed => 0,
+            PageFormat::PrefixCompressed => 1,
+        }
+    }
+
+    fn get_leaf_header_size(self) -> u64 {
+        match self {
+            PageFormat::Uncompressed => LEAF_HEADER_SIZE,
+            PageFormat::PrefixCompressed => LEAF_HEADER_SIZE + PREFIX_HEADER_SIZE,
+        }
+    }
+}
+
+/// The layout of the pages of a B+ tree, which consists of the format of the pages and their size
+/// in bytes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PageLayout {
+    pub format: PageFormat,
+    pub block_size: u64,
+}
+
+impl PageLayout {
+    pub fn new(format: PageFormat, block_size: u64) -> Self {
+        PageLayout { format, block_size }
+    }
+
+    // Returns true if `block_size` is a power of two within the supported bounds.
+    pub fn is_valid_block_size(block_size: u64) -> bool {
+        block_size.is_power_of_two() && (MIN_BLOCK_SIZE..=MAX_BLOCK_SIZE).contains(&block_size)
+    }
+
+    // Returns the largest cell in a leaf node. Bounding cells to a quarter of a page guarantees
+    // that both halves of a split leaf node fit in a page.
+    fn get_max_cell_size(block_size: u64) -> u64 {
+        (block_size - LEAF_HEADER_SIZE) / 4 - SLOT_SIZE
+    }
+
+    // Returns the number of bytes of a value that are stored in an overflow page.
+    pub fn get_overflow_capacity(self) -> usize {
+        (self.block_size - OVERFLOW_HEADER_SIZE) as usize
+    }
+
+    // Returns the maximum size of a leaf node without prefix compression. A leaf node that is
+    // within this size can always be split into two nodes that fit in a page after inserting an
+    // entry, even if the entry shares no prefix with the other keys of the node.
+    fn get_max_uncompressed_leaf_size(self) -> u64 {
+        let header_size = self.format.get_leaf_header_size();
+        let max_cell_size = Self::get_max_cell_size(self.block_size);
+        header_size + 2 * (self.block_size - header_size - max_cell_size - SLOT_SIZE)
+    }
+}
+
+/// The value of an entry in a leaf node. Values that are too large to be stored in a leaf node are
+/// stored in a chain of overflow pages.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Value<U> {
+    Inline(U),
+    Overflow { page: usize, len: u64 },
+}
+
+fn invalid_page(message: &str) -> bincode::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message).into()
+}
+
+fn read_page_index(page: &[u8]) -> Option<usize> {
+    match BigEndian::read_u64(page) {
+        NO_PAGE => None,
+        index => Some(index as usize),
+    }
+}
+
+fn write_page_index(page: &mut [u8], index: Option<usize>) {
+    BigEndian::write_u64(page, index.map_or(NO_PAGE, |index| index as u64));
+}
+
+fn get_common_prefix_len(left: &[u8], right: &[u8]) -> usize {
+    left.iter()
+        .zip(right)
+        .take_while(|(left, right)| left == right)
+        .count()
+}
+
+// Returns the length of the common prefix of the keys yielded by `keys` that starts at `offset`
+// for each prefix of `keys`. The length is zero if a key is shorter than `offset`.
+fn get_running_prefix_lens<'a, I>(mut keys: I, offset: usize) -> Vec<usize>
+where
+    I: Iterator<Item = &'a [u8]>,
+{
+    let mut ret = Vec::new();
+    let first = match keys.next() {
+        Some(first) if first.len() >= offset => first,
+        Some(_) => return vec![0; 1 + keys.count()],
+        None => return ret,
+    };
+    let mut prefix_len = first.len() - offset;
+    ret.push(prefix_len);
+    for key in keys {
+        prefix_len = if key.len() >= offset {
+            cmp::min(
+                prefix_len,
+                get_common_prefix_len(&first[offset..], &key[offset..]),
+            )
+        } else {
+            0
+        };
+        ret.push(prefix_len);
+    }
+    ret
+}
+
+// Returns the offset and the length of the longest common prefix of `keys`.
+fn get_common_prefix(keys: &[Vec<u8>]) -> (usize, usize) {
+    PREFIX_OFFSETS
+        .iter()
+        .map(|offset| {
+            let prefix_lens = get_running_prefix_lens(keys.iter().map(Vec::as_slice), *offset);
+            (*offset, prefix_lens.last().cloned().unwrap_or(0))
+        })
+        .fold(
+            (0, 0),
+            |best, curr| if curr.1 > best.1 { curr } else { best },
+        )
+}
+
+// Removes the common prefix from a serialized key.
+fn strip_prefix(key: &[u8], prefix: (usize, usize)) -> Vec<u8> {
+    let (offset, len) = prefix;
+    let mut ret = Vec::with_capacity(key.len() - len);
+    ret.extend_from_slice(&key[..offset]);
+    ret.extend_from_slice(&key[offset + len..]);
+    ret
+}
+
+// Writes the common prefix of `keys` after the header of a prefix-compressed page at `offset`,
+// and returns the offset after the prefix and the prefix.
+fn encode_prefix(page: &mut [u8], offset: usize, keys: &[Vec<u8>]) -> (usize, (usize, usize)) {
+    let prefix = get_common_prefix(keys);
+    let (prefix_offset, prefix_len) = prefix;
+    let start = offset + PREFIX_HEADER_SIZE as usize;
+    page[offset] = prefix_offset as u8;
+    BigEndian::write_u16(&mut page[offset + 1..], prefix_len as u16);
+    if prefix_len > 0 {
+        page[start..start + prefix_len]
+            .copy_from_slice(&keys[0][prefix_offset..prefix_offset + prefix_len]);
+    }
+    (start + prefix_len, prefix)
+}
+
+fn decode_prefix(page: &[u8], offset: usize) -> bincode::Result<(usize, usize, &[u8])> {
+    let prefix_offset = page[offset] as usize;
+    let prefix_len = BigEndian::read_u16(&page[offset + 1..]) as usize;
+    let start = offset + PREFIX_HEADER_SIZE as usize;
+    if start + prefix_len > page.len() {
+        return Err(invalid_page("Error: key prefix exceeds the page."));
+    }
+    Ok((
+        start + prefix_len,
+        prefix_offset,
+        &page[start..start + prefix_len],
+    ))
+}
+
+// Writes the slot directory of `cells` at `offset` and packs the cells at the end of the page.
+fn encode_cells(page: &mut [u8], mut offset: usize, cells: &[Vec<u8>]) -> bincode::Result<()> {
+    let mut end = page.len();
+    for cell in cells {
+        if offset + SLOT_SIZE as usize + cell.len() > end {
+            return Err(invalid_page("Error: node does not fit in a page."));
+        }
+        end -= cell.len();
+        page[end..end + cell.len()].copy_from_slice(cell);
+        BigEndian::write_u32(&mut page[offset..], end as u32);
+        BigEndian::write_u32(&mut page[offset + 4..], cell.len() as u32);
+        offset += SLOT_SIZE as usize;
+    }
+    Ok(())
+}
+
+// Reads `len` cells using the slot directory at `offset`. The common key prefix is inserted at
+// `prefix_offset` of each cell before it is deserialized.
+fn decode_cells<V>(
+    page: &[u8],
+    offset: usize,
+    len: usize,
+    prefix_offset: usize,
+    prefix: &[u8],
+) -> bincode::Result<Vec<V>>
+where
+    V: DeserializeOwned,
+{
+    if offset + len * SLOT_SIZE as usize > page.len() {
+        return Err(invalid_page("Error: slot directory exceeds the page."));
+    }
+    (0..len)
+        .map(|index| {
+            let slot = offset + index * SLOT_SIZE as usize;
+            let cell_offset = BigEndian::read_u32(&page[slot..]) as usize;
+            let cell_len = BigEndian::read_u32(&page[slot + 4..]) as usize;
+            if cell_offset + cell_len > page.len() {
+                return Err(invalid_page("Error: cell exceeds the page."));
+            }
+            let cell = &page[cell_offset..cell_offset + cell_len];
+            if prefix.is_empty() {
+                return deserialize(cell);
+            }
+            if cell_len < prefix_offset {
+                return Err(invalid_page(
+                    "Error: cell is shorter than its key prefix offset.",
+                ));
+            }
+            let mut buffer = Vec::with_capacity(cell_len + prefix.len());
+            buffer.extend_from_slice(&cell[..prefix_offset]);
+            buffer.extend_from_slice(prefix);
+            buffer.extend_from_slice(&cell[prefix_offset..]);
+            deserialize(&buffer)
+        })
+        .collect()
+}
+
+// The serialized key and the size of the cell and slot of an entry of a leaf node.
+struct CellLayout {
+    key: Vec<u8>,
+    size: u64,
+}
+
+// Returns the size of a leaf node with `cells` and the size of the leaf node without prefix
+// compression.
+fn get_leaf_sizes(cells: &[CellLayout], layout: PageLayout) -> (u64, u64) {
+    let uncompressed_size =
+        layout.format.get_leaf_header_size() + cells.iter().map(|cell| cell.size).sum::<u64>();
+    match layout.format {
+        PageFormat::Uncompressed => (uncompressed_size, uncompressed_size),
+        PageFormat::PrefixCompressed if cells.is_empty() => (uncompressed_size, uncompressed_size),
+        PageFormat::PrefixCompressed => {
+            let keys: Vec<Vec<u8>> = cells.iter().map(|cell| cell.key.clone()).collect();
+            let (_, prefix_len) = get_common_prefix(&keys);
+            let saved_size = (cells.len() as u64 - 1) * prefix_len as u64;
+            (uncompressed_size - saved_size, uncompressed_size)
+        }
+    }
+}
+
+fn fits_in_page(sizes: (u64, u64), layout: PageLayout) -> bool {
+    sizes.0 <= layout.block_size && sizes.1 <= layout.get_max_uncompressed_leaf_size()
+}
 
-#[derive(Serialize, Deserialize)]
 pub struct InternalNode<T, U> {
     pub len: usize,
     pub keys: Box<[Option<T>]>,
@@ -18,23 +332,22 @@ pub struct InternalNode<T, U> {
 }
 
 impl<T, U> InternalNode<T, U> {
-    // 1) a usize is encoded as u64 (8 bytes)
-    // 2) a boxed slice is encoded as a tuple of u64 (8 bytes) and the items
+    // 1) a pointer is encoded as u64 (8 bytes)
+    // 2) each key has a slot and is at most `key_size` bytes
+    // 3) a prefix-compressed page stores its common key prefix at most once more than the keys
     #[inline]
-    fn get_constant_size() -> u64 {
-        U64_SIZE * 4 + mem::size_of::<PhantomData<U>>() as u64
+    fn get_payload_size(key_size: u64) -> u64 {
+        mem::size_of::<u64>() as u64 + SLOT_SIZE + key_size
     }
 
     #[inline]
-    fn get_payload_size(key_size: u64) -> u64 {
-        let option_size = mem::size_of::<Option<T>>() as u64;
-        let entry_size = mem::size_of::<T>() as u64;
-        U64_SIZE + key_size + option_size - entry_size
+    fn get_constant_size() -> u64 {
+        INTERNAL_HEADER_SIZE + PREFIX_HEADER_SIZE + mem::size_of::<u64>() as u64
     }
 
     #[inline]
-    pub fn get_degree(key_size: u64) -> usize {
-        let payload_capacity = BLOCK_SIZE - Self::get_constant_size();
+    pub fn get_degree(block_size: u64, key_size: u64) -> usize {
+        let payload_capacity = block_size.saturating_sub(Self::get_constant_size());
         (payload_capacity / Self::get_payload_size(key_size)) as usize
     }
 
@@ -52,14 +365,15 @@ impl<T, U> InternalNode<T, U> {
         }
     }
 
-    pub fn insert(
+    pub fn insert<C>(
         &mut self,
         mut new_key: T,
         mut new_pointer: usize,
         is_right: bool,
+        compare: &C,
     ) -> Option<(T, Node<T, U>)>
     where
-        T: Ord,
+        C: Compare<T>,
     {
         let internal_degree = self.keys.len();
         let offset = is_right as usize;
@@ -67,7 +381,7 @@ impl<T, U> InternalNode<T, U> {
         if self.len < internal_degree {
             let mut index = 0;
             while let Some(ref mut key) = self.keys[index] {
-                if new_key < *key {
+                if compare.compare(&new_key, key) == Ordering::Less {
                     mem::swap(&mut new_key, key);
                     mem::swap(&mut new_pointer, &mut self.pointers[index + offset]);
                 }
@@ -87,7 +401,7 @@ impl<T, U> InternalNode<T, U> {
             let mut index = 0;
             while index < internal_degree {
                 if let Some(ref mut key) = self.keys[index] {
-                    if new_key < *key {
+                    if compare.compare(&new_key, key) == Ordering::Less {
                         mem::swap(&mut new_key, key);
                         mem::swap(&mut new_pointer, &mut self.pointers[index + offset]);
                     }
@@ -139,10 +453,11 @@ impl<T, U> InternalNode<T, U> {
         (ret_key, ret_pointer)
     }
 
-    pub fn search<V>(&self, search_key: &V) -> usize
+    pub fn search<V, C>(&self, search_key: &V, compare: &C) -> usize
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
         let mut lo = 0;
         let mut hi = (self.keys.len() - 1) as isize;
@@ -151,7 +466,7 @@ impl<T, U> InternalNode<T, U> {
             match self.keys[mid as usize] {
                 None => hi = mid - 1,
                 Some(ref key) => {
-                    if key.borrow() <= search_key {
+                    if compare.compare(key.borrow(), search_key) != Ordering::Greater {
                         lo = mid + 1;
                     } else {
                         hi = mid - 1;
@@ -175,228 +490,410 @@ impl<T, U> InternalNode<T, U> {
     }
 }
 
-#[derive(Serialize, Deserialize)]
 pub struct LeafNode<T, U> {
-    pub len: usize,
-    pub entries: Box<[Option<Entry<T, U>>]>,
+    pub entries: Vec<Entry<T, Value<U>>>,
     pub next_leaf: Option<usize>,
 }
 
-pub enum InsertCases<T, U> {
-    Split {
-        split_key: T,
-        split_node: Node<T, U>,
-    },
-    Entry(Entry<T, U>),
-}
-
 impl<T, U> LeafNode<T, U> {
-    // 1) a usize is encoded as u64 (8 bytes)
-    // 2) a boxed slice is encoded as a tuple of u64 (8 bytes) and the items
+    // Returns the maximum number of entries that can fit in a leaf node.
     #[inline]
-    fn get_constant_size() -> u64 {
-        U64_SIZE * 2 + OPT_U64_SIZE
+    pub fn get_degree(block_size: u64) -> usize {
+        (block_size.saturating_sub(LEAF_HEADER_SIZE) / (SLOT_SIZE + 1)) as usize
     }
 
+    // Returns the maximum size of a key such that an entry with an overflow value fits in a cell.
     #[inline]
-    fn get_payload_size(key_size: u64, value_size: u64) -> u64 {
-        let option_size = mem::size_of::<Option<Entry<T, U>>>() as u64;
-        let entry_size = mem::size_of::<Entry<T, U>>() as u64;
-        key_size + value_size + option_size - entry_size
+    pub fn get_max_key_size(block_size: u64) -> u64 {
+        PageLayout::get_max_cell_size(block_size) - OVERFLOW_VALUE_SIZE
     }
 
+    // Returns the maximum size of a value that is stored inline given the maximum size of a key.
     #[inline]
-    pub fn get_degree(key_size: u64, value_size: u64) -> usize {
-        let payload_capacity = BLOCK_SIZE - Self::get_constant_size();
-        (payload_capacity / Self::get_payload_size(key_size, value_size)) as usize
+    pub fn get_max_inline_value_size(block_size: u64, key_size: u64) -> u64 {
+        PageLayout::get_max_cell_size(block_size) - VALUE_TAG_SIZE - key_size
     }
 
-    #[inline]
-    pub fn get_max_size(degree: usize, key_size: u64, value_size: u64) -> u64 {
-        Self::get_constant_size() + degree as u64 * Self::get_payload_size(key_size, value_size)
-    }
-
-    pub fn new(degree: usize) -> Self {
+    pub fn new() -> Self {
         LeafNode {
-            len: 0,
-            entries: init_array!(Option<Entry<T, U>>, degree, None),
+            entries: Vec::new(),
             next_leaf: None,
         }
     }
 
-    pub fn insert(&mut self, mut new_entry: Entry<T, U>) -> Option<InsertCases<T, U>>
+    fn get_cell_layouts(&self) -> bincode::Result<Vec<CellLayout>>
     where
-        T: Clone + Ord,
+        T: Serialize,
+        U: Serialize,
     {
-        let leaf_degree = self.entries.len();
-        // node has room; can insert
-        if self.len < leaf_degree {
-            let mut index = 0;
-            while let Some(ref mut entry) = self.entries[index] {
-                if new_entry <= *entry {
-                    mem::swap(entry, &mut new_entry);
-                    if new_entry == *entry {
-                        return Some(InsertCases::Entry(new_entry));
-                    }
-                }
-                index += 1;
-            }
-            self.len += 1;
-            self.entries[index] = Some(new_entry);
-            None
-        }
-        // node is full; have to split
-        else {
-            let mut split_node = LeafNode::new(leaf_degree);
-            for index in 0..leaf_degree {
-                if let Some(ref mut entry) = self.entries[index] {
-                    if new_entry <= *entry {
-                        mem::swap(entry, &mut new_entry);
-                        if new_entry == *entry {
-                            return Some(InsertCases::Entry(new_entry));
-                        }
-                    }
-                }
-                if index > leaf_degree / 2 {
-                    mem::swap(
-                        &mut self.entries[index],
-                        &mut split_node.entries[index - leaf_degree / 2 - 1],
-                    );
-                }
-            }
-            split_node.entries[(leaf_degree - 1) / 2] = Some(new_entry);
-            let split_key = split_node.entries[0]
-                .as_ref()
-                .map(|entry| entry.key.clone())
-                .expect("Expected some key.");
-            let split_node = Node::Leaf(LeafNode {
-                len: (self.len + 1) / 2,
-                entries: split_node.entries,
-                next_leaf: self.next_leaf,
-            });
-            self.len = (self.len + 2) / 2;
-            Some(InsertCases::Split {
-                split_key,
-                split_node,
+        self.entries
+            .iter()
+            .map(|entry| {
+                Ok(CellLayout {
+                    key: serialize(&entry.key)?,
+                    size: serialized_size(entry)? + SLOT_SIZE,
+                })
             })
-        }
+            .collect()
     }
 
-    pub fn remove_at(&mut self, remove_index: usize) -> Entry<T, U> {
-        assert!(remove_index < self.len);
-        self.len -= 1;
-        for index in remove_index..self.len {
-            self.entries.swap(index, index + 1);
+    // Returns the size of the node when it is encoded in `layout`.
+    pub fn get_size(&self, layout: PageLayout) -> bincode::Result<u64>
+    where
+        T: Serialize,
+        U: Serialize,
+    {
+        Ok(get_leaf_sizes(&self.get_cell_layouts()?, layout).0)
+    }
+
+    pub fn is_overflow(&self, degree: usize, layout: PageLayout) -> bincode::Result<bool>
+    where
+        T: Serialize,
+        U: Serialize,
+    {
+        let sizes = get_leaf_sizes(&self.get_cell_layouts()?, layout);
+        Ok(self.entries.len() > degree || !fits_in_page(sizes, layout))
+    }
+
+    pub fn is_underflow(&self, degree: usize, layout: PageLayout) -> bincode::Result<bool>
+    where
+        T: Serialize,
+        U: Serialize,
+    {
+        Ok(self.entries.len() < degree.div_ceil(2)
+            && self.get_size(layout)? < layout.block_size / 2)
+    }
+
+    pub fn can_merge(
+        &self,
+        node: &LeafNode<T, U>,
+        degree: usize,
+        layout: PageLayout,
+    ) -> bincode::Result<bool>
+    where
+        T: Serialize,
+        U: Serialize,
+    {
+        let mut cells = self.get_cell_layouts()?;
+        cells.extend(node.get_cell_layouts()?);
+        let sizes = get_leaf_sizes(&cells, layout);
+        Ok(cells.len() <= degree && fits_in_page(sizes, layout))
+    }
+
+    // Returns true if the node still fits in a page after `new_entry` is inserted.
+    pub fn can_insert(
+        &self,
+        new_entry: &Entry<T, Value<U>>,
+        degree: usize,
+        layout: PageLayout,
+    ) -> bincode::Result<bool>
+    where
+        T: Serialize,
+        U: Serialize,
+    {
+        let mut cells = self.get_cell_layouts()?;
+        cells.push(CellLayout {
+            key: serialize(&new_entry.key)?,
+            size: serialized_size(new_entry)? + SLOT_SIZE,
+        });
+        let sizes = get_leaf_sizes(&cells, layout);
+        Ok(cells.len() <= degree && fits_in_page(sizes, layout))
+    }
+
+    pub fn insert<C>(
+        &mut self,
+        new_entry: Entry<T, Value<U>>,
+        compare: &C,
+    ) -> Option<Entry<T, Value<U>>>
+    where
+        C: Compare<T>,
+    {
+        match self
+            .entries
+            .binary_search_by(|entry| compare.compare(&entry.key, &new_entry.key))
+        {
+            Ok(index) => Some(mem::replace(&mut self.entries[index], new_entry)),
+            Err(index) => {
+                self.entries.insert(index, new_entry);
+                None
+            }
         }
-        self.entries[self.len].take().expect("Expected some entry.")
     }
 
-    pub fn remove<V>(&mut self, key: &V) -> Option<Entry<T, U>>
+    // Splits the node into two nodes that both fit in a page and whose encoded sizes are as close
+    // as possible, and returns the smallest key of the new right node.
+    pub fn split(&mut self, layout: PageLayout) -> bincode::Result<(T, LeafNode<T, U>)>
     where
-        T: Borrow<V>,
-        V: Eq + ?Sized,
+        T: Clone + Serialize,
+        U: Serialize,
     {
-        let mut removed = false;
-        for index in 0..self.len {
-            let swap = {
-                if let Some(ref entry) = self.entries[index] {
-                    if key == entry.key.borrow() {
-                        removed = true;
-                        index + 1 < self.len
-                    } else {
-                        false
-                    }
-                } else {
-                    false
+        assert!(self.entries.len() >= 2);
+        let cells = self.get_cell_layouts()?;
+        let len = cells.len();
+
+        // `prefix_lens[k]` is the length of the common key prefix of the first `k + 1` cells and
+        // `suffix_lens[k]` is the length of the common key prefix of the cells after `k`.
+        let mut prefix_lens = vec![0; len];
+        let mut suffix_lens = vec![0; len];
+        if layout.format == PageFormat::PrefixCompressed {
+            for offset in PREFIX_OFFSETS.iter() {
+                let keys = cells.iter().map(|cell| cell.key.as_slice());
+                let lens = get_running_prefix_lens(keys, *offset);
+                for (curr, prefix_len) in prefix_lens.iter_mut().zip(lens) {
+                    *curr = cmp::max(*curr, prefix_len);
+                }
+                let keys = cells.iter().rev().map(|cell| cell.key.as_slice());
+                let lens = get_running_prefix_lens(keys, *offset);
+                for (curr, suffix_len) in suffix_lens.iter_mut().rev().zip(lens) {
+                    *curr = cmp::max(*curr, suffix_len);
                 }
-            };
-
-            if swap {
-                self.entries.swap(index, index + 1);
             }
         }
 
-        if removed {
-            self.len -= 1;
-            self.entries[self.len].take()
-        } else {
-            None
+        let header_size = layout.format.get_leaf_header_size();
+        let max_uncompressed_size = layout.get_max_uncompressed_leaf_size();
+        let total_size: u64 = cells.iter().map(|cell| cell.size).sum();
+        let mut left_size = 0;
+        let mut best = None;
+        for split_index in 1..len {
+            left_size += cells[split_index - 1].size;
+            let right_size = total_size - left_size;
+            let left_saved_size = (split_index as u64 - 1) * prefix_lens[split_index - 1] as u64;
+            let right_saved_size = (len - split_index - 1) as u64 * suffix_lens[split_index] as u64;
+            let left = header_size + left_size - left_saved_size;
+            let right = header_size + right_size - right_saved_size;
+            if left > layout.block_size
+                || right > layout.block_size
+                || header_size + left_size > max_uncompressed_size
+                || header_size + right_size > max_uncompressed_size
+            {
+                continue;
+            }
+            let imbalance = cmp::max(left, right) - cmp::min(left, right);
+            if best.is_none_or(|(_, best_imbalance)| imbalance < best_imbalance) {
+                best = Some((split_index, imbalance));
+            }
         }
+
+        let (split_index, _) = best.expect("Expected a split that fits in a page.");
+        let split_node = LeafNode {
+            entries: self.entries.split_off(split_index),
+            next_leaf: self.next_leaf,
+        };
+        let split_key = split_node.entries[0].key.clone();
+        Ok((split_key, split_node))
+    }
+
+    pub fn remove_at(&mut self, remove_index: usize) -> Entry<T, Value<U>> {
+        self.entries.remove(remove_index)
     }
 
-    pub fn search<V>(&self, search_key: &V) -> Option<usize>
+    pub fn remove<V, C>(&mut self, key: &V, compare: &C) -> Option<Entry<T, Value<U>>>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        let mut lo = 0;
-        let mut hi = (self.entries.len() - 1) as isize;
-        while lo <= hi {
-            let mid = lo + ((hi - lo) >> 1);
-            match self.entries[mid as usize] {
-                None => hi = mid - 1,
-                Some(ref entry) => match entry.key.borrow().cmp(search_key) {
-                    Ordering::Less => lo = mid + 1,
-                    Ordering::Greater => hi = mid - 1,
-                    Ordering::Equal => return Some(mid as usize),
-                },
-            }
-        }
-        None
+        self.search(key, compare)
+            .map(|index| self.entries.remove(index))
+    }
+
+    pub fn search<V, C>(&self, search_key: &V, compare: &C) -> Option<usize>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        self.entries
+            .binary_search_by(|entry| compare.compare(entry.key.borrow(), search_key))
+            .ok()
     }
 
     pub fn merge(&mut self, node: &mut LeafNode<T, U>) {
-        assert!(self.len + node.len <= self.entries.len());
         self.next_leaf = node.next_leaf.take();
-        for index in 0..node.len {
-            self.entries[self.len + index] = node.entries[index].take();
-        }
-        self.len += node.len;
-        node.len = 0;
+        self.entries.append(&mut node.entries);
     }
 }
 
-#[derive(Serialize, Deserialize)]
 pub enum Node<T, U> {
     Internal(InternalNode<T, U>),
     Leaf(LeafNode<T, U>),
     Free(Option<usize>),
+    Overflow { next: Option<usize>, data: Vec<u8> },
 }
 
 impl<T, U> Node<T, U> {
-    #[inline]
-    pub fn get_max_size(
-        key_size: u64,
-        value_size: u64,
-        leaf_degree: usize,
-        internal_degree: usize,
-    ) -> u64 {
-        cmp::max(
-            LeafNode::<T, U>::get_max_size(leaf_degree, key_size, value_size),
-            InternalNode::<T, U>::get_max_size(internal_degree, key_size),
-        )
+    // Encodes the node into a page. Leaf and internal nodes store their keys and entries in cells
+    // that are packed at the end of the page and indexed by a slot directory after the header. In
+    // the prefix-compressed format, the common key prefix is stored before the slot directory.
+    pub fn encode(&self, layout: PageLayout) -> bincode::Result<Vec<u8>>
+    where
+        T: Serialize,
+        U: Serialize,
+    {
+        let mut page = vec![0; layout.block_size as usize];
+        match self {
+            Node::Leaf(node) => {
+                page[0] = LEAF_TAG;
+                BigEndian::write_u32(&mut page[1..], node.entries.len() as u32);
+                write_page_index(&mut page[5..], node.next_leaf);
+                let cells = node
+                    .entries
+                    .iter()
+                    .map(serialize)
+                    .collect::<bincode::Result<Vec<_>>>()?;
+                let offset = LEAF_HEADER_SIZE as usize;
+                match layout.format {
+                    PageFormat::Uncompressed => encode_cells(&mut page, offset, &cells)?,
+                    PageFormat::PrefixCompressed => {
+                        let keys = node
+                            .entries
+                            .iter()
+                            .map(|entry| serialize(&entry.key))
+                            .collect::<bincode::Result<Vec<_>>>()?;
+                        let (offset, prefix) = encode_prefix(&mut page, offset, &keys);
+                        let cells: Vec<Vec<u8>> = cells
+                            .iter()
+                            .map(|cell| strip_prefix(cell, prefix))
+                            .collect();
+                        encode_cells(&mut page, offset, &cells)?;
+                    }
+                }
+            }
+            Node::Internal(node) => {
+                page[0] = INTERNAL_TAG;
+                BigEndian::write_u32(&mut page[1..], node.len as u32);
+                let mut offset = INTERNAL_HEADER_SIZE as usize;
+                for pointer in &node.pointers[..=node.len] {
+                    BigEndian::write_u64(&mut page[offset..], *pointer as u64);
+                    offset += mem::size_of::<u64>();
+                }
+                let cells = node.keys[..node.len]
+                    .iter()
+                    .map(|key| serialize(key.as_ref().expect("Expected some key.")))
+                    .collect::<bincode::Result<Vec<_>>>()?;
+                match layout.format {
+                    PageFormat::Uncompressed => encode_cells(&mut page, offset, &cells)?,
+                    PageFormat::PrefixCompressed => {
+                        let (offset, prefix) = encode_prefix(&mut page, offset, &cells);
+                        let cells: Vec<Vec<u8>> = cells
+                            .iter()
+                            .map(|cell| strip_prefix(cell, prefix))
+                            .collect();
+                        encode_cells(&mut page, offset, &cells)?;
+                    }
+                }
+            }
+            Node::Free(next_free_page) => {
+                page[0] = FREE_TAG;
+                write_page_index(&mut page[1..], *next_free_page);
+            }
+            Node::Overflow { next, data } => {
+                if data.len() > layout.get_overflow_capacity() {
+                    return Err(invalid_page("Error: overflow data does not fit in a page."));
+                }
+                page[0] = OVERFLOW_TAG;
+                write_page_index(&mut page[1..], *next);
+                BigEndian::write_u32(&mut page[9..], data.len() as u32);
+                page[OVERFLOW_HEADER_SIZE as usize..OVERFLOW_HEADER_SIZE as usize + data.len()]
+                    .copy_from_slice(data);
+            }
+        }
+        Ok(page)
+    }
+
+    pub fn decode(page: &[u8], internal_degree: usize, layout: PageLayout) -> bincode::Result<Self>
+    where
+        T: DeserializeOwned,
+        U: DeserializeOwned,
+    {
+        if page.len() != layout.block_size as usize {
+            return Err(invalid_page("Error: page has an invalid size."));
+        }
+        match page[0] {
+            LEAF_TAG => {
+                let len = BigEndian::read_u32(&page[1..]) as usize;
+                let (offset, prefix_offset, prefix) = match layout.format {
+                    PageFormat::Uncompressed => (LEAF_HEADER_SIZE as usize, 0, &[][..]),
+                    PageFormat::PrefixCompressed => decode_prefix(page, LEAF_HEADER_SIZE as usize)?,
+                };
+                Ok(Node::Leaf(LeafNode {
+                    entries: decode_cells(page, offset, len, prefix_offset, prefix)?,
+                    next_leaf: read_page_index(&page[5..]),
+                }))
+            }
+            INTERNAL_TAG => {
+                let len = BigEndian::read_u32(&page[1..]) as usize;
+                if len > internal_degree {
+                    return Err(invalid_page("Error: internal node exceeds its degree."));
+                }
+                let mut node = InternalNode::new(internal_degree);
+                node.len = len;
+                let mut offset = INTERNAL_HEADER_SIZE as usize;
+                for pointer in &mut node.pointers[..=len] {
+                    *pointer = BigEndian::read_u64(&page[offset..]) as usize;
+                    offset += mem::size_of::<u64>();
+                }
+                let (offset, prefix_offset, prefix) = match layout.format {
+                    PageFormat::Uncompressed => (offset, 0, &[][..]),
+                    PageFormat::PrefixCompressed => decode_prefix(page, offset)?,
+                };
+                let keys = decode_cells(page, offset, len, prefix_offset, prefix)?;
+                for (index, key) in keys.into_iter().enumerate() {
+                    node.keys[index] = Some(key);
+                }
+                Ok(Node::Internal(node))
+            }
+            FREE_TAG => Ok(Node::Free(read_page_index(&page[1..]))),
+            OVERFLOW_TAG => {
+                let len = BigEndian::read_u32(&page[9..]) as usize;
+                if len > layout.get_overflow_capacity() {
+                    return Err(invalid_page("Error: overflow page exceeds its capacity."));
+                }
+                let start = OVERFLOW_HEADER_SIZE as usize;
+                Ok(Node::Overflow {
+                    next: read_page_index(&page[1..]),
+                    data: page[start..start + len].to_vec(),
+                })
+            }
+            _ => Err(invalid_page("Error: page has an invalid tag.")),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{InsertCases, InternalNode, LeafNode, Node};
+    use super::{InternalNode, LeafNode, Node, PageFormat, PageLayout, Value, DEFAULT_BLOCK_SIZE};
+    use crate::compare::Natural;
     use crate::entry::Entry;
     use std::marker::PhantomData;
 
-    #[test]
-    fn test_node_get_max_size() {
-        assert_eq!(Node::<u32, u64>::get_max_size(4, 8, 1, 1), 52);
+    fn entry(key: u32, value: u64) -> Entry<u32, Value<u64>> {
+        Entry {
+            key,
+            value: Value::Inline(value),
+        }
+    }
+
+    fn layout(format: PageFormat) -> PageLayout {
+        PageLayout::new(format, DEFAULT_BLOCK_SIZE)
+    }
+
+    fn keys(node: &LeafNode<u32, u64>) -> Vec<u32> {
+        node.entries.iter().map(|entry| entry.key).collect()
     }
 
     #[test]
     fn test_internal_node_degree() {
-        assert_eq!(InternalNode::<u32, u64>::get_degree(4), 254);
+        assert_eq!(
+            InternalNode::<u32, u64>::get_degree(DEFAULT_BLOCK_SIZE, 4),
+            204
+        );
     }
 
     #[test]
     fn test_internal_node_get_max_size() {
-        assert_eq!(InternalNode::<u32, u64>::get_max_size(1, 4), 48);
+        assert_eq!(InternalNode::<u32, u64>::get_max_size(1, 4), 36);
     }
 
     #[test]
@@ -417,7 +914,7 @@ mod tests {
             _marker: PhantomData,
         };
 
-        assert!(n.insert(1, 1, false).is_none());
+        assert!(n.insert(1, 1, false, &Natural).is_none());
         assert_eq!(n.len, 3);
         assert_eq!(*n.keys, [Some(0), Some(1), Some(2)]);
         assert_eq!(*n.pointers, [0, 1, 2, 3]);
@@ -432,7 +929,7 @@ mod tests {
             _marker: PhantomData,
         };
 
-        assert!(n.insert(1, 2, true).is_none());
+        assert!(n.insert(1, 2, true, &Natural).is_none());
         assert_eq!(n.len, 3);
         assert_eq!(*n.keys, [Some(0), Some(1), Some(2)]);
         assert_eq!(*n.pointers, [0, 1, 2, 3]);
@@ -446,7 +943,7 @@ mod tests {
             pointers: Box::new([0, 1, 3, 4]),
             _marker: PhantomData,
         };
-        let res = n.insert(2, 2, false).unwrap();
+        let res = n.insert(2, 2, false, &Natural).unwrap();
 
         let (split_key, split_node) = res;
         let internal_node = {
@@ -474,7 +971,7 @@ mod tests {
             pointers: Box::new([0, 1, 2, 4]),
             _marker: PhantomData,
         };
-        let res = n.insert(2, 3, true).unwrap();
+        let res = n.insert(2, 3, true, &Natural).unwrap();
 
         let (split_key, split_node) = res;
         let internal_node = {
@@ -533,13 +1030,13 @@ mod tests {
             _marker: PhantomData,
         };
 
-        assert_eq!(n.search(&0), 0);
-        assert_eq!(n.search(&1), 1);
-        assert_eq!(n.search(&2), 1);
-        assert_eq!(n.search(&3), 2);
-        assert_eq!(n.search(&4), 2);
-        assert_eq!(n.search(&5), 3);
-        assert_eq!(n.search(&6), 3);
+        assert_eq!(n.search(&0, &Natural), 0);
+        assert_eq!(n.search(&1, &Natural), 1);
+        assert_eq!(n.search(&2, &Natural), 1);
+        assert_eq!(n.search(&3, &Natural), 2);
+        assert_eq!(n.search(&4, &Natural), 2);
+        assert_eq!(n.search(&5, &Natural), 3);
+        assert_eq!(n.search(&6, &Natural), 3);
     }
 
     #[test]
@@ -568,238 +1065,436 @@ mod tests {
     }
 
     #[test]
-    fn test_leaf_node_degree() {
-        assert_eq!(LeafNode::<u32, u64>::get_degree(4, 8), 203);
-    }
+    fn test_leaf_node_get_size() {
+        let mut n = LeafNode::<u32, u64>::new();
+        assert_eq!(n.get_size(layout(PageFormat::Uncompressed)).unwrap(), 13);
 
-    #[test]
-    fn test_leaf_node_get_max_size() {
-        assert_eq!(LeafNode::<u32, u64>::get_max_size(1, 4, 8), 52);
+        n.insert(entry(0, 0), &Natural);
+        // slot (8 bytes), key (4 bytes), value tag (4 bytes), and value (8 bytes)
+        assert_eq!(n.get_size(layout(PageFormat::Uncompressed)).unwrap(), 37);
     }
 
     #[test]
-    fn test_leaf_node_new() {
-        let n = LeafNode::<u32, u64>::new(3);
+    fn test_leaf_node_insert() {
+        let mut n = LeafNode::<u32, u64>::new();
 
-        assert_eq!(n.len, 0);
-        assert_eq!(*n.entries, [None, None, None]);
+        assert!(n.insert(entry(2, 2), &Natural).is_none());
+        assert!(n.insert(entry(0, 0), &Natural).is_none());
+        assert!(n.insert(entry(1, 1), &Natural).is_none());
+        assert_eq!(keys(&n), vec![0, 1, 2]);
         assert_eq!(n.next_leaf, None);
     }
 
     #[test]
-    fn test_leaf_node_insert_not_full() {
+    fn test_leaf_node_insert_existing() {
         let mut n = LeafNode::<u32, u64> {
-            len: 2,
-            entries: Box::new([
-                Some(Entry { key: 0, value: 0 }),
-                Some(Entry { key: 2, value: 2 }),
-                None,
-            ]),
+            entries: vec![entry(0, 0), entry(1, 0), entry(2, 2)],
             next_leaf: None,
         };
 
-        assert!(n.insert(Entry { key: 1, value: 1 }).is_none());
-        assert_eq!(n.len, 3);
-        assert_eq!(
-            *n.entries,
-            [
-                Some(Entry { key: 0, value: 0 }),
-                Some(Entry { key: 1, value: 1 }),
-                Some(Entry { key: 2, value: 2 }),
-            ]
-        );
-        assert_eq!(n.next_leaf, None);
+        match n.insert(entry(1, 1), &Natural) {
+            Some(Entry {
+                key: 1,
+                value: Value::Inline(0),
+            }) => {}
+            _ => panic!("Expected the old entry."),
+        }
+        assert_eq!(keys(&n), vec![0, 1, 2]);
+        match n.entries[1].value {
+            Value::Inline(1) => {}
+            _ => panic!("Expected the new value."),
+        }
     }
 
     #[test]
-    fn test_leaf_node_insert_full() {
-        let mut n = LeafNode::<u32, u64> {
-            len: 3,
-            entries: Box::new([
-                Some(Entry { key: 0, value: 0 }),
-                Some(Entry { key: 2, value: 2 }),
-                Some(Entry { key: 3, value: 3 }),
-            ]),
-            next_leaf: None,
-        };
-        let res = n.insert(Entry { key: 1, value: 1 }).unwrap();
-
-        let (split_key, split_node) = {
-            match res {
-                InsertCases::Split {
-                    split_key,
-                    split_node,
-                } => (split_key, split_node),
-                _ => panic!("Expected split insert case."),
-            }
-        };
-
-        let leaf_node = {
-            match split_node {
-                Node::Leaf(node) => node,
-                _ => panic!("Expected leaf node."),
-            }
-        };
-
-        assert_eq!(split_key, 2);
-        assert_eq!(leaf_node.len, 2);
-        assert_eq!(
-            *leaf_node.entries,
-            [
-                Some(Entry { key: 2, value: 2 }),
-                Some(Entry { key: 3, value: 3 }),
-                None,
-            ]
-        );
-        assert_eq!(leaf_node.next_leaf, None);
+    fn test_leaf_node_is_overflow() {
+        let mut n = LeafNode::<u32, u64>::new();
+        for key in 0..3 {
+            n.insert(entry(key, 0), &Natural);
+        }
+        assert!(!n.is_overflow(3, layout(PageFormat::Uncompressed)).unwrap());
+        n.insert(entry(3, 0), &Natural);
+        assert!(n.is_overflow(3, layout(PageFormat::Uncompressed)).unwrap());
+
+        let mut n = LeafNode::<u32, Vec<u8>>::new();
+        for key in 0..4 {
+            n.insert(
+                Entry {
+                    key,
+                    value: Value::Inline(vec![0; 1000]),
+                },
+                &Natural,
+            );
+        }
+        assert!(n
+            .is_overflow(
+                LeafNode::<u32, Vec<u8>>::get_degree(DEFAULT_BLOCK_SIZE),
+                layout(PageFormat::Uncompressed)
+            )
+            .unwrap());
+    }
 
-        assert_eq!(n.len, 2);
-        assert_eq!(
-            *n.entries,
-            [
-                Some(Entry { key: 0, value: 0 }),
-                Some(Entry { key: 1, value: 1 }),
-                None,
-            ]
-        );
-        assert_eq!(n.next_leaf, None);
+    #[test]
+    fn test_leaf_node_is_underflow() {
+        let mut n = LeafNode::<u32, u64>::new();
+        n.insert(entry(0, 0), &Natural);
+        assert!(n.is_underflow(3, layout(PageFormat::Uncompressed)).unwrap());
+        n.insert(entry(1, 0), &Natural);
+        assert!(!n.is_underflow(3, layout(PageFormat::Uncompressed)).unwrap());
+
+        let mut n = LeafNode::<u32, Vec<u8>>::new();
+        for key in 0..3 {
+            n.insert(
+                Entry {
+                    key,
+                    value: Value::Inline(vec![0; 1000]),
+                },
+                &Natural,
+            );
+        }
+        assert!(!n
+            .is_underflow(
+                LeafNode::<u32, Vec<u8>>::get_degree(DEFAULT_BLOCK_SIZE),
+                layout(PageFormat::Uncompressed)
+            )
+            .unwrap());
     }
 
     #[test]
-    fn test_leaf_node_insert_existing() {
+    fn test_leaf_node_split() {
         let mut n = LeafNode::<u32, u64> {
-            len: 3,
-            entries: Box::new([
-                Some(Entry { key: 0, value: 0 }),
-                Some(Entry { key: 1, value: 0 }),
-                Some(Entry { key: 2, value: 2 }),
-            ]),
-            next_leaf: None,
-        };
-        let res = n.insert(Entry { key: 1, value: 1 }).unwrap();
-
-        let entry = match res {
-            InsertCases::Entry(entry) => entry,
-            _ => panic!("Expected entry insert case."),
+            entries: vec![entry(0, 0), entry(1, 1), entry(2, 2), entry(3, 3)],
+            next_leaf: Some(1),
         };
+        let (split_key, split_node) = n.split(layout(PageFormat::Uncompressed)).unwrap();
 
-        assert_eq!(entry.key, 1);
-        assert_eq!(entry.value, 0);
+        assert_eq!(split_key, 2);
+        assert_eq!(keys(&split_node), vec![2, 3]);
+        assert_eq!(split_node.next_leaf, Some(1));
+        assert_eq!(keys(&n), vec![0, 1]);
+    }
 
-        assert_eq!(n.len, 3);
-        assert_eq!(
-            *n.entries,
-            [
-                Some(Entry { key: 0, value: 0 }),
-                Some(Entry { key: 1, value: 1 }),
-                Some(Entry { key: 2, value: 2 }),
-            ]
+    #[test]
+    fn test_leaf_node_split_by_size() {
+        let mut n = LeafNode::<u32, Vec<u8>>::new();
+        n.insert(
+            Entry {
+                key: 0,
+                value: Value::Inline(vec![0; 900]),
+            },
+            &Natural,
         );
-        assert_eq!(n.next_leaf, None);
+        for key in 1..4 {
+            n.insert(
+                Entry {
+                    key,
+                    value: Value::Inline(vec![0; 300]),
+                },
+                &Natural,
+            );
+        }
+        let (split_key, split_node) = n.split(layout(PageFormat::Uncompressed)).unwrap();
+
+        assert_eq!(split_key, 1);
+        assert_eq!(split_node.entries.len(), 3);
+        assert_eq!(n.entries.len(), 1);
     }
 
     #[test]
     fn test_leaf_node_remove_at() {
         let mut n = LeafNode::<u32, u64> {
-            len: 3,
-            entries: Box::new([
-                Some(Entry { key: 0, value: 0 }),
-                Some(Entry { key: 1, value: 1 }),
-                Some(Entry { key: 2, value: 2 }),
-            ]),
+            entries: vec![entry(0, 0), entry(1, 1), entry(2, 2)],
             next_leaf: None,
         };
 
-        assert_eq!(n.remove_at(1), Entry { key: 1, value: 1 });
-        assert_eq!(n.len, 2);
-        assert_eq!(
-            *n.entries,
-            [
-                Some(Entry { key: 0, value: 0 }),
-                Some(Entry { key: 2, value: 2 }),
-                None,
-            ]
-        );
-        assert_eq!(n.next_leaf, None);
+        assert_eq!(n.remove_at(1).key, 1);
+        assert_eq!(keys(&n), vec![0, 2]);
     }
 
     #[test]
     fn test_leaf_node_search() {
         let n = LeafNode::<u32, u64> {
-            len: 3,
-            entries: Box::new([
-                Some(Entry { key: 1, value: 1 }),
-                Some(Entry { key: 3, value: 3 }),
-                Some(Entry { key: 5, value: 5 }),
-            ]),
+            entries: vec![entry(1, 1), entry(3, 3), entry(5, 5)],
             next_leaf: None,
         };
 
-        assert_eq!(n.search(&0), None);
-        assert_eq!(n.search(&1), Some(0));
-        assert_eq!(n.search(&2), None);
-        assert_eq!(n.search(&3), Some(1));
-        assert_eq!(n.search(&4), None);
-        assert_eq!(n.search(&5), Some(2));
-        assert_eq!(n.search(&6), None);
+        assert_eq!(n.search(&0, &Natural), None);
+        assert_eq!(n.search(&1, &Natural), Some(0));
+        assert_eq!(n.search(&2, &Natural), None);
+        assert_eq!(n.search(&3, &Natural), Some(1));
+        assert_eq!(n.search(&4, &Natural), None);
+        assert_eq!(n.search(&5, &Natural), Some(2));
+        assert_eq!(n.search(&6, &Natural), None);
     }
 
     #[test]
     fn test_leaf_node_remove() {
         let mut n = LeafNode::<u32, u64> {
-            len: 3,
-            entries: Box::new([
-                Some(Entry { key: 0, value: 0 }),
-                Some(Entry { key: 1, value: 1 }),
-                Some(Entry { key: 2, value: 2 }),
-            ]),
+            entries: vec![entry(0, 0), entry(1, 1), entry(2, 2)],
             next_leaf: None,
         };
 
-        assert_eq!(n.remove(&1), Some(Entry { key: 1, value: 1 }));
-        assert_eq!(n.len, 2);
-        assert_eq!(
-            *n.entries,
-            [
-                Some(Entry { key: 0, value: 0 }),
-                Some(Entry { key: 2, value: 2 }),
-                None,
-            ]
-        );
-        assert_eq!(n.next_leaf, None);
+        assert_eq!(n.remove(&1, &Natural).map(|entry| entry.key), Some(1));
+        assert!(n.remove(&1, &Natural).is_none());
+        assert_eq!(keys(&n), vec![0, 2]);
     }
 
     #[test]
     fn test_leaf_node_merge() {
         let mut n = LeafNode::<u32, u64> {
-            len: 2,
-            entries: Box::new([
-                Some(Entry { key: 0, value: 0 }),
-                Some(Entry { key: 1, value: 1 }),
-                None,
-            ]),
+            entries: vec![entry(0, 0), entry(1, 1)],
             next_leaf: None,
         };
         let mut m = LeafNode::<u32, u64> {
-            len: 1,
-            entries: Box::new([Some(Entry { key: 2, value: 2 }), None, None]),
+            entries: vec![entry(2, 2)],
             next_leaf: Some(1),
         };
+        assert!(n
+            .can_merge(&m, 3, layout(PageFormat::Uncompressed))
+            .unwrap());
+        assert!(!n
+            .can_merge(&m, 2, layout(PageFormat::Uncompressed))
+            .unwrap());
         n.merge(&mut m);
 
-        assert_eq!(n.len, 3);
-        assert_eq!(
-            *n.entries,
-            [
-                Some(Entry { key: 0, value: 0 }),
-                Some(Entry { key: 1, value: 1 }),
-                Some(Entry { key: 2, value: 2 }),
-            ]
-        );
+        assert_eq!(keys(&n), vec![0, 1, 2]);
         assert_eq!(n.next_leaf, Some(1));
 
-        assert_eq!(m.len, 0);
-        assert_eq!(*m.entries, [None, None, None]);
+        assert!(m.entries.is_empty());
         assert_eq!(m.next_leaf, None);
     }
+
+    #[test]
+    fn test_encode_decode_leaf_node() {
+        let n = Node::Leaf(LeafNode::<u32, u64> {
+            entries: vec![
+                entry(0, 1),
+                Entry {
+                    key: 1,
+                    value: Value::Overflow { page: 3, len: 5000 },
+                },
+            ],
+            next_leaf: Some(2),
+        });
+        let page = n.encode(layout(PageFormat::Uncompressed)).unwrap();
+        assert_eq!(page.len(), DEFAULT_BLOCK_SIZE as usize);
+
+        match Node::<u32, u64>::decode(&page, 3, layout(PageFormat::Uncompressed)).unwrap() {
+            Node::Leaf(node) => {
+                assert_eq!(keys(&node), vec![0, 1]);
+                assert_eq!(node.next_leaf, Some(2));
+                match node.entries[0].value {
+                    Value::Inline(1) => {}
+                    _ => panic!("Expected an inline value."),
+                }
+                match node.entries[1].value {
+                    Value::Overflow { page: 3, len: 5000 } => {}
+                    _ => panic!("Expected an overflow value."),
+                }
+            }
+            _ => panic!("Expected a leaf node."),
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_internal_node() {
+        let n = Node::Internal(InternalNode::<u32, u64> {
+            len: 2,
+            keys: Box::new([Some(1), Some(3), None]),
+            pointers: Box::new([4, 5, 6, 0]),
+            _marker: PhantomData,
+        });
+        let page = n.encode(layout(PageFormat::Uncompressed)).unwrap();
+
+        match Node::<u32, u64>::decode(&page, 3, layout(PageFormat::Uncompressed)).unwrap() {
+            Node::Internal(node) => {
+                assert_eq!(node.len, 2);
+                assert_eq!(*node.keys, [Some(1), Some(3), None]);
+                assert_eq!(*node.pointers, [4, 5, 6, 0]);
+            }
+            _ => panic!("Expected an internal node."),
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_free_and_overflow_node() {
+        let page = Node::<u32, u64>::Free(Some(7))
+            .encode(layout(PageFormat::Uncompressed))
+            .unwrap();
+        match Node::<u32, u64>::decode(&page, 3, layout(PageFormat::Uncompressed)).unwrap() {
+            Node::Free(Some(7)) => {}
+            _ => panic!("Expected a free node."),
+        }
+
+        let page = Node::<u32, u64>::Overflow {
+            next: None,
+            data: vec![1, 2, 3],
+        }
+        .encode(layout(PageFormat::Uncompressed))
+        .unwrap();
+        match Node::<u32, u64>::decode(&page, 3, layout(PageFormat::Uncompressed)).unwrap() {
+            Node::Overflow { next: None, data } => assert_eq!(data, vec![1, 2, 3]),
+            _ => panic!("Expected an overflow node."),
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid_page() {
+        let mut page = vec![0; DEFAULT_BLOCK_SIZE as usize];
+        page[0] = 9;
+        assert!(Node::<u32, u64>::decode(&page, 3, layout(PageFormat::Uncompressed)).is_err());
+        assert!(
+            Node::<u32, u64>::decode(&page[..10], 3, layout(PageFormat::Uncompressed)).is_err()
+        );
+    }
+
+    fn string_entry(key: &str, value: u64) -> Entry<String, Value<u64>> {
+        Entry {
+            key: String::from(key),
+            value: Value::Inline(value),
+        }
+    }
+
+    #[test]
+    fn test_page_layout_block_size() {
+        assert!(PageLayout::is_valid_block_size(512));
+        assert!(PageLayout::is_valid_block_size(65536));
+        assert!(!PageLayout::is_valid_block_size(256));
+        assert!(!PageLayout::is_valid_block_size(6144));
+        assert!(!PageLayout::is_valid_block_size(131_072));
+
+        let layout = PageLayout::new(PageFormat::PrefixCompressed, 65536);
+        assert_eq!(LeafNode::<u32, u64>::get_degree(65536), 7280);
+        assert_eq!(layout.get_overflow_capacity(), 65523);
+
+        let n = LeafNode::<u32, u64> {
+            entries: (0..1000).map(|key| entry(key, u64::from(key))).collect(),
+            next_leaf: None,
+        };
+        assert!(!n.is_overflow(7280, layout).unwrap());
+        let page = Node::Leaf(n).encode(layout).unwrap();
+        assert_eq!(page.len(), 65536);
+        match Node::<u32, u64>::decode(&page, 3, layout).unwrap() {
+            Node::Leaf(n) => assert_eq!(keys(&n), (0..1000).collect::<Vec<u32>>()),
+            _ => panic!("Expected leaf node."),
+        }
+    }
+
+    #[test]
+    fn test_page_format_version() {
+        for format in &[PageFormat::Uncompressed, PageFormat::PrefixCompressed] {
+            assert_eq!(PageFormat::from_version(format.version()), Some(*format));
+        }
+        assert_eq!(PageFormat::from_version(2), None);
+    }
+
+    #[test]
+    fn test_leaf_node_get_size_prefix_compressed() {
+        let mut n = LeafNode::<String, u64>::new();
+        assert_eq!(
+            n.get_size(layout(PageFormat::PrefixCompressed)).unwrap(),
+            16
+        );
+
+        n.insert(string_entry("prefix_a", 0), &Natural);
+        n.insert(string_entry("prefix_b", 0), &Natural);
+        // the common prefix of the length (8 bytes) and "prefix_" (7 bytes) is stored once
+        assert_eq!(
+            n.get_size(layout(PageFormat::PrefixCompressed)).unwrap(),
+            n.get_size(layout(PageFormat::Uncompressed)).unwrap() + 3 - 15,
+        );
+    }
+
+    #[test]
+    fn test_leaf_node_split_prefix_compressed() {
+        let mut n = LeafNode::<String, Vec<u8>>::new();
+        for key in 0..20 {
+            n.insert(
+                Entry {
+                    key: format!("{}{:02}", "a".repeat(150), key),
+                    value: Value::Inline(vec![0; 50]),
+                },
+                &Natural,
+            );
+        }
+        let degree = LeafNode::<String, Vec<u8>>::get_degree(DEFAULT_BLOCK_SIZE);
+        assert!(!n
+            .is_overflow(degree, layout(PageFormat::PrefixCompressed))
+            .unwrap());
+        assert!(n
+            .is_overflow(degree, layout(PageFormat::Uncompressed))
+            .unwrap());
+
+        n.insert(
+            Entry {
+                key: String::from("b"),
+                value: Value::Inline(vec![0; 50]),
+            },
+            &Natural,
+        );
+        assert!(n
+            .is_overflow(degree, layout(PageFormat::PrefixCompressed))
+            .unwrap());
+
+        let (split_key, split_node) = n.split(layout(PageFormat::PrefixCompressed)).unwrap();
+        assert!(split_key.starts_with('a'));
+        assert!(!n
+            .is_overflow(degree, layout(PageFormat::PrefixCompressed))
+            .unwrap());
+        assert!(!split_node
+            .is_overflow(degree, layout(PageFormat::PrefixCompressed))
+            .unwrap());
+        assert_eq!(n.entries.len() + split_node.entries.len(), 21);
+    }
+
+    #[test]
+    fn test_encode_decode_prefix_compressed() {
+        let n = Node::Leaf(LeafNode::<String, u64> {
+            entries: vec![
+                string_entry("apple", 0),
+                string_entry("applesauce", 1),
+                string_entry("application", 2),
+            ],
+            next_leaf: None,
+        });
+        let page = n.encode(layout(PageFormat::PrefixCompressed)).unwrap();
+        match Node::<String, u64>::decode(&page, 3, layout(PageFormat::PrefixCompressed)).unwrap() {
+            Node::Leaf(node) => {
+                let keys: Vec<&str> = node.entries.iter().map(|entry| &*entry.key).collect();
+                assert_eq!(keys, vec!["apple", "applesauce", "application"]);
+                match node.entries[2].value {
+                    Value::Inline(2) => {}
+                    _ => panic!("Expected an inline value."),
+                }
+            }
+            _ => panic!("Expected a leaf node."),
+        }
+
+        let n = Node::Internal(InternalNode::<String, u64> {
+            len: 2,
+            keys: Box::new([
+                Some(String::from("key_1")),
+                Some(String::from("key_10")),
+                None,
+            ]),
+            pointers: Box::new([4, 5, 6, 0]),
+            _marker: PhantomData,
+        });
+        let page = n.encode(layout(PageFormat::PrefixCompressed)).unwrap();
+        match Node::<String, u64>::decode(&page, 3, layout(PageFormat::PrefixCompressed)).unwrap() {
+            Node::Internal(node) => {
+                assert_eq!(node.len, 2);
+                assert_eq!(
+                    *node.keys,
+                    [
+                        Some(String::from("key_1")),
+                        Some(String::from("key_10")),
+                        None
+                    ],
+                );
+                assert_eq!(*node.pointers, [4, 5, 6, 0]);
+            }
+            _ => panic!("Expected an internal node."),
+        }
+    }
 }
@@ -1,25 +1,100 @@
-use crate::bp_tree::node::{LeafNode, Node};
+use crate::bp_tree::node::{
+    InternalNode, LeafNode, Node, PageFormat, PageLayout, Value, DEFAULT_BLOCK_SIZE,
+};
+#[cfg(unix)]
+use crate::bp_tree::storage::mmap::MmapFile;
+use crate::bp_tree::storage::Storage;
 use bincode::{self, deserialize, serialize, serialized_size};
+use byteorder::{BigEndian, ByteOrder};
 use serde::de::DeserializeOwned;
 use serde::ser::Serialize;
 use serde_derive::{Deserialize, Serialize};
-use std::borrow::Borrow;
+use std::borrow::{Borrow, Cow};
+use std::cmp;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
 use std::error;
 use std::fmt;
-use std::fs::{File, OpenOptions};
-use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
 use std::marker::PhantomData;
-use std::mem;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::result;
 
 /// Convenience `Error` enum for `bp_tree`.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
-    /// An input or output error.
+    /// An input or output error that is not associated with a particular file.
     IOError(io::Error),
-    /// A serialization or deserialization error.
+    /// A serialization or deserialization error that is not associated with a particular file.
     SerdeError(bincode::Error),
+    /// An input or output error that occurred while performing `operation` on the file at `path`.
+    FileIOError {
+        /// The path of the file.
+        path: PathBuf,
+        /// The operation that failed.
+        operation: &'static str,
+        /// The underlying error.
+        source: io::Error,
+    },
+    /// The data at `offset` in the file at `path` could not be decoded or is inconsistent with
+    /// the structure of the tree.
+    Corruption {
+        /// The path of the file.
+        path: PathBuf,
+        /// The offset of the data in the file.
+        offset: u64,
+    },
+    /// The file at `path` was written with a page format `version` that is not supported.
+    UnsupportedVersion {
+        /// The path of the file.
+        path: PathBuf,
+        /// The version of the page format.
+        version: u32,
+    },
+    /// A key of `actual` bytes was larger than the maximum key size of `max` bytes.
+    KeyTooLarge {
+        /// The maximum size of a key in bytes.
+        max: u64,
+        /// The size of the key in bytes.
+        actual: u64,
+    },
+    /// A value of `actual` bytes was larger than the maximum value size of `max` bytes.
+    ValueTooLarge {
+        /// The maximum size of a value in bytes.
+        max: u64,
+        /// The size of the value in bytes.
+        actual: u64,
+    },
+    /// A block size of `block_size` bytes is not a power of two between 512 and 65536 bytes.
+    InvalidBlockSize {
+        /// The block size in bytes.
+        block_size: u64,
+    },
+}
+
+impl Error {
+    pub(crate) fn file_io<P>(path: P, operation: &'static str, source: io::Error) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Error::FileIOError {
+            path: PathBuf::from(path.as_ref()),
+            operation,
+            source,
+        }
+    }
+
+    pub(crate) fn corruption<P>(path: P, offset: u64) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Error::Corruption {
+            path: PathBuf::from(path.as_ref()),
+            offset,
+        }
+    }
 }
 
 impl From<io::Error> for Error {
@@ -37,8 +112,14 @@ impl From<bincode::Error> for Error {
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
-            Error::IOError(ref error) => error.source(),
-            Error::SerdeError(ref error) => error.source(),
+            Error::IOError(ref error) => Some(error),
+            Error::SerdeError(ref error) => Some(error),
+            Error::FileIOError { ref source, .. } => Some(source),
+            Error::Corruption { .. }
+            | Error::UnsupportedVersion { .. }
+            | Error::KeyTooLarge { .. }
+            | Error::ValueTooLarge { .. }
+            | Error::InvalidBlockSize { .. } => None,
         }
     }
 }
@@ -48,6 +129,44 @@ impl fmt::Display for Error {
         match self {
             Error::IOError(ref error) => write!(f, "{}", error),
             Error::SerdeError(ref error) => write!(f, "{}", error),
+            Error::FileIOError {
+                ref path,
+                operation,
+                ref source,
+            } => write!(
+                f,
+                "failed to {} `{}`: {}",
+                operation,
+                path.display(),
+                source
+            ),
+            Error::Corruption { ref path, offset } => write!(
+                f,
+                "corrupted data at offset {} of `{}`",
+                offset,
+                path.display()
+            ),
+            Error::UnsupportedVersion { ref path, version } => write!(
+                f,
+                "unsupported page format version {} in `{}`",
+                version,
+                path.display()
+            ),
+            Error::KeyTooLarge { max, actual } => write!(
+                f,
+                "key of {} bytes exceeds the maximum key size of {} bytes",
+                actual, max
+            ),
+            Error::ValueTooLarge { max, actual } => write!(
+                f,
+                "value of {} bytes exceeds the maximum value size of {} bytes",
+                actual, max
+            ),
+            Error::InvalidBlockSize { block_size } => write!(
+                f,
+                "block size of {} bytes is not a power of two between 512 and 65536 bytes",
+                block_size
+            ),
         }
     }
 }
@@ -55,7 +174,19 @@ impl fmt::Display for Error {
 /// Convenience `Result` type for `bp_tree`.
 pub type Result<T> = result::Result<T, Error>;
 
-#[derive(Serialize, Deserialize)]
+// The size of the header of the file. The header has a fixed size so that fields can be added
+// without moving the pages of existing files.
+const HEADER_SIZE: u64 = 72;
+
+// The offset of the page format version in the header. Files that were written before the
+// version was stored have zeroes at this offset, which is the version of the uncompressed format.
+const VERSION_OFFSET: u64 = HEADER_SIZE - 4;
+
+// The offset of the base-2 logarithm of the block size in the header. Files that were written
+// before the block size was stored have a zero at this offset and use the default block size.
+const BLOCK_SIZE_OFFSET: u64 = VERSION_OFFSET - 1;
+
+#[derive(Clone, Serialize, Deserialize)]
 struct Metadata {
     pages: usize,
     len: usize,
@@ -68,27 +199,149 @@ struct Metadata {
 }
 
 pub struct Pager<T, U> {
-    db_file: File,
+    path: PathBuf,
+    storage: Storage,
     metadata: Metadata,
+    layout: PageLayout,
     _marker: PhantomData<(T, U)>,
 }
 
+// Opens the backing file of a pager, creating it if it does not exist.
+pub fn open_file<P>(file_path: P) -> Result<File>
+where
+    P: AsRef<Path>,
+{
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open(&file_path)
+        .map_err(|error| Error::file_io(file_path, "open", error))
+}
+
+// Replaces the pages that a node points to with their new indexes. Returns `None` if the node
+// points to a page without a new index.
+fn remap_node<T, U, F>(node: Node<T, U>, new_index: F) -> Option<Node<T, U>>
+where
+    F: Fn(usize) -> Option<usize>,
+{
+    match node {
+        Node::Internal(mut node) => {
+            for pointer in &mut node.pointers[..=node.len] {
+                *pointer = new_index(*pointer)?;
+            }
+            Some(Node::Internal(node))
+        }
+        Node::Leaf(mut node) => {
+            node.next_leaf = match node.next_leaf {
+                Some(next_leaf) => Some(new_index(next_leaf)?),
+                None => None,
+            };
+            for entry in &mut node.entries {
+                if let Value::Overflow { ref mut page, .. } = entry.value {
+                    *page = new_index(*page)?;
+                }
+            }
+            Some(Node::Leaf(node))
+        }
+        Node::Overflow { next, data } => {
+            let next = match next {
+                Some(next) => Some(new_index(next)?),
+                None => None,
+            };
+            Some(Node::Overflow { next, data })
+        }
+        Node::Free(_) => None,
+    }
+}
+
 impl<T, U> Pager<T, U> {
+    // Returns an error if the block size is not supported or if an entry with a key of `key_size`
+    // bytes does not fit in a leaf node.
+    fn validate_sizes(block_size: u64, key_size: u64) -> Result<()> {
+        if !PageLayout::is_valid_block_size(block_size) {
+            return Err(Error::InvalidBlockSize { block_size });
+        }
+        let max = LeafNode::<T, U>::get_max_key_size(block_size);
+        if key_size > max {
+            return Err(Error::KeyTooLarge {
+                max,
+                actual: key_size,
+            });
+        }
+        Ok(())
+    }
+
     pub fn new<P>(
         file_path: P,
         key_size: u64,
         value_size: u64,
         leaf_degree: usize,
         internal_degree: usize,
+        block_size: u64,
+    ) -> Result<Pager<T, U>>
+    where
+        T: Serialize,
+        U: Serialize,
+        P: AsRef<Path>,
+    {
+        Self::validate_sizes(block_size, key_size)?;
+        let storage = Storage::File(open_file(&file_path)?);
+        Self::with_storage(
+            file_path,
+            storage,
+            key_size,
+            value_size,
+            leaf_degree,
+            internal_degree,
+            block_size,
+        )
+    }
+
+    #[cfg(unix)]
+    pub fn new_mmap<P>(
+        file_path: P,
+        key_size: u64,
+        value_size: u64,
+        leaf_degree: usize,
+        internal_degree: usize,
+        block_size: u64,
+    ) -> Result<Pager<T, U>>
+    where
+        T: Serialize,
+        U: Serialize,
+        P: AsRef<Path>,
+    {
+        Self::validate_sizes(block_size, key_size)?;
+        let file = open_file(&file_path)?;
+        let storage = Storage::Mmap(
+            MmapFile::new(file).map_err(|error| Error::file_io(&file_path, "map", error))?,
+        );
+        Self::with_storage(
+            file_path,
+            storage,
+            key_size,
+            value_size,
+            leaf_degree,
+            internal_degree,
+            block_size,
+        )
+    }
+
+    fn with_storage<P>(
+        file_path: P,
+        storage: Storage,
+        key_size: u64,
+        value_size: u64,
+        leaf_degree: usize,
+        internal_degree: usize,
+        block_size: u64,
     ) -> Result<Pager<T, U>>
     where
         T: Serialize,
         U: Serialize,
         P: AsRef<Path>,
     {
-        let header_size = Self::get_metadata_size();
-        let body_size =
-            Node::<T, U>::get_max_size(key_size, value_size, leaf_degree, internal_degree) as u64;
         let metadata = Metadata {
             pages: 1,
             len: 0,
@@ -99,27 +352,14 @@ impl<T, U> Pager<T, U> {
             internal_degree,
             free_page: None,
         };
-        let mut db_file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open(file_path)?;
-        db_file.set_len(header_size + body_size)?;
-
-        db_file.seek(SeekFrom::Start(0))?;
-        let serialized_metadata = &serialize(&metadata)?;
-        db_file.write_all(serialized_metadata)?;
-
-        db_file.seek(SeekFrom::Start(header_size))?;
-        let serialized_node = &serialize(&Node::Leaf(LeafNode::<T, U>::new(leaf_degree)))?;
-        db_file.write_all(serialized_node)?;
-
-        let pager = Pager {
-            db_file,
+        let mut pager = Pager {
+            path: PathBuf::from(file_path.as_ref()),
+            storage,
             metadata,
+            layout: PageLayout::new(PageFormat::LATEST, block_size),
             _marker: PhantomData,
         };
-
+        pager.clear()?;
         Ok(pager)
     }
 
@@ -127,45 +367,131 @@ impl<T, U> Pager<T, U> {
     where
         P: AsRef<Path>,
     {
-        let mut db_file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open(file_path)?;
-        db_file.seek(SeekFrom::Start(0))?;
+        let storage = Storage::File(open_file(&file_path)?);
+        Self::open_storage(file_path, storage)
+    }
 
-        let mut buffer: Vec<u8> = vec![0; Self::get_metadata_size() as usize];
-        db_file.read_exact(buffer.as_mut_slice())?;
-        let metadata = deserialize(buffer.as_slice())?;
+    #[cfg(unix)]
+    pub fn open_mmap<P>(file_path: P) -> Result<Pager<T, U>>
+    where
+        P: AsRef<Path>,
+    {
+        let file = open_file(&file_path)?;
+        let storage = Storage::Mmap(
+            MmapFile::new(file).map_err(|error| Error::file_io(&file_path, "map", error))?,
+        );
+        Self::open_storage(file_path, storage)
+    }
+
+    fn open_storage<P>(file_path: P, mut storage: Storage) -> Result<Pager<T, U>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = PathBuf::from(file_path.as_ref());
+        let (metadata, version, block_size_log) = {
+            let buffer = storage
+                .read_at(0, HEADER_SIZE as usize)
+                .map_err(|error| Error::file_io(&path, "read", error))?;
+            let version = BigEndian::read_u32(&buffer[VERSION_OFFSET as usize..]);
+            let block_size_log = buffer[BLOCK_SIZE_OFFSET as usize];
+            let metadata: Metadata =
+                deserialize(&buffer).map_err(|_| Error::corruption(&path, 0))?;
+            (metadata, version, block_size_log)
+        };
+        let format = match PageFormat::from_version(version) {
+            Some(format) => format,
+            None => return Err(Error::UnsupportedVersion { path, version }),
+        };
+        let block_size = match block_size_log {
+            0 => DEFAULT_BLOCK_SIZE,
+            log if log < 64 && PageLayout::is_valid_block_size(1 << log) => 1 << log,
+            _ => return Err(Error::corruption(&path, BLOCK_SIZE_OFFSET)),
+        };
+        // the nodes of the tree must fit in the pages of the file
+        let max_key_size = LeafNode::<T, U>::get_max_key_size(block_size);
+        let max_internal_size =
+            InternalNode::<T, U>::get_max_size(metadata.internal_degree, metadata.key_size);
+        if metadata.key_size > max_key_size || max_internal_size > block_size {
+            return Err(Error::corruption(&path, 0));
+        }
 
         Ok(Pager {
-            db_file,
+            path,
+            storage,
             metadata,
+            layout: PageLayout::new(format, block_size),
             _marker: PhantomData,
         })
     }
 
     #[inline]
     fn get_node_size(&self) -> u64 {
-        Node::<T, U>::get_max_size(
-            self.metadata.key_size,
-            self.metadata.value_size,
-            self.metadata.leaf_degree,
-            self.metadata.internal_degree,
-        ) as u64
-    }
-
-    #[inline]
-    fn get_metadata_size() -> u64 {
-        mem::size_of::<Metadata>() as u64
+        self.layout.block_size
     }
 
     fn calculate_page_offset(&self, index: usize) -> u64 {
-        let header_size = Self::get_metadata_size();
+        let header_size = HEADER_SIZE;
         let body_offset = self.get_node_size() * index as u64;
         header_size + body_offset
     }
 
+    // Returns an error that indicates that the page at a particular index is corrupted.
+    pub fn corruption(&self, index: usize) -> Error {
+        Error::corruption(&self.path, self.calculate_page_offset(index))
+    }
+
+    fn read_at(&mut self, offset: u64, len: usize) -> Result<Cow<'_, [u8]>> {
+        let path = &self.path;
+        self.storage
+            .read_at(offset, len)
+            .map_err(|error| Error::file_io(path, "read", error))
+    }
+
+    fn write_at(&mut self, offset: u64, buffer: &[u8]) -> Result<()> {
+        let path = &self.path;
+        self.storage
+            .write_at(offset, buffer)
+            .map_err(|error| Error::file_io(path, "write", error))
+    }
+
+    fn encode_header(&self, metadata: &Metadata) -> Result<Vec<u8>> {
+        let mut header = vec![0; HEADER_SIZE as usize];
+        let serialized_metadata = serialize(metadata)?;
+        header[..serialized_metadata.len()].copy_from_slice(&serialized_metadata);
+        header[BLOCK_SIZE_OFFSET as usize] = self.layout.block_size.trailing_zeros() as u8;
+        BigEndian::write_u32(
+            &mut header[VERSION_OFFSET as usize..],
+            self.layout.format.version(),
+        );
+        Ok(header)
+    }
+
+    fn write_metadata(&mut self) -> Result<()> {
+        let header = self.encode_header(&self.metadata)?;
+        self.write_at(0, &header)
+    }
+
+    pub fn get_page_layout(&self) -> PageLayout {
+        self.layout
+    }
+
+    #[cfg(test)]
+    pub fn get_page_format(&self) -> PageFormat {
+        self.layout.format
+    }
+
+    // Changes the page format of an empty pager.
+    #[cfg(test)]
+    pub fn set_page_format(&mut self, format: PageFormat) -> Result<()>
+    where
+        T: Serialize,
+        U: Serialize,
+    {
+        assert_eq!(self.metadata.len, 0);
+        self.layout.format = format;
+        self.clear()
+    }
+
     pub fn get_leaf_degree(&self) -> usize {
         self.metadata.leaf_degree
     }
@@ -174,17 +500,17 @@ impl<T, U> Pager<T, U> {
         self.metadata.internal_degree
     }
 
+    pub fn get_page_count(&self) -> usize {
+        self.metadata.pages
+    }
+
     pub fn get_len(&self) -> usize {
         self.metadata.len
     }
 
     pub fn set_len(&mut self, len: usize) -> Result<()> {
         self.metadata.len = len;
-        self.db_file.seek(SeekFrom::Start(0))?;
-        let serialized_metadata = &serialize(&self.metadata)?;
-        self.db_file
-            .write_all(serialized_metadata)
-            .map_err(Error::IOError)
+        self.write_metadata()
     }
 
     pub fn get_root_page(&self) -> usize {
@@ -193,11 +519,7 @@ impl<T, U> Pager<T, U> {
 
     pub fn set_root_page(&mut self, new_root_page: usize) -> Result<()> {
         self.metadata.root_page = new_root_page;
-        self.db_file.seek(SeekFrom::Start(0))?;
-        let serialized_metadata = &serialize(&self.metadata)?;
-        self.db_file
-            .write_all(serialized_metadata)
-            .map_err(Error::IOError)
+        self.write_metadata()
     }
 
     pub fn get_page(&mut self, index: usize) -> Result<Node<T, U>>
@@ -206,10 +528,15 @@ impl<T, U> Pager<T, U> {
         U: DeserializeOwned,
     {
         let offset = self.calculate_page_offset(index);
-        self.db_file.seek(SeekFrom::Start(offset))?;
-        let mut buffer: Vec<u8> = vec![0; self.get_node_size() as usize];
-        self.db_file.read_exact(buffer.as_mut_slice())?;
-        deserialize(buffer.as_slice()).map_err(Error::SerdeError)
+        let node_size = self.get_node_size() as usize;
+        let internal_degree = self.metadata.internal_degree;
+        let path = &self.path;
+        let buffer = self
+            .storage
+            .read_at(offset, node_size)
+            .map_err(|error| Error::file_io(path, "read", error))?;
+        Node::decode(&buffer, internal_degree, self.layout)
+            .map_err(|_| Error::corruption(path, offset))
     }
 
     pub fn allocate_node(&mut self, new_node: &Node<T, U>) -> Result<usize>
@@ -222,35 +549,23 @@ impl<T, U> Pager<T, U> {
                 self.metadata.pages += 1;
                 let len = self.calculate_page_offset(self.metadata.pages);
                 let node_size = self.get_node_size();
-                self.db_file.set_len(len)?;
-                self.db_file.seek(SeekFrom::Start(len - node_size))?;
-                let serialized_node = &serialize(&new_node)?;
-                self.db_file.write_all(serialized_node)?;
-
-                self.db_file.seek(SeekFrom::Start(0))?;
-                let serialized_metadata = &serialize(&self.metadata)?;
-                self.db_file.write_all(serialized_metadata)?;
+                let path = &self.path;
+                self.storage
+                    .reserve(len)
+                    .map_err(|error| Error::file_io(path, "resize", error))?;
+                let encoded_node = &new_node.encode(self.layout)?;
+                self.write_at(len - node_size, encoded_node)?;
+                self.write_metadata()?;
 
                 Ok(self.metadata.pages - 1)
             }
             Some(free_page) => {
-                let offset = self.calculate_page_offset(free_page);
-                let mut buffer: Vec<u8> = vec![0; self.get_node_size() as usize];
-
-                self.db_file.seek(SeekFrom::Start(offset))?;
-                self.db_file.read_exact(buffer.as_mut_slice())?;
-
-                self.db_file.seek(SeekFrom::Start(offset))?;
-                let serialized_node = &serialize(&new_node)?;
-                self.db_file.write_all(serialized_node)?;
-
-                match deserialize(buffer.as_slice())? {
+                match self.get_page(free_page)? {
                     Node::Free::<T, U>(new_free_page) => self.metadata.free_page = new_free_page,
-                    _ => panic!("Expected a free node."),
+                    _ => return Err(self.corruption(free_page)),
                 }
-                self.db_file.seek(SeekFrom::Start(0))?;
-                let serialized_metadata = &serialize(&self.metadata)?;
-                self.db_file.write_all(serialized_metadata)?;
+                self.write_node(free_page, new_node)?;
+                self.write_metadata()?;
 
                 Ok(free_page)
             }
@@ -262,18 +577,9 @@ impl<T, U> Pager<T, U> {
         T: Serialize,
         U: Serialize,
     {
-        let offset = self.calculate_page_offset(index);
-
-        self.db_file.seek(SeekFrom::Start(offset))?;
-        let serialized_node = &serialize(&Node::Free::<T, U>(self.metadata.free_page))?;
-        self.db_file.write_all(serialized_node)?;
-
+        self.write_node(index, &Node::Free::<T, U>(self.metadata.free_page))?;
         self.metadata.free_page = Some(index);
-        self.db_file.seek(SeekFrom::Start(0))?;
-        let serialized_metadata = &serialize(&self.metadata)?;
-        self.db_file
-            .write_all(serialized_metadata)
-            .map_err(Error::IOError)
+        self.write_metadata()
     }
 
     pub fn write_node(&mut self, index: usize, node: &Node<T, U>) -> Result<()>
@@ -282,11 +588,8 @@ impl<T, U> Pager<T, U> {
         U: Serialize,
     {
         let offset = self.calculate_page_offset(index);
-        self.db_file.seek(SeekFrom::Start(offset))?;
-        let serialized_node = &serialize(&node)?;
-        self.db_file
-            .write_all(serialized_node)
-            .map_err(Error::IOError)
+        let encoded_node = &node.encode(self.layout)?;
+        self.write_at(offset, encoded_node)
     }
 
     pub fn clear(&mut self) -> Result<()>
@@ -294,25 +597,19 @@ impl<T, U> Pager<T, U> {
         T: Serialize,
         U: Serialize,
     {
-        let header_size = Self::get_metadata_size();
+        let header_size = HEADER_SIZE;
         let body_size = self.get_node_size();
         self.metadata.pages = 1;
         self.metadata.len = 0;
         self.metadata.root_page = 0;
         self.metadata.free_page = None;
-        self.db_file.set_len(header_size + body_size)?;
+        let path = &self.path;
+        self.storage
+            .set_len(header_size + body_size)
+            .map_err(|error| Error::file_io(path, "resize", error))?;
+        self.write_metadata()?;
 
-        self.db_file.seek(SeekFrom::Start(0))?;
-        let serialized_metadata = &serialize(&self.metadata)?;
-        self.db_file.write_all(serialized_metadata)?;
-
-        self.db_file.seek(SeekFrom::Start(header_size))?;
-        let serialized_node = &serialize(&Node::Leaf(LeafNode::<T, U>::new(
-            self.metadata.leaf_degree,
-        )))?;
-        self.db_file
-            .write_all(serialized_node)
-            .map_err(Error::IOError)
+        self.write_node(0, &Node::Leaf(LeafNode::<T, U>::new()))
     }
 
     pub fn validate_key<V>(&self, key: &V) -> Result<()>
@@ -320,16 +617,282 @@ impl<T, U> Pager<T, U> {
         T: Borrow<V>,
         V: Serialize + ?Sized,
     {
-        assert!(serialized_size(key)? <= self.metadata.key_size);
+        let size = serialized_size(key)?;
+        if size > self.metadata.key_size {
+            return Err(Error::KeyTooLarge {
+                max: self.metadata.key_size,
+                actual: size,
+            });
+        }
         Ok(())
     }
 
-    pub fn validate_value<V>(&self, value: &V) -> Result<()>
+    // Returns the value of an entry, reading it from its overflow pages if necessary.
+    pub fn get_value(&mut self, value: Value<U>) -> Result<U>
     where
-        U: Borrow<V>,
-        V: Serialize + ?Sized,
+        T: DeserializeOwned,
+        U: DeserializeOwned,
+    {
+        match value {
+            Value::Inline(value) => Ok(value),
+            Value::Overflow { page, len } => {
+                let buffer = self.read_overflow(page, len)?;
+                deserialize(&buffer).map_err(|_| self.corruption(page))
+            }
+        }
+    }
+
+    // Returns the value of an entry that is being removed and frees its overflow pages.
+    pub fn take_value(&mut self, value: Value<U>) -> Result<U>
+    where
+        T: DeserializeOwned + Serialize,
+        U: DeserializeOwned + Serialize,
+    {
+        let page = match value {
+            Value::Overflow { page, .. } => Some(page),
+            Value::Inline(_) => None,
+        };
+        let ret = self.get_value(value)?;
+        if let Some(page) = page {
+            self.deallocate_overflow(page)?;
+        }
+        Ok(ret)
+    }
+
+    // Wraps a value for a leaf node. Values that exceed the maximum inline value size are written
+    // to a chain of overflow pages.
+    pub fn allocate_value(&mut self, value: U) -> Result<Value<U>>
+    where
+        T: DeserializeOwned + Serialize,
+        U: DeserializeOwned + Serialize,
+    {
+        let max_inline_value_size = cmp::min(
+            self.metadata.value_size,
+            LeafNode::<T, U>::get_max_inline_value_size(
+                self.layout.block_size,
+                self.metadata.key_size,
+            ),
+        );
+        let size = serialized_size(&value)?;
+        if size <= max_inline_value_size {
+            return Ok(Value::Inline(value));
+        }
+        // values are read into memory in their entirety, so the size of a value must fit in a
+        // `usize`
+        if usize::try_from(size).is_err() {
+            return Err(Error::ValueTooLarge {
+                max: usize::MAX as u64,
+                actual: size,
+            });
+        }
+
+        let buffer = serialize(&value)?;
+        let mut next = None;
+        for chunk in buffer.rchunks(self.layout.get_overflow_capacity()) {
+            let node = Node::Overflow {
+                next,
+                data: chunk.to_vec(),
+            };
+            next = Some(self.allocate_node(&node)?);
+        }
+        Ok(Value::Overflow {
+            page: next.expect("Expected an overflow page."),
+            len: buffer.len() as u64,
+        })
+    }
+
+    fn read_overflow(&mut self, page: usize, len: u64) -> Result<Vec<u8>>
+    where
+        T: DeserializeOwned,
+        U: DeserializeOwned,
     {
-        assert!(serialized_size(value)? <= self.metadata.value_size);
+        let mut buffer = Vec::with_capacity(len as usize);
+        let mut curr_page = Some(page);
+        while let Some(page) = curr_page {
+            match self.get_page(page)? {
+                Node::Overflow { next, data } => {
+                    buffer.extend_from_slice(&data);
+                    curr_page = next;
+                }
+                _ => return Err(self.corruption(page)),
+            }
+        }
+        if buffer.len() as u64 != len {
+            return Err(self.corruption(page));
+        }
+        Ok(buffer)
+    }
+
+    fn deallocate_overflow(&mut self, page: usize) -> Result<()>
+    where
+        T: DeserializeOwned + Serialize,
+        U: DeserializeOwned + Serialize,
+    {
+        let mut curr_page = Some(page);
+        while let Some(page) = curr_page {
+            match self.get_page(page)? {
+                Node::Overflow { next, .. } => curr_page = next,
+                _ => return Err(self.corruption(page)),
+            }
+            self.deallocate_node(page)?;
+        }
         Ok(())
     }
+
+    // Returns the number of pages in the free list.
+    pub fn get_free_page_count(&mut self) -> Result<usize>
+    where
+        T: DeserializeOwned,
+        U: DeserializeOwned,
+    {
+        let mut ret = 0;
+        let mut curr_page = self.metadata.free_page;
+        while let Some(page) = curr_page {
+            // a free list that is longer than the file must contain a cycle
+            if page >= self.metadata.pages || ret == self.metadata.pages {
+                return Err(self.corruption(page));
+            }
+            match self.get_page(page)? {
+                Node::Free(next_free_page) => curr_page = next_free_page,
+                _ => return Err(self.corruption(page)),
+            }
+            ret += 1;
+        }
+        Ok(ret)
+    }
+
+    // Returns the pages that are reachable from the root in breadth-first order, and the new index
+    // of each page when the pages are stored in that order.
+    fn get_live_pages(&mut self) -> Result<(Vec<usize>, Vec<Option<usize>>)>
+    where
+        T: DeserializeOwned,
+        U: DeserializeOwned,
+    {
+        let mut pages = Vec::new();
+        let mut new_indexes = vec![None; self.metadata.pages];
+        let mut queue = VecDeque::new();
+        queue.push_back(self.metadata.root_page);
+        while let Some(page) = queue.pop_front() {
+            match new_indexes.get(page) {
+                Some(None) => new_indexes[page] = Some(pages.len()),
+                _ => return Err(self.corruption(page)),
+            }
+            pages.push(page);
+            match self.get_page(page)? {
+                Node::Internal(node) => queue.extend(&node.pointers[..=node.len]),
+                Node::Leaf(node) => {
+                    for entry in node.entries {
+                        if let Value::Overflow { page, .. } = entry.value {
+                            queue.push_back(page);
+                        }
+                    }
+                }
+                Node::Overflow { next, .. } => queue.extend(next),
+                Node::Free(_) => return Err(self.corruption(page)),
+            }
+        }
+        Ok((pages, new_indexes))
+    }
+
+    // Rewrites the pages that are reachable from the root into a new file without free pages and
+    // replaces the file with it. The existing file is left intact until it is replaced, so the
+    // tree is not lost if the compaction is interrupted.
+    pub fn compact(&mut self) -> Result<()>
+    where
+        T: DeserializeOwned + Serialize,
+        U: DeserializeOwned + Serialize,
+    {
+        self.sync()?;
+        let (pages, new_indexes) = self.get_live_pages()?;
+        let new_index = |page: usize| new_indexes.get(page).cloned().flatten();
+
+        let mut compact_path = self.path.clone().into_os_string();
+        compact_path.push(".compact");
+        let compact_path = PathBuf::from(compact_path);
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&compact_path)
+            .map_err(|error| Error::file_io(&compact_path, "create", error))?;
+
+        let mut metadata = self.metadata.clone();
+        metadata.pages = pages.len();
+        metadata.root_page = 0;
+        metadata.free_page = None;
+        let mut writer = BufWriter::new(&mut file);
+        writer
+            .write_all(&self.encode_header(&metadata)?)
+            .map_err(|error| Error::file_io(&compact_path, "write", error))?;
+        for page in pages {
+            let node = match remap_node(self.get_page(page)?, new_index) {
+                Some(node) => node,
+                None => return Err(self.corruption(page)),
+            };
+            writer
+                .write_all(&node.encode(self.layout)?)
+                .map_err(|error| Error::file_io(&compact_path, "write", error))?;
+        }
+        writer
+            .flush()
+            .map_err(|error| Error::file_io(&compact_path, "write", error))?;
+        drop(writer);
+        file.sync_all()
+            .map_err(|error| Error::file_io(&compact_path, "sync", error))?;
+        drop(file);
+
+        fs::rename(&compact_path, &self.path)
+            .map_err(|error| Error::file_io(&self.path, "rename", error))?;
+        let file = open_file(&self.path)?;
+        self.storage = match self.storage {
+            Storage::File(_) => Storage::File(file),
+            #[cfg(unix)]
+            Storage::Mmap(_) => Storage::Mmap(
+                MmapFile::new(file).map_err(|error| Error::file_io(&self.path, "map", error))?,
+            ),
+        };
+        self.metadata = metadata;
+        Ok(())
+    }
+
+    // Writes a copy of the file to `file_path` one page at a time, so the copy is consistent
+    // without reading the whole file into memory.
+    pub fn checkpoint<P>(&mut self, file_path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        self.sync()?;
+        let file_path = file_path.as_ref();
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(file_path)
+            .map_err(|error| Error::file_io(file_path, "create", error))?;
+        let header = self.read_at(0, HEADER_SIZE as usize)?;
+        file.write_all(&header)
+            .map_err(|error| Error::file_io(file_path, "write", error))?;
+        for index in 0..self.metadata.pages {
+            let offset = self.calculate_page_offset(index);
+            let node_size = self.get_node_size() as usize;
+            let page = self.read_at(offset, node_size)?;
+            file.write_all(&page)
+                .map_err(|error| Error::file_io(file_path, "write", error))?;
+        }
+        file.sync_all()
+            .map_err(|error| Error::file_io(file_path, "sync", error))
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        let path = &self.path;
+        self.storage
+            .flush()
+            .map_err(|error| Error::file_io(path, "flush", error))
+    }
+
+    pub fn sync(&mut self) -> Result<()> {
+        let path = &self.path;
+        self.storage
+            .sync()
+            .map_err(|error| Error::file_io(path, "sync", error))
+    }
 }
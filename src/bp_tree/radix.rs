@@ -0,0 +1,1405 @@
+use crate::bp_tree::node::DEFAULT_BLOCK_SIZE;
+use crate::bp_tree::pager::{open_file, Error, Result};
+#[cfg(unix)]
+use crate::bp_tree::storage::mmap::MmapFile;
+use crate::bp_tree::storage::Storage;
+use crate::durability::{SyncMode, SyncState};
+use crate::radix::{AsBytes, RadixKey};
+use bincode::{deserialize, serialize, serialized_size};
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use serde_derive::{Deserialize, Serialize};
+use std::cmp;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::result;
+
+// The size of the header of the file. The header has a fixed size so that fields can be added
+// without moving the pages of existing files.
+const HEADER_SIZE: u64 = 64;
+
+// The longest key fragment of a node. Fragments that are longer are stored in a chain of nodes
+// that each have a single child.
+const MAX_KEY_FRAGMENT_SIZE: u64 = 512;
+
+// A node page is encoded with a u32 tag (4 bytes), the length of its key fragment as a u64 (8
+// bytes), the tag of its optional value (1 byte), and the length of its children as a u64 (8
+// bytes).
+const NODE_HEADER_SIZE: u64 = 21;
+
+// A child is encoded as its first byte (1 byte) and its page as a u64 (8 bytes).
+const MAX_CHILDREN_SIZE: u64 = 256 * 9;
+
+// The largest value that fits in a node with the longest key fragment and a child for every byte.
+const MAX_VALUE_SIZE: u64 =
+    DEFAULT_BLOCK_SIZE - NODE_HEADER_SIZE - MAX_KEY_FRAGMENT_SIZE - MAX_CHILDREN_SIZE;
+
+#[derive(Serialize, Deserialize)]
+struct Metadata {
+    pages: usize,
+    len: usize,
+    root_page: usize,
+    free_page: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Node<T> {
+    key: Vec<u8>,
+    value: Option<T>,
+    // (first byte of the key fragment of the child, page of the child) sorted by the first byte
+    children: Vec<(u8, usize)>,
+}
+
+impl<T> Node<T> {
+    fn new(key: Vec<u8>, value: Option<T>) -> Self {
+        Node {
+            key,
+            value,
+            children: Vec::new(),
+        }
+    }
+
+    fn search(&self, byte: u8) -> result::Result<usize, usize> {
+        self.children
+            .binary_search_by_key(&byte, |&(child_byte, _)| child_byte)
+    }
+
+    fn insert_child(&mut self, byte: u8, page: usize) {
+        match self.search(byte) {
+            Ok(index) => self.children[index].1 = page,
+            Err(index) => self.children.insert(index, (byte, page)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum Page<T> {
+    Node(Node<T>),
+    Free(Option<usize>),
+}
+
+fn get_common_prefix_len(left: &[u8], right: &[u8]) -> usize {
+    left.iter()
+        .zip(right)
+        .take_while(|(left, right)| left == right)
+        .count()
+}
+
+// Manages the pages of a `RadixBpMap`. Unlike the pager of a `BpMap`, every page holds exactly one
+// node of the trie, so pages are encoded with bincode in their entirety.
+struct RadixPager<T> {
+    path: PathBuf,
+    storage: Storage,
+    metadata: Metadata,
+    _marker: PhantomData<T>,
+}
+
+impl<T> RadixPager<T> {
+    fn new<P>(file_path: P, storage: Storage) -> Result<RadixPager<T>>
+    where
+        T: Serialize,
+        P: AsRef<Path>,
+    {
+        let mut pager = RadixPager {
+            path: PathBuf::from(file_path.as_ref()),
+            storage,
+            metadata: Metadata {
+                pages: 1,
+                len: 0,
+                root_page: 0,
+                free_page: None,
+            },
+            _marker: PhantomData,
+        };
+        pager.clear()?;
+        Ok(pager)
+    }
+
+    fn open<P>(file_path: P, mut storage: Storage) -> Result<RadixPager<T>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = PathBuf::from(file_path.as_ref());
+        let metadata = {
+            let buffer = storage
+                .read_at(0, HEADER_SIZE as usize)
+                .map_err(|error| Error::file_io(&path, "read", error))?;
+            deserialize(&buffer).map_err(|_| Error::corruption(&path, 0))?
+        };
+        Ok(RadixPager {
+            path,
+            storage,
+            metadata,
+            _marker: PhantomData,
+        })
+    }
+
+    fn calculate_page_offset(&self, index: usize) -> u64 {
+        HEADER_SIZE + DEFAULT_BLOCK_SIZE * index as u64
+    }
+
+    // Returns an error that indicates that the page at a particular index is corrupted.
+    fn corruption(&self, index: usize) -> Error {
+        Error::corruption(&self.path, self.calculate_page_offset(index))
+    }
+
+    fn write_at(&mut self, offset: u64, buffer: &[u8]) -> Result<()> {
+        let path = &self.path;
+        self.storage
+            .write_at(offset, buffer)
+            .map_err(|error| Error::file_io(path, "write", error))
+    }
+
+    fn write_metadata(&mut self) -> Result<()> {
+        let serialized_metadata = &serialize(&self.metadata)?;
+        self.write_at(0, serialized_metadata)
+    }
+
+    fn get_len(&self) -> usize {
+        self.metadata.len
+    }
+
+    fn set_len(&mut self, len: usize) -> Result<()> {
+        self.metadata.len = len;
+        self.write_metadata()
+    }
+
+    fn get_root_page(&self) -> usize {
+        self.metadata.root_page
+    }
+
+    #[cfg(test)]
+    fn get_page_count(&self) -> usize {
+        self.metadata.pages
+    }
+
+    fn get_page(&mut self, index: usize) -> Result<Page<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let offset = self.calculate_page_offset(index);
+        let path = &self.path;
+        let buffer = self
+            .storage
+            .read_at(offset, DEFAULT_BLOCK_SIZE as usize)
+            .map_err(|error| Error::file_io(path, "read", error))?;
+        deserialize(&buffer).map_err(|_| Error::corruption(path, offset))
+    }
+
+    fn get_node(&mut self, index: usize) -> Result<Node<T>>
+    where
+        T: DeserializeOwned,
+    {
+        match self.get_page(index)? {
+            Page::Node(node) => Ok(node),
+            Page::Free(_) => Err(self.corruption(index)),
+        }
+    }
+
+    // Returns the child of a node at a particular page, checking that its key fragment starts
+    // with the byte that the child is indexed by.
+    fn get_child_node(&mut self, index: usize, byte: u8) -> Result<Node<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let node = self.get_node(index)?;
+        if node.key.first() != Some(&byte) {
+            return Err(self.corruption(index));
+        }
+        Ok(node)
+    }
+
+    fn write_page(&mut self, index: usize, page: &Page<T>) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let offset = self.calculate_page_offset(index);
+        let encoded_page = &serialize(page)?;
+        self.write_at(offset, encoded_page)
+    }
+
+    fn write_node(&mut self, index: usize, node: Node<T>) -> Result<()>
+    where
+        T: Serialize,
+    {
+        self.write_page(index, &Page::Node(node))
+    }
+
+    fn allocate_node(&mut self, node: Node<T>) -> Result<usize>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        let index = match self.metadata.free_page {
+            None => {
+                self.metadata.pages += 1;
+                let len = self.calculate_page_offset(self.metadata.pages);
+                let path = &self.path;
+                self.storage
+                    .reserve(len)
+                    .map_err(|error| Error::file_io(path, "resize", error))?;
+                self.metadata.pages - 1
+            }
+            Some(free_page) => {
+                match self.get_page(free_page)? {
+                    Page::Free(next_free_page) => self.metadata.free_page = next_free_page,
+                    Page::Node(_) => return Err(self.corruption(free_page)),
+                }
+                free_page
+            }
+        };
+        self.write_page(index, &Page::Node(node))?;
+        self.write_metadata()?;
+        Ok(index)
+    }
+
+    fn deallocate_node(&mut self, index: usize) -> Result<()>
+    where
+        T: Serialize,
+    {
+        self.write_page(index, &Page::Free(self.metadata.free_page))?;
+        self.metadata.free_page = Some(index);
+        self.write_metadata()
+    }
+
+    fn clear(&mut self) -> Result<()>
+    where
+        T: Serialize,
+    {
+        self.metadata.pages = 1;
+        self.metadata.len = 0;
+        self.metadata.root_page = 0;
+        self.metadata.free_page = None;
+        let path = &self.path;
+        self.storage
+            .set_len(HEADER_SIZE + DEFAULT_BLOCK_SIZE)
+            .map_err(|error| Error::file_io(path, "resize", error))?;
+        self.write_metadata()?;
+        self.write_page(0, &Page::Node(Node::new(Vec::new(), None)))
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        let path = &self.path;
+        self.storage
+            .flush()
+            .map_err(|error| Error::file_io(path, "flush", error))
+    }
+
+    fn sync(&mut self) -> Result<()> {
+        let path = &self.path;
+        self.storage
+            .sync()
+            .map_err(|error| Error::file_io(path, "sync", error))
+    }
+}
+
+/// An ordered map implemented using an on-disk radix tree.
+///
+/// A radix tree is a space optimized trie where nodes are merged with its parent if it is the only
+/// child and its parent does not contain a value. The map accepts keys as byte strings, which makes
+/// it suitable for indexing URLs and paths that share long prefixes and are too numerous to keep
+/// in memory. Like `BpMap`, the nodes are stored in fixed-size pages, and pages that are freed by
+/// removals are reused by later insertions.
+///
+/// Each node stores a fragment of at most 512 bytes of its key, so keys of any length are
+/// supported, but the size of a value is bounded so that a node with a child for every byte fits in
+/// a page.
+///
+/// The keys of the map are byte vectors by default, but any type that implements `RadixKey` can be
+/// used as a key.
+///
+/// # Examples
+///
+/// ```
+/// # use extended_collections::bp_tree::Result;
+/// # fn foo() -> Result<()> {
+/// # use std::fs;
+/// use extended_collections::bp_tree::RadixBpMap;
+///
+/// let mut map: RadixBpMap<u32> = RadixBpMap::new("radix_bp_map")?;
+/// map.insert(b"foo", 0)?;
+/// map.insert(b"foobar", 1)?;
+/// map.insert(b"baz", 2)?;
+///
+/// assert_eq!(map.get(b"foo")?, Some(0));
+/// assert_eq!(map.get(b"bar")?, None);
+/// assert_eq!(map.len(), 3);
+///
+/// let entries: Vec<(Vec<u8>, u32)> = map.iter_prefix_mut(b"foo")?.collect::<Result<_>>()?;
+/// assert_eq!(entries, vec![(b"foo".to_vec(), 0), (b"foobar".to_vec(), 1)]);
+///
+/// assert_eq!(map.remove(b"foo")?, Some((b"foo".to_vec(), 0)));
+/// assert_eq!(map.remove(b"foo")?, None);
+/// # fs::remove_file("radix_bp_map")?;
+/// # Ok(())
+/// # }
+/// # foo().unwrap();
+/// ```
+pub struct RadixBpMap<T, K = Vec<u8>> {
+    pager: RadixPager<T>,
+    sync_state: SyncState,
+    _marker: PhantomData<K>,
+}
+
+impl<T, K> RadixBpMap<T, K> {
+    fn from_pager(pager: RadixPager<T>) -> Self {
+        RadixBpMap {
+            pager,
+            sync_state: SyncState::new(SyncMode::default()),
+            _marker: PhantomData,
+        }
+    }
+
+    // Records a write to the map and syncs the map if the sync mode requires it.
+    fn record_write(&mut self) -> Result<()> {
+        if self.sync_state.record_write() {
+            self.sync()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Constructs a new, empty `RadixBpMap<T, K>` and creates a file for data persistence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_new")?;
+    /// # fs::remove_file("example_radix_bp_map_new")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn new<P>(file_path: P) -> Result<RadixBpMap<T, K>>
+    where
+        T: Serialize,
+        P: AsRef<Path>,
+    {
+        let storage = Storage::File(open_file(&file_path)?);
+        RadixPager::new(file_path, storage).map(RadixBpMap::from_pager)
+    }
+
+    /// Opens an existing `RadixBpMap<T, K>` from a file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let map: RadixBpMap<u32> = RadixBpMap::open("example_radix_bp_map_open")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn open<P>(file_path: P) -> Result<RadixBpMap<T, K>>
+    where
+        P: AsRef<Path>,
+    {
+        let storage = Storage::File(open_file(&file_path)?);
+        RadixPager::open(file_path, storage).map(RadixBpMap::from_pager)
+    }
+
+    /// Constructs a new, empty `RadixBpMap<T, K>` and creates a memory-mapped file for data
+    /// persistence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let mut map: RadixBpMap<u32> = RadixBpMap::new_mmap("example_radix_bp_map_new_mmap")?;
+    /// map.insert(b"foo", 1)?;
+    /// assert_eq!(map.get(b"foo")?, Some(1));
+    /// # fs::remove_file("example_radix_bp_map_new_mmap")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    #[cfg(unix)]
+    pub fn new_mmap<P>(file_path: P) -> Result<RadixBpMap<T, K>>
+    where
+        T: Serialize,
+        P: AsRef<Path>,
+    {
+        let file = open_file(&file_path)?;
+        let storage = Storage::Mmap(
+            MmapFile::new(file).map_err(|error| Error::file_io(&file_path, "map", error))?,
+        );
+        RadixPager::new(file_path, storage).map(RadixBpMap::from_pager)
+    }
+
+    /// Opens an existing `RadixBpMap<T, K>` from a file and memory-maps the file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let map: RadixBpMap<u32> = RadixBpMap::open_mmap("example_radix_bp_map_open_mmap")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    #[cfg(unix)]
+    pub fn open_mmap<P>(file_path: P) -> Result<RadixBpMap<T, K>>
+    where
+        P: AsRef<Path>,
+    {
+        let file = open_file(&file_path)?;
+        let storage = Storage::Mmap(
+            MmapFile::new(file).map_err(|error| Error::file_io(&file_path, "map", error))?,
+        );
+        RadixPager::open(file_path, storage).map(RadixBpMap::from_pager)
+    }
+
+    // Allocates a chain of nodes that stores `key` and `value` and returns the page of the first
+    // node of the chain.
+    fn allocate_chain(&mut self, key: &[u8], value: T) -> Result<usize>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        let mut value = Some(value);
+        let mut next = None;
+        for fragment in key.rchunks(MAX_KEY_FRAGMENT_SIZE as usize) {
+            let mut node = Node::new(fragment.to_vec(), value.take());
+            if let Some((byte, page)) = next {
+                node.children.push((byte, page));
+            }
+            next = Some((fragment[0], self.pager.allocate_node(node)?));
+        }
+        Ok(next.expect("Expected a non-empty key.").1)
+    }
+
+    // Writes a node to a page after merging it with its only child if it does not contain a value
+    // and the merged key fragment is not too long.
+    fn compress_node(&mut self, page: usize, mut node: Node<T>, is_root: bool) -> Result<()>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        if !is_root && node.value.is_none() && node.children.len() == 1 {
+            let (byte, child_page) = node.children[0];
+            let child_node = self.pager.get_child_node(child_page, byte)?;
+            if (node.key.len() + child_node.key.len()) as u64 <= MAX_KEY_FRAGMENT_SIZE {
+                node.key.extend_from_slice(&child_node.key);
+                node.value = child_node.value;
+                node.children = child_node.children;
+                self.pager.deallocate_node(child_page)?;
+            }
+        }
+        self.pager.write_node(page, node)
+    }
+
+    // Returns the page and the node that stores a particular key.
+    fn search_node(&mut self, key: &[u8]) -> Result<Option<(usize, Node<T>)>>
+    where
+        T: DeserializeOwned,
+    {
+        let mut curr_page = self.pager.get_root_page();
+        let mut curr_node = self.pager.get_node(curr_page)?;
+        let mut index = 0;
+        while index < key.len() {
+            let child_page = match curr_node.search(key[index]) {
+                Ok(child_index) => curr_node.children[child_index].1,
+                Err(_) => return Ok(None),
+            };
+            curr_node = self.pager.get_child_node(child_page, key[index])?;
+            curr_page = child_page;
+            if !key[index..].starts_with(&curr_node.key) {
+                return Ok(None);
+            }
+            index += curr_node.key.len();
+        }
+        Ok(Some((curr_page, curr_node)))
+    }
+
+    // Returns the page of the first node whose keys all start with a particular prefix and the
+    // key of the parent of the node.
+    fn search_prefix(&mut self, prefix: &[u8]) -> Result<Option<(usize, Vec<u8>)>>
+    where
+        T: DeserializeOwned,
+    {
+        let mut curr_node = self.pager.get_node(self.pager.get_root_page())?;
+        let mut index = 0;
+        while index < prefix.len() {
+            let child_page = match curr_node.search(prefix[index]) {
+                Ok(child_index) => curr_node.children[child_index].1,
+                Err(_) => return Ok(None),
+            };
+            curr_node = self.pager.get_child_node(child_page, prefix[index])?;
+            let len = cmp::min(curr_node.key.len(), prefix.len() - index);
+            if curr_node.key[..len] != prefix[index..index + len] {
+                return Ok(None);
+            }
+            if index + curr_node.key.len() >= prefix.len() {
+                return Ok(Some((child_page, prefix[..index].to_vec())));
+            }
+            index += curr_node.key.len();
+        }
+        Ok(Some((self.pager.get_root_page(), Vec::new())))
+    }
+
+    /// Inserts a key-value pair into the map. If the key already exists in the map, it will return
+    /// and replace the old key-value pair.
+    ///
+    /// Returns `Error::ValueTooLarge` if the value does not fit in a node. The map is left
+    /// unchanged if the error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let mut map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_insert")?;
+    /// assert_eq!(map.insert(b"foo", 1)?, None);
+    /// assert_eq!(map.get(b"foo")?, Some(1));
+    /// assert_eq!(map.insert(b"foo", 2)?, Some((b"foo".to_vec(), 1)));
+    /// assert_eq!(map.get(b"foo")?, Some(2));
+    /// # fs::remove_file("example_radix_bp_map_insert")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn insert<Q>(&mut self, key: &Q, value: T) -> Result<Option<(K, T)>>
+    where
+        T: DeserializeOwned + Serialize,
+        K: RadixKey,
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        let size = serialized_size(&value)?;
+        if size > MAX_VALUE_SIZE {
+            return Err(Error::ValueTooLarge {
+                max: MAX_VALUE_SIZE,
+                actual: size,
+            });
+        }
+
+        let key = key.to_bytes();
+        let mut curr_page = self.pager.get_root_page();
+        let mut curr_node = self.pager.get_node(curr_page)?;
+        let mut index = 0;
+        while index < key.len() {
+            let child_index = match curr_node.search(key[index]) {
+                Ok(child_index) => child_index,
+                Err(_) => {
+                    let chain_page = self.allocate_chain(&key[index..], value)?;
+                    curr_node.insert_child(key[index], chain_page);
+                    self.pager.write_node(curr_page, curr_node)?;
+                    return self.increment_len().map(|_| None);
+                }
+            };
+            let child_page = curr_node.children[child_index].1;
+            let mut child_node = self.pager.get_child_node(child_page, key[index])?;
+            let common_len = get_common_prefix_len(&child_node.key, &key[index..]);
+            if common_len == child_node.key.len() {
+                curr_page = child_page;
+                curr_node = child_node;
+                index += common_len;
+                continue;
+            }
+
+            // split the child at the end of the common prefix
+            let split_key = child_node.key.split_off(common_len);
+            let mut split_node = Node::new(child_node.key, None);
+            split_node.insert_child(split_key[0], child_page);
+            child_node.key = split_key;
+            self.pager.write_node(child_page, child_node)?;
+
+            index += common_len;
+            if index == key.len() {
+                split_node.value = Some(value);
+            } else {
+                let chain_page = self.allocate_chain(&key[index..], value)?;
+                split_node.insert_child(key[index], chain_page);
+            }
+            curr_node.children[child_index].1 = self.pager.allocate_node(split_node)?;
+            self.pager.write_node(curr_page, curr_node)?;
+            return self.increment_len().map(|_| None);
+        }
+
+        let old_value = curr_node.value.replace(value);
+        self.pager.write_node(curr_page, curr_node)?;
+        match old_value {
+            Some(old_value) => {
+                self.record_write()?;
+                Ok(Some((K::from_bytes(key.into_owned()), old_value)))
+            }
+            None => self.increment_len().map(|_| None),
+        }
+    }
+
+    fn increment_len(&mut self) -> Result<()> {
+        let new_len = self.pager.get_len() + 1;
+        self.pager.set_len(new_len)?;
+        self.record_write()
+    }
+
+    /// Removes a key-value pair from the map. If the key exists in the map, it will return the
+    /// associated key-value pair. Otherwise it will return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let mut map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_remove")?;
+    /// map.insert(b"foo", 1)?;
+    /// assert_eq!(map.remove(b"foo")?, Some((b"foo".to_vec(), 1)));
+    /// assert_eq!(map.remove(b"foo")?, None);
+    /// # fs::remove_file("example_radix_bp_map_remove")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Result<Option<(K, T)>>
+    where
+        T: DeserializeOwned + Serialize,
+        K: RadixKey,
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        let key = key.to_bytes();
+        // (page, node, index of the child that was descended into)
+        let mut stack = Vec::new();
+        let mut curr_page = self.pager.get_root_page();
+        let mut curr_node = self.pager.get_node(curr_page)?;
+        let mut index = 0;
+        while index < key.len() {
+            let child_index = match curr_node.search(key[index]) {
+                Ok(child_index) => child_index,
+                Err(_) => return Ok(None),
+            };
+            let child_page = curr_node.children[child_index].1;
+            let child_node = self.pager.get_child_node(child_page, key[index])?;
+            if !key[index..].starts_with(&child_node.key) {
+                return Ok(None);
+            }
+            index += child_node.key.len();
+            stack.push((curr_page, curr_node, child_index));
+            curr_page = child_page;
+            curr_node = child_node;
+        }
+
+        let value = match curr_node.value.take() {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        // remove nodes that no longer contain any keys
+        loop {
+            match stack.pop() {
+                Some((parent_page, mut parent_node, child_index))
+                    if curr_node.value.is_none() && curr_node.children.is_empty() =>
+                {
+                    self.pager.deallocate_node(curr_page)?;
+                    parent_node.children.remove(child_index);
+                    curr_page = parent_page;
+                    curr_node = parent_node;
+                }
+                parent => {
+                    self.compress_node(curr_page, curr_node, parent.is_none())?;
+                    break;
+                }
+            }
+        }
+
+        let new_len = self.pager.get_len() - 1;
+        self.pager.set_len(new_len)?;
+        self.record_write()?;
+        Ok(Some((K::from_bytes(key.into_owned()), value)))
+    }
+
+    /// Checks if a key exists in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let mut map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_contains_key")?;
+    /// map.insert(b"foo", 1)?;
+    /// assert!(!map.contains_key(b"bar")?);
+    /// assert!(map.contains_key(b"foo")?);
+    /// # fs::remove_file("example_radix_bp_map_contains_key")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn contains_key<Q>(&mut self, key: &Q) -> Result<bool>
+    where
+        T: DeserializeOwned,
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        self.get(key).map(|value| value.is_some())
+    }
+
+    /// Returns the value associated with a particular key. It will return `None` if the key does
+    /// not exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let mut map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_get")?;
+    /// map.insert(b"foo", 1)?;
+    /// assert_eq!(map.get(b"bar")?, None);
+    /// assert_eq!(map.get(b"foo")?, Some(1));
+    /// # fs::remove_file("example_radix_bp_map_get")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn get<Q>(&mut self, key: &Q) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        self.search_node(&key.to_bytes())
+            .map(|node| node.and_then(|(_, node)| node.value))
+    }
+
+    /// Returns the number of elements in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let mut map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_len")?;
+    /// map.insert(b"foo", 1)?;
+    /// assert_eq!(map.len(), 1);
+    /// # fs::remove_file("example_radix_bp_map_len")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn len(&self) -> usize {
+        self.pager.get_len()
+    }
+
+    /// Returns `true` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_is_empty")?;
+    /// assert!(map.is_empty());
+    /// # fs::remove_file("example_radix_bp_map_is_empty")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.pager.get_len() == 0
+    }
+
+    /// Clears the map, removing all values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let mut map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_clear")?;
+    /// map.insert(b"foo", 1)?;
+    /// map.insert(b"bar", 2)?;
+    /// map.clear()?;
+    /// assert_eq!(map.is_empty(), true);
+    /// # fs::remove_file("example_radix_bp_map_clear")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn clear(&mut self) -> Result<()>
+    where
+        T: Serialize,
+    {
+        self.pager.clear()?;
+        self.record_write()
+    }
+
+    /// Returns a mutable iterator over the map. The iterator will yield key-value pairs in
+    /// lexicographic order of their keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let mut map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_iter_mut")?;
+    /// map.insert(b"foo", 1)?;
+    /// map.insert(b"bar", 2)?;
+    ///
+    /// let mut iterator = map.iter_mut()?.map(|entry| entry.unwrap());
+    /// assert_eq!(iterator.next(), Some((b"bar".to_vec(), 2)));
+    /// assert_eq!(iterator.next(), Some((b"foo".to_vec(), 1)));
+    /// assert_eq!(iterator.next(), None);
+    /// # fs::remove_file("example_radix_bp_map_iter_mut")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn iter_mut(&mut self) -> Result<RadixBpMapIterMut<'_, T, K>> {
+        let root_page = self.pager.get_root_page();
+        Ok(RadixBpMapIterMut {
+            pager: &mut self.pager,
+            prefix: Vec::new(),
+            stack: vec![(root_page, 0)],
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns a mutable iterator over the key-value pairs of the map whose keys start with a
+    /// particular prefix. The iterator will yield key-value pairs in lexicographic order of their
+    /// keys, and only reads the pages of the nodes under the prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let mut map: RadixBpMap<u32, String> =
+    ///     RadixBpMap::new("example_radix_bp_map_iter_prefix_mut")?;
+    /// map.insert("/usr/bin", 1)?;
+    /// map.insert("/usr/lib", 2)?;
+    /// map.insert("/var/log", 3)?;
+    ///
+    /// let mut iterator = map.iter_prefix_mut("/usr/")?.map(|entry| entry.unwrap());
+    /// assert_eq!(iterator.next(), Some((String::from("/usr/bin"), 1)));
+    /// assert_eq!(iterator.next(), Some((String::from("/usr/lib"), 2)));
+    /// assert_eq!(iterator.next(), None);
+    /// # fs::remove_file("example_radix_bp_map_iter_prefix_mut")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn iter_prefix_mut<Q>(&mut self, prefix: &Q) -> Result<RadixBpMapIterMut<'_, T, K>>
+    where
+        T: DeserializeOwned,
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        let (prefix, stack) = match self.search_prefix(&prefix.to_bytes())? {
+            Some((page, prefix)) => {
+                let prefix_len = prefix.len();
+                (prefix, vec![(page, prefix_len)])
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+        Ok(RadixBpMapIterMut {
+            pager: &mut self.pager,
+            prefix,
+            stack,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Syncs all data to disk before consuming the map. Unlike relying on the map being dropped,
+    /// any errors encountered while persisting the map are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let mut map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_close")?;
+    /// map.insert(b"foo", 1)?;
+    /// map.close()?;
+    ///
+    /// let mut map: RadixBpMap<u32> = RadixBpMap::open("example_radix_bp_map_close")?;
+    /// assert_eq!(map.get(b"foo")?, Some(1));
+    /// # fs::remove_file("example_radix_bp_map_close")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn close(mut self) -> Result<()> {
+        self.sync()
+    }
+
+    /// Returns the sync mode of the map, which determines when writes are synced to disk. The
+    /// sync mode of a new or opened map is `SyncMode::Manual`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    /// use extended_collections::durability::SyncMode;
+    ///
+    /// let map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_sync_mode")?;
+    /// assert_eq!(map.sync_mode(), SyncMode::Manual);
+    /// # fs::remove_file("example_radix_bp_map_sync_mode")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn sync_mode(&self) -> SyncMode {
+        self.sync_state.mode()
+    }
+
+    /// Sets the sync mode of the map, which determines when writes are synced to disk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    /// use extended_collections::durability::SyncMode;
+    ///
+    /// let mut map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_set_sync_mode")?;
+    /// map.set_sync_mode(SyncMode::Always);
+    /// assert_eq!(map.sync_mode(), SyncMode::Always);
+    /// # fs::remove_file("example_radix_bp_map_set_sync_mode")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_sync_mode(&mut self, sync_mode: SyncMode) {
+        self.sync_state.set_mode(sync_mode);
+    }
+
+    /// Hands all outstanding writes to the operating system without waiting for them to reach the
+    /// disk. Flushed writes survive a crash of the process, but not a crash of the machine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let mut map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_flush")?;
+    /// map.insert(b"foo", 1)?;
+    /// map.flush()?;
+    /// # fs::remove_file("example_radix_bp_map_flush")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn flush(&mut self) -> Result<()> {
+        self.pager.flush()
+    }
+
+    /// Syncs all writes to disk and waits until they reach the disk. Synced writes survive a
+    /// crash of the machine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::bp_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::bp_tree::RadixBpMap;
+    ///
+    /// let mut map: RadixBpMap<u32> = RadixBpMap::new("example_radix_bp_map_sync")?;
+    /// map.insert(b"foo", 1)?;
+    /// map.sync()?;
+    ///
+    /// let map: RadixBpMap<u32> = RadixBpMap::open("example_radix_bp_map_sync")?;
+    /// assert_eq!(map.len(), 1);
+    /// # fs::remove_file("example_radix_bp_map_sync")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn sync(&mut self) -> Result<()> {
+        self.pager.sync()?;
+        self.sync_state.record_sync();
+        Ok(())
+    }
+}
+
+impl<T, K> Drop for RadixBpMap<T, K> {
+    fn drop(&mut self) {
+        // errors cannot be reported when the map is dropped, so `close` should be used instead if
+        // they need to be handled.
+        if self.sync_state.should_sync_on_drop() {
+            let _ = self.sync();
+        } else {
+            let _ = self.flush();
+        }
+    }
+}
+
+impl<'a, T, K> IntoIterator for &'a mut RadixBpMap<T, K>
+where
+    T: 'a + DeserializeOwned,
+    K: 'a + RadixKey,
+{
+    type IntoIter = RadixBpMapIterMut<'a, T, K>;
+    type Item = Result<(K, T)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut().unwrap()
+    }
+}
+
+/// A mutable iterator for `RadixBpMap<T, K>`.
+///
+/// This iterator traverses the elements of the map in lexicographic order of their keys and
+/// yields owned entries.
+pub struct RadixBpMapIterMut<'a, T, K> {
+    pager: &'a mut RadixPager<T>,
+    prefix: Vec<u8>,
+    // (page, length of the key of the parent of the node)
+    stack: Vec<(usize, usize)>,
+    _marker: PhantomData<K>,
+}
+
+impl<'a, T, K> Iterator for RadixBpMapIterMut<'a, T, K>
+where
+    T: 'a + DeserializeOwned,
+    K: 'a + RadixKey,
+{
+    type Item = Result<(K, T)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((page, prefix_len)) = self.stack.pop() {
+            let node = match self.pager.get_node(page) {
+                Ok(node) => node,
+                Err(error) => {
+                    self.stack.clear();
+                    return Some(Err(error));
+                }
+            };
+            self.prefix.truncate(prefix_len);
+            self.prefix.extend_from_slice(&node.key);
+            let prefix_len = self.prefix.len();
+            self.stack.extend(
+                node.children
+                    .iter()
+                    .rev()
+                    .map(|&(_, child_page)| (child_page, prefix_len)),
+            );
+            if let Some(value) = node.value {
+                return Some(Ok((K::from_bytes(self.prefix.clone()), value)));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RadixBpMap, MAX_KEY_FRAGMENT_SIZE, MAX_VALUE_SIZE};
+    use crate::bp_tree::{Error, Result};
+    use rand::{Rng, SeedableRng, XorShiftRng};
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::panic;
+
+    fn teardown(test_name: &str) {
+        fs::remove_file(test_name).ok();
+    }
+
+    fn run_test<T>(test: T, test_name: &str)
+    where
+        T: FnOnce() -> Result<()> + panic::UnwindSafe,
+    {
+        let result = panic::catch_unwind(|| test().unwrap());
+
+        teardown(test_name);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_len_empty() {
+        let test_name = "test_radix_bp_map_len_empty";
+        run_test(
+            || {
+                let map: RadixBpMap<u32> = RadixBpMap::new(test_name)?;
+                assert_eq!(map.len(), 0);
+                assert!(map.is_empty());
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_insert_split() {
+        let test_name = "test_radix_bp_map_insert_split";
+        run_test(
+            || {
+                let mut map: RadixBpMap<u32> = RadixBpMap::new(test_name)?;
+                assert_eq!(map.insert(b"foobar", 0)?, None);
+                assert_eq!(map.insert(b"foo", 1)?, None);
+                assert_eq!(map.insert(b"foobaz", 2)?, None);
+                assert_eq!(map.insert(b"", 3)?, None);
+                assert_eq!(map.len(), 4);
+                assert_eq!(map.get(b"foobar")?, Some(0));
+                assert_eq!(map.get(b"foo")?, Some(1));
+                assert_eq!(map.get(b"foobaz")?, Some(2));
+                assert_eq!(map.get(b"")?, Some(3));
+                assert_eq!(map.get(b"fooba")?, None);
+                assert_eq!(map.get(b"foobarr")?, None);
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_insert_replace() {
+        let test_name = "test_radix_bp_map_insert_replace";
+        run_test(
+            || {
+                let mut map: RadixBpMap<u32> = RadixBpMap::new(test_name)?;
+                assert_eq!(map.insert(b"foo", 0)?, None);
+                assert_eq!(map.insert(b"foo", 1)?, Some((b"foo".to_vec(), 0)));
+                assert_eq!(map.get(b"foo")?, Some(1));
+                assert_eq!(map.len(), 1);
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_value_too_large() {
+        let test_name = "test_radix_bp_map_value_too_large";
+        run_test(
+            || {
+                let mut map: RadixBpMap<Vec<u8>> = RadixBpMap::new(test_name)?;
+                let value = vec![0; MAX_VALUE_SIZE as usize];
+                match map.insert(b"foo", value) {
+                    Err(Error::ValueTooLarge { max, actual }) => {
+                        assert_eq!(max, MAX_VALUE_SIZE);
+                        assert_eq!(actual, MAX_VALUE_SIZE + 8);
+                    }
+                    _ => panic!("Expected `Error::ValueTooLarge`."),
+                }
+                assert!(map.is_empty());
+
+                let value = vec![0; MAX_VALUE_SIZE as usize - 8];
+                assert_eq!(map.insert(b"foo", value.clone())?, None);
+                assert_eq!(map.get(b"foo")?, Some(value));
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_long_keys() {
+        let test_name = "test_radix_bp_map_long_keys";
+        run_test(
+            || {
+                let mut map: RadixBpMap<u32> = RadixBpMap::new(test_name)?;
+                let len = MAX_KEY_FRAGMENT_SIZE as usize * 3 + 1;
+                let mut keys = Vec::new();
+                for i in 0..4 {
+                    let mut key = vec![b'a'; len];
+                    key[len - 1 - i * 400] = b'b';
+                    keys.push(key);
+                }
+                for (i, key) in keys.iter().enumerate() {
+                    assert_eq!(map.insert(key, i as u32)?, None);
+                }
+                for (i, key) in keys.iter().enumerate() {
+                    assert_eq!(map.get(key)?, Some(i as u32));
+                }
+                assert_eq!(map.get(&vec![b'a'; len])?, None);
+                for (i, key) in keys.iter().enumerate() {
+                    assert_eq!(map.remove(key)?, Some((key.clone(), i as u32)));
+                }
+                assert!(map.is_empty());
+                assert_eq!(map.iter_mut()?.count(), 0);
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_remove_reuses_pages() {
+        let test_name = "test_radix_bp_map_remove_reuses_pages";
+        run_test(
+            || {
+                let mut map: RadixBpMap<u32, String> = RadixBpMap::new(test_name)?;
+                for i in 0..100 {
+                    map.insert(&format!("/path/{}", i), i)?;
+                }
+                let page_count = map.pager.get_page_count();
+                for i in 0..100 {
+                    assert_eq!(
+                        map.remove(&format!("/path/{}", i))?,
+                        Some((format!("/path/{}", i), i))
+                    );
+                }
+                assert!(map.is_empty());
+                for i in 0..100 {
+                    map.insert(&format!("/path/{}", i), i)?;
+                }
+                assert_eq!(map.pager.get_page_count(), page_count);
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_iter_prefix_mut() {
+        let test_name = "test_radix_bp_map_iter_prefix_mut";
+        run_test(
+            || {
+                let mut map: RadixBpMap<u32, String> = RadixBpMap::new(test_name)?;
+                map.insert("http://a.com/", 0)?;
+                map.insert("http://a.com/x", 1)?;
+                map.insert("http://a.com/y", 2)?;
+                map.insert("http://b.com/", 3)?;
+                map.insert("https://a.com/", 4)?;
+
+                let get_keys = |map: &mut RadixBpMap<u32, String>, prefix: &str| {
+                    map.iter_prefix_mut(prefix)
+                        .unwrap()
+                        .map(|entry| entry.unwrap().1)
+                        .collect::<Vec<u32>>()
+                };
+                assert_eq!(get_keys(&mut map, ""), vec![0, 1, 2, 3, 4]);
+                assert_eq!(get_keys(&mut map, "http"), vec![0, 1, 2, 3, 4]);
+                assert_eq!(get_keys(&mut map, "http:"), vec![0, 1, 2, 3]);
+                assert_eq!(get_keys(&mut map, "http://a"), vec![0, 1, 2]);
+                assert_eq!(get_keys(&mut map, "http://a.com/"), vec![0, 1, 2]);
+                assert_eq!(get_keys(&mut map, "http://a.com/x"), vec![1]);
+                assert_eq!(get_keys(&mut map, "http://a.com/xx"), Vec::<u32>::new());
+                assert_eq!(get_keys(&mut map, "http://c"), Vec::<u32>::new());
+                assert_eq!(get_keys(&mut map, "ftp"), Vec::<u32>::new());
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_clear() {
+        let test_name = "test_radix_bp_map_clear";
+        run_test(
+            || {
+                let mut map: RadixBpMap<u32> = RadixBpMap::new(test_name)?;
+                map.insert(b"foo", 1)?;
+                map.insert(b"bar", 2)?;
+                map.clear()?;
+                assert!(map.is_empty());
+                assert_eq!(map.get(b"foo")?, None);
+                assert_eq!(map.pager.get_page_count(), 1);
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_open() {
+        let test_name = "test_radix_bp_map_open";
+        run_test(
+            || {
+                let mut map: RadixBpMap<u32> = RadixBpMap::new(test_name)?;
+                map.insert(b"foo", 1)?;
+                map.insert(b"foobar", 2)?;
+                map.close()?;
+
+                let mut map: RadixBpMap<u32> = RadixBpMap::open(test_name)?;
+                assert_eq!(map.len(), 2);
+                assert_eq!(map.get(b"foo")?, Some(1));
+                assert_eq!(map.get(b"foobar")?, Some(2));
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_open_corrupted() {
+        let test_name = "test_radix_bp_map_open_corrupted";
+        run_test(
+            || {
+                fs::write(test_name, b"")?;
+                match RadixBpMap::<u32>::open(test_name) {
+                    Err(Error::FileIOError { .. }) => {}
+                    _ => panic!("Expected `Error::FileIOError`."),
+                }
+                Ok(())
+            },
+            test_name,
+        );
+    }
+
+    #[test]
+    fn test_random() {
+        let test_name = "test_radix_bp_map_random";
+        run_test(
+            || {
+                let mut rng: XorShiftRng = SeedableRng::from_seed([1, 1, 1, 1]);
+                let mut map: RadixBpMap<u32> = RadixBpMap::new(test_name)?;
+                let mut expected = BTreeMap::new();
+                for _ in 0..2000 {
+                    let len = rng.gen_range(0, 8);
+                    let key: Vec<u8> = (0..len).map(|_| rng.gen_range(b'a', b'd')).collect();
+                    let value = rng.next_u32();
+
+                    if rng.gen_weighted_bool(3) {
+                        assert_eq!(map.remove(&key)?, expected.remove(&key).map(|v| (key, v)));
+                    } else {
+                        let old_value = expected.insert(key.clone(), value);
+                        assert_eq!(map.insert(&key, value)?, old_value.map(|v| (key, v)));
+                    }
+                    assert_eq!(map.len(), expected.len());
+                }
+
+                let entries = map.iter_mut()?.collect::<Result<Vec<_>>>()?;
+                assert_eq!(entries, expected.into_iter().collect::<Vec<_>>());
+                Ok(())
+            },
+            test_name,
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+// The backing file of a pager. Pages are either accessed with seeks, reads, and writes, or through
+// a shared memory mapping of the file.
+pub enum Storage {
+    File(File),
+    #[cfg(unix)]
+    Mmap(mmap::MmapFile),
+}
+
+impl Storage {
+    pub fn read_at(&mut self, offset: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        match self {
+            Storage::File(file) => {
+                let mut buffer = vec![0; len];
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(buffer.as_mut_slice())?;
+                Ok(Cow::Owned(buffer))
+            }
+            #[cfg(unix)]
+            Storage::Mmap(file) => file.slice(offset, len).map(Cow::Borrowed),
+        }
+    }
+
+    pub fn write_at(&mut self, offset: u64, data: &[u8]) -> io::Result<()> {
+        match self {
+            Storage::File(file) => {
+                file.seek(SeekFrom::Start(offset))?;
+                file.write_all(data)
+            }
+            #[cfg(unix)]
+            Storage::Mmap(file) => {
+                file.slice_mut(offset, data.len())?.copy_from_slice(data);
+                Ok(())
+            }
+        }
+    }
+
+    // Ensures that the storage is at least `len` bytes long.
+    pub fn reserve(&mut self, len: u64) -> io::Result<()> {
+        match self {
+            Storage::File(file) => file.set_len(len),
+            #[cfg(unix)]
+            Storage::Mmap(file) => file.reserve(len),
+        }
+    }
+
+    pub fn set_len(&mut self, len: u64) -> io::Result<()> {
+        match self {
+            Storage::File(file) => file.set_len(len),
+            #[cfg(unix)]
+            Storage::Mmap(file) => file.set_len(len),
+        }
+    }
+
+    // Hands the outstanding writes to the operating system without waiting for them to reach the
+    // storage device.
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Storage::File(file) => file.flush(),
+            #[cfg(unix)]
+            Storage::Mmap(file) => file.flush(),
+        }
+    }
+
+    pub fn sync(&mut self) -> io::Result<()> {
+        match self {
+            Storage::File(file) => file.sync_all(),
+            #[cfg(unix)]
+            Storage::Mmap(file) => file.sync(),
+        }
+    }
+}
+
+#[cfg(unix)]
+pub mod mmap {
+    use std::cmp;
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+    use std::ptr;
+    use std::slice;
+
+    // A file that is mapped into memory in its entirety. The file grows geometrically so that the
+    // mapping is rarely recreated when pages are allocated.
+    pub struct MmapFile {
+        file: File,
+        ptr: *mut u8,
+        len: usize,
+    }
+
+    // The mapping is exclusively owned by the `MmapFile` and is only accessed through references
+    // to it.
+    unsafe impl Send for MmapFile {}
+    unsafe impl Sync for MmapFile {}
+
+    impl MmapFile {
+        pub fn new(file: File) -> io::Result<Self> {
+            let len = file.metadata()?.len() as usize;
+            let mut ret = MmapFile {
+                file,
+                ptr: ptr::null_mut(),
+                len: 0,
+            };
+            ret.map(len)?;
+            Ok(ret)
+        }
+
+        fn map(&mut self, len: usize) -> io::Result<()> {
+            if len == 0 {
+                return Ok(());
+            }
+            let ptr = unsafe {
+                libc::mmap(
+                    ptr::null_mut(),
+                    len,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    libc::MAP_SHARED,
+                    self.file.as_raw_fd(),
+                    0,
+                )
+            };
+            if ptr == libc::MAP_FAILED {
+                return Err(io::Error::last_os_error());
+            }
+            self.ptr = ptr as *mut u8;
+            self.len = len;
+            Ok(())
+        }
+
+        fn unmap(&mut self) {
+            if !self.ptr.is_null() {
+                unsafe {
+                    libc::munmap(self.ptr as *mut libc::c_void, self.len);
+                }
+                self.ptr = ptr::null_mut();
+                self.len = 0;
+            }
+        }
+
+        fn check_bounds(&self, offset: u64, len: usize) -> io::Result<usize> {
+            let offset = offset as usize;
+            match offset.checked_add(len) {
+                Some(end) if end <= self.len => Ok(offset),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Error: access past the end of the mapped file.",
+                )),
+            }
+        }
+
+        pub fn slice(&self, offset: u64, len: usize) -> io::Result<&[u8]> {
+            let offset = self.check_bounds(offset, len)?;
+            if len == 0 {
+                return Ok(&[]);
+            }
+            Ok(unsafe { slice::from_raw_parts(self.ptr.add(offset), len) })
+        }
+
+        pub fn slice_mut(&mut self, offset: u64, len: usize) -> io::Result<&mut [u8]> {
+            let offset = self.check_bounds(offset, len)?;
+            if len == 0 {
+                return Ok(&mut []);
+            }
+            Ok(unsafe { slice::from_raw_parts_mut(self.ptr.add(offset), len) })
+        }
+
+        pub fn reserve(&mut self, len: u64) -> io::Result<()> {
+            if len as usize <= self.len {
+                return Ok(());
+            }
+            let new_len = cmp::max(len as usize, self.len * 2);
+            self.set_len(new_len as u64)
+        }
+
+        pub fn set_len(&mut self, len: u64) -> io::Result<()> {
+            self.unmap();
+            self.file.set_len(len)?;
+            self.map(len as usize)
+        }
+
+        fn msync(&mut self, flags: libc::c_int) -> io::Result<()> {
+            if !self.ptr.is_null() {
+                let res = unsafe { libc::msync(self.ptr as *mut libc::c_void, self.len, flags) };
+                if res != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        }
+
+        // Schedules the dirty pages of the mapping to be written back without waiting for them.
+        pub fn flush(&mut self) -> io::Result<()> {
+            self.msync(libc::MS_ASYNC)
+        }
+
+        pub fn sync(&mut self) -> io::Result<()> {
+            self.msync(libc::MS_SYNC)?;
+            self.file.sync_all()
+        }
+    }
+
+    impl Drop for MmapFile {
+        fn drop(&mut self) {
+            self.unmap();
+        }
+    }
+}
@@ -0,0 +1,835 @@
+use crate::btree::node::Node;
+use crate::entry::Entry;
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{Bound, Index, IndexMut, RangeBounds};
+use std::slice;
+use std::vec;
+
+/// An ordered map implemented using a B-tree whose nodes are augmented with the sizes of their
+/// subtrees.
+///
+/// A B-tree stores multiple entries in each node, which keeps the tree shallow and makes better
+/// use of the cache than a binary search tree. Each node additionally records the number of
+/// entries in its subtree, so the map can find the entry with a particular rank, the rank of a
+/// particular key, and the number of keys in a range in logarithmic time.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::btree::BTreeMapExt;
+///
+/// let mut map = BTreeMapExt::new();
+/// map.insert(0, 1);
+/// map.insert(3, 4);
+///
+/// assert_eq!(map[&0], 1);
+/// assert_eq!(map.get(&1), None);
+/// assert_eq!(map.len(), 2);
+///
+/// assert_eq!(map.select(1), Some((&3, &4)));
+/// assert_eq!(map.rank(&3), 1);
+/// assert_eq!(map.range_count(1..), 1);
+///
+/// map[&0] = 2;
+/// assert_eq!(map.remove(&0), Some((0, 2)));
+/// assert_eq!(map.remove(&1), None);
+/// ```
+#[derive(Clone)]
+pub struct BTreeMapExt<T, U> {
+    root: Node<T, U>,
+}
+
+impl<T, U> BTreeMapExt<T, U> {
+    /// Constructs a new, empty `BTreeMapExt<T, U>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let map: BTreeMapExt<u32, u32> = BTreeMapExt::new();
+    /// ```
+    pub fn new() -> Self {
+        BTreeMapExt { root: Node::new() }
+    }
+
+    /// Inserts a key-value pair into the map. If the key already exists in the map, it will return
+    /// and replace the old key-value pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// assert_eq!(map.insert(1, 1), None);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// assert_eq!(map.insert(1, 2), Some((1, 1)));
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// ```
+    pub fn insert(&mut self, key: T, value: U) -> Option<(T, U)>
+    where
+        T: Ord,
+    {
+        if let Some(entry) = self.root.get_mut(&key) {
+            return Some((
+                mem::replace(&mut entry.key, key),
+                mem::replace(&mut entry.value, value),
+            ));
+        }
+
+        if self.root.is_full() {
+            let mut new_root = Node::new();
+            new_root.size = self.root.size;
+            new_root
+                .children
+                .push(mem::replace(&mut self.root, Node::new()));
+            new_root.split_child(0);
+            self.root = new_root;
+        }
+        self.root.insert_non_full(Entry { key, value });
+        None
+    }
+
+    /// Removes a key-value pair from the map. If the key exists in the map, it will return the
+    /// associated key-value pair. Otherwise it will return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.remove(&1), Some((1, 1)));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<V>(&mut self, key: &V) -> Option<(T, U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        if !self.contains_key(key) {
+            return None;
+        }
+
+        let Entry { key, value } = self.root.remove(key);
+        if self.root.entries.is_empty() && !self.root.is_leaf() {
+            self.root = self.root.children.pop().expect("Expected non-empty node.");
+        }
+        Some((key, value))
+    }
+
+    /// Checks if a key exists in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// map.insert(1, 1);
+    /// assert!(!map.contains_key(&0));
+    /// assert!(map.contains_key(&1));
+    /// ```
+    pub fn contains_key<V>(&self, key: &V) -> bool
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Returns an immutable reference to the value associated with a particular key. It will
+    /// return `None` if the key does not exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.get(&0), None);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// ```
+    pub fn get<V>(&self, key: &V) -> Option<&U>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        self.root.get(key).map(|entry| &entry.value)
+    }
+
+    /// Returns a mutable reference to the value associated with a particular key. Returns `None`
+    /// if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// map.insert(1, 1);
+    /// *map.get_mut(&1).unwrap() = 2;
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// ```
+    pub fn get_mut<V>(&mut self, key: &V) -> Option<&mut U>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        self.root.get_mut(key).map(|entry| &mut entry.value)
+    }
+
+    /// Returns the number of elements in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.root.size
+    }
+
+    /// Returns `true` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let map: BTreeMapExt<u32, u32> = BTreeMapExt::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Clears the map, removing all values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.clear();
+    /// assert_eq!(map.is_empty(), true);
+    /// ```
+    pub fn clear(&mut self) {
+        self.root = Node::new();
+    }
+
+    /// Returns the minimum key of the map. Returns `None` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.min(), Some(&1));
+    /// ```
+    pub fn min(&self) -> Option<&T> {
+        self.root.min().map(|entry| &entry.key)
+    }
+
+    /// Returns the maximum key of the map. Returns `None` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.max(), Some(&3));
+    /// ```
+    pub fn max(&self) -> Option<&T> {
+        self.root.max().map(|entry| &entry.key)
+    }
+
+    /// Returns the key-value pair with `k` keys less than its key, which is the `k`-th smallest
+    /// key-value pair counting from zero. Returns `None` if `k` is not less than the number of
+    /// entries in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.select(0), Some((&1, &1)));
+    /// assert_eq!(map.select(1), Some((&3, &3)));
+    /// assert_eq!(map.select(2), None);
+    /// ```
+    pub fn select(&self, k: usize) -> Option<(&T, &U)> {
+        if k >= self.len() {
+            return None;
+        }
+        let entry = self.root.select(k);
+        Some((&entry.key, &entry.value))
+    }
+
+    /// Returns the number of keys in the map that are less than a particular key. The key does not
+    /// need to exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.rank(&1), 0);
+    /// assert_eq!(map.rank(&2), 1);
+    /// assert_eq!(map.rank(&4), 2);
+    /// ```
+    pub fn rank<V>(&self, key: &V) -> usize
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        self.root.rank_by(|curr| curr.borrow() < key)
+    }
+
+    /// Returns the number of keys in the map that are in a particular range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.range_count(2..), 2);
+    /// assert_eq!(map.range_count(1..3), 2);
+    /// assert_eq!(map.range_count(..=3), 3);
+    /// assert_eq!(map.range_count(4..), 0);
+    /// ```
+    pub fn range_count<V, R>(&self, range: R) -> usize
+    where
+        T: Borrow<V>,
+        R: RangeBounds<V>,
+        V: Ord + ?Sized,
+    {
+        let start_rank = match range.start_bound() {
+            Bound::Included(start) => self.root.rank_by(|key| key.borrow() < start),
+            Bound::Excluded(start) => self.root.rank_by(|key| key.borrow() <= start),
+            Bound::Unbounded => 0,
+        };
+        let end_rank = match range.end_bound() {
+            Bound::Included(end) => self.root.rank_by(|key| key.borrow() <= end),
+            Bound::Excluded(end) => self.root.rank_by(|key| key.borrow() < end),
+            Bound::Unbounded => self.len(),
+        };
+        end_rank.saturating_sub(start_rank)
+    }
+
+    /// Returns an iterator over the map. The iterator will yield key-value pairs in ascending
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// let mut iterator = map.iter();
+    /// assert_eq!(iterator.next(), Some((&1, &1)));
+    /// assert_eq!(iterator.next(), Some((&2, &2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> BTreeMapExtIter<'_, T, U> {
+        let mut iter = BTreeMapExtIter {
+            stack: Vec::new(),
+            len: self.len(),
+        };
+        iter.push_left(&self.root);
+        iter
+    }
+
+    /// Returns a mutable iterator over the map. The iterator will yield key-value pairs in
+    /// ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::btree::BTreeMapExt;
+    ///
+    /// let mut map = BTreeMapExt::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// for (key, value) in &mut map {
+    ///     *value += 1;
+    /// }
+    ///
+    /// let mut iterator = map.iter_mut();
+    /// assert_eq!(iterator.next(), Some((&1, &mut 2)));
+    /// assert_eq!(iterator.next(), Some((&2, &mut 3)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter_mut(&mut self) -> BTreeMapExtIterMut<'_, T, U> {
+        let mut iter = BTreeMapExtIterMut {
+            stack: Vec::new(),
+            len: self.len(),
+        };
+        iter.push_left(&mut self.root);
+        iter
+    }
+}
+
+impl<T, U> IntoIterator for BTreeMapExt<T, U> {
+    type IntoIter = BTreeMapExtIntoIter<T, U>;
+    type Item = (T, U);
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut iter = BTreeMapExtIntoIter {
+            stack: Vec::new(),
+            len: self.len(),
+        };
+        iter.push_left(self.root);
+        iter
+    }
+}
+
+impl<'a, T, U> IntoIterator for &'a BTreeMapExt<T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type IntoIter = BTreeMapExtIter<'a, T, U>;
+    type Item = (&'a T, &'a U);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, U> IntoIterator for &'a mut BTreeMapExt<T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type IntoIter = BTreeMapExtIterMut<'a, T, U>;
+    type Item = (&'a T, &'a mut U);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// The remaining entries and children of a node that is being traversed by an iterator.
+type IntoIterFrame<T, U> = (vec::IntoIter<Entry<T, U>>, vec::IntoIter<Node<T, U>>);
+type IterFrame<'a, T, U> = (slice::Iter<'a, Entry<T, U>>, slice::Iter<'a, Node<T, U>>);
+type IterMutFrame<'a, T, U> = (
+    slice::IterMut<'a, Entry<T, U>>,
+    slice::IterMut<'a, Node<T, U>>,
+);
+
+/// An owning iterator for `BTreeMapExt<T, U>`.
+///
+/// This iterator traverses the elements of the map in ascending order and yields owned entries.
+pub struct BTreeMapExtIntoIter<T, U> {
+    stack: Vec<IntoIterFrame<T, U>>,
+    len: usize,
+}
+
+impl<T, U> BTreeMapExtIntoIter<T, U> {
+    fn push_left(&mut self, mut node: Node<T, U>) {
+        loop {
+            let mut children = node.children.into_iter();
+            let child = children.next();
+            self.stack.push((node.entries.into_iter(), children));
+            match child {
+                Some(child) => node = child,
+                None => return,
+            }
+        }
+    }
+}
+
+impl<T, U> Iterator for BTreeMapExtIntoIter<T, U> {
+    type Item = (T, U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (entry, child) = {
+                let (entries, children) = self.stack.last_mut()?;
+                match entries.next() {
+                    Some(entry) => (entry, children.next()),
+                    None => {
+                        self.stack.pop();
+                        continue;
+                    }
+                }
+            };
+            if let Some(child) = child {
+                self.push_left(child);
+            }
+            self.len -= 1;
+            return Some((entry.key, entry.value));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+/// An iterator for `BTreeMapExt<T, U>`.
+///
+/// This iterator traverses the elements of the map in ascending order and yields immutable
+/// references.
+pub struct BTreeMapExtIter<'a, T, U> {
+    stack: Vec<IterFrame<'a, T, U>>,
+    len: usize,
+}
+
+impl<'a, T, U> BTreeMapExtIter<'a, T, U> {
+    fn push_left(&mut self, mut node: &'a Node<T, U>) {
+        loop {
+            let mut children = node.children.iter();
+            let child = children.next();
+            self.stack.push((node.entries.iter(), children));
+            match child {
+                Some(child) => node = child,
+                None => return,
+            }
+        }
+    }
+}
+
+impl<'a, T, U> Iterator for BTreeMapExtIter<'a, T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type Item = (&'a T, &'a U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (entry, child) = {
+                let (entries, children) = self.stack.last_mut()?;
+                match entries.next() {
+                    Some(entry) => (entry, children.next()),
+                    None => {
+                        self.stack.pop();
+                        continue;
+                    }
+                }
+            };
+            if let Some(child) = child {
+                self.push_left(child);
+            }
+            self.len -= 1;
+            return Some((&entry.key, &entry.value));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+/// A mutable iterator for `BTreeMapExt<T, U>`.
+///
+/// This iterator traverses the elements of the map in ascending order and yields mutable
+/// references.
+pub struct BTreeMapExtIterMut<'a, T, U> {
+    stack: Vec<IterMutFrame<'a, T, U>>,
+    len: usize,
+}
+
+impl<'a, T, U> BTreeMapExtIterMut<'a, T, U> {
+    fn push_left(&mut self, mut node: &'a mut Node<T, U>) {
+        loop {
+            let mut children = node.children.iter_mut();
+            let child = children.next();
+            self.stack.push((node.entries.iter_mut(), children));
+            match child {
+                Some(child) => node = child,
+                None => return,
+            }
+        }
+    }
+}
+
+impl<'a, T, U> Iterator for BTreeMapExtIterMut<'a, T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type Item = (&'a T, &'a mut U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (entry, child) = {
+                let (entries, children) = self.stack.last_mut()?;
+                match entries.next() {
+                    Some(entry) => (entry, children.next()),
+                    None => {
+                        self.stack.pop();
+                        continue;
+                    }
+                }
+            };
+            if let Some(child) = child {
+                self.push_left(child);
+            }
+            self.len -= 1;
+            return Some((&entry.key, &mut entry.value));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, U> fmt::Debug for BTreeMapExt<T, U>
+where
+    T: fmt::Debug,
+    U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, U> PartialEq for BTreeMapExt<T, U>
+where
+    T: PartialEq,
+    U: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T, U> Eq for BTreeMapExt<T, U>
+where
+    T: Eq,
+    U: Eq,
+{
+}
+
+impl<T, U> Default for BTreeMapExt<T, U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, U> FromIterator<(T, U)> for BTreeMapExt<T, U>
+where
+    T: Ord,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (T, U)>,
+    {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<T, U> Extend<(T, U)> for BTreeMapExt<T, U>
+where
+    T: Ord,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (T, U)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<T, U, V> Index<&V> for BTreeMapExt<T, U>
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    type Output = U;
+
+    fn index(&self, key: &V) -> &Self::Output {
+        self.get(key).expect("Error: key does not exist.")
+    }
+}
+
+impl<T, U, V> IndexMut<&V> for BTreeMapExt<T, U>
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    fn index_mut(&mut self, key: &V) -> &mut Self::Output {
+        self.get_mut(key).expect("Error: key does not exist.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BTreeMapExt;
+    use crate::btree::node::{Node, MIN_DEGREE};
+    use rand::{Rng, SeedableRng, XorShiftRng};
+    use std::collections::BTreeMap;
+
+    // Checks the ordering, occupancy, depth, and subtree sizes of the tree. Returns the depth of
+    // the subtree.
+    fn check_node(node: &Node<u32, u32>, is_root: bool) -> usize {
+        if !is_root {
+            assert!(node.entries.len() >= MIN_DEGREE - 1);
+        }
+        assert!(node.entries.len() < 2 * MIN_DEGREE);
+        assert!(node
+            .entries
+            .windows(2)
+            .all(|pair| pair[0].key < pair[1].key));
+        if node.is_leaf() {
+            assert_eq!(node.size, node.entries.len());
+            return 1;
+        }
+
+        assert_eq!(node.children.len(), node.entries.len() + 1);
+        let depths: Vec<usize> = node
+            .children
+            .iter()
+            .map(|child| check_node(child, false))
+            .collect();
+        assert!(depths.iter().all(|depth| *depth == depths[0]));
+        for (index, entry) in node.entries.iter().enumerate() {
+            assert!(node.children[index].max().unwrap().key < entry.key);
+            assert!(node.children[index + 1].min().unwrap().key > entry.key);
+        }
+        let size: usize = node.children.iter().map(|child| child.size).sum();
+        assert_eq!(node.size, size + node.entries.len());
+        depths[0] + 1
+    }
+
+    #[test]
+    fn test_len_empty() {
+        let map: BTreeMapExt<u32, u32> = BTreeMapExt::new();
+        assert_eq!(map.len(), 0);
+        assert_eq!(map.select(0), None);
+        assert_eq!(map.rank(&0), 0);
+    }
+
+    #[test]
+    fn test_insert_replace() {
+        let mut map = BTreeMapExt::new();
+        assert_eq!(map.insert(1, 1), None);
+        assert_eq!(map.insert(1, 3), Some((1, 1)));
+        assert_eq!(map.get(&1), Some(&3));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn test_min_max() {
+        let mut map = BTreeMapExt::new();
+        assert_eq!(map.min(), None);
+        for key in 0..100 {
+            map.insert(key, key);
+        }
+        assert_eq!(map.min(), Some(&0));
+        assert_eq!(map.max(), Some(&99));
+    }
+
+    #[test]
+    fn test_select_rank() {
+        let map: BTreeMapExt<u32, u32> = (0..1000).map(|key| (key * 2, key)).collect();
+        check_node(&map.root, true);
+        for key in 0..1000 {
+            assert_eq!(map.select(key as usize), Some((&(key * 2), &key)));
+            assert_eq!(map.rank(&(key * 2)), key as usize);
+            assert_eq!(map.rank(&(key * 2 + 1)), key as usize + 1);
+        }
+        assert_eq!(map.select(1000), None);
+    }
+
+    #[test]
+    fn test_range_count() {
+        let map: BTreeMapExt<u32, u32> = (0..100).map(|key| (key, key)).collect();
+        assert_eq!(map.range_count(..), 100);
+        assert_eq!(map.range_count(10..20), 10);
+        assert_eq!(map.range_count(10..=20), 11);
+        assert_eq!(map.range_count(90..200), 10);
+        let (start, end) = (20, 10);
+        assert_eq!(map.range_count(start..end), 0);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut map: BTreeMapExt<u32, u32> = (0..100).rev().map(|key| (key, key)).collect();
+        assert_eq!(map.iter().size_hint(), (100, Some(100)));
+        for (key, value) in &mut map {
+            *value += key;
+        }
+        assert_eq!(
+            map.into_iter().collect::<Vec<(u32, u32)>>(),
+            (0..100)
+                .map(|key| (key, key * 2))
+                .collect::<Vec<(u32, u32)>>(),
+        );
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng: XorShiftRng = SeedableRng::from_seed([1, 1, 1, 1]);
+        let mut map = BTreeMapExt::new();
+        let mut expected = BTreeMap::new();
+        for _ in 0..10_000 {
+            let key = rng.gen_range(0, 1000);
+            if rng.gen::<bool>() {
+                assert_eq!(
+                    map.insert(key, key).is_some(),
+                    expected.insert(key, key).is_some()
+                );
+            } else {
+                assert_eq!(
+                    map.remove(&key).map(|(_, value)| value),
+                    expected.remove(&key)
+                );
+            }
+        }
+        check_node(&map.root, true);
+        assert_eq!(map.len(), expected.len());
+        assert!(map.iter().eq(expected.iter()));
+        for (rank, key) in expected.keys().enumerate() {
+            assert_eq!(map.select(rank).map(|(key, _)| key), Some(key));
+            assert_eq!(map.rank(key), rank);
+        }
+
+        let keys: Vec<u32> = expected.keys().cloned().collect();
+        for key in keys {
+            assert_eq!(map.remove(&key), Some((key, key)));
+        }
+        check_node(&map.root, true);
+        assert!(map.is_empty());
+    }
+}
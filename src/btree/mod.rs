@@ -0,0 +1,7 @@
+//! In-memory B-tree whose nodes are augmented with the sizes of their subtrees to support order
+//! statistics.
+
+mod map;
+mod node;
+
+pub use self::map::{BTreeMapExt, BTreeMapExtIntoIter, BTreeMapExtIter, BTreeMapExtIterMut};
@@ -0,0 +1,316 @@
+use crate::entry::Entry;
+use std::borrow::Borrow;
+use std::mem;
+
+// The minimum degree of the tree. Every node other than the root has between `MIN_DEGREE - 1` and
+// `2 * MIN_DEGREE - 1` entries.
+pub const MIN_DEGREE: usize = 6;
+const MAX_ENTRIES: usize = 2 * MIN_DEGREE - 1;
+
+#[derive(Clone)]
+pub struct Node<T, U> {
+    pub entries: Vec<Entry<T, U>>,
+    pub children: Vec<Node<T, U>>,
+    // The number of entries in the subtree rooted at this node.
+    pub size: usize,
+}
+
+impl<T, U> Node<T, U> {
+    pub fn new() -> Self {
+        Node {
+            entries: Vec::with_capacity(MAX_ENTRIES),
+            children: Vec::new(),
+            size: 0,
+        }
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.entries.len() == MAX_ENTRIES
+    }
+
+    fn search<V>(&self, key: &V) -> Result<usize, usize>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        self.entries
+            .binary_search_by(|entry| entry.key.borrow().cmp(key))
+    }
+
+    pub fn get<V>(&self, key: &V) -> Option<&Entry<T, U>>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        let mut curr = self;
+        loop {
+            match curr.search(key) {
+                Ok(index) => return Some(&curr.entries[index]),
+                Err(index) => {
+                    if curr.is_leaf() {
+                        return None;
+                    }
+                    curr = &curr.children[index];
+                }
+            }
+        }
+    }
+
+    pub fn get_mut<V>(&mut self, key: &V) -> Option<&mut Entry<T, U>>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        let mut curr = self;
+        loop {
+            match curr.search(key) {
+                Ok(index) => return Some(&mut curr.entries[index]),
+                Err(index) => {
+                    if curr.is_leaf() {
+                        return None;
+                    }
+                    curr = &mut curr.children[index];
+                }
+            }
+        }
+    }
+
+    pub fn min(&self) -> Option<&Entry<T, U>> {
+        let mut curr = self;
+        while !curr.is_leaf() {
+            curr = &curr.children[0];
+        }
+        curr.entries.first()
+    }
+
+    pub fn max(&self) -> Option<&Entry<T, U>> {
+        let mut curr = self;
+        while !curr.is_leaf() {
+            curr = &curr.children[curr.children.len() - 1];
+        }
+        curr.entries.last()
+    }
+
+    // Returns the number of entries whose keys satisfy `is_below`, assuming that the keys that
+    // satisfy `is_below` are a prefix of the keys of the tree.
+    pub fn rank_by<F>(&self, mut is_below: F) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut curr = self;
+        let mut rank = 0;
+        loop {
+            let index = curr
+                .entries
+                .iter()
+                .position(|entry| !is_below(&entry.key))
+                .unwrap_or(curr.entries.len());
+            rank += index;
+            if curr.is_leaf() {
+                return rank;
+            }
+            rank += curr.children[..index]
+                .iter()
+                .map(|child| child.size)
+                .sum::<usize>();
+            curr = &curr.children[index];
+        }
+    }
+
+    // Returns the entry with `rank` entries before it, assuming that `rank` is less than the size
+    // of the subtree.
+    pub fn select(&self, mut rank: usize) -> &Entry<T, U> {
+        let mut curr = self;
+        'outer: loop {
+            if curr.is_leaf() {
+                return &curr.entries[rank];
+            }
+            for (index, child) in curr.children.iter().enumerate() {
+                if rank < child.size {
+                    curr = child;
+                    continue 'outer;
+                }
+                if rank == child.size {
+                    return &curr.entries[index];
+                }
+                rank -= child.size + 1;
+            }
+            unreachable!();
+        }
+    }
+
+    // Splits the full child at `index` into two nodes and moves its median entry into this node.
+    pub fn split_child(&mut self, index: usize) {
+        let (median, right) = {
+            let left = &mut self.children[index];
+            let mut right = Node::new();
+            right.entries.extend(left.entries.drain(MIN_DEGREE..));
+            if !left.is_leaf() {
+                right.children = left.children.split_off(MIN_DEGREE);
+            }
+            right.size =
+                right.entries.len() + right.children.iter().map(|child| child.size).sum::<usize>();
+            let median = left.entries.pop().expect("Expected non-empty node.");
+            left.size -= right.size + 1;
+            (median, right)
+        };
+        self.entries.insert(index, median);
+        self.children.insert(index + 1, right);
+    }
+
+    // Inserts an entry whose key is not in the subtree, assuming that this node is not full.
+    pub fn insert_non_full(&mut self, entry: Entry<T, U>)
+    where
+        T: Ord,
+    {
+        let mut curr = self;
+        loop {
+            curr.size += 1;
+            let mut index = match curr.search(&entry.key) {
+                Ok(_) => unreachable!(),
+                Err(index) => index,
+            };
+            if curr.is_leaf() {
+                curr.entries.insert(index, entry);
+                return;
+            }
+            if curr.children[index].is_full() {
+                curr.split_child(index);
+                if curr.entries[index].key < entry.key {
+                    index += 1;
+                }
+            }
+            curr = &mut curr.children[index];
+        }
+    }
+
+    // Merges the child at `index + 1` and the entry at `index` into the child at `index`.
+    fn merge_children(&mut self, index: usize) {
+        let entry = self.entries.remove(index);
+        let right = self.children.remove(index + 1);
+        let left = &mut self.children[index];
+        left.size += right.size + 1;
+        left.entries.push(entry);
+        left.entries.extend(right.entries);
+        left.children.extend(right.children);
+    }
+
+    // Ensures that the child at `index` has at least `MIN_DEGREE` entries by borrowing an entry
+    // from a sibling or merging it with a sibling. Returns the index of the child that now
+    // contains the entries of the original child.
+    fn fix_child(&mut self, index: usize) -> usize {
+        if self.children[index].entries.len() >= MIN_DEGREE {
+            return index;
+        }
+
+        if index > 0 && self.children[index - 1].entries.len() >= MIN_DEGREE {
+            let (entry, child) = {
+                let left = &mut self.children[index - 1];
+                let entry = left.entries.pop().expect("Expected non-empty node.");
+                let child = left.children.pop();
+                left.size -= 1 + child.as_ref().map_or(0, |child| child.size);
+                (entry, child)
+            };
+            let entry = mem::replace(&mut self.entries[index - 1], entry);
+            let curr = &mut self.children[index];
+            curr.size += 1 + child.as_ref().map_or(0, |child| child.size);
+            curr.entries.insert(0, entry);
+            if let Some(child) = child {
+                curr.children.insert(0, child);
+            }
+            return index;
+        }
+
+        if index + 1 < self.children.len() && self.children[index + 1].entries.len() >= MIN_DEGREE {
+            let (entry, child) = {
+                let right = &mut self.children[index + 1];
+                let entry = right.entries.remove(0);
+                let child = if right.is_leaf() {
+                    None
+                } else {
+                    Some(right.children.remove(0))
+                };
+                right.size -= 1 + child.as_ref().map_or(0, |child| child.size);
+                (entry, child)
+            };
+            let entry = mem::replace(&mut self.entries[index], entry);
+            let curr = &mut self.children[index];
+            curr.size += 1 + child.as_ref().map_or(0, |child| child.size);
+            curr.entries.push(entry);
+            if let Some(child) = child {
+                curr.children.push(child);
+            }
+            return index;
+        }
+
+        if index + 1 < self.children.len() {
+            self.merge_children(index);
+            index
+        } else {
+            self.merge_children(index - 1);
+            index - 1
+        }
+    }
+
+    fn remove_min(&mut self) -> Entry<T, U> {
+        let mut curr = self;
+        loop {
+            curr.size -= 1;
+            if curr.is_leaf() {
+                return curr.entries.remove(0);
+            }
+            let index = curr.fix_child(0);
+            curr = &mut curr.children[index];
+        }
+    }
+
+    fn remove_max(&mut self) -> Entry<T, U> {
+        let mut curr = self;
+        loop {
+            curr.size -= 1;
+            if curr.is_leaf() {
+                return curr.entries.pop().expect("Expected non-empty node.");
+            }
+            let index = curr.fix_child(curr.children.len() - 1);
+            curr = &mut curr.children[index];
+        }
+    }
+
+    // Removes the entry with a particular key, assuming that the key exists in the subtree and
+    // that this node is either the root or has at least `MIN_DEGREE` entries.
+    pub fn remove<V>(&mut self, key: &V) -> Entry<T, U>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        let mut curr = self;
+        loop {
+            curr.size -= 1;
+            match curr.search(key) {
+                Ok(index) => {
+                    if curr.is_leaf() {
+                        return curr.entries.remove(index);
+                    }
+                    if curr.children[index].entries.len() >= MIN_DEGREE {
+                        let predecessor = curr.children[index].remove_max();
+                        return mem::replace(&mut curr.entries[index], predecessor);
+                    }
+                    if curr.children[index + 1].entries.len() >= MIN_DEGREE {
+                        let successor = curr.children[index + 1].remove_min();
+                        return mem::replace(&mut curr.entries[index], successor);
+                    }
+                    curr.merge_children(index);
+                    curr = &mut curr.children[index];
+                }
+                Err(index) => {
+                    let index = curr.fix_child(index);
+                    curr = &mut curr.children[index];
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Orderings of keys for ordered collections.
+
+use core::cmp::Ordering;
+
+/// A total ordering of values of type `T`.
+///
+/// The ordered maps of this crate use `Natural`, the ordering of `Ord`, by default. A different
+/// ordering can be supplied on construction to order keys that do not implement `Ord` in the
+/// desired way without wrapping every key in a new type. Closures of the form
+/// `Fn(&T, &T) -> Ordering` implement `Compare<T>`.
+///
+/// An ordering must be consistent for the lifetime of a collection: if it changes, or if it
+/// differs from the ordering a persisted collection was written with, lookups may fail to find
+/// keys that exist in the collection.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::avl_tree::AvlMap;
+///
+/// let mut map = AvlMap::with_comparator(|a: &String, b: &String| {
+///     a.to_lowercase().cmp(&b.to_lowercase())
+/// });
+/// map.insert(String::from("b"), 1);
+/// map.insert(String::from("A"), 2);
+/// map.insert(String::from("a"), 3);
+///
+/// assert_eq!(map.len(), 2);
+/// assert_eq!(map.min(), Some(&String::from("a")));
+/// assert_eq!(map.get(&String::from("B")), Some(&1));
+/// ```
+pub trait Compare<T: ?Sized> {
+    /// Returns the ordering of `left` relative to `right`.
+    fn compare(&self, left: &T, right: &T) -> Ordering;
+}
+
+/// The ordering of values of a type that implements `Ord`.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::compare::{Compare, Natural};
+/// use std::cmp::Ordering;
+///
+/// assert_eq!(Natural.compare(&1, &2), Ordering::Less);
+/// assert_eq!(Natural.compare("b", "a"), Ordering::Greater);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Natural;
+
+impl<T> Compare<T> for Natural
+where
+    T: Ord + ?Sized,
+{
+    fn compare(&self, left: &T, right: &T) -> Ordering {
+        left.cmp(right)
+    }
+}
+
+impl<T, F> Compare<T> for F
+where
+    T: ?Sized,
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn compare(&self, left: &T, right: &T) -> Ordering {
+        self(left, right)
+    }
+}
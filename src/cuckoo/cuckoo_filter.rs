@@ -0,0 +1,827 @@
+use crate::bloom::{hash, SipHashBuilder};
+use crate::seed;
+use rand::{Rng, XorShiftRng};
+use serde_derive::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::mem;
+
+const DEFAULT_ENTRIES_PER_BUCKET: usize = 4;
+const DEFAULT_MAX_KICKS: usize = 512;
+const MAX_FINGERPRINT_BIT_COUNT: usize = 32;
+const EMPTY_FINGERPRINT: u32 = 0;
+// The fraction of occupied entries at which insertions into a cuckoo filter with four entries per
+// bucket start to fail.
+const LOAD_FACTOR: f64 = 0.95;
+
+/// A space-efficient probabilistic data structure to test for membership in a set that supports
+/// removing items.
+///
+/// A cuckoo filter is a table of buckets that each hold a fixed number of fingerprints. An item
+/// has two candidate buckets, the second of which is derived from the first and the fingerprint of
+/// the item, so fingerprints can be moved between their buckets without knowing the original
+/// items. Inserting an item into two full buckets evicts fingerprints to their alternate buckets
+/// until an empty entry is found. If no empty entry is found after a bounded number of evictions,
+/// the displaced fingerprint is stored in a list of extra items that is scanned on every lookup.
+/// Removing an item moves an extra item into the freed entry if it belongs in the same bucket, and
+/// `rebalance` moves as many extra items back into the buckets as possible.
+/// A cuckoo filter never has false negatives unless an item that was never inserted is removed,
+/// but may have false positives. The fingerprints and buckets of items are derived from the
+/// hashers built by a `BuildHasher`, which defaults to a `SipHashBuilder` with random keys.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::cuckoo::CuckooFilter;
+///
+/// let mut filter: CuckooFilter<String> = CuckooFilter::new(100, 0.01);
+///
+/// assert!(!filter.contains("foo"));
+/// filter.insert("foo");
+/// assert!(filter.contains("foo"));
+/// assert_eq!(filter.len(), 1);
+///
+/// assert!(filter.remove("foo"));
+/// assert!(!filter.contains("foo"));
+///
+/// assert_eq!(filter.capacity(), 128);
+/// assert_eq!(filter.fingerprint_bit_count(), 10);
+/// ```
+#[derive(Clone, Deserialize, Serialize)]
+pub struct CuckooFilter<T, S = SipHashBuilder> {
+    entries: Vec<u32>,
+    bucket_count: usize,
+    entries_per_bucket: usize,
+    fingerprint_bit_count: usize,
+    extra_items: Vec<(u32, usize)>,
+    len: usize,
+    max_kicks: usize,
+    hash_builder: S,
+    #[serde(skip, default = "rand::weak_rng")]
+    rng: XorShiftRng,
+    #[serde(skip)]
+    _marker: PhantomData<T>,
+}
+
+impl<T> CuckooFilter<T> {
+    /// Constructs a new, empty `CuckooFilter<T>` with an estimated maximum of `item_count` items
+    /// and a desired false positive probability of `fpp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let filter: CuckooFilter<String> = CuckooFilter::new(100, 0.01);
+    /// ```
+    pub fn new(item_count: usize, fpp: f64) -> Self {
+        Self::with_hasher(item_count, fpp, SipHashBuilder::new())
+    }
+
+    /// Constructs a new, empty `CuckooFilter<T>` with an estimated maximum of `item_count` items
+    /// and a desired false positive probability of `fpp` whose hash functions and evictions are
+    /// derived from `seed`. Filters constructed with the same seed behave identically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let filter: CuckooFilter<String> = CuckooFilter::with_seed(100, 0.01, 0);
+    /// let other: CuckooFilter<String> = CuckooFilter::with_seed(100, 0.01, 0);
+    /// assert_eq!(filter.hasher(), other.hasher());
+    /// ```
+    pub fn with_seed(item_count: usize, fpp: f64, seed: u64) -> Self {
+        Self::with_hasher_and_rng(
+            item_count,
+            fpp,
+            SipHashBuilder::with_seed(seed),
+            seed::rng_from_seed(seed),
+        )
+    }
+
+    /// Constructs a new, empty `CuckooFilter<T>` with an estimated maximum of `item_count` items
+    /// that stores fingerprints of `fingerprint_bit_count` bits in buckets of `entries_per_bucket`
+    /// entries. The number of buckets is rounded up to a power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fingerprint_bit_count` is not between 1 and 32, or if `entries_per_bucket` is
+    /// zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let filter: CuckooFilter<String> = CuckooFilter::from_fingerprint_bit_count(100, 16, 2);
+    /// assert_eq!(filter.capacity(), 128);
+    /// assert_eq!(filter.fingerprint_bit_count(), 16);
+    /// assert_eq!(filter.entries_per_bucket(), 2);
+    /// ```
+    pub fn from_fingerprint_bit_count(
+        item_count: usize,
+        fingerprint_bit_count: usize,
+        entries_per_bucket: usize,
+    ) -> Self {
+        Self::from_parts(
+            item_count,
+            fingerprint_bit_count,
+            entries_per_bucket,
+            SipHashBuilder::new(),
+            rand::weak_rng(),
+        )
+    }
+}
+
+impl<T, S> CuckooFilter<T, S>
+where
+    S: BuildHasher,
+{
+    /// Constructs a new, empty `CuckooFilter<T, S>` with an estimated maximum of `item_count`
+    /// items and a desired false positive probability of `fpp` that uses `hash_builder` to hash
+    /// items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let filter: CuckooFilter<String, RandomState> =
+    ///     CuckooFilter::with_hasher(100, 0.01, RandomState::new());
+    /// ```
+    pub fn with_hasher(item_count: usize, fpp: f64, hash_builder: S) -> Self {
+        Self::with_hasher_and_rng(item_count, fpp, hash_builder, rand::weak_rng())
+    }
+
+    pub(crate) fn with_hasher_and_rng(
+        item_count: usize,
+        fpp: f64,
+        hash_builder: S,
+        rng: XorShiftRng,
+    ) -> Self {
+        let fingerprint_bit_count = (2.0 * DEFAULT_ENTRIES_PER_BUCKET as f64 / fpp)
+            .log2()
+            .ceil();
+        Self::from_parts(
+            item_count,
+            (fingerprint_bit_count as usize).clamp(1, MAX_FINGERPRINT_BIT_COUNT),
+            DEFAULT_ENTRIES_PER_BUCKET,
+            hash_builder,
+            rng,
+        )
+    }
+
+    fn from_parts(
+        item_count: usize,
+        fingerprint_bit_count: usize,
+        entries_per_bucket: usize,
+        hash_builder: S,
+        rng: XorShiftRng,
+    ) -> Self {
+        assert!(
+            fingerprint_bit_count > 0 && fingerprint_bit_count <= MAX_FINGERPRINT_BIT_COUNT,
+            "Error: fingerprint bit count must be between 1 and 32."
+        );
+        assert!(
+            entries_per_bucket > 0,
+            "Error: entries per bucket must be positive."
+        );
+        let bucket_count = item_count.max(1) as f64 / (entries_per_bucket as f64 * LOAD_FACTOR);
+        let bucket_count = (bucket_count.ceil() as usize).next_power_of_two();
+        CuckooFilter {
+            entries: vec![EMPTY_FINGERPRINT; bucket_count * entries_per_bucket],
+            bucket_count,
+            entries_per_bucket,
+            fingerprint_bit_count,
+            extra_items: Vec::new(),
+            len: 0,
+            max_kicks: DEFAULT_MAX_KICKS,
+            hash_builder,
+            rng,
+            _marker: PhantomData,
+        }
+    }
+
+    // Returns the non-empty fingerprint of an item and the index of its first bucket.
+    fn get_fingerprint_and_index<U>(&self, item: &U) -> (u32, usize)
+    where
+        U: Hash + ?Sized,
+    {
+        let hash = self.hash_builder.hash_one(item);
+        let mask = (1u64 << self.fingerprint_bit_count) - 1;
+        let fingerprint = match (hash >> 32) & mask {
+            0 => 1,
+            fingerprint => fingerprint as u32,
+        };
+        (fingerprint, hash as usize & (self.bucket_count - 1))
+    }
+
+    // Returns the index of the other bucket of a fingerprint in the bucket at `index`. The number
+    // of buckets is a power of two, so the alternate index of the alternate index is `index`.
+    fn get_alt_index(&self, index: usize, fingerprint: u32) -> usize {
+        (index ^ self.hash_builder.hash_one(fingerprint) as usize) & (self.bucket_count - 1)
+    }
+
+    fn get_bucket(&self, index: usize) -> &[u32] {
+        let start = index * self.entries_per_bucket;
+        &self.entries[start..start + self.entries_per_bucket]
+    }
+
+    fn get_bucket_mut(&mut self, index: usize) -> &mut [u32] {
+        let start = index * self.entries_per_bucket;
+        &mut self.entries[start..start + self.entries_per_bucket]
+    }
+
+    fn insert_into_bucket(&mut self, index: usize, fingerprint: u32) -> bool {
+        match self
+            .get_bucket_mut(index)
+            .iter_mut()
+            .find(|entry| **entry == EMPTY_FINGERPRINT)
+        {
+            Some(entry) => {
+                *entry = fingerprint;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove_from_bucket(&mut self, index: usize, fingerprint: u32) -> bool {
+        match self
+            .get_bucket_mut(index)
+            .iter_mut()
+            .find(|entry| **entry == fingerprint)
+        {
+            Some(entry) => {
+                *entry = EMPTY_FINGERPRINT;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Inserts a fingerprint into the bucket at `index_1` or its alternate bucket, evicting
+    // fingerprints to their alternate buckets if both are full. Returns the displaced fingerprint
+    // and the index of its bucket if no empty entry was found.
+    fn insert_fingerprint(&mut self, mut fingerprint: u32, index_1: usize) -> Option<(u32, usize)> {
+        let index_2 = self.get_alt_index(index_1, fingerprint);
+        if self.insert_into_bucket(index_1, fingerprint)
+            || self.insert_into_bucket(index_2, fingerprint)
+        {
+            return None;
+        }
+
+        let mut index = if self.rng.gen::<bool>() {
+            index_1
+        } else {
+            index_2
+        };
+        for _ in 0..self.max_kicks {
+            let entry_index = self.rng.gen_range(0, self.entries_per_bucket);
+            mem::swap(
+                &mut fingerprint,
+                &mut self.get_bucket_mut(index)[entry_index],
+            );
+            index = self.get_alt_index(index, fingerprint);
+            if self.insert_into_bucket(index, fingerprint) {
+                return None;
+            }
+        }
+        Some((fingerprint, index))
+    }
+
+    // Moves an extra item that belongs in the bucket at `index` into the bucket after an entry of
+    // the bucket was freed.
+    fn unkick(&mut self, index: usize) {
+        let position = self
+            .extra_items
+            .iter()
+            .position(|&(fingerprint, extra_index)| {
+                extra_index == index || self.get_alt_index(extra_index, fingerprint) == index
+            });
+        if let Some(position) = position {
+            let (fingerprint, _) = self.extra_items.swap_remove(position);
+            self.insert_into_bucket(index, fingerprint);
+        }
+    }
+
+    /// Inserts an item into the filter. Inserting an item multiple times stores multiple copies of
+    /// its fingerprint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter: CuckooFilter<String> = CuckooFilter::new(100, 0.01);
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn insert<U>(&mut self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let (fingerprint, index) = self.get_fingerprint_and_index(item);
+        self.len += 1;
+        if let Some(extra_item) = self.insert_fingerprint(fingerprint, index) {
+            self.extra_items.push(extra_item);
+        }
+    }
+
+    /// Removes an item from the filter. Returns `true` if the item was possibly in the filter.
+    /// Otherwise the filter is not modified and `false` is returned.
+    ///
+    /// Removing an item that was never inserted may introduce false negatives for the items that
+    /// share its fingerprint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter: CuckooFilter<String> = CuckooFilter::new(100, 0.01);
+    /// filter.insert("foo");
+    /// assert!(filter.remove("foo"));
+    /// assert!(!filter.remove("foo"));
+    /// ```
+    pub fn remove<U>(&mut self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let (fingerprint, index_1) = self.get_fingerprint_and_index(item);
+        let index_2 = self.get_alt_index(index_1, fingerprint);
+        let removed_index = if self.remove_from_bucket(index_1, fingerprint) {
+            Some(index_1)
+        } else if self.remove_from_bucket(index_2, fingerprint) {
+            Some(index_2)
+        } else {
+            None
+        };
+        let is_removed = match removed_index {
+            Some(index) => {
+                self.unkick(index);
+                true
+            }
+            None => match self.extra_items.iter().position(|extra_item| {
+                extra_item.0 == fingerprint && (extra_item.1 == index_1 || extra_item.1 == index_2)
+            }) {
+                Some(position) => {
+                    self.extra_items.swap_remove(position);
+                    true
+                }
+                None => false,
+            },
+        };
+        if is_removed {
+            self.len -= 1;
+        }
+        is_removed
+    }
+
+    /// Checks if an item is possibly in the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter: CuckooFilter<String> = CuckooFilter::new(100, 0.01);
+    /// assert!(!filter.contains("foo"));
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let (fingerprint, index_1) = self.get_fingerprint_and_index(item);
+        let index_2 = self.get_alt_index(index_1, fingerprint);
+        self.get_bucket(index_1).contains(&fingerprint)
+            || self.get_bucket(index_2).contains(&fingerprint)
+            || self.extra_items.iter().any(|extra_item| {
+                extra_item.0 == fingerprint && (extra_item.1 == index_1 || extra_item.1 == index_2)
+            })
+    }
+
+    /// Inserts multiple items into the filter. The items are hashed in batches and the
+    /// fingerprints of each batch are inserted in ascending order of their buckets, which is
+    /// faster than inserting the items one at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter: CuckooFilter<u32> = CuckooFilter::new(100, 0.01);
+    /// filter.insert_all(&[1, 2, 3]);
+    /// assert_eq!(filter.len(), 3);
+    /// assert!(filter.contains(&2));
+    /// ```
+    pub fn insert_all<'a, I, U>(&mut self, items: I)
+    where
+        T: Borrow<U>,
+        I: IntoIterator<Item = &'a U>,
+        U: Hash + ?Sized + 'a,
+    {
+        let mut items = items.into_iter();
+        let mut batch = Vec::with_capacity(hash::BATCH_SIZE);
+        loop {
+            batch.clear();
+            for item in items.by_ref().take(hash::BATCH_SIZE) {
+                let (fingerprint, index) = self.get_fingerprint_and_index(item);
+                batch.push((index, fingerprint));
+            }
+            if batch.is_empty() {
+                break;
+            }
+            batch.sort_unstable();
+            self.len += batch.len();
+            for (index, fingerprint) in &batch {
+                if let Some(extra_item) = self.insert_fingerprint(*fingerprint, *index) {
+                    self.extra_items.push(extra_item);
+                }
+            }
+        }
+    }
+
+    /// Checks if multiple items are possibly in the filter. Returns whether each item is possibly
+    /// in the filter in the order of the items. The items are hashed in batches and the buckets
+    /// of each batch are probed in ascending order, which is faster than checking the items one at
+    /// a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter: CuckooFilter<u32> = CuckooFilter::new(100, 0.01);
+    /// filter.insert(&1);
+    /// filter.insert(&3);
+    /// assert_eq!(filter.contains_all(&[1, 2, 3]), vec![true, false, true]);
+    /// ```
+    pub fn contains_all<'a, I, U>(&self, items: I) -> Vec<bool>
+    where
+        T: Borrow<U>,
+        I: IntoIterator<Item = &'a U>,
+        U: Hash + ?Sized + 'a,
+    {
+        let mut items = items.into_iter();
+        let mut ret = Vec::new();
+        let mut batch = Vec::with_capacity(hash::BATCH_SIZE);
+        loop {
+            batch.clear();
+            for item in items.by_ref().take(hash::BATCH_SIZE) {
+                let (fingerprint, index) = self.get_fingerprint_and_index(item);
+                batch.push((index, fingerprint, ret.len()));
+                ret.push(false);
+            }
+            if batch.is_empty() {
+                break;
+            }
+            batch.sort_unstable();
+            for (index_1, fingerprint, position) in &batch {
+                let index_2 = self.get_alt_index(*index_1, *fingerprint);
+                ret[*position] = self.get_bucket(*index_1).contains(fingerprint)
+                    || self.get_bucket(index_2).contains(fingerprint)
+                    || self.extra_items.iter().any(|extra_item| {
+                        extra_item.0 == *fingerprint
+                            && (extra_item.1 == *index_1 || extra_item.1 == index_2)
+                    });
+            }
+        }
+        ret
+    }
+
+    /// Moves the extra items of the filter back into its buckets, evicting fingerprints to their
+    /// alternate buckets if necessary. Returns the number of extra items that were moved. Lookups
+    /// scan the extra items, so rebalancing a long-lived filter after many removals restores the
+    /// cost of its lookups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter: CuckooFilter<u32> = CuckooFilter::from_fingerprint_bit_count(100, 16, 4);
+    /// for item in 0..200 {
+    ///     filter.insert(&item);
+    /// }
+    /// assert!(filter.extra_item_count() > 0);
+    ///
+    /// for item in 0..150 {
+    ///     filter.remove(&item);
+    /// }
+    /// filter.rebalance();
+    /// assert_eq!(filter.extra_item_count(), 0);
+    /// assert!((150..200).all(|item| filter.contains(&item)));
+    /// ```
+    pub fn rebalance(&mut self) -> usize {
+        let extra_items = mem::take(&mut self.extra_items);
+        let extra_item_count = extra_items.len();
+        for (fingerprint, index) in extra_items {
+            if let Some(extra_item) = self.insert_fingerprint(fingerprint, index) {
+                self.extra_items.push(extra_item);
+            }
+        }
+        extra_item_count - self.extra_items.len()
+    }
+
+    /// Returns the number of items that did not fit in the buckets of the filter and are stored in
+    /// its list of extra items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter: CuckooFilter<u32> = CuckooFilter::from_fingerprint_bit_count(100, 16, 4);
+    /// filter.insert(&0);
+    /// assert_eq!(filter.extra_item_count(), 0);
+    /// ```
+    pub fn extra_item_count(&self) -> usize {
+        self.extra_items.len()
+    }
+
+    /// Clears all items from the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter: CuckooFilter<String> = CuckooFilter::new(100, 0.01);
+    /// filter.insert("foo");
+    /// filter.clear();
+    /// assert!(!filter.contains("foo"));
+    /// assert!(filter.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        for entry in &mut self.entries {
+            *entry = EMPTY_FINGERPRINT;
+        }
+        self.extra_items.clear();
+        self.len = 0;
+    }
+
+    /// Returns the number of items in the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter: CuckooFilter<String> = CuckooFilter::new(100, 0.01);
+    /// filter.insert("foo");
+    /// assert_eq!(filter.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the filter is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter: CuckooFilter<String> = CuckooFilter::new(100, 0.01);
+    /// assert!(filter.is_empty());
+    /// filter.insert("foo");
+    /// assert!(!filter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of entries in the buckets of the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let filter: CuckooFilter<String> = CuckooFilter::new(100, 0.01);
+    /// assert_eq!(filter.capacity(), 128);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the number of bits in the fingerprints of the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let filter: CuckooFilter<String> = CuckooFilter::new(100, 0.01);
+    /// assert_eq!(filter.fingerprint_bit_count(), 10);
+    /// ```
+    pub fn fingerprint_bit_count(&self) -> usize {
+        self.fingerprint_bit_count
+    }
+
+    /// Returns the number of entries in each bucket of the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let filter: CuckooFilter<String> = CuckooFilter::new(100, 0.01);
+    /// assert_eq!(filter.entries_per_bucket(), 4);
+    /// ```
+    pub fn entries_per_bucket(&self) -> usize {
+        self.entries_per_bucket
+    }
+
+    /// Returns a reference to the `BuildHasher` used by the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::SipHashBuilder;
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let filter: CuckooFilter<String> = CuckooFilter::with_seed(100, 0.01, 0);
+    /// assert_eq!(filter.hasher(), &SipHashBuilder::with_seed(0));
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns the estimated false positive probability of the filter based on the fraction of
+    /// entries that are occupied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter: CuckooFilter<String> = CuckooFilter::new(100, 0.01);
+    /// assert_eq!(filter.estimate_fpp(), 0.0);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimate_fpp() > 0.0 && filter.estimate_fpp() < 0.01);
+    /// ```
+    pub fn estimate_fpp(&self) -> f64 {
+        let load = self.len as f64 / self.capacity() as f64;
+        let fingerprint_fpp = 0.5f64.powi(self.fingerprint_bit_count as i32);
+        1.0 - (1.0 - fingerprint_fpp).powf(2.0 * self.entries_per_bucket as f64 * load)
+    }
+
+    // Returns `true` if insertions into the filter are likely to fail.
+    pub(crate) fn is_nearly_full(&self) -> bool {
+        !self.extra_items.is_empty() || self.len as f64 >= self.capacity() as f64 * LOAD_FACTOR
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CuckooFilter;
+
+    #[test]
+    #[should_panic]
+    fn test_zero_fingerprint_bit_count() {
+        let _filter: CuckooFilter<u32> = CuckooFilter::from_fingerprint_bit_count(100, 0, 4);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut filter: CuckooFilter<u32> = CuckooFilter::new(1000, 0.01);
+        for item in 0..1000u32 {
+            filter.insert(&item);
+        }
+        assert_eq!(filter.len(), 1000);
+        assert!(!filter.is_nearly_full());
+        for item in 0..500u32 {
+            assert!(filter.remove(&item));
+        }
+        for item in 500..1000u32 {
+            assert!(filter.contains(&item));
+        }
+        assert_eq!(filter.len(), 500);
+
+        let false_positives = (1000..11000u32)
+            .filter(|item| filter.contains(item))
+            .count();
+        assert!(false_positives < 200);
+        assert!(filter.estimate_fpp() < 0.01);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut filter: CuckooFilter<u32> = CuckooFilter::from_fingerprint_bit_count(100, 16, 4);
+        for item in 0..200u32 {
+            filter.insert(&item);
+        }
+        assert!(filter.is_nearly_full());
+        for item in 0..200u32 {
+            assert!(filter.contains(&item));
+        }
+        for item in 0..200u32 {
+            assert!(filter.remove(&item));
+        }
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_insert_all_contains_all() {
+        let items: Vec<u32> = (0..1000).collect();
+        let mut filter: CuckooFilter<u32> = CuckooFilter::new(1000, 0.01);
+        filter.insert_all(&items);
+        assert_eq!(filter.len(), 1000);
+        for item in &items {
+            assert!(filter.contains(item));
+        }
+
+        let queries: Vec<u32> = (500..1500).collect();
+        let expected: Vec<bool> = queries.iter().map(|item| filter.contains(item)).collect();
+        assert_eq!(filter.contains_all(&queries), expected);
+        assert!(filter.contains_all(&queries)[..500]
+            .iter()
+            .all(|found| *found));
+        assert!(filter.contains_all(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_remove_unkicks_extra_items() {
+        let mut filter: CuckooFilter<u32> = CuckooFilter::from_fingerprint_bit_count(100, 16, 4);
+        for item in 0..200u32 {
+            filter.insert(&item);
+        }
+        let extra_item_count = filter.extra_item_count();
+        assert!(extra_item_count > 0);
+
+        for item in 0..60u32 {
+            assert!(filter.remove(&item));
+        }
+        assert!(filter.extra_item_count() < extra_item_count);
+        assert_eq!(filter.len(), 140);
+        for item in 60..200u32 {
+            assert!(filter.contains(&item));
+        }
+    }
+
+    #[test]
+    fn test_rebalance() {
+        let mut filter: CuckooFilter<u32> = CuckooFilter::from_fingerprint_bit_count(100, 16, 4);
+        assert_eq!(filter.rebalance(), 0);
+        for item in 0..200u32 {
+            filter.insert(&item);
+        }
+        filter.rebalance();
+        let extra_item_count = filter.extra_item_count();
+        assert!(extra_item_count > 0);
+
+        // removing extra items directly does not free any entries of the buckets
+        filter.extra_items.truncate(extra_item_count / 2);
+        let entries: Vec<u32> = filter.entries.clone();
+        for (index, entry) in filter.entries.iter_mut().enumerate() {
+            if index % 2 == 0 {
+                *entry = 0;
+            }
+        }
+        let moved = filter.rebalance();
+        assert_eq!(moved, extra_item_count / 2);
+        assert_eq!(filter.extra_item_count(), 0);
+        assert_ne!(filter.entries, entries);
+    }
+
+    #[test]
+    fn test_with_seed() {
+        let mut filter: CuckooFilter<u32> = CuckooFilter::with_seed(100, 0.01, 1);
+        let mut other: CuckooFilter<u32> = CuckooFilter::with_seed(100, 0.01, 1);
+        for item in 0..200u32 {
+            filter.insert(&item);
+            other.insert(&item);
+        }
+        assert_eq!(
+            bincode::serialize(&filter).unwrap(),
+            bincode::serialize(&other).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut filter: CuckooFilter<u32> = CuckooFilter::new(100, 0.01);
+        for item in 0..100u32 {
+            filter.insert(&item);
+        }
+
+        let deserialized: CuckooFilter<u32> =
+            bincode::deserialize(&bincode::serialize(&filter).unwrap()).unwrap();
+        assert_eq!(deserialized.len(), 100);
+        assert_eq!(deserialized.hasher(), filter.hasher());
+        for item in 0..100u32 {
+            assert!(deserialized.contains(&item));
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! Space-efficient probabilistic data structures to test for membership in a set that support
+//! removing items.
+//!
+//! Every filter implements `Serialize` and `Deserialize`. Filters hash items with a `BuildHasher`,
+//! which defaults to a `bloom::SipHashBuilder` with random keys. A filter constructed with
+//! `with_seed` derives its keys and evictions from the seed, and a custom `BuildHasher` can be
+//! provided with `with_hasher`.
+
+mod cuckoo_filter;
+mod scalable_cuckoo_filter;
+
+pub use self::cuckoo_filter::CuckooFilter;
+pub use self::scalable_cuckoo_filter::ScalableCuckooFilter;
@@ -0,0 +1,460 @@
+use crate::bloom::SipHashBuilder;
+use crate::cuckoo::CuckooFilter;
+use crate::seed;
+use rand::{Rng, XorShiftRng};
+use serde_derive::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+
+/// A cuckoo filter that grows to accommodate an unknown number of items.
+///
+/// A scalable cuckoo filter is a chain of cuckoo filters. Items are inserted into the last filter
+/// of the chain, and a new filter is appended once the last filter is nearly full. Each appended
+/// filter has `growth_ratio` times the capacity of the previous filter and `tightening_ratio` times
+/// its false positive probability, so the false positive probability of the chain converges to at
+/// most `fpp / (1 - tightening_ratio)`. An item is reported to be in the filter if any filter of
+/// the chain reports it. Removing an item removes it from the most recently appended filter that
+/// possibly contains it. Every filter of the chain uses a clone of the same `BuildHasher`.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::cuckoo::ScalableCuckooFilter;
+///
+/// let mut filter: ScalableCuckooFilter<u32> = ScalableCuckooFilter::new(100, 0.01, 2.0, 0.5);
+///
+/// for item in 0..1000 {
+///     filter.insert(&item);
+/// }
+/// assert!(filter.contains(&0));
+/// assert_eq!(filter.len(), 1000);
+/// assert!(filter.filter_count() > 1);
+///
+/// assert!(filter.remove(&0));
+/// assert_eq!(filter.len(), 999);
+/// ```
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ScalableCuckooFilter<T, S = SipHashBuilder> {
+    filters: Vec<CuckooFilter<T, S>>,
+    initial_item_count: usize,
+    fpp: f64,
+    growth_ratio: f64,
+    tightening_ratio: f64,
+    hash_builder: S,
+    #[serde(skip, default = "rand::weak_rng")]
+    rng: XorShiftRng,
+}
+
+impl<T> ScalableCuckooFilter<T> {
+    /// Constructs a new, empty `ScalableCuckooFilter<T>` whose first filter has an estimated
+    /// maximum of `initial_item_count` items and a desired false positive probability of `fpp`.
+    /// Every subsequent filter has `growth_ratio` times the capacity and `tightening_ratio` times
+    /// the false positive probability of the previous filter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `growth_ratio` is less than 1 or if `tightening_ratio` is not between 0 and 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let filter: ScalableCuckooFilter<String> = ScalableCuckooFilter::new(100, 0.01, 2.0, 0.5);
+    /// ```
+    pub fn new(
+        initial_item_count: usize,
+        fpp: f64,
+        growth_ratio: f64,
+        tightening_ratio: f64,
+    ) -> Self {
+        Self::with_hasher(
+            initial_item_count,
+            fpp,
+            growth_ratio,
+            tightening_ratio,
+            SipHashBuilder::new(),
+        )
+    }
+
+    /// Constructs a new, empty `ScalableCuckooFilter<T>` whose first filter has an estimated
+    /// maximum of `initial_item_count` items and a desired false positive probability of `fpp`
+    /// whose hash functions and evictions are derived from `seed`. Filters constructed with the
+    /// same seed behave identically.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `growth_ratio` is less than 1 or if `tightening_ratio` is not between 0 and 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let filter: ScalableCuckooFilter<String> =
+    ///     ScalableCuckooFilter::with_seed(100, 0.01, 2.0, 0.5, 0);
+    /// let other: ScalableCuckooFilter<String> =
+    ///     ScalableCuckooFilter::with_seed(100, 0.01, 2.0, 0.5, 0);
+    /// assert_eq!(filter.hasher(), other.hasher());
+    /// ```
+    pub fn with_seed(
+        initial_item_count: usize,
+        fpp: f64,
+        growth_ratio: f64,
+        tightening_ratio: f64,
+        seed: u64,
+    ) -> Self {
+        Self::from_parts(
+            initial_item_count,
+            fpp,
+            growth_ratio,
+            tightening_ratio,
+            SipHashBuilder::with_seed(seed),
+            seed::rng_from_seed(seed),
+        )
+    }
+}
+
+impl<T, S> ScalableCuckooFilter<T, S>
+where
+    S: BuildHasher + Clone,
+{
+    /// Constructs a new, empty `ScalableCuckooFilter<T, S>` whose first filter has an estimated
+    /// maximum of `initial_item_count` items and a desired false positive probability of `fpp`
+    /// that uses `hash_builder` to hash items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `growth_ratio` is less than 1 or if `tightening_ratio` is not between 0 and 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::ScalableCuckooFilter;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let filter: ScalableCuckooFilter<String, RandomState> =
+    ///     ScalableCuckooFilter::with_hasher(100, 0.01, 2.0, 0.5, RandomState::new());
+    /// ```
+    pub fn with_hasher(
+        initial_item_count: usize,
+        fpp: f64,
+        growth_ratio: f64,
+        tightening_ratio: f64,
+        hash_builder: S,
+    ) -> Self {
+        Self::from_parts(
+            initial_item_count,
+            fpp,
+            growth_ratio,
+            tightening_ratio,
+            hash_builder,
+            rand::weak_rng(),
+        )
+    }
+
+    fn from_parts(
+        initial_item_count: usize,
+        fpp: f64,
+        growth_ratio: f64,
+        tightening_ratio: f64,
+        hash_builder: S,
+        rng: XorShiftRng,
+    ) -> Self {
+        assert!(
+            growth_ratio >= 1.0,
+            "Error: growth ratio must be at least 1."
+        );
+        assert!(
+            tightening_ratio > 0.0 && tightening_ratio < 1.0,
+            "Error: tightening ratio must be between 0 and 1."
+        );
+        let mut filter = ScalableCuckooFilter {
+            filters: Vec::new(),
+            initial_item_count,
+            fpp,
+            growth_ratio,
+            tightening_ratio,
+            hash_builder,
+            rng,
+        };
+        filter.push_filter();
+        filter
+    }
+
+    fn push_filter(&mut self) {
+        let exponent = self.filters.len() as i32;
+        let item_count = self.initial_item_count.max(1) as f64 * self.growth_ratio.powi(exponent);
+        let fpp = self.fpp * self.tightening_ratio.powi(exponent);
+        let rng = self.rng.gen();
+        self.filters.push(CuckooFilter::with_hasher_and_rng(
+            item_count.ceil() as usize,
+            fpp,
+            self.hash_builder.clone(),
+            rng,
+        ));
+    }
+
+    /// Inserts an item into the filter, appending a new filter to the chain if the last filter is
+    /// nearly full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let mut filter: ScalableCuckooFilter<String> = ScalableCuckooFilter::new(100, 0.01, 2.0, 0.5);
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn insert<U>(&mut self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        if self.filters.last().is_none_or(CuckooFilter::is_nearly_full) {
+            self.push_filter();
+        }
+        self.filters
+            .last_mut()
+            .expect("Expected non-empty chain of filters.")
+            .insert(item);
+    }
+
+    /// Removes an item from the most recently appended filter that possibly contains it. Returns
+    /// `true` if the item was possibly in the filter. Otherwise the filter is not modified and
+    /// `false` is returned.
+    ///
+    /// Removing an item that was never inserted may introduce false negatives for the items that
+    /// share its fingerprint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let mut filter: ScalableCuckooFilter<String> = ScalableCuckooFilter::new(100, 0.01, 2.0, 0.5);
+    /// filter.insert("foo");
+    /// assert!(filter.remove("foo"));
+    /// assert!(!filter.remove("foo"));
+    /// ```
+    pub fn remove<U>(&mut self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.filters
+            .iter_mut()
+            .rev()
+            .any(|filter| filter.remove(item))
+    }
+
+    /// Checks if an item is possibly in the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let mut filter: ScalableCuckooFilter<String> = ScalableCuckooFilter::new(100, 0.01, 2.0, 0.5);
+    /// assert!(!filter.contains("foo"));
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.filters.iter().any(|filter| filter.contains(item))
+    }
+
+    /// Clears all items from the filter and removes every filter of the chain except the first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let mut filter: ScalableCuckooFilter<u32> = ScalableCuckooFilter::new(100, 0.01, 2.0, 0.5);
+    /// for item in 0..1000 {
+    ///     filter.insert(&item);
+    /// }
+    /// filter.clear();
+    /// assert!(!filter.contains(&0));
+    /// assert_eq!(filter.filter_count(), 1);
+    /// ```
+    pub fn clear(&mut self) {
+        self.filters.truncate(1);
+        for filter in &mut self.filters {
+            filter.clear();
+        }
+    }
+
+    /// Returns the number of items in the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let mut filter: ScalableCuckooFilter<String> = ScalableCuckooFilter::new(100, 0.01, 2.0, 0.5);
+    /// filter.insert("foo");
+    /// assert_eq!(filter.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.filters.iter().map(CuckooFilter::len).sum()
+    }
+
+    /// Returns `true` if the filter is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let mut filter: ScalableCuckooFilter<String> = ScalableCuckooFilter::new(100, 0.01, 2.0, 0.5);
+    /// assert!(filter.is_empty());
+    /// filter.insert("foo");
+    /// assert!(!filter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.filters.iter().all(CuckooFilter::is_empty)
+    }
+
+    /// Returns the total number of entries in the buckets of the filters of the chain.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let filter: ScalableCuckooFilter<String> = ScalableCuckooFilter::new(100, 0.01, 2.0, 0.5);
+    /// assert_eq!(filter.capacity(), 128);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.filters.iter().map(CuckooFilter::capacity).sum()
+    }
+
+    /// Returns the number of filters in the chain.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let filter: ScalableCuckooFilter<String> = ScalableCuckooFilter::new(100, 0.01, 2.0, 0.5);
+    /// assert_eq!(filter.filter_count(), 1);
+    /// ```
+    pub fn filter_count(&self) -> usize {
+        self.filters.len()
+    }
+
+    /// Returns a reference to the `BuildHasher` used by the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::SipHashBuilder;
+    /// use extended_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let filter: ScalableCuckooFilter<String> =
+    ///     ScalableCuckooFilter::with_seed(100, 0.01, 2.0, 0.5, 0);
+    /// assert_eq!(filter.hasher(), &SipHashBuilder::with_seed(0));
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns the estimated false positive probability of the filter, which is the probability
+    /// that at least one filter of the chain reports a false positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let mut filter: ScalableCuckooFilter<String> = ScalableCuckooFilter::new(100, 0.01, 2.0, 0.5);
+    /// assert_eq!(filter.estimate_fpp(), 0.0);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimate_fpp() > 0.0 && filter.estimate_fpp() < 0.01);
+    /// ```
+    pub fn estimate_fpp(&self) -> f64 {
+        1.0 - self
+            .filters
+            .iter()
+            .map(|filter| 1.0 - filter.estimate_fpp())
+            .product::<f64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScalableCuckooFilter;
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_tightening_ratio() {
+        let _filter: ScalableCuckooFilter<u32> = ScalableCuckooFilter::new(100, 0.01, 2.0, 1.0);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut filter: ScalableCuckooFilter<u32> = ScalableCuckooFilter::new(100, 0.01, 2.0, 0.5);
+        for item in 0..10000u32 {
+            filter.insert(&item);
+        }
+        assert_eq!(filter.len(), 10000);
+        assert!(filter.filter_count() > 1);
+        for item in 0..10000u32 {
+            assert!(filter.contains(&item));
+        }
+
+        let false_positives = (10000..20000u32)
+            .filter(|item| filter.contains(item))
+            .count();
+        assert!(false_positives < 400);
+        assert!(filter.estimate_fpp() < 0.02);
+
+        for item in 5000..10000u32 {
+            assert!(filter.remove(&item));
+        }
+        assert_eq!(filter.len(), 5000);
+        for item in 0..5000u32 {
+            assert!(filter.contains(&item));
+        }
+    }
+
+    #[test]
+    fn test_with_seed() {
+        let mut filter: ScalableCuckooFilter<u32> =
+            ScalableCuckooFilter::with_seed(10, 0.01, 2.0, 0.5, 1);
+        let mut other: ScalableCuckooFilter<u32> =
+            ScalableCuckooFilter::with_seed(10, 0.01, 2.0, 0.5, 1);
+        for item in 0..100u32 {
+            filter.insert(&item);
+            other.insert(&item);
+        }
+        assert_eq!(
+            bincode::serialize(&filter).unwrap(),
+            bincode::serialize(&other).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut filter: ScalableCuckooFilter<u32> = ScalableCuckooFilter::new(10, 0.01, 2.0, 0.5);
+        for item in 0..100u32 {
+            filter.insert(&item);
+        }
+
+        let deserialized: ScalableCuckooFilter<u32> =
+            bincode::deserialize(&bincode::serialize(&filter).unwrap()).unwrap();
+        assert_eq!(deserialized.len(), 100);
+        assert_eq!(deserialized.filter_count(), filter.filter_count());
+        for item in 0..100u32 {
+            assert!(deserialized.contains(&item));
+        }
+    }
+}
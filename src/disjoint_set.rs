@@ -0,0 +1,436 @@
+//! Data structure that tracks a partition of elements into disjoint sets.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::mem;
+
+/// A collection of disjoint sets implemented using a union-find forest.
+///
+/// Each set is a tree whose root is the representative of the set. `find` compresses the path
+/// from an element to its root and `union` attaches the tree of smaller rank to the tree of larger
+/// rank, so both operations take amortized inverse Ackermann time. The members of each set are
+/// also linked in a circular list so that a set can be iterated in time proportional to its size.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::disjoint_set::DisjointSet;
+///
+/// let mut set = DisjointSet::new();
+/// set.make_set(0);
+/// set.make_set(1);
+/// set.make_set(2);
+/// assert_eq!(set.set_count(), 3);
+///
+/// assert!(set.union(&0, &1));
+/// assert!(set.same_set(&0, &1));
+/// assert!(!set.same_set(&0, &2));
+/// assert_eq!(set.set_count(), 2);
+///
+/// let mut members = set.iter_set(&1).cloned().collect::<Vec<u32>>();
+/// members.sort();
+/// assert_eq!(members, vec![0, 1]);
+/// ```
+pub struct DisjointSet<T> {
+    indexes: HashMap<T, usize>,
+    values: Vec<T>,
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+    next: Vec<usize>,
+    set_count: usize,
+}
+
+impl<T> DisjointSet<T>
+where
+    T: Clone + Hash + Eq,
+{
+    /// Constructs a new, empty `DisjointSet<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::disjoint_set::DisjointSet;
+    ///
+    /// let set: DisjointSet<u32> = DisjointSet::new();
+    /// ```
+    pub fn new() -> Self {
+        DisjointSet {
+            indexes: HashMap::new(),
+            values: Vec::new(),
+            parents: Vec::new(),
+            ranks: Vec::new(),
+            next: Vec::new(),
+            set_count: 0,
+        }
+    }
+
+    fn get_index<V>(&self, value: &V) -> Option<usize>
+    where
+        T: Borrow<V>,
+        V: Hash + Eq + ?Sized,
+    {
+        self.indexes.get(value).cloned()
+    }
+
+    fn find_root(&mut self, index: usize) -> usize {
+        let mut root = index;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        let mut curr = index;
+        while self.parents[curr] != root {
+            let next = self.parents[curr];
+            self.parents[curr] = root;
+            curr = next;
+        }
+
+        root
+    }
+
+    /// Inserts an element into a new singleton set. Returns `false` if the element already
+    /// exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::disjoint_set::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::new();
+    /// assert!(set.make_set(0));
+    /// assert!(!set.make_set(0));
+    /// ```
+    pub fn make_set(&mut self, value: T) -> bool {
+        if self.indexes.contains_key(&value) {
+            return false;
+        }
+
+        let index = self.values.len();
+        self.indexes.insert(value.clone(), index);
+        self.values.push(value);
+        self.parents.push(index);
+        self.ranks.push(0);
+        self.next.push(index);
+        self.set_count += 1;
+        true
+    }
+
+    /// Returns the representative of the set containing a particular element. Two elements are
+    /// in the same set if and only if they have the same representative. Returns `None` if the
+    /// element does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::disjoint_set::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::new();
+    /// set.make_set(0);
+    /// set.make_set(1);
+    /// set.union(&0, &1);
+    /// let root = *set.find(&0).unwrap();
+    /// assert_eq!(set.find(&1), Some(&root));
+    /// assert_eq!(set.find(&2), None);
+    /// ```
+    pub fn find<V>(&mut self, value: &V) -> Option<&T>
+    where
+        T: Borrow<V>,
+        V: Hash + Eq + ?Sized,
+    {
+        let index = self.get_index(value)?;
+        let root = self.find_root(index);
+        Some(&self.values[root])
+    }
+
+    /// Merges the sets containing two elements. Returns `false` if the elements were already in
+    /// the same set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either element does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::disjoint_set::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::new();
+    /// set.make_set(0);
+    /// set.make_set(1);
+    /// assert!(set.union(&0, &1));
+    /// assert!(!set.union(&1, &0));
+    /// ```
+    pub fn union<V>(&mut self, a: &V, b: &V) -> bool
+    where
+        T: Borrow<V>,
+        V: Hash + Eq + ?Sized,
+    {
+        let a_index = self.get_index(a).expect("Error: element does not exist.");
+        let b_index = self.get_index(b).expect("Error: element does not exist.");
+        let mut a_root = self.find_root(a_index);
+        let mut b_root = self.find_root(b_index);
+        if a_root == b_root {
+            return false;
+        }
+
+        if self.ranks[a_root] < self.ranks[b_root] {
+            mem::swap(&mut a_root, &mut b_root);
+        }
+        self.parents[b_root] = a_root;
+        if self.ranks[a_root] == self.ranks[b_root] {
+            self.ranks[a_root] += 1;
+        }
+
+        // splicing two circular lists merges them into one
+        self.next.swap(a_root, b_root);
+        self.set_count -= 1;
+        true
+    }
+
+    /// Returns `true` if two elements are in the same set. Returns `false` if either element does
+    /// not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::disjoint_set::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::new();
+    /// set.make_set(0);
+    /// set.make_set(1);
+    /// assert!(!set.same_set(&0, &1));
+    /// set.union(&0, &1);
+    /// assert!(set.same_set(&0, &1));
+    /// ```
+    pub fn same_set<V>(&mut self, a: &V, b: &V) -> bool
+    where
+        T: Borrow<V>,
+        V: Hash + Eq + ?Sized,
+    {
+        match (self.get_index(a), self.get_index(b)) {
+            (Some(a_index), Some(b_index)) => self.find_root(a_index) == self.find_root(b_index),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if an element exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::disjoint_set::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::new();
+    /// set.make_set(0);
+    /// assert!(set.contains(&0));
+    /// assert!(!set.contains(&1));
+    /// ```
+    pub fn contains<V>(&self, value: &V) -> bool
+    where
+        T: Borrow<V>,
+        V: Hash + Eq + ?Sized,
+    {
+        self.indexes.contains_key(value)
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::disjoint_set::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::new();
+    /// set.make_set(0);
+    /// set.make_set(1);
+    /// set.union(&0, &1);
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if there are no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::disjoint_set::DisjointSet;
+    ///
+    /// let set: DisjointSet<u32> = DisjointSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of disjoint sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::disjoint_set::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::new();
+    /// set.make_set(0);
+    /// set.make_set(1);
+    /// set.union(&0, &1);
+    /// assert_eq!(set.set_count(), 1);
+    /// ```
+    pub fn set_count(&self) -> usize {
+        self.set_count
+    }
+
+    /// Clears all elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::disjoint_set::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::new();
+    /// set.make_set(0);
+    /// set.clear();
+    /// assert_eq!(set.len(), 0);
+    /// assert_eq!(set.set_count(), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        self.indexes.clear();
+        self.values.clear();
+        self.parents.clear();
+        self.ranks.clear();
+        self.next.clear();
+        self.set_count = 0;
+    }
+
+    /// Returns an iterator over the members of the set containing a particular element. The
+    /// iterator yields no elements if the element does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::disjoint_set::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::new();
+    /// set.make_set(0);
+    /// set.make_set(1);
+    /// set.make_set(2);
+    /// set.union(&0, &2);
+    ///
+    /// let mut members = set.iter_set(&2).cloned().collect::<Vec<u32>>();
+    /// members.sort();
+    /// assert_eq!(members, vec![0, 2]);
+    /// ```
+    pub fn iter_set<V>(&self, value: &V) -> DisjointSetIter<'_, T>
+    where
+        T: Borrow<V>,
+        V: Hash + Eq + ?Sized,
+    {
+        let start = self.get_index(value);
+        DisjointSetIter {
+            set: self,
+            start,
+            curr: start,
+        }
+    }
+}
+
+impl<T> Default for DisjointSet<T>
+where
+    T: Clone + Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An iterator over the members of a set in a `DisjointSet<T>`.
+///
+/// This iterator yields immutable references to the members in no particular order.
+pub struct DisjointSetIter<'a, T> {
+    set: &'a DisjointSet<T>,
+    start: Option<usize>,
+    curr: Option<usize>,
+}
+
+impl<'a, T> Iterator for DisjointSetIter<'a, T>
+where
+    T: 'a,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let curr = self.curr?;
+        let next = self.set.next[curr];
+        self.curr = if Some(next) == self.start {
+            None
+        } else {
+            Some(next)
+        };
+        Some(&self.set.values[curr])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DisjointSet;
+
+    #[test]
+    fn test_len_empty() {
+        let set: DisjointSet<u32> = DisjointSet::new();
+        assert_eq!(set.len(), 0);
+        assert_eq!(set.set_count(), 0);
+        assert_eq!(set.iter_set(&0).next(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_union_missing() {
+        let mut set = DisjointSet::new();
+        set.make_set(0);
+        set.union(&0, &1);
+    }
+
+    #[test]
+    fn test_find_missing() {
+        let mut set: DisjointSet<u32> = DisjointSet::new();
+        assert_eq!(set.find(&0), None);
+        assert!(!set.same_set(&0, &0));
+    }
+
+    #[test]
+    fn test_borrowed_lookup() {
+        let mut set = DisjointSet::new();
+        set.make_set(String::from("a"));
+        set.make_set(String::from("b"));
+        assert!(set.union("a", "b"));
+        assert!(set.same_set("a", "b"));
+        assert!(set.contains("a"));
+    }
+
+    #[test]
+    fn test_union_find() {
+        let mut set = DisjointSet::new();
+        for value in 0..100u32 {
+            set.make_set(value);
+        }
+        for value in 0..100 {
+            set.union(&value, &(value % 7));
+        }
+        assert_eq!(set.set_count(), 7);
+        for a in 0..100 {
+            for b in 0..100 {
+                assert_eq!(set.same_set(&a, &b), a % 7 == b % 7);
+            }
+        }
+        for value in 0..100 {
+            let mut members = set.iter_set(&value).cloned().collect::<Vec<u32>>();
+            members.sort();
+            let expected = (0..100)
+                .filter(|member| member % 7 == value % 7)
+                .collect::<Vec<u32>>();
+            assert_eq!(members, expected);
+        }
+    }
+}
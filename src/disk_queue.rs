@@ -0,0 +1,1110 @@
+//! Persistent first-in, first-out queue stored in segment files.
+
+use crate::durability::{SyncMode, SyncState};
+use bincode::{self, deserialize, serialize};
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::result;
+
+const POINTERS_FILE_NAME: &str = "pointers.dat";
+const POINTERS_TEMP_FILE_NAME: &str = "pointers.dat.tmp";
+const POINTERS_HEADER_SIZE: usize = 8;
+const DEFAULT_SEGMENT_SIZE: u64 = 1 << 26;
+
+/// Convenience `Error` enum for `disk_queue`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// An input or output error that is not associated with a particular file.
+    IOError(io::Error),
+    /// A serialization or deserialization error that is not associated with a particular file.
+    SerdeError(bincode::Error),
+    /// An input or output error that occurred while performing `operation` on the file at `path`.
+    FileIOError {
+        /// The path of the file.
+        path: PathBuf,
+        /// The operation that failed.
+        operation: &'static str,
+        /// The underlying error.
+        source: io::Error,
+    },
+    /// The data at `offset` in the file at `path` could not be decoded or does not match its
+    /// checksum.
+    Corruption {
+        /// The path of the file.
+        path: PathBuf,
+        /// The offset of the data in the file.
+        offset: u64,
+    },
+}
+
+impl Error {
+    fn file_io<P>(path: P, operation: &'static str, source: io::Error) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Error::FileIOError {
+            path: PathBuf::from(path.as_ref()),
+            operation,
+            source,
+        }
+    }
+
+    fn corruption<P>(path: P, offset: u64) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Error::Corruption {
+            path: PathBuf::from(path.as_ref()),
+            offset,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::IOError(err)
+    }
+}
+
+impl From<bincode::Error> for Error {
+    fn from(err: bincode::Error) -> Error {
+        Error::SerdeError(err)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::IOError(ref error) => Some(error),
+            Error::SerdeError(ref error) => Some(error),
+            Error::FileIOError { ref source, .. } => Some(source),
+            Error::Corruption { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IOError(ref error) => write!(f, "{}", error),
+            Error::SerdeError(ref error) => write!(f, "{}", error),
+            Error::FileIOError {
+                ref path,
+                operation,
+                ref source,
+            } => write!(
+                f,
+                "failed to {} `{}`: {}",
+                operation,
+                path.display(),
+                source
+            ),
+            Error::Corruption { ref path, offset } => write!(
+                f,
+                "corrupted data at offset {} of `{}`",
+                offset,
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Convenience `Result` type for `disk_queue`.
+pub type Result<T> = result::Result<T, Error>;
+
+// Computes the 64-bit FNV-1a hash of a slice of bytes.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn segment_file_name(segment: u64) -> String {
+    format!("segment-{:020}.dat", segment)
+}
+
+fn parse_segment_file_name(file_name: &str) -> Option<u64> {
+    file_name
+        .strip_prefix("segment-")
+        .and_then(|file_name| file_name.strip_suffix(".dat"))
+        .and_then(|segment| segment.parse().ok())
+}
+
+/// Options for a `DiskQueue`.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::disk_queue::DiskQueueOptions;
+///
+/// let options = DiskQueueOptions {
+///     checksums: true,
+///     ..DiskQueueOptions::default()
+/// };
+/// assert_eq!(options.segment_size, 1 << 26);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DiskQueueOptions {
+    /// The size in bytes after which the segment file that items are pushed to is sealed and a
+    /// new segment file is started. A segment file is deleted once all of its items are popped.
+    /// The default is `1 << 26`.
+    pub segment_size: u64,
+    /// Whether each record is stored with a checksum of its data that is verified when it is
+    /// read. The default is `false`.
+    pub checksums: bool,
+}
+
+impl Default for DiskQueueOptions {
+    fn default() -> Self {
+        DiskQueueOptions {
+            segment_size: DEFAULT_SEGMENT_SIZE,
+            checksums: false,
+        }
+    }
+}
+
+// The head and tail pointers of a queue, which are persisted with a checksum in the pointers
+// file. The records between the head and the tail are the items of the queue.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct Pointers {
+    head_segment: u64,
+    head_offset: u64,
+    tail_segment: u64,
+    tail_offset: u64,
+    len: usize,
+    options: (u64, bool),
+}
+
+fn read_pointers(path: &Path) -> Result<Pointers> {
+    let mut file = File::open(path).map_err(|error| Error::file_io(path, "open", error))?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)
+        .map_err(|error| Error::file_io(path, "read", error))?;
+    if buffer.len() < POINTERS_HEADER_SIZE {
+        return Err(Error::corruption(path, 0));
+    }
+    let mut checksum_buffer = [0; POINTERS_HEADER_SIZE];
+    checksum_buffer.copy_from_slice(&buffer[..POINTERS_HEADER_SIZE]);
+    if u64::from_be_bytes(checksum_buffer) != checksum(&buffer[POINTERS_HEADER_SIZE..]) {
+        return Err(Error::corruption(path, 0));
+    }
+    deserialize(&buffer[POINTERS_HEADER_SIZE..])
+        .map_err(|_| Error::corruption(path, POINTERS_HEADER_SIZE as u64))
+}
+
+// Writes the pointers of a queue to a temporary file that is atomically renamed over the pointers
+// file. If `sync` is true, the write is durable once this function returns.
+fn write_pointers(path: &Path, pointers: &Pointers, sync: bool) -> Result<()> {
+    let serialized_pointers = serialize(pointers)?;
+    let mut buffer = checksum(&serialized_pointers).to_be_bytes().to_vec();
+    buffer.extend(serialized_pointers);
+
+    let temp_path = path.join(POINTERS_TEMP_FILE_NAME);
+    let mut temp_file =
+        File::create(&temp_path).map_err(|error| Error::file_io(&temp_path, "create", error))?;
+    temp_file
+        .write_all(&buffer)
+        .map_err(|error| Error::file_io(&temp_path, "write", error))?;
+    if sync {
+        temp_file
+            .sync_all()
+            .map_err(|error| Error::file_io(&temp_path, "sync", error))?;
+    }
+    fs::rename(&temp_path, path.join(POINTERS_FILE_NAME))?;
+    if sync {
+        File::open(path)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// A persistent first-in, first-out queue stored in a folder of segment files.
+///
+/// Items are serialized and appended as records to the tail segment file. Once the tail segment
+/// reaches the segment size, it is sealed and items are appended to a new segment, and a segment
+/// is deleted once all of its items have been popped. Each record can optionally be stored with a
+/// checksum that is verified when the record is read.
+///
+/// The head and tail pointers of the queue are written to a separate file that is atomically
+/// replaced, so the queue can always be opened in the state of its last sync or flush. Pushes and
+/// pops after the last sync are lost if the machine crashes, and pops are only durable once they
+/// are synced, so an item may be popped again after a crash. The sync mode of the queue determines
+/// when the queue is synced.
+///
+/// # Examples
+///
+/// ```
+/// # use extended_collections::disk_queue::Result;
+/// # fn foo() -> Result<()> {
+/// # use std::fs;
+/// use extended_collections::disk_queue::DiskQueue;
+///
+/// let mut queue = DiskQueue::new("example_disk_queue")?;
+/// queue.push(String::from("a"))?;
+/// queue.push(String::from("b"))?;
+/// queue.close()?;
+///
+/// let mut queue: DiskQueue<String> = DiskQueue::open("example_disk_queue")?;
+/// assert_eq!(queue.len(), 2);
+/// assert_eq!(queue.peek()?, Some(String::from("a")));
+/// assert_eq!(queue.pop()?, Some(String::from("a")));
+/// assert_eq!(queue.pop()?, Some(String::from("b")));
+/// assert_eq!(queue.pop()?, None);
+/// # fs::remove_dir_all("example_disk_queue")?;
+/// # Ok(())
+/// # }
+/// # foo().unwrap();
+/// ```
+pub struct DiskQueue<T> {
+    path: PathBuf,
+    options: DiskQueueOptions,
+    pointers: Pointers,
+    // the sizes of the sealed segments from the head segment to the segment before the tail
+    // segment.
+    sealed_segment_sizes: VecDeque<u64>,
+    // the segments whose items were all popped, which are deleted once the queue is synced.
+    obsolete_segments: Vec<u64>,
+    head_file: Option<File>,
+    tail_writer: BufWriter<File>,
+    sync_state: SyncState,
+    _marker: PhantomData<T>,
+}
+
+impl<T> DiskQueue<T> {
+    /// Constructs a new, empty `DiskQueue<T>` in a new folder at `path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::DiskQueue;
+    ///
+    /// let queue: DiskQueue<u32> = DiskQueue::new("example_disk_queue_new")?;
+    /// # fs::remove_dir_all("example_disk_queue_new")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn new<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::with_options(path, DiskQueueOptions::default())
+    }
+
+    /// Constructs a new, empty `DiskQueue<T>` in a new folder at `path` using `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the segment size is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::{DiskQueue, DiskQueueOptions};
+    ///
+    /// let options = DiskQueueOptions {
+    ///     segment_size: 1 << 20,
+    ///     checksums: true,
+    /// };
+    /// let queue: DiskQueue<u32> =
+    ///     DiskQueue::with_options("example_disk_queue_with_options", options)?;
+    /// # fs::remove_dir_all("example_disk_queue_with_options")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn with_options<P>(path: P, options: DiskQueueOptions) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        assert!(
+            options.segment_size > 0,
+            "Error: segment size must be positive."
+        );
+        let path = PathBuf::from(path.as_ref());
+        fs::create_dir(&path).map_err(|error| Error::file_io(&path, "create", error))?;
+        let pointers = Pointers {
+            head_segment: 0,
+            head_offset: 0,
+            tail_segment: 0,
+            tail_offset: 0,
+            len: 0,
+            options: (options.segment_size, options.checksums),
+        };
+        let tail_writer = Self::create_segment(&path, 0)?;
+        write_pointers(&path, &pointers, true)?;
+        Ok(DiskQueue {
+            path,
+            options,
+            pointers,
+            sealed_segment_sizes: VecDeque::new(),
+            obsolete_segments: Vec::new(),
+            head_file: None,
+            tail_writer,
+            sync_state: SyncState::new(SyncMode::default()),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Opens an existing `DiskQueue<T>` from a folder in the state of its last sync or flush.
+    /// Records that were written after the last sync or flush are discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::DiskQueue;
+    ///
+    /// let mut queue = DiskQueue::new("example_disk_queue_open")?;
+    /// queue.push(1)?;
+    /// queue.close()?;
+    ///
+    /// let queue: DiskQueue<u32> = DiskQueue::open("example_disk_queue_open")?;
+    /// assert_eq!(queue.len(), 1);
+    /// # fs::remove_dir_all("example_disk_queue_open")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = PathBuf::from(path.as_ref());
+        let pointers = read_pointers(&path.join(POINTERS_FILE_NAME))?;
+        let options = DiskQueueOptions {
+            segment_size: pointers.options.0,
+            checksums: pointers.options.1,
+        };
+
+        // segments before the head segment were popped and segments after the tail segment were
+        // started after the last sync or flush.
+        let dir_entries =
+            fs::read_dir(&path).map_err(|error| Error::file_io(&path, "read", error))?;
+        for dir_entry in dir_entries {
+            let entry_path = dir_entry?.path();
+            let segment = entry_path
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .and_then(parse_segment_file_name);
+            if let Some(segment) = segment {
+                if segment < pointers.head_segment || segment > pointers.tail_segment {
+                    fs::remove_file(&entry_path)
+                        .map_err(|error| Error::file_io(&entry_path, "remove", error))?;
+                }
+            }
+        }
+
+        let mut sealed_segment_sizes = VecDeque::new();
+        for segment in pointers.head_segment..pointers.tail_segment {
+            let segment_path = path.join(segment_file_name(segment));
+            let metadata = fs::metadata(&segment_path)
+                .map_err(|error| Error::file_io(&segment_path, "open", error))?;
+            sealed_segment_sizes.push_back(metadata.len());
+        }
+
+        // records after the tail pointer were written after the last sync or flush.
+        let tail_path = path.join(segment_file_name(pointers.tail_segment));
+        let tail_file = OpenOptions::new()
+            .append(true)
+            .open(&tail_path)
+            .map_err(|error| Error::file_io(&tail_path, "open", error))?;
+        let tail_len = tail_file
+            .metadata()
+            .map_err(|error| Error::file_io(&tail_path, "open", error))?
+            .len();
+        if tail_len < pointers.tail_offset {
+            return Err(Error::corruption(&tail_path, tail_len));
+        }
+        tail_file
+            .set_len(pointers.tail_offset)
+            .map_err(|error| Error::file_io(&tail_path, "truncate", error))?;
+
+        Ok(DiskQueue {
+            path,
+            options,
+            pointers,
+            sealed_segment_sizes,
+            obsolete_segments: Vec::new(),
+            head_file: None,
+            tail_writer: BufWriter::new(tail_file),
+            sync_state: SyncState::new(SyncMode::default()),
+            _marker: PhantomData,
+        })
+    }
+
+    fn create_segment(path: &Path, segment: u64) -> Result<BufWriter<File>> {
+        let segment_path = path.join(segment_file_name(segment));
+        let file = OpenOptions::new()
+            .append(true)
+            .create_new(true)
+            .open(&segment_path)
+            .map_err(|error| Error::file_io(&segment_path, "create", error))?;
+        Ok(BufWriter::new(file))
+    }
+
+    fn segment_path(&self, segment: u64) -> PathBuf {
+        self.path.join(segment_file_name(segment))
+    }
+
+    fn header_size(&self) -> u64 {
+        if self.options.checksums {
+            16
+        } else {
+            8
+        }
+    }
+
+    // Moves the head pointer past the sealed segments whose items were all popped.
+    fn skip_popped_segments(&mut self) {
+        while let Some(&segment_size) = self.sealed_segment_sizes.front() {
+            if self.pointers.head_offset < segment_size {
+                break;
+            }
+            self.sealed_segment_sizes.pop_front();
+            self.obsolete_segments.push(self.pointers.head_segment);
+            self.pointers.head_segment += 1;
+            self.pointers.head_offset = 0;
+            self.head_file = None;
+        }
+    }
+
+    // Reads the record at the head pointer and returns its item and its size.
+    fn read_head(&mut self) -> Result<Option<(T, u64)>>
+    where
+        T: DeserializeOwned,
+    {
+        if self.pointers.len == 0 {
+            return Ok(None);
+        }
+
+        let head_path = self.segment_path(self.pointers.head_segment);
+        if self.pointers.head_segment == self.pointers.tail_segment {
+            self.tail_writer
+                .flush()
+                .map_err(|error| Error::file_io(&head_path, "write", error))?;
+        }
+        if self.head_file.is_none() {
+            let head_file = File::open(&head_path)
+                .map_err(|error| Error::file_io(&head_path, "open", error))?;
+            self.head_file = Some(head_file);
+        }
+
+        let offset = self.pointers.head_offset;
+        let header_size = self.header_size();
+        let head_file = self.head_file.as_mut().expect("Expected opened file.");
+        let map_read_error = |error: io::Error| match error.kind() {
+            ErrorKind::UnexpectedEof => Error::corruption(&head_path, offset),
+            _ => Error::file_io(&head_path, "read", error),
+        };
+        head_file
+            .seek(SeekFrom::Start(offset))
+            .map_err(map_read_error)?;
+        let mut header = [0; 16];
+        head_file
+            .read_exact(&mut header[..header_size as usize])
+            .map_err(map_read_error)?;
+        let mut size_buffer = [0; 8];
+        size_buffer.copy_from_slice(&header[..8]);
+        let size = u64::from_be_bytes(size_buffer);
+        let mut buffer = vec![0; size as usize];
+        head_file.read_exact(&mut buffer).map_err(map_read_error)?;
+
+        if self.options.checksums {
+            let mut checksum_buffer = [0; 8];
+            checksum_buffer.copy_from_slice(&header[8..]);
+            if u64::from_be_bytes(checksum_buffer) != checksum(&buffer) {
+                return Err(Error::corruption(&head_path, offset));
+            }
+        }
+        let item = deserialize(&buffer).map_err(|_| Error::corruption(&head_path, offset))?;
+        Ok(Some((item, header_size + size)))
+    }
+
+    fn record_write(&mut self) -> Result<()> {
+        if self.sync_state.record_write() {
+            self.sync()?;
+        }
+        Ok(())
+    }
+
+    /// Appends an item to the back of the queue. If the tail segment has reached the segment
+    /// size, it is sealed and the item is appended to a new segment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::DiskQueue;
+    ///
+    /// let mut queue = DiskQueue::new("example_disk_queue_push")?;
+    /// queue.push(1)?;
+    /// assert_eq!(queue.len(), 1);
+    /// # fs::remove_dir_all("example_disk_queue_push")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn push(&mut self, item: T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        if self.pointers.tail_offset >= self.options.segment_size {
+            let tail_path = self.segment_path(self.pointers.tail_segment);
+            self.tail_writer
+                .flush()
+                .map_err(|error| Error::file_io(&tail_path, "write", error))?;
+            let tail_writer = Self::create_segment(&self.path, self.pointers.tail_segment + 1)?;
+            self.tail_writer = tail_writer;
+            self.sealed_segment_sizes
+                .push_back(self.pointers.tail_offset);
+            self.pointers.tail_segment += 1;
+            self.pointers.tail_offset = 0;
+            self.skip_popped_segments();
+        }
+
+        let serialized_item = serialize(&item)?;
+        let mut header = (serialized_item.len() as u64).to_be_bytes().to_vec();
+        if self.options.checksums {
+            header.extend_from_slice(&checksum(&serialized_item).to_be_bytes());
+        }
+        let tail_path = self.segment_path(self.pointers.tail_segment);
+        self.tail_writer
+            .write_all(&header)
+            .and_then(|_| self.tail_writer.write_all(&serialized_item))
+            .map_err(|error| Error::file_io(&tail_path, "write", error))?;
+        self.pointers.tail_offset += (header.len() + serialized_item.len()) as u64;
+        self.pointers.len += 1;
+        self.record_write()
+    }
+
+    /// Removes the item at the front of the queue and returns it. Returns `None` if the queue is
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::DiskQueue;
+    ///
+    /// let mut queue = DiskQueue::new("example_disk_queue_pop")?;
+    /// queue.push(1)?;
+    /// queue.push(2)?;
+    /// assert_eq!(queue.pop()?, Some(1));
+    /// assert_eq!(queue.pop()?, Some(2));
+    /// assert_eq!(queue.pop()?, None);
+    /// # fs::remove_dir_all("example_disk_queue_pop")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn pop(&mut self) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        match self.read_head()? {
+            Some((item, record_size)) => {
+                self.pointers.head_offset += record_size;
+                self.pointers.len -= 1;
+                self.skip_popped_segments();
+                self.record_write()?;
+                Ok(Some(item))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the item at the front of the queue without removing it. Returns `None` if the
+    /// queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::DiskQueue;
+    ///
+    /// let mut queue = DiskQueue::new("example_disk_queue_peek")?;
+    /// assert_eq!(queue.peek()?, None);
+    /// queue.push(1)?;
+    /// assert_eq!(queue.peek()?, Some(1));
+    /// assert_eq!(queue.len(), 1);
+    /// # fs::remove_dir_all("example_disk_queue_peek")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn peek(&mut self) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.read_head().map(|head| head.map(|(item, _)| item))
+    }
+
+    /// Returns the number of items in the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::DiskQueue;
+    ///
+    /// let mut queue = DiskQueue::new("example_disk_queue_len")?;
+    /// queue.push(1)?;
+    /// assert_eq!(queue.len(), 1);
+    /// # fs::remove_dir_all("example_disk_queue_len")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn len(&self) -> usize {
+        self.pointers.len
+    }
+
+    /// Returns `true` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::DiskQueue;
+    ///
+    /// let queue: DiskQueue<u32> = DiskQueue::new("example_disk_queue_is_empty")?;
+    /// assert!(queue.is_empty());
+    /// # fs::remove_dir_all("example_disk_queue_is_empty")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of segment files that contain items of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::{DiskQueue, DiskQueueOptions};
+    ///
+    /// let options = DiskQueueOptions {
+    ///     segment_size: 1,
+    ///     ..DiskQueueOptions::default()
+    /// };
+    /// let mut queue = DiskQueue::with_options("example_disk_queue_segment_count", options)?;
+    /// queue.push(1)?;
+    /// queue.push(2)?;
+    /// assert_eq!(queue.segment_count(), 2);
+    ///
+    /// queue.pop()?;
+    /// assert_eq!(queue.segment_count(), 1);
+    /// # fs::remove_dir_all("example_disk_queue_segment_count")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn segment_count(&self) -> usize {
+        (self.pointers.tail_segment - self.pointers.head_segment) as usize + 1
+    }
+
+    /// Returns the options of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::{DiskQueue, DiskQueueOptions};
+    ///
+    /// let queue: DiskQueue<u32> = DiskQueue::new("example_disk_queue_options")?;
+    /// assert_eq!(queue.options(), DiskQueueOptions::default());
+    /// # fs::remove_dir_all("example_disk_queue_options")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn options(&self) -> DiskQueueOptions {
+        self.options
+    }
+
+    /// Returns the sync mode of the queue, which determines when pushes and pops are synced to
+    /// disk. The sync mode of a new or opened queue is `SyncMode::Manual`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::DiskQueue;
+    /// use extended_collections::durability::SyncMode;
+    ///
+    /// let queue: DiskQueue<u32> = DiskQueue::new("example_disk_queue_sync_mode")?;
+    /// assert_eq!(queue.sync_mode(), SyncMode::Manual);
+    /// # fs::remove_dir_all("example_disk_queue_sync_mode")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn sync_mode(&self) -> SyncMode {
+        self.sync_state.mode()
+    }
+
+    /// Sets the sync mode of the queue, which determines when pushes and pops are synced to disk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::DiskQueue;
+    /// use extended_collections::durability::SyncMode;
+    ///
+    /// let mut queue: DiskQueue<u32> = DiskQueue::new("example_disk_queue_set_sync_mode")?;
+    /// queue.set_sync_mode(SyncMode::Always);
+    /// assert_eq!(queue.sync_mode(), SyncMode::Always);
+    /// # fs::remove_dir_all("example_disk_queue_set_sync_mode")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_sync_mode(&mut self, sync_mode: SyncMode) {
+        self.sync_state.set_mode(sync_mode);
+    }
+
+    /// Hands all outstanding pushes and pops to the operating system without waiting for them to
+    /// reach the disk. Flushed writes survive a crash of the process, but not a crash of the
+    /// machine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::DiskQueue;
+    ///
+    /// let mut queue = DiskQueue::new("example_disk_queue_flush")?;
+    /// queue.push(1)?;
+    /// queue.flush()?;
+    /// # fs::remove_dir_all("example_disk_queue_flush")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn flush(&mut self) -> Result<()> {
+        let tail_path = self.segment_path(self.pointers.tail_segment);
+        self.tail_writer
+            .flush()
+            .map_err(|error| Error::file_io(&tail_path, "write", error))?;
+        write_pointers(&self.path, &self.pointers, false)
+    }
+
+    /// Syncs all pushes and pops to disk, waits until they reach the disk, and deletes the
+    /// segment files whose items were all popped. Synced writes survive a crash of the machine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::DiskQueue;
+    ///
+    /// let mut queue = DiskQueue::new("example_disk_queue_sync")?;
+    /// queue.push(1)?;
+    /// queue.sync()?;
+    /// # fs::remove_dir_all("example_disk_queue_sync")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn sync(&mut self) -> Result<()> {
+        let tail_path = self.segment_path(self.pointers.tail_segment);
+        self.tail_writer
+            .flush()
+            .and_then(|_| self.tail_writer.get_ref().sync_data())
+            .map_err(|error| Error::file_io(&tail_path, "sync", error))?;
+        // the sealed segments were flushed when they were sealed, but are only synced once the
+        // pointers that reference the following segments are synced.
+        for segment in self.pointers.head_segment..self.pointers.tail_segment {
+            let segment_path = self.segment_path(segment);
+            File::open(&segment_path)
+                .and_then(|file| file.sync_data())
+                .map_err(|error| Error::file_io(&segment_path, "sync", error))?;
+        }
+        write_pointers(&self.path, &self.pointers, true)?;
+
+        // the synced pointers no longer reference the obsolete segments, so they can be deleted.
+        for segment in mem::take(&mut self.obsolete_segments) {
+            let segment_path = self.segment_path(segment);
+            fs::remove_file(&segment_path)
+                .map_err(|error| Error::file_io(&segment_path, "remove", error))?;
+        }
+        self.sync_state.record_sync();
+        Ok(())
+    }
+
+    /// Syncs all pushes and pops to disk before consuming the queue. Unlike relying on the queue
+    /// being dropped, any errors encountered while persisting the queue are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::disk_queue::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::disk_queue::DiskQueue;
+    ///
+    /// let mut queue = DiskQueue::new("example_disk_queue_close")?;
+    /// queue.push(1)?;
+    /// queue.close()?;
+    ///
+    /// let queue: DiskQueue<u32> = DiskQueue::open("example_disk_queue_close")?;
+    /// assert_eq!(queue.len(), 1);
+    /// # fs::remove_dir_all("example_disk_queue_close")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn close(mut self) -> Result<()> {
+        self.sync()
+    }
+}
+
+impl<T> Drop for DiskQueue<T> {
+    fn drop(&mut self) {
+        // errors cannot be reported when the queue is dropped, so `close` should be used instead
+        // if they need to be handled.
+        if self.sync_state.should_sync_on_drop() {
+            let _ = self.sync();
+        } else {
+            let _ = self.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DiskQueue, DiskQueueOptions, Error};
+    use crate::durability::SyncMode;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::mem;
+    use std::path::Path;
+
+    struct TestFolder(&'static str);
+
+    impl TestFolder {
+        fn new(path: &'static str) -> Self {
+            let _ = fs::remove_dir_all(path);
+            TestFolder(path)
+        }
+
+        fn segment_paths(&self) -> Vec<String> {
+            let mut ret: Vec<String> = fs::read_dir(self.0)
+                .unwrap()
+                .map(|dir_entry| dir_entry.unwrap().file_name().into_string().unwrap())
+                .filter(|file_name| file_name.starts_with("segment-"))
+                .collect();
+            ret.sort();
+            ret
+        }
+    }
+
+    impl Drop for TestFolder {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(self.0);
+        }
+    }
+
+    fn small_segments(checksums: bool) -> DiskQueueOptions {
+        DiskQueueOptions {
+            segment_size: 64,
+            checksums,
+        }
+    }
+
+    #[test]
+    fn test_fifo() {
+        let folder = TestFolder::new("test_disk_queue_fifo");
+        let mut queue = DiskQueue::with_options(folder.0, small_segments(false)).unwrap();
+        for item in 0..100u64 {
+            queue.push(item).unwrap();
+            if item % 3 == 0 {
+                assert_eq!(queue.pop().unwrap(), Some(item / 3));
+            }
+        }
+        assert_eq!(queue.len(), 66);
+        for item in 34..100 {
+            assert_eq!(queue.peek().unwrap(), Some(item));
+            assert_eq!(queue.pop().unwrap(), Some(item));
+        }
+        assert_eq!(queue.pop().unwrap(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_reopen() {
+        let folder = TestFolder::new("test_disk_queue_reopen");
+        let mut queue = DiskQueue::with_options(folder.0, small_segments(true)).unwrap();
+        for item in 0..50 {
+            queue.push(format!("item-{}", item)).unwrap();
+        }
+        for _ in 0..20 {
+            queue.pop().unwrap();
+        }
+        queue.close().unwrap();
+
+        let mut queue: DiskQueue<String> = DiskQueue::open(folder.0).unwrap();
+        assert_eq!(queue.options(), small_segments(true));
+        assert_eq!(queue.len(), 30);
+        for item in 20..50 {
+            assert_eq!(queue.pop().unwrap(), Some(format!("item-{}", item)));
+        }
+    }
+
+    #[test]
+    fn test_segments_are_deleted() {
+        let folder = TestFolder::new("test_disk_queue_segments_are_deleted");
+        let mut queue = DiskQueue::with_options(folder.0, small_segments(false)).unwrap();
+        for item in 0..32u64 {
+            queue.push(item).unwrap();
+        }
+        // each record is 16 bytes, so each segment contains 4 records.
+        assert_eq!(queue.segment_count(), 8);
+        for _ in 0..30 {
+            queue.pop().unwrap();
+        }
+        assert_eq!(queue.segment_count(), 1);
+        assert_eq!(folder.segment_paths().len(), 8);
+
+        queue.sync().unwrap();
+        assert_eq!(
+            folder.segment_paths(),
+            vec![String::from("segment-00000000000000000007.dat")],
+        );
+        assert_eq!(queue.pop().unwrap(), Some(30));
+    }
+
+    #[test]
+    fn test_crash_discards_unsynced_writes() {
+        let folder = TestFolder::new("test_disk_queue_crash_discards_unsynced_writes");
+        let mut queue = DiskQueue::with_options(folder.0, small_segments(false)).unwrap();
+        for item in 0..10u64 {
+            queue.push(item).unwrap();
+        }
+        queue.pop().unwrap();
+        queue.sync().unwrap();
+        for item in 10..20 {
+            queue.push(item).unwrap();
+        }
+        queue.pop().unwrap();
+        queue.tail_writer.flush().unwrap();
+        // simulate a crash by not flushing the pointers.
+        mem::forget(queue);
+
+        let mut queue: DiskQueue<u64> = DiskQueue::open(folder.0).unwrap();
+        assert_eq!(queue.len(), 9);
+        assert_eq!(folder.segment_paths().len(), 3);
+        queue.push(100).unwrap();
+        let items: Vec<u64> = (0..10).map(|_| queue.pop().unwrap().unwrap()).collect();
+        assert_eq!(items, vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 100]);
+    }
+
+    #[test]
+    fn test_drop_flushes() {
+        let folder = TestFolder::new("test_disk_queue_drop_flushes");
+        let mut queue = DiskQueue::new(folder.0).unwrap();
+        queue.set_sync_mode(SyncMode::Manual);
+        queue.push(1u32).unwrap();
+        drop(queue);
+
+        let mut queue: DiskQueue<u32> = DiskQueue::open(folder.0).unwrap();
+        assert_eq!(queue.pop().unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_sync_mode_always() {
+        let folder = TestFolder::new("test_disk_queue_sync_mode_always");
+        let mut queue = DiskQueue::new(folder.0).unwrap();
+        queue.set_sync_mode(SyncMode::Always);
+        queue.push(1u32).unwrap();
+        queue.push(2).unwrap();
+        queue.pop().unwrap();
+        mem::forget(queue);
+
+        let mut queue: DiskQueue<u32> = DiskQueue::open(folder.0).unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.pop().unwrap(), Some(2));
+    }
+
+    #[test]
+    fn test_checksum_detects_corruption() {
+        let folder = TestFolder::new("test_disk_queue_checksum_detects_corruption");
+        let mut queue = DiskQueue::with_options(folder.0, small_segments(true)).unwrap();
+        queue.push(String::from("a")).unwrap();
+        queue.push(String::from("b")).unwrap();
+        queue.flush().unwrap();
+
+        // each record has a 16 byte header and a 9 byte string, so the second record starts at
+        // offset 25 and its last byte is at offset 49.
+        let segment_path = Path::new(folder.0).join("segment-00000000000000000000.dat");
+        let mut bytes = fs::read(&segment_path).unwrap();
+        bytes[49] = b'c';
+        OpenOptions::new()
+            .write(true)
+            .open(&segment_path)
+            .unwrap()
+            .write_all(&bytes)
+            .unwrap();
+
+        assert_eq!(queue.pop().unwrap(), Some(String::from("a")));
+        match queue.pop() {
+            Err(Error::Corruption { path, offset }) => {
+                assert_eq!(path, segment_path);
+                assert_eq!(offset, 25);
+            }
+            _ => panic!("Expected corruption error."),
+        }
+    }
+
+    #[test]
+    fn test_open_missing() {
+        match DiskQueue::<u32>::open("test_disk_queue_open_missing") {
+            Err(Error::FileIOError { operation, .. }) => assert_eq!(operation, "open"),
+            _ => panic!("Expected file error."),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Configuration of when persistent collections sync their data to disk.
+
+use std::time::{Duration, Instant};
+
+/// Determines when the writes to a persistent collection are synced to disk.
+///
+/// Syncing a collection flushes its outstanding state and waits until the operating system has
+/// written its data to the storage device, so that the data survives a crash of the machine.
+/// Independently of the mode, a collection flushes its outstanding state to the operating system
+/// when it is dropped, and syncs it as well unless the mode is `SyncMode::Manual`.
+///
+/// # Examples
+///
+/// ```
+/// # use extended_collections::bp_tree::Result;
+/// # fn foo() -> Result<()> {
+/// # use std::fs;
+/// use extended_collections::bp_tree::BpMap;
+/// use extended_collections::durability::SyncMode;
+/// use std::time::Duration;
+///
+/// let mut map: BpMap<u32, u64> = BpMap::new("example_sync_mode", 4, 8)?;
+/// assert_eq!(map.sync_mode(), SyncMode::Manual);
+///
+/// map.set_sync_mode(SyncMode::Interval(Duration::from_secs(1)));
+/// map.insert(1, 1)?;
+/// # fs::remove_file("example_sync_mode")?;
+/// # Ok(())
+/// # }
+/// # foo().unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SyncMode {
+    /// Every write is synced before it returns.
+    Always,
+    /// A write is synced before it returns if at least the given duration has elapsed since the
+    /// last sync.
+    Interval(Duration),
+    /// Writes are only synced by explicit calls to `sync` and when the collection is closed.
+    #[default]
+    Manual,
+}
+
+// Tracks the writes of a collection that have not been synced and decides when to sync them.
+pub(crate) struct SyncState {
+    mode: SyncMode,
+    last_sync: Instant,
+    is_dirty: bool,
+}
+
+impl SyncState {
+    pub fn new(mode: SyncMode) -> Self {
+        SyncState {
+            mode,
+            last_sync: Instant::now(),
+            is_dirty: false,
+        }
+    }
+
+    pub fn mode(&self) -> SyncMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: SyncMode) {
+        self.mode = mode;
+    }
+
+    // Returns whether there are writes that have not been synced.
+    pub fn is_dirty(&self) -> bool {
+        self.is_dirty
+    }
+
+    // Records a write and returns whether the collection should be synced.
+    pub fn record_write(&mut self) -> bool {
+        self.is_dirty = true;
+        match self.mode {
+            SyncMode::Always => true,
+            SyncMode::Interval(interval) => self.last_sync.elapsed() >= interval,
+            SyncMode::Manual => false,
+        }
+    }
+
+    pub fn record_sync(&mut self) {
+        self.last_sync = Instant::now();
+        self.is_dirty = false;
+    }
+
+    // Returns whether the collection should be synced when it is dropped.
+    pub fn should_sync_on_drop(&self) -> bool {
+        self.is_dirty() && self.mode != SyncMode::Manual
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SyncMode, SyncState};
+    use std::time::Duration;
+
+    #[test]
+    fn test_always() {
+        let mut state = SyncState::new(SyncMode::Always);
+        assert!(!state.is_dirty());
+        assert!(state.record_write());
+        assert!(state.is_dirty());
+        assert!(state.should_sync_on_drop());
+        state.record_sync();
+        assert!(!state.is_dirty());
+        assert!(!state.should_sync_on_drop());
+    }
+
+    #[test]
+    fn test_interval() {
+        let mut state = SyncState::new(SyncMode::Interval(Duration::from_secs(3600)));
+        assert!(!state.record_write());
+        assert!(state.should_sync_on_drop());
+
+        state.set_mode(SyncMode::Interval(Duration::from_secs(0)));
+        assert!(state.record_write());
+    }
+
+    #[test]
+    fn test_manual() {
+        let mut state = SyncState::new(SyncMode::default());
+        assert_eq!(state.mode(), SyncMode::Manual);
+        assert!(!state.record_write());
+        assert!(state.is_dirty());
+        assert!(!state.should_sync_on_drop());
+    }
+}
@@ -1,7 +1,9 @@
+use core::cmp::Ordering;
+#[cfg(feature = "serde")]
 use serde_derive::{Deserialize, Serialize};
-use std::cmp::Ordering;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Entry<T, U> {
     pub key: T,
     pub value: U,
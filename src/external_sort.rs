@@ -0,0 +1,753 @@
+//! External merge sort of streams that do not fit in memory.
+
+use bincode::{self, deserialize, serialize};
+use rand::{thread_rng, Rng};
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::marker::PhantomData;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::result;
+use std::vec;
+
+/// Convenience `Error` enum for `external_sort`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// An input or output error that is not associated with a particular file.
+    IOError(io::Error),
+    /// A serialization or deserialization error that is not associated with a particular file.
+    SerdeError(bincode::Error),
+    /// An input or output error that occurred while performing `operation` on the file at `path`.
+    FileIOError {
+        /// The path of the file.
+        path: PathBuf,
+        /// The operation that failed.
+        operation: &'static str,
+        /// The underlying error.
+        source: io::Error,
+    },
+    /// The data at `offset` in the file at `path` could not be decoded.
+    Corruption {
+        /// The path of the file.
+        path: PathBuf,
+        /// The offset of the data in the file.
+        offset: u64,
+    },
+}
+
+impl Error {
+    fn file_io<P>(path: P, operation: &'static str, source: io::Error) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Error::FileIOError {
+            path: PathBuf::from(path.as_ref()),
+            operation,
+            source,
+        }
+    }
+
+    fn corruption<P>(path: P, offset: u64) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Error::Corruption {
+            path: PathBuf::from(path.as_ref()),
+            offset,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::IOError(err)
+    }
+}
+
+impl From<bincode::Error> for Error {
+    fn from(err: bincode::Error) -> Error {
+        Error::SerdeError(err)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::IOError(ref error) => Some(error),
+            Error::SerdeError(ref error) => Some(error),
+            Error::FileIOError { ref source, .. } => Some(source),
+            Error::Corruption { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IOError(ref error) => write!(f, "{}", error),
+            Error::SerdeError(ref error) => write!(f, "{}", error),
+            Error::FileIOError {
+                ref path,
+                operation,
+                ref source,
+            } => write!(
+                f,
+                "failed to {} `{}`: {}",
+                operation,
+                path.display(),
+                source
+            ),
+            Error::Corruption { ref path, offset } => write!(
+                f,
+                "corrupted data at offset {} of `{}`",
+                offset,
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Convenience `Result` type for `external_sort`.
+pub type Result<T> = result::Result<T, Error>;
+
+// The folder of the spilled runs of a sort. The folder is only created when the first run is
+// spilled, and it is removed with all of its runs when it is dropped.
+struct SpillFolder {
+    path: PathBuf,
+    is_created: bool,
+}
+
+impl SpillFolder {
+    fn new(db_path: &Path) -> Self {
+        let file_name: String = thread_rng().gen_ascii_chars().take(32).collect();
+        SpillFolder {
+            path: db_path.join(file_name),
+            is_created: false,
+        }
+    }
+
+    fn create_run_path(&mut self, index: usize) -> Result<PathBuf> {
+        if !self.is_created {
+            fs::create_dir(&self.path)
+                .map_err(|error| Error::file_io(&self.path, "create", error))?;
+            self.is_created = true;
+        }
+        Ok(self.path.join(format!("run-{}.dat", index)))
+    }
+}
+
+impl Drop for SpillFolder {
+    fn drop(&mut self) {
+        if self.is_created {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+// A sorted run of items that was spilled to a file. Each item is stored as its serialized length
+// as a big-endian `u64` followed by its serialized bytes.
+struct Run<T> {
+    path: PathBuf,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Run<T>
+where
+    T: Serialize,
+{
+    fn write(path: PathBuf, items: &[T]) -> Result<Self> {
+        let file = File::create(&path).map_err(|error| Error::file_io(&path, "create", error))?;
+        let mut writer = BufWriter::new(file);
+        for item in items {
+            let serialized_item = serialize(item)?;
+            writer
+                .write_all(&(serialized_item.len() as u64).to_be_bytes())
+                .and_then(|_| writer.write_all(&serialized_item))
+                .map_err(|error| Error::file_io(&path, "write", error))?;
+        }
+        writer
+            .flush()
+            .map_err(|error| Error::file_io(&path, "write", error))?;
+        Ok(Run {
+            path,
+            len: items.len(),
+            _marker: PhantomData,
+        })
+    }
+}
+
+struct RunReader<T> {
+    path: PathBuf,
+    reader: BufReader<File>,
+    offset: u64,
+    remaining: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> RunReader<T>
+where
+    T: DeserializeOwned,
+{
+    fn new(run: Run<T>) -> Result<Self> {
+        let file =
+            File::open(&run.path).map_err(|error| Error::file_io(&run.path, "open", error))?;
+        Ok(RunReader {
+            path: run.path,
+            reader: BufReader::new(file),
+            offset: 0,
+            remaining: run.len,
+            _marker: PhantomData,
+        })
+    }
+
+    fn read_item(&mut self) -> Result<Option<T>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        let offset = self.offset;
+        let map_read_error = |path: &Path, error: io::Error| match error.kind() {
+            ErrorKind::UnexpectedEof => Error::corruption(path, offset),
+            _ => Error::file_io(path, "read", error),
+        };
+        let mut size_buffer = [0; 8];
+        self.reader
+            .read_exact(&mut size_buffer)
+            .map_err(|error| map_read_error(&self.path, error))?;
+        let size = u64::from_be_bytes(size_buffer);
+        let mut buffer = vec![0; size as usize];
+        self.reader
+            .read_exact(&mut buffer)
+            .map_err(|error| map_read_error(&self.path, error))?;
+        let item = deserialize(&buffer).map_err(|_| Error::corruption(&self.path, offset))?;
+
+        self.offset += 8 + size;
+        self.remaining -= 1;
+        Ok(Some(item))
+    }
+}
+
+/// A sorter that sorts a stream of items that may not fit in memory.
+///
+/// Items are buffered in memory until `run_len` items are buffered, at which point the buffer is
+/// sorted and spilled to a file in a new folder in the folder at `path`. When the sorter is
+/// finished, the spilled runs and the remaining items in memory are lazily merged into a single
+/// sorted iterator. At most `run_len` items are held in memory while items are pushed, and at most
+/// `run_len` items and one item of each spilled run are held in memory while the sorted items are
+/// iterated. The sort is stable, so equal items are yielded in the order that they were pushed.
+/// The spilled runs are deleted when the sorter or its iterator is dropped.
+///
+/// # Examples
+///
+/// ```
+/// # use extended_collections::external_sort::Result;
+/// # fn foo() -> Result<()> {
+/// # use std::fs;
+/// use extended_collections::external_sort::ExternalSorter;
+///
+/// fs::create_dir("example_external_sorter")?;
+/// let mut sorter = ExternalSorter::new("example_external_sorter", 2);
+/// for item in vec![3, 1, 4, 1, 5] {
+///     sorter.push(item)?;
+/// }
+/// assert_eq!(sorter.run_count(), 2);
+///
+/// let sorted: Vec<u32> = sorter.finish()?.collect::<Result<_>>()?;
+/// assert_eq!(sorted, vec![1, 1, 3, 4, 5]);
+/// # fs::remove_dir_all("example_external_sorter")?;
+/// # Ok(())
+/// # }
+/// # foo().unwrap();
+/// ```
+pub struct ExternalSorter<T> {
+    folder: SpillFolder,
+    run_len: usize,
+    buffer: Vec<T>,
+    runs: Vec<Run<T>>,
+    len: usize,
+}
+
+impl<T> ExternalSorter<T>
+where
+    T: Ord + Serialize + DeserializeOwned,
+{
+    /// Constructs a new, empty `ExternalSorter<T>` that holds at most `run_len` items in memory
+    /// and spills sorted runs to a new folder in the existing folder at `path`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `run_len` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::external_sort::ExternalSorter;
+    ///
+    /// let sorter: ExternalSorter<u32> = ExternalSorter::new("example_external_sorter_new", 100);
+    /// ```
+    pub fn new<P>(path: P, run_len: usize) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        assert!(run_len > 0, "Error: run length must be positive.");
+        ExternalSorter {
+            folder: SpillFolder::new(path.as_ref()),
+            run_len,
+            buffer: Vec::new(),
+            runs: Vec::new(),
+            len: 0,
+        }
+    }
+
+    fn spill(&mut self) -> Result<()> {
+        self.buffer.sort();
+        let run_path = self.folder.create_run_path(self.runs.len())?;
+        self.runs.push(Run::write(run_path, &self.buffer)?);
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Pushes an item into the sorter. If `run_len` items are buffered in memory, they are sorted
+    /// and spilled to a file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::external_sort::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::external_sort::ExternalSorter;
+    ///
+    /// fs::create_dir("example_external_sorter_push")?;
+    /// let mut sorter = ExternalSorter::new("example_external_sorter_push", 2);
+    /// sorter.push(2)?;
+    /// assert_eq!(sorter.run_count(), 0);
+    /// sorter.push(1)?;
+    /// assert_eq!(sorter.run_count(), 1);
+    /// # fs::remove_dir_all("example_external_sorter_push")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn push(&mut self, item: T) -> Result<()> {
+        self.buffer.push(item);
+        self.len += 1;
+        if self.buffer.len() == self.run_len {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of items pushed into the sorter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::external_sort::Result;
+    /// # fn foo() -> Result<()> {
+    /// use extended_collections::external_sort::ExternalSorter;
+    ///
+    /// let mut sorter = ExternalSorter::new("example_external_sorter_len", 100);
+    /// sorter.push(1)?;
+    /// assert_eq!(sorter.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no items were pushed into the sorter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::external_sort::ExternalSorter;
+    ///
+    /// let sorter: ExternalSorter<u32> = ExternalSorter::new("example_external_sorter_empty", 100);
+    /// assert!(sorter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of runs that were spilled to files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::external_sort::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::external_sort::ExternalSorter;
+    ///
+    /// fs::create_dir("example_external_sorter_run_count")?;
+    /// let mut sorter = ExternalSorter::new("example_external_sorter_run_count", 1);
+    /// sorter.push(1)?;
+    /// sorter.push(2)?;
+    /// assert_eq!(sorter.run_count(), 2);
+    /// # fs::remove_dir_all("example_external_sorter_run_count")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn run_count(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Sorts the items in memory and returns an iterator that merges them with the spilled runs.
+    /// The iterator yields the items in ascending order, or an error if a spilled run could not be
+    /// read, after which it yields `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::external_sort::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::external_sort::ExternalSorter;
+    ///
+    /// fs::create_dir("example_external_sorter_finish")?;
+    /// let mut sorter = ExternalSorter::new("example_external_sorter_finish", 2);
+    /// sorter.push(3)?;
+    /// sorter.push(2)?;
+    /// sorter.push(1)?;
+    ///
+    /// let mut iterator = sorter.finish()?;
+    /// assert_eq!(iterator.next().transpose()?, Some(1));
+    /// assert_eq!(iterator.next().transpose()?, Some(2));
+    /// assert_eq!(iterator.next().transpose()?, Some(3));
+    /// assert!(iterator.next().is_none());
+    /// # fs::remove_dir_all("example_external_sorter_finish")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn finish(mut self) -> Result<ExternalSortIter<T>> {
+        self.buffer.sort();
+        let mut in_memory = mem::take(&mut self.buffer).into_iter();
+        let mut run_readers = Vec::with_capacity(self.runs.len());
+        let mut heap = BinaryHeap::with_capacity(self.runs.len() + 1);
+        for (index, run) in self.runs.drain(..).enumerate() {
+            let mut run_reader = RunReader::new(run)?;
+            if let Some(item) = run_reader.read_item()? {
+                heap.push(Reverse(HeapEntry { item, index }));
+            }
+            run_readers.push(run_reader);
+        }
+        // the items in memory were pushed last, so they are ordered after equal items of the runs
+        if let Some(item) = in_memory.next() {
+            heap.push(Reverse(HeapEntry {
+                item,
+                index: run_readers.len(),
+            }));
+        }
+
+        Ok(ExternalSortIter {
+            _folder: self.folder,
+            in_memory,
+            run_readers,
+            heap,
+            is_finished: false,
+        })
+    }
+}
+
+/// Sorts the items of an iterator that may not fit in memory using an `ExternalSorter` that holds
+/// at most `run_len` items in memory and spills sorted runs to a new folder in the existing folder
+/// at `path`.
+///
+/// # Panics
+///
+/// Panics if `run_len` is zero.
+///
+/// # Examples
+///
+/// ```
+/// # use extended_collections::external_sort::Result;
+/// # fn foo() -> Result<()> {
+/// # use std::fs;
+/// use extended_collections::external_sort::external_sort;
+///
+/// fs::create_dir("example_external_sort")?;
+/// let iterator = external_sort((0..100u32).rev(), "example_external_sort", 10)?;
+/// let sorted: Vec<u32> = iterator.collect::<Result<_>>()?;
+/// assert_eq!(sorted, (0..100).collect::<Vec<u32>>());
+/// # fs::remove_dir_all("example_external_sort")?;
+/// # Ok(())
+/// # }
+/// # foo().unwrap();
+/// ```
+pub fn external_sort<I, P>(iter: I, path: P, run_len: usize) -> Result<ExternalSortIter<I::Item>>
+where
+    I: IntoIterator,
+    I::Item: Ord + Serialize + DeserializeOwned,
+    P: AsRef<Path>,
+{
+    let mut sorter = ExternalSorter::new(path, run_len);
+    for item in iter {
+        sorter.push(item)?;
+    }
+    sorter.finish()
+}
+
+struct HeapEntry<T> {
+    item: T,
+    index: usize,
+}
+
+impl<T> Ord for HeapEntry<T>
+where
+    T: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.item
+            .cmp(&other.item)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+impl<T> PartialOrd for HeapEntry<T>
+where
+    T: Ord,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for HeapEntry<T>
+where
+    T: Ord,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for HeapEntry<T> where T: Ord {}
+
+/// An iterator for `ExternalSorter<T>`.
+///
+/// This iterator merges the spilled runs and the items in memory of the sorter and yields the
+/// items in ascending order. The spilled runs are deleted when the iterator is dropped.
+pub struct ExternalSortIter<T> {
+    // the spilled runs are deleted when the iterator is dropped.
+    _folder: SpillFolder,
+    in_memory: vec::IntoIter<T>,
+    run_readers: Vec<RunReader<T>>,
+    heap: BinaryHeap<Reverse<HeapEntry<T>>>,
+    is_finished: bool,
+}
+
+impl<T> Iterator for ExternalSortIter<T>
+where
+    T: Ord + DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+
+        let Reverse(HeapEntry { item, index }) = self.heap.pop()?;
+        let next_item = match self.run_readers.get_mut(index) {
+            Some(run_reader) => match run_reader.read_item() {
+                Ok(next_item) => next_item,
+                Err(error) => {
+                    self.is_finished = true;
+                    return Some(Err(error));
+                }
+            },
+            None => self.in_memory.next(),
+        };
+        if let Some(next_item) = next_item {
+            self.heap.push(Reverse(HeapEntry {
+                item: next_item,
+                index,
+            }));
+        }
+        Some(Ok(item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{external_sort, Error, ExternalSorter, Result};
+    use rand::{thread_rng, Rng};
+    use serde_derive::{Deserialize, Serialize};
+    use std::cmp::Ordering;
+    use std::fs;
+
+    struct TestFolder(&'static str);
+
+    impl TestFolder {
+        fn new(path: &'static str) -> Self {
+            let _ = fs::remove_dir_all(path);
+            fs::create_dir(path).unwrap();
+            TestFolder(path)
+        }
+
+        fn entry_count(&self) -> usize {
+            fs::read_dir(self.0).unwrap().count()
+        }
+    }
+
+    impl Drop for TestFolder {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(self.0);
+        }
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Record {
+        key: u32,
+        index: usize,
+    }
+
+    impl Ord for Record {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.key.cmp(&other.key)
+        }
+    }
+
+    impl PartialOrd for Record {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl PartialEq for Record {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+
+    impl Eq for Record {}
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_run_len() {
+        let _: ExternalSorter<u32> = ExternalSorter::new("test_external_sort_invalid_run_len", 0);
+    }
+
+    #[test]
+    fn test_empty() {
+        let folder = TestFolder::new("test_external_sort_empty");
+        let mut iterator = external_sort(Vec::<u32>::new(), folder.0, 10).unwrap();
+        assert!(iterator.next().is_none());
+        assert_eq!(folder.entry_count(), 0);
+    }
+
+    #[test]
+    fn test_in_memory() {
+        let folder = TestFolder::new("test_external_sort_in_memory");
+        let sorted: Vec<u32> = external_sort(vec![2, 3, 1], folder.0, 10)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(sorted, vec![1, 2, 3]);
+        assert_eq!(folder.entry_count(), 0);
+    }
+
+    #[test]
+    fn test_random() {
+        let folder = TestFolder::new("test_external_sort_random");
+        let mut rng = thread_rng();
+        let mut items: Vec<u32> = (0..10_000).map(|_| rng.gen_range(0, 1_000)).collect();
+
+        let mut sorter = ExternalSorter::new(folder.0, 128);
+        for item in &items {
+            sorter.push(*item).unwrap();
+        }
+        assert_eq!(sorter.len(), 10_000);
+        assert_eq!(sorter.run_count(), 10_000 / 128);
+
+        let iterator = sorter.finish().unwrap();
+        assert_eq!(folder.entry_count(), 1);
+        let sorted: Vec<u32> = iterator.collect::<Result<_>>().unwrap();
+        items.sort();
+        assert_eq!(sorted, items);
+        assert_eq!(folder.entry_count(), 0);
+    }
+
+    #[test]
+    fn test_stable() {
+        let folder = TestFolder::new("test_external_sort_stable");
+        let mut rng = thread_rng();
+        let records = (0..1_000).map(|index| Record {
+            key: rng.gen_range(0, 10),
+            index,
+        });
+
+        let sorted: Vec<Record> = external_sort(records, folder.0, 64)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(sorted.len(), 1_000);
+        for window in sorted.windows(2) {
+            assert!(
+                (window[0].key, window[0].index) < (window[1].key, window[1].index),
+                "{:?} is not before {:?}",
+                window[0],
+                window[1],
+            );
+        }
+    }
+
+    #[test]
+    fn test_drop_sorter() {
+        let folder = TestFolder::new("test_external_sort_drop_sorter");
+        let mut sorter = ExternalSorter::new(folder.0, 1);
+        sorter.push(1).unwrap();
+        assert_eq!(folder.entry_count(), 1);
+        drop(sorter);
+        assert_eq!(folder.entry_count(), 0);
+    }
+
+    #[test]
+    fn test_corrupted_run() {
+        let folder = TestFolder::new("test_external_sort_corrupted_run");
+        let mut sorter = ExternalSorter::new(folder.0, 2);
+        for item in 0..4u64 {
+            sorter.push(item).unwrap();
+        }
+        let spill_path = fs::read_dir(folder.0)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let run_path = spill_path.join("run-1.dat");
+        let bytes = fs::read(&run_path).unwrap();
+        fs::write(&run_path, &bytes[..bytes.len() - 1]).unwrap();
+
+        let mut iterator = sorter.finish().unwrap();
+        let mut items = Vec::new();
+        let error = loop {
+            match iterator.next().unwrap() {
+                Ok(item) => items.push(item),
+                Err(error) => break error,
+            }
+        };
+        assert!(iterator.next().is_none());
+        match error {
+            Error::Corruption { path, offset } => {
+                assert_eq!(path, run_path);
+                assert_eq!(offset, 16);
+            }
+            _ => panic!("Expected corruption error."),
+        }
+        assert_eq!(items, vec![0, 1]);
+    }
+}
@@ -0,0 +1,385 @@
+//! Data structure that efficiently updates elements and calculates prefix sums of a sequence.
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+use std::ops::{AddAssign, Range, Sub};
+
+fn lowest_bit(index: usize) -> usize {
+    index & index.wrapping_neg()
+}
+
+/// A sequence of values that supports updating elements and calculating prefix sums in
+/// logarithmic time.
+///
+/// A fenwick tree, also known as a binary indexed tree, is an implicit tree stored in an array.
+/// The node at index `i` (one-indexed) stores the sum of the `i & -i` elements ending at index
+/// `i`. Updating an element and calculating a prefix sum both visit at most a logarithmic number
+/// of nodes. The default value of `T` is treated as zero.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::fenwick_tree::FenwickTree;
+///
+/// let mut tree = FenwickTree::from(vec![1, 2, 3, 4]);
+/// assert_eq!(tree.prefix_sum(2), 3);
+/// assert_eq!(tree.range_sum(1..3), 5);
+///
+/// tree.add(0, 10);
+/// assert_eq!(tree.prefix_sum(2), 13);
+/// assert_eq!(tree.get(0), 11);
+///
+/// assert_eq!(tree.lower_bound(&14), 2);
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct FenwickTree<T> {
+    tree: Vec<T>,
+}
+
+impl<T> FenwickTree<T> {
+    /// Constructs a new `FenwickTree<T>` with `len` elements that are all zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::fenwick_tree::FenwickTree;
+    ///
+    /// let tree: FenwickTree<u32> = FenwickTree::new(10);
+    /// assert_eq!(tree.prefix_sum(10), 0);
+    /// ```
+    pub fn new(len: usize) -> Self
+    where
+        T: Clone + Default,
+    {
+        FenwickTree {
+            tree: vec![T::default(); len],
+        }
+    }
+
+    /// Adds `delta` to the element at a particular index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::fenwick_tree::FenwickTree;
+    ///
+    /// let mut tree = FenwickTree::new(4);
+    /// tree.add(1, 2);
+    /// tree.add(3, 4);
+    /// assert_eq!(tree.prefix_sum(2), 2);
+    /// assert_eq!(tree.prefix_sum(4), 6);
+    /// ```
+    pub fn add(&mut self, index: usize, delta: T)
+    where
+        T: AddAssign + Clone,
+    {
+        assert!(index < self.len());
+        let mut curr = index + 1;
+        while curr <= self.len() {
+            self.tree[curr - 1] += delta.clone();
+            curr += lowest_bit(curr);
+        }
+    }
+
+    /// Appends an element to the back of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::fenwick_tree::FenwickTree;
+    ///
+    /// let mut tree = FenwickTree::from(vec![1, 2, 3]);
+    /// tree.push(4);
+    /// assert_eq!(tree.len(), 4);
+    /// assert_eq!(tree.prefix_sum(4), 10);
+    /// ```
+    pub fn push(&mut self, value: T)
+    where
+        T: AddAssign + Clone,
+    {
+        let index = self.len() + 1;
+        let mut node = value;
+        let mut child = index - 1;
+        while child > index - lowest_bit(index) {
+            node += self.tree[child - 1].clone();
+            child -= lowest_bit(child);
+        }
+        self.tree.push(node);
+    }
+
+    /// Removes the last element of the tree and returns it, or `None` if the tree is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::fenwick_tree::FenwickTree;
+    ///
+    /// let mut tree = FenwickTree::from(vec![1, 2, 3]);
+    /// assert_eq!(tree.pop(), Some(3));
+    /// assert_eq!(tree.prefix_sum(2), 3);
+    /// ```
+    pub fn pop(&mut self) -> Option<T>
+    where
+        T: AddAssign + Clone + Default + Sub<Output = T>,
+    {
+        if self.is_empty() {
+            return None;
+        }
+        let value = self.get(self.len() - 1);
+        self.tree.pop();
+        Some(value)
+    }
+
+    /// Returns the sum of the first `len` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is greater than the number of elements in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::fenwick_tree::FenwickTree;
+    ///
+    /// let tree = FenwickTree::from(vec![1, 2, 3]);
+    /// assert_eq!(tree.prefix_sum(0), 0);
+    /// assert_eq!(tree.prefix_sum(2), 3);
+    /// assert_eq!(tree.prefix_sum(3), 6);
+    /// ```
+    pub fn prefix_sum(&self, len: usize) -> T
+    where
+        T: AddAssign + Clone + Default,
+    {
+        assert!(len <= self.len());
+        let mut ret = T::default();
+        let mut curr = len;
+        while curr > 0 {
+            ret += self.tree[curr - 1].clone();
+            curr -= lowest_bit(curr);
+        }
+        ret
+    }
+
+    /// Returns the sum of the elements in a particular range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or if the start of the range is greater than its end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::fenwick_tree::FenwickTree;
+    ///
+    /// let tree = FenwickTree::from(vec![1, 2, 3, 4]);
+    /// assert_eq!(tree.range_sum(1..3), 5);
+    /// assert_eq!(tree.range_sum(2..2), 0);
+    /// ```
+    pub fn range_sum(&self, range: Range<usize>) -> T
+    where
+        T: AddAssign + Clone + Default + Sub<Output = T>,
+    {
+        assert!(range.start <= range.end);
+        self.prefix_sum(range.end) - self.prefix_sum(range.start)
+    }
+
+    /// Returns the element at a particular index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::fenwick_tree::FenwickTree;
+    ///
+    /// let tree = FenwickTree::from(vec![1, 2, 3, 4]);
+    /// assert_eq!(tree.get(2), 3);
+    /// ```
+    pub fn get(&self, index: usize) -> T
+    where
+        T: AddAssign + Clone + Default + Sub<Output = T>,
+    {
+        self.range_sum(index..index + 1)
+    }
+
+    /// Returns the smallest index such that the sum of the elements up to and including that
+    /// index is greater than or equal to `value`. Returns the number of elements in the tree if no
+    /// such index exists. All elements of the tree are expected to be non-negative.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::fenwick_tree::FenwickTree;
+    ///
+    /// let tree = FenwickTree::from(vec![1, 0, 2, 3]);
+    /// assert_eq!(tree.lower_bound(&0), 0);
+    /// assert_eq!(tree.lower_bound(&2), 2);
+    /// assert_eq!(tree.lower_bound(&3), 2);
+    /// assert_eq!(tree.lower_bound(&4), 3);
+    /// assert_eq!(tree.lower_bound(&7), 4);
+    /// ```
+    pub fn lower_bound(&self, value: &T) -> usize
+    where
+        T: AddAssign + Clone + Default + PartialOrd,
+    {
+        let mut index = 0;
+        let mut sum = T::default();
+        let mut step = if self.is_empty() {
+            0
+        } else {
+            1 << (63 - (self.len() as u64).leading_zeros())
+        };
+
+        while step > 0 {
+            if index + step <= self.len() {
+                let mut next_sum = sum.clone();
+                next_sum += self.tree[index + step - 1].clone();
+                if next_sum < *value {
+                    index += step;
+                    sum = next_sum;
+                }
+            }
+            step >>= 1;
+        }
+
+        index
+    }
+
+    /// Returns the number of elements in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::fenwick_tree::FenwickTree;
+    ///
+    /// let tree: FenwickTree<u32> = FenwickTree::new(10);
+    /// assert_eq!(tree.len(), 10);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns `true` if the tree is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::fenwick_tree::FenwickTree;
+    ///
+    /// let tree: FenwickTree<u32> = FenwickTree::new(0);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> From<Vec<T>> for FenwickTree<T>
+where
+    T: AddAssign + Clone,
+{
+    fn from(mut values: Vec<T>) -> Self {
+        let len = values.len();
+        for index in 1..=len {
+            let parent = index + lowest_bit(index);
+            if parent <= len {
+                let value = values[index - 1].clone();
+                values[parent - 1] += value;
+            }
+        }
+        FenwickTree { tree: values }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FenwickTree;
+
+    #[test]
+    fn test_len_empty() {
+        let tree: FenwickTree<u32> = FenwickTree::new(0);
+        assert_eq!(tree.len(), 0);
+        assert_eq!(tree.prefix_sum(0), 0);
+        assert_eq!(tree.lower_bound(&1), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_add_out_of_bounds() {
+        let mut tree: FenwickTree<u32> = FenwickTree::new(4);
+        tree.add(4, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_prefix_sum_out_of_bounds() {
+        let tree: FenwickTree<u32> = FenwickTree::new(4);
+        tree.prefix_sum(5);
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let values: Vec<i64> = (0..100).map(|i| (i * 37) % 11 - 5).collect();
+        let tree = FenwickTree::from(values.clone());
+        for len in 0..=values.len() {
+            assert_eq!(tree.prefix_sum(len), values[..len].iter().sum());
+        }
+    }
+
+    #[test]
+    fn test_add() {
+        let mut values = vec![0i64; 100];
+        let mut tree = FenwickTree::new(100);
+        for i in 0..100 {
+            let index = (i * 13) % 100;
+            let delta = (i as i64 * 7) % 19 - 9;
+            values[index] += delta;
+            tree.add(index, delta);
+        }
+        for start in 0..values.len() {
+            for end in start..=values.len() {
+                assert_eq!(tree.range_sum(start..end), values[start..end].iter().sum());
+            }
+            assert_eq!(tree.get(start), values[start]);
+        }
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let values: Vec<i64> = (0..100).map(|i| (i * 37) % 11 - 5).collect();
+        let mut tree = FenwickTree::new(0);
+        for (index, value) in values.iter().enumerate() {
+            tree.push(*value);
+            assert_eq!(tree.prefix_sum(index + 1), values[..=index].iter().sum());
+        }
+        for index in (0..values.len()).rev() {
+            assert_eq!(tree.pop(), Some(values[index]));
+            assert_eq!(tree.prefix_sum(index), values[..index].iter().sum());
+        }
+        assert_eq!(tree.pop(), None);
+    }
+
+    #[test]
+    fn test_lower_bound() {
+        let values: Vec<u64> = (0..100).map(|i| (i * 7) % 5).collect();
+        let tree = FenwickTree::from(values.clone());
+        for value in 0..250 {
+            let mut sum = 0;
+            let expected = values
+                .iter()
+                .position(|element| {
+                    sum += element;
+                    sum >= value
+                })
+                .unwrap_or(values.len());
+            assert_eq!(tree.lower_bound(&value), expected);
+        }
+    }
+}
@@ -0,0 +1,875 @@
+//! Graphs represented as adjacency lists.
+
+use crate::arena::{Entry, TypedArena, TypedArenaIter};
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::{Index, IndexMut};
+use core::slice;
+
+const DEFAULT_CHUNK_SIZE: usize = 1024;
+
+/// A struct representing a node of `AdjacencyList<N, E>`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NodeId(Entry);
+
+impl NodeId {
+    /// Returns the index of the node, which is less than the number of nodes that the graph can
+    /// hold without allocating, so it can be used to index into dense tables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph: AdjacencyList<&str, ()> = AdjacencyList::new();
+    /// graph.add_node("a");
+    /// assert_eq!(graph.add_node("b").index(), 1);
+    /// ```
+    pub fn index(self) -> usize {
+        usize::from(self.0)
+    }
+}
+
+/// A struct representing an edge of `AdjacencyList<N, E>`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct EdgeId(Entry);
+
+impl EdgeId {
+    /// Returns the index of the edge, which is less than the number of edges that the graph can
+    /// hold without allocating, so it can be used to index into dense tables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// assert_eq!(graph.add_edge(a, a, 1).index(), 0);
+    /// ```
+    pub fn index(self) -> usize {
+        usize::from(self.0)
+    }
+}
+
+#[derive(Clone)]
+struct Node<N> {
+    value: N,
+    outgoing: Vec<EdgeId>,
+    incoming: Vec<EdgeId>,
+}
+
+#[derive(Clone)]
+struct Edge<E> {
+    value: E,
+    source: NodeId,
+    target: NodeId,
+}
+
+fn remove_edge_id(edges: &mut Vec<EdgeId>, edge: EdgeId) {
+    let index = edges
+        .iter()
+        .position(|other| *other == edge)
+        .expect("Expected edge in adjacency list.");
+    edges.remove(index);
+}
+
+/// A directed graph that stores the values of its nodes and edges in typed arenas.
+///
+/// Each node keeps the lists of its outgoing and incoming edges, so adding a node or an edge takes
+/// constant amortized time, and removing an edge takes time proportional to the degrees of its
+/// endpoints. Removing a node also removes all of the edges incident to it. Nodes and edges are
+/// identified by a `NodeId` and an `EdgeId` that remain valid until they are removed, after which
+/// they may be reused by new nodes and edges. Parallel edges and self-loops are allowed, and an
+/// undirected graph can be represented by adding an edge in each direction.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::graph::AdjacencyList;
+///
+/// let mut graph = AdjacencyList::new();
+/// let a = graph.add_node("a");
+/// let b = graph.add_node("b");
+/// let c = graph.add_node("c");
+/// graph.add_edge(a, b, 1);
+/// graph.add_edge(b, c, 2);
+///
+/// assert_eq!(graph.neighbors(a).collect::<Vec<_>>(), vec![b]);
+/// assert_eq!(graph.bfs(a).map(|node| graph[node]).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+///
+/// assert_eq!(graph.remove_node(b), Some("b"));
+/// assert_eq!(graph.edge_count(), 0);
+/// ```
+#[derive(Clone)]
+pub struct AdjacencyList<N, E> {
+    nodes: TypedArena<Node<N>>,
+    edges: TypedArena<Edge<E>>,
+}
+
+impl<N, E> AdjacencyList<N, E> {
+    /// Constructs a new, empty `AdjacencyList<N, E>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let graph: AdjacencyList<&str, u32> = AdjacencyList::new();
+    /// assert!(graph.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        AdjacencyList {
+            nodes: TypedArena::new(DEFAULT_CHUNK_SIZE),
+            edges: TypedArena::new(DEFAULT_CHUNK_SIZE),
+        }
+    }
+
+    /// Adds a node to the graph and returns its id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph: AdjacencyList<&str, u32> = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// assert_eq!(graph.node(a), Some(&"a"));
+    /// ```
+    pub fn add_node(&mut self, value: N) -> NodeId {
+        NodeId(self.nodes.insert(Node {
+            value,
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+        }))
+    }
+
+    /// Removes a node and all of the edges incident to it from the graph. If the node exists in
+    /// the graph, its value is returned. Otherwise it will return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// let b = graph.add_node("b");
+    /// graph.add_edge(a, b, 1);
+    ///
+    /// assert_eq!(graph.remove_node(a), Some("a"));
+    /// assert_eq!(graph.remove_node(a), None);
+    /// assert_eq!(graph.edge_count(), 0);
+    /// ```
+    pub fn remove_node(&mut self, node: NodeId) -> Option<N> {
+        let Node {
+            value,
+            outgoing,
+            incoming,
+        } = self.nodes.remove(&node.0)?;
+        for edge_id in outgoing {
+            if let Some(edge) = self.edges.remove(&edge_id.0) {
+                // the target of a self-loop is the removed node
+                if let Some(target) = self.nodes.get_mut(&edge.target.0) {
+                    remove_edge_id(&mut target.incoming, edge_id);
+                }
+            }
+        }
+        for edge_id in incoming {
+            // self-loops were already removed with the outgoing edges
+            if let Some(edge) = self.edges.remove(&edge_id.0) {
+                let source = &mut self.nodes[edge.source.0];
+                remove_edge_id(&mut source.outgoing, edge_id);
+            }
+        }
+        Some(value)
+    }
+
+    /// Adds an edge from `source` to `target` to the graph and returns its id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either `source` or `target` does not exist in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// let b = graph.add_node("b");
+    /// let edge = graph.add_edge(a, b, 1);
+    /// assert_eq!(graph.edge_endpoints(edge), Some((a, b)));
+    /// ```
+    pub fn add_edge(&mut self, source: NodeId, target: NodeId, value: E) -> EdgeId {
+        assert!(
+            self.contains_node(source) && self.contains_node(target),
+            "Error: attempting to add edge between nonexistent nodes."
+        );
+        let edge = EdgeId(self.edges.insert(Edge {
+            value,
+            source,
+            target,
+        }));
+        self.nodes[source.0].outgoing.push(edge);
+        self.nodes[target.0].incoming.push(edge);
+        edge
+    }
+
+    /// Removes an edge from the graph. If the edge exists in the graph, its value is returned.
+    /// Otherwise it will return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// let b = graph.add_node("b");
+    /// let edge = graph.add_edge(a, b, 1);
+    ///
+    /// assert_eq!(graph.remove_edge(edge), Some(1));
+    /// assert_eq!(graph.remove_edge(edge), None);
+    /// assert_eq!(graph.neighbors(a).count(), 0);
+    /// ```
+    pub fn remove_edge(&mut self, edge: EdgeId) -> Option<E> {
+        let Edge {
+            value,
+            source,
+            target,
+        } = self.edges.remove(&edge.0)?;
+        remove_edge_id(&mut self.nodes[source.0].outgoing, edge);
+        remove_edge_id(&mut self.nodes[target.0].incoming, edge);
+        Some(value)
+    }
+
+    /// Returns `true` if the node exists in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph: AdjacencyList<&str, u32> = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// assert!(graph.contains_node(a));
+    ///
+    /// graph.remove_node(a);
+    /// assert!(!graph.contains_node(a));
+    /// ```
+    pub fn contains_node(&self, node: NodeId) -> bool {
+        self.nodes.contains(&node.0)
+    }
+
+    /// Returns `true` if the edge exists in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// let edge = graph.add_edge(a, a, 1);
+    /// assert!(graph.contains_edge(edge));
+    ///
+    /// graph.remove_edge(edge);
+    /// assert!(!graph.contains_edge(edge));
+    /// ```
+    pub fn contains_edge(&self, edge: EdgeId) -> bool {
+        self.edges.contains(&edge.0)
+    }
+
+    /// Returns an immutable reference to the value of a node. Returns `None` if the node does not
+    /// exist in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph: AdjacencyList<&str, u32> = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// assert_eq!(graph.node(a), Some(&"a"));
+    /// ```
+    pub fn node(&self, node: NodeId) -> Option<&N> {
+        self.nodes.get(&node.0).map(|node| &node.value)
+    }
+
+    /// Returns a mutable reference to the value of a node. Returns `None` if the node does not
+    /// exist in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph: AdjacencyList<u32, u32> = AdjacencyList::new();
+    /// let a = graph.add_node(1);
+    /// *graph.node_mut(a).unwrap() += 1;
+    /// assert_eq!(graph.node(a), Some(&2));
+    /// ```
+    pub fn node_mut(&mut self, node: NodeId) -> Option<&mut N> {
+        self.nodes.get_mut(&node.0).map(|node| &mut node.value)
+    }
+
+    /// Returns an immutable reference to the value of an edge. Returns `None` if the edge does not
+    /// exist in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// let edge = graph.add_edge(a, a, 1);
+    /// assert_eq!(graph.edge(edge), Some(&1));
+    /// ```
+    pub fn edge(&self, edge: EdgeId) -> Option<&E> {
+        self.edges.get(&edge.0).map(|edge| &edge.value)
+    }
+
+    /// Returns a mutable reference to the value of an edge. Returns `None` if the edge does not
+    /// exist in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// let edge = graph.add_edge(a, a, 1);
+    /// *graph.edge_mut(edge).unwrap() += 1;
+    /// assert_eq!(graph.edge(edge), Some(&2));
+    /// ```
+    pub fn edge_mut(&mut self, edge: EdgeId) -> Option<&mut E> {
+        self.edges.get_mut(&edge.0).map(|edge| &mut edge.value)
+    }
+
+    /// Returns the source and target of an edge. Returns `None` if the edge does not exist in the
+    /// graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// let b = graph.add_node("b");
+    /// let edge = graph.add_edge(a, b, 1);
+    /// assert_eq!(graph.edge_endpoints(edge), Some((a, b)));
+    /// ```
+    pub fn edge_endpoints(&self, edge: EdgeId) -> Option<(NodeId, NodeId)> {
+        self.edges
+            .get(&edge.0)
+            .map(|edge| (edge.source, edge.target))
+    }
+
+    /// Returns the number of nodes in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph: AdjacencyList<&str, u32> = AdjacencyList::new();
+    /// graph.add_node("a");
+    /// assert_eq!(graph.node_count(), 1);
+    /// ```
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the number of edges in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// graph.add_edge(a, a, 1);
+    /// assert_eq!(graph.edge_count(), 1);
+    /// ```
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Returns `true` if the graph contains no nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph: AdjacencyList<&str, u32> = AdjacencyList::new();
+    /// assert!(graph.is_empty());
+    ///
+    /// graph.add_node("a");
+    /// assert!(!graph.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns an iterator over the nodes of the graph. The iterator will yield the id of each node
+    /// and an immutable reference to its value in ascending order of ids.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph: AdjacencyList<&str, u32> = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// let b = graph.add_node("b");
+    ///
+    /// let mut iterator = graph.iter();
+    /// assert_eq!(iterator.next(), Some((a, &"a")));
+    /// assert_eq!(iterator.next(), Some((b, &"b")));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> AdjacencyListIter<'_, N> {
+        AdjacencyListIter {
+            inner: self.nodes.iter(),
+        }
+    }
+
+    fn outgoing(&self, node: NodeId) -> slice::Iter<'_, EdgeId> {
+        self.nodes
+            .get(&node.0)
+            .expect("Error: node does not exist.")
+            .outgoing
+            .iter()
+    }
+
+    /// Returns an iterator over the targets of the outgoing edges of a node in the order that the
+    /// edges were added. A target is yielded once for each edge to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node does not exist in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// let b = graph.add_node("b");
+    /// let c = graph.add_node("c");
+    /// graph.add_edge(a, c, 1);
+    /// graph.add_edge(a, b, 2);
+    ///
+    /// let mut iterator = graph.neighbors(a);
+    /// assert_eq!(iterator.next(), Some(c));
+    /// assert_eq!(iterator.next(), Some(b));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn neighbors(&self, node: NodeId) -> AdjacencyListNeighborsIter<'_, N, E> {
+        AdjacencyListNeighborsIter {
+            graph: self,
+            inner: self.outgoing(node),
+        }
+    }
+
+    /// Returns an iterator over the outgoing edges of a node in the order that they were added.
+    /// The iterator will yield the id of each edge and an immutable reference to its value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node does not exist in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// let b = graph.add_node("b");
+    /// let x = graph.add_edge(a, b, 1);
+    /// let y = graph.add_edge(a, a, 2);
+    ///
+    /// let mut iterator = graph.edges(a);
+    /// assert_eq!(iterator.next(), Some((x, &1)));
+    /// assert_eq!(iterator.next(), Some((y, &2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn edges(&self, node: NodeId) -> AdjacencyListEdgesIter<'_, N, E> {
+        AdjacencyListEdgesIter {
+            graph: self,
+            inner: self.outgoing(node),
+        }
+    }
+
+    /// Returns an iterator over the nodes that are reachable from `start` in breadth-first order.
+    /// Each reachable node is yielded once, starting with `start`, and the outgoing edges of each
+    /// node are followed in the order that they were added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` does not exist in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// let b = graph.add_node("b");
+    /// let c = graph.add_node("c");
+    /// let d = graph.add_node("d");
+    /// graph.add_edge(a, b, ());
+    /// graph.add_edge(b, d, ());
+    /// graph.add_edge(a, c, ());
+    ///
+    /// assert_eq!(graph.bfs(a).collect::<Vec<_>>(), vec![a, b, c, d]);
+    /// ```
+    pub fn bfs(&self, start: NodeId) -> AdjacencyListBfsIter<'_, N, E> {
+        assert!(self.contains_node(start), "Error: node does not exist.");
+        let mut visited = vec![false; self.nodes.capacity()];
+        visited[start.index()] = true;
+        let mut queue = VecDeque::new();
+        queue.push_back(start);
+        AdjacencyListBfsIter {
+            graph: self,
+            queue,
+            visited,
+        }
+    }
+
+    /// Returns an iterator over the nodes that are reachable from `start` in depth-first preorder.
+    /// Each reachable node is yielded once, starting with `start`, and the outgoing edges of each
+    /// node are followed in the order that they were added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` does not exist in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::graph::AdjacencyList;
+    ///
+    /// let mut graph = AdjacencyList::new();
+    /// let a = graph.add_node("a");
+    /// let b = graph.add_node("b");
+    /// let c = graph.add_node("c");
+    /// let d = graph.add_node("d");
+    /// graph.add_edge(a, b, ());
+    /// graph.add_edge(b, d, ());
+    /// graph.add_edge(a, c, ());
+    ///
+    /// assert_eq!(graph.dfs(a).collect::<Vec<_>>(), vec![a, b, d, c]);
+    /// ```
+    pub fn dfs(&self, start: NodeId) -> AdjacencyListDfsIter<'_, N, E> {
+        assert!(self.contains_node(start), "Error: node does not exist.");
+        AdjacencyListDfsIter {
+            graph: self,
+            stack: vec![start],
+            visited: vec![false; self.nodes.capacity()],
+        }
+    }
+}
+
+impl<N, E> Default for AdjacencyList<N, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, E> Index<NodeId> for AdjacencyList<N, E> {
+    type Output = N;
+
+    fn index(&self, node: NodeId) -> &Self::Output {
+        self.node(node).expect("Error: node does not exist.")
+    }
+}
+
+impl<N, E> IndexMut<NodeId> for AdjacencyList<N, E> {
+    fn index_mut(&mut self, node: NodeId) -> &mut Self::Output {
+        self.node_mut(node).expect("Error: node does not exist.")
+    }
+}
+
+impl<N, E> Index<EdgeId> for AdjacencyList<N, E> {
+    type Output = E;
+
+    fn index(&self, edge: EdgeId) -> &Self::Output {
+        self.edge(edge).expect("Error: edge does not exist.")
+    }
+}
+
+impl<N, E> IndexMut<EdgeId> for AdjacencyList<N, E> {
+    fn index_mut(&mut self, edge: EdgeId) -> &mut Self::Output {
+        self.edge_mut(edge).expect("Error: edge does not exist.")
+    }
+}
+
+impl<'a, N, E> IntoIterator for &'a AdjacencyList<N, E>
+where
+    N: 'a,
+    E: 'a,
+{
+    type IntoIter = AdjacencyListIter<'a, N>;
+    type Item = (NodeId, &'a N);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator for `AdjacencyList<N, E>`.
+///
+/// This iterator yields the nodes of the graph in ascending order of ids.
+pub struct AdjacencyListIter<'a, N> {
+    inner: TypedArenaIter<'a, Node<N>>,
+}
+
+impl<'a, N> Iterator for AdjacencyListIter<'a, N>
+where
+    N: 'a,
+{
+    type Item = (NodeId, &'a N);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|(entry, node)| (NodeId(entry), &node.value))
+    }
+}
+
+/// An iterator over the neighbors of a node in `AdjacencyList<N, E>`.
+///
+/// This iterator yields the targets of the outgoing edges of the node in the order that the edges
+/// were added.
+pub struct AdjacencyListNeighborsIter<'a, N, E> {
+    graph: &'a AdjacencyList<N, E>,
+    inner: slice::Iter<'a, EdgeId>,
+}
+
+impl<'a, N, E> Iterator for AdjacencyListNeighborsIter<'a, N, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let edge = self.inner.next()?;
+        Some(self.graph.edges[edge.0].target)
+    }
+}
+
+/// An iterator over the outgoing edges of a node in `AdjacencyList<N, E>`.
+///
+/// This iterator yields the outgoing edges of the node in the order that they were added.
+pub struct AdjacencyListEdgesIter<'a, N, E> {
+    graph: &'a AdjacencyList<N, E>,
+    inner: slice::Iter<'a, EdgeId>,
+}
+
+impl<'a, N, E> Iterator for AdjacencyListEdgesIter<'a, N, E> {
+    type Item = (EdgeId, &'a E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let edge = *self.inner.next()?;
+        Some((edge, &self.graph.edges[edge.0].value))
+    }
+}
+
+/// A breadth-first iterator for `AdjacencyList<N, E>`.
+///
+/// This iterator yields the nodes that are reachable from a starting node in breadth-first order.
+pub struct AdjacencyListBfsIter<'a, N, E> {
+    graph: &'a AdjacencyList<N, E>,
+    queue: VecDeque<NodeId>,
+    visited: Vec<bool>,
+}
+
+impl<'a, N, E> Iterator for AdjacencyListBfsIter<'a, N, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        for neighbor in self.graph.neighbors(node) {
+            if !self.visited[neighbor.index()] {
+                self.visited[neighbor.index()] = true;
+                self.queue.push_back(neighbor);
+            }
+        }
+        Some(node)
+    }
+}
+
+/// A depth-first iterator for `AdjacencyList<N, E>`.
+///
+/// This iterator yields the nodes that are reachable from a starting node in depth-first
+/// preorder.
+pub struct AdjacencyListDfsIter<'a, N, E> {
+    graph: &'a AdjacencyList<N, E>,
+    stack: Vec<NodeId>,
+    visited: Vec<bool>,
+}
+
+impl<'a, N, E> Iterator for AdjacencyListDfsIter<'a, N, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = self.stack.pop()?;
+            if self.visited[node.index()] {
+                continue;
+            }
+            self.visited[node.index()] = true;
+            // neighbors are pushed in reverse so that the first edge of the node is followed first
+            for edge in self.graph.outgoing(node).rev() {
+                let neighbor = self.graph.edges[edge.0].target;
+                if !self.visited[neighbor.index()] {
+                    self.stack.push(neighbor);
+                }
+            }
+            return Some(node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AdjacencyList, NodeId};
+
+    fn complete_graph(node_count: usize) -> (AdjacencyList<usize, (usize, usize)>, Vec<NodeId>) {
+        let mut graph = AdjacencyList::new();
+        let nodes: Vec<NodeId> = (0..node_count).map(|value| graph.add_node(value)).collect();
+        for i in 0..node_count {
+            for j in 0..node_count {
+                if i != j {
+                    graph.add_edge(nodes[i], nodes[j], (i, j));
+                }
+            }
+        }
+        (graph, nodes)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_add_edge_nonexistent_node() {
+        let mut graph = AdjacencyList::new();
+        let a = graph.add_node(0);
+        let b = graph.add_node(1);
+        graph.remove_node(b);
+        graph.add_edge(a, b, ());
+    }
+
+    #[test]
+    fn test_remove_edge() {
+        let (mut graph, nodes) = complete_graph(4);
+        let edges: Vec<_> = graph.edges(nodes[1]).map(|(edge, _)| edge).collect();
+        assert_eq!(graph.remove_edge(edges[1]), Some((1, 2)));
+        assert_eq!(graph.remove_edge(edges[1]), None);
+        assert_eq!(graph.edge_count(), 11);
+        assert_eq!(
+            graph.neighbors(nodes[1]).collect::<Vec<_>>(),
+            vec![nodes[0], nodes[3]],
+        );
+        assert_eq!(
+            graph
+                .iter()
+                .filter(|(node, _)| graph.neighbors(*node).any(|other| other == nodes[2]))
+                .count(),
+            2,
+        );
+    }
+
+    #[test]
+    fn test_remove_node() {
+        let (mut graph, nodes) = complete_graph(4);
+        let self_loop = graph.add_edge(nodes[2], nodes[2], (2, 2));
+        assert_eq!(graph.remove_node(nodes[2]), Some(2));
+        assert_eq!(graph.remove_node(nodes[2]), None);
+        assert!(!graph.contains_edge(self_loop));
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 6);
+        for node in &[nodes[0], nodes[1], nodes[3]] {
+            assert_eq!(graph.neighbors(*node).count(), 2);
+            assert!(graph.neighbors(*node).all(|other| other != nodes[2]));
+            assert!(graph.edges(*node).all(|(_, (_, target))| *target != 2));
+        }
+
+        let node = graph.add_node(4);
+        assert_eq!(node, nodes[2]);
+        assert_eq!(graph.neighbors(node).count(), 0);
+        assert_eq!(graph.bfs(nodes[0]).count(), 3);
+    }
+
+    #[test]
+    fn test_parallel_edges() {
+        let mut graph = AdjacencyList::new();
+        let a = graph.add_node(0);
+        let b = graph.add_node(1);
+        let x = graph.add_edge(a, b, 0);
+        let y = graph.add_edge(a, b, 1);
+        assert_eq!(graph.neighbors(a).collect::<Vec<_>>(), vec![b, b]);
+
+        graph.remove_edge(x);
+        assert_eq!(graph.edges(a).collect::<Vec<_>>(), vec![(y, &1)]);
+        assert_eq!(graph.bfs(a).collect::<Vec<_>>(), vec![a, b]);
+    }
+
+    #[test]
+    fn test_bfs() {
+        // a grid where each node has an edge to its right and bottom neighbors
+        let mut graph = AdjacencyList::new();
+        let nodes: Vec<NodeId> = (0..9).map(|value| graph.add_node(value)).collect();
+        for index in 0..9 {
+            if index % 3 != 2 {
+                graph.add_edge(nodes[index], nodes[index + 1], ());
+            }
+            if index < 6 {
+                graph.add_edge(nodes[index], nodes[index + 3], ());
+            }
+        }
+        let order: Vec<usize> = graph.bfs(nodes[0]).map(|node| graph[node]).collect();
+        assert_eq!(order, vec![0, 1, 3, 2, 4, 6, 5, 7, 8]);
+
+        let order: Vec<usize> = graph.bfs(nodes[4]).map(|node| graph[node]).collect();
+        assert_eq!(order, vec![4, 5, 7, 8]);
+    }
+
+    #[test]
+    fn test_dfs() {
+        let mut graph = AdjacencyList::new();
+        let nodes: Vec<NodeId> = (0..6).map(|value| graph.add_node(value)).collect();
+        graph.add_edge(nodes[0], nodes[1], ());
+        graph.add_edge(nodes[0], nodes[2], ());
+        graph.add_edge(nodes[1], nodes[3], ());
+        graph.add_edge(nodes[3], nodes[0], ());
+        graph.add_edge(nodes[3], nodes[2], ());
+        graph.add_edge(nodes[2], nodes[4], ());
+        graph.add_edge(nodes[5], nodes[0], ());
+
+        let order: Vec<usize> = graph.dfs(nodes[0]).map(|node| graph[node]).collect();
+        assert_eq!(order, vec![0, 1, 3, 2, 4]);
+    }
+
+    #[test]
+    fn test_traversal_after_growth() {
+        let mut graph = AdjacencyList::new();
+        let mut prev = graph.add_node(0);
+        let start = prev;
+        for value in 1..3000 {
+            let node = graph.add_node(value);
+            graph.add_edge(prev, node, ());
+            prev = node;
+        }
+        assert_eq!(graph.bfs(start).count(), 3000);
+        assert_eq!(graph.dfs(start).count(), 3000);
+        assert!(graph.dfs(start).map(|node| graph[node]).eq(0..3000));
+    }
+}
@@ -0,0 +1,8 @@
+//! Hash maps that store their entries inline in a single table using open addressing.
+
+mod robin_hood_map;
+
+pub use self::robin_hood_map::{
+    Entry, OccupiedEntry, RobinHoodMap, RobinHoodMapIntoIter, RobinHoodMapIter,
+    RobinHoodMapIterMut, VacantEntry,
+};
@@ -0,0 +1,1294 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{Index, IndexMut};
+use std::slice;
+use std::vec;
+
+const DEFAULT_MAX_LOAD_FACTOR: f64 = 0.9;
+const MIN_BUCKET_COUNT: usize = 8;
+
+#[derive(Clone)]
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+/// A hash map implemented using open addressing with Robin Hood probing.
+///
+/// The entries of the map are stored inline in a single table whose size is a power of two. An
+/// entry is stored in the first bucket after its ideal bucket where it is further from its ideal
+/// bucket than the entry already stored there, in which case the two entries are swapped and the
+/// displaced entry continues probing. This bounds the variance of the probe lengths, and a lookup
+/// can stop as soon as it reaches an entry that is closer to its ideal bucket than the key would
+/// be. Removing an entry shifts the following entries of its probe sequence back by one bucket
+/// instead of leaving a tombstone. The table grows once the ratio of entries to buckets exceeds
+/// the maximum load factor of the map, which defaults to 0.9.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::hash_map::RobinHoodMap;
+///
+/// let mut map = RobinHoodMap::new();
+/// map.insert(0, 1);
+/// map.insert(3, 4);
+///
+/// assert_eq!(map[&0], 1);
+/// assert_eq!(map.get(&1), None);
+/// assert_eq!(map.len(), 2);
+///
+/// *map.entry(3).or_insert(0) += 1;
+/// assert_eq!(map[&3], 5);
+///
+/// map[&0] = 2;
+/// assert_eq!(map.remove(&0), Some((0, 2)));
+/// assert_eq!(map.remove(&1), None);
+/// ```
+#[derive(Clone)]
+pub struct RobinHoodMap<K, V, S = RandomState> {
+    buckets: Vec<Option<Bucket<K, V>>>,
+    len: usize,
+    max_load_factor: f64,
+    hash_builder: S,
+}
+
+impl<K, V> RobinHoodMap<K, V>
+where
+    K: Eq + Hash,
+{
+    /// Constructs a new, empty `RobinHoodMap<K, V>`. The map does not allocate until an entry is
+    /// inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let map: RobinHoodMap<u32, u32> = RobinHoodMap::new();
+    /// assert_eq!(map.capacity(), 0);
+    /// ```
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    /// Constructs a new, empty `RobinHoodMap<K, V>` that can hold at least `capacity` entries
+    /// without growing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let map: RobinHoodMap<u32, u32> = RobinHoodMap::with_capacity(100);
+    /// assert!(map.capacity() >= 100);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S> RobinHoodMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Constructs a new, empty `RobinHoodMap<K, V, S>` that uses `hash_builder` to hash keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let map: RobinHoodMap<u32, u32> = RobinHoodMap::with_hasher(RandomState::new());
+    /// ```
+    pub fn with_hasher(hash_builder: S) -> Self {
+        RobinHoodMap {
+            buckets: Vec::new(),
+            len: 0,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            hash_builder,
+        }
+    }
+
+    /// Constructs a new, empty `RobinHoodMap<K, V, S>` that can hold at least `capacity` entries
+    /// without growing and uses `hash_builder` to hash keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let map: RobinHoodMap<u32, u32> =
+    ///     RobinHoodMap::with_capacity_and_hasher(100, RandomState::new());
+    /// assert!(map.capacity() >= 100);
+    /// ```
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        let mut map = Self::with_hasher(hash_builder);
+        map.reserve(capacity);
+        map
+    }
+
+    fn hash<Q>(&self, key: &Q) -> u64
+    where
+        Q: Hash + ?Sized,
+    {
+        self.hash_builder.hash_one(key)
+    }
+
+    fn mask(&self) -> usize {
+        self.buckets.len() - 1
+    }
+
+    // Returns the distance between the bucket at `index` and the ideal bucket of `hash`.
+    fn probe_distance(&self, hash: u64, index: usize) -> usize {
+        index.wrapping_sub(hash as usize) & self.mask()
+    }
+
+    // Returns the smallest number of buckets that can hold `item_count` entries without exceeding
+    // the maximum load factor.
+    fn get_bucket_count(&self, item_count: usize) -> usize {
+        let mut bucket_count = MIN_BUCKET_COUNT;
+        while ((bucket_count as f64 * self.max_load_factor) as usize) < item_count {
+            bucket_count *= 2;
+        }
+        bucket_count
+    }
+
+    fn resize(&mut self, bucket_count: usize) {
+        let buckets = mem::replace(&mut self.buckets, (0..bucket_count).map(|_| None).collect());
+        self.len = 0;
+        for bucket in buckets.into_iter().flatten() {
+            self.insert_new(bucket);
+        }
+    }
+
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let mask = self.mask();
+        let mut index = hash as usize & mask;
+        let mut distance = 0;
+        // The table always has an empty bucket, so the probe sequence terminates.
+        while let Some(ref bucket) = self.buckets[index] {
+            if self.probe_distance(bucket.hash, index) < distance {
+                return None;
+            }
+            if bucket.hash == hash && bucket.key.borrow() == key {
+                return Some(index);
+            }
+            index = (index + 1) & mask;
+            distance += 1;
+        }
+        None
+    }
+
+    // Inserts an entry whose key is not in the map, assuming that the table has room for it.
+    // Returns the index of the bucket of the inserted entry.
+    fn insert_new(&mut self, mut bucket: Bucket<K, V>) -> usize {
+        let mask = self.mask();
+        let mut index = bucket.hash as usize & mask;
+        let mut distance = 0;
+        let mut inserted_index = None;
+        loop {
+            match self.buckets[index] {
+                None => {
+                    self.buckets[index] = Some(bucket);
+                    self.len += 1;
+                    return inserted_index.unwrap_or(index);
+                }
+                Some(ref mut resident) => {
+                    let resident_distance = index.wrapping_sub(resident.hash as usize) & mask;
+                    if resident_distance < distance {
+                        mem::swap(resident, &mut bucket);
+                        inserted_index.get_or_insert(index);
+                        distance = resident_distance;
+                    }
+                }
+            }
+            index = (index + 1) & mask;
+            distance += 1;
+        }
+    }
+
+    // Removes the entry in the bucket at `index` and shifts the following entries of its probe
+    // sequence back by one bucket.
+    fn remove_at(&mut self, mut index: usize) -> (K, V) {
+        let mask = self.mask();
+        let Bucket { key, value, .. } = self.buckets[index]
+            .take()
+            .expect("Expected occupied bucket.");
+        self.len -= 1;
+        loop {
+            let next_index = (index + 1) & mask;
+            match self.buckets[next_index] {
+                Some(ref bucket) if self.probe_distance(bucket.hash, next_index) > 0 => {}
+                _ => break,
+            }
+            self.buckets[index] = self.buckets[next_index].take();
+            index = next_index;
+        }
+        (key, value)
+    }
+
+    fn get_bucket(&self, index: usize) -> &Bucket<K, V> {
+        self.buckets[index]
+            .as_ref()
+            .expect("Expected occupied bucket.")
+    }
+
+    fn get_bucket_mut(&mut self, index: usize) -> &mut Bucket<K, V> {
+        self.buckets[index]
+            .as_mut()
+            .expect("Expected occupied bucket.")
+    }
+
+    /// Inserts a key-value pair into the map. If the key already exists in the map, it will return
+    /// and replace the old key-value pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// assert_eq!(map.insert(1, 1), None);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// assert_eq!(map.insert(1, 2), Some((1, 1)));
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<(K, V)> {
+        let hash = self.hash(&key);
+        if let Some(index) = self.find(hash, &key) {
+            let bucket = self.get_bucket_mut(index);
+            return Some((
+                mem::replace(&mut bucket.key, key),
+                mem::replace(&mut bucket.value, value),
+            ));
+        }
+        self.reserve(1);
+        self.insert_new(Bucket { hash, key, value });
+        None
+    }
+
+    /// Removes a key-value pair from the map. If the key exists in the map, it will return the
+    /// associated key-value pair. Otherwise it will return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.remove(&1), Some((1, 1)));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let hash = self.hash(key);
+        self.find(hash, key).map(|index| self.remove_at(index))
+    }
+
+    /// Checks if a key exists in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 1);
+    /// assert!(!map.contains_key(&0));
+    /// assert!(map.contains_key(&1));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Returns an immutable reference to the value associated with a particular key. It will
+    /// return `None` if the key does not exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.get(&0), None);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let hash = self.hash(key);
+        self.find(hash, key)
+            .map(|index| &self.get_bucket(index).value)
+    }
+
+    /// Returns a mutable reference to the value associated with a particular key. Returns `None`
+    /// if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 1);
+    /// *map.get_mut(&1).unwrap() = 2;
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let hash = self.hash(key);
+        match self.find(hash, key) {
+            Some(index) => Some(&mut self.get_bucket_mut(index).value),
+            None => None,
+        }
+    }
+
+    /// Returns the entry of a particular key in the map for in-place manipulation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// for word in "a b a c a".split(' ') {
+    ///     *map.entry(word).or_insert(0) += 1;
+    /// }
+    /// assert_eq!(map[&"a"], 3);
+    /// assert_eq!(map[&"c"], 1);
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        self.reserve(1);
+        let hash = self.hash(&key);
+        match self.find(hash, &key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            None => Entry::Vacant(VacantEntry {
+                map: self,
+                hash,
+                key,
+            }),
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more entries to be inserted into the map
+    /// without growing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map: RobinHoodMap<u32, u32> = RobinHoodMap::new();
+    /// map.reserve(100);
+    /// assert!(map.capacity() >= 100);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        let item_count = self
+            .len
+            .checked_add(additional)
+            .expect("Error: capacity overflow.");
+        if item_count > self.capacity() {
+            let bucket_count = self.get_bucket_count(item_count);
+            self.resize(bucket_count);
+        }
+    }
+
+    /// Shrinks the table of the map to the smallest size that holds its entries without exceeding
+    /// the maximum load factor. An empty map releases its table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map: RobinHoodMap<u32, u32> = RobinHoodMap::with_capacity(100);
+    /// map.insert(1, 1);
+    /// map.shrink_to_fit();
+    /// assert!(map.capacity() < 100);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        if self.len == 0 {
+            self.buckets = Vec::new();
+        } else {
+            let bucket_count = self.get_bucket_count(self.len);
+            if bucket_count < self.buckets.len() {
+                self.resize(bucket_count);
+            }
+        }
+    }
+
+    /// Sets the maximum ratio of entries to buckets of the map. The table of the map grows when an
+    /// insertion would exceed the maximum load factor. Higher load factors use less memory at the
+    /// cost of longer probe sequences.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_load_factor` is not strictly between 0 and 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map: RobinHoodMap<u32, u32> = RobinHoodMap::new();
+    /// map.set_max_load_factor(0.5);
+    /// assert_eq!(map.max_load_factor(), 0.5);
+    ///
+    /// for key in 0..100 {
+    ///     map.insert(key, key);
+    /// }
+    /// assert!(map.load_factor() <= 0.5);
+    /// ```
+    pub fn set_max_load_factor(&mut self, max_load_factor: f64) {
+        assert!(
+            max_load_factor > 0.0 && max_load_factor < 1.0,
+            "Error: max load factor must be between 0 and 1."
+        );
+        self.max_load_factor = max_load_factor;
+        if self.len > self.capacity() {
+            let bucket_count = self.get_bucket_count(self.len);
+            self.resize(bucket_count);
+        }
+    }
+}
+
+impl<K, V, S> RobinHoodMap<K, V, S> {
+    /// Returns the number of elements in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let map: RobinHoodMap<u32, u32> = RobinHoodMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the map, removing all values. The table of the map is kept for reuse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.clear();
+    /// assert_eq!(map.is_empty(), true);
+    /// ```
+    pub fn clear(&mut self) {
+        for bucket in &mut self.buckets {
+            *bucket = None;
+        }
+        self.len = 0;
+    }
+
+    /// Returns the number of entries the map can hold without growing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// assert_eq!(map.capacity(), 0);
+    /// map.insert(1, 1);
+    /// assert_eq!(map.capacity(), 7);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        (self.buckets.len() as f64 * self.max_load_factor) as usize
+    }
+
+    /// Returns the ratio of entries to buckets of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// assert_eq!(map.load_factor(), 0.0);
+    /// map.insert(1, 1);
+    /// assert_eq!(map.load_factor(), 0.125);
+    /// ```
+    pub fn load_factor(&self) -> f64 {
+        if self.buckets.is_empty() {
+            0.0
+        } else {
+            self.len as f64 / self.buckets.len() as f64
+        }
+    }
+
+    /// Returns the maximum ratio of entries to buckets of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let map: RobinHoodMap<u32, u32> = RobinHoodMap::new();
+    /// assert_eq!(map.max_load_factor(), 0.9);
+    /// ```
+    pub fn max_load_factor(&self) -> f64 {
+        self.max_load_factor
+    }
+
+    /// Returns a reference to the `BuildHasher` used by the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let map: RobinHoodMap<u32, u32> = RobinHoodMap::new();
+    /// let hash_builder = map.hasher();
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns an iterator over the map. The iterator will yield key-value pairs in an arbitrary
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// let mut entries: Vec<(&u32, &u32)> = map.iter().collect();
+    /// entries.sort();
+    /// assert_eq!(entries, vec![(&1, &1), (&2, &2)]);
+    /// ```
+    pub fn iter(&self) -> RobinHoodMapIter<'_, K, V> {
+        RobinHoodMapIter {
+            buckets: self.buckets.iter(),
+            len: self.len,
+        }
+    }
+
+    /// Returns a mutable iterator over the map. The iterator will yield key-value pairs in an
+    /// arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// for (_, value) in &mut map {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(map[&1], 2);
+    /// assert_eq!(map[&2], 3);
+    /// ```
+    pub fn iter_mut(&mut self) -> RobinHoodMapIterMut<'_, K, V> {
+        RobinHoodMapIterMut {
+            buckets: self.buckets.iter_mut(),
+            len: self.len,
+        }
+    }
+}
+
+/// A view into a single entry of a `RobinHoodMap<K, V, S>`, which may be either occupied or
+/// vacant.
+///
+/// This enum is constructed from the `entry` method of `RobinHoodMap<K, V, S>`.
+pub enum Entry<'a, K, V, S> {
+    /// An entry whose key exists in the map.
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    /// An entry whose key does not exist in the map.
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Returns a reference to the key of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map: RobinHoodMap<u32, u32> = RobinHoodMap::new();
+    /// assert_eq!(map.entry(1).key(), &1);
+    /// ```
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Inserts `default` if the entry is vacant and returns a mutable reference to the value of
+    /// the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// *map.entry(1).or_insert(1) += 1;
+    /// *map.entry(1).or_insert(1) += 1;
+    /// assert_eq!(map[&1], 3);
+    /// ```
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Inserts the result of `default` if the entry is vacant and returns a mutable reference to
+    /// the value of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map: RobinHoodMap<u32, Vec<u32>> = RobinHoodMap::new();
+    /// map.entry(1).or_insert_with(Vec::new).push(1);
+    /// map.entry(1).or_insert_with(Vec::new).push(2);
+    /// assert_eq!(map[&1], vec![1, 2]);
+    /// ```
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Applies `f` to the value of the entry if it is occupied and returns the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::RobinHoodMap;
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.entry(1).and_modify(|value| *value += 1).or_insert(1);
+    /// map.entry(1).and_modify(|value| *value += 1).or_insert(1);
+    /// assert_eq!(map[&1], 2);
+    /// ```
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Entry::Occupied(ref mut entry) = self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+/// A view into an occupied entry of a `RobinHoodMap<K, V, S>`.
+pub struct OccupiedEntry<'a, K, V, S> {
+    map: &'a mut RobinHoodMap<K, V, S>,
+    index: usize,
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Returns a reference to the key of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::{Entry, RobinHoodMap};
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 1);
+    /// if let Entry::Occupied(entry) = map.entry(1) {
+    ///     assert_eq!(entry.key(), &1);
+    /// }
+    /// ```
+    pub fn key(&self) -> &K {
+        &self.map.get_bucket(self.index).key
+    }
+
+    /// Returns a reference to the value of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::{Entry, RobinHoodMap};
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 2);
+    /// if let Entry::Occupied(entry) = map.entry(1) {
+    ///     assert_eq!(entry.get(), &2);
+    /// }
+    /// ```
+    pub fn get(&self) -> &V {
+        &self.map.get_bucket(self.index).value
+    }
+
+    /// Returns a mutable reference to the value of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::{Entry, RobinHoodMap};
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 2);
+    /// if let Entry::Occupied(mut entry) = map.entry(1) {
+    ///     *entry.get_mut() += 1;
+    /// }
+    /// assert_eq!(map[&1], 3);
+    /// ```
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.get_bucket_mut(self.index).value
+    }
+
+    /// Converts the entry into a mutable reference to its value with the lifetime of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::{Entry, RobinHoodMap};
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 2);
+    /// if let Entry::Occupied(entry) = map.entry(1) {
+    ///     *entry.into_mut() += 1;
+    /// }
+    /// assert_eq!(map[&1], 3);
+    /// ```
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.get_bucket_mut(self.index).value
+    }
+
+    /// Replaces the value of the entry with `value` and returns the old value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::{Entry, RobinHoodMap};
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 2);
+    /// if let Entry::Occupied(mut entry) = map.entry(1) {
+    ///     assert_eq!(entry.insert(3), 2);
+    /// }
+    /// assert_eq!(map[&1], 3);
+    /// ```
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry from the map and returns its key-value pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::{Entry, RobinHoodMap};
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// map.insert(1, 2);
+    /// if let Entry::Occupied(entry) = map.entry(1) {
+    ///     assert_eq!(entry.remove(), (1, 2));
+    /// }
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove(self) -> (K, V) {
+        self.map.remove_at(self.index)
+    }
+}
+
+/// A view into a vacant entry of a `RobinHoodMap<K, V, S>`.
+pub struct VacantEntry<'a, K, V, S> {
+    map: &'a mut RobinHoodMap<K, V, S>,
+    hash: u64,
+    key: K,
+}
+
+impl<'a, K, V, S> VacantEntry<'a, K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Returns a reference to the key of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::{Entry, RobinHoodMap};
+    ///
+    /// let mut map: RobinHoodMap<u32, u32> = RobinHoodMap::new();
+    /// if let Entry::Vacant(entry) = map.entry(1) {
+    ///     assert_eq!(entry.key(), &1);
+    /// }
+    /// ```
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Takes ownership of the key of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::{Entry, RobinHoodMap};
+    ///
+    /// let mut map: RobinHoodMap<u32, u32> = RobinHoodMap::new();
+    /// if let Entry::Vacant(entry) = map.entry(1) {
+    ///     assert_eq!(entry.into_key(), 1);
+    /// }
+    /// ```
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Inserts the key of the entry with `value` into the map and returns a mutable reference to
+    /// the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_map::{Entry, RobinHoodMap};
+    ///
+    /// let mut map = RobinHoodMap::new();
+    /// if let Entry::Vacant(entry) = map.entry(1) {
+    ///     *entry.insert(1) += 1;
+    /// }
+    /// assert_eq!(map[&1], 2);
+    /// ```
+    pub fn insert(self, value: V) -> &'a mut V {
+        let VacantEntry { map, hash, key } = self;
+        let index = map.insert_new(Bucket { hash, key, value });
+        &mut map.get_bucket_mut(index).value
+    }
+}
+
+impl<K, V, S> IntoIterator for RobinHoodMap<K, V, S> {
+    type IntoIter = RobinHoodMapIntoIter<K, V>;
+    type Item = (K, V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        Self::IntoIter {
+            buckets: self.buckets.into_iter(),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a RobinHoodMap<K, V, S>
+where
+    K: 'a,
+    V: 'a,
+{
+    type IntoIter = RobinHoodMapIter<'a, K, V>;
+    type Item = (&'a K, &'a V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut RobinHoodMap<K, V, S>
+where
+    K: 'a,
+    V: 'a,
+{
+    type IntoIter = RobinHoodMapIterMut<'a, K, V>;
+    type Item = (&'a K, &'a mut V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An owning iterator for `RobinHoodMap<K, V, S>`.
+///
+/// This iterator traverses the buckets of the map and yields owned entries in an arbitrary order.
+pub struct RobinHoodMapIntoIter<K, V> {
+    buckets: vec::IntoIter<Option<Bucket<K, V>>>,
+    len: usize,
+}
+
+impl<K, V> Iterator for RobinHoodMapIntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Bucket { key, value, .. } = self.buckets.by_ref().flatten().next()?;
+        self.len -= 1;
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+/// An iterator for `RobinHoodMap<K, V, S>`.
+///
+/// This iterator traverses the buckets of the map and yields immutable references in an
+/// arbitrary order.
+pub struct RobinHoodMapIter<'a, K, V> {
+    buckets: slice::Iter<'a, Option<Bucket<K, V>>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for RobinHoodMapIter<'a, K, V>
+where
+    K: 'a,
+    V: 'a,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Bucket { key, value, .. } = self.buckets.by_ref().flatten().next()?;
+        self.len -= 1;
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+/// A mutable iterator for `RobinHoodMap<K, V, S>`.
+///
+/// This iterator traverses the buckets of the map and yields mutable references in an arbitrary
+/// order.
+pub struct RobinHoodMapIterMut<'a, K, V> {
+    buckets: slice::IterMut<'a, Option<Bucket<K, V>>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for RobinHoodMapIterMut<'a, K, V>
+where
+    K: 'a,
+    V: 'a,
+{
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Bucket { key, value, .. } = self.buckets.by_ref().flatten().next()?;
+        self.len -= 1;
+        Some((&*key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V, S> fmt::Debug for RobinHoodMap<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S> PartialEq for RobinHoodMap<K, V, S>
+where
+    K: Eq + Hash,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K, V, S> Eq for RobinHoodMap<K, V, S>
+where
+    K: Eq + Hash,
+    V: Eq,
+    S: BuildHasher,
+{
+}
+
+impl<K, V, S> Default for RobinHoodMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for RobinHoodMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for RobinHoodMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V, S, Q> Index<&Q> for RobinHoodMap<K, V, S>
+where
+    K: Eq + Hash + Borrow<Q>,
+    S: BuildHasher,
+    Q: Eq + Hash + ?Sized,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get(key).expect("Error: key does not exist.")
+    }
+}
+
+impl<K, V, S, Q> IndexMut<&Q> for RobinHoodMap<K, V, S>
+where
+    K: Eq + Hash + Borrow<Q>,
+    S: BuildHasher,
+    Q: Eq + Hash + ?Sized,
+{
+    fn index_mut(&mut self, key: &Q) -> &mut Self::Output {
+        self.get_mut(key).expect("Error: key does not exist.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, RobinHoodMap};
+    use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+
+    // A hasher that maps every key to the same bucket to force long probe sequences.
+    #[derive(Default)]
+    struct ConstantHasher;
+
+    impl Hasher for ConstantHasher {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, _: &[u8]) {}
+    }
+
+    // Checks that every entry can be found and that the probe distances of consecutive buckets
+    // increase by at most one, which is the invariant maintained by Robin Hood probing.
+    fn check_invariants<K, V, S>(map: &RobinHoodMap<K, V, S>)
+    where
+        K: Eq + Hash,
+        S: BuildHasher,
+    {
+        let bucket_count = map.buckets.len();
+        let mut len = 0;
+        for index in 0..bucket_count {
+            if let Some(ref bucket) = map.buckets[index] {
+                len += 1;
+                assert_eq!(map.find(bucket.hash, &bucket.key), Some(index));
+                let distance = map.probe_distance(bucket.hash, index);
+                let next_index = (index + 1) % bucket_count;
+                if let Some(ref next_bucket) = map.buckets[next_index] {
+                    assert!(map.probe_distance(next_bucket.hash, next_index) <= distance + 1);
+                }
+            }
+        }
+        assert_eq!(len, map.len());
+    }
+
+    #[test]
+    fn test_len_empty() {
+        let map: RobinHoodMap<u32, u32> = RobinHoodMap::new();
+        assert_eq!(map.len(), 0);
+        assert_eq!(map.get(&0), None);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut map = RobinHoodMap::new();
+        for key in 0..1000u32 {
+            assert_eq!(map.insert(key, key * 2), None);
+        }
+        check_invariants(&map);
+        assert_eq!(map.len(), 1000);
+        assert!(map.load_factor() <= map.max_load_factor());
+
+        for key in (0..1000u32).step_by(2) {
+            assert_eq!(map.remove(&key), Some((key, key * 2)));
+        }
+        check_invariants(&map);
+        assert_eq!(map.len(), 500);
+        for key in 0..1000u32 {
+            let expected = if key % 2 == 0 { None } else { Some(key * 2) };
+            assert_eq!(map.get(&key).cloned(), expected);
+        }
+    }
+
+    #[test]
+    fn test_colliding_keys() {
+        let mut map: RobinHoodMap<u32, u32, BuildHasherDefault<ConstantHasher>> =
+            RobinHoodMap::default();
+        for key in 0..50u32 {
+            map.insert(key, key);
+        }
+        check_invariants(&map);
+        for key in (0..50u32).filter(|key| key % 3 == 0) {
+            assert_eq!(map.remove(&key), Some((key, key)));
+            check_invariants(&map);
+        }
+        for key in 0..50u32 {
+            assert_eq!(map.contains_key(&key), key % 3 != 0);
+        }
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut map = RobinHoodMap::new();
+        for key in 0..100u32 {
+            *map.entry(key % 10).or_insert(0) += 1;
+        }
+        assert_eq!(map.len(), 10);
+        assert!(map.iter().all(|(_, value)| *value == 10));
+
+        match map.entry(0) {
+            Entry::Occupied(entry) => assert_eq!(entry.remove(), (0, 10)),
+            Entry::Vacant(_) => panic!("Expected occupied entry."),
+        }
+        match map.entry(0) {
+            Entry::Occupied(_) => panic!("Expected vacant entry."),
+            Entry::Vacant(entry) => assert_eq!(*entry.insert(1), 1),
+        }
+        check_invariants(&map);
+    }
+
+    #[test]
+    fn test_max_load_factor() {
+        let mut map = RobinHoodMap::new();
+        for key in 0..100u32 {
+            map.insert(key, key);
+        }
+        let capacity = map.capacity();
+        map.set_max_load_factor(0.25);
+        assert!(map.capacity() > capacity);
+        assert!(map.load_factor() <= 0.25);
+        check_invariants(&map);
+
+        map.clear();
+        map.shrink_to_fit();
+        assert_eq!(map.capacity(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_max_load_factor() {
+        let mut map: RobinHoodMap<u32, u32> = RobinHoodMap::new();
+        map.set_max_load_factor(1.0);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut map: RobinHoodMap<u32, u32> = (0..100).map(|key| (key, key)).collect();
+        assert_eq!(map.iter().size_hint(), (100, Some(100)));
+        for (key, value) in &mut map {
+            *value += key;
+        }
+        let mut entries: Vec<(u32, u32)> = map.clone().into_iter().collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            (0..100).map(|key| (key, key * 2)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_eq() {
+        let map: RobinHoodMap<u32, u32> = (0..100).map(|key| (key, key)).collect();
+        let other: RobinHoodMap<u32, u32> = (0..100).rev().map(|key| (key, key)).collect();
+        assert_eq!(map, other);
+    }
+}
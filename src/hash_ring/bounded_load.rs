@@ -0,0 +1,534 @@
+use crate::hash_ring::hash;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+struct NodeInfo {
+    weight: usize,
+    load: usize,
+    points: Vec<u64>,
+}
+
+/// A consistent hash ring that bounds the number of keys assigned to each node.
+///
+/// Each node is placed on the ring at `replicas * weight` points. A key is assigned to the first
+/// node clockwise from its hash that has not reached its capacity. The capacity of a node is
+/// `⌈c * m * w / W⌉` where `c` is the load factor, `m` is the number of assigned keys, `w` is the
+/// weight of the node, and `W` is the total weight of all nodes. When every node has equal weight,
+/// no node is assigned more than `⌈c * average⌉` keys. The bound is enforced whenever a key is
+/// assigned. When a node is added or removed, only the keys of the removed node and the excess keys
+/// of the nodes that are over their new capacity are reassigned.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::hash_ring::BoundedLoadRing;
+///
+/// let mut ring = BoundedLoadRing::new(10, 1.25);
+/// ring.add_node("node-1", 1);
+/// ring.add_node("node-2", 1);
+///
+/// for key in 0..100 {
+///     ring.insert(key);
+/// }
+///
+/// assert!(ring.load(&"node-1").unwrap() <= 63);
+/// assert!(ring.load(&"node-2").unwrap() <= 63);
+/// assert!(ring.get_node(&0).is_some());
+///
+/// ring.remove_node(&"node-1");
+/// assert_eq!(ring.get_node(&0), Some(&"node-2"));
+/// ```
+pub struct BoundedLoadRing<T, U> {
+    replicas: usize,
+    load_factor: f64,
+    ring: BTreeMap<u64, U>,
+    nodes: HashMap<U, NodeInfo>,
+    total_weight: usize,
+    keys: HashMap<T, Option<U>>,
+    assigned_len: usize,
+}
+
+impl<T, U> BoundedLoadRing<T, U>
+where
+    T: Clone + Eq + Hash,
+    U: Clone + Eq + Hash,
+{
+    /// Constructs a new, empty `BoundedLoadRing<T, U>` where each unit of weight of a node is
+    /// placed on the ring `replicas` times and nodes are assigned at most `load_factor` times
+    /// their fair share of keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `replicas` is zero or if `load_factor` is less than one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::BoundedLoadRing;
+    ///
+    /// let ring: BoundedLoadRing<u32, &str> = BoundedLoadRing::new(10, 1.25);
+    /// ```
+    pub fn new(replicas: usize, load_factor: f64) -> Self {
+        assert!(replicas > 0);
+        assert!(load_factor >= 1.0);
+        BoundedLoadRing {
+            replicas,
+            load_factor,
+            ring: BTreeMap::new(),
+            nodes: HashMap::new(),
+            total_weight: 0,
+            keys: HashMap::new(),
+            assigned_len: 0,
+        }
+    }
+
+    fn get_capacity(&self, weight: usize) -> usize {
+        let fair_share = (self.assigned_len + 1) as f64 * weight as f64 / self.total_weight as f64;
+        (self.load_factor * fair_share).ceil() as usize
+    }
+
+    fn assign(&mut self, key: &T) -> Option<U> {
+        let key_hash = hash(key);
+        let node = {
+            let mut points = self
+                .ring
+                .range(key_hash..)
+                .chain(self.ring.range(..key_hash));
+            points
+                .find(|(_, node)| {
+                    let node_info = &self.nodes[*node];
+                    node_info.load < self.get_capacity(node_info.weight)
+                })
+                .map(|(_, node)| node.clone())
+        };
+
+        if let Some(ref node) = node {
+            if let Some(node_info) = self.nodes.get_mut(node) {
+                node_info.load += 1;
+            }
+            self.assigned_len += 1;
+        }
+        node
+    }
+
+    // Reassigns the keys that are not assigned to a node in the ring and the excess keys of the
+    // nodes that are over their capacity once every key is assigned. Every other key keeps its
+    // node.
+    fn rebalance(&mut self) {
+        let mut excess = HashMap::new();
+        if !self.nodes.is_empty() {
+            let fair_share = self.keys.len() as f64 / self.total_weight as f64;
+            for (node, node_info) in &self.nodes {
+                let capacity = (self.load_factor * fair_share * node_info.weight as f64).ceil();
+                if node_info.load > capacity as usize {
+                    excess.insert(node.clone(), node_info.load - capacity as usize);
+                }
+            }
+        }
+
+        let mut moved_keys = Vec::new();
+        let mut overloaded_keys: HashMap<U, Vec<T>> = HashMap::new();
+        for (key, node) in &self.keys {
+            match node {
+                Some(ref node) if self.nodes.contains_key(node) => {
+                    if excess.contains_key(node) {
+                        overloaded_keys
+                            .entry(node.clone())
+                            .or_default()
+                            .push(key.clone());
+                    }
+                }
+                _ => moved_keys.push(key.clone()),
+            }
+        }
+
+        for (node, mut keys) in overloaded_keys {
+            let node_excess = excess[&node];
+            keys.sort_by_key(|key| hash(key));
+            for key in keys.into_iter().rev().take(node_excess) {
+                self.keys.insert(key.clone(), None);
+                moved_keys.push(key);
+            }
+            if let Some(node_info) = self.nodes.get_mut(&node) {
+                node_info.load -= node_excess;
+            }
+            self.assigned_len -= node_excess;
+        }
+
+        moved_keys.sort_by_key(|key| hash(key));
+        for key in moved_keys {
+            let node = self.assign(&key);
+            self.keys.insert(key, node);
+        }
+    }
+
+    // Places a node on the ring at `len` points. A point that is already taken by another node is
+    // skipped, so the points of a node are recorded to remove exactly those points later.
+    fn add_points(&mut self, node: &U, len: usize) -> Vec<u64> {
+        let mut points = Vec::with_capacity(len);
+        let mut index = 0;
+        while points.len() < len {
+            if let Entry::Vacant(entry) = self.ring.entry(hash(&(node, index))) {
+                points.push(*entry.key());
+                entry.insert(node.clone());
+            }
+            index += 1;
+        }
+        points
+    }
+
+    fn remove_points(&mut self, node: &U) -> Option<NodeInfo> {
+        let node_info = self.nodes.remove(node)?;
+        for point in &node_info.points {
+            self.ring.remove(point);
+        }
+        self.total_weight -= node_info.weight;
+        Some(node_info)
+    }
+
+    /// Adds a node with a particular weight to the ring and moves the excess keys of the nodes that
+    /// are over their new capacity. If the node already exists in the ring, its weight is replaced
+    /// and the old weight is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weight` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::BoundedLoadRing;
+    ///
+    /// let mut ring: BoundedLoadRing<u32, &str> = BoundedLoadRing::new(10, 1.25);
+    /// assert_eq!(ring.add_node("node-1", 1), None);
+    /// assert_eq!(ring.add_node("node-1", 2), Some(1));
+    /// ```
+    pub fn add_node(&mut self, node: U, weight: usize) -> Option<usize> {
+        assert!(weight > 0);
+        // the keys of a node that already exists keep their assignment
+        let old_node_info = self.remove_points(&node);
+        let load = old_node_info.as_ref().map_or(0, |node_info| node_info.load);
+        let points = self.add_points(&node, self.replicas * weight);
+        self.total_weight += weight;
+        self.nodes.insert(
+            node,
+            NodeInfo {
+                weight,
+                load,
+                points,
+            },
+        );
+        self.rebalance();
+        old_node_info.map(|node_info| node_info.weight)
+    }
+
+    /// Removes a node from the ring and reassigns the keys that were assigned to it. If the node
+    /// exists in the ring, its weight is returned. Otherwise it will return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::BoundedLoadRing;
+    ///
+    /// let mut ring: BoundedLoadRing<u32, &str> = BoundedLoadRing::new(10, 1.25);
+    /// ring.add_node("node-1", 1);
+    /// assert_eq!(ring.remove_node(&"node-1"), Some(1));
+    /// assert_eq!(ring.remove_node(&"node-1"), None);
+    /// ```
+    pub fn remove_node(&mut self, node: &U) -> Option<usize> {
+        let node_info = self.remove_points(node)?;
+        self.assigned_len -= node_info.load;
+        self.rebalance();
+        Some(node_info.weight)
+    }
+
+    /// Inserts a key into the ring and returns the node that it is assigned to. If the key
+    /// already exists in the ring, its current assignment is returned. Returns `None` if there
+    /// are no nodes in the ring. Keys that are not assigned are assigned when a node is added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::BoundedLoadRing;
+    ///
+    /// let mut ring = BoundedLoadRing::new(10, 1.25);
+    /// assert_eq!(ring.insert(1), None);
+    ///
+    /// ring.add_node("node-1", 1);
+    /// assert_eq!(ring.get_node(&1), Some(&"node-1"));
+    /// assert_eq!(ring.insert(2), Some(&"node-1"));
+    /// ```
+    pub fn insert(&mut self, key: T) -> Option<&U> {
+        if !self.keys.contains_key(&key) {
+            let node = self.assign(&key);
+            self.keys.insert(key.clone(), node);
+        }
+        self.get_node(&key)
+    }
+
+    /// Removes a key from the ring. If the key exists in the ring, it will return the node that
+    /// it was assigned to. Otherwise it will return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::BoundedLoadRing;
+    ///
+    /// let mut ring = BoundedLoadRing::new(10, 1.25);
+    /// ring.add_node("node-1", 1);
+    /// ring.insert(1);
+    /// assert_eq!(ring.remove(&1), Some("node-1"));
+    /// assert_eq!(ring.remove(&1), None);
+    /// ```
+    pub fn remove(&mut self, key: &T) -> Option<U> {
+        let node = self.keys.remove(key)??;
+        if let Some(node_info) = self.nodes.get_mut(&node) {
+            node_info.load -= 1;
+        }
+        self.assigned_len -= 1;
+        Some(node)
+    }
+
+    /// Returns the node that a key is assigned to. Returns `None` if the key does not exist in
+    /// the ring or if it is not assigned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::BoundedLoadRing;
+    ///
+    /// let mut ring = BoundedLoadRing::new(10, 1.25);
+    /// ring.add_node("node-1", 1);
+    /// ring.insert(1);
+    /// assert_eq!(ring.get_node(&1), Some(&"node-1"));
+    /// assert_eq!(ring.get_node(&2), None);
+    /// ```
+    pub fn get_node(&self, key: &T) -> Option<&U> {
+        self.keys.get(key).and_then(|node| node.as_ref())
+    }
+
+    /// Returns the number of keys assigned to a node. Returns `None` if the node does not exist in
+    /// the ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::BoundedLoadRing;
+    ///
+    /// let mut ring = BoundedLoadRing::new(10, 1.25);
+    /// ring.add_node("node-1", 1);
+    /// ring.insert(1);
+    /// assert_eq!(ring.load(&"node-1"), Some(1));
+    /// assert_eq!(ring.load(&"node-2"), None);
+    /// ```
+    pub fn load(&self, node: &U) -> Option<usize> {
+        self.nodes.get(node).map(|node_info| node_info.load)
+    }
+
+    /// Returns the number of keys in the ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::BoundedLoadRing;
+    ///
+    /// let mut ring: BoundedLoadRing<u32, &str> = BoundedLoadRing::new(10, 1.25);
+    /// ring.insert(1);
+    /// assert_eq!(ring.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if the ring contains no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::BoundedLoadRing;
+    ///
+    /// let ring: BoundedLoadRing<u32, &str> = BoundedLoadRing::new(10, 1.25);
+    /// assert!(ring.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoundedLoadRing;
+    use crate::hash_ring::hash;
+
+    fn check_bound(ring: &BoundedLoadRing<u32, u32>, nodes: &[u32]) {
+        let total_weight: usize = nodes.iter().map(|node| ring.nodes[node].weight).sum();
+        let mut total_load = 0;
+        for node in nodes {
+            let weight = ring.nodes[node].weight;
+            let bound = ring.load_factor * ring.len() as f64 * weight as f64 / total_weight as f64;
+            let load = ring.load(node).unwrap();
+            assert!(load <= bound.ceil() as usize);
+            total_load += load;
+        }
+        assert_eq!(total_load, ring.len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_load_factor() {
+        let _: BoundedLoadRing<u32, u32> = BoundedLoadRing::new(10, 0.5);
+    }
+
+    #[test]
+    fn test_insert_without_nodes() {
+        let mut ring: BoundedLoadRing<u32, u32> = BoundedLoadRing::new(10, 1.25);
+        assert_eq!(ring.insert(1), None);
+        assert_eq!(ring.len(), 1);
+        ring.add_node(1, 1);
+        assert_eq!(ring.get_node(&1), Some(&1));
+    }
+
+    #[test]
+    fn test_bounded_load() {
+        let mut ring = BoundedLoadRing::new(20, 1.1);
+        let nodes: Vec<u32> = (0..5).collect();
+        for node in &nodes {
+            ring.add_node(*node, 1);
+        }
+        for key in 0..1000 {
+            ring.insert(key);
+        }
+        check_bound(&ring, &nodes);
+    }
+
+    #[test]
+    fn test_weighted_bounded_load() {
+        let mut ring = BoundedLoadRing::new(20, 1.1);
+        let nodes: Vec<u32> = (0..5).collect();
+        for node in &nodes {
+            ring.add_node(*node, *node as usize + 1);
+        }
+        for key in 0..1000 {
+            ring.insert(key);
+        }
+        check_bound(&ring, &nodes);
+        assert!(ring.load(&4).unwrap() > ring.load(&0).unwrap());
+    }
+
+    #[test]
+    fn test_remove_node() {
+        let mut ring = BoundedLoadRing::new(20, 1.1);
+        let mut nodes: Vec<u32> = (0..5).collect();
+        for node in &nodes {
+            ring.add_node(*node, 1);
+        }
+        for key in 0..1000 {
+            ring.insert(key);
+        }
+
+        assert_eq!(ring.remove_node(&2), Some(1));
+        nodes.retain(|node| *node != 2);
+        check_bound(&ring, &nodes);
+        for key in 0..1000 {
+            assert_ne!(ring.get_node(&key), Some(&2));
+        }
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut ring = BoundedLoadRing::new(20, 1.1);
+        ring.add_node(0, 1);
+        ring.add_node(1, 1);
+        for key in 0..100 {
+            ring.insert(key);
+        }
+        for key in 0..100 {
+            assert!(ring.remove(&key).is_some());
+        }
+        assert!(ring.is_empty());
+        assert_eq!(ring.load(&0), Some(0));
+        assert_eq!(ring.load(&1), Some(0));
+    }
+
+    #[test]
+    fn test_add_node_moves_excess_keys() {
+        let mut ring = BoundedLoadRing::new(20, 1.1);
+        let mut nodes: Vec<u32> = (0..5).collect();
+        for node in &nodes {
+            ring.add_node(*node, 1);
+        }
+        for key in 0..1000 {
+            ring.insert(key);
+        }
+        let old_nodes: Vec<u32> = (0..1000).map(|key| *ring.get_node(&key).unwrap()).collect();
+        let capacity = (1.1 * 1000.0 / 6.0_f64).ceil() as usize;
+        let excess: usize = nodes
+            .iter()
+            .map(|node| ring.load(node).unwrap().saturating_sub(capacity))
+            .sum();
+
+        ring.add_node(5, 1);
+        nodes.push(5);
+        check_bound(&ring, &nodes);
+        let moved = (0..1000)
+            .filter(|key| ring.get_node(key) != Some(&old_nodes[*key as usize]))
+            .count();
+        assert_eq!(moved, excess);
+    }
+
+    #[test]
+    fn test_remove_node_moves_its_keys() {
+        let mut ring = BoundedLoadRing::new(20, 1.1);
+        for node in 0..5 {
+            ring.add_node(node, 1);
+        }
+        for key in 0..1000 {
+            ring.insert(key);
+        }
+        let old_nodes: Vec<u32> = (0..1000).map(|key| *ring.get_node(&key).unwrap()).collect();
+
+        ring.remove_node(&2);
+        for key in 0..1000 {
+            if old_nodes[key as usize] != 2 {
+                assert_eq!(ring.get_node(&key), Some(&old_nodes[key as usize]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_point_collision() {
+        let mut ring = BoundedLoadRing::new(20, 1.1);
+        ring.add_node(0, 1);
+        ring.insert(0);
+
+        // the first point of node 1 is taken by node 0
+        let point = hash(&(&1u32, 0usize));
+        ring.ring.insert(point, 0);
+        ring.nodes.get_mut(&0).unwrap().points.push(point);
+
+        ring.add_node(1, 1);
+        assert_eq!(ring.nodes[&1].points.len(), 20);
+        assert_eq!(ring.ring.len(), 41);
+        assert_eq!(ring.ring.get(&point), Some(&0));
+
+        ring.remove_node(&1);
+        assert_eq!(ring.ring.len(), 21);
+        assert_eq!(ring.ring.get(&point), Some(&0));
+        ring.remove_node(&0);
+        assert!(ring.ring.is_empty());
+        assert_eq!(ring.get_node(&0), None);
+    }
+
+    #[test]
+    fn test_reweight_node_keeps_keys() {
+        let mut ring = BoundedLoadRing::new(20, 1.1);
+        ring.add_node(0, 1);
+        for key in 0..100 {
+            ring.insert(key);
+        }
+        assert_eq!(ring.add_node(0, 2), Some(1));
+        assert_eq!(ring.load(&0), Some(100));
+        check_bound(&ring, &[0]);
+    }
+}
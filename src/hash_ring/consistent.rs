@@ -0,0 +1,306 @@
+use crate::hash_ring::hash;
+use std::collections::hash_map;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+/// A consistent hash ring with virtual nodes.
+///
+/// Each node is placed on the ring at `replicas * weight` points, which are called virtual nodes.
+/// A key is assigned to the node of the first point clockwise from its hash. Adding or removing a
+/// node only reassigns the keys that are assigned to its points, and a node is assigned a share of
+/// the keys that is proportional to its weight. More replicas reduce the variance of the shares at
+/// the cost of memory and slower updates.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::hash_ring::ConsistentHashRing;
+///
+/// let mut ring = ConsistentHashRing::new(10);
+/// ring.add_node("node-1", 1);
+/// ring.add_node("node-2", 1);
+///
+/// let node = *ring.get_node(&"key").unwrap();
+/// assert!(node == "node-1" || node == "node-2");
+///
+/// ring.remove_node(&"node-1");
+/// assert_eq!(ring.get_node(&"key"), Some(&"node-2"));
+/// ```
+pub struct ConsistentHashRing<U> {
+    replicas: usize,
+    ring: BTreeMap<u64, U>,
+    nodes: HashMap<U, usize>,
+}
+
+impl<U> ConsistentHashRing<U>
+where
+    U: Clone + Eq + Hash,
+{
+    /// Constructs a new, empty `ConsistentHashRing<U>` where each unit of weight of a node is
+    /// placed on the ring `replicas` times.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `replicas` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::ConsistentHashRing;
+    ///
+    /// let ring: ConsistentHashRing<&str> = ConsistentHashRing::new(10);
+    /// ```
+    pub fn new(replicas: usize) -> Self {
+        assert!(replicas > 0);
+        ConsistentHashRing {
+            replicas,
+            ring: BTreeMap::new(),
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Adds a node with a particular weight to the ring. If the node already exists in the ring,
+    /// its weight is replaced and the old weight is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weight` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::ConsistentHashRing;
+    ///
+    /// let mut ring = ConsistentHashRing::new(10);
+    /// assert_eq!(ring.add_node("node-1", 1), None);
+    /// assert_eq!(ring.add_node("node-1", 2), Some(1));
+    /// ```
+    pub fn add_node(&mut self, node: U, weight: usize) -> Option<usize> {
+        assert!(weight > 0);
+        let ret = self.remove_node(&node);
+        for index in 0..self.replicas * weight {
+            self.ring.insert(hash(&(&node, index)), node.clone());
+        }
+        self.nodes.insert(node, weight);
+        ret
+    }
+
+    /// Removes a node from the ring. If the node exists in the ring, its weight is returned.
+    /// Otherwise it will return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::ConsistentHashRing;
+    ///
+    /// let mut ring = ConsistentHashRing::new(10);
+    /// ring.add_node("node-1", 1);
+    /// assert_eq!(ring.remove_node(&"node-1"), Some(1));
+    /// assert_eq!(ring.remove_node(&"node-1"), None);
+    /// ```
+    pub fn remove_node(&mut self, node: &U) -> Option<usize> {
+        let weight = self.nodes.remove(node)?;
+        for index in 0..self.replicas * weight {
+            let point = hash(&(node, index));
+            // another node may have replaced the point if their hashes collided
+            if self.ring.get(&point) == Some(node) {
+                self.ring.remove(&point);
+            }
+        }
+        Some(weight)
+    }
+
+    /// Returns the node that a key is assigned to. Returns `None` if there are no nodes in the
+    /// ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::ConsistentHashRing;
+    ///
+    /// let mut ring = ConsistentHashRing::new(10);
+    /// assert_eq!(ring.get_node(&1), None);
+    ///
+    /// ring.add_node("node-1", 1);
+    /// assert_eq!(ring.get_node(&1), Some(&"node-1"));
+    /// ```
+    pub fn get_node<T>(&self, key: &T) -> Option<&U>
+    where
+        T: Hash + ?Sized,
+    {
+        let key_hash = hash(key);
+        self.ring
+            .range(key_hash..)
+            .chain(self.ring.range(..key_hash))
+            .next()
+            .map(|(_, node)| node)
+    }
+
+    /// Returns `true` if a node exists in the ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::ConsistentHashRing;
+    ///
+    /// let mut ring = ConsistentHashRing::new(10);
+    /// ring.add_node("node-1", 1);
+    /// assert!(ring.contains_node(&"node-1"));
+    /// assert!(!ring.contains_node(&"node-2"));
+    /// ```
+    pub fn contains_node(&self, node: &U) -> bool {
+        self.nodes.contains_key(node)
+    }
+
+    /// Returns the weight of a node. Returns `None` if the node does not exist in the ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::ConsistentHashRing;
+    ///
+    /// let mut ring = ConsistentHashRing::new(10);
+    /// ring.add_node("node-1", 2);
+    /// assert_eq!(ring.weight(&"node-1"), Some(2));
+    /// assert_eq!(ring.weight(&"node-2"), None);
+    /// ```
+    pub fn weight(&self, node: &U) -> Option<usize> {
+        self.nodes.get(node).cloned()
+    }
+
+    /// Returns the number of nodes in the ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::ConsistentHashRing;
+    ///
+    /// let mut ring = ConsistentHashRing::new(10);
+    /// ring.add_node("node-1", 1);
+    /// assert_eq!(ring.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the ring contains no nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::ConsistentHashRing;
+    ///
+    /// let ring: ConsistentHashRing<&str> = ConsistentHashRing::new(10);
+    /// assert!(ring.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the nodes of the ring and their weights in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::ConsistentHashRing;
+    ///
+    /// let mut ring = ConsistentHashRing::new(10);
+    /// ring.add_node("node-1", 1);
+    /// ring.add_node("node-2", 2);
+    ///
+    /// let mut nodes: Vec<(&&str, usize)> = ring.iter().collect();
+    /// nodes.sort();
+    /// assert_eq!(nodes, vec![(&"node-1", 1), (&"node-2", 2)]);
+    /// ```
+    pub fn iter(&self) -> ConsistentHashRingIter<'_, U> {
+        ConsistentHashRingIter {
+            inner: self.nodes.iter(),
+        }
+    }
+}
+
+/// An iterator for `ConsistentHashRing<U>`.
+///
+/// This iterator yields the nodes of the ring and their weights in arbitrary order.
+pub struct ConsistentHashRingIter<'a, U> {
+    inner: hash_map::Iter<'a, U, usize>,
+}
+
+impl<'a, U> Iterator for ConsistentHashRingIter<'a, U> {
+    type Item = (&'a U, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(node, weight)| (node, *weight))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConsistentHashRing;
+    use std::collections::HashMap;
+
+    fn get_loads(ring: &ConsistentHashRing<u32>) -> HashMap<u32, usize> {
+        let mut loads = HashMap::new();
+        for key in 0..10_000 {
+            *loads.entry(*ring.get_node(&key).unwrap()).or_insert(0) += 1;
+        }
+        loads
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_replicas() {
+        let _: ConsistentHashRing<u32> = ConsistentHashRing::new(0);
+    }
+
+    #[test]
+    fn test_add_node_moves_few_keys() {
+        let mut ring = ConsistentHashRing::new(100);
+        for node in 0..4 {
+            ring.add_node(node, 1);
+        }
+        let old_nodes: Vec<u32> = (0..10_000)
+            .map(|key| *ring.get_node(&key).unwrap())
+            .collect();
+
+        ring.add_node(4, 1);
+        for (key, old_node) in old_nodes.into_iter().enumerate() {
+            let node = *ring.get_node(&(key as i32)).unwrap();
+            assert!(node == old_node || node == 4);
+        }
+    }
+
+    #[test]
+    fn test_remove_node() {
+        let mut ring = ConsistentHashRing::new(100);
+        for node in 0..5 {
+            ring.add_node(node, 1);
+        }
+        let old_nodes: Vec<u32> = (0..10_000)
+            .map(|key| *ring.get_node(&key).unwrap())
+            .collect();
+
+        assert_eq!(ring.remove_node(&2), Some(1));
+        for (key, old_node) in old_nodes.into_iter().enumerate() {
+            let node = *ring.get_node(&(key as i32)).unwrap();
+            assert_ne!(node, 2);
+            if old_node != 2 {
+                assert_eq!(node, old_node);
+            }
+        }
+    }
+
+    #[test]
+    fn test_weighted_loads() {
+        let mut ring = ConsistentHashRing::new(100);
+        ring.add_node(0, 1);
+        ring.add_node(1, 3);
+        let loads = get_loads(&ring);
+        assert!(loads[&1] > 2 * loads[&0]);
+
+        ring.add_node(1, 1);
+        assert_eq!(ring.weight(&1), Some(1));
+        let loads = get_loads(&ring);
+        assert!(loads[&0] > 4_000 && loads[&1] > 4_000);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::hash_ring::hash;
+use std::hash::Hash;
+
+/// Returns the bucket in `0..buckets` that a key is assigned to using the jump consistent hash of
+/// Lamping and Veach.
+///
+/// Keys are spread evenly across the buckets without storing any state. When the number of buckets
+/// grows from `n` to `n + 1`, only about `1 / (n + 1)` of the keys move, and all of them move to
+/// the new bucket `n`. Buckets can only be added or removed at the end of the range, so jump hash
+/// is best suited to numbered shards rather than to arbitrary nodes.
+///
+/// # Panics
+///
+/// Panics if `buckets` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::hash_ring::jump_hash;
+///
+/// let bucket = jump_hash(&"key", 10);
+/// assert!(bucket < 10);
+///
+/// let new_bucket = jump_hash(&"key", 11);
+/// assert!(new_bucket == bucket || new_bucket == 10);
+/// ```
+pub fn jump_hash<T>(key: &T, buckets: usize) -> usize
+where
+    T: Hash + ?Sized,
+{
+    assert!(buckets > 0);
+    let mut key = hash(key);
+    let mut curr = 0;
+    let mut next = 0;
+    while next < buckets as u64 {
+        curr = next;
+        key = key.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+        next = ((curr + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as u64;
+    }
+    curr as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::jump_hash;
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_buckets() {
+        jump_hash(&0, 0);
+    }
+
+    #[test]
+    fn test_single_bucket() {
+        for key in 0..1_000 {
+            assert_eq!(jump_hash(&key, 1), 0);
+        }
+    }
+
+    #[test]
+    fn test_distribution() {
+        let mut loads = vec![0; 10];
+        for key in 0..100_000 {
+            loads[jump_hash(&key, 10)] += 1;
+        }
+        for load in loads {
+            assert!(load > 9_000 && load < 11_000);
+        }
+    }
+
+    #[test]
+    fn test_add_bucket_moves_few_keys() {
+        for buckets in 1..20 {
+            let mut moved = 0;
+            for key in 0..10_000 {
+                let old_bucket = jump_hash(&key, buckets);
+                let bucket = jump_hash(&key, buckets + 1);
+                if bucket != old_bucket {
+                    assert_eq!(bucket, buckets);
+                    moved += 1;
+                }
+            }
+            let expected = 10_000 / (buckets + 1);
+            assert!(moved > expected * 8 / 10 && moved < expected * 12 / 10);
+        }
+    }
+}
@@ -0,0 +1,396 @@
+use crate::hash_ring::hash;
+use std::hash::Hash;
+use std::slice;
+
+fn is_prime(n: usize) -> bool {
+    if n < 2 {
+        return false;
+    }
+    let mut divisor = 2;
+    while divisor * divisor <= n {
+        if n.is_multiple_of(divisor) {
+            return false;
+        }
+        divisor += 1;
+    }
+    true
+}
+
+/// A lookup table for Maglev hashing.
+///
+/// Each backend generates a permutation of the slots of the table from its hash, and the backends
+/// take turns claiming the next unclaimed slot in their permutation until the table is full. A key
+/// is assigned to the backend of the slot that its hash maps to. Every backend owns either
+/// `⌊m / n⌋` or `⌈m / n⌉` slots, where `m` is the size of the table and `n` is the number of
+/// backends, and when the set of backends changes, most slots keep their backend. The table is
+/// rebuilt in `O(m log m)` expected time whenever a backend is added or removed, so `set_backends`
+/// should be used to apply many changes at once. The size of the table should be a prime that is
+/// much larger than the number of backends; if there are more backends than slots, some backends
+/// are not assigned any keys.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::hash_ring::MaglevTable;
+///
+/// let mut table = MaglevTable::new(13);
+/// table.add_backend("backend-1");
+/// table.add_backend("backend-2");
+///
+/// let backend = *table.get_backend(&"key").unwrap();
+/// assert!(backend == "backend-1" || backend == "backend-2");
+///
+/// table.remove_backend(&"backend-1");
+/// assert_eq!(table.get_backend(&"key"), Some(&"backend-2"));
+/// ```
+pub struct MaglevTable<U> {
+    backends: Vec<U>,
+    table: Vec<usize>,
+    table_size: usize,
+}
+
+impl<U> MaglevTable<U>
+where
+    U: Eq + Hash,
+{
+    /// Constructs a new, empty `MaglevTable<U>` with `table_size` slots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `table_size` is not prime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::MaglevTable;
+    ///
+    /// let table: MaglevTable<&str> = MaglevTable::new(65_537);
+    /// ```
+    pub fn new(table_size: usize) -> Self {
+        assert!(is_prime(table_size), "Error: table size must be prime.");
+        MaglevTable {
+            backends: Vec::new(),
+            table: Vec::new(),
+            table_size,
+        }
+    }
+
+    fn rebuild(&mut self) {
+        self.table.clear();
+        if self.backends.is_empty() {
+            return;
+        }
+
+        let table_size = self.table_size as u64;
+        let mut positions = Vec::with_capacity(self.backends.len());
+        let mut skips = Vec::with_capacity(self.backends.len());
+        for backend in &self.backends {
+            positions.push(hash(&(backend, 0)) % table_size);
+            skips.push(if table_size == 2 {
+                1
+            } else {
+                hash(&(backend, 1)) % (table_size - 1) + 1
+            });
+        }
+
+        let mut slots = vec![None; self.table_size];
+        let mut filled = 0;
+        'fill: loop {
+            for index in 0..self.backends.len() {
+                // the skip is coprime with the prime table size, so the permutation visits every
+                // slot and an unclaimed slot is always found
+                while slots[positions[index] as usize].is_some() {
+                    positions[index] = (positions[index] + skips[index]) % table_size;
+                }
+                slots[positions[index] as usize] = Some(index);
+                filled += 1;
+                if filled == self.table_size {
+                    break 'fill;
+                }
+            }
+        }
+        self.table = slots
+            .into_iter()
+            .map(|slot| slot.expect("Expected filled slot."))
+            .collect();
+    }
+
+    /// Adds a backend to the table and rebuilds the table. Returns `false` if the backend already
+    /// exists in the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::MaglevTable;
+    ///
+    /// let mut table = MaglevTable::new(13);
+    /// assert!(table.add_backend("backend-1"));
+    /// assert!(!table.add_backend("backend-1"));
+    /// ```
+    pub fn add_backend(&mut self, backend: U) -> bool {
+        if self.contains_backend(&backend) {
+            return false;
+        }
+        self.backends.push(backend);
+        self.rebuild();
+        true
+    }
+
+    /// Removes a backend from the table and rebuilds the table. If the backend exists in the
+    /// table, it is returned. Otherwise it will return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::MaglevTable;
+    ///
+    /// let mut table = MaglevTable::new(13);
+    /// table.add_backend("backend-1");
+    /// assert_eq!(table.remove_backend(&"backend-1"), Some("backend-1"));
+    /// assert_eq!(table.remove_backend(&"backend-1"), None);
+    /// ```
+    pub fn remove_backend(&mut self, backend: &U) -> Option<U> {
+        let index = self.backends.iter().position(|other| other == backend)?;
+        let ret = self.backends.remove(index);
+        self.rebuild();
+        Some(ret)
+    }
+
+    /// Replaces the backends of the table and rebuilds the table once. Duplicate backends are
+    /// ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::MaglevTable;
+    ///
+    /// let mut table = MaglevTable::new(13);
+    /// table.add_backend("backend-1");
+    /// table.set_backends(vec!["backend-2", "backend-3", "backend-2"]);
+    ///
+    /// assert_eq!(table.len(), 2);
+    /// assert!(!table.contains_backend(&"backend-1"));
+    /// ```
+    pub fn set_backends<I>(&mut self, backends: I)
+    where
+        I: IntoIterator<Item = U>,
+    {
+        self.backends.clear();
+        for backend in backends {
+            if !self.contains_backend(&backend) {
+                self.backends.push(backend);
+            }
+        }
+        self.rebuild();
+    }
+
+    /// Returns the backend that a key is assigned to. Returns `None` if there are no backends in
+    /// the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::MaglevTable;
+    ///
+    /// let mut table = MaglevTable::new(13);
+    /// assert_eq!(table.get_backend(&1), None);
+    ///
+    /// table.add_backend("backend-1");
+    /// assert_eq!(table.get_backend(&1), Some(&"backend-1"));
+    /// ```
+    pub fn get_backend<T>(&self, key: &T) -> Option<&U>
+    where
+        T: Hash + ?Sized,
+    {
+        if self.table.is_empty() {
+            return None;
+        }
+        let slot = (hash(key) % self.table_size as u64) as usize;
+        Some(&self.backends[self.table[slot]])
+    }
+
+    /// Returns `true` if a backend exists in the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::MaglevTable;
+    ///
+    /// let mut table = MaglevTable::new(13);
+    /// table.add_backend("backend-1");
+    /// assert!(table.contains_backend(&"backend-1"));
+    /// assert!(!table.contains_backend(&"backend-2"));
+    /// ```
+    pub fn contains_backend(&self, backend: &U) -> bool {
+        self.backends.contains(backend)
+    }
+
+    /// Returns the number of backends in the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::MaglevTable;
+    ///
+    /// let mut table = MaglevTable::new(13);
+    /// table.add_backend("backend-1");
+    /// assert_eq!(table.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.backends.len()
+    }
+
+    /// Returns `true` if the table contains no backends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::MaglevTable;
+    ///
+    /// let table: MaglevTable<&str> = MaglevTable::new(13);
+    /// assert!(table.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of slots in the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::MaglevTable;
+    ///
+    /// let table: MaglevTable<&str> = MaglevTable::new(13);
+    /// assert_eq!(table.table_size(), 13);
+    /// ```
+    pub fn table_size(&self) -> usize {
+        self.table_size
+    }
+
+    /// Returns an iterator over the backends of the table in the order that they were added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::MaglevTable;
+    ///
+    /// let mut table = MaglevTable::new(13);
+    /// table.add_backend("backend-1");
+    /// table.add_backend("backend-2");
+    ///
+    /// let mut iterator = table.iter();
+    /// assert_eq!(iterator.next(), Some(&"backend-1"));
+    /// assert_eq!(iterator.next(), Some(&"backend-2"));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> MaglevTableIter<'_, U> {
+        MaglevTableIter {
+            inner: self.backends.iter(),
+        }
+    }
+}
+
+/// An iterator for `MaglevTable<U>`.
+///
+/// This iterator yields the backends of the table in the order that they were added.
+pub struct MaglevTableIter<'a, U> {
+    inner: slice::Iter<'a, U>,
+}
+
+impl<'a, U> Iterator for MaglevTableIter<'a, U> {
+    type Item = &'a U;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MaglevTable;
+
+    fn get_backends(table: &MaglevTable<u32>) -> Vec<u32> {
+        (0..10_000)
+            .map(|key| *table.get_backend(&key).unwrap())
+            .collect()
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_table_size() {
+        let _: MaglevTable<u32> = MaglevTable::new(12);
+    }
+
+    #[test]
+    fn test_slots_are_balanced() {
+        let mut table = MaglevTable::new(65_537);
+        table.set_backends(0..10);
+        let mut slots = vec![0; 10];
+        for slot in &table.table {
+            slots[*slot] += 1;
+        }
+        for count in slots {
+            assert!(count == 6_553 || count == 6_554);
+        }
+    }
+
+    #[test]
+    fn test_distribution() {
+        let mut table = MaglevTable::new(65_537);
+        table.set_backends(0..5);
+        let mut loads = vec![0; 5];
+        for backend in get_backends(&table) {
+            loads[backend as usize] += 1;
+        }
+        for load in loads {
+            assert!(load > 1_700 && load < 2_300);
+        }
+    }
+
+    #[test]
+    fn test_remove_backend_moves_few_keys() {
+        let mut table = MaglevTable::new(65_537);
+        table.set_backends(0..10);
+        let old_backends = get_backends(&table);
+
+        assert_eq!(table.remove_backend(&3), Some(3));
+        let mut moved = 0;
+        for (old_backend, backend) in old_backends.into_iter().zip(get_backends(&table)) {
+            assert_ne!(backend, 3);
+            if old_backend != 3 && backend != old_backend {
+                moved += 1;
+            }
+        }
+        assert!(moved < 500);
+    }
+
+    #[test]
+    fn test_add_backend_moves_few_keys() {
+        let mut table = MaglevTable::new(65_537);
+        table.set_backends(0..10);
+        let old_backends = get_backends(&table);
+
+        assert!(table.add_backend(10));
+        let mut moved = 0;
+        let mut added = 0;
+        for (old_backend, backend) in old_backends.into_iter().zip(get_backends(&table)) {
+            if backend == 10 {
+                added += 1;
+            } else if backend != old_backend {
+                moved += 1;
+            }
+        }
+        assert!(added > 700 && added < 1_100);
+        assert!(moved < 500);
+    }
+
+    #[test]
+    fn test_small_table() {
+        let mut table = MaglevTable::new(2);
+        table.set_backends(0..3);
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.table.len(), 2);
+        assert!(table.get_backend(&0).is_some());
+    }
+}
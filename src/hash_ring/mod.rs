@@ -0,0 +1,25 @@
+//! Consistent hashing schemes for distributing keys across a changing set of nodes.
+
+mod bounded_load;
+mod consistent;
+mod jump;
+mod maglev;
+mod rendezvous;
+
+pub use self::bounded_load::BoundedLoadRing;
+pub use self::consistent::{ConsistentHashRing, ConsistentHashRingIter};
+pub use self::jump::jump_hash;
+pub use self::maglev::{MaglevTable, MaglevTableIter};
+pub use self::rendezvous::{RendezvousRing, RendezvousRingIter};
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+fn hash<T>(value: &T) -> u64
+where
+    T: Hash + ?Sized,
+{
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,302 @@
+use crate::hash_ring::hash;
+use std::collections::hash_map;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// Returns the score of a node with a particular weight for a key. Mapping the hash of the node and
+// the key to a uniform value `x` in (0, 1] and scoring it as `-weight / ln(x)` assigns each key to
+// each node with probability proportional to its weight.
+fn score<T, U>(key: &T, node: &U, weight: usize) -> f64
+where
+    T: Hash + ?Sized,
+    U: Hash,
+{
+    let x = ((hash(&(key, node)) >> 11) + 1) as f64 / (1u64 << 53) as f64;
+    -(weight as f64) / x.ln()
+}
+
+/// A weighted rendezvous hashing ring, also known as highest random weight hashing.
+///
+/// A key is assigned to the node with the highest score, which is computed by hashing the key
+/// together with each node and scaling the hash by the weight of the node. Adding or removing a
+/// node only reassigns the keys that are assigned to it, and a node is assigned a share of the
+/// keys that is proportional to its weight. Unlike `ConsistentHashRing`, no virtual nodes are
+/// stored, but finding the node of a key takes time linear in the number of nodes.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::hash_ring::RendezvousRing;
+///
+/// let mut ring = RendezvousRing::new();
+/// ring.add_node("node-1", 1);
+/// ring.add_node("node-2", 1);
+///
+/// let node = *ring.get_node(&"key").unwrap();
+/// assert!(node == "node-1" || node == "node-2");
+///
+/// ring.remove_node(&"node-1");
+/// assert_eq!(ring.get_node(&"key"), Some(&"node-2"));
+/// ```
+pub struct RendezvousRing<U> {
+    nodes: HashMap<U, usize>,
+}
+
+impl<U> RendezvousRing<U>
+where
+    U: Eq + Hash,
+{
+    /// Constructs a new, empty `RendezvousRing<U>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::RendezvousRing;
+    ///
+    /// let ring: RendezvousRing<&str> = RendezvousRing::new();
+    /// ```
+    pub fn new() -> Self {
+        RendezvousRing {
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Adds a node with a particular weight to the ring. If the node already exists in the ring,
+    /// its weight is replaced and the old weight is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weight` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::RendezvousRing;
+    ///
+    /// let mut ring = RendezvousRing::new();
+    /// assert_eq!(ring.add_node("node-1", 1), None);
+    /// assert_eq!(ring.add_node("node-1", 2), Some(1));
+    /// ```
+    pub fn add_node(&mut self, node: U, weight: usize) -> Option<usize> {
+        assert!(weight > 0);
+        self.nodes.insert(node, weight)
+    }
+
+    /// Removes a node from the ring. If the node exists in the ring, its weight is returned.
+    /// Otherwise it will return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::RendezvousRing;
+    ///
+    /// let mut ring = RendezvousRing::new();
+    /// ring.add_node("node-1", 1);
+    /// assert_eq!(ring.remove_node(&"node-1"), Some(1));
+    /// assert_eq!(ring.remove_node(&"node-1"), None);
+    /// ```
+    pub fn remove_node(&mut self, node: &U) -> Option<usize> {
+        self.nodes.remove(node)
+    }
+
+    /// Returns the node that a key is assigned to. Returns `None` if there are no nodes in the
+    /// ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::RendezvousRing;
+    ///
+    /// let mut ring = RendezvousRing::new();
+    /// assert_eq!(ring.get_node(&1), None);
+    ///
+    /// ring.add_node("node-1", 1);
+    /// assert_eq!(ring.get_node(&1), Some(&"node-1"));
+    /// ```
+    pub fn get_node<T>(&self, key: &T) -> Option<&U>
+    where
+        T: Hash + ?Sized,
+    {
+        self.nodes
+            .iter()
+            .map(|(node, weight)| (node, score(key, node, *weight)))
+            .max_by(|(_, score_1), (_, score_2)| score_1.total_cmp(score_2))
+            .map(|(node, _)| node)
+    }
+
+    /// Returns `true` if a node exists in the ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::RendezvousRing;
+    ///
+    /// let mut ring = RendezvousRing::new();
+    /// ring.add_node("node-1", 1);
+    /// assert!(ring.contains_node(&"node-1"));
+    /// assert!(!ring.contains_node(&"node-2"));
+    /// ```
+    pub fn contains_node(&self, node: &U) -> bool {
+        self.nodes.contains_key(node)
+    }
+
+    /// Returns the weight of a node. Returns `None` if the node does not exist in the ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::RendezvousRing;
+    ///
+    /// let mut ring = RendezvousRing::new();
+    /// ring.add_node("node-1", 2);
+    /// assert_eq!(ring.weight(&"node-1"), Some(2));
+    /// assert_eq!(ring.weight(&"node-2"), None);
+    /// ```
+    pub fn weight(&self, node: &U) -> Option<usize> {
+        self.nodes.get(node).cloned()
+    }
+
+    /// Returns the number of nodes in the ring.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::RendezvousRing;
+    ///
+    /// let mut ring = RendezvousRing::new();
+    /// ring.add_node("node-1", 1);
+    /// assert_eq!(ring.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the ring contains no nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::RendezvousRing;
+    ///
+    /// let ring: RendezvousRing<&str> = RendezvousRing::new();
+    /// assert!(ring.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the nodes of the ring and their weights in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::hash_ring::RendezvousRing;
+    ///
+    /// let mut ring = RendezvousRing::new();
+    /// ring.add_node("node-1", 1);
+    /// ring.add_node("node-2", 2);
+    ///
+    /// let mut nodes: Vec<(&&str, usize)> = ring.iter().collect();
+    /// nodes.sort();
+    /// assert_eq!(nodes, vec![(&"node-1", 1), (&"node-2", 2)]);
+    /// ```
+    pub fn iter(&self) -> RendezvousRingIter<'_, U> {
+        RendezvousRingIter {
+            inner: self.nodes.iter(),
+        }
+    }
+}
+
+impl<U> Default for RendezvousRing<U>
+where
+    U: Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An iterator for `RendezvousRing<U>`.
+///
+/// This iterator yields the nodes of the ring and their weights in arbitrary order.
+pub struct RendezvousRingIter<'a, U> {
+    inner: hash_map::Iter<'a, U, usize>,
+}
+
+impl<'a, U> Iterator for RendezvousRingIter<'a, U> {
+    type Item = (&'a U, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(node, weight)| (node, *weight))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RendezvousRing;
+    use std::collections::HashMap;
+
+    fn get_loads(ring: &RendezvousRing<u32>) -> HashMap<u32, usize> {
+        let mut loads = HashMap::new();
+        for key in 0..10_000 {
+            *loads.entry(*ring.get_node(&key).unwrap()).or_insert(0) += 1;
+        }
+        loads
+    }
+
+    #[test]
+    fn test_add_node_moves_few_keys() {
+        let mut ring = RendezvousRing::new();
+        for node in 0..4 {
+            ring.add_node(node, 1);
+        }
+        let old_nodes: Vec<u32> = (0..10_000)
+            .map(|key| *ring.get_node(&key).unwrap())
+            .collect();
+
+        ring.add_node(4, 1);
+        let mut moved = 0;
+        for (key, old_node) in old_nodes.into_iter().enumerate() {
+            let node = *ring.get_node(&(key as i32)).unwrap();
+            assert!(node == old_node || node == 4);
+            if node == 4 {
+                moved += 1;
+            }
+        }
+        assert!(moved > 1_500 && moved < 2_500);
+    }
+
+    #[test]
+    fn test_remove_node() {
+        let mut ring = RendezvousRing::new();
+        for node in 0..5 {
+            ring.add_node(node, 1);
+        }
+        let old_nodes: Vec<u32> = (0..10_000)
+            .map(|key| *ring.get_node(&key).unwrap())
+            .collect();
+
+        assert_eq!(ring.remove_node(&2), Some(1));
+        for (key, old_node) in old_nodes.into_iter().enumerate() {
+            let node = *ring.get_node(&(key as i32)).unwrap();
+            assert_ne!(node, 2);
+            if old_node != 2 {
+                assert_eq!(node, old_node);
+            }
+        }
+    }
+
+    #[test]
+    fn test_weighted_loads() {
+        let mut ring = RendezvousRing::new();
+        ring.add_node(0, 1);
+        ring.add_node(1, 3);
+        let loads = get_loads(&ring);
+        assert!(loads[&1] > 2 * loads[&0]);
+
+        ring.add_node(1, 1);
+        assert_eq!(ring.weight(&1), Some(1));
+        let loads = get_loads(&ring);
+        assert!(loads[&0] > 4_000 && loads[&1] > 4_000);
+    }
+}
@@ -0,0 +1,359 @@
+use crate::treap::{TreapMap, TreapSet};
+use std::mem;
+
+/// A min-priority queue of keys that supports changing the priority of a key and removing a key.
+///
+/// The queue is implemented using two treaps. The first treap orders the keys by their priorities
+/// and then by the keys themselves, and the second treap maps each key to its current priority.
+/// Pushing, popping, changing the priority of, and removing a key all take expected logarithmic
+/// time. Keys with equal priorities are popped in ascending order of their keys.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::heap::IndexedPriorityQueue;
+///
+/// let mut queue = IndexedPriorityQueue::new();
+/// queue.push("a", 3);
+/// queue.push("b", 5);
+/// queue.push("c", 4);
+///
+/// assert_eq!(queue.peek_min(), Some((&"a", &3)));
+/// assert_eq!(queue.change_priority(&"b", 1), Some(5));
+/// assert_eq!(queue.remove(&"c"), Some(("c", 4)));
+/// assert_eq!(queue.pop_min(), Some(("b", 1)));
+/// assert_eq!(queue.pop_min(), Some(("a", 3)));
+/// assert_eq!(queue.pop_min(), None);
+/// ```
+pub struct IndexedPriorityQueue<K, P> {
+    queue: TreapSet<(P, K)>,
+    priorities: TreapMap<K, P>,
+}
+
+impl<K, P> IndexedPriorityQueue<K, P>
+where
+    K: Ord + Clone,
+    P: Ord + Clone,
+{
+    /// Constructs a new, empty `IndexedPriorityQueue<K, P>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::IndexedPriorityQueue;
+    ///
+    /// let queue: IndexedPriorityQueue<u32, u32> = IndexedPriorityQueue::new();
+    /// ```
+    pub fn new() -> Self {
+        IndexedPriorityQueue {
+            queue: TreapSet::new(),
+            priorities: TreapMap::new(),
+        }
+    }
+
+    /// Pushes a key with a particular priority into the queue. If the key already exists in the
+    /// queue, its priority is replaced and the old priority is returned. Otherwise it will return
+    /// `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// assert_eq!(queue.push(1, 2), None);
+    /// assert_eq!(queue.push(1, 3), Some(2));
+    /// assert_eq!(queue.priority(&1), Some(&3));
+    /// ```
+    pub fn push(&mut self, key: K, priority: P) -> Option<P> {
+        let ret = self.priorities.insert(key.clone(), priority.clone());
+        let ret = ret.map(|(key, old_priority)| {
+            self.queue.remove(&(old_priority.clone(), key));
+            old_priority
+        });
+        self.queue.insert((priority, key));
+        ret
+    }
+
+    /// Removes and returns the key with the minimum priority along with its priority. Returns
+    /// `None` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push(1, 2);
+    /// queue.push(2, 1);
+    /// assert_eq!(queue.pop_min(), Some((2, 1)));
+    /// assert_eq!(queue.pop_min(), Some((1, 2)));
+    /// assert_eq!(queue.pop_min(), None);
+    /// ```
+    pub fn pop_min(&mut self) -> Option<(K, P)> {
+        let min = self.queue.min()?.clone();
+        let (priority, key) = self.queue.remove(&min).expect("Expected non-empty queue.");
+        self.priorities.remove(&key);
+        Some((key, priority))
+    }
+
+    /// Returns the key with the minimum priority along with its priority. Returns `None` if the
+    /// queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push(1, 2);
+    /// queue.push(2, 1);
+    /// assert_eq!(queue.peek_min(), Some((&2, &1)));
+    /// ```
+    pub fn peek_min(&self) -> Option<(&K, &P)> {
+        self.queue.min().map(|(priority, key)| (key, priority))
+    }
+
+    /// Changes the priority of a key in the queue and returns the old priority. Returns `None`
+    /// and leaves the queue unchanged if the key does not exist in the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push(1, 2);
+    /// queue.push(2, 3);
+    /// assert_eq!(queue.change_priority(&2, 1), Some(3));
+    /// assert_eq!(queue.change_priority(&3, 1), None);
+    /// assert_eq!(queue.peek_min(), Some((&2, &1)));
+    /// ```
+    pub fn change_priority(&mut self, key: &K, priority: P) -> Option<P> {
+        let old_priority = {
+            let curr_priority = self.priorities.get_mut(key)?;
+            mem::replace(curr_priority, priority.clone())
+        };
+        let (_, key) = self
+            .queue
+            .remove(&(old_priority.clone(), key.clone()))
+            .expect("Expected key to be in queue.");
+        self.queue.insert((priority, key));
+        Some(old_priority)
+    }
+
+    /// Removes a key from the queue. If the key exists in the queue, it will return the key and
+    /// its priority. Otherwise it will return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push(1, 2);
+    /// assert_eq!(queue.remove(&1), Some((1, 2)));
+    /// assert_eq!(queue.remove(&1), None);
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<(K, P)> {
+        let (key, priority) = self.priorities.remove(key)?;
+        self.queue.remove(&(priority.clone(), key.clone()));
+        Some((key, priority))
+    }
+
+    /// Returns the priority of a particular key. Returns `None` if the key does not exist in the
+    /// queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push(1, 2);
+    /// assert_eq!(queue.priority(&0), None);
+    /// assert_eq!(queue.priority(&1), Some(&2));
+    /// ```
+    pub fn priority(&self, key: &K) -> Option<&P> {
+        self.priorities.get(key)
+    }
+
+    /// Checks if a key exists in the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push(1, 2);
+    /// assert!(!queue.contains_key(&0));
+    /// assert!(queue.contains_key(&1));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.priorities.contains_key(key)
+    }
+
+    /// Returns the number of keys in the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push(1, 2);
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.priorities.len()
+    }
+
+    /// Returns `true` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::IndexedPriorityQueue;
+    ///
+    /// let queue: IndexedPriorityQueue<u32, u32> = IndexedPriorityQueue::new();
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.priorities.is_empty()
+    }
+
+    /// Clears the queue, removing all keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push(1, 2);
+    /// queue.clear();
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.priorities.clear();
+    }
+}
+
+impl<K, P> Default for IndexedPriorityQueue<K, P>
+where
+    K: Ord + Clone,
+    P: Ord + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexedPriorityQueue;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_len_empty() {
+        let queue: IndexedPriorityQueue<u32, u32> = IndexedPriorityQueue::new();
+        assert_eq!(queue.len(), 0);
+        assert_eq!(queue.peek_min(), None);
+    }
+
+    #[test]
+    fn test_pop_empty() {
+        let mut queue: IndexedPriorityQueue<u32, u32> = IndexedPriorityQueue::new();
+        assert_eq!(queue.pop_min(), None);
+    }
+
+    #[test]
+    fn test_equal_priorities() {
+        let mut queue = IndexedPriorityQueue::new();
+        queue.push(3, 0);
+        queue.push(1, 0);
+        queue.push(2, 0);
+        assert_eq!(queue.pop_min(), Some((1, 0)));
+        assert_eq!(queue.pop_min(), Some((2, 0)));
+        assert_eq!(queue.pop_min(), Some((3, 0)));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let edges: Vec<Vec<(usize, u32)>> = vec![
+            vec![(1, 7), (2, 9), (5, 14)],
+            vec![(0, 7), (2, 10), (3, 15)],
+            vec![(0, 9), (1, 10), (3, 11), (5, 2)],
+            vec![(1, 15), (2, 11), (4, 6)],
+            vec![(3, 6), (5, 9)],
+            vec![(0, 14), (2, 2), (4, 9)],
+        ];
+        let mut dist = vec![None; edges.len()];
+        let mut queue = IndexedPriorityQueue::new();
+        queue.push(0, 0);
+        while let Some((u, d)) = queue.pop_min() {
+            dist[u] = Some(d);
+            for &(v, w) in &edges[u] {
+                if dist[v].is_some() {
+                    continue;
+                }
+                match queue.priority(&v) {
+                    Some(&curr) if curr <= d + w => {}
+                    Some(_) => {
+                        queue.change_priority(&v, d + w);
+                    }
+                    None => {
+                        queue.push(v, d + w);
+                    }
+                }
+            }
+        }
+        assert_eq!(
+            dist,
+            vec![Some(0), Some(7), Some(9), Some(20), Some(20), Some(11)],
+        );
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng: XorShiftRng = SeedableRng::from_seed([1, 1, 1, 1]);
+        let mut queue = IndexedPriorityQueue::new();
+        let mut expected = BTreeMap::new();
+
+        for _ in 0..10000 {
+            let key = rng.gen_range(0, 500u32);
+            let priority = rng.gen_range(0, 100u32);
+            match rng.gen_range(0, 4) {
+                0 => {
+                    assert_eq!(queue.push(key, priority), expected.insert(key, priority));
+                }
+                1 => {
+                    let expected_priority = expected.get_mut(&key).map(|curr| {
+                        let old_priority = *curr;
+                        *curr = priority;
+                        old_priority
+                    });
+                    assert_eq!(queue.change_priority(&key, priority), expected_priority);
+                }
+                2 => {
+                    assert_eq!(queue.remove(&key), expected.remove(&key).map(|p| (key, p)));
+                }
+                _ => {
+                    let expected_min = expected
+                        .iter()
+                        .map(|(key, priority)| (*priority, *key))
+                        .min()
+                        .map(|(priority, key)| (key, priority));
+                    if let Some((key, _)) = expected_min {
+                        expected.remove(&key);
+                    }
+                    assert_eq!(queue.pop_min(), expected_min);
+                }
+            }
+            assert_eq!(queue.len(), expected.len());
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! Priority queues that support efficient merging, decreasing keys, and changing priorities.
+
+mod indexed_priority_queue;
+mod pairing_heap;
+
+pub use self::indexed_priority_queue::IndexedPriorityQueue;
+pub use self::pairing_heap::{PairingHeap, PairingHeapHandle};
@@ -0,0 +1,524 @@
+use crate::arena::{GenerationalArena, GenerationalEntry};
+use std::collections::HashMap;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const CHUNK_SIZE: usize = 1024;
+
+// The ids of the segments that nodes are allocated in. The ids are unique across all heaps, so the
+// segments of two heaps can be moved into the same map when the heaps are merged.
+static SEGMENT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct NodeRef {
+    segment: usize,
+    entry: GenerationalEntry,
+}
+
+struct Node<T> {
+    value: T,
+    child: Option<NodeRef>,
+    next: Option<NodeRef>,
+    // the parent if the node is the first child, otherwise the previous sibling
+    prev: Option<NodeRef>,
+}
+
+/// A handle to an element in a `PairingHeap<T>` that is used to decrease its key.
+///
+/// A handle remains valid when its heap is merged into another heap, and is invalidated when its
+/// element is popped. An invalidated handle is rejected even if the space of its element is reused.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PairingHeapHandle {
+    node: NodeRef,
+}
+
+/// A mergeable min-heap implemented using a pairing heap.
+///
+/// A pairing heap is a heap-ordered multiway tree. Pushing an element and merging two trees are
+/// constant time operations that link two roots together. Popping the minimum element merges the
+/// children of the root in two passes, which takes amortized logarithmic time. Decreasing the key
+/// of an element detaches its subtree and links it with the root. The nodes are allocated from
+/// segments of `GenerationalArena`s, and merging two heaps moves the segments rather than the
+/// nodes.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::heap::PairingHeap;
+///
+/// let mut heap = PairingHeap::new();
+/// heap.push(3);
+/// let handle = heap.push(5);
+/// heap.push(4);
+///
+/// assert_eq!(heap.peek(), Some(&3));
+/// heap.decrease_key(handle, 1);
+/// assert_eq!(heap.pop(), Some(1));
+/// assert_eq!(heap.pop(), Some(3));
+/// assert_eq!(heap.len(), 1);
+/// ```
+pub struct PairingHeap<T> {
+    segments: HashMap<usize, GenerationalArena<Node<T>>>,
+    // the segment that new nodes are allocated in
+    segment: usize,
+    root: Option<NodeRef>,
+    len: usize,
+}
+
+impl<T> PairingHeap<T>
+where
+    T: Ord,
+{
+    /// Constructs a new, empty `PairingHeap<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::PairingHeap;
+    ///
+    /// let heap: PairingHeap<u32> = PairingHeap::new();
+    /// ```
+    pub fn new() -> Self {
+        let segment = SEGMENT_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let mut segments = HashMap::new();
+        segments.insert(segment, GenerationalArena::new(CHUNK_SIZE));
+        PairingHeap {
+            segments,
+            segment,
+            root: None,
+            len: 0,
+        }
+    }
+
+    fn try_get_node(&self, node: NodeRef) -> Option<&Node<T>> {
+        self.segments
+            .get(&node.segment)
+            .and_then(|segment| segment.try_get(&node.entry))
+    }
+
+    fn try_get_node_mut(&mut self, node: NodeRef) -> Option<&mut Node<T>> {
+        self.segments
+            .get_mut(&node.segment)
+            .and_then(|segment| segment.try_get_mut(&node.entry))
+    }
+
+    fn node(&self, node: NodeRef) -> &Node<T> {
+        self.try_get_node(node).expect("Expected a valid node.")
+    }
+
+    fn node_mut(&mut self, node: NodeRef) -> &mut Node<T> {
+        self.try_get_node_mut(node).expect("Expected a valid node.")
+    }
+
+    // Links two detached trees and returns the root of the combined tree.
+    fn link(&mut self, mut a: NodeRef, mut b: NodeRef) -> NodeRef {
+        if self.node(b).value < self.node(a).value {
+            mem::swap(&mut a, &mut b);
+        }
+
+        let child = self.node_mut(a).child;
+        if let Some(child) = child {
+            self.node_mut(child).prev = Some(b);
+        }
+        self.node_mut(b).next = child;
+        self.node_mut(b).prev = Some(a);
+        self.node_mut(a).child = Some(b);
+        a
+    }
+
+    fn link_with_root(&mut self, node: NodeRef) {
+        self.root = Some(match self.root {
+            Some(root) => self.link(root, node),
+            None => node,
+        });
+    }
+
+    /// Pushes an element into the heap and returns a handle to the element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(1);
+    /// assert_eq!(heap.peek(), Some(&1));
+    /// ```
+    pub fn push(&mut self, value: T) -> PairingHeapHandle {
+        let segment = self.segment;
+        let entry = self
+            .segments
+            .get_mut(&segment)
+            .expect("Expected the segment of the heap to exist.")
+            .allocate(Node {
+                value,
+                child: None,
+                next: None,
+                prev: None,
+            });
+        let node = NodeRef { segment, entry };
+        self.link_with_root(node);
+        self.len += 1;
+        PairingHeapHandle { node }
+    }
+
+    /// Removes and returns the minimum element of the heap. Returns `None` if the heap is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(2);
+    /// heap.push(1);
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), Some(2));
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        let root = self.root.take()?;
+        let node = {
+            let segment = self
+                .segments
+                .get_mut(&root.segment)
+                .expect("Expected the segment of the root to exist.");
+            let node = segment.free(&root.entry).expect("Expected a valid root.");
+            // a segment that was merged from another heap is dropped once it is empty
+            if segment.is_empty() && root.segment != self.segment {
+                self.segments.remove(&root.segment);
+            }
+            node
+        };
+        self.len -= 1;
+
+        let mut children = Vec::new();
+        let mut curr = node.child;
+        while let Some(child) = curr {
+            curr = self.node_mut(child).next.take();
+            self.node_mut(child).prev = None;
+            children.push(child);
+        }
+
+        // first pass: link pairs of children from left to right
+        let mut pairs = Vec::with_capacity(children.len());
+        let mut children = children.into_iter();
+        while let Some(a) = children.next() {
+            pairs.push(match children.next() {
+                Some(b) => self.link(a, b),
+                None => a,
+            });
+        }
+
+        // second pass: link the pairs from right to left
+        let mut pairs = pairs.into_iter().rev();
+        self.root = pairs.next().map(|first| {
+            let mut ret = first;
+            for node in pairs {
+                ret = self.link(ret, node);
+            }
+            ret
+        });
+
+        Some(node.value)
+    }
+
+    /// Returns an immutable reference to the minimum element of the heap. Returns `None` if the
+    /// heap is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// assert_eq!(heap.peek(), None);
+    /// heap.push(1);
+    /// assert_eq!(heap.peek(), Some(&1));
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        self.root.map(|root| &self.node(root).value)
+    }
+
+    /// Returns an immutable reference to the element associated with a particular handle. Returns
+    /// `None` if the handle does not correspond to an element in the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// let handle = heap.push(1);
+    /// assert_eq!(heap.get(handle), Some(&1));
+    /// ```
+    pub fn get(&self, handle: PairingHeapHandle) -> Option<&T> {
+        self.try_get_node(handle.node).map(|node| &node.value)
+    }
+
+    /// Decreases the value of the element associated with a particular handle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle does not correspond to an element in the heap or if the new value is
+    /// greater than the current value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(1);
+    /// let handle = heap.push(2);
+    /// heap.decrease_key(handle, 0);
+    /// assert_eq!(heap.peek(), Some(&0));
+    /// ```
+    pub fn decrease_key(&mut self, handle: PairingHeapHandle, value: T) {
+        let target = handle.node;
+        {
+            let node = self
+                .try_get_node_mut(target)
+                .expect("Error: handle does not exist.");
+            assert!(
+                value <= node.value,
+                "Error: new value is greater than the current value."
+            );
+            node.value = value;
+        }
+
+        if self.root == Some(target) {
+            return;
+        }
+
+        let prev = self
+            .node_mut(target)
+            .prev
+            .take()
+            .expect("Expected a non-root node to have a previous node.");
+        let next = self.node_mut(target).next.take();
+        if self.node_mut(prev).child == Some(target) {
+            self.node_mut(prev).child = next;
+        } else {
+            self.node_mut(prev).next = next;
+        }
+        if let Some(next) = next {
+            self.node_mut(next).prev = Some(prev);
+        }
+
+        self.link_with_root(target);
+    }
+
+    /// Moves all elements of `other` into the heap. The roots of the two heaps are linked in
+    /// constant time, and the segments of the heap with fewer segments are moved into the other
+    /// heap, so merging does not depend on the number of elements. Handles to elements of `other`
+    /// remain valid for the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(2);
+    ///
+    /// let mut other = PairingHeap::new();
+    /// other.push(1);
+    ///
+    /// heap.merge(other);
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), Some(2));
+    /// ```
+    pub fn merge(&mut self, mut other: Self) {
+        if self.segments.len() < other.segments.len() {
+            mem::swap(&mut self.segments, &mut other.segments);
+        }
+        self.segments.extend(other.segments.drain());
+        self.len += other.len;
+        if let Some(root) = other.root.take() {
+            self.link_with_root(root);
+        }
+    }
+
+    /// Returns the number of elements in the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(1);
+    /// assert_eq!(heap.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the heap is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::PairingHeap;
+    ///
+    /// let heap: PairingHeap<u32> = PairingHeap::new();
+    /// assert!(heap.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Clears the heap, removing all elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::heap::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(1);
+    /// heap.clear();
+    /// assert!(heap.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<T> Default for PairingHeap<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PairingHeap;
+
+    #[test]
+    fn test_len_empty() {
+        let heap: PairingHeap<u32> = PairingHeap::new();
+        assert_eq!(heap.len(), 0);
+        assert_eq!(heap.peek(), None);
+    }
+
+    #[test]
+    fn test_pop_empty() {
+        let mut heap: PairingHeap<u32> = PairingHeap::new();
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decrease_key_greater() {
+        let mut heap = PairingHeap::new();
+        let handle = heap.push(1);
+        heap.decrease_key(handle, 2);
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut heap = PairingHeap::new();
+        for value in 0..1000u32 {
+            heap.push((value * 7919) % 1000);
+        }
+        assert_eq!(heap.len(), 1000);
+        for value in 0..1000 {
+            assert_eq!(heap.pop(), Some(value));
+        }
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_decrease_key() {
+        let mut heap = PairingHeap::new();
+        let handles: Vec<_> = (0..100u32).map(|value| heap.push(value + 1000)).collect();
+        heap.pop();
+        for (value, handle) in handles.iter().enumerate().skip(1).rev() {
+            heap.decrease_key(*handle, value as u32);
+            assert_eq!(heap.get(*handle), Some(&(value as u32)));
+        }
+        for value in 1..100 {
+            assert_eq!(heap.pop(), Some(value));
+        }
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut heap = PairingHeap::new();
+        let mut other = PairingHeap::new();
+        for value in 0..100u32 {
+            if value % 2 == 0 {
+                heap.push(value);
+            } else {
+                other.push(value);
+            }
+        }
+        other.pop();
+        heap.merge(other);
+        assert_eq!(heap.len(), 99);
+        let mut expected = (0..100).filter(|value| *value != 1);
+        while let Some(value) = heap.pop() {
+            assert_eq!(Some(value), expected.next());
+        }
+        assert_eq!(expected.next(), None);
+    }
+
+    #[test]
+    fn test_stale_handle() {
+        let mut heap = PairingHeap::new();
+        let handle = heap.push(1);
+        assert_eq!(heap.pop(), Some(1));
+        let new_handle = heap.push(2);
+        assert_eq!(heap.get(handle), None);
+        assert_eq!(heap.get(new_handle), Some(&2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decrease_key_stale_handle() {
+        let mut heap = PairingHeap::new();
+        let handle = heap.push(1);
+        heap.pop();
+        heap.push(2);
+        heap.decrease_key(handle, 0);
+    }
+
+    #[test]
+    fn test_handle_of_other_heap() {
+        let mut heap = PairingHeap::new();
+        heap.push(1);
+        let mut other = PairingHeap::new();
+        let handle = other.push(2);
+        assert_eq!(heap.get(handle), None);
+
+        heap.merge(other);
+        assert_eq!(heap.get(handle), Some(&2));
+        heap.decrease_key(handle, 0);
+        assert_eq!(heap.pop(), Some(0));
+        assert_eq!(heap.pop(), Some(1));
+        assert_eq!(heap.get(handle), None);
+    }
+
+    #[test]
+    fn test_merge_segments() {
+        let mut heap = PairingHeap::new();
+        for value in 0..10u32 {
+            let mut other = PairingHeap::new();
+            other.push(value);
+            heap.merge(other);
+        }
+        assert_eq!(heap.segments.len(), 11);
+        for value in 0..10 {
+            assert_eq!(heap.pop(), Some(value));
+        }
+        assert_eq!(heap.segments.len(), 1);
+        heap.push(0);
+        assert_eq!(heap.pop(), Some(0));
+    }
+}
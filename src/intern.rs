@@ -0,0 +1,389 @@
+//! Interners that map values to small integer symbols.
+
+use crate::arena::{Entry, TypedArena};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+
+const DEFAULT_CHUNK_SIZE: usize = 1024;
+
+/// A symbol that identifies an interned value.
+///
+/// Symbols are assigned in the order that values are interned, starting from zero, so they can be
+/// used to index into dense tables.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::intern::Interner;
+///
+/// let mut interner: Interner<String> = Interner::new();
+/// let symbol = interner.get_or_intern("foo");
+/// assert_eq!(symbol.index(), 0);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub(crate) fn new(index: usize) -> Self {
+        assert!(
+            index <= u32::MAX as usize,
+            "Error: interner cannot have more than 2^32 symbols."
+        );
+        Symbol(index as u32)
+    }
+
+    /// Returns the index of the symbol, which is the number of values that were interned before
+    /// the value of the symbol.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::intern::Interner;
+    ///
+    /// let mut interner: Interner<String> = Interner::new();
+    /// interner.get_or_intern("foo");
+    /// assert_eq!(interner.get_or_intern("bar").index(), 1);
+    /// ```
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A collection that stores each distinct value once and identifies it by a `Symbol`.
+///
+/// Interning a value returns the symbol of an equal value if one was already interned, and
+/// otherwise stores the value in a `TypedArena` and returns a new symbol. Comparing and hashing
+/// symbols is much cheaper than comparing and hashing the values themselves, and symbols remain
+/// valid for the lifetime of the interner because values are never removed. The interner keeps
+/// a hash table from the hashes of the values to their symbols, so each value is stored once and
+/// values can be looked up by a borrowed form, such as `&str` for `String` values.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::intern::Interner;
+///
+/// let mut interner: Interner<String> = Interner::new();
+/// let foo = interner.get_or_intern("foo");
+/// let bar = interner.get_or_intern("bar");
+///
+/// assert_ne!(foo, bar);
+/// assert_eq!(interner.get_or_intern("foo"), foo);
+/// assert_eq!(interner.resolve(foo).map(String::as_str), Some("foo"));
+/// assert_eq!(interner.len(), 2);
+/// ```
+pub struct Interner<T, S = RandomState> {
+    arena: TypedArena<T>,
+    entries: Vec<Entry>,
+    // the symbols of the values with each hash, which almost always contains a single symbol
+    symbols: HashMap<u64, Vec<Symbol>>,
+    hash_builder: S,
+}
+
+impl<T> Interner<T>
+where
+    T: Eq + Hash,
+{
+    /// Constructs a new, empty `Interner<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::intern::Interner;
+    ///
+    /// let interner: Interner<String> = Interner::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<T, S> Interner<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Constructs a new, empty `Interner<T, S>` that uses `hash_builder` to hash values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::intern::Interner;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let interner: Interner<String, RandomState> = Interner::with_hasher(RandomState::new());
+    /// ```
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Interner {
+            arena: TypedArena::new(DEFAULT_CHUNK_SIZE),
+            entries: Vec::new(),
+            symbols: HashMap::new(),
+            hash_builder,
+        }
+    }
+
+    fn find<U>(&self, hash: u64, value: &U) -> Option<Symbol>
+    where
+        T: Borrow<U>,
+        U: Eq + ?Sized,
+    {
+        self.symbols
+            .get(&hash)?
+            .iter()
+            .find(|symbol| self.arena[self.entries[symbol.index()]].borrow() == value)
+            .cloned()
+    }
+
+    fn insert(&mut self, hash: u64, value: T) -> Symbol {
+        let symbol = Symbol::new(self.entries.len());
+        self.entries.push(self.arena.allocate(value));
+        self.symbols.entry(hash).or_default().push(symbol);
+        symbol
+    }
+
+    /// Returns the symbol of a value, interning a copy of the value if it was not interned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `2^32` values are interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::intern::Interner;
+    ///
+    /// let mut interner: Interner<String> = Interner::new();
+    /// let symbol = interner.get_or_intern("foo");
+    /// assert_eq!(interner.get_or_intern("foo"), symbol);
+    /// ```
+    pub fn get_or_intern<U>(&mut self, value: &U) -> Symbol
+    where
+        T: Borrow<U>,
+        U: Eq + Hash + ToOwned<Owned = T> + ?Sized,
+    {
+        let hash = self.hash_builder.hash_one(value);
+        match self.find(hash, value) {
+            Some(symbol) => symbol,
+            None => self.insert(hash, value.to_owned()),
+        }
+    }
+
+    /// Returns the symbol of a value, interning the value if it was not interned. Unlike
+    /// `get_or_intern`, the value is moved into the interner instead of being copied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `2^32` values are interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::intern::Interner;
+    ///
+    /// let mut interner = Interner::new();
+    /// let symbol = interner.intern(String::from("foo"));
+    /// assert_eq!(interner.get_or_intern("foo"), symbol);
+    /// ```
+    pub fn intern(&mut self, value: T) -> Symbol {
+        let hash = self.hash_builder.hash_one(&value);
+        match self.find(hash, &value) {
+            Some(symbol) => symbol,
+            None => self.insert(hash, value),
+        }
+    }
+
+    /// Returns the symbol of a value without interning it. Returns `None` if the value was not
+    /// interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::intern::Interner;
+    ///
+    /// let mut interner: Interner<String> = Interner::new();
+    /// let symbol = interner.get_or_intern("foo");
+    /// assert_eq!(interner.get("foo"), Some(symbol));
+    /// assert_eq!(interner.get("bar"), None);
+    /// ```
+    pub fn get<U>(&self, value: &U) -> Option<Symbol>
+    where
+        T: Borrow<U>,
+        U: Eq + Hash + ?Sized,
+    {
+        self.find(self.hash_builder.hash_one(value), value)
+    }
+
+    /// Returns the value of a symbol. Returns `None` if the symbol was not returned by this
+    /// interner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::intern::Interner;
+    ///
+    /// let mut interner: Interner<String> = Interner::new();
+    /// let symbol = interner.get_or_intern("foo");
+    /// assert_eq!(interner.resolve(symbol), Some(&String::from("foo")));
+    /// ```
+    pub fn resolve(&self, symbol: Symbol) -> Option<&T> {
+        let entry = self.entries.get(symbol.index())?;
+        self.arena.get(entry)
+    }
+
+    /// Returns the number of values in the interner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::intern::Interner;
+    ///
+    /// let mut interner: Interner<String> = Interner::new();
+    /// interner.get_or_intern("foo");
+    /// interner.get_or_intern("foo");
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the interner contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::intern::Interner;
+    ///
+    /// let mut interner: Interner<String> = Interner::new();
+    /// assert!(interner.is_empty());
+    /// interner.get_or_intern("foo");
+    /// assert!(!interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the interner. The iterator will yield symbols and their values in
+    /// ascending order of symbols.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::intern::Interner;
+    ///
+    /// let mut interner: Interner<String> = Interner::new();
+    /// let foo = interner.get_or_intern("foo");
+    /// let bar = interner.get_or_intern("bar");
+    ///
+    /// let mut iterator = interner.iter();
+    /// assert_eq!(iterator.next(), Some((foo, &String::from("foo"))));
+    /// assert_eq!(iterator.next(), Some((bar, &String::from("bar"))));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> InternerIter<'_, T, S> {
+        InternerIter {
+            interner: self,
+            index: 0,
+        }
+    }
+}
+
+impl<T> Default for Interner<T>
+where
+    T: Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An iterator for `Interner<T, S>`.
+///
+/// This iterator yields symbols and their values in ascending order of symbols.
+pub struct InternerIter<'a, T, S> {
+    interner: &'a Interner<T, S>,
+    index: usize,
+}
+
+impl<'a, T, S> Iterator for InternerIter<'a, T, S> {
+    type Item = (Symbol, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.interner.entries.get(self.index)?;
+        let symbol = Symbol::new(self.index);
+        self.index += 1;
+        Some((symbol, &self.interner.arena[*entry]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Interner, Symbol};
+    use std::hash::{BuildHasher, Hasher};
+
+    // A hasher that maps every value to the same hash.
+    #[derive(Clone, Default)]
+    struct ConstantHasher;
+
+    impl Hasher for ConstantHasher {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, _: &[u8]) {}
+    }
+
+    impl BuildHasher for ConstantHasher {
+        type Hasher = ConstantHasher;
+
+        fn build_hasher(&self) -> ConstantHasher {
+            ConstantHasher
+        }
+    }
+
+    #[test]
+    fn test_get_or_intern() {
+        let mut interner: Interner<String> = Interner::new();
+        let symbols: Vec<Symbol> = (0..10_000)
+            .map(|index| interner.get_or_intern(format!("value-{}", index).as_str()))
+            .collect();
+        assert_eq!(interner.len(), 10_000);
+
+        for (index, symbol) in symbols.iter().enumerate() {
+            let value = format!("value-{}", index);
+            assert_eq!(symbol.index(), index);
+            assert_eq!(interner.get_or_intern(value.as_str()), *symbol);
+            assert_eq!(interner.resolve(*symbol), Some(&value));
+        }
+        assert_eq!(interner.len(), 10_000);
+        assert_eq!(interner.resolve(Symbol::new(10_000)), None);
+    }
+
+    #[test]
+    fn test_hash_collisions() {
+        let mut interner: Interner<u32, ConstantHasher> = Interner::with_hasher(ConstantHasher);
+        for value in 0..100 {
+            assert_eq!(interner.intern(value).index(), value as usize);
+        }
+        for value in 0..100 {
+            assert_eq!(
+                interner.get(&value).map(Symbol::index),
+                Some(value as usize)
+            );
+        }
+        assert_eq!(interner.get(&100), None);
+        assert_eq!(interner.symbols.len(), 1);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut interner = Interner::new();
+        for value in (0..100u32).rev() {
+            interner.intern(value);
+        }
+        let values: Vec<u32> = interner.iter().map(|(_, value)| *value).collect();
+        assert_eq!(values, (0..100).rev().collect::<Vec<u32>>());
+    }
+}
@@ -0,0 +1,649 @@
+use crate::entry::Entry;
+use crate::interval_tree::node::Node;
+use crate::interval_tree::tree;
+use std::ops::{Index, IndexMut};
+
+/// An ordered map of closed intervals implemented using an augmented avl tree.
+///
+/// Each node of the tree stores an interval `(low, high)` and is ordered by the interval. Each node
+/// is augmented with the maximum endpoint of all intervals in its subtree, which allows subtrees
+/// that cannot contain an overlapping interval to be skipped. Finding all `k` intervals that overlap
+/// a point or interval takes `O(log n + k)` time.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::interval_tree::IntervalMap;
+///
+/// let mut map = IntervalMap::new();
+/// map.insert((0, 5), "a");
+/// map.insert((3, 8), "b");
+/// map.insert((10, 12), "c");
+///
+/// assert_eq!(map[&(0, 5)], "a");
+/// assert_eq!(map.get(&(0, 1)), None);
+/// assert_eq!(map.len(), 3);
+///
+/// assert_eq!(
+///     map.find_overlapping(&4).collect::<Vec<(&(u32, u32), &&str)>>(),
+///     vec![(&(0, 5), &"a"), (&(3, 8), &"b")],
+/// );
+/// assert_eq!(
+///     map.find_intersecting(&(6, 11)).collect::<Vec<(&(u32, u32), &&str)>>(),
+///     vec![(&(3, 8), &"b"), (&(10, 12), &"c")],
+/// );
+///
+/// map[&(0, 5)] = "d";
+/// assert_eq!(map.remove(&(0, 5)), Some(((0, 5), "d")));
+/// assert_eq!(map.remove(&(0, 5)), None);
+/// ```
+pub struct IntervalMap<T, U> {
+    tree: tree::Tree<T, U>,
+    len: usize,
+}
+
+impl<T, U> IntervalMap<T, U> {
+    /// Constructs a new, empty `IntervalMap<T, U>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let map: IntervalMap<u32, u32> = IntervalMap::new();
+    /// ```
+    pub fn new() -> Self {
+        IntervalMap { tree: None, len: 0 }
+    }
+
+    /// Inserts an interval-value pair into the map. If the interval already exists in the map, it
+    /// will return and replace the old interval-value pair.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lower endpoint of the interval is greater than the upper endpoint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// assert_eq!(map.insert((1, 2), 1), None);
+    /// assert_eq!(map.get(&(1, 2)), Some(&1));
+    /// assert_eq!(map.insert((1, 2), 2), Some(((1, 2), 1)));
+    /// assert_eq!(map.get(&(1, 2)), Some(&2));
+    /// ```
+    pub fn insert(&mut self, interval: (T, T), value: U) -> Option<((T, T), U)>
+    where
+        T: Clone + Ord,
+    {
+        assert!(
+            interval.0 <= interval.1,
+            "Error: lower endpoint of interval is greater than upper endpoint."
+        );
+        let IntervalMap {
+            ref mut tree,
+            ref mut len,
+        } = self;
+        let new_node = Node::new(interval, value);
+        *len += 1;
+        tree::insert(tree, new_node).map(|entry| {
+            let Entry { key, value } = entry;
+            *len -= 1;
+            (key, value)
+        })
+    }
+
+    /// Removes an interval-value pair from the map. If the interval exists in the map, it will
+    /// return the associated interval-value pair. Otherwise it will return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert((1, 2), 1);
+    /// assert_eq!(map.remove(&(1, 2)), Some(((1, 2), 1)));
+    /// assert_eq!(map.remove(&(1, 2)), None);
+    /// ```
+    pub fn remove(&mut self, interval: &(T, T)) -> Option<((T, T), U)>
+    where
+        T: Clone + Ord,
+    {
+        let IntervalMap {
+            ref mut tree,
+            ref mut len,
+        } = self;
+        tree::remove(tree, interval).map(|entry| {
+            let Entry { key, value } = entry;
+            *len -= 1;
+            (key, value)
+        })
+    }
+
+    /// Checks if an interval exists in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert((1, 2), 1);
+    /// assert!(!map.contains_key(&(1, 3)));
+    /// assert!(map.contains_key(&(1, 2)));
+    /// ```
+    pub fn contains_key(&self, interval: &(T, T)) -> bool
+    where
+        T: Ord,
+    {
+        self.get(interval).is_some()
+    }
+
+    /// Returns an immutable reference to the value associated with a particular interval. It will
+    /// return `None` if the interval does not exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert((1, 2), 1);
+    /// assert_eq!(map.get(&(1, 3)), None);
+    /// assert_eq!(map.get(&(1, 2)), Some(&1));
+    /// ```
+    pub fn get(&self, interval: &(T, T)) -> Option<&U>
+    where
+        T: Ord,
+    {
+        tree::get(&self.tree, interval).map(|entry| &entry.value)
+    }
+
+    /// Returns a mutable reference to the value associated with a particular interval. Returns
+    /// `None` if such an interval does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert((1, 2), 1);
+    /// *map.get_mut(&(1, 2)).unwrap() = 2;
+    /// assert_eq!(map.get(&(1, 2)), Some(&2));
+    /// ```
+    pub fn get_mut(&mut self, interval: &(T, T)) -> Option<&mut U>
+    where
+        T: Ord,
+    {
+        tree::get_mut(&mut self.tree, interval).map(|entry| &mut entry.value)
+    }
+
+    /// Returns the number of elements in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert((1, 2), 1);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let map: IntervalMap<u32, u32> = IntervalMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Clears the map, removing all values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert((1, 2), 1);
+    /// map.insert((2, 3), 2);
+    /// map.clear();
+    /// assert_eq!(map.is_empty(), true);
+    /// ```
+    pub fn clear(&mut self) {
+        self.tree = None;
+        self.len = 0;
+    }
+
+    /// Returns the minimum interval of the map. Intervals are ordered by their lower endpoint and
+    /// then by their upper endpoint. Returns `None` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert((1, 4), 1);
+    /// map.insert((1, 3), 3);
+    /// assert_eq!(map.min(), Some(&(1, 3)));
+    /// ```
+    pub fn min(&self) -> Option<&(T, T)> {
+        tree::min(&self.tree).map(|entry| &entry.key)
+    }
+
+    /// Returns the maximum interval of the map. Intervals are ordered by their lower endpoint and
+    /// then by their upper endpoint. Returns `None` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert((1, 4), 1);
+    /// map.insert((1, 3), 3);
+    /// assert_eq!(map.max(), Some(&(1, 4)));
+    /// ```
+    pub fn max(&self) -> Option<&(T, T)> {
+        tree::max(&self.tree).map(|entry| &entry.key)
+    }
+
+    /// Returns an iterator over all interval-value pairs whose interval contains a particular
+    /// point. The iterator will yield interval-value pairs in ascending order of their intervals.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert((1, 3), 1);
+    /// map.insert((2, 4), 2);
+    /// map.insert((4, 5), 3);
+    ///
+    /// let mut iterator = map.find_overlapping(&3);
+    /// assert_eq!(iterator.next(), Some((&(1, 3), &1)));
+    /// assert_eq!(iterator.next(), Some((&(2, 4), &2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn find_overlapping(&self, point: &T) -> IntervalMapOverlapIter<'_, T, U>
+    where
+        T: Clone + Ord,
+    {
+        IntervalMapOverlapIter::new(&self.tree, point.clone(), point.clone())
+    }
+
+    /// Returns an iterator over all interval-value pairs whose interval intersects a particular
+    /// interval. The iterator will yield interval-value pairs in ascending order of their
+    /// intervals.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lower endpoint of the interval is greater than the upper endpoint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert((1, 3), 1);
+    /// map.insert((2, 4), 2);
+    /// map.insert((6, 7), 3);
+    ///
+    /// let mut iterator = map.find_intersecting(&(3, 5));
+    /// assert_eq!(iterator.next(), Some((&(1, 3), &1)));
+    /// assert_eq!(iterator.next(), Some((&(2, 4), &2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn find_intersecting(&self, interval: &(T, T)) -> IntervalMapOverlapIter<'_, T, U>
+    where
+        T: Clone + Ord,
+    {
+        assert!(
+            interval.0 <= interval.1,
+            "Error: lower endpoint of interval is greater than upper endpoint."
+        );
+        IntervalMapOverlapIter::new(&self.tree, interval.0.clone(), interval.1.clone())
+    }
+
+    /// Returns an iterator over the map. The iterator will yield interval-value pairs using
+    /// in-order traversal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::interval_tree::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert((1, 2), 1);
+    /// map.insert((2, 3), 2);
+    ///
+    /// let mut iterator = map.iter();
+    /// assert_eq!(iterator.next(), Some((&(1, 2), &1)));
+    /// assert_eq!(iterator.next(), Some((&(2, 3), &2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> IntervalMapIter<'_, T, U> {
+        IntervalMapIter {
+            current: &self.tree,
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl<'a, T, U> IntoIterator for &'a IntervalMap<T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type IntoIter = IntervalMapIter<'a, T, U>;
+    type Item = (&'a (T, T), &'a U);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator for `IntervalMap<T, U>`.
+///
+/// This iterator traverses the elements of the map in-order and yields immutable references.
+pub struct IntervalMapIter<'a, T, U> {
+    current: &'a tree::Tree<T, U>,
+    stack: Vec<&'a Node<T, U>>,
+}
+
+impl<'a, T, U> Iterator for IntervalMapIter<'a, T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type Item = (&'a (T, T), &'a U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(ref node) = self.current {
+            self.current = &node.left;
+            self.stack.push(node);
+        }
+        self.stack.pop().map(|node| {
+            let Node {
+                entry: Entry { ref key, ref value },
+                ref right,
+                ..
+            } = node;
+            self.current = right;
+            (key, value)
+        })
+    }
+}
+
+/// An iterator over the intervals of an `IntervalMap<T, U>` that overlap a query interval.
+///
+/// This iterator traverses the elements of the map in-order, skipping subtrees that cannot contain
+/// an overlapping interval, and yields immutable references.
+pub struct IntervalMapOverlapIter<'a, T, U> {
+    low: T,
+    high: T,
+    stack: Vec<&'a Node<T, U>>,
+}
+
+impl<'a, T, U> IntervalMapOverlapIter<'a, T, U>
+where
+    T: 'a + Ord,
+    U: 'a,
+{
+    fn new(tree: &'a tree::Tree<T, U>, low: T, high: T) -> Self {
+        let mut ret = IntervalMapOverlapIter {
+            low,
+            high,
+            stack: Vec::new(),
+        };
+        ret.push_left(tree);
+        ret
+    }
+
+    fn push_left(&mut self, mut tree: &'a tree::Tree<T, U>) {
+        while let Some(ref node) = tree {
+            // no interval in this subtree ends at or after the query interval starts
+            if node.max < self.low {
+                break;
+            }
+            self.stack.push(node);
+            tree = &node.left;
+        }
+    }
+}
+
+impl<'a, T, U> Iterator for IntervalMapOverlapIter<'a, T, U>
+where
+    T: 'a + Ord,
+    U: 'a,
+{
+    type Item = (&'a (T, T), &'a U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            // all remaining intervals start after the query interval ends
+            if node.entry.key.0 > self.high {
+                self.stack.clear();
+                return None;
+            }
+            self.push_left(&node.right);
+            if node.entry.key.1 >= self.low {
+                return Some((&node.entry.key, &node.entry.value));
+            }
+        }
+        None
+    }
+}
+
+impl<T, U> Default for IntervalMap<T, U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, U> Index<&(T, T)> for IntervalMap<T, U>
+where
+    T: Ord,
+{
+    type Output = U;
+
+    fn index(&self, interval: &(T, T)) -> &Self::Output {
+        self.get(interval).expect("Error: key does not exist.")
+    }
+}
+
+impl<T, U> IndexMut<&(T, T)> for IntervalMap<T, U>
+where
+    T: Ord,
+{
+    fn index_mut(&mut self, interval: &(T, T)) -> &mut Self::Output {
+        self.get_mut(interval).expect("Error: key does not exist.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntervalMap;
+
+    fn check_max<T, U>(tree: &super::tree::Tree<T, U>) -> Option<T>
+    where
+        T: Clone + Ord,
+    {
+        tree.as_ref().map(|node| {
+            let mut expected = node.entry.key.1.clone();
+            for child_max in [check_max(&node.left), check_max(&node.right)]
+                .iter()
+                .flatten()
+            {
+                if *child_max > expected {
+                    expected = child_max.clone();
+                }
+            }
+            assert!(node.max == expected);
+            expected
+        })
+    }
+
+    #[test]
+    fn test_len_empty() {
+        let map: IntervalMap<u32, u32> = IntervalMap::new();
+        assert_eq!(map.len(), 0);
+    }
+
+    #[test]
+    fn test_is_empty() {
+        let map: IntervalMap<u32, u32> = IntervalMap::new();
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_min_max_empty() {
+        let map: IntervalMap<u32, u32> = IntervalMap::new();
+        assert_eq!(map.min(), None);
+        assert_eq!(map.max(), None);
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut map = IntervalMap::new();
+        assert_eq!(map.insert((1, 2), 1), None);
+        assert!(map.contains_key(&(1, 2)));
+        assert_eq!(map.get(&(1, 2)), Some(&1));
+    }
+
+    #[test]
+    fn test_insert_replace() {
+        let mut map = IntervalMap::new();
+        assert_eq!(map.insert((1, 2), 1), None);
+        assert_eq!(map.insert((1, 2), 3), Some(((1, 2), 1)));
+        assert_eq!(map.get(&(1, 2)), Some(&3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_invalid_interval() {
+        let mut map = IntervalMap::new();
+        map.insert((2, 1), 1);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut map = IntervalMap::new();
+        map.insert((1, 2), 1);
+        assert_eq!(map.remove(&(1, 2)), Some(((1, 2), 1)));
+        assert!(!map.contains_key(&(1, 2)));
+    }
+
+    #[test]
+    fn test_min_max() {
+        let mut map = IntervalMap::new();
+        map.insert((1, 5), 1);
+        map.insert((3, 3), 3);
+        map.insert((5, 6), 5);
+
+        assert_eq!(map.min(), Some(&(1, 5)));
+        assert_eq!(map.max(), Some(&(5, 6)));
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut map = IntervalMap::new();
+        map.insert((1, 2), 1);
+        {
+            let value = map.get_mut(&(1, 2));
+            *value.unwrap() = 3;
+        }
+        assert_eq!(map.get(&(1, 2)), Some(&3));
+    }
+
+    #[test]
+    fn test_find_overlapping() {
+        let mut map = IntervalMap::new();
+        map.insert((1, 10), 1);
+        map.insert((2, 3), 2);
+        map.insert((4, 6), 3);
+        map.insert((7, 7), 4);
+        map.insert((11, 12), 5);
+
+        assert_eq!(
+            map.find_overlapping(&5)
+                .collect::<Vec<(&(u32, u32), &u32)>>(),
+            vec![(&(1, 10), &1), (&(4, 6), &3)],
+        );
+        assert_eq!(
+            map.find_overlapping(&7)
+                .collect::<Vec<(&(u32, u32), &u32)>>(),
+            vec![(&(1, 10), &1), (&(7, 7), &4)],
+        );
+        assert_eq!(map.find_overlapping(&0).next(), None);
+        assert_eq!(map.find_overlapping(&13).next(), None);
+    }
+
+    #[test]
+    fn test_find_intersecting() {
+        let mut map = IntervalMap::new();
+        map.insert((1, 2), 1);
+        map.insert((3, 4), 2);
+        map.insert((5, 6), 3);
+        map.insert((7, 8), 4);
+
+        assert_eq!(
+            map.find_intersecting(&(2, 5))
+                .collect::<Vec<(&(u32, u32), &u32)>>(),
+            vec![(&(1, 2), &1), (&(3, 4), &2), (&(5, 6), &3)],
+        );
+        assert_eq!(map.find_intersecting(&(9, 10)).next(), None);
+    }
+
+    #[test]
+    fn test_augmented_max() {
+        let mut map = IntervalMap::new();
+        for i in 0..100u32 {
+            map.insert((i, i + (i * 7) % 13), i);
+            check_max(&map.tree);
+        }
+        for i in (0..100u32).step_by(3) {
+            map.remove(&(i, i + (i * 7) % 13));
+            check_max(&map.tree);
+        }
+
+        for point in 0..120 {
+            let expected: Vec<_> = map
+                .iter()
+                .filter(|(interval, _)| interval.0 <= point && point <= interval.1)
+                .collect();
+            let actual: Vec<_> = map.find_overlapping(&point).collect();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut map = IntervalMap::new();
+        map.insert((1, 2), 2);
+        map.insert((5, 6), 6);
+        map.insert((3, 4), 4);
+
+        assert_eq!(
+            map.iter().collect::<Vec<(&(u32, u32), &u64)>>(),
+            vec![(&(1, 2), &2), (&(3, 4), &4), (&(5, 6), &6)],
+        );
+    }
+}
@@ -0,0 +1,8 @@
+//! Self-balancing binary search tree augmented to efficiently find all intervals that overlap a
+//! given point or interval.
+
+mod map;
+mod node;
+mod tree;
+
+pub use self::map::{IntervalMap, IntervalMapIter, IntervalMapOverlapIter};
@@ -0,0 +1,77 @@
+use crate::entry::Entry;
+use crate::interval_tree::tree;
+use std::cmp;
+use std::mem;
+
+/// A struct representing an internal node of an interval tree.
+pub struct Node<T, U> {
+    pub entry: Entry<(T, T), U>,
+    pub max: T,
+    pub height: usize,
+    pub left: tree::Tree<T, U>,
+    pub right: tree::Tree<T, U>,
+}
+
+impl<T, U> Node<T, U>
+where
+    T: Clone + Ord,
+{
+    pub fn new(interval: (T, T), value: U) -> Self {
+        let max = interval.1.clone();
+        Node {
+            entry: Entry {
+                key: interval,
+                value,
+            },
+            max,
+            height: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    pub fn update(&mut self) {
+        let Node {
+            ref entry,
+            ref mut max,
+            ref mut height,
+            ref left,
+            ref right,
+        } = self;
+        *height = cmp::max(tree::height(left), tree::height(right)) + 1;
+        *max = entry.key.1.clone();
+        for child in left.iter().chain(right.iter()) {
+            if child.max > *max {
+                *max = child.max.clone();
+            }
+        }
+    }
+
+    pub fn balance(&self) -> i32 {
+        (tree::height(&self.left) as i32) - (tree::height(&self.right) as i32)
+    }
+
+    pub fn rotate_left(&mut self) {
+        let mut child = self
+            .right
+            .take()
+            .expect("Expected right child node to be `Some`.");
+        self.right = child.left.take();
+        mem::swap(&mut *child, self);
+        child.update();
+        self.left = Some(child);
+        self.update();
+    }
+
+    pub fn rotate_right(&mut self) {
+        let mut child = self
+            .left
+            .take()
+            .expect("Expected left child node to be `Some`.");
+        self.left = child.right.take();
+        mem::swap(&mut *child, self);
+        child.update();
+        self.right = Some(child);
+        self.update();
+    }
+}
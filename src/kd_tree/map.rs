@@ -0,0 +1,720 @@
+use crate::entry::Entry;
+use crate::kd_tree::node::Node;
+use crate::kd_tree::point::Point;
+use crate::kd_tree::tree;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Index, IndexMut};
+
+/// A map of points to values implemented using a k-d tree.
+///
+/// A k-d tree is a binary search tree where each level of the tree splits the points along a
+/// different axis. The axes are cycled through in order, so the children of a node at depth `d`
+/// are split along the axis `d % k` of a `k`-dimensional point. Points in the left subtree of a
+/// node have a smaller coordinate along the splitting axis than the node, points in the right
+/// subtree have a larger coordinate, and ties are broken by comparing the subsequent axes. A
+/// subtree is rebuilt around its median point when one of its children contains more than three
+/// quarters of its points, which keeps the height of the tree logarithmic in the number of points.
+///
+/// Nearest neighbor and axis-aligned range queries skip subtrees that are on the far side of a
+/// splitting plane from the query.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::kd_tree::KdMap;
+///
+/// let mut map = KdMap::new();
+/// map.insert([0.0, 0.0], "origin");
+/// map.insert([3.0, 4.0], "a");
+/// map.insert([-1.0, 2.0], "b");
+///
+/// assert_eq!(map[&[0.0, 0.0]], "origin");
+/// assert_eq!(map.get(&[1.0, 1.0]), None);
+/// assert_eq!(map.len(), 3);
+///
+/// assert_eq!(
+///     map.nearest_neighbor(&[2.5, 3.0], 1),
+///     vec![(&[3.0, 4.0], &"a")],
+/// );
+/// assert_eq!(
+///     map.range(&[-2.0, 1.0], &[4.0, 5.0]).count(),
+///     2,
+/// );
+///
+/// map[&[0.0, 0.0]] = "c";
+/// assert_eq!(map.remove(&[0.0, 0.0]), Some(([0.0, 0.0], "c")));
+/// assert_eq!(map.remove(&[0.0, 0.0]), None);
+/// ```
+pub struct KdMap<P, U> {
+    tree: tree::Tree<P, U>,
+}
+
+impl<P, U> KdMap<P, U> {
+    /// Constructs a new, empty `KdMap<P, U>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::kd_tree::KdMap;
+    ///
+    /// let map: KdMap<[f64; 2], u32> = KdMap::new();
+    /// ```
+    pub fn new() -> Self {
+        KdMap { tree: None }
+    }
+
+    /// Inserts a point-value pair into the map. If the point already exists in the map, it will
+    /// return and replace the old point-value pair. Two points are the same if all of their
+    /// coordinates are equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::kd_tree::KdMap;
+    ///
+    /// let mut map = KdMap::new();
+    /// assert_eq!(map.insert([1, 2], 1), None);
+    /// assert_eq!(map.get(&[1, 2]), Some(&1));
+    /// assert_eq!(map.insert([1, 2], 2), Some(([1, 2], 1)));
+    /// assert_eq!(map.get(&[1, 2]), Some(&2));
+    /// ```
+    pub fn insert(&mut self, key: P, value: U) -> Option<(P, U)>
+    where
+        P: Point,
+    {
+        tree::insert(&mut self.tree, Entry { key, value }, 0).map(|entry| (entry.key, entry.value))
+    }
+
+    /// Removes a point from the map. If the point exists in the map, it will return the
+    /// associated point-value pair. Otherwise it will return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::kd_tree::KdMap;
+    ///
+    /// let mut map = KdMap::new();
+    /// map.insert([1, 2], 1);
+    /// assert_eq!(map.remove(&[1, 2]), Some(([1, 2], 1)));
+    /// assert_eq!(map.remove(&[1, 2]), None);
+    /// ```
+    pub fn remove(&mut self, key: &P) -> Option<(P, U)>
+    where
+        P: Point,
+    {
+        tree::remove(&mut self.tree, key, 0).map(|entry| (entry.key, entry.value))
+    }
+
+    /// Checks if a point exists in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::kd_tree::KdMap;
+    ///
+    /// let mut map = KdMap::new();
+    /// map.insert([1, 2], 1);
+    /// assert!(!map.contains_key(&[0, 0]));
+    /// assert!(map.contains_key(&[1, 2]));
+    /// ```
+    pub fn contains_key(&self, key: &P) -> bool
+    where
+        P: Point,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Returns an immutable reference to the value associated with a particular point. It will
+    /// return `None` if the point does not exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::kd_tree::KdMap;
+    ///
+    /// let mut map = KdMap::new();
+    /// map.insert([1, 2], 1);
+    /// assert_eq!(map.get(&[0, 0]), None);
+    /// assert_eq!(map.get(&[1, 2]), Some(&1));
+    /// ```
+    pub fn get(&self, key: &P) -> Option<&U>
+    where
+        P: Point,
+    {
+        tree::get(&self.tree, key).map(|entry| &entry.value)
+    }
+
+    /// Returns a mutable reference to the value associated with a particular point. Returns `None`
+    /// if such a point does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::kd_tree::KdMap;
+    ///
+    /// let mut map = KdMap::new();
+    /// map.insert([1, 2], 1);
+    /// *map.get_mut(&[1, 2]).unwrap() = 2;
+    /// assert_eq!(map.get(&[1, 2]), Some(&2));
+    /// ```
+    pub fn get_mut(&mut self, key: &P) -> Option<&mut U>
+    where
+        P: Point,
+    {
+        tree::get_mut(&mut self.tree, key).map(|entry| &mut entry.value)
+    }
+
+    /// Returns the number of elements in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::kd_tree::KdMap;
+    ///
+    /// let mut map = KdMap::new();
+    /// map.insert([1, 2], 1);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.tree.as_ref().map_or(0, |node| node.size)
+    }
+
+    /// Returns `true` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::kd_tree::KdMap;
+    ///
+    /// let map: KdMap<[u32; 2], u32> = KdMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_none()
+    }
+
+    /// Clears the map, removing all values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::kd_tree::KdMap;
+    ///
+    /// let mut map = KdMap::new();
+    /// map.insert([1, 2], 1);
+    /// map.insert([3, 4], 2);
+    /// map.clear();
+    /// assert_eq!(map.is_empty(), true);
+    /// ```
+    pub fn clear(&mut self) {
+        self.tree = None;
+    }
+
+    /// Returns the `k` point-value pairs whose points are closest to `key` by Euclidean distance,
+    /// ordered from nearest to farthest. Fewer than `k` pairs are returned if the map contains
+    /// fewer than `k` points. Points that are equally distant from `key` are returned in an
+    /// arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::kd_tree::KdMap;
+    ///
+    /// let mut map = KdMap::new();
+    /// map.insert([0, 0], 1);
+    /// map.insert([5, 5], 2);
+    /// map.insert([1, 2], 3);
+    ///
+    /// assert_eq!(
+    ///     map.nearest_neighbor(&[2, 2], 2),
+    ///     vec![(&[1, 2], &3), (&[0, 0], &1)],
+    /// );
+    /// assert_eq!(map.nearest_neighbor(&[2, 2], 5).len(), 3);
+    /// ```
+    pub fn nearest_neighbor(&self, key: &P, k: usize) -> Vec<(&P, &U)>
+    where
+        P: Point,
+    {
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        tree::nearest_neighbor(&self.tree, key, k, 0, &mut heap);
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|neighbor| (&neighbor.entry.key, &neighbor.entry.value))
+            .collect()
+    }
+
+    /// Returns an iterator over the point-value pairs whose points are within the axis-aligned
+    /// box with corners `lower` and `upper`, inclusive. A point is within the box if each of its
+    /// coordinates is between the corresponding coordinates of `lower` and `upper`. The iterator
+    /// yields the pairs in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::kd_tree::KdMap;
+    ///
+    /// let mut map = KdMap::new();
+    /// map.insert([0, 0], 1);
+    /// map.insert([5, 5], 2);
+    /// map.insert([1, 2], 3);
+    ///
+    /// let mut points = map.range(&[0, 0], &[2, 5]).map(|(key, _)| *key).collect::<Vec<_>>();
+    /// points.sort();
+    /// assert_eq!(points, vec![[0, 0], [1, 2]]);
+    /// ```
+    pub fn range(&self, lower: &P, upper: &P) -> KdMapRangeIter<'_, P, U>
+    where
+        P: Point,
+    {
+        KdMapRangeIter {
+            lower: (0..P::DIMENSIONS)
+                .map(|axis| lower.coordinate(axis))
+                .collect(),
+            upper: (0..P::DIMENSIONS)
+                .map(|axis| upper.coordinate(axis))
+                .collect(),
+            stack: self.tree.iter().map(|node| (&**node, 0)).collect(),
+        }
+    }
+
+    /// Returns an iterator over the map. The iterator will yield point-value pairs in no
+    /// particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::kd_tree::KdMap;
+    ///
+    /// let mut map = KdMap::new();
+    /// map.insert([1, 2], 1);
+    /// map.insert([3, 4], 2);
+    ///
+    /// let mut pairs = map.iter().map(|(key, value)| (*key, *value)).collect::<Vec<_>>();
+    /// pairs.sort();
+    /// assert_eq!(pairs, vec![([1, 2], 1), ([3, 4], 2)]);
+    /// ```
+    pub fn iter(&self) -> KdMapIter<'_, P, U> {
+        KdMapIter {
+            stack: self.tree.iter().map(|node| &**node).collect(),
+        }
+    }
+}
+
+impl<P, U> IntoIterator for KdMap<P, U> {
+    type IntoIter = KdMapIntoIter<P, U>;
+    type Item = (P, U);
+
+    fn into_iter(self) -> Self::IntoIter {
+        KdMapIntoIter {
+            stack: self.tree.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a, P, U> IntoIterator for &'a KdMap<P, U>
+where
+    P: 'a,
+    U: 'a,
+{
+    type IntoIter = KdMapIter<'a, P, U>;
+    type Item = (&'a P, &'a U);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An owning iterator for `KdMap<P, U>`.
+///
+/// This iterator traverses the elements of the map in pre-order and yields owned entries.
+pub struct KdMapIntoIter<P, U> {
+    stack: Vec<Box<Node<P, U>>>,
+}
+
+impl<P, U> Iterator for KdMapIntoIter<P, U> {
+    type Item = (P, U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.stack.pop().map(|node| {
+            let Node {
+                entry: Entry { key, value },
+                left,
+                right,
+                ..
+            } = *node;
+            self.stack.extend(right);
+            self.stack.extend(left);
+            (key, value)
+        })
+    }
+}
+
+/// An iterator for `KdMap<P, U>`.
+///
+/// This iterator traverses the elements of the map in pre-order and yields immutable references.
+pub struct KdMapIter<'a, P, U> {
+    stack: Vec<&'a Node<P, U>>,
+}
+
+impl<'a, P, U> Iterator for KdMapIter<'a, P, U>
+where
+    P: 'a,
+    U: 'a,
+{
+    type Item = (&'a P, &'a U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.stack.pop().map(|node| {
+            self.stack.extend(node.right.as_deref());
+            self.stack.extend(node.left.as_deref());
+            (&node.entry.key, &node.entry.value)
+        })
+    }
+}
+
+/// An iterator over the points of a `KdMap<P, U>` that are within an axis-aligned box.
+///
+/// This iterator traverses the elements of the map in pre-order, skipping subtrees that are
+/// outside of the box, and yields immutable references.
+pub struct KdMapRangeIter<'a, P, U>
+where
+    P: Point,
+{
+    lower: Vec<P::Coordinate>,
+    upper: Vec<P::Coordinate>,
+    stack: Vec<(&'a Node<P, U>, usize)>,
+}
+
+impl<'a, P, U> KdMapRangeIter<'a, P, U>
+where
+    P: Point,
+{
+    fn contains(&self, key: &P) -> bool {
+        (0..P::DIMENSIONS).all(|axis| {
+            let coordinate = key.coordinate(axis);
+            self.lower[axis] <= coordinate && coordinate <= self.upper[axis]
+        })
+    }
+}
+
+impl<'a, P, U> Iterator for KdMapRangeIter<'a, P, U>
+where
+    P: 'a + Point,
+    U: 'a,
+{
+    type Item = (&'a P, &'a U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, depth)) = self.stack.pop() {
+            let axis = depth % P::DIMENSIONS;
+            let coordinate = node.entry.key.coordinate(axis);
+            // the right subtree only contains points with coordinates at least `coordinate`
+            if let Some(ref right) = node.right {
+                if coordinate <= self.upper[axis] {
+                    self.stack.push((right, depth + 1));
+                }
+            }
+            // the left subtree only contains points with coordinates at most `coordinate`
+            if let Some(ref left) = node.left {
+                if self.lower[axis] <= coordinate {
+                    self.stack.push((left, depth + 1));
+                }
+            }
+            if self.contains(&node.entry.key) {
+                return Some((&node.entry.key, &node.entry.value));
+            }
+        }
+        None
+    }
+}
+
+impl<P, U> Default for KdMap<P, U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P, U> fmt::Debug for KdMap<P, U>
+where
+    P: fmt::Debug,
+    U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<P, U> FromIterator<(P, U)> for KdMap<P, U>
+where
+    P: Point,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (P, U)>,
+    {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<P, U> Extend<(P, U)> for KdMap<P, U>
+where
+    P: Point,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (P, U)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<P, U> Index<&P> for KdMap<P, U>
+where
+    P: Point,
+{
+    type Output = U;
+
+    fn index(&self, key: &P) -> &Self::Output {
+        self.get(key).expect("Error: key does not exist.")
+    }
+}
+
+impl<P, U> IndexMut<&P> for KdMap<P, U>
+where
+    P: Point,
+{
+    fn index_mut(&mut self, key: &P) -> &mut Self::Output {
+        self.get_mut(key).expect("Error: key does not exist.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KdMap;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    fn check_tree(map: &KdMap<[i32; 3], u32>) {
+        fn check<'a>(
+            tree: &'a super::tree::Tree<[i32; 3], u32>,
+            depth: usize,
+            points: &mut Vec<&'a [i32; 3]>,
+        ) -> usize {
+            match tree {
+                Some(ref node) => {
+                    let axis = depth % 3;
+                    let mut left_points = Vec::new();
+                    let mut right_points = Vec::new();
+                    let left_size = check(&node.left, depth + 1, &mut left_points);
+                    let right_size = check(&node.right, depth + 1, &mut right_points);
+                    let superkey = |point: &[i32; 3]| {
+                        (point[axis], point[(axis + 1) % 3], point[(axis + 2) % 3])
+                    };
+                    assert!(left_points
+                        .iter()
+                        .all(|point| superkey(point) < superkey(&node.entry.key)));
+                    assert!(right_points
+                        .iter()
+                        .all(|point| superkey(point) > superkey(&node.entry.key)));
+                    assert_eq!(node.size, left_size + right_size + 1);
+                    assert!(left_size.max(right_size) * 4 <= node.size * 3);
+                    points.push(&node.entry.key);
+                    points.extend(left_points);
+                    points.extend(right_points);
+                    node.size
+                }
+                None => 0,
+            }
+        }
+        let mut points = Vec::new();
+        assert_eq!(check(&map.tree, 0, &mut points), map.len());
+    }
+
+    #[test]
+    fn test_len_empty() {
+        let map: KdMap<[u32; 2], u32> = KdMap::new();
+        assert_eq!(map.len(), 0);
+    }
+
+    #[test]
+    fn test_is_empty() {
+        let map: KdMap<[u32; 2], u32> = KdMap::new();
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut map = KdMap::new();
+        assert_eq!(map.insert([1, 1], 1), None);
+        assert!(map.contains_key(&[1, 1]));
+        assert_eq!(map.get(&[1, 1]), Some(&1));
+    }
+
+    #[test]
+    fn test_insert_replace() {
+        let mut map = KdMap::new();
+        assert_eq!(map.insert([1, 1], 1), None);
+        assert_eq!(map.insert([1, 1], 3), Some(([1, 1], 1)));
+        assert_eq!(map.get(&[1, 1]), Some(&3));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut map = KdMap::new();
+        map.insert([1, 1], 1);
+        map.insert([1, 2], 2);
+        map.insert([0, 2], 3);
+        assert_eq!(map.remove(&[1, 1]), Some(([1, 1], 1)));
+        assert!(!map.contains_key(&[1, 1]));
+        assert_eq!(map.get(&[1, 2]), Some(&2));
+        assert_eq!(map.get(&[0, 2]), Some(&3));
+        assert_eq!(map.remove(&[1, 1]), None);
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut map = KdMap::new();
+        map.insert([1, 1], 1);
+        {
+            let value = map.get_mut(&[1, 1]);
+            *value.unwrap() = 3;
+        }
+        assert_eq!(map.get(&[1, 1]), Some(&3));
+    }
+
+    #[test]
+    fn test_nearest_neighbor() {
+        let mut map = KdMap::new();
+        map.insert([0.0, 0.0], 0);
+        map.insert([1.0, 0.0], 1);
+        map.insert([0.0, 3.0], 2);
+        map.insert([-4.0, -4.0], 3);
+
+        assert_eq!(map.nearest_neighbor(&[0.2, 0.1], 0), vec![]);
+        assert_eq!(
+            map.nearest_neighbor(&[0.9, 0.1], 2),
+            vec![(&[1.0, 0.0], &1), (&[0.0, 0.0], &0)],
+        );
+        assert_eq!(map.nearest_neighbor(&[0.0, 0.0], 10).len(), 4);
+        assert_eq!(
+            map.nearest_neighbor(&[-3.0, -3.0], 1),
+            vec![(&[-4.0, -4.0], &3)],
+        );
+    }
+
+    #[test]
+    fn test_range() {
+        let mut map = KdMap::new();
+        map.insert([0, 0], 0);
+        map.insert([1, 0], 1);
+        map.insert([0, 3], 2);
+        map.insert([-4, -4], 3);
+
+        let mut values = map
+            .range(&[0, 0], &[1, 3])
+            .map(|(_, value)| *value)
+            .collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, vec![0, 1, 2]);
+        assert_eq!(map.range(&[2, 2], &[5, 5]).count(), 0);
+        assert_eq!(map.range(&[-4, -4], &[-4, -4]).count(), 1);
+    }
+
+    #[test]
+    fn test_iter() {
+        let map: KdMap<[u32; 2], u32> = (0..10).map(|index| ([index, 9 - index], index)).collect();
+        let mut values = map.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, (0..10).collect::<Vec<_>>());
+
+        let mut values = map.into_iter().map(|(_, value)| value).collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng: XorShiftRng = SeedableRng::from_seed([1, 1, 1, 1]);
+        let mut map = KdMap::new();
+        let mut expected = Vec::new();
+
+        for _ in 0..2000 {
+            let key = [
+                rng.gen_range(-20, 20),
+                rng.gen_range(-20, 20),
+                rng.gen_range(-20, 20),
+            ];
+            let value = rng.gen::<u32>();
+            if rng.gen::<bool>() {
+                let index = expected.iter().position(|&(point, _)| point == key);
+                let old = index.map(|index| expected.swap_remove(index));
+                expected.push((key, value));
+                assert_eq!(map.insert(key, value), old);
+            } else {
+                let index = expected.iter().position(|&(point, _)| point == key);
+                let old = index.map(|index| expected.swap_remove(index));
+                assert_eq!(map.remove(&key), old);
+            }
+            assert_eq!(map.len(), expected.len());
+        }
+        check_tree(&map);
+
+        for _ in 0..100 {
+            let query = [
+                rng.gen_range(-25, 25),
+                rng.gen_range(-25, 25),
+                rng.gen_range(-25, 25),
+            ];
+            let distance = |point: &[i32; 3]| {
+                (0..3)
+                    .map(|axis| (point[axis] - query[axis]).pow(2))
+                    .sum::<i32>()
+            };
+            let mut distances = expected
+                .iter()
+                .map(|(point, _)| distance(point))
+                .collect::<Vec<_>>();
+            distances.sort();
+            let neighbors = map.nearest_neighbor(&query, 5);
+            assert_eq!(
+                neighbors
+                    .iter()
+                    .map(|(point, _)| distance(point))
+                    .collect::<Vec<_>>(),
+                distances[..5].to_vec(),
+            );
+
+            let upper = [query[0] + 5, query[1] + 8, query[2] + 3];
+            let mut actual = map
+                .range(&query, &upper)
+                .map(|(point, value)| (*point, *value))
+                .collect::<Vec<_>>();
+            let mut in_range = expected
+                .iter()
+                .filter(|(point, _)| {
+                    (0..3).all(|axis| query[axis] <= point[axis] && point[axis] <= upper[axis])
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            actual.sort();
+            in_range.sort();
+            assert_eq!(actual, in_range);
+        }
+
+        for (key, value) in expected.clone() {
+            assert_eq!(map.remove(&key), Some((key, value)));
+            check_tree(&map);
+        }
+        assert!(map.is_empty());
+    }
+}
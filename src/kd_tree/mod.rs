@@ -0,0 +1,10 @@
+//! k-d tree that partitions fixed-dimension points to efficiently answer nearest neighbor and
+//! axis-aligned range queries.
+
+mod map;
+mod node;
+mod point;
+mod tree;
+
+pub use self::map::{KdMap, KdMapIntoIter, KdMapIter, KdMapRangeIter};
+pub use self::point::Point;
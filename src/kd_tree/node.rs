@@ -0,0 +1,35 @@
+use crate::entry::Entry;
+use crate::kd_tree::tree::Tree;
+
+pub struct Node<P, U> {
+    pub entry: Entry<P, U>,
+    // The number of entries in the subtree rooted at this node.
+    pub size: usize,
+    pub left: Tree<P, U>,
+    pub right: Tree<P, U>,
+}
+
+impl<P, U> Node<P, U> {
+    pub fn new(key: P, value: U) -> Self {
+        Node {
+            entry: Entry { key, value },
+            size: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    pub fn update(&mut self) {
+        let Node {
+            ref mut size,
+            ref left,
+            ref right,
+            ..
+        } = self;
+        *size = 1 + tree_size(left) + tree_size(right);
+    }
+}
+
+pub fn tree_size<P, U>(tree: &Tree<P, U>) -> usize {
+    tree.as_ref().map_or(0, |node| node.size)
+}
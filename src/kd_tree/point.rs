@@ -0,0 +1,98 @@
+use std::cmp::Ordering;
+
+/// A point with a fixed number of dimensions that can be used as the key of a k-d tree.
+///
+/// Distances between points are the Euclidean distances between their coordinates, which are
+/// converted to `f64`.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::kd_tree::{KdMap, Point};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Pixel {
+///     x: u16,
+///     y: u16,
+/// }
+///
+/// impl Point for Pixel {
+///     type Coordinate = u16;
+///     const DIMENSIONS: usize = 2;
+///
+///     fn coordinate(&self, axis: usize) -> u16 {
+///         if axis == 0 { self.x } else { self.y }
+///     }
+/// }
+///
+/// let mut map = KdMap::new();
+/// map.insert(Pixel { x: 0, y: 0 }, "black");
+/// map.insert(Pixel { x: 10, y: 10 }, "white");
+///
+/// assert_eq!(
+///     map.nearest_neighbor(&Pixel { x: 2, y: 3 }, 1),
+///     vec![(&Pixel { x: 0, y: 0 }, &"black")],
+/// );
+/// ```
+pub trait Point {
+    /// The type of the coordinates of the point.
+    type Coordinate: Copy + PartialOrd + Into<f64>;
+
+    /// The number of dimensions of the point. It must be greater than zero.
+    const DIMENSIONS: usize;
+
+    /// Returns the coordinate of the point along `axis`, where `axis` is less than `DIMENSIONS`.
+    fn coordinate(&self, axis: usize) -> Self::Coordinate;
+}
+
+impl<T, const N: usize> Point for [T; N]
+where
+    T: Copy + PartialOrd + Into<f64>,
+{
+    type Coordinate = T;
+
+    const DIMENSIONS: usize = N;
+
+    fn coordinate(&self, axis: usize) -> T {
+        self[axis]
+    }
+}
+
+// Compares two points by their coordinates, starting from `axis` and cycling through the rest of
+// the axes to break ties. Distinct points are never equal under this ordering, so every point has
+// a unique position in a k-d tree.
+pub fn cmp_from_axis<P>(left: &P, right: &P, axis: usize) -> Ordering
+where
+    P: Point,
+{
+    for offset in 0..P::DIMENSIONS {
+        let axis = (axis + offset) % P::DIMENSIONS;
+        match left.coordinate(axis).partial_cmp(&right.coordinate(axis)) {
+            Some(Ordering::Less) => return Ordering::Less,
+            Some(Ordering::Greater) => return Ordering::Greater,
+            _ => {}
+        }
+    }
+    Ordering::Equal
+}
+
+// Returns the difference between two coordinates along an axis.
+pub fn axis_distance<P>(left: &P, right: &P, axis: usize) -> f64
+where
+    P: Point,
+{
+    left.coordinate(axis).into() - right.coordinate(axis).into()
+}
+
+// Returns the squared Euclidean distance between two points.
+pub fn distance_squared<P>(left: &P, right: &P) -> f64
+where
+    P: Point,
+{
+    (0..P::DIMENSIONS)
+        .map(|axis| {
+            let distance = axis_distance(left, right, axis);
+            distance * distance
+        })
+        .sum()
+}
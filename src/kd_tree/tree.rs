@@ -0,0 +1,314 @@
+use crate::entry::Entry;
+use crate::kd_tree::node::{tree_size, Node};
+use crate::kd_tree::point::{self, Point};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::mem;
+
+pub type Tree<P, U> = Option<Box<Node<P, U>>>;
+
+fn split_axis<P>(depth: usize) -> usize
+where
+    P: Point,
+{
+    depth % P::DIMENSIONS
+}
+
+// Builds a balanced tree by splitting the entries at their median along the axis of each level.
+pub fn build<P, U>(mut entries: Vec<Entry<P, U>>, depth: usize) -> Tree<P, U>
+where
+    P: Point,
+{
+    if entries.is_empty() {
+        return None;
+    }
+    let axis = split_axis::<P>(depth);
+    let mid = entries.len() / 2;
+    entries.select_nth_unstable_by(mid, |left, right| {
+        point::cmp_from_axis(&left.key, &right.key, axis)
+    });
+    let right_entries = entries.split_off(mid + 1);
+    let entry = entries.pop().expect("Expected non-empty entries.");
+    let mut node = Node {
+        entry,
+        size: 1,
+        left: build(entries, depth + 1),
+        right: build(right_entries, depth + 1),
+    };
+    node.update();
+    Some(Box::new(node))
+}
+
+fn collect<P, U>(tree: Tree<P, U>, entries: &mut Vec<Entry<P, U>>) {
+    if let Some(node) = tree {
+        let Node {
+            entry, left, right, ..
+        } = *node;
+        collect(left, entries);
+        entries.push(entry);
+        collect(right, entries);
+    }
+}
+
+// Rebuilds the subtree if one of its children contains more than three quarters of its entries,
+// which bounds the height of the tree to be logarithmic in its size.
+fn rebalance<P, U>(tree: &mut Tree<P, U>, depth: usize)
+where
+    P: Point,
+{
+    let is_unbalanced = match tree {
+        Some(ref node) => {
+            let max_child_size = tree_size(&node.left).max(tree_size(&node.right));
+            max_child_size * 4 > node.size * 3
+        }
+        None => false,
+    };
+    if is_unbalanced {
+        let mut entries = Vec::with_capacity(tree_size(tree));
+        collect(tree.take(), &mut entries);
+        *tree = build(entries, depth);
+    }
+}
+
+pub fn insert<P, U>(tree: &mut Tree<P, U>, entry: Entry<P, U>, depth: usize) -> Option<Entry<P, U>>
+where
+    P: Point,
+{
+    let ret = match tree {
+        Some(ref mut node) => {
+            let ret =
+                match point::cmp_from_axis(&entry.key, &node.entry.key, split_axis::<P>(depth)) {
+                    Ordering::Less => insert(&mut node.left, entry, depth + 1),
+                    Ordering::Greater => insert(&mut node.right, entry, depth + 1),
+                    Ordering::Equal => return Some(mem::replace(&mut node.entry, entry)),
+                };
+            if ret.is_none() {
+                node.size += 1;
+            }
+            ret
+        }
+        None => {
+            *tree = Some(Box::new(Node::new(entry.key, entry.value)));
+            return None;
+        }
+    };
+    if ret.is_none() {
+        rebalance(tree, depth);
+    }
+    ret
+}
+
+// Returns the minimum point in the tree when compared starting from `axis` and the reversed path
+// to it, where `false` denotes the left child and `true` denotes the right child.
+fn min_path<P, U>(tree: &Tree<P, U>, axis: usize, depth: usize) -> Option<(&P, Vec<bool>)>
+where
+    P: Point,
+{
+    tree.as_ref().map(|node| {
+        let mut ret = (&node.entry.key, Vec::new());
+        let left_min = min_path(&node.left, axis, depth + 1).map(|(min, mut path)| {
+            path.push(false);
+            (min, path)
+        });
+        // the right subtree cannot contain a smaller point if it is split along `axis`
+        let right_min = if split_axis::<P>(depth) == axis {
+            None
+        } else {
+            min_path(&node.right, axis, depth + 1).map(|(min, mut path)| {
+                path.push(true);
+                (min, path)
+            })
+        };
+        for child_min in left_min.into_iter().chain(right_min) {
+            if point::cmp_from_axis(child_min.0, ret.0, axis) == Ordering::Less {
+                ret = child_min;
+            }
+        }
+        ret
+    })
+}
+
+// Removes the entry at the end of the reversed path.
+fn remove_path<P, U>(tree: &mut Tree<P, U>, path: &mut Vec<bool>, depth: usize) -> Entry<P, U>
+where
+    P: Point,
+{
+    let ret = match path.pop() {
+        Some(is_right) => {
+            let node = tree.as_mut().expect("Expected non-empty tree.");
+            let ret = if is_right {
+                remove_path(&mut node.right, path, depth + 1)
+            } else {
+                remove_path(&mut node.left, path, depth + 1)
+            };
+            node.size -= 1;
+            ret
+        }
+        None => return remove_root(tree, depth),
+    };
+    rebalance(tree, depth);
+    ret
+}
+
+// Removes the root of the tree by replacing it with the minimum entry of the right subtree when
+// compared starting from the axis of the root. If the right subtree is empty, the minimum entry of
+// the left subtree is used and the left subtree becomes the right subtree.
+fn remove_root<P, U>(tree: &mut Tree<P, U>, depth: usize) -> Entry<P, U>
+where
+    P: Point,
+{
+    let axis = split_axis::<P>(depth);
+    let ret = {
+        let node = tree.as_mut().expect("Expected non-empty tree.");
+        if let Some((_, mut path)) = min_path(&node.right, axis, depth + 1) {
+            let entry = remove_path(&mut node.right, &mut path, depth + 1);
+            node.size -= 1;
+            Some(mem::replace(&mut node.entry, entry))
+        } else if let Some((_, mut path)) = min_path(&node.left, axis, depth + 1) {
+            let entry = remove_path(&mut node.left, &mut path, depth + 1);
+            node.right = node.left.take();
+            node.size -= 1;
+            Some(mem::replace(&mut node.entry, entry))
+        } else {
+            None
+        }
+    };
+    match ret {
+        Some(entry) => {
+            rebalance(tree, depth);
+            entry
+        }
+        None => tree.take().expect("Expected non-empty tree.").entry,
+    }
+}
+
+pub fn remove<P, U>(tree: &mut Tree<P, U>, key: &P, depth: usize) -> Option<Entry<P, U>>
+where
+    P: Point,
+{
+    let ret = match tree {
+        Some(ref mut node) => {
+            let ret = match point::cmp_from_axis(key, &node.entry.key, split_axis::<P>(depth)) {
+                Ordering::Less => remove(&mut node.left, key, depth + 1),
+                Ordering::Greater => remove(&mut node.right, key, depth + 1),
+                Ordering::Equal => return Some(remove_root(tree, depth)),
+            };
+            if ret.is_some() {
+                node.size -= 1;
+            }
+            ret
+        }
+        None => return None,
+    };
+    if ret.is_some() {
+        rebalance(tree, depth);
+    }
+    ret
+}
+
+pub fn get<'a, P, U>(tree: &'a Tree<P, U>, key: &P) -> Option<&'a Entry<P, U>>
+where
+    P: Point,
+{
+    let mut curr = tree;
+    let mut depth = 0;
+    while let Some(ref node) = curr {
+        match point::cmp_from_axis(key, &node.entry.key, split_axis::<P>(depth)) {
+            Ordering::Less => curr = &node.left,
+            Ordering::Greater => curr = &node.right,
+            Ordering::Equal => return Some(&node.entry),
+        }
+        depth += 1;
+    }
+    None
+}
+
+pub fn get_mut<'a, P, U>(tree: &'a mut Tree<P, U>, key: &P) -> Option<&'a mut Entry<P, U>>
+where
+    P: Point,
+{
+    let mut curr = tree;
+    let mut depth = 0;
+    while let Some(node) = curr {
+        match point::cmp_from_axis(key, &node.entry.key, split_axis::<P>(depth)) {
+            Ordering::Less => curr = &mut node.left,
+            Ordering::Greater => curr = &mut node.right,
+            Ordering::Equal => return Some(&mut node.entry),
+        }
+        depth += 1;
+    }
+    None
+}
+
+// A candidate for the nearest neighbors of a point, ordered by its distance to the point.
+pub struct Neighbor<'a, P, U> {
+    pub distance: f64,
+    pub entry: &'a Entry<P, U>,
+}
+
+impl<'a, P, U> PartialEq for Neighbor<'a, P, U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<'a, P, U> Eq for Neighbor<'a, P, U> {}
+
+impl<'a, P, U> PartialOrd for Neighbor<'a, P, U> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, P, U> Ord for Neighbor<'a, P, U> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .partial_cmp(&other.distance)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+// Maintains the `k` entries closest to `key` in a max-heap, visiting the child on the same side
+// as `key` first and skipping the other child if the splitting plane is farther than the current
+// `k`-th nearest entry.
+pub fn nearest_neighbor<'a, P, U>(
+    tree: &'a Tree<P, U>,
+    key: &P,
+    k: usize,
+    depth: usize,
+    heap: &mut BinaryHeap<Neighbor<'a, P, U>>,
+) where
+    P: Point,
+{
+    if let Some(ref node) = tree {
+        let distance = point::distance_squared(&node.entry.key, key);
+        if heap.len() < k {
+            heap.push(Neighbor {
+                distance,
+                entry: &node.entry,
+            });
+        } else if heap.peek().is_some_and(|max| distance < max.distance) {
+            heap.pop();
+            heap.push(Neighbor {
+                distance,
+                entry: &node.entry,
+            });
+        }
+
+        let axis = split_axis::<P>(depth);
+        let axis_distance = point::axis_distance(key, &node.entry.key, axis);
+        let (near, far) = if point::cmp_from_axis(key, &node.entry.key, axis) == Ordering::Less {
+            (&node.left, &node.right)
+        } else {
+            (&node.right, &node.left)
+        };
+        nearest_neighbor(near, key, k, depth + 1, heap);
+        let is_far_reachable = heap.len() < k
+            || heap
+                .peek()
+                .is_some_and(|max| axis_distance * axis_distance < max.distance);
+        if is_far_reachable {
+            nearest_neighbor(far, key, k, depth + 1, heap);
+        }
+    }
+}
@@ -25,6 +25,47 @@
 //! extern crate extended_collections;
 //! ```
 //!
+//! ## Features
+//!
+//! The collections that depend on serialization, files, or atomics are behind Cargo features, which
+//! are all enabled by default:
+//!
+//! - `std`: the collections that depend on the standard library.
+//! - `serde`: `Serialize` and `Deserialize` for the collections that support them,
+//!   `static_search_tree`, and `external_sort`.
+//! - `bp_tree`: the disk-resident `bp_tree`, which contains `BpMap` and `RadixBpMap`.
+//! - `disk_queue`: the disk-resident `disk_queue`, which contains `DiskQueue`.
+//! - `lsm`: the disk-resident `lsm_tree` and its compaction strategies. Enables `probabilistic`.
+//! - `probabilistic`: the filters in `bloom` and `cuckoo`, and `sync::BloomFilter`.
+//! - `sync`: the concurrent collections in `sync`.
+//!
+//! The `model_testing` feature, which is not enabled by default, adds `model_testing` to check the
+//! disk-resident maps against a `BTreeMap` oracle.
+//!
+//! A crate that only needs the in-memory collections can disable the default features and avoid
+//! compiling `bincode`, `byteorder`, `serde`, and the compaction machinery of `lsm_tree`:
+//!
+//! ```toml
+//! [dependencies]
+//! extended-collections = { version = "*", default-features = false, features = ["std"] }
+//! ```
+//!
+//! ## `no_std` support
+//!
+//! The in-memory collections in `arena`, `avl_tree`, `compare`, `graph`, `merge`, `ordered`,
+//! `radix`, `red_black_tree`, `skiplist`, `splay_tree`, and `treap` only depend on `core` and
+//! `alloc`.
+//! Disable the default features to use them in a `no_std` crate:
+//!
+//! ```toml
+//! [dependencies]
+//! extended-collections = { version = "*", default-features = false }
+//! ```
+//!
+//! The other modules and the parallel set operations of `treap` require the `std` feature. Without
+//! it, collections that are not explicitly seeded derive their random number generators from a
+//! counter instead of from system entropy.
+//!
 //! ## Changelog
 //!
 //! See [CHANGELOG](CHANGELOG.md) for more details.
@@ -47,16 +88,78 @@
 //!
 //! See [LICENSE-APACHE](LICENSE-APACHE) and [LICENSE-MIT](LICENSE-MIT) for more details.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(missing_docs)]
 
+extern crate alloc;
+
 pub mod arena;
 pub mod avl_tree;
+#[cfg(feature = "std")]
+pub mod bench_support;
+#[cfg(feature = "std")]
+pub mod bit_set;
+#[cfg(feature = "std")]
+pub mod bit_vec;
+#[cfg(feature = "probabilistic")]
+pub mod bloom;
+#[cfg(feature = "bp_tree")]
 pub mod bp_tree;
+#[cfg(feature = "std")]
+pub mod btree;
+pub mod compare;
+#[cfg(feature = "probabilistic")]
+pub mod cuckoo;
+#[cfg(feature = "std")]
+pub mod disjoint_set;
+#[cfg(feature = "disk_queue")]
+pub mod disk_queue;
+#[cfg(any(feature = "bp_tree", feature = "disk_queue", feature = "lsm"))]
+pub mod durability;
 mod entry;
+#[cfg(feature = "serde")]
+pub mod external_sort;
+#[cfg(feature = "std")]
+pub mod fenwick_tree;
+pub mod graph;
+#[cfg(feature = "std")]
+pub mod hash_map;
+#[cfg(feature = "std")]
+pub mod hash_ring;
+#[cfg(feature = "std")]
+pub mod heap;
+#[cfg(feature = "std")]
+pub mod intern;
+#[cfg(feature = "std")]
+pub mod interval_tree;
+#[cfg(feature = "std")]
+pub mod kd_tree;
+#[cfg(feature = "lsm")]
 pub mod lsm_tree;
+pub mod merge;
+#[cfg(any(
+    feature = "model_testing",
+    all(test, feature = "bp_tree", feature = "lsm")
+))]
+pub mod model_testing;
+#[cfg(feature = "std")]
+pub mod multi;
+mod node_arena;
+pub mod ordered;
 pub mod radix;
 pub mod red_black_tree;
+#[cfg(feature = "std")]
+pub mod rope;
+mod seed;
+mod set_ops;
 pub mod skiplist;
 pub mod splay_tree;
+#[cfg(feature = "serde")]
+pub mod static_search_tree;
+#[cfg(feature = "std")]
+pub mod suffix_array;
+#[cfg(feature = "sync")]
 pub mod sync;
+#[cfg(feature = "std")]
+pub mod top_k;
 pub mod treap;
@@ -1,7 +1,12 @@
 use crate::entry::Entry;
-use crate::lsm_tree::compaction::{CompactionIter, CompactionStrategy};
-use crate::lsm_tree::{sstable, Result, SSTable, SSTableBuilder, SSTableDataIter, SSTableValue};
-use bincode::{deserialize, serialize};
+use crate::lsm_tree::compaction::{
+    self, CompactionInfo, CompactionIter, CompactionSeekIter, CompactionSnapshot, CompactionStats,
+    CompactionStrategy, EventListener, FlushInfo, LevelStats, MetadataFile,
+};
+use crate::lsm_tree::{
+    sstable, Error, FilterOptions, FilterStats, IoOptions, PrefixExtractor, RangeTombstone,
+    RateLimiter, Result, SSTable, SSTableBuilder, SSTableEntryIter, SSTableValue,
+};
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use serde::de::DeserializeOwned;
 use serde::ser::Serialize;
@@ -13,13 +18,13 @@ use std::collections::{BTreeMap, BinaryHeap, HashSet, VecDeque};
 use std::fmt::{self, Debug};
 use std::fs;
 use std::hash::Hash;
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{Seek, SeekFrom};
 use std::mem;
 use std::ops::Bound::{Included, Unbounded};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
 use std::thread;
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -33,8 +38,10 @@ where
     max_sstable_size: u64,
     max_initial_level_count: usize,
     growth_factor: u64,
+    index_block_size: Option<usize>,
     sstables: Vec<Arc<SSTable<T, U>>>,
     levels: Vec<BTreeMap<T, Arc<SSTable<T, U>>>>,
+    range_tombstones: Vec<RangeTombstone<T>>,
 }
 
 impl<T, U> LeveledMetadata<T, U>
@@ -47,6 +54,7 @@ where
         max_sstable_size: u64,
         max_initial_level_count: usize,
         growth_factor: u64,
+        index_block_size: Option<usize>,
     ) -> Self {
         LeveledMetadata {
             max_in_memory_size,
@@ -54,24 +62,88 @@ where
             max_sstable_size,
             max_initial_level_count,
             growth_factor,
+            index_block_size,
             sstables: Vec::new(),
             levels: Vec::new(),
+            range_tombstones: Vec::new(),
         }
     }
 
+    pub fn sstable_paths(&self) -> Vec<PathBuf> {
+        let level_sstable_iter = self
+            .levels
+            .iter()
+            .flat_map(|level| level.iter().map(|level_entry| level_entry.1));
+        self.sstables
+            .iter()
+            .chain(level_sstable_iter)
+            .map(|sstable| sstable.path.clone())
+            .collect()
+    }
+
     pub fn push_sstable(&mut self, sstable: Arc<SSTable<T, U>>) {
         self.sstables.push(sstable);
     }
 
-    pub fn insert_sstable(&mut self, index: usize, sstable: Arc<SSTable<T, U>>)
+    pub fn checkpoint<P>(&self, path: P) -> Result<Self>
     where
-        T: Clone,
+        T: Clone + DeserializeOwned,
+        U: Clone,
+        P: AsRef<Path>,
     {
-        while index >= self.levels.len() {
-            self.levels.push(BTreeMap::new());
+        let path = path.as_ref();
+        let sstables = self
+            .sstables
+            .iter()
+            .map(|sstable| sstable.checkpoint(path).map(Arc::new))
+            .collect::<Result<Vec<_>>>()?;
+        let levels = self
+            .levels
+            .iter()
+            .map(|level| {
+                level
+                    .iter()
+                    .map(|(key, sstable)| Ok((key.clone(), Arc::new(sstable.checkpoint(path)?))))
+                    .collect::<Result<BTreeMap<_, _>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(LeveledMetadata {
+            sstables,
+            levels,
+            ..self.clone()
+        })
+    }
+
+    // Returns the maximum number of SSTables in a level before it must be compacted into the next
+    // level.
+    pub fn get_max_level_len(&self, level_index: usize) -> usize {
+        if level_index == 0 {
+            self.max_sstable_count
+        } else {
+            let exponent = self.growth_factor.pow(level_index as u32 - 1) as usize;
+            self.max_initial_level_count * exponent
         }
+    }
+
+    // Discards the range tombstones that cannot delete an entry of any SSTable.
+    pub fn retain_range_tombstones(&mut self) {
+        let sstables = &self.sstables;
+        let levels = &self.levels;
+        self.range_tombstones.retain(|range_tombstone| {
+            sstables
+                .iter()
+                .chain(levels.iter().flat_map(|level| level.values()))
+                .any(|sstable| range_tombstone.may_delete(sstable))
+        });
+    }
 
-        self.levels[index].insert(sstable.summary.key_range.1.clone(), sstable);
+    pub fn should_compact(&self, level_index: usize) -> bool {
+        let level_len = if level_index == 0 {
+            self.sstables.len()
+        } else {
+            self.levels[level_index - 1].len()
+        };
+        level_len > self.get_max_level_len(level_index)
     }
 }
 
@@ -92,6 +164,279 @@ where
     }
 }
 
+fn insert_sstable<T, U>(level: &mut BTreeMap<T, Arc<SSTable<T, U>>>, sstable: Arc<SSTable<T, U>>)
+where
+    T: Clone + Ord,
+{
+    level.insert(sstable.summary.key_range.1.clone(), sstable);
+}
+
+// The result of a compaction of a level into the next level.
+struct LeveledCompactionOutput<T, U> {
+    // The paths of the SSTables in the first level that were merged.
+    sstables: HashSet<PathBuf>,
+    // The new contents of the compacted level if it is not the first level.
+    level: Option<BTreeMap<T, Arc<SSTable<T, U>>>>,
+    // The new contents of the next level.
+    next_level: BTreeMap<T, Arc<SSTable<T, U>>>,
+    // The number of bytes of the SSTables that were built.
+    size: u64,
+}
+
+struct LeveledCompactions<T, U> {
+    // The indexes of the levels being compacted. A compaction of level `i` also writes to level
+    // `i + 1`.
+    running: Vec<usize>,
+    // The results of finished compactions that have not been applied to the current metadata.
+    completed: Vec<(usize, Option<LeveledCompactionOutput<T, U>>)>,
+}
+
+impl<T, U> LeveledCompactions<T, U> {
+    pub fn new() -> Self {
+        LeveledCompactions {
+            running: Vec::new(),
+            completed: Vec::new(),
+        }
+    }
+}
+
+// A compaction of a level into the next level along with a snapshot of the SSTables of both
+// levels. Level 0 is the unsorted SSTables and level `i` is `levels[i - 1]` for `i > 0`.
+struct LeveledCompactionJob<T, U> {
+    level_index: usize,
+    sstables: Vec<Arc<SSTable<T, U>>>,
+    level: BTreeMap<T, Arc<SSTable<T, U>>>,
+    next_level: Option<BTreeMap<T, Arc<SSTable<T, U>>>>,
+    is_next_level_last: bool,
+    range_tombstones: Vec<RangeTombstone<T>>,
+    max_level_len: usize,
+    max_sstable_size: u64,
+    index_block_size: Option<usize>,
+    path: PathBuf,
+    filter_options: FilterOptions,
+    io_options: IoOptions,
+    prefix_extractor: Option<PrefixExtractor<T>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl<T, U> LeveledCompactionJob<T, U>
+where
+    T: Clone + DeserializeOwned + Hash + Ord + Serialize,
+    U: DeserializeOwned + Serialize,
+{
+    pub fn new(
+        metadata: &LeveledMetadata<T, U>,
+        level_index: usize,
+        path: PathBuf,
+        filter_options: FilterOptions,
+        io_options: IoOptions,
+        prefix_extractor: Option<PrefixExtractor<T>>,
+        rate_limiter: Option<Arc<RateLimiter>>,
+    ) -> Self {
+        let (sstables, level) = if level_index == 0 {
+            (metadata.sstables.clone(), BTreeMap::new())
+        } else {
+            (Vec::new(), metadata.levels[level_index - 1].clone())
+        };
+
+        LeveledCompactionJob {
+            level_index,
+            sstables,
+            level,
+            next_level: metadata.levels.get(level_index).cloned(),
+            is_next_level_last: level_index + 1 >= metadata.levels.len(),
+            range_tombstones: metadata.range_tombstones.clone(),
+            max_level_len: metadata.get_max_level_len(level_index),
+            max_sstable_size: metadata.max_sstable_size,
+            index_block_size: metadata.index_block_size,
+            path,
+            filter_options,
+            io_options,
+            prefix_extractor,
+            rate_limiter,
+        }
+    }
+
+    fn new_sstable_builder(&self, entry_count_hint: usize) -> Result<SSTableBuilder<T, U>> {
+        let mut sstable_builder = SSTableBuilder::with_options(
+            self.path.as_path(),
+            entry_count_hint,
+            self.index_block_size,
+            self.filter_options,
+            self.io_options,
+        )?;
+        sstable_builder.set_prefix_extractor(self.prefix_extractor.clone());
+        sstable_builder.set_rate_limiter(self.rate_limiter.clone());
+        Ok(sstable_builder)
+    }
+
+    // Merges the entries of `compaction_iter` into SSTables of at most `max_sstable_size` bytes
+    // and inserts them into `level`. Returns the number of bytes of the SSTables that were built.
+    fn merge_into(
+        &self,
+        level: &mut BTreeMap<T, Arc<SSTable<T, U>>>,
+        compaction_iter: LeveledIter<T, U>,
+        entry_count_hint: usize,
+        purge_tombstones: bool,
+    ) -> Result<u64> {
+        let mut size = 0;
+        let mut sstable_builder = self.new_sstable_builder(entry_count_hint)?;
+        for entry in compaction_iter {
+            let (key, value) = entry?;
+
+            if sstable::is_range_deleted(&self.range_tombstones, &key, value.logical_time) {
+                continue;
+            }
+
+            if !purge_tombstones || value.data.is_some() {
+                sstable_builder.append(key, value)?;
+            }
+
+            if sstable_builder.size > self.max_sstable_size {
+                let new_sstable = SSTable::open(sstable_builder.flush()?)?;
+                size += new_sstable.summary.size;
+                insert_sstable(level, Arc::new(new_sstable));
+                sstable_builder = self.new_sstable_builder(entry_count_hint)?;
+            }
+        }
+
+        if sstable_builder.key_range.is_some() {
+            let new_sstable = SSTable::open(sstable_builder.flush()?)?;
+            size += new_sstable.summary.size;
+            insert_sstable(level, Arc::new(new_sstable));
+        }
+
+        Ok(size)
+    }
+
+    pub fn info(&self) -> CompactionInfo {
+        let sstable_count = if self.level_index == 0 {
+            self.sstables.len()
+        } else {
+            self.level.len()
+        };
+        CompactionInfo {
+            level: self.level_index,
+            sstable_count,
+        }
+    }
+
+    pub fn run(mut self) -> Result<LeveledCompactionOutput<T, U>> {
+        if self.level_index == 0 {
+            self.compact_first_level()
+        } else {
+            self.compact_level()
+        }
+    }
+
+    fn compact_first_level(&mut self) -> Result<LeveledCompactionOutput<T, U>> {
+        let mut entry_count_hint = 0;
+        let sstable_data_iters: Vec<_> = self
+            .sstables
+            .iter()
+            .map(|sstable| {
+                entry_count_hint += sstable.summary.entry_count;
+                sstable.entry_iter()
+            })
+            .collect();
+        let next_level = self.next_level.take().unwrap_or_default();
+        for sstable in next_level.values() {
+            entry_count_hint = cmp::max(entry_count_hint, sstable.summary.entry_count);
+        }
+        let level_data_iter = next_level
+            .into_iter()
+            .map(|level_entry| level_entry.1.entry_iter())
+            .collect();
+
+        let compaction_iter = LeveledIter::new(None, sstable_data_iters, vec![level_data_iter])?;
+        let mut next_level = BTreeMap::new();
+        let size = self.merge_into(
+            &mut next_level,
+            compaction_iter,
+            entry_count_hint,
+            self.is_next_level_last,
+        )?;
+
+        Ok(LeveledCompactionOutput {
+            sstables: self
+                .sstables
+                .iter()
+                .map(|sstable| sstable.path.clone())
+                .collect(),
+            level: None,
+            next_level,
+            size,
+        })
+    }
+
+    fn compact_level(&mut self) -> Result<LeveledCompactionOutput<T, U>> {
+        let mut level = mem::take(&mut self.level);
+        let mut next_level_opt = self.next_level.take();
+        let mut size = 0;
+
+        while level.len() > self.max_level_len {
+            let sstable = {
+                let sstable_key = level
+                    .iter()
+                    .max_by(|x, y| {
+                        (x.1.summary.tombstone_count * y.1.summary.entry_count)
+                            .cmp(&(y.1.summary.tombstone_count * x.1.summary.entry_count))
+                    })
+                    .map(|level_entry| level_entry.1.summary.key_range.1.clone())
+                    .expect("Expected non-empty level to remove from.");
+                level
+                    .remove(&sstable_key)
+                    .expect("Expected SSTable to remove to exist.")
+            };
+
+            let next_level = match next_level_opt {
+                Some(ref mut next_level) => next_level,
+                None => {
+                    let mut next_level = BTreeMap::new();
+                    insert_sstable(&mut next_level, sstable);
+                    next_level_opt = Some(next_level);
+                    continue;
+                }
+            };
+
+            let (old_level, new_level): (BTreeMap<_, _>, BTreeMap<_, _>) =
+                mem::take(next_level).into_iter().partition(|level_entry| {
+                    sstable::is_intersecting(
+                        &sstable.summary.key_range,
+                        &level_entry.1.summary.key_range,
+                    )
+                });
+            *next_level = new_level;
+
+            let entry_count_hint = old_level
+                .values()
+                .map(|sstable| sstable.summary.entry_count)
+                .fold(sstable.summary.entry_count, cmp::max);
+            let compaction_iter = LeveledIter::new(
+                None,
+                vec![sstable.entry_iter()],
+                vec![old_level
+                    .into_iter()
+                    .map(|level_entry| level_entry.1.entry_iter())
+                    .collect()],
+            )?;
+            size += self.merge_into(
+                next_level,
+                compaction_iter,
+                entry_count_hint,
+                self.is_next_level_last,
+            )?;
+        }
+
+        Ok(LeveledCompactionOutput {
+            sstables: HashSet::new(),
+            level: Some(level),
+            next_level: next_level_opt.unwrap_or_default(),
+            size,
+        })
+    }
+}
+
 /// A compaction strategy based on grouping SSTables into levels of exponential increasing sizes.
 /// Each SSTable is a a fixed size and two SSTables are guaranteed to be non-overlapping if they
 /// are in the same level. As smaller levels fill up, SSTables are merged into larger levels.
@@ -111,14 +456,25 @@ where
     T: Ord,
 {
     path: PathBuf,
-    compaction_thread_join_handle: Option<thread::JoinHandle<()>>,
-    is_compacting: Arc<AtomicBool>,
+    compaction_threads: Vec<thread::JoinHandle<()>>,
+    compactions: Arc<(Mutex<LeveledCompactions<T, U>>, Condvar)>,
+    max_concurrent_compactions: usize,
+    compaction_rate_limiter: Option<Arc<RateLimiter>>,
+    write_stall_threshold: Option<usize>,
     curr_logical_time: u64,
     logical_time_file: fs::File,
     metadata_lock_count: Rc<Cell<u64>>,
-    metadata_file: fs::File,
+    metadata_file: MetadataFile<T, U>,
     curr_metadata: Arc<Mutex<LeveledMetadata<T, U>>>,
-    next_metadata: Arc<Mutex<Option<LeveledMetadata<T, U>>>>,
+    filter_false_positive_rate: f64,
+    filter_target_false_positive_rate: Option<f64>,
+    filter_partition_threshold: Option<usize>,
+    io_options: IoOptions,
+    prefix_extractor: Option<PrefixExtractor<T>>,
+    event_listener: Option<Arc<dyn EventListener>>,
+    flushed_size: u64,
+    compacted_size: Arc<AtomicU64>,
+    is_closed: bool,
 }
 
 impl<T, U> LeveledStrategy<T, U>
@@ -150,6 +506,85 @@ where
         max_initial_level_count: usize,
         growth_factor: u64,
     ) -> Result<Self>
+    where
+        T: Serialize,
+        U: Serialize,
+        P: AsRef<Path>,
+    {
+        let curr_metadata = LeveledMetadata::new(
+            max_in_memory_size,
+            max_sstable_count,
+            max_sstable_size,
+            max_initial_level_count,
+            growth_factor,
+            None,
+        );
+        Self::from_metadata(path, curr_metadata)
+    }
+
+    /// Constructs a new `LeveledStrategy<T, U>` with specific configuration parameters whose
+    /// SSTables have index blocks of `index_block_size` entries. The index of a SSTable keeps the
+    /// first key of each of its index blocks in memory, so larger index blocks use less memory,
+    /// but reading an entry requires reading a larger index block from disk. By default, the
+    /// index blocks of a SSTable have the square root of the number of its entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index_block_size` is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{CompactionStrategy, LeveledStrategy};
+    ///
+    /// let ls: LeveledStrategy<u32, u32> = LeveledStrategy::with_index_block_size(
+    ///     "leveled_strategy_with_index_block_size",
+    ///     10000,
+    ///     4,
+    ///     50000,
+    ///     10,
+    ///     10,
+    ///     128,
+    /// )?;
+    /// assert_eq!(ls.get_index_block_size(), Some(128));
+    /// # fs::remove_dir_all("leveled_strategy_with_index_block_size")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn with_index_block_size<P>(
+        path: P,
+        max_in_memory_size: u64,
+        max_sstable_count: usize,
+        max_sstable_size: u64,
+        max_initial_level_count: usize,
+        growth_factor: u64,
+        index_block_size: usize,
+    ) -> Result<Self>
+    where
+        T: Serialize,
+        U: Serialize,
+        P: AsRef<Path>,
+    {
+        assert!(
+            index_block_size > 0,
+            "Error: index block size must be greater than 0."
+        );
+        let curr_metadata = LeveledMetadata::new(
+            max_in_memory_size,
+            max_sstable_count,
+            max_sstable_size,
+            max_initial_level_count,
+            growth_factor,
+            Some(index_block_size),
+        );
+        Self::from_metadata(path, curr_metadata)
+    }
+
+    fn from_metadata<P>(path: P, curr_metadata: LeveledMetadata<T, U>) -> Result<Self>
     where
         T: Serialize,
         U: Serialize,
@@ -157,11 +592,7 @@ where
     {
         fs::create_dir(path.as_ref())?;
 
-        let metadata_file = fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open(path.as_ref().join("metadata.dat"))?;
+        let metadata_file = MetadataFile::new(path.as_ref(), &curr_metadata)?;
         let logical_time_file = fs::OpenOptions::new()
             .read(true)
             .write(true)
@@ -169,292 +600,614 @@ where
             .open(path.as_ref().join("logical_time.dat"))?;
         let mut ret = LeveledStrategy {
             path: PathBuf::from(path.as_ref()),
-            compaction_thread_join_handle: None,
-            is_compacting: Arc::new(AtomicBool::new(false)),
+            compaction_threads: Vec::new(),
+            compactions: Arc::new((Mutex::new(LeveledCompactions::new()), Condvar::new())),
+            max_concurrent_compactions: 1,
+            compaction_rate_limiter: None,
+            write_stall_threshold: None,
             curr_logical_time: 0,
             logical_time_file,
             metadata_lock_count: Rc::new(Cell::new(0)),
             metadata_file,
-            curr_metadata: Arc::new(Mutex::new(LeveledMetadata::new(
-                max_in_memory_size,
-                max_sstable_count,
-                max_sstable_size,
-                max_initial_level_count,
-                growth_factor,
-            ))),
-            next_metadata: Arc::new(Mutex::new(None)),
+            curr_metadata: Arc::new(Mutex::new(curr_metadata)),
+            filter_false_positive_rate: sstable::DEFAULT_FILTER_FALSE_POSITIVE_RATE,
+            filter_target_false_positive_rate: None,
+            filter_partition_threshold: None,
+            io_options: IoOptions::default(),
+            prefix_extractor: None,
+            event_listener: None,
+            flushed_size: 0,
+            compacted_size: Arc::new(AtomicU64::new(0)),
+            is_closed: false,
         };
 
-        {
-            let curr_metadata = ret.curr_metadata.lock().unwrap();
-            ret.metadata_file.seek(SeekFrom::Start(0))?;
-            ret.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
-        }
+        ret.logical_time_file
+            .write_u64::<BigEndian>(ret.curr_logical_time)?;
 
         Ok(ret)
     }
 
-    /// Opens an existing `LeveledStrategy<T, U>` from a folder.
+    /// Opens an existing `LeveledStrategy<T, U>` from a folder.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::LeveledStrategy;
+    ///
+    /// let sts: LeveledStrategy<u32, u32> = LeveledStrategy::open("leveled_strategy_open")?;
+    /// # fs::remove_dir_all("leveled_strategy_open")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        T: DeserializeOwned,
+        U: DeserializeOwned,
+        P: AsRef<Path>,
+    {
+        let logical_time_path = path.as_ref().join("logical_time.dat");
+        let (metadata_file, curr_metadata) = MetadataFile::open(path.as_ref())?;
+        compaction::collect_garbage(
+            path.as_ref(),
+            &curr_metadata,
+            LeveledMetadata::sstable_paths,
+            false,
+        )?;
+        let mut logical_time_file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&logical_time_path)
+            .map_err(|error| Error::file_io(&logical_time_path, "open", error))?;
+
+        let curr_logical_time = logical_time_file
+            .seek(SeekFrom::Start(0))
+            .and_then(|_| logical_time_file.read_u64::<BigEndian>())
+            .map_err(|error| Error::file_io(&logical_time_path, "read", error))?;
+        Ok(LeveledStrategy {
+            path: PathBuf::from(path.as_ref()),
+            compaction_threads: Vec::new(),
+            compactions: Arc::new((Mutex::new(LeveledCompactions::new()), Condvar::new())),
+            max_concurrent_compactions: 1,
+            compaction_rate_limiter: None,
+            write_stall_threshold: None,
+            curr_logical_time,
+            logical_time_file,
+            metadata_lock_count: Rc::new(Cell::new(0)),
+            metadata_file,
+            curr_metadata: Arc::new(Mutex::new(curr_metadata)),
+            filter_false_positive_rate: sstable::DEFAULT_FILTER_FALSE_POSITIVE_RATE,
+            filter_target_false_positive_rate: None,
+            filter_partition_threshold: None,
+            io_options: IoOptions::default(),
+            prefix_extractor: None,
+            event_listener: None,
+            flushed_size: 0,
+            compacted_size: Arc::new(AtomicU64::new(0)),
+            is_closed: false,
+        })
+    }
+
+    /// Removes the SSTables in the folder of a `LeveledStrategy<T, U>` that are not referenced by
+    /// its metadata, and returns their paths. These SSTables are left behind if the process
+    /// crashes after a compaction writes them, but before the metadata is replaced. If `dry_run` is
+    /// true, then the paths are returned without removing anything. `open` removes these SSTables
+    /// automatically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{CompactionStrategy, LeveledStrategy};
+    ///
+    /// let mut ls: LeveledStrategy<u32, u32> =
+    ///     LeveledStrategy::new("leveled_strategy_gc", 10000, 4, 50000, 10, 10)?;
+    /// ls.close()?;
+    ///
+    /// let garbage =
+    ///     LeveledStrategy::<u32, u32>::collect_garbage("leveled_strategy_gc", true)?;
+    /// assert!(garbage.is_empty());
+    /// # fs::remove_dir_all("leveled_strategy_gc")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn collect_garbage<P>(path: P, dry_run: bool) -> Result<Vec<PathBuf>>
+    where
+        T: DeserializeOwned,
+        U: DeserializeOwned,
+        P: AsRef<Path>,
+    {
+        let (_, metadata): (MetadataFile<T, U>, LeveledMetadata<T, U>) =
+            MetadataFile::open(path.as_ref())?;
+        compaction::collect_garbage(
+            path.as_ref(),
+            &metadata,
+            LeveledMetadata::sstable_paths,
+            dry_run,
+        )
+    }
+
+    /// Sets the false positive rate of the bloom filters of newly built SSTables. The default
+    /// false positive rate is `0.05`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{CompactionStrategy, LeveledStrategy};
+    ///
+    /// let mut strategy: LeveledStrategy<u32, u32> =
+    ///     LeveledStrategy::new("leveled_strategy_filter_rate", 1000, 4, 25000, 10, 10)?;
+    /// strategy.set_filter_false_positive_rate(0.01);
+    /// assert_eq!(strategy.get_filter_false_positive_rate(), 0.01);
+    /// # fs::remove_dir_all("leveled_strategy_filter_rate")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_filter_false_positive_rate(&mut self, filter_false_positive_rate: f64) {
+        self.filter_false_positive_rate = filter_false_positive_rate;
+    }
+
+    /// Enables or disables auto-tuning of the bloom filters of newly built SSTables. If enabled,
+    /// the false positive rate of newly built filters is adjusted whenever a SSTable is added
+    /// such that the observed false positive rate of existing filters approaches
+    /// `target_false_positive_rate`. Auto-tuning is disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::LeveledStrategy;
+    ///
+    /// let mut strategy: LeveledStrategy<u32, u32> =
+    ///     LeveledStrategy::new("leveled_strategy_filter_tuning", 1000, 4, 25000, 10, 10)?;
+    /// strategy.set_filter_auto_tuning(Some(0.01));
+    /// # fs::remove_dir_all("leveled_strategy_filter_tuning")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_filter_auto_tuning(&mut self, target_false_positive_rate: Option<f64>) {
+        self.filter_target_false_positive_rate = target_false_positive_rate;
+    }
+
+    /// Sets the false positive rate of the bloom filters of newly built SSTables to the false
+    /// positive rate of a bloom filter that uses `bits_per_key` bits for each key. For example,
+    /// `10` bits per key yields a false positive rate of about `0.0082`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits_per_key` is not greater than `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{CompactionStrategy, LeveledStrategy};
+    ///
+    /// let mut strategy: LeveledStrategy<u32, u32> =
+    ///     LeveledStrategy::new("leveled_strategy_filter_bits", 1000, 4, 25000, 10, 10)?;
+    /// strategy.set_filter_bits_per_key(10.0);
+    /// assert!((strategy.get_filter_false_positive_rate() - 0.0082).abs() < 0.0001);
+    /// # fs::remove_dir_all("leveled_strategy_filter_bits")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_filter_bits_per_key(&mut self, bits_per_key: f64) {
+        self.filter_false_positive_rate = sstable::get_false_positive_rate(bits_per_key);
+    }
+
+    /// Enables or disables partitioned bloom filters for newly built SSTables with at least
+    /// `partition_threshold` entries. A SSTable with a partitioned bloom filter has a bloom filter
+    /// for each of its index blocks that is read from disk when a lookup queries it, instead of a
+    /// single bloom filter that is kept in memory. Partitioned bloom filters are disabled by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{CompactionStrategy, LeveledStrategy};
+    ///
+    /// let mut strategy: LeveledStrategy<u32, u32> =
+    ///     LeveledStrategy::new("leveled_strategy_filter_partitions", 1000, 4, 25000, 10, 10)?;
+    /// strategy.set_filter_partition_threshold(Some(1_000_000));
+    /// assert_eq!(strategy.get_filter_partition_threshold(), Some(1_000_000));
+    /// # fs::remove_dir_all("leveled_strategy_filter_partitions")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_filter_partition_threshold(&mut self, partition_threshold: Option<usize>) {
+        self.filter_partition_threshold = partition_threshold;
+    }
+
+    fn filter_options(&self) -> FilterOptions {
+        FilterOptions {
+            false_positive_rate: self.filter_false_positive_rate,
+            partition_threshold: self.filter_partition_threshold,
+        }
+    }
+
+    /// Sets the options for the writes of newly built SSTables. See `IoOptions` for the defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{CompactionStrategy, LeveledStrategy};
+    /// use extended_collections::lsm_tree::IoOptions;
+    ///
+    /// let mut strategy: LeveledStrategy<u32, u32> =
+    ///     LeveledStrategy::new("leveled_strategy_io_options", 10000, 4, 50000, 4, 10)?;
+    /// let io_options = IoOptions {
+    ///     write_buffer_size: 1 << 20,
+    ///     sync_on_seal: true,
+    ///     direct_io: true,
+    /// };
+    /// strategy.set_io_options(io_options);
+    /// assert_eq!(strategy.get_io_options(), io_options);
+    /// # fs::remove_dir_all("leveled_strategy_io_options")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_io_options(&mut self, io_options: IoOptions) {
+        self.io_options = io_options;
+    }
+
+    /// Sets the function that maps keys to their prefixes, or disables prefix bloom filters if
+    /// `prefix_extractor` is `None`. Newly built SSTables have a bloom filter of the prefixes of
+    /// their keys, which lets range queries whose start and end keys have the same prefix skip
+    /// the SSTables that do not contain the prefix. The prefix extractor is not persisted and must
+    /// be set to the same function every time the compaction strategy is opened, as SSTables
+    /// built with a different prefix extractor may be skipped incorrectly. Prefix bloom filters
+    /// are disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::LeveledStrategy;
+    /// use std::sync::Arc;
+    ///
+    /// let mut strategy: LeveledStrategy<(u32, u64), u32> =
+    ///     LeveledStrategy::new("leveled_strategy_prefix", 10000, 4, 50000, 4, 10)?;
+    /// let prefix_extractor = Arc::new(|key: &(u32, u64)| key.0.to_be_bytes().to_vec());
+    /// strategy.set_prefix_extractor(Some(prefix_extractor));
+    /// # fs::remove_dir_all("leveled_strategy_prefix")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_prefix_extractor(&mut self, prefix_extractor: Option<PrefixExtractor<T>>) {
+        self.prefix_extractor = prefix_extractor;
+    }
+
+    /// Sets the listener that is notified of the flushes and compactions of the compaction
+    /// strategy, or removes it if `event_listener` is `None`. See `EventListener`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{
+    ///     CompactionInfo, EventListener, LeveledStrategy,
+    /// };
+    /// use std::sync::Arc;
+    ///
+    /// struct CompactionLogger;
+    ///
+    /// impl EventListener for CompactionLogger {
+    ///     fn on_compaction_start(&self, info: &CompactionInfo) {
+    ///         eprintln!("Compacting level {}.", info.level);
+    ///     }
+    /// }
+    ///
+    /// let mut strategy: LeveledStrategy<u32, u32> =
+    ///     LeveledStrategy::new("leveled_strategy_listener", 10000, 4, 50000, 4, 10)?;
+    /// strategy.set_event_listener(Some(Arc::new(CompactionLogger)));
+    /// # fs::remove_dir_all("leveled_strategy_listener")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_event_listener(&mut self, event_listener: Option<Arc<dyn EventListener>>) {
+        self.event_listener = event_listener;
+    }
+
+    /// Sets the maximum number of bytes per second written by compactions, or removes the limit
+    /// if `bytes_per_second` is `None`. The limit is shared by all running compactions and takes
+    /// effect for compactions started after it is set. Compactions are not rate limited by
+    /// default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes_per_second` is `Some(0)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::LeveledStrategy;
+    ///
+    /// let mut strategy: LeveledStrategy<u32, u32> =
+    ///     LeveledStrategy::new("leveled_strategy_rate_limit", 1000, 4, 25000, 10, 10)?;
+    /// strategy.set_compaction_rate_limit(Some(1 << 20));
+    /// # fs::remove_dir_all("leveled_strategy_rate_limit")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_compaction_rate_limit(&mut self, bytes_per_second: Option<u64>) {
+        self.compaction_rate_limiter =
+            bytes_per_second.map(|bytes_per_second| Arc::new(RateLimiter::new(bytes_per_second)));
+    }
+
+    /// Sets the number of SSTables in the first level at which writes are stalled, or disables
+    /// stalling if `write_stall_threshold` is `None`. When the number of SSTables exceeds the
+    /// threshold after the in-memory tree is flushed, the flush blocks until compactions reduce
+    /// the number of SSTables to at most the threshold. Writes are not stalled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::LeveledStrategy;
+    ///
+    /// let mut strategy: LeveledStrategy<u32, u32> =
+    ///     LeveledStrategy::new("leveled_strategy_write_stall", 1000, 4, 25000, 10, 10)?;
+    /// strategy.set_write_stall_threshold(Some(16));
+    /// # fs::remove_dir_all("leveled_strategy_write_stall")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_write_stall_threshold(&mut self, write_stall_threshold: Option<usize>) {
+        self.write_stall_threshold = write_stall_threshold;
+    }
+
+    /// Sets the maximum number of compactions that run concurrently. Compactions of a level into
+    /// the next level run concurrently only if they do not share any levels. The default maximum
+    /// is `1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_concurrent_compactions` is `0`.
     ///
     /// # Examples
     ///
-    /// ```no_run
+    /// ```
     /// # use extended_collections::lsm_tree::Result;
     /// # fn foo() -> Result<()> {
     /// # use std::fs;
     /// use extended_collections::lsm_tree::compaction::LeveledStrategy;
     ///
-    /// let sts: LeveledStrategy<u32, u32> = LeveledStrategy::open("leveled_strategy_open")?;
-    /// # fs::remove_dir_all("leveled_strategy_open")?;
+    /// let mut strategy: LeveledStrategy<u32, u32> =
+    ///     LeveledStrategy::new("leveled_strategy_concurrent", 1000, 4, 25000, 10, 10)?;
+    /// strategy.set_max_concurrent_compactions(4);
+    /// # fs::remove_dir_all("leveled_strategy_concurrent")?;
     /// # Ok(())
     /// # }
     /// # foo().unwrap();
     /// ```
-    pub fn open<P>(path: P) -> Result<Self>
-    where
-        T: DeserializeOwned,
-        U: DeserializeOwned,
-        P: AsRef<Path>,
-    {
-        let mut metadata_file = fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open(path.as_ref().join("metadata.dat"))?;
-        let mut logical_time_file = fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open(path.as_ref().join("logical_time.dat"))?;
-        let mut buffer = Vec::new();
-        metadata_file.read_to_end(&mut buffer)?;
-        logical_time_file.seek(SeekFrom::Start(0))?;
-        Ok(LeveledStrategy {
-            path: PathBuf::from(path.as_ref()),
-            compaction_thread_join_handle: None,
-            is_compacting: Arc::new(AtomicBool::new(false)),
-            curr_logical_time: logical_time_file.read_u64::<BigEndian>()?,
-            logical_time_file,
-            metadata_lock_count: Rc::new(Cell::new(0)),
-            metadata_file,
-            curr_metadata: Arc::new(Mutex::new(deserialize(&buffer)?)),
-            next_metadata: Arc::new(Mutex::new(None)),
-        })
+    pub fn set_max_concurrent_compactions(&mut self, max_concurrent_compactions: usize) {
+        assert!(
+            max_concurrent_compactions > 0,
+            "Error: maximum number of concurrent compactions must be greater than 0."
+        );
+        self.max_concurrent_compactions = max_concurrent_compactions;
+    }
+
+    fn check_open(&self) -> Result<()> {
+        if self.is_closed {
+            Err(Error::Closed)
+        } else {
+            Ok(())
+        }
     }
 
+    // Applies the results of finished compactions to the current metadata and removes the
+    // SSTables that are no longer referenced.
     fn try_replace_metadata(
         &self,
         curr_metadata: &mut MutexGuard<'_, LeveledMetadata<T, U>>,
     ) -> Result<bool> {
-        let mut next_metadata = self.next_metadata.lock().unwrap();
+        let mut compactions = self.compactions.0.lock().unwrap();
 
-        if let Some(next_metadata) = next_metadata.take() {
-            let logical_time_opt = next_metadata
-                .levels
-                .iter()
-                .map(|level_entry| {
-                    level_entry
-                        .iter()
-                        .map(|level_entry| level_entry.1.summary.logical_time_range.1)
-                        .max()
-                })
-                .max()
-                .and_then(|max_opt| max_opt);
-            let old_sstables = mem::replace(&mut curr_metadata.sstables, next_metadata.sstables);
-            let old_levels = mem::replace(&mut curr_metadata.levels, next_metadata.levels);
-            curr_metadata.sstables.extend(
-                old_sstables
-                    .iter()
-                    .filter(|sstable| Some(sstable.summary.logical_time_range.0) > logical_time_opt)
-                    .map(|sstable| Arc::clone(sstable)),
-            );
+        if compactions.completed.is_empty() {
+            return Ok(false);
+        }
 
-            let path_iter = curr_metadata.sstables.iter().map(|sstable| &sstable.path);
-            let level_path_iter = curr_metadata
-                .levels
-                .iter()
-                .flat_map(|level| level.iter().map(|level_entry| &level_entry.1.path));
-            let new_sstable_paths: HashSet<_> = path_iter.chain(level_path_iter).collect();
+        let mut old_sstables = Vec::new();
+        for (level_index, output_opt) in mem::take(&mut compactions.completed) {
+            compactions
+                .running
+                .retain(|running_index| *running_index != level_index);
 
-            let old_path_iter = old_sstables.iter().map(|sstable| &sstable.path);
-            let old_level_path_iter = old_levels
-                .iter()
-                .flat_map(|level| level.iter().map(|level_entry| &level_entry.1.path));
+            let output = match output_opt {
+                Some(output) => output,
+                None => continue,
+            };
 
-            for path in old_path_iter.chain(old_level_path_iter) {
-                if !new_sstable_paths.contains(path) {
-                    fs::remove_dir_all(path)?;
-                }
+            if level_index == 0 {
+                let (merged_sstables, sstables) = mem::take(&mut curr_metadata.sstables)
+                    .into_iter()
+                    .partition(|sstable| output.sstables.contains(&sstable.path));
+                curr_metadata.sstables = sstables;
+                old_sstables.extend(merged_sstables);
+            } else if let Some(level) = output.level {
+                let old_level = mem::replace(&mut curr_metadata.levels[level_index - 1], level);
+                old_sstables.extend(old_level.into_iter().map(|level_entry| level_entry.1));
             }
 
-            Ok(true)
-        } else {
-            Ok(false)
+            while level_index >= curr_metadata.levels.len() {
+                curr_metadata.levels.push(BTreeMap::new());
+            }
+            let old_level = mem::replace(&mut curr_metadata.levels[level_index], output.next_level);
+            old_sstables.extend(old_level.into_iter().map(|level_entry| level_entry.1));
         }
-    }
 
-    fn compact<P>(
-        path: P,
-        is_compacting: &Arc<AtomicBool>,
-        mut metadata_snapshot: LeveledMetadata<T, U>,
-        next_metadata: &Arc<Mutex<Option<LeveledMetadata<T, U>>>>,
-    ) -> Result<()>
-    where
-        T: Clone + DeserializeOwned + Hash + Serialize,
-        U: DeserializeOwned + Serialize,
-        P: AsRef<Path>,
-    {
-        println!("Started compacting.");
-
-        if metadata_snapshot.levels.is_empty() {
-            metadata_snapshot.levels.push(BTreeMap::new());
-        }
+        let path_iter = curr_metadata.sstables.iter().map(|sstable| &sstable.path);
+        let level_path_iter = curr_metadata
+            .levels
+            .iter()
+            .flat_map(|level| level.iter().map(|level_entry| &level_entry.1.path));
+        let new_sstable_paths: HashSet<_> = path_iter.chain(level_path_iter).collect();
 
-        // compacting L0
-        let mut entry_count_hint = 0;
-        let sstable_data_iters: Vec<_> = metadata_snapshot
-            .sstables
-            .drain(..)
-            .map(|sstable| {
-                entry_count_hint += sstable.summary.entry_count;
-                sstable.data_iter()
-            })
-            .collect();
-        for sstable in metadata_snapshot.levels[0].values() {
-            entry_count_hint = cmp::max(entry_count_hint, sstable.summary.entry_count);
+        for old_sstable in old_sstables {
+            if !new_sstable_paths.contains(&old_sstable.path) {
+                self.metadata_file.remove_sstable(old_sstable);
+            }
         }
-        let level_data_iter = mem::replace(&mut metadata_snapshot.levels[0], BTreeMap::new())
-            .into_iter()
-            .map(|entry| entry.1.data_iter())
-            .collect();
-
-        let mut sstable_builder = SSTableBuilder::new(path.as_ref(), entry_count_hint)?;
-
-        let compaction_iter = LeveledIter::new(None, sstable_data_iters, vec![level_data_iter])?;
-
-        for entry in compaction_iter {
-            let (key, value) = entry?;
+        curr_metadata.retain_range_tombstones();
 
-            if metadata_snapshot.levels.len() > 1 || value.data.is_some() {
-                sstable_builder.append(key, value)?;
-            }
+        Ok(true)
+    }
 
-            if sstable_builder.size > metadata_snapshot.max_sstable_size {
-                let new_sstable = Arc::new(SSTable::new(sstable_builder.flush()?)?);
-                metadata_snapshot.insert_sstable(0, new_sstable);
-                sstable_builder = SSTableBuilder::new(path.as_ref(), entry_count_hint)?;
-            }
-        }
+    // Spawns a compaction thread for each level that is too large and is disjoint from the
+    // levels of running compactions, up to the maximum number of concurrent compactions. Returns
+    // the number of compactions that were spawned.
+    fn spawn_compactions(&mut self) -> Result<usize>
+    where
+        T: 'static + Clone + DeserializeOwned + Hash + Send + Serialize + Sync,
+        U: 'static + DeserializeOwned + Serialize + Send + Sync,
+    {
+        self.compaction_threads
+            .retain(|compaction_thread| !compaction_thread.is_finished());
 
-        if sstable_builder.key_range.is_some() {
-            let new_sstable = Arc::new(SSTable::new(sstable_builder.flush()?)?);
-            metadata_snapshot.insert_sstable(0, new_sstable);
+        if self.metadata_lock_count.get() != 0 {
+            return Ok(0);
         }
 
-        // compacting L1 and onwards
-        for index in 0.. {
-            if index == metadata_snapshot.levels.len() {
-                break;
+        let compaction_jobs = {
+            let mut curr_metadata = self.curr_metadata.lock().unwrap();
+            if self.try_replace_metadata(&mut curr_metadata)? {
+                self.metadata_file.write(&*curr_metadata)?;
             }
 
-            let should_merge = |metadata_snapshot: &LeveledMetadata<T, U>, index: usize| {
-                let curr_len = metadata_snapshot.levels[index].len();
-                let exponent = metadata_snapshot.growth_factor.pow(index as u32) as usize;
-                let max_len = metadata_snapshot.max_initial_level_count * exponent;
-                curr_len > max_len as usize
-            };
-
-            while should_merge(&metadata_snapshot, index) {
-                let sstable = {
-                    let sstable_key = metadata_snapshot.levels[index]
-                        .iter()
-                        .max_by(|x, y| {
-                            (x.1.summary.tombstone_count * y.1.summary.entry_count)
-                                .cmp(&(y.1.summary.tombstone_count * x.1.summary.entry_count))
-                        })
-                        .map(|level_entry| level_entry.1.summary.key_range.1.clone())
-                        .expect("Expected non-empty level to remove from.");
-                    metadata_snapshot.levels[index]
-                        .remove(&sstable_key)
-                        .expect("Expected SSTable to remove to exist.")
-                };
-
-                let mut sstable_builder = SSTableBuilder::new(path.as_ref(), entry_count_hint)?;
-
-                if index + 1 == metadata_snapshot.levels.len() {
-                    metadata_snapshot.insert_sstable(index + 1, sstable);
-                    continue;
+            let mut compactions = self.compactions.0.lock().unwrap();
+            let mut compaction_jobs = Vec::new();
+            for level_index in 0..=curr_metadata.levels.len() {
+                if compactions.running.len() >= self.max_concurrent_compactions {
+                    break;
                 }
 
-                let sstable_data_iter = sstable.data_iter();
-                let level = mem::replace(&mut metadata_snapshot.levels[index + 1], BTreeMap::new());
-                let (old_level, new_level): (BTreeMap<_, _>, BTreeMap<_, _>) =
-                    level.into_iter().partition(|level_entry| {
-                        sstable::is_intersecting(
-                            &sstable.summary.key_range,
-                            &level_entry.1.summary.key_range,
-                        )
-                    });
-
-                metadata_snapshot.levels[index + 1] = new_level;
-
-                let compaction_iter = LeveledIter::new(
-                    None,
-                    vec![sstable_data_iter],
-                    vec![old_level
-                        .into_iter()
-                        .map(|level_entry| level_entry.1.data_iter())
-                        .collect()],
-                )?;
-
-                for entry in compaction_iter {
-                    let (key, value) = entry?;
-
-                    if index + 1 != metadata_snapshot.levels.len() - 1 || value.data.is_some() {
-                        sstable_builder.append(key, value)?;
-                    }
+                let is_disjoint = compactions.running.iter().all(|running_index| {
+                    running_index + 1 < level_index || level_index + 1 < *running_index
+                });
+                if is_disjoint && curr_metadata.should_compact(level_index) {
+                    compactions.running.push(level_index);
+                    compaction_jobs.push(LeveledCompactionJob::new(
+                        &curr_metadata,
+                        level_index,
+                        self.path.clone(),
+                        self.filter_options(),
+                        self.io_options,
+                        self.prefix_extractor.clone(),
+                        self.compaction_rate_limiter.clone(),
+                    ));
+                }
+            }
+            compaction_jobs
+        };
 
-                    if sstable_builder.size > metadata_snapshot.max_sstable_size {
-                        let new_sstable = Arc::new(SSTable::new(sstable_builder.flush()?)?);
-                        metadata_snapshot.insert_sstable(index + 1, new_sstable);
-                        sstable_builder = SSTableBuilder::new(path.as_ref(), entry_count_hint)?;
-                    }
+        let compaction_count = compaction_jobs.len();
+        for compaction_job in compaction_jobs {
+            let compactions = Arc::clone(&self.compactions);
+            let compacted_size = Arc::clone(&self.compacted_size);
+            let event_listener = self.event_listener.clone();
+            self.compaction_threads.push(thread::spawn(move || {
+                let level_index = compaction_job.level_index;
+                let compaction_info = compaction_job.info();
+                if let Some(ref event_listener) = event_listener {
+                    event_listener.on_compaction_start(&compaction_info);
+                }
+                let output_result = compaction_job.run();
+                if let Some(ref event_listener) = event_listener {
+                    let size_result = output_result.as_ref().map(|output| output.size);
+                    event_listener.on_compaction_end(&compaction_info, size_result);
                 }
 
-                if sstable_builder.key_range.is_some() {
-                    let new_sstable = Arc::new(SSTable::new(sstable_builder.flush()?)?);
-                    metadata_snapshot.insert_sstable(index + 1, new_sstable);
+                let output_opt = output_result.ok();
+                if let Some(ref output) = output_opt {
+                    compacted_size.fetch_add(output.size, Ordering::Relaxed);
                 }
-            }
-        }
 
-        *next_metadata.lock().unwrap() = Some(metadata_snapshot);
+                let (compactions, compaction_finished) = &*compactions;
+                compactions
+                    .lock()
+                    .unwrap()
+                    .completed
+                    .push((level_index, output_opt));
+                compaction_finished.notify_all();
+            }));
+        }
 
-        is_compacting.store(false, Ordering::Release);
+        Ok(compaction_count)
+    }
 
-        println!("Finished compacting");
-        Ok(())
+    fn join_compaction_threads(&mut self) {
+        for compaction_thread in self.compaction_threads.drain(..) {
+            compaction_thread.join().ok();
+        }
     }
 
-    fn spawn_compaction_thread(&mut self, metadata_snapshot: LeveledMetadata<T, U>)
+    // Blocks until the number of SSTables in the first level is at most `write_stall_threshold`,
+    // or until no running compaction can reduce it.
+    fn stall_writes(&mut self, write_stall_threshold: usize) -> Result<()>
     where
         T: 'static + Clone + DeserializeOwned + Hash + Send + Serialize + Sync,
         U: 'static + DeserializeOwned + Serialize + Send + Sync,
     {
-        let path = self.path.clone();
-        let next_metadata = self.next_metadata.clone();
-        let is_compacting = self.is_compacting.clone();
-        self.is_compacting.store(true, Ordering::Release);
-        self.compaction_thread_join_handle = Some(thread::spawn(move || {
-            let compaction_result =
-                LeveledStrategy::compact(path, &is_compacting, metadata_snapshot, &next_metadata);
-
-            match compaction_result {
-                Ok(_) => println!("Compaction terminated successfully."),
-                Err(error) => {
-                    is_compacting.store(false, Ordering::Release);
-                    println!("Compaction terminated with error: {:?}", error);
+        loop {
+            {
+                let mut curr_metadata = self.curr_metadata.lock().unwrap();
+                if self.try_replace_metadata(&mut curr_metadata)? {
+                    self.metadata_file.write(&*curr_metadata)?;
+                }
+                if curr_metadata.sstables.len() <= write_stall_threshold {
+                    return Ok(());
                 }
             }
-        }))
+
+            self.spawn_compactions()?;
+
+            let (compactions, compaction_finished) = &*self.compactions;
+            let mut compactions = compactions.lock().unwrap();
+            if compactions.running.is_empty() {
+                return Ok(());
+            }
+            while compactions.completed.is_empty() {
+                compactions = compaction_finished.wait(compactions).unwrap();
+            }
+        }
     }
 }
 
@@ -471,7 +1224,33 @@ where
         self.curr_metadata.lock().unwrap().max_in_memory_size
     }
 
+    fn get_index_block_size(&self) -> Option<usize> {
+        self.curr_metadata.lock().unwrap().index_block_size
+    }
+
+    fn get_filter_false_positive_rate(&self) -> f64 {
+        self.filter_false_positive_rate
+    }
+
+    fn get_filter_partition_threshold(&self) -> Option<usize> {
+        self.filter_partition_threshold
+    }
+
+    fn get_io_options(&self) -> IoOptions {
+        self.io_options
+    }
+
+    fn get_prefix_extractor(&self) -> Option<PrefixExtractor<T>> {
+        self.prefix_extractor.clone()
+    }
+
+    fn get_logical_time(&self) -> u64 {
+        self.curr_logical_time
+    }
+
     fn get_and_increment_logical_time(&mut self) -> Result<u64> {
+        self.check_open()?;
+
         let ret = self.curr_logical_time;
         self.curr_logical_time += 1;
         self.logical_time_file.seek(SeekFrom::Start(0))?;
@@ -480,49 +1259,86 @@ where
         Ok(ret)
     }
 
+    fn advance_logical_time(&mut self, logical_time: u64) -> Result<()> {
+        self.check_open()?;
+
+        if logical_time > self.curr_logical_time {
+            self.curr_logical_time = logical_time;
+            self.logical_time_file.seek(SeekFrom::Start(0))?;
+            self.logical_time_file
+                .write_u64::<BigEndian>(self.curr_logical_time)?;
+        }
+        Ok(())
+    }
+
     fn try_compact(&mut self, sstable: SSTable<T, U>) -> Result<()> {
+        self.check_open()?;
+
+        self.flushed_size += sstable.summary.size;
+        let flush_info = FlushInfo {
+            path: sstable.path.clone(),
+            entry_count: sstable.summary.entry_count,
+            size: sstable.summary.size,
+        };
         {
             let mut curr_metadata = self.curr_metadata.lock().unwrap();
             curr_metadata.push_sstable(Arc::new(sstable));
-            self.metadata_file.seek(SeekFrom::Start(0))?;
-            self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
+            self.metadata_file.write(&*curr_metadata)?;
+        }
+        if let Some(ref event_listener) = self.event_listener {
+            event_listener.on_flush(&flush_info);
         }
 
-        if self.is_compacting.load(Ordering::Acquire) || self.metadata_lock_count.get() != 0 {
-            return Ok(());
+        if let Some(target_false_positive_rate) = self.filter_target_false_positive_rate {
+            let filter_stats = self.filter_stats()?;
+            self.filter_false_positive_rate = sstable::tune_false_positive_rate(
+                self.filter_false_positive_rate,
+                target_false_positive_rate,
+                &filter_stats,
+            );
         }
 
-        // taking snapshot of current metadata
-        let metadata_snapshot = {
-            let mut curr_metadata = self.curr_metadata.lock().unwrap();
-            if self.try_replace_metadata(&mut curr_metadata)? {
-                self.metadata_file.seek(SeekFrom::Start(0))?;
-                self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
-            }
-            curr_metadata.clone()
-        };
+        self.spawn_compactions()?;
 
-        if metadata_snapshot.sstables.len() > metadata_snapshot.max_sstable_count {
-            self.spawn_compaction_thread(metadata_snapshot);
+        if let Some(write_stall_threshold) = self.write_stall_threshold {
+            self.stall_writes(write_stall_threshold)?;
         }
 
         Ok(())
     }
 
+    fn remove_range(&mut self, start: T, end: T, logical_time: u64) -> Result<()> {
+        self.check_open()?;
+
+        let mut curr_metadata = self.curr_metadata.lock().unwrap();
+        self.try_replace_metadata(&mut curr_metadata)?;
+        curr_metadata.range_tombstones.push(RangeTombstone {
+            start,
+            end,
+            logical_time,
+        });
+        curr_metadata.retain_range_tombstones();
+        self.metadata_file.write(&*curr_metadata)?;
+        Ok(())
+    }
+
     fn flush(&mut self) -> Result<()> {
-        if let Some(compaction_thread_join_handle) = self.compaction_thread_join_handle.take() {
-            match compaction_thread_join_handle.join() {
-                Ok(_) => println!("Child thread terminated successfully."),
-                Err(error) => println!("Child thread terminated with error: {:?}", error),
+        self.check_open()?;
+
+        loop {
+            self.join_compaction_threads();
+
+            {
+                let mut curr_metadata = self.curr_metadata.lock().unwrap();
+                if self.try_replace_metadata(&mut curr_metadata)? {
+                    self.metadata_file.write(&*curr_metadata)?;
+                }
             }
 
-            let mut curr_metadata = self.curr_metadata.lock().unwrap();
-            if self.try_replace_metadata(&mut curr_metadata)? {
-                self.metadata_file.seek(SeekFrom::Start(0))?;
-                self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
+            if self.spawn_compactions()? == 0 {
+                return Ok(());
             }
         }
-        Ok(())
     }
 
     fn get<V>(&mut self, key: &V) -> Result<Option<SSTableValue<U>>>
@@ -530,44 +1346,64 @@ where
         T: Borrow<V>,
         V: Ord + Hash + ?Sized,
     {
+        self.check_open()?;
+
         let mut curr_metadata = self.curr_metadata.lock().unwrap();
         if self.try_replace_metadata(&mut curr_metadata)? {
-            self.metadata_file.seek(SeekFrom::Start(0))?;
-            self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
+            self.metadata_file.write(&*curr_metadata)?;
         }
 
         let mut ret = None;
         for sstable in &curr_metadata.sstables {
-            let res = sstable.get(&key)?;
+            let res = sstable.get_value(&key)?;
             if res.is_some() && (ret.is_none() || res < ret) {
                 ret = res;
             }
         }
 
-        if ret.is_some() {
-            return Ok(ret);
-        }
-
-        for level in &curr_metadata.levels {
-            let sstable_opt = level
-                .range((Included(key), Unbounded))
-                .next()
-                .map(|entry| entry.1);
-            if let Some(sstable) = sstable_opt {
-                if let Some(value) = sstable.get(key)? {
-                    return Ok(Some(value));
+        if ret.is_none() {
+            for level in &curr_metadata.levels {
+                let sstable_opt = level
+                    .range((Included(key), Unbounded))
+                    .next()
+                    .map(|entry| entry.1);
+                if let Some(sstable) = sstable_opt {
+                    ret = sstable.get_value(key)?;
+                    if ret.is_some() {
+                        break;
+                    }
                 }
             }
         }
 
-        Ok(None)
+        Ok(sstable::apply_range_tombstones(
+            &curr_metadata.range_tombstones,
+            key,
+            ret,
+        ))
+    }
+
+    fn snapshot(&mut self) -> Result<CompactionSnapshot<T, U>> {
+        self.check_open()?;
+
+        let mut curr_metadata = self.curr_metadata.lock().unwrap();
+        if self.try_replace_metadata(&mut curr_metadata)? {
+            self.metadata_file.write(&*curr_metadata)?;
+        }
+
+        Ok(CompactionSnapshot::new(
+            curr_metadata.sstables.clone(),
+            curr_metadata.levels.clone(),
+            curr_metadata.range_tombstones.clone(),
+        ))
     }
 
     fn len_hint(&mut self) -> Result<usize> {
+        self.check_open()?;
+
         let mut curr_metadata = self.curr_metadata.lock().unwrap();
         if self.try_replace_metadata(&mut curr_metadata)? {
-            self.metadata_file.seek(SeekFrom::Start(0))?;
-            self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
+            self.metadata_file.write(&*curr_metadata)?;
         }
 
         let sstables_len_hint: usize = curr_metadata
@@ -591,7 +1427,7 @@ where
     }
 
     fn len(&mut self) -> Result<usize> {
-        Ok(self.iter()?.count())
+        Ok(self.iter(None)?.count())
     }
 
     fn is_empty(&mut self) -> Result<bool> {
@@ -599,59 +1435,111 @@ where
     }
 
     fn clear(&mut self) -> Result<()> {
-        if let Some(compaction_thread_join_handle) = self.compaction_thread_join_handle.take() {
-            match compaction_thread_join_handle.join() {
-                Ok(_) => println!("Child thread terminated successfully."),
-                Err(error) => println!("Child thread terminated with error: {:?}", error),
-            }
-        }
+        self.check_open()?;
+
+        self.join_compaction_threads();
 
         let mut curr_metadata = self.curr_metadata.lock().unwrap();
-        let mut next_metadata = self.next_metadata.lock().unwrap();
-        curr_metadata.sstables.clear();
-        curr_metadata.levels.clear();
-        *next_metadata = None;
+        let mut compactions = self.compactions.0.lock().unwrap();
+        // snapshots may still hold the SSTables, so their files are deleted once they are dropped.
+        let mut sstable_paths = HashSet::new();
+        let metadata = &mut *curr_metadata;
+        let level_iter = metadata
+            .levels
+            .drain(..)
+            .flat_map(|level| level.into_iter().map(|level_entry| level_entry.1));
+        let sstables: Vec<_> = metadata.sstables.drain(..).chain(level_iter).collect();
+        for sstable in sstables {
+            sstable_paths.insert(sstable.path.clone());
+            self.metadata_file.remove_sstable(sstable);
+        }
+        sstable_paths.extend(self.metadata_file.removed_sstable_paths());
+        curr_metadata.range_tombstones.clear();
+        compactions.running.clear();
+        compactions.completed.clear();
 
         for dir_entry in fs::read_dir(self.path.as_path())? {
             let dir_path = dir_entry?.path();
-            if dir_path.is_dir() {
+            if dir_path.is_dir() && !sstable_paths.contains(&dir_path) {
                 fs::remove_dir_all(dir_path)?;
             }
         }
 
-        self.metadata_file.seek(SeekFrom::Start(0))?;
-        self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
+        self.metadata_file.write(&*curr_metadata)?;
 
         Ok(())
     }
 
     fn min(&mut self) -> Result<Option<T>> {
-        match self.iter()?.next() {
+        match self.iter(None)?.next() {
             Some(entry) => Ok(Some(entry?.0)),
             None => Ok(None),
         }
     }
 
     fn max(&mut self) -> Result<Option<T>> {
-        match self.iter()?.last() {
+        match self.iter(None)?.last() {
             Some(entry) => Ok(Some(entry?.0)),
             None => Ok(None),
         }
     }
 
-    fn iter(&mut self) -> Result<Box<CompactionIter<T, U>>> {
+    fn filter_stats(&mut self) -> Result<Vec<FilterStats>> {
+        self.check_open()?;
+
+        let mut curr_metadata = self.curr_metadata.lock().unwrap();
+        if self.try_replace_metadata(&mut curr_metadata)? {
+            self.metadata_file.write(&*curr_metadata)?;
+        }
+
+        let filter_stats = curr_metadata
+            .sstables
+            .iter()
+            .chain(curr_metadata.levels.iter().flat_map(|level| level.values()))
+            .map(|sstable| sstable.filter_stats())
+            .collect();
+
+        Ok(filter_stats)
+    }
+
+    fn stats(&mut self) -> Result<CompactionStats> {
+        self.check_open()?;
+
+        let mut curr_metadata = self.curr_metadata.lock().unwrap();
+        if self.try_replace_metadata(&mut curr_metadata)? {
+            self.metadata_file.write(&*curr_metadata)?;
+        }
+
+        let mut levels = vec![LevelStats::new(&curr_metadata.sstables)?];
+        for level in &curr_metadata.levels {
+            levels.push(LevelStats::new(level.values())?);
+        }
+
+        Ok(CompactionStats {
+            levels,
+            flushed_size: self.flushed_size,
+            compacted_size: self.compacted_size.load(Ordering::Relaxed),
+        })
+    }
+
+    fn iter(&mut self, range: Option<(&T, &T)>) -> Result<Box<CompactionIter<T, U>>> {
+        if let Some((start, end)) = range {
+            return Ok(Box::new(self.iter_from(start, Some(end))?));
+        }
+
+        self.check_open()?;
+
         let mut curr_metadata = self.curr_metadata.lock().unwrap();
         // should never need to replace metadata as the compaction thread should not be running
         // when yielding calling iter.
         if self.try_replace_metadata(&mut curr_metadata)? {
-            self.metadata_file.seek(SeekFrom::Start(0))?;
-            self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
+            self.metadata_file.write(&*curr_metadata)?;
         }
 
         let sstable_data_iters = curr_metadata
             .sstables
             .iter()
-            .map(|sstable| sstable.data_iter())
+            .map(|sstable| sstable.entry_iter())
             .collect();
         let level_data_iters = curr_metadata
             .levels
@@ -659,19 +1547,23 @@ where
             .map(|level| {
                 level
                     .iter()
-                    .map(|level_entry| level_entry.1.data_iter())
+                    .map(|level_entry| level_entry.1.entry_iter())
                     .collect()
             })
             .collect();
+        let range_tombstones = curr_metadata.range_tombstones.clone();
         let metadata_lock_count = Rc::clone(&self.metadata_lock_count);
         let compaction_iter = LeveledIter::new(
             Some(metadata_lock_count),
             sstable_data_iters,
             level_data_iters,
         )?
-        .filter_map(|entry_result| match entry_result {
+        .filter_map(move |entry_result| match entry_result {
             Ok(entry) => {
                 let (key, value) = entry;
+                if sstable::is_range_deleted(&range_tombstones, &key, value.logical_time) {
+                    return None;
+                }
                 value.data.map(|value| Ok((key, value)))
             }
             Err(error) => Some(Err(error)),
@@ -679,6 +1571,150 @@ where
 
         Ok(Box::new(compaction_iter))
     }
+
+    fn iter_from(&mut self, key: &T, end: Option<&T>) -> Result<CompactionSeekIter<T, U>> {
+        self.check_open()?;
+
+        let mut curr_metadata = self.curr_metadata.lock().unwrap();
+        if self.try_replace_metadata(&mut curr_metadata)? {
+            self.metadata_file.write(&*curr_metadata)?;
+        }
+
+        let sstables = curr_metadata.sstables.clone();
+        let levels = curr_metadata.levels.clone();
+        let range_tombstones = curr_metadata.range_tombstones.clone();
+        let metadata_lock_count = Rc::clone(&self.metadata_lock_count);
+        let seek_end = end.cloned();
+        let prefix_extractor = self.prefix_extractor.clone();
+        let mut ret = CompactionSeekIter::new(
+            move |key| {
+                let prefix =
+                    compaction::get_range_prefix(prefix_extractor.as_ref(), key, seek_end.as_ref());
+                let sstable_data_iters = sstables
+                    .iter()
+                    .filter(|sstable| {
+                        compaction::is_in_range(&sstable.summary.key_range, key, seek_end.as_ref())
+                            && compaction::may_contain_prefix(sstable, prefix.as_deref())
+                    })
+                    .map(|sstable| sstable.entry_iter_from(key))
+                    .collect::<Result<_>>()?;
+                // the SSTables of a level are keyed by their maximum key, so every SSTable before
+                // the first one with a maximum key greater than or equal to `key` is skipped, as
+                // is every SSTable after the last one with a minimum key less than `end`.
+                let mut level_data_iters = Vec::with_capacity(levels.len());
+                for level in &levels {
+                    let mut level_data_iter = VecDeque::new();
+                    let level_entries = level.range(key..).take_while(|level_entry| {
+                        compaction::is_in_range(
+                            &level_entry.1.summary.key_range,
+                            key,
+                            seek_end.as_ref(),
+                        )
+                    });
+                    for (index, level_entry) in level_entries.enumerate() {
+                        if !compaction::may_contain_prefix(level_entry.1, prefix.as_deref()) {
+                            continue;
+                        }
+                        if index == 0 {
+                            level_data_iter.push_back(level_entry.1.entry_iter_from(key)?);
+                        } else {
+                            level_data_iter.push_back(level_entry.1.entry_iter());
+                        }
+                    }
+                    level_data_iters.push(level_data_iter);
+                }
+                let range_tombstones = range_tombstones.clone();
+                let compaction_iter = LeveledIter::new(
+                    Some(Rc::clone(&metadata_lock_count)),
+                    sstable_data_iters,
+                    level_data_iters,
+                )?
+                .filter_map(move |entry_result| match entry_result {
+                    Ok(entry) => {
+                        let (key, value) = entry;
+                        if sstable::is_range_deleted(&range_tombstones, &key, value.logical_time) {
+                            return None;
+                        }
+                        value.data.map(|value| Ok((key, value)))
+                    }
+                    Err(error) => Some(Err(error)),
+                });
+                Ok(Box::new(compaction_iter) as Box<CompactionIter<T, U>>)
+            },
+            key,
+        )?;
+        if let Some(end) = end {
+            ret.set_end(end.clone());
+        }
+        Ok(ret)
+    }
+
+    fn checkpoint<P>(&mut self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        self.check_open()?;
+
+        let mut curr_metadata = self.curr_metadata.lock().unwrap();
+        if self.try_replace_metadata(&mut curr_metadata)? {
+            self.metadata_file.write(&*curr_metadata)?;
+        }
+
+        fs::create_dir(path.as_ref())?;
+        let metadata = curr_metadata.checkpoint(path.as_ref())?;
+        compaction::write_checkpoint(path.as_ref(), &metadata, self.curr_logical_time)
+    }
+
+    fn restore<P, Q>(checkpoint_path: P, path: Q) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let mut checkpoint = Self::open(checkpoint_path)?;
+        checkpoint.checkpoint(path.as_ref())?;
+        checkpoint.close()?;
+        Self::open(path)
+    }
+
+    fn sync(&mut self) -> Result<()> {
+        self.flush()?;
+
+        {
+            let curr_metadata = self.curr_metadata.lock().unwrap();
+            let level_sstable_iter = curr_metadata
+                .levels
+                .iter()
+                .flat_map(|level| level.iter().map(|level_entry| level_entry.1));
+            for sstable in curr_metadata.sstables.iter().chain(level_sstable_iter) {
+                sstable.sync()?;
+            }
+        }
+
+        self.logical_time_file.seek(SeekFrom::Start(0))?;
+        self.logical_time_file
+            .write_u64::<BigEndian>(self.curr_logical_time)?;
+
+        self.logical_time_file.sync_all()?;
+
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<()> {
+        if self.is_closed {
+            return Ok(());
+        }
+
+        self.sync()?;
+        self.is_closed = true;
+
+        Ok(())
+    }
+
+    #[cfg(any(feature = "model_testing", test))]
+    fn crash(&mut self) {
+        self.join_compaction_threads();
+        self.is_closed = true;
+    }
 }
 
 #[derive(Eq, Ord, PartialEq, PartialOrd)]
@@ -691,8 +1727,8 @@ type LeveledIterEntry<T, U> = cmp::Reverse<(T, SSTableValue<U>, LeveledIterEntry
 
 struct LeveledIter<T, U> {
     metadata_lock_count: Option<Rc<Cell<u64>>>,
-    sstable_data_iters: Vec<SSTableDataIter<T, U>>,
-    level_data_iters: Vec<VecDeque<SSTableDataIter<T, U>>>,
+    sstable_data_iters: Vec<SSTableEntryIter<T, U>>,
+    level_data_iters: Vec<VecDeque<SSTableEntryIter<T, U>>>,
     entries: BinaryHeap<LeveledIterEntry<T, U>>,
     last_key_opt: Option<T>,
 }
@@ -703,8 +1739,8 @@ where
     U: DeserializeOwned + Serialize,
 {
     fn get_next_level_entry(
-        level_data_iter: &mut VecDeque<SSTableDataIter<T, U>>,
-    ) -> Option<<SSTableDataIter<T, U> as Iterator>::Item> {
+        level_data_iter: &mut VecDeque<SSTableEntryIter<T, U>>,
+    ) -> Option<<SSTableEntryIter<T, U> as Iterator>::Item> {
         loop {
             let entry_opt = match level_data_iter.front_mut() {
                 Some(data_iter) => data_iter.next(),
@@ -720,8 +1756,8 @@ where
 
     pub fn new(
         metadata_lock_count: Option<Rc<Cell<u64>>>,
-        mut sstable_data_iters: Vec<SSTableDataIter<T, U>>,
-        mut level_data_iters: Vec<VecDeque<SSTableDataIter<T, U>>>,
+        mut sstable_data_iters: Vec<SSTableEntryIter<T, U>>,
+        mut level_data_iters: Vec<VecDeque<SSTableEntryIter<T, U>>>,
     ) -> Result<Self> {
         if let Some(ref metadata_lock_count) = metadata_lock_count {
             metadata_lock_count.set(metadata_lock_count.get() + 1);
@@ -0,0 +1,75 @@
+use crate::lsm_tree::Error;
+use std::path::PathBuf;
+
+/// Information about a SSTable that was flushed from the in-memory tree of a `LsmMap` or ingested
+/// into it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlushInfo {
+    /// The path of the folder of the SSTable.
+    pub path: PathBuf,
+    /// The number of entries in the SSTable.
+    pub entry_count: usize,
+    /// The size of the SSTable in bytes.
+    pub size: u64,
+}
+
+/// Information about a compaction of a compaction strategy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactionInfo {
+    /// The level that is compacted. Level `0` contains the newly flushed SSTables and is the only
+    /// level of a `SizeTieredStrategy`.
+    pub level: usize,
+    /// The number of SSTables of the level that are compacted.
+    pub sstable_count: usize,
+}
+
+/// A listener of the flushes and compactions of a compaction strategy.
+///
+/// The methods are called on the thread that performs the flush or compaction, which is a
+/// background thread for compactions, so they should return quickly. All methods do nothing by
+/// default.
+///
+/// # Examples
+///
+/// ```
+/// # use extended_collections::lsm_tree::Result;
+/// # fn foo() -> Result<()> {
+/// # use std::fs;
+/// use extended_collections::lsm_tree::compaction::{EventListener, FlushInfo, SizeTieredStrategy};
+/// use extended_collections::lsm_tree::LsmMap;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// #[derive(Default)]
+/// struct FlushCounter(AtomicUsize);
+///
+/// impl EventListener for FlushCounter {
+///     fn on_flush(&self, _info: &FlushInfo) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let flush_counter = Arc::new(FlushCounter::default());
+/// let mut sts = SizeTieredStrategy::new("example_event_listener", 10000, 4, 50000, 0.5, 1.5)?;
+/// sts.set_event_listener(Some(flush_counter.clone()));
+/// let mut map = LsmMap::new(sts);
+///
+/// map.insert(1, 1)?;
+/// map.flush()?;
+/// assert_eq!(flush_counter.0.load(Ordering::Relaxed), 1);
+/// # fs::remove_dir_all("example_event_listener")?;
+/// # Ok(())
+/// # }
+/// # foo().unwrap();
+/// ```
+pub trait EventListener: Send + Sync {
+    /// Called after a SSTable is flushed or ingested and added to the compaction strategy.
+    fn on_flush(&self, _info: &FlushInfo) {}
+
+    /// Called before a compaction starts.
+    fn on_compaction_start(&self, _info: &CompactionInfo) {}
+
+    /// Called after a compaction ends with the number of bytes written by the compaction, or the
+    /// error that aborted it.
+    fn on_compaction_end(&self, _info: &CompactionInfo, _result: Result<u64, &Error>) {}
+}
@@ -1,19 +1,477 @@
 //! Strategies for merging disk-resident sorted runs of data.
 
 mod leveled;
+mod listener;
 mod size_tiered;
 
 pub use self::leveled::LeveledStrategy;
+pub use self::listener::{CompactionInfo, EventListener, FlushInfo};
 pub use self::size_tiered::SizeTieredStrategy;
 
-use crate::lsm_tree::{Result, SSTable, SSTableValue};
+use crate::lsm_tree::{
+    sstable, Error, FilterStats, IoOptions, PrefixExtractor, RangeTombstone, Result, SSTable,
+    SSTableValue,
+};
+use bincode::{deserialize, serialize};
+use byteorder::{BigEndian, ByteOrder};
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
 use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
 use std::hash::Hash;
-use std::path::Path;
+use std::io::{Read, Write};
+use std::mem;
+use std::ops::Bound::{Included, Unbounded};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const METADATA_FILE_NAME: &str = "metadata.dat";
+const METADATA_PREV_FILE_NAME: &str = "metadata.dat.prev";
+const METADATA_TEMP_FILE_NAME: &str = "metadata.dat.tmp";
+const METADATA_HEADER_SIZE: usize = 16;
+
+// Computes the 64-bit FNV-1a hash of a slice of bytes.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// Reads a version of the metadata of a compaction strategy and returns its version number.
+fn read_metadata<M>(path: &Path) -> Result<(u64, M)>
+where
+    M: DeserializeOwned,
+{
+    let mut file = fs::File::open(path).map_err(|error| Error::file_io(path, "open", error))?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)
+        .map_err(|error| Error::file_io(path, "read", error))?;
+    if buffer.len() < METADATA_HEADER_SIZE
+        || BigEndian::read_u64(&buffer[8..16]) != checksum(&buffer[METADATA_HEADER_SIZE..])
+    {
+        return Err(Error::corruption(path, 0));
+    }
+    let metadata = deserialize(&buffer[METADATA_HEADER_SIZE..])
+        .map_err(|_| Error::corruption(path, METADATA_HEADER_SIZE as u64))?;
+    Ok((BigEndian::read_u64(&buffer[..8]), metadata))
+}
+
+// Durably writes a version of the metadata of a compaction strategy to the metadata file in
+// `path`. The version is written to a temporary file that is atomically renamed over the current
+// file, which becomes the previous file.
+fn write_metadata<M>(path: &Path, version: u64, metadata: &M) -> Result<()>
+where
+    M: Serialize,
+{
+    let mut buffer = vec![0; METADATA_HEADER_SIZE];
+    buffer.extend(serialize(metadata)?);
+    let checksum = checksum(&buffer[METADATA_HEADER_SIZE..]);
+    BigEndian::write_u64(&mut buffer[..8], version);
+    BigEndian::write_u64(&mut buffer[8..16], checksum);
+
+    let curr_path = path.join(METADATA_FILE_NAME);
+    let temp_path = path.join(METADATA_TEMP_FILE_NAME);
+    let mut temp_file = fs::File::create(&temp_path)?;
+    temp_file.write_all(&buffer)?;
+    temp_file.sync_all()?;
+    if curr_path.exists() {
+        fs::rename(&curr_path, path.join(METADATA_PREV_FILE_NAME))?;
+    }
+    fs::rename(&temp_path, &curr_path)?;
+    fs::File::open(path)?.sync_all()?;
+    Ok(())
+}
+
+// Removes the entries in the folder of a compaction strategy that are not referenced by the
+// current or the previous version of its metadata, and returns their paths. If `dry_run` is true,
+// then nothing is removed. SSTables are compared by name so that the folder can be moved.
+fn collect_garbage<M, F>(
+    path: &Path,
+    metadata: &M,
+    sstable_paths: F,
+    dry_run: bool,
+) -> Result<Vec<PathBuf>>
+where
+    M: DeserializeOwned,
+    F: Fn(&M) -> Vec<PathBuf>,
+{
+    let mut sstable_names = HashSet::new();
+    sstable_names.extend(sstable_paths(metadata));
+    // an invalid previous version cannot be opened, so its SSTables do not need to be kept
+    if let Ok((_, prev_metadata)) = read_metadata(&path.join(METADATA_PREV_FILE_NAME)) {
+        sstable_names.extend(sstable_paths(&prev_metadata));
+    }
+    let sstable_names: HashSet<_> = sstable_names
+        .iter()
+        .filter_map(|sstable_path| sstable_path.file_name())
+        .collect();
+
+    let mut ret = Vec::new();
+    for dir_entry in fs::read_dir(path).map_err(|error| Error::file_io(path, "read", error))? {
+        let entry_path = dir_entry?.path();
+        let is_garbage = match entry_path.file_name() {
+            Some(name) if entry_path.is_dir() => !sstable_names.contains(name),
+            Some(name) => name == METADATA_TEMP_FILE_NAME,
+            None => false,
+        };
+        if !is_garbage {
+            continue;
+        }
+        if !dry_run {
+            if entry_path.is_dir() {
+                fs::remove_dir_all(&entry_path)?;
+            } else {
+                fs::remove_file(&entry_path)?;
+            }
+        }
+        ret.push(entry_path);
+    }
+    ret.sort();
+    Ok(ret)
+}
+
+// The metadata file of a compaction strategy. Each version of the metadata is prefixed with its
+// version number and a checksum, and the previous version is kept so that a strategy can be opened
+// even if the current version is corrupted. SSTables that are removed from the metadata are only
+// deleted once no version on disk references them.
+struct MetadataFile<T, U> {
+    path: PathBuf,
+    version: u64,
+    // SSTables that were removed since the current version was written.
+    removed_sstables: RefCell<Vec<Arc<SSTable<T, U>>>>,
+    // SSTables that are only referenced by the previous version.
+    prev_sstables: Vec<Arc<SSTable<T, U>>>,
+}
+
+impl<T, U> MetadataFile<T, U> {
+    fn new<M>(path: &Path, metadata: &M) -> Result<Self>
+    where
+        M: Serialize,
+    {
+        write_metadata(path, 1, metadata)?;
+        Ok(MetadataFile {
+            path: PathBuf::from(path),
+            version: 1,
+            removed_sstables: RefCell::new(Vec::new()),
+            prev_sstables: Vec::new(),
+        })
+    }
+
+    // Opens the metadata file in `path` and returns the current version of the metadata, falling
+    // back to the previous version if the current version is missing or corrupted. If neither
+    // version is valid, then the error of the current version is returned.
+    fn open<M>(path: &Path) -> Result<(Self, M)>
+    where
+        M: DeserializeOwned,
+    {
+        let prev_path = path.join(METADATA_PREV_FILE_NAME);
+        let (version, metadata) = match read_metadata(&path.join(METADATA_FILE_NAME)) {
+            Ok(curr) => curr,
+            Err(error) => {
+                if !prev_path.exists() {
+                    return Err(error);
+                }
+                read_metadata(&prev_path).map_err(|_| error)?
+            }
+        };
+        let ret = MetadataFile {
+            path: PathBuf::from(path),
+            version,
+            removed_sstables: RefCell::new(Vec::new()),
+            prev_sstables: Vec::new(),
+        };
+        Ok((ret, metadata))
+    }
+
+    // Defers the deletion of an SSTable that was removed from the metadata until no version on
+    // disk references it.
+    fn remove_sstable(&self, sstable: Arc<SSTable<T, U>>) {
+        self.removed_sstables.borrow_mut().push(sstable);
+    }
+
+    // Returns the paths of the removed SSTables that have not been deleted yet.
+    fn removed_sstable_paths(&self) -> Vec<PathBuf> {
+        let removed_sstables = self.removed_sstables.borrow();
+        removed_sstables
+            .iter()
+            .chain(self.prev_sstables.iter())
+            .map(|sstable| sstable.path.clone())
+            .collect()
+    }
+
+    // Writes a new version of the metadata. The new version is durable once this function
+    // returns, so the SSTables that were only referenced by the previous version are deleted.
+    fn write<M>(&mut self, metadata: &M) -> Result<()>
+    where
+        M: Serialize,
+    {
+        write_metadata(&self.path, self.version + 1, metadata)?;
+        self.version += 1;
+        for sstable in self.prev_sstables.drain(..) {
+            sstable.mark_obsolete();
+        }
+        self.prev_sstables = mem::take(self.removed_sstables.get_mut());
+        Ok(())
+    }
+}
+
+// Writes the metadata and the logical time of a checkpoint of a compaction strategy to `path`.
+fn write_checkpoint<M>(path: &Path, metadata: &M, logical_time: u64) -> Result<()>
+where
+    M: Serialize,
+{
+    write_metadata(path, 1, metadata)?;
+    let mut buffer = [0; 8];
+    BigEndian::write_u64(&mut buffer, logical_time);
+    fs::write(path.join("logical_time.dat"), buffer)?;
+    fs::File::open(path.join("logical_time.dat"))?.sync_all()?;
+    Ok(())
+}
+
+// Returns whether an SSTable whose keys are in `key_range` may contain a key in `[start, end)`. A
+// missing `end` includes every key greater than or equal to `start`.
+fn is_in_range<T>(key_range: &(T, T), start: &T, end: Option<&T>) -> bool
+where
+    T: Ord,
+{
+    *start <= key_range.1 && end.is_none_or(|end| key_range.0 < *end)
+}
+
+// Returns the prefix of every key in `[start, end)` if `start` and `end` have the same prefix.
+fn get_range_prefix<T>(
+    prefix_extractor: Option<&PrefixExtractor<T>>,
+    start: &T,
+    end: Option<&T>,
+) -> Option<Vec<u8>> {
+    let prefix_extractor = prefix_extractor?;
+    let prefix = prefix_extractor(start);
+    if prefix == prefix_extractor(end?) {
+        Some(prefix)
+    } else {
+        None
+    }
+}
+
+// Returns whether `sstable` may contain a key with `prefix`, or `true` if there is no prefix.
+fn may_contain_prefix<T, U>(sstable: &SSTable<T, U>, prefix: Option<&[u8]>) -> bool {
+    prefix.is_none_or(|prefix| sstable.may_contain_prefix(prefix))
+}
+
+/// Statistics of the SSTables in a level of a compaction strategy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevelStats {
+    /// The number of SSTables in the level.
+    pub sstable_count: usize,
+    /// The number of entries in the SSTables of the level, including tombstones.
+    pub entry_count: usize,
+    /// The number of tombstones in the SSTables of the level.
+    pub tombstone_count: usize,
+    /// The total size of the files of the SSTables of the level in bytes.
+    pub disk_size: u64,
+    /// The observed false positive rate of the bloom filters of the SSTables of the level. See
+    /// `FilterStats::false_positive_rate`.
+    pub filter_false_positive_rate: f64,
+}
+
+impl LevelStats {
+    fn new<'a, T, U, I>(sstables: I) -> Result<Self>
+    where
+        T: 'a,
+        U: 'a,
+        I: IntoIterator<Item = &'a Arc<SSTable<T, U>>>,
+    {
+        let mut ret = LevelStats {
+            sstable_count: 0,
+            entry_count: 0,
+            tombstone_count: 0,
+            disk_size: 0,
+            filter_false_positive_rate: 0.0,
+        };
+        let mut false_positive_count = 0;
+        let mut negative_count = 0;
+        for sstable in sstables {
+            let filter_stats = sstable.filter_stats();
+            ret.sstable_count += 1;
+            ret.entry_count += sstable.summary.entry_count;
+            ret.tombstone_count += sstable.summary.tombstone_count;
+            ret.disk_size += sstable.disk_size()?;
+            false_positive_count += filter_stats.false_positive_count;
+            negative_count += filter_stats.negative_count();
+        }
+        if negative_count != 0 {
+            ret.filter_false_positive_rate = false_positive_count as f64 / negative_count as f64;
+        }
+        Ok(ret)
+    }
+}
+
+/// Statistics of the disk-resident data of a compaction strategy.
+///
+/// The number of bytes written by flushes and compactions are counted from when the compaction
+/// strategy was constructed or opened, and are not persisted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactionStats {
+    /// The statistics of each level of SSTables. Strategies that do not organize SSTables into
+    /// levels report a single level.
+    pub levels: Vec<LevelStats>,
+    /// The number of bytes of SSTables built from flushed in-memory trees.
+    pub flushed_size: u64,
+    /// The number of bytes of SSTables built by compactions.
+    pub compacted_size: u64,
+}
+
+impl CompactionStats {
+    /// Returns the total number of SSTables across all levels.
+    pub fn sstable_count(&self) -> usize {
+        self.levels.iter().map(|level| level.sstable_count).sum()
+    }
+
+    /// Returns the total size of the files of the SSTables across all levels in bytes.
+    pub fn disk_size(&self) -> u64 {
+        self.levels.iter().map(|level| level.disk_size).sum()
+    }
+
+    /// Returns the write amplification, which is the ratio of the number of bytes written to disk
+    /// to the number of bytes flushed from in-memory trees. Returns `0.0` if no bytes were
+    /// flushed.
+    pub fn write_amplification(&self) -> f64 {
+        if self.flushed_size == 0 {
+            0.0
+        } else {
+            (self.flushed_size + self.compacted_size) as f64 / self.flushed_size as f64
+        }
+    }
+}
 
 /// An iterator for the disk-resident data.
 pub type CompactionIter<T, U> = dyn Iterator<Item = Result<(T, U)>>;
 
+type SeekFn<T, U> = dyn Fn(&T) -> Result<Box<CompactionIter<T, U>>>;
+
+/// An iterator for the disk-resident data that can be repositioned to any key.
+///
+/// The iterator is created from a function that returns an iterator over the key-value pairs with
+/// keys greater than or equal to a given key. Seeking replaces the current iterator with the one
+/// returned by the function, so a compaction strategy can locate the key using the indexes of its
+/// SSTables instead of scanning the disk-resident data from the beginning.
+pub struct CompactionSeekIter<T, U> {
+    seek_fn: Box<SeekFn<T, U>>,
+    iter: Box<CompactionIter<T, U>>,
+    end: Option<T>,
+}
+
+impl<T, U> CompactionSeekIter<T, U>
+where
+    T: Ord,
+{
+    /// Constructs a new `CompactionSeekIter<T, U>` positioned at the first key greater than or
+    /// equal to `key`.
+    pub fn new<F>(seek_fn: F, key: &T) -> Result<Self>
+    where
+        F: 'static + Fn(&T) -> Result<Box<CompactionIter<T, U>>>,
+    {
+        let iter = seek_fn(key)?;
+        Ok(CompactionSeekIter {
+            seek_fn: Box::new(seek_fn),
+            iter,
+            end: None,
+        })
+    }
+
+    // Stops the iterator at the first key greater than or equal to `end`.
+    pub(crate) fn set_end(&mut self, end: T) {
+        self.end = Some(end);
+    }
+
+    /// Repositions the iterator so that the next key-value pair yielded is the one with the
+    /// smallest key greater than or equal to `key`.
+    pub fn seek(&mut self, key: &T) -> Result<()> {
+        self.iter = (self.seek_fn)(key)?;
+        Ok(())
+    }
+}
+
+impl<T, U> Iterator for CompactionSeekIter<T, U>
+where
+    T: Ord,
+{
+    type Item = Result<(T, U)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.iter.next() {
+            Some(Ok((ref key, _))) if self.end.as_ref().is_some_and(|end| key >= end) => None,
+            entry_opt => entry_opt,
+        }
+    }
+}
+
+/// An immutable point-in-time view of the SSTables and range tombstones of a compaction strategy.
+///
+/// The view holds references to the SSTables, so their files are not deleted while the view is
+/// alive, even if the compaction strategy has since replaced them. Lookups on the view do not
+/// lock the compaction strategy, so it can be shared between threads.
+pub struct CompactionSnapshot<T, U> {
+    sstables: Vec<Arc<SSTable<T, U>>>,
+    levels: Vec<BTreeMap<T, Arc<SSTable<T, U>>>>,
+    range_tombstones: Vec<RangeTombstone<T>>,
+}
+
+impl<T, U> CompactionSnapshot<T, U> {
+    // Constructs a snapshot from a list of possibly overlapping SSTables, a list of levels of
+    // disjoint SSTables keyed by their maximum keys, and a list of range tombstones. The SSTables
+    // are searched before the levels, and the levels are searched in order.
+    pub(crate) fn new(
+        sstables: Vec<Arc<SSTable<T, U>>>,
+        levels: Vec<BTreeMap<T, Arc<SSTable<T, U>>>>,
+        range_tombstones: Vec<RangeTombstone<T>>,
+    ) -> Self {
+        CompactionSnapshot {
+            sstables,
+            levels,
+            range_tombstones,
+        }
+    }
+
+    // Returns the most recent value associated with `key` in the snapshot.
+    pub(crate) fn get<V>(&self, key: &V) -> Result<Option<SSTableValue<U>>>
+    where
+        T: Borrow<V> + DeserializeOwned + Ord,
+        U: DeserializeOwned,
+        V: Ord + Hash + ?Sized,
+    {
+        let mut ret = None;
+        for sstable in &self.sstables {
+            let res = sstable.get_value(key)?;
+            if res.is_some() && (ret.is_none() || res < ret) {
+                ret = res;
+            }
+        }
+
+        if ret.is_none() {
+            for level in &self.levels {
+                let sstable_opt = level
+                    .range((Included(key), Unbounded))
+                    .next()
+                    .map(|entry| entry.1);
+                if let Some(sstable) = sstable_opt {
+                    ret = sstable.get_value(key)?;
+                    if ret.is_some() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(sstable::apply_range_tombstones(
+            &self.range_tombstones,
+            key,
+            ret,
+        ))
+    }
+}
+
 /// Trait for types that have compaction logic for disk-resident data.
 ///
 /// A compaction strategy should incrementally accept SSTables and handle the logic for creating
@@ -25,13 +483,44 @@ pub trait CompactionStrategy<T, U> {
     /// Returns the maximum size of the in-memory tree in bytes.
     fn get_max_in_memory_size(&self) -> u64;
 
+    /// Returns the number of entries in each index block of newly built SSTables, or `None` if it
+    /// is the square root of the number of entries of each SSTable.
+    fn get_index_block_size(&self) -> Option<usize>;
+
+    /// Returns the false positive rate of the bloom filters of newly built SSTables.
+    fn get_filter_false_positive_rate(&self) -> f64;
+
+    /// Returns the minimum number of entries of newly built SSTables with partitioned bloom
+    /// filters, or `None` if partitioned bloom filters are disabled.
+    fn get_filter_partition_threshold(&self) -> Option<usize>;
+
+    /// Returns the options for the writes of newly built SSTables.
+    fn get_io_options(&self) -> IoOptions;
+
+    /// Returns the function that maps keys to the prefixes in the prefix bloom filters of newly
+    /// built SSTables, or `None` if prefix bloom filters are disabled.
+    fn get_prefix_extractor(&self) -> Option<PrefixExtractor<T>>;
+
+    /// Returns the current logical time of the compaction strategy.
+    fn get_logical_time(&self) -> u64;
+
     /// Returns and increments the current logical time of the compaction strategy.
     fn get_and_increment_logical_time(&mut self) -> Result<u64>;
 
+    /// Advances the current logical time of the compaction strategy to `logical_time` if it is
+    /// greater than the current logical time.
+    fn advance_logical_time(&mut self, logical_time: u64) -> Result<()>;
+
     /// Adds a SSTable to the compaction strategy and compacts the SSTables being tracked, if
     /// needed.
     fn try_compact(&mut self, sstable: SSTable<T, U>) -> Result<()>;
 
+    /// Deletes the keys in the range `[start, end)` of the disk-resident data that were written
+    /// before `logical_time` by recording a range tombstone. Reads and compactions ignore the
+    /// deleted entries, and the range tombstone is discarded once no SSTable may contain an entry
+    /// that it deletes.
+    fn remove_range(&mut self, start: T, end: T, logical_time: u64) -> Result<()>;
+
     /// Waits until the current compaction thread, if any, terminates and updates the metadata of
     /// the compaction strategy.
     fn flush(&mut self) -> Result<()>;
@@ -43,6 +532,10 @@ pub trait CompactionStrategy<T, U> {
         T: Borrow<V>,
         V: Ord + Hash + ?Sized;
 
+    /// Returns an immutable view of the disk-resident data that can be searched without locking
+    /// the compaction strategy.
+    fn snapshot(&mut self) -> Result<CompactionSnapshot<T, U>>;
+
     /// Returns the approximate number of items in the disk-resident data.
     fn len_hint(&mut self) -> Result<usize>;
 
@@ -63,7 +556,53 @@ pub trait CompactionStrategy<T, U> {
     /// is empty.
     fn max(&mut self) -> Result<Option<T>>;
 
+    /// Returns the runtime statistics of the bloom filters of the SSTables being tracked.
+    fn filter_stats(&mut self) -> Result<Vec<FilterStats>>;
+
+    /// Returns the statistics of the SSTables being tracked and the number of bytes written by
+    /// flushes and compactions.
+    fn stats(&mut self) -> Result<CompactionStats>;
+
     /// Returns an iterator over the disk-resident data. The iterator will yield key-value pairs
-    /// in ascending order.
-    fn iter(&mut self) -> Result<Box<CompactionIter<T, U>>>;
+    /// in ascending order. If `range` is `Some((start, end))`, only the keys in `[start, end)` are
+    /// yielded and the SSTables whose key ranges do not intersect the range are never opened.
+    fn iter(&mut self, range: Option<(&T, &T)>) -> Result<Box<CompactionIter<T, U>>>;
+
+    /// Returns a seekable iterator over the disk-resident data starting from the first key greater
+    /// than or equal to `key`. The iterator will yield key-value pairs in ascending order. If
+    /// `end` is `Some`, the iterator stops at the first key greater than or equal to `end`, even
+    /// after seeking, and the SSTables whose key ranges lie entirely outside of the keys being
+    /// iterated are never opened.
+    fn iter_from(&mut self, key: &T, end: Option<&T>) -> Result<CompactionSeekIter<T, U>>;
+
+    /// Creates a consistent point-in-time copy of the disk-resident data in a new folder at
+    /// `path`. The SSTables are hard-linked into the copy if possible and copied otherwise. The
+    /// compaction strategy remains open and an ongoing compaction is not interrupted.
+    fn checkpoint<P>(&mut self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>;
+
+    /// Restores a checkpoint created by `checkpoint` into a new folder at `path` and opens the
+    /// compaction strategy from the new folder. The checkpoint is left unmodified.
+    fn restore<P, Q>(checkpoint_path: P, path: Q) -> Result<Self>
+    where
+        Self: Sized,
+        P: AsRef<Path>,
+        Q: AsRef<Path>;
+
+    /// Waits until the current compaction thread, if any, terminates, persists the metadata of the
+    /// compaction strategy, and syncs all disk-resident data.
+    fn sync(&mut self) -> Result<()>;
+
+    /// Waits until the current compaction thread, if any, terminates, persists the metadata of the
+    /// compaction strategy, and syncs all disk-resident data. Any subsequent operations on the
+    /// compaction strategy will return `Error::Closed`.
+    fn close(&mut self) -> Result<()>;
+
+    /// Waits until the compaction threads, if any, terminate without persisting the metadata of
+    /// the compaction strategy or syncing any data, as if the process crashed after the
+    /// compactions finished. Any subsequent operations on the compaction strategy will return
+    /// `Error::Closed`.
+    #[cfg(any(feature = "model_testing", test))]
+    fn crash(&mut self) {}
 }
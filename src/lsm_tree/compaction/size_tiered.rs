@@ -1,7 +1,12 @@
 use crate::entry::Entry;
-use crate::lsm_tree::compaction::{CompactionIter, CompactionStrategy};
-use crate::lsm_tree::{sstable, Result, SSTable, SSTableBuilder, SSTableDataIter, SSTableValue};
-use bincode::{deserialize, serialize};
+use crate::lsm_tree::compaction::{
+    self, CompactionInfo, CompactionIter, CompactionSeekIter, CompactionSnapshot, CompactionStats,
+    CompactionStrategy, EventListener, FlushInfo, LevelStats, MetadataFile,
+};
+use crate::lsm_tree::{
+    sstable, Error, FilterOptions, FilterStats, IoOptions, PrefixExtractor, RangeTombstone,
+    RateLimiter, Result, SSTable, SSTableBuilder, SSTableEntryIter, SSTableValue,
+};
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use serde::de::DeserializeOwned;
 use serde::ser::Serialize;
@@ -12,13 +17,13 @@ use std::cmp;
 use std::collections::{BinaryHeap, HashSet};
 use std::fs;
 use std::hash::Hash;
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{Seek, SeekFrom};
 use std::iter::FromIterator;
 use std::marker::Send;
 use std::mem;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread;
 
@@ -30,7 +35,9 @@ struct SizeTieredMetadata<T, U> {
     min_sstable_size: u64,
     bucket_low: f64,
     bucket_high: f64,
+    index_block_size: Option<usize>,
     sstables: Vec<Arc<SSTable<T, U>>>,
+    range_tombstones: Vec<RangeTombstone<T>>,
 }
 
 impl<T, U> SizeTieredMetadata<T, U> {
@@ -40,6 +47,7 @@ impl<T, U> SizeTieredMetadata<T, U> {
         min_sstable_size: u64,
         bucket_low: f64,
         bucket_high: f64,
+        index_block_size: Option<usize>,
     ) -> Self {
         SizeTieredMetadata {
             max_in_memory_size,
@@ -47,14 +55,53 @@ impl<T, U> SizeTieredMetadata<T, U> {
             min_sstable_size,
             bucket_low,
             bucket_high,
+            index_block_size,
             sstables: Vec::new(),
+            range_tombstones: Vec::new(),
         }
     }
 
+    pub fn sstable_paths(&self) -> Vec<PathBuf> {
+        self.sstables
+            .iter()
+            .map(|sstable| sstable.path.clone())
+            .collect()
+    }
+
     pub fn push_sstable(&mut self, sstable: Arc<SSTable<T, U>>) {
         self.sstables.push(sstable);
     }
 
+    // Discards the range tombstones that cannot delete an entry of any SSTable.
+    pub fn retain_range_tombstones(&mut self)
+    where
+        T: Ord,
+    {
+        let sstables = &self.sstables;
+        self.range_tombstones.retain(|range_tombstone| {
+            sstables
+                .iter()
+                .any(|sstable| range_tombstone.may_delete(sstable))
+        });
+    }
+
+    pub fn checkpoint<P>(&self, path: P) -> Result<Self>
+    where
+        T: Clone + DeserializeOwned,
+        U: Clone,
+        P: AsRef<Path>,
+    {
+        let sstables = self
+            .sstables
+            .iter()
+            .map(|sstable| sstable.checkpoint(path.as_ref()).map(Arc::new))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(SizeTieredMetadata {
+            sstables,
+            ..self.clone()
+        })
+    }
+
     pub fn get_compaction_range(&mut self) -> Option<(usize, usize)> {
         self.sstables.sort_by_key(|sstable| sstable.summary.size);
 
@@ -88,7 +135,15 @@ impl<T, U> SizeTieredMetadata<T, U> {
         }
     }
 
-    fn compact<P>(&mut self, path: P, range: (usize, usize)) -> Result<()>
+    fn compact<P>(
+        &mut self,
+        path: P,
+        range: (usize, usize),
+        filter_options: FilterOptions,
+        io_options: IoOptions,
+        prefix_extractor: Option<PrefixExtractor<T>>,
+        rate_limiter: Option<Arc<RateLimiter>>,
+    ) -> Result<u64>
     where
         T: Clone + DeserializeOwned + Hash + Ord + Serialize,
         U: DeserializeOwned + Serialize,
@@ -119,17 +174,22 @@ impl<T, U> SizeTieredMetadata<T, U> {
             is_older_range && !key_intersecting
         });
 
-        let mut sstable_builder = SSTableBuilder::new(
+        let mut sstable_builder = SSTableBuilder::with_options(
             path.as_ref(),
             old_sstables
                 .iter()
                 .map(|sstable| sstable.summary.entry_count)
                 .sum(),
+            self.index_block_size,
+            filter_options,
+            io_options,
         )?;
+        sstable_builder.set_prefix_extractor(prefix_extractor);
+        sstable_builder.set_rate_limiter(rate_limiter);
 
         let old_sstable_data_iters = old_sstables
             .iter()
-            .map(|sstable| sstable.data_iter())
+            .map(|sstable| sstable.entry_iter())
             .collect();
 
         drop(old_sstables);
@@ -138,16 +198,23 @@ impl<T, U> SizeTieredMetadata<T, U> {
         for entry in compaction_iter {
             let (key, value) = entry?;
 
+            if sstable::is_range_deleted(&self.range_tombstones, &key, value.logical_time) {
+                continue;
+            }
+
             if !purge_tombstone || value.data.is_some() {
                 sstable_builder.append(key, value)?;
             }
         }
 
         if sstable_builder.key_range.is_some() {
-            self.push_sstable(Arc::new(SSTable::new(sstable_builder.flush()?)?));
+            let sstable = SSTable::open(sstable_builder.flush()?)?;
+            let size = sstable.summary.size;
+            self.push_sstable(Arc::new(sstable));
+            Ok(size)
+        } else {
+            Ok(0)
         }
-
-        Ok(())
     }
 }
 
@@ -170,12 +237,23 @@ pub struct SizeTieredStrategy<T, U> {
     path: PathBuf,
     compaction_thread_join_handle: Option<thread::JoinHandle<()>>,
     is_compacting: Arc<AtomicBool>,
+    compaction_rate_limiter: Option<Arc<RateLimiter>>,
+    write_stall_threshold: Option<usize>,
     curr_logical_time: u64,
     logical_time_file: fs::File,
     metadata_lock_count: Rc<Cell<u64>>,
-    metadata_file: fs::File,
+    metadata_file: MetadataFile<T, U>,
     curr_metadata: Arc<Mutex<SizeTieredMetadata<T, U>>>,
     next_metadata: Arc<Mutex<Option<SizeTieredMetadata<T, U>>>>,
+    filter_false_positive_rate: f64,
+    filter_target_false_positive_rate: Option<f64>,
+    filter_partition_threshold: Option<usize>,
+    io_options: IoOptions,
+    prefix_extractor: Option<PrefixExtractor<T>>,
+    event_listener: Option<Arc<dyn EventListener>>,
+    flushed_size: u64,
+    compacted_size: Arc<AtomicU64>,
+    is_closed: bool,
 }
 
 impl<T, U> SizeTieredStrategy<T, U> {
@@ -204,6 +282,85 @@ impl<T, U> SizeTieredStrategy<T, U> {
         bucket_low: f64,
         bucket_high: f64,
     ) -> Result<Self>
+    where
+        T: Serialize,
+        U: Serialize,
+        P: AsRef<Path>,
+    {
+        let curr_metadata = SizeTieredMetadata::new(
+            max_in_memory_size,
+            max_sstable_count,
+            min_sstable_size,
+            bucket_low,
+            bucket_high,
+            None,
+        );
+        Self::from_metadata(path, curr_metadata)
+    }
+
+    /// Constructs a new `SizeTieredStrategy<T, U>` with specific configuration parameters whose
+    /// SSTables have index blocks of `index_block_size` entries. The index of a SSTable keeps the
+    /// first key of each of its index blocks in memory, so larger index blocks use less memory,
+    /// but reading an entry requires reading a larger index block from disk. By default, the
+    /// index blocks of a SSTable have the square root of the number of its entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index_block_size` is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{CompactionStrategy, SizeTieredStrategy};
+    ///
+    /// let sts: SizeTieredStrategy<u32, u32> = SizeTieredStrategy::with_index_block_size(
+    ///     "size_tiered_strategy_with_index_block_size",
+    ///     10000,
+    ///     4,
+    ///     50000,
+    ///     0.5,
+    ///     1.5,
+    ///     128,
+    /// )?;
+    /// assert_eq!(sts.get_index_block_size(), Some(128));
+    /// # fs::remove_dir_all("size_tiered_strategy_with_index_block_size")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn with_index_block_size<P>(
+        path: P,
+        max_in_memory_size: u64,
+        max_sstable_count: usize,
+        min_sstable_size: u64,
+        bucket_low: f64,
+        bucket_high: f64,
+        index_block_size: usize,
+    ) -> Result<Self>
+    where
+        T: Serialize,
+        U: Serialize,
+        P: AsRef<Path>,
+    {
+        assert!(
+            index_block_size > 0,
+            "Error: index block size must be greater than 0."
+        );
+        let curr_metadata = SizeTieredMetadata::new(
+            max_in_memory_size,
+            max_sstable_count,
+            min_sstable_size,
+            bucket_low,
+            bucket_high,
+            Some(index_block_size),
+        );
+        Self::from_metadata(path, curr_metadata)
+    }
+
+    fn from_metadata<P>(path: P, curr_metadata: SizeTieredMetadata<T, U>) -> Result<Self>
     where
         T: Serialize,
         U: Serialize,
@@ -211,11 +368,7 @@ impl<T, U> SizeTieredStrategy<T, U> {
     {
         fs::create_dir(path.as_ref())?;
 
-        let metadata_file = fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open(path.as_ref().join("metadata.dat"))?;
+        let metadata_file = MetadataFile::new(path.as_ref(), &curr_metadata)?;
         let logical_time_file = fs::OpenOptions::new()
             .read(true)
             .write(true)
@@ -225,25 +378,27 @@ impl<T, U> SizeTieredStrategy<T, U> {
             path: PathBuf::from(path.as_ref()),
             compaction_thread_join_handle: None,
             is_compacting: Arc::new(AtomicBool::new(false)),
+            compaction_rate_limiter: None,
+            write_stall_threshold: None,
             curr_logical_time: 0,
             logical_time_file,
             metadata_lock_count: Rc::new(Cell::new(0)),
             metadata_file,
-            curr_metadata: Arc::new(Mutex::new(SizeTieredMetadata::new(
-                max_in_memory_size,
-                max_sstable_count,
-                min_sstable_size,
-                bucket_low,
-                bucket_high,
-            ))),
+            curr_metadata: Arc::new(Mutex::new(curr_metadata)),
             next_metadata: Arc::new(Mutex::new(None)),
+            filter_false_positive_rate: sstable::DEFAULT_FILTER_FALSE_POSITIVE_RATE,
+            filter_target_false_positive_rate: None,
+            filter_partition_threshold: None,
+            io_options: IoOptions::default(),
+            prefix_extractor: None,
+            event_listener: None,
+            flushed_size: 0,
+            compacted_size: Arc::new(AtomicU64::new(0)),
+            is_closed: false,
         };
 
-        {
-            let curr_metadata = ret.curr_metadata.lock().unwrap();
-            ret.metadata_file.seek(SeekFrom::Start(0))?;
-            ret.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
-        }
+        ret.logical_time_file
+            .write_u64::<BigEndian>(ret.curr_logical_time)?;
 
         Ok(ret)
     }
@@ -270,55 +425,93 @@ impl<T, U> SizeTieredStrategy<T, U> {
         U: DeserializeOwned,
         P: AsRef<Path>,
     {
-        let mut metadata_file = fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open(path.as_ref().join("metadata.dat"))?;
+        let logical_time_path = path.as_ref().join("logical_time.dat");
+        let (metadata_file, curr_metadata) = MetadataFile::open(path.as_ref())?;
+        compaction::collect_garbage(
+            path.as_ref(),
+            &curr_metadata,
+            SizeTieredMetadata::sstable_paths,
+            false,
+        )?;
         let mut logical_time_file = fs::OpenOptions::new()
             .read(true)
             .write(true)
-            .open(path.as_ref().join("logical_time.dat"))?;
-        let mut buffer = Vec::new();
-        metadata_file.read_to_end(&mut buffer)?;
-        logical_time_file.seek(SeekFrom::Start(0))?;
+            .open(&logical_time_path)
+            .map_err(|error| Error::file_io(&logical_time_path, "open", error))?;
+
+        let curr_logical_time = logical_time_file
+            .seek(SeekFrom::Start(0))
+            .and_then(|_| logical_time_file.read_u64::<BigEndian>())
+            .map_err(|error| Error::file_io(&logical_time_path, "read", error))?;
         Ok(SizeTieredStrategy {
             path: PathBuf::from(path.as_ref()),
             compaction_thread_join_handle: None,
             is_compacting: Arc::new(AtomicBool::new(false)),
-            curr_logical_time: logical_time_file.read_u64::<BigEndian>()?,
+            compaction_rate_limiter: None,
+            write_stall_threshold: None,
+            curr_logical_time,
             logical_time_file,
             metadata_lock_count: Rc::new(Cell::new(0)),
             metadata_file,
-            curr_metadata: Arc::new(Mutex::new(deserialize(&buffer)?)),
+            curr_metadata: Arc::new(Mutex::new(curr_metadata)),
             next_metadata: Arc::new(Mutex::new(None)),
+            filter_false_positive_rate: sstable::DEFAULT_FILTER_FALSE_POSITIVE_RATE,
+            filter_target_false_positive_rate: None,
+            filter_partition_threshold: None,
+            io_options: IoOptions::default(),
+            prefix_extractor: None,
+            event_listener: None,
+            flushed_size: 0,
+            compacted_size: Arc::new(AtomicU64::new(0)),
+            is_closed: false,
         })
     }
 
-    fn compact<P>(
-        path: P,
-        is_compacting: &Arc<AtomicBool>,
-        mut metadata_snapshot: SizeTieredMetadata<T, U>,
-        next_metadata: &Arc<Mutex<Option<SizeTieredMetadata<T, U>>>>,
-        range: (usize, usize),
-    ) -> Result<()>
+    /// Removes the SSTables in the folder of a `SizeTieredStrategy<T, U>` that are not referenced
+    /// by its metadata, and returns their paths. These SSTables are left behind if the process
+    /// crashes after a compaction writes them, but before the metadata is replaced. If `dry_run` is
+    /// true, then the paths are returned without removing anything. `open` removes these SSTables
+    /// automatically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{CompactionStrategy, SizeTieredStrategy};
+    ///
+    /// let mut sts: SizeTieredStrategy<u32, u32> =
+    ///     SizeTieredStrategy::new("size_tiered_strategy_gc", 10000, 4, 50000, 0.5, 1.5)?;
+    /// sts.close()?;
+    ///
+    /// let garbage =
+    ///     SizeTieredStrategy::<u32, u32>::collect_garbage("size_tiered_strategy_gc", true)?;
+    /// assert!(garbage.is_empty());
+    /// # fs::remove_dir_all("size_tiered_strategy_gc")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn collect_garbage<P>(path: P, dry_run: bool) -> Result<Vec<PathBuf>>
     where
-        T: Clone + DeserializeOwned + Hash + Ord + Serialize,
-        U: DeserializeOwned + Serialize,
+        T: DeserializeOwned,
+        U: DeserializeOwned,
         P: AsRef<Path>,
     {
-        println!("Started compacting.");
-
-        metadata_snapshot.compact(path, range)?;
-        *next_metadata.lock().unwrap() = Some(metadata_snapshot);
-        is_compacting.store(false, Ordering::Release);
-
-        println!("Finished compacting");
-        Ok(())
+        let (_, metadata): (MetadataFile<T, U>, SizeTieredMetadata<T, U>) =
+            MetadataFile::open(path.as_ref())?;
+        compaction::collect_garbage(
+            path.as_ref(),
+            &metadata,
+            SizeTieredMetadata::sstable_paths,
+            dry_run,
+        )
     }
 
     fn spawn_compaction_thread(
         &mut self,
-        metadata_snapshot: SizeTieredMetadata<T, U>,
+        mut metadata_snapshot: SizeTieredMetadata<T, U>,
         range: (usize, usize),
     ) where
         T: 'static + Clone + DeserializeOwned + Hash + Ord + Send + Serialize + Sync,
@@ -327,30 +520,317 @@ impl<T, U> SizeTieredStrategy<T, U> {
         let path = self.path.clone();
         let next_metadata = self.next_metadata.clone();
         let is_compacting = self.is_compacting.clone();
+        let compacted_size = self.compacted_size.clone();
+        let filter_options = self.filter_options();
+        let io_options = self.io_options;
+        let prefix_extractor = self.prefix_extractor.clone();
+        let rate_limiter = self.compaction_rate_limiter.clone();
+        let event_listener = self.event_listener.clone();
         self.is_compacting.store(true, Ordering::Release);
         self.compaction_thread_join_handle = Some(thread::spawn(move || {
-            let compaction_result = SizeTieredStrategy::compact(
+            let compaction_info = CompactionInfo {
+                level: 0,
+                sstable_count: range.1 - range.0,
+            };
+            if let Some(ref event_listener) = event_listener {
+                event_listener.on_compaction_start(&compaction_info);
+            }
+            let compaction_result = metadata_snapshot.compact(
                 path,
-                &is_compacting,
-                metadata_snapshot,
-                &next_metadata,
                 range,
+                filter_options,
+                io_options,
+                prefix_extractor,
+                rate_limiter,
             );
+            if let Some(ref event_listener) = event_listener {
+                event_listener
+                    .on_compaction_end(&compaction_info, compaction_result.as_ref().copied());
+            }
 
-            match compaction_result {
-                Ok(_) => println!("Compaction terminated successfully."),
-                Err(error) => {
-                    is_compacting.store(false, Ordering::Release);
-                    println!("Compaction terminated with error: {:?}", error);
-                }
+            if let Ok(size) = compaction_result {
+                *next_metadata.lock().unwrap() = Some(metadata_snapshot);
+                compacted_size.fetch_add(size, Ordering::Relaxed);
             }
+            is_compacting.store(false, Ordering::Release);
         }));
     }
 
+    /// Sets the false positive rate of the bloom filters of newly built SSTables. The default
+    /// false positive rate is `0.05`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{CompactionStrategy, SizeTieredStrategy};
+    ///
+    /// let mut strategy: SizeTieredStrategy<u32, u32> =
+    ///     SizeTieredStrategy::new("size_tiered_strategy_filter_rate", 10000, 4, 50000, 0.5, 1.5)?;
+    /// strategy.set_filter_false_positive_rate(0.01);
+    /// assert_eq!(strategy.get_filter_false_positive_rate(), 0.01);
+    /// # fs::remove_dir_all("size_tiered_strategy_filter_rate")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_filter_false_positive_rate(&mut self, filter_false_positive_rate: f64) {
+        self.filter_false_positive_rate = filter_false_positive_rate;
+    }
+
+    /// Enables or disables auto-tuning of the bloom filters of newly built SSTables. If enabled,
+    /// the false positive rate of newly built filters is adjusted whenever a SSTable is added
+    /// such that the observed false positive rate of existing filters approaches
+    /// `target_false_positive_rate`. Auto-tuning is disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    ///
+    /// let mut strategy: SizeTieredStrategy<u32, u32> =
+    ///     SizeTieredStrategy::new("size_tiered_strategy_filter_tuning", 10000, 4, 50000, 0.5, 1.5)?;
+    /// strategy.set_filter_auto_tuning(Some(0.01));
+    /// # fs::remove_dir_all("size_tiered_strategy_filter_tuning")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_filter_auto_tuning(&mut self, target_false_positive_rate: Option<f64>) {
+        self.filter_target_false_positive_rate = target_false_positive_rate;
+    }
+
+    /// Sets the false positive rate of the bloom filters of newly built SSTables to the false
+    /// positive rate of a bloom filter that uses `bits_per_key` bits for each key. For example,
+    /// `10` bits per key yields a false positive rate of about `0.0082`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits_per_key` is not greater than `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{CompactionStrategy, SizeTieredStrategy};
+    ///
+    /// let mut strategy: SizeTieredStrategy<u32, u32> =
+    ///     SizeTieredStrategy::new("size_tiered_strategy_filter_bits", 1000, 4, 25000, 0.5, 1.5)?;
+    /// strategy.set_filter_bits_per_key(10.0);
+    /// assert!((strategy.get_filter_false_positive_rate() - 0.0082).abs() < 0.0001);
+    /// # fs::remove_dir_all("size_tiered_strategy_filter_bits")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_filter_bits_per_key(&mut self, bits_per_key: f64) {
+        self.filter_false_positive_rate = sstable::get_false_positive_rate(bits_per_key);
+    }
+
+    /// Enables or disables partitioned bloom filters for newly built SSTables with at least
+    /// `partition_threshold` entries. A SSTable with a partitioned bloom filter has a bloom filter
+    /// for each of its index blocks that is read from disk when a lookup queries it, instead of a
+    /// single bloom filter that is kept in memory. Partitioned bloom filters are disabled by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{CompactionStrategy, SizeTieredStrategy};
+    ///
+    /// let mut strategy: SizeTieredStrategy<u32, u32> =
+    ///     SizeTieredStrategy::new("size_tiered_strategy_filter_partitions", 1000, 4, 25000, 0.5, 1.5)?;
+    /// strategy.set_filter_partition_threshold(Some(1_000_000));
+    /// assert_eq!(strategy.get_filter_partition_threshold(), Some(1_000_000));
+    /// # fs::remove_dir_all("size_tiered_strategy_filter_partitions")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_filter_partition_threshold(&mut self, partition_threshold: Option<usize>) {
+        self.filter_partition_threshold = partition_threshold;
+    }
+
+    fn filter_options(&self) -> FilterOptions {
+        FilterOptions {
+            false_positive_rate: self.filter_false_positive_rate,
+            partition_threshold: self.filter_partition_threshold,
+        }
+    }
+
+    /// Sets the options for the writes of newly built SSTables. See `IoOptions` for the defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{CompactionStrategy, SizeTieredStrategy};
+    /// use extended_collections::lsm_tree::IoOptions;
+    ///
+    /// let mut strategy: SizeTieredStrategy<u32, u32> =
+    ///     SizeTieredStrategy::new("size_tiered_strategy_io_options", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let io_options = IoOptions {
+    ///     write_buffer_size: 1 << 20,
+    ///     sync_on_seal: true,
+    ///     direct_io: true,
+    /// };
+    /// strategy.set_io_options(io_options);
+    /// assert_eq!(strategy.get_io_options(), io_options);
+    /// # fs::remove_dir_all("size_tiered_strategy_io_options")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_io_options(&mut self, io_options: IoOptions) {
+        self.io_options = io_options;
+    }
+
+    /// Sets the function that maps keys to their prefixes, or disables prefix bloom filters if
+    /// `prefix_extractor` is `None`. Newly built SSTables have a bloom filter of the prefixes of
+    /// their keys, which lets range queries whose start and end keys have the same prefix skip
+    /// the SSTables that do not contain the prefix. The prefix extractor is not persisted and must
+    /// be set to the same function every time the compaction strategy is opened, as SSTables
+    /// built with a different prefix extractor may be skipped incorrectly. Prefix bloom filters
+    /// are disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use std::sync::Arc;
+    ///
+    /// let mut strategy: SizeTieredStrategy<(u32, u64), u32> =
+    ///     SizeTieredStrategy::new("size_tiered_strategy_prefix", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let prefix_extractor = Arc::new(|key: &(u32, u64)| key.0.to_be_bytes().to_vec());
+    /// strategy.set_prefix_extractor(Some(prefix_extractor));
+    /// # fs::remove_dir_all("size_tiered_strategy_prefix")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_prefix_extractor(&mut self, prefix_extractor: Option<PrefixExtractor<T>>) {
+        self.prefix_extractor = prefix_extractor;
+    }
+
+    /// Sets the listener that is notified of the flushes and compactions of the compaction
+    /// strategy, or removes it if `event_listener` is `None`. See `EventListener`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::{
+    ///     CompactionInfo, EventListener, SizeTieredStrategy,
+    /// };
+    /// use std::sync::Arc;
+    ///
+    /// struct CompactionLogger;
+    ///
+    /// impl EventListener for CompactionLogger {
+    ///     fn on_compaction_start(&self, info: &CompactionInfo) {
+    ///         eprintln!("Compacting level {}.", info.level);
+    ///     }
+    /// }
+    ///
+    /// let mut strategy: SizeTieredStrategy<u32, u32> =
+    ///     SizeTieredStrategy::new("size_tiered_strategy_listener", 10000, 4, 50000, 0.5, 1.5)?;
+    /// strategy.set_event_listener(Some(Arc::new(CompactionLogger)));
+    /// # fs::remove_dir_all("size_tiered_strategy_listener")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_event_listener(&mut self, event_listener: Option<Arc<dyn EventListener>>) {
+        self.event_listener = event_listener;
+    }
+
+    /// Sets the maximum number of bytes per second written by compactions, or removes the limit
+    /// if `bytes_per_second` is `None`. The limit is shared by all running compactions and takes
+    /// effect for compactions started after it is set. Compactions are not rate limited by
+    /// default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes_per_second` is `Some(0)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    ///
+    /// let mut strategy: SizeTieredStrategy<u32, u32> =
+    ///     SizeTieredStrategy::new("size_tiered_strategy_rate_limit", 10000, 4, 50000, 0.5, 1.5)?;
+    /// strategy.set_compaction_rate_limit(Some(1 << 20));
+    /// # fs::remove_dir_all("size_tiered_strategy_rate_limit")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_compaction_rate_limit(&mut self, bytes_per_second: Option<u64>) {
+        self.compaction_rate_limiter =
+            bytes_per_second.map(|bytes_per_second| Arc::new(RateLimiter::new(bytes_per_second)));
+    }
+
+    /// Sets the number of SSTables at which writes are stalled, or disables stalling if
+    /// `write_stall_threshold` is `None`. When the number of SSTables exceeds the
+    /// threshold after the in-memory tree is flushed, the flush blocks until compactions reduce
+    /// the number of SSTables to at most the threshold. Writes are not stalled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    ///
+    /// let mut strategy: SizeTieredStrategy<u32, u32> =
+    ///     SizeTieredStrategy::new("size_tiered_strategy_write_stall", 10000, 4, 50000, 0.5, 1.5)?;
+    /// strategy.set_write_stall_threshold(Some(16));
+    /// # fs::remove_dir_all("size_tiered_strategy_write_stall")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_write_stall_threshold(&mut self, write_stall_threshold: Option<usize>) {
+        self.write_stall_threshold = write_stall_threshold;
+    }
+
+    fn check_open(&self) -> Result<()> {
+        if self.is_closed {
+            Err(Error::Closed)
+        } else {
+            Ok(())
+        }
+    }
+
     fn try_replace_metadata(
         &self,
         curr_metadata: &mut MutexGuard<'_, SizeTieredMetadata<T, U>>,
-    ) -> Result<bool> {
+    ) -> Result<bool>
+    where
+        T: Ord,
+    {
         let mut next_metadata = self.next_metadata.lock().unwrap();
 
         if let Some(next_metadata) = next_metadata.take() {
@@ -372,14 +852,72 @@ impl<T, U> SizeTieredStrategy<T, U> {
 
             for old_sstable in old_sstables {
                 if !new_sstable_paths.contains(&old_sstable.path) {
-                    fs::remove_dir_all(old_sstable.path.as_path())?;
+                    self.metadata_file.remove_sstable(old_sstable);
                 }
             }
+            curr_metadata.retain_range_tombstones();
             Ok(true)
         } else {
             Ok(false)
         }
     }
+
+    // Spawns a compaction thread if no compaction is running and a bucket is full. Returns whether
+    // a compaction thread was spawned.
+    fn try_spawn_compaction_thread(&mut self) -> Result<bool>
+    where
+        T: 'static + Clone + DeserializeOwned + Hash + Ord + Send + Serialize + Sync,
+        U: 'static + Clone + DeserializeOwned + Send + Serialize + Sync,
+    {
+        if self.is_compacting.load(Ordering::Acquire) || self.metadata_lock_count.get() != 0 {
+            return Ok(false);
+        }
+
+        // taking snapshot of current metadata
+        let mut metadata_snapshot = {
+            let mut curr_metadata = self.curr_metadata.lock().unwrap();
+            if self.try_replace_metadata(&mut curr_metadata)? {
+                self.metadata_file.write(&*curr_metadata)?;
+            }
+            curr_metadata.clone()
+        };
+
+        match metadata_snapshot.get_compaction_range() {
+            Some(range) => {
+                self.spawn_compaction_thread(metadata_snapshot, range);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    // Blocks until the number of SSTables is at most `write_stall_threshold`, or until no
+    // compaction can reduce it.
+    fn stall_writes(&mut self, write_stall_threshold: usize) -> Result<()>
+    where
+        T: 'static + Clone + DeserializeOwned + Hash + Ord + Send + Serialize + Sync,
+        U: 'static + Clone + DeserializeOwned + Send + Serialize + Sync,
+    {
+        loop {
+            if let Some(compaction_thread_join_handle) = self.compaction_thread_join_handle.take() {
+                compaction_thread_join_handle.join().ok();
+            }
+
+            {
+                let mut curr_metadata = self.curr_metadata.lock().unwrap();
+                if self.try_replace_metadata(&mut curr_metadata)? {
+                    self.metadata_file.write(&*curr_metadata)?;
+                }
+                if curr_metadata.sstables.len() <= write_stall_threshold {
+                    return Ok(());
+                }
+            }
+
+            if !self.try_spawn_compaction_thread()? {
+                return Ok(());
+            }
+        }
+    }
 }
 
 impl<T, U> CompactionStrategy<T, U> for SizeTieredStrategy<T, U>
@@ -395,7 +933,33 @@ where
         self.curr_metadata.lock().unwrap().max_in_memory_size
     }
 
+    fn get_index_block_size(&self) -> Option<usize> {
+        self.curr_metadata.lock().unwrap().index_block_size
+    }
+
+    fn get_filter_false_positive_rate(&self) -> f64 {
+        self.filter_false_positive_rate
+    }
+
+    fn get_filter_partition_threshold(&self) -> Option<usize> {
+        self.filter_partition_threshold
+    }
+
+    fn get_io_options(&self) -> IoOptions {
+        self.io_options
+    }
+
+    fn get_prefix_extractor(&self) -> Option<PrefixExtractor<T>> {
+        self.prefix_extractor.clone()
+    }
+
+    fn get_logical_time(&self) -> u64 {
+        self.curr_logical_time
+    }
+
     fn get_and_increment_logical_time(&mut self) -> Result<u64> {
+        self.check_open()?;
+
         let ret = self.curr_logical_time;
         self.curr_logical_time += 1;
         self.logical_time_file.seek(SeekFrom::Start(0))?;
@@ -404,46 +968,78 @@ where
         Ok(ret)
     }
 
+    fn advance_logical_time(&mut self, logical_time: u64) -> Result<()> {
+        self.check_open()?;
+
+        if logical_time > self.curr_logical_time {
+            self.curr_logical_time = logical_time;
+            self.logical_time_file.seek(SeekFrom::Start(0))?;
+            self.logical_time_file
+                .write_u64::<BigEndian>(self.curr_logical_time)?;
+        }
+        Ok(())
+    }
+
     fn try_compact(&mut self, sstable: SSTable<T, U>) -> Result<()> {
+        self.check_open()?;
+
+        self.flushed_size += sstable.summary.size;
+        let flush_info = FlushInfo {
+            path: sstable.path.clone(),
+            entry_count: sstable.summary.entry_count,
+            size: sstable.summary.size,
+        };
         {
             let mut curr_metadata = self.curr_metadata.lock().unwrap();
             curr_metadata.push_sstable(Arc::new(sstable));
-            self.metadata_file.seek(SeekFrom::Start(0))?;
-            self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
+            self.metadata_file.write(&*curr_metadata)?;
+        }
+        if let Some(ref event_listener) = self.event_listener {
+            event_listener.on_flush(&flush_info);
         }
 
-        if self.is_compacting.load(Ordering::Acquire) || self.metadata_lock_count.get() != 0 {
-            return Ok(());
+        if let Some(target_false_positive_rate) = self.filter_target_false_positive_rate {
+            let filter_stats = self.filter_stats()?;
+            self.filter_false_positive_rate = sstable::tune_false_positive_rate(
+                self.filter_false_positive_rate,
+                target_false_positive_rate,
+                &filter_stats,
+            );
         }
 
-        // taking snapshot of current metadata
-        let mut metadata_snapshot = {
-            let mut curr_metadata = self.curr_metadata.lock().unwrap();
-            if self.try_replace_metadata(&mut curr_metadata)? {
-                self.metadata_file.seek(SeekFrom::Start(0))?;
-                self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
-            }
-            curr_metadata.clone()
-        };
+        self.try_spawn_compaction_thread()?;
 
-        if let Some(range) = metadata_snapshot.get_compaction_range() {
-            self.spawn_compaction_thread(metadata_snapshot, range);
+        if let Some(write_stall_threshold) = self.write_stall_threshold {
+            self.stall_writes(write_stall_threshold)?;
         }
 
         Ok(())
     }
 
+    fn remove_range(&mut self, start: T, end: T, logical_time: u64) -> Result<()> {
+        self.check_open()?;
+
+        let mut curr_metadata = self.curr_metadata.lock().unwrap();
+        self.try_replace_metadata(&mut curr_metadata)?;
+        curr_metadata.range_tombstones.push(RangeTombstone {
+            start,
+            end,
+            logical_time,
+        });
+        curr_metadata.retain_range_tombstones();
+        self.metadata_file.write(&*curr_metadata)?;
+        Ok(())
+    }
+
     fn flush(&mut self) -> Result<()> {
+        self.check_open()?;
+
         if let Some(compaction_thread_join_handle) = self.compaction_thread_join_handle.take() {
-            match compaction_thread_join_handle.join() {
-                Ok(_) => println!("Child thread terminated successfully."),
-                Err(error) => println!("Child thread terminated with error: {:?}", error),
-            }
+            compaction_thread_join_handle.join().ok();
 
             let mut curr_metadata = self.curr_metadata.lock().unwrap();
             if self.try_replace_metadata(&mut curr_metadata)? {
-                self.metadata_file.seek(SeekFrom::Start(0))?;
-                self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
+                self.metadata_file.write(&*curr_metadata)?;
             }
         }
         Ok(())
@@ -454,28 +1050,49 @@ where
         T: Borrow<V>,
         V: Ord + Hash + ?Sized,
     {
+        self.check_open()?;
+
         let mut curr_metadata = self.curr_metadata.lock().unwrap();
         if self.try_replace_metadata(&mut curr_metadata)? {
-            self.metadata_file.seek(SeekFrom::Start(0))?;
-            self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
+            self.metadata_file.write(&*curr_metadata)?;
         }
 
         let mut ret = None;
         for sstable in &curr_metadata.sstables {
-            let res = sstable.get(&key)?;
+            let res = sstable.get_value(&key)?;
             if res.is_some() && (ret.is_none() || res < ret) {
                 ret = res;
             }
         }
 
-        Ok(ret)
+        Ok(sstable::apply_range_tombstones(
+            &curr_metadata.range_tombstones,
+            key,
+            ret,
+        ))
+    }
+
+    fn snapshot(&mut self) -> Result<CompactionSnapshot<T, U>> {
+        self.check_open()?;
+
+        let mut curr_metadata = self.curr_metadata.lock().unwrap();
+        if self.try_replace_metadata(&mut curr_metadata)? {
+            self.metadata_file.write(&*curr_metadata)?;
+        }
+
+        Ok(CompactionSnapshot::new(
+            curr_metadata.sstables.clone(),
+            Vec::new(),
+            curr_metadata.range_tombstones.clone(),
+        ))
     }
 
     fn len_hint(&mut self) -> Result<usize> {
+        self.check_open()?;
+
         let mut curr_metadata = self.curr_metadata.lock().unwrap();
         if self.try_replace_metadata(&mut curr_metadata)? {
-            self.metadata_file.seek(SeekFrom::Start(0))?;
-            self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
+            self.metadata_file.write(&*curr_metadata)?;
         }
 
         let len_hint = curr_metadata
@@ -488,7 +1105,7 @@ where
     }
 
     fn len(&mut self) -> Result<usize> {
-        Ok(self.iter()?.count())
+        Ok(self.iter(None)?.count())
     }
 
     fn is_empty(&mut self) -> Result<bool> {
@@ -496,64 +1113,110 @@ where
     }
 
     fn clear(&mut self) -> Result<()> {
+        self.check_open()?;
+
         if let Some(compaction_thread_join_handle) = self.compaction_thread_join_handle.take() {
-            match compaction_thread_join_handle.join() {
-                Ok(_) => println!("Child thread terminated successfully."),
-                Err(error) => println!("Child thread terminated with error: {:?}", error),
-            }
+            compaction_thread_join_handle.join().ok();
         }
 
         let mut curr_metadata = self.curr_metadata.lock().unwrap();
         let mut next_metadata = self.next_metadata.lock().unwrap();
-        curr_metadata.sstables.clear();
+        // snapshots may still hold the SSTables, so their files are deleted once they are dropped.
+        let mut sstable_paths = HashSet::new();
+        for sstable in curr_metadata.sstables.drain(..) {
+            sstable_paths.insert(sstable.path.clone());
+            self.metadata_file.remove_sstable(sstable);
+        }
+        sstable_paths.extend(self.metadata_file.removed_sstable_paths());
+        curr_metadata.range_tombstones.clear();
         *next_metadata = None;
 
         for dir_entry in fs::read_dir(self.path.as_path())? {
             let dir_path = dir_entry?.path();
-            if dir_path.is_dir() {
+            if dir_path.is_dir() && !sstable_paths.contains(&dir_path) {
                 fs::remove_dir_all(dir_path)?;
             }
         }
 
-        self.metadata_file.seek(SeekFrom::Start(0))?;
-        self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
+        self.metadata_file.write(&*curr_metadata)?;
 
         Ok(())
     }
 
     fn min(&mut self) -> Result<Option<T>> {
-        match self.iter()?.next() {
+        match self.iter(None)?.next() {
             Some(entry) => Ok(Some(entry?.0)),
             None => Ok(None),
         }
     }
 
     fn max(&mut self) -> Result<Option<T>> {
-        match self.iter()?.last() {
+        match self.iter(None)?.last() {
             Some(entry) => Ok(Some(entry?.0)),
             None => Ok(None),
         }
     }
 
-    fn iter(&mut self) -> Result<Box<CompactionIter<T, U>>> {
+    fn filter_stats(&mut self) -> Result<Vec<FilterStats>> {
+        self.check_open()?;
+
+        let mut curr_metadata = self.curr_metadata.lock().unwrap();
+        if self.try_replace_metadata(&mut curr_metadata)? {
+            self.metadata_file.write(&*curr_metadata)?;
+        }
+
+        let filter_stats = curr_metadata
+            .sstables
+            .iter()
+            .map(|sstable| sstable.filter_stats())
+            .collect();
+
+        Ok(filter_stats)
+    }
+
+    fn stats(&mut self) -> Result<CompactionStats> {
+        self.check_open()?;
+
+        let mut curr_metadata = self.curr_metadata.lock().unwrap();
+        if self.try_replace_metadata(&mut curr_metadata)? {
+            self.metadata_file.write(&*curr_metadata)?;
+        }
+
+        Ok(CompactionStats {
+            levels: vec![LevelStats::new(&curr_metadata.sstables)?],
+            flushed_size: self.flushed_size,
+            compacted_size: self.compacted_size.load(Ordering::Relaxed),
+        })
+    }
+
+    fn iter(&mut self, range: Option<(&T, &T)>) -> Result<Box<CompactionIter<T, U>>> {
+        if let Some((start, end)) = range {
+            return Ok(Box::new(self.iter_from(start, Some(end))?));
+        }
+
+        self.check_open()?;
+
         let mut curr_metadata = self.curr_metadata.lock().unwrap();
         // should never need to replace metadata as the compaction thread should not be running
         // when yielding calling iter.
         if self.try_replace_metadata(&mut curr_metadata)? {
-            self.metadata_file.seek(SeekFrom::Start(0))?;
-            self.metadata_file.write_all(&serialize(&*curr_metadata)?)?;
+            self.metadata_file.write(&*curr_metadata)?;
         }
 
         let sstable_data_iters = curr_metadata
             .sstables
             .iter()
-            .map(|sstable| sstable.data_iter())
+            .map(|sstable| sstable.entry_iter())
             .collect();
+        let range_tombstones = curr_metadata.range_tombstones.clone();
         let metadata_lock_count = Rc::clone(&self.metadata_lock_count);
         let compaction_iter = SizeTieredIter::new(Some(metadata_lock_count), sstable_data_iters)?
-            .filter_map(|entry_result| match entry_result {
+            .filter_map(move |entry_result| match entry_result {
                 Ok(entry) => {
                     let (key, value) = entry;
+                    if sstable::is_range_deleted(&range_tombstones, &key, value.logical_time) {
+                        return None;
+                    }
                     value.data.map(|value| Ok((key, value)))
                 }
                 Err(error) => Some(Err(error)),
@@ -561,13 +1224,130 @@ where
 
         Ok(Box::new(compaction_iter))
     }
+
+    fn iter_from(&mut self, key: &T, end: Option<&T>) -> Result<CompactionSeekIter<T, U>> {
+        self.check_open()?;
+
+        let mut curr_metadata = self.curr_metadata.lock().unwrap();
+        if self.try_replace_metadata(&mut curr_metadata)? {
+            self.metadata_file.write(&*curr_metadata)?;
+        }
+
+        let sstables = curr_metadata.sstables.clone();
+        let range_tombstones = curr_metadata.range_tombstones.clone();
+        let metadata_lock_count = Rc::clone(&self.metadata_lock_count);
+        let seek_end = end.cloned();
+        let prefix_extractor = self.prefix_extractor.clone();
+        let mut ret = CompactionSeekIter::new(
+            move |key| {
+                let prefix =
+                    compaction::get_range_prefix(prefix_extractor.as_ref(), key, seek_end.as_ref());
+                let sstable_data_iters = sstables
+                    .iter()
+                    .filter(|sstable| {
+                        compaction::is_in_range(&sstable.summary.key_range, key, seek_end.as_ref())
+                            && compaction::may_contain_prefix(sstable, prefix.as_deref())
+                    })
+                    .map(|sstable| sstable.entry_iter_from(key))
+                    .collect::<Result<_>>()?;
+                let range_tombstones = range_tombstones.clone();
+                let compaction_iter =
+                    SizeTieredIter::new(Some(Rc::clone(&metadata_lock_count)), sstable_data_iters)?
+                        .filter_map(move |entry_result| match entry_result {
+                            Ok(entry) => {
+                                let (key, value) = entry;
+                                if sstable::is_range_deleted(
+                                    &range_tombstones,
+                                    &key,
+                                    value.logical_time,
+                                ) {
+                                    return None;
+                                }
+                                value.data.map(|value| Ok((key, value)))
+                            }
+                            Err(error) => Some(Err(error)),
+                        });
+                Ok(Box::new(compaction_iter) as Box<CompactionIter<T, U>>)
+            },
+            key,
+        )?;
+        if let Some(end) = end {
+            ret.set_end(end.clone());
+        }
+        Ok(ret)
+    }
+
+    fn checkpoint<P>(&mut self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        self.check_open()?;
+
+        let mut curr_metadata = self.curr_metadata.lock().unwrap();
+        if self.try_replace_metadata(&mut curr_metadata)? {
+            self.metadata_file.write(&*curr_metadata)?;
+        }
+
+        fs::create_dir(path.as_ref())?;
+        let metadata = curr_metadata.checkpoint(path.as_ref())?;
+        compaction::write_checkpoint(path.as_ref(), &metadata, self.curr_logical_time)
+    }
+
+    fn restore<P, Q>(checkpoint_path: P, path: Q) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let mut checkpoint = Self::open(checkpoint_path)?;
+        checkpoint.checkpoint(path.as_ref())?;
+        checkpoint.close()?;
+        Self::open(path)
+    }
+
+    fn sync(&mut self) -> Result<()> {
+        self.flush()?;
+
+        {
+            let curr_metadata = self.curr_metadata.lock().unwrap();
+            for sstable in &curr_metadata.sstables {
+                sstable.sync()?;
+            }
+        }
+
+        self.logical_time_file.seek(SeekFrom::Start(0))?;
+        self.logical_time_file
+            .write_u64::<BigEndian>(self.curr_logical_time)?;
+
+        self.logical_time_file.sync_all()?;
+
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<()> {
+        if self.is_closed {
+            return Ok(());
+        }
+
+        self.sync()?;
+        self.is_closed = true;
+
+        Ok(())
+    }
+
+    #[cfg(any(feature = "model_testing", test))]
+    fn crash(&mut self) {
+        if let Some(compaction_thread_join_handle) = self.compaction_thread_join_handle.take() {
+            compaction_thread_join_handle.join().ok();
+        }
+        self.is_closed = true;
+    }
 }
 
 type SizeTieredIterEntry<T, U> = cmp::Reverse<(T, SSTableValue<U>, usize)>;
 
 struct SizeTieredIter<T, U> {
     metadata_lock_count: Option<Rc<Cell<u64>>>,
-    sstable_data_iters: Vec<SSTableDataIter<T, U>>,
+    sstable_data_iters: Vec<SSTableEntryIter<T, U>>,
     entries: BinaryHeap<SizeTieredIterEntry<T, U>>,
     last_key_opt: Option<T>,
 }
@@ -579,7 +1359,7 @@ where
 {
     pub fn new(
         metadata_lock_count: Option<Rc<Cell<u64>>>,
-        mut sstable_data_iters: Vec<SSTableDataIter<T, U>>,
+        mut sstable_data_iters: Vec<SSTableEntryIter<T, U>>,
     ) -> Result<Self> {
         if let Some(ref metadata_lock_count) = metadata_lock_count {
             metadata_lock_count.set(metadata_lock_count.get() + 1);
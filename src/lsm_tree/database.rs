@@ -0,0 +1,431 @@
+use crate::lsm_tree::compaction::CompactionStrategy;
+use crate::lsm_tree::{Error, LsmMap, Result};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::Hash;
+use std::io::{Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+// A column family of a `LsmDatabase`. Column families are stored as trait objects so that a
+// database can have column families with different key, value, and compaction strategy types.
+trait ColumnFamily {
+    fn flush(&mut self) -> Result<()>;
+
+    fn sync(&mut self) -> Result<()>;
+
+    fn close(self: Box<Self>) -> Result<()>;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T, U, C> ColumnFamily for LsmMap<T, U, C>
+where
+    T: Clone + Ord + Hash + DeserializeOwned + Serialize + 'static,
+    U: Clone + DeserializeOwned + Serialize + 'static,
+    C: CompactionStrategy<T, U> + 'static,
+{
+    fn flush(&mut self) -> Result<()> {
+        LsmMap::flush(self)
+    }
+
+    fn sync(&mut self) -> Result<()> {
+        LsmMap::sync(self)
+    }
+
+    fn close(self: Box<Self>) -> Result<()> {
+        LsmMap::close(*self)
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// A database of named `LsmMap`s, called column families, that are stored in a single folder.
+///
+/// Each column family has its own key and value types, in-memory tree, and compaction strategy,
+/// which is stored in a subfolder named after the column family. The column families share the
+/// logical clock of the database, so all writes to the database are ordered by their logical
+/// times, and they are flushed, synced, and closed together.
+///
+/// The column families of a database are not persisted, so they have to be added again with
+/// `add_cf` after the database is reopened.
+///
+/// # Examples
+///
+/// ```
+/// # use extended_collections::lsm_tree::Result;
+/// # fn foo() -> Result<()> {
+/// # use std::fs;
+/// use extended_collections::lsm_tree::compaction::{LeveledStrategy, SizeTieredStrategy};
+/// use extended_collections::lsm_tree::{LsmDatabase, LsmMap};
+///
+/// let mut db = LsmDatabase::new("example_lsm_database")?;
+/// db.add_cf("names", |path| {
+///     SizeTieredStrategy::<u32, String>::new(path, 10000, 4, 50000, 0.5, 1.5)
+/// })?;
+/// db.add_cf("scores", |path| {
+///     LeveledStrategy::<String, u64>::new(path, 10000, 4, 50000, 4, 10)
+/// })?;
+///
+/// let names: &mut LsmMap<u32, String, SizeTieredStrategy<u32, String>> = db.cf("names");
+/// names.insert(1, String::from("a"))?;
+/// let scores: &mut LsmMap<String, u64, LeveledStrategy<String, u64>> = db.cf("scores");
+/// scores.insert(String::from("a"), 10)?;
+/// db.close()?;
+///
+/// let mut db = LsmDatabase::open("example_lsm_database")?;
+/// let names = db.add_cf("names", SizeTieredStrategy::<u32, String>::open)?;
+/// assert_eq!(names.get(&1)?, Some(String::from("a")));
+/// # fs::remove_dir_all("example_lsm_database")?;
+/// # Ok(())
+/// # }
+/// # foo().unwrap();
+/// ```
+pub struct LsmDatabase {
+    path: PathBuf,
+    logical_clock: Rc<Cell<u64>>,
+    logical_time_file: fs::File,
+    column_families: HashMap<String, Box<dyn ColumnFamily>>,
+}
+
+impl LsmDatabase {
+    /// Constructs a new `LsmDatabase` without any column families in a new folder at `path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::LsmDatabase;
+    ///
+    /// let db = LsmDatabase::new("example_lsm_database_new")?;
+    /// # fs::remove_dir_all("example_lsm_database_new")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn new<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        fs::create_dir(path.as_ref())?;
+        let mut logical_time_file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path.as_ref().join("logical_time.dat"))?;
+        logical_time_file.write_u64::<BigEndian>(0)?;
+
+        Ok(LsmDatabase {
+            path: PathBuf::from(path.as_ref()),
+            logical_clock: Rc::new(Cell::new(0)),
+            logical_time_file,
+            column_families: HashMap::new(),
+        })
+    }
+
+    /// Opens an existing `LsmDatabase` from a folder. The column families of the database have
+    /// to be added again with `add_cf`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::LsmDatabase;
+    ///
+    /// let db = LsmDatabase::open("lsm_database_open")?;
+    /// # fs::remove_dir_all("lsm_database_open")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let logical_time_path = path.as_ref().join("logical_time.dat");
+        let mut logical_time_file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&logical_time_path)
+            .map_err(|error| Error::file_io(&logical_time_path, "open", error))?;
+        let logical_time = logical_time_file
+            .seek(SeekFrom::Start(0))
+            .and_then(|_| logical_time_file.read_u64::<BigEndian>())
+            .map_err(|error| Error::file_io(&logical_time_path, "read", error))?;
+
+        Ok(LsmDatabase {
+            path: PathBuf::from(path.as_ref()),
+            logical_clock: Rc::new(Cell::new(logical_time)),
+            logical_time_file,
+            column_families: HashMap::new(),
+        })
+    }
+
+    /// Adds a column family named `name` to the database and returns it. `f` is called with the
+    /// path of the folder of the column family and should either construct a new compaction
+    /// strategy in the folder or open the existing compaction strategy in it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the database already has a column family named `name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmDatabase;
+    ///
+    /// let mut db = LsmDatabase::new("example_lsm_database_add_cf")?;
+    /// let cf = db.add_cf("cf", |path| SizeTieredStrategy::new(path, 10000, 4, 50000, 0.5, 1.5))?;
+    /// cf.insert(1, 1)?;
+    /// assert_eq!(cf.get(&1)?, Some(1));
+    /// # fs::remove_dir_all("example_lsm_database_add_cf")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn add_cf<T, U, C, F>(&mut self, name: &str, f: F) -> Result<&mut LsmMap<T, U, C>>
+    where
+        T: Clone + Ord + Hash + DeserializeOwned + Serialize + 'static,
+        U: Clone + DeserializeOwned + Serialize + 'static,
+        C: CompactionStrategy<T, U> + 'static,
+        F: FnOnce(PathBuf) -> Result<C>,
+    {
+        assert!(
+            !self.contains_cf(name),
+            "Error: column family already exists."
+        );
+        let mut map = LsmMap::new(f(self.path.join(name))?);
+        map.set_logical_clock(Rc::clone(&self.logical_clock));
+        self.column_families.insert(name.to_string(), Box::new(map));
+        Ok(self.cf(name))
+    }
+
+    /// Returns `true` if the database has a column family named `name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmDatabase;
+    ///
+    /// let mut db = LsmDatabase::new("example_lsm_database_contains_cf")?;
+    /// db.add_cf("cf", |path| {
+    ///     SizeTieredStrategy::<u32, u32>::new(path, 10000, 4, 50000, 0.5, 1.5)
+    /// })?;
+    /// assert!(db.contains_cf("cf"));
+    /// assert!(!db.contains_cf("other_cf"));
+    /// # fs::remove_dir_all("example_lsm_database_contains_cf")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn contains_cf(&self, name: &str) -> bool {
+        self.column_families.contains_key(name)
+    }
+
+    /// Returns a mutable reference to the column family named `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no column family named `name`, or if its key, value, or compaction
+    /// strategy type does not match the column family that was added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::{LsmDatabase, LsmMap};
+    ///
+    /// let mut db = LsmDatabase::new("example_lsm_database_cf")?;
+    /// db.add_cf("cf", |path| {
+    ///     SizeTieredStrategy::<u32, u32>::new(path, 10000, 4, 50000, 0.5, 1.5)
+    /// })?;
+    ///
+    /// let cf: &mut LsmMap<u32, u32, SizeTieredStrategy<u32, u32>> = db.cf("cf");
+    /// cf.insert(1, 1)?;
+    /// assert_eq!(cf.get(&1)?, Some(1));
+    /// # fs::remove_dir_all("example_lsm_database_cf")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn cf<T, U, C>(&mut self, name: &str) -> &mut LsmMap<T, U, C>
+    where
+        T: Clone + Ord + Hash + DeserializeOwned + Serialize + 'static,
+        U: Clone + DeserializeOwned + Serialize + 'static,
+        C: CompactionStrategy<T, U> + 'static,
+    {
+        self.column_families
+            .get_mut(name)
+            .expect("Expected a column family with the given name.")
+            .as_any_mut()
+            .downcast_mut()
+            .expect("Expected the column family to have the given types.")
+    }
+
+    /// Closes the column family named `name`, removes it from the database, and deletes its
+    /// folder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no column family named `name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmDatabase;
+    ///
+    /// let mut db = LsmDatabase::new("example_lsm_database_remove_cf")?;
+    /// let cf = db.add_cf("cf", |path| SizeTieredStrategy::new(path, 10000, 4, 50000, 0.5, 1.5))?;
+    /// cf.insert(1, 1)?;
+    ///
+    /// db.remove_cf("cf")?;
+    /// assert!(!db.contains_cf("cf"));
+    /// # fs::remove_dir_all("example_lsm_database_remove_cf")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn remove_cf(&mut self, name: &str) -> Result<()> {
+        self.column_families
+            .remove(name)
+            .expect("Expected a column family with the given name.")
+            .close()?;
+        fs::remove_dir_all(self.path.join(name))?;
+        Ok(())
+    }
+
+    // Persists the logical clock of the database.
+    fn write_logical_time(&mut self) -> Result<()> {
+        self.logical_time_file.seek(SeekFrom::Start(0))?;
+        self.logical_time_file
+            .write_u64::<BigEndian>(self.logical_clock.get())?;
+        Ok(())
+    }
+
+    /// Flushes the in-memory trees of all column families of the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmDatabase;
+    ///
+    /// let mut db = LsmDatabase::new("example_lsm_database_flush")?;
+    /// let cf = db.add_cf("cf", |path| SizeTieredStrategy::new(path, 10000, 4, 50000, 0.5, 1.5))?;
+    /// cf.insert(1, 1)?;
+    /// db.flush()?;
+    /// # fs::remove_dir_all("example_lsm_database_flush")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn flush(&mut self) -> Result<()> {
+        for column_family in self.column_families.values_mut() {
+            column_family.flush()?;
+        }
+        self.write_logical_time()
+    }
+
+    /// Flushes all column families of the database and syncs all of their data to disk, including
+    /// the logical clock of the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmDatabase;
+    ///
+    /// let mut db = LsmDatabase::new("example_lsm_database_sync")?;
+    /// let cf = db.add_cf("cf", |path| SizeTieredStrategy::new(path, 10000, 4, 50000, 0.5, 1.5))?;
+    /// cf.insert(1, 1)?;
+    /// db.sync()?;
+    /// # fs::remove_dir_all("example_lsm_database_sync")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn sync(&mut self) -> Result<()> {
+        for column_family in self.column_families.values_mut() {
+            column_family.sync()?;
+        }
+        self.write_logical_time()?;
+        self.logical_time_file.sync_all()?;
+        Ok(())
+    }
+
+    /// Closes all column families of the database and syncs the logical clock of the database
+    /// before consuming the database. Unlike relying on the database being dropped, any errors
+    /// encountered while persisting the column families are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmDatabase;
+    ///
+    /// let mut db = LsmDatabase::new("example_lsm_database_close")?;
+    /// let cf = db.add_cf("cf", |path| SizeTieredStrategy::new(path, 10000, 4, 50000, 0.5, 1.5))?;
+    /// cf.insert(1, 1)?;
+    /// db.close()?;
+    ///
+    /// let mut db = LsmDatabase::open("example_lsm_database_close")?;
+    /// let cf = db.add_cf("cf", SizeTieredStrategy::<u32, u32>::open)?;
+    /// assert_eq!(cf.get(&1)?, Some(1));
+    /// # fs::remove_dir_all("example_lsm_database_close")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn close(mut self) -> Result<()> {
+        for (_, column_family) in self.column_families.drain() {
+            column_family.close()?;
+        }
+        self.write_logical_time()?;
+        self.logical_time_file.sync_all()?;
+        Ok(())
+    }
+}
+
+impl Drop for LsmDatabase {
+    fn drop(&mut self) {
+        // the column families flush themselves when they are dropped, so only the logical clock
+        // needs to be persisted.
+        let _ = self.write_logical_time();
+    }
+}
@@ -0,0 +1,220 @@
+#[cfg(target_os = "linux")]
+use std::cmp;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+#[cfg(target_os = "linux")]
+use std::os::unix::fs::OpenOptionsExt;
+#[cfg(target_os = "linux")]
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+// The alignment of the buffer, the offsets, and the lengths of direct writes. It is a multiple of
+// the logical block size of most devices.
+#[cfg(target_os = "linux")]
+const DIRECT_IO_ALIGNMENT: usize = 4096;
+
+// A writer of a file of a SSTable that either buffers writes and writes them through the page
+// cache, or writes them with direct I/O so that they bypass the page cache.
+pub enum FileWriter {
+    Buffered(BufWriter<fs::File>),
+    #[cfg(target_os = "linux")]
+    Direct(DirectWriter),
+}
+
+impl FileWriter {
+    // Creates the file at `path` and returns a writer that buffers up to `buffer_size` bytes. If
+    // `direct_io` is set, the file is written with direct I/O if the platform and the file system
+    // support it, and through the page cache otherwise.
+    pub fn create(path: &Path, buffer_size: usize, direct_io: bool) -> io::Result<Self> {
+        #[cfg(target_os = "linux")]
+        {
+            if direct_io {
+                if let Some(direct_writer) = DirectWriter::create(path, buffer_size)? {
+                    return Ok(FileWriter::Direct(direct_writer));
+                }
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = direct_io;
+
+        let file = fs::File::create(path)?;
+        Ok(FileWriter::Buffered(BufWriter::with_capacity(
+            buffer_size,
+            file,
+        )))
+    }
+}
+
+impl Write for FileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            FileWriter::Buffered(ref mut writer) => writer.write(buf),
+            #[cfg(target_os = "linux")]
+            FileWriter::Direct(ref mut writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            FileWriter::Buffered(ref mut writer) => writer.flush(),
+            #[cfg(target_os = "linux")]
+            FileWriter::Direct(ref mut writer) => writer.flush(),
+        }
+    }
+}
+
+// A writer that writes whole blocks of `DIRECT_IO_ALIGNMENT` bytes from an aligned buffer to a
+// file opened with `O_DIRECT`. Direct I/O is turned off for the file when the writer is flushed
+// with a partial block in its buffer, so the unaligned tail of the file is written through the
+// page cache.
+#[cfg(target_os = "linux")]
+pub struct DirectWriter {
+    file: fs::File,
+    buffer: Vec<u8>,
+    // the offset of the first aligned byte of `buffer`.
+    start: usize,
+    capacity: usize,
+    len: usize,
+    is_direct: bool,
+}
+
+#[cfg(target_os = "linux")]
+impl DirectWriter {
+    // Returns `None` if the file system does not support direct I/O.
+    fn create(path: &Path, buffer_size: usize) -> io::Result<Option<Self>> {
+        let file_result = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .custom_flags(libc::O_DIRECT)
+            .open(path);
+        let file = match file_result {
+            Ok(file) => file,
+            Err(ref error) if error.raw_os_error() == Some(libc::EINVAL) => return Ok(None),
+            Err(error) => return Err(error),
+        };
+
+        let capacity = cmp::max(buffer_size, 1).div_ceil(DIRECT_IO_ALIGNMENT) * DIRECT_IO_ALIGNMENT;
+        let buffer = vec![0; capacity + DIRECT_IO_ALIGNMENT];
+        let start = buffer.as_ptr().align_offset(DIRECT_IO_ALIGNMENT);
+        Ok(Some(DirectWriter {
+            file,
+            buffer,
+            start,
+            capacity,
+            len: 0,
+            is_direct: true,
+        }))
+    }
+
+    // Writes the whole blocks in the buffer to the file and moves the partial block to the start
+    // of the buffer.
+    fn write_blocks(&mut self) -> io::Result<()> {
+        let blocks_len = self.len / DIRECT_IO_ALIGNMENT * DIRECT_IO_ALIGNMENT;
+        if blocks_len > 0 {
+            let start = self.start;
+            self.file
+                .write_all(&self.buffer[start..start + blocks_len])?;
+            self.buffer
+                .copy_within(start + blocks_len..start + self.len, start);
+            self.len -= blocks_len;
+        }
+        Ok(())
+    }
+
+    fn disable_direct_io(&mut self) -> io::Result<()> {
+        let fd = self.file.as_raw_fd();
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags == -1 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_DIRECT) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        self.is_direct = false;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Write for DirectWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.is_direct {
+            return self.file.write(buf);
+        }
+
+        if self.len == self.capacity {
+            self.write_blocks()?;
+        }
+        let len = cmp::min(buf.len(), self.capacity - self.len);
+        let offset = self.start + self.len;
+        self.buffer[offset..offset + len].copy_from_slice(&buf[..len]);
+        self.len += len;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.is_direct {
+            self.write_blocks()?;
+            if self.len > 0 {
+                self.disable_direct_io()?;
+                let start = self.start;
+                self.file.write_all(&self.buffer[start..start + self.len])?;
+                self.len = 0;
+            }
+        }
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileWriter;
+    use rand::{thread_rng, Rng};
+    use std::fs;
+    use std::io::Write;
+    use std::path::Path;
+
+    fn check_write(test_name: &str, buffer_size: usize, direct_io: bool) {
+        let path = Path::new(test_name);
+        let mut rng = thread_rng();
+        let mut expected = Vec::new();
+        let mut writer = FileWriter::create(path, buffer_size, direct_io).unwrap();
+        for _ in 0..1000 {
+            let len = rng.gen_range(0, 100);
+            let bytes: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            writer.write_all(&bytes).unwrap();
+            expected.extend(bytes);
+        }
+        writer.flush().unwrap();
+        drop(writer);
+
+        let bytes = fs::read(path).unwrap();
+        fs::remove_file(path).unwrap();
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn test_buffered_write() {
+        check_write("test_file_writer_buffered_write", 1000, false);
+    }
+
+    #[test]
+    fn test_direct_write() {
+        check_write("test_file_writer_direct_write", 1000, true);
+    }
+
+    #[test]
+    fn test_direct_write_aligned() {
+        let path = Path::new("test_file_writer_direct_write_aligned");
+        let mut writer = FileWriter::create(path, 4096, true).unwrap();
+        writer.write_all(&[1; 8192]).unwrap();
+        writer.flush().unwrap();
+        writer.write_all(&[2; 100]).unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+
+        let bytes = fs::read(path).unwrap();
+        fs::remove_file(path).unwrap();
+        assert_eq!(bytes.len(), 8292);
+        assert!(bytes[..8192].iter().all(|byte| *byte == 1));
+        assert!(bytes[8192..].iter().all(|byte| *byte == 2));
+    }
+}
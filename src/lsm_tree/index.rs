@@ -0,0 +1,111 @@
+use crate::lsm_tree::compaction::CompactionStrategy;
+use crate::lsm_tree::{LsmMap, Result};
+use bincode::{self, serialize};
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use std::hash::Hash;
+
+// A secondary index of an `LsmMap` that is updated whenever an entry of the map changes. Indexes
+// are stored as trait objects so that a map can have indexes with different derived key types and
+// compaction strategies.
+pub trait SecondaryIndex<T, U> {
+    // Updates the index after the value associated with `key` changes from `old_value` to
+    // `new_value`.
+    fn update(&mut self, key: &T, old_value: Option<&U>, new_value: Option<&U>) -> Result<()>;
+
+    // Returns the primary keys associated with a serialized derived key in ascending order.
+    fn get(&mut self, derived_key: &[u8]) -> Result<Vec<T>>;
+
+    fn flush(&mut self) -> Result<()>;
+
+    fn sync(&mut self) -> Result<()>;
+
+    fn clear(&mut self) -> Result<()>;
+
+    fn close(self: Box<Self>) -> Result<()>;
+}
+
+type DeriveFn<U> = dyn Fn(&U) -> bincode::Result<Vec<u8>>;
+
+// A secondary index backed by a separate LSM tree that maps serialized derived keys to the sorted
+// primary keys of the entries with that derived key.
+pub struct LsmIndex<T, U, C>
+where
+    T: Clone + Ord + DeserializeOwned + Serialize,
+    C: CompactionStrategy<Vec<u8>, Vec<T>>,
+{
+    tree: LsmMap<Vec<u8>, Vec<T>, C>,
+    derive: Box<DeriveFn<U>>,
+}
+
+impl<T, U, C> LsmIndex<T, U, C>
+where
+    T: Clone + Ord + DeserializeOwned + Serialize,
+    C: CompactionStrategy<Vec<u8>, Vec<T>>,
+{
+    pub fn new<K, F>(compaction_strategy: C, f: F) -> Self
+    where
+        K: Serialize,
+        F: Fn(&U) -> K + 'static,
+    {
+        LsmIndex {
+            tree: LsmMap::new(compaction_strategy),
+            derive: Box::new(move |value| serialize(&f(value))),
+        }
+    }
+}
+
+impl<T, U, C> SecondaryIndex<T, U> for LsmIndex<T, U, C>
+where
+    T: Clone + Ord + Hash + DeserializeOwned + Serialize,
+    C: CompactionStrategy<Vec<u8>, Vec<T>>,
+{
+    fn update(&mut self, key: &T, old_value: Option<&U>, new_value: Option<&U>) -> Result<()> {
+        let old_derived_key = old_value.map(|value| (self.derive)(value)).transpose()?;
+        let new_derived_key = new_value.map(|value| (self.derive)(value)).transpose()?;
+        if old_derived_key == new_derived_key {
+            return Ok(());
+        }
+
+        if let Some(derived_key) = old_derived_key {
+            let mut keys = self.tree.get(derived_key.as_slice())?.unwrap_or_default();
+            if let Ok(index) = keys.binary_search(key) {
+                keys.remove(index);
+                if keys.is_empty() {
+                    self.tree.remove(derived_key)?;
+                } else {
+                    self.tree.insert(derived_key, keys)?;
+                }
+            }
+        }
+
+        if let Some(derived_key) = new_derived_key {
+            let mut keys = self.tree.get(derived_key.as_slice())?.unwrap_or_default();
+            if let Err(index) = keys.binary_search(key) {
+                keys.insert(index, key.clone());
+                self.tree.insert(derived_key, keys)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn get(&mut self, derived_key: &[u8]) -> Result<Vec<T>> {
+        Ok(self.tree.get(derived_key)?.unwrap_or_default())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.tree.flush()
+    }
+
+    fn sync(&mut self) -> Result<()> {
+        self.tree.sync()
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.tree.clear()
+    }
+
+    fn close(self: Box<Self>) -> Result<()> {
+        self.tree.close()
+    }
+}
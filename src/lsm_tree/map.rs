@@ -1,13 +1,26 @@
-use crate::lsm_tree::compaction::{CompactionIter, CompactionStrategy};
-use crate::lsm_tree::{Result, SSTable, SSTableBuilder, SSTableValue};
-use bincode::serialized_size;
+use crate::durability::{SyncMode, SyncState};
+use crate::entry::Entry;
+use crate::lsm_tree::compaction::{
+    CompactionIter, CompactionSeekIter, CompactionSnapshot, CompactionStats, CompactionStrategy,
+};
+use crate::lsm_tree::index::{LsmIndex, SecondaryIndex};
+use crate::lsm_tree::reader::{LsmSnapshot, SharedSnapshot};
+use crate::lsm_tree::{
+    FilterOptions, FilterStats, IoOptions, LsmReader, Result, SSTable, SSTableBuilder, SSTableValue,
+};
+use crate::treap::PersistentTreapMap;
+use bincode::{serialize, serialized_size};
 use serde::de::DeserializeOwned;
 use serde::ser::Serialize;
 use std::borrow::Borrow;
+use std::cell::Cell;
 use std::cmp;
-use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::hash::Hash;
 use std::mem;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::{Arc, RwLock};
 
 /// An ordered map implemented using a log structured merge-tree.
 ///
@@ -18,6 +31,10 @@ use std::mem;
 /// replaced, it could occur in multiple SSTables. The value in the most recent SSTable is fetched.
 /// When an entry is deleted, a tombstone is inserted to indicate that the entry is deleted.
 ///
+/// The in-memory tree is a persistent treap, so an immutable snapshot of it can be taken in
+/// constant time. The map acts as the single writer, and `reader` returns handles that perform
+/// lookups on snapshots of the map from other threads without blocking on the writer.
+///
 /// # Examples
 ///
 /// ```
@@ -49,10 +66,25 @@ use std::mem;
 /// # }
 /// # foo().unwrap();
 /// ```
-pub struct LsmMap<T, U, C> {
-    in_memory_tree: BTreeMap<T, SSTableValue<U>>,
+pub struct LsmMap<T, U, C>
+where
+    T: Clone + Ord + Hash + DeserializeOwned + Serialize,
+    U: Clone + DeserializeOwned + Serialize,
+    C: CompactionStrategy<T, U>,
+{
+    in_memory_tree: PersistentTreapMap<T, SSTableValue<U>>,
     in_memory_usage: u64,
     compaction_strategy: C,
+    indexes: HashMap<String, Box<dyn SecondaryIndex<T, U>>>,
+    // The snapshot of the disk-resident data that is published to readers, or `None` if the
+    // disk-resident data has changed since the snapshot was taken.
+    disk_snapshot: Option<Arc<CompactionSnapshot<T, U>>>,
+    // The snapshot shared with readers, or `None` if no reader has been created.
+    shared_snapshot: Option<SharedSnapshot<T, U>>,
+    sync_state: SyncState,
+    // The logical clock shared with the other column families of a `LsmDatabase`, or `None` if
+    // the map uses the logical clock of its compaction strategy.
+    logical_clock: Option<Rc<Cell<u64>>>,
 }
 
 impl<T, U, C> LsmMap<T, U, C>
@@ -81,23 +113,121 @@ where
     /// ```
     pub fn new(compaction_strategy: C) -> Self {
         LsmMap {
-            in_memory_tree: BTreeMap::new(),
+            in_memory_tree: PersistentTreapMap::new(),
             in_memory_usage: 0,
             compaction_strategy,
+            indexes: HashMap::new(),
+            disk_snapshot: None,
+            shared_snapshot: None,
+            sync_state: SyncState::new(SyncMode::default()),
+            logical_clock: None,
+        }
+    }
+
+    // Makes the map take its logical times from `logical_clock`, which is advanced past the
+    // logical time of the compaction strategy so that the logical times of the map keep
+    // increasing.
+    pub(crate) fn set_logical_clock(&mut self, logical_clock: Rc<Cell<u64>>) {
+        let logical_time = self.compaction_strategy.get_logical_time();
+        logical_clock.set(cmp::max(logical_clock.get(), logical_time));
+        self.logical_clock = Some(logical_clock);
+    }
+
+    // Returns and increments the logical time of the map.
+    fn next_logical_time(&mut self) -> Result<u64> {
+        match self.logical_clock {
+            Some(ref logical_clock) => {
+                let logical_time = logical_clock.get();
+                self.compaction_strategy
+                    .advance_logical_time(logical_time + 1)?;
+                logical_clock.set(logical_time + 1);
+                Ok(logical_time)
+            }
+            None => self.compaction_strategy.get_and_increment_logical_time(),
+        }
+    }
+
+    // Records a write to the map and syncs the map if the sync mode requires it.
+    fn record_write(&mut self) -> Result<()> {
+        if self.sync_state.record_write() {
+            self.sync()
+        } else {
+            Ok(())
+        }
+    }
+
+    // Publishes a snapshot of the current state of the map to the readers, if there are any.
+    fn publish_snapshot(&mut self) -> Result<()> {
+        let shared_snapshot = match self.shared_snapshot {
+            Some(ref shared_snapshot) => shared_snapshot,
+            None => return Ok(()),
+        };
+        let disk_snapshot = match self.disk_snapshot {
+            Some(ref disk_snapshot) => Arc::clone(disk_snapshot),
+            None => {
+                let disk_snapshot = Arc::new(self.compaction_strategy.snapshot()?);
+                self.disk_snapshot = Some(Arc::clone(&disk_snapshot));
+                disk_snapshot
+            }
+        };
+        *shared_snapshot.write().unwrap() = Arc::new(LsmSnapshot {
+            in_memory_tree: self.in_memory_tree.clone(),
+            disk_snapshot,
+        });
+        Ok(())
+    }
+
+    // Publishes a snapshot of the map after the disk-resident data has changed.
+    fn publish_disk_snapshot(&mut self) -> Result<()> {
+        self.disk_snapshot = None;
+        self.publish_snapshot()
+    }
+
+    // Updates the secondary indexes after the value associated with `key` changes to `new_value`.
+    fn update_indexes(&mut self, key: &T, new_value: Option<&U>) -> Result<()> {
+        if self.indexes.is_empty() {
+            return Ok(());
+        }
+        let old_value = self.get(key)?;
+        for index in self.indexes.values_mut() {
+            index.update(key, old_value.as_ref(), new_value)?;
+        }
+        Ok(())
+    }
+
+    // Returns the options of the bloom filters of newly built SSTables.
+    fn filter_options(&self) -> FilterOptions {
+        FilterOptions {
+            false_positive_rate: self.compaction_strategy.get_filter_false_positive_rate(),
+            partition_threshold: self.compaction_strategy.get_filter_partition_threshold(),
+        }
+    }
+
+    // Returns the options for the writes of the SSTables built by the map. Only compactions write
+    // SSTables with direct I/O, as recently flushed data is likely to be read soon.
+    fn io_options(&self) -> IoOptions {
+        IoOptions {
+            direct_io: false,
+            ..self.compaction_strategy.get_io_options()
         }
     }
 
     fn try_compact(&mut self) -> Result<()> {
         self.in_memory_usage = 0;
-        let mut sstable_builder = SSTableBuilder::new(
+        let mut sstable_builder = SSTableBuilder::with_options(
             self.compaction_strategy.get_path(),
             self.in_memory_tree.len(),
+            self.compaction_strategy.get_index_block_size(),
+            self.filter_options(),
+            self.io_options(),
         )?;
-        for entry in mem::replace(&mut self.in_memory_tree, BTreeMap::new()) {
-            sstable_builder.append(entry.0, entry.1)?;
+        sstable_builder.set_prefix_extractor(self.compaction_strategy.get_prefix_extractor());
+        for entry in &mem::take(&mut self.in_memory_tree) {
+            sstable_builder.append(entry.0.clone(), entry.1.clone())?;
         }
-        let sstable = SSTable::new(sstable_builder.flush()?)?;
-        self.compaction_strategy.try_compact(sstable)
+        let sstable = SSTable::open(sstable_builder.flush()?)?;
+        self.compaction_strategy.try_compact(sstable)?;
+        self.publish_disk_snapshot()
     }
 
     /// Inserts a key-value pair into the map. If the key-value pair causes the size of the
@@ -127,9 +257,10 @@ where
     /// # foo().unwrap();
     /// ```
     pub fn insert(&mut self, key: T, value: U) -> Result<()> {
+        self.update_indexes(&key, Some(&value))?;
         let value = SSTableValue {
             data: Some(value),
-            logical_time: self.compaction_strategy.get_and_increment_logical_time()?,
+            logical_time: self.next_logical_time()?,
         };
         let key_size = serialized_size(&key)?;
         let value_size = serialized_size(&value)?;
@@ -140,13 +271,14 @@ where
         }
 
         self.in_memory_usage += key_size + value_size;
-        self.in_memory_tree.insert(key, value);
+        self.in_memory_tree = self.in_memory_tree.insert(key, value);
 
         if self.in_memory_usage > self.compaction_strategy.get_max_in_memory_size() {
-            self.try_compact()
+            self.try_compact()?;
         } else {
-            Ok(())
+            self.publish_snapshot()?;
         }
+        self.record_write()
     }
 
     /// Removes a key-value pair into the map by inserting a tombstone. If the key-value pair causes
@@ -176,10 +308,11 @@ where
     /// # foo().unwrap();
     /// ```
     pub fn remove(&mut self, key: T) -> Result<()> {
+        self.update_indexes(&key, None)?;
         let key_size = serialized_size(&key)?;
         let value = SSTableValue {
             data: None,
-            logical_time: self.compaction_strategy.get_and_increment_logical_time()?,
+            logical_time: self.next_logical_time()?,
         };
 
         if let Some(ref value) = self.in_memory_tree.get(&key) {
@@ -189,13 +322,133 @@ where
 
         self.in_memory_usage += serialized_size(&key)?;
         self.in_memory_usage += serialized_size(&value)?;
-        self.in_memory_tree.insert(key, value);
+        self.in_memory_tree = self.in_memory_tree.insert(key, value);
 
         if self.in_memory_usage > self.compaction_strategy.get_max_in_memory_size() {
-            self.try_compact()
+            self.try_compact()?;
         } else {
-            Ok(())
+            self.publish_snapshot()?;
+        }
+        self.record_write()
+    }
+
+    /// Removes all keys in the range `[start, end)` from the map by recording a single range
+    /// tombstone instead of a tombstone for each key. The in-memory tree is flushed before the
+    /// range tombstone is recorded. Reads and compactions ignore the removed entries, and the
+    /// range tombstone is discarded once compactions have removed every entry that it deletes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_remove_range", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    ///
+    /// for key in 0..10 {
+    ///     map.insert(key, key)?;
+    /// }
+    /// map.remove_range(2, 8)?;
+    /// map.insert(5, 5)?;
+    ///
+    /// let keys: Vec<u32> = map.iter()?.map(|entry| entry.unwrap().0).collect();
+    /// assert_eq!(keys, vec![0, 1, 5, 8, 9]);
+    /// # fs::remove_dir_all("example_lsm_map_remove_range")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn remove_range(&mut self, start: T, end: T) -> Result<()> {
+        if start >= end {
+            return Ok(());
+        }
+
+        if !self.indexes.is_empty() {
+            let mut keys = Vec::new();
+            for entry in self.range(&start, &end)? {
+                keys.push(entry?.0);
+            }
+            for key in keys {
+                self.update_indexes(&key, None)?;
+            }
+        }
+
+        if !self.in_memory_tree.is_empty() {
+            self.try_compact()?;
         }
+        let logical_time = self.next_logical_time()?;
+        self.compaction_strategy
+            .remove_range(start, end, logical_time)?;
+        self.publish_disk_snapshot()?;
+        self.record_write()
+    }
+
+    /// Adds the entries of a SSTable built by a `SSTableBuilder` at `path` to the map, replacing
+    /// the values of existing keys. The in-memory tree is flushed first, and the entries are copied
+    /// sequentially into a new SSTable that is handed to the compaction strategy like a flushed
+    /// in-memory tree, so every entry is assigned the same logical time. The SSTable at `path` is
+    /// not modified. This is much faster than inserting the entries one at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::{LsmMap, SSTableBuilder};
+    ///
+    /// fs::create_dir("example_lsm_map_ingest_sstable_data")?;
+    /// let mut builder = SSTableBuilder::new("example_lsm_map_ingest_sstable_data", 100)?;
+    /// for key in 0..100 {
+    ///     builder.insert(key, key * 2)?;
+    /// }
+    /// let sstable_path = builder.finish()?;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_ingest_sstable", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    /// map.insert(1, 1)?;
+    /// map.ingest_sstable(&sstable_path)?;
+    /// assert_eq!(map.get(&1)?, Some(2));
+    /// assert_eq!(map.len()?, 100);
+    /// # fs::remove_dir_all("example_lsm_map_ingest_sstable_data")?;
+    /// # fs::remove_dir_all("example_lsm_map_ingest_sstable")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn ingest_sstable<P>(&mut self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let sstable: SSTable<T, U> = SSTable::open(path.as_ref())?;
+        if !self.in_memory_tree.is_empty() {
+            self.try_compact()?;
+        }
+
+        let logical_time = self.next_logical_time()?;
+        let mut sstable_builder = SSTableBuilder::with_options(
+            self.compaction_strategy.get_path(),
+            sstable.summary.entry_count,
+            self.compaction_strategy.get_index_block_size(),
+            self.filter_options(),
+            self.io_options(),
+        )?;
+        sstable_builder.set_prefix_extractor(self.compaction_strategy.get_prefix_extractor());
+        for entry in sstable.entry_iter() {
+            let Entry { key, mut value } = entry?;
+            self.update_indexes(&key, value.data.as_ref())?;
+            value.logical_time = logical_time;
+            sstable_builder.append(key, value)?;
+        }
+        let sstable = SSTable::open(sstable_builder.flush()?)?;
+        self.compaction_strategy.try_compact(sstable)?;
+        self.publish_disk_snapshot()?;
+        self.record_write()
     }
 
     /// Checks if a key exists in the map.
@@ -265,6 +518,149 @@ where
         }
     }
 
+    /// Returns a read-only handle to the map that can be cloned and shared between threads. The
+    /// handle performs lookups on the most recent snapshot of the map without blocking on the
+    /// writer, and observes every write once the corresponding operation on the map returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_reader", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    /// let reader = map.reader()?;
+    ///
+    /// map.insert(1, 1)?;
+    /// assert_eq!(reader.get(&1)?, Some(1));
+    ///
+    /// map.remove(1)?;
+    /// assert_eq!(reader.get(&1)?, None);
+    /// # fs::remove_dir_all("example_lsm_map_reader")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn reader(&mut self) -> Result<LsmReader<T, U>> {
+        if self.shared_snapshot.is_none() {
+            let disk_snapshot = Arc::new(self.compaction_strategy.snapshot()?);
+            self.disk_snapshot = Some(Arc::clone(&disk_snapshot));
+            self.shared_snapshot = Some(Arc::new(RwLock::new(Arc::new(LsmSnapshot {
+                in_memory_tree: self.in_memory_tree.clone(),
+                disk_snapshot,
+            }))));
+        }
+        let shared_snapshot = self
+            .shared_snapshot
+            .as_ref()
+            .expect("Expected a shared snapshot.");
+        Ok(LsmReader::new(Arc::clone(shared_snapshot)))
+    }
+
+    /// Creates a secondary index named `name` that maps the keys derived from the values of the
+    /// map by `f` to the keys of the map. The index is stored in a separate LSM tree managed by
+    /// `compaction_strategy` and is rebuilt from the entries of the map when it is created. The
+    /// index is kept consistent with the map on every insertion and removal, so an index should
+    /// be recreated whenever the map is reopened. An existing index with the same name is
+    /// replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_create_index", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    /// map.insert(1, String::from("a"))?;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_create_index_len", 10000, 4, 50000, 0.5, 1.5)?;
+    /// map.create_index("len", sts, |value: &String| value.len())?;
+    /// map.insert(2, String::from("bb"))?;
+    /// map.insert(3, String::from("c"))?;
+    ///
+    /// assert_eq!(map.get_by_index("len", &1usize)?, vec![1, 3]);
+    /// assert_eq!(map.get_by_index("len", &2usize)?, vec![2]);
+    /// # fs::remove_dir_all("example_lsm_map_create_index")?;
+    /// # fs::remove_dir_all("example_lsm_map_create_index_len")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn create_index<K, D, F>(&mut self, name: &str, compaction_strategy: D, f: F) -> Result<()>
+    where
+        T: 'static,
+        U: 'static,
+        K: Serialize,
+        D: CompactionStrategy<Vec<u8>, Vec<T>> + 'static,
+        F: Fn(&U) -> K + 'static,
+    {
+        let mut index = LsmIndex::new(compaction_strategy, f);
+        index.clear()?;
+        for entry in self.iter()? {
+            let (key, value) = entry?;
+            index.update(&key, None, Some(&value))?;
+        }
+        self.indexes.insert(name.to_string(), Box::new(index));
+        Ok(())
+    }
+
+    /// Returns the keys of the entries whose values have a derived key of `key` in the secondary
+    /// index named `name`. The keys are returned in ascending order. `key` must serialize
+    /// identically to the keys derived by the index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no index named `name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_get_by_index", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_get_by_index_parity", 10000, 4, 50000, 0.5, 1.5)?;
+    /// map.create_index("parity", sts, |value: &u32| value % 2)?;
+    /// map.insert(1, 10)?;
+    /// map.insert(2, 11)?;
+    /// assert_eq!(map.get_by_index("parity", &0u32)?, vec![1]);
+    ///
+    /// map.insert(1, 13)?;
+    /// assert_eq!(map.get_by_index("parity", &0u32)?, vec![]);
+    /// assert_eq!(map.get_by_index("parity", &1u32)?, vec![1, 2]);
+    ///
+    /// map.remove(2)?;
+    /// assert_eq!(map.get_by_index("parity", &1u32)?, vec![1]);
+    /// # fs::remove_dir_all("example_lsm_map_get_by_index")?;
+    /// # fs::remove_dir_all("example_lsm_map_get_by_index_parity")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn get_by_index<K>(&mut self, name: &str, key: &K) -> Result<Vec<T>>
+    where
+        K: Serialize + ?Sized,
+    {
+        let derived_key = serialize(key)?;
+        self.indexes
+            .get_mut(name)
+            .expect("Expected an index with the given name.")
+            .get(&derived_key)
+    }
+
     /// Returns the approximate number of elements in the map. The length returned will always be
     /// greater than or equal to the actual length. It counts all the non-tombstone entries stored
     /// in the SSTables, so it will overcount if there are duplicate entries or if a tombstone
@@ -294,6 +690,73 @@ where
         Ok(self.in_memory_tree.len() + self.compaction_strategy.len_hint()?)
     }
 
+    /// Returns the runtime statistics of the bloom filters of the SSTables in the map. A false
+    /// positive occurs when a filter reports that a key may exist in a SSTable, but the key does
+    /// not exist in the SSTable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_filter_stats", 10, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    ///
+    /// map.insert(1, 1)?;
+    /// map.insert(3, 3)?;
+    /// assert_eq!(map.get(&2)?, None);
+    ///
+    /// let filter_stats = map.filter_stats()?;
+    /// assert!(filter_stats.iter().all(|filter_stats| filter_stats.false_positive_rate() <= 1.0));
+    /// # fs::remove_dir_all("example_lsm_map_filter_stats")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn filter_stats(&mut self) -> Result<Vec<FilterStats>> {
+        self.compaction_strategy.filter_stats()
+    }
+
+    /// Returns the statistics of the SSTables in the map, such as the number of SSTables, the
+    /// number of tombstones, and the size on disk of each level, along with the number of bytes
+    /// written by flushes and compactions. The number of bytes written are counted from when the
+    /// compaction strategy was constructed or opened.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_stats", 10, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    ///
+    /// map.insert(1, 1)?;
+    /// map.insert(2, 2)?;
+    /// map.remove(1)?;
+    /// map.flush()?;
+    ///
+    /// let stats = map.stats()?;
+    /// assert_eq!(stats.levels.len(), 1);
+    /// assert_eq!(stats.levels[0].tombstone_count, 1);
+    /// assert!(stats.disk_size() > 0);
+    /// assert!(stats.write_amplification() >= 1.0);
+    /// # fs::remove_dir_all("example_lsm_map_stats")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn stats(&mut self) -> Result<CompactionStats> {
+        self.compaction_strategy.stats()
+    }
+
     /// Returns the number of elements in the map by first flushing the in-memory tree and then
     /// doing a full scan of all entries. For a more efficient, but approximate way of getting the
     /// length, see `len_hint`.
@@ -374,8 +837,13 @@ where
     /// # foo().unwrap();
     /// ```
     pub fn clear(&mut self) -> Result<()> {
-        self.in_memory_tree.clear();
-        self.compaction_strategy.clear()
+        self.in_memory_tree = PersistentTreapMap::new();
+        for index in self.indexes.values_mut() {
+            index.clear()?;
+        }
+        self.compaction_strategy.clear()?;
+        self.publish_disk_snapshot()?;
+        self.record_write()
     }
 
     /// Returns the minimum key of the map. Returns `None` if the map is empty.
@@ -443,15 +911,17 @@ where
         Ok(cmp::max(
             self.in_memory_tree
                 .iter()
-                .rev()
-                .find(|entry| entry.1.data.is_some())
+                .filter(|entry| entry.1.data.is_some())
+                .last()
                 .map(|entry| entry.0.clone()),
             self.compaction_strategy.max()?,
         ))
     }
 
-    /// Flushes the in-memory tree into a SSTable if it is not empty. The map must be flushed
-    /// before being dropped or the contents of the in-memory tree will be lost.
+    /// Flushes the in-memory tree into a SSTable if it is not empty and waits for any ongoing
+    /// compaction to terminate. Flushed writes survive a crash of the process, but not a crash of
+    /// the machine. The secondary indexes of the map are flushed as well. The map is also flushed
+    /// when it is dropped, but errors encountered while doing so are ignored.
     ///
     /// # Examples
     ///
@@ -477,7 +947,88 @@ where
         if !self.in_memory_tree.is_empty() {
             self.try_compact()?;
         }
-        self.compaction_strategy.flush()
+        for index in self.indexes.values_mut() {
+            index.flush()?;
+        }
+        self.compaction_strategy.flush()?;
+        self.publish_disk_snapshot()
+    }
+
+    /// Flushes the in-memory tree and creates a consistent point-in-time copy of the map in a new
+    /// folder at `path`. The map remains open and can be modified while the copy is used. The
+    /// secondary indexes of the map are not included in the copy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_checkpoint", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    ///
+    /// map.insert(1, 1)?;
+    /// map.checkpoint("example_lsm_map_checkpoint_backup")?;
+    /// map.insert(1, 2)?;
+    ///
+    /// let sts: SizeTieredStrategy<u32, u32> =
+    ///     SizeTieredStrategy::open("example_lsm_map_checkpoint_backup")?;
+    /// let mut backup = LsmMap::new(sts);
+    /// assert_eq!(backup.get(&1)?, Some(1));
+    /// assert_eq!(map.get(&1)?, Some(2));
+    /// # fs::remove_dir_all("example_lsm_map_checkpoint")?;
+    /// # fs::remove_dir_all("example_lsm_map_checkpoint_backup")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn checkpoint<P>(&mut self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        self.flush()?;
+        self.compaction_strategy.checkpoint(path)
+    }
+
+    /// Restores a checkpoint created by `checkpoint` into a new folder at `path` and opens the
+    /// map from the new folder. The checkpoint is left unmodified, so it can be restored again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_restore", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    /// map.insert(1, 1)?;
+    /// map.checkpoint("example_lsm_map_restore_backup")?;
+    /// map.clear()?;
+    ///
+    /// let mut map: LsmMap<u32, u32, SizeTieredStrategy<u32, u32>> = LsmMap::restore(
+    ///     "example_lsm_map_restore_backup",
+    ///     "example_lsm_map_restore_restored",
+    /// )?;
+    /// assert_eq!(map.get(&1)?, Some(1));
+    /// # fs::remove_dir_all("example_lsm_map_restore")?;
+    /// # fs::remove_dir_all("example_lsm_map_restore_backup")?;
+    /// # fs::remove_dir_all("example_lsm_map_restore_restored")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn restore<P, Q>(checkpoint_path: P, path: Q) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        Ok(LsmMap::new(C::restore(checkpoint_path, path)?))
     }
 
     /// Returns an iterator over the map. The iterator will yield key-value pairs in ascending
@@ -510,7 +1061,230 @@ where
     /// ```
     pub fn iter(&mut self) -> Result<Box<CompactionIter<T, U>>> {
         self.flush()?;
-        self.compaction_strategy.iter()
+        self.compaction_strategy.iter(None)
+    }
+
+    /// Returns a seekable iterator over the map starting from the first key greater than or equal
+    /// to `key`. The iterator will yield key-value pairs in ascending order. The iterator locates
+    /// `key` using the indexes of the SSTables instead of scanning the map from the beginning, and
+    /// can be repositioned with `seek`. The in-memory tree will be flushed before yielding the
+    /// iterator. The map will not perform any compactions if there are any undropped iterators.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_iter_from", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    ///
+    /// map.insert(1, 1)?;
+    /// map.insert(3, 3)?;
+    /// map.insert(5, 5)?;
+    ///
+    /// let mut iterator = map.iter_from(&2)?;
+    /// assert_eq!(iterator.next().unwrap()?, (3, 3));
+    /// assert_eq!(iterator.next().unwrap()?, (5, 5));
+    /// assert!(iterator.next().is_none());
+    ///
+    /// iterator.seek(&1)?;
+    /// assert_eq!(iterator.next().unwrap()?, (1, 1));
+    /// # fs::remove_dir_all("example_lsm_map_iter_from")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn iter_from(&mut self, key: &T) -> Result<CompactionSeekIter<T, U>> {
+        self.flush()?;
+        self.compaction_strategy.iter_from(key, None)
+    }
+
+    /// Returns a seekable iterator over the keys in the range `[start, end)` of the map. The
+    /// iterator will yield key-value pairs in ascending order and stops at the first key greater
+    /// than or equal to `end`, even after seeking. The SSTables whose key ranges do not intersect
+    /// the range are skipped instead of being opened. See `iter_from`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_range", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    ///
+    /// for key in 0..10 {
+    ///     map.insert(key, key)?;
+    /// }
+    ///
+    /// let keys: Vec<u32> = map.range(&3, &6)?.map(|entry| entry.unwrap().0).collect();
+    /// assert_eq!(keys, vec![3, 4, 5]);
+    /// # fs::remove_dir_all("example_lsm_map_range")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn range(&mut self, start: &T, end: &T) -> Result<CompactionSeekIter<T, U>> {
+        self.flush()?;
+        self.compaction_strategy.iter_from(start, Some(end))
+    }
+
+    /// Flushes the map and syncs all of its data to disk, including the metadata of the compaction
+    /// strategy and the secondary indexes of the map. Synced writes survive a crash of the machine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_sync", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    ///
+    /// map.insert(1, 1)?;
+    /// map.sync()?;
+    /// # fs::remove_dir_all("example_lsm_map_sync")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn sync(&mut self) -> Result<()> {
+        self.flush()?;
+        for index in self.indexes.values_mut() {
+            index.sync()?;
+        }
+        self.compaction_strategy.sync()?;
+        self.sync_state.record_sync();
+        Ok(())
+    }
+
+    /// Returns the sync mode of the map, which determines when writes are synced to disk. The
+    /// sync mode of a new map is `SyncMode::Manual`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::durability::SyncMode;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_sync_mode", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let map: LsmMap<u32, u32, _> = LsmMap::new(sts);
+    /// assert_eq!(map.sync_mode(), SyncMode::Manual);
+    /// # fs::remove_dir_all("example_lsm_map_sync_mode")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn sync_mode(&self) -> SyncMode {
+        self.sync_state.mode()
+    }
+
+    /// Sets the sync mode of the map, which determines when writes are synced to disk. Since the
+    /// map does not have a write-ahead log, syncing a write flushes the in-memory tree into a new
+    /// SSTable, so `SyncMode::Always` creates a SSTable for every write and should only be used
+    /// when writes are infrequent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::durability::SyncMode;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    /// use std::time::Duration;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_set_sync_mode", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    /// map.set_sync_mode(SyncMode::Interval(Duration::from_secs(1)));
+    ///
+    /// map.insert(1, 1)?;
+    /// assert_eq!(map.get(&1)?, Some(1));
+    /// # fs::remove_dir_all("example_lsm_map_set_sync_mode")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn set_sync_mode(&mut self, sync_mode: SyncMode) {
+        self.sync_state.set_mode(sync_mode);
+    }
+
+    /// Flushes the in-memory tree, waits for any ongoing compaction to terminate, and syncs all
+    /// data to disk before consuming the map. Unlike relying on the map being dropped, any errors
+    /// encountered while persisting the map are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_map_close", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    ///
+    /// map.insert(1, 1)?;
+    /// map.close()?;
+    ///
+    /// let sts: SizeTieredStrategy<u32, u32> = SizeTieredStrategy::open("example_lsm_map_close")?;
+    /// let mut map = LsmMap::new(sts);
+    /// assert_eq!(map.get(&1)?, Some(1));
+    /// # fs::remove_dir_all("example_lsm_map_close")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn close(mut self) -> Result<()> {
+        self.flush()?;
+        for (_, index) in self.indexes.drain() {
+            index.close()?;
+        }
+        self.compaction_strategy.close()?;
+        self.sync_state.record_sync();
+        Ok(())
+    }
+
+    // Leaks the map without flushing it after its compaction threads terminate, as if the process
+    // crashed. Unlike leaking the map directly, no compaction thread races with the next open of
+    // the map.
+    #[cfg(any(feature = "model_testing", test))]
+    pub(crate) fn crash(mut self) {
+        self.compaction_strategy.crash();
+        mem::forget(self);
+    }
+}
+
+impl<T, U, C> Drop for LsmMap<T, U, C>
+where
+    T: Clone + Ord + Hash + DeserializeOwned + Serialize,
+    U: Clone + DeserializeOwned + Serialize,
+    C: CompactionStrategy<T, U>,
+{
+    fn drop(&mut self) {
+        // errors cannot be reported when the map is dropped, so `close` should be used instead if
+        // they need to be handled.
+        if self.sync_state.should_sync_on_drop() {
+            let _ = self.sync();
+        } else {
+            let _ = self.flush();
+        }
     }
 }
 
@@ -1,24 +1,88 @@
 //! Hybrid tree comprised of disk-resident sorted runs of data and memory-resident tree.
 
 pub mod compaction;
+mod database;
+mod file_writer;
+mod index;
 mod map;
+mod rate_limiter;
+mod reader;
 mod sstable;
 
+pub use self::database::LsmDatabase;
+use self::file_writer::FileWriter;
 pub use self::map::LsmMap;
-use self::sstable::{SSTable, SSTableBuilder, SSTableDataIter, SSTableValue};
+use self::rate_limiter::RateLimiter;
+pub use self::reader::LsmReader;
+use self::sstable::{FilterOptions, RangeTombstone, SSTableEntryIter, SSTableValue};
+pub use self::sstable::{
+    FilterStats, IoOptions, PrefixExtractor, SSTable, SSTableBuilder, SSTableDataIter,
+    SSTableSummary, SizeHistogram,
+};
 use bincode;
 use std::error;
 use std::fmt;
 use std::io;
+use std::path::{Path, PathBuf};
 use std::result;
 
 /// Convenience `Error` enum for `lsm_tree`.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
-    /// An input or output error.
+    /// An input or output error that is not associated with a particular file.
     IOError(io::Error),
-    /// A serialization or deserialization error.
+    /// A serialization or deserialization error that is not associated with a particular file.
     SerdeError(bincode::Error),
+    /// An input or output error that occurred while performing `operation` on the file at `path`.
+    FileIOError {
+        /// The path of the file.
+        path: PathBuf,
+        /// The operation that failed.
+        operation: &'static str,
+        /// The underlying error.
+        source: io::Error,
+    },
+    /// The data at `offset` in the file at `path` could not be decoded.
+    Corruption {
+        /// The path of the file.
+        path: PathBuf,
+        /// The offset of the data in the file.
+        offset: u64,
+    },
+    /// The SSTable with the summary at `path` was written with a format `version` that is not
+    /// supported. SSTables written before the format version was stored have version `0`.
+    UnsupportedVersion {
+        /// The path of the summary of the SSTable.
+        path: PathBuf,
+        /// The format version of the SSTable.
+        version: u32,
+    },
+    /// An operation was attempted on a closed compaction strategy.
+    Closed,
+}
+
+impl Error {
+    pub(crate) fn file_io<P>(path: P, operation: &'static str, source: io::Error) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Error::FileIOError {
+            path: PathBuf::from(path.as_ref()),
+            operation,
+            source,
+        }
+    }
+
+    pub(crate) fn corruption<P>(path: P, offset: u64) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Error::Corruption {
+            path: PathBuf::from(path.as_ref()),
+            offset,
+        }
+    }
 }
 
 impl From<io::Error> for Error {
@@ -36,8 +100,10 @@ impl From<bincode::Error> for Error {
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
-            Error::IOError(ref error) => error.source(),
-            Error::SerdeError(ref error) => error.source(),
+            Error::IOError(ref error) => Some(error),
+            Error::SerdeError(ref error) => Some(error),
+            Error::FileIOError { ref source, .. } => Some(source),
+            Error::Corruption { .. } | Error::UnsupportedVersion { .. } | Error::Closed => None,
         }
     }
 }
@@ -47,6 +113,30 @@ impl fmt::Display for Error {
         match self {
             Error::IOError(ref error) => write!(f, "{}", error),
             Error::SerdeError(ref error) => write!(f, "{}", error),
+            Error::FileIOError {
+                ref path,
+                operation,
+                ref source,
+            } => write!(
+                f,
+                "failed to {} `{}`: {}",
+                operation,
+                path.display(),
+                source
+            ),
+            Error::Corruption { ref path, offset } => write!(
+                f,
+                "corrupted data at offset {} of `{}`",
+                offset,
+                path.display()
+            ),
+            Error::UnsupportedVersion { ref path, version } => write!(
+                f,
+                "unsupported SSTable format version {} in `{}`",
+                version,
+                path.display()
+            ),
+            Error::Closed => write!(f, "attempted to use a closed compaction strategy"),
         }
     }
 }
@@ -0,0 +1,95 @@
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+struct RateLimiterState {
+    available: f64,
+    last_refill: Instant,
+}
+
+// A token bucket that limits the number of bytes written per second. The bucket holds at most one
+// second worth of bytes and starts empty. A writer that acquires more bytes than are available
+// goes into debt and sleeps until the debt is repaid, so concurrent writers sharing a limiter are
+// throttled to the combined rate.
+pub struct RateLimiter {
+    bytes_per_second: u64,
+    state: Mutex<RateLimiterState>,
+}
+
+impl RateLimiter {
+    pub fn new(bytes_per_second: u64) -> Self {
+        assert!(
+            bytes_per_second > 0,
+            "Error: rate limit must be greater than 0 bytes per second."
+        );
+        RateLimiter {
+            bytes_per_second,
+            state: Mutex::new(RateLimiterState {
+                available: 0.0,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    // Blocks the current thread until `bytes` can be written without exceeding the rate limit.
+    pub fn acquire(&self, bytes: u64) {
+        let rate = self.bytes_per_second as f64;
+        let delay = {
+            let mut state = self.state.lock().unwrap();
+            let now = Instant::now();
+            let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+            state.available = (state.available + elapsed * rate).min(rate);
+            state.last_refill = now;
+            state.available -= bytes as f64;
+            if state.available < 0.0 {
+                Duration::from_secs_f64(-state.available / rate)
+            } else {
+                Duration::from_secs(0)
+            }
+        };
+
+        if delay > Duration::from_secs(0) {
+            thread::sleep(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimiter;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_acquire() {
+        let rate_limiter = RateLimiter::new(10_000);
+        let start = Instant::now();
+        for _ in 0..5 {
+            rate_limiter.acquire(1_000);
+        }
+        assert!(start.elapsed() >= Duration::from_millis(450));
+    }
+
+    #[test]
+    fn test_acquire_concurrent() {
+        let rate_limiter = Arc::new(RateLimiter::new(10_000));
+        let start = Instant::now();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let rate_limiter = Arc::clone(&rate_limiter);
+                thread::spawn(move || rate_limiter.acquire(1_000))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(350));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_rate() {
+        RateLimiter::new(0);
+    }
+}
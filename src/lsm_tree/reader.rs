@@ -0,0 +1,145 @@
+use crate::lsm_tree::compaction::CompactionSnapshot;
+use crate::lsm_tree::{Result, SSTableValue};
+use crate::treap::PersistentTreapMap;
+use serde::de::DeserializeOwned;
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+
+// An immutable point-in-time view of the in-memory tree and the disk-resident data of a `LsmMap`.
+pub struct LsmSnapshot<T, U> {
+    pub in_memory_tree: PersistentTreapMap<T, SSTableValue<U>>,
+    pub disk_snapshot: Arc<CompactionSnapshot<T, U>>,
+}
+
+// The most recent snapshot published by a `LsmMap`. The lock is only held to clone or replace the
+// snapshot, so readers never wait on the disk operations of the writer.
+pub type SharedSnapshot<T, U> = Arc<RwLock<Arc<LsmSnapshot<T, U>>>>;
+
+/// A read-only handle to a `LsmMap` that can be shared between threads.
+///
+/// A reader is created using `LsmMap::reader` and is cheap to clone. Each lookup is performed on
+/// the most recent snapshot published by the `LsmMap`, which consists of an immutable copy of the
+/// in-memory tree and the set of SSTables at the time of the last write. Lookups do not lock the
+/// `LsmMap` or its compaction strategy, so any number of readers can run concurrently with a
+/// single writer. A write is visible to readers once the operation on the `LsmMap` returns.
+///
+/// # Examples
+///
+/// ```
+/// # use extended_collections::lsm_tree::Result;
+/// # fn foo() -> Result<()> {
+/// # use std::fs;
+/// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+/// use extended_collections::lsm_tree::LsmMap;
+/// use std::thread;
+///
+/// let sts = SizeTieredStrategy::new("example_lsm_reader", 10000, 4, 50000, 0.5, 1.5)?;
+/// let mut map = LsmMap::new(sts);
+/// let reader = map.reader()?;
+///
+/// map.insert(0, 1)?;
+///
+/// let handle = thread::spawn(move || reader.get(&0));
+/// assert_eq!(handle.join().unwrap()?, Some(1));
+/// # fs::remove_dir_all("example_lsm_reader")?;
+/// # Ok(())
+/// # }
+/// # foo().unwrap();
+/// ```
+pub struct LsmReader<T, U> {
+    snapshot: SharedSnapshot<T, U>,
+}
+
+impl<T, U> LsmReader<T, U>
+where
+    T: Ord + DeserializeOwned,
+    U: Clone + DeserializeOwned,
+{
+    pub(crate) fn new(snapshot: SharedSnapshot<T, U>) -> Self {
+        LsmReader { snapshot }
+    }
+
+    fn current_snapshot(&self) -> Arc<LsmSnapshot<T, U>> {
+        Arc::clone(&self.snapshot.read().unwrap())
+    }
+
+    /// Checks if a key exists in the most recent snapshot of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_reader_contains_key", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    /// let reader = map.reader()?;
+    ///
+    /// map.insert(1, 1)?;
+    /// assert!(!reader.contains_key(&0)?);
+    /// assert!(reader.contains_key(&1)?);
+    /// # fs::remove_dir_all("example_lsm_reader_contains_key")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn contains_key<V>(&self, key: &V) -> Result<bool>
+    where
+        T: Borrow<V>,
+        V: Ord + Hash + ?Sized,
+    {
+        self.get(key).map(|value| value.is_some())
+    }
+
+    /// Returns the value associated with a particular key in the most recent snapshot of the map.
+    /// Returns `None` if the key does not exist in the snapshot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::compaction::SizeTieredStrategy;
+    /// use extended_collections::lsm_tree::LsmMap;
+    ///
+    /// let sts = SizeTieredStrategy::new("example_lsm_reader_get", 10000, 4, 50000, 0.5, 1.5)?;
+    /// let mut map = LsmMap::new(sts);
+    /// let reader = map.reader()?;
+    ///
+    /// map.insert(1, 1)?;
+    /// assert_eq!(reader.get(&0)?, None);
+    /// assert_eq!(reader.get(&1)?, Some(1));
+    /// # fs::remove_dir_all("example_lsm_reader_get")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn get<V>(&self, key: &V) -> Result<Option<U>>
+    where
+        T: Borrow<V>,
+        V: Ord + Hash + ?Sized,
+    {
+        let snapshot = self.current_snapshot();
+        if let Some(value) = snapshot.in_memory_tree.get(key) {
+            Ok(value.data.clone())
+        } else {
+            snapshot
+                .disk_snapshot
+                .get(key)
+                .map(|value_opt| value_opt.and_then(|value| value.data))
+        }
+    }
+}
+
+impl<T, U> Clone for LsmReader<T, U> {
+    fn clone(&self) -> Self {
+        LsmReader {
+            snapshot: Arc::clone(&self.snapshot),
+        }
+    }
+}
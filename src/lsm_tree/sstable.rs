@@ -1,21 +1,173 @@
+use crate::bloom::BloomFilter;
 use crate::entry::Entry;
-use crate::lsm_tree::{Error, Result};
-use bincode::{deserialize, serialize};
-use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
-use probabilistic_collections::bloom::BloomFilter;
+use crate::lsm_tree::{Error, FileWriter, RateLimiter, Result};
+use bincode::{deserialize, deserialize_from, serialize};
+use byteorder::{BigEndian, ByteOrder, ReadBytesExt, WriteBytesExt};
 use rand::{thread_rng, Rng};
 use serde::de::{self, Deserialize, DeserializeOwned, Deserializer};
 use serde::ser::{Serialize, Serializer};
 use serde_derive::{Deserialize, Serialize};
 use std::borrow::Borrow;
 use std::cmp;
+use std::f64;
 use std::fmt::{self, Debug};
 use std::fs;
 use std::hash::Hash;
-use std::io::{BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
 use std::marker::PhantomData;
+use std::mem;
 use std::path::{Path, PathBuf};
 use std::result;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+pub const DEFAULT_FILTER_FALSE_POSITIVE_RATE: f64 = 0.05;
+const DEFAULT_WRITE_BUFFER_SIZE: usize = 8 * 1024;
+const MIN_FILTER_FALSE_POSITIVE_RATE: f64 = 0.0001;
+const MAX_FILTER_FALSE_POSITIVE_RATE: f64 = 0.5;
+const MIN_FILTER_TUNING_SAMPLE_COUNT: usize = 1000;
+const SSTABLE_FILE_NAMES: [&str; 5] = [
+    "data.dat",
+    "index.dat",
+    "summary.dat",
+    "filter.dat",
+    "prefix_filter.dat",
+];
+// The summary of a SSTable starts with these bytes followed by the format version of the SSTable.
+const SSTABLE_MAGIC: [u8; 4] = *b"SSTB";
+const SSTABLE_FORMAT_VERSION: u32 = 3;
+const SSTABLE_HEADER_SIZE: usize = 8;
+
+// Syncs the files of the SSTable in the folder at `path`.
+fn sync_files(path: &Path) -> Result<()> {
+    for file_name in SSTABLE_FILE_NAMES.iter() {
+        fs::File::open(path.join(file_name))?.sync_all()?;
+    }
+    Ok(())
+}
+
+// Reads and deserializes the entire file at `path`.
+fn read_file<P, V>(path: P) -> Result<V>
+where
+    P: AsRef<Path>,
+    V: DeserializeOwned,
+{
+    let buffer = fs::read(path.as_ref()).map_err(|error| Error::file_io(&path, "read", error))?;
+    deserialize(&buffer).map_err(|_| Error::corruption(&path, 0))
+}
+
+// Reads the length-prefixed record at `offset` of the file at `path`.
+fn read_record_bytes<P>(path: P, offset: u64) -> Result<Vec<u8>>
+where
+    P: AsRef<Path>,
+{
+    let read = || -> io::Result<Vec<u8>> {
+        let mut file = fs::File::open(path.as_ref())?;
+        file.seek(SeekFrom::Start(offset))?;
+        let size = file.read_u64::<BigEndian>()?;
+        let mut buffer = vec![0; size as usize];
+        file.read_exact(buffer.as_mut_slice())?;
+        Ok(buffer)
+    };
+    read().map_err(|error| Error::file_io(&path, "read", error))
+}
+
+// Reads and deserializes the length-prefixed record at `offset` of the file at `path`.
+fn read_record<P, V>(path: P, offset: u64) -> Result<V>
+where
+    P: AsRef<Path>,
+    V: DeserializeOwned,
+{
+    let buffer = read_record_bytes(&path, offset)?;
+    deserialize(&buffer).map_err(|_| Error::corruption(&path, offset))
+}
+
+// Reads the summary of the SSTable in the folder at `path` and checks its format version.
+fn read_summary<T>(path: &Path) -> Result<SSTableSummary<T>>
+where
+    T: DeserializeOwned,
+{
+    let summary_path = path.join("summary.dat");
+    let buffer =
+        fs::read(&summary_path).map_err(|error| Error::file_io(&summary_path, "read", error))?;
+    // summaries written before the format version was stored start with the entry count instead
+    let version = if buffer.len() >= SSTABLE_HEADER_SIZE && buffer[..4] == SSTABLE_MAGIC {
+        BigEndian::read_u32(&buffer[4..SSTABLE_HEADER_SIZE])
+    } else {
+        0
+    };
+    if version != SSTABLE_FORMAT_VERSION {
+        return Err(Error::UnsupportedVersion {
+            path: summary_path,
+            version,
+        });
+    }
+    deserialize(&buffer[SSTABLE_HEADER_SIZE..])
+        .map_err(|_| Error::corruption(&summary_path, SSTABLE_HEADER_SIZE as u64))
+}
+
+// Returns the false positive rate of a bloom filter with an optimal number of hash functions that
+// uses `bits_per_key` bits for each key.
+pub fn get_false_positive_rate(bits_per_key: f64) -> f64 {
+    assert!(
+        bits_per_key > 0.0,
+        "Error: bits per key must be greater than 0."
+    );
+    (-bits_per_key * f64::consts::LN_2 * f64::consts::LN_2).exp()
+}
+
+/// A function that maps a key to its prefix. Keys with the same prefix must form a contiguous range
+/// of keys, so a prefix is usually a leading part of the serialized key, such as the user of a
+/// `(user_id, timestamp)` key.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::lsm_tree::PrefixExtractor;
+/// use std::sync::Arc;
+///
+/// let prefix_extractor: PrefixExtractor<(u32, u64)> =
+///     Arc::new(|key: &(u32, u64)| key.0.to_be_bytes().to_vec());
+/// assert_eq!(prefix_extractor(&(1, 2)), prefix_extractor(&(1, 3)));
+/// ```
+pub type PrefixExtractor<T> = Arc<dyn Fn(&T) -> Vec<u8> + Send + Sync>;
+
+// The configuration of the bloom filters of newly built SSTables.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FilterOptions {
+    pub false_positive_rate: f64,
+    // SSTables with at least this many entries have a bloom filter for each index block that is
+    // read from disk when queried instead of a single bloom filter that is kept in memory.
+    pub partition_threshold: Option<usize>,
+}
+
+// Returns the false positive rate of the filters of new SSTables such that the observed false
+// positive rate of the filters of existing SSTables approaches `target_rate`.
+pub fn tune_false_positive_rate(
+    curr_rate: f64,
+    target_rate: f64,
+    filter_stats: &[FilterStats],
+) -> f64 {
+    let negative_count: usize = filter_stats.iter().map(FilterStats::negative_count).sum();
+    if negative_count < MIN_FILTER_TUNING_SAMPLE_COUNT {
+        return curr_rate;
+    }
+
+    let false_positive_count: usize = filter_stats
+        .iter()
+        .map(|filter_stats| filter_stats.false_positive_count)
+        .sum();
+    let observed_rate = false_positive_count as f64 / negative_count as f64;
+    let new_rate = if observed_rate == 0.0 {
+        curr_rate * 2.0
+    } else {
+        curr_rate * target_rate / observed_rate
+    };
+    new_rate.clamp(
+        MIN_FILTER_FALSE_POSITIVE_RATE,
+        MAX_FILTER_FALSE_POSITIVE_RATE,
+    )
+}
 
 pub fn merge_ranges<T>(range_1: (T, T), range_2: (T, T)) -> (T, T)
 where
@@ -36,7 +188,93 @@ where
     l <= r
 }
 
-#[derive(Deserialize, Serialize)]
+// A deletion of all keys in `[start, end)` that were written before `logical_time`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RangeTombstone<T> {
+    pub start: T,
+    pub end: T,
+    pub logical_time: u64,
+}
+
+impl<T> RangeTombstone<T>
+where
+    T: Ord,
+{
+    pub fn contains<V>(&self, key: &V) -> bool
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        self.start.borrow() <= key && key < self.end.borrow()
+    }
+
+    pub fn is_intersecting(&self, key_range: &(T, T)) -> bool {
+        self.start <= key_range.1 && key_range.0 < self.end
+    }
+
+    // Returns whether the range tombstone may delete an entry of `sstable`.
+    pub fn may_delete<U>(&self, sstable: &SSTable<T, U>) -> bool {
+        sstable.summary.logical_time_range.0 < self.logical_time
+            && self.is_intersecting(&sstable.summary.key_range)
+    }
+}
+
+// Returns the logical time of the most recent range tombstone that contains `key`.
+pub fn get_range_tombstone_time<T, V>(
+    range_tombstones: &[RangeTombstone<T>],
+    key: &V,
+) -> Option<u64>
+where
+    T: Borrow<V> + Ord,
+    V: Ord + ?Sized,
+{
+    range_tombstones
+        .iter()
+        .filter(|range_tombstone| range_tombstone.contains(key))
+        .map(|range_tombstone| range_tombstone.logical_time)
+        .max()
+}
+
+// Returns whether an entry with `key` written at `logical_time` is deleted by a range tombstone.
+pub fn is_range_deleted<T, V>(
+    range_tombstones: &[RangeTombstone<T>],
+    key: &V,
+    logical_time: u64,
+) -> bool
+where
+    T: Borrow<V> + Ord,
+    V: Ord + ?Sized,
+{
+    get_range_tombstone_time(range_tombstones, key)
+        .is_some_and(|range_tombstone_time| range_tombstone_time > logical_time)
+}
+
+// Applies the range tombstones that contain `key` to the most recent value of `key`.
+pub fn apply_range_tombstones<T, U, V>(
+    range_tombstones: &[RangeTombstone<T>],
+    key: &V,
+    value_opt: Option<SSTableValue<U>>,
+) -> Option<SSTableValue<U>>
+where
+    T: Borrow<V> + Ord,
+    V: Ord + ?Sized,
+{
+    match get_range_tombstone_time(range_tombstones, key) {
+        Some(logical_time)
+            if value_opt
+                .as_ref()
+                .is_none_or(|value| value.logical_time < logical_time) =>
+        {
+            Some(SSTableValue {
+                data: None,
+                logical_time,
+            })
+        }
+        _ => value_opt,
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
 pub struct SSTableValue<U> {
     pub data: Option<U>,
     pub logical_time: u64,
@@ -62,34 +300,243 @@ impl<U> PartialOrd for SSTableValue<U> {
 
 impl<U> Eq for SSTableValue<U> {}
 
+/// Runtime statistics of the bloom filter of a SSTable.
+///
+/// A lookup queries the filter of a SSTable if the key is in the key range of the SSTable. The
+/// statistics are not persisted and are reset when the SSTable is reopened.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilterStats {
+    /// The path of the SSTable.
+    pub path: PathBuf,
+    /// The number of lookups that queried the filter.
+    pub query_count: usize,
+    /// The number of lookups where the filter reported that the key may exist and the key existed.
+    pub true_positive_count: usize,
+    /// The number of lookups where the filter reported that the key may exist, but the key did not
+    /// exist.
+    pub false_positive_count: usize,
+}
+
+impl FilterStats {
+    pub(crate) fn negative_count(&self) -> usize {
+        self.query_count.saturating_sub(self.true_positive_count)
+    }
+
+    /// Returns the observed false positive rate of the filter, which is the fraction of lookups of
+    /// keys that did not exist that the filter failed to reject. Returns `0.0` if no such lookups
+    /// occurred.
+    pub fn false_positive_rate(&self) -> f64 {
+        let negative_count = self.negative_count();
+        if negative_count == 0 {
+            0.0
+        } else {
+            self.false_positive_count as f64 / negative_count as f64
+        }
+    }
+}
+
+/// Options for the writes of newly built SSTables.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::lsm_tree::IoOptions;
+///
+/// let io_options = IoOptions {
+///     write_buffer_size: 1 << 20,
+///     ..IoOptions::default()
+/// };
+/// assert!(!io_options.sync_on_seal);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IoOptions {
+    /// The number of bytes buffered in memory for each file of a SSTable before they are written
+    /// to the file. The default is `8192`.
+    pub write_buffer_size: usize,
+    /// Whether the files of a SSTable are synced to disk as soon as it is sealed, instead of when
+    /// the compaction strategy is synced. The default is `false`.
+    pub sync_on_seal: bool,
+    /// Whether the SSTables built by compactions are written with direct I/O, which bypasses the
+    /// page cache so that large compactions do not evict recently read data. Direct I/O is only
+    /// supported on Linux, and SSTables are written through the page cache on other platforms
+    /// and on file systems that do not support it. The default is `false`.
+    pub direct_io: bool,
+}
+
+impl Default for IoOptions {
+    fn default() -> Self {
+        IoOptions {
+            write_buffer_size: DEFAULT_WRITE_BUFFER_SIZE,
+            sync_on_seal: false,
+            direct_io: false,
+        }
+    }
+}
+
+/// The distribution of the encoded sizes of the keys or the values of a SSTable.
+///
+/// Sizes are grouped into buckets by their number of significant bits, so bucket `0` counts sizes
+/// of zero bytes and bucket `i` counts sizes of at least `2^(i - 1)` and less than `2^i` bytes.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct SizeHistogram {
+    /// The number of sizes in the histogram.
+    pub count: usize,
+    /// The smallest size in bytes, or `0` if the histogram is empty.
+    pub min: u64,
+    /// The largest size in bytes, or `0` if the histogram is empty.
+    pub max: u64,
+    /// The sum of the sizes in bytes.
+    pub total: u64,
+    /// The number of sizes in each bucket. Trailing empty buckets are omitted.
+    pub buckets: Vec<usize>,
+}
+
+impl SizeHistogram {
+    pub(crate) fn insert(&mut self, size: u64) {
+        if self.count == 0 {
+            self.min = size;
+            self.max = size;
+        } else {
+            self.min = cmp::min(self.min, size);
+            self.max = cmp::max(self.max, size);
+        }
+        self.count += 1;
+        self.total += size;
+
+        let bucket = (64 - size.leading_zeros()) as usize;
+        if self.buckets.len() <= bucket {
+            self.buckets.resize(bucket + 1, 0);
+        }
+        self.buckets[bucket] += 1;
+    }
+
+    /// Returns the average size in bytes. Returns `0.0` if the histogram is empty.
+    pub fn average(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.total as f64 / self.count as f64
+        }
+    }
+}
+
+/// The summary of a SSTable, which is written by `SSTableBuilder` and kept in memory while the
+/// SSTable is open.
+///
+/// # Examples
+///
+/// ```
+/// # use extended_collections::lsm_tree::Result;
+/// # fn foo() -> Result<()> {
+/// # use std::fs;
+/// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+///
+/// fs::create_dir("example_sstable_summary")?;
+/// let mut builder = SSTableBuilder::new("example_sstable_summary", 100)?;
+/// for key in 0..100u32 {
+///     builder.insert(key, vec![0u8; key as usize])?;
+/// }
+///
+/// let sstable: SSTable<u32, Vec<u8>> = SSTable::open(builder.finish()?)?;
+/// let summary = sstable.summary();
+/// assert_eq!(summary.entry_count, 100);
+/// assert_eq!(summary.key_sizes.min, 4);
+/// assert_eq!(summary.key_sizes.max, 4);
+///
+/// // a vector is encoded as its length followed by its elements
+/// assert_eq!(summary.value_sizes.min, 8);
+/// assert_eq!(summary.value_sizes.max, 107);
+/// assert_eq!(summary.value_sizes.average(), 57.5);
+/// assert_eq!(summary.value_sizes.buckets.iter().sum::<usize>(), 100);
+/// # fs::remove_dir_all("example_sstable_summary")?;
+/// # Ok(())
+/// # }
+/// # foo().unwrap();
+/// ```
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SSTableSummary<T> {
+    /// The number of entries in the SSTable, including tombstones.
     pub entry_count: usize,
+    /// The number of tombstones in the SSTable.
     pub tombstone_count: usize,
+    /// The size of the entries and the index blocks of the SSTable in bytes.
     pub size: u64,
+    /// The smallest and the largest key of the SSTable.
     pub key_range: (T, T),
-    pub logical_time_range: (u64, u64),
-    pub index: Vec<(T, u64)>,
+    pub(crate) logical_time_range: (u64, u64),
+    /// The distribution of the encoded sizes of the keys of the SSTable.
+    pub key_sizes: SizeHistogram,
+    /// The distribution of the encoded sizes of the values of the SSTable, excluding tombstones.
+    pub value_sizes: SizeHistogram,
+    // The number of entries in each index block.
+    pub(crate) index_block_size: usize,
+    pub(crate) index: Vec<(T, u64)>,
+    // The offset in the data file of the first entry of each index block.
+    pub(crate) data_offsets: Vec<u64>,
+    // The size of the data file.
+    pub(crate) data_size: u64,
+    // The offsets of the bloom filter of each index block if the bloom filter is partitioned.
+    pub(crate) filter_offsets: Vec<u64>,
 }
 
+/// A builder of a Sorted String Table (SSTable), which is an immutable sorted run of entries stored
+/// in its own folder.
+///
+/// A SSTable can be built offline from entries in ascending order of keys and added to a `LsmMap`
+/// with `LsmMap::ingest_sstable`, which is much faster than inserting the entries one at a time,
+/// or opened on its own with `SSTable::open`.
+///
+/// # Examples
+///
+/// ```
+/// # use extended_collections::lsm_tree::Result;
+/// # fn foo() -> Result<()> {
+/// # use std::fs;
+/// use extended_collections::lsm_tree::SSTableBuilder;
+///
+/// fs::create_dir("example_sstable_builder")?;
+/// let mut builder = SSTableBuilder::new("example_sstable_builder", 100)?;
+/// for key in 0..100u32 {
+///     builder.insert(key, key * 2)?;
+/// }
+/// let sstable_path = builder.finish()?;
+/// assert!(sstable_path.starts_with("example_sstable_builder"));
+/// # fs::remove_dir_all("example_sstable_builder")?;
+/// # Ok(())
+/// # }
+/// # foo().unwrap();
+/// ```
 pub struct SSTableBuilder<T, U> {
-    pub sstable_path: PathBuf,
+    pub(crate) sstable_path: PathBuf,
 
-    pub entry_count: usize,
-    pub tombstone_count: usize,
-    pub size: u64,
-    pub key_range: Option<(T, T)>,
-    pub logical_time_range: Option<(u64, u64)>,
-    pub index: Vec<(T, u64)>,
+    pub(crate) entry_count: usize,
+    pub(crate) tombstone_count: usize,
+    pub(crate) size: u64,
+    pub(crate) key_range: Option<(T, T)>,
+    pub(crate) logical_time_range: Option<(u64, u64)>,
+    pub(crate) index: Vec<(T, u64)>,
+    data_offsets: Vec<u64>,
+    key_sizes: SizeHistogram,
+    value_sizes: SizeHistogram,
 
     block_index: usize,
     block_size: usize,
     index_block: Vec<(T, u64)>,
     filter: BloomFilter<T>,
+    filter_false_positive_rate: f64,
+    filter_offsets: Option<Vec<u64>>,
+    filter_offset: u64,
+    filter_stream: FileWriter,
+    entry_count_hint: usize,
+    prefix_extractor: Option<PrefixExtractor<T>>,
+    prefix_filter: Option<BloomFilter<Vec<u8>>>,
+    last_prefix: Option<Vec<u8>>,
     index_offset: u64,
-    index_stream: BufWriter<fs::File>,
+    index_stream: FileWriter,
     data_offset: u64,
-    data_stream: BufWriter<fs::File>,
+    data_stream: FileWriter,
+    sync_on_seal: bool,
+    rate_limiter: Option<Arc<RateLimiter>>,
     _marker: PhantomData<U>,
 }
 
@@ -98,7 +545,87 @@ impl<T, U> SSTableBuilder<T, U> {
         thread_rng().gen_ascii_chars().take(32).collect()
     }
 
-    pub fn new<P>(db_path: P, entry_count_hint: usize) -> Result<Self>
+    /// Constructs a new `SSTableBuilder<T, U>` that writes a SSTable to a new folder in the existing
+    /// folder at `path`. `entry_count_hint` is the expected number of entries, which is used to
+    /// size the bloom filter and the index blocks of the SSTable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::SSTableBuilder;
+    ///
+    /// fs::create_dir("example_sstable_builder_new")?;
+    /// let builder: SSTableBuilder<u32, u32> =
+    ///     SSTableBuilder::new("example_sstable_builder_new", 100)?;
+    /// # fs::remove_dir_all("example_sstable_builder_new")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn new<P>(path: P, entry_count_hint: usize) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::with_io_options(path, entry_count_hint, IoOptions::default())
+    }
+
+    /// Constructs a new `SSTableBuilder<T, U>` that writes a SSTable to a new folder in the existing
+    /// folder at `path` using `io_options`. `entry_count_hint` is the expected number of entries,
+    /// which is used to size the bloom filter and the index blocks of the SSTable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::{IoOptions, SSTableBuilder};
+    ///
+    /// let io_options = IoOptions {
+    ///     write_buffer_size: 1 << 16,
+    ///     ..IoOptions::default()
+    /// };
+    /// fs::create_dir("example_sstable_builder_io_options")?;
+    /// let builder: SSTableBuilder<u32, u32> =
+    ///     SSTableBuilder::with_io_options("example_sstable_builder_io_options", 100, io_options)?;
+    /// # fs::remove_dir_all("example_sstable_builder_io_options")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn with_io_options<P>(
+        path: P,
+        entry_count_hint: usize,
+        io_options: IoOptions,
+    ) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let filter_options = FilterOptions {
+            false_positive_rate: DEFAULT_FILTER_FALSE_POSITIVE_RATE,
+            partition_threshold: None,
+        };
+        Self::with_options(
+            path,
+            cmp::max(entry_count_hint, 1),
+            None,
+            filter_options,
+            io_options,
+        )
+    }
+
+    // Constructs a new `SSTableBuilder<T, U>` with index blocks of `index_block_size` entries, or
+    // of the square root of `entry_count_hint` entries if `index_block_size` is `None`.
+    pub(crate) fn with_options<P>(
+        db_path: P,
+        entry_count_hint: usize,
+        index_block_size: Option<usize>,
+        filter_options: FilterOptions,
+        io_options: IoOptions,
+    ) -> Result<Self>
     where
         P: AsRef<Path>,
     {
@@ -106,11 +633,28 @@ impl<T, U> SSTableBuilder<T, U> {
         let sstable_path = db_path.join(Self::generate_file_name());
         fs::create_dir(sstable_path.as_path())?;
 
-        let data_file = fs::File::create(sstable_path.join("data.dat"))?;
-        let data_stream = BufWriter::new(data_file);
+        let create_stream = |file_name| {
+            FileWriter::create(
+                &sstable_path.join(file_name),
+                io_options.write_buffer_size,
+                io_options.direct_io,
+            )
+        };
+        let data_stream = create_stream("data.dat")?;
+        let index_stream = create_stream("index.dat")?;
+        let filter_stream = create_stream("filter.dat")?;
 
-        let index_file = fs::File::create(sstable_path.join("index.dat"))?;
-        let index_stream = BufWriter::new(index_file);
+        let block_size = index_block_size
+            .unwrap_or_else(|| (entry_count_hint as f64).sqrt().ceil() as usize)
+            .max(1);
+        let is_partitioned = filter_options
+            .partition_threshold
+            .is_some_and(|partition_threshold| entry_count_hint >= partition_threshold);
+        let filter_item_count = if is_partitioned {
+            block_size
+        } else {
+            entry_count_hint
+        };
 
         Ok(SSTableBuilder {
             sstable_path,
@@ -121,24 +665,153 @@ impl<T, U> SSTableBuilder<T, U> {
             key_range: None,
             logical_time_range: None,
             index: Vec::new(),
+            data_offsets: Vec::new(),
+            key_sizes: SizeHistogram::default(),
+            value_sizes: SizeHistogram::default(),
 
             block_index: 0,
-            block_size: (entry_count_hint as f64).sqrt().ceil() as usize,
+            block_size,
             index_block: Vec::new(),
-            filter: BloomFilter::new(entry_count_hint, 0.05),
+            filter: BloomFilter::new(filter_item_count, filter_options.false_positive_rate),
+            filter_false_positive_rate: filter_options.false_positive_rate,
+            filter_offsets: if is_partitioned {
+                Some(Vec::new())
+            } else {
+                None
+            },
+            filter_offset: 0,
+            filter_stream,
+            entry_count_hint,
+            prefix_extractor: None,
+            prefix_filter: None,
+            last_prefix: None,
             index_offset: 0,
             index_stream,
             data_offset: 0,
             data_stream,
+            sync_on_seal: io_options.sync_on_seal,
+            rate_limiter: None,
             _marker: PhantomData,
         })
     }
 
-    pub fn append(&mut self, key: T, value: SSTableValue<U>) -> Result<()>
+    /// Returns the path of the folder of the SSTable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::SSTableBuilder;
+    ///
+    /// fs::create_dir("example_sstable_builder_path")?;
+    /// let builder: SSTableBuilder<u32, u32> =
+    ///     SSTableBuilder::new("example_sstable_builder_path", 100)?;
+    /// assert!(builder.path().starts_with("example_sstable_builder_path"));
+    /// # fs::remove_dir_all("example_sstable_builder_path")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn path(&self) -> &Path {
+        &self.sstable_path
+    }
+
+    /// Appends a key-value pair to the SSTable. Keys must be inserted in strictly ascending order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not greater than the previously inserted key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::SSTableBuilder;
+    ///
+    /// fs::create_dir("example_sstable_builder_insert")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_builder_insert", 100)?;
+    /// builder.insert(1, 2)?;
+    /// builder.insert(3, 4)?;
+    /// # fs::remove_dir_all("example_sstable_builder_insert")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn insert(&mut self, key: T, value: U) -> Result<()>
+    where
+        T: Clone + Hash + Ord + Serialize,
+        U: Serialize,
+    {
+        if let Some((_, ref last_key)) = self.key_range {
+            assert!(
+                key > *last_key,
+                "Error: keys must be inserted in strictly ascending order."
+            );
+        }
+        let value = SSTableValue {
+            data: Some(value),
+            logical_time: 0,
+        };
+        self.append(key, value)
+    }
+
+    /// Writes the remaining entries and the summary of the SSTable, syncs its files, and returns
+    /// the path of its folder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no entries were inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::SSTableBuilder;
+    ///
+    /// fs::create_dir("example_sstable_builder_finish")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_builder_finish", 100)?;
+    /// builder.insert(1, 2)?;
+    /// let sstable_path = builder.finish()?;
+    /// assert!(sstable_path.join("data.dat").exists());
+    /// # fs::remove_dir_all("example_sstable_builder_finish")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn finish(mut self) -> Result<PathBuf>
+    where
+        T: Clone + Serialize,
+    {
+        self.sync_on_seal = true;
+        self.flush()
+    }
+
+    // Adds a bloom filter of the prefixes of the keys extracted by `prefix_extractor` to the
+    // SSTable. Must be called before any entries are appended.
+    pub(crate) fn set_prefix_extractor(&mut self, prefix_extractor: Option<PrefixExtractor<T>>) {
+        self.prefix_filter = prefix_extractor
+            .as_ref()
+            .map(|_| BloomFilter::new(self.entry_count_hint, self.filter_false_positive_rate));
+        self.prefix_extractor = prefix_extractor;
+    }
+
+    // Throttles subsequent appends to the rate of `rate_limiter`.
+    pub(crate) fn set_rate_limiter(&mut self, rate_limiter: Option<Arc<RateLimiter>>) {
+        self.rate_limiter = rate_limiter;
+    }
+
+    pub(crate) fn append(&mut self, key: T, value: SSTableValue<U>) -> Result<()>
     where
         T: Clone + Hash + Serialize,
         U: Serialize,
     {
+        let prev_size = self.size;
         let logical_time = value.logical_time;
         self.entry_count += 1;
         if value.data.is_none() {
@@ -158,9 +831,30 @@ impl<T, U> SSTableBuilder<T, U> {
         }
 
         self.filter.insert(&key);
+        if let Some(ref prefix_extractor) = self.prefix_extractor {
+            let prefix = prefix_extractor(&key);
+            // keys with the same prefix are contiguous, so each prefix only needs to be inserted
+            // once.
+            if self.last_prefix.as_ref() != Some(&prefix) {
+                if let Some(ref mut prefix_filter) = self.prefix_filter {
+                    prefix_filter.insert(&prefix);
+                }
+                self.last_prefix = Some(prefix);
+            }
+        }
         self.index_block.push((key.clone(), self.data_offset));
 
-        let serialized_entry = serialize(&(key, value))?;
+        // an entry is encoded as its key followed by its value, so they are serialized separately
+        // to measure their sizes.
+        let mut serialized_entry = serialize(&key)?;
+        self.key_sizes.insert(serialized_entry.len() as u64);
+        let serialized_value = serialize(&value)?;
+        if value.data.is_some() {
+            // the value is preceded by the tag of the option and followed by the logical time
+            let value_size = serialized_value.len() - 1 - mem::size_of::<u64>();
+            self.value_sizes.insert(value_size as u64);
+        }
+        serialized_entry.extend(serialized_value);
         self.data_stream
             .write_u64::<BigEndian>(serialized_entry.len() as u64)?;
         self.data_stream.write_all(&serialized_entry)?;
@@ -171,6 +865,7 @@ impl<T, U> SSTableBuilder<T, U> {
         if self.block_index == self.block_size {
             self.index
                 .push((self.index_block[0].0.clone(), self.index_offset));
+            self.data_offsets.push(self.index_block[0].1);
 
             let serialized_index_block = serialize(&self.index_block)?;
             self.index_stream
@@ -180,23 +875,54 @@ impl<T, U> SSTableBuilder<T, U> {
             self.size += 8 + serialized_index_block.len() as u64;
             self.block_index = 0;
             self.index_block.clear();
+            self.write_filter_partition()?;
+        }
+
+        if let Some(ref rate_limiter) = self.rate_limiter {
+            rate_limiter.acquire(self.size - prev_size);
         }
 
         Ok(())
     }
 
-    pub fn flush(&mut self) -> Result<PathBuf>
+    // Writes the bloom filter of the last index block if the bloom filter is partitioned.
+    fn write_filter_partition(&mut self) -> Result<()>
+    where
+        T: Serialize,
+    {
+        if let Some(ref mut filter_offsets) = self.filter_offsets {
+            let filter = mem::replace(
+                &mut self.filter,
+                BloomFilter::new(self.block_size, self.filter_false_positive_rate),
+            );
+            let serialized_filter = serialize(&filter)?;
+            filter_offsets.push(self.filter_offset);
+            self.filter_stream
+                .write_u64::<BigEndian>(serialized_filter.len() as u64)?;
+            self.filter_stream.write_all(&serialized_filter)?;
+            self.filter_offset += 8 + serialized_filter.len() as u64;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn flush(&mut self) -> Result<PathBuf>
     where
         T: Clone + Serialize,
     {
         if !self.index_block.is_empty() {
             self.index
                 .push((self.index_block[0].0.clone(), self.index_offset));
+            self.data_offsets.push(self.index_block[0].1);
 
             let serialized_index_block = serialize(&self.index_block)?;
             self.index_stream
                 .write_u64::<BigEndian>(serialized_index_block.len() as u64)?;
             self.index_stream.write_all(&serialized_index_block)?;
+            self.write_filter_partition()?;
+        }
+
+        if self.filter_offsets.is_none() {
+            self.filter_stream.write_all(&serialize(&self.filter)?)?;
         }
 
         let key_range = {
@@ -213,52 +939,350 @@ impl<T, U> SSTableBuilder<T, U> {
             }
         };
 
-        let serialized_summary = serialize(&SSTableSummary {
+        let mut serialized_summary = SSTABLE_MAGIC.to_vec();
+        serialized_summary.write_u32::<BigEndian>(SSTABLE_FORMAT_VERSION)?;
+        serialized_summary.extend(serialize(&SSTableSummary {
             entry_count: self.entry_count,
             tombstone_count: self.tombstone_count,
             size: self.size,
             key_range,
             logical_time_range,
+            index_block_size: self.block_size,
+            key_sizes: self.key_sizes.clone(),
+            value_sizes: self.value_sizes.clone(),
             index: self.index.clone(),
-        })?;
+            data_offsets: self.data_offsets.clone(),
+            data_size: self.data_offset,
+            filter_offsets: self.filter_offsets.clone().unwrap_or_default(),
+        })?);
         fs::write(self.sstable_path.join("summary.dat"), &serialized_summary)?;
+        fs::write(
+            self.sstable_path.join("prefix_filter.dat"),
+            serialize(&self.prefix_filter)?,
+        )?;
 
-        let serialized_filter = serialize(&self.filter)?;
-        fs::write(self.sstable_path.join("filter.dat"), &serialized_filter)?;
-
+        self.filter_stream.flush()?;
         self.index_stream.flush()?;
         self.data_stream.flush()?;
+        if self.sync_on_seal {
+            sync_files(&self.sstable_path)?;
+        }
         Ok(self.sstable_path.clone())
     }
 }
 
+/// A Sorted String Table (SSTable), which is an immutable sorted run of entries stored in its own
+/// folder.
+///
+/// The entries of a SSTable are stored in ascending order of keys in `data.dat`, and the offsets of
+/// the entries are grouped into index blocks in `index.dat`. The summary of the SSTable in
+/// `summary.dat` holds the first key of each index block, so a lookup reads at most one index
+/// block and one entry, and a bloom filter rejects most lookups of keys that do not exist without
+/// reading either. The summary starts with the format version of the SSTable, and SSTables with an
+/// unsupported format version are reported as `Error::UnsupportedVersion` when they are opened.
+///
+/// A SSTable is usually built with a `SSTableBuilder`. The SSTables of a `LsmMap` may also contain
+/// tombstones of deleted keys, which are skipped by lookups and iterators.
+///
+/// # Examples
+///
+/// ```
+/// # use extended_collections::lsm_tree::Result;
+/// # fn foo() -> Result<()> {
+/// # use std::fs;
+/// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+///
+/// fs::create_dir("example_sstable")?;
+/// let mut builder = SSTableBuilder::new("example_sstable", 100)?;
+/// for key in 0..100u32 {
+///     builder.insert(key, key * 2)?;
+/// }
+///
+/// let sstable: SSTable<u32, u32> = SSTable::open(builder.finish()?)?;
+/// assert_eq!(sstable.get(&1)?, Some(2));
+/// assert_eq!(sstable.get(&100)?, None);
+///
+/// let entries: Vec<(u32, u32)> = sstable.range(&3, &6)?.collect::<Result<_>>()?;
+/// assert_eq!(entries, vec![(3, 6), (4, 8), (5, 10)]);
+/// # fs::remove_dir_all("example_sstable")?;
+/// # Ok(())
+/// # }
+/// # foo().unwrap();
+/// ```
 pub struct SSTable<T, U> {
-    pub path: PathBuf,
-    pub summary: SSTableSummary<T>,
-    pub filter: BloomFilter<T>,
+    pub(crate) path: PathBuf,
+    pub(crate) summary: SSTableSummary<T>,
+    // The bloom filter of the SSTable, or `None` if the bloom filter is partitioned.
+    pub(crate) filter: Option<BloomFilter<T>>,
+    // The bloom filter of the prefixes of the keys of the SSTable, or `None` if the SSTable was
+    // built without a prefix extractor.
+    pub(crate) prefix_filter: Option<BloomFilter<Vec<u8>>>,
+    filter_query_count: AtomicUsize,
+    filter_true_positive_count: AtomicUsize,
+    filter_false_positive_count: AtomicUsize,
+    // Whether the files of the SSTable are deleted when the SSTable is dropped.
+    is_obsolete: AtomicBool,
     _marker: PhantomData<U>,
 }
 
 impl<T, U> SSTable<T, U> {
-    pub fn new<P>(path: P) -> Result<Self>
+    /// Opens the SSTable in the folder at `path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+    ///
+    /// fs::create_dir("example_sstable_open")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_open", 100)?;
+    /// builder.insert(1, 2)?;
+    /// let sstable_path = builder.finish()?;
+    ///
+    /// let sstable: SSTable<u32, u32> = SSTable::open(&sstable_path)?;
+    /// assert_eq!(sstable.path(), sstable_path.as_path());
+    /// # fs::remove_dir_all("example_sstable_open")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn open<P>(path: P) -> Result<Self>
     where
         T: DeserializeOwned,
         P: AsRef<Path>,
     {
-        let buffer = fs::read(path.as_ref().join("summary.dat"))?;
-        let summary = deserialize(&buffer)?;
-
-        let buffer = fs::read(path.as_ref().join("filter.dat"))?;
-        let filter = deserialize(&buffer)?;
+        let summary: SSTableSummary<T> = read_summary(path.as_ref())?;
+        let filter = if summary.filter_offsets.is_empty() {
+            Some(read_file(path.as_ref().join("filter.dat"))?)
+        } else {
+            None
+        };
+        let prefix_filter = read_file(path.as_ref().join("prefix_filter.dat"))?;
 
         Ok(SSTable {
             path: PathBuf::from(path.as_ref()),
             summary,
             filter,
+            prefix_filter,
+            filter_query_count: AtomicUsize::new(0),
+            filter_true_positive_count: AtomicUsize::new(0),
+            filter_false_positive_count: AtomicUsize::new(0),
+            is_obsolete: AtomicBool::new(false),
             _marker: PhantomData,
         })
     }
 
+    /// Returns the path of the folder of the SSTable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+    ///
+    /// fs::create_dir("example_sstable_path")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_path", 100)?;
+    /// builder.insert(1, 2)?;
+    ///
+    /// let sstable: SSTable<u32, u32> = SSTable::open(builder.finish()?)?;
+    /// assert!(sstable.path().starts_with("example_sstable_path"));
+    /// # fs::remove_dir_all("example_sstable_path")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the number of entries in the SSTable, including tombstones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+    ///
+    /// fs::create_dir("example_sstable_len")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_len", 100)?;
+    /// builder.insert(1, 2)?;
+    /// builder.insert(3, 4)?;
+    ///
+    /// let sstable: SSTable<u32, u32> = SSTable::open(builder.finish()?)?;
+    /// assert_eq!(sstable.len(), 2);
+    /// # fs::remove_dir_all("example_sstable_len")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn len(&self) -> usize {
+        self.summary.entry_count
+    }
+
+    /// Returns `true` if the SSTable contains no entries. A SSTable built by a `SSTableBuilder`
+    /// always contains at least one entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+    ///
+    /// fs::create_dir("example_sstable_is_empty")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_is_empty", 100)?;
+    /// builder.insert(1, 2)?;
+    ///
+    /// let sstable: SSTable<u32, u32> = SSTable::open(builder.finish()?)?;
+    /// assert!(!sstable.is_empty());
+    /// # fs::remove_dir_all("example_sstable_is_empty")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the smallest and the largest key of the SSTable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+    ///
+    /// fs::create_dir("example_sstable_key_range")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_key_range", 100)?;
+    /// builder.insert(1, 2)?;
+    /// builder.insert(3, 4)?;
+    ///
+    /// let sstable: SSTable<u32, u32> = SSTable::open(builder.finish()?)?;
+    /// assert_eq!(sstable.key_range(), (&1, &3));
+    /// # fs::remove_dir_all("example_sstable_key_range")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn key_range(&self) -> (&T, &T) {
+        (&self.summary.key_range.0, &self.summary.key_range.1)
+    }
+
+    /// Returns the summary of the SSTable, which includes the distributions of the sizes of its
+    /// keys and values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+    ///
+    /// fs::create_dir("example_sstable_summary_method")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_summary_method", 100)?;
+    /// builder.insert(1u32, 2u64)?;
+    ///
+    /// let sstable: SSTable<u32, u64> = SSTable::open(builder.finish()?)?;
+    /// assert_eq!(sstable.summary().entry_count, 1);
+    /// assert_eq!(sstable.summary().value_sizes.average(), 8.0);
+    /// # fs::remove_dir_all("example_sstable_summary_method")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn summary(&self) -> &SSTableSummary<T> {
+        &self.summary
+    }
+
+    /// Returns the size of the data file of the SSTable in bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+    ///
+    /// fs::create_dir("example_sstable_data_size")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_data_size", 100)?;
+    /// builder.insert(1, 2)?;
+    /// let sstable_path = builder.finish()?;
+    ///
+    /// let sstable: SSTable<u32, u32> = SSTable::open(&sstable_path)?;
+    /// assert_eq!(sstable.data_size(), fs::metadata(sstable_path.join("data.dat"))?.len());
+    /// # fs::remove_dir_all("example_sstable_data_size")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn data_size(&self) -> u64 {
+        self.summary.data_size
+    }
+
+    /// Returns the first key of each index block of the SSTable and the offset of the block in the
+    /// data file, in ascending order of keys. The entries with keys from the first key of a block
+    /// up to the first key of the next block are stored from the offset of the block up to the
+    /// offset of the next block, or up to `data_size` for the last block. The offsets are read
+    /// from the summary, so they are useful for splitting the SSTable into ranges of roughly equal
+    /// size without reading its data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+    ///
+    /// fs::create_dir("example_sstable_get_approximate_offsets")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_get_approximate_offsets", 100)?;
+    /// for key in 0..100u32 {
+    ///     builder.insert(key, key)?;
+    /// }
+    ///
+    /// let sstable: SSTable<u32, u32> = SSTable::open(builder.finish()?)?;
+    /// let offsets = sstable.get_approximate_offsets();
+    /// assert_eq!(offsets[0], (&0, 0));
+    /// assert!(offsets.windows(2).all(|pair| pair[0] < pair[1]));
+    ///
+    /// // split the SSTable at the block closest to the middle of its data
+    /// let middle = sstable.data_size() / 2;
+    /// let split_key = offsets
+    ///     .iter()
+    ///     .min_by_key(|(_, offset)| (*offset as i64 - middle as i64).abs())
+    ///     .map(|(key, _)| **key);
+    /// assert!(split_key > Some(25) && split_key < Some(75));
+    /// # fs::remove_dir_all("example_sstable_get_approximate_offsets")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn get_approximate_offsets(&self) -> Vec<(&T, u64)> {
+        self.summary
+            .index
+            .iter()
+            .zip(self.summary.data_offsets.iter())
+            .map(|(index_entry, data_offset)| (&index_entry.0, *data_offset))
+            .collect()
+    }
+
+    // Marks the SSTable as no longer tracked by its compaction strategy. Its files are deleted
+    // once the last reference to it is dropped, so snapshots that still hold it remain readable.
+    pub(crate) fn mark_obsolete(&self) {
+        self.is_obsolete.store(true, Ordering::Release);
+    }
+
     fn floor_offset<V>(index: &[(T, u64)], key: &V) -> Option<usize>
     where
         T: Borrow<V>,
@@ -282,96 +1306,471 @@ impl<T, U> SSTable<T, U> {
         }
     }
 
-    pub fn get<V>(&self, key: &V) -> Result<Option<SSTableValue<U>>>
+    // Checks if the bloom filter of the SSTable may contain `key`, which is in the index block at
+    // `index`. Only the bloom filter of that index block is read if the bloom filter is
+    // partitioned.
+    fn filter_contains<V>(&self, index: usize, key: &V) -> Result<bool>
+    where
+        T: Borrow<V> + DeserializeOwned,
+        V: Hash + ?Sized,
+    {
+        if let Some(ref filter) = self.filter {
+            return Ok(filter.contains(key));
+        }
+
+        let filter_path = self.path.join("filter.dat");
+        let filter: BloomFilter<T> = read_record(filter_path, self.summary.filter_offsets[index])?;
+        Ok(filter.contains(key))
+    }
+
+    // Checks if the SSTable may contain a key with `prefix`. Always returns `true` if the SSTable
+    // was built without a prefix extractor.
+    pub(crate) fn may_contain_prefix(&self, prefix: &[u8]) -> bool {
+        match self.prefix_filter {
+            Some(ref prefix_filter) => prefix_filter.contains(prefix),
+            None => true,
+        }
+    }
+
+    fn read_index_block(&self, index: usize) -> Result<Vec<(T, u64)>>
+    where
+        T: DeserializeOwned,
+    {
+        read_record(self.path.join("index.dat"), self.summary.index[index].1)
+    }
+
+    /// Returns the value associated with a particular key in the SSTable. Returns `None` if the
+    /// key does not exist or if the SSTable contains a tombstone of the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+    ///
+    /// fs::create_dir("example_sstable_get")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_get", 100)?;
+    /// builder.insert(1, 2)?;
+    ///
+    /// let sstable: SSTable<u32, u32> = SSTable::open(builder.finish()?)?;
+    /// assert_eq!(sstable.get(&1)?, Some(2));
+    /// assert_eq!(sstable.get(&2)?, None);
+    /// # fs::remove_dir_all("example_sstable_get")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn get<V>(&self, key: &V) -> Result<Option<U>>
     where
         T: Borrow<V> + DeserializeOwned,
         U: DeserializeOwned,
         V: Ord + Hash + ?Sized,
     {
-        if key < self.summary.key_range.0.borrow() || key > self.summary.key_range.1.borrow() {
-            return Ok(None);
+        Ok(self.get_value(key)?.and_then(|value| value.data))
+    }
+
+    /// Returns the encoded bytes of the value associated with a particular key in the SSTable
+    /// without deserializing the value. Returns `None` if the key does not exist or if the SSTable
+    /// contains a tombstone of the key. The bytes are the value as serialized by
+    /// `bincode::serialize`, so large values can be decoded lazily or only in part by the caller.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+    ///
+    /// fs::create_dir("example_sstable_get_raw")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_get_raw", 100)?;
+    /// builder.insert(1, vec![1u8, 2, 3])?;
+    ///
+    /// let sstable: SSTable<u32, Vec<u8>> = SSTable::open(builder.finish()?)?;
+    /// let bytes = sstable.get_raw(&1)?.unwrap();
+    /// let value: Vec<u8> = bincode::deserialize(&bytes)?;
+    /// assert_eq!(value, vec![1, 2, 3]);
+    /// assert_eq!(sstable.get_raw(&2)?, None);
+    /// # fs::remove_dir_all("example_sstable_get_raw")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn get_raw<V>(&self, key: &V) -> Result<Option<Vec<u8>>>
+    where
+        T: Borrow<V> + DeserializeOwned,
+        V: Ord + Hash + ?Sized,
+    {
+        let offset = match self.get_data_offset(key)? {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+
+        let data_path = self.path.join("data.dat");
+        let buffer = read_record_bytes(&data_path, offset)?;
+        // an entry is encoded as its key followed by the tag of the optional value, the value,
+        // and the logical time, so the key is the only part that needs to be decoded.
+        let mut reader = buffer.as_slice();
+        deserialize_from::<_, T>(&mut reader).map_err(|_| Error::corruption(&data_path, offset))?;
+        let logical_time_size = mem::size_of::<u64>();
+        match reader.split_first() {
+            Some((0, rest)) if rest.len() == logical_time_size => Ok(None),
+            Some((1, rest)) if rest.len() >= logical_time_size => {
+                Ok(Some(rest[..rest.len() - logical_time_size].to_vec()))
+            }
+            _ => Err(Error::corruption(&data_path, offset)),
         }
+    }
 
-        if !self.filter.contains(key) {
+    // Returns the offset in the data file of the entry with `key`, or `None` if the SSTable does
+    // not contain the key.
+    fn get_data_offset<V>(&self, key: &V) -> Result<Option<u64>>
+    where
+        T: Borrow<V> + DeserializeOwned,
+        V: Ord + Hash + ?Sized,
+    {
+        if key < self.summary.key_range.0.borrow() || key > self.summary.key_range.1.borrow() {
             return Ok(None);
         }
 
+        self.filter_query_count.fetch_add(1, Ordering::Relaxed);
         let index = match Self::floor_offset(&self.summary.index, key) {
             Some(index) => index,
             None => return Ok(None),
         };
 
-        let mut index_file = fs::File::open(self.path.join("index.dat"))?;
-        index_file.seek(SeekFrom::Start(self.summary.index[index].1))?;
-        let size = index_file.read_u64::<BigEndian>()?;
-        let mut buffer = vec![0; size as usize];
-        index_file.read_exact(buffer.as_mut_slice())?;
-        let index_block: Vec<(T, u64)> = deserialize(&buffer)?;
+        if !self.filter_contains(index, key)? {
+            return Ok(None);
+        }
+
+        let index_block = self.read_index_block(index)?;
 
         let index = {
             match index_block.binary_search_by_key(&key, |index_entry| index_entry.0.borrow()) {
                 Ok(index) => index,
-                Err(_) => return Ok(None),
+                Err(_) => {
+                    self.filter_false_positive_count
+                        .fetch_add(1, Ordering::Relaxed);
+                    return Ok(None);
+                }
             }
         };
+        self.filter_true_positive_count
+            .fetch_add(1, Ordering::Relaxed);
+        Ok(Some(index_block[index].1))
+    }
 
-        let mut data_file = fs::File::open(self.path.join("data.dat"))?;
-        data_file.seek(SeekFrom::Start(index_block[index].1))?;
-        let size = data_file.read_u64::<BigEndian>()?;
-        let mut buffer = vec![0; size as usize];
-        data_file.read_exact(buffer.as_mut_slice())?;
-        deserialize(&buffer)
-            .map_err(Error::SerdeError)
-            .map(|entry: Entry<T, SSTableValue<U>>| Some(entry.value))
+    // Returns the most recent value of `key` in the SSTable, which is a tombstone if the key was
+    // deleted.
+    pub(crate) fn get_value<V>(&self, key: &V) -> Result<Option<SSTableValue<U>>>
+    where
+        T: Borrow<V> + DeserializeOwned,
+        U: DeserializeOwned,
+        V: Ord + Hash + ?Sized,
+    {
+        match self.get_data_offset(key)? {
+            Some(offset) => read_record(self.path.join("data.dat"), offset)
+                .map(|entry: Entry<T, SSTableValue<U>>| Some(entry.value)),
+            None => Ok(None),
+        }
     }
 
-    pub fn data_iter(&self) -> SSTableDataIter<T, U> {
+    pub(crate) fn filter_stats(&self) -> FilterStats {
+        FilterStats {
+            path: self.path.clone(),
+            query_count: self.filter_query_count.load(Ordering::Relaxed),
+            true_positive_count: self.filter_true_positive_count.load(Ordering::Relaxed),
+            false_positive_count: self.filter_false_positive_count.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns an iterator over the SSTable. The iterator will yield key-value pairs in ascending
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+    ///
+    /// fs::create_dir("example_sstable_iter")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_iter", 100)?;
+    /// builder.insert(1, 2)?;
+    /// builder.insert(3, 4)?;
+    ///
+    /// let sstable: SSTable<u32, u32> = SSTable::open(builder.finish()?)?;
+    /// let mut iterator = sstable.iter();
+    /// assert_eq!(iterator.next().transpose()?, Some((1, 2)));
+    /// assert_eq!(iterator.next().transpose()?, Some((3, 4)));
+    /// assert!(iterator.next().is_none());
+    /// # fs::remove_dir_all("example_sstable_iter")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn iter(&self) -> SSTableDataIter<T, U>
+    where
+        T: Clone,
+    {
         SSTableDataIter {
+            inner: self.entry_iter(),
+            end: None,
+        }
+    }
+
+    /// Returns an iterator over the key-value pairs of the SSTable with keys in `[start, end)`.
+    /// The index of the SSTable is used to seek to the first such key, so no preceding entries are
+    /// read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::lsm_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::lsm_tree::{SSTable, SSTableBuilder};
+    ///
+    /// fs::create_dir("example_sstable_range")?;
+    /// let mut builder = SSTableBuilder::new("example_sstable_range", 100)?;
+    /// for key in 0..10u32 {
+    ///     builder.insert(key, key)?;
+    /// }
+    ///
+    /// let sstable: SSTable<u32, u32> = SSTable::open(builder.finish()?)?;
+    /// let keys: Vec<u32> = sstable.range(&3, &6)?.map(|entry| entry.unwrap().0).collect();
+    /// assert_eq!(keys, vec![3, 4, 5]);
+    /// # fs::remove_dir_all("example_sstable_range")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn range(&self, start: &T, end: &T) -> Result<SSTableDataIter<T, U>>
+    where
+        T: Clone + DeserializeOwned + Ord,
+    {
+        Ok(SSTableDataIter {
+            inner: self.entry_iter_from(start)?,
+            end: Some(end.clone()),
+        })
+    }
+
+    pub(crate) fn entry_iter(&self) -> SSTableEntryIter<T, U>
+    where
+        T: Clone,
+    {
+        SSTableEntryIter {
             data_path: self.path.join("data.dat"),
             data_file: None,
+            data_offset: 0,
+            last_key: self.summary.key_range.1.clone(),
+            is_finished: false,
             _marker: PhantomData,
         }
     }
+
+    // Returns an iterator over the entries of the SSTable with keys greater than or equal to
+    // `key`. The summary and the index are used to find the offset of the first such entry in the
+    // data file, so no preceding entries are read.
+    pub(crate) fn entry_iter_from<V>(&self, key: &V) -> Result<SSTableEntryIter<T, U>>
+    where
+        T: Borrow<V> + Clone + DeserializeOwned,
+        V: Ord + ?Sized,
+    {
+        let data_path = self.path.join("data.dat");
+        let is_finished = key > self.summary.key_range.1.borrow();
+        let data_offset = if key <= self.summary.key_range.0.borrow() || is_finished {
+            0
+        } else {
+            let index = Self::floor_offset(&self.summary.index, key).unwrap_or(0);
+            let index_block = self.read_index_block(index)?;
+            let index =
+                match index_block.binary_search_by(|index_entry| index_entry.0.borrow().cmp(key)) {
+                    Ok(index) | Err(index) => index,
+                };
+
+            match index_block.get(index) {
+                Some(index_entry) => index_entry.1,
+                // the first entry is in the next index block, so it immediately follows the last
+                // entry of this index block.
+                None => {
+                    let data_offset = index_block
+                        .last()
+                        .expect("Expected non-empty index block.")
+                        .1;
+                    let mut data_file = fs::File::open(data_path.as_path())?;
+                    data_file.seek(SeekFrom::Start(data_offset))?;
+                    data_offset + 8 + data_file.read_u64::<BigEndian>()?
+                }
+            }
+        };
+
+        Ok(SSTableEntryIter {
+            data_path,
+            data_file: None,
+            data_offset,
+            last_key: self.summary.key_range.1.clone(),
+            is_finished,
+            _marker: PhantomData,
+        })
+    }
+
+    pub(crate) fn sync(&self) -> Result<()> {
+        sync_files(&self.path)
+    }
+
+    // Returns the total size of the files of the SSTable in bytes.
+    pub(crate) fn disk_size(&self) -> Result<u64> {
+        let mut ret = 0;
+        for file_name in SSTABLE_FILE_NAMES.iter() {
+            ret += fs::metadata(self.path.join(file_name))?.len();
+        }
+        Ok(ret)
+    }
+
+    // Creates a copy of the SSTable in `db_path` with the same name. The files of a SSTable are
+    // never modified after it is built, so they are hard-linked if possible and copied otherwise.
+    pub(crate) fn checkpoint<P>(&self, db_path: P) -> Result<SSTable<T, U>>
+    where
+        T: DeserializeOwned,
+        P: AsRef<Path>,
+    {
+        let file_name = self
+            .path
+            .file_name()
+            .expect("Expected a SSTable directory.");
+        let sstable_path = db_path.as_ref().join(file_name);
+        fs::create_dir(sstable_path.as_path())?;
+        for file_name in SSTABLE_FILE_NAMES.iter() {
+            let old_path = self.path.join(file_name);
+            let new_path = sstable_path.join(file_name);
+            if fs::hard_link(&old_path, &new_path).is_err() {
+                fs::copy(&old_path, &new_path)?;
+            }
+        }
+
+        let sstable = SSTable::open(sstable_path)?;
+        sstable.sync()?;
+        Ok(sstable)
+    }
 }
 
-pub struct SSTableDataIter<T, U> {
+pub struct SSTableEntryIter<T, U> {
     data_path: PathBuf,
     data_file: Option<fs::File>,
-    _marker: PhantomData<(T, U)>,
+    data_offset: u64,
+    // The iterator ends after the last key of the SSTable, so a data file that ends before it is
+    // reported as corrupted instead of silently dropping entries.
+    last_key: T,
+    is_finished: bool,
+    _marker: PhantomData<U>,
 }
 
-impl<T, U> Iterator for SSTableDataIter<T, U>
+impl<T, U> Iterator for SSTableEntryIter<T, U>
 where
-    T: DeserializeOwned,
+    T: DeserializeOwned + PartialEq,
     U: DeserializeOwned,
 {
     type Item = Result<Entry<T, SSTableValue<U>>>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+
         if self.data_file.is_none() {
-            match fs::File::open(self.data_path.as_path()) {
+            let data_file = fs::File::open(self.data_path.as_path()).and_then(|mut data_file| {
+                data_file.seek(SeekFrom::Start(self.data_offset))?;
+                Ok(data_file)
+            });
+            match data_file {
                 Ok(data_file) => self.data_file = Some(data_file),
-                Err(error) => return Some(Err(Error::from(error))),
+                Err(error) => return Some(Err(Error::file_io(&self.data_path, "open", error))),
             }
         }
 
         let data_file = self.data_file.as_mut().expect("Expected opened file.");
 
+        let offset = self.data_offset;
         let size = match data_file.read_u64::<BigEndian>() {
             Ok(size) => size,
-            Err(error) => match error.kind() {
-                ErrorKind::UnexpectedEof => return None,
-                _ => return Some(Err(Error::from(error))),
-            },
+            Err(error) => {
+                self.is_finished = true;
+                return match error.kind() {
+                    ErrorKind::UnexpectedEof => {
+                        Some(Err(Error::corruption(&self.data_path, offset)))
+                    }
+                    _ => Some(Err(Error::file_io(&self.data_path, "read", error))),
+                };
+            }
         };
 
         let mut buffer = vec![0; size as usize];
         let result = data_file.read_exact(buffer.as_mut_slice());
         if let Err(error) = result {
-            return Some(Err(Error::from(error)));
+            self.is_finished = true;
+            return match error.kind() {
+                ErrorKind::UnexpectedEof => Some(Err(Error::corruption(&self.data_path, offset))),
+                _ => Some(Err(Error::file_io(&self.data_path, "read", error))),
+            };
         }
 
-        Some(deserialize(&buffer).map_err(Error::SerdeError))
+        self.data_offset += 8 + size;
+        let entry: Entry<T, SSTableValue<U>> = match deserialize(&buffer) {
+            Ok(entry) => entry,
+            Err(_) => {
+                self.is_finished = true;
+                return Some(Err(Error::corruption(&self.data_path, offset)));
+            }
+        };
+        if entry.key == self.last_key {
+            self.is_finished = true;
+        }
+        Some(Ok(entry))
+    }
+}
+
+/// An iterator for `SSTable<T, U>`.
+///
+/// This iterator traverses the entries of the SSTable in ascending order of keys and yields
+/// key-value pairs, skipping tombstones. An error is yielded if an entry cannot be read.
+pub struct SSTableDataIter<T, U> {
+    inner: SSTableEntryIter<T, U>,
+    end: Option<T>,
+}
+
+impl<T, U> Iterator for SSTableDataIter<T, U>
+where
+    T: DeserializeOwned + Ord,
+    U: DeserializeOwned,
+{
+    type Item = Result<(T, U)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = match self.inner.next()? {
+                Ok(entry) => entry,
+                Err(error) => return Some(Err(error)),
+            };
+            if self.end.as_ref().is_some_and(|end| entry.key >= *end) {
+                self.inner.is_finished = true;
+                return None;
+            }
+            if let Some(data) = entry.value.data {
+                return Some(Ok((entry.key, data)));
+            }
+        }
+    }
+}
+
+impl<T, U> Drop for SSTable<T, U> {
+    fn drop(&mut self) {
+        if self.is_obsolete.load(Ordering::Acquire) {
+            let _ = fs::remove_dir_all(self.path.as_path());
+        }
     }
 }
 
@@ -393,7 +1792,7 @@ where
     where
         D: Deserializer<'de>,
     {
-        let ret = SSTable::new(PathBuf::deserialize(deserializer)?).map_err(de::Error::custom);
+        let ret = SSTable::open(PathBuf::deserialize(deserializer)?).map_err(de::Error::custom);
         Ok(ret?)
     }
 }
@@ -409,3 +1808,360 @@ where
         writeln!(f, "key range: {:?}", self.summary.key_range)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_range_tombstones, get_false_positive_rate, is_range_deleted,
+        tune_false_positive_rate, FilterOptions, FilterStats, IoOptions, RangeTombstone, SSTable,
+        SSTableBuilder, SSTableValue, SizeHistogram,
+    };
+    use crate::lsm_tree::Error;
+    use bincode::deserialize;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_range_tombstones() {
+        let range_tombstones = vec![
+            RangeTombstone {
+                start: 10,
+                end: 20,
+                logical_time: 5,
+            },
+            RangeTombstone {
+                start: 15,
+                end: 30,
+                logical_time: 10,
+            },
+        ];
+
+        assert!(!is_range_deleted(&range_tombstones, &9, 0));
+        assert!(is_range_deleted(&range_tombstones, &10, 4));
+        assert!(!is_range_deleted(&range_tombstones, &10, 6));
+        assert!(is_range_deleted(&range_tombstones, &19, 6));
+        assert!(!is_range_deleted(&range_tombstones, &30, 0));
+
+        assert!(range_tombstones[0].is_intersecting(&(0, 10)));
+        assert!(!range_tombstones[0].is_intersecting(&(20, 25)));
+
+        let value = |logical_time| {
+            Some(SSTableValue {
+                data: Some(1),
+                logical_time,
+            })
+        };
+        let value_opt = apply_range_tombstones(&range_tombstones, &12, value(7));
+        assert_eq!(value_opt.map(|value| value.data), Some(Some(1)));
+        let value_opt = apply_range_tombstones(&range_tombstones, &16, value(7));
+        assert_eq!(
+            value_opt.map(|value| (value.data, value.logical_time)),
+            Some((None, 10))
+        );
+        let value_opt = apply_range_tombstones::<_, u32, _>(&range_tombstones, &40, None);
+        assert!(value_opt.is_none());
+    }
+
+    fn filter_stats(query_count: usize, false_positive_count: usize) -> FilterStats {
+        FilterStats {
+            path: PathBuf::new(),
+            query_count,
+            true_positive_count: 0,
+            false_positive_count,
+        }
+    }
+
+    #[test]
+    fn test_tune_false_positive_rate_small_sample() {
+        let stats = vec![filter_stats(10, 10)];
+        assert_eq!(tune_false_positive_rate(0.05, 0.01, &stats), 0.05);
+    }
+
+    #[test]
+    fn test_tune_false_positive_rate() {
+        let stats = vec![filter_stats(1000, 50), filter_stats(1000, 50)];
+        let rate = tune_false_positive_rate(0.05, 0.01, &stats);
+        assert!((rate - 0.01).abs() < 1e-9);
+
+        let stats = vec![filter_stats(1000, 0)];
+        assert_eq!(tune_false_positive_rate(0.05, 0.01, &stats), 0.1);
+        assert_eq!(tune_false_positive_rate(0.4, 0.01, &stats), 0.5);
+    }
+
+    #[test]
+    fn test_size_histogram() {
+        let mut histogram = SizeHistogram::default();
+        assert_eq!(histogram.average(), 0.0);
+
+        for size in &[0, 1, 2, 3, 4, 100] {
+            histogram.insert(*size);
+        }
+        assert_eq!(histogram.count, 6);
+        assert_eq!(histogram.min, 0);
+        assert_eq!(histogram.max, 100);
+        assert_eq!(histogram.total, 110);
+        assert_eq!(histogram.buckets, vec![1, 1, 2, 1, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_get_false_positive_rate() {
+        assert!((get_false_positive_rate(10.0) - 0.0082).abs() < 1e-4);
+        assert!(get_false_positive_rate(5.0) > get_false_positive_rate(10.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_get_false_positive_rate_zero_bits() {
+        get_false_positive_rate(0.0);
+    }
+
+    #[test]
+    fn test_builder_insert() {
+        let path = "test_sstable_builder_insert";
+        fs::create_dir(path).unwrap();
+        let mut sstable_builder = SSTableBuilder::new(path, 0).unwrap();
+        for key in 0..100u32 {
+            sstable_builder.insert(key, key + 1).unwrap();
+        }
+        let sstable_path = sstable_builder.finish().unwrap();
+
+        let sstable: SSTable<u32, u32> = SSTable::open(sstable_path).unwrap();
+        assert_eq!(sstable.summary.entry_count, 100);
+        assert_eq!(sstable.summary.key_range, (0, 99));
+        for key in 0..100u32 {
+            let value = sstable.get_value(&key).unwrap().unwrap();
+            assert_eq!(value.data, Some(key + 1));
+            assert_eq!(value.logical_time, 0);
+        }
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_builder_insert_unsorted() {
+        let path = "test_sstable_builder_insert_unsorted";
+        fs::create_dir(path).unwrap();
+        let mut sstable_builder = SSTableBuilder::new(path, 100).unwrap();
+        sstable_builder.insert(1u32, 1u32).unwrap();
+        fs::remove_dir_all(path).unwrap();
+        sstable_builder.insert(1, 1).unwrap();
+    }
+
+    #[test]
+    fn test_partitioned_filter() {
+        let path = "test_sstable_partitioned_filter";
+        fs::create_dir(path).unwrap();
+        let filter_options = FilterOptions {
+            false_positive_rate: 0.01,
+            partition_threshold: Some(1000),
+        };
+        let mut sstable_builder =
+            SSTableBuilder::with_options(path, 1000, None, filter_options, IoOptions::default())
+                .unwrap();
+        for key in 0..1000u32 {
+            let value = SSTableValue {
+                data: Some(key),
+                logical_time: 0,
+            };
+            sstable_builder.append(key * 2, value).unwrap();
+        }
+        let sstable_path = sstable_builder.flush().unwrap();
+
+        let sstable: SSTable<u32, u32> = SSTable::open(sstable_path).unwrap();
+        assert!(sstable.filter.is_none());
+        assert_eq!(
+            sstable.summary.filter_offsets.len(),
+            sstable.summary.index.len()
+        );
+        for key in 0..1000u32 {
+            let value_opt = sstable.get_value(&(key * 2)).unwrap();
+            assert_eq!(value_opt.and_then(|value| value.data), Some(key));
+            assert!(sstable.get_value(&(key * 2 + 1)).unwrap().is_none());
+        }
+        assert_eq!(sstable.filter_stats().true_positive_count, 1000);
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_index_block_size() {
+        let path = "test_sstable_index_block_size";
+        fs::create_dir(path).unwrap();
+        let filter_options = FilterOptions {
+            false_positive_rate: 0.01,
+            partition_threshold: None,
+        };
+        let mut sstable_builder =
+            SSTableBuilder::with_options(path, 1000, Some(8), filter_options, IoOptions::default())
+                .unwrap();
+        for key in 0..1000u32 {
+            sstable_builder.insert(key, key).unwrap();
+        }
+        let sstable: SSTable<u32, u32> = SSTable::open(sstable_builder.flush().unwrap()).unwrap();
+        assert_eq!(sstable.summary.index_block_size, 8);
+        assert_eq!(sstable.summary.index.len(), 125);
+        for key in 0..1000u32 {
+            let value_opt = sstable.get_value(&key).unwrap();
+            assert_eq!(value_opt.and_then(|value| value.data), Some(key));
+        }
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_prefix_filter() {
+        let path = "test_sstable_prefix_filter";
+        fs::create_dir(path).unwrap();
+        let mut sstable_builder = SSTableBuilder::new(path, 100).unwrap();
+        sstable_builder.set_prefix_extractor(Some(Arc::new(|key: &u32| vec![(key / 10) as u8])));
+        for key in 0..50u32 {
+            sstable_builder.insert(key, key).unwrap();
+        }
+        let sstable: SSTable<u32, u32> = SSTable::open(sstable_builder.flush().unwrap()).unwrap();
+        for prefix in 0..5 {
+            assert!(sstable.may_contain_prefix(&[prefix]));
+        }
+
+        let mut sstable_builder = SSTableBuilder::new(path, 100).unwrap();
+        sstable_builder.insert(0u32, 0u32).unwrap();
+        let sstable: SSTable<u32, u32> = SSTable::open(sstable_builder.flush().unwrap()).unwrap();
+        assert!(sstable.prefix_filter.is_none());
+        assert!(sstable.may_contain_prefix(&[5]));
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_range() {
+        let path = "test_sstable_range";
+        fs::create_dir(path).unwrap();
+        let filter_options = FilterOptions {
+            false_positive_rate: 0.01,
+            partition_threshold: None,
+        };
+        let mut sstable_builder =
+            SSTableBuilder::with_options(path, 100, Some(8), filter_options, IoOptions::default())
+                .unwrap();
+        for key in 0..100u32 {
+            let value = SSTableValue {
+                data: if key % 10 == 0 { None } else { Some(key) },
+                logical_time: 0,
+            };
+            sstable_builder.append(key * 2, value).unwrap();
+        }
+        let sstable: SSTable<u32, u32> = SSTable::open(sstable_builder.flush().unwrap()).unwrap();
+
+        let keys = |start, end| -> Vec<u32> {
+            sstable
+                .range(&start, &end)
+                .unwrap()
+                .map(|entry| entry.unwrap().0)
+                .collect()
+        };
+        assert_eq!(keys(15, 24), vec![16, 18, 22]);
+        assert_eq!(keys(31, 35), vec![32, 34]);
+        assert_eq!(keys(195, 300), vec![196, 198]);
+        assert!(keys(300, 400).is_empty());
+        assert!(keys(40, 40).is_empty());
+
+        assert_eq!(sstable.iter().count(), 90);
+        assert_eq!(sstable.get(&2).unwrap(), Some(1));
+        assert_eq!(sstable.get(&20).unwrap(), None);
+        assert_eq!(sstable.len(), 100);
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_get_raw() {
+        let path = "test_sstable_get_raw";
+        fs::create_dir(path).unwrap();
+        let filter_options = FilterOptions {
+            false_positive_rate: 0.01,
+            partition_threshold: None,
+        };
+        let mut sstable_builder =
+            SSTableBuilder::with_options(path, 100, None, filter_options, IoOptions::default())
+                .unwrap();
+        for key in 0..100u32 {
+            let value = SSTableValue {
+                data: if key % 10 == 0 {
+                    None
+                } else {
+                    Some(format!("value-{}", key))
+                },
+                logical_time: u64::from(key),
+            };
+            sstable_builder
+                .append(format!("key-{:03}", key), value)
+                .unwrap();
+        }
+        let sstable: SSTable<String, String> =
+            SSTable::open(sstable_builder.flush().unwrap()).unwrap();
+
+        for key in 0..100u32 {
+            let bytes_opt = sstable.get_raw(format!("key-{:03}", key).as_str()).unwrap();
+            if key % 10 == 0 {
+                assert!(bytes_opt.is_none());
+            } else {
+                let value: String = deserialize(&bytes_opt.unwrap()).unwrap();
+                assert_eq!(value, format!("value-{}", key));
+            }
+        }
+        assert!(sstable.get_raw("key-100").unwrap().is_none());
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_get_approximate_offsets() {
+        let path = "test_sstable_get_approximate_offsets";
+        fs::create_dir(path).unwrap();
+        let filter_options = FilterOptions {
+            false_positive_rate: 0.01,
+            partition_threshold: None,
+        };
+        let mut sstable_builder =
+            SSTableBuilder::with_options(path, 100, Some(10), filter_options, IoOptions::default())
+                .unwrap();
+        for key in 0..95u32 {
+            sstable_builder.insert(key, key).unwrap();
+        }
+        let sstable: SSTable<u32, u32> = SSTable::open(sstable_builder.flush().unwrap()).unwrap();
+
+        let offsets = sstable.get_approximate_offsets();
+        assert_eq!(offsets.len(), 10);
+        for (index, (key, offset)) in offsets.into_iter().enumerate() {
+            assert_eq!(*key, index as u32 * 10);
+            let mut data_iter = sstable.entry_iter_from(key).unwrap();
+            let entry = data_iter.next().unwrap().unwrap();
+            assert_eq!(entry.key, *key);
+            assert_eq!(data_iter.data_offset - offset, sstable.data_size() / 95);
+        }
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let path = "test_sstable_unsupported_version";
+        fs::create_dir(path).unwrap();
+        let mut sstable_builder = SSTableBuilder::new(path, 100).unwrap();
+        sstable_builder.insert(0u32, 0u32).unwrap();
+        let sstable_path = sstable_builder.finish().unwrap();
+
+        let summary_path = sstable_path.join("summary.dat");
+        let mut buffer = fs::read(&summary_path).unwrap();
+        buffer[7] = 2;
+        fs::write(&summary_path, &buffer).unwrap();
+        match SSTable::<u32, u32>::open(&sstable_path) {
+            Err(Error::UnsupportedVersion { path, version }) => {
+                assert_eq!(path, summary_path);
+                assert_eq!(version, 2);
+            }
+            _ => panic!("Expected unsupported version."),
+        }
+
+        fs::write(&summary_path, &buffer[8..]).unwrap();
+        match SSTable::<u32, u32>::open(&sstable_path) {
+            Err(Error::UnsupportedVersion { version, .. }) => assert_eq!(version, 0),
+            _ => panic!("Expected unsupported version."),
+        }
+        fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,313 @@
+//! Utilities for merging ordered iterators.
+
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
+use core::cmp::{Ordering, Reverse};
+
+/// An item yielded by an ordered iterator that can be compared by key.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::merge::{KeyedItem, MergeIter, TieBreak};
+///
+/// struct Version {
+///     key: u32,
+///     timestamp: u64,
+/// }
+///
+/// impl KeyedItem for Version {
+///     type Key = u32;
+///
+///     fn key(&self) -> &u32 {
+///         &self.key
+///     }
+/// }
+///
+/// let newer = vec![Version { key: 1, timestamp: 2 }];
+/// let older = vec![Version { key: 1, timestamp: 1 }];
+/// let mut iter = MergeIter::new(vec![newer.into_iter(), older.into_iter()], TieBreak::First);
+/// assert_eq!(iter.next().map(|version| version.timestamp), Some(2));
+/// assert!(iter.next().is_none());
+/// ```
+pub trait KeyedItem {
+    /// The type of the key of the item.
+    type Key: Ord;
+
+    /// Returns the key of the item.
+    fn key(&self) -> &Self::Key;
+}
+
+impl<T> KeyedItem for &T
+where
+    T: Ord,
+{
+    type Key = T;
+
+    fn key(&self) -> &T {
+        self
+    }
+}
+
+impl<T, U> KeyedItem for (T, U)
+where
+    T: Ord,
+{
+    type Key = T;
+
+    fn key(&self) -> &T {
+        &self.0
+    }
+}
+
+/// The policy used by `MergeIter` to resolve items with equal keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TieBreak {
+    /// Yields only the item from the iterator that was passed first.
+    First,
+    /// Yields only the item from the iterator that was passed last.
+    Last,
+    /// Yields every item in the order that their iterators were passed.
+    All,
+}
+
+struct HeapEntry<T> {
+    item: T,
+    index: usize,
+}
+
+impl<T> Ord for HeapEntry<T>
+where
+    T: KeyedItem,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.item
+            .key()
+            .cmp(other.item.key())
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+impl<T> PartialOrd for HeapEntry<T>
+where
+    T: KeyedItem,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for HeapEntry<T>
+where
+    T: KeyedItem,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for HeapEntry<T> where T: KeyedItem {}
+
+/// A lazy iterator that merges any number of ordered iterators.
+///
+/// Each iterator must yield items in ascending order of their keys. The merged iterator yields
+/// the items of all iterators in ascending order of their keys, and resolves items with equal keys
+/// using a `TieBreak` policy. The next items of the iterators are kept in a binary heap, so
+/// yielding an item takes logarithmic time in the number of iterators.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::merge::{MergeIter, TieBreak};
+/// use extended_collections::treap::TreapMap;
+///
+/// let mut newer = TreapMap::new();
+/// newer.insert(1, "b");
+/// newer.insert(3, "c");
+///
+/// let mut older = TreapMap::new();
+/// older.insert(1, "a");
+/// older.insert(2, "a");
+///
+/// let merged: Vec<(&u32, &&str)> =
+///     MergeIter::new(vec![newer.iter(), older.iter()], TieBreak::First).collect();
+/// assert_eq!(merged, vec![(&1, &"b"), (&2, &"a"), (&3, &"c")]);
+/// ```
+pub struct MergeIter<I>
+where
+    I: Iterator,
+{
+    iters: Vec<I>,
+    entries: BinaryHeap<Reverse<HeapEntry<I::Item>>>,
+    tie_break: TieBreak,
+}
+
+impl<I> MergeIter<I>
+where
+    I: Iterator,
+    I::Item: KeyedItem,
+{
+    /// Constructs a new `MergeIter<I>` that merges `iters` and resolves items with equal keys
+    /// using `tie_break`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::merge::{MergeIter, TieBreak};
+    ///
+    /// let left = [1, 3, 5];
+    /// let right = [2, 3, 4];
+    ///
+    /// let merged: Vec<&u32> =
+    ///     MergeIter::new(vec![left.iter(), right.iter()], TieBreak::All).collect();
+    /// assert_eq!(merged, vec![&1, &2, &3, &3, &4, &5]);
+    /// ```
+    pub fn new<J>(iters: J, tie_break: TieBreak) -> Self
+    where
+        J: IntoIterator<Item = I>,
+    {
+        let mut iters: Vec<I> = iters.into_iter().collect();
+        let mut entries = BinaryHeap::with_capacity(iters.len());
+        for (index, iter) in iters.iter_mut().enumerate() {
+            if let Some(item) = iter.next() {
+                entries.push(Reverse(HeapEntry { item, index }));
+            }
+        }
+
+        MergeIter {
+            iters,
+            entries,
+            tie_break,
+        }
+    }
+
+    // Removes the entry with the smallest key and refills the heap from the iterator of the entry.
+    fn pop(&mut self) -> Option<HeapEntry<I::Item>> {
+        let Reverse(entry) = self.entries.pop()?;
+        if let Some(item) = self.iters[entry.index].next() {
+            self.entries.push(Reverse(HeapEntry {
+                item,
+                index: entry.index,
+            }));
+        }
+        Some(entry)
+    }
+
+    fn is_next_key(&self, key: &<I::Item as KeyedItem>::Key) -> bool {
+        match self.entries.peek() {
+            Some(Reverse(entry)) => entry.item.key() == key,
+            None => false,
+        }
+    }
+}
+
+impl<I> Iterator for MergeIter<I>
+where
+    I: Iterator,
+    I::Item: KeyedItem,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut entry = self.pop()?;
+        match self.tie_break {
+            TieBreak::All => {}
+            TieBreak::First => {
+                while self.is_next_key(entry.item.key()) {
+                    self.pop();
+                }
+            }
+            TieBreak::Last => {
+                while self.is_next_key(entry.item.key()) {
+                    entry = self.pop().expect("Expected a non-empty heap.");
+                }
+            }
+        }
+        Some(entry.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.tie_break {
+            TieBreak::All => {
+                let mut lower = self.entries.len();
+                let mut upper = Some(self.entries.len());
+                for iter in &self.iters {
+                    let (iter_lower, iter_upper) = iter.size_hint();
+                    lower = lower.saturating_add(iter_lower);
+                    upper = match (upper, iter_upper) {
+                        (Some(upper), Some(iter_upper)) => upper.checked_add(iter_upper),
+                        _ => None,
+                    };
+                }
+                (lower, upper)
+            }
+            _ => (if self.entries.is_empty() { 0 } else { 1 }, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MergeIter, TieBreak};
+
+    #[test]
+    fn test_empty() {
+        let iters: Vec<std::slice::Iter<'_, u32>> = Vec::new();
+        assert_eq!(MergeIter::new(iters, TieBreak::All).next(), None);
+
+        let empty: [u32; 0] = [];
+        let mut iter = MergeIter::new(vec![empty.iter(), empty.iter()], TieBreak::First);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_tie_break() {
+        let first = [(1, 'a'), (3, 'a')];
+        let second = [(1, 'b'), (2, 'b'), (3, 'b')];
+        let third = [(3, 'c'), (4, 'c')];
+        let iters = || {
+            vec![
+                first.iter().cloned(),
+                second.iter().cloned(),
+                third.iter().cloned(),
+            ]
+        };
+
+        assert_eq!(
+            MergeIter::new(iters(), TieBreak::First).collect::<Vec<(u32, char)>>(),
+            vec![(1, 'a'), (2, 'b'), (3, 'a'), (4, 'c')],
+        );
+        assert_eq!(
+            MergeIter::new(iters(), TieBreak::Last).collect::<Vec<(u32, char)>>(),
+            vec![(1, 'b'), (2, 'b'), (3, 'c'), (4, 'c')],
+        );
+        assert_eq!(
+            MergeIter::new(iters(), TieBreak::All).collect::<Vec<(u32, char)>>(),
+            vec![
+                (1, 'a'),
+                (1, 'b'),
+                (2, 'b'),
+                (3, 'a'),
+                (3, 'b'),
+                (3, 'c'),
+                (4, 'c'),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_many_iters() {
+        let vecs: Vec<Vec<u32>> = (0..10)
+            .map(|offset| (0..100).map(|key| key * 10 + offset).collect())
+            .collect();
+        let iter = MergeIter::new(vecs.iter().map(|vec| vec.iter()), TieBreak::First);
+        assert_eq!(iter.size_hint(), (1, None));
+        assert_eq!(
+            iter.cloned().collect::<Vec<u32>>(),
+            (0..1000).collect::<Vec<u32>>()
+        );
+
+        let iter = MergeIter::new(vecs.iter().map(|vec| vec.iter()), TieBreak::All);
+        assert_eq!(iter.size_hint(), (1000, Some(1000)));
+    }
+}
@@ -0,0 +1,592 @@
+//! Model-based testing of the disk-resident maps.
+//!
+//! A test generates a random program of operations and runs it against a `Store` and a `BTreeMap`
+//! oracle, checking that every lookup returns the same result as the oracle. Besides reads and
+//! writes, a program flushes the store, reopens it, and simulates crashes of the process by
+//! abandoning the store without flushing it. A program may also end by truncating one of the files
+//! of the store, after which every lookup must either return an error or the correct value, or the
+//! values of an earlier state if the store falls back to a previous version of its files. When a
+//! program fails, it is shrunk to a shorter program that still fails before being reported.
+//!
+//! This module is only available with the `model_testing` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use extended_collections::bp_tree::BpMap;
+//! use extended_collections::model_testing;
+//! use rand::Rng;
+//! use std::path::Path;
+//!
+//! model_testing::check_model(
+//!     4,
+//!     100,
+//!     Path::new("example_model_testing"),
+//!     |path| BpMap::with_degrees(path, 4, 8, 3, 3),
+//!     |path| BpMap::open(path),
+//!     |rng| rng.gen_range(0u32, 50),
+//!     |rng| rng.gen::<u64>(),
+//! );
+//! ```
+
+use crate::bp_tree::{self, BpMap};
+use crate::compare::Compare;
+use crate::lsm_tree::compaction::CompactionStrategy;
+use crate::lsm_tree::{self, LsmMap};
+use rand::{Rng, SeedableRng, XorShiftRng};
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::fs;
+use std::hash::Hash;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+/// A disk-resident map that can be checked against a `BTreeMap` oracle.
+pub trait Store<T, U>: Sized {
+    /// The error returned by the operations of the map.
+    type Error: Debug;
+
+    /// Whether writes that have not been flushed survive a crash of the process. If `false`, the
+    /// contents of the map after a crash may be any of the states of the map since the last flush.
+    const PERSISTS_UNFLUSHED_WRITES: bool;
+
+    /// Whether the map falls back to a previous version of its files if the current version is
+    /// corrupted. If `true`, the contents of the map after one of its files is truncated may be any
+    /// of the states of the map since it was created.
+    const RECOVERS_PREVIOUS_VERSIONS: bool;
+
+    /// Inserts a key-value pair into the map, replacing the value of an existing key.
+    fn insert(&mut self, key: T, value: U) -> Result<(), Self::Error>;
+
+    /// Removes a key from the map if it exists.
+    fn remove(&mut self, key: &T) -> Result<(), Self::Error>;
+
+    /// Returns the value associated with a key.
+    fn get(&mut self, key: &T) -> Result<Option<U>, Self::Error>;
+
+    /// Returns every key-value pair of the map in ascending order of keys.
+    fn entries(&mut self) -> Result<Vec<(T, U)>, Self::Error>;
+
+    /// Flushes the outstanding writes of the map to the operating system.
+    fn flush(&mut self) -> Result<(), Self::Error>;
+
+    /// Persists the map and consumes it.
+    fn close(self) -> Result<(), Self::Error>;
+
+    /// Consumes the map without flushing it, as if the process crashed. Background threads of the
+    /// map must terminate before this method returns so that they do not race with the next open
+    /// of the map. By default, the map is leaked.
+    fn crash(self) {
+        mem::forget(self);
+    }
+}
+
+impl<T, U, C> Store<T, U> for BpMap<T, U, C>
+where
+    T: Clone + DeserializeOwned + Serialize,
+    U: DeserializeOwned + Serialize,
+    C: Compare<T>,
+{
+    type Error = bp_tree::Error;
+
+    // every write is written to the file before it returns
+    const PERSISTS_UNFLUSHED_WRITES: bool = true;
+
+    const RECOVERS_PREVIOUS_VERSIONS: bool = false;
+
+    fn insert(&mut self, key: T, value: U) -> bp_tree::Result<()> {
+        BpMap::insert(self, key, value).map(|_| ())
+    }
+
+    fn remove(&mut self, key: &T) -> bp_tree::Result<()> {
+        BpMap::remove(self, key).map(|_| ())
+    }
+
+    fn get(&mut self, key: &T) -> bp_tree::Result<Option<U>> {
+        BpMap::get(self, key)
+    }
+
+    fn entries(&mut self) -> bp_tree::Result<Vec<(T, U)>> {
+        self.iter_mut()?.collect()
+    }
+
+    fn flush(&mut self) -> bp_tree::Result<()> {
+        BpMap::flush(self)
+    }
+
+    fn close(self) -> bp_tree::Result<()> {
+        BpMap::close(self)
+    }
+}
+
+impl<T, U, C> Store<T, U> for LsmMap<T, U, C>
+where
+    T: Clone + Ord + Hash + DeserializeOwned + Serialize,
+    U: Clone + DeserializeOwned + Serialize,
+    C: CompactionStrategy<T, U>,
+{
+    type Error = lsm_tree::Error;
+
+    // the in-memory tree is lost unless it was flushed into a SSTable
+    const PERSISTS_UNFLUSHED_WRITES: bool = false;
+
+    // the compaction strategies keep the previous version of their metadata
+    const RECOVERS_PREVIOUS_VERSIONS: bool = true;
+
+    fn insert(&mut self, key: T, value: U) -> lsm_tree::Result<()> {
+        LsmMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &T) -> lsm_tree::Result<()> {
+        LsmMap::remove(self, key.clone())
+    }
+
+    fn get(&mut self, key: &T) -> lsm_tree::Result<Option<U>> {
+        LsmMap::get(self, key)
+    }
+
+    fn entries(&mut self) -> lsm_tree::Result<Vec<(T, U)>> {
+        self.iter()?.collect()
+    }
+
+    fn flush(&mut self) -> lsm_tree::Result<()> {
+        LsmMap::flush(self)
+    }
+
+    fn close(self) -> lsm_tree::Result<()> {
+        LsmMap::close(self)
+    }
+
+    fn crash(self) {
+        LsmMap::crash(self)
+    }
+}
+
+/// An operation in a program run against a `Store`.
+#[derive(Clone, Debug)]
+pub enum Op<T, U> {
+    /// Inserts a key-value pair.
+    Insert(T, U),
+    /// Removes a key.
+    Remove(T),
+    /// Looks up a key.
+    Get(T),
+    /// Flushes the store.
+    Flush,
+    /// Closes the store and opens it again.
+    Reopen,
+    /// Crashes the store without flushing it and opens it again.
+    Crash,
+    /// Closes the store, truncates one of its files, and opens it again. This operation ends the
+    /// program.
+    Truncate {
+        /// The index of the file in the sorted list of files of the store, modulo its length.
+        file: usize,
+        /// The fraction of the length of the file that is kept.
+        fraction: f64,
+    },
+}
+
+/// Generates a program of `len` operations whose keys and values are produced by `generate_key`
+/// and `generate_value`. Every other program ends by truncating a file of the store.
+pub fn generate_ops<T, U, F, G>(
+    seed: u32,
+    len: usize,
+    mut generate_key: F,
+    mut generate_value: G,
+) -> Vec<Op<T, U>>
+where
+    F: FnMut(&mut XorShiftRng) -> T,
+    G: FnMut(&mut XorShiftRng) -> U,
+{
+    let mut rng = XorShiftRng::from_seed([seed, seed ^ 0x9E37_79B9, 1, 1]);
+    let mut ops: Vec<Op<T, U>> = (0..len)
+        .map(|_| match rng.gen_range(0, 100) {
+            0..=49 => {
+                let key = generate_key(&mut rng);
+                Op::Insert(key, generate_value(&mut rng))
+            }
+            50..=69 => Op::Remove(generate_key(&mut rng)),
+            70..=89 => Op::Get(generate_key(&mut rng)),
+            90..=93 => Op::Flush,
+            94..=96 => Op::Reopen,
+            _ => Op::Crash,
+        })
+        .collect();
+    if rng.gen() {
+        ops.push(Op::Truncate {
+            file: rng.gen(),
+            fraction: rng.gen(),
+        });
+    }
+    ops
+}
+
+fn remove_path(path: &Path) {
+    if path.is_dir() {
+        fs::remove_dir_all(path).ok();
+    } else {
+        fs::remove_file(path).ok();
+    }
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        if let Ok(entries) = fs::read_dir(path) {
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                collect_files(&entry.path(), files);
+            }
+        }
+    } else if path.is_file() {
+        files.push(path.to_path_buf());
+    }
+}
+
+fn truncate_file(path: &Path, file: usize, fraction: f64) -> Result<String, String> {
+    let mut files = Vec::new();
+    collect_files(path, &mut files);
+    if files.is_empty() {
+        return Err(format!("Expected {:?} to contain files.", path));
+    }
+    files.sort();
+    let file_path = &files[file % files.len()];
+    let len = fs::metadata(file_path)
+        .map_err(|error| format!("Failed to read metadata of {:?}: {}", file_path, error))?
+        .len();
+    let new_len = (len as f64 * fraction) as u64;
+    fs::OpenOptions::new()
+        .write(true)
+        .open(file_path)
+        .and_then(|file| file.set_len(new_len))
+        .map_err(|error| format!("Failed to truncate {:?}: {}", file_path, error))?;
+    Ok(format!("{:?} from {} to {} bytes", file_path, len, new_len))
+}
+
+// Checks that a store opened after one of its files was truncated either reports errors or
+// returns the contents of one of `models`, the last of which is the current model.
+fn check_truncated<S, T, U>(store: &mut S, models: &[BTreeMap<T, U>]) -> Result<(), String>
+where
+    S: Store<T, U>,
+    T: Debug + Ord,
+    U: Debug + PartialEq,
+{
+    let curr_model = models.last().expect("Expected a model.");
+    let model = match store.entries() {
+        Ok(entries) => {
+            let is_model = |model: &&BTreeMap<T, U>| {
+                entries.iter().map(|(key, value)| (key, value)).eq(*model)
+            };
+            match models.iter().rev().find(is_model) {
+                Some(model) => model,
+                None => {
+                    return Err(format!(
+                        "Expected entries to be {:?} or an error, got {:?}.",
+                        curr_model, entries,
+                    ));
+                }
+            }
+        }
+        Err(_) => curr_model,
+    };
+    for (key, value) in model {
+        if let Ok(actual) = store.get(key) {
+            if actual.as_ref() != Some(value) {
+                return Err(format!(
+                    "Expected get({:?}) to return {:?} or an error, got {:?}.",
+                    key, value, actual,
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_ops<S, T, U, C, O>(path: &Path, ops: &[Op<T, U>], create: &C, open: &O) -> Result<(), String>
+where
+    S: Store<T, U>,
+    T: Clone + Debug + Ord,
+    U: Clone + Debug + PartialEq,
+    C: Fn(&Path) -> Result<S, S::Error>,
+    O: Fn(&Path) -> Result<S, S::Error>,
+{
+    let mut store = create(path).map_err(|error| format!("Failed to create store: {:?}", error))?;
+    let mut model = BTreeMap::new();
+    // the states of the model since the last flush, excluding the current state, that the store
+    // may recover to after a crash
+    let mut recoverable_models = Vec::new();
+    // the states of the model since the store was created, excluding the current state, that the
+    // store may recover to after one of its files is truncated
+    let mut previous_models = Vec::new();
+
+    for (index, op) in ops.iter().enumerate() {
+        let context = |message: String| format!("Op {} ({:?}): {}", index, op, message);
+        match op {
+            Op::Insert(..) | Op::Remove(_) | Op::Crash if S::RECOVERS_PREVIOUS_VERSIONS => {
+                previous_models.push(model.clone());
+            }
+            _ => {}
+        }
+        match op {
+            Op::Insert(..) | Op::Remove(_) if !S::PERSISTS_UNFLUSHED_WRITES => {
+                recoverable_models.push(model.clone());
+            }
+            _ => {}
+        }
+
+        match op {
+            Op::Insert(key, value) => {
+                store
+                    .insert(key.clone(), value.clone())
+                    .map_err(|error| context(format!("{:?}", error)))?;
+                model.insert(key.clone(), value.clone());
+            }
+            Op::Remove(key) => {
+                store
+                    .remove(key)
+                    .map_err(|error| context(format!("{:?}", error)))?;
+                model.remove(key);
+            }
+            Op::Get(key) => {
+                let actual = store
+                    .get(key)
+                    .map_err(|error| context(format!("{:?}", error)))?;
+                let expected = model.get(key);
+                if actual.as_ref() != expected {
+                    return Err(context(format!(
+                        "Expected {:?}, got {:?}.",
+                        expected, actual
+                    )));
+                }
+            }
+            Op::Flush => {
+                store
+                    .flush()
+                    .map_err(|error| context(format!("{:?}", error)))?;
+                recoverable_models.clear();
+            }
+            Op::Reopen => {
+                store
+                    .close()
+                    .map_err(|error| context(format!("{:?}", error)))?;
+                store = open(path).map_err(|error| context(format!("{:?}", error)))?;
+                recoverable_models.clear();
+            }
+            Op::Crash => {
+                store.crash();
+                store = open(path).map_err(|error| context(format!("{:?}", error)))?;
+                let entries = store
+                    .entries()
+                    .map_err(|error| context(format!("{:?}", error)))?;
+                let recovered = recoverable_models
+                    .iter()
+                    .chain(Some(&model))
+                    .find(|model| entries.iter().map(|(key, value)| (key, value)).eq(*model))
+                    .cloned();
+                match recovered {
+                    Some(recovered) => model = recovered,
+                    None => {
+                        return Err(context(format!(
+                            "Expected a state since the last flush, got {:?}.",
+                            entries,
+                        )));
+                    }
+                }
+                recoverable_models.clear();
+            }
+            Op::Truncate { file, fraction } => {
+                store
+                    .close()
+                    .map_err(|error| context(format!("{:?}", error)))?;
+                let truncated = truncate_file(path, *file, *fraction).map_err(context)?;
+                previous_models.push(model);
+                return match open(path) {
+                    Ok(mut store) => {
+                        check_truncated(&mut store, &previous_models).map_err(|message| {
+                            context(format!("Truncated {}. {}", truncated, message))
+                        })
+                    }
+                    Err(_) => Ok(()),
+                };
+            }
+        }
+    }
+
+    let entries = store
+        .entries()
+        .map_err(|error| format!("Failed to read entries: {:?}", error))?;
+    if !entries.iter().map(|(key, value)| (key, value)).eq(&model) {
+        return Err(format!("Expected entries {:?}, got {:?}.", model, entries));
+    }
+    store
+        .close()
+        .map_err(|error| format!("Failed to close store: {:?}", error))
+}
+
+/// Runs a program against a new store at `path` and returns a description of the first operation
+/// whose result differs from the model. `create` creates a new store at `path`, and `open` opens
+/// the existing store at `path`. Any files at `path` are removed before and after the program
+/// runs.
+pub fn run<S, T, U, C, O>(path: &Path, ops: &[Op<T, U>], create: &C, open: &O) -> Result<(), String>
+where
+    S: Store<T, U>,
+    T: Clone + Debug + Ord,
+    U: Clone + Debug + PartialEq,
+    C: Fn(&Path) -> Result<S, S::Error>,
+    O: Fn(&Path) -> Result<S, S::Error>,
+{
+    remove_path(path);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| run_ops(path, ops, create, open)))
+        .unwrap_or_else(|_| Err(String::from("The store panicked.")));
+    remove_path(path);
+    result
+}
+
+/// Removes operations from a failing program while it keeps failing, and returns the shortened
+/// program and its failure.
+pub fn shrink<S, T, U, C, O>(
+    path: &Path,
+    mut ops: Vec<Op<T, U>>,
+    mut failure: String,
+    create: &C,
+    open: &O,
+) -> (Vec<Op<T, U>>, String)
+where
+    S: Store<T, U>,
+    T: Clone + Debug + Ord,
+    U: Clone + Debug + PartialEq,
+    C: Fn(&Path) -> Result<S, S::Error>,
+    O: Fn(&Path) -> Result<S, S::Error>,
+{
+    let mut chunk_len = ops.len() / 2;
+    while chunk_len > 0 {
+        let mut start = 0;
+        while start < ops.len() {
+            let end = (start + chunk_len).min(ops.len());
+            let mut candidate = ops[..start].to_vec();
+            candidate.extend_from_slice(&ops[end..]);
+            match run(path, &candidate, create, open) {
+                Err(candidate_failure) => {
+                    ops = candidate;
+                    failure = candidate_failure;
+                }
+                Ok(()) => start += chunk_len,
+            }
+        }
+        chunk_len /= 2;
+    }
+    (ops, failure)
+}
+
+/// Runs randomly generated programs of `ops_per_round` operations against new stores for a number
+/// of rounds and panics with the shrunk program if any of them fails.
+pub fn check_model<S, T, U, C, O, F, G>(
+    rounds: u32,
+    ops_per_round: usize,
+    path: &Path,
+    create: C,
+    open: O,
+    mut generate_key: F,
+    mut generate_value: G,
+) where
+    S: Store<T, U>,
+    T: Clone + Debug + Ord,
+    U: Clone + Debug + PartialEq,
+    C: Fn(&Path) -> Result<S, S::Error>,
+    O: Fn(&Path) -> Result<S, S::Error>,
+    F: FnMut(&mut XorShiftRng) -> T,
+    G: FnMut(&mut XorShiftRng) -> U,
+{
+    for round in 0..rounds {
+        let ops = generate_ops(round, ops_per_round, &mut generate_key, &mut generate_value);
+        if let Err(failure) = run(path, &ops, &create, &open) {
+            let (ops, failure) = shrink(path, ops, failure, &create, &open);
+            panic!(
+                "Expected round {} to match the model. {}\n{:#?}",
+                round, failure, ops,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_model, run, Op};
+    use crate::bp_tree::BpMap;
+    use crate::lsm_tree::compaction::{LeveledStrategy, SizeTieredStrategy};
+    use crate::lsm_tree::LsmMap;
+    use rand::Rng;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_bp_map() {
+        check_model(
+            16,
+            300,
+            Path::new("test_model_testing_bp_map"),
+            |path| BpMap::with_degrees(path, 4, 8, 3, 3),
+            |path| BpMap::open(path),
+            |rng| rng.gen_range(0u32, 100),
+            |rng| rng.gen::<u64>(),
+        );
+    }
+
+    #[test]
+    fn test_bp_map_mmap() {
+        check_model(
+            16,
+            300,
+            Path::new("test_model_testing_bp_map_mmap"),
+            |path| BpMap::new_mmap(path, 4, 8),
+            |path| BpMap::open_mmap(path),
+            |rng| rng.gen_range(0u32, 100),
+            |rng| rng.gen::<u64>(),
+        );
+    }
+
+    #[test]
+    fn test_lsm_map_size_tiered_strategy() {
+        check_model(
+            16,
+            300,
+            Path::new("test_model_testing_lsm_map_size_tiered_strategy"),
+            |path| SizeTieredStrategy::new(path, 200, 2, 400, 0.5, 1.5).map(LsmMap::new),
+            |path| SizeTieredStrategy::open(path).map(LsmMap::new),
+            |rng| rng.gen_range(0u32, 100),
+            |rng| rng.gen::<u64>(),
+        );
+    }
+
+    #[test]
+    fn test_lsm_map_leveled_strategy() {
+        check_model(
+            16,
+            300,
+            Path::new("test_model_testing_lsm_map_leveled_strategy"),
+            |path| LeveledStrategy::new(path, 200, 2, 400, 2, 2).map(LsmMap::new),
+            |path| LeveledStrategy::open(path).map(LsmMap::new),
+            |rng| rng.gen_range(0u32, 100),
+            |rng| rng.gen::<u64>(),
+        );
+    }
+
+    #[test]
+    fn test_detects_lost_writes() {
+        // a store that forgets every write when it is reopened does not match the model
+        let path = Path::new("test_model_testing_detects_lost_writes");
+        let ops = vec![Op::Insert(1u32, 1u64), Op::Reopen, Op::Get(1)];
+        let result = run(
+            path,
+            &ops,
+            &|path: &Path| BpMap::with_degrees(path, 4, 8, 3, 3),
+            &|path: &Path| {
+                fs::remove_file(path).ok();
+                BpMap::with_degrees(path, 4, 8, 3, 3)
+            },
+        );
+        assert!(result.is_err());
+    }
+}
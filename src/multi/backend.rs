@@ -0,0 +1,146 @@
+use crate::avl_tree::map::{AvlMap, AvlMapIter};
+use crate::btree::{BTreeMapExt, BTreeMapExtIter};
+use crate::red_black_tree::map::{RedBlackMap, RedBlackMapIter};
+use crate::skiplist::map::{SkipMap, SkipMapIter};
+use crate::splay_tree::map::{SplayMap, SplayMapIter};
+use crate::treap::map::{TreapMap, TreapMapIter};
+use std::collections::btree_map;
+use std::collections::BTreeMap;
+
+/// An ordered map that stores the entries of a `MultiMap` or `MultiSet`.
+///
+/// The trait is implemented for the ordered maps of this crate and for the `BTreeMap` of the
+/// standard library, so a multimap or multiset can use whichever map best fits its workload.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::avl_tree::AvlMap;
+/// use extended_collections::multi::MultiMap;
+///
+/// let mut map: MultiMap<u32, &str, AvlMap<u32, Vec<&str>>> = MultiMap::with_backend();
+/// map.insert(1, "a");
+/// map.insert(1, "b");
+/// assert_eq!(map.get_all(&1), &["a", "b"]);
+/// ```
+pub trait MapBackend<K, V>: Default {
+    /// The type of the iterator over the entries of the map.
+    type Iter<'a>: Iterator<Item = (&'a K, &'a V)>
+    where
+        Self: 'a,
+        K: 'a,
+        V: 'a;
+
+    /// Returns an immutable reference to the value associated with a particular key.
+    fn get(&self, key: &K) -> Option<&V>;
+
+    /// Returns a mutable reference to the value associated with a particular key.
+    fn get_mut(&mut self, key: &K) -> Option<&mut V>;
+
+    /// Inserts a key-value pair into the map, returning the old key-value pair if the key already
+    /// existed in the map.
+    fn insert(&mut self, key: K, value: V) -> Option<(K, V)>;
+
+    /// Removes a key from the map, returning the associated key-value pair if the key existed in
+    /// the map.
+    fn remove(&mut self, key: &K) -> Option<(K, V)>;
+
+    /// Returns the number of entries in the map.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the map is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the entries of the map in ascending order of their keys.
+    fn iter(&self) -> Self::Iter<'_>;
+}
+
+macro_rules! map_backend {
+    ($($map:ident, $iter:ident)*) => {
+        $(
+            impl<K, V> MapBackend<K, V> for $map<K, V>
+            where
+                K: Ord,
+            {
+                type Iter<'a> = $iter<'a, K, V>
+                where
+                    Self: 'a,
+                    K: 'a,
+                    V: 'a;
+
+                fn get(&self, key: &K) -> Option<&V> {
+                    $map::get(self, key)
+                }
+
+                fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+                    $map::get_mut(self, key)
+                }
+
+                fn insert(&mut self, key: K, value: V) -> Option<(K, V)> {
+                    $map::insert(self, key, value)
+                }
+
+                fn remove(&mut self, key: &K) -> Option<(K, V)> {
+                    $map::remove(self, key)
+                }
+
+                fn len(&self) -> usize {
+                    $map::len(self)
+                }
+
+                fn iter(&self) -> Self::Iter<'_> {
+                    $map::iter(self)
+                }
+            }
+        )*
+    }
+}
+
+map_backend!(
+    AvlMap, AvlMapIter
+    BTreeMapExt, BTreeMapExtIter
+    RedBlackMap, RedBlackMapIter
+    SkipMap, SkipMapIter
+    SplayMap, SplayMapIter
+    TreapMap, TreapMapIter
+);
+
+impl<K, V> MapBackend<K, V> for BTreeMap<K, V>
+where
+    K: Ord,
+{
+    type Iter<'a>
+        = btree_map::Iter<'a, K, V>
+    where
+        Self: 'a,
+        K: 'a,
+        V: 'a;
+
+    fn get(&self, key: &K) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        BTreeMap::get_mut(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<(K, V)> {
+        let old_entry = BTreeMap::remove_entry(self, &key);
+        BTreeMap::insert(self, key, value);
+        old_entry
+    }
+
+    fn remove(&mut self, key: &K) -> Option<(K, V)> {
+        BTreeMap::remove_entry(self, key)
+    }
+
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        BTreeMap::iter(self)
+    }
+}
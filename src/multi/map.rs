@@ -0,0 +1,563 @@
+use crate::multi::backend::MapBackend;
+use crate::treap::TreapMap;
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::slice;
+
+/// An ordered multimap that associates each key with a list of values.
+///
+/// The values of each key are stored in a `Vec<V>` in insertion order, and the keys are stored in
+/// an ordered map that implements `MapBackend`. The map defaults to a `TreapMap`, but any of the
+/// ordered maps of the crate can be used instead.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::multi::MultiMap;
+///
+/// let mut map = MultiMap::new();
+/// map.insert(1, "a");
+/// map.insert(2, "b");
+/// map.insert(1, "c");
+///
+/// assert_eq!(map.get_all(&1), &["a", "c"]);
+/// assert_eq!(map.count(&1), 2);
+/// assert_eq!(map.len(), 3);
+///
+/// assert_eq!(map.remove_one(&1), Some("c"));
+/// assert_eq!(map.remove_all(&1), vec!["a"]);
+/// assert_eq!(map.get_all(&1), &[] as &[&str]);
+/// ```
+pub struct MultiMap<K, V, M = TreapMap<K, Vec<V>>> {
+    map: M,
+    len: usize,
+    _marker: PhantomData<(K, V)>,
+}
+
+impl<K, V> MultiMap<K, V>
+where
+    K: Ord,
+{
+    /// Constructs a new, empty `MultiMap<K, V>` backed by a `TreapMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let map: MultiMap<u32, u32> = MultiMap::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::with_backend()
+    }
+}
+
+impl<K, V, M> MultiMap<K, V, M>
+where
+    M: MapBackend<K, Vec<V>>,
+{
+    /// Constructs a new, empty `MultiMap<K, V, M>` backed by an empty map of type `M`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let map: MultiMap<u32, u32, AvlMap<u32, Vec<u32>>> = MultiMap::with_backend();
+    /// ```
+    pub fn with_backend() -> Self {
+        MultiMap {
+            map: M::default(),
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Inserts a key-value pair into the map. The value is appended after the existing values of
+    /// the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(1, 2);
+    /// assert_eq!(map.get_all(&1), &[1, 2]);
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        match self.map.get_mut(&key) {
+            Some(values) => values.push(value),
+            None => {
+                self.map.insert(key, vec![value]);
+            }
+        }
+        self.len += 1;
+    }
+
+    /// Removes the most recently inserted value of a key from the map and returns it. Returns
+    /// `None` if the key does not exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(1, 2);
+    /// assert_eq!(map.remove_one(&1), Some(2));
+    /// assert_eq!(map.remove_one(&1), Some(1));
+    /// assert_eq!(map.remove_one(&1), None);
+    /// ```
+    pub fn remove_one(&mut self, key: &K) -> Option<V> {
+        let (ret, is_empty) = {
+            let values = self.map.get_mut(key)?;
+            (values.pop(), values.is_empty())
+        };
+        if is_empty {
+            self.map.remove(key);
+        }
+        self.len -= 1;
+        ret
+    }
+
+    /// Removes a key and all of its values from the map and returns the values in insertion order.
+    /// Returns an empty vector if the key does not exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(1, 2);
+    /// assert_eq!(map.remove_all(&1), vec![1, 2]);
+    /// assert_eq!(map.remove_all(&1), vec![]);
+    /// ```
+    pub fn remove_all(&mut self, key: &K) -> Vec<V> {
+        let values = self
+            .map
+            .remove(key)
+            .map(|(_, values)| values)
+            .unwrap_or_default();
+        self.len -= values.len();
+        values
+    }
+
+    /// Checks if a key exists in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, 1);
+    /// assert!(!map.contains_key(&0));
+    /// assert!(map.contains_key(&1));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.get(key).is_some()
+    }
+
+    /// Returns an immutable reference to the first value associated with a particular key. It
+    /// will return `None` if the key does not exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(1, 2);
+    /// assert_eq!(map.get(&0), None);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.get_all(key).first()
+    }
+
+    /// Returns the values associated with a particular key in insertion order. It will return an
+    /// empty slice if the key does not exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(1, 2);
+    /// assert_eq!(map.get_all(&0), &[]);
+    /// assert_eq!(map.get_all(&1), &[1, 2]);
+    /// ```
+    pub fn get_all(&self, key: &K) -> &[V] {
+        self.map.get(key).map_or(&[], |values| values)
+    }
+
+    /// Returns a mutable reference to the values associated with a particular key in insertion
+    /// order. It will return an empty slice if the key does not exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(1, 2);
+    /// for value in map.get_all_mut(&1) {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(map.get_all(&1), &[2, 3]);
+    /// ```
+    pub fn get_all_mut(&mut self, key: &K) -> &mut [V] {
+        self.map.get_mut(key).map_or(&mut [], |values| values)
+    }
+
+    /// Returns the number of values associated with a particular key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(1, 2);
+    /// assert_eq!(map.count(&0), 0);
+    /// assert_eq!(map.count(&1), 2);
+    /// ```
+    pub fn count(&self, key: &K) -> usize {
+        self.get_all(key).len()
+    }
+
+    /// Returns the number of values in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(1, 2);
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of distinct keys in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(1, 2);
+    /// assert_eq!(map.key_count(), 1);
+    /// ```
+    pub fn key_count(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let map: MultiMap<u32, u32> = MultiMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the map, removing all values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.clear();
+    /// assert_eq!(map.is_empty(), true);
+    /// ```
+    pub fn clear(&mut self) {
+        self.map = M::default();
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the map. The iterator will yield key-value pairs in ascending
+    /// order of their keys, and the values of each key in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(2, 2);
+    /// map.insert(1, 1);
+    /// map.insert(2, 3);
+    ///
+    /// let mut iterator = map.iter();
+    /// assert_eq!(iterator.next(), Some((&1, &1)));
+    /// assert_eq!(iterator.next(), Some((&2, &2)));
+    /// assert_eq!(iterator.next(), Some((&2, &3)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> MultiMapIter<'_, K, V, M> {
+        MultiMapIter {
+            entries: self.map.iter(),
+            current: None,
+        }
+    }
+
+    /// Returns an iterator over the distinct keys of the map and the number of values associated
+    /// with each key. The iterator will yield the keys in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(2, 2);
+    /// map.insert(1, 1);
+    /// map.insert(2, 3);
+    ///
+    /// assert_eq!(map.iter_counts().collect::<Vec<_>>(), vec![(&1, 1), (&2, 2)]);
+    /// ```
+    pub fn iter_counts(&self) -> MultiMapCountIter<'_, K, V, M> {
+        MultiMapCountIter {
+            entries: self.map.iter(),
+        }
+    }
+}
+
+impl<'a, K, V, M> IntoIterator for &'a MultiMap<K, V, M>
+where
+    K: 'a,
+    V: 'a,
+    M: 'a + MapBackend<K, Vec<V>>,
+{
+    type IntoIter = MultiMapIter<'a, K, V, M>;
+    type Item = (&'a K, &'a V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator for `MultiMap<K, V, M>`.
+///
+/// This iterator traverses the keys of the map in ascending order and yields immutable references
+/// to each key-value pair.
+pub struct MultiMapIter<'a, K, V, M>
+where
+    K: 'a,
+    V: 'a,
+    M: 'a + MapBackend<K, Vec<V>>,
+{
+    entries: M::Iter<'a>,
+    current: Option<(&'a K, slice::Iter<'a, V>)>,
+}
+
+impl<'a, K, V, M> Iterator for MultiMapIter<'a, K, V, M>
+where
+    K: 'a,
+    V: 'a,
+    M: 'a + MapBackend<K, Vec<V>>,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, ref mut values)) = self.current {
+                if let Some(value) = values.next() {
+                    return Some((key, value));
+                }
+            }
+            let (key, values) = self.entries.next()?;
+            self.current = Some((key, values.iter()));
+        }
+    }
+}
+
+/// An iterator over the distinct keys of a `MultiMap<K, V, M>` and their number of values.
+///
+/// This iterator traverses the keys of the map in ascending order.
+pub struct MultiMapCountIter<'a, K, V, M>
+where
+    K: 'a,
+    V: 'a,
+    M: 'a + MapBackend<K, Vec<V>>,
+{
+    entries: M::Iter<'a>,
+}
+
+impl<'a, K, V, M> Iterator for MultiMapCountIter<'a, K, V, M>
+where
+    K: 'a,
+    V: 'a,
+    M: 'a + MapBackend<K, Vec<V>>,
+{
+    type Item = (&'a K, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|(key, values)| (key, values.len()))
+    }
+}
+
+impl<K, V, M> Default for MultiMap<K, V, M>
+where
+    M: MapBackend<K, Vec<V>>,
+{
+    fn default() -> Self {
+        Self::with_backend()
+    }
+}
+
+impl<K, V, M> fmt::Debug for MultiMap<K, V, M>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+    M: MapBackend<K, Vec<V>>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.map.iter()).finish()
+    }
+}
+
+impl<K, V, M> FromIterator<(K, V)> for MultiMap<K, V, M>
+where
+    M: MapBackend<K, Vec<V>>,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = Self::with_backend();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, M> Extend<(K, V)> for MultiMap<K, V, M>
+where
+    M: MapBackend<K, Vec<V>>,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiMap;
+    use crate::avl_tree::AvlMap;
+    use crate::multi::MapBackend;
+    use crate::skiplist::SkipMap;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_len_empty() {
+        let map: MultiMap<u32, u32> = MultiMap::new();
+        assert_eq!(map.len(), 0);
+        assert_eq!(map.key_count(), 0);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut map = MultiMap::new();
+        map.insert(1, 1);
+        map.insert(1, 2);
+        map.insert(2, 3);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.key_count(), 2);
+
+        assert_eq!(map.remove_one(&1), Some(2));
+        assert_eq!(map.remove_one(&1), Some(1));
+        assert!(!map.contains_key(&1));
+        assert_eq!(map.remove_one(&1), None);
+        assert_eq!(map.remove_all(&2), vec![3]);
+        assert!(map.is_empty());
+        assert_eq!(map.key_count(), 0);
+    }
+
+    #[test]
+    fn test_debug() {
+        let map: MultiMap<u32, u32> = vec![(1, 1), (2, 2), (1, 3)].into_iter().collect();
+        assert_eq!(format!("{:?}", map), "{1: [1, 3], 2: [2]}");
+    }
+
+    fn check_random<M>()
+    where
+        M: MapBackend<u32, Vec<u32>>,
+    {
+        let mut rng: XorShiftRng = SeedableRng::from_seed([1, 1, 1, 1]);
+        let mut map: MultiMap<u32, u32, M> = MultiMap::with_backend();
+        let mut expected: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+
+        for _ in 0..2000 {
+            let key = rng.gen_range(0, 50);
+            match rng.gen_range(0, 4) {
+                0 | 1 => {
+                    let value = rng.gen::<u32>();
+                    map.insert(key, value);
+                    expected.entry(key).or_default().push(value);
+                }
+                2 => {
+                    let expected_value = expected.get_mut(&key).and_then(|values| values.pop());
+                    if expected.get(&key).is_some_and(|values| values.is_empty()) {
+                        expected.remove(&key);
+                    }
+                    assert_eq!(map.remove_one(&key), expected_value);
+                }
+                _ => {
+                    let expected_values = expected.remove(&key).unwrap_or_default();
+                    assert_eq!(map.remove_all(&key), expected_values);
+                }
+            }
+            assert_eq!(map.len(), expected.values().map(Vec::len).sum::<usize>());
+            assert_eq!(map.key_count(), expected.len());
+        }
+
+        assert!(map.iter().eq(expected
+            .iter()
+            .flat_map(|(key, values)| values.iter().map(move |value| (key, value)))));
+        assert!(map
+            .iter_counts()
+            .eq(expected.iter().map(|(key, values)| (key, values.len()))));
+    }
+
+    #[test]
+    fn test_random() {
+        check_random::<crate::treap::TreapMap<u32, Vec<u32>>>();
+        check_random::<AvlMap<u32, Vec<u32>>>();
+        check_random::<SkipMap<u32, Vec<u32>>>();
+        check_random::<BTreeMap<u32, Vec<u32>>>();
+    }
+}
@@ -0,0 +1,9 @@
+//! Multimaps and multisets that allow duplicate keys, built on top of an ordered map.
+
+mod backend;
+mod map;
+mod set;
+
+pub use self::backend::MapBackend;
+pub use self::map::{MultiMap, MultiMapCountIter, MultiMapIter};
+pub use self::set::{MultiSet, MultiSetCountIter, MultiSetIter};
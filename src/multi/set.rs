@@ -0,0 +1,491 @@
+use crate::multi::backend::MapBackend;
+use crate::treap::TreapMap;
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+/// An ordered multiset that counts the number of occurrences of each key.
+///
+/// The number of occurrences of each key is stored in an ordered map that implements
+/// `MapBackend`. The map defaults to a `TreapMap`, but any of the ordered maps of the crate can
+/// be used instead.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::multi::MultiSet;
+///
+/// let mut set = MultiSet::new();
+/// set.insert(1);
+/// set.insert(2);
+/// set.insert(1);
+///
+/// assert_eq!(set.count(&1), 2);
+/// assert_eq!(set.len(), 3);
+///
+/// assert!(set.remove_one(&1));
+/// assert_eq!(set.remove_all(&1), 1);
+/// assert_eq!(set.count(&1), 0);
+/// ```
+pub struct MultiSet<K, M = TreapMap<K, usize>> {
+    map: M,
+    len: usize,
+    _marker: PhantomData<K>,
+}
+
+impl<K> MultiSet<K>
+where
+    K: Ord,
+{
+    /// Constructs a new, empty `MultiSet<K>` backed by a `TreapMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let set: MultiSet<u32> = MultiSet::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::with_backend()
+    }
+}
+
+impl<K, M> MultiSet<K, M>
+where
+    M: MapBackend<K, usize>,
+{
+    /// Constructs a new, empty `MultiSet<K, M>` backed by an empty map of type `M`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::avl_tree::AvlMap;
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let set: MultiSet<u32, AvlMap<u32, usize>> = MultiSet::with_backend();
+    /// ```
+    pub fn with_backend() -> Self {
+        MultiSet {
+            map: M::default(),
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Inserts an occurrence of a key into the set and returns the number of occurrences of the
+    /// key after the insertion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let mut set = MultiSet::new();
+    /// assert_eq!(set.insert(1), 1);
+    /// assert_eq!(set.insert(1), 2);
+    /// ```
+    pub fn insert(&mut self, key: K) -> usize {
+        self.insert_many(key, 1)
+    }
+
+    /// Inserts `count` occurrences of a key into the set and returns the number of occurrences of
+    /// the key after the insertion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let mut set = MultiSet::new();
+    /// assert_eq!(set.insert_many(1, 3), 3);
+    /// assert_eq!(set.insert_many(1, 2), 5);
+    /// ```
+    pub fn insert_many(&mut self, key: K, count: usize) -> usize {
+        if count == 0 {
+            return self.count(&key);
+        }
+        self.len += count;
+        match self.map.get_mut(&key) {
+            Some(curr_count) => {
+                *curr_count += count;
+                *curr_count
+            }
+            None => {
+                self.map.insert(key, count);
+                count
+            }
+        }
+    }
+
+    /// Removes an occurrence of a key from the set. Returns `true` if the key existed in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let mut set = MultiSet::new();
+    /// set.insert(1);
+    /// assert!(set.remove_one(&1));
+    /// assert!(!set.remove_one(&1));
+    /// ```
+    pub fn remove_one(&mut self, key: &K) -> bool {
+        let is_empty = match self.map.get_mut(key) {
+            Some(count) => {
+                *count -= 1;
+                *count == 0
+            }
+            None => return false,
+        };
+        if is_empty {
+            self.map.remove(key);
+        }
+        self.len -= 1;
+        true
+    }
+
+    /// Removes all occurrences of a key from the set and returns the number of occurrences that
+    /// were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let mut set = MultiSet::new();
+    /// set.insert(1);
+    /// set.insert(1);
+    /// assert_eq!(set.remove_all(&1), 2);
+    /// assert_eq!(set.remove_all(&1), 0);
+    /// ```
+    pub fn remove_all(&mut self, key: &K) -> usize {
+        let count = self.map.remove(key).map_or(0, |(_, count)| count);
+        self.len -= count;
+        count
+    }
+
+    /// Checks if a key exists in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let mut set = MultiSet::new();
+    /// set.insert(1);
+    /// assert!(!set.contains(&0));
+    /// assert!(set.contains(&1));
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.map.get(key).is_some()
+    }
+
+    /// Returns the number of occurrences of a key in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let mut set = MultiSet::new();
+    /// set.insert(1);
+    /// set.insert(1);
+    /// assert_eq!(set.count(&0), 0);
+    /// assert_eq!(set.count(&1), 2);
+    /// ```
+    pub fn count(&self, key: &K) -> usize {
+        self.map.get(key).cloned().unwrap_or(0)
+    }
+
+    /// Returns the number of occurrences of all keys in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let mut set = MultiSet::new();
+    /// set.insert(1);
+    /// set.insert(1);
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of distinct keys in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let mut set = MultiSet::new();
+    /// set.insert(1);
+    /// set.insert(1);
+    /// assert_eq!(set.key_count(), 1);
+    /// ```
+    pub fn key_count(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let set: MultiSet<u32> = MultiSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the set, removing all keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let mut set = MultiSet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    /// set.clear();
+    /// assert_eq!(set.is_empty(), true);
+    /// ```
+    pub fn clear(&mut self) {
+        self.map = M::default();
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the set. The iterator will yield each key as many times as it
+    /// occurs in the set, in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let mut set = MultiSet::new();
+    /// set.insert(2);
+    /// set.insert(1);
+    /// set.insert(2);
+    ///
+    /// let mut iterator = set.iter();
+    /// assert_eq!(iterator.next(), Some(&1));
+    /// assert_eq!(iterator.next(), Some(&2));
+    /// assert_eq!(iterator.next(), Some(&2));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> MultiSetIter<'_, K, M> {
+        MultiSetIter {
+            entries: self.map.iter(),
+            current: None,
+        }
+    }
+
+    /// Returns an iterator over the distinct keys of the set and their number of occurrences. The
+    /// iterator will yield the keys in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::multi::MultiSet;
+    ///
+    /// let mut set = MultiSet::new();
+    /// set.insert(2);
+    /// set.insert(1);
+    /// set.insert(2);
+    ///
+    /// assert_eq!(set.iter_counts().collect::<Vec<_>>(), vec![(&1, 1), (&2, 2)]);
+    /// ```
+    pub fn iter_counts(&self) -> MultiSetCountIter<'_, K, M> {
+        MultiSetCountIter {
+            entries: self.map.iter(),
+        }
+    }
+}
+
+impl<'a, K, M> IntoIterator for &'a MultiSet<K, M>
+where
+    K: 'a,
+    M: 'a + MapBackend<K, usize>,
+{
+    type IntoIter = MultiSetIter<'a, K, M>;
+    type Item = &'a K;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator for `MultiSet<K, M>`.
+///
+/// This iterator traverses the keys of the set in ascending order and yields an immutable
+/// reference to each key once for every occurrence.
+pub struct MultiSetIter<'a, K, M>
+where
+    K: 'a,
+    M: 'a + MapBackend<K, usize>,
+{
+    entries: M::Iter<'a>,
+    current: Option<(&'a K, usize)>,
+}
+
+impl<'a, K, M> Iterator for MultiSetIter<'a, K, M>
+where
+    K: 'a,
+    M: 'a + MapBackend<K, usize>,
+{
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, ref mut remaining)) = self.current {
+                if *remaining > 0 {
+                    *remaining -= 1;
+                    return Some(key);
+                }
+            }
+            let (key, count) = self.entries.next()?;
+            self.current = Some((key, *count));
+        }
+    }
+}
+
+/// An iterator over the distinct keys of a `MultiSet<K, M>` and their number of occurrences.
+///
+/// This iterator traverses the keys of the set in ascending order.
+pub struct MultiSetCountIter<'a, K, M>
+where
+    K: 'a,
+    M: 'a + MapBackend<K, usize>,
+{
+    entries: M::Iter<'a>,
+}
+
+impl<'a, K, M> Iterator for MultiSetCountIter<'a, K, M>
+where
+    K: 'a,
+    M: 'a + MapBackend<K, usize>,
+{
+    type Item = (&'a K, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|(key, count)| (key, *count))
+    }
+}
+
+impl<K, M> Default for MultiSet<K, M>
+where
+    M: MapBackend<K, usize>,
+{
+    fn default() -> Self {
+        Self::with_backend()
+    }
+}
+
+impl<K, M> fmt::Debug for MultiSet<K, M>
+where
+    K: fmt::Debug,
+    M: MapBackend<K, usize>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.map.iter()).finish()
+    }
+}
+
+impl<K, M> FromIterator<K> for MultiSet<K, M>
+where
+    M: MapBackend<K, usize>,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+    {
+        let mut set = Self::with_backend();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<K, M> Extend<K> for MultiSet<K, M>
+where
+    M: MapBackend<K, usize>,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = K>,
+    {
+        for key in iter {
+            self.insert(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiSet;
+    use crate::red_black_tree::RedBlackMap;
+    use crate::splay_tree::SplayMap;
+
+    #[test]
+    fn test_len_empty() {
+        let set: MultiSet<u32> = MultiSet::new();
+        assert_eq!(set.len(), 0);
+        assert_eq!(set.key_count(), 0);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set = MultiSet::new();
+        assert_eq!(set.insert(1), 1);
+        assert_eq!(set.insert_many(1, 2), 3);
+        assert_eq!(set.insert_many(2, 0), 0);
+        assert!(!set.contains(&2));
+        assert_eq!(set.insert(2), 1);
+        assert_eq!(set.len(), 4);
+        assert_eq!(set.key_count(), 2);
+
+        assert!(set.remove_one(&1));
+        assert_eq!(set.count(&1), 2);
+        assert_eq!(set.remove_all(&1), 2);
+        assert!(!set.remove_one(&1));
+        assert!(!set.contains(&1));
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.key_count(), 1);
+    }
+
+    #[test]
+    fn test_iter() {
+        let set: MultiSet<u32> = vec![3, 1, 3, 2, 3].into_iter().collect();
+        assert_eq!(set.iter().collect::<Vec<&u32>>(), vec![&1, &2, &3, &3, &3]);
+        assert_eq!(
+            set.iter_counts().collect::<Vec<(&u32, usize)>>(),
+            vec![(&1, 1), (&2, 1), (&3, 3)],
+        );
+        assert_eq!(format!("{:?}", set), "{1: 1, 2: 1, 3: 3}");
+    }
+
+    #[test]
+    fn test_backends() {
+        let mut red_black_set: MultiSet<u32, RedBlackMap<u32, usize>> = MultiSet::with_backend();
+        let mut splay_set: MultiSet<u32, SplayMap<u32, usize>> = MultiSet::with_backend();
+        for key in &[5, 1, 5, 3, 1, 5] {
+            red_black_set.insert(*key);
+            splay_set.insert(*key);
+        }
+        red_black_set.remove_one(&5);
+        splay_set.remove_one(&5);
+        assert!(red_black_set.iter().eq(splay_set.iter()));
+        assert_eq!(
+            red_black_set.iter_counts().collect::<Vec<(&u32, usize)>>(),
+            vec![(&1, 2), (&3, 1), (&5, 2)],
+        );
+    }
+}
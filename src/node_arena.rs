@@ -0,0 +1,251 @@
+use crate::arena::{self, TypedArena};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ops::{Index, IndexMut};
+use core::ptr::{self, NonNull};
+
+const CHUNK_SIZE: usize = 64;
+
+// A handle to a node of a binary search tree. A boxed node is referenced by a pointer whose type
+// is erased so that the links of a node do not depend on its type parameters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NodeRef {
+    Boxed(NonNull<u8>),
+    Pooled(arena::Entry),
+}
+
+// A handle does not give access to a node by itself. Nodes are only reached through the
+// `NodeArena` that owns them, which is `Send` and `Sync` only if the nodes are.
+unsafe impl Send for NodeRef {}
+unsafe impl Sync for NodeRef {}
+
+// The links of a node to its children.
+pub trait Links {
+    fn links_mut(&mut self) -> (&mut Option<NodeRef>, &mut Option<NodeRef>);
+}
+
+// The storage of the nodes of a binary search tree. Boxed nodes are allocated individually, so
+// subtrees can be moved between trees in constant time. Pooled nodes are allocated from a
+// `TypedArena` for better cache locality, so subtrees must be moved one node at a time.
+pub enum NodeArena<N> {
+    Boxed(usize, PhantomData<Box<N>>),
+    Pooled(TypedArena<N>),
+}
+
+impl<N> NodeArena<N> {
+    pub fn boxed() -> Self {
+        NodeArena::Boxed(0, PhantomData)
+    }
+
+    pub fn pooled() -> Self {
+        NodeArena::Pooled(TypedArena::new(CHUNK_SIZE))
+    }
+
+    // Returns an empty storage of the same kind.
+    pub fn empty(&self) -> Self {
+        match self {
+            NodeArena::Boxed(..) => Self::boxed(),
+            NodeArena::Pooled(_) => Self::pooled(),
+        }
+    }
+
+    pub fn allocate(&mut self, node: N) -> NodeRef {
+        match self {
+            NodeArena::Boxed(len, _) => {
+                *len += 1;
+                NodeRef::Boxed(NonNull::from(Box::leak(Box::new(node))).cast())
+            }
+            NodeArena::Pooled(arena) => NodeRef::Pooled(arena.allocate(node)),
+        }
+    }
+
+    pub fn free(&mut self, node: &NodeRef) -> N {
+        match (self, node) {
+            (NodeArena::Boxed(len, _), NodeRef::Boxed(ptr)) => {
+                *len -= 1;
+                *unsafe { Box::from_raw(ptr.cast::<N>().as_ptr()) }
+            }
+            (NodeArena::Pooled(arena), NodeRef::Pooled(entry)) => arena.free(entry),
+            _ => panic!("Error: attempting to free a node of another kind of storage."),
+        }
+    }
+
+    pub fn swap(&mut self, a: &NodeRef, b: &NodeRef) {
+        match (self, a, b) {
+            (NodeArena::Boxed(..), NodeRef::Boxed(a), NodeRef::Boxed(b)) => unsafe {
+                ptr::swap(a.cast::<N>().as_ptr(), b.cast::<N>().as_ptr())
+            },
+            (NodeArena::Pooled(arena), NodeRef::Pooled(a), NodeRef::Pooled(b)) => arena.swap(a, b),
+            _ => panic!("Error: attempting to swap nodes of another kind of storage."),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            NodeArena::Boxed(len, _) => *len,
+            NodeArena::Pooled(arena) => arena.len(),
+        }
+    }
+
+    // Moves the ownership of a subtree of `len` nodes to `new_arena` in constant time if both
+    // storages are boxed. Returns `false` if the nodes have to be moved one at a time instead.
+    pub fn try_move(&mut self, new_arena: &mut Self, len: usize) -> bool {
+        match (self, new_arena) {
+            (NodeArena::Boxed(old_len, _), NodeArena::Boxed(new_len, _)) => {
+                *old_len -= len;
+                *new_len += len;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<N> NodeArena<N>
+where
+    N: Links,
+{
+    // Frees the nodes of a tree before the storage is dropped. The traversal is iterative because
+    // the tree may be arbitrarily deep. Pooled nodes are freed with their arena instead.
+    pub fn release(&mut self, tree: Option<NodeRef>) {
+        if let NodeArena::Pooled(_) = self {
+            return;
+        }
+        let mut stack: Vec<NodeRef> = tree.into_iter().collect();
+        while let Some(node) = stack.pop() {
+            let mut node = self.free(&node);
+            let (left, right) = node.links_mut();
+            stack.extend(left.take());
+            stack.extend(right.take());
+        }
+    }
+
+    // Returns a copy of the storage and of a tree in it. The traversal is iterative because the
+    // tree may be arbitrarily deep.
+    pub fn clone_tree(&self, tree: Option<NodeRef>) -> (Self, Option<NodeRef>)
+    where
+        N: Clone,
+    {
+        let mut ret = match self {
+            NodeArena::Boxed(..) => Self::boxed(),
+            NodeArena::Pooled(arena) => return (NodeArena::Pooled(arena.clone()), tree),
+        };
+        let tree = tree.map(|node| ret.allocate(self[node].clone()));
+        let mut stack: Vec<NodeRef> = tree.into_iter().collect();
+        while let Some(node) = stack.pop() {
+            // the links of a copied node still refer to the children of the original node
+            let (left, right) = {
+                let (left, right) = ret[node].links_mut();
+                (*left, *right)
+            };
+            let left = left.map(|child| ret.allocate(self[child].clone()));
+            let right = right.map(|child| ret.allocate(self[child].clone()));
+            let links = ret[node].links_mut();
+            *links.0 = left;
+            *links.1 = right;
+            stack.extend(left);
+            stack.extend(right);
+        }
+        (ret, tree)
+    }
+}
+
+impl<N> Index<NodeRef> for NodeArena<N> {
+    type Output = N;
+
+    fn index(&self, node: NodeRef) -> &N {
+        match (self, node) {
+            (NodeArena::Boxed(..), NodeRef::Boxed(ptr)) => unsafe { &*ptr.cast::<N>().as_ptr() },
+            (NodeArena::Pooled(arena), NodeRef::Pooled(entry)) => &arena[entry],
+            _ => panic!("Error: attempting to index a node of another kind of storage."),
+        }
+    }
+}
+
+impl<N> IndexMut<NodeRef> for NodeArena<N> {
+    fn index_mut(&mut self, node: NodeRef) -> &mut N {
+        match (self, node) {
+            (NodeArena::Boxed(..), NodeRef::Boxed(ptr)) => unsafe {
+                &mut *ptr.cast::<N>().as_ptr()
+            },
+            (NodeArena::Pooled(arena), NodeRef::Pooled(entry)) => &mut arena[entry],
+            _ => panic!("Error: attempting to index a node of another kind of storage."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Links, NodeArena, NodeRef};
+
+    #[derive(Clone)]
+    struct Node {
+        value: u32,
+        left: Option<NodeRef>,
+        right: Option<NodeRef>,
+    }
+
+    impl Links for Node {
+        fn links_mut(&mut self) -> (&mut Option<NodeRef>, &mut Option<NodeRef>) {
+            (&mut self.left, &mut self.right)
+        }
+    }
+
+    fn node(value: u32, left: Option<NodeRef>, right: Option<NodeRef>) -> Node {
+        Node { value, left, right }
+    }
+
+    fn values(arena: &NodeArena<Node>, tree: Option<NodeRef>) -> Vec<u32> {
+        match tree {
+            None => Vec::new(),
+            Some(node) => {
+                let mut ret = values(arena, arena[node].left);
+                ret.push(arena[node].value);
+                ret.extend(values(arena, arena[node].right));
+                ret
+            }
+        }
+    }
+
+    fn check_clone_release(mut arena: NodeArena<Node>) {
+        let left = arena.allocate(node(0, None, None));
+        let right = arena.allocate(node(2, None, None));
+        let tree = Some(arena.allocate(node(1, Some(left), Some(right))));
+        arena.swap(&left, &right);
+        arena.swap(&left, &right);
+
+        let (mut clone, clone_tree) = arena.clone_tree(tree);
+        assert_eq!(clone.len(), 3);
+        assert_eq!(values(&clone, clone_tree), vec![0, 1, 2]);
+        if let NodeArena::Boxed(..) = clone {
+            clone[clone_tree.expect("Expected a non-empty tree.")].value = 3;
+            assert_eq!(values(&arena, tree), vec![0, 1, 2]);
+        }
+
+        arena.release(tree);
+        clone.release(clone_tree);
+    }
+
+    #[test]
+    fn test_boxed() {
+        check_clone_release(NodeArena::boxed());
+    }
+
+    #[test]
+    fn test_pooled() {
+        check_clone_release(NodeArena::pooled());
+    }
+
+    #[test]
+    fn test_try_move() {
+        let mut arena = NodeArena::boxed();
+        let mut new_arena = NodeArena::boxed();
+        let tree = Some(arena.allocate(node(0, None, None)));
+        assert!(arena.try_move(&mut new_arena, 1));
+        assert_eq!(arena.len(), 0);
+        assert_eq!(new_arena.len(), 1);
+        assert!(!new_arena.try_move(&mut NodeArena::pooled(), 1));
+        new_arena.release(tree);
+    }
+}
@@ -0,0 +1,251 @@
+//! Traits for ordered maps and sets.
+//!
+//! `OrderedMap` and `OrderedSet` are implemented by the ordered maps and sets in `avl_tree`,
+//! `red_black_tree`, `skiplist`, `splay_tree`, and `treap`, so code that only needs the common
+//! operations of an ordered collection can be written once and used with any of them.
+
+/// An ordered map of keys to values.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::avl_tree::AvlMap;
+/// use extended_collections::ordered::OrderedMap;
+/// use extended_collections::skiplist::SkipMap;
+///
+/// fn count_words<M>(map: &mut M, text: &str)
+/// where
+///     M: OrderedMap<String, u32>,
+/// {
+///     for word in text.split_whitespace() {
+///         match map.get_mut(&String::from(word)) {
+///             Some(count) => *count += 1,
+///             None => {
+///                 map.insert(String::from(word), 1);
+///             }
+///         }
+///     }
+/// }
+///
+/// let mut avl_map = AvlMap::new();
+/// count_words(&mut avl_map, "b a b");
+/// let mut skip_map = SkipMap::new();
+/// count_words(&mut skip_map, "b a b");
+///
+/// assert!(OrderedMap::iter(&avl_map).eq(OrderedMap::iter(&skip_map)));
+/// assert_eq!(OrderedMap::min(&avl_map), Some(&String::from("a")));
+/// ```
+pub trait OrderedMap<K, V> {
+    /// An iterator over the key-value pairs of the map in ascending order of keys.
+    type Iter<'a>: Iterator<Item = (&'a K, &'a V)>
+    where
+        Self: 'a,
+        K: 'a,
+        V: 'a;
+
+    /// Inserts a key-value pair into the map. If the key already exists in the map, it will
+    /// return and replace the old key-value pair.
+    fn insert(&mut self, key: K, value: V) -> Option<(K, V)>;
+
+    /// Removes a key-value pair from the map. If the key exists in the map, it will return the
+    /// associated key-value pair. Otherwise it will return `None`.
+    fn remove(&mut self, key: &K) -> Option<(K, V)>;
+
+    /// Checks if a key exists in the map.
+    fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns an immutable reference to the value associated with a particular key. It will
+    /// return `None` if the key does not exist in the map.
+    fn get(&self, key: &K) -> Option<&V>;
+
+    /// Returns a mutable reference to the value associated with a particular key. Returns `None`
+    /// if such a key does not exist.
+    fn get_mut(&mut self, key: &K) -> Option<&mut V>;
+
+    /// Returns the number of elements in the map.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the map is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a key in the map that is less than or equal to a particular key. Returns `None` if
+    /// such a key does not exist.
+    fn floor(&self, key: &K) -> Option<&K>;
+
+    /// Returns a key in the map that is greater than or equal to a particular key. Returns `None`
+    /// if such a key does not exist.
+    fn ceil(&self, key: &K) -> Option<&K>;
+
+    /// Returns the minimum key of the map. Returns `None` if the map is empty.
+    fn min(&self) -> Option<&K>;
+
+    /// Returns the maximum key of the map. Returns `None` if the map is empty.
+    fn max(&self) -> Option<&K>;
+
+    /// Returns an iterator over the map. The iterator will yield key-value pairs in ascending
+    /// order of keys.
+    fn iter(&self) -> Self::Iter<'_>;
+}
+
+/// An ordered set of keys.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::ordered::OrderedSet;
+/// use extended_collections::red_black_tree::RedBlackSet;
+/// use extended_collections::treap::TreapSet;
+///
+/// fn insert_all<S>(set: &mut S, keys: &[u32])
+/// where
+///     S: OrderedSet<u32>,
+/// {
+///     for key in keys {
+///         set.insert(*key);
+///     }
+/// }
+///
+/// let mut red_black_set = RedBlackSet::new();
+/// insert_all(&mut red_black_set, &[3, 1, 2]);
+/// let mut treap_set = TreapSet::new();
+/// insert_all(&mut treap_set, &[3, 1, 2]);
+///
+/// assert!(OrderedSet::iter(&red_black_set).eq(OrderedSet::iter(&treap_set)));
+/// assert_eq!(OrderedSet::max(&treap_set), Some(&3));
+/// ```
+pub trait OrderedSet<T> {
+    /// An iterator over the keys of the set in ascending order.
+    type Iter<'a>: Iterator<Item = &'a T>
+    where
+        Self: 'a,
+        T: 'a;
+
+    /// Inserts a key into the set. If the key already exists in the set, it will return and
+    /// replace the key.
+    fn insert(&mut self, key: T) -> Option<T>;
+
+    /// Removes a key from the set. If the key exists in the set, it will return the associated
+    /// key. Otherwise it will return `None`.
+    fn remove(&mut self, key: &T) -> Option<T>;
+
+    /// Checks if a key exists in the set.
+    fn contains(&self, key: &T) -> bool;
+
+    /// Returns the number of elements in the set.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the set is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a key in the set that is less than or equal to a particular key. Returns `None` if
+    /// such a key does not exist.
+    fn floor(&self, key: &T) -> Option<&T>;
+
+    /// Returns a key in the set that is greater than or equal to a particular key. Returns `None`
+    /// if such a key does not exist.
+    fn ceil(&self, key: &T) -> Option<&T>;
+
+    /// Returns the minimum key of the set. Returns `None` if the set is empty.
+    fn min(&self) -> Option<&T>;
+
+    /// Returns the maximum key of the set. Returns `None` if the set is empty.
+    fn max(&self) -> Option<&T>;
+
+    /// Returns an iterator over the set. The iterator will yield keys in ascending order.
+    fn iter(&self) -> Self::Iter<'_>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OrderedMap, OrderedSet};
+    use crate::avl_tree::{AvlMap, AvlSet};
+    use crate::red_black_tree::{RedBlackMap, RedBlackSet};
+    use crate::skiplist::{SkipMap, SkipSet};
+    use crate::splay_tree::{SplayMap, SplaySet};
+    use crate::treap::{TreapMap, TreapSet};
+
+    fn check_map<M>(mut map: M)
+    where
+        M: OrderedMap<u32, u32>,
+    {
+        assert!(map.is_empty());
+        assert_eq!(map.min(), None);
+        assert_eq!(map.max(), None);
+        for key in 0..100 {
+            assert_eq!(map.insert(key * 37 % 100 * 2, key), None);
+        }
+        assert_eq!(map.insert(0, 1), Some((0, 0)));
+        assert_eq!(map.len(), 100);
+
+        *map.get_mut(&0).unwrap() += 1;
+        assert_eq!(map.get(&0), Some(&2));
+        assert!(map.contains_key(&2));
+        assert!(!map.contains_key(&1));
+        assert_eq!(map.floor(&5), Some(&4));
+        assert_eq!(map.ceil(&5), Some(&6));
+        assert_eq!(map.ceil(&199), None);
+        assert_eq!(map.min(), Some(&0));
+        assert_eq!(map.max(), Some(&198));
+        assert!(map
+            .iter()
+            .map(|(key, _)| *key)
+            .eq((0..100).map(|key| key * 2)));
+
+        assert_eq!(map.remove(&0), Some((0, 2)));
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 99);
+        assert!(!map.is_empty());
+    }
+
+    fn check_set<S>(mut set: S)
+    where
+        S: OrderedSet<u32>,
+    {
+        assert!(set.is_empty());
+        assert_eq!(set.min(), None);
+        assert_eq!(set.max(), None);
+        for key in 0..100 {
+            assert_eq!(set.insert(key * 37 % 100 * 2), None);
+        }
+        assert_eq!(set.insert(0), Some(0));
+        assert_eq!(set.len(), 100);
+
+        assert!(set.contains(&2));
+        assert!(!set.contains(&1));
+        assert_eq!(set.floor(&5), Some(&4));
+        assert_eq!(set.ceil(&5), Some(&6));
+        assert_eq!(set.ceil(&199), None);
+        assert_eq!(set.min(), Some(&0));
+        assert_eq!(set.max(), Some(&198));
+        assert!(set.iter().cloned().eq((0..100).map(|key| key * 2)));
+
+        assert_eq!(set.remove(&0), Some(0));
+        assert_eq!(set.remove(&0), None);
+        assert_eq!(set.len(), 99);
+        assert!(!set.is_empty());
+    }
+
+    #[test]
+    fn test_ordered_map() {
+        check_map(AvlMap::new());
+        check_map(RedBlackMap::new());
+        check_map(SkipMap::new());
+        check_map(SplayMap::new());
+        check_map(TreapMap::new());
+    }
+
+    #[test]
+    fn test_ordered_set() {
+        check_set(AvlSet::new());
+        check_set(RedBlackSet::new());
+        check_set(SkipSet::new());
+        check_set(SplaySet::new());
+        check_set(TreapSet::new());
+    }
+}
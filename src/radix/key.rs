@@ -0,0 +1,200 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A type that can be used as the key of a radix tree.
+///
+/// A radix tree stores the byte encoding of its keys, so a key must be able to be reconstructed
+/// from the bytes produced by its `AsBytes` implementation. The encoding must preserve the order
+/// of the keys for the radix tree to iterate over keys in ascending order.
+pub trait RadixKey: Sized {
+    /// Reconstructs a key from its byte encoding.
+    fn from_bytes(bytes: Vec<u8>) -> Self;
+}
+
+/// A type that can be encoded as the bytes of a `RadixKey`.
+///
+/// Both owned keys and their borrowed forms implement `AsBytes`, which allows a radix tree with
+/// `String` keys to be queried with a `&str` for example.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::radix::{AsBytes, RadixKey, RadixMap};
+/// use std::borrow::Cow;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Ip([u8; 4]);
+///
+/// impl RadixKey for Ip {
+///     fn from_bytes(bytes: Vec<u8>) -> Self {
+///         Ip([bytes[0], bytes[1], bytes[2], bytes[3]])
+///     }
+/// }
+///
+/// impl AsBytes for Ip {
+///     type Key = Ip;
+///
+///     fn to_bytes(&self) -> Cow<'_, [u8]> {
+///         Cow::Borrowed(&self.0)
+///     }
+/// }
+///
+/// let mut map: RadixMap<u32, Ip> = RadixMap::default();
+/// map.insert(&Ip([10, 0, 0, 1]), 1);
+/// assert_eq!(map.min(), Some(Ip([10, 0, 0, 1])));
+/// ```
+pub trait AsBytes {
+    /// The type of the key that is encoded.
+    type Key: RadixKey;
+
+    /// Returns the byte encoding of the key.
+    fn to_bytes(&self) -> Cow<'_, [u8]>;
+}
+
+impl<Q> AsBytes for &Q
+where
+    Q: AsBytes + ?Sized,
+{
+    type Key = Q::Key;
+
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        (**self).to_bytes()
+    }
+}
+
+impl RadixKey for Vec<u8> {
+    fn from_bytes(bytes: Vec<u8>) -> Self {
+        bytes
+    }
+}
+
+impl AsBytes for Vec<u8> {
+    type Key = Vec<u8>;
+
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl AsBytes for [u8] {
+    type Key = Vec<u8>;
+
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<const N: usize> AsBytes for [u8; N] {
+    type Key = Vec<u8>;
+
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl RadixKey for String {
+    fn from_bytes(bytes: Vec<u8>) -> Self {
+        String::from_utf8(bytes).expect("Expected key to be valid UTF-8.")
+    }
+}
+
+impl AsBytes for String {
+    type Key = String;
+
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+}
+
+impl AsBytes for str {
+    type Key = String;
+
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+}
+
+// Unsigned integers are encoded in big-endian so that the byte order matches the numeric order.
+macro_rules! unsigned_radix_key {
+    ($($type_name:ty)*) => {
+        $(
+            impl RadixKey for $type_name {
+                fn from_bytes(bytes: Vec<u8>) -> Self {
+                    let mut buf = [0; core::mem::size_of::<$type_name>()];
+                    buf.copy_from_slice(&bytes);
+                    <$type_name>::from_be_bytes(buf)
+                }
+            }
+
+            impl AsBytes for $type_name {
+                type Key = $type_name;
+
+                fn to_bytes(&self) -> Cow<'_, [u8]> {
+                    Cow::Owned(self.to_be_bytes().to_vec())
+                }
+            }
+        )*
+    }
+}
+
+// Signed integers additionally have their sign bit flipped so that negative integers are ordered
+// before positive integers.
+macro_rules! signed_radix_key {
+    ($($type_name:ty, $unsigned_type_name:ty)*) => {
+        $(
+            impl RadixKey for $type_name {
+                fn from_bytes(bytes: Vec<u8>) -> Self {
+                    (<$unsigned_type_name>::from_bytes(bytes) ^ (1 << (<$type_name>::BITS - 1)))
+                        as $type_name
+                }
+            }
+
+            impl AsBytes for $type_name {
+                type Key = $type_name;
+
+                fn to_bytes(&self) -> Cow<'_, [u8]> {
+                    let key = *self as $unsigned_type_name ^ (1 << (<$type_name>::BITS - 1));
+                    Cow::Owned(key.to_be_bytes().to_vec())
+                }
+            }
+        )*
+    }
+}
+
+unsigned_radix_key!(u8 u16 u32 u64 u128 usize);
+signed_radix_key!(i8, u8 i16, u16 i32, u32 i64, u64 i128, u128 isize, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::{AsBytes, RadixKey};
+
+    fn roundtrip<T>(key: T) -> T
+    where
+        T: RadixKey + AsBytes,
+    {
+        T::from_bytes(key.to_bytes().into_owned())
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        assert_eq!(roundtrip(vec![1u8, 2, 3]), vec![1, 2, 3]);
+        assert_eq!(roundtrip(String::from("foo")), "foo");
+        assert_eq!(roundtrip(0xDEAD_BEEFu32), 0xDEAD_BEEF);
+        assert_eq!(roundtrip(-5i64), -5);
+        assert_eq!(roundtrip(i8::MIN), i8::MIN);
+    }
+
+    #[test]
+    fn test_integer_order() {
+        let values = [i32::MIN, -256, -1, 0, 1, 255, 256, i32::MAX];
+        for pair in values.windows(2) {
+            assert!(pair[0].to_bytes() < pair[1].to_bytes());
+        }
+
+        let values = [0u16, 1, 255, 256, u16::MAX];
+        for pair in values.windows(2) {
+            assert!(pair[0].to_bytes() < pair[1].to_bytes());
+        }
+    }
+}
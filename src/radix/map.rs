@@ -1,6 +1,14 @@
+use crate::radix::key::{AsBytes, RadixKey};
 use crate::radix::node::Node;
 use crate::radix::tree;
-use std::ops::{Index, IndexMut};
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::marker::PhantomData;
+use core::mem;
+use core::ops::{Index, IndexMut};
 
 /// An ordered map implemented using a radix tree.
 ///
@@ -12,6 +20,10 @@ use std::ops::{Index, IndexMut};
 /// children should be represented as a fixed length array of size 256 if the tree is dense for
 /// performance.
 ///
+/// The keys of the map are byte vectors by default, but any type that implements `RadixKey` can be
+/// used as a key. The map stores the byte encoding of the keys and decodes the keys when they are
+/// returned.
+///
 /// # Examples
 ///
 /// ```
@@ -37,10 +49,16 @@ use std::ops::{Index, IndexMut};
 ///     map.remove(b"foo"),
 ///     Some((String::from("foo").into_bytes(), 2)),
 /// );
+///
+/// let mut map: RadixMap<u32, String> = RadixMap::default();
+/// map.insert("foo", 0);
+/// map.insert(&String::from("bar"), 1);
+/// assert_eq!(map.min(), Some(String::from("bar")));
 /// ```
-pub struct RadixMap<T> {
+pub struct RadixMap<T, K = Vec<u8>> {
     root: tree::Tree<T>,
     len: usize,
+    _marker: PhantomData<K>,
 }
 
 impl<T> RadixMap<T> {
@@ -54,12 +72,14 @@ impl<T> RadixMap<T> {
     /// let map: RadixMap<u32> = RadixMap::new();
     /// ```
     pub fn new() -> Self {
-        RadixMap {
-            root: Some(Box::new(Node::new(Vec::new(), None))),
-            len: 0,
-        }
+        Self::default()
     }
+}
 
+impl<T, K> RadixMap<T, K>
+where
+    K: RadixKey,
+{
     /// Inserts a key-value pair into the map. If the key already exists in the map, it will return
     /// and replace the old key-value pair.
     ///
@@ -77,9 +97,14 @@ impl<T> RadixMap<T> {
     /// );
     /// assert_eq!(map.get(b"foo"), Some(&2));
     /// ```
-    pub fn insert(&mut self, key: &[u8], value: T) -> Option<(Vec<u8>, T)> {
+    pub fn insert<Q>(&mut self, key: &Q, value: T) -> Option<(K, T)>
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        let key = key.to_bytes();
         self.len += 1;
-        let ret = tree::insert(&mut self.root, key, value).map(|value| (key.to_vec(), value));
+        let ret = tree::insert(&mut self.root, &key, value)
+            .map(|value| (K::from_bytes(key.into_owned()), value));
         if ret.is_some() {
             self.len -= 1;
         }
@@ -102,10 +127,13 @@ impl<T> RadixMap<T> {
     /// );
     /// assert_eq!(map.remove(b"foobar"), None);
     /// ```
-    pub fn remove(&mut self, key: &[u8]) -> Option<(Vec<u8>, T)> {
-        tree::remove(&mut self.root, key, 0).and_then(|value| {
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<(K, T)>
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        tree::remove(&mut self.root, &key.to_bytes(), 0).and_then(|(key, value)| {
             self.len -= 1;
-            Some(value)
+            Some((K::from_bytes(key), value))
         })
     }
 
@@ -121,7 +149,10 @@ impl<T> RadixMap<T> {
     /// assert!(map.contains_key(b"foo"));
     /// assert!(!map.contains_key(b"foobar"));
     /// ```
-    pub fn contains_key(&self, key: &[u8]) -> bool {
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
         self.get(key).is_some()
     }
 
@@ -138,8 +169,11 @@ impl<T> RadixMap<T> {
     /// assert_eq!(map.get(b"foobar"), None);
     /// assert_eq!(map.get(b"foo"), Some(&1));
     /// ```
-    pub fn get(&self, key: &[u8]) -> Option<&T> {
-        tree::get(&self.root, key, 0)
+    pub fn get<Q>(&self, key: &Q) -> Option<&T>
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        tree::get(&self.root, &key.to_bytes(), 0)
     }
 
     /// Returns a mutable reference to the value associated with a particular key. Returns `None`
@@ -155,8 +189,11 @@ impl<T> RadixMap<T> {
     /// *map.get_mut(b"foo").unwrap() = 2;
     /// assert_eq!(map.get(b"foo"), Some(&2));
     /// ```
-    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut T> {
-        tree::get_mut(&mut self.root, key, 0)
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut T>
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        tree::get_mut(&mut self.root, &key.to_bytes(), 0)
     }
 
     /// Returns the number of elements in the map.
@@ -206,6 +243,59 @@ impl<T> RadixMap<T> {
         self.len = 0;
     }
 
+    /// Retains only the key-value pairs that satisfy a predicate. The key-value pairs are visited
+    /// in lexographic order of their keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixMap;
+    ///
+    /// let mut map = RadixMap::new();
+    /// map.insert(b"foo", 1);
+    /// map.insert(b"foobar", 2);
+    /// map.insert(b"bar", 3);
+    ///
+    /// map.retain(|key, value| {
+    ///     *value += 1;
+    ///     key.starts_with(b"foo")
+    /// });
+    /// assert_eq!(
+    ///     map.iter().collect::<Vec<(Vec<u8>, &u32)>>(),
+    ///     vec![(b"foo".to_vec(), &2), (b"foobar".to_vec(), &3)],
+    /// );
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut T) -> bool,
+    {
+        self.len -= tree::retain(&mut self.root, &mut |key: &[u8], value: &mut T| {
+            f(&K::from_bytes(key.to_vec()), value)
+        });
+    }
+
+    /// Clears the map, returning all key-value pairs as an iterator. The iterator yields the
+    /// key-value pairs in lexographic order of their keys, and the map can be reused afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixMap;
+    ///
+    /// let mut map = RadixMap::new();
+    /// map.insert(b"foo", 1);
+    /// map.insert(b"bar", 2);
+    ///
+    /// assert_eq!(
+    ///     map.drain().collect::<Vec<(Vec<u8>, u32)>>(),
+    ///     vec![(b"bar".to_vec(), 2), (b"foo".to_vec(), 1)],
+    /// );
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> RadixMapIntoIter<T, K> {
+        mem::take(self).into_iter()
+    }
+
     /// Returns all keys that share the longest common prefix with the specified key.
     ///
     /// # Examples
@@ -219,10 +309,173 @@ impl<T> RadixMap<T> {
     ///
     /// assert_eq!(map.get_longest_prefix(b"foob"), vec![b"foobar"]);
     /// ```
-    pub fn get_longest_prefix(&self, key: &[u8]) -> Vec<Vec<u8>> {
+    pub fn get_longest_prefix<Q>(&self, key: &Q) -> Vec<K>
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
         let mut keys = Vec::new();
-        tree::get_longest_prefix(&self.root, key, 0, Vec::new(), &mut keys);
-        keys
+        tree::get_longest_prefix(&self.root, &key.to_bytes(), 0, Vec::new(), &mut keys);
+        keys.into_iter().map(K::from_bytes).collect()
+    }
+
+    /// Returns an iterator over all key-value pairs whose keys start with a particular prefix. The
+    /// iterator will yield key-value pairs in lexographic order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixMap;
+    ///
+    /// let mut map = RadixMap::new();
+    /// map.insert(b"foo", 0);
+    /// map.insert(b"foobar", 1);
+    /// map.insert(b"bar", 2);
+    ///
+    /// let mut iterator = map.iter_prefix(b"fo");
+    /// assert_eq!(
+    ///     iterator.next(),
+    ///     Some((String::from("foo").into_bytes(), &0)),
+    /// );
+    /// assert_eq!(
+    ///     iterator.next(),
+    ///     Some((String::from("foobar").into_bytes(), &1)),
+    /// );
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter_prefix<Q>(&self, prefix: &Q) -> RadixMapPrefixIter<'_, T, K>
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        let stack = match tree::get_prefix_node(&self.root, &prefix.to_bytes()) {
+            Some((node, mut key)) => {
+                let prefix_len = key.len() - node.key.len();
+                key.truncate(prefix_len);
+                return RadixMapPrefixIter {
+                    map_iter: RadixMapIter {
+                        bytes_iter: RadixMapBytesIter {
+                            prefix: key,
+                            stack: vec![(node, prefix_len)],
+                        },
+                        _marker: PhantomData,
+                    },
+                };
+            }
+            None => Vec::new(),
+        };
+        RadixMapPrefixIter {
+            map_iter: RadixMapIter {
+                bytes_iter: RadixMapBytesIter {
+                    prefix: Vec::new(),
+                    stack,
+                },
+                _marker: PhantomData,
+            },
+        }
+    }
+
+    /// Removes all key-value pairs whose keys start with a particular prefix and returns them as a
+    /// new map. The subtree that contains the keys is detached from the map as a whole rather than
+    /// removing the keys one at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixMap;
+    ///
+    /// let mut map = RadixMap::new();
+    /// map.insert(b"foo", 0);
+    /// map.insert(b"foobar", 1);
+    /// map.insert(b"bar", 2);
+    ///
+    /// let removed = map.remove_prefix(b"fo");
+    /// assert_eq!(
+    ///     removed.iter().collect::<Vec<(Vec<u8>, &u32)>>(),
+    ///     vec![(b"foo".to_vec(), &0), (b"foobar".to_vec(), &1)],
+    /// );
+    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(map.get(b"foo"), None);
+    /// ```
+    pub fn remove_prefix<Q>(&mut self, prefix: &Q) -> Self
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        self.detach(|root| tree::remove_prefix(root, &prefix.to_bytes()))
+    }
+
+    /// Splits the map and returns the right part of the map. If `inclusive` is true, then the map
+    /// will retain the given key if it exists. Otherwise, the right part of the map will contain
+    /// the key if it exists. The keys are compared in lexographic order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixMap;
+    ///
+    /// let mut map = RadixMap::new();
+    /// map.insert(b"a", 0);
+    /// map.insert(b"ab", 1);
+    /// map.insert(b"b", 2);
+    ///
+    /// let split = map.split_off(b"ab", true);
+    /// assert_eq!(map.get(b"a"), Some(&0));
+    /// assert_eq!(map.get(b"ab"), Some(&1));
+    /// assert_eq!(split.get(b"b"), Some(&2));
+    /// ```
+    pub fn split_off<Q>(&mut self, key: &Q, inclusive: bool) -> Self
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        self.detach(|root| tree::split_off(root, &key.to_bytes(), inclusive))
+    }
+
+    // Builds a new map from the subtree that `f` detaches from the tree of this map.
+    fn detach<F>(&mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut tree::Tree<T>) -> tree::Tree<T>,
+    {
+        match f(&mut self.root) {
+            Some(root) => {
+                let len = tree::size(&root);
+                self.len -= len;
+                RadixMap {
+                    root: Some(root),
+                    len,
+                    _marker: PhantomData,
+                }
+            }
+            None => RadixMap::default(),
+        }
+    }
+
+    /// Returns the key-value pair with the longest key that is a prefix of a particular key.
+    /// Returns `None` if no key in the map is a prefix of the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixMap;
+    ///
+    /// let mut map = RadixMap::new();
+    /// map.insert(b"10.0", 0);
+    /// map.insert(b"10.0.1", 1);
+    ///
+    /// assert_eq!(
+    ///     map.longest_prefix_match(b"10.0.2.1"),
+    ///     Some((String::from("10.0").into_bytes(), &0)),
+    /// );
+    /// assert_eq!(
+    ///     map.longest_prefix_match(b"10.0.1.1"),
+    ///     Some((String::from("10.0.1").into_bytes(), &1)),
+    /// );
+    /// assert_eq!(map.longest_prefix_match(b"192.168"), None);
+    /// ```
+    pub fn longest_prefix_match<Q>(&self, key: &Q) -> Option<(K, &T)>
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        let key = key.to_bytes();
+        tree::longest_prefix_match(&self.root, &key)
+            .map(|(len, value)| (K::from_bytes(key[..len].to_vec()), value))
     }
 
     /// Returns the minimum lexographic key of the map. Returns `None` if the map is empty.
@@ -237,8 +490,8 @@ impl<T> RadixMap<T> {
     /// map.insert(b"foobar", 3);
     /// assert_eq!(map.min(), Some(String::from("foo").into_bytes()));
     /// ```
-    pub fn min(&self) -> Option<Vec<u8>> {
-        tree::min(&self.root, Vec::new())
+    pub fn min(&self) -> Option<K> {
+        tree::min(&self.root, Vec::new()).map(K::from_bytes)
     }
 
     /// Returns the maximum lexographic key of the map. Returns `None` if the map is empty.
@@ -253,8 +506,8 @@ impl<T> RadixMap<T> {
     /// map.insert(b"foobar", 3);
     /// assert_eq!(map.max(), Some(String::from("foobar").into_bytes()));
     /// ```
-    pub fn max(&self) -> Option<Vec<u8>> {
-        tree::max(&self.root, Vec::new())
+    pub fn max(&self) -> Option<K> {
+        tree::max(&self.root, Vec::new()).map(K::from_bytes)
     }
 
     /// Returns an iterator over the map. The iterator will yield key-value pairs in lexographic
@@ -280,11 +533,36 @@ impl<T> RadixMap<T> {
     /// );
     /// assert_eq!(iterator.next(), None);
     /// ```
-    pub fn iter(&self) -> RadixMapIter<'_, T> {
+    pub fn iter(&self) -> RadixMapIter<'_, T, K> {
         RadixMapIter {
+            bytes_iter: self.iter_bytes(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the map that yields the byte encodings of the keys without
+    /// decoding or allocating them. The iterator reuses a single key buffer, so each key only
+    /// lives until the next call to `next_entry`. The key-value pairs are yielded in lexographic
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixMap;
+    ///
+    /// let mut map = RadixMap::new();
+    /// map.insert(b"foo", 1);
+    /// map.insert(b"foobar", 2);
+    ///
+    /// let mut iterator = map.iter_bytes();
+    /// assert_eq!(iterator.next_entry(), Some((&b"foo"[..], &1)));
+    /// assert_eq!(iterator.next_entry(), Some((&b"foobar"[..], &2)));
+    /// assert_eq!(iterator.next_entry(), None);
+    /// ```
+    pub fn iter_bytes(&self) -> RadixMapBytesIter<'_, T> {
+        RadixMapBytesIter {
             prefix: Vec::new(),
-            current: &self.root,
-            stack: Vec::new(),
+            stack: self.root.iter().map(|node| (&**node, 0)).collect(),
         }
     }
 
@@ -315,201 +593,398 @@ impl<T> RadixMap<T> {
     /// );
     /// assert_eq!(iterator.next(), None);
     /// ```
-    pub fn iter_mut(&mut self) -> RadixMapIterMut<'_, T> {
+    pub fn iter_mut(&mut self) -> RadixMapIterMut<'_, T, K> {
         RadixMapIterMut {
+            bytes_iter: self.iter_bytes_mut(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a mutable iterator over the map that yields the byte encodings of the keys without
+    /// decoding or allocating them. The iterator reuses a single key buffer, so each key only
+    /// lives until the next call to `next_entry`. The key-value pairs are yielded in lexographic
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixMap;
+    ///
+    /// let mut map = RadixMap::new();
+    /// map.insert(b"foo", 1);
+    /// map.insert(b"foobar", 2);
+    ///
+    /// let mut iterator = map.iter_bytes_mut();
+    /// while let Some((key, value)) = iterator.next_entry() {
+    ///     *value += key.len() as u32;
+    /// }
+    /// assert_eq!(map.get(b"foo"), Some(&4));
+    /// assert_eq!(map.get(b"foobar"), Some(&8));
+    /// ```
+    pub fn iter_bytes_mut(&mut self) -> RadixMapBytesIterMut<'_, T> {
+        RadixMapBytesIterMut {
             prefix: Vec::new(),
-            current: self.root.as_mut().map(|node| &mut **node),
-            stack: Vec::new(),
+            stack: self.root.iter_mut().map(|node| (&mut **node, 0)).collect(),
         }
     }
 }
 
-impl<T> IntoIterator for RadixMap<T> {
-    type IntoIter = RadixMapIntoIter<T>;
-    type Item = (Vec<u8>, T);
+impl<T, K> IntoIterator for RadixMap<T, K>
+where
+    K: RadixKey,
+{
+    type IntoIter = RadixMapIntoIter<T, K>;
+    type Item = (K, T);
 
     fn into_iter(self) -> Self::IntoIter {
         Self::IntoIter {
             prefix: Vec::new(),
-            current: self.root,
-            stack: Vec::new(),
+            stack: self.root.into_iter().map(|node| (node, 0)).collect(),
+            _marker: PhantomData,
         }
     }
 }
 
-impl<'a, T> IntoIterator for &'a RadixMap<T>
+impl<'a, T, K> IntoIterator for &'a RadixMap<T, K>
 where
     T: 'a,
+    K: RadixKey,
 {
-    type IntoIter = RadixMapIter<'a, T>;
-    type Item = (Vec<u8>, &'a T);
+    type IntoIter = RadixMapIter<'a, T, K>;
+    type Item = (K, &'a T);
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
-impl<'a, T> IntoIterator for &'a mut RadixMap<T>
+impl<'a, T, K> IntoIterator for &'a mut RadixMap<T, K>
 where
     T: 'a,
+    K: RadixKey,
 {
-    type IntoIter = RadixMapIterMut<'a, T>;
-    type Item = (Vec<u8>, &'a mut T);
+    type IntoIter = RadixMapIterMut<'a, T, K>;
+    type Item = (K, &'a mut T);
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter_mut()
     }
 }
 
-/// An owning iterator for `RadixMap<T>`.
+/// An owning iterator for `RadixMap<T, K>`.
 ///
 /// This iterator traverse the elements of the map in lexographic order and yields owned entries.
-pub struct RadixMapIntoIter<T> {
+pub struct RadixMapIntoIter<T, K> {
     prefix: Vec<u8>,
-    current: tree::Tree<T>,
-    stack: Vec<(tree::Tree<T>, usize)>,
+    stack: Vec<(Box<Node<T>>, usize)>,
+    _marker: PhantomData<K>,
 }
 
-impl<T> Iterator for RadixMapIntoIter<T> {
-    type Item = (Vec<u8>, T);
+impl<T, K> Iterator for RadixMapIntoIter<T, K>
+where
+    K: RadixKey,
+{
+    type Item = (K, T);
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            while let Some(node) = self.current.take() {
-                let unboxed_node = *node;
-                let Node {
-                    mut key,
-                    value,
-                    next,
-                    mut child,
-                } = unboxed_node;
-                let key_len = key.len();
-                self.prefix.append(&mut key);
-                self.current = child.take();
-                self.stack.push((next, key_len));
-                if let Some(value) = value {
-                    return Some((self.prefix.clone(), value));
-                }
-            }
-            match self.stack.pop() {
-                Some((next_tree, key_len)) => {
-                    let new_len = self.prefix.len() - key_len;
-                    self.prefix.split_off(new_len);
-                    self.current = next_tree;
-                }
-                None => return None,
+        while let Some((node, prefix_len)) = self.stack.pop() {
+            let Node {
+                key,
+                value,
+                children,
+            } = *node;
+            self.prefix.truncate(prefix_len);
+            self.prefix.extend_from_slice(key.as_slice());
+            let len = self.prefix.len();
+            self.stack.extend(
+                children
+                    .into_vec()
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, len)),
+            );
+            if let Some(value) = value {
+                return Some((K::from_bytes(self.prefix.clone()), value));
             }
         }
+        None
     }
 }
 
-/// An iterator for `RadixMap<T>`.
+/// An iterator for `RadixMap<T, K>`.
 ///
 /// This iterator traverse the elements of the map in lexographic order and yields immutable
 /// references.
-pub struct RadixMapIter<'a, T> {
-    prefix: Vec<u8>,
-    current: &'a tree::Tree<T>,
-    stack: Vec<(&'a tree::Tree<T>, usize)>,
+pub struct RadixMapIter<'a, T, K> {
+    bytes_iter: RadixMapBytesIter<'a, T>,
+    _marker: PhantomData<K>,
 }
 
-impl<'a, T> Iterator for RadixMapIter<'a, T>
+impl<'a, T, K> Iterator for RadixMapIter<'a, T, K>
 where
     T: 'a,
+    K: RadixKey,
 {
-    type Item = (Vec<u8>, &'a T);
+    type Item = (K, &'a T);
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            while let Some(ref node) = self.current {
-                let Node {
-                    ref key,
-                    ref value,
-                    ref next,
-                    ref child,
-                } = **node;
-                let key_len = key.len();
-                self.prefix.extend_from_slice(key.as_slice());
-                self.current = child;
-                self.stack.push((next, key_len));
-                if let Some(ref value) = value {
-                    return Some((self.prefix.clone(), value));
-                }
-            }
-            match self.stack.pop() {
-                Some((next_tree, key_len)) => {
-                    let new_len = self.prefix.len() - key_len;
-                    self.prefix.split_off(new_len);
-                    self.current = next_tree;
-                }
-                None => return None,
+        self.bytes_iter
+            .next_entry()
+            .map(|(key, value)| (K::from_bytes(key.to_vec()), value))
+    }
+}
+
+/// An iterator for `RadixMap<T, K>` that yields the byte encodings of the keys.
+///
+/// This iterator traverse the elements of the map in lexographic order and yields immutable
+/// references. The keys borrow a buffer of the iterator, so it does not implement `Iterator` and
+/// is advanced with `next_entry` instead.
+pub struct RadixMapBytesIter<'a, T> {
+    prefix: Vec<u8>,
+    stack: Vec<(&'a Node<T>, usize)>,
+}
+
+impl<'a, T> RadixMapBytesIter<'a, T>
+where
+    T: 'a,
+{
+    /// Advances the iterator and returns the next key-value pair, or `None` if the iteration is
+    /// finished.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixMap;
+    ///
+    /// let mut map = RadixMap::new();
+    /// map.insert(b"foo", 1);
+    ///
+    /// let mut iterator = map.iter_bytes();
+    /// assert_eq!(iterator.next_entry(), Some((&b"foo"[..], &1)));
+    /// assert_eq!(iterator.next_entry(), None);
+    /// ```
+    pub fn next_entry(&mut self) -> Option<(&[u8], &'a T)> {
+        while let Some((node, prefix_len)) = self.stack.pop() {
+            self.prefix.truncate(prefix_len);
+            self.prefix.extend_from_slice(node.key.as_slice());
+            let len = self.prefix.len();
+            self.stack
+                .extend(node.children.iter().rev().map(|child| (child, len)));
+            if let Some(ref value) = node.value {
+                return Some((&self.prefix, value));
             }
         }
+        None
+    }
+}
+
+/// An iterator over the entries of a `RadixMap<T, K>` whose keys start with a particular prefix.
+///
+/// This iterator traverse the matching elements of the map in lexographic order and yields
+/// immutable references.
+pub struct RadixMapPrefixIter<'a, T, K> {
+    map_iter: RadixMapIter<'a, T, K>,
+}
+
+impl<'a, T, K> Iterator for RadixMapPrefixIter<'a, T, K>
+where
+    T: 'a,
+    K: RadixKey,
+{
+    type Item = (K, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.map_iter.next()
     }
 }
 
-/// A mutable iterator for `RadixMap<T>`.
+/// A mutable iterator for `RadixMap<T, K>`.
 ///
 /// This iterator traverse the elements of the map in lexographic order and yields mutable
 /// references.
-pub struct RadixMapIterMut<'a, T> {
-    prefix: Vec<u8>,
-    current: Option<&'a mut Node<T>>,
-    stack: Vec<(&'a mut tree::Tree<T>, usize)>,
+pub struct RadixMapIterMut<'a, T, K> {
+    bytes_iter: RadixMapBytesIterMut<'a, T>,
+    _marker: PhantomData<K>,
 }
 
-impl<'a, T> Iterator for RadixMapIterMut<'a, T>
+impl<'a, T, K> Iterator for RadixMapIterMut<'a, T, K>
 where
     T: 'a,
+    K: RadixKey,
 {
-    type Item = (Vec<u8>, &'a mut T);
+    type Item = (K, &'a mut T);
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            while let Some(node) = self.current.take() {
-                let Node {
-                    ref key,
-                    ref mut value,
-                    ref mut next,
-                    ref mut child,
-                } = node;
-                let key_len = key.len();
-
-                self.prefix.extend_from_slice(key.as_slice());
-                self.current = child.as_mut().map(|node| &mut **node);
-                self.stack.push((next, key_len));
-                if value.is_some() {
-                    return value.as_mut().map(|value| (self.prefix.clone(), value));
-                }
+        self.bytes_iter
+            .next_entry()
+            .map(|(key, value)| (K::from_bytes(key.to_vec()), value))
+    }
+}
+
+/// A mutable iterator for `RadixMap<T, K>` that yields the byte encodings of the keys.
+///
+/// This iterator traverse the elements of the map in lexographic order and yields mutable
+/// references. The keys borrow a buffer of the iterator, so it does not implement `Iterator` and
+/// is advanced with `next_entry` instead.
+pub struct RadixMapBytesIterMut<'a, T> {
+    prefix: Vec<u8>,
+    stack: Vec<(&'a mut Node<T>, usize)>,
+}
+
+impl<'a, T> RadixMapBytesIterMut<'a, T>
+where
+    T: 'a,
+{
+    /// Advances the iterator and returns the next key-value pair, or `None` if the iteration is
+    /// finished.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixMap;
+    ///
+    /// let mut map = RadixMap::new();
+    /// map.insert(b"foo", 1);
+    ///
+    /// let mut iterator = map.iter_bytes_mut();
+    /// assert_eq!(iterator.next_entry(), Some((&b"foo"[..], &mut 1)));
+    /// assert_eq!(iterator.next_entry(), None);
+    /// ```
+    pub fn next_entry(&mut self) -> Option<(&[u8], &'a mut T)> {
+        while let Some((node, prefix_len)) = self.stack.pop() {
+            let Node {
+                key,
+                value,
+                children,
+            } = node;
+            self.prefix.truncate(prefix_len);
+            self.prefix.extend_from_slice(key.as_slice());
+            let len = self.prefix.len();
+            self.stack.extend(
+                children
+                    .iter_mut()
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, len)),
+            );
+            if let Some(value) = value {
+                return Some((&self.prefix, value));
             }
-            match self.stack.pop() {
-                Some((next_tree, key_len)) => {
-                    let new_len = self.prefix.len() - key_len;
-                    self.prefix.split_off(new_len);
-                    self.current = next_tree.as_mut().map(|node| &mut **node);
-                }
-                None => return None,
+        }
+        None
+    }
+}
+
+impl<T, K> Clone for RadixMap<T, K>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        RadixMap {
+            root: self.root.clone(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, K> fmt::Debug for RadixMap<T, K>
+where
+    T: fmt::Debug,
+    K: RadixKey + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, K> PartialEq for RadixMap<T, K>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        // compares the byte encodings of the keys so that the keys do not need to be decoded
+        fn iter<T>(root: &tree::Tree<T>) -> RadixMapBytesIter<'_, T> {
+            RadixMapBytesIter {
+                prefix: Vec::new(),
+                stack: root.iter().map(|node| (&**node, 0)).collect(),
+            }
+        }
+        if self.len != other.len {
+            return false;
+        }
+        let mut self_iter = iter(&self.root);
+        let mut other_iter = iter(&other.root);
+        loop {
+            match (self_iter.next_entry(), other_iter.next_entry()) {
+                (None, None) => return true,
+                (Some(entry), Some(other_entry)) if entry == other_entry => {}
+                _ => return false,
             }
         }
     }
 }
 
-impl<T> Default for RadixMap<T> {
+impl<T, K> Eq for RadixMap<T, K> where T: Eq {}
+
+impl<T, K> Default for RadixMap<T, K> {
     fn default() -> Self {
-        Self::new()
+        RadixMap {
+            root: Some(Box::new(Node::new(Vec::new(), None))),
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, K, Q> FromIterator<(Q, T)> for RadixMap<T, K>
+where
+    K: RadixKey,
+    Q: AsBytes<Key = K>,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (Q, T)>,
+    {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<T, K, Q> Extend<(Q, T)> for RadixMap<T, K>
+where
+    K: RadixKey,
+    Q: AsBytes<Key = K>,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (Q, T)>,
+    {
+        for (key, value) in iter {
+            self.insert(&key, value);
+        }
     }
 }
 
-impl<'a, T> Index<&'a [u8]> for RadixMap<T> {
+impl<T, K, Q> Index<&Q> for RadixMap<T, K>
+where
+    K: RadixKey,
+    Q: AsBytes<Key = K> + ?Sized,
+{
     type Output = T;
 
-    fn index(&self, key: &[u8]) -> &Self::Output {
+    fn index(&self, key: &Q) -> &Self::Output {
         self.get(key).expect("Error: key does not exist.")
     }
 }
 
-impl<'a, T> IndexMut<&'a [u8]> for RadixMap<T> {
-    fn index_mut(&mut self, key: &[u8]) -> &mut Self::Output {
+impl<T, K, Q> IndexMut<&Q> for RadixMap<T, K>
+where
+    K: RadixKey,
+    Q: AsBytes<Key = K> + ?Sized,
+{
+    fn index_mut(&mut self, key: &Q) -> &mut Self::Output {
         self.get_mut(key).expect("Error: key does not exist.")
     }
 }
@@ -517,6 +992,8 @@ impl<'a, T> IndexMut<&'a [u8]> for RadixMap<T> {
 #[cfg(test)]
 mod tests {
     use super::RadixMap;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+    use std::collections::BTreeMap;
 
     fn get_bytes_vec(key: &str) -> Vec<u8> {
         String::from(key).into_bytes()
@@ -677,6 +1154,30 @@ mod tests {
         assert_eq!(map.get_longest_prefix(b"aaa").len(), 0);
     }
 
+    #[test]
+    fn test_remove_missing_prefix_then_insert() {
+        let mut map = RadixMap::new();
+        map.insert(b"ab", 0);
+        assert_eq!(map.remove(b"a"), None);
+        assert_eq!(map.remove(b"ab"), Some((get_bytes_vec("ab"), 0)));
+        map.insert(b"ac", 1);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.min(), Some(get_bytes_vec("ac")));
+        assert_eq!(map.max(), Some(get_bytes_vec("ac")));
+    }
+
+    #[test]
+    fn test_remove_missing_empty_key_then_insert() {
+        let mut map = RadixMap::new();
+        map.insert(&[2][..], 0);
+        assert_eq!(map.remove(&[][..]), None);
+        assert_eq!(map.remove(&[2][..]), Some((vec![2], 0)));
+        map.insert(&[][..], 1);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.min(), Some(vec![]));
+        assert_eq!(map.max(), Some(vec![]));
+    }
+
     #[test]
     fn test_min_max() {
         let mut map = RadixMap::new();
@@ -707,6 +1208,86 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_iter() {
+        let mut map: RadixMap<u32> = vec![(b"b", 2), (b"a", 1), (b"b", 3)].into_iter().collect();
+        map.extend(vec![(b"c".to_vec(), 4)]);
+        assert_eq!(
+            map.into_iter().collect::<Vec<(Vec<u8>, u32)>>(),
+            vec![(b"a".to_vec(), 1), (b"b".to_vec(), 3), (b"c".to_vec(), 4)],
+        );
+    }
+
+    #[test]
+    fn test_typed_keys() {
+        let mut map: RadixMap<u32, String> = RadixMap::default();
+        map.insert("foobar", 1);
+        map.insert(&String::from("foo"), 0);
+        assert_eq!(map["foo"], 0);
+        assert_eq!(map.remove("foobar"), Some((String::from("foobar"), 1)));
+        assert_eq!(
+            map.into_iter().collect::<Vec<(String, u32)>>(),
+            vec![(String::from("foo"), 0)],
+        );
+
+        let map: RadixMap<(), i32> = vec![(3, ()), (-1, ()), (256, ()), (-300, ())]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            map.iter().map(|pair| pair.0).collect::<Vec<i32>>(),
+            vec![-300, -1, 3, 256],
+        );
+    }
+
+    #[test]
+    fn test_iter_prefix() {
+        let mut map = RadixMap::new();
+        map.insert(b"a", 0);
+        map.insert(b"ab", 1);
+        map.insert(b"abc", 2);
+        map.insert(b"abd", 3);
+        map.insert(b"b", 4);
+
+        let keys = |prefix: &[u8]| {
+            map.iter_prefix(prefix)
+                .map(|(key, value)| (String::from_utf8(key).unwrap(), *value))
+                .collect::<Vec<(String, u32)>>()
+        };
+        assert_eq!(keys(b"").len(), 5);
+        assert_eq!(
+            keys(b"ab"),
+            vec![
+                (String::from("ab"), 1),
+                (String::from("abc"), 2),
+                (String::from("abd"), 3),
+            ],
+        );
+        assert_eq!(keys(b"abd"), vec![(String::from("abd"), 3)]);
+        assert_eq!(keys(b"abe"), vec![]);
+        assert_eq!(keys(b"abcd"), vec![]);
+        assert_eq!(keys(b"c"), vec![]);
+    }
+
+    #[test]
+    fn test_longest_prefix_match() {
+        let mut map = RadixMap::new();
+        assert_eq!(map.longest_prefix_match(b"a"), None);
+        map.insert(b"", 0);
+        map.insert(b"ab", 1);
+        map.insert(b"abcd", 2);
+
+        assert_eq!(map.longest_prefix_match(b"a"), Some((Vec::new(), &0)));
+        assert_eq!(map.longest_prefix_match(b"abc"), Some((b"ab".to_vec(), &1)));
+        assert_eq!(
+            map.longest_prefix_match(b"abcd"),
+            Some((b"abcd".to_vec(), &2))
+        );
+        assert_eq!(
+            map.longest_prefix_match(b"abcde"),
+            Some((b"abcd".to_vec(), &2))
+        );
+    }
+
     #[test]
     fn test_iter() {
         let mut map = RadixMap::new();
@@ -744,4 +1325,229 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_iter_bytes() {
+        let mut map = RadixMap::new();
+        map.insert(b"a", 2);
+        map.insert(b"ab", 6);
+        map.insert(b"aa", 4);
+        map.insert(b"b", 8);
+
+        let mut iterator = map.iter_bytes_mut();
+        while let Some((key, value)) = iterator.next_entry() {
+            *value += key.len() as u32;
+        }
+
+        let mut actual = Vec::new();
+        let mut iterator = map.iter_bytes();
+        while let Some((key, value)) = iterator.next_entry() {
+            actual.push((key.to_vec(), value));
+        }
+        assert_eq!(actual, map.iter().collect::<Vec<(Vec<u8>, &u32)>>());
+        assert_eq!(
+            actual,
+            vec![
+                (get_bytes_vec("a"), &3),
+                (get_bytes_vec("aa"), &6),
+                (get_bytes_vec("ab"), &8),
+                (get_bytes_vec("b"), &9),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_clone_eq() {
+        let mut map = RadixMap::new();
+        map.insert(b"a", 1);
+        map.insert(b"ab", 2);
+        map.insert(b"b", 3);
+
+        let mut cloned = map.clone();
+        assert_eq!(cloned, map);
+        cloned.insert(b"ab", 4);
+        assert_ne!(cloned, map);
+        cloned.insert(b"ab", 2);
+        cloned.insert(b"abc", 5);
+        assert_ne!(cloned, map);
+        cloned.remove(b"abc");
+        assert_eq!(cloned, map);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut map: RadixMap<u32, String> = RadixMap::default();
+        map.insert("b", 2);
+        map.insert("a", 1);
+        assert_eq!(format!("{:?}", map), r#"{"a": 1, "b": 2}"#);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut map = RadixMap::new();
+        map.insert(b"a", 1);
+        map.insert(b"ab", 2);
+        map.insert(b"abc", 3);
+        map.insert(b"abd", 4);
+        map.insert(b"b", 5);
+
+        map.retain(|key, value| {
+            *value += 1;
+            key.len() != 2 && key != b"abd"
+        });
+        assert_eq!(map.len(), 3);
+        assert_eq!(
+            map.iter().collect::<Vec<(Vec<u8>, &u32)>>(),
+            vec![
+                (get_bytes_vec("a"), &2),
+                (get_bytes_vec("abc"), &4),
+                (get_bytes_vec("b"), &6),
+            ],
+        );
+        assert_eq!(map.get(b"ab"), None);
+        assert_eq!(map.get(b"abc"), Some(&4));
+
+        map.retain(|_, _| false);
+        assert!(map.is_empty());
+        assert_eq!(map.min(), None);
+        map.insert(b"a", 1);
+        assert_eq!(map.get(b"a"), Some(&1));
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut map = RadixMap::new();
+        map.insert(b"b", 2);
+        map.insert(b"a", 1);
+
+        assert_eq!(
+            map.drain().collect::<Vec<(Vec<u8>, u32)>>(),
+            vec![(get_bytes_vec("a"), 1), (get_bytes_vec("b"), 2)],
+        );
+        assert!(map.is_empty());
+        map.insert(b"a", 1);
+        assert_eq!(map.get(b"a"), Some(&1));
+    }
+
+    #[test]
+    fn test_remove_prefix() {
+        let mut map = RadixMap::new();
+        map.insert(b"a", 0);
+        map.insert(b"ab", 1);
+        map.insert(b"abc", 2);
+        map.insert(b"abd", 3);
+        map.insert(b"b", 4);
+
+        let removed = map.remove_prefix(b"abc");
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed.get(b"abc"), Some(&2));
+
+        let removed = map.remove_prefix(b"ab");
+        assert_eq!(removed.len(), 2);
+        assert_eq!(
+            removed.iter().collect::<Vec<(Vec<u8>, &u32)>>(),
+            vec![(get_bytes_vec("ab"), &1), (get_bytes_vec("abd"), &3)],
+        );
+        assert_eq!(map.len(), 2);
+        assert_eq!(
+            map.iter().collect::<Vec<(Vec<u8>, &u32)>>(),
+            vec![(get_bytes_vec("a"), &0), (get_bytes_vec("b"), &4)],
+        );
+
+        assert!(map.remove_prefix(b"c").is_empty());
+        let removed = map.remove_prefix(b"");
+        assert_eq!(removed.len(), 2);
+        assert!(map.is_empty());
+        map.insert(b"a", 1);
+        assert_eq!(map.get(b"a"), Some(&1));
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut map = RadixMap::new();
+        map.insert(b"a", 0);
+        map.insert(b"ab", 1);
+        map.insert(b"abc", 2);
+        map.insert(b"b", 3);
+
+        let split = map.split_off(b"ab", false);
+        assert_eq!(
+            map.iter().collect::<Vec<(Vec<u8>, &u32)>>(),
+            vec![(get_bytes_vec("a"), &0)],
+        );
+        assert_eq!(
+            split.iter().collect::<Vec<(Vec<u8>, &u32)>>(),
+            vec![
+                (get_bytes_vec("ab"), &1),
+                (get_bytes_vec("abc"), &2),
+                (get_bytes_vec("b"), &3),
+            ],
+        );
+        assert_eq!(split.len(), 3);
+        assert_eq!(split.min(), Some(get_bytes_vec("ab")));
+    }
+
+    #[test]
+    fn test_random_detach() {
+        let mut rng: XorShiftRng = SeedableRng::from_seed([1, 1, 1, 1]);
+        for _ in 0..200 {
+            let mut map = RadixMap::new();
+            let mut expected = BTreeMap::new();
+            for _ in 0..100 {
+                let len = rng.gen_range(0, 5);
+                let key: Vec<u8> = (0..len).map(|_| rng.gen_range(b'a', b'd')).collect();
+                let value = rng.gen::<u32>();
+                map.insert(&key, value);
+                expected.insert(key, value);
+            }
+
+            let len = rng.gen_range(0, 4);
+            let key: Vec<u8> = (0..len).map(|_| rng.gen_range(b'a', b'd')).collect();
+            let (mut right, mut expected_right): (_, BTreeMap<Vec<u8>, u32>) = if rng.gen() {
+                let inclusive = rng.gen();
+                let right = map.split_off(&key, inclusive);
+                let mut expected_right = expected.split_off(&key);
+                if inclusive {
+                    if let Some(value) = expected_right.remove(&key) {
+                        expected.insert(key.clone(), value);
+                    }
+                }
+                (right, expected_right)
+            } else {
+                let right = map.remove_prefix(&key);
+                let expected_right = expected
+                    .iter()
+                    .filter(|(curr_key, _)| curr_key.starts_with(&key))
+                    .map(|(curr_key, value)| (curr_key.clone(), *value))
+                    .collect();
+                expected.retain(|curr_key, _| !curr_key.starts_with(&key));
+                (right, expected_right)
+            };
+
+            for (map, expected) in [(&mut map, &mut expected), (&mut right, &mut expected_right)] {
+                assert_eq!(map.len(), expected.len());
+                assert!(map
+                    .iter()
+                    .eq(expected.iter().map(|(key, value)| (key.clone(), value))));
+                assert_eq!(map.min(), expected.keys().next().cloned());
+                assert_eq!(map.max(), expected.keys().next_back().cloned());
+                for _ in 0..20 {
+                    let len = rng.gen_range(0, 5);
+                    let key: Vec<u8> = (0..len).map(|_| rng.gen_range(b'a', b'd')).collect();
+                    assert_eq!(map.get(&key), expected.get(&key));
+                    if rng.gen() {
+                        assert_eq!(
+                            map.insert(&key, 0).map(|pair| pair.1),
+                            expected.insert(key, 0),
+                        );
+                    } else {
+                        assert_eq!(map.remove(&key).map(|pair| pair.1), expected.remove(&key),);
+                    }
+                }
+                assert!(map
+                    .iter()
+                    .eq(expected.iter().map(|(key, value)| (key.clone(), value))));
+            }
+        }
+    }
 }
@@ -1,9 +1,11 @@
 //! Space-optimized trie.
 
+mod key;
 mod map;
 mod node;
 mod set;
 mod tree;
 
-pub use self::map::RadixMap;
+pub use self::key::{AsBytes, RadixKey};
+pub use self::map::{RadixMap, RadixMapBytesIter, RadixMapBytesIterMut};
 pub use self::set::RadixSet;
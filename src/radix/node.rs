@@ -1,11 +1,406 @@
 use crate::radix::tree::Tree;
-use std::mem;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::mem;
 
+// The children of a node are stored in one of four representations depending on how many children
+// the node has, as described in "The Adaptive Radix Tree: ARTful Indexing for Main-Memory
+// Databases" by Leis et al. A node grows into the next representation when it is full and shrinks
+// into the previous representation when it has sufficiently few children. The thresholds for
+// shrinking are lower than the capacities of the smaller representations to avoid repeatedly
+// switching representations when a child is inserted and removed.
+const NODE4_CAPACITY: usize = 4;
+const NODE16_CAPACITY: usize = 16;
+const NODE48_CAPACITY: usize = 48;
+const NODE16_SHRINK_THRESHOLD: usize = 3;
+const NODE48_SHRINK_THRESHOLD: usize = 12;
+const NODE256_SHRINK_THRESHOLD: usize = 40;
+const EMPTY_SLOT: u8 = u8::MAX;
+
+fn empty_trees<T, const N: usize>() -> [Tree<T>; N] {
+    [(); N].map(|_| None)
+}
+
+// Children stored in parallel arrays of keys and nodes that are sorted by key. Used for nodes with
+// at most 4 or 16 children.
+#[derive(Clone)]
+pub struct SortedChildren<T, const N: usize> {
+    len: usize,
+    keys: [u8; N],
+    children: [Tree<T>; N],
+}
+
+impl<T, const N: usize> SortedChildren<T, N> {
+    fn new() -> Self {
+        SortedChildren {
+            len: 0,
+            keys: [0; N],
+            children: empty_trees(),
+        }
+    }
+
+    fn from_sorted<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Box<Node<T>>>,
+    {
+        let mut ret = Self::new();
+        for node in iter {
+            ret.keys[ret.len] = node.key[0];
+            ret.children[ret.len] = Some(node);
+            ret.len += 1;
+        }
+        ret
+    }
+
+    fn position(&self, byte: u8) -> Result<usize, usize> {
+        self.keys[..self.len].binary_search(&byte)
+    }
+
+    fn get(&self, byte: u8) -> &Tree<T> {
+        match self.position(byte) {
+            Ok(index) => &self.children[index],
+            Err(_) => &None,
+        }
+    }
+
+    fn get_mut(&mut self, byte: u8) -> Option<&mut Tree<T>> {
+        match self.position(byte) {
+            Ok(index) => Some(&mut self.children[index]),
+            Err(_) => None,
+        }
+    }
+
+    fn insert(&mut self, node: Box<Node<T>>) {
+        let index = match self.position(node.key[0]) {
+            Ok(index) | Err(index) => index,
+        };
+        self.keys[index..=self.len].rotate_right(1);
+        self.children[index..=self.len].rotate_right(1);
+        self.keys[index] = node.key[0];
+        self.children[index] = Some(node);
+        self.len += 1;
+    }
+
+    fn remove(&mut self, byte: u8) -> Tree<T> {
+        let index = self.position(byte).ok()?;
+        let ret = self.children[index].take();
+        self.keys[index..self.len].rotate_left(1);
+        self.children[index..self.len].rotate_left(1);
+        self.len -= 1;
+        ret
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = Box<Node<T>>> + '_ {
+        let len = mem::replace(&mut self.len, 0);
+        self.children[..len].iter_mut().filter_map(Option::take)
+    }
+}
+
+// Children stored in at most 48 slots with an index from each byte to the slot of its child.
+#[derive(Clone)]
+pub struct IndexedChildren<T> {
+    len: usize,
+    index: [u8; 256],
+    children: [Tree<T>; NODE48_CAPACITY],
+}
+
+impl<T> IndexedChildren<T> {
+    fn from_sorted<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Box<Node<T>>>,
+    {
+        let mut ret = IndexedChildren {
+            len: 0,
+            index: [EMPTY_SLOT; 256],
+            children: empty_trees(),
+        };
+        for node in iter {
+            ret.insert(node);
+        }
+        ret
+    }
+
+    fn get(&self, byte: u8) -> &Tree<T> {
+        match self.index[byte as usize] {
+            EMPTY_SLOT => &None,
+            slot => &self.children[slot as usize],
+        }
+    }
+
+    fn get_mut(&mut self, byte: u8) -> Option<&mut Tree<T>> {
+        match self.index[byte as usize] {
+            EMPTY_SLOT => None,
+            slot => Some(&mut self.children[slot as usize]),
+        }
+    }
+
+    fn insert(&mut self, node: Box<Node<T>>) {
+        let slot = self
+            .children
+            .iter()
+            .position(Option::is_none)
+            .expect("Expected an empty slot.");
+        self.index[node.key[0] as usize] = slot as u8;
+        self.children[slot] = Some(node);
+        self.len += 1;
+    }
+
+    fn remove(&mut self, byte: u8) -> Tree<T> {
+        match mem::replace(&mut self.index[byte as usize], EMPTY_SLOT) {
+            EMPTY_SLOT => None,
+            slot => {
+                self.len -= 1;
+                self.children[slot as usize].take()
+            }
+        }
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = Box<Node<T>>> + '_ {
+        self.len = 0;
+        let IndexedChildren {
+            ref mut index,
+            ref mut children,
+            ..
+        } = *self;
+        index
+            .iter_mut()
+            .filter_map(move |slot| match mem::replace(slot, EMPTY_SLOT) {
+                EMPTY_SLOT => None,
+                slot => children[slot as usize].take(),
+            })
+    }
+}
+
+// Children stored directly in a slot for every byte.
+#[derive(Clone)]
+pub struct DirectChildren<T> {
+    len: usize,
+    children: [Tree<T>; 256],
+}
+
+impl<T> DirectChildren<T> {
+    fn from_sorted<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Box<Node<T>>>,
+    {
+        let mut ret = DirectChildren {
+            len: 0,
+            children: empty_trees(),
+        };
+        for node in iter {
+            ret.insert(node);
+        }
+        ret
+    }
+
+    fn insert(&mut self, node: Box<Node<T>>) {
+        let byte = node.key[0] as usize;
+        self.children[byte] = Some(node);
+        self.len += 1;
+    }
+
+    fn remove(&mut self, byte: u8) -> Tree<T> {
+        let ret = self.children[byte as usize].take();
+        if ret.is_some() {
+            self.len -= 1;
+        }
+        ret
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = Box<Node<T>>> + '_ {
+        self.len = 0;
+        self.children.iter_mut().filter_map(Option::take)
+    }
+}
+
+#[derive(Clone)]
+pub enum Children<T> {
+    Empty,
+    Node4(Box<SortedChildren<T, NODE4_CAPACITY>>),
+    Node16(Box<SortedChildren<T, NODE16_CAPACITY>>),
+    Node48(Box<IndexedChildren<T>>),
+    Node256(Box<DirectChildren<T>>),
+}
+
+impl<T> Children<T> {
+    pub fn len(&self) -> usize {
+        match self {
+            Children::Empty => 0,
+            Children::Node4(children) => children.len,
+            Children::Node16(children) => children.len,
+            Children::Node48(children) => children.len,
+            Children::Node256(children) => children.len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, byte: u8) -> &Tree<T> {
+        match self {
+            Children::Empty => &None,
+            Children::Node4(children) => children.get(byte),
+            Children::Node16(children) => children.get(byte),
+            Children::Node48(children) => children.get(byte),
+            Children::Node256(children) => &children.children[byte as usize],
+        }
+    }
+
+    pub fn get_mut(&mut self, byte: u8) -> Option<&mut Tree<T>> {
+        match self {
+            Children::Empty => None,
+            Children::Node4(children) => children.get_mut(byte),
+            Children::Node16(children) => children.get_mut(byte),
+            Children::Node48(children) => children.get_mut(byte),
+            Children::Node256(children) => {
+                let tree = &mut children.children[byte as usize];
+                if tree.is_some() {
+                    Some(tree)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn grow(&mut self) {
+        let grown = match self {
+            Children::Empty => Children::Node4(Box::new(SortedChildren::new())),
+            Children::Node4(children) if children.len == NODE4_CAPACITY => {
+                Children::Node16(Box::new(SortedChildren::from_sorted(children.drain())))
+            }
+            Children::Node16(children) if children.len == NODE16_CAPACITY => {
+                Children::Node48(Box::new(IndexedChildren::from_sorted(children.drain())))
+            }
+            Children::Node48(children) if children.len == NODE48_CAPACITY => {
+                Children::Node256(Box::new(DirectChildren::from_sorted(children.drain())))
+            }
+            _ => return,
+        };
+        *self = grown;
+    }
+
+    fn shrink(&mut self) {
+        let shrunk = match self {
+            Children::Node4(children) if children.len == 0 => Children::Empty,
+            Children::Node16(children) if children.len <= NODE16_SHRINK_THRESHOLD => {
+                Children::Node4(Box::new(SortedChildren::from_sorted(children.drain())))
+            }
+            Children::Node48(children) if children.len <= NODE48_SHRINK_THRESHOLD => {
+                Children::Node16(Box::new(SortedChildren::from_sorted(children.drain())))
+            }
+            Children::Node256(children) if children.len <= NODE256_SHRINK_THRESHOLD => {
+                Children::Node48(Box::new(IndexedChildren::from_sorted(children.drain())))
+            }
+            _ => return,
+        };
+        *self = shrunk;
+    }
+
+    pub fn insert(&mut self, node: Box<Node<T>>) {
+        self.grow();
+        match self {
+            Children::Empty => unreachable!(),
+            Children::Node4(children) => children.insert(node),
+            Children::Node16(children) => children.insert(node),
+            Children::Node48(children) => children.insert(node),
+            Children::Node256(children) => children.insert(node),
+        }
+    }
+
+    pub fn remove(&mut self, byte: u8) -> Tree<T> {
+        let ret = match self {
+            Children::Empty => None,
+            Children::Node4(children) => children.remove(byte),
+            Children::Node16(children) => children.remove(byte),
+            Children::Node48(children) => children.remove(byte),
+            Children::Node256(children) => children.remove(byte),
+        };
+        self.shrink();
+        ret
+    }
+
+    // Returns the children in ascending order of their keys.
+    pub fn iter(&self) -> Box<dyn DoubleEndedIterator<Item = &Node<T>> + '_> {
+        match self {
+            Children::Empty => Box::new(None.into_iter()),
+            Children::Node4(children) => Box::new(
+                children.children[..children.len]
+                    .iter()
+                    .flatten()
+                    .map(|node| &**node),
+            ),
+            Children::Node16(children) => Box::new(
+                children.children[..children.len]
+                    .iter()
+                    .flatten()
+                    .map(|node| &**node),
+            ),
+            Children::Node48(children) => Box::new(
+                children
+                    .index
+                    .iter()
+                    .filter(|slot| **slot != EMPTY_SLOT)
+                    .flat_map(move |slot| children.children[*slot as usize].as_ref())
+                    .map(|node| &**node),
+            ),
+            Children::Node256(children) => {
+                Box::new(children.children.iter().flatten().map(|node| &**node))
+            }
+        }
+    }
+
+    // Returns the children in ascending order of their keys.
+    pub fn iter_mut(&mut self) -> Vec<&mut Node<T>> {
+        match self {
+            Children::Empty => Vec::new(),
+            Children::Node4(children) => children.children[..children.len]
+                .iter_mut()
+                .flatten()
+                .map(|node| &mut **node)
+                .collect(),
+            Children::Node16(children) => children.children[..children.len]
+                .iter_mut()
+                .flatten()
+                .map(|node| &mut **node)
+                .collect(),
+            Children::Node48(children) => {
+                let mut ret: Vec<&mut Node<T>> = children
+                    .children
+                    .iter_mut()
+                    .flatten()
+                    .map(|node| &mut **node)
+                    .collect();
+                ret.sort_unstable_by_key(|node| node.key[0]);
+                ret
+            }
+            Children::Node256(children) => children
+                .children
+                .iter_mut()
+                .flatten()
+                .map(|node| &mut **node)
+                .collect(),
+        }
+    }
+
+    // Returns the children in ascending order of their keys.
+    pub fn into_vec(mut self) -> Vec<Box<Node<T>>> {
+        match self {
+            Children::Empty => Vec::new(),
+            Children::Node4(ref mut children) => children.drain().collect(),
+            Children::Node16(ref mut children) => children.drain().collect(),
+            Children::Node48(ref mut children) => children.drain().collect(),
+            Children::Node256(ref mut children) => children.drain().collect(),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Node<T> {
     pub key: Vec<u8>,
     pub value: Option<T>,
-    pub next: Tree<T>,
-    pub child: Tree<T>,
+    pub children: Children<T>,
 }
 
 impl<T> Node<T> {
@@ -13,122 +408,162 @@ impl<T> Node<T> {
         Self {
             key,
             value,
-            next: None,
-            child: None,
+            children: Children::Empty,
         }
     }
 
-    pub fn contains(&self, byte: u8) -> bool {
-        self.get(byte).is_some()
-    }
-
     pub fn get(&self, byte: u8) -> &Tree<T> {
-        let mut curr = &self.child;
-        loop {
-            match curr {
-                Some(ref node) if node.key[0] != byte => curr = &node.next,
-                tree => return tree,
-            };
-        }
+        self.children.get(byte)
     }
 
-    pub fn get_mut(&mut self, byte: u8) -> &mut Tree<T> {
-        let mut curr = &mut self.child;
-        loop {
-            let matches = match curr {
-                Some(ref mut node) => node.key[0] == byte,
-                None => false,
-            };
-            if matches {
-                return curr;
-            } else {
-                match { curr } {
-                    Some(ref mut node) => curr = &mut node.next,
-                    tree => return tree,
-                }
-            }
-        }
+    pub fn get_mut(&mut self, byte: u8) -> Option<&mut Tree<T>> {
+        self.children.get_mut(byte)
     }
 
     pub fn split(&mut self, split_index: usize) {
         let split_key = self.key.split_off(split_index);
         let mut split = Node::new(split_key, None);
         mem::swap(&mut self.value, &mut split.value);
-        mem::swap(&mut self.child, &mut split.child);
+        mem::swap(&mut self.children, &mut split.children);
         self.insert_child(split);
     }
 
     pub fn insert_child(&mut self, child: Node<T>) {
-        fn insert_inner<T>(tree: &mut Tree<T>, mut new_node: Box<Node<T>>) {
-            match tree {
-                Some(ref mut node) => {
-                    if node.key[0] > new_node.key[0] {
-                        mem::swap(node, &mut new_node);
-                        node.next = Some(new_node);
-                    } else {
-                        insert_inner(&mut node.next, new_node);
-                    }
-                }
-                None => *tree = Some(new_node),
+        self.children.insert(Box::new(child));
+    }
+
+    // Removes the child whose key starts with `byte` if it no longer contains any entries.
+    pub fn prune_child(&mut self, byte: u8) {
+        if let Some(ref child) = self.get(byte) {
+            if child.value.is_none() && child.is_leaf() {
+                self.children.remove(byte);
             }
         }
-        insert_inner(&mut self.child, Box::new(child));
     }
 
     pub fn merge(&mut self) {
-        if let Some(mut child_node) = self.child.take() {
-            if self.value.is_none() && child_node.next.is_none() {
-                self.key.extend(child_node.key.iter());
-                self.value = child_node.value.take();
-                self.child = child_node.child.take();
-            } else {
-                self.child = Some(child_node);
-            }
+        if self.value.is_none() && self.children.len() == 1 {
+            let mut child_node = mem::replace(&mut self.children, Children::Empty)
+                .into_vec()
+                .pop()
+                .expect("Expected a single child.");
+            self.key.append(&mut child_node.key);
+            self.value = child_node.value.take();
+            self.children = mem::replace(&mut child_node.children, Children::Empty);
         }
     }
 
     pub fn is_leaf(&self) -> bool {
-        self.child.is_none()
+        self.children.is_empty()
     }
 
-    pub fn get_replacement_tree(&mut self) -> Tree<T> {
-        self.next.take()
+    pub fn push_all_children(&self, curr_key: Vec<u8>, keys: &mut Vec<Vec<u8>>) {
+        for child in self.children.iter() {
+            let mut child_key = curr_key.clone();
+            child_key.extend_from_slice(&child.key);
+            if child.value.is_some() {
+                keys.push(child_key.clone());
+            }
+            child.push_all_children(child_key, keys);
+        }
     }
 
-    pub fn push_all_children(&self, curr_key: Vec<u8>, keys: &mut Vec<Vec<u8>>) {
-        fn push_all_children_inner<T>(
-            tree: &Tree<T>,
-            mut curr_key: Vec<u8>,
-            keys: &mut Vec<Vec<u8>>,
-        ) {
-            if let Some(ref node) = tree {
-                let len = curr_key.len();
-
-                curr_key.extend(node.key.iter());
-                if node.value.is_some() {
-                    keys.push(curr_key.clone());
-                }
-                push_all_children_inner(&node.child, curr_key.clone(), keys);
+    pub fn min(&self) -> Option<&Node<T>> {
+        self.children.iter().next()
+    }
 
-                curr_key.split_off(len);
-                push_all_children_inner(&node.next, curr_key, keys);
-            }
+    pub fn max(&self) -> Option<&Node<T>> {
+        self.children.iter().next_back()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Children, Node};
+
+    fn capacity<T>(node: &Node<T>) -> usize {
+        match node.children {
+            Children::Empty => 0,
+            Children::Node4(_) => 4,
+            Children::Node16(_) => 16,
+            Children::Node48(_) => 48,
+            Children::Node256(_) => 256,
         }
-        push_all_children_inner(&self.child, curr_key, keys);
     }
 
-    pub fn min(&self) -> &Tree<T> {
-        &self.child
+    fn assert_children(node: &Node<u8>, expected: &[u8]) {
+        assert_eq!(node.children.len(), expected.len());
+        assert_eq!(
+            node.children
+                .iter()
+                .map(|child| child.key[0])
+                .collect::<Vec<u8>>(),
+            expected,
+        );
+        for byte in expected {
+            assert_eq!(
+                node.get(*byte).as_ref().and_then(|child| child.value),
+                Some(*byte)
+            );
+        }
     }
 
-    pub fn max(&self) -> &Tree<T> {
-        let mut curr_tree = &self.child;
-        while let Some(ref curr_node) = curr_tree {
-            if (*curr_node).next.is_none() {
-                return curr_tree;
-            }
-            curr_tree = &curr_node.next;
+    #[test]
+    fn test_grow() {
+        let mut node = Node::new(Vec::new(), None);
+        let mut expected = Vec::new();
+        for i in 0..256usize {
+            // 97 is coprime with 256 so every byte is inserted in a scrambled order.
+            let byte = (i * 97 % 256) as u8;
+            node.insert_child(Node::new(vec![byte], Some(byte)));
+            expected.push(byte);
+            expected.sort();
+
+            let expected_capacity = match expected.len() {
+                0..=4 => 4,
+                5..=16 => 16,
+                17..=48 => 48,
+                _ => 256,
+            };
+            assert_eq!(capacity(&node), expected_capacity);
+            assert_children(&node, &expected);
         }
-        &None
+    }
+
+    #[test]
+    fn test_shrink() {
+        let mut node = Node::new(Vec::new(), None);
+        for byte in 0..=255 {
+            node.insert_child(Node::new(vec![byte], Some(byte)));
+        }
+
+        let mut expected = (0..=255).collect::<Vec<u8>>();
+        let mut expected_capacity = 256;
+        while let Some(byte) = expected.pop() {
+            assert_eq!(
+                node.children.remove(byte).map(|child| child.key),
+                Some(vec![byte])
+            );
+
+            expected_capacity = match expected.len() {
+                0 => 0,
+                3 => 4,
+                12 => 16,
+                40 => 48,
+                _ => expected_capacity,
+            };
+            assert_eq!(capacity(&node), expected_capacity);
+            assert_children(&node, &expected);
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut node = Node::new(b"a".to_vec(), None);
+        node.insert_child(Node::new(b"bc".to_vec(), Some(1)));
+        node.merge();
+        assert_eq!(node.key, b"abc".to_vec());
+        assert_eq!(node.value, Some(1));
+        assert!(node.is_leaf());
     }
 }
@@ -1,4 +1,8 @@
-use crate::radix::map::{RadixMap, RadixMapIntoIter, RadixMapIter};
+use crate::radix::key::{AsBytes, RadixKey};
+use crate::radix::map::{RadixMap, RadixMapIntoIter, RadixMapIter, RadixMapPrefixIter};
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
 
 /// An ordered set implemented using a radix tree.
 ///
@@ -10,6 +14,9 @@ use crate::radix::map::{RadixMap, RadixMapIntoIter, RadixMapIter};
 /// children should be represented as a fixed length array of size 256 if the tree is dense for
 /// performance.
 ///
+/// The keys of the set are byte vectors by default, but any type that implements `RadixKey` can be
+/// used as a key.
+///
 /// # Examples
 ///
 /// ```
@@ -29,9 +36,14 @@ use crate::radix::map::{RadixMap, RadixMapIntoIter, RadixMapIter};
 /// );
 ///
 /// assert_eq!(set.remove(b"foo"), Some(String::from("foo").into_bytes()),);
+///
+/// let mut set: RadixSet<u32> = RadixSet::default();
+/// set.insert(&256u32);
+/// set.insert(&1u32);
+/// assert_eq!(set.iter().collect::<Vec<u32>>(), vec![1, 256]);
 /// ```
-pub struct RadixSet {
-    map: RadixMap<()>,
+pub struct RadixSet<K = Vec<u8>> {
+    map: RadixMap<(), K>,
 }
 
 impl RadixSet {
@@ -45,11 +57,14 @@ impl RadixSet {
     /// let set = RadixSet::new();
     /// ```
     pub fn new() -> Self {
-        RadixSet {
-            map: RadixMap::new(),
-        }
+        Self::default()
     }
+}
 
+impl<K> RadixSet<K>
+where
+    K: RadixKey,
+{
     /// Inserts a key into the set. If the key already exists in the set, it will return and
     /// replace the old key.
     ///
@@ -63,7 +78,10 @@ impl RadixSet {
     /// assert!(set.contains(b"foo"));
     /// assert_eq!(set.insert(b"foo"), Some(String::from("foo").into_bytes()),);
     /// ```
-    pub fn insert(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+    pub fn insert<Q>(&mut self, key: &Q) -> Option<K>
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
         self.map.insert(key, ()).map(|pair| pair.0)
     }
 
@@ -80,7 +98,10 @@ impl RadixSet {
     /// assert_eq!(set.remove(b"foo"), Some(String::from("foo").into_bytes()),);
     /// assert_eq!(set.remove(b"foobar"), None);
     /// ```
-    pub fn remove(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<K>
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
         self.map.remove(key).map(|pair| pair.0)
     }
 
@@ -96,7 +117,10 @@ impl RadixSet {
     /// assert!(set.contains(b"foo"));
     /// assert!(!set.contains(b"foobar"));
     /// ```
-    pub fn contains(&self, key: &[u8]) -> bool {
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
         self.map.contains_key(key)
     }
 
@@ -146,6 +170,55 @@ impl RadixSet {
         self.map.clear();
     }
 
+    /// Retains only the keys that satisfy a predicate. The keys are visited in lexographic order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixSet;
+    ///
+    /// let mut set = RadixSet::new();
+    /// set.insert(b"foo");
+    /// set.insert(b"foobar");
+    /// set.insert(b"bar");
+    ///
+    /// set.retain(|key| key.starts_with(b"foo"));
+    /// assert_eq!(
+    ///     set.iter().collect::<Vec<Vec<u8>>>(),
+    ///     vec![b"foo".to_vec(), b"foobar".to_vec()],
+    /// );
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K) -> bool,
+    {
+        self.map.retain(|key, _| f(key));
+    }
+
+    /// Clears the set, returning all keys as an iterator. The iterator yields the keys in
+    /// lexographic order, and the set can be reused afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixSet;
+    ///
+    /// let mut set = RadixSet::new();
+    /// set.insert(b"foo");
+    /// set.insert(b"bar");
+    ///
+    /// assert_eq!(
+    ///     set.drain().collect::<Vec<Vec<u8>>>(),
+    ///     vec![b"bar".to_vec(), b"foo".to_vec()],
+    /// );
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> RadixSetIntoIter<K> {
+        RadixSetIntoIter {
+            map_iter: self.map.drain(),
+        }
+    }
+
     /// Returns all keys that share the longest common prefix with the specified key.
     ///
     /// # Examples
@@ -162,10 +235,124 @@ impl RadixSet {
     ///     vec![String::from("foobar").into_bytes()],
     /// );
     /// ```
-    pub fn get_longest_prefix(&self, key: &[u8]) -> Vec<Vec<u8>> {
+    pub fn get_longest_prefix<Q>(&self, key: &Q) -> Vec<K>
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
         self.map.get_longest_prefix(key)
     }
 
+    /// Returns an iterator over all keys that start with a particular prefix. The iterator will
+    /// yield keys in lexographic order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixSet;
+    ///
+    /// let mut set = RadixSet::new();
+    /// set.insert(b"foo");
+    /// set.insert(b"foobar");
+    /// set.insert(b"bar");
+    ///
+    /// let mut iterator = set.iter_prefix(b"fo");
+    /// assert_eq!(iterator.next(), Some(String::from("foo").into_bytes()));
+    /// assert_eq!(iterator.next(), Some(String::from("foobar").into_bytes()));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter_prefix<Q>(&self, prefix: &Q) -> RadixSetPrefixIter<'_, K>
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        RadixSetPrefixIter {
+            map_iter: self.map.iter_prefix(prefix),
+        }
+    }
+
+    /// Removes all keys that start with a particular prefix and returns them as a new set. The
+    /// subtree that contains the keys is detached from the set as a whole rather than removing the
+    /// keys one at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixSet;
+    ///
+    /// let mut set = RadixSet::new();
+    /// set.insert(b"foo");
+    /// set.insert(b"foobar");
+    /// set.insert(b"bar");
+    ///
+    /// let removed = set.remove_prefix(b"fo");
+    /// assert_eq!(
+    ///     removed.iter().collect::<Vec<Vec<u8>>>(),
+    ///     vec![b"foo".to_vec(), b"foobar".to_vec()],
+    /// );
+    /// assert_eq!(set.len(), 1);
+    /// assert!(!set.contains(b"foo"));
+    /// ```
+    pub fn remove_prefix<Q>(&mut self, prefix: &Q) -> Self
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        RadixSet {
+            map: self.map.remove_prefix(prefix),
+        }
+    }
+
+    /// Splits the set and returns the right part of the set. If `inclusive` is true, then the set
+    /// will retain the given key if it exists. Otherwise, the right part of the set will contain
+    /// the key if it exists. The keys are compared in lexographic order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixSet;
+    ///
+    /// let mut set = RadixSet::new();
+    /// set.insert(b"a");
+    /// set.insert(b"ab");
+    /// set.insert(b"b");
+    ///
+    /// let split = set.split_off(b"ab", true);
+    /// assert!(set.contains(b"a"));
+    /// assert!(set.contains(b"ab"));
+    /// assert!(split.contains(b"b"));
+    /// ```
+    pub fn split_off<Q>(&mut self, key: &Q, inclusive: bool) -> Self
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        RadixSet {
+            map: self.map.split_off(key, inclusive),
+        }
+    }
+
+    /// Returns the longest key that is a prefix of a particular key. Returns `None` if no key in
+    /// the set is a prefix of the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::radix::RadixSet;
+    ///
+    /// let mut set = RadixSet::new();
+    /// set.insert(b"10.0");
+    /// set.insert(b"10.0.1");
+    ///
+    /// assert_eq!(
+    ///     set.longest_prefix_match(b"10.0.2.1"),
+    ///     Some(String::from("10.0").into_bytes()),
+    /// );
+    /// assert_eq!(set.longest_prefix_match(b"192.168"), None);
+    /// ```
+    pub fn longest_prefix_match<Q>(&self, key: &Q) -> Option<K>
+    where
+        Q: AsBytes<Key = K> + ?Sized,
+    {
+        self.map.longest_prefix_match(key).map(|pair| pair.0)
+    }
+
     /// Returns the minimum lexographic key of the set. Returns `None` if the set is empty.
     ///
     /// # Examples
@@ -178,7 +365,7 @@ impl RadixSet {
     /// set.insert(b"foobar");
     /// assert_eq!(set.min(), Some(String::from("foo").into_bytes()));
     /// ```
-    pub fn min(&self) -> Option<Vec<u8>> {
+    pub fn min(&self) -> Option<K> {
         self.map.min()
     }
 
@@ -194,7 +381,7 @@ impl RadixSet {
     /// set.insert(b"foobar");
     /// assert_eq!(set.max(), Some(String::from("foobar").into_bytes()));
     /// ```
-    pub fn max(&self) -> Option<Vec<u8>> {
+    pub fn max(&self) -> Option<K> {
         self.map.max()
     }
 
@@ -214,16 +401,19 @@ impl RadixSet {
     /// assert_eq!(iterator.next(), Some(String::from("foobar").into_bytes()));
     /// assert_eq!(iterator.next(), None);
     /// ```
-    pub fn iter(&self) -> RadixSetIter<'_> {
+    pub fn iter(&self) -> RadixSetIter<'_, K> {
         RadixSetIter {
             map_iter: self.map.iter(),
         }
     }
 }
 
-impl IntoIterator for RadixSet {
-    type IntoIter = RadixSetIntoIter;
-    type Item = Vec<u8>;
+impl<K> IntoIterator for RadixSet<K>
+where
+    K: RadixKey,
+{
+    type IntoIter = RadixSetIntoIter<K>;
+    type Item = K;
 
     fn into_iter(self) -> Self::IntoIter {
         Self::IntoIter {
@@ -232,48 +422,131 @@ impl IntoIterator for RadixSet {
     }
 }
 
-impl<'a> IntoIterator for &'a RadixSet {
-    type IntoIter = RadixSetIter<'a>;
-    type Item = Vec<u8>;
+impl<'a, K> IntoIterator for &'a RadixSet<K>
+where
+    K: RadixKey,
+{
+    type IntoIter = RadixSetIter<'a, K>;
+    type Item = K;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
-/// An owning iterator for `RadixSet`.
+/// An owning iterator for `RadixSet<K>`.
 ///
 /// This iterator traverse the elements of the set in lexographic order and yields owned keys.
-pub struct RadixSetIntoIter {
-    map_iter: RadixMapIntoIter<()>,
+pub struct RadixSetIntoIter<K> {
+    map_iter: RadixMapIntoIter<(), K>,
 }
 
-impl Iterator for RadixSetIntoIter {
-    type Item = Vec<u8>;
+impl<K> Iterator for RadixSetIntoIter<K>
+where
+    K: RadixKey,
+{
+    type Item = K;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.map_iter.next().map(|pair| pair.0)
     }
 }
 
-/// An iterator for `RadixSet`.
+/// An iterator for `RadixSet<K>`.
 ///
 /// This iterator traverse the elements of the set in lexographic order and yields owned keys.
-pub struct RadixSetIter<'a> {
-    map_iter: RadixMapIter<'a, ()>,
+pub struct RadixSetIter<'a, K> {
+    map_iter: RadixMapIter<'a, (), K>,
+}
+
+impl<'a, K> Iterator for RadixSetIter<'a, K>
+where
+    K: RadixKey,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.map_iter.next().map(|pair| pair.0)
+    }
+}
+
+/// An iterator over the keys of a `RadixSet<K>` that start with a particular prefix.
+///
+/// This iterator traverse the matching elements of the set in lexographic order and yields owned
+/// keys.
+pub struct RadixSetPrefixIter<'a, K> {
+    map_iter: RadixMapPrefixIter<'a, (), K>,
 }
 
-impl<'a> Iterator for RadixSetIter<'a> {
-    type Item = Vec<u8>;
+impl<'a, K> Iterator for RadixSetPrefixIter<'a, K>
+where
+    K: RadixKey,
+{
+    type Item = K;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.map_iter.next().map(|pair| pair.0)
     }
 }
 
-impl Default for RadixSet {
+impl<K> Clone for RadixSet<K> {
+    fn clone(&self) -> Self {
+        RadixSet {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<K> fmt::Debug for RadixSet<K>
+where
+    K: RadixKey + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K> PartialEq for RadixSet<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<K> Eq for RadixSet<K> {}
+
+impl<K> Default for RadixSet<K> {
     fn default() -> Self {
-        Self::new()
+        RadixSet {
+            map: RadixMap::default(),
+        }
+    }
+}
+
+impl<K, Q> FromIterator<Q> for RadixSet<K>
+where
+    K: RadixKey,
+    Q: AsBytes<Key = K>,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Q>,
+    {
+        RadixSet {
+            map: iter.into_iter().map(|key| (key, ())).collect(),
+        }
+    }
+}
+
+impl<K, Q> Extend<Q> for RadixSet<K>
+where
+    K: RadixKey,
+    Q: AsBytes<Key = K>,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = Q>,
+    {
+        self.map.extend(iter.into_iter().map(|key| (key, ())));
     }
 }
 
@@ -477,6 +750,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_iter() {
+        let mut set: RadixSet = vec![b"b", b"a", b"b"].into_iter().collect();
+        set.extend(vec![b"c".to_vec()]);
+        assert_eq!(
+            set.into_iter().collect::<Vec<Vec<u8>>>(),
+            vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()],
+        );
+    }
+
+    #[test]
+    fn test_iter_prefix() {
+        let mut set = RadixSet::new();
+        set.insert(b"a");
+        set.insert(b"ab");
+        set.insert(b"b");
+        assert_eq!(
+            set.iter_prefix(b"a").collect::<Vec<Vec<u8>>>(),
+            vec![b"a".to_vec(), b"ab".to_vec()],
+        );
+        assert_eq!(set.longest_prefix_match(b"abc"), Some(b"ab".to_vec()));
+    }
+
     #[test]
     fn test_iter() {
         let mut set = RadixSet::new();
@@ -489,4 +785,52 @@ mod tests {
             vec![get_bytes_vec("a"), get_bytes_vec("aa"), get_bytes_vec("ab")],
         );
     }
+
+    #[test]
+    fn test_clone_eq() {
+        let mut set = RadixSet::new();
+        set.insert(b"a");
+        set.insert(b"ab");
+
+        let mut cloned = set.clone();
+        assert_eq!(cloned, set);
+        cloned.insert(b"abc");
+        assert_ne!(cloned, set);
+        cloned.remove(b"abc");
+        assert_eq!(cloned, set);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut set: RadixSet<String> = RadixSet::default();
+        set.insert("b");
+        set.insert("a");
+        assert_eq!(format!("{:?}", set), r#"{"a", "b"}"#);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut set = RadixSet::new();
+        set.insert(b"a");
+        set.insert(b"ab");
+        set.insert(b"abc");
+
+        set.retain(|key| key.len() != 2);
+        assert_eq!(set.len(), 2);
+        assert!(!set.contains(b"ab"));
+        assert!(set.contains(b"abc"));
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut set = RadixSet::new();
+        set.insert(b"b");
+        set.insert(b"a");
+
+        assert_eq!(
+            set.drain().collect::<Vec<Vec<u8>>>(),
+            vec![b"a".to_vec(), b"b".to_vec()],
+        );
+        assert!(set.is_empty());
+    }
 }
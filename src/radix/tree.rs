@@ -1,6 +1,8 @@
 use crate::radix::node::Node;
-use std::cmp::Ordering;
-use std::mem;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::mem;
 
 pub type Tree<T> = Option<Box<Node<T>>>;
 
@@ -22,12 +24,12 @@ pub fn insert<T>(tree: &mut Tree<T>, mut key: &[u8], value: T) -> Option<T> {
         None => match node.key.len().cmp(&key.len()) {
             Ordering::Less => {
                 key = key.split_at(node.key.len()).1;
-                let byte = key[0];
-                if node.contains(byte) {
-                    insert(node.get_mut(byte), key, value)
-                } else {
-                    node.insert_child(Node::new(key.to_vec(), Some(value)));
-                    None
+                match node.get_mut(key[0]) {
+                    Some(child) => insert(child, key, value),
+                    None => {
+                        node.insert_child(Node::new(key.to_vec(), Some(value)));
+                        None
+                    }
                 }
             }
             Ordering::Greater => {
@@ -40,45 +42,92 @@ pub fn insert<T>(tree: &mut Tree<T>, mut key: &[u8], value: T) -> Option<T> {
     }
 }
 
-pub fn remove<T>(tree: &mut Tree<T>, key: &[u8], mut index: usize) -> Option<(Vec<u8>, T)> {
-    let mut next_tree = None;
-    let ret;
-    {
-        let node = match tree {
-            Some(ref mut node) => node,
-            None => return None,
-        };
-        let split_index = node
-            .key
-            .iter()
-            .zip(key[index..].iter())
-            .position(|pair| pair.0 != pair.1);
-        match split_index {
-            Some(_) => return None,
-            None => match node.key.len().cmp(&(key.len() - index)) {
-                Ordering::Less => {
-                    index += node.key.len();
-                    let byte = key[index];
-                    ret = remove(node.get_mut(byte), key, index);
-                    node.merge();
-                    if node.value.is_none() && node.is_leaf() {
-                        next_tree = Some(node.get_replacement_tree());
-                    }
-                }
-                Ordering::Greater => return None,
-                Ordering::Equal => {
-                    ret = node.value.take().map(|value| (key.to_vec(), value));
-                    node.merge();
-                    if node.value.is_none() && node.is_leaf() {
-                        next_tree = Some(node.get_replacement_tree());
-                    }
-                }
-            },
+// Removes the value with `key` from the subtree rooted at `node`. A node is never merged with its
+// own child, so the root of the tree keeps its place. Instead, each node merges and prunes the
+// child that a value was removed from.
+fn remove_node<T>(node: &mut Node<T>, key: &[u8], mut index: usize) -> Option<(Vec<u8>, T)> {
+    let split_index = node
+        .key
+        .iter()
+        .zip(key[index..].iter())
+        .position(|pair| pair.0 != pair.1);
+    match split_index {
+        Some(_) => None,
+        None => match node.key.len().cmp(&(key.len() - index)) {
+            Ordering::Less => {
+                index += node.key.len();
+                let byte = key[index];
+                let ret = {
+                    let child = node.get_mut(byte).and_then(|child| child.as_mut())?;
+                    let ret = remove_node(child, key, index)?;
+                    child.merge();
+                    ret
+                };
+                node.prune_child(byte);
+                Some(ret)
+            }
+            Ordering::Greater => None,
+            Ordering::Equal => node.value.take().map(|value| (key.to_vec(), value)),
+        },
+    }
+}
+
+// Resets the root of the tree to an empty node once it no longer contains any entries, so that
+// later insertions do not split off a leaf without a value.
+fn reset_root<T>(tree: &mut Tree<T>) {
+    if let Some(ref mut node) = tree {
+        if node.value.is_none() && node.is_leaf() {
+            node.key.clear();
         }
     }
-    if let Some(next_tree) = next_tree {
-        *tree = next_tree;
+}
+
+pub fn remove<T>(tree: &mut Tree<T>, key: &[u8], index: usize) -> Option<(Vec<u8>, T)> {
+    let ret = remove_node(tree.as_mut()?, key, index);
+    reset_root(tree);
+    ret
+}
+
+fn retain_node<T, F>(node: &mut Node<T>, prefix: &mut Vec<u8>, f: &mut F) -> usize
+where
+    F: FnMut(&[u8], &mut T) -> bool,
+{
+    let prefix_len = prefix.len();
+    prefix.extend_from_slice(node.key.as_slice());
+    let mut ret = 0;
+    let keep = match node.value {
+        Some(ref mut value) => f(prefix, value),
+        None => true,
+    };
+    if !keep {
+        node.value = None;
+        ret += 1;
+    }
+
+    let mut bytes = Vec::new();
+    for child in node.children.iter_mut() {
+        bytes.push(child.key[0]);
+        ret += retain_node(child, prefix, f);
     }
+    for byte in bytes {
+        node.prune_child(byte);
+    }
+    node.merge();
+    prefix.truncate(prefix_len);
+    ret
+}
+
+// Removes all values whose keys and values do not satisfy `f` and returns the number of removed
+// values. The values are visited in lexographic order of their keys.
+pub fn retain<T, F>(tree: &mut Tree<T>, f: &mut F) -> usize
+where
+    F: FnMut(&[u8], &mut T) -> bool,
+{
+    let ret = match tree {
+        Some(ref mut node) => retain_node(node, &mut Vec::new(), f),
+        None => 0,
+    };
+    reset_root(tree);
     ret
 }
 
@@ -120,7 +169,10 @@ pub fn get_mut<'a, T>(tree: &'a mut Tree<T>, key: &[u8], mut index: usize) -> Op
         None => match node.key.len().cmp(&(key.len() - index)) {
             Ordering::Less => {
                 index += node.key.len();
-                get_mut(node.get_mut(key[index]), key, index)
+                match node.get_mut(key[index]) {
+                    Some(child) => get_mut(child, key, index),
+                    None => None,
+                }
             }
             Ordering::Greater => None,
             Ordering::Equal => node.value.as_mut(),
@@ -175,32 +227,226 @@ pub fn get_longest_prefix<T>(
     }
 }
 
-pub fn min<T>(tree: &Tree<T>, mut curr_key: Vec<u8>) -> Option<Vec<u8>> {
-    let node = match tree {
-        Some(ref node) => node,
-        None => return None,
-    };
+// Returns the highest node whose subtree contains exactly the keys that start with `prefix` and the
+// full key of that node.
+pub fn get_prefix_node<'a, T>(tree: &'a Tree<T>, prefix: &[u8]) -> Option<(&'a Node<T>, Vec<u8>)> {
+    let mut curr_tree = tree;
+    let mut curr_key = Vec::new();
+    while let Some(ref node) = curr_tree {
+        let rest = &prefix[curr_key.len()..];
+        if node.key.len() >= rest.len() {
+            if node.key.starts_with(rest) {
+                curr_key.extend_from_slice(node.key.as_slice());
+                return Some((node, curr_key));
+            }
+            return None;
+        }
 
-    curr_key.extend_from_slice(node.key.as_slice());
+        if !rest.starts_with(node.key.as_slice()) {
+            return None;
+        }
+        curr_key.extend_from_slice(node.key.as_slice());
+        curr_tree = node.get(prefix[curr_key.len()]);
+    }
+    None
+}
 
-    if node.value.is_some() {
-        Some(curr_key)
-    } else {
-        min(node.min(), curr_key)
+// Returns the length of the longest key that is a prefix of `key` and its associated value.
+pub fn longest_prefix_match<'a, T>(tree: &'a Tree<T>, key: &[u8]) -> Option<(usize, &'a T)> {
+    let mut curr_tree = tree;
+    let mut index = 0;
+    let mut ret = None;
+    while let Some(ref node) = curr_tree {
+        if !key[index..].starts_with(node.key.as_slice()) {
+            break;
+        }
+        index += node.key.len();
+        if let Some(ref value) = node.value {
+            ret = Some((index, value));
+        }
+        if index == key.len() {
+            break;
+        }
+        curr_tree = node.get(key[index]);
+    }
+    ret
+}
+
+pub fn min<T>(tree: &Tree<T>, mut curr_key: Vec<u8>) -> Option<Vec<u8>> {
+    let mut curr_node = tree.as_deref();
+    while let Some(node) = curr_node {
+        curr_key.extend_from_slice(node.key.as_slice());
+        if node.value.is_some() {
+            return Some(curr_key);
+        }
+        curr_node = node.min();
     }
+    None
 }
 
 pub fn max<T>(tree: &Tree<T>, mut curr_key: Vec<u8>) -> Option<Vec<u8>> {
-    let node = match tree {
-        Some(ref node) => node,
+    let mut curr_node = tree.as_deref();
+    while let Some(node) = curr_node {
+        curr_key.extend_from_slice(node.key.as_slice());
+        if node.value.is_some() && node.is_leaf() {
+            return Some(curr_key);
+        }
+        curr_node = node.max();
+    }
+    None
+}
+
+// Returns the number of values in the subtree rooted at `node`.
+pub fn size<T>(node: &Node<T>) -> usize {
+    let mut ret = if node.value.is_some() { 1 } else { 0 };
+    for child in node.children.iter() {
+        ret += size(child);
+    }
+    ret
+}
+
+// Detaches the child of `node` that contains exactly the keys that start with `prefix`. The bytes
+// of `prefix` before `index` have already been matched by `node` and its ancestors.
+fn remove_prefix_node<T>(node: &mut Node<T>, prefix: &[u8], index: usize) -> Tree<T> {
+    let byte = prefix[index];
+    let rest = &prefix[index..];
+    let detach = match node.get(byte) {
+        Some(ref child) if child.key.len() >= rest.len() => {
+            if !child.key.starts_with(rest) {
+                return None;
+            }
+            true
+        }
+        Some(ref child) => {
+            if !rest.starts_with(child.key.as_slice()) {
+                return None;
+            }
+            false
+        }
         None => return None,
     };
 
-    curr_key.extend_from_slice(node.key.as_slice());
-
-    if node.value.is_some() && node.is_leaf() {
-        Some(curr_key)
+    let ret = if detach {
+        let mut child = node.children.remove(byte).expect("Expected child.");
+        let mut key = prefix[..index].to_vec();
+        key.append(&mut child.key);
+        child.key = key;
+        Some(child)
     } else {
-        max(node.max(), curr_key)
+        let child = node
+            .get_mut(byte)
+            .and_then(|child| child.as_mut())
+            .expect("Expected child.");
+        let child_index = index + child.key.len();
+        let ret = remove_prefix_node(child, prefix, child_index);
+        node.prune_child(byte);
+        ret
+    };
+    node.merge();
+    ret
+}
+
+// Detaches the subtree that contains exactly the keys that start with `prefix` and returns it. The
+// key of the root of the returned subtree is its full key.
+pub fn remove_prefix<T>(tree: &mut Tree<T>, prefix: &[u8]) -> Tree<T> {
+    let node = tree.as_mut()?;
+    if node.key.len() >= prefix.len() {
+        if !node.key.starts_with(prefix) {
+            return None;
+        }
+        return tree.replace(Box::new(Node::new(Vec::new(), None)));
+    }
+    if !prefix.starts_with(node.key.as_slice()) {
+        return None;
+    }
+    let index = node.key.len();
+    let ret = remove_prefix_node(node, prefix, index);
+    reset_root(tree);
+    ret
+}
+
+enum Split<T> {
+    Left,
+    Right,
+    Both(Box<Node<T>>),
+}
+
+// Splits the subtree rooted at `node` around `key`. The bytes of `key` before `index` have already
+// been matched by the ancestors of `node`. If the subtree is split, the right part of the subtree
+// is returned with the same key as `node`.
+fn split_node<T>(node: &mut Node<T>, key: &[u8], index: usize, inclusive: bool) -> Split<T> {
+    let rest = &key[index..];
+    let split_index = node
+        .key
+        .iter()
+        .zip(rest.iter())
+        .position(|pair| pair.0 != pair.1);
+    if let Some(split_index) = split_index {
+        if node.key[split_index] < rest[split_index] {
+            return Split::Left;
+        }
+        return Split::Right;
+    }
+
+    match node.key.len().cmp(&rest.len()) {
+        Ordering::Greater => return Split::Right,
+        Ordering::Equal if !inclusive => return Split::Right,
+        _ => {}
     }
+
+    let mut right = Node::new(node.key.clone(), None);
+    let child_index = index + node.key.len();
+    let byte = key.get(child_index).cloned();
+    let bytes: Vec<u8> = node
+        .children
+        .iter()
+        .map(|child| child.key[0])
+        .filter(|child_byte| byte.is_none_or(|byte| *child_byte > byte))
+        .collect();
+    for child_byte in bytes {
+        let child = node.children.remove(child_byte).expect("Expected child.");
+        right.children.insert(child);
+    }
+
+    if let Some(byte) = byte {
+        let split = match node.get_mut(byte).and_then(|child| child.as_mut()) {
+            Some(child) => split_node(child, key, child_index, inclusive),
+            None => Split::Left,
+        };
+        match split {
+            Split::Left => {}
+            Split::Right => {
+                let child = node.children.remove(byte).expect("Expected child.");
+                right.children.insert(child);
+            }
+            Split::Both(child) => {
+                node.prune_child(byte);
+                right.children.insert(child);
+            }
+        }
+    }
+
+    node.merge();
+    if right.is_leaf() {
+        return Split::Left;
+    }
+    right.merge();
+    Split::Both(Box::new(right))
+}
+
+// Splits the tree around `key` and returns the right part of the tree. If `inclusive` is true,
+// then the tree will retain `key` if it exists. Otherwise, the returned tree will contain `key` if
+// it exists.
+pub fn split_off<T>(tree: &mut Tree<T>, key: &[u8], inclusive: bool) -> Tree<T> {
+    let split = match tree {
+        Some(ref mut node) => split_node(node, key, 0, inclusive),
+        None => return None,
+    };
+    let ret = match split {
+        Split::Left => None,
+        Split::Right => tree.replace(Box::new(Node::new(Vec::new(), None))),
+        Split::Both(node) => Some(node),
+    };
+    reset_root(tree);
+    ret
 }
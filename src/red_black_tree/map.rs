@@ -1,8 +1,14 @@
 use crate::entry::Entry;
+use crate::node_arena::{NodeArena, NodeRef};
+use crate::ordered::OrderedMap;
 use crate::red_black_tree::node::{Color, Node};
 use crate::red_black_tree::tree;
-use std::borrow::Borrow;
-use std::ops::{Index, IndexMut};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::{Index, IndexMut};
 
 /// An ordered map implemented using an avl tree.
 ///
@@ -31,7 +37,8 @@ use std::ops::{Index, IndexMut};
 /// assert_eq!(map.remove(&1), None);
 /// ```
 pub struct RedBlackMap<T, U> {
-    tree: tree::Tree<T, U>,
+    arena: tree::Arena<T, U>,
+    tree: tree::Tree,
     len: usize,
 }
 
@@ -46,7 +53,33 @@ impl<T, U> RedBlackMap<T, U> {
     /// let map: RedBlackMap<u32, u32> = RedBlackMap::new();
     /// ```
     pub fn new() -> Self {
-        RedBlackMap { tree: None, len: 0 }
+        RedBlackMap {
+            arena: NodeArena::boxed(),
+            tree: None,
+            len: 0,
+        }
+    }
+
+    /// Constructs a new, empty `RedBlackMap<T, U>` that allocates its nodes from a `TypedArena` to
+    /// improve cache locality. Because nodes cannot be shared between arenas, `split_off` and the
+    /// operations that combine two maps take linear time in the number of nodes that are moved
+    /// between maps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut map = RedBlackMap::with_arena();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// ```
+    pub fn with_arena() -> Self {
+        RedBlackMap {
+            arena: NodeArena::pooled(),
+            tree: None,
+            len: 0,
+        }
     }
 
     /// Inserts a key-value pair into the map. If the key already exists in the map, it will return
@@ -68,19 +101,20 @@ impl<T, U> RedBlackMap<T, U> {
         T: Ord,
     {
         let RedBlackMap {
+            ref mut arena,
             ref mut tree,
             ref mut len,
         } = self;
         let new_node = Node::new(key, value);
         *len += 1;
-        let ret = tree::insert(tree, new_node).and_then(|entry| {
+        let ret = tree::insert(arena, tree, new_node).and_then(|entry| {
             let Entry { key, value } = entry;
             *len -= 1;
             Some((key, value))
         });
 
-        if let Some(ref mut node) = tree {
-            node.color = Color::Black;
+        if let Some(node) = *tree {
+            arena[node].color = Color::Black;
         }
 
         ret
@@ -105,20 +139,21 @@ impl<T, U> RedBlackMap<T, U> {
         V: Ord + ?Sized,
     {
         let RedBlackMap {
+            ref mut arena,
             ref mut tree,
             ref mut len,
         } = self;
 
-        tree::fix_root(tree);
+        tree::fix_root(arena, *tree);
 
-        let ret = tree::remove(tree, &key).and_then(|entry| {
+        let ret = tree::remove(arena, tree, key).and_then(|entry| {
             let Entry { key, value } = entry;
             *len -= 1;
             Some((key, value))
         });
 
-        if let Some(ref mut node) = tree {
-            node.color = Color::Black;
+        if let Some(node) = *tree {
+            arena[node].color = Color::Black;
         }
 
         ret
@@ -162,7 +197,7 @@ impl<T, U> RedBlackMap<T, U> {
         T: Borrow<V>,
         V: Ord + ?Sized,
     {
-        tree::get(&self.tree, key).map(|entry| &entry.value)
+        tree::get(&self.arena, self.tree, key).map(|entry| &entry.value)
     }
 
     /// Returns a mutable reference to the value associated with a particular key. Returns `None`
@@ -183,7 +218,7 @@ impl<T, U> RedBlackMap<T, U> {
         T: Borrow<V>,
         V: Ord + ?Sized,
     {
-        tree::get_mut(&mut self.tree, key).map(|entry| &mut entry.value)
+        tree::get_mut(&mut self.arena, self.tree, key).map(|entry| &mut entry.value)
     }
 
     /// Returns the number of elements in the map.
@@ -229,10 +264,67 @@ impl<T, U> RedBlackMap<T, U> {
     /// assert_eq!(map.is_empty(), true);
     /// ```
     pub fn clear(&mut self) {
+        self.arena.release(self.tree.take());
+        self.arena = self.arena.empty();
         self.tree = None;
         self.len = 0;
     }
 
+    /// Retains only the key-value pairs that satisfy a predicate. The key-value pairs are visited
+    /// in ascending order of their keys. The tree is rebalanced by joining the retained subtrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut map = RedBlackMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.insert(3, 3);
+    ///
+    /// map.retain(|key, value| {
+    ///     *value += 1;
+    ///     key % 2 == 1
+    /// });
+    /// assert_eq!(map.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&1, &2), (&3, &4)]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T, &mut U) -> bool,
+    {
+        self.tree = tree::retain(&mut self.arena, self.tree, &mut f);
+        if let Some(node) = self.tree {
+            self.arena[node].color = Color::Black;
+        }
+        self.len = self.arena.len();
+    }
+
+    /// Clears the map, returning all key-value pairs as an iterator. The iterator yields the
+    /// key-value pairs in ascending order of their keys, and the map can be reused afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut map = RedBlackMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// assert_eq!(map.drain().collect::<Vec<(u32, u32)>>(), vec![(1, 1), (2, 2)]);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> RedBlackMapIntoIter<T, U> {
+        self.len = 0;
+        let arena = self.arena.empty();
+        RedBlackMapIntoIter {
+            arena: mem::replace(&mut self.arena, arena),
+            current: self.tree.take(),
+            stack: Vec::new(),
+        }
+    }
+
     /// Returns a key in the map that is less than or equal to a particular key. Returns `None` if
     /// such a key does not exist.
     ///
@@ -251,7 +343,7 @@ impl<T, U> RedBlackMap<T, U> {
         T: Borrow<V>,
         V: Ord + ?Sized,
     {
-        tree::floor(&self.tree, key).map(|entry| &entry.key)
+        tree::floor(&self.arena, self.tree, key, true).map(|entry| &entry.key)
     }
 
     /// Returns a key in the map that is greater than or equal to a particular key. Returns `None`
@@ -272,7 +364,143 @@ impl<T, U> RedBlackMap<T, U> {
         T: Borrow<V>,
         V: Ord + ?Sized,
     {
-        tree::ceil(&self.tree, key).map(|entry| &entry.key)
+        tree::ceil(&self.arena, self.tree, key, true).map(|entry| &entry.key)
+    }
+
+    /// Returns the key-value pair in the map with the greatest key that is less than or equal to a
+    /// particular key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut map = RedBlackMap::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.floor_entry(&0), None);
+    /// assert_eq!(map.floor_entry(&2), Some((&1, &1)));
+    /// ```
+    pub fn floor_entry<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::floor(&self.arena, self.tree, key, true).map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Returns the key and a mutable reference to the value of the key-value pair in the map with
+    /// the greatest key that is less than or equal to a particular key. Returns `None` if such a
+    /// key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut map = RedBlackMap::new();
+    /// map.insert(1, 1);
+    /// if let Some((_, value)) = map.floor_entry_mut(&2) {
+    ///     *value = 2;
+    /// }
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// ```
+    pub fn floor_entry_mut<V>(&mut self, key: &V) -> Option<(&T, &mut U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::floor_mut(&mut self.arena, self.tree, key, true)
+            .map(|entry| (&entry.key, &mut entry.value))
+    }
+
+    /// Returns the key-value pair in the map with the least key that is greater than or equal to a
+    /// particular key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut map = RedBlackMap::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.ceil_entry(&2), None);
+    /// assert_eq!(map.ceil_entry(&0), Some((&1, &1)));
+    /// ```
+    pub fn ceil_entry<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::ceil(&self.arena, self.tree, key, true).map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Returns the key and a mutable reference to the value of the key-value pair in the map with
+    /// the least key that is greater than or equal to a particular key. Returns `None` if such a
+    /// key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut map = RedBlackMap::new();
+    /// map.insert(1, 1);
+    /// if let Some((_, value)) = map.ceil_entry_mut(&0) {
+    ///     *value = 2;
+    /// }
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// ```
+    pub fn ceil_entry_mut<V>(&mut self, key: &V) -> Option<(&T, &mut U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::ceil_mut(&mut self.arena, self.tree, key, true)
+            .map(|entry| (&entry.key, &mut entry.value))
+    }
+
+    /// Returns the key-value pair in the map with the greatest key that is less than a particular
+    /// key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut map = RedBlackMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.next_below(&1), None);
+    /// assert_eq!(map.next_below(&2), Some((&1, &1)));
+    /// ```
+    pub fn next_below<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::floor(&self.arena, self.tree, key, false).map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Returns the key-value pair in the map with the least key that is greater than a particular
+    /// key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut map = RedBlackMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.next_above(&2), None);
+    /// assert_eq!(map.next_above(&1), Some((&2, &2)));
+    /// ```
+    pub fn next_above<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::ceil(&self.arena, self.tree, key, false).map(|entry| (&entry.key, &entry.value))
     }
 
     /// Returns the minimum key of the map. Returns `None` if the map is empty.
@@ -291,7 +519,7 @@ impl<T, U> RedBlackMap<T, U> {
     where
         T: Ord,
     {
-        tree::min(&self.tree).map(|entry| &entry.key)
+        tree::min(&self.arena, self.tree).map(|entry| &entry.key)
     }
 
     /// Returns the maximum key of the map. Returns `None` if the map is empty.
@@ -310,7 +538,319 @@ impl<T, U> RedBlackMap<T, U> {
     where
         T: Ord,
     {
-        tree::max(&self.tree).map(|entry| &entry.key)
+        tree::max(&self.arena, self.tree).map(|entry| &entry.key)
+    }
+
+    /// Removes and returns the key-value pair with the minimum key in the map. Returns `None` if
+    /// the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut map = RedBlackMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.pop_min(), Some((1, 1)));
+    /// assert_eq!(map.pop_min(), Some((3, 3)));
+    /// assert_eq!(map.pop_min(), None);
+    /// ```
+    pub fn pop_min(&mut self) -> Option<(T, U)> {
+        let RedBlackMap {
+            ref mut arena,
+            ref mut tree,
+            ref mut len,
+        } = self;
+
+        tree::fix_root(arena, *tree);
+
+        let ret = tree::pop_min(arena, tree).map(|entry| {
+            let Entry { key, value } = entry;
+            *len -= 1;
+            (key, value)
+        });
+
+        if let Some(node) = *tree {
+            arena[node].color = Color::Black;
+        }
+
+        ret
+    }
+
+    /// Removes and returns the key-value pair with the maximum key in the map. Returns `None` if
+    /// the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut map = RedBlackMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.pop_max(), Some((3, 3)));
+    /// assert_eq!(map.pop_max(), Some((1, 1)));
+    /// assert_eq!(map.pop_max(), None);
+    /// ```
+    pub fn pop_max(&mut self) -> Option<(T, U)> {
+        let RedBlackMap {
+            ref mut arena,
+            ref mut tree,
+            ref mut len,
+        } = self;
+
+        tree::fix_root(arena, *tree);
+
+        let ret = tree::pop_max(arena, tree).map(|entry| {
+            let Entry { key, value } = entry;
+            *len -= 1;
+            (key, value)
+        });
+
+        if let Some(node) = *tree {
+            arena[node].color = Color::Black;
+        }
+
+        ret
+    }
+
+    // Replaces the tree of the map with a tree whose nodes are the only nodes in the storage of
+    // the map. The root of the tree may be red.
+    fn set_tree(&mut self, tree: tree::Tree) {
+        if let Some(node) = tree {
+            self.arena[node].color = Color::Black;
+        }
+        self.tree = tree;
+        self.len = self.arena.len();
+    }
+
+    /// Splits the map and returns the right part of the map. If `inclusive` is true, then the map
+    /// will retain the given key if it exists. Otherwise, the right part of the map will contain
+    /// the key if it exists. The map is split in logarithmic time. If the map allocates its nodes
+    /// from an arena, the nodes of the right part are also moved to the arena of the new map in
+    /// linear time in its length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut map = RedBlackMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.insert(3, 3);
+    ///
+    /// let split = map.split_off(&2, true);
+    /// assert_eq!(map[&1], 1);
+    /// assert_eq!(map[&2], 2);
+    /// assert_eq!(split[&3], 3);
+    /// ```
+    pub fn split_off<V>(&mut self, key: &V, inclusive: bool) -> Self
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        let (left, right) = tree::split(&mut self.arena, self.tree, key, inclusive);
+        let mut ret = RedBlackMap {
+            arena: self.arena.empty(),
+            tree: None,
+            len: 0,
+        };
+        let (right, _) = tree::transfer(&mut self.arena, &mut ret.arena, right);
+        self.set_tree(left);
+        ret.set_tree(right);
+        ret
+    }
+
+    // Combines the trees of two maps with `f`. The nodes of the smaller map are moved to the
+    // storage of the larger map, and `f` is called with the tree of the larger map first and
+    // whether the maps were swapped to do so.
+    fn combine<F>(mut left: Self, mut right: Self, f: F) -> Self
+    where
+        F: FnOnce(&mut tree::Arena<T, U>, tree::Tree, tree::Tree, bool) -> tree::Tree,
+    {
+        let tree = if left.len < right.len {
+            let (left_tree, _) =
+                tree::transfer(&mut left.arena, &mut right.arena, left.tree.take());
+            let right_tree = right.tree.take();
+            mem::swap(&mut left.arena, &mut right.arena);
+            f(&mut left.arena, right_tree, left_tree, true)
+        } else {
+            let (right_tree, _) =
+                tree::transfer(&mut right.arena, &mut left.arena, right.tree.take());
+            let left_tree = left.tree.take();
+            f(&mut left.arena, left_tree, right_tree, false)
+        };
+        left.set_tree(tree);
+        left
+    }
+
+    /// Joins two maps. All keys in `left` must be less than all keys in `right`. The maps are
+    /// joined in logarithmic time. If either map allocates its nodes from an arena, the nodes of
+    /// the smaller map are also moved to the storage of the larger map in linear time in its
+    /// length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the maximum key in `left` is not less than the minimum key in `right`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut n = RedBlackMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = RedBlackMap::new();
+    /// m.insert(3, 3);
+    ///
+    /// let joined = RedBlackMap::join(n, m);
+    /// assert_eq!(
+    ///     joined.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1), (&2, &2), (&3, &3)],
+    /// );
+    /// ```
+    pub fn join(left: Self, right: Self) -> Self
+    where
+        T: Ord,
+    {
+        if let (Some(max), Some(min)) = (left.max(), right.min()) {
+            assert!(
+                max < min,
+                "Error: all keys in `left` must be less than all keys in `right`."
+            );
+        }
+        Self::combine(left, right, |arena, tree, other_tree, swapped| {
+            if swapped {
+                tree::join_subtrees(arena, other_tree, tree)
+            } else {
+                tree::join_subtrees(arena, tree, other_tree)
+            }
+        })
+    }
+
+    /// Returns the union of two maps. If there is a key that is found in both `left` and `right`,
+    /// the union will contain the value associated with the key in `left`. The maps are combined
+    /// by splitting and joining their trees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut n = RedBlackMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = RedBlackMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// let union = RedBlackMap::union(n, m);
+    /// assert_eq!(
+    ///     union.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1), (&2, &2), (&3, &3)],
+    /// );
+    /// ```
+    pub fn union(left: Self, right: Self) -> Self
+    where
+        T: Ord,
+    {
+        Self::combine(left, right, tree::union)
+    }
+
+    /// Returns the intersection of two maps. If there is a key that is found in both `left` and
+    /// `right`, the intersection will contain the value associated with the key in `left`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut n = RedBlackMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = RedBlackMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// let intersection = RedBlackMap::intersection(n, m);
+    /// assert_eq!(
+    ///     intersection.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&2, &2)],
+    /// );
+    /// ```
+    pub fn intersection(left: Self, right: Self) -> Self
+    where
+        T: Ord,
+    {
+        Self::combine(left, right, tree::intersection)
+    }
+
+    /// Returns the difference of `left` and `right`. The returned map will contain all entries
+    /// that do not have a key in `right`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut n = RedBlackMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = RedBlackMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// let difference = RedBlackMap::difference(n, m);
+    /// assert_eq!(
+    ///     difference.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1)],
+    /// );
+    /// ```
+    pub fn difference(left: Self, right: Self) -> Self
+    where
+        T: Ord,
+    {
+        Self::combine(left, right, |arena, tree, other_tree, swapped| {
+            tree::difference(arena, tree, other_tree, swapped, false)
+        })
+    }
+
+    /// Returns the symmetric difference of `left` and `right`. The returned map will contain all
+    /// entries that exist in one map, but not both maps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackMap;
+    ///
+    /// let mut n = RedBlackMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = RedBlackMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// let symmetric_difference = RedBlackMap::symmetric_difference(n, m);
+    /// assert_eq!(
+    ///     symmetric_difference.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1), (&3, &3)],
+    /// );
+    /// ```
+    pub fn symmetric_difference(left: Self, right: Self) -> Self
+    where
+        T: Ord,
+    {
+        Self::combine(left, right, |arena, tree, other_tree, swapped| {
+            tree::difference(arena, tree, other_tree, swapped, true)
+        })
     }
 
     /// Returns an iterator over the map. The iterator will yield key-value pairs using in-order
@@ -332,7 +872,8 @@ impl<T, U> RedBlackMap<T, U> {
     /// ```
     pub fn iter(&self) -> RedBlackMapIter<'_, T, U> {
         RedBlackMapIter {
-            current: &self.tree,
+            arena: &self.arena,
+            current: self.tree,
             stack: Vec::new(),
         }
     }
@@ -360,7 +901,8 @@ impl<T, U> RedBlackMap<T, U> {
     /// ```
     pub fn iter_mut(&mut self) -> RedBlackMapIterMut<'_, T, U> {
         RedBlackMapIterMut {
-            current: self.tree.as_mut().map(|node| &mut **node),
+            arena: &mut self.arena,
+            current: self.tree,
             stack: Vec::new(),
         }
     }
@@ -370,11 +912,8 @@ impl<T, U> IntoIterator for RedBlackMap<T, U> {
     type IntoIter = RedBlackMapIntoIter<T, U>;
     type Item = (T, U);
 
-    fn into_iter(self) -> Self::IntoIter {
-        Self::IntoIter {
-            current: self.tree,
-            stack: Vec::new(),
-        }
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.drain()
     }
 }
 
@@ -408,36 +947,44 @@ where
 ///
 /// This iterator traverses the elements of the map in-order and yields owned entries.
 pub struct RedBlackMapIntoIter<T, U> {
-    current: tree::Tree<T, U>,
-    stack: Vec<Node<T, U>>,
+    arena: tree::Arena<T, U>,
+    current: tree::Tree,
+    stack: Vec<NodeRef>,
 }
 
 impl<T, U> Iterator for RedBlackMapIntoIter<T, U> {
     type Item = (T, U);
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(mut node) = self.current.take() {
-            self.current = node.left.take();
-            self.stack.push(*node);
+        while let Some(node) = self.current {
+            self.current = self.arena[node].left;
+            self.stack.push(node);
         }
         self.stack.pop().map(|node| {
             let Node {
                 entry: Entry { key, value },
                 right,
                 ..
-            } = node;
+            } = self.arena.free(&node);
             self.current = right;
             (key, value)
         })
     }
 }
 
+impl<T, U> Drop for RedBlackMapIntoIter<T, U> {
+    fn drop(&mut self) {
+        while self.next().is_some() {}
+    }
+}
+
 /// An iterator for `RedBlackMap<T, U>`.
 ///
 /// This iterator traverses the elements of the map in-order and yields immutable references.
 pub struct RedBlackMapIter<'a, T, U> {
-    current: &'a tree::Tree<T, U>,
-    stack: Vec<&'a Node<T, U>>,
+    arena: &'a tree::Arena<T, U>,
+    current: tree::Tree,
+    stack: Vec<NodeRef>,
 }
 
 impl<'a, T, U> Iterator for RedBlackMapIter<'a, T, U>
@@ -448,31 +995,30 @@ where
     type Item = (&'a T, &'a U);
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(ref node) = self.current {
-            self.current = &node.left;
+        let arena = self.arena;
+        while let Some(node) = self.current {
+            self.current = arena[node].left;
             self.stack.push(node);
         }
         self.stack.pop().map(|node| {
             let Node {
                 entry: Entry { ref key, ref value },
-                ref right,
+                right,
                 ..
-            } = node;
+            } = arena[node];
             self.current = right;
             (key, value)
         })
     }
 }
 
-type BorrowedIterEntryMut<'a, T, U> = Option<(&'a mut Entry<T, U>, BorrowedTreeMut<'a, T, U>)>;
-type BorrowedTreeMut<'a, T, U> = Option<&'a mut Node<T, U>>;
-
 /// A mutable iterator for `RedBlackMap<T, U>`.
 ///
 /// This iterator traverses the elements of the map in-order and yields mutable references.
 pub struct RedBlackMapIterMut<'a, T, U> {
-    current: Option<&'a mut Node<T, U>>,
-    stack: Vec<BorrowedIterEntryMut<'a, T, U>>,
+    arena: &'a mut tree::Arena<T, U>,
+    current: tree::Tree,
+    stack: Vec<NodeRef>,
 }
 
 impl<'a, T, U> Iterator for RedBlackMapIterMut<'a, T, U>
@@ -483,37 +1029,170 @@ where
     type Item = (&'a T, &'a mut U);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let RedBlackMapIterMut {
-            ref mut current,
-            ref mut stack,
-        } = self;
-        while current.is_some() {
-            stack.push(current.take().map(|node| {
-                *current = node.left.as_mut().map(|node| &mut **node);
-                (&mut node.entry, node.right.as_mut().map(|node| &mut **node))
-            }));
+        while let Some(node) = self.current {
+            self.current = self.arena[node].left;
+            self.stack.push(node);
         }
-        stack.pop().and_then(|pair_opt| match pair_opt {
-            Some(pair) => {
-                let (entry, right) = pair;
-                let Entry {
-                    ref key,
-                    ref mut value,
-                } = entry;
-                *current = right;
-                Some((key, value))
-            }
-            None => None,
+        self.stack.pop().map(|node| {
+            // each node is yielded at most once, so the mutable references are disjoint
+            let node = unsafe { &mut *(&mut self.arena[node] as *mut Node<T, U>) };
+            let Node {
+                entry:
+                    Entry {
+                        ref key,
+                        ref mut value,
+                    },
+                right,
+                ..
+            } = node;
+            self.current = *right;
+            (key, value)
         })
     }
 }
 
+impl<T, U> OrderedMap<T, U> for RedBlackMap<T, U>
+where
+    T: Ord,
+{
+    type Iter<'a>
+        = RedBlackMapIter<'a, T, U>
+    where
+        Self: 'a,
+        T: 'a,
+        U: 'a;
+
+    fn insert(&mut self, key: T, value: U) -> Option<(T, U)> {
+        RedBlackMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &T) -> Option<(T, U)> {
+        RedBlackMap::remove(self, key)
+    }
+
+    fn contains_key(&self, key: &T) -> bool {
+        RedBlackMap::contains_key(self, key)
+    }
+
+    fn get(&self, key: &T) -> Option<&U> {
+        RedBlackMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &T) -> Option<&mut U> {
+        RedBlackMap::get_mut(self, key)
+    }
+
+    fn len(&self) -> usize {
+        RedBlackMap::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        RedBlackMap::is_empty(self)
+    }
+
+    fn floor(&self, key: &T) -> Option<&T> {
+        RedBlackMap::floor(self, key)
+    }
+
+    fn ceil(&self, key: &T) -> Option<&T> {
+        RedBlackMap::ceil(self, key)
+    }
+
+    fn min(&self) -> Option<&T> {
+        RedBlackMap::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        RedBlackMap::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        RedBlackMap::iter(self)
+    }
+}
+
+impl<T, U> Clone for RedBlackMap<T, U>
+where
+    T: Clone,
+    U: Clone,
+{
+    fn clone(&self) -> Self {
+        let (arena, tree) = self.arena.clone_tree(self.tree);
+        RedBlackMap {
+            arena,
+            tree,
+            len: self.len,
+        }
+    }
+}
+
+impl<T, U> Drop for RedBlackMap<T, U> {
+    fn drop(&mut self) {
+        self.arena.release(self.tree.take());
+    }
+}
+
+impl<T, U> fmt::Debug for RedBlackMap<T, U>
+where
+    T: fmt::Debug,
+    U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, U> PartialEq for RedBlackMap<T, U>
+where
+    T: PartialEq,
+    U: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T, U> Eq for RedBlackMap<T, U>
+where
+    T: Eq,
+    U: Eq,
+{
+}
+
 impl<T, U> Default for RedBlackMap<T, U> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl<T, U> FromIterator<(T, U)> for RedBlackMap<T, U>
+where
+    T: Ord,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (T, U)>,
+    {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<T, U> Extend<(T, U)> for RedBlackMap<T, U>
+where
+    T: Ord,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (T, U)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
 impl<'a, T, U, V> Index<&'a V> for RedBlackMap<T, U>
 where
     T: Borrow<V>,
@@ -539,6 +1218,8 @@ where
 #[cfg(test)]
 mod tests {
     use super::RedBlackMap;
+    use crate::red_black_tree::node::Color;
+    use crate::red_black_tree::tree;
 
     #[test]
     fn test_len_empty() {
@@ -623,6 +1304,67 @@ mod tests {
         assert_eq!(map.ceil(&6), None);
     }
 
+    #[test]
+    fn test_floor_ceil_entry() {
+        let mut map = RedBlackMap::new();
+        for key in (0..100u32).rev() {
+            map.insert(key * 2, key * 2);
+        }
+
+        let to_key = |entry: Option<(&u32, &u32)>| {
+            entry.map(|(key, value)| {
+                assert_eq!(key, value);
+                *key
+            })
+        };
+        for key in 0..200 {
+            assert_eq!(to_key(map.floor_entry(&key)), Some(key / 2 * 2));
+            assert_eq!(
+                to_key(map.ceil_entry(&key)),
+                Some(key.div_ceil(2) * 2).filter(|key| *key < 200),
+            );
+            assert_eq!(
+                to_key(map.next_below(&key)),
+                key.checked_sub(1).map(|key| key / 2 * 2),
+            );
+            assert_eq!(
+                to_key(map.next_above(&key)),
+                Some(key / 2 * 2 + 2).filter(|key| *key < 200),
+            );
+        }
+
+        *map.floor_entry_mut(&5).unwrap().1 = 0;
+        *map.ceil_entry_mut(&5).unwrap().1 = 0;
+        assert_eq!(map.get(&4), Some(&0));
+        assert_eq!(map.get(&6), Some(&0));
+        assert_eq!(map.floor_entry_mut(&200).map(|(key, _)| *key), Some(198));
+        assert!(map.ceil_entry_mut(&199).is_none());
+    }
+
+    #[test]
+    fn test_pop_min_max() {
+        let mut map = RedBlackMap::new();
+        for key in 0..1_000u32 {
+            map.insert(key * 7_919 % 1_000, key);
+        }
+
+        let (mut min, mut max) = (0, 999);
+        while min <= max {
+            let (key, _) = map.pop_min().unwrap();
+            assert_eq!(key, min);
+            min += 1;
+            let (key, _) = map.pop_max().unwrap();
+            assert_eq!(key, max);
+            max -= 1;
+            assert_eq!(map.len(), (max + 1 - min) as usize);
+            check_invariants(&map.arena, map.tree);
+            assert_eq!(map.arena.len(), map.len());
+        }
+        assert_eq!(map.pop_min(), None);
+        assert_eq!(map.pop_max(), None);
+        assert!(map.is_empty());
+    }
+
     #[test]
     fn test_into_iter() {
         let mut map = RedBlackMap::new();
@@ -665,4 +1407,253 @@ mod tests {
             vec![(&1, &3), (&3, &5), (&5, &7)],
         );
     }
+
+    #[test]
+    fn test_clone_eq() {
+        let mut map = RedBlackMap::new();
+        map.insert(1, 2);
+        map.insert(5, 6);
+        map.insert(3, 4);
+
+        let mut cloned = map.clone();
+        assert_eq!(cloned, map);
+        cloned.insert(3, 5);
+        assert_ne!(cloned, map);
+        cloned.insert(3, 4);
+        cloned.insert(7, 8);
+        assert_ne!(cloned, map);
+        cloned.remove(&7);
+        assert_eq!(cloned, map);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut map = RedBlackMap::new();
+        map.insert(3, 4);
+        map.insert(1, 2);
+        assert_eq!(format!("{:?}", map), "{1: 2, 3: 4}");
+    }
+
+    // Checks that the tree is a left-leaning red-black tree and returns its black height.
+    fn check_black_height<T, U>(arena: &tree::Arena<T, U>, tree: tree::Tree) -> usize {
+        match tree {
+            None => 0,
+            Some(node) => {
+                assert!(!tree::is_red(arena, arena[node].right));
+                if arena[node].color == Color::Red {
+                    assert!(!tree::is_red(arena, arena[node].left));
+                }
+                let left_height = check_black_height(arena, arena[node].left);
+                let right_height = check_black_height(arena, arena[node].right);
+                assert_eq!(left_height, right_height);
+                assert_eq!(
+                    arena[node].len,
+                    tree::len(arena, arena[node].left) + tree::len(arena, arena[node].right) + 1,
+                );
+                match arena[node].color {
+                    Color::Red => left_height,
+                    Color::Black => left_height + 1,
+                }
+            }
+        }
+    }
+
+    fn check_invariants<T, U>(arena: &tree::Arena<T, U>, tree: tree::Tree) {
+        assert!(!tree::is_red(arena, tree));
+        check_black_height(arena, tree);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut map = RedBlackMap::new();
+        for key in 0..10_007u32 {
+            map.insert(key * 7_919 % 10_007, key);
+        }
+
+        let is_retained = |key: &u32| !key.is_multiple_of(3) && (*key < 2_000 || *key > 6_000);
+        map.retain(|key, value| {
+            *value += 1;
+            is_retained(key)
+        });
+        let expected: Vec<u32> = (0..10_007).filter(is_retained).collect();
+        assert_eq!(map.len(), expected.len());
+        assert_eq!(
+            map.iter().map(|(key, _)| *key).collect::<Vec<u32>>(),
+            expected,
+        );
+        check_invariants(&map.arena, map.tree);
+
+        for key in expected.iter().filter(|key| *key % 2 == 0) {
+            assert!(map.remove(key).is_some());
+        }
+        map.insert(4_000, 0);
+        check_invariants(&map.arena, map.tree);
+        assert_eq!(
+            map.len(),
+            expected.iter().filter(|key| *key % 2 == 1).count() + 1
+        );
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut map = RedBlackMap::new();
+        map.insert(1, 2);
+        map.insert(5, 6);
+        map.insert(3, 4);
+
+        assert_eq!(
+            map.drain().collect::<Vec<(u32, u32)>>(),
+            vec![(1, 2), (3, 4), (5, 6)],
+        );
+        assert!(map.is_empty());
+        map.insert(1, 2);
+        assert_eq!(map.get(&1), Some(&2));
+    }
+
+    #[test]
+    fn test_split_off() {
+        for key in 0..=100 {
+            for &inclusive in [true, false].iter() {
+                let mut map = RedBlackMap::new();
+                for key in 0..100u32 {
+                    map.insert(key * 37 % 100, key);
+                }
+
+                let split = map.split_off(&key, inclusive);
+                let split_key = if inclusive { key + 1 } else { key };
+                assert!(map.iter().map(|(key, _)| *key).eq(0..split_key.min(100)));
+                assert!(split
+                    .iter()
+                    .map(|(key, _)| *key)
+                    .eq(split_key.min(100)..100));
+                check_invariants(&map.arena, map.tree);
+                check_invariants(&split.arena, split.tree);
+                assert_eq!(map.arena.len(), map.len());
+                assert_eq!(split.arena.len(), split.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_join() {
+        for &(left_len, right_len) in [(1_000, 10), (10, 1_000), (0, 10), (10, 0)].iter() {
+            let mut left = RedBlackMap::new();
+            for key in (0..left_len).rev() {
+                left.insert(key, key);
+            }
+            let mut right = RedBlackMap::new();
+            for key in left_len..left_len + right_len {
+                right.insert(key, key);
+            }
+
+            let mut map = RedBlackMap::join(left, right);
+            assert!(map
+                .iter()
+                .map(|(key, value)| (*key, *value))
+                .eq((0..left_len + right_len).map(|key| (key, key))));
+            check_invariants(&map.arena, map.tree);
+            assert_eq!(map.arena.len(), map.len());
+
+            map.insert(left_len + right_len, 0);
+            assert_eq!(map.remove(&0), Some((0, 0)));
+            check_invariants(&map.arena, map.tree);
+        }
+    }
+
+    #[test]
+    fn test_with_arena() {
+        let mut map = RedBlackMap::with_arena();
+        for key in 0..100 {
+            map.insert(key, key);
+        }
+        let split = map.split_off(&49, true);
+        assert_eq!(map.len(), 50);
+        assert_eq!(split.len(), 50);
+        check_invariants(&split.arena, split.tree);
+        assert_eq!(split.arena.len(), split.len());
+
+        let mut boxed = RedBlackMap::new();
+        for key in 100..110 {
+            boxed.insert(key, key);
+        }
+        let map = RedBlackMap::join(RedBlackMap::union(map, split.clone()), boxed);
+        assert!(map.iter().map(|(key, _)| *key).eq(0..110));
+        check_invariants(&map.arena, map.tree);
+        assert_eq!(map.arena.len(), map.len());
+        assert_eq!(map.into_iter().take(5).count(), 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_join_overlapping() {
+        let mut left = RedBlackMap::new();
+        left.insert(2, 2);
+        let mut right = RedBlackMap::new();
+        right.insert(1, 1);
+        RedBlackMap::join(left, right);
+    }
+
+    fn check_set_operation<F, G>(f: F, is_included: G)
+    where
+        F: Fn(RedBlackMap<u32, u32>, RedBlackMap<u32, u32>) -> RedBlackMap<u32, u32>,
+        G: Fn(bool, bool) -> bool,
+    {
+        for &(left_len, right_len) in [(3_000, 300), (300, 3_000), (0, 300), (300, 0)].iter() {
+            let mut left = RedBlackMap::new();
+            for key in (0..left_len).filter(|key| key % 2 == 0) {
+                left.insert(key, 0);
+            }
+            let mut right = RedBlackMap::new();
+            for key in (0..right_len).rev().filter(|key| key % 3 == 0) {
+                right.insert(key, 1);
+            }
+
+            let map = f(left, right);
+            let expected: Vec<(u32, u32)> = (0..left_len.max(right_len))
+                .filter_map(|key| {
+                    let in_left = key < left_len && key % 2 == 0;
+                    let in_right = key < right_len && key % 3 == 0;
+                    if is_included(in_left, in_right) {
+                        Some((key, if in_left { 0 } else { 1 }))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            assert_eq!(
+                map.iter()
+                    .map(|(key, value)| (*key, *value))
+                    .collect::<Vec<(u32, u32)>>(),
+                expected,
+            );
+            check_invariants(&map.arena, map.tree);
+            assert_eq!(map.arena.len(), map.len());
+        }
+    }
+
+    #[test]
+    fn test_union() {
+        check_set_operation(RedBlackMap::union, |in_left, in_right| in_left || in_right);
+    }
+
+    #[test]
+    fn test_intersection() {
+        check_set_operation(RedBlackMap::intersection, |in_left, in_right| {
+            in_left && in_right
+        });
+    }
+
+    #[test]
+    fn test_difference() {
+        check_set_operation(RedBlackMap::difference, |in_left, in_right| {
+            in_left && !in_right
+        });
+    }
+
+    #[test]
+    fn test_symmetric_difference() {
+        check_set_operation(RedBlackMap::symmetric_difference, |in_left, in_right| {
+            in_left != in_right
+        });
+    }
 }
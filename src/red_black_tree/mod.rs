@@ -1,7 +1,7 @@
 //! Self-balancing binary search tree that uses a color bit to ensure that the tree remains
 //! approximately balanced during insertions and deletions.
 
-mod map;
+pub(crate) mod map;
 mod node;
 mod set;
 mod tree;
@@ -1,6 +1,6 @@
 use crate::entry::Entry;
+use crate::node_arena::{Links, NodeArena, NodeRef};
 use crate::red_black_tree::tree;
-use std::mem;
 
 /// An enum representing the color of a node in a red black tree.
 #[derive(Clone, Copy, PartialEq)]
@@ -19,11 +19,13 @@ impl Color {
 }
 
 /// A struct representing an internal node of a red black tree.
+#[derive(Clone)]
 pub struct Node<T, U> {
     pub entry: Entry<T, U>,
     pub color: Color,
-    pub left: tree::Tree<T, U>,
-    pub right: tree::Tree<T, U>,
+    pub len: usize,
+    pub left: tree::Tree,
+    pub right: tree::Tree,
 }
 
 impl<T, U> Node<T, U> {
@@ -31,90 +33,103 @@ impl<T, U> Node<T, U> {
         Node {
             entry: Entry { key, value },
             color: Color::Red,
+            len: 1,
             left: None,
             right: None,
         }
     }
 
-    pub fn flip_colors(&mut self) {
-        self.color = self.color.flip();
-        if let Some(ref mut child) = self.left {
-            child.color = child.color.flip();
+    pub fn update(arena: &mut NodeArena<Self>, node: NodeRef) {
+        let len = tree::len(arena, arena[node].left) + tree::len(arena, arena[node].right) + 1;
+        arena[node].len = len;
+    }
+
+    pub fn flip_colors(arena: &mut NodeArena<Self>, node: NodeRef) {
+        arena[node].color = arena[node].color.flip();
+        if let Some(child) = arena[node].left {
+            arena[child].color = arena[child].color.flip();
         }
-        if let Some(ref mut child) = self.right {
-            child.color = child.color.flip();
+        if let Some(child) = arena[node].right {
+            arena[child].color = arena[child].color.flip();
         }
     }
 
-    pub fn rotate_left(&mut self) {
-        let mut child = self
+    // Rotations swap the contents of the nodes so that the root of the subtree keeps its handle.
+    pub fn rotate_left(arena: &mut NodeArena<Self>, node: NodeRef) {
+        let child = arena[node]
             .right
             .take()
             .expect("Expected right child node to be `Some`.");
-        self.right = child.left.take();
-        mem::swap(&mut *child, self);
-        self.color = child.color;
-        child.color = Color::Red;
-        self.left = Some(child);
+        arena[node].right = arena[child].left.take();
+        arena.swap(&node, &child);
+        arena[node].color = arena[child].color;
+        arena[child].color = Color::Red;
+        Self::update(arena, child);
+        arena[node].left = Some(child);
+        Self::update(arena, node);
     }
 
-    pub fn rotate_right(&mut self) {
-        let mut child = self
+    pub fn rotate_right(arena: &mut NodeArena<Self>, node: NodeRef) {
+        let child = arena[node]
             .left
             .take()
             .expect("Expected left child node to be `Some`.");
-        self.left = child.right.take();
-        mem::swap(&mut *child, self);
-        self.color = child.color;
-        child.color = Color::Red;
-        self.right = Some(child);
+        arena[node].left = arena[child].right.take();
+        arena.swap(&node, &child);
+        arena[node].color = arena[child].color;
+        arena[child].color = Color::Red;
+        Self::update(arena, child);
+        arena[node].right = Some(child);
+        Self::update(arena, node);
     }
 
-    pub fn balance(&mut self) {
-        if tree::is_red(&self.right) {
-            self.rotate_left();
+    pub fn balance(arena: &mut NodeArena<Self>, node: NodeRef) {
+        if tree::is_red(arena, arena[node].right) {
+            Self::rotate_left(arena, node);
         }
 
         let should_rotate = {
-            if let Some(ref child) = self.left {
-                child.color == Color::Red && tree::is_red(&child.left)
+            if let Some(child) = arena[node].left {
+                arena[child].color == Color::Red && tree::is_red(arena, arena[child].left)
             } else {
                 false
             }
         };
         if should_rotate {
-            self.rotate_right();
+            Self::rotate_right(arena, node);
         }
 
-        if tree::is_red(&self.left) && tree::is_red(&self.right) {
-            self.flip_colors();
+        if tree::is_red(arena, arena[node].left) && tree::is_red(arena, arena[node].right) {
+            Self::flip_colors(arena, node);
         }
+
+        Self::update(arena, node);
     }
 
-    pub fn shift_left(&mut self) {
-        self.flip_colors();
-        if let Some(mut child) = self.right.take() {
-            if tree::is_red(&child.left) {
-                child.rotate_right();
-                self.right = Some(child);
-                self.rotate_left();
-                self.flip_colors();
-            } else {
-                self.right = Some(child);
+    pub fn shift_left(arena: &mut NodeArena<Self>, node: NodeRef) {
+        Self::flip_colors(arena, node);
+        if let Some(child) = arena[node].right {
+            if tree::is_red(arena, arena[child].left) {
+                Self::rotate_right(arena, child);
+                Self::rotate_left(arena, node);
+                Self::flip_colors(arena, node);
             }
         }
     }
 
-    pub fn shift_right(&mut self) {
-        self.flip_colors();
-        if let Some(child) = self.left.take() {
-            if tree::is_red(&child.left) {
-                self.left = Some(child);
-                self.rotate_right();
-                self.flip_colors();
-            } else {
-                self.left = Some(child);
+    pub fn shift_right(arena: &mut NodeArena<Self>, node: NodeRef) {
+        Self::flip_colors(arena, node);
+        if let Some(child) = arena[node].left {
+            if tree::is_red(arena, arena[child].left) {
+                Self::rotate_right(arena, node);
+                Self::flip_colors(arena, node);
             }
         }
     }
 }
+
+impl<T, U> Links for Node<T, U> {
+    fn links_mut(&mut self) -> (&mut tree::Tree, &mut tree::Tree) {
+        (&mut self.left, &mut self.right)
+    }
+}
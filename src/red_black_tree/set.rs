@@ -1,5 +1,8 @@
+use crate::ordered::OrderedSet;
 use crate::red_black_tree::map::{RedBlackMap, RedBlackMapIntoIter, RedBlackMapIter};
-use std::borrow::Borrow;
+use core::borrow::Borrow;
+use core::fmt;
+use core::iter::FromIterator;
 
 /// An ordered set implemented using a red_black_tree.
 ///
@@ -23,6 +26,7 @@ use std::borrow::Borrow;
 /// assert_eq!(set.remove(&0), Some(0));
 /// assert_eq!(set.remove(&1), None);
 /// ```
+#[derive(Clone)]
 pub struct RedBlackSet<T> {
     map: RedBlackMap<T, ()>,
 }
@@ -43,6 +47,24 @@ impl<T> RedBlackSet<T> {
         }
     }
 
+    /// Constructs a new, empty `RedBlackSet<T>` that allocates its nodes from a `TypedArena`. See
+    /// `RedBlackMap::with_arena` for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackSet;
+    ///
+    /// let mut set = RedBlackSet::with_arena();
+    /// set.insert(1);
+    /// assert!(set.contains(&1));
+    /// ```
+    pub fn with_arena() -> Self {
+        RedBlackSet {
+            map: RedBlackMap::with_arena(),
+        }
+    }
+
     /// Inserts a key into the set. If the key already exists in the set, it will return and
     /// replace the key.
     ///
@@ -76,9 +98,10 @@ impl<T> RedBlackSet<T> {
     /// assert_eq!(set.remove(&1), Some(1));
     /// assert_eq!(set.remove(&1), None);
     /// ```
-    pub fn remove(&mut self, key: &T) -> Option<T>
+    pub fn remove<V>(&mut self, key: &V) -> Option<T>
     where
-        T: Ord,
+        T: Borrow<V>,
+        V: Ord + ?Sized,
     {
         self.map.remove(key).map(|pair| pair.0)
     }
@@ -149,6 +172,49 @@ impl<T> RedBlackSet<T> {
         self.map.clear();
     }
 
+    /// Retains only the keys that satisfy a predicate. The keys are visited in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackSet;
+    ///
+    /// let mut set = RedBlackSet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    /// set.insert(3);
+    ///
+    /// set.retain(|key| key % 2 == 1);
+    /// assert_eq!(set.iter().collect::<Vec<&u32>>(), vec![&1, &3]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.map.retain(|key, _| f(key));
+    }
+
+    /// Clears the set, returning all keys as an iterator. The iterator yields the keys in
+    /// ascending order, and the set can be reused afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackSet;
+    ///
+    /// let mut set = RedBlackSet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    ///
+    /// assert_eq!(set.drain().collect::<Vec<u32>>(), vec![1, 2]);
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> RedBlackSetIntoIter<T> {
+        RedBlackSetIntoIter {
+            map_iter: self.map.drain(),
+        }
+    }
+
     /// Returns a key in the set that is less than or equal to a particular key. Returns `None` if
     /// such a key does not exist.
     ///
@@ -229,6 +295,177 @@ impl<T> RedBlackSet<T> {
         self.map.max()
     }
 
+    /// Splits the set and returns the right part of the set. If `inclusive` is true, then the set
+    /// will retain the given key if it exists. Otherwise, the right part of the set will contain
+    /// the key if it exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackSet;
+    ///
+    /// let mut set = RedBlackSet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    /// set.insert(3);
+    ///
+    /// let split = set.split_off(&2, true);
+    /// assert!(set.contains(&1));
+    /// assert!(set.contains(&2));
+    /// assert!(split.contains(&3));
+    /// ```
+    pub fn split_off<V>(&mut self, key: &V, inclusive: bool) -> Self
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        RedBlackSet {
+            map: self.map.split_off(key, inclusive),
+        }
+    }
+
+    /// Joins two sets. All keys in `left` must be less than all keys in `right`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the maximum key in `left` is not less than the minimum key in `right`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackSet;
+    ///
+    /// let mut n = RedBlackSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = RedBlackSet::new();
+    /// m.insert(3);
+    ///
+    /// let joined = RedBlackSet::join(n, m);
+    /// assert_eq!(joined.iter().collect::<Vec<&u32>>(), vec![&1, &2, &3]);
+    /// ```
+    pub fn join(left: Self, right: Self) -> Self
+    where
+        T: Ord,
+    {
+        RedBlackSet {
+            map: RedBlackMap::join(left.map, right.map),
+        }
+    }
+
+    /// Returns the union of two sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackSet;
+    ///
+    /// let mut n = RedBlackSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = RedBlackSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// let union = RedBlackSet::union(n, m);
+    /// assert_eq!(union.iter().collect::<Vec<&u32>>(), vec![&1, &2, &3]);
+    /// ```
+    pub fn union(left: Self, right: Self) -> Self
+    where
+        T: Ord,
+    {
+        RedBlackSet {
+            map: RedBlackMap::union(left.map, right.map),
+        }
+    }
+
+    /// Returns the intersection of two sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackSet;
+    ///
+    /// let mut n = RedBlackSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = RedBlackSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// let intersection = RedBlackSet::intersection(n, m);
+    /// assert_eq!(intersection.iter().collect::<Vec<&u32>>(), vec![&2]);
+    /// ```
+    pub fn intersection(left: Self, right: Self) -> Self
+    where
+        T: Ord,
+    {
+        RedBlackSet {
+            map: RedBlackMap::intersection(left.map, right.map),
+        }
+    }
+
+    /// Returns the difference of `left` and `right`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackSet;
+    ///
+    /// let mut n = RedBlackSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = RedBlackSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// let difference = RedBlackSet::difference(n, m);
+    /// assert_eq!(difference.iter().collect::<Vec<&u32>>(), vec![&1]);
+    /// ```
+    pub fn difference(left: Self, right: Self) -> Self
+    where
+        T: Ord,
+    {
+        RedBlackSet {
+            map: RedBlackMap::difference(left.map, right.map),
+        }
+    }
+
+    /// Returns the symmetric difference of `left` and `right`. The returned set will contain all
+    /// keys that exist in one set, but not both sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::red_black_tree::RedBlackSet;
+    ///
+    /// let mut n = RedBlackSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = RedBlackSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// let symmetric_difference = RedBlackSet::symmetric_difference(n, m);
+    /// assert_eq!(
+    ///     symmetric_difference.iter().collect::<Vec<&u32>>(),
+    ///     vec![&1, &3],
+    /// );
+    /// ```
+    pub fn symmetric_difference(left: Self, right: Self) -> Self
+    where
+        T: Ord,
+    {
+        RedBlackSet {
+            map: RedBlackMap::symmetric_difference(left.map, right.map),
+        }
+    }
+
     /// Returns an iterator over the set. The iterator will yield keys using in-order traversal.
     ///
     /// # Examples
@@ -308,12 +545,109 @@ where
     }
 }
 
+impl<T> OrderedSet<T> for RedBlackSet<T>
+where
+    T: Ord,
+{
+    type Iter<'a>
+        = RedBlackSetIter<'a, T>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn insert(&mut self, key: T) -> Option<T> {
+        RedBlackSet::insert(self, key)
+    }
+
+    fn remove(&mut self, key: &T) -> Option<T> {
+        RedBlackSet::remove(self, key)
+    }
+
+    fn contains(&self, key: &T) -> bool {
+        RedBlackSet::contains(self, key)
+    }
+
+    fn len(&self) -> usize {
+        RedBlackSet::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        RedBlackSet::is_empty(self)
+    }
+
+    fn floor(&self, key: &T) -> Option<&T> {
+        RedBlackSet::floor(self, key)
+    }
+
+    fn ceil(&self, key: &T) -> Option<&T> {
+        RedBlackSet::ceil(self, key)
+    }
+
+    fn min(&self) -> Option<&T> {
+        RedBlackSet::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        RedBlackSet::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        RedBlackSet::iter(self)
+    }
+}
+
+impl<T> fmt::Debug for RedBlackSet<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T> PartialEq for RedBlackSet<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T> Eq for RedBlackSet<T> where T: Eq {}
+
 impl<T> Default for RedBlackSet<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl<T> FromIterator<T> for RedBlackSet<T>
+where
+    T: Ord,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        RedBlackSet {
+            map: iter.into_iter().map(|key| (key, ())).collect(),
+        }
+    }
+}
+
+impl<T> Extend<T> for RedBlackSet<T>
+where
+    T: Ord,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.map.extend(iter.into_iter().map(|key| (key, ())));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::RedBlackSet;
@@ -398,6 +732,13 @@ mod tests {
         assert_eq!(set.into_iter().collect::<Vec<u32>>(), vec![1, 3, 5]);
     }
 
+    #[test]
+    fn test_from_iter() {
+        let mut set: RedBlackSet<u32> = vec![3, 1, 2, 1].into_iter().collect();
+        set.extend(vec![5, 4]);
+        assert_eq!(set.into_iter().collect::<Vec<u32>>(), vec![1, 2, 3, 4, 5]);
+    }
+
     #[test]
     fn test_iter() {
         let mut set = RedBlackSet::new();
@@ -407,4 +748,90 @@ mod tests {
 
         assert_eq!(set.iter().collect::<Vec<&u32>>(), vec![&1, &3, &5]);
     }
+
+    #[test]
+    fn test_clone_eq() {
+        let mut set = RedBlackSet::new();
+        set.insert(1);
+        set.insert(5);
+        set.insert(3);
+
+        let mut cloned = set.clone();
+        assert_eq!(cloned, set);
+        cloned.insert(7);
+        assert_ne!(cloned, set);
+        cloned.remove(&7);
+        assert_eq!(cloned, set);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut set = RedBlackSet::new();
+        set.insert(3);
+        set.insert(1);
+        assert_eq!(format!("{:?}", set), "{1, 3}");
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut set = RedBlackSet::new();
+        for key in 0..100 {
+            set.insert(key);
+        }
+
+        set.retain(|key| key % 3 == 0);
+        assert_eq!(set.len(), 34);
+        assert_eq!(
+            set.into_iter().collect::<Vec<u32>>(),
+            (0..100).filter(|key| key % 3 == 0).collect::<Vec<u32>>(),
+        );
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut set = RedBlackSet::new();
+        set.insert(1);
+        set.insert(5);
+        set.insert(3);
+
+        assert_eq!(set.drain().collect::<Vec<u32>>(), vec![1, 3, 5]);
+        assert!(set.is_empty());
+        set.insert(1);
+        assert!(set.contains(&1));
+    }
+
+    #[test]
+    fn test_join() {
+        let left: RedBlackSet<u32> = (0..50).collect();
+        let right: RedBlackSet<u32> = (50..100).collect();
+        let set = RedBlackSet::join(left, right);
+        assert_eq!(set.len(), 100);
+        assert!(set.iter().cloned().eq(0..100));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let n: RedBlackSet<u32> = (0..100).filter(|key| key % 2 == 0).collect();
+        let m: RedBlackSet<u32> = (0..100).filter(|key| key % 3 == 0).collect();
+        let union = RedBlackSet::union(n.clone(), m.clone());
+        assert!(union
+            .iter()
+            .cloned()
+            .eq((0..100).filter(|key| key % 2 == 0 || key % 3 == 0)));
+        let intersection = RedBlackSet::intersection(n.clone(), m.clone());
+        assert!(intersection
+            .iter()
+            .cloned()
+            .eq((0..100).filter(|key| key % 6 == 0)));
+        let difference = RedBlackSet::difference(n.clone(), m.clone());
+        assert!(difference
+            .iter()
+            .cloned()
+            .eq((0..100).filter(|key| key % 2 == 0 && key % 3 != 0)));
+        let symmetric_difference = RedBlackSet::symmetric_difference(n, m);
+        assert!(symmetric_difference
+            .iter()
+            .cloned()
+            .eq((0..100).filter(|key| (key % 2 == 0) != (key % 3 == 0))));
+    }
 }
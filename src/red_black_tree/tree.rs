@@ -1,256 +1,721 @@
 use crate::entry::Entry;
+use crate::node_arena::{NodeArena, NodeRef};
 use crate::red_black_tree::node::{Color, Node};
-use std::borrow::Borrow;
-use std::cmp::Ordering;
-use std::mem;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::mem;
 
-pub type Tree<T, U> = Option<Box<Node<T, U>>>;
+pub type Tree = Option<NodeRef>;
 
-pub fn is_red<T, U>(tree: &Tree<T, U>) -> bool {
+pub type Arena<T, U> = NodeArena<Node<T, U>>;
+
+pub fn is_red<T, U>(arena: &Arena<T, U>, tree: Tree) -> bool {
     match tree {
         None => false,
-        Some(ref node) => node.color == Color::Red,
+        Some(node) => arena[node].color == Color::Red,
+    }
+}
+
+pub fn len<T, U>(arena: &Arena<T, U>, tree: Tree) -> usize {
+    match tree {
+        None => 0,
+        Some(node) => arena[node].len,
+    }
+}
+
+fn should_shift_left<T, U>(arena: &Arena<T, U>, node: NodeRef) -> bool {
+    if let Some(child) = arena[node].left {
+        arena[child].color != Color::Red && !is_red(arena, arena[child].left)
+    } else {
+        false
+    }
+}
+
+fn should_shift_right<T, U>(arena: &Arena<T, U>, node: NodeRef) -> bool {
+    if let Some(child) = arena[node].right {
+        arena[child].color != Color::Red && !is_red(arena, arena[child].left)
+    } else {
+        false
     }
 }
 
 // precondition: there exists a minimum node in the tree
-fn remove_min<T, U>(tree: &mut Tree<T, U>) -> Box<Node<T, U>> {
-    if let Some(ref mut node) = tree {
-        if node.left.is_some() {
-            let should_shift = {
-                if let Some(ref child) = node.left {
-                    child.color != Color::Red && !is_red(&child.left)
-                } else {
-                    false
-                }
-            };
-            if should_shift {
-                node.shift_left();
+fn remove_min<T, U>(arena: &mut Arena<T, U>, tree: &mut Tree) -> NodeRef {
+    if let Some(node) = *tree {
+        if arena[node].left.is_some() {
+            if should_shift_left(arena, node) {
+                Node::shift_left(arena, node);
             }
 
-            let ret = remove_min(&mut node.left);
-            node.balance();
+            let mut left = arena[node].left;
+            let ret = remove_min(arena, &mut left);
+            arena[node].left = left;
+            Node::balance(arena, node);
             return ret;
         }
     }
 
-    let mut node = tree.take().expect("Expected a non-empty tree.");
-    *tree = node.right.take();
+    let node = tree.take().expect("Expected a non-empty tree.");
+    *tree = arena[node].right.take();
     node
 }
 
+// precondition: there exists a maximum node in the tree
+fn remove_max<T, U>(arena: &mut Arena<T, U>, tree: &mut Tree) -> NodeRef {
+    let node = tree.expect("Expected a non-empty tree.");
+    if is_red(arena, arena[node].left) {
+        Node::rotate_right(arena, node);
+    }
+
+    if arena[node].right.is_none() {
+        assert!(arena[node].left.is_none());
+        *tree = None;
+        return node;
+    }
+
+    if should_shift_right(arena, node) {
+        Node::shift_right(arena, node);
+    }
+
+    let mut right = arena[node].right;
+    let ret = remove_max(arena, &mut right);
+    arena[node].right = right;
+    Node::balance(arena, node);
+    ret
+}
+
 fn combine_subtrees<T, U>(
-    left_tree: Tree<T, U>,
-    mut right_tree: Tree<T, U>,
+    arena: &mut Arena<T, U>,
+    left_tree: Tree,
+    mut right_tree: Tree,
     color: Color,
-) -> Tree<T, U> {
-    let mut new_root = remove_min(&mut right_tree);
-    new_root.left = left_tree;
-    new_root.right = right_tree;
-    new_root.color = color;
+) -> Tree {
+    let new_root = remove_min(arena, &mut right_tree);
+    arena[new_root].left = left_tree;
+    arena[new_root].right = right_tree;
+    arena[new_root].color = color;
     Some(new_root)
 }
 
-pub fn fix_root<T, U>(tree: &mut Tree<T, U>) {
-    if let Some(ref mut node) = tree {
-        if !is_red(&node.left) && !is_red(&node.right) {
-            node.color = Color::Red;
+pub fn fix_root<T, U>(arena: &mut Arena<T, U>, tree: Tree) {
+    if let Some(node) = tree {
+        if !is_red(arena, arena[node].left) && !is_red(arena, arena[node].right) {
+            arena[node].color = Color::Red;
+        }
+    }
+}
+
+// Returns the number of black nodes on a path from the root of the tree to a leaf.
+fn black_height<T, U>(arena: &Arena<T, U>, tree: Tree) -> usize {
+    let mut ret = 0;
+    let mut curr = tree;
+    while let Some(node) = curr {
+        if arena[node].color == Color::Black {
+            ret += 1;
+        }
+        curr = arena[node].left;
+    }
+    ret
+}
+
+// Replaces the subtree on the right spine of `node` with the same black height as `right` with a
+// red node whose children are that subtree and `right`.
+fn join_right<T, U>(
+    arena: &mut Arena<T, U>,
+    node: NodeRef,
+    height: usize,
+    mid: NodeRef,
+    right: Tree,
+    right_height: usize,
+) -> NodeRef {
+    let child = arena[node].right;
+    let child_height = height - 1;
+    let child = if child_height == right_height {
+        arena[mid].left = child;
+        arena[mid].right = right;
+        arena[mid].color = Color::Red;
+        Node::update(arena, mid);
+        mid
+    } else {
+        let child = child.expect("Expected a non-empty tree.");
+        join_right(arena, child, child_height, mid, right, right_height)
+    };
+    arena[node].right = Some(child);
+    Node::balance(arena, node);
+    node
+}
+
+// Replaces the black subtree on the left spine of `node` with the same black height as `left` with
+// a red node whose children are `left` and that subtree.
+fn join_left<T, U>(
+    arena: &mut Arena<T, U>,
+    node: NodeRef,
+    height: usize,
+    mid: NodeRef,
+    left: Tree,
+    left_height: usize,
+) -> NodeRef {
+    let child = arena[node].left;
+    let child_height = match arena[node].color {
+        Color::Red => height,
+        Color::Black => height - 1,
+    };
+    let child = if child_height == left_height && !is_red(arena, child) {
+        arena[mid].left = left;
+        arena[mid].right = child;
+        arena[mid].color = Color::Red;
+        Node::update(arena, mid);
+        mid
+    } else {
+        let child = child.expect("Expected a non-empty tree.");
+        join_left(arena, child, child_height, mid, left, left_height)
+    };
+    arena[node].left = Some(child);
+    Node::balance(arena, node);
+    node
+}
+
+// Joins two trees with `mid` as the middle key. All keys in `left` must be less than the key of
+// `mid` and all keys in `right` must be greater than the key of `mid`. The root of the returned
+// tree may be red.
+fn join<T, U>(arena: &mut Arena<T, U>, left: Tree, mid: NodeRef, right: Tree) -> NodeRef {
+    for node in left.iter().chain(right.iter()) {
+        arena[*node].color = Color::Black;
+    }
+    let left_height = black_height(arena, left);
+    let right_height = black_height(arena, right);
+    match left_height.cmp(&right_height) {
+        Ordering::Greater => {
+            let left = left.expect("Expected a non-empty tree.");
+            join_right(arena, left, left_height, mid, right, right_height)
+        }
+        Ordering::Less => {
+            let right = right.expect("Expected a non-empty tree.");
+            join_left(arena, right, right_height, mid, left, left_height)
+        }
+        Ordering::Equal => {
+            arena[mid].left = left;
+            arena[mid].right = right;
+            arena[mid].color = Color::Red;
+            Node::update(arena, mid);
+            mid
+        }
+    }
+}
+
+// Joins two trees without a middle key. All keys in `left` must be less than all keys in `right`.
+pub fn join_subtrees<T, U>(arena: &mut Arena<T, U>, left: Tree, mut right: Tree) -> Tree {
+    if let Some(node) = right {
+        arena[node].color = Color::Black;
+    } else {
+        return left;
+    }
+    fix_root(arena, right);
+    let mid = remove_min(arena, &mut right);
+    Some(join(arena, left, mid, right))
+}
+
+// Removes all entries whose key-value pairs do not satisfy `f` by joining the retained subtrees, so
+// the tree is rebalanced without being rebuilt. The entries are visited in ascending order. The
+// root of the returned tree may be red.
+pub fn retain<T, U, F>(arena: &mut Arena<T, U>, tree: Tree, f: &mut F) -> Tree
+where
+    F: FnMut(&T, &mut U) -> bool,
+{
+    let node = tree?;
+    let left = arena[node].left.take();
+    let left = retain(arena, left, f);
+    let keep = {
+        let Entry {
+            ref key,
+            ref mut value,
+        } = arena[node].entry;
+        f(key, value)
+    };
+    let right = arena[node].right.take();
+    let right = retain(arena, right, f);
+    if keep {
+        Some(join(arena, left, node, right))
+    } else {
+        arena.free(&node);
+        join_subtrees(arena, left, right)
+    }
+}
+
+// Splits a tree into the nodes whose keys are less than `key` and the remaining nodes by joining
+// the subtrees along the search path. A node whose key is equal to `key` is kept in the left tree
+// if `inclusive` is true. The roots of the returned trees may be red.
+pub fn split<T, U, V>(arena: &mut Arena<T, U>, tree: Tree, key: &V, inclusive: bool) -> (Tree, Tree)
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    let node = match tree {
+        Some(node) => node,
+        None => return (None, None),
+    };
+    let left = arena[node].left.take();
+    let right = arena[node].right.take();
+    let is_left = match arena[node].entry.key.borrow().cmp(key) {
+        Ordering::Less => true,
+        Ordering::Equal => inclusive,
+        Ordering::Greater => false,
+    };
+    if is_left {
+        let (middle, right) = split(arena, right, key, inclusive);
+        (Some(join(arena, left, node, middle)), right)
+    } else {
+        let (left, middle) = split(arena, left, key, inclusive);
+        (left, Some(join(arena, middle, node, right)))
+    }
+}
+
+// Moves a tree from `arena` to `new_arena` without changing its shape. Boxed nodes are moved in
+// constant time, and pooled nodes are moved one at a time. Returns the moved tree and its number
+// of nodes.
+pub fn transfer<T, U>(
+    arena: &mut Arena<T, U>,
+    new_arena: &mut Arena<T, U>,
+    tree: Tree,
+) -> (Tree, usize) {
+    let len = len(arena, tree);
+    if arena.try_move(new_arena, len) {
+        return (tree, len);
+    }
+    (move_nodes(arena, new_arena, tree), len)
+}
+
+fn move_nodes<T, U>(arena: &mut Arena<T, U>, new_arena: &mut Arena<T, U>, tree: Tree) -> Tree {
+    let mut node = arena.free(&tree?);
+    node.left = move_nodes(arena, new_arena, node.left.take());
+    node.right = move_nodes(arena, new_arena, node.right.take());
+    Some(new_arena.allocate(node))
+}
+
+// Frees all nodes of a tree.
+fn free<T, U>(arena: &mut Arena<T, U>, tree: Tree) {
+    if let Some(node) = tree {
+        let Node { left, right, .. } = arena.free(&node);
+        free(arena, left);
+        free(arena, right);
+    }
+}
+
+// Splits a tree into the nodes whose keys are less than the key of `pivot`, the node whose key is
+// equal to the key of `pivot` if it exists, and the nodes whose keys are greater than the key of
+// `pivot`. `pivot` must not be in the tree.
+fn split_at<T, U>(arena: &mut Arena<T, U>, tree: Tree, pivot: NodeRef) -> (Tree, Tree, Tree)
+where
+    T: Ord,
+{
+    let node = match tree {
+        Some(node) => node,
+        None => return (None, None, None),
+    };
+    let left = arena[node].left.take();
+    let right = arena[node].right.take();
+    match arena[node].entry.key.cmp(&arena[pivot].entry.key) {
+        Ordering::Less => {
+            let (middle, dup, right) = split_at(arena, right, pivot);
+            (Some(join(arena, left, node, middle)), dup, right)
+        }
+        Ordering::Equal => (left, Some(node), right),
+        Ordering::Greater => {
+            let (left, dup, middle) = split_at(arena, left, pivot);
+            (left, dup, Some(join(arena, middle, node, right)))
+        }
+    }
+}
+
+// Returns the union of two trees. If a key is in both trees, the entry in `left` is kept, or the
+// entry in `right` if `swapped` is true. The root of the returned tree may be red.
+pub fn union<T, U>(arena: &mut Arena<T, U>, left: Tree, right: Tree, swapped: bool) -> Tree
+where
+    T: Ord,
+{
+    let node = match (left, right) {
+        (Some(node), Some(_)) => node,
+        (None, tree) | (tree, None) => return tree,
+    };
+    let left_left = arena[node].left.take();
+    let left_right = arena[node].right.take();
+    let (right_left, dup, right_right) = split_at(arena, right, node);
+    let left = union(arena, left_left, right_left, swapped);
+    let right = union(arena, left_right, right_right, swapped);
+    let node = match dup {
+        Some(dup) if swapped => {
+            arena.free(&node);
+            dup
+        }
+        Some(dup) => {
+            arena.free(&dup);
+            node
+        }
+        None => node,
+    };
+    Some(join(arena, left, node, right))
+}
+
+// Returns the intersection of two trees. The entry in `left` is kept, or the entry in `right` if
+// `swapped` is true. The root of the returned tree may be red.
+pub fn intersection<T, U>(arena: &mut Arena<T, U>, left: Tree, right: Tree, swapped: bool) -> Tree
+where
+    T: Ord,
+{
+    let node = match (left, right) {
+        (Some(node), Some(_)) => node,
+        _ => {
+            free(arena, left);
+            free(arena, right);
+            return None;
+        }
+    };
+    let left_left = arena[node].left.take();
+    let left_right = arena[node].right.take();
+    let (right_left, dup, right_right) = split_at(arena, right, node);
+    let left = intersection(arena, left_left, right_left, swapped);
+    let right = intersection(arena, left_right, right_right, swapped);
+    match dup {
+        Some(dup) => {
+            let (node, dup) = if swapped { (dup, node) } else { (node, dup) };
+            arena.free(&dup);
+            Some(join(arena, left, node, right))
+        }
+        None => {
+            arena.free(&node);
+            join_subtrees(arena, left, right)
         }
     }
 }
 
-pub fn insert<T, U>(tree: &mut Tree<T, U>, new_node: Node<T, U>) -> Option<Entry<T, U>>
+// Returns the entries of `left` whose keys are not in `right`, or the entries of `right` whose
+// keys are not in `left` if `swapped` is true. If `symmetric` is true, the entries of both trees
+// whose keys are not in the other tree are returned. The root of the returned tree may be red.
+pub fn difference<T, U>(
+    arena: &mut Arena<T, U>,
+    left: Tree,
+    right: Tree,
+    swapped: bool,
+    symmetric: bool,
+) -> Tree
 where
     T: Ord,
 {
-    let ret = match tree {
-        Some(ref mut node) => match new_node.entry.key.cmp(&node.entry.key) {
-            Ordering::Less => insert(&mut node.left, new_node),
-            Ordering::Greater => insert(&mut node.right, new_node),
-            Ordering::Equal => {
-                let Node { ref mut entry, .. } = &mut **node;
-                Some(mem::replace(entry, new_node.entry))
+    let keep_left = !swapped || symmetric;
+    let keep_right = swapped || symmetric;
+    let node = match (left, right) {
+        (Some(node), Some(_)) => node,
+        (left, None) => {
+            if keep_left {
+                return left;
+            }
+            free(arena, left);
+            return None;
+        }
+        (None, right) => {
+            if keep_right {
+                return right;
+            }
+            free(arena, right);
+            return None;
+        }
+    };
+    let left_left = arena[node].left.take();
+    let left_right = arena[node].right.take();
+    let (right_left, dup, right_right) = split_at(arena, right, node);
+    let left = difference(arena, left_left, right_left, swapped, symmetric);
+    let right = difference(arena, left_right, right_right, swapped, symmetric);
+    match dup {
+        None if keep_left => Some(join(arena, left, node, right)),
+        _ => {
+            if let Some(dup) = dup {
+                arena.free(&dup);
             }
-        },
+            arena.free(&node);
+            join_subtrees(arena, left, right)
+        }
+    }
+}
+
+pub fn insert<T, U>(
+    arena: &mut Arena<T, U>,
+    tree: &mut Tree,
+    new_node: Node<T, U>,
+) -> Option<Entry<T, U>>
+where
+    T: Ord,
+{
+    let node = match *tree {
+        Some(node) => node,
         None => {
-            *tree = Some(Box::new(new_node));
+            *tree = Some(arena.allocate(new_node));
             return None;
         }
     };
 
-    let node = tree.as_mut().expect("Expected non-empty tree.");
+    let ret = match new_node.entry.key.cmp(&arena[node].entry.key) {
+        Ordering::Less => {
+            let mut left = arena[node].left;
+            let ret = insert(arena, &mut left, new_node);
+            arena[node].left = left;
+            ret
+        }
+        Ordering::Greater => {
+            let mut right = arena[node].right;
+            let ret = insert(arena, &mut right, new_node);
+            arena[node].right = right;
+            ret
+        }
+        Ordering::Equal => Some(mem::replace(&mut arena[node].entry, new_node.entry)),
+    };
 
-    if is_red(&node.right) && !is_red(&node.left) {
-        node.rotate_left();
+    if is_red(arena, arena[node].right) && !is_red(arena, arena[node].left) {
+        Node::rotate_left(arena, node);
     }
 
     let should_rotate = {
-        if let Some(ref child) = node.left {
-            child.color == Color::Red && is_red(&child.left)
+        if let Some(child) = arena[node].left {
+            arena[child].color == Color::Red && is_red(arena, arena[child].left)
         } else {
             false
         }
     };
     if should_rotate {
-        node.rotate_right();
+        Node::rotate_right(arena, node);
     }
 
-    if is_red(&node.left) && is_red(&node.right) {
-        node.flip_colors();
+    if is_red(arena, arena[node].left) && is_red(arena, arena[node].right) {
+        Node::flip_colors(arena, node);
     }
 
+    Node::update(arena, node);
     ret
 }
 
-pub fn remove<T, U, V>(tree: &mut Tree<T, U>, key: &V) -> Option<Entry<T, U>>
+pub fn remove<T, U, V>(arena: &mut Arena<T, U>, tree: &mut Tree, key: &V) -> Option<Entry<T, U>>
 where
     T: Borrow<V>,
     V: Ord + ?Sized,
 {
-    let ret = match tree.take() {
-        Some(mut node) => {
-            if key < node.entry.key.borrow() {
-                let should_shift = {
-                    if let Some(ref child) = node.left {
-                        child.color != Color::Red && !is_red(&child.left)
-                    } else {
-                        false
-                    }
-                };
-                if should_shift {
-                    node.shift_left();
-                }
-
-                let ret = remove(&mut node.left, key);
-                *tree = Some(node);
-                ret
-            } else {
-                if is_red(&node.left) {
-                    node.rotate_right();
-                }
-
-                if key == node.entry.key.borrow() && node.right.is_none() {
-                    assert!(node.left.is_none());
-                    return Some(node.entry);
-                }
-
-                let should_shift = {
-                    if let Some(ref child) = node.right {
-                        child.color != Color::Red && !is_red(&child.left)
-                    } else {
-                        false
-                    }
-                };
-                if should_shift {
-                    node.shift_right();
-                }
-
-                if key == node.entry.key.borrow() {
-                    let unboxed_node = *node;
-                    let Node {
-                        entry,
-                        left,
-                        right,
-                        color,
-                    } = unboxed_node;
-                    *tree = combine_subtrees(left, right, color);
-                    Some(entry)
-                } else {
-                    let ret = remove(&mut node.right, key);
-                    *tree = Some(node);
-                    ret
-                }
-            }
+    let node = (*tree)?;
+    let ret = if key < arena[node].entry.key.borrow() {
+        if should_shift_left(arena, node) {
+            Node::shift_left(arena, node);
+        }
+
+        let mut left = arena[node].left;
+        let ret = remove(arena, &mut left, key);
+        arena[node].left = left;
+        ret
+    } else {
+        if is_red(arena, arena[node].left) {
+            Node::rotate_right(arena, node);
+        }
+
+        if key == arena[node].entry.key.borrow() && arena[node].right.is_none() {
+            assert!(arena[node].left.is_none());
+            *tree = None;
+            return Some(arena.free(&node).entry);
+        }
+
+        if should_shift_right(arena, node) {
+            Node::shift_right(arena, node);
+        }
+
+        if key == arena[node].entry.key.borrow() {
+            let Node {
+                entry,
+                left,
+                right,
+                color,
+                ..
+            } = arena.free(&node);
+            *tree = combine_subtrees(arena, left, right, color);
+            Some(entry)
+        } else {
+            let mut right = arena[node].right;
+            let ret = remove(arena, &mut right, key);
+            arena[node].right = right;
+            ret
         }
-        None => return None,
     };
 
-    let node = tree.as_mut().expect("Expected non-empty tree.");
-    node.balance();
+    let node = tree.expect("Expected non-empty tree.");
+    Node::balance(arena, node);
 
     ret
 }
 
-pub fn get<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V) -> Option<&'a Entry<T, U>>
+pub fn pop_min<T, U>(arena: &mut Arena<T, U>, tree: &mut Tree) -> Option<Entry<T, U>> {
+    if tree.is_none() {
+        return None;
+    }
+    let node = remove_min(arena, tree);
+    Some(arena.free(&node).entry)
+}
+
+pub fn pop_max<T, U>(arena: &mut Arena<T, U>, tree: &mut Tree) -> Option<Entry<T, U>> {
+    if tree.is_none() {
+        return None;
+    }
+    let node = remove_max(arena, tree);
+    Some(arena.free(&node).entry)
+}
+
+fn find<T, U, V>(arena: &Arena<T, U>, tree: Tree, key: &V) -> Tree
 where
     T: Borrow<V>,
     V: Ord + ?Sized,
 {
-    tree.as_ref()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Less => get(&node.left, key),
-            Ordering::Greater => get(&node.right, key),
-            Ordering::Equal => Some(&node.entry),
-        })
+    let mut curr = tree;
+    while let Some(node) = curr {
+        match key.cmp(arena[node].entry.key.borrow()) {
+            Ordering::Less => curr = arena[node].left,
+            Ordering::Greater => curr = arena[node].right,
+            Ordering::Equal => break,
+        }
+    }
+    curr
 }
 
-pub fn get_mut<'a, T, U, V>(tree: &'a mut Tree<T, U>, key: &V) -> Option<&'a mut Entry<T, U>>
+pub fn get<'a, T, U, V>(arena: &'a Arena<T, U>, tree: Tree, key: &V) -> Option<&'a Entry<T, U>>
 where
     T: Borrow<V>,
     V: Ord + ?Sized,
 {
-    tree.as_mut()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Less => get_mut(&mut node.left, key),
-            Ordering::Greater => get_mut(&mut node.right, key),
-            Ordering::Equal => Some(&mut node.entry),
-        })
+    find(arena, tree, key).map(|node| &arena[node].entry)
 }
 
-pub fn ceil<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V) -> Option<&'a Entry<T, U>>
+pub fn get_mut<'a, T, U, V>(
+    arena: &'a mut Arena<T, U>,
+    tree: Tree,
+    key: &V,
+) -> Option<&'a mut Entry<T, U>>
 where
     T: Borrow<V>,
     V: Ord + ?Sized,
 {
-    tree.as_ref()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Greater => ceil(&node.right, key),
-            Ordering::Less => match ceil(&node.left, key) {
-                None => Some(&node.entry),
-                res => res,
-            },
-            Ordering::Equal => Some(&node.entry),
-        })
-}
-
-pub fn floor<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V) -> Option<&'a Entry<T, U>>
+    find(arena, tree, key).map(move |node| &mut arena[node].entry)
+}
+
+// Returns the node with the least key that is greater than a particular key, or equal to it if
+// `inclusive` is true.
+fn find_ceil<T, U, V>(arena: &Arena<T, U>, tree: Tree, key: &V, inclusive: bool) -> Tree
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    let mut curr = tree;
+    let mut ret = None;
+    while let Some(node) = curr {
+        match key.cmp(arena[node].entry.key.borrow()) {
+            Ordering::Less => {
+                ret = curr;
+                curr = arena[node].left;
+            }
+            Ordering::Equal if inclusive => return curr,
+            _ => curr = arena[node].right,
+        }
+    }
+    ret
+}
+
+// Returns the node with the greatest key that is less than a particular key, or equal to it if
+// `inclusive` is true.
+fn find_floor<T, U, V>(arena: &Arena<T, U>, tree: Tree, key: &V, inclusive: bool) -> Tree
 where
     T: Borrow<V>,
     V: Ord + ?Sized,
 {
-    tree.as_ref()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Less => floor(&node.left, key),
-            Ordering::Greater => match floor(&node.right, key) {
-                None => Some(&node.entry),
-                res => res,
-            },
-            Ordering::Equal => Some(&node.entry),
-        })
-}
-
-pub fn min<T, U>(tree: &Tree<T, U>) -> Option<&Entry<T, U>>
+    let mut curr = tree;
+    let mut ret = None;
+    while let Some(node) = curr {
+        match key.cmp(arena[node].entry.key.borrow()) {
+            Ordering::Greater => {
+                ret = curr;
+                curr = arena[node].right;
+            }
+            Ordering::Equal if inclusive => return curr,
+            _ => curr = arena[node].left,
+        }
+    }
+    ret
+}
+
+pub fn ceil<'a, T, U, V>(
+    arena: &'a Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+) -> Option<&'a Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    find_ceil(arena, tree, key, inclusive).map(|node| &arena[node].entry)
+}
+
+pub fn ceil_mut<'a, T, U, V>(
+    arena: &'a mut Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+) -> Option<&'a mut Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    find_ceil(arena, tree, key, inclusive).map(move |node| &mut arena[node].entry)
+}
+
+pub fn floor<'a, T, U, V>(
+    arena: &'a Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+) -> Option<&'a Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    find_floor(arena, tree, key, inclusive).map(|node| &arena[node].entry)
+}
+
+pub fn floor_mut<'a, T, U, V>(
+    arena: &'a mut Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+) -> Option<&'a mut Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    find_floor(arena, tree, key, inclusive).map(move |node| &mut arena[node].entry)
+}
+
+pub fn min<T, U>(arena: &Arena<T, U>, tree: Tree) -> Option<&Entry<T, U>>
 where
     T: Ord,
 {
-    tree.as_ref().and_then(|node| {
-        let mut curr = node;
-        while let Some(ref left_node) = curr.left {
-            curr = left_node;
+    tree.map(|node| {
+        let mut curr = &arena[node];
+        while let Some(left_node) = curr.left {
+            curr = &arena[left_node];
         }
-        Some(&curr.entry)
+        &curr.entry
     })
 }
 
-pub fn max<T, U>(tree: &Tree<T, U>) -> Option<&Entry<T, U>>
+pub fn max<T, U>(arena: &Arena<T, U>, tree: Tree) -> Option<&Entry<T, U>>
 where
     T: Ord,
 {
-    tree.as_ref().and_then(|node| {
-        let mut curr = node;
-        while let Some(ref right_node) = curr.right {
-            curr = right_node;
+    tree.map(|node| {
+        let mut curr = &arena[node];
+        while let Some(right_node) = curr.right {
+            curr = &arena[right_node];
         }
-        Some(&curr.entry)
+        &curr.entry
     })
 }
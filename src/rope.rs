@@ -0,0 +1,707 @@
+//! Sequence of characters or bytes optimized for editing large strings.
+
+use crate::seed;
+use crate::treap::implicit_tree;
+use crate::treap::node::ImplicitNode;
+use rand::Rng;
+use rand::XorShiftRng;
+use std::fmt;
+use std::ops::{Add, Range};
+
+/// A rope implemented using an implicit treap.
+///
+/// A rope is a string-like structure that allows for efficient insertion, deletion, and
+/// concatenation of text. Each element of the rope is stored in a node of an implicit treap, so
+/// the position of an element is implicitly determined by the size of the subtrees to its left.
+/// Inserting a string of length `M` into a rope of length `N` takes `O(M + log N)` expected time,
+/// deleting a range takes `O(log N)` expected time, and concatenating two ropes takes `O(log N)`
+/// expected time.
+///
+/// A `Rope<char>`, which is the default, holds text and is indexed by characters rather than by
+/// bytes. A `Rope<u8>` holds arbitrary bytes and is indexed by bytes.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::rope::Rope;
+///
+/// let mut rope = Rope::from("Hello world!");
+/// rope.insert_str(5, ",");
+/// assert_eq!(rope.to_string(), "Hello, world!");
+///
+/// rope.delete(5..6);
+/// assert_eq!(rope.slice(6..11), "world");
+/// assert_eq!(rope.char_at(0), Some('H'));
+///
+/// let rope = rope + Rope::from(" Bye!");
+/// assert_eq!(rope.to_string(), "Hello world! Bye!");
+/// assert_eq!(rope.len(), 17);
+///
+/// let mut rope = Rope::from(&b"\x00\xff"[..]);
+/// rope.insert_bytes(1, b"\x80");
+/// assert_eq!(rope.slice(0..3), vec![0x00, 0x80, 0xff]);
+/// ```
+pub struct Rope<T = char> {
+    tree: implicit_tree::Tree<T>,
+    rng: XorShiftRng,
+}
+
+impl<T> Rope<T> {
+    /// Constructs a new, empty `Rope<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let rope: Rope = Rope::new();
+    /// let bytes: Rope<u8> = Rope::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::with_rng(seed::new_rng())
+    }
+
+    /// Constructs a new, empty `Rope<T>` that uses `rng` to generate the priorities of its nodes.
+    /// Seeding `rng` makes the internal structure reproducible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    /// use rand::{SeedableRng, XorShiftRng};
+    ///
+    /// let rope: Rope = Rope::with_rng(XorShiftRng::from_seed([1, 1, 1, 1]));
+    /// ```
+    pub fn with_rng(rng: XorShiftRng) -> Self {
+        Rope { tree: None, rng }
+    }
+
+    // Builds the new elements into a treap in linear time so that they can be merged into the rope
+    // in logarithmic time.
+    fn build_tree<I>(&mut self, values: I) -> implicit_tree::Tree<T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let Rope { ref mut rng, .. } = self;
+        implicit_tree::build(
+            values
+                .into_iter()
+                .map(|value| ImplicitNode::new(value, rng.next_u32())),
+        )
+    }
+
+    fn insert_tree(&mut self, index: usize, new_tree: implicit_tree::Tree<T>) {
+        let Rope { ref mut tree, .. } = self;
+        let right = implicit_tree::split(tree, index + 1, true);
+        implicit_tree::merge(tree, new_tree);
+        implicit_tree::merge(tree, right);
+    }
+
+    /// Inserts an element into the rope at a particular index, shifting elements to the right if
+    /// needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the rope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let mut rope = Rope::from("ac");
+    /// rope.insert(1, 'b');
+    /// assert_eq!(rope.to_string(), "abc");
+    /// ```
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len());
+        let Rope {
+            ref mut tree,
+            ref mut rng,
+        } = self;
+        implicit_tree::insert(tree, index + 1, ImplicitNode::new(value, rng.next_u32()));
+    }
+
+    /// Removes the elements in a particular range from the rope.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end of the range, or if the end of the
+    /// range is greater than the length of the rope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let mut rope = Rope::from("abcd");
+    /// rope.delete(1..3);
+    /// assert_eq!(rope.to_string(), "ad");
+    /// ```
+    pub fn delete(&mut self, range: Range<usize>) {
+        assert!(range.start <= range.end && range.end <= self.len());
+        let Rope { ref mut tree, .. } = self;
+        let right = implicit_tree::split(tree, range.end + 1, true);
+        implicit_tree::split(tree, range.start + 1, true);
+        implicit_tree::merge(tree, right);
+    }
+
+    /// Moves all the elements of `other` to the end of the rope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let mut rope = Rope::from("ab");
+    /// rope.append(Rope::from("cd"));
+    /// assert_eq!(rope.to_string(), "abcd");
+    /// ```
+    pub fn append(&mut self, other: Rope<T>) {
+        implicit_tree::merge(&mut self.tree, other.tree);
+    }
+
+    /// Splits the rope at a particular index and returns the elements at and after the index as a
+    /// new rope.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the rope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let mut rope = Rope::from("abcd");
+    /// let split = rope.split_off(1);
+    /// assert_eq!(rope.to_string(), "a");
+    /// assert_eq!(split.to_string(), "bcd");
+    /// ```
+    pub fn split_off(&mut self, index: usize) -> Rope<T> {
+        assert!(index <= self.len());
+        Rope {
+            tree: implicit_tree::split(&mut self.tree, index + 1, true),
+            rng: self.rng.gen(),
+        }
+    }
+
+    /// Returns the number of elements in the rope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let rope = Rope::from("abc");
+    /// assert_eq!(rope.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        implicit_tree::len(&self.tree)
+    }
+
+    /// Returns `true` if the rope is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let rope: Rope = Rope::new();
+    /// assert!(rope.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_none()
+    }
+
+    /// Clears the rope, removing all elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let mut rope = Rope::from("abc");
+    /// rope.clear();
+    /// assert_eq!(rope.is_empty(), true);
+    /// ```
+    pub fn clear(&mut self) {
+        self.tree = None;
+    }
+
+    fn iter(&self) -> RopeIter<'_, T> {
+        RopeIter {
+            current: &self.tree,
+            stack: Vec::new(),
+        }
+    }
+
+    fn iter_from(&self, mut index: usize) -> RopeIter<'_, T> {
+        let mut stack = Vec::new();
+        let mut current = &self.tree;
+        while let Some(ref node) = current {
+            let key = node.get_implicit_key() - 1;
+            if index <= key {
+                stack.push(&**node);
+                current = &node.left;
+            } else {
+                index -= key + 1;
+                current = &node.right;
+            }
+        }
+        RopeIter {
+            current: &None,
+            stack,
+        }
+    }
+}
+
+impl Rope<char> {
+    /// Inserts a string into the rope at a particular character index, shifting characters to the
+    /// right if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the rope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let mut rope = Rope::from("ac");
+    /// rope.insert_str(1, "b");
+    /// assert_eq!(rope.to_string(), "abc");
+    /// ```
+    pub fn insert_str(&mut self, index: usize, s: &str) {
+        assert!(index <= self.len());
+        let new_tree = self.build_tree(s.chars());
+        self.insert_tree(index, new_tree);
+    }
+
+    /// Returns the characters in a particular range of the rope as a `String`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end of the range, or if the end of the
+    /// range is greater than the length of the rope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let rope = Rope::from("abcd");
+    /// assert_eq!(rope.slice(1..3), "bc");
+    /// ```
+    pub fn slice(&self, range: Range<usize>) -> String {
+        assert!(range.start <= range.end && range.end <= self.len());
+        self.chars_from(range.start)
+            .take(range.end - range.start)
+            .collect()
+    }
+
+    /// Returns the character at a particular character index. Returns `None` if the index is out
+    /// of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let rope = Rope::from("abc");
+    /// assert_eq!(rope.char_at(1), Some('b'));
+    /// assert_eq!(rope.char_at(3), None);
+    /// ```
+    pub fn char_at(&self, index: usize) -> Option<char> {
+        implicit_tree::get(&self.tree, index + 1).cloned()
+    }
+
+    /// Returns an iterator over the characters of the rope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let rope = Rope::from("ab");
+    ///
+    /// let mut iterator = rope.chars();
+    /// assert_eq!(iterator.next(), Some('a'));
+    /// assert_eq!(iterator.next(), Some('b'));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn chars(&self) -> RopeIter<'_, char> {
+        self.iter()
+    }
+
+    /// Returns an iterator over the characters of the rope starting at a particular character
+    /// index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let rope = Rope::from("abc");
+    ///
+    /// let mut iterator = rope.chars_from(1);
+    /// assert_eq!(iterator.next(), Some('b'));
+    /// assert_eq!(iterator.next(), Some('c'));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn chars_from(&self, index: usize) -> RopeIter<'_, char> {
+        self.iter_from(index)
+    }
+}
+
+impl Rope<u8> {
+    /// Inserts a slice of bytes into the rope at a particular byte index, shifting bytes to the
+    /// right if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the rope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let mut rope = Rope::from(&b"ac"[..]);
+    /// rope.insert_bytes(1, b"b");
+    /// assert_eq!(rope.slice(0..3), b"abc");
+    /// ```
+    pub fn insert_bytes(&mut self, index: usize, bytes: &[u8]) {
+        assert!(index <= self.len());
+        let new_tree = self.build_tree(bytes.iter().cloned());
+        self.insert_tree(index, new_tree);
+    }
+
+    /// Returns the bytes in a particular range of the rope as a `Vec<u8>`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end of the range, or if the end of the
+    /// range is greater than the length of the rope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let rope = Rope::from(&b"abcd"[..]);
+    /// assert_eq!(rope.slice(1..3), b"bc");
+    /// ```
+    pub fn slice(&self, range: Range<usize>) -> Vec<u8> {
+        assert!(range.start <= range.end && range.end <= self.len());
+        self.bytes_from(range.start)
+            .take(range.end - range.start)
+            .collect()
+    }
+
+    /// Returns the byte at a particular byte index. Returns `None` if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let rope = Rope::from(&b"abc"[..]);
+    /// assert_eq!(rope.byte_at(1), Some(b'b'));
+    /// assert_eq!(rope.byte_at(3), None);
+    /// ```
+    pub fn byte_at(&self, index: usize) -> Option<u8> {
+        implicit_tree::get(&self.tree, index + 1).cloned()
+    }
+
+    /// Returns an iterator over the bytes of the rope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let rope = Rope::from(&b"ab"[..]);
+    ///
+    /// let mut iterator = rope.bytes();
+    /// assert_eq!(iterator.next(), Some(b'a'));
+    /// assert_eq!(iterator.next(), Some(b'b'));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn bytes(&self) -> RopeIter<'_, u8> {
+        self.iter()
+    }
+
+    /// Returns an iterator over the bytes of the rope starting at a particular byte index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::rope::Rope;
+    ///
+    /// let rope = Rope::from(&b"abc"[..]);
+    ///
+    /// let mut iterator = rope.bytes_from(1);
+    /// assert_eq!(iterator.next(), Some(b'b'));
+    /// assert_eq!(iterator.next(), Some(b'c'));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn bytes_from(&self, index: usize) -> RopeIter<'_, u8> {
+        self.iter_from(index)
+    }
+}
+
+/// An iterator over the elements of a `Rope<T>`.
+///
+/// This iterator traverses the elements of the rope in-order.
+pub struct RopeIter<'a, T> {
+    current: &'a implicit_tree::Tree<T>,
+    stack: Vec<&'a ImplicitNode<T>>,
+}
+
+impl<'a, T> Iterator for RopeIter<'a, T>
+where
+    T: Copy,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(ref node) = self.current {
+            self.current = &node.left;
+            self.stack.push(node);
+        }
+        self.stack.pop().map(|node| {
+            let ImplicitNode {
+                ref value,
+                ref right,
+                ..
+            } = node;
+            self.current = right;
+            *value
+        })
+    }
+}
+
+impl<'a> From<&'a str> for Rope<char> {
+    fn from(s: &'a str) -> Self {
+        let mut rope = Rope::new();
+        rope.tree = rope.build_tree(s.chars());
+        rope
+    }
+}
+
+impl<'a> From<&'a [u8]> for Rope<u8> {
+    fn from(bytes: &'a [u8]) -> Self {
+        let mut rope = Rope::new();
+        rope.tree = rope.build_tree(bytes.iter().cloned());
+        rope
+    }
+}
+
+impl fmt::Display for Rope<char> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.chars() {
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Default for Rope<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Add for Rope<T> {
+    type Output = Rope<T>;
+
+    fn add(mut self, other: Rope<T>) -> Rope<T> {
+        self.append(other);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rope;
+    use crate::treap::implicit_tree;
+    use rand::{SeedableRng, XorShiftRng};
+
+    fn get_priorities<T>(tree: &implicit_tree::Tree<T>, priorities: &mut Vec<u32>) {
+        if let Some(ref node) = tree {
+            get_priorities(&node.left, priorities);
+            priorities.push(node.priority);
+            get_priorities(&node.right, priorities);
+        }
+    }
+
+    fn get_shape(rope: &Rope) -> Vec<u32> {
+        let mut priorities = Vec::new();
+        get_priorities(&rope.tree, &mut priorities);
+        priorities
+    }
+
+    #[test]
+    fn test_len_empty() {
+        let rope: Rope = Rope::new();
+        assert_eq!(rope.len(), 0);
+    }
+
+    #[test]
+    fn test_is_empty() {
+        let rope: Rope = Rope::new();
+        assert!(rope.is_empty());
+    }
+
+    #[test]
+    fn test_from() {
+        let rope = Rope::from("abc");
+        assert_eq!(rope.len(), 3);
+        assert_eq!(rope.to_string(), "abc");
+    }
+
+    #[test]
+    fn test_insert_str() {
+        let mut rope = Rope::from("ad");
+        rope.insert_str(1, "bc");
+        rope.insert_str(0, "_");
+        rope.insert_str(5, "_");
+        assert_eq!(rope.to_string(), "_abcd_");
+        assert_eq!(rope.len(), 6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_str_out_of_bounds() {
+        let mut rope = Rope::from("ab");
+        rope.insert_str(3, "c");
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut rope = Rope::new();
+        rope.insert(0, 'b');
+        rope.insert(0, 'a');
+        rope.insert(2, 'c');
+        assert_eq!(rope.to_string(), "abc");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_out_of_bounds() {
+        let mut rope = Rope::from("ab");
+        rope.insert(3, 'c');
+    }
+
+    #[test]
+    fn test_delete() {
+        let mut rope = Rope::from("abcdef");
+        rope.delete(1..3);
+        assert_eq!(rope.to_string(), "adef");
+        rope.delete(2..2);
+        assert_eq!(rope.to_string(), "adef");
+        rope.delete(0..4);
+        assert!(rope.is_empty());
+    }
+
+    #[test]
+    fn test_slice() {
+        let rope = Rope::from("abcdef");
+        assert_eq!(rope.slice(0..6), "abcdef");
+        assert_eq!(rope.slice(2..5), "cde");
+        assert_eq!(rope.slice(3..3), "");
+    }
+
+    #[test]
+    fn test_char_at() {
+        let rope = Rope::from("añc");
+        assert_eq!(rope.char_at(0), Some('a'));
+        assert_eq!(rope.char_at(1), Some('ñ'));
+        assert_eq!(rope.char_at(2), Some('c'));
+        assert_eq!(rope.char_at(3), None);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut rope = Rope::from("ab");
+        rope.append(Rope::from("cd"));
+        rope.append(Rope::new());
+        assert_eq!(rope.to_string(), "abcd");
+        assert_eq!(rope.len(), 4);
+    }
+
+    #[test]
+    fn test_add() {
+        let rope = Rope::from("ab") + Rope::from("cd");
+        assert_eq!(rope.to_string(), "abcd");
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut rope = Rope::from("abcd");
+        let split = rope.split_off(2);
+        assert_eq!(rope.to_string(), "ab");
+        assert_eq!(split.to_string(), "cd");
+    }
+
+    #[test]
+    fn test_split_off_seeded() {
+        let build = || {
+            let mut rope = Rope::with_rng(XorShiftRng::from_seed([1, 1, 1, 1]));
+            rope.insert_str(0, "abcdef");
+            let mut split = rope.split_off(3);
+            split.insert_str(1, "xyz");
+            (rope, split)
+        };
+        let (rope, split) = build();
+        let (other_rope, other_split) = build();
+        assert_eq!(split.to_string(), "dxyzef");
+        assert_eq!(get_shape(&rope), get_shape(&other_rope));
+        assert_eq!(get_shape(&split), get_shape(&other_split));
+    }
+
+    #[test]
+    fn test_chars_from() {
+        let rope = Rope::from("abcd");
+        assert_eq!(rope.chars_from(0).collect::<String>(), "abcd");
+        assert_eq!(rope.chars_from(3).collect::<String>(), "d");
+        assert_eq!(rope.chars_from(4).collect::<String>(), "");
+    }
+
+    #[test]
+    fn test_from_long() {
+        let s: String = (0..100_000)
+            .map(|i| (b'a' + (i % 26) as u8) as char)
+            .collect();
+        let mut rope = Rope::from(s.as_str());
+        assert_eq!(rope.len(), 100_000);
+        assert_eq!(rope.to_string(), s);
+        rope.insert_str(50_000, &s);
+        assert_eq!(rope.len(), 200_000);
+        assert_eq!(rope.slice(50_000..150_000), s);
+    }
+
+    #[test]
+    fn test_bytes() {
+        let mut rope = Rope::from(&b"ad"[..]);
+        rope.insert_bytes(1, b"bc");
+        rope.insert(4, 0xff);
+        assert_eq!(rope.len(), 5);
+        assert_eq!(rope.slice(0..5), b"abcd\xff");
+        assert_eq!(rope.byte_at(4), Some(0xff));
+        assert_eq!(rope.byte_at(5), None);
+
+        let split = rope.split_off(2);
+        assert_eq!(rope.bytes().collect::<Vec<u8>>(), b"ab");
+        assert_eq!(split.bytes_from(1).collect::<Vec<u8>>(), b"d\xff");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_bytes_out_of_bounds() {
+        let mut rope = Rope::from(&b"ab"[..]);
+        rope.insert_bytes(3, b"c");
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::{AtomicUsize, Ordering};
+use rand::{SeedableRng, XorShiftRng};
+
+// Returns the `index`-th output of a SplitMix64 generator seeded with `seed`. Distinct indexes
+// yield independent and well-mixed values, so several keys can be derived from a single seed.
+pub fn split_mix(seed: u64, index: u64) -> u64 {
+    let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Returns a random number generator whose state is derived from `seed`.
+pub fn rng_from_seed(seed: u64) -> XorShiftRng {
+    let low = split_mix(seed, 0);
+    let high = split_mix(seed, 1);
+    // The state of a xorshift generator must not be all zeros.
+    XorShiftRng::from_seed([
+        low as u32,
+        (low >> 32) as u32,
+        high as u32,
+        (high >> 32) as u32 | 1,
+    ])
+}
+
+// Returns a random number generator for a new collection. Without the standard library there is
+// no source of entropy, so the generators are instead derived from a counter of the generators
+// created so far.
+#[cfg(feature = "std")]
+pub fn new_rng() -> XorShiftRng {
+    rand::weak_rng()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn new_rng() -> XorShiftRng {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    rng_from_seed(COUNTER.fetch_add(1, Ordering::Relaxed) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rng_from_seed, split_mix};
+    use rand::Rng;
+
+    #[test]
+    fn test_split_mix() {
+        assert_eq!(split_mix(0, 0), split_mix(0, 0));
+        assert_ne!(split_mix(0, 0), split_mix(0, 1));
+        assert_ne!(split_mix(0, 0), split_mix(1, 0));
+    }
+
+    #[test]
+    fn test_rng_from_seed() {
+        let value = rng_from_seed(0).next_u64();
+        assert_eq!(rng_from_seed(0).next_u64(), value);
+        assert_ne!(rng_from_seed(1).next_u64(), value);
+    }
+}
@@ -0,0 +1,254 @@
+use crate::merge::KeyedItem;
+use core::cmp::Ordering;
+use core::iter::Peekable;
+
+// Compares the next items of two iterators, where an exhausted iterator is treated as greater than
+// any item. Returns `None` if both iterators are exhausted.
+fn compare<I>(left: &mut Peekable<I>, right: &mut Peekable<I>) -> Option<Ordering>
+where
+    I: Iterator,
+    I::Item: KeyedItem,
+{
+    match (left.peek(), right.peek()) {
+        (Some(left_item), Some(right_item)) => Some(left_item.key().cmp(right_item.key())),
+        (Some(_), None) => Some(Ordering::Less),
+        (None, Some(_)) => Some(Ordering::Greater),
+        (None, None) => None,
+    }
+}
+
+/// A lazy iterator over the union of two ordered collections.
+///
+/// This iterator yields the items of both collections in ascending order. If a key exists in both
+/// collections, only the item from the left collection is yielded.
+pub struct UnionIter<I>
+where
+    I: Iterator,
+{
+    left: Peekable<I>,
+    right: Peekable<I>,
+}
+
+impl<I> UnionIter<I>
+where
+    I: Iterator,
+{
+    pub(crate) fn new(left: I, right: I) -> Self {
+        UnionIter {
+            left: left.peekable(),
+            right: right.peekable(),
+        }
+    }
+}
+
+impl<I> Iterator for UnionIter<I>
+where
+    I: Iterator,
+    I::Item: KeyedItem,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match compare(&mut self.left, &mut self.right)? {
+            Ordering::Less => self.left.next(),
+            Ordering::Greater => self.right.next(),
+            Ordering::Equal => {
+                self.right.next();
+                self.left.next()
+            }
+        }
+    }
+}
+
+/// A lazy iterator over the intersection of two ordered collections.
+///
+/// This iterator yields the items of the left collection whose keys also exist in the right
+/// collection in ascending order.
+pub struct IntersectionIter<I>
+where
+    I: Iterator,
+{
+    left: Peekable<I>,
+    right: Peekable<I>,
+}
+
+impl<I> IntersectionIter<I>
+where
+    I: Iterator,
+{
+    pub(crate) fn new(left: I, right: I) -> Self {
+        IntersectionIter {
+            left: left.peekable(),
+            right: right.peekable(),
+        }
+    }
+}
+
+impl<I> Iterator for IntersectionIter<I>
+where
+    I: Iterator,
+    I::Item: KeyedItem,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.left.peek()?;
+            self.right.peek()?;
+            match compare(&mut self.left, &mut self.right)? {
+                Ordering::Less => {
+                    self.left.next();
+                }
+                Ordering::Greater => {
+                    self.right.next();
+                }
+                Ordering::Equal => {
+                    self.right.next();
+                    return self.left.next();
+                }
+            }
+        }
+    }
+}
+
+/// A lazy iterator over the difference of two ordered collections.
+///
+/// This iterator yields the items of the left collection whose keys do not exist in the right
+/// collection in ascending order.
+pub struct DifferenceIter<I>
+where
+    I: Iterator,
+{
+    left: Peekable<I>,
+    right: Peekable<I>,
+}
+
+impl<I> DifferenceIter<I>
+where
+    I: Iterator,
+{
+    pub(crate) fn new(left: I, right: I) -> Self {
+        DifferenceIter {
+            left: left.peekable(),
+            right: right.peekable(),
+        }
+    }
+}
+
+impl<I> Iterator for DifferenceIter<I>
+where
+    I: Iterator,
+    I::Item: KeyedItem,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.left.peek()?;
+            match compare(&mut self.left, &mut self.right)? {
+                Ordering::Less => return self.left.next(),
+                Ordering::Greater => {
+                    self.right.next();
+                }
+                Ordering::Equal => {
+                    self.left.next();
+                    self.right.next();
+                }
+            }
+        }
+    }
+}
+
+/// A lazy iterator over the symmetric difference of two ordered collections.
+///
+/// This iterator yields the items of both collections whose keys exist in exactly one of the
+/// collections in ascending order.
+pub struct SymmetricDifferenceIter<I>
+where
+    I: Iterator,
+{
+    left: Peekable<I>,
+    right: Peekable<I>,
+}
+
+impl<I> SymmetricDifferenceIter<I>
+where
+    I: Iterator,
+{
+    pub(crate) fn new(left: I, right: I) -> Self {
+        SymmetricDifferenceIter {
+            left: left.peekable(),
+            right: right.peekable(),
+        }
+    }
+}
+
+impl<I> Iterator for SymmetricDifferenceIter<I>
+where
+    I: Iterator,
+    I::Item: KeyedItem,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match compare(&mut self.left, &mut self.right)? {
+                Ordering::Less => return self.left.next(),
+                Ordering::Greater => return self.right.next(),
+                Ordering::Equal => {
+                    self.left.next();
+                    self.right.next();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DifferenceIter, IntersectionIter, SymmetricDifferenceIter, UnionIter};
+
+    #[test]
+    fn test_empty() {
+        let empty: Vec<u32> = Vec::new();
+        let values = [1, 2];
+        assert_eq!(
+            UnionIter::new(empty.iter(), values.iter()).collect::<Vec<&u32>>(),
+            vec![&1, &2],
+        );
+        assert_eq!(
+            IntersectionIter::new(values.iter(), empty.iter()).next(),
+            None,
+        );
+        assert_eq!(
+            DifferenceIter::new(values.iter(), empty.iter()).collect::<Vec<&u32>>(),
+            vec![&1, &2],
+        );
+        assert_eq!(
+            SymmetricDifferenceIter::new(empty.iter(), values.iter()).collect::<Vec<&u32>>(),
+            vec![&1, &2],
+        );
+    }
+
+    #[test]
+    fn test_interleaved() {
+        let left = [1, 3, 4, 6, 8];
+        let right = [2, 3, 5, 6, 7];
+        assert_eq!(
+            UnionIter::new(left.iter(), right.iter()).collect::<Vec<&u32>>(),
+            vec![&1, &2, &3, &4, &5, &6, &7, &8],
+        );
+        assert_eq!(
+            IntersectionIter::new(left.iter(), right.iter()).collect::<Vec<&u32>>(),
+            vec![&3, &6],
+        );
+        assert_eq!(
+            DifferenceIter::new(left.iter(), right.iter()).collect::<Vec<&u32>>(),
+            vec![&1, &4, &8],
+        );
+        assert_eq!(
+            SymmetricDifferenceIter::new(left.iter(), right.iter()).collect::<Vec<&u32>>(),
+            vec![&1, &2, &4, &5, &7, &8],
+        );
+    }
+}
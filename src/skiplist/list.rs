@@ -1,8 +1,11 @@
+use crate::seed;
+use alloc::vec::Vec;
+use core::cmp;
+use core::mem;
+use core::ops::{Add, Index, IndexMut};
+use core::ptr;
 use rand::Rng;
 use rand::XorShiftRng;
-use std::mem;
-use std::ops::{Add, Index, IndexMut};
-use std::ptr;
 
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -19,6 +22,7 @@ struct Node<T> {
 }
 
 const MAX_HEIGHT: usize = 32;
+const DEFAULT_PROBABILITY: f64 = 0.25;
 
 impl<T> Node<T> {
     pub fn new(value: T, links_len: usize) -> *mut Self {
@@ -30,11 +34,11 @@ impl<T> Node<T> {
     }
 
     pub fn get_pointer(&self, height: usize) -> &Link<T> {
-        unsafe { self.links.get_unchecked(height) }
+        unsafe { &*self.links.as_ptr().add(height) }
     }
 
     pub fn get_pointer_mut(&mut self, height: usize) -> &mut Link<T> {
-        unsafe { self.links.get_unchecked_mut(height) }
+        unsafe { &mut *self.links.as_mut_ptr().add(height) }
     }
 
     fn get_size_in_u64s(links_len: usize) -> usize {
@@ -51,7 +55,7 @@ impl<T> Node<T> {
         mem::forget(v);
         ptr::write(&mut (*ptr).links_len, links_len);
         // fill with null pointers
-        ptr::write_bytes((*ptr).links.get_unchecked_mut(0), 0, links_len);
+        ptr::write_bytes((*ptr).links.as_mut_ptr(), 0, links_len);
         ptr
     }
 
@@ -72,9 +76,13 @@ impl<T> Node<T> {
 /// A skiplist is a probabilistic data structure that allows for binary search tree operations by
 /// maintaining a linked hierarchy of subsequences. The first subsequence is essentially a sorted
 /// linked list of all the elements that it contains. Each successive subsequence contains
-/// approximately half the elements of the previous subsequence. Using the sparser subsequences,
-/// elements can be skipped and searching, insertion, and deletion of keys can be done in
-/// approximately logarithm time.
+/// approximately a quarter of the elements of the previous subsequence by default. Using the
+/// sparser subsequences, elements can be skipped and searching, insertion, and deletion of keys
+/// can be done in approximately logarithm time.
+///
+/// The heights of new nodes are capped at the number of levels that the current length of the list
+/// needs, and the head of the list only grows as tall as its tallest node, so small lists do not
+/// pay for the levels of large ones.
 ///
 /// Each link in this skiplist store the width of the link. The width is defined as the number of
 /// bottom layer links being traversed by each of the higher layer links. This augmentation allows
@@ -101,9 +109,15 @@ impl<T> Node<T> {
 pub struct SkipList<T> {
     head: *mut Node<T>,
     rng: XorShiftRng,
+    probability: f64,
     len: usize,
 }
 
+// the list uniquely owns its nodes, and the raw pointers are only dereferenced through `&self` to
+// read values or through `&mut self` to modify them, so the list behaves like a `Vec<T>`
+unsafe impl<T: Send> Send for SkipList<T> {}
+unsafe impl<T: Sync> Sync for SkipList<T> {}
+
 impl<T> SkipList<T> {
     /// Constructs a new, empty `SkipList<T>`.
     ///
@@ -115,15 +129,129 @@ impl<T> SkipList<T> {
     /// let list: SkipList<u32> = SkipList::new();
     /// ```
     pub fn new() -> Self {
+        Self::with_rng(seed::new_rng())
+    }
+
+    /// Constructs a new, empty `SkipList<T>` that uses `rng` to generate the heights of its nodes.
+    /// Seeding `rng` makes the internal structure reproducible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipList;
+    /// use rand::{SeedableRng, XorShiftRng};
+    ///
+    /// let list: SkipList<u32> = SkipList::with_rng(XorShiftRng::from_seed([1, 1, 1, 1]));
+    /// ```
+    pub fn with_rng(rng: XorShiftRng) -> Self {
         SkipList {
-            head: unsafe { Node::allocate(MAX_HEIGHT + 1) },
-            rng: XorShiftRng::new_unseeded(),
+            head: unsafe { Node::allocate(1) },
+            rng,
+            probability: DEFAULT_PROBABILITY,
             len: 0,
         }
     }
 
+    /// Constructs a new, empty `SkipList<T>` that generates the heights of its nodes with a random
+    /// number generator seeded with `seed`. Lists constructed with the same seed have the same
+    /// internal structure after the same sequence of operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipList;
+    ///
+    /// let list: SkipList<u32> = SkipList::with_seed(1);
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(seed::rng_from_seed(seed))
+    }
+
+    /// Constructs a new, empty `SkipList<T>` where each node is promoted to the next level with
+    /// probability `probability`. A lower probability uses fewer links per node at the cost of
+    /// longer searches on each level. The default probability is `0.25`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is not strictly between `0` and `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipList;
+    ///
+    /// let mut list = SkipList::with_probability(0.5);
+    /// list.push_back(1);
+    /// assert_eq!(list.probability(), 0.5);
+    /// assert_eq!(list.get(0), Some(&1));
+    /// ```
+    pub fn with_probability(probability: f64) -> Self {
+        assert!(
+            probability > 0.0 && probability < 1.0,
+            "Error: probability must be strictly between 0 and 1."
+        );
+        let mut ret = Self::new();
+        ret.probability = probability;
+        ret
+    }
+
+    /// Returns the probability that a node is promoted to the next level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipList;
+    ///
+    /// let list: SkipList<u32> = SkipList::new();
+    /// assert_eq!(list.probability(), 0.25);
+    /// ```
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    fn get_starting_height(&self) -> usize {
+        unsafe { (*self.head).links_len - 1 }
+    }
+
+    // Returns the maximum height of a new node, which is the number of levels that are expected to
+    // contain at least one node in a list with the current length.
+    fn get_max_height(&self) -> usize {
+        let mut height = 0;
+        let mut expected_len = self.len as f64 * self.probability;
+        while expected_len >= 1.0 {
+            expected_len *= self.probability;
+            height += 1;
+        }
+        height + 1
+    }
+
     fn gen_random_height(&mut self) -> usize {
-        self.rng.next_u32().leading_zeros() as usize
+        let max_height = cmp::min(self.get_max_height(), MAX_HEIGHT);
+        let threshold = (self.probability * f64::from(u32::MAX)) as u32;
+        let mut height = 0;
+        while height < max_height && self.rng.next_u32() < threshold {
+            height += 1;
+        }
+        height
+    }
+
+    // Grows the head so that it has at least `links_len` links. The distance of a link to the end
+    // of the list is the number of values after its node, so the new links span the whole list.
+    unsafe fn grow_head(&mut self, links_len: usize, len: usize) {
+        let old_links_len = (*self.head).links_len;
+        if links_len <= old_links_len {
+            return;
+        }
+        let new_head = Node::allocate(links_len);
+        ptr::copy_nonoverlapping(
+            (*self.head).links.as_ptr(),
+            (*new_head).links.as_mut_ptr(),
+            old_links_len,
+        );
+        for height in old_links_len..links_len {
+            (*new_head).get_pointer_mut(height).distance = len;
+        }
+        Node::deallocate(mem::replace(&mut self.head, new_head));
     }
 
     /// Inserts a value into the list at a particular index, shifting elements one position to the
@@ -145,7 +273,10 @@ impl<T> SkipList<T> {
         self.len += 1;
         let new_height = self.gen_random_height();
         let new_node = Node::new(value, new_height + 1);
-        let mut curr_height = MAX_HEIGHT;
+        let len = self.len - 1;
+        unsafe { self.grow_head(new_height + 1, len) };
+        let links_len = unsafe { (*self.head).links_len };
+        let mut curr_height = links_len - 1;
         let mut last_nodes = [(self.head, 0); MAX_HEIGHT + 1];
         let mut curr_node = &mut self.head;
 
@@ -177,7 +308,7 @@ impl<T> SkipList<T> {
                 curr_height -= 1;
             }
 
-            for i in 1..=MAX_HEIGHT {
+            for i in 1..links_len {
                 last_nodes[i].1 += last_nodes[i - 1].1;
                 if i <= new_height {
                     (*last_nodes[i].0).get_pointer_mut(i).distance = last_nodes[i - 1].1 + 1;
@@ -202,7 +333,7 @@ impl<T> SkipList<T> {
     /// ```
     pub fn remove(&mut self, mut index: usize) -> T {
         assert!(index < self.len);
-        let mut curr_height = MAX_HEIGHT;
+        let mut curr_height = self.get_starting_height();
         let mut curr_node = &mut self.head;
 
         unsafe {
@@ -214,20 +345,20 @@ impl<T> SkipList<T> {
                     curr_node = &mut mem::replace(&mut next_link, next_next_link).next;
                 }
 
-                if !next_link.next.is_null() {
-                    if next_link.distance == index + 1 {
-                        let Link { next, distance } = *next_link;
-                        mem::swap(next_link, (*next).get_pointer_mut(curr_height));
-                        next_link.distance += distance - 1;
-                        if curr_height == 0 {
-                            let ret = ptr::read(&(*next).value);
-                            Node::deallocate(next);
-                            self.len -= 1;
-                            return ret;
-                        }
-                    } else {
-                        next_link.distance -= 1;
+                // the distance of a null link is the number of values after its node, so it also
+                // shrinks when a value after it is removed
+                if !next_link.next.is_null() && next_link.distance == index + 1 {
+                    let Link { next, distance } = *next_link;
+                    mem::swap(next_link, (*next).get_pointer_mut(curr_height));
+                    next_link.distance += distance - 1;
+                    if curr_height == 0 {
+                        let ret = ptr::read(&(*next).value);
+                        Node::deallocate(next);
+                        self.len -= 1;
+                        return ret;
                     }
+                } else {
+                    next_link.distance -= 1;
                 }
 
                 curr_height -= 1;
@@ -323,7 +454,7 @@ impl<T> SkipList<T> {
     /// assert_eq!(list.get(0), Some(&2));
     /// ```
     pub fn get(&self, mut index: usize) -> Option<&T> {
-        let mut curr_height = MAX_HEIGHT;
+        let mut curr_height = self.get_starting_height();
         let mut curr_node = &self.head;
 
         unsafe {
@@ -363,7 +494,7 @@ impl<T> SkipList<T> {
     /// assert_eq!(list.get(0), Some(&2));
     /// ```
     pub fn get_mut(&mut self, mut index: usize) -> Option<&mut T> {
-        let mut curr_height = MAX_HEIGHT;
+        let mut curr_height = self.get_starting_height();
         let mut curr_node = &self.head;
 
         unsafe {
@@ -439,7 +570,7 @@ impl<T> SkipList<T> {
                 let next_node = (*curr_node).get_pointer(0).next;
                 Node::free(mem::replace(&mut curr_node, next_node));
             }
-            ptr::write_bytes((*self.head).links.get_unchecked_mut(0), 0, MAX_HEIGHT + 1);
+            ptr::write_bytes((*self.head).links.as_mut_ptr(), 0, (*self.head).links_len);
         }
     }
 
@@ -467,6 +598,111 @@ impl<T> SkipList<T> {
         }
     }
 
+    /// Returns an iterator over the list starting at a particular index. The iterator will be
+    /// empty if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipList;
+    ///
+    /// let mut list = SkipList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    ///
+    /// let mut iterator = list.iter_from_index(1);
+    /// assert_eq!(iterator.next(), Some(&2));
+    /// assert_eq!(iterator.next(), Some(&3));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter_from_index(&self, index: usize) -> SkipListIter<'_, T> {
+        let mut index = cmp::min(index, self.len);
+        let mut curr_node = self.head;
+
+        unsafe {
+            for height in (0..(*self.head).links_len).rev() {
+                loop {
+                    let Link { next, distance } = *(*curr_node).get_pointer(height);
+                    if next.is_null() || distance > index {
+                        break;
+                    }
+                    index -= distance;
+                    curr_node = next;
+                }
+            }
+            SkipListIter {
+                current: &(*curr_node).get_pointer(0).next,
+            }
+        }
+    }
+
+    /// Returns a cursor that points at the first value of the list, or past the end of the list if
+    /// it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipList;
+    ///
+    /// let mut list = SkipList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    ///
+    /// let mut cursor = list.cursor_front();
+    /// assert_eq!(cursor.current(), Some(&1));
+    /// cursor.move_next();
+    /// assert_eq!(cursor.current(), Some(&2));
+    /// ```
+    pub fn cursor_front(&mut self) -> SkipListCursor<'_, T> {
+        SkipListCursor {
+            last_nodes: [(self.head, 0); MAX_HEIGHT + 1],
+            list: self,
+        }
+    }
+
+    /// Returns a cursor that points at the value at a particular index, or past the end of the
+    /// list if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipList;
+    ///
+    /// let mut list = SkipList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    ///
+    /// let mut cursor = list.cursor_at(1);
+    /// assert_eq!(cursor.current(), Some(&2));
+    /// ```
+    pub fn cursor_at(&mut self, index: usize) -> SkipListCursor<'_, T> {
+        let index = cmp::min(index, self.len);
+        let mut last_nodes = [(self.head, 0); MAX_HEIGHT + 1];
+        let mut curr_node = self.head;
+        let mut curr_index = 0;
+
+        unsafe {
+            let links_len = (*self.head).links_len;
+            for (height, last_node) in last_nodes[..links_len].iter_mut().enumerate().rev() {
+                loop {
+                    let Link { next, distance } = *(*curr_node).get_pointer(height);
+                    if next.is_null() || curr_index + distance > index {
+                        break;
+                    }
+                    curr_index += distance;
+                    curr_node = next;
+                }
+                *last_node = (curr_node, curr_index);
+            }
+        }
+
+        SkipListCursor {
+            list: self,
+            last_nodes,
+        }
+    }
+
     /// Returns a mutable iterator over the list.
     ///
     /// # Examples
@@ -516,9 +752,9 @@ impl<T> IntoIterator for SkipList<T> {
     fn into_iter(self) -> Self::IntoIter {
         unsafe {
             let ret = Self::IntoIter {
-                current: (*self.head).links.get_unchecked_mut(0).next,
+                current: (*(*self.head).links.as_mut_ptr()).next,
             };
-            ptr::write_bytes((*self.head).links.get_unchecked_mut(0), 0, MAX_HEIGHT + 1);
+            ptr::write_bytes((*self.head).links.as_mut_ptr(), 0, (*self.head).links_len);
             ret
         }
     }
@@ -555,6 +791,9 @@ pub struct SkipListIntoIter<T> {
     current: *mut Node<T>,
 }
 
+unsafe impl<T: Send> Send for SkipListIntoIter<T> {}
+unsafe impl<T: Sync> Sync for SkipListIntoIter<T> {}
+
 impl<T> Iterator for SkipListIntoIter<T> {
     type Item = T;
 
@@ -591,6 +830,10 @@ pub struct SkipListIter<'a, T> {
     current: &'a *mut Node<T>,
 }
 
+// the iterator only yields shared references, so it behaves like a `&'a T`
+unsafe impl<'a, T: Sync> Send for SkipListIter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for SkipListIter<'a, T> {}
+
 impl<'a, T> Iterator for SkipListIter<'a, T>
 where
     T: 'a,
@@ -618,6 +861,10 @@ pub struct SkipListIterMut<'a, T> {
     current: &'a mut *mut Node<T>,
 }
 
+// the iterator yields each mutable reference once, so it behaves like a `&'a mut T`
+unsafe impl<'a, T: Send> Send for SkipListIterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for SkipListIterMut<'a, T> {}
+
 impl<'a, T> Iterator for SkipListIterMut<'a, T>
 where
     T: 'a,
@@ -638,6 +885,237 @@ where
     }
 }
 
+/// A cursor over the values of a `SkipList<T>`.
+///
+/// A cursor points at a value of the list or past its last value. It keeps the last node before
+/// its position on every level of the skiplist, so it can move in both directions, and remove the
+/// value that it points at, without searching from the head of the list.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::skiplist::SkipList;
+///
+/// let mut list = SkipList::new();
+/// for value in &[1, 1, 2, 3, 3, 3, 4] {
+///     list.push_back(*value);
+/// }
+///
+/// // removes consecutive duplicates in linear time
+/// let mut cursor = list.cursor_front();
+/// let mut prev = None;
+/// while let Some(&value) = cursor.current() {
+///     if prev == Some(value) {
+///         cursor.remove_current();
+///     } else {
+///         prev = Some(value);
+///         cursor.move_next();
+///     }
+/// }
+/// assert_eq!(list.iter().collect::<Vec<&u32>>(), vec![&1, &2, &3, &4]);
+/// ```
+pub struct SkipListCursor<'a, T> {
+    list: &'a mut SkipList<T>,
+    last_nodes: [(*mut Node<T>, usize); MAX_HEIGHT + 1],
+}
+
+impl<'a, T> SkipListCursor<'a, T> {
+    fn current_node(&self) -> *mut Node<T> {
+        unsafe { (*self.last_nodes[0].0).get_pointer(0).next }
+    }
+
+    /// Returns the index of the value that the cursor points at, or the length of the list if the
+    /// cursor is past its last value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipList;
+    ///
+    /// let mut list = SkipList::new();
+    /// list.push_back(1);
+    ///
+    /// let mut cursor = list.cursor_front();
+    /// assert_eq!(cursor.index(), 0);
+    /// cursor.move_next();
+    /// assert_eq!(cursor.index(), 1);
+    /// ```
+    pub fn index(&self) -> usize {
+        self.last_nodes[0].1
+    }
+
+    /// Returns the value that the cursor points at, or `None` if the cursor is past the last value
+    /// of the list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipList;
+    ///
+    /// let mut list = SkipList::new();
+    /// list.push_back(1);
+    ///
+    /// let mut cursor = list.cursor_front();
+    /// assert_eq!(cursor.current(), Some(&1));
+    /// cursor.move_next();
+    /// assert_eq!(cursor.current(), None);
+    /// ```
+    pub fn current(&self) -> Option<&T> {
+        let node = self.current_node();
+        if node.is_null() {
+            None
+        } else {
+            unsafe { Some(&(*node).value) }
+        }
+    }
+
+    /// Returns a mutable reference to the value that the cursor points at, or `None` if the cursor
+    /// is past the last value of the list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipList;
+    ///
+    /// let mut list = SkipList::new();
+    /// list.push_back(1);
+    ///
+    /// let mut cursor = list.cursor_front();
+    /// if let Some(value) = cursor.current_mut() {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(list.get(0), Some(&2));
+    /// ```
+    pub fn current_mut(&mut self) -> Option<&mut T> {
+        let node = self.current_node();
+        if node.is_null() {
+            None
+        } else {
+            unsafe { Some(&mut (*node).value) }
+        }
+    }
+
+    /// Moves the cursor to the next value of the list. Returns `false` and does not move if the
+    /// cursor is past the last value of the list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipList;
+    ///
+    /// let mut list = SkipList::new();
+    /// list.push_back(1);
+    ///
+    /// let mut cursor = list.cursor_front();
+    /// assert!(cursor.move_next());
+    /// assert!(!cursor.move_next());
+    /// ```
+    pub fn move_next(&mut self) -> bool {
+        let node = self.current_node();
+        if node.is_null() {
+            return false;
+        }
+        let index = self.last_nodes[0].1 + 1;
+        unsafe {
+            for last_node in &mut self.last_nodes[..(*node).links_len] {
+                *last_node = (node, index);
+            }
+        }
+        true
+    }
+
+    /// Moves the cursor to the previous value of the list. Returns `false` and does not move if
+    /// the cursor points at the first value of the list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipList;
+    ///
+    /// let mut list = SkipList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    ///
+    /// let mut cursor = list.cursor_at(1);
+    /// assert!(cursor.move_prev());
+    /// assert_eq!(cursor.current(), Some(&1));
+    /// assert!(!cursor.move_prev());
+    /// ```
+    pub fn move_prev(&mut self) -> bool {
+        let prev_node = self.last_nodes[0].0;
+        if prev_node == self.list.head {
+            return false;
+        }
+        unsafe {
+            // the previous node is the last node on every level that it is on, so the last node
+            // before it on those levels is found by walking forward from the level above
+            let links_len = (*self.list.head).links_len;
+            for height in (0..links_len).rev() {
+                if self.last_nodes[height].0 != prev_node {
+                    continue;
+                }
+                let (mut curr_node, mut curr_index) = if height + 1 < links_len {
+                    self.last_nodes[height + 1]
+                } else {
+                    (self.list.head, 0)
+                };
+                loop {
+                    let Link { next, distance } = *(*curr_node).get_pointer(height);
+                    if next == prev_node {
+                        break;
+                    }
+                    curr_node = next;
+                    curr_index += distance;
+                }
+                self.last_nodes[height] = (curr_node, curr_index);
+            }
+        }
+        true
+    }
+
+    /// Removes the value that the cursor points at and moves the cursor to the next value.
+    /// Returns the removed value, or `None` if the cursor is past the last value of the list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipList;
+    ///
+    /// let mut list = SkipList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    ///
+    /// let mut cursor = list.cursor_front();
+    /// assert_eq!(cursor.remove_current(), Some(1));
+    /// assert_eq!(cursor.current(), Some(&2));
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current_node();
+        if node.is_null() {
+            return None;
+        }
+        unsafe {
+            let links_len = (*self.list.head).links_len;
+            for (height, &(last_node, _)) in self.last_nodes[..links_len].iter().enumerate() {
+                let link = (*last_node).get_pointer_mut(height);
+                if link.next == node {
+                    let Link { next, distance } = *(*node).get_pointer(height);
+                    link.next = next;
+                    link.distance = link.distance + distance - 1;
+                } else {
+                    link.distance -= 1;
+                }
+            }
+
+            self.list.len -= 1;
+            let ret = ptr::read(&(*node).value);
+            Node::deallocate(node);
+            Some(ret)
+        }
+    }
+}
+
 impl<T> Default for SkipList<T> {
     fn default() -> Self {
         Self::new()
@@ -648,11 +1126,17 @@ impl<T> Add for SkipList<T> {
     type Output = SkipList<T>;
 
     fn add(mut self, other: SkipList<T>) -> SkipList<T> {
-        self.len += other.len();
-
-        let mut curr_nodes = [self.head; MAX_HEIGHT + 1];
         unsafe {
-            let mut curr_height = MAX_HEIGHT;
+            // the nodes of `other` keep their heights, so the head needs to be as tall as the
+            // taller head
+            let len = self.len;
+            self.grow_head((*other.head).links_len, len);
+            self.len += other.len();
+            let links_len = (*self.head).links_len;
+            let other_links_len = (*other.head).links_len;
+            let mut curr_nodes = [self.head; MAX_HEIGHT + 1];
+
+            let mut curr_height = links_len - 1;
             let mut curr_node = self.head;
             while !curr_node.is_null() {
                 while (*curr_node).get_pointer(curr_height).next.is_null() {
@@ -665,7 +1149,12 @@ impl<T> Add for SkipList<T> {
                 curr_node = (*curr_node).get_pointer(curr_height).next;
             }
 
-            for (i, curr_node) in curr_nodes.iter_mut().enumerate().take(MAX_HEIGHT + 1) {
+            for (i, curr_node) in curr_nodes[..links_len].iter_mut().enumerate() {
+                // the levels above the head of `other` skip over all of its values
+                if i >= other_links_len {
+                    (**curr_node).get_pointer_mut(i).distance += other.len();
+                    continue;
+                }
                 mem::swap(
                     (**curr_node).get_pointer_mut(i),
                     (*other.head).get_pointer_mut(i),
@@ -694,39 +1183,31 @@ impl<T> IndexMut<usize> for SkipList<T> {
 
 #[cfg(test)]
 mod tests {
-    use super::SkipList;
-    use std::mem;
+    use super::{Link, SkipList};
 
-    pub fn check_valid<T>(list: &mut SkipList<T>)
-    where
-        T: PartialEq,
-    {
+    pub fn check_valid<T>(list: &mut SkipList<T>) {
         unsafe {
-            let mut curr_node = &mut (*list.head).get_pointer_mut(0).next;
-            let mut actual = vec![];
+            let mut nodes = vec![list.head];
+            let mut curr_node = (*list.head).get_pointer(0).next;
             while !curr_node.is_null() {
-                actual.push(&(**curr_node).value);
-                let mut next_link = (**curr_node).get_pointer_mut(0);
-                let next_next_link = (*next_link.next).get_pointer_mut(0);
-                curr_node = &mut mem::replace(&mut next_link, next_next_link).next;
+                nodes.push(curr_node);
+                curr_node = (*curr_node).get_pointer(0).next;
             }
-
-            for i in 1..=super::MAX_HEIGHT {
-                let mut curr_node = &mut (*list.head).get_pointer_mut(i).next;
-                while !curr_node.is_null() {
-                    let x = &(**curr_node).value;
-                    let mut next_link = (**curr_node).get_pointer_mut(i);
-                    let next_link_distance = next_link.distance;
-                    let next_next_link = (*next_link.next).get_pointer_mut(0);
-
-                    curr_node = &mut mem::replace(&mut next_link, next_next_link).next;
-                    if !curr_node.is_null() {
-                        let y = &(**curr_node).value;
-                        let x_index = actual.iter().position(|&n| n == x).unwrap();
-                        let y_index = actual.iter().position(|&n| n == y).unwrap();
-
-                        assert_eq!(next_link_distance, y_index - x_index);
+            assert_eq!(nodes.len(), list.len() + 1);
+
+            for i in 0..(*list.head).links_len {
+                let mut curr_node = list.head;
+                loop {
+                    let Link { next, distance } = *(*curr_node).get_pointer(i);
+                    let curr_index = nodes.iter().position(|&n| n == curr_node).unwrap();
+                    if next.is_null() {
+                        // the distance of a null link is the number of values after its node
+                        assert_eq!(distance, list.len() - curr_index);
+                        break;
                     }
+                    let next_index = nodes.iter().position(|&n| n == next).unwrap();
+                    assert_eq!(distance, next_index - curr_index);
+                    curr_node = next;
                 }
             }
         }
@@ -838,6 +1319,35 @@ mod tests {
         assert_eq!(res.len(), 6);
     }
 
+    #[test]
+    fn test_add_after_remove() {
+        let mut n = SkipList::with_seed(1);
+        let mut m = SkipList::with_seed(2);
+        for i in 0..100 {
+            n.push_back(i);
+            m.push_back(i + 100);
+        }
+        for _ in 0..10 {
+            n.pop_back();
+            n.pop_front();
+        }
+        {
+            let mut cursor = m.cursor_at(50);
+            for _ in 0..50 {
+                cursor.remove_current();
+            }
+        }
+
+        let mut res = n + m;
+        let expected = (10..90).chain(100..150).collect::<Vec<u32>>();
+
+        check_valid(&mut res);
+        assert_eq!(res.len(), expected.len());
+        for (index, value) in expected.iter().enumerate() {
+            assert_eq!(res.get(index), Some(value));
+        }
+    }
+
     #[test]
     fn test_into_iter() {
         let mut list = SkipList::new();
@@ -874,4 +1384,111 @@ mod tests {
         check_valid(&mut list);
         assert_eq!(list.iter().collect::<Vec<&u32>>(), vec![&3, &4, &2]);
     }
+
+    #[test]
+    fn test_iter_from_index() {
+        let mut list = SkipList::new();
+        for value in 0..100 {
+            list.push_back(value);
+        }
+
+        for index in 0..=100 {
+            assert!(list.iter_from_index(index).cloned().eq(index..100));
+        }
+        assert_eq!(list.iter_from_index(200).next(), None);
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut list = SkipList::with_seed(1);
+        let mut expected = Vec::new();
+        for value in 0..200 {
+            list.push_back(value);
+            expected.push(value);
+        }
+
+        {
+            let mut cursor = list.cursor_at(50);
+            assert_eq!(cursor.current(), Some(&50));
+            for _ in 0..20 {
+                assert!(cursor.move_prev());
+            }
+            assert_eq!(cursor.index(), 30);
+            while let Some(&value) = cursor.current() {
+                if value % 3 == 0 {
+                    assert_eq!(cursor.remove_current(), Some(value));
+                } else {
+                    *cursor.current_mut().unwrap() += 1000;
+                    cursor.move_next();
+                }
+            }
+            assert_eq!(cursor.index(), 143);
+            while cursor.move_prev() {}
+            assert_eq!(cursor.index(), 0);
+            assert_eq!(cursor.current(), Some(&0));
+        }
+        expected = expected
+            .into_iter()
+            .enumerate()
+            .filter(|&(index, value)| index < 30 || value % 3 != 0)
+            .map(|(index, value)| if index < 30 { value } else { value + 1000 })
+            .collect();
+
+        check_valid(&mut list);
+        assert_eq!(list.len(), expected.len());
+        assert_eq!(list.iter().cloned().collect::<Vec<u32>>(), expected);
+        for (index, value) in expected.iter().enumerate() {
+            assert_eq!(list.get(index), Some(value));
+        }
+    }
+
+    #[test]
+    fn test_height_cap() {
+        let mut list = SkipList::with_seed(1);
+        unsafe {
+            assert_eq!((*list.head).links_len, 1);
+            for value in 0..1000 {
+                list.push_back(value);
+                assert!((*list.head).links_len <= list.get_max_height() + 1);
+            }
+
+            let mut curr_node = (*list.head).get_pointer(0).next;
+            while !curr_node.is_null() {
+                assert!((*curr_node).links_len <= (*list.head).links_len);
+                curr_node = (*curr_node).get_pointer(0).next;
+            }
+        }
+        check_valid(&mut list);
+        assert_eq!(
+            list.iter().cloned().collect::<Vec<u32>>(),
+            (0..1000).collect::<Vec<u32>>()
+        );
+    }
+
+    #[test]
+    fn test_with_probability() {
+        let mut list = SkipList::with_probability(0.5);
+        let mut expected = Vec::new();
+        for value in 0..1000 {
+            list.push_back(value);
+            expected.push(value);
+        }
+        for index in (0..1000).step_by(3).rev() {
+            assert_eq!(list.remove(index), index as u32);
+        }
+        expected.retain(|value| value % 3 != 0);
+
+        check_valid(&mut list);
+        assert_eq!(list.probability(), 0.5);
+        assert_eq!(list.iter().cloned().collect::<Vec<u32>>(), expected);
+        for (index, value) in expected.iter().enumerate() {
+            assert_eq!(list.get(index), Some(value));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_with_probability_invalid() {
+        SkipList::<u32>::with_probability(0.0);
+    }
 }
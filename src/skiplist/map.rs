@@ -1,20 +1,34 @@
+use crate::compare::{Compare, Natural};
 use crate::entry::Entry;
+use crate::ordered::OrderedMap;
+use crate::seed;
+use crate::set_ops::{DifferenceIter, IntersectionIter, SymmetricDifferenceIter, UnionIter};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::{Add, Bound, Index, IndexMut, RangeBounds, Sub};
+use core::ptr;
 use rand::Rng;
 use rand::XorShiftRng;
-use std::borrow::Borrow;
-use std::cmp;
-use std::mem;
-use std::ops::{Add, Index, IndexMut, Sub};
-use std::ptr;
+
+#[repr(C)]
+struct Link<T, U> {
+    next: *mut Node<T, U>,
+    distance: usize,
+}
 
 #[repr(C)]
 struct Node<T, U> {
     links_len: usize,
     entry: Entry<T, U>,
-    links: [*mut Node<T, U>; 0],
+    links: [Link<T, U>; 0],
 }
 
 const MAX_HEIGHT: usize = 32;
+const DEFAULT_PROBABILITY: f64 = 0.25;
 
 impl<T, U> Node<T, U> {
     pub fn new(key: T, value: U, links_len: usize) -> *mut Self {
@@ -26,19 +40,27 @@ impl<T, U> Node<T, U> {
     }
 
     pub fn get_pointer(&self, height: usize) -> &*mut Node<T, U> {
-        unsafe { self.links.get_unchecked(height) }
+        unsafe { &(*self.links.as_ptr().add(height)).next }
     }
 
     pub fn get_pointer_mut(&mut self, height: usize) -> &mut *mut Node<T, U> {
-        unsafe { self.links.get_unchecked_mut(height) }
+        unsafe { &mut (*self.links.as_mut_ptr().add(height)).next }
+    }
+
+    pub fn get_link(&self, height: usize) -> &Link<T, U> {
+        unsafe { &*self.links.as_ptr().add(height) }
+    }
+
+    pub fn get_link_mut(&mut self, height: usize) -> &mut Link<T, U> {
+        unsafe { &mut *self.links.as_mut_ptr().add(height) }
     }
 
     fn get_size_in_u64s(links_len: usize) -> usize {
         let base_size = mem::size_of::<Node<T, U>>();
-        let ptr_size = mem::size_of::<*mut Node<T, U>>();
+        let link_size = mem::size_of::<Link<T, U>>();
         let u64_size = mem::size_of::<u64>();
 
-        (base_size + ptr_size * links_len + u64_size - 1) / u64_size
+        (base_size + link_size * links_len).div_ceil(u64_size)
     }
 
     unsafe fn allocate(links_len: usize) -> *mut Self {
@@ -46,8 +68,8 @@ impl<T, U> Node<T, U> {
         let ptr = v.as_mut_ptr() as *mut Node<T, U>;
         mem::forget(v);
         ptr::write(&mut (*ptr).links_len, links_len);
-        // fill with null pointers
-        ptr::write_bytes((*ptr).links.get_unchecked_mut(0), 0, links_len);
+        // fill with null links
+        ptr::write_bytes((*ptr).links.as_mut_ptr(), 0, links_len);
         ptr
     }
 
@@ -68,9 +90,13 @@ impl<T, U> Node<T, U> {
 /// A skiplist is a probabilistic data structure that allows for binary search tree operations by
 /// maintaining a linked hierarchy of subsequences. The first subsequence is essentially a sorted
 /// linked list of all the elements that it contains. Each successive subsequence contains
-/// approximately half the elements of the previous subsequence. Using the sparser subsequences,
-/// elements can be skipped and searching, insertion, and deletion of entries can be done in
-/// approximately logarithm time.
+/// approximately a quarter of the elements of the previous subsequence by default. Using the
+/// sparser subsequences, elements can be skipped and searching, insertion, and deletion of entries
+/// can be done in approximately logarithm time.
+///
+/// The heights of new nodes are capped at the number of levels that the current length of the map
+/// needs, and the head of the map only grows as tall as its tallest node, so small maps do not pay
+/// for the levels of large ones.
 ///
 /// # Examples
 ///
@@ -92,12 +118,19 @@ impl<T, U> Node<T, U> {
 /// assert_eq!(map.remove(&0), Some((0, 2)));
 /// assert_eq!(map.remove(&1), None);
 /// ```
-pub struct SkipMap<T, U> {
+pub struct SkipMap<T, U, C = Natural> {
     head: *mut Node<T, U>,
     rng: XorShiftRng,
+    probability: f64,
     len: usize,
+    compare: C,
 }
 
+// the map uniquely owns its nodes, and the raw pointers are only dereferenced through `&self` to
+// read entries or through `&mut self` to modify them, so the map behaves like a `Vec<(T, U)>`
+unsafe impl<T: Send, U: Send, C: Send> Send for SkipMap<T, U, C> {}
+unsafe impl<T: Sync, U: Sync, C: Sync> Sync for SkipMap<T, U, C> {}
+
 impl<T, U> SkipMap<T, U> {
     /// Constructs a new, empty `SkipMap<T, U>`.
     ///
@@ -109,19 +142,199 @@ impl<T, U> SkipMap<T, U> {
     /// let map: SkipMap<u32, u32> = SkipMap::new();
     /// ```
     pub fn new() -> Self {
+        Self::with_rng(seed::new_rng())
+    }
+
+    /// Constructs a new, empty `SkipMap<T, U>` that uses `rng` to generate the heights of its
+    /// nodes. Seeding `rng` makes the internal structure reproducible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    /// use rand::{SeedableRng, XorShiftRng};
+    ///
+    /// let map: SkipMap<u32, u32> = SkipMap::with_rng(XorShiftRng::from_seed([1, 1, 1, 1]));
+    /// ```
+    pub fn with_rng(rng: XorShiftRng) -> Self {
+        Self::with_rng_and_comparator(rng, Natural)
+    }
+
+    /// Constructs a new, empty `SkipMap<T, U>` that generates the heights of its nodes with a
+    /// random number generator seeded with `seed`. Maps constructed with the same seed have the
+    /// same internal structure after the same sequence of operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let map: SkipMap<u32, u32> = SkipMap::with_seed(1);
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(seed::rng_from_seed(seed))
+    }
+
+    /// Constructs a new, empty `SkipMap<T, U>` where each node is promoted to the next level with
+    /// probability `probability`. A lower probability uses fewer links per node at the cost of
+    /// longer searches on each level. The default probability is `0.25`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is not strictly between `0` and `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::with_probability(0.5);
+    /// map.insert(1, 1);
+    /// assert_eq!(map.probability(), 0.5);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// ```
+    pub fn with_probability(probability: f64) -> Self {
+        assert!(
+            probability > 0.0 && probability < 1.0,
+            "Error: probability must be strictly between 0 and 1."
+        );
+        let mut ret = Self::new();
+        ret.probability = probability;
+        ret
+    }
+}
+
+impl<T, U, C> SkipMap<T, U, C> {
+    /// Constructs a new, empty `SkipMap<T, U, C>` that uses `compare` to order keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::with_comparator(|a: &u32, b: &u32| b.cmp(a));
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.min(), Some(&2));
+    /// assert_eq!(map.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&2, &2), (&1, &1)]);
+    /// ```
+    pub fn with_comparator(compare: C) -> Self {
+        Self::with_rng_and_comparator(seed::new_rng(), compare)
+    }
+
+    /// Constructs a new, empty `SkipMap<T, U, C>` that uses `rng` to generate the heights of its
+    /// nodes and `compare` to order keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    /// use rand::{SeedableRng, XorShiftRng};
+    ///
+    /// let rng = XorShiftRng::from_seed([1, 1, 1, 1]);
+    /// let mut map = SkipMap::with_rng_and_comparator(rng, |a: &u32, b: &u32| b.cmp(a));
+    /// map.insert(1, 1);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// ```
+    pub fn with_rng_and_comparator(rng: XorShiftRng, compare: C) -> Self {
         SkipMap {
-            head: unsafe { Node::allocate(MAX_HEIGHT + 1) },
-            rng: XorShiftRng::new_unseeded(),
+            head: unsafe { Node::allocate(1) },
+            rng,
+            probability: DEFAULT_PROBABILITY,
             len: 0,
+            compare,
         }
     }
 
+    /// Returns the probability that a node is promoted to the next level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let map: SkipMap<u32, u32> = SkipMap::new();
+    /// assert_eq!(map.probability(), 0.25);
+    /// ```
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
     fn get_starting_height(&self) -> usize {
-        MAX_HEIGHT - (self.len as u32).leading_zeros() as usize
+        unsafe { (*self.head).links_len - 1 }
+    }
+
+    // Returns the maximum height of a new node, which is the number of levels that are expected to
+    // contain at least one node in a map with the current length.
+    fn get_max_height(&self) -> usize {
+        let mut height = 0;
+        let mut expected_len = self.len as f64 * self.probability;
+        while expected_len >= 1.0 {
+            expected_len *= self.probability;
+            height += 1;
+        }
+        height + 1
     }
 
     fn gen_random_height(&mut self) -> usize {
-        self.rng.next_u32().leading_zeros() as usize
+        let max_height = cmp::min(self.get_max_height(), MAX_HEIGHT);
+        let threshold = (self.probability * f64::from(u32::MAX)) as u32;
+        let mut height = 0;
+        while height < max_height && self.rng.next_u32() < threshold {
+            height += 1;
+        }
+        height
+    }
+
+    // Grows the head so that it has at least `links_len` links.
+    unsafe fn grow_head(&mut self, links_len: usize) {
+        let old_links_len = (*self.head).links_len;
+        if links_len <= old_links_len {
+            return;
+        }
+        let new_head = Node::allocate(links_len);
+        ptr::copy_nonoverlapping(
+            (*self.head).links.as_ptr(),
+            (*new_head).links.as_mut_ptr(),
+            old_links_len,
+        );
+        Node::deallocate(mem::replace(&mut self.head, new_head));
+    }
+
+    // Appends `node` at `index` to a map that is being built in ascending order, where
+    // `last_nodes` contains the last node at each height and its index. The head of the map is at
+    // index zero.
+    unsafe fn link_last(
+        last_nodes: &mut [(*mut Node<T, U>, usize)],
+        node: *mut Node<T, U>,
+        index: usize,
+    ) {
+        let links_len = (*node).links_len;
+        for (height, last_node) in last_nodes.iter_mut().enumerate().take(links_len) {
+            let link = (*last_node.0).get_link_mut(height);
+            link.next = node;
+            link.distance = index - last_node.1;
+            *last_node = (node, index);
+        }
+    }
+
+    // Returns the node at `index`, where the head of the map is at index zero, assuming that
+    // `index` is at most the length of the map.
+    fn get_node(&self, mut index: usize) -> *mut Node<T, U> {
+        let mut curr_node = self.head;
+        unsafe {
+            for height in (0..=self.get_starting_height()).rev() {
+                loop {
+                    let Link { next, distance } = *(*curr_node).get_link(height);
+                    if next.is_null() || distance > index {
+                        break;
+                    }
+                    curr_node = next;
+                    index -= distance;
+                }
+            }
+        }
+        curr_node
     }
 
     /// Inserts a key-value pair into the map. If the key already exists in the map, it will return
@@ -140,49 +353,68 @@ impl<T, U> SkipMap<T, U> {
     /// ```
     pub fn insert(&mut self, key: T, value: U) -> Option<(T, U)>
     where
-        T: Ord,
+        C: Compare<T>,
     {
-        self.len += 1;
-        let new_height = self.gen_random_height();
-        let new_node = Node::new(key, value, new_height + 1);
-        let mut curr_height = MAX_HEIGHT;
-        let mut curr_node = &mut self.head;
-        let mut ret = None;
+        let mut last_nodes = [(self.head, 0); MAX_HEIGHT + 1];
+        let mut curr_node = self.head;
+        let mut curr_index = 0;
 
         unsafe {
-            loop {
-                let mut next_node = (**curr_node).get_pointer_mut(curr_height);
-                while !next_node.is_null() && (**next_node).entry.key < (*new_node).entry.key {
-                    let next_next_node = (**next_node).get_pointer_mut(curr_height);
-                    curr_node = mem::replace(&mut next_node, next_next_node);
-                }
-
-                if !next_node.is_null() && (**next_node).entry.key == (*new_node).entry.key {
-                    let temp = *next_node;
-                    let next_next_node = *(**next_node).get_pointer_mut(curr_height);
-                    *(**curr_node).get_pointer_mut(curr_height) = next_next_node;
-                    if curr_height == 0 {
-                        ret = Some((
-                            ptr::read(&(*temp).entry.key),
-                            ptr::read(&(*temp).entry.value),
-                        ));
-                        Node::deallocate(temp);
-                        self.len -= 1;
+            let links_len = (*self.head).links_len;
+            for (height, last_node) in last_nodes[..links_len].iter_mut().enumerate().rev() {
+                loop {
+                    let Link { next, distance } = *(*curr_node).get_link(height);
+                    if next.is_null()
+                        || self.compare.compare(&(*next).entry.key, &key) != cmp::Ordering::Less
+                    {
+                        break;
                     }
+                    curr_node = next;
+                    curr_index += distance;
                 }
+                *last_node = (curr_node, curr_index);
+            }
 
-                if curr_height <= new_height {
-                    let next_node = &mut *(**curr_node).get_pointer_mut(curr_height);
-                    *(*new_node).get_pointer_mut(curr_height) = mem::replace(next_node, new_node);
-                }
+            let next_node = *(*curr_node).get_pointer(0);
+            if !next_node.is_null()
+                && self.compare.compare(&(*next_node).entry.key, &key) == cmp::Ordering::Equal
+            {
+                let Entry {
+                    key: ref mut old_key,
+                    value: ref mut old_value,
+                } = (*next_node).entry;
+                return Some((mem::replace(old_key, key), mem::replace(old_value, value)));
+            }
 
-                if curr_height == 0 {
-                    break;
+            self.len += 1;
+            let new_height = self.gen_random_height();
+            if new_height >= links_len {
+                let old_head = self.head;
+                self.grow_head(new_height + 1);
+                for last_node in &mut last_nodes[..=new_height] {
+                    if last_node.0 == old_head {
+                        last_node.0 = self.head;
+                    }
                 }
-
-                curr_height -= 1;
             }
-            ret
+            let new_node = Node::new(key, value, new_height + 1);
+            let new_index = curr_index + 1;
+            let links_len = (*self.head).links_len;
+            for (height, &(last_node, last_index)) in last_nodes[..links_len].iter().enumerate() {
+                let link = (*last_node).get_link_mut(height);
+                if height <= new_height {
+                    let new_link = (*new_node).get_link_mut(height);
+                    new_link.next = link.next;
+                    if !link.next.is_null() {
+                        new_link.distance = last_index + link.distance + 1 - new_index;
+                    }
+                    link.next = new_node;
+                    link.distance = new_index - last_index;
+                } else if !link.next.is_null() {
+                    link.distance += 1;
+                }
+            }
+            None
         }
     }
 
@@ -202,41 +434,52 @@ impl<T, U> SkipMap<T, U> {
     pub fn remove<V>(&mut self, key: &V) -> Option<(T, U)>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        let mut curr_height = MAX_HEIGHT;
-        let mut curr_node = &mut self.head;
-        let mut ret = None;
+        let mut last_nodes = [self.head; MAX_HEIGHT + 1];
+        let mut curr_node = self.head;
 
         unsafe {
-            loop {
-                let mut next_node = (**curr_node).get_pointer_mut(curr_height);
-                while !next_node.is_null() && (**next_node).entry.key.borrow() < key {
-                    let next_next_node = (**next_node).get_pointer_mut(curr_height);
-                    curr_node = mem::replace(&mut next_node, next_next_node);
-                }
-
-                if !next_node.is_null() && (**next_node).entry.key.borrow() == key {
-                    let temp = *next_node;
-                    let next_next_node = *(**next_node).get_pointer_mut(curr_height);
-                    *(**curr_node).get_pointer_mut(curr_height) = next_next_node;
-                    if curr_height == 0 {
-                        ret = Some((
-                            ptr::read(&(*temp).entry.key),
-                            ptr::read(&(*temp).entry.value),
-                        ));
-                        Node::deallocate(temp);
-                        self.len -= 1;
+            let links_len = (*self.head).links_len;
+            for (height, last_node) in last_nodes[..links_len].iter_mut().enumerate().rev() {
+                loop {
+                    let next = *(*curr_node).get_pointer(height);
+                    if next.is_null()
+                        || self.compare.compare((*next).entry.key.borrow(), key)
+                            != cmp::Ordering::Less
+                    {
+                        break;
                     }
+                    curr_node = next;
                 }
+                *last_node = curr_node;
+            }
 
-                if curr_height == 0 {
-                    break;
-                }
+            let node = *(*curr_node).get_pointer(0);
+            if node.is_null()
+                || self.compare.compare((*node).entry.key.borrow(), key) != cmp::Ordering::Equal
+            {
+                return None;
+            }
 
-                curr_height -= 1;
+            for (height, last_node) in last_nodes[..links_len].iter().enumerate() {
+                let link = (**last_node).get_link_mut(height);
+                if link.next == node {
+                    let Link { next, distance } = *(*node).get_link(height);
+                    link.next = next;
+                    if !next.is_null() {
+                        link.distance += distance - 1;
+                    }
+                } else if !link.next.is_null() {
+                    link.distance -= 1;
+                }
             }
-            ret
+
+            self.len -= 1;
+            let Entry { key, value } = ptr::read(&(*node).entry);
+            Node::deallocate(node);
+            Some((key, value))
         }
     }
 
@@ -255,7 +498,8 @@ impl<T, U> SkipMap<T, U> {
     pub fn contains_key<V>(&self, key: &V) -> bool
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
         self.get(key).is_some()
     }
@@ -276,7 +520,8 @@ impl<T, U> SkipMap<T, U> {
     pub fn get<V>(&self, key: &V) -> Option<&U>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
         let mut curr_height = self.get_starting_height();
         let mut curr_node = &self.head;
@@ -284,12 +529,18 @@ impl<T, U> SkipMap<T, U> {
         unsafe {
             loop {
                 let mut next_node = (**curr_node).get_pointer(curr_height);
-                while !next_node.is_null() && (**next_node).entry.key.borrow() < key {
+                while !next_node.is_null()
+                    && self.compare.compare((**next_node).entry.key.borrow(), key)
+                        == cmp::Ordering::Less
+                {
                     let next_next_node = (**next_node).get_pointer(curr_height);
                     curr_node = mem::replace(&mut next_node, next_next_node);
                 }
 
-                if !next_node.is_null() && (**next_node).entry.key.borrow() == key {
+                if !next_node.is_null()
+                    && self.compare.compare((**next_node).entry.key.borrow(), key)
+                        == cmp::Ordering::Equal
+                {
                     return Some(&(**next_node).entry.value);
                 }
 
@@ -319,7 +570,8 @@ impl<T, U> SkipMap<T, U> {
     pub fn get_mut<V>(&mut self, key: &V) -> Option<&mut U>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
         let mut curr_height = self.get_starting_height();
         let mut curr_node = &mut self.head;
@@ -327,12 +579,18 @@ impl<T, U> SkipMap<T, U> {
         unsafe {
             loop {
                 let mut next_node = (**curr_node).get_pointer_mut(curr_height);
-                while !next_node.is_null() && (**next_node).entry.key.borrow() < key {
+                while !next_node.is_null()
+                    && self.compare.compare((**next_node).entry.key.borrow(), key)
+                        == cmp::Ordering::Less
+                {
                     let next_next_node = (**next_node).get_pointer_mut(curr_height);
                     curr_node = mem::replace(&mut next_node, next_next_node);
                 }
 
-                if !next_node.is_null() && (**next_node).entry.key.borrow() == key {
+                if !next_node.is_null()
+                    && self.compare.compare((**next_node).entry.key.borrow(), key)
+                        == cmp::Ordering::Equal
+                {
                     return Some(&mut (**next_node).entry.value);
                 }
 
@@ -396,12 +654,12 @@ impl<T, U> SkipMap<T, U> {
                 let next_node = *(*curr_node).get_pointer(0);
                 Node::free(mem::replace(&mut curr_node, next_node));
             }
-            ptr::write_bytes((*self.head).links.get_unchecked_mut(0), 0, MAX_HEIGHT + 1);
+            ptr::write_bytes((*self.head).links.as_mut_ptr(), 0, (*self.head).links_len);
         }
     }
 
-    /// Returns a key in the map that is less than or equal to a particular key. Returns `None` if
-    /// such a key does not exist.
+    /// Retains only the key-value pairs that satisfy a predicate. The key-value pairs are visited
+    /// in ascending order of their keys. The removed nodes are unlinked in a single pass over the skiplist.
     ///
     /// # Examples
     ///
@@ -410,40 +668,46 @@ impl<T, U> SkipMap<T, U> {
     ///
     /// let mut map = SkipMap::new();
     /// map.insert(1, 1);
-    /// assert_eq!(map.floor(&0), None);
-    /// assert_eq!(map.floor(&2), Some(&1));
+    /// map.insert(2, 2);
+    /// map.insert(3, 3);
+    ///
+    /// map.retain(|key, value| {
+    ///     *value += 1;
+    ///     key % 2 == 1
+    /// });
+    /// assert_eq!(map.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&1, &2), (&3, &4)]);
     /// ```
-    pub fn floor<V>(&self, key: &V) -> Option<&T>
+    pub fn retain<F>(&mut self, mut f: F)
     where
-        T: Borrow<V>,
-        V: Ord + ?Sized,
+        F: FnMut(&T, &mut U) -> bool,
     {
-        let mut curr_height = self.get_starting_height();
-        let mut curr_node = &self.head;
-
         unsafe {
-            loop {
-                let mut next_node = (**curr_node).get_pointer(curr_height);
-                while !next_node.is_null() && (**next_node).entry.key.borrow() <= key {
-                    let next_next_node = (**next_node).get_pointer(curr_height);
-                    curr_node = mem::replace(&mut next_node, next_next_node);
-                }
-
-                if curr_height == 0 {
-                    if curr_node == &self.head {
-                        return None;
-                    } else {
-                        return Some(&(**curr_node).entry.key);
-                    }
+            let mut last_nodes = [(self.head, 0); MAX_HEIGHT + 1];
+            let mut curr_node = *(*self.head).get_pointer(0);
+            self.len = 0;
+            while !curr_node.is_null() {
+                let next_node = *(*curr_node).get_pointer(0);
+                let Entry {
+                    ref key,
+                    ref mut value,
+                } = (*curr_node).entry;
+                if f(key, value) {
+                    self.len += 1;
+                    Self::link_last(&mut last_nodes, curr_node, self.len);
+                } else {
+                    Node::free(curr_node);
                 }
-
-                curr_height -= 1;
+                curr_node = next_node;
+            }
+            let links_len = (*self.head).links_len;
+            for (height, &(last_node, _)) in last_nodes[..links_len].iter().enumerate() {
+                *(*last_node).get_pointer_mut(height) = ptr::null_mut();
             }
         }
     }
 
-    /// Returns a key in the map that is greater than or equal to a particular key. Returns `None`
-    /// if such a key does not exist.
+    /// Clears the map, returning all key-value pairs as an iterator. The iterator yields the
+    /// key-value pairs in ascending order of their keys, and the map can be reused afterwards.
     ///
     /// # Examples
     ///
@@ -452,30 +716,51 @@ impl<T, U> SkipMap<T, U> {
     ///
     /// let mut map = SkipMap::new();
     /// map.insert(1, 1);
-    /// assert_eq!(map.ceil(&0), Some(&1));
-    /// assert_eq!(map.ceil(&2), None);
+    /// map.insert(2, 2);
+    ///
+    /// assert_eq!(map.drain().collect::<Vec<(u32, u32)>>(), vec![(1, 1), (2, 2)]);
+    /// assert!(map.is_empty());
     /// ```
-    pub fn ceil<V>(&self, key: &V) -> Option<&T>
+    pub fn drain(&mut self) -> SkipMapIntoIter<T, U> {
+        self.len = 0;
+        unsafe {
+            let ret = SkipMapIntoIter {
+                current: *(*self.head).get_pointer(0),
+            };
+            ptr::write_bytes((*self.head).links.as_mut_ptr(), 0, (*self.head).links_len);
+            ret
+        }
+    }
+
+    // Returns the node with the greatest key that is less than a particular key, or equal to it if
+    // `inclusive` is true. Returns a null pointer if such a node does not exist.
+    fn find_floor<V>(&self, key: &V, inclusive: bool) -> *mut Node<T, U>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
         let mut curr_height = self.get_starting_height();
-        let mut curr_node = &self.head;
+        let mut curr_node = self.head;
 
         unsafe {
             loop {
-                let mut next_node = (**curr_node).get_pointer(curr_height);
-                while !next_node.is_null() && (**next_node).entry.key.borrow() < key {
-                    let next_next_node = (**next_node).get_pointer(curr_height);
-                    curr_node = mem::replace(&mut next_node, next_next_node);
+                let mut next_node = *(*curr_node).get_pointer(curr_height);
+                while !next_node.is_null() {
+                    match self.compare.compare((*next_node).entry.key.borrow(), key) {
+                        cmp::Ordering::Less => {}
+                        cmp::Ordering::Equal if inclusive => {}
+                        _ => break,
+                    }
+                    curr_node = next_node;
+                    next_node = *(*curr_node).get_pointer(curr_height);
                 }
 
                 if curr_height == 0 {
-                    if next_node.is_null() {
-                        return None;
+                    if curr_node == self.head {
+                        return ptr::null_mut();
                     } else {
-                        return Some(&(**next_node).entry.key);
+                        return curr_node;
                     }
                 }
 
@@ -484,7 +769,41 @@ impl<T, U> SkipMap<T, U> {
         }
     }
 
-    /// Returns the minimum key of the map. Returns `None` if the map is empty.
+    // Returns the node with the least key that is greater than a particular key, or equal to it if
+    // `inclusive` is true. Returns a null pointer if such a node does not exist.
+    fn find_ceil<V>(&self, key: &V, inclusive: bool) -> *mut Node<T, U>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        let mut curr_height = self.get_starting_height();
+        let mut curr_node = self.head;
+
+        unsafe {
+            loop {
+                let mut next_node = *(*curr_node).get_pointer(curr_height);
+                while !next_node.is_null() {
+                    match self.compare.compare((*next_node).entry.key.borrow(), key) {
+                        cmp::Ordering::Less => {}
+                        cmp::Ordering::Equal if !inclusive => {}
+                        _ => break,
+                    }
+                    curr_node = next_node;
+                    next_node = *(*curr_node).get_pointer(curr_height);
+                }
+
+                if curr_height == 0 {
+                    return next_node;
+                }
+
+                curr_height -= 1;
+            }
+        }
+    }
+
+    /// Returns a key in the map that is less than or equal to a particular key. Returns `None` if
+    /// such a key does not exist.
     ///
     /// # Examples
     ///
@@ -493,24 +812,24 @@ impl<T, U> SkipMap<T, U> {
     ///
     /// let mut map = SkipMap::new();
     /// map.insert(1, 1);
-    /// map.insert(3, 3);
-    /// assert_eq!(map.min(), Some(&1));
+    /// assert_eq!(map.floor(&0), None);
+    /// assert_eq!(map.floor(&2), Some(&1));
     /// ```
-    pub fn min(&self) -> Option<&T>
+    pub fn floor<V>(&self, key: &V) -> Option<&T>
     where
-        T: Ord,
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
     {
         unsafe {
-            let min_node = (*self.head).get_pointer(0);
-            if min_node.is_null() {
-                None
-            } else {
-                Some(&(**min_node).entry.key)
-            }
+            self.find_floor(key, true)
+                .as_ref()
+                .map(|node| &node.entry.key)
         }
     }
 
-    /// Returns the maximum key of the map. Returns `None` if the map is empty.
+    /// Returns a key in the map that is greater than or equal to a particular key. Returns `None`
+    /// if such a key does not exist.
     ///
     /// # Examples
     ///
@@ -519,40 +838,631 @@ impl<T, U> SkipMap<T, U> {
     ///
     /// let mut map = SkipMap::new();
     /// map.insert(1, 1);
-    /// map.insert(3, 3);
-    /// assert_eq!(map.max(), Some(&3));
+    /// assert_eq!(map.ceil(&0), Some(&1));
+    /// assert_eq!(map.ceil(&2), None);
     /// ```
-    pub fn max(&self) -> Option<&T>
+    pub fn ceil<V>(&self, key: &V) -> Option<&T>
     where
-        T: Ord,
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        let mut curr_height = self.get_starting_height();
-        let mut curr_node = &self.head;
-
         unsafe {
-            loop {
-                let mut next_node = (**curr_node).get_pointer(curr_height);
-                while !next_node.is_null() {
-                    let next_next_node = (**next_node).get_pointer(curr_height);
-                    curr_node = mem::replace(&mut next_node, next_next_node);
-                }
-
-                if curr_height == 0 {
-                    if curr_node == &self.head {
-                        return None;
-                    } else {
-                        return Some(&(**curr_node).entry.key);
-                    };
-                }
-
-                curr_height -= 1;
-            }
+            self.find_ceil(key, true)
+                .as_ref()
+                .map(|node| &node.entry.key)
         }
     }
 
-    /// Returns the union of two maps. If there is a key that is found in both `left` and `right`,
-    /// the union will contain the value associated with the key in `left`. The `+`
-    /// operator is implemented to take the union of two maps.
+    /// Returns the key-value pair in the map with the greatest key that is less than or equal to a
+    /// particular key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.floor_entry(&0), None);
+    /// assert_eq!(map.floor_entry(&2), Some((&1, &1)));
+    /// ```
+    pub fn floor_entry<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        unsafe {
+            self.find_floor(key, true)
+                .as_ref()
+                .map(|node| (&node.entry.key, &node.entry.value))
+        }
+    }
+
+    /// Returns the key and a mutable reference to the value of the key-value pair in the map with
+    /// the greatest key that is less than or equal to a particular key. Returns `None` if such a
+    /// key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// if let Some((_, value)) = map.floor_entry_mut(&2) {
+    ///     *value = 2;
+    /// }
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// ```
+    pub fn floor_entry_mut<V>(&mut self, key: &V) -> Option<(&T, &mut U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        unsafe {
+            self.find_floor(key, true)
+                .as_mut()
+                .map(|node| (&node.entry.key, &mut node.entry.value))
+        }
+    }
+
+    /// Returns the key-value pair in the map with the least key that is greater than or equal to a
+    /// particular key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.ceil_entry(&2), None);
+    /// assert_eq!(map.ceil_entry(&0), Some((&1, &1)));
+    /// ```
+    pub fn ceil_entry<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        unsafe {
+            self.find_ceil(key, true)
+                .as_ref()
+                .map(|node| (&node.entry.key, &node.entry.value))
+        }
+    }
+
+    /// Returns the key and a mutable reference to the value of the key-value pair in the map with
+    /// the least key that is greater than or equal to a particular key. Returns `None` if such a
+    /// key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// if let Some((_, value)) = map.ceil_entry_mut(&0) {
+    ///     *value = 2;
+    /// }
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// ```
+    pub fn ceil_entry_mut<V>(&mut self, key: &V) -> Option<(&T, &mut U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        unsafe {
+            self.find_ceil(key, true)
+                .as_mut()
+                .map(|node| (&node.entry.key, &mut node.entry.value))
+        }
+    }
+
+    /// Returns the key-value pair in the map with the greatest key that is less than a particular
+    /// key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.next_below(&1), None);
+    /// assert_eq!(map.next_below(&2), Some((&1, &1)));
+    /// ```
+    pub fn next_below<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        unsafe {
+            self.find_floor(key, false)
+                .as_ref()
+                .map(|node| (&node.entry.key, &node.entry.value))
+        }
+    }
+
+    /// Returns the key-value pair in the map with the least key that is greater than a particular
+    /// key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.next_above(&2), None);
+    /// assert_eq!(map.next_above(&1), Some((&2, &2)));
+    /// ```
+    pub fn next_above<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        unsafe {
+            self.find_ceil(key, false)
+                .as_ref()
+                .map(|node| (&node.entry.key, &node.entry.value))
+        }
+    }
+
+    /// Returns the minimum key of the map. Returns `None` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.min(), Some(&1));
+    /// ```
+    pub fn min(&self) -> Option<&T> {
+        unsafe {
+            let min_node = (*self.head).get_pointer(0);
+            if min_node.is_null() {
+                None
+            } else {
+                Some(&(**min_node).entry.key)
+            }
+        }
+    }
+
+    /// Returns the maximum key of the map. Returns `None` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.max(), Some(&3));
+    /// ```
+    pub fn max(&self) -> Option<&T> {
+        let mut curr_height = self.get_starting_height();
+        let mut curr_node = &self.head;
+
+        unsafe {
+            loop {
+                let mut next_node = (**curr_node).get_pointer(curr_height);
+                while !next_node.is_null() {
+                    let next_next_node = (**next_node).get_pointer(curr_height);
+                    curr_node = mem::replace(&mut next_node, next_next_node);
+                }
+
+                if curr_height == 0 {
+                    if curr_node == &self.head {
+                        return None;
+                    } else {
+                        return Some(&(**curr_node).entry.key);
+                    };
+                }
+
+                curr_height -= 1;
+            }
+        }
+    }
+
+    /// Removes and returns the key-value pair with the minimum key in the map. Returns `None` if
+    /// the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.pop_min(), Some((1, 1)));
+    /// assert_eq!(map.pop_min(), Some((3, 3)));
+    /// assert_eq!(map.pop_min(), None);
+    /// ```
+    pub fn pop_min(&mut self) -> Option<(T, U)> {
+        self.cursor_front().remove_current()
+    }
+
+    /// Removes and returns the key-value pair with the maximum key in the map. Returns `None` if
+    /// the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.pop_max(), Some((3, 3)));
+    /// assert_eq!(map.pop_max(), Some((1, 1)));
+    /// assert_eq!(map.pop_max(), None);
+    /// ```
+    pub fn pop_max(&mut self) -> Option<(T, U)> {
+        let last_nodes = self.get_last_nodes_before(self.len);
+        SkipMapCursor {
+            last_nodes,
+            map: self,
+        }
+        .remove_current()
+    }
+
+    /// Returns the key-value pair at a particular index of the map, where the key-value pairs are
+    /// ordered by key. Returns `None` if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.get_index(0), Some((&1, &1)));
+    /// assert_eq!(map.get_index(1), Some((&3, &3)));
+    /// assert_eq!(map.get_index(2), None);
+    /// ```
+    pub fn get_index(&self, index: usize) -> Option<(&T, &U)> {
+        if index >= self.len {
+            return None;
+        }
+        unsafe {
+            let Entry { ref key, ref value } = (*self.get_node(index + 1)).entry;
+            Some((key, value))
+        }
+    }
+
+    /// Returns the number of keys in the map that are less than a particular key. If the key
+    /// exists in the map, this is its index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.rank(&1), 0);
+    /// assert_eq!(map.rank(&2), 1);
+    /// assert_eq!(map.rank(&4), 2);
+    /// ```
+    pub fn rank<V>(&self, key: &V) -> usize
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        let mut curr_node = self.head;
+        let mut index = 0;
+        unsafe {
+            for height in (0..=self.get_starting_height()).rev() {
+                loop {
+                    let Link { next, distance } = *(*curr_node).get_link(height);
+                    if next.is_null()
+                        || self.compare.compare((*next).entry.key.borrow(), key)
+                            != cmp::Ordering::Less
+                    {
+                        break;
+                    }
+                    curr_node = next;
+                    index += distance;
+                }
+            }
+        }
+        index
+    }
+
+    /// Returns an iterator over the map starting at a particular index. The iterator will yield
+    /// key-value pairs in ascending order, and will be empty if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.insert(3, 3);
+    ///
+    /// let mut iterator = map.iter_from_index(1);
+    /// assert_eq!(iterator.next(), Some((&2, &2)));
+    /// assert_eq!(iterator.next(), Some((&3, &3)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter_from_index(&self, index: usize) -> SkipMapIter<'_, T, U> {
+        let node = self.get_node(cmp::min(index, self.len));
+        unsafe {
+            SkipMapIter {
+                current: (*node).get_pointer(0),
+            }
+        }
+    }
+
+    /// Returns a cursor that points at the entry with the smallest key in the map, or past the end
+    /// of the map if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// let mut cursor = map.cursor_front();
+    /// assert_eq!(cursor.current(), Some((&1, &1)));
+    /// cursor.move_next();
+    /// assert_eq!(cursor.current(), Some((&2, &2)));
+    /// ```
+    pub fn cursor_front(&mut self) -> SkipMapCursor<'_, T, U, C> {
+        SkipMapCursor {
+            last_nodes: [(self.head, 0); MAX_HEIGHT + 1],
+            map: self,
+        }
+    }
+
+    /// Returns a cursor that points at the entry with the smallest key that is greater than or
+    /// equal to a particular key, or past the end of the map if such an entry does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    ///
+    /// let mut cursor = map.cursor_at(&2);
+    /// assert_eq!(cursor.current(), Some((&3, &3)));
+    /// assert_eq!(cursor.index(), 1);
+    /// ```
+    pub fn cursor_at<V>(&mut self, key: &V) -> SkipMapCursor<'_, T, U, C>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        SkipMapCursor {
+            last_nodes: self.get_last_nodes(key, false),
+            map: self,
+        }
+    }
+
+    // Returns the last node on each level whose key is less than `key`, or less than or equal to
+    // `key` if `inclusive` is true, and the index of each node.
+    fn get_last_nodes<V>(
+        &self,
+        key: &V,
+        inclusive: bool,
+    ) -> [(*mut Node<T, U>, usize); MAX_HEIGHT + 1]
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        let mut last_nodes = [(self.head, 0); MAX_HEIGHT + 1];
+        let mut curr_node = self.head;
+        let mut curr_index = 0;
+
+        unsafe {
+            let links_len = (*self.head).links_len;
+            for (height, last_node) in last_nodes[..links_len].iter_mut().enumerate().rev() {
+                loop {
+                    let Link { next, distance } = *(*curr_node).get_link(height);
+                    if next.is_null() {
+                        break;
+                    }
+                    match self.compare.compare((*next).entry.key.borrow(), key) {
+                        cmp::Ordering::Less => {}
+                        cmp::Ordering::Equal if inclusive => {}
+                        _ => break,
+                    }
+                    curr_node = next;
+                    curr_index += distance;
+                }
+                *last_node = (curr_node, curr_index);
+            }
+        }
+        last_nodes
+    }
+
+    // Returns the last node on each level whose index is less than `index`, and the index of each
+    // node.
+    fn get_last_nodes_before(&self, index: usize) -> [(*mut Node<T, U>, usize); MAX_HEIGHT + 1] {
+        let mut last_nodes = [(self.head, 0); MAX_HEIGHT + 1];
+        let mut curr_node = self.head;
+        let mut curr_index = 0;
+
+        unsafe {
+            let links_len = (*self.head).links_len;
+            for (height, last_node) in last_nodes[..links_len].iter_mut().enumerate().rev() {
+                loop {
+                    let Link { next, distance } = *(*curr_node).get_link(height);
+                    if next.is_null() || curr_index + distance >= index {
+                        break;
+                    }
+                    curr_node = next;
+                    curr_index += distance;
+                }
+                *last_node = (curr_node, curr_index);
+            }
+        }
+        last_nodes
+    }
+
+    // Cuts every link after `last_nodes` and returns the nodes after them as a new map.
+    fn split_after(&mut self, last_nodes: &[(*mut Node<T, U>, usize)]) -> Self
+    where
+        C: Clone,
+    {
+        unsafe {
+            let links_len = (*self.head).links_len;
+            let split_index = last_nodes[0].1;
+            let new_head = Node::allocate(links_len);
+            for (height, &(last_node, last_index)) in last_nodes[..links_len].iter().enumerate() {
+                let link = (*last_node).get_link_mut(height);
+                if !link.next.is_null() {
+                    let new_link = (*new_head).get_link_mut(height);
+                    new_link.next = link.next;
+                    new_link.distance = last_index + link.distance - split_index;
+                    link.next = ptr::null_mut();
+                    link.distance = 0;
+                }
+            }
+
+            let ret = SkipMap {
+                head: new_head,
+                rng: self.rng.gen(),
+                probability: self.probability,
+                len: self.len - split_index,
+                compare: self.compare.clone(),
+            };
+            self.len = split_index;
+            ret
+        }
+    }
+
+    // Appends the entries of `other` to the map, assuming that all of its keys are greater than
+    // the keys of the map.
+    fn append_greater(&mut self, mut other: Self) {
+        unsafe {
+            let other_links_len = (*other.head).links_len;
+            self.grow_head(other_links_len);
+            let mut curr_node = self.head;
+            let mut curr_index = 0;
+            for height in (0..other_links_len).rev() {
+                loop {
+                    let Link { next, distance } = *(*curr_node).get_link(height);
+                    if next.is_null() {
+                        break;
+                    }
+                    curr_node = next;
+                    curr_index += distance;
+                }
+
+                let Link { next, distance } = *(*other.head).get_link(height);
+                if !next.is_null() {
+                    let link = (*curr_node).get_link_mut(height);
+                    link.next = next;
+                    link.distance = self.len - curr_index + distance;
+                }
+            }
+
+            ptr::write_bytes((*other.head).links.as_mut_ptr(), 0, other_links_len);
+            self.len += mem::replace(&mut other.len, 0);
+        }
+    }
+
+    /// Splits the map and returns the right part of the map. If `inclusive` is true, then the map
+    /// will retain the given key if it exists. Otherwise, the right part of the map will contain
+    /// the key if it exists. The split cuts the links on each level in logarithmic time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.insert(3, 3);
+    ///
+    /// let split = map.split_off(&2, true);
+    /// assert_eq!(map[&1], 1);
+    /// assert_eq!(map[&2], 2);
+    /// assert_eq!(split[&3], 3);
+    /// ```
+    pub fn split_off<V>(&mut self, key: &V, inclusive: bool) -> Self
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V> + Clone,
+    {
+        let last_nodes = self.get_last_nodes(key, inclusive);
+        self.split_after(&last_nodes)
+    }
+
+    /// Removes all entries whose keys are in `range` from the map and returns them as a new map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// for key in 0..5 {
+    ///     map.insert(key, key);
+    /// }
+    ///
+    /// let removed = map.remove_range(1..3);
+    /// assert_eq!(removed.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&1, &1), (&2, &2)]);
+    /// assert_eq!(
+    ///     map.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&0, &0), (&3, &3), (&4, &4)],
+    /// );
+    /// ```
+    pub fn remove_range<V, R>(&mut self, range: R) -> Self
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V> + Clone,
+        R: RangeBounds<V>,
+    {
+        let mut ret = match range.start_bound() {
+            Bound::Included(start) => self.split_off(start, false),
+            Bound::Excluded(start) => self.split_off(start, true),
+            Bound::Unbounded => self.split_after(&[(self.head, 0); MAX_HEIGHT + 1]),
+        };
+        let right = match range.end_bound() {
+            Bound::Included(end) => ret.split_off(end, true),
+            Bound::Excluded(end) => ret.split_off(end, false),
+            Bound::Unbounded => return ret,
+        };
+        self.append_greater(right);
+        ret
+    }
+
+    /// Returns the union of two maps. If there is a key that is found in both `left` and `right`,
+    /// the union will contain the value associated with the key in `left`. The `+`
+    /// operator is implemented to take the union of two maps.
     ///
     /// # Examples
     ///
@@ -575,29 +1485,35 @@ impl<T, U> SkipMap<T, U> {
     /// ```
     pub fn union(mut left: Self, mut right: Self) -> Self
     where
-        T: Ord,
+        C: Compare<T> + Clone,
     {
+        // every node keeps its height, so the head needs to be as tall as the taller head
+        let links_len = unsafe { cmp::max((*left.head).links_len, (*right.head).links_len) };
         let mut ret = SkipMap {
-            head: unsafe { Node::allocate(MAX_HEIGHT + 1) },
-            rng: XorShiftRng::new_unseeded(),
+            head: unsafe { Node::allocate(links_len) },
+            rng: left.rng.gen(),
+            probability: left.probability,
             len: 0,
+            compare: left.compare.clone(),
         };
-        let mut curr_nodes = [ret.head; MAX_HEIGHT + 1];
+        let mut curr_nodes = [(ret.head, 0); MAX_HEIGHT + 1];
 
         unsafe {
             let next_left_node = *(*left.head).get_pointer(0);
             let left_head = mem::replace(&mut left.head, next_left_node);
             let next_right_node = *(*right.head).get_pointer(0);
             let right_head = mem::replace(&mut right.head, next_right_node);
-            ptr::write_bytes((*left_head).links.get_unchecked_mut(0), 0, MAX_HEIGHT + 1);
-            ptr::write_bytes((*right_head).links.get_unchecked_mut(0), 0, MAX_HEIGHT + 1);
+            ptr::write_bytes((*left_head).links.as_mut_ptr(), 0, (*left_head).links_len);
+            ptr::write_bytes((*right_head).links.as_mut_ptr(), 0, (*right_head).links_len);
 
             loop {
                 let next_node;
                 match (left.head.is_null(), right.head.is_null()) {
                     (true, true) => break,
                     (false, false) => {
-                        let cmp = (*left.head).entry.cmp(&(*right.head).entry);
+                        let cmp = left
+                            .compare
+                            .compare(&(*left.head).entry.key, &(*right.head).entry.key);
                         match cmp {
                             cmp::Ordering::Equal => {
                                 let next_right_node = *(*right.head).get_pointer(0);
@@ -625,17 +1541,8 @@ impl<T, U> SkipMap<T, U> {
                 }
                 ret.len += 1;
 
-                ptr::write_bytes(
-                    (*next_node).links.get_unchecked_mut(0),
-                    0,
-                    (*next_node).links_len,
-                );
-
-                let links_len = (*next_node).links_len;
-                for (i, curr_node) in curr_nodes.iter_mut().enumerate().take(links_len) {
-                    *(**curr_node).get_pointer_mut(i) = next_node;
-                    *curr_node = next_node;
-                }
+                ptr::write_bytes((*next_node).links.as_mut_ptr(), 0, (*next_node).links_len);
+                Self::link_last(&mut curr_nodes, next_node, ret.len);
             }
             left.head = left_head;
             right.head = right_head;
@@ -667,29 +1574,35 @@ impl<T, U> SkipMap<T, U> {
     /// ```
     pub fn intersection(mut left: Self, mut right: Self) -> Self
     where
-        T: Ord,
+        C: Compare<T> + Clone,
     {
+        // every node keeps its height, so the head needs to be as tall as the taller head
+        let links_len = unsafe { cmp::max((*left.head).links_len, (*right.head).links_len) };
         let mut ret = SkipMap {
-            head: unsafe { Node::allocate(MAX_HEIGHT + 1) },
-            rng: XorShiftRng::new_unseeded(),
+            head: unsafe { Node::allocate(links_len) },
+            rng: left.rng.gen(),
+            probability: left.probability,
             len: 0,
+            compare: left.compare.clone(),
         };
-        let mut curr_nodes = [ret.head; MAX_HEIGHT + 1];
+        let mut curr_nodes = [(ret.head, 0); MAX_HEIGHT + 1];
 
         unsafe {
             let next_left_node = *(*left.head).get_pointer(0);
             let left_head = mem::replace(&mut left.head, next_left_node);
             let next_right_node = *(*right.head).get_pointer(0);
             let right_head = mem::replace(&mut right.head, next_right_node);
-            ptr::write_bytes((*left_head).links.get_unchecked_mut(0), 0, MAX_HEIGHT + 1);
-            ptr::write_bytes((*right_head).links.get_unchecked_mut(0), 0, MAX_HEIGHT + 1);
+            ptr::write_bytes((*left_head).links.as_mut_ptr(), 0, (*left_head).links_len);
+            ptr::write_bytes((*right_head).links.as_mut_ptr(), 0, (*right_head).links_len);
 
             loop {
                 let next_node;
                 match (left.head.is_null(), right.head.is_null()) {
                     (true, true) => break,
                     (false, false) => {
-                        let cmp = (*left.head).entry.cmp(&(*right.head).entry);
+                        let cmp = left
+                            .compare
+                            .compare(&(*left.head).entry.key, &(*right.head).entry.key);
                         match cmp {
                             cmp::Ordering::Equal => {
                                 let next_left_node = *(*left.head).get_pointer(0);
@@ -722,17 +1635,8 @@ impl<T, U> SkipMap<T, U> {
                 }
                 ret.len += 1;
 
-                ptr::write_bytes(
-                    (*next_node).links.get_unchecked_mut(0),
-                    0,
-                    (*next_node).links_len,
-                );
-
-                let links_len = (*next_node).links_len;
-                for (i, curr_node) in curr_nodes.iter_mut().enumerate().take(links_len + 1) {
-                    *(**curr_node).get_pointer_mut(i) = next_node;
-                    *curr_node = next_node;
-                }
+                ptr::write_bytes((*next_node).links.as_mut_ptr(), 0, (*next_node).links_len);
+                Self::link_last(&mut curr_nodes, next_node, ret.len);
             }
             left.head = left_head;
             right.head = right_head;
@@ -742,29 +1646,35 @@ impl<T, U> SkipMap<T, U> {
 
     fn map_difference(mut left: Self, mut right: Self, symmetric: bool) -> Self
     where
-        T: Ord,
+        C: Compare<T> + Clone,
     {
+        // every node keeps its height, so the head needs to be as tall as the taller head
+        let links_len = unsafe { cmp::max((*left.head).links_len, (*right.head).links_len) };
         let mut ret = SkipMap {
-            head: unsafe { Node::allocate(MAX_HEIGHT + 1) },
-            rng: XorShiftRng::new_unseeded(),
+            head: unsafe { Node::allocate(links_len) },
+            rng: left.rng.gen(),
+            probability: left.probability,
             len: 0,
+            compare: left.compare.clone(),
         };
-        let mut curr_nodes = [ret.head; MAX_HEIGHT + 1];
+        let mut curr_nodes = [(ret.head, 0); MAX_HEIGHT + 1];
 
         unsafe {
             let next_left_node = *(*left.head).get_pointer(0);
             let left_head = mem::replace(&mut left.head, next_left_node);
             let next_right_node = *(*right.head).get_pointer(0);
             let right_head = mem::replace(&mut right.head, next_right_node);
-            ptr::write_bytes((*left_head).links.get_unchecked_mut(0), 0, MAX_HEIGHT + 1);
-            ptr::write_bytes((*right_head).links.get_unchecked_mut(0), 0, MAX_HEIGHT + 1);
+            ptr::write_bytes((*left_head).links.as_mut_ptr(), 0, (*left_head).links_len);
+            ptr::write_bytes((*right_head).links.as_mut_ptr(), 0, (*right_head).links_len);
 
             loop {
                 let next_node;
                 match (left.head.is_null(), right.head.is_null()) {
                     (true, true) => break,
                     (false, false) => {
-                        let cmp = (*left.head).entry.cmp(&(*right.head).entry);
+                        let cmp = left
+                            .compare
+                            .compare(&(*left.head).entry.key, &(*right.head).entry.key);
                         match cmp {
                             cmp::Ordering::Equal => {
                                 let next_left_node = *(*left.head).get_pointer(0);
@@ -798,23 +1708,14 @@ impl<T, U> SkipMap<T, U> {
                         }
                     }
                     (false, true) => {
-                        let next_right_node = *(*right.head).get_pointer(0);
-                        next_node = mem::replace(&mut right.head, next_right_node);
+                        let next_left_node = *(*left.head).get_pointer(0);
+                        next_node = mem::replace(&mut left.head, next_left_node);
                     }
                 }
                 ret.len += 1;
 
-                ptr::write_bytes(
-                    (*next_node).links.get_unchecked_mut(0),
-                    0,
-                    (*next_node).links_len,
-                );
-
-                let links_len = (*next_node).links_len;
-                for (i, curr_node) in curr_nodes.iter_mut().enumerate().take(links_len) {
-                    *(**curr_node).get_pointer_mut(i) = next_node;
-                    *curr_node = next_node;
-                }
+                ptr::write_bytes((*next_node).links.as_mut_ptr(), 0, (*next_node).links_len);
+                Self::link_last(&mut curr_nodes, next_node, ret.len);
             }
             left.head = left_head;
             right.head = right_head;
@@ -847,7 +1748,7 @@ impl<T, U> SkipMap<T, U> {
     /// ```
     pub fn difference(left: Self, right: Self) -> Self
     where
-        T: Ord,
+        C: Compare<T> + Clone,
     {
         Self::map_difference(left, right, false)
     }
@@ -876,7 +1777,7 @@ impl<T, U> SkipMap<T, U> {
     /// ```
     pub fn symmetric_difference(left: Self, right: Self) -> Self
     where
-        T: Ord,
+        C: Compare<T> + Clone,
     {
         Self::map_difference(left, right, true)
     }
@@ -893,20 +1794,144 @@ impl<T, U> SkipMap<T, U> {
     /// map.insert(1, 1);
     /// map.insert(2, 2);
     ///
-    /// let mut iterator = map.iter();
-    /// assert_eq!(iterator.next(), Some((&1, &1)));
-    /// assert_eq!(iterator.next(), Some((&2, &2)));
-    /// assert_eq!(iterator.next(), None);
+    /// let mut iterator = map.iter();
+    /// assert_eq!(iterator.next(), Some((&1, &1)));
+    /// assert_eq!(iterator.next(), Some((&2, &2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> SkipMapIter<'_, T, U> {
+        unsafe {
+            SkipMapIter {
+                current: &*(*self.head).get_pointer(0),
+            }
+        }
+    }
+
+    /// Returns a mutable iterator over the map. The iterator will yield key-value pairs in
+    /// ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// for (key, value) in &mut map {
+    ///     *value += 1;
+    /// }
+    ///
+    /// let mut iterator = map.iter_mut();
+    /// assert_eq!(iterator.next(), Some((&1, &mut 2)));
+    /// assert_eq!(iterator.next(), Some((&2, &mut 3)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter_mut(&mut self) -> SkipMapIterMut<'_, T, U> {
+        unsafe {
+            SkipMapIterMut {
+                current: &mut *(*self.head).get_pointer_mut(0),
+            }
+        }
+    }
+}
+
+impl<T, U> SkipMap<T, U> {
+    /// Returns a lazy iterator over the union of two maps without consuming them. The iterator
+    /// will yield key-value pairs in ascending order. If there is a key that is found in both `self`
+    /// and `other`, the iterator will yield the value associated with the key in `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut n = SkipMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = SkipMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// assert_eq!(
+    ///     n.union_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1), (&2, &2), (&3, &3)],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn union_iter<'a>(&'a self, other: &'a Self) -> UnionIter<SkipMapIter<'a, T, U>>
+    where
+        T: Ord,
+    {
+        UnionIter::new(self.iter(), other.iter())
+    }
+
+    /// Returns a lazy iterator over the intersection of two maps without consuming them. The
+    /// iterator will yield key-value pairs in ascending order. If there is a key that is found in both
+    /// `self` and `other`, the iterator will yield the value associated with the key in `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut n = SkipMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = SkipMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// assert_eq!(
+    ///     n.intersection_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&2, &2)],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn intersection_iter<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> IntersectionIter<SkipMapIter<'a, T, U>>
+    where
+        T: Ord,
+    {
+        IntersectionIter::new(self.iter(), other.iter())
+    }
+
+    /// Returns a lazy iterator over the difference of two maps without consuming them. The
+    /// iterator will yield the key-value pairs of `self` that are not in `other` in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut n = SkipMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = SkipMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// assert_eq!(
+    ///     n.difference_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1)],
+    /// );
+    /// assert_eq!(n.len(), 2);
     /// ```
-    pub fn iter(&self) -> SkipMapIter<'_, T, U> {
-        unsafe {
-            SkipMapIter {
-                current: &*(*self.head).get_pointer(0),
-            }
-        }
+    pub fn difference_iter<'a>(&'a self, other: &'a Self) -> DifferenceIter<SkipMapIter<'a, T, U>>
+    where
+        T: Ord,
+    {
+        DifferenceIter::new(self.iter(), other.iter())
     }
 
-    /// Returns a mutable iterator over the map. The iterator will yield key-value pairs in
+    /// Returns a lazy iterator over the symmetric difference of two maps without consuming
+    /// them. The iterator will yield the key-value pairs that exist in one map, but not both maps, in
     /// ascending order.
     ///
     /// # Examples
@@ -914,29 +1939,32 @@ impl<T, U> SkipMap<T, U> {
     /// ```
     /// use extended_collections::skiplist::SkipMap;
     ///
-    /// let mut map = SkipMap::new();
-    /// map.insert(1, 1);
-    /// map.insert(2, 2);
+    /// let mut n = SkipMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
     ///
-    /// for (key, value) in &mut map {
-    ///     *value += 1;
-    /// }
+    /// let mut m = SkipMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
     ///
-    /// let mut iterator = map.iter_mut();
-    /// assert_eq!(iterator.next(), Some((&1, &mut 2)));
-    /// assert_eq!(iterator.next(), Some((&2, &mut 3)));
-    /// assert_eq!(iterator.next(), None);
+    /// assert_eq!(
+    ///     n.symmetric_difference_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1), (&3, &3)],
+    /// );
+    /// assert_eq!(n.len(), 2);
     /// ```
-    pub fn iter_mut(&self) -> SkipMapIterMut<'_, T, U> {
-        unsafe {
-            SkipMapIterMut {
-                current: &mut *(*self.head).get_pointer_mut(0),
-            }
-        }
+    pub fn symmetric_difference_iter<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> SymmetricDifferenceIter<SkipMapIter<'a, T, U>>
+    where
+        T: Ord,
+    {
+        SymmetricDifferenceIter::new(self.iter(), other.iter())
     }
 }
 
-impl<T, U> Drop for SkipMap<T, U> {
+impl<T, U, C> Drop for SkipMap<T, U, C> {
     fn drop(&mut self) {
         unsafe {
             let next_node = *(*self.head).get_pointer(0);
@@ -949,22 +1977,22 @@ impl<T, U> Drop for SkipMap<T, U> {
     }
 }
 
-impl<T, U> IntoIterator for SkipMap<T, U> {
+impl<T, U, C> IntoIterator for SkipMap<T, U, C> {
     type IntoIter = SkipMapIntoIter<T, U>;
     type Item = (T, U);
 
     fn into_iter(self) -> Self::IntoIter {
         unsafe {
             let ret = Self::IntoIter {
-                current: *(*self.head).links.get_unchecked_mut(0),
+                current: (*(*self.head).links.as_mut_ptr()).next,
             };
-            ptr::write_bytes((*self.head).links.get_unchecked_mut(0), 0, MAX_HEIGHT + 1);
+            ptr::write_bytes((*self.head).links.as_mut_ptr(), 0, (*self.head).links_len);
             ret
         }
     }
 }
 
-impl<'a, T, U> IntoIterator for &'a SkipMap<T, U>
+impl<'a, T, U, C> IntoIterator for &'a SkipMap<T, U, C>
 where
     T: 'a,
     U: 'a,
@@ -977,7 +2005,7 @@ where
     }
 }
 
-impl<'a, T, U> IntoIterator for &'a mut SkipMap<T, U>
+impl<'a, T, U, C> IntoIterator for &'a mut SkipMap<T, U, C>
 where
     T: 'a,
     U: 'a,
@@ -997,6 +2025,9 @@ pub struct SkipMapIntoIter<T, U> {
     current: *mut Node<T, U>,
 }
 
+unsafe impl<T: Send, U: Send> Send for SkipMapIntoIter<T, U> {}
+unsafe impl<T: Sync, U: Sync> Sync for SkipMapIntoIter<T, U> {}
+
 impl<T, U> Iterator for SkipMapIntoIter<T, U> {
     type Item = (T, U);
 
@@ -1034,6 +2065,10 @@ pub struct SkipMapIter<'a, T, U> {
     current: &'a *mut Node<T, U>,
 }
 
+// the iterator only yields shared references, so it behaves like a `&'a (T, U)`
+unsafe impl<'a, T: Sync, U: Sync> Send for SkipMapIter<'a, T, U> {}
+unsafe impl<'a, T: Sync, U: Sync> Sync for SkipMapIter<'a, T, U> {}
+
 impl<'a, T, U> Iterator for SkipMapIter<'a, T, U>
 where
     T: 'a,
@@ -1055,69 +2090,468 @@ where
     }
 }
 
-/// A mutable iterator for `SkipMap<T, U>`.
-///
-/// This iterator traverses the elements of a map in ascending order and yields mutable references.
-pub struct SkipMapIterMut<'a, T, U> {
-    current: &'a mut *mut Node<T, U>,
+/// A mutable iterator for `SkipMap<T, U>`.
+///
+/// This iterator traverses the elements of a map in ascending order and yields mutable references.
+pub struct SkipMapIterMut<'a, T, U> {
+    current: &'a mut *mut Node<T, U>,
+}
+
+// the iterator yields shared references to keys and mutable references to values, so it behaves
+// like a `(&'a T, &'a mut U)`
+unsafe impl<'a, T: Sync, U: Send> Send for SkipMapIterMut<'a, T, U> {}
+unsafe impl<'a, T: Sync, U: Sync> Sync for SkipMapIterMut<'a, T, U> {}
+
+impl<'a, T, U> Iterator for SkipMapIterMut<'a, T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type Item = (&'a T, &'a mut U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current.is_null() {
+            None
+        } else {
+            unsafe {
+                let Entry {
+                    ref key,
+                    ref mut value,
+                } = (**self.current).entry;
+                let next_node = &mut *(**self.current).get_pointer_mut(0);
+                mem::replace(&mut self.current, next_node);
+                Some((key, value))
+            }
+        }
+    }
+}
+
+/// A cursor over the entries of a `SkipMap<T, U, C>`.
+///
+/// A cursor points at an entry of the map or past its last entry. It keeps the last node before
+/// its position on every level of the skiplist, so it can move in both directions, and remove the
+/// entry that it points at, without searching from the head of the map.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::skiplist::SkipMap;
+///
+/// let mut map = SkipMap::new();
+/// for key in 0..10 {
+///     map.insert(key, key);
+/// }
+///
+/// let mut cursor = map.cursor_front();
+/// while let Some((key, _)) = cursor.current() {
+///     if key % 3 == 0 {
+///         cursor.remove_current();
+///     } else {
+///         cursor.move_next();
+///     }
+/// }
+/// assert_eq!(
+///     map.iter().map(|(key, _)| *key).collect::<Vec<u32>>(),
+///     vec![1, 2, 4, 5, 7, 8],
+/// );
+/// ```
+pub struct SkipMapCursor<'a, T, U, C = Natural> {
+    map: &'a mut SkipMap<T, U, C>,
+    last_nodes: [(*mut Node<T, U>, usize); MAX_HEIGHT + 1],
+}
+
+impl<'a, T, U, C> SkipMapCursor<'a, T, U, C> {
+    fn current_node(&self) -> *mut Node<T, U> {
+        unsafe { *(*self.last_nodes[0].0).get_pointer(0) }
+    }
+
+    /// Returns the index of the entry that the cursor points at, or the length of the map if the
+    /// cursor is past its last entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    ///
+    /// let mut cursor = map.cursor_front();
+    /// assert_eq!(cursor.index(), 0);
+    /// cursor.move_next();
+    /// assert_eq!(cursor.index(), 1);
+    /// ```
+    pub fn index(&self) -> usize {
+        self.last_nodes[0].1
+    }
+
+    /// Returns the key-value pair that the cursor points at, or `None` if the cursor is past the
+    /// last entry of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    ///
+    /// let mut cursor = map.cursor_front();
+    /// assert_eq!(cursor.current(), Some((&1, &1)));
+    /// cursor.move_next();
+    /// assert_eq!(cursor.current(), None);
+    /// ```
+    pub fn current(&self) -> Option<(&T, &U)> {
+        let node = self.current_node();
+        if node.is_null() {
+            None
+        } else {
+            unsafe {
+                let Entry { ref key, ref value } = (*node).entry;
+                Some((key, value))
+            }
+        }
+    }
+
+    /// Returns the key and a mutable reference to the value that the cursor points at, or `None`
+    /// if the cursor is past the last entry of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    ///
+    /// let mut cursor = map.cursor_front();
+    /// if let Some((_, value)) = cursor.current_mut() {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// ```
+    pub fn current_mut(&mut self) -> Option<(&T, &mut U)> {
+        let node = self.current_node();
+        if node.is_null() {
+            None
+        } else {
+            unsafe {
+                let Entry {
+                    ref key,
+                    ref mut value,
+                } = (*node).entry;
+                Some((key, value))
+            }
+        }
+    }
+
+    /// Moves the cursor to the next entry of the map. Returns `false` and does not move if the
+    /// cursor is past the last entry of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    ///
+    /// let mut cursor = map.cursor_front();
+    /// assert!(cursor.move_next());
+    /// assert!(!cursor.move_next());
+    /// ```
+    pub fn move_next(&mut self) -> bool {
+        let node = self.current_node();
+        if node.is_null() {
+            return false;
+        }
+        let index = self.last_nodes[0].1 + 1;
+        unsafe {
+            for last_node in &mut self.last_nodes[..(*node).links_len] {
+                *last_node = (node, index);
+            }
+        }
+        true
+    }
+
+    /// Moves the cursor to the previous entry of the map. Returns `false` and does not move if
+    /// the cursor points at the first entry of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// let mut cursor = map.cursor_at(&2);
+    /// assert!(cursor.move_prev());
+    /// assert_eq!(cursor.current(), Some((&1, &1)));
+    /// assert!(!cursor.move_prev());
+    /// ```
+    pub fn move_prev(&mut self) -> bool {
+        let prev_node = self.last_nodes[0].0;
+        if prev_node == self.map.head {
+            return false;
+        }
+        unsafe {
+            // the previous node is the last node on every level that it is on, so the last node
+            // before it on those levels is found by walking forward from the level above
+            let links_len = (*self.map.head).links_len;
+            for height in (0..links_len).rev() {
+                if self.last_nodes[height].0 != prev_node {
+                    continue;
+                }
+                let (mut curr_node, mut curr_index) = if height + 1 < links_len {
+                    self.last_nodes[height + 1]
+                } else {
+                    (self.map.head, 0)
+                };
+                loop {
+                    let Link { next, distance } = *(*curr_node).get_link(height);
+                    if next == prev_node {
+                        break;
+                    }
+                    curr_node = next;
+                    curr_index += distance;
+                }
+                self.last_nodes[height] = (curr_node, curr_index);
+            }
+        }
+        true
+    }
+
+    /// Removes the entry that the cursor points at and moves the cursor to the next entry.
+    /// Returns the removed key-value pair, or `None` if the cursor is past the last entry of the
+    /// map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipMap;
+    ///
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// let mut cursor = map.cursor_front();
+    /// assert_eq!(cursor.remove_current(), Some((1, 1)));
+    /// assert_eq!(cursor.current(), Some((&2, &2)));
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn remove_current(&mut self) -> Option<(T, U)> {
+        let node = self.current_node();
+        if node.is_null() {
+            return None;
+        }
+        unsafe {
+            let links_len = (*self.map.head).links_len;
+            for (height, &(last_node, _)) in self.last_nodes[..links_len].iter().enumerate() {
+                let link = (*last_node).get_link_mut(height);
+                if link.next == node {
+                    let Link { next, distance } = *(*node).get_link(height);
+                    link.next = next;
+                    if !next.is_null() {
+                        link.distance += distance - 1;
+                    }
+                } else if !link.next.is_null() {
+                    link.distance -= 1;
+                }
+            }
+
+            self.map.len -= 1;
+            let Entry { key, value } = ptr::read(&(*node).entry);
+            Node::deallocate(node);
+            Some((key, value))
+        }
+    }
+}
+
+impl<T, U, C> Clone for SkipMap<T, U, C>
+where
+    T: Clone,
+    U: Clone,
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        unsafe {
+            let ret = SkipMap {
+                head: Node::allocate((*self.head).links_len),
+                rng: self.rng.clone(),
+                probability: self.probability,
+                len: self.len,
+                compare: self.compare.clone(),
+            };
+            // copy each node with the same height so that the clone has the same structure
+            let mut last_nodes = [(ret.head, 0); MAX_HEIGHT + 1];
+            let mut curr_node = *(*self.head).get_pointer(0);
+            let mut index = 0;
+            while !curr_node.is_null() {
+                let links_len = (*curr_node).links_len;
+                let Entry { ref key, ref value } = (*curr_node).entry;
+                let new_node = Node::new(key.clone(), value.clone(), links_len);
+                index += 1;
+                Self::link_last(&mut last_nodes, new_node, index);
+                curr_node = *(*curr_node).get_pointer(0);
+            }
+            ret
+        }
+    }
+}
+
+impl<T, U, C> OrderedMap<T, U> for SkipMap<T, U, C>
+where
+    C: Compare<T>,
+{
+    type Iter<'a>
+        = SkipMapIter<'a, T, U>
+    where
+        Self: 'a,
+        T: 'a,
+        U: 'a;
+
+    fn insert(&mut self, key: T, value: U) -> Option<(T, U)> {
+        SkipMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &T) -> Option<(T, U)> {
+        SkipMap::remove(self, key)
+    }
+
+    fn contains_key(&self, key: &T) -> bool {
+        SkipMap::contains_key(self, key)
+    }
+
+    fn get(&self, key: &T) -> Option<&U> {
+        SkipMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &T) -> Option<&mut U> {
+        SkipMap::get_mut(self, key)
+    }
+
+    fn len(&self) -> usize {
+        SkipMap::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        SkipMap::is_empty(self)
+    }
+
+    fn floor(&self, key: &T) -> Option<&T> {
+        SkipMap::floor(self, key)
+    }
+
+    fn ceil(&self, key: &T) -> Option<&T> {
+        SkipMap::ceil(self, key)
+    }
+
+    fn min(&self) -> Option<&T> {
+        SkipMap::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        SkipMap::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        SkipMap::iter(self)
+    }
+}
+
+impl<T, U, C> fmt::Debug for SkipMap<T, U, C>
+where
+    T: fmt::Debug,
+    U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, U, C> PartialEq for SkipMap<T, U, C>
+where
+    T: PartialEq,
+    U: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T, U, C> Eq for SkipMap<T, U, C>
+where
+    T: Eq,
+    U: Eq,
+{
 }
 
-impl<'a, T, U> Iterator for SkipMapIterMut<'a, T, U>
+impl<T, U, C> Default for SkipMap<T, U, C>
 where
-    T: 'a,
-    U: 'a,
+    C: Default,
 {
-    type Item = (&'a T, &'a mut U);
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.current.is_null() {
-            None
-        } else {
-            unsafe {
-                let Entry {
-                    ref key,
-                    ref mut value,
-                } = (**self.current).entry;
-                let next_node = &mut *(**self.current).get_pointer_mut(0);
-                mem::replace(&mut self.current, next_node);
-                Some((key, value))
-            }
-        }
+impl<T, U> FromIterator<(T, U)> for SkipMap<T, U>
+where
+    T: Ord,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (T, U)>,
+    {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
     }
 }
 
-impl<T, U> Default for SkipMap<T, U> {
-    fn default() -> Self {
-        Self::new()
+impl<T, U, C> Extend<(T, U)> for SkipMap<T, U, C>
+where
+    C: Compare<T>,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (T, U)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
     }
 }
 
-impl<T, U> Add for SkipMap<T, U>
+impl<T, U, C> Add for SkipMap<T, U, C>
 where
-    T: Ord,
+    C: Compare<T> + Clone,
 {
-    type Output = SkipMap<T, U>;
+    type Output = SkipMap<T, U, C>;
 
-    fn add(self, other: SkipMap<T, U>) -> SkipMap<T, U> {
+    fn add(self, other: SkipMap<T, U, C>) -> SkipMap<T, U, C> {
         Self::union(self, other)
     }
 }
 
-impl<T, U> Sub for SkipMap<T, U>
+impl<T, U, C> Sub for SkipMap<T, U, C>
 where
-    T: Ord,
+    C: Compare<T> + Clone,
 {
-    type Output = SkipMap<T, U>;
+    type Output = SkipMap<T, U, C>;
 
-    fn sub(self, other: SkipMap<T, U>) -> SkipMap<T, U> {
+    fn sub(self, other: SkipMap<T, U, C>) -> SkipMap<T, U, C> {
         Self::difference(self, other)
     }
 }
 
-impl<'a, T, U, V> Index<&'a V> for SkipMap<T, U>
+impl<'a, T, U, V, C> Index<&'a V> for SkipMap<T, U, C>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
     type Output = U;
 
@@ -1126,10 +2560,11 @@ where
     }
 }
 
-impl<'a, T, U, V> IndexMut<&'a V> for SkipMap<T, U>
+impl<'a, T, U, V, C> IndexMut<&'a V> for SkipMap<T, U, C>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
     fn index_mut(&mut self, key: &V) -> &mut Self::Output {
         self.get_mut(key).expect("Error: key does not exist.")
@@ -1139,6 +2574,9 @@ where
 #[cfg(test)]
 mod tests {
     use super::SkipMap;
+    use core::ops::{Bound, RangeBounds};
+    use rand::{Rng, SeedableRng, XorShiftRng};
+    use std::collections::HashMap;
 
     #[test]
     fn test_len_empty() {
@@ -1223,6 +2661,143 @@ mod tests {
         assert_eq!(map.ceil(&6), None);
     }
 
+    #[test]
+    fn test_floor_ceil_entry() {
+        let mut map = SkipMap::new();
+        for key in (0..100u32).rev() {
+            map.insert(key * 2, key * 2);
+        }
+
+        let to_key = |entry: Option<(&u32, &u32)>| {
+            entry.map(|(key, value)| {
+                assert_eq!(key, value);
+                *key
+            })
+        };
+        for key in 0..200 {
+            assert_eq!(to_key(map.floor_entry(&key)), Some(key / 2 * 2));
+            assert_eq!(
+                to_key(map.ceil_entry(&key)),
+                Some(key.div_ceil(2) * 2).filter(|key| *key < 200),
+            );
+            assert_eq!(
+                to_key(map.next_below(&key)),
+                key.checked_sub(1).map(|key| key / 2 * 2),
+            );
+            assert_eq!(
+                to_key(map.next_above(&key)),
+                Some(key / 2 * 2 + 2).filter(|key| *key < 200),
+            );
+        }
+
+        *map.floor_entry_mut(&5).unwrap().1 = 0;
+        *map.ceil_entry_mut(&5).unwrap().1 = 0;
+        assert_eq!(map.get(&4), Some(&0));
+        assert_eq!(map.get(&6), Some(&0));
+        assert_eq!(map.floor_entry_mut(&200).map(|(key, _)| *key), Some(198));
+        assert!(map.ceil_entry_mut(&199).is_none());
+    }
+
+    #[test]
+    fn test_pop_min_max() {
+        let mut map = SkipMap::new();
+        for key in 0..1_000u32 {
+            map.insert(key * 7_919 % 1_000, key);
+        }
+
+        let (mut min, mut max) = (0, 999);
+        while min <= max {
+            let (key, _) = map.pop_min().unwrap();
+            assert_eq!(key, min);
+            min += 1;
+            let (key, _) = map.pop_max().unwrap();
+            assert_eq!(key, max);
+            max -= 1;
+            assert_eq!(map.len(), (max + 1 - min) as usize);
+            check_distances(&map);
+        }
+        assert_eq!(map.pop_min(), None);
+        assert_eq!(map.pop_max(), None);
+        assert!(map.is_empty());
+    }
+
+    // Checks that the distance of each link is the difference between the indexes of its nodes.
+    fn check_distances<T, U>(map: &SkipMap<T, U>) {
+        unsafe {
+            let mut indexes = HashMap::new();
+            let mut curr_node = map.head;
+            let mut index = 0;
+            while !curr_node.is_null() {
+                indexes.insert(curr_node, index);
+                curr_node = *(*curr_node).get_pointer(0);
+                index += 1;
+            }
+            assert_eq!(index, map.len() + 1);
+
+            for (&node, &index) in &indexes {
+                for height in 0..(*node).links_len {
+                    let link = (*node).get_link(height);
+                    if !link.next.is_null() {
+                        assert_eq!(indexes[&link.next] - index, link.distance);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_index_rank() {
+        let mut rng: XorShiftRng = SeedableRng::from_seed([1, 1, 1, 1]);
+        let mut map = SkipMap::with_seed(1);
+        let mut expected = Vec::new();
+        for _ in 0..1000 {
+            let key = rng.gen_range(0, 500);
+            if rng.gen::<bool>() {
+                map.insert(key, key);
+                if let Err(index) = expected.binary_search(&key) {
+                    expected.insert(index, key);
+                }
+            } else {
+                map.remove(&key);
+                if let Ok(index) = expected.binary_search(&key) {
+                    expected.remove(index);
+                }
+            }
+        }
+        check_distances(&map);
+
+        for (index, key) in expected.iter().enumerate() {
+            assert_eq!(map.get_index(index), Some((key, key)));
+            assert_eq!(map.rank(key), index);
+        }
+        assert_eq!(map.get_index(expected.len()), None);
+        assert_eq!(map.rank(&500), expected.len());
+
+        map.retain(|key, _| key % 3 == 0);
+        expected.retain(|key| key % 3 == 0);
+        check_distances(&map);
+        for (index, key) in expected.iter().enumerate() {
+            assert_eq!(map.get_index(index), Some((key, key)));
+        }
+        check_distances(&map.clone());
+
+        let other: SkipMap<u32, u32> = (0..600).step_by(5).map(|key| (key, key)).collect();
+        check_distances(&SkipMap::union(map.clone(), other.clone()));
+        check_distances(&SkipMap::intersection(map.clone(), other.clone()));
+        check_distances(&SkipMap::difference(map, other));
+    }
+
+    #[test]
+    fn test_iter_from_index() {
+        let map: SkipMap<u32, u32> = (0..100).map(|key| (key, key)).collect();
+        assert!(map
+            .iter_from_index(90)
+            .map(|(key, value)| (*key, *value))
+            .eq((90..100).map(|key| (key, key))));
+        assert_eq!(map.iter_from_index(100).next(), None);
+        assert_eq!(map.iter_from_index(200).next(), None);
+    }
+
     #[test]
     fn test_union() {
         let mut n = SkipMap::new();
@@ -1307,6 +2882,39 @@ mod tests {
         assert_eq!(symmetric_difference.len(), 4);
     }
 
+    #[test]
+    fn test_set_op_iters() {
+        let mut n = SkipMap::new();
+        n.insert(1, 1);
+        n.insert(2, 2);
+        n.insert(3, 3);
+
+        let mut m = SkipMap::new();
+        m.insert(3, 5);
+        m.insert(4, 4);
+        m.insert(5, 5);
+
+        assert_eq!(
+            n.union_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+            vec![(&1, &1), (&2, &2), (&3, &3), (&4, &4), (&5, &5)],
+        );
+        assert_eq!(
+            n.intersection_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+            vec![(&3, &3)],
+        );
+        assert_eq!(
+            n.difference_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+            vec![(&1, &1), (&2, &2)],
+        );
+        assert_eq!(
+            n.symmetric_difference_iter(&m)
+                .collect::<Vec<(&u32, &u32)>>(),
+            vec![(&1, &1), (&2, &2), (&4, &4), (&5, &5)],
+        );
+        assert_eq!(n.len(), 3);
+        assert_eq!(m.len(), 3);
+    }
+
     #[test]
     fn test_into_iter() {
         let mut map = SkipMap::new();
@@ -1349,4 +2957,339 @@ mod tests {
             vec![(&1, &3), (&3, &5), (&5, &7)],
         );
     }
+
+    #[test]
+    fn test_with_rng() {
+        let mut map = SkipMap::with_rng(XorShiftRng::from_seed([1, 1, 1, 1]));
+        let mut other = SkipMap::with_rng(XorShiftRng::from_seed([1, 1, 1, 1]));
+        for key in 0..100 {
+            map.insert(key, key);
+            other.insert(key, key);
+        }
+
+        unsafe {
+            let mut curr_node = *(*map.head).get_pointer(0);
+            let mut other_curr_node = *(*other.head).get_pointer(0);
+            while !curr_node.is_null() {
+                assert_eq!((*curr_node).links_len, (*other_curr_node).links_len);
+                curr_node = *(*curr_node).get_pointer(0);
+                other_curr_node = *(*other_curr_node).get_pointer(0);
+            }
+            assert!(other_curr_node.is_null());
+        }
+    }
+
+    #[test]
+    fn test_height_cap() {
+        let mut map = SkipMap::with_seed(1);
+        unsafe {
+            assert_eq!((*map.head).links_len, 1);
+            for key in 0..1000 {
+                map.insert(key, key);
+                assert!((*map.head).links_len <= map.get_max_height() + 1);
+            }
+
+            let mut curr_node = *(*map.head).get_pointer(0);
+            while !curr_node.is_null() {
+                assert!((*curr_node).links_len <= (*map.head).links_len);
+                curr_node = *(*curr_node).get_pointer(0);
+            }
+        }
+        assert_eq!(
+            map.iter().map(|(key, _)| *key).collect::<Vec<u32>>(),
+            (0..1000).collect::<Vec<u32>>()
+        );
+    }
+
+    #[test]
+    fn test_with_probability() {
+        let mut map = SkipMap::with_probability(0.5);
+        let mut expected = Vec::new();
+        for key in 0..1000 {
+            map.insert(key, key);
+            expected.push(key);
+        }
+        for key in (0..1000).step_by(3) {
+            assert_eq!(map.remove(&key), Some((key, key)));
+        }
+        expected.retain(|key| key % 3 != 0);
+        assert_eq!(map.probability(), 0.5);
+        assert_eq!(
+            map.iter().map(|(key, _)| *key).collect::<Vec<u32>>(),
+            expected
+        );
+        for (index, key) in expected.iter().enumerate() {
+            assert_eq!(map.get_index(index), Some((key, key)));
+            assert_eq!(map.rank(key), index);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_with_probability_invalid() {
+        SkipMap::<u32, u32>::with_probability(1.0);
+    }
+
+    #[test]
+    fn test_with_seed() {
+        let mut map = SkipMap::with_seed(1);
+        let mut other = SkipMap::with_seed(1);
+        for key in 0..100 {
+            map.insert(key, key);
+            other.insert(key, key);
+        }
+
+        unsafe {
+            let mut curr_node = *(*map.head).get_pointer(0);
+            let mut other_curr_node = *(*other.head).get_pointer(0);
+            while !curr_node.is_null() {
+                assert_eq!((*curr_node).links_len, (*other_curr_node).links_len);
+                curr_node = *(*curr_node).get_pointer(0);
+                other_curr_node = *(*other_curr_node).get_pointer(0);
+            }
+            assert!(other_curr_node.is_null());
+        }
+    }
+
+    #[test]
+    fn test_clone_eq() {
+        let mut map = SkipMap::new();
+        map.insert(1, 2);
+        map.insert(5, 6);
+        map.insert(3, 4);
+
+        let mut cloned = map.clone();
+        assert_eq!(cloned, map);
+        cloned.insert(3, 5);
+        assert_ne!(cloned, map);
+        cloned.insert(3, 4);
+        cloned.insert(7, 8);
+        assert_ne!(cloned, map);
+        cloned.remove(&7);
+        assert_eq!(cloned, map);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut map = SkipMap::new();
+        map.insert(3, 4);
+        map.insert(1, 2);
+        assert_eq!(format!("{:?}", map), "{1: 2, 3: 4}");
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut map = SkipMap::new();
+        for key in 0..10_007u32 {
+            map.insert(key * 7_919 % 10_007, key);
+        }
+
+        let is_retained = |key: &u32| !key.is_multiple_of(3) && (*key < 2_000 || *key > 6_000);
+        map.retain(|key, value| {
+            *value += 1;
+            is_retained(key)
+        });
+        let expected: Vec<u32> = (0..10_007).filter(is_retained).collect();
+        assert_eq!(map.len(), expected.len());
+        assert_eq!(
+            map.iter().map(|(key, _)| *key).collect::<Vec<u32>>(),
+            expected,
+        );
+
+        for key in expected.iter().filter(|key| *key % 2 == 0) {
+            assert!(map.remove(key).is_some());
+        }
+        map.insert(4_000, 0);
+        assert_eq!(
+            map.len(),
+            expected.iter().filter(|key| *key % 2 == 1).count() + 1
+        );
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut map = SkipMap::with_seed(1);
+        let mut expected = Vec::new();
+        for key in 0..200 {
+            map.insert(key, key);
+            expected.push((key, key));
+        }
+
+        {
+            let mut cursor = map.cursor_at(&50);
+            assert_eq!(cursor.index(), 50);
+            for _ in 0..20 {
+                assert!(cursor.move_prev());
+            }
+            assert_eq!(cursor.current(), Some((&30, &30)));
+            while let Some((&key, _)) = cursor.current() {
+                if key % 3 == 0 {
+                    assert_eq!(cursor.remove_current(), Some((key, key)));
+                } else {
+                    *cursor.current_mut().unwrap().1 += 1000;
+                    cursor.move_next();
+                }
+            }
+            assert!(!cursor.move_next());
+            assert_eq!(cursor.index(), 143);
+            while cursor.move_prev() {}
+            assert_eq!(cursor.current(), Some((&0, &0)));
+        }
+        expected = expected
+            .into_iter()
+            .filter(|&(key, _)| key < 30 || key % 3 != 0)
+            .map(|(key, value)| {
+                if key < 30 {
+                    (key, value)
+                } else {
+                    (key, value + 1000)
+                }
+            })
+            .collect();
+
+        assert_eq!(map.len(), expected.len());
+        assert_eq!(
+            map.iter()
+                .map(|(key, value)| (*key, *value))
+                .collect::<Vec<(u32, u32)>>(),
+            expected,
+        );
+        for (index, (key, value)) in expected.iter().enumerate() {
+            assert_eq!(map.get_index(index), Some((key, value)));
+            assert_eq!(map.rank(key), index);
+        }
+        assert_eq!(map.cursor_at(&1000).current(), None);
+    }
+
+    #[test]
+    fn test_split_off_inclusive() {
+        let mut map = SkipMap::new();
+        map.insert(1, 1);
+        map.insert(2, 2);
+        map.insert(3, 3);
+
+        let split = map.split_off(&2, true);
+        assert_eq!(
+            map.iter().collect::<Vec<(&u32, &u32)>>(),
+            vec![(&1, &1), (&2, &2)],
+        );
+        assert_eq!(split.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&3, &3)]);
+    }
+
+    #[test]
+    fn test_split_off_not_inclusive() {
+        let mut map = SkipMap::new();
+        map.insert(1, 1);
+        map.insert(2, 2);
+        map.insert(3, 3);
+
+        let split = map.split_off(&2, false);
+        assert_eq!(map.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&1, &1)]);
+        assert_eq!(
+            split.iter().collect::<Vec<(&u32, &u32)>>(),
+            vec![(&2, &2), (&3, &3)],
+        );
+    }
+
+    #[test]
+    fn test_remove_range() {
+        let ranges: [(Bound<u32>, Bound<u32>); 6] = [
+            (Bound::Included(20), Bound::Excluded(70)),
+            (Bound::Excluded(20), Bound::Included(70)),
+            (Bound::Unbounded, Bound::Excluded(35)),
+            (Bound::Included(35), Bound::Unbounded),
+            (Bound::Unbounded, Bound::Unbounded),
+            (Bound::Included(50), Bound::Excluded(50)),
+        ];
+        for range in ranges.iter() {
+            let mut map = SkipMap::new();
+            for key in (0..100).rev() {
+                map.insert(key, key);
+            }
+
+            let removed = map.remove_range(*range);
+            let is_removed = |key: &u32| range.contains(key);
+            assert_eq!(map.len() + removed.len(), 100);
+            assert!(map
+                .iter()
+                .map(|(key, _)| *key)
+                .eq((0..100).filter(|key| !is_removed(key))));
+            assert!(removed
+                .iter()
+                .map(|(key, _)| *key)
+                .eq((0..100).filter(is_removed)));
+            for (index, (key, _)) in map.iter().enumerate() {
+                assert_eq!(map.rank(key), index);
+            }
+            for (index, (key, _)) in removed.iter().enumerate() {
+                assert_eq!(removed.rank(key), index);
+            }
+        }
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut map = SkipMap::new();
+        map.insert(1, 2);
+        map.insert(5, 6);
+        map.insert(3, 4);
+
+        assert_eq!(
+            map.drain().collect::<Vec<(u32, u32)>>(),
+            vec![(1, 2), (3, 4), (5, 6)],
+        );
+        assert!(map.is_empty());
+        map.insert(1, 2);
+        assert_eq!(map.get(&1), Some(&2));
+    }
+
+    #[test]
+    fn test_comparator() {
+        let mut map = SkipMap::with_comparator(|a: &String, b: &String| {
+            a.to_lowercase().cmp(&b.to_lowercase())
+        });
+        map.insert(String::from("b"), 1);
+        map.insert(String::from("C"), 2);
+        assert_eq!(
+            map.insert(String::from("B"), 3),
+            Some((String::from("b"), 1))
+        );
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&String::from("c")), Some(&2));
+        assert_eq!(map.floor(&String::from("bb")), Some(&String::from("B")));
+        assert_eq!(map.ceil(&String::from("a")), Some(&String::from("B")));
+        assert_eq!(map.remove(&String::from("c")), Some((String::from("C"), 2)));
+        assert_eq!(
+            map.iter().collect::<Vec<(&String, &u32)>>(),
+            vec![(&String::from("B"), &3)],
+        );
+    }
+
+    #[test]
+    fn test_comparator_set_ops() {
+        let compare = |a: &u32, b: &u32| b.cmp(a);
+        let mut n = SkipMap::with_comparator(compare);
+        n.insert(1, 1);
+        n.insert(2, 2);
+
+        let mut m = SkipMap::with_comparator(compare);
+        m.insert(2, 3);
+        m.insert(3, 3);
+
+        assert_eq!(n.rank(&1), 1);
+        assert_eq!(n.get_index(0), Some((&2, &2)));
+
+        let union = SkipMap::union(n.clone(), m.clone());
+        assert_eq!(
+            union.iter().collect::<Vec<(&u32, &u32)>>(),
+            vec![(&3, &3), (&2, &2), (&1, &1)],
+        );
+
+        let difference = SkipMap::difference(n, m);
+        assert_eq!(
+            difference.iter().collect::<Vec<(&u32, &u32)>>(),
+            vec![(&1, &1)]
+        );
+    }
 }
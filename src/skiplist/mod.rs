@@ -1,9 +1,9 @@
 //! Probabilistic linked hierarchy of subsequences.
 
 mod list;
-mod map;
+pub(crate) mod map;
 mod set;
 
-pub use self::list::SkipList;
-pub use self::map::SkipMap;
+pub use self::list::{SkipList, SkipListCursor};
+pub use self::map::{SkipMap, SkipMapCursor};
 pub use self::set::SkipSet;
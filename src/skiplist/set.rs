@@ -1,6 +1,11 @@
+use crate::ordered::OrderedSet;
+use crate::set_ops::{DifferenceIter, IntersectionIter, SymmetricDifferenceIter, UnionIter};
 use crate::skiplist::map::{SkipMap, SkipMapIntoIter, SkipMapIter};
-use std::borrow::Borrow;
-use std::ops::{Add, Sub};
+use core::borrow::Borrow;
+use core::fmt;
+use core::iter::FromIterator;
+use core::ops::{Add, RangeBounds, Sub};
+use rand::XorShiftRng;
 
 /// An ordered set implemented using a skiplist.
 ///
@@ -28,6 +33,7 @@ use std::ops::{Add, Sub};
 /// assert_eq!(set.remove(&0), Some(0));
 /// assert_eq!(set.remove(&1), None);
 /// ```
+#[derive(Clone)]
 pub struct SkipSet<T> {
     map: SkipMap<T, ()>,
 }
@@ -48,6 +54,62 @@ impl<T> SkipSet<T> {
         }
     }
 
+    /// Constructs a new, empty `SkipSet<T>` that uses `rng` to generate the heights of its nodes.
+    /// Seeding `rng` makes the internal structure reproducible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    /// use rand::{SeedableRng, XorShiftRng};
+    ///
+    /// let set: SkipSet<u32> = SkipSet::with_rng(XorShiftRng::from_seed([1, 1, 1, 1]));
+    /// ```
+    pub fn with_rng(rng: XorShiftRng) -> Self {
+        SkipSet {
+            map: SkipMap::with_rng(rng),
+        }
+    }
+
+    /// Constructs a new, empty `SkipSet<T>` that generates the heights of its nodes with a random
+    /// number generator seeded with `seed`. Sets constructed with the same seed have the same
+    /// internal structure after the same sequence of operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    ///
+    /// let set: SkipSet<u32> = SkipSet::with_seed(1);
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        SkipSet {
+            map: SkipMap::with_seed(seed),
+        }
+    }
+
+    /// Constructs a new, empty `SkipSet<T>` where each node is promoted to the next level with
+    /// probability `probability`. The default probability is `0.25`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is not strictly between `0` and `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    ///
+    /// let mut set = SkipSet::with_probability(0.5);
+    /// set.insert(1);
+    /// assert!(set.contains(&1));
+    /// ```
+    pub fn with_probability(probability: f64) -> Self {
+        SkipSet {
+            map: SkipMap::with_probability(probability),
+        }
+    }
+
     /// Inserts a key into the set. If the key already exists in the set, it will return and
     /// replace the key.
     ///
@@ -155,6 +217,49 @@ impl<T> SkipSet<T> {
         self.map.clear();
     }
 
+    /// Retains only the keys that satisfy a predicate. The keys are visited in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    ///
+    /// let mut set = SkipSet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    /// set.insert(3);
+    ///
+    /// set.retain(|key| key % 2 == 1);
+    /// assert_eq!(set.iter().collect::<Vec<&u32>>(), vec![&1, &3]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.map.retain(|key, _| f(key));
+    }
+
+    /// Clears the set, returning all keys as an iterator. The iterator yields the keys in
+    /// ascending order, and the set can be reused afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    ///
+    /// let mut set = SkipSet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    ///
+    /// assert_eq!(set.drain().collect::<Vec<u32>>(), vec![1, 2]);
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> SkipSetIntoIter<T> {
+        SkipSetIntoIter {
+            map_iter: self.map.drain(),
+        }
+    }
+
     /// Returns a key in the set that is less than or equal to a particular key. Returns `None` if
     /// such a key does not exist.
     ///
@@ -235,6 +340,102 @@ impl<T> SkipSet<T> {
         self.map.max()
     }
 
+    /// Splits the set and returns the right part of the set. If `inclusive` is true, then the set
+    /// will retain the given key if it exists. Otherwise, the right part of the set will contain
+    /// the key if it exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    ///
+    /// let mut set = SkipSet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    /// set.insert(3);
+    ///
+    /// let split = set.split_off(&2, true);
+    /// assert!(set.contains(&1));
+    /// assert!(set.contains(&2));
+    /// assert!(split.contains(&3));
+    /// ```
+    pub fn split_off<V>(&mut self, key: &V, inclusive: bool) -> Self
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        SkipSet {
+            map: self.map.split_off(key, inclusive),
+        }
+    }
+
+    /// Removes all keys that are in `range` from the set and returns them as a new set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    ///
+    /// let mut set = SkipSet::new();
+    /// for key in 0..5 {
+    ///     set.insert(key);
+    /// }
+    ///
+    /// let removed = set.remove_range(1..3);
+    /// assert_eq!(removed.iter().collect::<Vec<&u32>>(), vec![&1, &2]);
+    /// assert_eq!(set.iter().collect::<Vec<&u32>>(), vec![&0, &3, &4]);
+    /// ```
+    pub fn remove_range<V, R>(&mut self, range: R) -> Self
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+        R: RangeBounds<V>,
+    {
+        SkipSet {
+            map: self.map.remove_range(range),
+        }
+    }
+
+    /// Returns the key at a particular index of the set, where the keys are in ascending order.
+    /// Returns `None` if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    ///
+    /// let mut set = SkipSet::new();
+    /// set.insert(1);
+    /// set.insert(3);
+    /// assert_eq!(set.get_index(1), Some(&3));
+    /// assert_eq!(set.get_index(2), None);
+    /// ```
+    pub fn get_index(&self, index: usize) -> Option<&T> {
+        self.map.get_index(index).map(|pair| pair.0)
+    }
+
+    /// Returns the number of keys in the set that are less than a particular key. If the key
+    /// exists in the set, this is its index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    ///
+    /// let mut set = SkipSet::new();
+    /// set.insert(1);
+    /// set.insert(3);
+    /// assert_eq!(set.rank(&2), 1);
+    /// assert_eq!(set.rank(&3), 1);
+    /// ```
+    pub fn rank<V>(&self, key: &V) -> usize
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        self.map.rank(key)
+    }
+
     /// Returns the union of two set. The `+` operator is implemented to take the union of two
     /// sets.
     ///
@@ -349,6 +550,126 @@ impl<T> SkipSet<T> {
         }
     }
 
+    /// Returns a lazy iterator over the union of two sets without consuming them. The iterator
+    /// will yield keys in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    ///
+    /// let mut n = SkipSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = SkipSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// assert_eq!(
+    ///     n.union_iter(&m).collect::<Vec<&u32>>(),
+    ///     vec![&1, &2, &3],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn union_iter<'a>(&'a self, other: &'a Self) -> UnionIter<SkipSetIter<'a, T>>
+    where
+        T: Ord,
+    {
+        UnionIter::new(self.iter(), other.iter())
+    }
+
+    /// Returns a lazy iterator over the intersection of two sets without consuming them. The
+    /// iterator will yield keys in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    ///
+    /// let mut n = SkipSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = SkipSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// assert_eq!(
+    ///     n.intersection_iter(&m).collect::<Vec<&u32>>(),
+    ///     vec![&2],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn intersection_iter<'a>(&'a self, other: &'a Self) -> IntersectionIter<SkipSetIter<'a, T>>
+    where
+        T: Ord,
+    {
+        IntersectionIter::new(self.iter(), other.iter())
+    }
+
+    /// Returns a lazy iterator over the difference of two sets without consuming them. The
+    /// iterator will yield the keys of `self` that are not in `other` in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    ///
+    /// let mut n = SkipSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = SkipSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// assert_eq!(
+    ///     n.difference_iter(&m).collect::<Vec<&u32>>(),
+    ///     vec![&1],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn difference_iter<'a>(&'a self, other: &'a Self) -> DifferenceIter<SkipSetIter<'a, T>>
+    where
+        T: Ord,
+    {
+        DifferenceIter::new(self.iter(), other.iter())
+    }
+
+    /// Returns a lazy iterator over the symmetric difference of two sets without consuming
+    /// them. The iterator will yield the keys that exist in one set, but not both sets, in
+    /// ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    ///
+    /// let mut n = SkipSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = SkipSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// assert_eq!(
+    ///     n.symmetric_difference_iter(&m).collect::<Vec<&u32>>(),
+    ///     vec![&1, &3],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn symmetric_difference_iter<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> SymmetricDifferenceIter<SkipSetIter<'a, T>>
+    where
+        T: Ord,
+    {
+        SymmetricDifferenceIter::new(self.iter(), other.iter())
+    }
+
     /// Returns an iterator over the set. The iterator will yield key in ascending order.
     ///
     /// # Examples
@@ -370,6 +691,28 @@ impl<T> SkipSet<T> {
             map_iter: self.map.iter(),
         }
     }
+
+    /// Returns an iterator over the set starting at a particular index. The iterator will yield
+    /// keys in ascending order, and will be empty if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::skiplist::SkipSet;
+    ///
+    /// let mut set = SkipSet::new();
+    /// set.insert(1);
+    /// set.insert(3);
+    ///
+    /// let mut iterator = set.iter_from_index(1);
+    /// assert_eq!(iterator.next(), Some(&3));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter_from_index(&self, index: usize) -> SkipSetIter<'_, T> {
+        SkipSetIter {
+            map_iter: self.map.iter_from_index(index),
+        }
+    }
 }
 
 impl<T> IntoIterator for SkipSet<T> {
@@ -429,12 +772,109 @@ where
     }
 }
 
+impl<T> OrderedSet<T> for SkipSet<T>
+where
+    T: Ord,
+{
+    type Iter<'a>
+        = SkipSetIter<'a, T>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn insert(&mut self, key: T) -> Option<T> {
+        SkipSet::insert(self, key)
+    }
+
+    fn remove(&mut self, key: &T) -> Option<T> {
+        SkipSet::remove(self, key)
+    }
+
+    fn contains(&self, key: &T) -> bool {
+        SkipSet::contains(self, key)
+    }
+
+    fn len(&self) -> usize {
+        SkipSet::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        SkipSet::is_empty(self)
+    }
+
+    fn floor(&self, key: &T) -> Option<&T> {
+        SkipSet::floor(self, key)
+    }
+
+    fn ceil(&self, key: &T) -> Option<&T> {
+        SkipSet::ceil(self, key)
+    }
+
+    fn min(&self) -> Option<&T> {
+        SkipSet::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        SkipSet::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        SkipSet::iter(self)
+    }
+}
+
+impl<T> fmt::Debug for SkipSet<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T> PartialEq for SkipSet<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T> Eq for SkipSet<T> where T: Eq {}
+
 impl<T> Default for SkipSet<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl<T> FromIterator<T> for SkipSet<T>
+where
+    T: Ord,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        SkipSet {
+            map: iter.into_iter().map(|key| (key, ())).collect(),
+        }
+    }
+}
+
+impl<T> Extend<T> for SkipSet<T>
+where
+    T: Ord,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.map.extend(iter.into_iter().map(|key| (key, ())));
+    }
+}
+
 impl<T> Add for SkipSet<T>
 where
     T: Ord,
@@ -609,6 +1049,32 @@ mod tests {
         assert_eq!(symmetric_difference.len(), 4);
     }
 
+    #[test]
+    fn test_set_op_iters() {
+        let mut n = SkipSet::new();
+        n.insert(1);
+        n.insert(2);
+        n.insert(3);
+
+        let mut m = SkipSet::new();
+        m.insert(3);
+        m.insert(4);
+        m.insert(5);
+
+        assert_eq!(
+            n.union_iter(&m).collect::<Vec<&u32>>(),
+            vec![&1, &2, &3, &4, &5],
+        );
+        assert_eq!(n.intersection_iter(&m).collect::<Vec<&u32>>(), vec![&3]);
+        assert_eq!(n.difference_iter(&m).collect::<Vec<&u32>>(), vec![&1, &2]);
+        assert_eq!(
+            n.symmetric_difference_iter(&m).collect::<Vec<&u32>>(),
+            vec![&1, &2, &4, &5],
+        );
+        assert_eq!(n.len(), 3);
+        assert_eq!(m.len(), 3);
+    }
+
     #[test]
     fn test_into_iter() {
         let mut set = SkipSet::new();
@@ -619,6 +1085,13 @@ mod tests {
         assert_eq!(set.into_iter().collect::<Vec<u32>>(), vec![1, 3, 5]);
     }
 
+    #[test]
+    fn test_from_iter() {
+        let mut set: SkipSet<u32> = vec![3, 1, 2, 1].into_iter().collect();
+        set.extend(vec![5, 4]);
+        assert_eq!(set.into_iter().collect::<Vec<u32>>(), vec![1, 2, 3, 4, 5]);
+    }
+
     #[test]
     fn test_iter() {
         let mut set = SkipSet::new();
@@ -628,4 +1101,55 @@ mod tests {
 
         assert_eq!(set.iter().collect::<Vec<&u32>>(), vec![&1, &3, &5]);
     }
+
+    #[test]
+    fn test_clone_eq() {
+        let mut set = SkipSet::new();
+        set.insert(1);
+        set.insert(5);
+        set.insert(3);
+
+        let mut cloned = set.clone();
+        assert_eq!(cloned, set);
+        cloned.insert(7);
+        assert_ne!(cloned, set);
+        cloned.remove(&7);
+        assert_eq!(cloned, set);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut set = SkipSet::new();
+        set.insert(3);
+        set.insert(1);
+        assert_eq!(format!("{:?}", set), "{1, 3}");
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut set = SkipSet::new();
+        for key in 0..100 {
+            set.insert(key);
+        }
+
+        set.retain(|key| key % 3 == 0);
+        assert_eq!(set.len(), 34);
+        assert_eq!(
+            set.into_iter().collect::<Vec<u32>>(),
+            (0..100).filter(|key| key % 3 == 0).collect::<Vec<u32>>(),
+        );
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut set = SkipSet::new();
+        set.insert(1);
+        set.insert(5);
+        set.insert(3);
+
+        assert_eq!(set.drain().collect::<Vec<u32>>(), vec![1, 3, 5]);
+        assert!(set.is_empty());
+        set.insert(1);
+        assert!(set.contains(&1));
+    }
 }
@@ -1,14 +1,22 @@
 use crate::entry::Entry;
+use crate::node_arena::{NodeArena, NodeRef};
+use crate::ordered::OrderedMap;
 use crate::splay_tree::node::Node;
 use crate::splay_tree::tree;
-use std::borrow::Borrow;
-use std::ops::{Index, IndexMut};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::{Index, IndexMut};
 
 /// An ordered map implemented using splay tree.
 ///
 /// An splay tree is a self-adjusting binary tree with an additional property that recently accessed
 /// items are quick to access again. After each operation, the item that was accessed is "splayed"
-/// to the root of the tree.
+/// to the root of the tree. Each node is allocated in its own box unless the map is constructed
+/// with `with_arena`.
 ///
 /// # Examples
 ///
@@ -31,7 +39,8 @@ use std::ops::{Index, IndexMut};
 /// assert_eq!(map.remove(&1), None);
 /// ```
 pub struct SplayMap<T, U> {
-    tree: tree::Tree<T, U>,
+    arena: tree::Arena<T, U>,
+    tree: tree::Tree,
     len: usize,
 }
 
@@ -46,7 +55,31 @@ impl<T, U> SplayMap<T, U> {
     /// let map: SplayMap<u32, u32> = SplayMap::new();
     /// ```
     pub fn new() -> Self {
-        SplayMap { tree: None, len: 0 }
+        SplayMap {
+            arena: NodeArena::boxed(),
+            tree: None,
+            len: 0,
+        }
+    }
+
+    /// Constructs a new, empty `SplayMap<T, U>` that allocates its nodes from a `TypedArena` to
+    /// improve cache locality.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::with_arena();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// ```
+    pub fn with_arena() -> Self {
+        SplayMap {
+            arena: NodeArena::pooled(),
+            tree: None,
+            len: 0,
+        }
     }
 
     /// Inserts a key-value pair into the map. If the key already exists in the map, it will return
@@ -68,12 +101,13 @@ impl<T, U> SplayMap<T, U> {
         T: Ord,
     {
         let SplayMap {
+            ref mut arena,
             ref mut tree,
             ref mut len,
         } = self;
         let new_node = Node::new(key, value);
         *len += 1;
-        tree::insert(tree, new_node).and_then(|entry| {
+        tree::insert(arena, tree, new_node).and_then(|entry| {
             let Entry { key, value } = entry;
             *len -= 1;
             Some((key, value))
@@ -99,10 +133,11 @@ impl<T, U> SplayMap<T, U> {
         V: Ord + ?Sized,
     {
         let SplayMap {
+            ref mut arena,
             ref mut tree,
             ref mut len,
         } = self;
-        tree::remove(tree, &key).and_then(|entry| {
+        tree::remove(arena, tree, key).and_then(|entry| {
             let Entry { key, value } = entry;
             *len -= 1;
             Some((key, value))
@@ -149,7 +184,7 @@ impl<T, U> SplayMap<T, U> {
         T: Borrow<V>,
         V: Ord + ?Sized,
     {
-        tree::get(&self.tree, key).map(|entry| &entry.value)
+        tree::get(&self.arena, self.tree, key).map(|entry| &entry.value)
     }
 
     /// Returns a mutable reference to the value associated with a particular key. Returns `None`
@@ -170,7 +205,78 @@ impl<T, U> SplayMap<T, U> {
         T: Borrow<V>,
         V: Ord + ?Sized,
     {
-        tree::get_mut(&mut self.tree, key).map(|entry| &mut entry.value)
+        tree::get_mut(&mut self.arena, self.tree, key).map(|entry| &mut entry.value)
+    }
+
+    /// Returns a mutable reference to the value associated with a particular key without splaying
+    /// the tree. Returns `None` if such a key does not exist. Unlike `get_mut`, the structure of
+    /// the tree is left unchanged, so the access does not affect which entries are quick to access
+    /// again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// *map.get_quiet_mut(&1).unwrap() = 3;
+    /// assert_eq!(map.get(&1), Some(&3));
+    /// assert_eq!(map.top(), Some((&2, &2)));
+    /// ```
+    pub fn get_quiet_mut<V>(&mut self, key: &V) -> Option<&mut U>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::get_quiet_mut(&mut self.arena, self.tree, key).map(|entry| &mut entry.value)
+    }
+
+    /// Splays a particular key to the top of the tree and returns an immutable reference to its
+    /// associated value. Returns `None` if the key does not exist in the map, in which case the
+    /// last key visited during the search is splayed to the top instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.splay(&1), Some(&1));
+    /// assert_eq!(map.top(), Some((&1, &1)));
+    /// ```
+    pub fn splay<V>(&mut self, key: &V) -> Option<&U>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::get_mut(&mut self.arena, self.tree, key).map(|entry| &entry.value)
+    }
+
+    /// Returns the key-value pair at the top of the tree, which is the entry that was most
+    /// recently inserted or splayed. Returns `None` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.top(), Some((&2, &2)));
+    ///
+    /// map.get_mut(&1);
+    /// assert_eq!(map.top(), Some((&1, &1)));
+    /// ```
+    pub fn top(&self) -> Option<(&T, &U)> {
+        self.tree.map(|node| {
+            let Entry { ref key, ref value } = self.arena[node].entry;
+            (key, value)
+        })
     }
 
     /// Returns the number of elements in the map.
@@ -216,10 +322,64 @@ impl<T, U> SplayMap<T, U> {
     /// assert_eq!(map.is_empty(), true);
     /// ```
     pub fn clear(&mut self) {
+        self.arena.release(self.tree.take());
+        self.arena = self.arena.empty();
         self.tree = None;
         self.len = 0;
     }
 
+    /// Retains only the key-value pairs that satisfy a predicate. The key-value pairs are visited
+    /// in ascending order of their keys. The retained nodes are relinked into a balanced tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.insert(3, 3);
+    ///
+    /// map.retain(|key, value| {
+    ///     *value += 1;
+    ///     key % 2 == 1
+    /// });
+    /// assert_eq!(map.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&1, &2), (&3, &4)]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T, &mut U) -> bool,
+    {
+        self.tree = tree::retain(&mut self.arena, self.tree, &mut f);
+        self.len = self.arena.len();
+    }
+
+    /// Clears the map, returning all key-value pairs as an iterator. The iterator yields the
+    /// key-value pairs in ascending order of their keys, and the map can be reused afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// assert_eq!(map.drain().collect::<Vec<(u32, u32)>>(), vec![(1, 1), (2, 2)]);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> SplayMapIntoIter<T, U> {
+        self.len = 0;
+        let arena = self.arena.empty();
+        SplayMapIntoIter {
+            arena: mem::replace(&mut self.arena, arena),
+            current: self.tree.take(),
+            stack: Vec::new(),
+        }
+    }
+
     /// Returns a key in the map that is less than or equal to a particular key. Returns `None` if
     /// such a key does not exist. Note that `floor` does not splay the tree in order to use a
     /// non-mutable reference.
@@ -239,7 +399,7 @@ impl<T, U> SplayMap<T, U> {
         T: Borrow<V>,
         V: Ord + ?Sized,
     {
-        tree::floor(&self.tree, key).map(|entry| &entry.key)
+        tree::floor(&self.arena, self.tree, key, true).map(|entry| &entry.key)
     }
 
     /// Returns a key in the map that is greater than or equal to a particular key. Returns `None`
@@ -261,7 +421,147 @@ impl<T, U> SplayMap<T, U> {
         T: Borrow<V>,
         V: Ord + ?Sized,
     {
-        tree::ceil(&self.tree, key).map(|entry| &entry.key)
+        tree::ceil(&self.arena, self.tree, key, true).map(|entry| &entry.key)
+    }
+
+    /// Returns the key-value pair in the map with the greatest key that is less than or equal to a
+    /// particular key. Returns `None` if such a key does not exist. Note that `floor_entry` does
+    /// not splay the tree in order to use a non-mutable reference.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.floor_entry(&0), None);
+    /// assert_eq!(map.floor_entry(&2), Some((&1, &1)));
+    /// ```
+    pub fn floor_entry<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::floor(&self.arena, self.tree, key, true).map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Returns the key and a mutable reference to the value of the key-value pair in the map with
+    /// the greatest key that is less than or equal to a particular key. Returns `None` if such a
+    /// key does not exist. Note that `floor_entry_mut` does not splay the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// if let Some((_, value)) = map.floor_entry_mut(&2) {
+    ///     *value = 2;
+    /// }
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// ```
+    pub fn floor_entry_mut<V>(&mut self, key: &V) -> Option<(&T, &mut U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::floor_mut(&mut self.arena, self.tree, key, true)
+            .map(|entry| (&entry.key, &mut entry.value))
+    }
+
+    /// Returns the key-value pair in the map with the least key that is greater than or equal to a
+    /// particular key. Returns `None` if such a key does not exist. Note that `ceil_entry` does not
+    /// splay the tree in order to use a non-mutable reference.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.ceil_entry(&2), None);
+    /// assert_eq!(map.ceil_entry(&0), Some((&1, &1)));
+    /// ```
+    pub fn ceil_entry<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::ceil(&self.arena, self.tree, key, true).map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Returns the key and a mutable reference to the value of the key-value pair in the map with
+    /// the least key that is greater than or equal to a particular key. Returns `None` if such a
+    /// key does not exist. Note that `ceil_entry_mut` does not splay the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// if let Some((_, value)) = map.ceil_entry_mut(&0) {
+    ///     *value = 2;
+    /// }
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// ```
+    pub fn ceil_entry_mut<V>(&mut self, key: &V) -> Option<(&T, &mut U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::ceil_mut(&mut self.arena, self.tree, key, true)
+            .map(|entry| (&entry.key, &mut entry.value))
+    }
+
+    /// Returns the key-value pair in the map with the greatest key that is less than a particular
+    /// key. Returns `None` if such a key does not exist. Note that `next_below` does not splay the
+    /// tree in order to use a non-mutable reference.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.next_below(&1), None);
+    /// assert_eq!(map.next_below(&2), Some((&1, &1)));
+    /// ```
+    pub fn next_below<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::floor(&self.arena, self.tree, key, false).map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Returns the key-value pair in the map with the least key that is greater than a particular
+    /// key. Returns `None` if such a key does not exist. Note that `next_above` does not splay the
+    /// tree in order to use a non-mutable reference.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.next_above(&2), None);
+    /// assert_eq!(map.next_above(&1), Some((&2, &2)));
+    /// ```
+    pub fn next_above<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        tree::ceil(&self.arena, self.tree, key, false).map(|entry| (&entry.key, &entry.value))
     }
 
     /// Returns the minimum key of the map. Returns `None` if the map is empty. Node that `min`
@@ -281,7 +581,7 @@ impl<T, U> SplayMap<T, U> {
     where
         T: Ord,
     {
-        tree::min(&self.tree).map(|entry| &entry.key)
+        tree::min(&self.arena, self.tree).map(|entry| &entry.key)
     }
 
     /// Returns the maximum key of the map. Returns `None` if the map is empty. Node that `max`
@@ -301,7 +601,63 @@ impl<T, U> SplayMap<T, U> {
     where
         T: Ord,
     {
-        tree::max(&self.tree).map(|entry| &entry.key)
+        tree::max(&self.arena, self.tree).map(|entry| &entry.key)
+    }
+
+    /// Removes and returns the key-value pair with the minimum key in the map. Returns `None` if
+    /// the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.pop_min(), Some((1, 1)));
+    /// assert_eq!(map.pop_min(), Some((3, 3)));
+    /// assert_eq!(map.pop_min(), None);
+    /// ```
+    pub fn pop_min(&mut self) -> Option<(T, U)> {
+        let SplayMap {
+            ref mut arena,
+            ref mut tree,
+            ref mut len,
+        } = self;
+        tree::pop_min(arena, tree).map(|entry| {
+            let Entry { key, value } = entry;
+            *len -= 1;
+            (key, value)
+        })
+    }
+
+    /// Removes and returns the key-value pair with the maximum key in the map. Returns `None` if
+    /// the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.pop_max(), Some((3, 3)));
+    /// assert_eq!(map.pop_max(), Some((1, 1)));
+    /// assert_eq!(map.pop_max(), None);
+    /// ```
+    pub fn pop_max(&mut self) -> Option<(T, U)> {
+        let SplayMap {
+            ref mut arena,
+            ref mut tree,
+            ref mut len,
+        } = self;
+        tree::pop_max(arena, tree).map(|entry| {
+            let Entry { key, value } = entry;
+            *len -= 1;
+            (key, value)
+        })
     }
 
     /// Returns an iterator over the map. The iterator will yield key-value pairs using in-order
@@ -323,7 +679,8 @@ impl<T, U> SplayMap<T, U> {
     /// ```
     pub fn iter(&self) -> SplayMapIter<'_, T, U> {
         SplayMapIter {
-            current: &self.tree,
+            arena: &self.arena,
+            current: self.tree,
             stack: Vec::new(),
         }
     }
@@ -351,21 +708,46 @@ impl<T, U> SplayMap<T, U> {
     /// ```
     pub fn iter_mut(&mut self) -> SplayMapIterMut<'_, T, U> {
         SplayMapIterMut {
-            current: self.tree.as_mut().map(|node| &mut **node),
+            arena: &mut self.arena,
+            current: self.tree,
             stack: Vec::new(),
         }
     }
+
+    /// Returns an iterator over the map that approximates the order in which the entries were
+    /// accessed. The iterator will yield key-value pairs in breadth-first order starting from the
+    /// top of the tree. Since accessed entries are splayed to the top, recently accessed entries
+    /// tend to be yielded before entries that have not been accessed in a while.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplayMap;
+    ///
+    /// let mut map = SplayMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.insert(3, 3);
+    /// map.splay(&2);
+    ///
+    /// let mut iterator = map.iter_access_order();
+    /// assert_eq!(iterator.next(), Some((&2, &2)));
+    /// assert_eq!(iterator.count(), 2);
+    /// ```
+    pub fn iter_access_order(&self) -> SplayMapAccessOrderIter<'_, T, U> {
+        SplayMapAccessOrderIter {
+            arena: &self.arena,
+            queue: self.tree.into_iter().collect(),
+        }
+    }
 }
 
 impl<T, U> IntoIterator for SplayMap<T, U> {
     type IntoIter = SplayMapIntoIter<T, U>;
     type Item = (T, U);
 
-    fn into_iter(self) -> Self::IntoIter {
-        Self::IntoIter {
-            current: self.tree,
-            stack: Vec::new(),
-        }
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.drain()
     }
 }
 
@@ -399,36 +781,44 @@ where
 ///
 /// This iterator traverses the elements of the map in-order and yields owned entries.
 pub struct SplayMapIntoIter<T, U> {
-    current: tree::Tree<T, U>,
-    stack: Vec<Node<T, U>>,
+    arena: tree::Arena<T, U>,
+    current: tree::Tree,
+    stack: Vec<NodeRef>,
 }
 
 impl<T, U> Iterator for SplayMapIntoIter<T, U> {
     type Item = (T, U);
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(mut node) = self.current.take() {
-            self.current = node.left.take();
-            self.stack.push(*node);
+        while let Some(node) = self.current {
+            self.current = self.arena[node].left;
+            self.stack.push(node);
         }
         self.stack.pop().map(|node| {
             let Node {
                 entry: Entry { key, value },
                 right,
                 ..
-            } = node;
+            } = self.arena.free(&node);
             self.current = right;
             (key, value)
         })
     }
 }
 
+impl<T, U> Drop for SplayMapIntoIter<T, U> {
+    fn drop(&mut self) {
+        while self.next().is_some() {}
+    }
+}
+
 /// An iterator for `SplayMap<T, U>`.
 ///
 /// This iterator traverses the elements of the map in-order and yields immutable references.
 pub struct SplayMapIter<'a, T, U> {
-    current: &'a tree::Tree<T, U>,
-    stack: Vec<&'a Node<T, U>>,
+    arena: &'a tree::Arena<T, U>,
+    current: tree::Tree,
+    stack: Vec<NodeRef>,
 }
 
 impl<'a, T, U> Iterator for SplayMapIter<'a, T, U>
@@ -439,31 +829,61 @@ where
     type Item = (&'a T, &'a U);
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(ref node) = self.current {
-            self.current = &node.left;
+        let arena = self.arena;
+        while let Some(node) = self.current {
+            self.current = arena[node].left;
             self.stack.push(node);
         }
         self.stack.pop().map(|node| {
             let Node {
                 entry: Entry { ref key, ref value },
-                ref right,
+                right,
                 ..
-            } = node;
+            } = arena[node];
             self.current = right;
             (key, value)
         })
     }
 }
 
-type BorrowedIterEntryMut<'a, T, U> = Option<(&'a mut Entry<T, U>, BorrowedTreeMut<'a, T, U>)>;
-type BorrowedTreeMut<'a, T, U> = Option<&'a mut Node<T, U>>;
+/// An iterator for `SplayMap<T, U>` that approximates access order.
+///
+/// This iterator traverses the elements of the map in breadth-first order starting from the top of
+/// the tree and yields immutable references.
+pub struct SplayMapAccessOrderIter<'a, T, U> {
+    arena: &'a tree::Arena<T, U>,
+    queue: VecDeque<NodeRef>,
+}
+
+impl<'a, T, U> Iterator for SplayMapAccessOrderIter<'a, T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type Item = (&'a T, &'a U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let arena = self.arena;
+        self.queue.pop_front().map(|node| {
+            let Node {
+                entry: Entry { ref key, ref value },
+                left,
+                right,
+            } = arena[node];
+            self.queue.extend(left);
+            self.queue.extend(right);
+            (key, value)
+        })
+    }
+}
 
 /// A mutable iterator for `SplayMap<T, U>`.
 ///
 /// This iterator traverses the elements of the map in-order and yields mutable references.
 pub struct SplayMapIterMut<'a, T, U> {
-    current: Option<&'a mut Node<T, U>>,
-    stack: Vec<BorrowedIterEntryMut<'a, T, U>>,
+    arena: &'a mut tree::Arena<T, U>,
+    current: tree::Tree,
+    stack: Vec<NodeRef>,
 }
 
 impl<'a, T, U> Iterator for SplayMapIterMut<'a, T, U>
@@ -474,37 +894,170 @@ where
     type Item = (&'a T, &'a mut U);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let SplayMapIterMut {
-            ref mut current,
-            ref mut stack,
-        } = self;
-        while current.is_some() {
-            stack.push(current.take().map(|node| {
-                *current = node.left.as_mut().map(|node| &mut **node);
-                (&mut node.entry, node.right.as_mut().map(|node| &mut **node))
-            }));
+        while let Some(node) = self.current {
+            self.current = self.arena[node].left;
+            self.stack.push(node);
         }
-        stack.pop().and_then(|pair_opt| match pair_opt {
-            Some(pair) => {
-                let (entry, right) = pair;
-                let Entry {
-                    ref key,
-                    ref mut value,
-                } = entry;
-                *current = right;
-                Some((key, value))
-            }
-            None => None,
+        self.stack.pop().map(|node| {
+            // each node is yielded at most once, so the mutable references are disjoint
+            let node = unsafe { &mut *(&mut self.arena[node] as *mut Node<T, U>) };
+            let Node {
+                entry:
+                    Entry {
+                        ref key,
+                        ref mut value,
+                    },
+                right,
+                ..
+            } = node;
+            self.current = *right;
+            (key, value)
         })
     }
 }
 
+impl<T, U> OrderedMap<T, U> for SplayMap<T, U>
+where
+    T: Ord,
+{
+    type Iter<'a>
+        = SplayMapIter<'a, T, U>
+    where
+        Self: 'a,
+        T: 'a,
+        U: 'a;
+
+    fn insert(&mut self, key: T, value: U) -> Option<(T, U)> {
+        SplayMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &T) -> Option<(T, U)> {
+        SplayMap::remove(self, key)
+    }
+
+    fn contains_key(&self, key: &T) -> bool {
+        SplayMap::contains_key(self, key)
+    }
+
+    fn get(&self, key: &T) -> Option<&U> {
+        SplayMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &T) -> Option<&mut U> {
+        SplayMap::get_mut(self, key)
+    }
+
+    fn len(&self) -> usize {
+        SplayMap::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        SplayMap::is_empty(self)
+    }
+
+    fn floor(&self, key: &T) -> Option<&T> {
+        SplayMap::floor(self, key)
+    }
+
+    fn ceil(&self, key: &T) -> Option<&T> {
+        SplayMap::ceil(self, key)
+    }
+
+    fn min(&self) -> Option<&T> {
+        SplayMap::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        SplayMap::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        SplayMap::iter(self)
+    }
+}
+
+impl<T, U> Clone for SplayMap<T, U>
+where
+    T: Clone,
+    U: Clone,
+{
+    fn clone(&self) -> Self {
+        let (arena, tree) = self.arena.clone_tree(self.tree);
+        SplayMap {
+            arena,
+            tree,
+            len: self.len,
+        }
+    }
+}
+
+impl<T, U> Drop for SplayMap<T, U> {
+    fn drop(&mut self) {
+        self.arena.release(self.tree.take());
+    }
+}
+
+impl<T, U> fmt::Debug for SplayMap<T, U>
+where
+    T: fmt::Debug,
+    U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, U> PartialEq for SplayMap<T, U>
+where
+    T: PartialEq,
+    U: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T, U> Eq for SplayMap<T, U>
+where
+    T: Eq,
+    U: Eq,
+{
+}
+
 impl<T, U> Default for SplayMap<T, U> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl<T, U> FromIterator<(T, U)> for SplayMap<T, U>
+where
+    T: Ord,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (T, U)>,
+    {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<T, U> Extend<(T, U)> for SplayMap<T, U>
+where
+    T: Ord,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (T, U)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
 impl<'a, T, U, V> Index<&'a V> for SplayMap<T, U>
 where
     T: Borrow<V>,
@@ -614,6 +1167,66 @@ mod tests {
         assert_eq!(map.ceil(&6), None);
     }
 
+    #[test]
+    fn test_floor_ceil_entry() {
+        let mut map = SplayMap::new();
+        for key in (0..100u32).rev() {
+            map.insert(key * 2, key * 2);
+        }
+
+        let to_key = |entry: Option<(&u32, &u32)>| {
+            entry.map(|(key, value)| {
+                assert_eq!(key, value);
+                *key
+            })
+        };
+        for key in 0..200 {
+            assert_eq!(to_key(map.floor_entry(&key)), Some(key / 2 * 2));
+            assert_eq!(
+                to_key(map.ceil_entry(&key)),
+                Some(key.div_ceil(2) * 2).filter(|key| *key < 200),
+            );
+            assert_eq!(
+                to_key(map.next_below(&key)),
+                key.checked_sub(1).map(|key| key / 2 * 2),
+            );
+            assert_eq!(
+                to_key(map.next_above(&key)),
+                Some(key / 2 * 2 + 2).filter(|key| *key < 200),
+            );
+        }
+
+        *map.floor_entry_mut(&5).unwrap().1 = 0;
+        *map.ceil_entry_mut(&5).unwrap().1 = 0;
+        assert_eq!(map.get(&4), Some(&0));
+        assert_eq!(map.get(&6), Some(&0));
+        assert_eq!(map.floor_entry_mut(&200).map(|(key, _)| *key), Some(198));
+        assert!(map.ceil_entry_mut(&199).is_none());
+    }
+
+    #[test]
+    fn test_pop_min_max() {
+        let mut map = SplayMap::new();
+        for key in 0..1_000u32 {
+            map.insert(key * 7_919 % 1_000, key);
+        }
+
+        let (mut min, mut max) = (0, 999);
+        while min <= max {
+            let (key, _) = map.pop_min().unwrap();
+            assert_eq!(key, min);
+            min += 1;
+            let (key, _) = map.pop_max().unwrap();
+            assert_eq!(key, max);
+            max -= 1;
+            assert_eq!(map.len(), (max + 1 - min) as usize);
+            assert_eq!(map.arena.len(), map.len());
+        }
+        assert_eq!(map.pop_min(), None);
+        assert_eq!(map.pop_max(), None);
+        assert!(map.is_empty());
+    }
+
     #[test]
     fn test_into_iter() {
         let mut map = SplayMap::new();
@@ -656,4 +1269,137 @@ mod tests {
             vec![(&1, &3), (&3, &5), (&5, &7)],
         );
     }
+
+    #[test]
+    fn test_clone_eq() {
+        let mut map = SplayMap::new();
+        map.insert(1, 2);
+        map.insert(5, 6);
+        map.insert(3, 4);
+
+        let mut cloned = map.clone();
+        assert_eq!(cloned, map);
+        cloned.insert(3, 5);
+        assert_ne!(cloned, map);
+        cloned.insert(3, 4);
+        cloned.insert(7, 8);
+        assert_ne!(cloned, map);
+        cloned.remove(&7);
+        assert_eq!(cloned, map);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut map = SplayMap::new();
+        map.insert(3, 4);
+        map.insert(1, 2);
+        assert_eq!(format!("{:?}", map), "{1: 2, 3: 4}");
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut map = SplayMap::new();
+        for key in 0..10_007u32 {
+            map.insert(key * 7_919 % 10_007, key);
+        }
+
+        let is_retained = |key: &u32| !key.is_multiple_of(3) && (*key < 2_000 || *key > 6_000);
+        map.retain(|key, value| {
+            *value += 1;
+            is_retained(key)
+        });
+        let expected: Vec<u32> = (0..10_007).filter(is_retained).collect();
+        assert_eq!(map.len(), expected.len());
+        assert_eq!(
+            map.iter().map(|(key, _)| *key).collect::<Vec<u32>>(),
+            expected,
+        );
+
+        for key in expected.iter().filter(|key| *key % 2 == 0) {
+            assert!(map.remove(key).is_some());
+        }
+        map.insert(4_000, 0);
+        assert_eq!(
+            map.len(),
+            expected.iter().filter(|key| *key % 2 == 1).count() + 1
+        );
+    }
+
+    #[test]
+    fn test_splay_top() {
+        let mut map = SplayMap::new();
+        assert_eq!(map.top(), None);
+        map.insert(1, 2);
+        map.insert(5, 6);
+        map.insert(3, 4);
+        assert_eq!(map.top(), Some((&3, &4)));
+
+        assert_eq!(map.splay(&1), Some(&2));
+        assert_eq!(map.top(), Some((&1, &2)));
+        assert_eq!(map.splay(&2), None);
+        assert_eq!(map.len(), 3);
+    }
+
+    #[test]
+    fn test_get_quiet_mut() {
+        let mut map = SplayMap::new();
+        map.insert(1, 2);
+        map.insert(5, 6);
+        map.insert(3, 4);
+
+        *map.get_quiet_mut(&1).unwrap() = 3;
+        assert_eq!(map.get_quiet_mut(&2), None);
+        assert_eq!(map.top(), Some((&3, &4)));
+        assert_eq!(map.get(&1), Some(&3));
+    }
+
+    #[test]
+    fn test_iter_access_order() {
+        let mut map = SplayMap::new();
+        for key in 0..100 {
+            map.insert(key, key);
+        }
+        map.splay(&50);
+        map.splay(&10);
+
+        let mut keys: Vec<u32> = map.iter_access_order().map(|(key, _)| *key).collect();
+        assert_eq!(keys[0], 10);
+        keys.sort();
+        assert_eq!(keys, (0..100).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut map = SplayMap::new();
+        map.insert(1, 2);
+        map.insert(5, 6);
+        map.insert(3, 4);
+
+        assert_eq!(
+            map.drain().collect::<Vec<(u32, u32)>>(),
+            vec![(1, 2), (3, 4), (5, 6)],
+        );
+        assert!(map.is_empty());
+        map.insert(1, 2);
+        assert_eq!(map.get(&1), Some(&2));
+    }
+
+    #[test]
+    fn test_with_arena() {
+        let mut map = SplayMap::with_arena();
+        for key in 0..100 {
+            map.insert(key, key);
+        }
+        assert_eq!(map.remove(&50), Some((50, 50)));
+        let clone = map.clone();
+        map.clear();
+        map.insert(1, 2);
+        assert_eq!(map.get(&1), Some(&2));
+        assert_eq!(clone.len(), 99);
+        assert_eq!(clone.arena.len(), 99);
+        assert!(clone
+            .into_iter()
+            .map(|(key, _)| key)
+            .eq((0..50).chain(51..100)));
+    }
 }
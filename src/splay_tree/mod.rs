@@ -1,7 +1,7 @@
 //! Self-adjusting binary search tree with the additional property that recently accessed elements
 //! are quick to access again.
 
-mod map;
+pub(crate) mod map;
 mod node;
 mod set;
 mod tree;
@@ -1,11 +1,12 @@
 use crate::entry::Entry;
+use crate::node_arena::{Links, NodeArena, NodeRef};
 use crate::splay_tree::tree;
-use std::mem;
 
+#[derive(Clone)]
 pub struct Node<T, U> {
     pub entry: Entry<T, U>,
-    pub left: tree::Tree<T, U>,
-    pub right: tree::Tree<T, U>,
+    pub left: tree::Tree,
+    pub right: tree::Tree,
 }
 
 impl<T, U> Node<T, U> {
@@ -17,23 +18,30 @@ impl<T, U> Node<T, U> {
         }
     }
 
-    pub fn rotate_left(&mut self) {
-        let mut child = self
+    // Rotations swap the contents of the nodes so that the root of the subtree keeps its handle.
+    pub fn rotate_left(arena: &mut NodeArena<Self>, node: NodeRef) {
+        let child = arena[node]
             .right
             .take()
             .expect("Expected right child node to be `Some`.");
-        self.right = child.left.take();
-        mem::swap(&mut *child, self);
-        self.left = Some(child);
+        arena[node].right = arena[child].left.take();
+        arena.swap(&node, &child);
+        arena[node].left = Some(child);
     }
 
-    pub fn rotate_right(&mut self) {
-        let mut child = self
+    pub fn rotate_right(arena: &mut NodeArena<Self>, node: NodeRef) {
+        let child = arena[node]
             .left
             .take()
             .expect("Expected left child node to be `Some`.");
-        self.left = child.right.take();
-        mem::swap(&mut *child, self);
-        self.right = Some(child);
+        arena[node].left = arena[child].right.take();
+        arena.swap(&node, &child);
+        arena[node].right = Some(child);
+    }
+}
+
+impl<T, U> Links for Node<T, U> {
+    fn links_mut(&mut self) -> (&mut tree::Tree, &mut tree::Tree) {
+        (&mut self.left, &mut self.right)
     }
 }
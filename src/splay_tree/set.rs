@@ -1,5 +1,8 @@
+use crate::ordered::OrderedSet;
 use crate::splay_tree::map::{SplayMap, SplayMapIntoIter, SplayMapIter};
-use std::borrow::Borrow;
+use core::borrow::Borrow;
+use core::fmt;
+use core::iter::FromIterator;
 
 /// An ordered map implemented using splay tree.
 ///
@@ -24,6 +27,7 @@ use std::borrow::Borrow;
 /// assert_eq!(set.remove(&0), Some(0));
 /// assert_eq!(set.remove(&1), None);
 /// ```
+#[derive(Clone)]
 pub struct SplaySet<T> {
     map: SplayMap<T, ()>,
 }
@@ -44,6 +48,24 @@ impl<T> SplaySet<T> {
         }
     }
 
+    /// Constructs a new, empty `SplaySet<T>` that allocates its nodes from a `TypedArena`. See
+    /// `SplayMap::with_arena` for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplaySet;
+    ///
+    /// let mut set = SplaySet::with_arena();
+    /// set.insert(1);
+    /// assert!(set.contains(&1));
+    /// ```
+    pub fn with_arena() -> Self {
+        SplaySet {
+            map: SplayMap::with_arena(),
+        }
+    }
+
     /// Inserts a key into the set. If the key already exists in the set, it will return and
     /// replace the key.
     ///
@@ -77,9 +99,10 @@ impl<T> SplaySet<T> {
     /// assert_eq!(set.remove(&1), Some(1));
     /// assert_eq!(set.remove(&1), None);
     /// ```
-    pub fn remove(&mut self, key: &T) -> Option<T>
+    pub fn remove<V>(&mut self, key: &V) -> Option<T>
     where
-        T: Ord,
+        T: Borrow<V>,
+        V: Ord + ?Sized,
     {
         self.map.remove(key).map(|pair| pair.0)
     }
@@ -150,6 +173,49 @@ impl<T> SplaySet<T> {
         self.map.clear();
     }
 
+    /// Retains only the keys that satisfy a predicate. The keys are visited in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplaySet;
+    ///
+    /// let mut set = SplaySet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    /// set.insert(3);
+    ///
+    /// set.retain(|key| key % 2 == 1);
+    /// assert_eq!(set.iter().collect::<Vec<&u32>>(), vec![&1, &3]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.map.retain(|key, _| f(key));
+    }
+
+    /// Clears the set, returning all keys as an iterator. The iterator yields the keys in
+    /// ascending order, and the set can be reused afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::splay_tree::SplaySet;
+    ///
+    /// let mut set = SplaySet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    ///
+    /// assert_eq!(set.drain().collect::<Vec<u32>>(), vec![1, 2]);
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> SplaySetIntoIter<T> {
+        SplaySetIntoIter {
+            map_iter: self.map.drain(),
+        }
+    }
+
     /// Returns a key in the set that is less than or equal to a particular key. Returns `None` if
     /// such a key does not exist.
     ///
@@ -309,12 +375,109 @@ where
     }
 }
 
+impl<T> OrderedSet<T> for SplaySet<T>
+where
+    T: Ord,
+{
+    type Iter<'a>
+        = SplaySetIter<'a, T>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn insert(&mut self, key: T) -> Option<T> {
+        SplaySet::insert(self, key)
+    }
+
+    fn remove(&mut self, key: &T) -> Option<T> {
+        SplaySet::remove(self, key)
+    }
+
+    fn contains(&self, key: &T) -> bool {
+        SplaySet::contains(self, key)
+    }
+
+    fn len(&self) -> usize {
+        SplaySet::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        SplaySet::is_empty(self)
+    }
+
+    fn floor(&self, key: &T) -> Option<&T> {
+        SplaySet::floor(self, key)
+    }
+
+    fn ceil(&self, key: &T) -> Option<&T> {
+        SplaySet::ceil(self, key)
+    }
+
+    fn min(&self) -> Option<&T> {
+        SplaySet::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        SplaySet::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        SplaySet::iter(self)
+    }
+}
+
+impl<T> fmt::Debug for SplaySet<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T> PartialEq for SplaySet<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T> Eq for SplaySet<T> where T: Eq {}
+
 impl<T> Default for SplaySet<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl<T> FromIterator<T> for SplaySet<T>
+where
+    T: Ord,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        SplaySet {
+            map: iter.into_iter().map(|key| (key, ())).collect(),
+        }
+    }
+}
+
+impl<T> Extend<T> for SplaySet<T>
+where
+    T: Ord,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.map.extend(iter.into_iter().map(|key| (key, ())));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::SplaySet;
@@ -399,6 +562,13 @@ mod tests {
         assert_eq!(set.into_iter().collect::<Vec<u32>>(), vec![1, 3, 5]);
     }
 
+    #[test]
+    fn test_from_iter() {
+        let mut set: SplaySet<u32> = vec![3, 1, 2, 1].into_iter().collect();
+        set.extend(vec![5, 4]);
+        assert_eq!(set.into_iter().collect::<Vec<u32>>(), vec![1, 2, 3, 4, 5]);
+    }
+
     #[test]
     fn test_iter() {
         let mut set = SplaySet::new();
@@ -408,4 +578,55 @@ mod tests {
 
         assert_eq!(set.iter().collect::<Vec<&u32>>(), vec![&1, &3, &5]);
     }
+
+    #[test]
+    fn test_clone_eq() {
+        let mut set = SplaySet::new();
+        set.insert(1);
+        set.insert(5);
+        set.insert(3);
+
+        let mut cloned = set.clone();
+        assert_eq!(cloned, set);
+        cloned.insert(7);
+        assert_ne!(cloned, set);
+        cloned.remove(&7);
+        assert_eq!(cloned, set);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut set = SplaySet::new();
+        set.insert(3);
+        set.insert(1);
+        assert_eq!(format!("{:?}", set), "{1, 3}");
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut set = SplaySet::new();
+        for key in 0..100 {
+            set.insert(key);
+        }
+
+        set.retain(|key| key % 3 == 0);
+        assert_eq!(set.len(), 34);
+        assert_eq!(
+            set.into_iter().collect::<Vec<u32>>(),
+            (0..100).filter(|key| key % 3 == 0).collect::<Vec<u32>>(),
+        );
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut set = SplaySet::new();
+        set.insert(1);
+        set.insert(5);
+        set.insert(3);
+
+        assert_eq!(set.drain().collect::<Vec<u32>>(), vec![1, 3, 5]);
+        assert!(set.is_empty());
+        set.insert(1);
+        assert!(set.contains(&1));
+    }
 }
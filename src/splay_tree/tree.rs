@@ -1,129 +1,152 @@
 use crate::entry::Entry;
+use crate::node_arena::{NodeArena, NodeRef};
 use crate::splay_tree::node::Node;
-use std::borrow::Borrow;
-use std::cmp::Ordering;
-use std::mem;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::mem;
 
-pub type Tree<T, U> = Option<Box<Node<T, U>>>;
+pub type Tree = Option<NodeRef>;
 
-fn splay<T, U, V>(node: &mut Box<Node<T, U>>, key: &V)
+pub type Arena<T, U> = NodeArena<Node<T, U>>;
+
+fn splay<T, U, V>(arena: &mut Arena<T, U>, node: NodeRef, key: &V)
 where
     T: Borrow<V>,
     V: Ord + ?Sized,
 {
-    let mut left_subtree: Tree<T, U> = None;
-    let mut right_subtree: Tree<T, U> = None;
-    {
-        let mut left = &mut right_subtree;
-        let mut right = &mut left_subtree;
-        loop {
-            match key.cmp(node.entry.key.borrow()) {
-                Ordering::Less => {
-                    let should_rotate = match &mut node.left {
-                        Some(ref mut child) => key.cmp(child.entry.key.borrow()) == Ordering::Less,
-                        None => break,
-                    };
-                    if should_rotate {
-                        node.rotate_right();
-                    }
-
-                    let child = match node.left.take() {
-                        Some(child) => child,
-                        None => break,
-                    };
-                    *right = Some(mem::replace(node, child));
-                    right = &mut { right }
-                        .as_mut()
-                        .expect("Expected non-empty left child")
-                        .left;
+    splay_by(arena, node, |other| key.cmp(other.borrow()));
+}
+
+// Top-down splay that keeps the handle of the root of the tree. `compare` returns the ordering of
+// the target key relative to a key in the tree. Nodes with keys less than the target key are
+// collected into `lesser` and nodes with keys greater than the target key are collected into
+// `greater`.
+fn splay_by<T, U, F>(arena: &mut Arena<T, U>, node: NodeRef, mut compare: F)
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let mut lesser: Tree = None;
+    let mut greater: Tree = None;
+    let mut lesser_max: Tree = None;
+    let mut greater_min: Tree = None;
+    loop {
+        match compare(&arena[node].entry.key) {
+            Ordering::Less => {
+                let should_rotate = match arena[node].left {
+                    Some(child) => compare(&arena[child].entry.key) == Ordering::Less,
+                    None => break,
+                };
+                if should_rotate {
+                    Node::rotate_right(arena, node);
                 }
-                Ordering::Greater => {
-                    let should_rotate = match &mut node.right {
-                        Some(ref mut child) => {
-                            key.cmp(child.entry.key.borrow()) == Ordering::Greater
-                        }
-                        None => break,
-                    };
-                    if should_rotate {
-                        node.rotate_left();
-                    }
-
-                    let child = match node.right.take() {
-                        Some(child) => child,
-                        None => break,
-                    };
-                    *left = Some(mem::replace(node, child));
-                    left = &mut { left }
-                        .as_mut()
-                        .expect("Expected non-empty right child")
-                        .right;
+
+                let child = match arena[node].left.take() {
+                    Some(child) => child,
+                    None => break,
+                };
+                arena.swap(&node, &child);
+                match greater_min {
+                    Some(parent) => arena[parent].left = Some(child),
+                    None => greater = Some(child),
                 }
-                Ordering::Equal => break,
+                greater_min = Some(child);
             }
+            Ordering::Greater => {
+                let should_rotate = match arena[node].right {
+                    Some(child) => compare(&arena[child].entry.key) == Ordering::Greater,
+                    None => break,
+                };
+                if should_rotate {
+                    Node::rotate_left(arena, node);
+                }
+
+                let child = match arena[node].right.take() {
+                    Some(child) => child,
+                    None => break,
+                };
+                arena.swap(&node, &child);
+                match lesser_max {
+                    Some(parent) => arena[parent].right = Some(child),
+                    None => lesser = Some(child),
+                }
+                lesser_max = Some(child);
+            }
+            Ordering::Equal => break,
         }
+    }
 
-        mem::swap(left, &mut node.left);
-        mem::swap(right, &mut node.right);
+    let left = arena[node].left.take();
+    match lesser_max {
+        Some(parent) => arena[parent].right = left,
+        None => lesser = left,
+    }
+    let right = arena[node].right.take();
+    match greater_min {
+        Some(parent) => arena[parent].left = right,
+        None => greater = right,
     }
 
-    node.left = right_subtree;
-    node.right = left_subtree;
+    arena[node].left = lesser;
+    arena[node].right = greater;
 }
 
-pub fn insert<T, U>(tree: &mut Tree<T, U>, mut new_node: Node<T, U>) -> Option<Entry<T, U>>
+pub fn insert<T, U>(
+    arena: &mut Arena<T, U>,
+    tree: &mut Tree,
+    mut new_node: Node<T, U>,
+) -> Option<Entry<T, U>>
 where
     T: Ord,
 {
-    match tree {
-        Some(ref mut node) => {
-            splay(node, &new_node.entry.key);
-            match new_node.entry.key.cmp(&node.entry.key) {
+    match *tree {
+        Some(node) => {
+            splay(arena, node, &new_node.entry.key);
+            match new_node.entry.key.cmp(&arena[node].entry.key) {
                 Ordering::Less => {
-                    new_node.left = node.left.take();
-                    mem::swap(&mut **node, &mut new_node);
-                    node.right = Some(Box::new(new_node));
+                    new_node.left = arena[node].left.take();
+                    let child = arena.allocate(new_node);
+                    arena.swap(&node, &child);
+                    arena[node].right = Some(child);
                     None
                 }
                 Ordering::Greater => {
-                    new_node.right = node.right.take();
-                    mem::swap(&mut **node, &mut new_node);
-                    node.left = Some(Box::new(new_node));
+                    new_node.right = arena[node].right.take();
+                    let child = arena.allocate(new_node);
+                    arena.swap(&node, &child);
+                    arena[node].left = Some(child);
                     None
                 }
                 Ordering::Equal => {
-                    let ret = mem::replace(&mut node.entry, new_node.entry);
+                    let ret = mem::replace(&mut arena[node].entry, new_node.entry);
                     Some(ret)
                 }
             }
         }
         None => {
-            *tree = Some(Box::new(new_node));
+            *tree = Some(arena.allocate(new_node));
             None
         }
     }
 }
 
-pub fn remove<T, U, V>(tree: &mut Tree<T, U>, key: &V) -> Option<Entry<T, U>>
+pub fn remove<T, U, V>(arena: &mut Arena<T, U>, tree: &mut Tree, key: &V) -> Option<Entry<T, U>>
 where
     T: Borrow<V>,
     V: Ord + ?Sized,
 {
-    match tree {
-        Some(ref mut node) => {
-            splay(node, key);
-            if key != node.entry.key.borrow() {
-                return None;
-            }
-        }
-        None => return None,
-    };
+    let node = (*tree)?;
+    splay(arena, node, key);
+    if key != arena[node].entry.key.borrow() {
+        return None;
+    }
 
-    let unboxed_node = *tree.take().expect("Expected non-empty tree.");
-    let Node { left, right, entry } = unboxed_node;
+    let Node { left, right, entry } = arena.free(&node);
     *tree = match left {
-        Some(mut left_child) => {
-            splay(&mut left_child, key);
-            left_child.right = right;
+        Some(left_child) => {
+            splay(arena, left_child, key);
+            arena[left_child].right = right;
             Some(left_child)
         }
         None => right,
@@ -131,87 +154,248 @@ where
     Some(entry)
 }
 
-pub fn get<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V) -> Option<&'a Entry<T, U>>
+pub fn pop_min<T, U>(arena: &mut Arena<T, U>, tree: &mut Tree) -> Option<Entry<T, U>> {
+    let node = (*tree)?;
+    splay_by(arena, node, |_| Ordering::Less);
+    let Node { right, entry, .. } = arena.free(&node);
+    *tree = right;
+    Some(entry)
+}
+
+pub fn pop_max<T, U>(arena: &mut Arena<T, U>, tree: &mut Tree) -> Option<Entry<T, U>> {
+    let node = (*tree)?;
+    splay_by(arena, node, |_| Ordering::Greater);
+    let Node { left, entry, .. } = arena.free(&node);
+    *tree = left;
+    Some(entry)
+}
+
+fn find<T, U, V>(arena: &Arena<T, U>, tree: Tree, key: &V) -> Tree
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    let mut curr = tree;
+    while let Some(node) = curr {
+        match key.cmp(arena[node].entry.key.borrow()) {
+            Ordering::Less => curr = arena[node].left,
+            Ordering::Greater => curr = arena[node].right,
+            Ordering::Equal => break,
+        }
+    }
+    curr
+}
+
+pub fn get<'a, T, U, V>(arena: &'a Arena<T, U>, tree: Tree, key: &V) -> Option<&'a Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    find(arena, tree, key).map(|node| &arena[node].entry)
+}
+
+pub fn get_quiet_mut<'a, T, U, V>(
+    arena: &'a mut Arena<T, U>,
+    tree: Tree,
+    key: &V,
+) -> Option<&'a mut Entry<T, U>>
 where
     T: Borrow<V>,
     V: Ord + ?Sized,
 {
-    tree.as_ref()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Less => get(&node.left, key),
-            Ordering::Greater => get(&node.right, key),
-            Ordering::Equal => Some(&node.entry),
-        })
+    find(arena, tree, key).map(move |node| &mut arena[node].entry)
 }
 
-pub fn get_mut<'a, T, U, V>(tree: &'a mut Tree<T, U>, key: &V) -> Option<&'a mut Entry<T, U>>
+pub fn get_mut<'a, T, U, V>(
+    arena: &'a mut Arena<T, U>,
+    tree: Tree,
+    key: &V,
+) -> Option<&'a mut Entry<T, U>>
 where
     T: Borrow<V>,
     V: Ord + ?Sized,
 {
-    if let Some(ref mut node) = tree {
-        splay(node, key);
-        if node.entry.key.borrow() == key {
-            return Some(&mut node.entry);
+    if let Some(node) = tree {
+        splay(arena, node, key);
+        if arena[node].entry.key.borrow() == key {
+            return Some(&mut arena[node].entry);
         }
     }
     None
 }
 
-pub fn ceil<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V) -> Option<&'a Entry<T, U>>
+// Returns the node with the least key that is greater than a particular key, or equal to it if
+// `inclusive` is true.
+fn find_ceil<T, U, V>(arena: &Arena<T, U>, tree: Tree, key: &V, inclusive: bool) -> Tree
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    let mut curr = tree;
+    let mut ret = None;
+    while let Some(node) = curr {
+        match key.cmp(arena[node].entry.key.borrow()) {
+            Ordering::Less => {
+                ret = curr;
+                curr = arena[node].left;
+            }
+            Ordering::Equal if inclusive => return curr,
+            _ => curr = arena[node].right,
+        }
+    }
+    ret
+}
+
+// Returns the node with the greatest key that is less than a particular key, or equal to it if
+// `inclusive` is true.
+fn find_floor<T, U, V>(arena: &Arena<T, U>, tree: Tree, key: &V, inclusive: bool) -> Tree
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    let mut curr = tree;
+    let mut ret = None;
+    while let Some(node) = curr {
+        match key.cmp(arena[node].entry.key.borrow()) {
+            Ordering::Greater => {
+                ret = curr;
+                curr = arena[node].right;
+            }
+            Ordering::Equal if inclusive => return curr,
+            _ => curr = arena[node].left,
+        }
+    }
+    ret
+}
+
+pub fn ceil<'a, T, U, V>(
+    arena: &'a Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+) -> Option<&'a Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    find_ceil(arena, tree, key, inclusive).map(|node| &arena[node].entry)
+}
+
+pub fn ceil_mut<'a, T, U, V>(
+    arena: &'a mut Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+) -> Option<&'a mut Entry<T, U>>
 where
     T: Borrow<V>,
     V: Ord + ?Sized,
 {
-    tree.as_ref()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Greater => ceil(&node.right, key),
-            Ordering::Less => match ceil(&node.left, key) {
-                None => Some(&node.entry),
-                res => res,
-            },
-            Ordering::Equal => Some(&node.entry),
-        })
+    find_ceil(arena, tree, key, inclusive).map(move |node| &mut arena[node].entry)
 }
 
-pub fn floor<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V) -> Option<&'a Entry<T, U>>
+pub fn floor<'a, T, U, V>(
+    arena: &'a Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+) -> Option<&'a Entry<T, U>>
 where
     T: Borrow<V>,
     V: Ord + ?Sized,
 {
-    tree.as_ref()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Less => floor(&node.left, key),
-            Ordering::Greater => match floor(&node.right, key) {
-                None => Some(&node.entry),
-                res => res,
-            },
-            Ordering::Equal => Some(&node.entry),
-        })
+    find_floor(arena, tree, key, inclusive).map(|node| &arena[node].entry)
 }
 
-pub fn min<T, U>(tree: &Tree<T, U>) -> Option<&Entry<T, U>>
+pub fn floor_mut<'a, T, U, V>(
+    arena: &'a mut Arena<T, U>,
+    tree: Tree,
+    key: &V,
+    inclusive: bool,
+) -> Option<&'a mut Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    find_floor(arena, tree, key, inclusive).map(move |node| &mut arena[node].entry)
+}
+
+pub fn min<T, U>(arena: &Arena<T, U>, tree: Tree) -> Option<&Entry<T, U>>
 where
     T: Ord,
 {
-    tree.as_ref().and_then(|node| {
-        let mut curr = node;
-        while let Some(ref left_node) = curr.left {
-            curr = left_node;
+    tree.map(|node| {
+        let mut curr = &arena[node];
+        while let Some(left_node) = curr.left {
+            curr = &arena[left_node];
         }
-        Some(&curr.entry)
+        &curr.entry
     })
 }
 
-pub fn max<T, U>(tree: &Tree<T, U>) -> Option<&Entry<T, U>>
+pub fn max<T, U>(arena: &Arena<T, U>, tree: Tree) -> Option<&Entry<T, U>>
 where
     T: Ord,
 {
-    tree.as_ref().and_then(|node| {
-        let mut curr = node;
-        while let Some(ref right_node) = curr.right {
-            curr = right_node;
+    tree.map(|node| {
+        let mut curr = &arena[node];
+        while let Some(right_node) = curr.right {
+            curr = &arena[right_node];
         }
-        Some(&curr.entry)
+        &curr.entry
     })
 }
+
+fn build<T, U>(arena: &mut Arena<T, U>, nodes: &mut vec::IntoIter<NodeRef>, len: usize) -> Tree {
+    if len == 0 {
+        return None;
+    }
+
+    let left_len = len / 2;
+    let left = build(arena, nodes, left_len);
+    let node = nodes.next().expect("Expected a non-empty iterator.");
+    let right = build(arena, nodes, len - left_len - 1);
+    arena[node].left = left;
+    arena[node].right = right;
+    Some(node)
+}
+
+// Removes all entries whose key-value pairs do not satisfy `f`. The entries are visited in
+// ascending order. A splay tree does not maintain any balance invariant, so the retained nodes are
+// relinked into a balanced tree. The traversal is iterative because the tree may be arbitrarily
+// deep.
+pub fn retain<T, U, F>(arena: &mut Arena<T, U>, tree: Tree, f: &mut F) -> Tree
+where
+    F: FnMut(&T, &mut U) -> bool,
+{
+    let mut nodes = Vec::new();
+    let mut stack = Vec::new();
+    let mut curr = tree;
+    loop {
+        while let Some(node) = curr {
+            curr = arena[node].left.take();
+            stack.push(node);
+        }
+        match stack.pop() {
+            Some(node) => {
+                curr = arena[node].right.take();
+                let keep = {
+                    let Entry {
+                        ref key,
+                        ref mut value,
+                    } = arena[node].entry;
+                    f(key, value)
+                };
+                if keep {
+                    nodes.push(node);
+                } else {
+                    arena.free(&node);
+                }
+            }
+            None => break,
+        }
+    }
+
+    let len = nodes.len();
+    build(arena, &mut nodes.into_iter(), len)
+}
@@ -0,0 +1,166 @@
+use crate::entry::Entry;
+use crate::static_search_tree::StaticSearchTree;
+
+/// A builder that constructs a `StaticSearchTree<T, U>` from key-value pairs in ascending order.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+///
+/// let mut builder = StaticSearchTreeBuilder::new();
+/// builder.append(1, 1);
+/// builder.append(2, 2);
+/// assert_eq!(builder.len(), 2);
+///
+/// let tree = builder.build();
+/// assert_eq!(tree.get(&2), Some(&2));
+/// ```
+pub struct StaticSearchTreeBuilder<T, U> {
+    entries: Vec<Entry<T, U>>,
+}
+
+impl<T, U> StaticSearchTreeBuilder<T, U>
+where
+    T: Ord,
+{
+    /// Constructs a new, empty `StaticSearchTreeBuilder<T, U>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+    ///
+    /// let builder: StaticSearchTreeBuilder<u32, u32> = StaticSearchTreeBuilder::new();
+    /// ```
+    pub fn new() -> Self {
+        StaticSearchTreeBuilder {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Appends a key-value pair to the builder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not strictly greater than the previously appended key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+    ///
+    /// let mut builder = StaticSearchTreeBuilder::new();
+    /// builder.append(1, 1);
+    /// builder.append(2, 2);
+    /// ```
+    pub fn append(&mut self, key: T, value: U) {
+        if let Some(entry) = self.entries.last() {
+            assert!(
+                entry.key < key,
+                "Expected keys to be appended in strictly ascending order."
+            );
+        }
+        self.entries.push(Entry { key, value });
+    }
+
+    /// Returns the number of key-value pairs appended to the builder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+    ///
+    /// let mut builder = StaticSearchTreeBuilder::new();
+    /// builder.append(1, 1);
+    /// assert_eq!(builder.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no key-value pairs have been appended to the builder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+    ///
+    /// let builder: StaticSearchTreeBuilder<u32, u32> = StaticSearchTreeBuilder::new();
+    /// assert!(builder.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Consumes the builder and constructs a `StaticSearchTree<T, U>` containing all appended
+    /// key-value pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+    ///
+    /// let mut builder = StaticSearchTreeBuilder::new();
+    /// builder.append(1, 1);
+    /// let tree = builder.build();
+    /// assert_eq!(tree.get(&1), Some(&1));
+    /// ```
+    pub fn build(self) -> StaticSearchTree<T, U> {
+        StaticSearchTree::from_sorted_entries(self.entries)
+    }
+}
+
+impl<T, U> Default for StaticSearchTreeBuilder<T, U>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StaticSearchTreeBuilder;
+    use std::fs;
+
+    #[test]
+    #[should_panic]
+    fn test_append_out_of_order() {
+        let mut builder = StaticSearchTreeBuilder::new();
+        builder.append(2, 2);
+        builder.append(1, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_append_duplicate() {
+        let mut builder = StaticSearchTreeBuilder::new();
+        builder.append(1, 1);
+        builder.append(1, 1);
+    }
+
+    #[test]
+    fn test_save_open() {
+        let mut builder = StaticSearchTreeBuilder::new();
+        for key in 0..1000u32 {
+            builder.append(key, key * 2);
+        }
+        let tree = builder.build();
+        tree.save("test_static_search_tree_save_open").unwrap();
+
+        let tree: super::StaticSearchTree<u32, u32> =
+            super::StaticSearchTree::open("test_static_search_tree_save_open").unwrap();
+        fs::remove_file("test_static_search_tree_save_open").unwrap();
+
+        assert_eq!(tree.len(), 1000);
+        for key in 0..1000 {
+            assert_eq!(tree.get(&key), Some(&(key * 2)));
+        }
+        assert_eq!(
+            tree.iter().map(|entry| *entry.0).collect::<Vec<u32>>(),
+            (0..1000).collect::<Vec<u32>>(),
+        );
+    }
+}
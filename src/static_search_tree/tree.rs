@@ -0,0 +1,529 @@
+use crate::entry::Entry;
+use crate::static_search_tree::Result;
+use bincode::{deserialize, serialize};
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use serde_derive::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fs;
+use std::ops::{Bound, RangeBounds};
+use std::path::Path;
+
+fn get_height(len: usize) -> usize {
+    64 - (len as u64).leading_zeros() as usize
+}
+
+fn get_depth(index: usize) -> usize {
+    63 - (index as u64).leading_zeros() as usize
+}
+
+// Converts the one-indexed breadth-first index of a node in a complete tree of a particular height
+// to its position in the van Emde Boas layout of the tree.
+fn get_position(index: usize, height: usize) -> usize {
+    if height == 1 {
+        return 0;
+    }
+
+    let top_height = height / 2;
+    let bottom_height = height - top_height;
+    let depth = get_depth(index);
+    if depth < top_height {
+        return get_position(index, top_height);
+    }
+
+    let subtree_depth = depth - top_height;
+    let subtree_root = index >> subtree_depth;
+    let subtree_index = subtree_root - (1 << top_height);
+    let subtree_local_index = (1 << subtree_depth) | (index & ((1 << subtree_depth) - 1));
+    (1 << top_height) - 1
+        + subtree_index * ((1 << bottom_height) - 1)
+        + get_position(subtree_local_index, bottom_height)
+}
+
+// Converts the one-indexed in-order rank of a node to its one-indexed breadth-first index.
+fn get_index(rank: usize, height: usize) -> usize {
+    let trailing_zeros = rank.trailing_zeros() as usize;
+    let depth = height - 1 - trailing_zeros;
+    (1 << depth) | (rank >> (trailing_zeros + 1))
+}
+
+// Converts the one-indexed breadth-first index of a node to its one-indexed in-order rank.
+fn get_rank(index: usize, height: usize) -> usize {
+    let depth = get_depth(index);
+    let trailing_zeros = height - 1 - depth;
+    ((index - (1 << depth)) << (trailing_zeros + 1)) | (1 << trailing_zeros)
+}
+
+/// An immutable ordered map implemented using a static search tree in van Emde Boas layout.
+///
+/// The entries are stored in a complete binary search tree. The tree is recursively split at half
+/// its height into a top subtree and bottom subtrees, and each subtree is stored contiguously. A
+/// lookup touches `O(log_B n)` blocks of memory for any block size `B` without knowing `B`, so the
+/// tree is cache efficient at every level of the memory hierarchy. The tree is constructed using
+/// a `StaticSearchTreeBuilder<T, U>` and can be saved to and opened from a single file.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+///
+/// let mut builder = StaticSearchTreeBuilder::new();
+/// builder.append(1, 2);
+/// builder.append(3, 4);
+/// builder.append(5, 6);
+/// let tree = builder.build();
+///
+/// assert_eq!(tree.get(&1), Some(&2));
+/// assert_eq!(tree.get(&2), None);
+/// assert_eq!(tree.len(), 3);
+///
+/// assert_eq!(
+///     tree.range(2..).collect::<Vec<(&u32, &u32)>>(),
+///     vec![(&3, &4), (&5, &6)],
+/// );
+/// ```
+#[derive(Serialize, Deserialize)]
+pub struct StaticSearchTree<T, U> {
+    nodes: Vec<Option<Entry<T, U>>>,
+    len: usize,
+    height: usize,
+}
+
+impl<T, U> StaticSearchTree<T, U> {
+    pub(crate) fn from_sorted_entries(entries: Vec<Entry<T, U>>) -> Self {
+        let len = entries.len();
+        let height = get_height(len);
+        let mut nodes: Vec<Option<Entry<T, U>>> = (0..(1 << height) - 1).map(|_| None).collect();
+        for (index, entry) in entries.into_iter().enumerate() {
+            nodes[get_position(get_index(index + 1, height), height)] = Some(entry);
+        }
+        StaticSearchTree { nodes, len, height }
+    }
+
+    fn get_entry(&self, rank: usize) -> Option<&Entry<T, U>> {
+        self.nodes[get_position(get_index(rank, self.height), self.height)].as_ref()
+    }
+
+    // Returns the rank of the first entry whose key satisfies `is_after`, or `len + 1` if there is
+    // no such entry. `is_after` must be monotonic over the keys in ascending order.
+    fn lower_bound<F>(&self, is_after: F) -> usize
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut ret = self.len + 1;
+        let mut index = 1;
+        while index < (1 << self.height) {
+            match self.nodes[get_position(index, self.height)] {
+                Some(ref entry) if !is_after(&entry.key) => index = 2 * index + 1,
+                Some(_) => {
+                    ret = get_rank(index, self.height);
+                    index *= 2;
+                }
+                None => index *= 2,
+            }
+        }
+        ret
+    }
+
+    /// Opens an existing `StaticSearchTree<T, U>` from a file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use extended_collections::static_search_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// use extended_collections::static_search_tree::StaticSearchTree;
+    ///
+    /// let tree: StaticSearchTree<u32, u32> =
+    ///     StaticSearchTree::open("example_static_search_tree_open")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn open<P>(file_path: P) -> Result<Self>
+    where
+        T: DeserializeOwned,
+        U: DeserializeOwned,
+        P: AsRef<Path>,
+    {
+        let buffer = fs::read(file_path)?;
+        Ok(deserialize(&buffer)?)
+    }
+
+    /// Saves the tree to a file, overwriting the file if it exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use extended_collections::static_search_tree::Result;
+    /// # fn foo() -> Result<()> {
+    /// # use std::fs;
+    /// use extended_collections::static_search_tree::{StaticSearchTree, StaticSearchTreeBuilder};
+    ///
+    /// let mut builder = StaticSearchTreeBuilder::new();
+    /// builder.append(1, 1);
+    /// builder.build().save("example_static_search_tree_save")?;
+    ///
+    /// let tree: StaticSearchTree<u32, u32> =
+    ///     StaticSearchTree::open("example_static_search_tree_save")?;
+    /// assert_eq!(tree.get(&1), Some(&1));
+    /// # fs::remove_file("example_static_search_tree_save")?;
+    /// # Ok(())
+    /// # }
+    /// # foo().unwrap();
+    /// ```
+    pub fn save<P>(&self, file_path: P) -> Result<()>
+    where
+        T: Serialize,
+        U: Serialize,
+        P: AsRef<Path>,
+    {
+        fs::write(file_path, &serialize(self)?)?;
+        Ok(())
+    }
+
+    /// Checks if a key exists in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+    ///
+    /// let mut builder = StaticSearchTreeBuilder::new();
+    /// builder.append(1, 1);
+    /// let tree = builder.build();
+    /// assert!(!tree.contains_key(&0));
+    /// assert!(tree.contains_key(&1));
+    /// ```
+    pub fn contains_key<V>(&self, key: &V) -> bool
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Returns an immutable reference to the value associated with a particular key. It will
+    /// return `None` if the key does not exist in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+    ///
+    /// let mut builder = StaticSearchTreeBuilder::new();
+    /// builder.append(1, 1);
+    /// let tree = builder.build();
+    /// assert_eq!(tree.get(&0), None);
+    /// assert_eq!(tree.get(&1), Some(&1));
+    /// ```
+    pub fn get<V>(&self, key: &V) -> Option<&U>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        let mut index = 1;
+        while index < (1 << self.height) {
+            match self.nodes[get_position(index, self.height)] {
+                Some(ref entry) => match key.cmp(entry.key.borrow()) {
+                    Ordering::Less => index *= 2,
+                    Ordering::Greater => index = 2 * index + 1,
+                    Ordering::Equal => return Some(&entry.value),
+                },
+                None => index *= 2,
+            }
+        }
+        None
+    }
+
+    /// Returns the number of elements in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+    ///
+    /// let mut builder = StaticSearchTreeBuilder::new();
+    /// builder.append(1, 1);
+    /// assert_eq!(builder.build().len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+    ///
+    /// let builder: StaticSearchTreeBuilder<u32, u32> = StaticSearchTreeBuilder::new();
+    /// assert!(builder.build().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the minimum key of the tree. Returns `None` if the tree is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+    ///
+    /// let mut builder = StaticSearchTreeBuilder::new();
+    /// builder.append(1, 1);
+    /// builder.append(3, 3);
+    /// assert_eq!(builder.build().min(), Some(&1));
+    /// ```
+    pub fn min(&self) -> Option<&T> {
+        if self.is_empty() {
+            None
+        } else {
+            self.get_entry(1).map(|entry| &entry.key)
+        }
+    }
+
+    /// Returns the maximum key of the tree. Returns `None` if the tree is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+    ///
+    /// let mut builder = StaticSearchTreeBuilder::new();
+    /// builder.append(1, 1);
+    /// builder.append(3, 3);
+    /// assert_eq!(builder.build().max(), Some(&3));
+    /// ```
+    pub fn max(&self) -> Option<&T> {
+        if self.is_empty() {
+            None
+        } else {
+            self.get_entry(self.len).map(|entry| &entry.key)
+        }
+    }
+
+    /// Returns an iterator over the entries of the tree whose keys are in a particular range. The
+    /// iterator will yield key-value pairs in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+    ///
+    /// let mut builder = StaticSearchTreeBuilder::new();
+    /// builder.append(1, 1);
+    /// builder.append(2, 2);
+    /// builder.append(3, 3);
+    /// let tree = builder.build();
+    ///
+    /// let mut iterator = tree.range(2..=3);
+    /// assert_eq!(iterator.next(), Some((&2, &2)));
+    /// assert_eq!(iterator.next(), Some((&3, &3)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn range<V, R>(&self, range: R) -> StaticSearchTreeRange<'_, T, U>
+    where
+        T: Borrow<V>,
+        R: RangeBounds<V>,
+        V: Ord + ?Sized,
+    {
+        let start_rank = match range.start_bound() {
+            Bound::Included(start) => self.lower_bound(|key| key.borrow() >= start),
+            Bound::Excluded(start) => self.lower_bound(|key| key.borrow() > start),
+            Bound::Unbounded => 1,
+        };
+        let end_rank = match range.end_bound() {
+            Bound::Included(end) => self.lower_bound(|key| key.borrow() > end),
+            Bound::Excluded(end) => self.lower_bound(|key| key.borrow() >= end),
+            Bound::Unbounded => self.len + 1,
+        };
+        StaticSearchTreeRange {
+            tree: self,
+            start_rank,
+            end_rank,
+        }
+    }
+
+    /// Returns an iterator over the tree. The iterator will yield key-value pairs in ascending
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::static_search_tree::StaticSearchTreeBuilder;
+    ///
+    /// let mut builder = StaticSearchTreeBuilder::new();
+    /// builder.append(1, 1);
+    /// builder.append(2, 2);
+    /// let tree = builder.build();
+    ///
+    /// let mut iterator = tree.iter();
+    /// assert_eq!(iterator.next(), Some((&1, &1)));
+    /// assert_eq!(iterator.next(), Some((&2, &2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> StaticSearchTreeRange<'_, T, U> {
+        StaticSearchTreeRange {
+            tree: self,
+            start_rank: 1,
+            end_rank: self.len + 1,
+        }
+    }
+}
+
+impl<'a, T, U> IntoIterator for &'a StaticSearchTree<T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type IntoIter = StaticSearchTreeRange<'a, T, U>;
+    type Item = (&'a T, &'a U);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over a range of entries of a `StaticSearchTree<T, U>`.
+///
+/// This iterator traverses the elements of the tree in ascending order and yields immutable
+/// references.
+pub struct StaticSearchTreeRange<'a, T, U> {
+    tree: &'a StaticSearchTree<T, U>,
+    start_rank: usize,
+    end_rank: usize,
+}
+
+impl<'a, T, U> Iterator for StaticSearchTreeRange<'a, T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type Item = (&'a T, &'a U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start_rank >= self.end_rank {
+            return None;
+        }
+        self.start_rank += 1;
+        self.tree
+            .get_entry(self.start_rank - 1)
+            .map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+impl<'a, T, U> DoubleEndedIterator for StaticSearchTreeRange<'a, T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start_rank >= self.end_rank {
+            return None;
+        }
+        self.end_rank -= 1;
+        self.tree
+            .get_entry(self.end_rank)
+            .map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_index, get_position, get_rank};
+    use crate::static_search_tree::StaticSearchTreeBuilder;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_layout_is_permutation() {
+        for height in 1..10 {
+            let size = (1 << height) - 1;
+            let positions: HashSet<usize> = (1..=size)
+                .map(|index| get_position(index, height))
+                .collect();
+            assert_eq!(positions.len(), size);
+            assert!(positions.iter().all(|position| *position < size));
+        }
+    }
+
+    #[test]
+    fn test_layout_subtrees_contiguous() {
+        // a tree of height 4 is split into a top tree of height 2 and four bottom trees of
+        // height 2
+        assert_eq!(get_position(1, 4), 0);
+        assert_eq!(get_position(2, 4), 1);
+        assert_eq!(get_position(3, 4), 2);
+        assert_eq!(get_position(4, 4), 3);
+        assert_eq!(get_position(8, 4), 4);
+        assert_eq!(get_position(9, 4), 5);
+        assert_eq!(get_position(5, 4), 6);
+        assert_eq!(get_position(15, 4), 14);
+    }
+
+    #[test]
+    fn test_rank_index_round_trip() {
+        for height in 1..10 {
+            for rank in 1..(1 << height) {
+                assert_eq!(get_rank(get_index(rank, height), height), rank);
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let builder: StaticSearchTreeBuilder<u32, u32> = StaticSearchTreeBuilder::new();
+        let tree = builder.build();
+        assert!(tree.is_empty());
+        assert_eq!(tree.get(&0), None);
+        assert_eq!(tree.min(), None);
+        assert_eq!(tree.max(), None);
+        assert_eq!(tree.iter().next(), None);
+        assert_eq!(tree.range(1..2).next(), None);
+    }
+
+    #[test]
+    fn test_get() {
+        for len in 0..100u32 {
+            let mut builder = StaticSearchTreeBuilder::new();
+            for key in 0..len {
+                builder.append(key * 2, key);
+            }
+            let tree = builder.build();
+            for key in 0..len {
+                assert_eq!(tree.get(&(key * 2)), Some(&key));
+                assert_eq!(tree.get(&(key * 2 + 1)), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_range() {
+        let mut builder = StaticSearchTreeBuilder::new();
+        for key in 0..50u32 {
+            builder.append(key * 2, key);
+        }
+        let tree = builder.build();
+        let keys: Vec<u32> = (0..50).map(|key| key * 2).collect();
+
+        for start in 0..101 {
+            for end in start..101 {
+                let expected: Vec<&u32> = keys
+                    .iter()
+                    .filter(|key| start <= **key && **key < end)
+                    .collect();
+                let actual: Vec<&u32> = tree.range(start..end).map(|entry| entry.0).collect();
+                assert_eq!(actual, expected);
+            }
+        }
+
+        assert_eq!(tree.range(..=4).count(), 3);
+        assert_eq!(tree.range(96..).count(), 2);
+        assert_eq!(tree.iter().next_back(), Some((&98, &49)));
+        assert_eq!(tree.min(), Some(&0));
+        assert_eq!(tree.max(), Some(&98));
+    }
+}
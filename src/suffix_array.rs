@@ -0,0 +1,469 @@
+//! Suffix array that indexes a text for fast substring queries.
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+use std::cmp;
+
+const EMPTY: usize = usize::MAX;
+
+// Returns the exclusive end of the bucket of each character in the suffix array.
+fn bucket_tails(counts: &[usize]) -> Vec<usize> {
+    counts
+        .iter()
+        .scan(0, |sum, count| {
+            *sum += count;
+            Some(*sum)
+        })
+        .collect()
+}
+
+// Returns the start of the bucket of each character in the suffix array.
+fn bucket_heads(counts: &[usize]) -> Vec<usize> {
+    counts
+        .iter()
+        .scan(0, |sum, count| {
+            let head = *sum;
+            *sum += count;
+            Some(head)
+        })
+        .collect()
+}
+
+fn is_lms(is_s: &[bool], index: usize) -> bool {
+    index > 0 && is_s[index] && !is_s[index - 1]
+}
+
+// Places the LMS suffixes at the ends of their buckets in the order given by `lms`, and then
+// induces the order of the L-type suffixes from left to right and the order of the S-type suffixes
+// from right to left.
+fn induce(text: &[usize], counts: &[usize], is_s: &[bool], lms: &[usize], sa: &mut [usize]) {
+    for suffix in sa.iter_mut() {
+        *suffix = EMPTY;
+    }
+
+    let mut tails = bucket_tails(counts);
+    for &index in lms.iter().rev() {
+        tails[text[index]] -= 1;
+        sa[tails[text[index]]] = index;
+    }
+
+    let mut heads = bucket_heads(counts);
+    for i in 0..sa.len() {
+        let index = sa[i];
+        if index != EMPTY && index > 0 && !is_s[index - 1] {
+            sa[heads[text[index - 1]]] = index - 1;
+            heads[text[index - 1]] += 1;
+        }
+    }
+
+    let mut tails = bucket_tails(counts);
+    for i in (0..sa.len()).rev() {
+        let index = sa[i];
+        if index != EMPTY && index > 0 && is_s[index - 1] {
+            tails[text[index - 1]] -= 1;
+            sa[tails[text[index - 1]]] = index - 1;
+        }
+    }
+}
+
+// Returns true if the LMS substrings starting at `left` and `right` are equal.
+fn lms_substring_eq(text: &[usize], is_s: &[bool], left: usize, right: usize) -> bool {
+    if left == text.len() - 1 || right == text.len() - 1 {
+        return left == right;
+    }
+    let mut offset = 0;
+    loop {
+        let (left_index, right_index) = (left + offset, right + offset);
+        if text[left_index] != text[right_index] || is_s[left_index] != is_s[right_index] {
+            return false;
+        }
+        let is_left_lms = is_lms(is_s, left_index);
+        let is_right_lms = is_lms(is_s, right_index);
+        if offset > 0 && (is_left_lms || is_right_lms) {
+            return is_left_lms && is_right_lms;
+        }
+        offset += 1;
+    }
+}
+
+// Constructs the suffix array of `text` using the SA-IS algorithm, assuming that every character
+// is less than `alphabet_size` and that the text ends with a unique smallest character.
+fn sais(text: &[usize], alphabet_size: usize) -> Vec<usize> {
+    let len = text.len();
+    if len == 1 {
+        return vec![0];
+    }
+
+    let mut is_s = vec![false; len];
+    is_s[len - 1] = true;
+    for i in (0..len - 1).rev() {
+        is_s[i] = text[i] < text[i + 1] || (text[i] == text[i + 1] && is_s[i + 1]);
+    }
+
+    let mut counts = vec![0; alphabet_size];
+    for &c in text {
+        counts[c] += 1;
+    }
+
+    // sorting the LMS substrings is done by inducing from the LMS suffixes in any order
+    let lms: Vec<usize> = (1..len).filter(|&index| is_lms(&is_s, index)).collect();
+    let mut sa = vec![EMPTY; len];
+    induce(text, &counts, &is_s, &lms, &mut sa);
+
+    // name the LMS substrings by their rank among the distinct LMS substrings
+    let mut names = vec![EMPTY; len];
+    let mut name = 0;
+    let mut prev = None;
+    for &index in sa.iter().filter(|&&index| is_lms(&is_s, index)) {
+        if let Some(prev) = prev {
+            if !lms_substring_eq(text, &is_s, prev, index) {
+                name += 1;
+            }
+        }
+        names[index] = name;
+        prev = Some(index);
+    }
+
+    // sort the LMS suffixes by recursing on the reduced string if the names are not unique
+    let reduced_text: Vec<usize> = lms.iter().map(|&index| names[index]).collect();
+    let reduced_sa = if name + 1 < lms.len() {
+        sais(&reduced_text, name + 1)
+    } else {
+        let mut reduced_sa = vec![0; lms.len()];
+        for (index, &name) in reduced_text.iter().enumerate() {
+            reduced_sa[name] = index;
+        }
+        reduced_sa
+    };
+
+    let sorted_lms: Vec<usize> = reduced_sa.into_iter().map(|index| lms[index]).collect();
+    induce(text, &counts, &is_s, &sorted_lms, &mut sa);
+    sa
+}
+
+/// A suffix array of a byte string.
+///
+/// A suffix array stores the starting positions of all suffixes of a text in lexicographical
+/// order. Since all suffixes that start with a particular pattern are adjacent in the suffix array,
+/// the occurrences of a pattern can be found with a binary search in `O(m log n)` time where `m`
+/// is the length of the pattern and `n` is the length of the text. The suffix array is
+/// constructed in linear time using the SA-IS algorithm. The longest common prefix (LCP) array,
+/// which stores the length of the longest common prefix of each suffix and the suffix preceding
+/// it in the suffix array, is constructed in linear time using Kasai's algorithm.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::suffix_array::SuffixArray;
+///
+/// let suffix_array = SuffixArray::new("banana");
+///
+/// assert_eq!(suffix_array.suffixes(), &[5, 3, 1, 0, 4, 2]);
+/// assert_eq!(suffix_array.lcp(), &[0, 1, 3, 0, 0, 2]);
+///
+/// assert!(suffix_array.contains(b"nan"));
+/// assert_eq!(suffix_array.count(b"ana"), 2);
+/// assert_eq!(suffix_array.longest_repeated_substring(), b"ana");
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct SuffixArray {
+    text: Vec<u8>,
+    suffixes: Vec<usize>,
+    lcp: Vec<usize>,
+}
+
+impl SuffixArray {
+    /// Constructs a new `SuffixArray` of `text`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::suffix_array::SuffixArray;
+    ///
+    /// let suffix_array = SuffixArray::new(vec![3, 1, 2]);
+    /// assert_eq!(suffix_array.suffixes(), &[1, 2, 0]);
+    /// ```
+    pub fn new<T>(text: T) -> Self
+    where
+        T: Into<Vec<u8>>,
+    {
+        let text = text.into();
+
+        // shift the bytes up by one to append a unique smallest sentinel
+        let mut shifted_text: Vec<usize> = text.iter().map(|&byte| usize::from(byte) + 1).collect();
+        shifted_text.push(0);
+        let mut suffixes = sais(&shifted_text, 257);
+        suffixes.remove(0);
+
+        let mut ranks = vec![0; text.len()];
+        for (rank, &suffix) in suffixes.iter().enumerate() {
+            ranks[suffix] = rank;
+        }
+
+        // the longest common prefix decreases by at most one when moving to the next suffix in
+        // the text
+        let mut lcp = vec![0; text.len()];
+        let mut len = 0;
+        for (suffix, &rank) in ranks.iter().enumerate() {
+            if rank == 0 {
+                len = 0;
+                continue;
+            }
+            let prev_suffix = suffixes[rank - 1];
+            while suffix + len < text.len()
+                && prev_suffix + len < text.len()
+                && text[suffix + len] == text[prev_suffix + len]
+            {
+                len += 1;
+            }
+            lcp[rank] = len;
+            len = len.saturating_sub(1);
+        }
+
+        SuffixArray {
+            text,
+            suffixes,
+            lcp,
+        }
+    }
+
+    /// Returns the indexed text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::suffix_array::SuffixArray;
+    ///
+    /// let suffix_array = SuffixArray::new("banana");
+    /// assert_eq!(suffix_array.text(), b"banana");
+    /// ```
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// Returns the length of the indexed text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::suffix_array::SuffixArray;
+    ///
+    /// let suffix_array = SuffixArray::new("banana");
+    /// assert_eq!(suffix_array.len(), 6);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    /// Returns `true` if the indexed text is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::suffix_array::SuffixArray;
+    ///
+    /// let suffix_array = SuffixArray::new("");
+    /// assert!(suffix_array.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Returns the starting positions of the suffixes of the text in lexicographical order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::suffix_array::SuffixArray;
+    ///
+    /// let suffix_array = SuffixArray::new("abab");
+    /// assert_eq!(suffix_array.suffixes(), &[2, 0, 3, 1]);
+    /// ```
+    pub fn suffixes(&self) -> &[usize] {
+        &self.suffixes
+    }
+
+    /// Returns the longest common prefix array of the text. The `i`-th element is the length of
+    /// the longest common prefix of the `i`-th and `(i - 1)`-th suffixes in lexicographical order.
+    /// The first element is always zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::suffix_array::SuffixArray;
+    ///
+    /// let suffix_array = SuffixArray::new("abab");
+    /// assert_eq!(suffix_array.lcp(), &[0, 2, 0, 1]);
+    /// ```
+    pub fn lcp(&self) -> &[usize] {
+        &self.lcp
+    }
+
+    // Returns the range of the suffix array containing the suffixes that start with `pattern`.
+    fn find_range(&self, pattern: &[u8]) -> (usize, usize) {
+        let prefix = |suffix: usize| {
+            let end = cmp::min(self.text.len(), suffix + pattern.len());
+            &self.text[suffix..end]
+        };
+        let start = self
+            .suffixes
+            .partition_point(|&suffix| prefix(suffix) < pattern);
+        let end = self
+            .suffixes
+            .partition_point(|&suffix| prefix(suffix) <= pattern);
+        (start, end)
+    }
+
+    /// Returns `true` if `pattern` is a substring of the text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::suffix_array::SuffixArray;
+    ///
+    /// let suffix_array = SuffixArray::new("banana");
+    /// assert!(suffix_array.contains(b"anan"));
+    /// assert!(!suffix_array.contains(b"nab"));
+    /// ```
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        let (start, end) = self.find_range(pattern);
+        start < end
+    }
+
+    /// Returns the number of occurrences of `pattern` in the text. Occurrences may overlap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::suffix_array::SuffixArray;
+    ///
+    /// let suffix_array = SuffixArray::new("banana");
+    /// assert_eq!(suffix_array.count(b"ana"), 2);
+    /// assert_eq!(suffix_array.count(b"nab"), 0);
+    /// ```
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        let (start, end) = self.find_range(pattern);
+        end - start
+    }
+
+    /// Returns the starting positions of all occurrences of `pattern` in the text. The positions
+    /// are ordered by the lexicographical order of the suffixes that start at them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::suffix_array::SuffixArray;
+    ///
+    /// let suffix_array = SuffixArray::new("banana");
+    /// let mut positions = suffix_array.find_all(b"an").to_vec();
+    /// positions.sort();
+    /// assert_eq!(positions, vec![1, 3]);
+    /// ```
+    pub fn find_all(&self, pattern: &[u8]) -> &[usize] {
+        let (start, end) = self.find_range(pattern);
+        &self.suffixes[start..end]
+    }
+
+    /// Returns the longest substring that occurs at least twice in the text. Occurrences may
+    /// overlap. If there are multiple such substrings, the lexicographically smallest one is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::suffix_array::SuffixArray;
+    ///
+    /// let suffix_array = SuffixArray::new("mississippi");
+    /// assert_eq!(suffix_array.longest_repeated_substring(), b"issi");
+    /// ```
+    pub fn longest_repeated_substring(&self) -> &[u8] {
+        let mut ret = (0, 0);
+        for (rank, &len) in self.lcp.iter().enumerate() {
+            if len > ret.1 {
+                ret = (self.suffixes[rank], len);
+            }
+        }
+        &self.text[ret.0..ret.0 + ret.1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SuffixArray;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    fn naive_suffixes(text: &[u8]) -> Vec<usize> {
+        let mut suffixes: Vec<usize> = (0..text.len()).collect();
+        suffixes.sort_by(|&i, &j| text[i..].cmp(&text[j..]));
+        suffixes
+    }
+
+    #[test]
+    fn test_empty() {
+        let suffix_array = SuffixArray::new("");
+        assert!(suffix_array.is_empty());
+        assert_eq!(suffix_array.suffixes(), &[] as &[usize]);
+        assert_eq!(suffix_array.lcp(), &[] as &[usize]);
+        assert_eq!(suffix_array.count(b""), 0);
+        assert!(!suffix_array.contains(b"a"));
+        assert_eq!(suffix_array.longest_repeated_substring(), b"");
+    }
+
+    #[test]
+    fn test_single_character() {
+        let suffix_array = SuffixArray::new("aaaa");
+        assert_eq!(suffix_array.suffixes(), &[3, 2, 1, 0]);
+        assert_eq!(suffix_array.lcp(), &[0, 1, 2, 3]);
+        assert_eq!(suffix_array.count(b"aa"), 3);
+        assert_eq!(suffix_array.longest_repeated_substring(), b"aaa");
+    }
+
+    #[test]
+    fn test_mississippi() {
+        let suffix_array = SuffixArray::new("mississippi");
+        assert_eq!(suffix_array.suffixes(), &[10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2],);
+        assert_eq!(suffix_array.lcp(), &[0, 1, 1, 4, 0, 0, 1, 0, 2, 1, 3]);
+        assert_eq!(suffix_array.count(b"ssi"), 2);
+        assert_eq!(suffix_array.count(b"i"), 4);
+        assert_eq!(suffix_array.count(b""), 11);
+        assert!(!suffix_array.contains(b"mississippis"));
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng: XorShiftRng = SeedableRng::from_seed([1, 1, 1, 1]);
+        for _ in 0..200 {
+            let len = rng.gen_range(0, 200);
+            let alphabet_size = rng.gen_range(1, 5);
+            let text: Vec<u8> = (0..len)
+                .map(|_| b'a' + rng.gen_range(0, alphabet_size))
+                .collect();
+            let suffix_array = SuffixArray::new(text.clone());
+            let expected = naive_suffixes(&text);
+            assert_eq!(suffix_array.suffixes(), &expected[..]);
+
+            for rank in 1..len {
+                let (i, j) = (expected[rank - 1], expected[rank]);
+                let lcp = text[i..]
+                    .iter()
+                    .zip(&text[j..])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                assert_eq!(suffix_array.lcp()[rank], lcp);
+            }
+
+            for _ in 0..10 {
+                let pattern: Vec<u8> = (0..rng.gen_range(1, 4))
+                    .map(|_| b'a' + rng.gen_range(0, alphabet_size))
+                    .collect();
+                let mut positions = suffix_array.find_all(&pattern).to_vec();
+                positions.sort();
+                let expected_positions: Vec<usize> = (0..len)
+                    .filter(|&i| text[i..].starts_with(&pattern))
+                    .collect();
+                assert_eq!(positions, expected_positions);
+            }
+        }
+    }
+}
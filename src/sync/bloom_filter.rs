@@ -0,0 +1,372 @@
+use crate::bit_vec::BitVec;
+use crate::bloom::{self, hash, SipHashBuilder};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const WORD_BITS: usize = 64;
+
+/// A concurrent bloom filter that supports inserting items from multiple threads without locking.
+///
+/// The bits of the filter are stored in atomic 64-bit words, so inserting an item atomically sets
+/// each of its bits and checking for an item atomically reads each of its bits. Concurrent
+/// insertions never lose bits, and an item is guaranteed to be reported in the filter once the
+/// insertion that added it is visible to the reading thread, such as after joining the inserting
+/// thread.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::sync::BloomFilter;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let filter: Arc<BloomFilter<u32>> = Arc::new(BloomFilter::new(1000, 0.01));
+/// let handles: Vec<_> = (0..4)
+///     .map(|thread_index| {
+///         let filter = Arc::clone(&filter);
+///         thread::spawn(move || {
+///             for item in thread_index * 100..(thread_index + 1) * 100 {
+///                 filter.insert(&item);
+///             }
+///         })
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+/// assert!((0..400).all(|item| filter.contains(&item)));
+/// ```
+pub struct BloomFilter<T, S = SipHashBuilder> {
+    words: Vec<AtomicU64>,
+    bit_count: usize,
+    hasher_count: usize,
+    hash_builder: S,
+    _marker: PhantomData<T>,
+}
+
+impl<T> BloomFilter<T> {
+    /// Constructs a new, empty `BloomFilter<T>` with an estimated maximum of `item_count` items
+    /// and a desired false positive probability of `fpp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// ```
+    pub fn new(item_count: usize, fpp: f64) -> Self {
+        Self::from_bit_count(
+            hash::optimal_slot_count(item_count, fpp),
+            hash::optimal_hasher_count(fpp),
+        )
+    }
+
+    /// Constructs a new, empty `BloomFilter<T>` with `bit_count` bits and `hasher_count` hash
+    /// functions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_count` or `hasher_count` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::from_bit_count(100, 4);
+    /// assert_eq!(filter.bit_count(), 100);
+    /// assert_eq!(filter.hasher_count(), 4);
+    /// ```
+    pub fn from_bit_count(bit_count: usize, hasher_count: usize) -> Self {
+        Self::from_parts(bit_count, hasher_count, SipHashBuilder::new())
+    }
+
+    /// Constructs a new, empty `BloomFilter<T>` with an estimated maximum of `item_count` items
+    /// and a desired false positive probability of `fpp` whose hash functions are derived from
+    /// `seed`. Filters constructed with the same seed hash every item identically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::with_seed(10, 0.01, 0);
+    /// let other: BloomFilter<String> = BloomFilter::with_seed(10, 0.01, 0);
+    /// assert_eq!(filter.hasher(), other.hasher());
+    /// ```
+    pub fn with_seed(item_count: usize, fpp: f64, seed: u64) -> Self {
+        Self::with_hasher(item_count, fpp, SipHashBuilder::with_seed(seed))
+    }
+}
+
+impl<T, S> BloomFilter<T, S>
+where
+    S: BuildHasher,
+{
+    /// Constructs a new, empty `BloomFilter<T, S>` with an estimated maximum of `item_count` items
+    /// and a desired false positive probability of `fpp` that uses `hash_builder` to hash items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::BloomFilter;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let filter: BloomFilter<String, RandomState> =
+    ///     BloomFilter::with_hasher(10, 0.01, RandomState::new());
+    /// ```
+    pub fn with_hasher(item_count: usize, fpp: f64, hash_builder: S) -> Self {
+        Self::from_parts(
+            hash::optimal_slot_count(item_count, fpp),
+            hash::optimal_hasher_count(fpp),
+            hash_builder,
+        )
+    }
+
+    fn from_parts(bit_count: usize, hasher_count: usize, hash_builder: S) -> Self {
+        assert!(bit_count > 0, "Error: bit count must be positive.");
+        assert!(hasher_count > 0, "Error: hasher count must be positive.");
+        BloomFilter {
+            words: (0..bit_count.div_ceil(WORD_BITS))
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+            bit_count,
+            hasher_count,
+            hash_builder,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Inserts an item into the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn insert<U>(&self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        for slot in hash::slots(&self.hash_builder, item, self.hasher_count, self.bit_count) {
+            self.words[slot / WORD_BITS].fetch_or(1 << (slot % WORD_BITS), Ordering::Release);
+        }
+    }
+
+    /// Checks if an item is possibly in the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// assert!(!filter.contains("foo"));
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        hash::slots(&self.hash_builder, item, self.hasher_count, self.bit_count).all(|slot| {
+            self.words[slot / WORD_BITS].load(Ordering::Acquire) & (1 << (slot % WORD_BITS)) != 0
+        })
+    }
+
+    /// Clears all items from the filter. Items that are inserted concurrently may be partially
+    /// cleared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// filter.insert("foo");
+    /// filter.clear();
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn clear(&self) {
+        for word in &self.words {
+            word.store(0, Ordering::Release);
+        }
+    }
+
+    /// Returns the number of bits in the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// assert_eq!(filter.bit_count(), 96);
+    /// ```
+    pub fn bit_count(&self) -> usize {
+        self.bit_count
+    }
+
+    /// Returns the number of hash functions used by the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// assert_eq!(filter.hasher_count(), 7);
+    /// ```
+    pub fn hasher_count(&self) -> usize {
+        self.hasher_count
+    }
+
+    /// Returns a reference to the `BuildHasher` used by the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::bloom::SipHashBuilder;
+    /// use extended_collections::sync::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::with_seed(10, 0.01, 0);
+    /// assert_eq!(filter.hasher(), &SipHashBuilder::with_seed(0));
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns the estimated false positive probability of the filter based on the fraction of
+    /// bits that are set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// assert_eq!(filter.estimate_fpp(), 0.0);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimate_fpp() > 0.0 && filter.estimate_fpp() < 0.01);
+    /// ```
+    pub fn estimate_fpp(&self) -> f64 {
+        let occupied_count = self
+            .words
+            .iter()
+            .map(|word| word.load(Ordering::Acquire).count_ones() as usize)
+            .sum();
+        hash::estimate_fpp(occupied_count, self.bit_count, self.hasher_count)
+    }
+
+    /// Returns a `bloom::BloomFilter<T, S>` that contains the items that have been inserted into the
+    /// filter. Both filters use the same hash functions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::BloomFilter;
+    ///
+    /// let filter: BloomFilter<String> = BloomFilter::new(10, 0.01);
+    /// filter.insert("foo");
+    ///
+    /// let snapshot = filter.to_bloom_filter();
+    /// assert!(snapshot.contains("foo"));
+    /// ```
+    pub fn to_bloom_filter(&self) -> bloom::BloomFilter<T, S>
+    where
+        S: Clone,
+    {
+        let words = self
+            .words
+            .iter()
+            .map(|word| word.load(Ordering::Acquire))
+            .collect();
+        let bit_vec = BitVec::from_words(self.bit_count, words).expect("Expected valid words.");
+        bloom::BloomFilter::from_bit_vec(bit_vec, self.hasher_count, self.hash_builder.clone())
+    }
+}
+
+impl<T, S> From<bloom::BloomFilter<T, S>> for BloomFilter<T, S>
+where
+    S: BuildHasher,
+{
+    fn from(filter: bloom::BloomFilter<T, S>) -> Self {
+        let (bit_vec, hasher_count, hash_builder) = filter.into_parts();
+        BloomFilter {
+            words: bit_vec
+                .words()
+                .iter()
+                .map(|word| AtomicU64::new(*word))
+                .collect(),
+            bit_count: bit_vec.len(),
+            hasher_count,
+            hash_builder,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BloomFilter;
+    use crate::bloom;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_concurrent_insert() {
+        let filter: Arc<BloomFilter<u32>> = Arc::new(BloomFilter::new(8000, 0.01));
+        let handles: Vec<_> = (0..8)
+            .map(|thread_index| {
+                let filter = Arc::clone(&filter);
+                thread::spawn(move || {
+                    for item in thread_index * 1000..(thread_index + 1) * 1000 {
+                        filter.insert(&item);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        for item in 0..8000u32 {
+            assert!(filter.contains(&item));
+        }
+        let false_positives = (8000..18000u32)
+            .filter(|item| filter.contains(item))
+            .count();
+        assert!(false_positives < 200);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let mut filter: bloom::BloomFilter<u32> = bloom::BloomFilter::new(100, 0.01);
+        for item in 0..50u32 {
+            filter.insert(&item);
+        }
+
+        let concurrent_filter = BloomFilter::from(filter.clone());
+        for item in 50..100u32 {
+            concurrent_filter.insert(&item);
+        }
+        assert_eq!(concurrent_filter.bit_count(), filter.bit_count());
+
+        let snapshot = concurrent_filter.to_bloom_filter();
+        for item in 0..100u32 {
+            assert!(snapshot.contains(&item));
+        }
+        assert_eq!(snapshot.estimate_fpp(), concurrent_filter.estimate_fpp());
+    }
+}
@@ -0,0 +1,284 @@
+//! Concurrency test harness for the data structures in `sync`.
+//!
+//! A test describes a concurrent data structure with a sequential `Model`, generates a random
+//! program of operations for each thread, and runs the program against the data structure. Each
+//! operation is timestamped when it is invoked and when it responds, and the resulting history is
+//! checked for linearizability against the model: there must be a sequential ordering of the
+//! operations that respects real-time order and produces the same results as the model.
+
+use rand::{Rng, SeedableRng, XorShiftRng};
+use std::collections::HashSet;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+/// The maximum number of operations in a history that can be checked for linearizability.
+pub const MAX_HISTORY_LEN: usize = 64;
+
+/// A sequential specification of a concurrent data structure.
+pub trait Model: Clone + Eq + Hash + 'static {
+    /// An operation on the data structure.
+    type Op: Clone + Debug + Send + Sync + 'static;
+
+    /// The result of an operation on the data structure.
+    type Ret: Clone + Debug + Eq + Send + 'static;
+
+    /// Applies an operation to the model and returns its result.
+    fn apply(&mut self, op: &Self::Op) -> Self::Ret;
+}
+
+/// A completed operation in a concurrent history.
+pub struct Event<M>
+where
+    M: Model,
+{
+    pub thread: usize,
+    pub op: M::Op,
+    pub ret: M::Ret,
+    pub invoke: usize,
+    pub response: usize,
+}
+
+impl<M> Debug for Event<M>
+where
+    M: Model,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}, {}] thread {}: {:?} -> {:?}",
+            self.invoke, self.response, self.thread, self.op, self.ret,
+        )
+    }
+}
+
+/// Generates a program of `ops_per_thread` operations for each of `thread_count` threads.
+pub fn generate_ops<T, F>(
+    seed: u32,
+    thread_count: usize,
+    ops_per_thread: usize,
+    mut generate_op: F,
+) -> Vec<Vec<T>>
+where
+    F: FnMut(&mut XorShiftRng) -> T,
+{
+    let mut rng = XorShiftRng::from_seed([seed, seed ^ 0x9E37_79B9, 1, 1]);
+    (0..thread_count)
+        .map(|_| (0..ops_per_thread).map(|_| generate_op(&mut rng)).collect())
+        .collect()
+}
+
+/// Runs a program against a shared data structure and returns the recorded history. All threads
+/// start at the same time and randomly yield between operations to vary the interleavings.
+pub fn run<S, M, F>(structure: Arc<S>, program: Vec<Vec<M::Op>>, apply: F) -> Vec<Event<M>>
+where
+    S: Send + Sync + 'static,
+    M: Model,
+    F: Fn(&S, &M::Op) -> M::Ret + Send + Sync + 'static,
+{
+    let apply = Arc::new(apply);
+    let clock = Arc::new(AtomicUsize::new(0));
+    let barrier = Arc::new(Barrier::new(program.len()));
+    let handles: Vec<_> = program
+        .into_iter()
+        .enumerate()
+        .map(|(thread_index, ops)| {
+            let structure = Arc::clone(&structure);
+            let apply = Arc::clone(&apply);
+            let clock = Arc::clone(&clock);
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                let mut rng = XorShiftRng::from_seed([thread_index as u32 + 1, 1, 1, 1]);
+                barrier.wait();
+                ops.into_iter()
+                    .map(|op| {
+                        if rng.gen() {
+                            thread::yield_now();
+                        }
+                        let invoke = clock.fetch_add(1, Ordering::SeqCst);
+                        let ret = apply(&structure, &op);
+                        let response = clock.fetch_add(1, Ordering::SeqCst);
+                        Event {
+                            thread: thread_index,
+                            op,
+                            ret,
+                            invoke,
+                            response,
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    handles
+        .into_iter()
+        .flat_map(|handle| handle.join().expect("Expected thread to not panic."))
+        .collect()
+}
+
+fn search<M>(
+    model: &M,
+    history: &[Event<M>],
+    linearized: u64,
+    visited: &mut HashSet<(u64, M)>,
+) -> bool
+where
+    M: Model,
+{
+    if linearized.count_ones() as usize == history.len() {
+        return true;
+    }
+
+    if !visited.insert((linearized, model.clone())) {
+        return false;
+    }
+
+    // an operation can be linearized next only if it was invoked before every pending operation
+    // responded
+    let min_response = history
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| linearized & (1 << index) == 0)
+        .map(|(_, event)| event.response)
+        .min()
+        .expect("Expected a pending operation.");
+
+    for (index, event) in history.iter().enumerate() {
+        if linearized & (1 << index) != 0 || event.invoke > min_response {
+            continue;
+        }
+
+        let mut next_model = model.clone();
+        if next_model.apply(&event.op) == event.ret
+            && search(&next_model, history, linearized | (1 << index), visited)
+        {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Returns `true` if a history is linearizable with respect to a model in its initial state.
+///
+/// # Panics
+///
+/// Panics if the history contains more than `MAX_HISTORY_LEN` operations.
+pub fn is_linearizable<M>(model: &M, history: &[Event<M>]) -> bool
+where
+    M: Model,
+{
+    assert!(
+        history.len() <= MAX_HISTORY_LEN,
+        "Expected a history of at most {} operations.",
+        MAX_HISTORY_LEN,
+    );
+    search(model, history, 0, &mut HashSet::new())
+}
+
+/// Runs randomly generated programs against fresh data structures for a number of rounds and
+/// panics with the offending history if any of them is not linearizable.
+pub fn check_linearizable<S, M, C, G, F>(
+    rounds: u32,
+    thread_count: usize,
+    ops_per_thread: usize,
+    model: &M,
+    create: C,
+    mut generate_op: G,
+    apply: F,
+) where
+    S: Send + Sync + 'static,
+    M: Model,
+    C: Fn() -> S,
+    G: FnMut(&mut XorShiftRng) -> M::Op,
+    F: Fn(&S, &M::Op) -> M::Ret + Clone + Send + Sync + 'static,
+{
+    for round in 0..rounds {
+        let program = generate_ops(round, thread_count, ops_per_thread, &mut generate_op);
+        let history = run(Arc::new(create()), program, apply.clone());
+        assert!(
+            is_linearizable(model, &history),
+            "Expected history to be linearizable: {:#?}",
+            history,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_linearizable, Event, Model};
+
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    struct Register(u32);
+
+    #[derive(Clone, Debug)]
+    enum RegisterOp {
+        Read,
+        Write(u32),
+    }
+
+    impl Model for Register {
+        type Op = RegisterOp;
+        type Ret = Option<u32>;
+
+        fn apply(&mut self, op: &RegisterOp) -> Option<u32> {
+            match *op {
+                RegisterOp::Read => Some(self.0),
+                RegisterOp::Write(value) => {
+                    self.0 = value;
+                    None
+                }
+            }
+        }
+    }
+
+    fn event(
+        thread: usize,
+        op: RegisterOp,
+        ret: Option<u32>,
+        invoke: usize,
+        response: usize,
+    ) -> Event<Register> {
+        Event {
+            thread,
+            op,
+            ret,
+            invoke,
+            response,
+        }
+    }
+
+    #[test]
+    fn test_empty_history() {
+        assert!(is_linearizable(&Register(0), &[]));
+    }
+
+    #[test]
+    fn test_overlapping_operations() {
+        // the read overlaps with the write, so it can observe either value
+        let history = vec![
+            event(0, RegisterOp::Write(1), None, 0, 3),
+            event(1, RegisterOp::Read, Some(0), 1, 2),
+        ];
+        assert!(is_linearizable(&Register(0), &history));
+
+        let history = vec![
+            event(0, RegisterOp::Write(1), None, 0, 3),
+            event(1, RegisterOp::Read, Some(1), 1, 2),
+        ];
+        assert!(is_linearizable(&Register(0), &history));
+    }
+
+    #[test]
+    fn test_stale_read() {
+        // the read starts after the write completes, so it cannot observe the old value
+        let history = vec![
+            event(0, RegisterOp::Write(1), None, 0, 1),
+            event(1, RegisterOp::Read, Some(0), 2, 3),
+        ];
+        assert!(!is_linearizable(&Register(0), &history));
+    }
+}
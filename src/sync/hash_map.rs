@@ -0,0 +1,542 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::{self, RandomState};
+use std::hash::{BuildHasher, Hash};
+use std::ops::{Deref, DerefMut};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::vec;
+
+const DEFAULT_SHARD_COUNT: usize = 16;
+
+/// A concurrent hash map that partitions its entries across multiple independently locked shards.
+///
+/// Each key is hashed to a shard, and each shard is a `std::collections::HashMap` protected by a
+/// reader-writer lock. Operations on keys in different shards never contend, and lookups on the
+/// same shard can proceed concurrently. Lookups return a guard that holds the read lock of the
+/// shard, so a thread should not update the map while holding a guard to a key in the same shard.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::sync::HashMap;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let map = Arc::new(HashMap::new());
+/// let handles: Vec<_> = (0..4)
+///     .map(|thread_index| {
+///         let map = Arc::clone(&map);
+///         thread::spawn(move || {
+///             for key in thread_index * 100..(thread_index + 1) * 100 {
+///                 map.insert(key, key * 2);
+///             }
+///         })
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+/// assert_eq!(map.len(), 400);
+/// assert_eq!(*map.get(&10).unwrap(), 20);
+/// ```
+pub struct HashMap<K, V, S = RandomState> {
+    shards: Vec<RwLock<hash_map::HashMap<K, V, S>>>,
+    hash_builder: S,
+}
+
+impl<K, V> HashMap<K, V>
+where
+    K: Eq + Hash,
+{
+    /// Constructs a new, empty `HashMap<K, V>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::HashMap;
+    ///
+    /// let map: HashMap<u32, u32> = HashMap::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::with_shards(DEFAULT_SHARD_COUNT)
+    }
+
+    /// Constructs a new, empty `HashMap<K, V>` with a specific number of shards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard_count` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::HashMap;
+    ///
+    /// let map: HashMap<u32, u32> = HashMap::with_shards(4);
+    /// assert_eq!(map.shard_count(), 4);
+    /// ```
+    pub fn with_shards(shard_count: usize) -> Self {
+        Self::with_shards_and_hasher(shard_count, RandomState::new())
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Clone,
+{
+    /// Constructs a new, empty `HashMap<K, V, S>` with a specific number of shards that uses
+    /// `hash_builder` to hash keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard_count` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::HashMap;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let map: HashMap<u32, u32> = HashMap::with_shards_and_hasher(4, RandomState::new());
+    /// assert_eq!(map.shard_count(), 4);
+    /// ```
+    pub fn with_shards_and_hasher(shard_count: usize, hash_builder: S) -> Self {
+        assert!(shard_count > 0, "Error: shard count must be positive.");
+        HashMap {
+            shards: (0..shard_count)
+                .map(|_| RwLock::new(hash_map::HashMap::with_hasher(hash_builder.clone())))
+                .collect(),
+            hash_builder,
+        }
+    }
+
+    fn get_shard<Q>(&self, key: &Q) -> &RwLock<hash_map::HashMap<K, V, S>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        let hash = self.hash_builder.hash_one(key);
+        &self.shards[(hash % self.shards.len() as u64) as usize]
+    }
+
+    /// Inserts a key-value pair into the map. If the key already exists in the map, it will
+    /// return and replace the old value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// assert_eq!(map.insert(1, 1), None);
+    /// assert_eq!(map.insert(1, 2), Some(1));
+    /// assert_eq!(*map.get(&1).unwrap(), 2);
+    /// ```
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.get_shard(&key).write().unwrap().insert(key, value)
+    }
+
+    /// Removes a key from the map. If the key exists in the map, it will return the associated
+    /// value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.remove(&1), Some(1));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.get_shard(key).write().unwrap().remove(key)
+    }
+
+    /// Checks if a key exists in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// map.insert(1, 1);
+    /// assert!(!map.contains_key(&0));
+    /// assert!(map.contains_key(&1));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.get_shard(key).read().unwrap().contains_key(key)
+    }
+
+    /// Returns a guard to the value associated with a particular key. The guard holds the read
+    /// lock of the shard that contains the key until it is dropped. Returns `None` if the key
+    /// does not exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// map.insert(1, 1);
+    /// assert!(map.get(&0).is_none());
+    /// assert_eq!(*map.get(&1).unwrap(), 1);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<HashMapRef<'_, K, V, S>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let guard = self.get_shard(key).read().unwrap();
+        let value = guard.get(key)? as *const V;
+        Some(HashMapRef {
+            _guard: guard,
+            value,
+        })
+    }
+
+    /// Returns a guard to the mutable value associated with a particular key. The guard holds the
+    /// write lock of the shard that contains the key until it is dropped. Returns `None` if the
+    /// key does not exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// map.insert(1, 1);
+    /// assert!(map.get_mut(&0).is_none());
+    /// *map.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(*map.get(&1).unwrap(), 2);
+    /// ```
+    pub fn get_mut<Q>(&self, key: &Q) -> Option<HashMapRefMut<'_, K, V, S>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let mut guard = self.get_shard(key).write().unwrap();
+        let value = guard.get_mut(key)? as *mut V;
+        Some(HashMapRefMut {
+            _guard: guard,
+            value,
+        })
+    }
+
+    /// Returns the number of elements in the map. The count is not a consistent snapshot if the
+    /// map is being updated concurrently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().unwrap().len())
+            .sum()
+    }
+
+    /// Returns `true` if the map is empty. The result is not a consistent snapshot if the map is
+    /// being updated concurrently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// assert!(map.is_empty());
+    ///
+    /// map.insert(1, 1);
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.shards
+            .iter()
+            .all(|shard| shard.read().unwrap().is_empty())
+    }
+
+    /// Clears the map, removing all values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.clear();
+    /// assert_eq!(map.is_empty(), true);
+    /// ```
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            shard.write().unwrap().clear();
+        }
+    }
+
+    /// Returns the number of shards in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::HashMap;
+    ///
+    /// let map: HashMap<u32, u32> = HashMap::new();
+    /// assert_eq!(map.shard_count(), 16);
+    /// ```
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns an iterator over a snapshot of the map. Each shard is copied while holding its
+    /// read lock, so the snapshot of each shard is consistent, but the snapshot of the entire map
+    /// is not if the map is being updated concurrently. The iterator yields entries in arbitrary
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// map.insert(1, 2);
+    /// map.insert(3, 4);
+    ///
+    /// let mut entries: Vec<(u32, u32)> = map.iter().collect();
+    /// entries.sort();
+    /// assert_eq!(entries, vec![(1, 2), (3, 4)]);
+    /// ```
+    pub fn iter(&self) -> HashMapIter<K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let mut entries = Vec::new();
+        for shard in &self.shards {
+            let shard = shard.read().unwrap();
+            entries.extend(
+                shard
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone())),
+            );
+        }
+        HashMapIter {
+            entries: entries.into_iter(),
+        }
+    }
+}
+
+impl<K, V> Default for HashMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A guard to a value in a `HashMap` that holds the read lock of the shard that contains it.
+pub struct HashMapRef<'a, K, V, S> {
+    _guard: RwLockReadGuard<'a, hash_map::HashMap<K, V, S>>,
+    value: *const V,
+}
+
+// the pointer is only used to create a shared reference while the read lock is held, so the
+// guard can be shared like a `RwLockReadGuard`
+unsafe impl<'a, K: Sync, V: Sync, S: Sync> Sync for HashMapRef<'a, K, V, S> {}
+
+impl<'a, K, V, S> Deref for HashMapRef<'a, K, V, S> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        // the value cannot move or be dropped while the read lock is held
+        unsafe { &*self.value }
+    }
+}
+
+/// A guard to a mutable value in a `HashMap` that holds the write lock of the shard that contains
+/// it.
+pub struct HashMapRefMut<'a, K, V, S> {
+    _guard: RwLockWriteGuard<'a, hash_map::HashMap<K, V, S>>,
+    value: *mut V,
+}
+
+// the pointer is only used to create references while the write lock is held, so the guard can be
+// shared like a `RwLockWriteGuard`
+unsafe impl<'a, K: Sync, V: Sync, S: Sync> Sync for HashMapRefMut<'a, K, V, S> {}
+
+impl<'a, K, V, S> Deref for HashMapRefMut<'a, K, V, S> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        // the value cannot move or be dropped while the write lock is held
+        unsafe { &*self.value }
+    }
+}
+
+impl<'a, K, V, S> DerefMut for HashMapRefMut<'a, K, V, S> {
+    fn deref_mut(&mut self) -> &mut V {
+        // the value cannot move or be dropped while the write lock is held
+        unsafe { &mut *self.value }
+    }
+}
+
+/// An iterator over a snapshot of a `HashMap`.
+///
+/// This iterator yields entries in arbitrary order.
+pub struct HashMapIter<K, V> {
+    entries: vec::IntoIter<(K, V)>,
+}
+
+impl<K, V> Iterator for HashMapIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HashMap;
+    use crate::sync::harness::{self, Model};
+    use rand::Rng;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    struct HashMapModel(BTreeMap<u32, u32>);
+
+    #[derive(Clone, Debug)]
+    enum HashMapOp {
+        Insert(u32, u32),
+        Remove(u32),
+        Get(u32),
+    }
+
+    impl Model for HashMapModel {
+        type Op = HashMapOp;
+        type Ret = Option<u32>;
+
+        fn apply(&mut self, op: &HashMapOp) -> Option<u32> {
+            match *op {
+                HashMapOp::Insert(key, value) => self.0.insert(key, value),
+                HashMapOp::Remove(key) => self.0.remove(&key),
+                HashMapOp::Get(key) => self.0.get(&key).cloned(),
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_shards() {
+        let _map: HashMap<u32, u32> = HashMap::with_shards(0);
+    }
+
+    #[test]
+    fn test_len_empty() {
+        let map: HashMap<u32, u32> = HashMap::new();
+        assert_eq!(map.len(), 0);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let map = HashMap::with_shards(4);
+        for key in 0..100 {
+            assert_eq!(map.insert(key, key), None);
+        }
+        assert_eq!(map.len(), 100);
+        for key in 0..50 {
+            assert_eq!(map.remove(&key), Some(key));
+        }
+        assert_eq!(map.len(), 50);
+        assert!((0..50).all(|key| !map.contains_key(&key)));
+        assert!((50..100).all(|key| *map.get(&key).unwrap() == key));
+    }
+
+    #[test]
+    fn test_get_borrowed() {
+        let map = HashMap::new();
+        map.insert(String::from("foo"), 1);
+        assert_eq!(*map.get("foo").unwrap(), 1);
+        *map.get_mut("foo").unwrap() += 1;
+        assert_eq!(map.remove("foo"), Some(2));
+    }
+
+    #[test]
+    fn test_iter() {
+        let map = HashMap::with_shards(4);
+        for key in 0..100 {
+            map.insert(key, key * 2);
+        }
+        let mut entries: Vec<(u32, u32)> = map.iter().collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            (0..100).map(|key| (key, key * 2)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_concurrent_update() {
+        let map = Arc::new(HashMap::with_shards(4));
+        for key in 0..10 {
+            map.insert(key, 0);
+        }
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let map = Arc::clone(&map);
+                thread::spawn(move || {
+                    for key in 0..1000 {
+                        *map.get_mut(&(key % 10)).unwrap() += 1;
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!((0..10).all(|key| *map.get(&key).unwrap() == 800));
+    }
+
+    #[test]
+    fn test_linearizable() {
+        harness::check_linearizable(
+            100,
+            4,
+            6,
+            &HashMapModel(BTreeMap::new()),
+            || HashMap::with_shards(2),
+            |rng| match rng.gen_range(0, 3) {
+                0 => HashMapOp::Insert(rng.gen_range(0, 4), rng.gen_range(0, 4)),
+                1 => HashMapOp::Remove(rng.gen_range(0, 4)),
+                _ => HashMapOp::Get(rng.gen_range(0, 4)),
+            },
+            |map: &HashMap<u32, u32>, op: &HashMapOp| match *op {
+                HashMapOp::Insert(key, value) => map.insert(key, value),
+                HashMapOp::Remove(key) => map.remove(&key),
+                HashMapOp::Get(key) => map.get(&key).map(|value| *value),
+            },
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use crate::intern::Symbol;
+use crate::sync::HashMap;
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::sync::{Arc, RwLock};
+
+/// A concurrent interner that maps values to small integer symbols.
+///
+/// The symbols of the values are stored in a sharded `HashMap`, so threads that look up values
+/// that are already interned do not contend. Interning a new value takes a lock that serializes
+/// the assignment of symbols, so symbols are assigned in the order that values are interned,
+/// starting from zero, like the symbols of `intern::Interner`. The values of the symbols are
+/// shared with `Arc`, so resolving a symbol does not hold any lock once it returns.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::sync::Interner;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let interner: Arc<Interner<String>> = Arc::new(Interner::new());
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let interner = Arc::clone(&interner);
+///         thread::spawn(move || {
+///             (0..100)
+///                 .map(|index| interner.get_or_intern(format!("value-{}", index).as_str()))
+///                 .collect::<Vec<_>>()
+///         })
+///     })
+///     .collect();
+///
+/// let symbols: Vec<_> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+/// assert!(symbols.iter().all(|other| *other == symbols[0]));
+/// assert_eq!(interner.len(), 100);
+/// assert_eq!(*interner.resolve(symbols[0][5]).unwrap(), "value-5");
+/// ```
+pub struct Interner<T, S = RandomState> {
+    symbols: HashMap<T, Symbol, S>,
+    values: RwLock<Vec<Arc<T>>>,
+}
+
+impl<T> Interner<T>
+where
+    T: Clone + Eq + Hash,
+{
+    /// Constructs a new, empty `Interner<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::Interner;
+    ///
+    /// let interner: Interner<String> = Interner::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Interner {
+            symbols: HashMap::new(),
+            values: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl<T, S> Interner<T, S>
+where
+    T: Clone + Eq + Hash,
+    S: BuildHasher + Clone,
+{
+    /// Constructs a new, empty `Interner<T, S>` with a specific number of shards that uses
+    /// `hash_builder` to hash values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard_count` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::Interner;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let interner: Interner<String> = Interner::with_shards_and_hasher(4, RandomState::new());
+    /// ```
+    pub fn with_shards_and_hasher(shard_count: usize, hash_builder: S) -> Self {
+        Interner {
+            symbols: HashMap::with_shards_and_hasher(shard_count, hash_builder),
+            values: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Returns the symbol of a value, interning a copy of the value if it was not interned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `2^32` values are interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::Interner;
+    ///
+    /// let interner: Interner<String> = Interner::new();
+    /// let symbol = interner.get_or_intern("foo");
+    /// assert_eq!(interner.get_or_intern("foo"), symbol);
+    /// assert_eq!(symbol.index(), 0);
+    /// ```
+    pub fn get_or_intern<U>(&self, value: &U) -> Symbol
+    where
+        T: Borrow<U>,
+        U: Eq + Hash + ToOwned<Owned = T> + ?Sized,
+    {
+        if let Some(symbol) = self.get(value) {
+            return symbol;
+        }
+
+        let mut values = self.values.write().unwrap();
+        // another thread may have interned the value before the lock was acquired
+        if let Some(symbol) = self.get(value) {
+            return symbol;
+        }
+        let symbol = Symbol::new(values.len());
+        let value = value.to_owned();
+        values.push(Arc::new(value.clone()));
+        self.symbols.insert(value, symbol);
+        symbol
+    }
+
+    /// Returns the symbol of a value without interning it. Returns `None` if the value was not
+    /// interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::Interner;
+    ///
+    /// let interner: Interner<String> = Interner::new();
+    /// let symbol = interner.get_or_intern("foo");
+    /// assert_eq!(interner.get("foo"), Some(symbol));
+    /// assert_eq!(interner.get("bar"), None);
+    /// ```
+    pub fn get<U>(&self, value: &U) -> Option<Symbol>
+    where
+        T: Borrow<U>,
+        U: Eq + Hash + ?Sized,
+    {
+        self.symbols.get(value).map(|symbol| *symbol)
+    }
+
+    /// Returns the value of a symbol. Returns `None` if the symbol was not returned by this
+    /// interner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::Interner;
+    ///
+    /// let interner: Interner<String> = Interner::new();
+    /// let symbol = interner.get_or_intern("foo");
+    /// assert_eq!(*interner.resolve(symbol).unwrap(), "foo");
+    /// ```
+    pub fn resolve(&self, symbol: Symbol) -> Option<Arc<T>> {
+        self.values.read().unwrap().get(symbol.index()).cloned()
+    }
+
+    /// Returns the number of values in the interner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::Interner;
+    ///
+    /// let interner: Interner<String> = Interner::new();
+    /// interner.get_or_intern("foo");
+    /// interner.get_or_intern("foo");
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.values.read().unwrap().len()
+    }
+
+    /// Returns `true` if the interner contains no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::Interner;
+    ///
+    /// let interner: Interner<String> = Interner::new();
+    /// assert!(interner.is_empty());
+    /// interner.get_or_intern("foo");
+    /// assert!(!interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for Interner<T>
+where
+    T: Clone + Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interner;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_concurrent_get_or_intern() {
+        let interner: Arc<Interner<u32>> = Arc::new(Interner::new());
+        let handles: Vec<_> = (0..8u32)
+            .map(|thread_index| {
+                let interner = Arc::clone(&interner);
+                thread::spawn(move || {
+                    (0..1000u32)
+                        .map(|index| {
+                            let value = (index * 7 + thread_index * 13) % 1000;
+                            (value, interner.get_or_intern(&value))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut symbols = vec![None; 1000];
+        for handle in handles {
+            for (value, symbol) in handle.join().unwrap() {
+                let expected = *symbols[value as usize].get_or_insert(symbol);
+                assert_eq!(symbol, expected);
+            }
+        }
+        assert_eq!(interner.len(), 1000);
+
+        let mut indexes: Vec<usize> = symbols
+            .iter()
+            .map(|symbol| symbol.unwrap().index())
+            .collect();
+        indexes.sort();
+        assert_eq!(indexes, (0..1000).collect::<Vec<usize>>());
+        for (value, symbol) in symbols.into_iter().enumerate() {
+            assert_eq!(*interner.resolve(symbol.unwrap()).unwrap(), value as u32);
+        }
+    }
+}
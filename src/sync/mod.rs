@@ -1,5 +1,21 @@
 //! Lock-free data structures.
 
+#[cfg(feature = "probabilistic")]
+mod bloom_filter;
+#[cfg(test)]
+pub(crate) mod harness;
+mod hash_map;
+mod interner;
+mod queue;
+mod skip_set;
 mod stack;
+mod striped_counter;
 
+#[cfg(feature = "probabilistic")]
+pub use self::bloom_filter::BloomFilter;
+pub use self::hash_map::HashMap;
+pub use self::interner::Interner;
+pub use self::queue::Queue;
+pub use self::skip_set::SkipSet;
 pub use self::stack::Stack;
+pub use self::striped_counter::StripedCounter;
@@ -0,0 +1,355 @@
+use crossbeam_epoch::{self, Atomic, Owned, Shared};
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// The value of the head node is always uninitialized or already moved out of the queue, so the
+// head acts as a sentinel node.
+struct Node<T> {
+    value: MaybeUninit<T>,
+    next: Atomic<Node<T>>,
+}
+
+/// A concurrent and lock-free multi-producer multi-consumer queue using the Michael-Scott
+/// algorithm.
+///
+/// The queue is a singly linked list with a sentinel node at its head. Items are pushed by
+/// swinging the `next` pointer of the tail node with a "compare-and-swap", and popped by swinging
+/// the head pointer. Threads that observe a lagging tail pointer help advance it before retrying.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::sync::Queue;
+///
+/// let q = Queue::new();
+///
+/// q.push(0);
+/// q.push(1);
+/// assert_eq!(q.len(), 2);
+///
+/// assert_eq!(q.try_pop(), Some(0));
+/// assert_eq!(q.try_pop(), Some(1));
+/// assert_eq!(q.len(), 0);
+/// ```
+pub struct Queue<T> {
+    head: Atomic<Node<T>>,
+    tail: Atomic<Node<T>>,
+    len: AtomicUsize,
+}
+
+impl<T> Queue<T> {
+    /// Constructs a new, empty `Queue<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::Queue;
+    ///
+    /// let q: Queue<u32> = Queue::new();
+    /// ```
+    pub fn new() -> Self {
+        let sentinel = Owned::new(Node {
+            value: MaybeUninit::uninit(),
+            next: Atomic::null(),
+        });
+        let queue = Queue {
+            head: Atomic::null(),
+            tail: Atomic::null(),
+            len: AtomicUsize::new(0),
+        };
+        let guard = &crossbeam_epoch::pin();
+        let sentinel = sentinel.into_shared(guard);
+        queue.head.store(sentinel, Ordering::Relaxed);
+        queue.tail.store(sentinel, Ordering::Relaxed);
+        queue
+    }
+
+    /// Pushes an item onto the back of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::Queue;
+    ///
+    /// let q = Queue::new();
+    /// q.push(0);
+    /// ```
+    pub fn push(&self, value: T) {
+        let mut new_node = Owned::new(Node {
+            value: MaybeUninit::new(value),
+            next: Atomic::null(),
+        });
+
+        let guard = &crossbeam_epoch::pin();
+        loop {
+            let tail_shared = self.tail.load(Ordering::Acquire, guard);
+            let tail = unsafe { tail_shared.deref() };
+            let next_shared = tail.next.load(Ordering::Acquire, guard);
+
+            // The tail pointer is lagging behind, so help advance it before retrying.
+            if !next_shared.is_null() {
+                let _ =
+                    self.tail
+                        .compare_and_set(tail_shared, next_shared, Ordering::Release, guard);
+                continue;
+            }
+
+            match tail
+                .next
+                .compare_and_set(Shared::null(), new_node, Ordering::Release, guard)
+            {
+                Ok(new_shared) => {
+                    let _ = self.tail.compare_and_set(
+                        tail_shared,
+                        new_shared,
+                        Ordering::Release,
+                        guard,
+                    );
+                    self.len.fetch_add(1, Ordering::Release);
+                    break;
+                }
+                Err(e) => new_node = e.new,
+            }
+        }
+    }
+
+    /// Attempts to pop the front element of the queue. Returns `None` if it was unable to pop the
+    /// front element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::Queue;
+    ///
+    /// let q = Queue::new();
+    ///
+    /// q.push(0);
+    ///
+    /// assert_eq!(q.try_pop(), Some(0));
+    /// assert_eq!(q.try_pop(), None);
+    /// ```
+    pub fn try_pop(&self) -> Option<T> {
+        let guard = &crossbeam_epoch::pin();
+        loop {
+            let head_shared = self.head.load(Ordering::Acquire, guard);
+            let head = unsafe { head_shared.deref() };
+            let next_shared = head.next.load(Ordering::Acquire, guard);
+            let next = unsafe { next_shared.as_ref() }?;
+
+            // The tail must not point to a node that is about to be reclaimed.
+            let tail_shared = self.tail.load(Ordering::Relaxed, guard);
+            if tail_shared == head_shared {
+                let _ =
+                    self.tail
+                        .compare_and_set(tail_shared, next_shared, Ordering::Release, guard);
+            }
+
+            if self
+                .head
+                .compare_and_set(head_shared, next_shared, Ordering::Release, guard)
+                .is_ok()
+            {
+                unsafe {
+                    self.len.fetch_sub(1, Ordering::Release);
+                    guard.defer(move || head_shared.into_owned());
+                    return Some(ptr::read(next.value.as_ptr()));
+                }
+            }
+        }
+    }
+
+    /// Returns the approximate number of elements in the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::Queue;
+    ///
+    /// let q = Queue::new();
+    /// assert_eq!(q.len(), 0);
+    ///
+    /// q.push(0);
+    /// assert_eq!(q.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    /// Returns `true` if the approximate number of elements in the queue is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::Queue;
+    ///
+    /// let q = Queue::new();
+    /// assert!(q.is_empty());
+    ///
+    /// q.push(0);
+    /// assert!(!q.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = crossbeam_epoch::unprotected();
+            let mut curr = self.head.load(Ordering::Relaxed, guard);
+            let mut is_sentinel = true;
+            while !curr.is_null() {
+                let mut node = curr.into_owned();
+                curr = node.next.load(Ordering::Relaxed, guard);
+                if !is_sentinel {
+                    ptr::drop_in_place(node.value.as_mut_ptr());
+                }
+                is_sentinel = false;
+            }
+        }
+    }
+}
+
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::Queue;
+    use crate::sync::harness::{self, Model};
+    use rand::Rng;
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    struct QueueModel(VecDeque<u32>);
+
+    #[derive(Clone, Debug)]
+    enum QueueOp {
+        Push(u32),
+        TryPop,
+    }
+
+    impl Model for QueueModel {
+        type Op = QueueOp;
+        type Ret = Option<u32>;
+
+        fn apply(&mut self, op: &QueueOp) -> Option<u32> {
+            match *op {
+                QueueOp::Push(value) => {
+                    self.0.push_back(value);
+                    None
+                }
+                QueueOp::TryPop => self.0.pop_front(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_len_empty() {
+        let queue: Queue<u32> = Queue::new();
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[test]
+    fn test_is_empty() {
+        let queue: Queue<u32> = Queue::new();
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let queue = Queue::new();
+        queue.push(0);
+        queue.push(1);
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.try_pop(), Some(0));
+        assert_eq!(queue.try_pop(), Some(1));
+        assert_eq!(queue.try_pop(), None);
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[test]
+    fn test_drop() {
+        let value = Arc::new(0);
+        let queue = Queue::new();
+        for _ in 0..10 {
+            queue.push(Arc::clone(&value));
+        }
+        queue.try_pop();
+        drop(queue);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_concurrent_push_pop() {
+        let queue = Arc::new(Queue::new());
+        let producers: Vec<_> = (0..4)
+            .map(|thread_index| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    for value in 0..1000 {
+                        queue.push((thread_index, value));
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..4)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    let mut last_values = [None; 4];
+                    let mut popped = 0;
+                    while popped < 1000 {
+                        if let Some((thread_index, value)) = queue.try_pop() {
+                            // Items from a single producer are popped in order.
+                            assert!(last_values[thread_index] < Some(value));
+                            last_values[thread_index] = Some(value);
+                            popped += 1;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for handle in producers.into_iter().chain(consumers) {
+            handle.join().unwrap();
+        }
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_linearizable() {
+        harness::check_linearizable(
+            100,
+            4,
+            6,
+            &QueueModel(VecDeque::new()),
+            Queue::new,
+            |rng| {
+                if rng.gen() {
+                    QueueOp::Push(rng.gen_range(0, 4))
+                } else {
+                    QueueOp::TryPop
+                }
+            },
+            |queue: &Queue<u32>, op: &QueueOp| match *op {
+                QueueOp::Push(value) => {
+                    queue.push(value);
+                    None
+                }
+                QueueOp::TryPop => queue.try_pop(),
+            },
+        );
+    }
+}
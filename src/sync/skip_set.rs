@@ -0,0 +1,650 @@
+use crossbeam_epoch::{self, Atomic, Guard, Owned, Shared};
+use std::borrow::Borrow;
+use std::cmp;
+use std::ops::{Bound, RangeBounds};
+use std::ptr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+const MAX_HEIGHT: usize = 32;
+
+// A node is removed by marking the tag of each of its links from the top level down. The node is
+// logically removed once its bottom link is marked, and it is physically unlinked from each level
+// by the next search that passes it.
+struct Node<T> {
+    key: T,
+    // The number of levels that link to the node, plus one while the inserting thread is still
+    // linking the node. The node is reclaimed once it is no longer referenced.
+    refs: AtomicUsize,
+    links: Box<[Atomic<Node<T>>]>,
+}
+
+impl<T> Node<T> {
+    unsafe fn release(node: Shared<'_, Node<T>>, guard: &Guard) {
+        if node.deref().refs.fetch_sub(1, Ordering::AcqRel) == 1 {
+            guard.defer(move || node.into_owned());
+        }
+    }
+}
+
+// The predecessor links and successors of a key at each level.
+struct Position<'g, T> {
+    preds: [&'g [Atomic<Node<T>>]; MAX_HEIGHT],
+    succs: [Shared<'g, Node<T>>; MAX_HEIGHT],
+}
+
+impl<'g, T> Position<'g, T> {
+    fn get_match<V>(&self, key: &V) -> Option<Shared<'g, Node<T>>>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        match unsafe { self.succs[0].as_ref() } {
+            Some(node) if node.key.borrow() == key => Some(self.succs[0]),
+            _ => None,
+        }
+    }
+}
+
+/// A concurrent and lock-free ordered set using a skiplist.
+///
+/// Every level of the skiplist is a lock-free linked list in the style of Harris and Michael.
+/// Insertions link a node from the bottom level up, and removals mark the links of a node from the
+/// top level down, so a key is in the set exactly when it is linked and unmarked on the bottom
+/// level. Removed nodes are reclaimed using epoch-based garbage collection.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::sync::SkipSet;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let set = Arc::new(SkipSet::new());
+/// let handles: Vec<_> = (0..4)
+///     .map(|thread_index| {
+///         let set = Arc::clone(&set);
+///         thread::spawn(move || {
+///             for key in thread_index * 100..(thread_index + 1) * 100 {
+///                 set.insert(key);
+///             }
+///         })
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+/// assert_eq!(set.len(), 400);
+/// assert_eq!(set.range(10..13).collect::<Vec<u32>>(), vec![10, 11, 12]);
+/// ```
+pub struct SkipSet<T> {
+    head: Box<[Atomic<Node<T>>]>,
+    seed: AtomicU64,
+    len: AtomicUsize,
+}
+
+impl<T> SkipSet<T>
+where
+    T: Ord,
+{
+    /// Constructs a new, empty `SkipSet<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::SkipSet;
+    ///
+    /// let set: SkipSet<u32> = SkipSet::new();
+    /// ```
+    pub fn new() -> Self {
+        SkipSet {
+            head: (0..MAX_HEIGHT).map(|_| Atomic::null()).collect(),
+            seed: AtomicU64::new(0),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    fn gen_random_height(&self) -> usize {
+        // splitmix64 so that concurrent insertions do not contend on a shared generator
+        let mut x = self
+            .seed
+            .fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed)
+            .wrapping_add(0x9E37_79B9_7F4A_7C15);
+        x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        x ^= x >> 31;
+        cmp::min(x.trailing_zeros() as usize + 1, MAX_HEIGHT)
+    }
+
+    // Finds the predecessors and successors of `key` at each level, unlinking removed nodes along
+    // the way. Each successor is the first unremoved node with a key that is not less than `key`.
+    fn find<'g, V>(&'g self, key: &V, guard: &'g Guard) -> Position<'g, T>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        'search: loop {
+            let mut pos = Position {
+                preds: [&*self.head; MAX_HEIGHT],
+                succs: [Shared::null(); MAX_HEIGHT],
+            };
+            let mut pred: &'g [Atomic<Node<T>>] = &self.head;
+            for level in (0..MAX_HEIGHT).rev() {
+                let mut curr = pred[level].load(Ordering::Acquire, guard);
+                // the predecessor is being removed
+                if curr.tag() == 1 {
+                    continue 'search;
+                }
+                while let Some(node) = unsafe { curr.as_ref() } {
+                    let succ = node.links[level].load(Ordering::Acquire, guard);
+                    if succ.tag() == 1 {
+                        let succ = succ.with_tag(0);
+                        match pred[level].compare_and_set(curr, succ, Ordering::AcqRel, guard) {
+                            Ok(_) => {
+                                unsafe { Node::release(curr, guard) };
+                                curr = succ;
+                                continue;
+                            }
+                            Err(_) => continue 'search,
+                        }
+                    }
+                    if node.key.borrow() >= key {
+                        break;
+                    }
+                    pred = &node.links;
+                    curr = succ;
+                }
+                pos.preds[level] = pred;
+                pos.succs[level] = curr;
+            }
+            return pos;
+        }
+    }
+
+    /// Inserts a key into the set. Returns `true` if the key was not already in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::SkipSet;
+    ///
+    /// let set = SkipSet::new();
+    /// assert!(set.insert(1));
+    /// assert!(!set.insert(1));
+    /// assert!(set.contains(&1));
+    /// ```
+    pub fn insert(&self, key: T) -> bool {
+        let guard = &crossbeam_epoch::pin();
+        let mut pos = self.find(&key, guard);
+        if pos.get_match(&key).is_some() {
+            return false;
+        }
+
+        let height = self.gen_random_height();
+        let node = Owned::new(Node {
+            key,
+            refs: AtomicUsize::new(2),
+            links: (0..height).map(|_| Atomic::null()).collect(),
+        })
+        .into_shared(guard);
+        let node_ref = unsafe { node.deref() };
+
+        // the key is inserted once the node is linked on the bottom level
+        loop {
+            node_ref.links[0].store(pos.succs[0], Ordering::Relaxed);
+            if pos.preds[0][0]
+                .compare_and_set(pos.succs[0], node, Ordering::AcqRel, guard)
+                .is_ok()
+            {
+                break;
+            }
+            pos = self.find(&node_ref.key, guard);
+            if pos.get_match(&node_ref.key).is_some() {
+                unsafe { drop(node.into_owned()) };
+                return false;
+            }
+        }
+        self.len.fetch_add(1, Ordering::Relaxed);
+
+        'build: for level in 1..height {
+            loop {
+                let next = node_ref.links[level].load(Ordering::Acquire, guard);
+                let succ = pos.succs[level];
+                // the link fails to update only if the node is being removed
+                if next.tag() == 1
+                    || node_ref.links[level]
+                        .compare_and_set(next, succ, Ordering::AcqRel, guard)
+                        .is_err()
+                {
+                    break 'build;
+                }
+                node_ref.refs.fetch_add(1, Ordering::Relaxed);
+                if pos.preds[level][level]
+                    .compare_and_set(succ, node, Ordering::AcqRel, guard)
+                    .is_ok()
+                {
+                    break;
+                }
+                node_ref.refs.fetch_sub(1, Ordering::Relaxed);
+                pos = self.find(&node_ref.key, guard);
+                if pos.succs[0] != node {
+                    break 'build;
+                }
+            }
+        }
+
+        // the node may have been removed while it was being linked on the upper levels
+        if node_ref.links[0].load(Ordering::Acquire, guard).tag() == 1 {
+            self.find(&node_ref.key, guard);
+        }
+        unsafe { Node::release(node, guard) };
+        true
+    }
+
+    /// Removes a key from the set. Returns `true` if the key was in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::SkipSet;
+    ///
+    /// let set = SkipSet::new();
+    /// set.insert(1);
+    /// assert!(set.remove(&1));
+    /// assert!(!set.remove(&1));
+    /// ```
+    pub fn remove<V>(&self, key: &V) -> bool
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        let guard = &crossbeam_epoch::pin();
+        let node = match self.find(key, guard).get_match(key) {
+            Some(node) => unsafe { node.deref() },
+            None => return false,
+        };
+
+        for level in (1..node.links.len()).rev() {
+            node.links[level].fetch_or(1, Ordering::AcqRel, guard);
+        }
+        if node.links[0].fetch_or(1, Ordering::AcqRel, guard).tag() == 1 {
+            return false;
+        }
+        self.len.fetch_sub(1, Ordering::Relaxed);
+
+        // unlink the node from every level
+        self.find(key, guard);
+        true
+    }
+
+    /// Checks if a key exists in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::SkipSet;
+    ///
+    /// let set = SkipSet::new();
+    /// set.insert(1);
+    /// assert!(!set.contains(&0));
+    /// assert!(set.contains(&1));
+    /// ```
+    pub fn contains<V>(&self, key: &V) -> bool
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        let guard = &crossbeam_epoch::pin();
+        self.find(key, guard).get_match(key).is_some()
+    }
+
+    /// Returns the approximate number of elements in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::SkipSet;
+    ///
+    /// let set = SkipSet::new();
+    /// set.insert(1);
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the approximate number of elements in the set is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::SkipSet;
+    ///
+    /// let set = SkipSet::new();
+    /// assert!(set.is_empty());
+    ///
+    /// set.insert(1);
+    /// assert!(!set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the keys of the set in a particular range. The iterator will yield
+    /// copies of the keys in ascending order.
+    ///
+    /// The iterator is weakly consistent: it yields every key that is in the range for the entire
+    /// lifetime of the iterator, never yields a key that is not in the range, and may or may not
+    /// yield keys that are inserted or removed concurrently. The iterator pins the current thread
+    /// until it is dropped, which delays the reclamation of removed keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::SkipSet;
+    ///
+    /// let set = SkipSet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    /// set.insert(3);
+    ///
+    /// let mut iterator = set.range(2..);
+    /// assert_eq!(iterator.next(), Some(2));
+    /// assert_eq!(iterator.next(), Some(3));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn range<R>(&self, range: R) -> SkipSetRange<'_, T>
+    where
+        T: Clone,
+        R: RangeBounds<T>,
+    {
+        let guard = crossbeam_epoch::pin();
+        let mut curr = match range.start_bound() {
+            Bound::Included(start) => self.find(start, &guard).succs[0],
+            Bound::Excluded(start) => self.find(start, &guard).succs[0],
+            Bound::Unbounded => self.head[0].load(Ordering::Acquire, &guard),
+        }
+        .as_raw();
+        if let Bound::Excluded(start) = range.start_bound() {
+            while let Some(node) = unsafe { curr.as_ref() } {
+                if &node.key > start {
+                    break;
+                }
+                curr = node.links[0].load(Ordering::Acquire, &guard).as_raw();
+            }
+        }
+        SkipSetRange {
+            _set: self,
+            guard,
+            curr,
+            end: range.end_bound().cloned(),
+        }
+    }
+
+    /// Returns an iterator over the set. The iterator will yield copies of the keys in ascending
+    /// order. The iterator has the same consistency guarantees as the iterator returned by
+    /// `range`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::SkipSet;
+    ///
+    /// let set = SkipSet::new();
+    /// set.insert(2);
+    /// set.insert(1);
+    ///
+    /// let mut iterator = set.iter();
+    /// assert_eq!(iterator.next(), Some(1));
+    /// assert_eq!(iterator.next(), Some(2));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> SkipSetRange<'_, T>
+    where
+        T: Clone,
+    {
+        self.range(..)
+    }
+}
+
+impl<T> Default for SkipSet<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for SkipSet<T> {
+    fn drop(&mut self) {
+        // every key that is in the set is linked on the bottom level, and every removed key has
+        // already been unlinked from every level
+        unsafe {
+            let guard = crossbeam_epoch::unprotected();
+            let mut curr = self.head[0].load(Ordering::Relaxed, guard);
+            while !curr.is_null() {
+                let node = curr.into_owned();
+                curr = node.links[0].load(Ordering::Relaxed, guard);
+            }
+        }
+    }
+}
+
+/// An iterator over a range of a `SkipSet`.
+///
+/// This iterator yields copies of the keys in ascending order.
+pub struct SkipSetRange<'a, T> {
+    _set: &'a SkipSet<T>,
+    guard: Guard,
+    curr: *const Node<T>,
+    end: Bound<T>,
+}
+
+impl<'a, T> Iterator for SkipSetRange<'a, T>
+where
+    T: Clone + Ord,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = unsafe { self.curr.as_ref() } {
+            let next = node.links[0].load(Ordering::Acquire, &self.guard);
+            self.curr = next.as_raw();
+            if next.tag() == 1 {
+                continue;
+            }
+            let is_in_range = match self.end {
+                Bound::Included(ref end) => node.key <= *end,
+                Bound::Excluded(ref end) => node.key < *end,
+                Bound::Unbounded => true,
+            };
+            if !is_in_range {
+                self.curr = ptr::null();
+                return None;
+            }
+            return Some(node.key.clone());
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SkipSet;
+    use crate::sync::harness::{self, Model};
+    use rand::{Rng, XorShiftRng};
+    use std::collections::BTreeSet;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    struct SkipSetModel(BTreeSet<u32>);
+
+    #[derive(Clone, Debug)]
+    enum SkipSetOp {
+        Insert(u32),
+        Remove(u32),
+        Contains(u32),
+    }
+
+    impl Model for SkipSetModel {
+        type Op = SkipSetOp;
+        type Ret = bool;
+
+        fn apply(&mut self, op: &SkipSetOp) -> bool {
+            match *op {
+                SkipSetOp::Insert(key) => self.0.insert(key),
+                SkipSetOp::Remove(key) => self.0.remove(&key),
+                SkipSetOp::Contains(key) => self.0.contains(&key),
+            }
+        }
+    }
+
+    #[test]
+    fn test_len_empty() {
+        let set: SkipSet<u32> = SkipSet::new();
+        assert_eq!(set.len(), 0);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let set = SkipSet::new();
+        let mut rng: XorShiftRng = rand::SeedableRng::from_seed([1, 1, 1, 1]);
+        let mut expected = BTreeSet::new();
+        for _ in 0..10_000 {
+            let key = rng.gen_range(0, 1000);
+            if rng.gen() {
+                assert_eq!(set.insert(key), expected.insert(key));
+            } else {
+                assert_eq!(set.remove(&key), expected.remove(&key));
+            }
+        }
+        assert_eq!(set.len(), expected.len());
+        assert_eq!(
+            set.iter().collect::<Vec<u32>>(),
+            expected.into_iter().collect::<Vec<u32>>()
+        );
+    }
+
+    #[test]
+    fn test_range() {
+        let set = SkipSet::new();
+        for key in (0..100).rev() {
+            set.insert(key);
+        }
+        assert_eq!(set.range(10..13).collect::<Vec<u32>>(), vec![10, 11, 12]);
+        assert_eq!(
+            set.range(10..=13).collect::<Vec<u32>>(),
+            vec![10, 11, 12, 13]
+        );
+        assert_eq!(set.range(97..).collect::<Vec<u32>>(), vec![97, 98, 99]);
+        assert_eq!(set.range(..3).collect::<Vec<u32>>(), vec![0, 1, 2]);
+        assert_eq!(set.range(100..).next(), None);
+    }
+
+    #[test]
+    fn test_range_excluded_start() {
+        use std::ops::Bound;
+
+        let set = SkipSet::new();
+        for key in 0..10 {
+            set.insert(key * 2);
+        }
+        let range = (Bound::Excluded(4), Bound::Included(8));
+        assert_eq!(set.range(range).collect::<Vec<u32>>(), vec![6, 8]);
+        let range = (Bound::Excluded(5), Bound::Unbounded);
+        assert_eq!(set.range(range).next(), Some(6));
+    }
+
+    #[test]
+    fn test_range_with_concurrent_removal() {
+        let set = SkipSet::new();
+        for key in 0..10 {
+            set.insert(key);
+        }
+        let mut iterator = set.range(..);
+        assert_eq!(iterator.next(), Some(0));
+        set.remove(&1);
+        set.remove(&5);
+        assert_eq!(iterator.collect::<Vec<u32>>(), vec![2, 3, 4, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_concurrent_insert_remove() {
+        let set = Arc::new(SkipSet::new());
+        let handles: Vec<_> = (0..8)
+            .map(|thread_index| {
+                let set = Arc::clone(&set);
+                thread::spawn(move || {
+                    for key in 0..2000 {
+                        set.insert(key * 8 + thread_index);
+                    }
+                    for key in 0..1000 {
+                        assert!(set.remove(&(key * 8 + thread_index)));
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(set.len(), 8000);
+        assert_eq!(
+            set.iter().collect::<Vec<u32>>(),
+            (8000..16000).collect::<Vec<u32>>()
+        );
+    }
+
+    #[test]
+    fn test_concurrent_contention() {
+        let set = Arc::new(SkipSet::new());
+        let handles: Vec<_> = (0..8)
+            .map(|thread_index| {
+                let set = Arc::clone(&set);
+                thread::spawn(move || {
+                    let mut rng: XorShiftRng =
+                        rand::SeedableRng::from_seed([thread_index + 1, 1, 1, 1]);
+                    for _ in 0..10_000 {
+                        let key = rng.gen_range(0, 16);
+                        if rng.gen() {
+                            set.insert(key);
+                        } else {
+                            set.remove(&key);
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let keys = set.iter().collect::<Vec<u32>>();
+        assert_eq!(set.len(), keys.len());
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_linearizable() {
+        harness::check_linearizable(
+            100,
+            4,
+            6,
+            &SkipSetModel(BTreeSet::new()),
+            SkipSet::new,
+            |rng| match rng.gen_range(0, 3) {
+                0 => SkipSetOp::Insert(rng.gen_range(0, 4)),
+                1 => SkipSetOp::Remove(rng.gen_range(0, 4)),
+                _ => SkipSetOp::Contains(rng.gen_range(0, 4)),
+            },
+            |set: &SkipSet<u32>, op: &SkipSetOp| match *op {
+                SkipSetOp::Insert(key) => set.insert(key),
+                SkipSetOp::Remove(key) => set.remove(&key),
+                SkipSetOp::Contains(key) => set.contains(&key),
+            },
+        );
+    }
+}
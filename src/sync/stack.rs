@@ -1,9 +1,11 @@
 use crossbeam_epoch::{self, Atomic, Owned};
+use std::mem::ManuallyDrop;
 use std::ptr;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
+// The value of a node is moved out when the node is popped, so it is not dropped with the node.
 struct Node<T> {
-    value: T,
+    value: ManuallyDrop<T>,
     next: Atomic<Node<T>>,
 }
 
@@ -61,7 +63,7 @@ impl<T> Stack<T> {
     /// ```
     pub fn push(&self, value: T) {
         let mut new_node = Owned::new(Node {
-            value,
+            value: ManuallyDrop::new(value),
             next: Atomic::null(),
         });
 
@@ -112,7 +114,7 @@ impl<T> Stack<T> {
                         unsafe {
                             self.len.fetch_sub(1, Ordering::Release);
                             guard.defer(move || head_shared.into_owned());
-                            return Some(ptr::read(&(*head).value));
+                            return Some(ptr::read(&*head.value));
                         }
                     }
                 }
@@ -162,9 +164,50 @@ impl<T> Default for Stack<T> {
     }
 }
 
+impl<T> Drop for Stack<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = crossbeam_epoch::unprotected();
+            let mut curr = self.head.load(Ordering::Relaxed, guard);
+            while !curr.is_null() {
+                let mut node = curr.into_owned();
+                curr = node.next.load(Ordering::Relaxed, guard);
+                ManuallyDrop::drop(&mut node.value);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Stack;
+    use crate::sync::harness::{self, Model};
+    use rand::Rng;
+    use std::sync::Arc;
+
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    struct StackModel(Vec<u32>);
+
+    #[derive(Clone, Debug)]
+    enum StackOp {
+        Push(u32),
+        TryPop,
+    }
+
+    impl Model for StackModel {
+        type Op = StackOp;
+        type Ret = Option<u32>;
+
+        fn apply(&mut self, op: &StackOp) -> Option<u32> {
+            match *op {
+                StackOp::Push(value) => {
+                    self.0.push(value);
+                    None
+                }
+                StackOp::TryPop => self.0.pop(),
+            }
+        }
+    }
 
     #[test]
     fn test_len_empty() {
@@ -189,4 +232,41 @@ mod tests {
         assert_eq!(stack.try_pop(), Some(0));
         assert_eq!(stack.len(), 0);
     }
+
+    #[test]
+    fn test_drop() {
+        let value = Arc::new(0);
+        let stack = Stack::new();
+        for _ in 0..10 {
+            stack.push(Arc::clone(&value));
+        }
+        stack.try_pop();
+        drop(stack);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_linearizable() {
+        harness::check_linearizable(
+            100,
+            4,
+            6,
+            &StackModel(Vec::new()),
+            Stack::new,
+            |rng| {
+                if rng.gen() {
+                    StackOp::Push(rng.gen_range(0, 4))
+                } else {
+                    StackOp::TryPop
+                }
+            },
+            |stack: &Stack<u32>, op: &StackOp| match *op {
+                StackOp::Push(value) => {
+                    stack.push(value);
+                    None
+                }
+                StackOp::TryPop => stack.try_pop(),
+            },
+        );
+    }
 }
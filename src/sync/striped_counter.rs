@@ -0,0 +1,236 @@
+use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+
+const DEFAULT_SHARD_COUNT: usize = 16;
+
+static NEXT_THREAD_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static THREAD_INDEX: usize = NEXT_THREAD_INDEX.fetch_add(1, Ordering::Relaxed);
+}
+
+// aligned to a cache line to avoid false sharing between shards
+#[repr(align(64))]
+struct Shard {
+    value: AtomicIsize,
+}
+
+/// A concurrent counter that spreads updates across multiple shards.
+///
+/// Each thread updates the shard that it is assigned to, so threads incrementing the counter
+/// concurrently rarely contend on the same cache line. Reading the counter sums every shard, so
+/// updates are cheap while reads are proportional to the number of shards. The sum is not a
+/// consistent snapshot if the counter is being updated concurrently.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::sync::StripedCounter;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let counter = Arc::new(StripedCounter::new());
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let counter = Arc::clone(&counter);
+///         thread::spawn(move || {
+///             for _ in 0..100 {
+///                 counter.increment();
+///             }
+///         })
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+/// assert_eq!(counter.sum(), 400);
+/// ```
+pub struct StripedCounter {
+    shards: Vec<Shard>,
+}
+
+impl StripedCounter {
+    /// Constructs a new `StripedCounter` with a value of zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::StripedCounter;
+    ///
+    /// let counter = StripedCounter::new();
+    /// assert_eq!(counter.sum(), 0);
+    /// ```
+    pub fn new() -> Self {
+        Self::with_shards(DEFAULT_SHARD_COUNT)
+    }
+
+    /// Constructs a new `StripedCounter` with a value of zero and a specific number of shards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard_count` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::StripedCounter;
+    ///
+    /// let counter = StripedCounter::with_shards(4);
+    /// assert_eq!(counter.sum(), 0);
+    /// ```
+    pub fn with_shards(shard_count: usize) -> Self {
+        assert!(shard_count > 0);
+        StripedCounter {
+            shards: (0..shard_count)
+                .map(|_| Shard {
+                    value: AtomicIsize::new(0),
+                })
+                .collect(),
+        }
+    }
+
+    fn get_shard(&self) -> &Shard {
+        let index = THREAD_INDEX.with(|index| *index);
+        &self.shards[index % self.shards.len()]
+    }
+
+    /// Adds `delta` to the counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::StripedCounter;
+    ///
+    /// let counter = StripedCounter::new();
+    /// counter.add(5);
+    /// counter.add(-2);
+    /// assert_eq!(counter.sum(), 3);
+    /// ```
+    pub fn add(&self, delta: isize) {
+        self.get_shard().value.fetch_add(delta, Ordering::Relaxed);
+    }
+
+    /// Increments the counter by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::StripedCounter;
+    ///
+    /// let counter = StripedCounter::new();
+    /// counter.increment();
+    /// assert_eq!(counter.sum(), 1);
+    /// ```
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    /// Decrements the counter by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::StripedCounter;
+    ///
+    /// let counter = StripedCounter::new();
+    /// counter.decrement();
+    /// assert_eq!(counter.sum(), -1);
+    /// ```
+    pub fn decrement(&self) {
+        self.add(-1);
+    }
+
+    /// Returns the value of the counter by summing all shards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::StripedCounter;
+    ///
+    /// let counter = StripedCounter::new();
+    /// counter.add(2);
+    /// assert_eq!(counter.sum(), 2);
+    /// ```
+    pub fn sum(&self) -> isize {
+        self.shards
+            .iter()
+            .map(|shard| shard.value.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Resets the counter to zero and returns the value of the counter before it was reset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::sync::StripedCounter;
+    ///
+    /// let counter = StripedCounter::new();
+    /// counter.add(2);
+    /// assert_eq!(counter.reset(), 2);
+    /// assert_eq!(counter.sum(), 0);
+    /// ```
+    pub fn reset(&self) -> isize {
+        self.shards
+            .iter()
+            .map(|shard| shard.value.swap(0, Ordering::Relaxed))
+            .sum()
+    }
+}
+
+impl Default for StripedCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StripedCounter;
+    use std::mem;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_shard_alignment() {
+        assert_eq!(mem::align_of::<super::Shard>(), 64);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_shards() {
+        StripedCounter::with_shards(0);
+    }
+
+    #[test]
+    fn test_add() {
+        let counter = StripedCounter::with_shards(1);
+        counter.add(3);
+        counter.increment();
+        counter.decrement();
+        assert_eq!(counter.sum(), 3);
+    }
+
+    #[test]
+    fn test_concurrent_add() {
+        let counter = Arc::new(StripedCounter::with_shards(4));
+        let handles: Vec<_> = (0..8)
+            .map(|thread_index| {
+                let counter = Arc::clone(&counter);
+                thread::spawn(move || {
+                    for _ in 0..10_000 {
+                        counter.add(thread_index);
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(counter.sum(), (0..8).sum::<isize>() * 10_000);
+        assert_eq!(counter.reset(), (0..8).sum::<isize>() * 10_000);
+        assert_eq!(counter.sum(), 0);
+    }
+}
@@ -0,0 +1,433 @@
+//! Approximate frequent items of a stream using the Space-Saving algorithm.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+
+#[derive(Clone)]
+struct Counter<T> {
+    item: T,
+    count: u64,
+    error: u64,
+}
+
+/// A summary of the most frequent items of a stream using the Space-Saving algorithm.
+///
+/// The summary monitors at most `capacity` items, each with a counter and a maximum
+/// overestimation error. Inserting an item that is monitored increments its counter. Otherwise,
+/// if the summary is full, the item with the minimum counter is replaced by the new item, which
+/// inherits the minimum counter as its error. Every item that occurs more than `n / capacity`
+/// times in a stream of `n` items is guaranteed to be monitored, and the true count of a monitored
+/// item is between `count - error` and `count`. The counters are ordered by a `BTreeSet`, so
+/// inserting an item takes logarithmic time in `capacity`.
+///
+/// Two summaries can be merged, which allows summaries of partitions of a stream to be combined.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::top_k::TopK;
+///
+/// let mut top_k = TopK::new(2);
+/// for item in &["a", "b", "a", "c", "a"] {
+///     top_k.insert(*item);
+/// }
+///
+/// assert_eq!(top_k.query(), vec![("a", 3, 0), ("c", 2, 1)]);
+/// assert_eq!(top_k.estimate(&"b"), None);
+/// assert_eq!(top_k.total(), 5);
+/// ```
+#[derive(Clone)]
+pub struct TopK<T> {
+    capacity: usize,
+    counters: Vec<Counter<T>>,
+    indices: HashMap<T, usize>,
+    order: BTreeSet<(u64, usize)>,
+    total: u64,
+}
+
+impl<T> TopK<T>
+where
+    T: Hash + Eq + Clone,
+{
+    /// Constructs a new, empty `TopK<T>` that monitors at most `capacity` items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::top_k::TopK;
+    ///
+    /// let top_k: TopK<u32> = TopK::new(10);
+    /// assert_eq!(top_k.capacity(), 10);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Error: capacity must be greater than zero.");
+        TopK {
+            capacity,
+            counters: Vec::with_capacity(capacity),
+            indices: HashMap::with_capacity(capacity),
+            order: BTreeSet::new(),
+            total: 0,
+        }
+    }
+
+    /// Inserts an occurrence of an item into the summary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::top_k::TopK;
+    ///
+    /// let mut top_k = TopK::new(10);
+    /// top_k.insert("foo");
+    /// assert_eq!(top_k.estimate(&"foo"), Some((1, 0)));
+    /// ```
+    pub fn insert(&mut self, item: T) {
+        self.insert_many(item, 1);
+    }
+
+    /// Inserts `count` occurrences of an item into the summary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::top_k::TopK;
+    ///
+    /// let mut top_k = TopK::new(1);
+    /// top_k.insert_many("foo", 3);
+    /// top_k.insert_many("bar", 2);
+    /// assert_eq!(top_k.estimate(&"foo"), None);
+    /// assert_eq!(top_k.estimate(&"bar"), Some((5, 3)));
+    /// ```
+    pub fn insert_many(&mut self, item: T, count: u64) {
+        if count == 0 {
+            return;
+        }
+        self.total += count;
+
+        if let Some(&index) = self.indices.get(&item) {
+            self.add_count(index, count);
+            return;
+        }
+
+        if self.counters.len() < self.capacity {
+            let index = self.counters.len();
+            self.order.insert((count, index));
+            self.indices.insert(item.clone(), index);
+            self.counters.push(Counter {
+                item,
+                count,
+                error: 0,
+            });
+            return;
+        }
+
+        let (min_count, index) = *self
+            .order
+            .iter()
+            .next()
+            .expect("Expected non-empty summary.");
+        let counter = &mut self.counters[index];
+        self.indices.remove(&counter.item);
+        self.indices.insert(item.clone(), index);
+        counter.item = item;
+        counter.error = min_count;
+        self.add_count(index, count);
+    }
+
+    fn add_count(&mut self, index: usize, count: u64) {
+        let counter = &mut self.counters[index];
+        self.order.remove(&(counter.count, index));
+        counter.count += count;
+        self.order.insert((counter.count, index));
+    }
+
+    /// Returns the estimated count and the maximum overestimation error of an item. Returns
+    /// `None` if the item is not monitored by the summary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::top_k::TopK;
+    ///
+    /// let mut top_k = TopK::new(10);
+    /// top_k.insert("foo");
+    /// top_k.insert("foo");
+    /// assert_eq!(top_k.estimate(&"foo"), Some((2, 0)));
+    /// assert_eq!(top_k.estimate(&"bar"), None);
+    /// ```
+    pub fn estimate(&self, item: &T) -> Option<(u64, u64)> {
+        self.indices.get(item).map(|index| {
+            let counter = &self.counters[*index];
+            (counter.count, counter.error)
+        })
+    }
+
+    /// Returns the monitored items with their estimated counts and maximum overestimation errors.
+    /// The items are sorted in descending order of their estimated counts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::top_k::TopK;
+    ///
+    /// let mut top_k = TopK::new(10);
+    /// top_k.insert("foo");
+    /// top_k.insert("bar");
+    /// top_k.insert("bar");
+    /// assert_eq!(top_k.query(), vec![("bar", 2, 0), ("foo", 1, 0)]);
+    /// ```
+    pub fn query(&self) -> Vec<(T, u64, u64)> {
+        self.order
+            .iter()
+            .rev()
+            .map(|(_, index)| {
+                let counter = &self.counters[*index];
+                (counter.item.clone(), counter.count, counter.error)
+            })
+            .collect()
+    }
+
+    /// Merges another summary into this summary. An item that is not monitored by a full summary
+    /// is assumed to have occurred as many times as the minimum counter of that summary, so the
+    /// guarantees of both summaries hold for the merged summary. The capacity of this summary is
+    /// retained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::top_k::TopK;
+    ///
+    /// let mut top_k = TopK::new(10);
+    /// let mut other = TopK::new(10);
+    /// top_k.insert_many("foo", 2);
+    /// other.insert_many("foo", 3);
+    /// other.insert_many("bar", 1);
+    ///
+    /// top_k.merge(&other);
+    /// assert_eq!(top_k.query(), vec![("foo", 5, 0), ("bar", 1, 0)]);
+    /// assert_eq!(top_k.total(), 6);
+    /// ```
+    pub fn merge(&mut self, other: &Self) {
+        let min_count = self.min_count();
+        let other_min_count = other.min_count();
+        let mut merged: HashMap<T, (u64, u64)> = HashMap::new();
+
+        for counter in &self.counters {
+            let (count, error) = other
+                .estimate(&counter.item)
+                .unwrap_or((other_min_count, other_min_count));
+            merged.insert(
+                counter.item.clone(),
+                (counter.count + count, counter.error + error),
+            );
+        }
+        for counter in &other.counters {
+            if !self.indices.contains_key(&counter.item) {
+                merged.insert(
+                    counter.item.clone(),
+                    (counter.count + min_count, counter.error + min_count),
+                );
+            }
+        }
+
+        let mut merged: Vec<(T, (u64, u64))> = merged.into_iter().collect();
+        merged.sort_by_key(|pair| Reverse((pair.1).0));
+        merged.truncate(self.capacity);
+
+        let total = self.total + other.total;
+        self.clear();
+        self.total = total;
+        for (index, (item, (count, error))) in merged.into_iter().enumerate() {
+            self.order.insert((count, index));
+            self.indices.insert(item.clone(), index);
+            self.counters.push(Counter { item, count, error });
+        }
+    }
+
+    // Returns the count that an unmonitored item could have occurred at most.
+    fn min_count(&self) -> u64 {
+        if self.counters.len() < self.capacity {
+            return 0;
+        }
+        self.order.iter().next().map_or(0, |pair| pair.0)
+    }
+
+    /// Returns the maximum number of items monitored by the summary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::top_k::TopK;
+    ///
+    /// let top_k: TopK<u32> = TopK::new(10);
+    /// assert_eq!(top_k.capacity(), 10);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of items monitored by the summary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::top_k::TopK;
+    ///
+    /// let mut top_k = TopK::new(10);
+    /// top_k.insert("foo");
+    /// top_k.insert("foo");
+    /// assert_eq!(top_k.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Returns `true` if the summary is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::top_k::TopK;
+    ///
+    /// let top_k: TopK<u32> = TopK::new(10);
+    /// assert!(top_k.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty()
+    }
+
+    /// Returns the total number of occurrences inserted into the summary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::top_k::TopK;
+    ///
+    /// let mut top_k = TopK::new(1);
+    /// top_k.insert("foo");
+    /// top_k.insert("bar");
+    /// assert_eq!(top_k.total(), 2);
+    /// ```
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Clears the summary, removing all items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::top_k::TopK;
+    ///
+    /// let mut top_k = TopK::new(10);
+    /// top_k.insert("foo");
+    /// top_k.clear();
+    /// assert!(top_k.is_empty());
+    /// assert_eq!(top_k.total(), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        self.counters.clear();
+        self.indices.clear();
+        self.order.clear();
+        self.total = 0;
+    }
+}
+
+impl<T> Extend<T> for TopK<T>
+where
+    T: Hash + Eq + Clone,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TopK;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+    use std::collections::HashMap;
+
+    #[test]
+    #[should_panic]
+    fn test_zero_capacity() {
+        let _: TopK<u32> = TopK::new(0);
+    }
+
+    #[test]
+    fn test_len_empty() {
+        let top_k: TopK<u32> = TopK::new(10);
+        assert_eq!(top_k.len(), 0);
+        assert!(top_k.is_empty());
+        assert_eq!(top_k.query(), vec![]);
+    }
+
+    #[test]
+    fn test_exact_under_capacity() {
+        let mut top_k = TopK::new(10);
+        top_k.extend(vec![1, 2, 2, 3, 3, 3]);
+        assert_eq!(top_k.query(), vec![(3, 3, 0), (2, 2, 0), (1, 1, 0)]);
+    }
+
+    #[test]
+    fn test_eviction() {
+        let mut top_k = TopK::new(2);
+        top_k.extend(vec![1, 1, 2, 3]);
+        assert_eq!(top_k.estimate(&1), Some((2, 0)));
+        assert_eq!(top_k.estimate(&2), None);
+        assert_eq!(top_k.estimate(&3), Some((2, 1)));
+    }
+
+    fn check_guarantees(top_k: &TopK<u32>, counts: &HashMap<u32, u64>, total: u64) {
+        assert_eq!(top_k.total(), total);
+        for (item, count) in counts {
+            match top_k.estimate(item) {
+                Some((estimate, error)) => {
+                    assert!(estimate >= *count);
+                    assert!(estimate - error <= *count);
+                }
+                None => assert!(*count <= total / top_k.capacity() as u64),
+            }
+        }
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng: XorShiftRng = SeedableRng::from_seed([1, 1, 1, 1]);
+        let mut top_k = TopK::new(20);
+        let mut other = TopK::new(20);
+        let mut counts = HashMap::new();
+
+        for i in 0..10000 {
+            // skew the distribution so that a few items are frequent
+            let item = if rng.gen_weighted_bool(2) {
+                rng.gen_range(0, 5)
+            } else {
+                rng.gen_range(0, 1000)
+            };
+            *counts.entry(item).or_insert(0) += 1;
+            if i % 2 == 0 {
+                top_k.insert(item);
+            } else {
+                other.insert(item);
+            }
+        }
+
+        top_k.merge(&other);
+        check_guarantees(&top_k, &counts, 10000);
+        let top: Vec<u32> = top_k.query().into_iter().take(5).map(|x| x.0).collect();
+        for item in 0..5 {
+            assert!(top.contains(&item));
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use crate::treap::node::ImplicitNode;
-use std::cmp::Ordering;
-use std::mem;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::mem;
 
 pub type Tree<T> = Option<Box<ImplicitNode<T>>>;
 
@@ -46,6 +48,38 @@ pub fn split<T>(tree: &mut Tree<T>, index: usize, left_inclusive: bool) -> Tree<
     }
 }
 
+// Builds an implicit treap from nodes in order in linear time by maintaining the right spine of the
+// tree on a stack.
+pub fn build<T, I>(nodes: I) -> Tree<T>
+where
+    I: IntoIterator<Item = ImplicitNode<T>>,
+{
+    let mut spine: Vec<Box<ImplicitNode<T>>> = Vec::new();
+    for node in nodes {
+        let mut new_node = Box::new(node);
+        let mut left = None;
+        while let Some(mut top) = spine.pop() {
+            if top.priority >= new_node.priority {
+                spine.push(top);
+                break;
+            }
+            top.right = left;
+            top.update();
+            left = Some(top);
+        }
+        new_node.left = left;
+        spine.push(new_node);
+    }
+
+    let mut ret = None;
+    while let Some(mut top) = spine.pop() {
+        top.right = ret;
+        top.update();
+        ret = Some(top);
+    }
+    ret
+}
+
 pub fn insert<T>(tree: &mut Tree<T>, index: usize, new_node: ImplicitNode<T>) {
     assert!(1 <= index && index <= len(tree) + 1);
     let right = split(tree, index, true);
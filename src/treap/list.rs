@@ -1,8 +1,11 @@
+use crate::seed;
 use crate::treap::implicit_tree;
 use crate::treap::node::ImplicitNode;
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+use core::ops::{Add, Bound, Index, IndexMut, RangeBounds};
 use rand::Rng;
 use rand::XorShiftRng;
-use std::ops::{Add, Index, IndexMut};
 
 /// A list implemented using an implicit treap.
 ///
@@ -50,10 +53,37 @@ impl<T> TreapList<T> {
     /// let list: TreapList<u32> = TreapList::new();
     /// ```
     pub fn new() -> Self {
-        TreapList {
-            tree: None,
-            rng: XorShiftRng::new_unseeded(),
-        }
+        Self::with_rng(seed::new_rng())
+    }
+
+    /// Constructs a new, empty `TreapList<T>` that uses `rng` to generate the priorities of its
+    /// nodes. Seeding `rng` makes the internal structure reproducible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapList;
+    /// use rand::{SeedableRng, XorShiftRng};
+    ///
+    /// let list: TreapList<u32> = TreapList::with_rng(XorShiftRng::from_seed([1, 1, 1, 1]));
+    /// ```
+    pub fn with_rng(rng: XorShiftRng) -> Self {
+        TreapList { tree: None, rng }
+    }
+
+    /// Constructs a new, empty `TreapList<T>` that generates the priorities of its nodes with a
+    /// random number generator seeded with `seed`. Lists constructed with the same seed have the
+    /// same internal structure after the same sequence of operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapList;
+    ///
+    /// let list: TreapList<u32> = TreapList::with_seed(1);
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(seed::rng_from_seed(seed))
     }
 
     /// Inserts a value into the list at a particular index, shifting elements one position to the
@@ -247,6 +277,102 @@ impl<T> TreapList<T> {
         self.tree = None;
     }
 
+    /// Shortens the list to its first `len` values, dropping the rest. Does nothing if `len` is at
+    /// least the length of the list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapList;
+    ///
+    /// let mut list: TreapList<u32> = (0..5).collect();
+    /// list.truncate(2);
+    /// assert_eq!(list.iter().collect::<Vec<&u32>>(), vec![&0, &1]);
+    /// ```
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            implicit_tree::split(&mut self.tree, len + 1, true);
+        }
+    }
+
+    /// Clones and appends all values of a slice to the back of the list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapList;
+    ///
+    /// let mut list = TreapList::new();
+    /// list.push_back(1);
+    /// list.extend_from_slice(&[2, 3]);
+    /// assert_eq!(list.iter().collect::<Vec<&u32>>(), vec![&1, &2, &3]);
+    /// ```
+    pub fn extend_from_slice(&mut self, values: &[T])
+    where
+        T: Clone,
+    {
+        self.extend(values.iter().cloned());
+    }
+
+    /// Returns an iterator over the values of the list whose indexes are in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if the end of the range is
+    /// greater than the length of the list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapList;
+    ///
+    /// let list: TreapList<u32> = (0..5).collect();
+    /// assert_eq!(list.get_range(1..3).collect::<Vec<&u32>>(), vec![&1, &2]);
+    /// assert_eq!(list.get_range(3..).collect::<Vec<&u32>>(), vec![&3, &4]);
+    /// ```
+    pub fn get_range<R>(&self, range: R) -> TreapListIter<'_, T>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        assert!(
+            start <= end && end <= self.len(),
+            "Error: range out of bounds."
+        );
+
+        // descend to the first value of the range, keeping the ancestors that come after it
+        let mut stack = Vec::new();
+        let mut curr_tree = &self.tree;
+        let mut index = start;
+        while let Some(ref node) = curr_tree {
+            let left_len = implicit_tree::len(&node.left);
+            if index < left_len {
+                stack.push(&**node);
+                curr_tree = &node.left;
+            } else if index == left_len {
+                stack.push(&**node);
+                break;
+            } else {
+                index -= left_len + 1;
+                curr_tree = &node.right;
+            }
+        }
+        TreapListIter {
+            current: None,
+            stack,
+            remaining: end - start,
+        }
+    }
+
     /// Returns an iterator over the list.
     ///
     /// # Examples
@@ -265,8 +391,9 @@ impl<T> TreapList<T> {
     /// ```
     pub fn iter(&self) -> TreapListIter<'_, T> {
         TreapListIter {
-            current: &self.tree,
+            current: self.tree.as_deref(),
             stack: Vec::new(),
+            remaining: self.len(),
         }
     }
 
@@ -362,8 +489,9 @@ impl<T> Iterator for TreapListIntoIter<T> {
 ///
 /// This iterator traverses the elements of the list in-order and yields immutable references.
 pub struct TreapListIter<'a, T> {
-    current: &'a implicit_tree::Tree<T>,
+    current: Option<&'a ImplicitNode<T>>,
     stack: Vec<&'a ImplicitNode<T>>,
+    remaining: usize,
 }
 
 impl<'a, T> Iterator for TreapListIter<'a, T>
@@ -373,8 +501,11 @@ where
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(ref node) = self.current {
-            self.current = &node.left;
+        if self.remaining == 0 {
+            return None;
+        }
+        while let Some(node) = self.current {
+            self.current = node.left.as_deref();
             self.stack.push(node);
         }
         self.stack.pop().map(|node| {
@@ -383,10 +514,15 @@ where
                 ref right,
                 ..
             } = node;
-            self.current = right;
+            self.current = right.as_deref();
+            self.remaining -= 1;
             value
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
 }
 
 type BorrowedTreeMut<'a, T> = Option<&'a mut ImplicitNode<T>>;
@@ -430,6 +566,35 @@ impl<T> Default for TreapList<T> {
     }
 }
 
+impl<T> FromIterator<T> for TreapList<T> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for TreapList<T> {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let TreapList {
+            ref mut tree,
+            ref mut rng,
+        } = self;
+        // the new values are built into a treap in linear time before being merged at the back
+        let new_tree = implicit_tree::build(
+            iter.into_iter()
+                .map(|value| ImplicitNode::new(value, rng.next_u32())),
+        );
+        implicit_tree::merge(tree, new_tree);
+    }
+}
+
 impl<T> Add for TreapList<T> {
     type Output = TreapList<T>;
 
@@ -585,4 +750,55 @@ mod tests {
 
         assert_eq!(list.iter().collect::<Vec<&u32>>(), vec![&3, &4, &2]);
     }
+
+    #[test]
+    fn test_from_iter_extend() {
+        let mut list: TreapList<u32> = (0..100).collect();
+        list.extend(100..200);
+        list.extend_from_slice(&[200, 201]);
+        assert_eq!(list.len(), 202);
+        assert_eq!(
+            list.iter().cloned().collect::<Vec<u32>>(),
+            (0..202).collect::<Vec<u32>>(),
+        );
+        for index in 0..202 {
+            assert_eq!(list.get(index), Some(&(index as u32)));
+        }
+    }
+
+    #[test]
+    fn test_get_range() {
+        let list: TreapList<u32> = (0..100).collect();
+        for start in 0..=100 {
+            for end in start..=100 {
+                assert_eq!(
+                    list.get_range(start..end).cloned().collect::<Vec<u32>>(),
+                    (start as u32..end as u32).collect::<Vec<u32>>(),
+                );
+            }
+        }
+        assert_eq!(list.get_range(..=1).collect::<Vec<&u32>>(), vec![&0, &1]);
+        assert_eq!(list.get_range(..).count(), 100);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_get_range_out_of_bounds() {
+        let list: TreapList<u32> = (0..10).collect();
+        list.get_range(5..11);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut list: TreapList<u32> = (0..10).collect();
+        list.truncate(20);
+        assert_eq!(list.len(), 10);
+        list.truncate(4);
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.iter().collect::<Vec<&u32>>(), vec![&0, &1, &2, &3]);
+        list.push_back(4);
+        assert_eq!(list.get(4), Some(&4));
+        list.truncate(0);
+        assert!(list.is_empty());
+    }
 }
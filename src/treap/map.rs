@@ -1,10 +1,17 @@
+use crate::compare::{Compare, Natural};
 use crate::entry::Entry;
+use crate::ordered::OrderedMap;
+use crate::seed;
+use crate::set_ops::{DifferenceIter, IntersectionIter, SymmetricDifferenceIter, UnionIter};
 use crate::treap::node::Node;
 use crate::treap::tree;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::iter::FromIterator;
+use core::ops::{Add, Bound, Index, IndexMut, RangeBounds, Sub};
 use rand::Rng;
 use rand::XorShiftRng;
-use std::borrow::Borrow;
-use std::ops::{Add, Index, IndexMut, Sub};
 
 /// An ordered map implemented using a treap.
 ///
@@ -34,9 +41,11 @@ use std::ops::{Add, Index, IndexMut, Sub};
 /// assert_eq!(map.remove(&0), Some((0, 2)));
 /// assert_eq!(map.remove(&1), None);
 /// ```
-pub struct TreapMap<T, U> {
+#[derive(Clone)]
+pub struct TreapMap<T, U, C = Natural> {
     tree: tree::Tree<T, U>,
     rng: XorShiftRng,
+    compare: C,
 }
 
 impl<T, U> TreapMap<T, U> {
@@ -50,9 +59,77 @@ impl<T, U> TreapMap<T, U> {
     /// let map: TreapMap<u32, u32> = TreapMap::new();
     /// ```
     pub fn new() -> Self {
+        Self::with_rng(seed::new_rng())
+    }
+
+    /// Constructs a new, empty `TreapMap<T, U>` that uses `rng` to generate the priorities of its
+    /// nodes. Seeding `rng` makes the internal structure reproducible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    /// use rand::{SeedableRng, XorShiftRng};
+    ///
+    /// let map: TreapMap<u32, u32> = TreapMap::with_rng(XorShiftRng::from_seed([1, 1, 1, 1]));
+    /// ```
+    pub fn with_rng(rng: XorShiftRng) -> Self {
+        Self::with_rng_and_comparator(rng, Natural)
+    }
+
+    /// Constructs a new, empty `TreapMap<T, U>` that generates the priorities of its nodes with a
+    /// random number generator seeded with `seed`. Maps constructed with the same seed have the
+    /// same internal structure after the same sequence of operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let map: TreapMap<u32, u32> = TreapMap::with_seed(1);
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(seed::rng_from_seed(seed))
+    }
+}
+
+impl<T, U, C> TreapMap<T, U, C> {
+    /// Constructs a new, empty `TreapMap<T, U, C>` that uses `compare` to order keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut map = TreapMap::with_comparator(|a: &u32, b: &u32| b.cmp(a));
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.min(), Some(&2));
+    /// assert_eq!(map.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&2, &2), (&1, &1)]);
+    /// ```
+    pub fn with_comparator(compare: C) -> Self {
+        Self::with_rng_and_comparator(seed::new_rng(), compare)
+    }
+
+    /// Constructs a new, empty `TreapMap<T, U, C>` that uses `rng` to generate the priorities of
+    /// its nodes and `compare` to order keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    /// use rand::{SeedableRng, XorShiftRng};
+    ///
+    /// let rng = XorShiftRng::from_seed([1, 1, 1, 1]);
+    /// let mut map = TreapMap::with_rng_and_comparator(rng, |a: &u32, b: &u32| b.cmp(a));
+    /// map.insert(1, 1);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// ```
+    pub fn with_rng_and_comparator(rng: XorShiftRng, compare: C) -> Self {
         TreapMap {
             tree: None,
-            rng: XorShiftRng::new_unseeded(),
+            rng,
+            compare,
         }
     }
 
@@ -72,14 +149,15 @@ impl<T, U> TreapMap<T, U> {
     /// ```
     pub fn insert(&mut self, key: T, value: U) -> Option<(T, U)>
     where
-        T: Ord,
+        C: Compare<T>,
     {
         let TreapMap {
             ref mut tree,
             ref mut rng,
+            ref compare,
         } = self;
         let new_node = Node::new(key, value, rng.next_u32());
-        tree::insert(tree, new_node).and_then(|entry| {
+        tree::insert(tree, new_node, compare).and_then(|entry| {
             let Entry { key, value } = entry;
             Some((key, value))
         })
@@ -101,10 +179,15 @@ impl<T, U> TreapMap<T, U> {
     pub fn remove<V>(&mut self, key: &V) -> Option<(T, U)>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        let TreapMap { ref mut tree, .. } = self;
-        tree::remove(tree, key).and_then(|entry| {
+        let TreapMap {
+            ref mut tree,
+            ref compare,
+            ..
+        } = self;
+        tree::remove(tree, key, compare).and_then(|entry| {
             let Entry { key, value } = entry;
             Some((key, value))
         })
@@ -125,7 +208,8 @@ impl<T, U> TreapMap<T, U> {
     pub fn contains_key<V>(&self, key: &V) -> bool
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
         self.get(key).is_some()
     }
@@ -146,9 +230,10 @@ impl<T, U> TreapMap<T, U> {
     pub fn get<V>(&self, key: &V) -> Option<&U>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        tree::get(&self.tree, key).map(|entry| &entry.value)
+        tree::get(&self.tree, key, &self.compare).map(|entry| &entry.value)
     }
 
     /// Returns a mutable reference to the value associated with a particular key. Returns `None`
@@ -167,9 +252,10 @@ impl<T, U> TreapMap<T, U> {
     pub fn get_mut<V>(&mut self, key: &V) -> Option<&mut U>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        tree::get_mut(&mut self.tree, key).map(|entry| &mut entry.value)
+        tree::get_mut(&mut self.tree, key, &self.compare).map(|entry| &mut entry.value)
     }
 
     /// Returns the number of elements in the map.
@@ -221,6 +307,54 @@ impl<T, U> TreapMap<T, U> {
         self.tree = None;
     }
 
+    /// Retains only the key-value pairs that satisfy a predicate. The key-value pairs are visited
+    /// in ascending order of their keys. The children of each removed node are merged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut map = TreapMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// map.insert(3, 3);
+    ///
+    /// map.retain(|key, value| {
+    ///     *value += 1;
+    ///     key % 2 == 1
+    /// });
+    /// assert_eq!(map.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&1, &2), (&3, &4)]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T, &mut U) -> bool,
+    {
+        tree::retain(&mut self.tree, &mut f);
+    }
+
+    /// Clears the map, returning all key-value pairs as an iterator. The iterator yields the
+    /// key-value pairs in ascending order of their keys, and the map can be reused afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut map = TreapMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    ///
+    /// assert_eq!(map.drain().collect::<Vec<(u32, u32)>>(), vec![(1, 1), (2, 2)]);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> TreapMapIntoIter<T, U> {
+        TreapMapIntoIter {
+            current: self.tree.take(),
+            stack: Vec::new(),
+        }
+    }
+
     /// Returns a key in the map that is less than or equal to a particular key. Returns `None` if
     /// such a key does not exist.
     ///
@@ -237,9 +371,10 @@ impl<T, U> TreapMap<T, U> {
     pub fn floor<V>(&self, key: &V) -> Option<&T>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        tree::floor(&self.tree, key).map(|entry| &entry.key)
+        tree::floor(&self.tree, key, true, &self.compare).map(|entry| &entry.key)
     }
 
     /// Returns a key in the map that is greater than or equal to a particular key. Returns `None`
@@ -258,9 +393,152 @@ impl<T, U> TreapMap<T, U> {
     pub fn ceil<V>(&self, key: &V) -> Option<&T>
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        tree::ceil(&self.tree, key, true, &self.compare).map(|entry| &entry.key)
+    }
+
+    /// Returns the key-value pair in the map with the greatest key that is less than or equal to a
+    /// particular key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut map = TreapMap::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.floor_entry(&0), None);
+    /// assert_eq!(map.floor_entry(&2), Some((&1, &1)));
+    /// ```
+    pub fn floor_entry<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
     {
-        tree::ceil(&self.tree, key).map(|entry| &entry.key)
+        tree::floor(&self.tree, key, true, &self.compare).map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Returns the key and a mutable reference to the value of the key-value pair in the map with
+    /// the greatest key that is less than or equal to a particular key. Returns `None` if such a
+    /// key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut map = TreapMap::new();
+    /// map.insert(1, 1);
+    /// if let Some((_, value)) = map.floor_entry_mut(&2) {
+    ///     *value = 2;
+    /// }
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// ```
+    pub fn floor_entry_mut<V>(&mut self, key: &V) -> Option<(&T, &mut U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        tree::floor_mut(&mut self.tree, key, true, &self.compare)
+            .map(|entry| (&entry.key, &mut entry.value))
+    }
+
+    /// Returns the key-value pair in the map with the least key that is greater than or equal to a
+    /// particular key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut map = TreapMap::new();
+    /// map.insert(1, 1);
+    /// assert_eq!(map.ceil_entry(&2), None);
+    /// assert_eq!(map.ceil_entry(&0), Some((&1, &1)));
+    /// ```
+    pub fn ceil_entry<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        tree::ceil(&self.tree, key, true, &self.compare).map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Returns the key and a mutable reference to the value of the key-value pair in the map with
+    /// the least key that is greater than or equal to a particular key. Returns `None` if such a
+    /// key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut map = TreapMap::new();
+    /// map.insert(1, 1);
+    /// if let Some((_, value)) = map.ceil_entry_mut(&0) {
+    ///     *value = 2;
+    /// }
+    /// assert_eq!(map.get(&1), Some(&2));
+    /// ```
+    pub fn ceil_entry_mut<V>(&mut self, key: &V) -> Option<(&T, &mut U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        tree::ceil_mut(&mut self.tree, key, true, &self.compare)
+            .map(|entry| (&entry.key, &mut entry.value))
+    }
+
+    /// Returns the key-value pair in the map with the greatest key that is less than a particular
+    /// key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut map = TreapMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.next_below(&1), None);
+    /// assert_eq!(map.next_below(&2), Some((&1, &1)));
+    /// ```
+    pub fn next_below<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        tree::floor(&self.tree, key, false, &self.compare).map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Returns the key-value pair in the map with the least key that is greater than a particular
+    /// key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut map = TreapMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(2, 2);
+    /// assert_eq!(map.next_above(&2), None);
+    /// assert_eq!(map.next_above(&1), Some((&2, &2)));
+    /// ```
+    pub fn next_above<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V>,
+    {
+        tree::ceil(&self.tree, key, false, &self.compare).map(|entry| (&entry.key, &entry.value))
     }
 
     /// Returns the minimum key of the map. Returns `None` if the map is empty.
@@ -275,10 +553,7 @@ impl<T, U> TreapMap<T, U> {
     /// map.insert(3, 3);
     /// assert_eq!(map.min(), Some(&1));
     /// ```
-    pub fn min(&self) -> Option<&T>
-    where
-        T: Ord,
-    {
+    pub fn min(&self) -> Option<&T> {
         tree::min(&self.tree).map(|entry| &entry.key)
     }
 
@@ -294,13 +569,54 @@ impl<T, U> TreapMap<T, U> {
     /// map.insert(3, 3);
     /// assert_eq!(map.max(), Some(&3));
     /// ```
-    pub fn max(&self) -> Option<&T>
-    where
-        T: Ord,
-    {
+    pub fn max(&self) -> Option<&T> {
         tree::max(&self.tree).map(|entry| &entry.key)
     }
 
+    /// Removes and returns the key-value pair with the minimum key in the map. Returns `None` if
+    /// the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut map = TreapMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.pop_min(), Some((1, 1)));
+    /// assert_eq!(map.pop_min(), Some((3, 3)));
+    /// assert_eq!(map.pop_min(), None);
+    /// ```
+    pub fn pop_min(&mut self) -> Option<(T, U)> {
+        tree::pop_min(&mut self.tree).map(|entry| {
+            let Entry { key, value } = entry;
+            (key, value)
+        })
+    }
+
+    /// Removes and returns the key-value pair with the maximum key in the map. Returns `None` if
+    /// the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut map = TreapMap::new();
+    /// map.insert(1, 1);
+    /// map.insert(3, 3);
+    /// assert_eq!(map.pop_max(), Some((3, 3)));
+    /// assert_eq!(map.pop_max(), Some((1, 1)));
+    /// assert_eq!(map.pop_max(), None);
+    /// ```
+    pub fn pop_max(&mut self) -> Option<(T, U)> {
+        tree::pop_max(&mut self.tree).map(|entry| {
+            let Entry { key, value } = entry;
+            (key, value)
+        })
+    }
+
     /// Splits the map and returns the right part of the map. If `inclusive` is true, then the map
     /// will retain the given key if it exists. Otherwise, the right part of the map will contain
     /// the key if it exists.
@@ -323,25 +639,74 @@ impl<T, U> TreapMap<T, U> {
     pub fn split_off<V>(&mut self, key: &V, inclusive: bool) -> Self
     where
         T: Borrow<V>,
-        V: Ord + ?Sized,
+        V: ?Sized,
+        C: Compare<V> + Clone,
     {
-        let TreapMap { ref mut tree, .. } = self;
-        let (mut split_node, ret) = tree::split(tree, key);
-        if inclusive {
+        let TreapMap {
+            ref mut tree,
+            ref compare,
+            ..
+        } = self;
+        let (mut split_node, ret) = tree::split(tree, key, compare);
+        let tree = if inclusive {
             tree::merge(tree, split_node);
-            TreapMap {
-                tree: ret,
-                rng: XorShiftRng::new_unseeded(),
-            }
+            ret
         } else {
             tree::merge(&mut split_node, ret);
-            TreapMap {
-                tree: split_node,
-                rng: XorShiftRng::new_unseeded(),
-            }
+            split_node
+        };
+        TreapMap {
+            tree,
+            rng: self.rng.gen(),
+            compare: self.compare.clone(),
         }
     }
 
+    /// Removes all entries whose keys are in `range` from the map and returns them as a new map.
+    /// The entries are split off and the remaining parts are merged in logarithmic time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut map = TreapMap::new();
+    /// for key in 0..5 {
+    ///     map.insert(key, key);
+    /// }
+    ///
+    /// let removed = map.remove_range(1..3);
+    /// assert_eq!(removed.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&1, &1), (&2, &2)]);
+    /// assert_eq!(
+    ///     map.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&0, &0), (&3, &3), (&4, &4)],
+    /// );
+    /// ```
+    pub fn remove_range<V, R>(&mut self, range: R) -> Self
+    where
+        T: Borrow<V>,
+        V: ?Sized,
+        C: Compare<V> + Clone,
+        R: RangeBounds<V>,
+    {
+        let mut ret = match range.start_bound() {
+            Bound::Included(start) => self.split_off(start, false),
+            Bound::Excluded(start) => self.split_off(start, true),
+            Bound::Unbounded => TreapMap {
+                tree: self.tree.take(),
+                rng: self.rng.gen(),
+                compare: self.compare.clone(),
+            },
+        };
+        let right = match range.end_bound() {
+            Bound::Included(end) => ret.split_off(end, true),
+            Bound::Excluded(end) => ret.split_off(end, false),
+            Bound::Unbounded => return ret,
+        };
+        tree::merge(&mut self.tree, right.tree);
+        ret
+    }
+
     /// Returns the union of two maps. If there is a key that is found in both `left` and `right`,
     /// the union will contain the value associated with the key in `left`. The `+`
     /// operator is implemented to take the union of two maps.
@@ -367,18 +732,20 @@ impl<T, U> TreapMap<T, U> {
     /// ```
     pub fn union(left: Self, right: Self) -> Self
     where
-        T: Ord,
+        C: Compare<T>,
     {
         let TreapMap {
             tree: left_tree,
             rng,
+            compare,
         } = left;
         let TreapMap {
             tree: right_tree, ..
         } = right;
         TreapMap {
-            tree: tree::union(left_tree, right_tree, false),
+            tree: tree::union(left_tree, right_tree, false, &compare),
             rng,
+            compare,
         }
     }
 
@@ -406,15 +773,17 @@ impl<T, U> TreapMap<T, U> {
     /// ```
     pub fn intersection(left: Self, right: Self) -> Self
     where
-        T: Ord,
+        C: Compare<T>,
     {
         let TreapMap {
             tree: left_tree,
             rng,
+            compare,
         } = left;
         TreapMap {
-            tree: tree::intersection(left_tree, right.tree, false),
+            tree: tree::intersection(left_tree, right.tree, false, &compare),
             rng,
+            compare,
         }
     }
 
@@ -443,15 +812,17 @@ impl<T, U> TreapMap<T, U> {
     /// ```
     pub fn difference(left: Self, right: Self) -> Self
     where
-        T: Ord,
+        C: Compare<T>,
     {
         let TreapMap {
             tree: left_tree,
             rng,
+            compare,
         } = left;
         TreapMap {
-            tree: tree::difference(left_tree, right.tree, false, false),
+            tree: tree::difference(left_tree, right.tree, false, false, &compare),
             rng,
+            compare,
         }
     }
 
@@ -479,18 +850,185 @@ impl<T, U> TreapMap<T, U> {
     /// ```
     pub fn symmetric_difference(left: Self, right: Self) -> Self
     where
-        T: Ord,
+        C: Compare<T>,
     {
         let TreapMap {
             tree: left_tree,
             rng,
+            compare,
         } = left;
         let TreapMap {
             tree: right_tree, ..
         } = right;
         TreapMap {
-            tree: tree::difference(left_tree, right_tree, false, true),
+            tree: tree::difference(left_tree, right_tree, false, true, &compare),
+            rng,
+            compare,
+        }
+    }
+
+    /// Returns the union of two maps, splitting the work across threads. If there is a key that is
+    /// found in both `left` and `right`, the union will contain the value associated with the key
+    /// in `left`. The result is the same as `TreapMap::union`, but large maps are combined by
+    /// recursing on disjoint subtrees in parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let n: TreapMap<u32, u32> = (0..100).map(|key| (key, key)).collect();
+    /// let m: TreapMap<u32, u32> = (50..150).map(|key| (key, 0)).collect();
+    ///
+    /// let union = TreapMap::par_union(n, m);
+    /// assert_eq!(union.len(), 150);
+    /// assert_eq!(union[&75], 75);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn par_union(left: Self, right: Self) -> Self
+    where
+        T: Send,
+        U: Send,
+        C: Compare<T> + Sync,
+    {
+        let TreapMap {
+            tree: left_tree,
+            rng,
+            compare,
+        } = left;
+        TreapMap {
+            tree: tree::par_union(
+                left_tree,
+                right.tree,
+                false,
+                tree::parallel_depth(),
+                &compare,
+            ),
+            rng,
+            compare,
+        }
+    }
+
+    /// Returns the intersection of two maps, splitting the work across threads. If there is a key
+    /// that is found in both `left` and `right`, the intersection will contain the value
+    /// associated with the key in `left`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let n: TreapMap<u32, u32> = (0..100).map(|key| (key, key)).collect();
+    /// let m: TreapMap<u32, u32> = (50..150).map(|key| (key, 0)).collect();
+    ///
+    /// let intersection = TreapMap::par_intersection(n, m);
+    /// assert_eq!(intersection.len(), 50);
+    /// assert_eq!(intersection[&75], 75);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn par_intersection(left: Self, right: Self) -> Self
+    where
+        T: Send,
+        U: Send,
+        C: Compare<T> + Sync,
+    {
+        let TreapMap {
+            tree: left_tree,
+            rng,
+            compare,
+        } = left;
+        TreapMap {
+            tree: tree::par_intersection(
+                left_tree,
+                right.tree,
+                false,
+                tree::parallel_depth(),
+                &compare,
+            ),
+            rng,
+            compare,
+        }
+    }
+
+    /// Returns the difference of `left` and `right`, splitting the work across threads. The
+    /// returned map will contain all entries that do not have a key in `right`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let n: TreapMap<u32, u32> = (0..100).map(|key| (key, key)).collect();
+    /// let m: TreapMap<u32, u32> = (50..150).map(|key| (key, 0)).collect();
+    ///
+    /// let difference = TreapMap::par_difference(n, m);
+    /// assert_eq!(difference.len(), 50);
+    /// assert_eq!(difference.max(), Some(&49));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn par_difference(left: Self, right: Self) -> Self
+    where
+        T: Send,
+        U: Send,
+        C: Compare<T> + Sync,
+    {
+        let TreapMap {
+            tree: left_tree,
+            rng,
+            compare,
+        } = left;
+        TreapMap {
+            tree: tree::par_difference(
+                left_tree,
+                right.tree,
+                false,
+                false,
+                tree::parallel_depth(),
+                &compare,
+            ),
             rng,
+            compare,
+        }
+    }
+
+    /// Returns the symmetric difference of `left` and `right`, splitting the work across threads.
+    /// The returned map will contain all entries that exist in one map, but not both maps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let n: TreapMap<u32, u32> = (0..100).map(|key| (key, key)).collect();
+    /// let m: TreapMap<u32, u32> = (50..150).map(|key| (key, 0)).collect();
+    ///
+    /// let symmetric_difference = TreapMap::par_symmetric_difference(n, m);
+    /// assert_eq!(symmetric_difference.len(), 100);
+    /// assert_eq!(symmetric_difference.get(&75), None);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn par_symmetric_difference(left: Self, right: Self) -> Self
+    where
+        T: Send,
+        U: Send,
+        C: Compare<T> + Sync,
+    {
+        let TreapMap {
+            tree: left_tree,
+            rng,
+            compare,
+        } = left;
+        TreapMap {
+            tree: tree::par_difference(
+                left_tree,
+                right.tree,
+                false,
+                true,
+                tree::parallel_depth(),
+                &compare,
+            ),
+            rng,
+            compare,
         }
     }
 
@@ -530,24 +1068,296 @@ impl<T, U> TreapMap<T, U> {
     /// map.insert(1, 1);
     /// map.insert(2, 2);
     ///
-    /// for (key, value) in &mut map {
-    ///     *value += 1;
-    /// }
+    /// for (key, value) in &mut map {
+    ///     *value += 1;
+    /// }
+    ///
+    /// let mut iterator = map.iter_mut();
+    /// assert_eq!(iterator.next(), Some((&1, &mut 2)));
+    /// assert_eq!(iterator.next(), Some((&2, &mut 3)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter_mut(&mut self) -> TreapMapIterMut<'_, T, U> {
+        TreapMapIterMut {
+            current: self.tree.as_mut().map(|node| &mut **node),
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl<T, U> TreapMap<T, U> {
+    /// Returns a lazy iterator over the union of two maps without consuming them. The iterator
+    /// will yield key-value pairs in ascending order. If there is a key that is found in both `self`
+    /// and `other`, the iterator will yield the value associated with the key in `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut n = TreapMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = TreapMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// assert_eq!(
+    ///     n.union_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1), (&2, &2), (&3, &3)],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn union_iter<'a>(&'a self, other: &'a Self) -> UnionIter<TreapMapIter<'a, T, U>>
+    where
+        T: Ord,
+    {
+        UnionIter::new(self.iter(), other.iter())
+    }
+
+    /// Returns a lazy iterator over the intersection of two maps without consuming them. The
+    /// iterator will yield key-value pairs in ascending order. If there is a key that is found in both
+    /// `self` and `other`, the iterator will yield the value associated with the key in `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut n = TreapMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = TreapMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// assert_eq!(
+    ///     n.intersection_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&2, &2)],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn intersection_iter<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> IntersectionIter<TreapMapIter<'a, T, U>>
+    where
+        T: Ord,
+    {
+        IntersectionIter::new(self.iter(), other.iter())
+    }
+
+    /// Returns a lazy iterator over the difference of two maps without consuming them. The
+    /// iterator will yield the key-value pairs of `self` that are not in `other` in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut n = TreapMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = TreapMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// assert_eq!(
+    ///     n.difference_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1)],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn difference_iter<'a>(&'a self, other: &'a Self) -> DifferenceIter<TreapMapIter<'a, T, U>>
+    where
+        T: Ord,
+    {
+        DifferenceIter::new(self.iter(), other.iter())
+    }
+
+    /// Returns a lazy iterator over the symmetric difference of two maps without consuming
+    /// them. The iterator will yield the key-value pairs that exist in one map, but not both maps, in
+    /// ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut n = TreapMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = TreapMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// assert_eq!(
+    ///     n.symmetric_difference_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1), (&3, &3)],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn symmetric_difference_iter<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> SymmetricDifferenceIter<TreapMapIter<'a, T, U>>
+    where
+        T: Ord,
+    {
+        SymmetricDifferenceIter::new(self.iter(), other.iter())
+    }
+
+    /// Returns the union of two maps without consuming them. Only the entries of the result are
+    /// cloned. If there is a key that is found in both `self` and `other`, the union will contain
+    /// the value associated with the key in `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut n = TreapMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = TreapMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// let union = n.union_cloned(&m);
+    /// assert_eq!(
+    ///     union.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1), (&2, &2), (&3, &3)],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn union_cloned(&self, other: &Self) -> Self
+    where
+        T: Ord + Clone,
+        U: Clone,
+    {
+        self.build_cloned(self.union_iter(other))
+    }
+
+    /// Returns the intersection of two maps without consuming them. Only the entries of the
+    /// result are cloned. If there is a key that is found in both `self` and `other`, the
+    /// intersection will contain the value associated with the key in `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut n = TreapMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = TreapMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// let intersection = n.intersection_cloned(&m);
+    /// assert_eq!(
+    ///     intersection.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&2, &2)],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn intersection_cloned(&self, other: &Self) -> Self
+    where
+        T: Ord + Clone,
+        U: Clone,
+    {
+        self.build_cloned(self.intersection_iter(other))
+    }
+
+    /// Returns the difference of `self` and `other` without consuming them. Only the entries of
+    /// the result are cloned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut n = TreapMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = TreapMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
+    ///
+    /// let difference = n.difference_cloned(&m);
+    /// assert_eq!(
+    ///     difference.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1)],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn difference_cloned(&self, other: &Self) -> Self
+    where
+        T: Ord + Clone,
+        U: Clone,
+    {
+        self.build_cloned(self.difference_iter(other))
+    }
+
+    /// Returns the symmetric difference of `self` and `other` without consuming them. Only the
+    /// entries of the result are cloned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapMap;
+    ///
+    /// let mut n = TreapMap::new();
+    /// n.insert(1, 1);
+    /// n.insert(2, 2);
+    ///
+    /// let mut m = TreapMap::new();
+    /// m.insert(2, 3);
+    /// m.insert(3, 3);
     ///
-    /// let mut iterator = map.iter_mut();
-    /// assert_eq!(iterator.next(), Some((&1, &mut 2)));
-    /// assert_eq!(iterator.next(), Some((&2, &mut 3)));
-    /// assert_eq!(iterator.next(), None);
+    /// let symmetric_difference = n.symmetric_difference_cloned(&m);
+    /// assert_eq!(
+    ///     symmetric_difference.iter().collect::<Vec<(&u32, &u32)>>(),
+    ///     vec![(&1, &1), (&3, &3)],
+    /// );
+    /// assert_eq!(n.len(), 2);
     /// ```
-    pub fn iter_mut(&mut self) -> TreapMapIterMut<'_, T, U> {
-        TreapMapIterMut {
-            current: self.tree.as_mut().map(|node| &mut **node),
-            stack: Vec::new(),
+    pub fn symmetric_difference_cloned(&self, other: &Self) -> Self
+    where
+        T: Ord + Clone,
+        U: Clone,
+    {
+        self.build_cloned(self.symmetric_difference_iter(other))
+    }
+
+    // Builds a map from key-value pairs in strictly ascending order in linear time, using a copy
+    // of this map's random number generator to generate priorities.
+    fn build_cloned<'a, I>(&self, iter: I) -> Self
+    where
+        T: 'a + Clone,
+        U: 'a + Clone,
+        I: Iterator<Item = (&'a T, &'a U)>,
+    {
+        let mut rng = self.rng.clone();
+        let tree = tree::build(
+            iter.map(|(key, value)| Node::new(key.clone(), value.clone(), rng.next_u32())),
+        );
+        TreapMap {
+            tree,
+            rng,
+            compare: Natural,
         }
     }
 }
 
-impl<T, U> IntoIterator for TreapMap<T, U> {
+impl<T, U, C> IntoIterator for TreapMap<T, U, C> {
     type IntoIter = TreapMapIntoIter<T, U>;
     type Item = (T, U);
 
@@ -559,7 +1369,7 @@ impl<T, U> IntoIterator for TreapMap<T, U> {
     }
 }
 
-impl<'a, T, U> IntoIterator for &'a TreapMap<T, U>
+impl<'a, T, U, C> IntoIterator for &'a TreapMap<T, U, C>
 where
     T: 'a,
     U: 'a,
@@ -572,7 +1382,7 @@ where
     }
 }
 
-impl<'a, T, U> IntoIterator for &'a mut TreapMap<T, U>
+impl<'a, T, U, C> IntoIterator for &'a mut TreapMap<T, U, C>
 where
     T: 'a,
     U: 'a,
@@ -689,38 +1499,172 @@ where
     }
 }
 
-impl<T, U> Default for TreapMap<T, U> {
+impl<T, U, C> OrderedMap<T, U> for TreapMap<T, U, C>
+where
+    C: Compare<T>,
+{
+    type Iter<'a>
+        = TreapMapIter<'a, T, U>
+    where
+        Self: 'a,
+        T: 'a,
+        U: 'a;
+
+    fn insert(&mut self, key: T, value: U) -> Option<(T, U)> {
+        TreapMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &T) -> Option<(T, U)> {
+        TreapMap::remove(self, key)
+    }
+
+    fn contains_key(&self, key: &T) -> bool {
+        TreapMap::contains_key(self, key)
+    }
+
+    fn get(&self, key: &T) -> Option<&U> {
+        TreapMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &T) -> Option<&mut U> {
+        TreapMap::get_mut(self, key)
+    }
+
+    fn len(&self) -> usize {
+        TreapMap::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        TreapMap::is_empty(self)
+    }
+
+    fn floor(&self, key: &T) -> Option<&T> {
+        TreapMap::floor(self, key)
+    }
+
+    fn ceil(&self, key: &T) -> Option<&T> {
+        TreapMap::ceil(self, key)
+    }
+
+    fn min(&self) -> Option<&T> {
+        TreapMap::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        TreapMap::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        TreapMap::iter(self)
+    }
+}
+
+impl<T, U, C> fmt::Debug for TreapMap<T, U, C>
+where
+    T: fmt::Debug,
+    U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, U, C> PartialEq for TreapMap<T, U, C>
+where
+    T: PartialEq,
+    U: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T, U, C> Eq for TreapMap<T, U, C>
+where
+    T: Eq,
+    U: Eq,
+{
+}
+
+impl<T, U, C> Default for TreapMap<T, U, C>
+where
+    C: Default,
+{
     fn default() -> Self {
-        Self::new()
+        Self::with_comparator(C::default())
     }
 }
 
-impl<T, U> Add for TreapMap<T, U>
+impl<T, U> FromIterator<(T, U)> for TreapMap<T, U>
 where
     T: Ord,
 {
-    type Output = TreapMap<T, U>;
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (T, U)>,
+    {
+        let entries: Vec<(T, U)> = iter.into_iter().collect();
+        let mut map = Self::new();
+        // strictly ascending keys can be built bottom up in linear time
+        if entries.windows(2).all(|pair| pair[0].0 < pair[1].0) {
+            let TreapMap {
+                ref mut tree,
+                ref mut rng,
+                ..
+            } = map;
+            *tree = tree::build(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| Node::new(key, value, rng.next_u32())),
+            );
+        } else {
+            map.extend(entries);
+        }
+        map
+    }
+}
+
+impl<T, U, C> Extend<(T, U)> for TreapMap<T, U, C>
+where
+    C: Compare<T>,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (T, U)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<T, U, C> Add for TreapMap<T, U, C>
+where
+    C: Compare<T>,
+{
+    type Output = TreapMap<T, U, C>;
 
-    fn add(self, other: TreapMap<T, U>) -> TreapMap<T, U> {
+    fn add(self, other: TreapMap<T, U, C>) -> TreapMap<T, U, C> {
         Self::union(self, other)
     }
 }
 
-impl<T, U> Sub for TreapMap<T, U>
+impl<T, U, C> Sub for TreapMap<T, U, C>
 where
-    T: Ord,
+    C: Compare<T>,
 {
-    type Output = TreapMap<T, U>;
+    type Output = TreapMap<T, U, C>;
 
-    fn sub(self, other: TreapMap<T, U>) -> TreapMap<T, U> {
+    fn sub(self, other: TreapMap<T, U, C>) -> TreapMap<T, U, C> {
         Self::difference(self, other)
     }
 }
 
-impl<'a, T, U, V> Index<&'a V> for TreapMap<T, U>
+impl<'a, T, U, V, C> Index<&'a V> for TreapMap<T, U, C>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
     type Output = U;
 
@@ -729,10 +1673,11 @@ where
     }
 }
 
-impl<'a, T, U, V> IndexMut<&'a V> for TreapMap<T, U>
+impl<'a, T, U, V, C> IndexMut<&'a V> for TreapMap<T, U, C>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
     fn index_mut(&mut self, key: &V) -> &mut Self::Output {
         self.get_mut(key).expect("Error: key does not exist.")
@@ -742,6 +1687,8 @@ where
 #[cfg(test)]
 mod tests {
     use super::TreapMap;
+    use core::ops::{Bound, RangeBounds};
+    use rand::{Rng, SeedableRng, XorShiftRng};
 
     #[test]
     fn test_len_empty() {
@@ -826,6 +1773,65 @@ mod tests {
         assert_eq!(map.ceil(&6), None);
     }
 
+    #[test]
+    fn test_floor_ceil_entry() {
+        let mut map = TreapMap::new();
+        for key in (0..100u32).rev() {
+            map.insert(key * 2, key * 2);
+        }
+
+        let to_key = |entry: Option<(&u32, &u32)>| {
+            entry.map(|(key, value)| {
+                assert_eq!(key, value);
+                *key
+            })
+        };
+        for key in 0..200 {
+            assert_eq!(to_key(map.floor_entry(&key)), Some(key / 2 * 2));
+            assert_eq!(
+                to_key(map.ceil_entry(&key)),
+                Some(key.div_ceil(2) * 2).filter(|key| *key < 200),
+            );
+            assert_eq!(
+                to_key(map.next_below(&key)),
+                key.checked_sub(1).map(|key| key / 2 * 2),
+            );
+            assert_eq!(
+                to_key(map.next_above(&key)),
+                Some(key / 2 * 2 + 2).filter(|key| *key < 200),
+            );
+        }
+
+        *map.floor_entry_mut(&5).unwrap().1 = 0;
+        *map.ceil_entry_mut(&5).unwrap().1 = 0;
+        assert_eq!(map.get(&4), Some(&0));
+        assert_eq!(map.get(&6), Some(&0));
+        assert_eq!(map.floor_entry_mut(&200).map(|(key, _)| *key), Some(198));
+        assert!(map.ceil_entry_mut(&199).is_none());
+    }
+
+    #[test]
+    fn test_pop_min_max() {
+        let mut map = TreapMap::new();
+        for key in 0..1_000u32 {
+            map.insert(key * 7_919 % 1_000, key);
+        }
+
+        let (mut min, mut max) = (0, 999);
+        while min <= max {
+            let (key, _) = map.pop_min().unwrap();
+            assert_eq!(key, min);
+            min += 1;
+            let (key, _) = map.pop_max().unwrap();
+            assert_eq!(key, max);
+            max -= 1;
+            assert_eq!(map.len(), (max + 1 - min) as usize);
+        }
+        assert_eq!(map.pop_min(), None);
+        assert_eq!(map.pop_max(), None);
+        assert!(map.is_empty());
+    }
+
     #[test]
     fn test_split_off_inclusive() {
         let mut map = TreapMap::new();
@@ -856,6 +1862,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_remove_range() {
+        let ranges: [(Bound<u32>, Bound<u32>); 6] = [
+            (Bound::Included(20), Bound::Excluded(70)),
+            (Bound::Excluded(20), Bound::Included(70)),
+            (Bound::Unbounded, Bound::Excluded(35)),
+            (Bound::Included(35), Bound::Unbounded),
+            (Bound::Unbounded, Bound::Unbounded),
+            (Bound::Included(50), Bound::Excluded(50)),
+        ];
+        for range in ranges.iter() {
+            let mut map = TreapMap::new();
+            for key in (0..100).rev() {
+                map.insert(key, key);
+            }
+
+            let removed = map.remove_range(*range);
+            let is_removed = |key: &u32| range.contains(key);
+            assert_eq!(map.len() + removed.len(), 100);
+            assert!(map
+                .iter()
+                .map(|(key, _)| *key)
+                .eq((0..100).filter(|key| !is_removed(key))));
+            assert!(removed
+                .iter()
+                .map(|(key, _)| *key)
+                .eq((0..100).filter(is_removed)));
+        }
+    }
+
     #[test]
     fn test_union() {
         let mut n = TreapMap::new();
@@ -940,6 +1976,111 @@ mod tests {
         assert_eq!(symmetric_difference.len(), 4);
     }
 
+    #[test]
+    fn test_set_op_iters() {
+        let mut n = TreapMap::new();
+        n.insert(1, 1);
+        n.insert(2, 2);
+        n.insert(3, 3);
+
+        let mut m = TreapMap::new();
+        m.insert(3, 5);
+        m.insert(4, 4);
+        m.insert(5, 5);
+
+        assert_eq!(
+            n.union_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+            vec![(&1, &1), (&2, &2), (&3, &3), (&4, &4), (&5, &5)],
+        );
+        assert_eq!(
+            n.intersection_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+            vec![(&3, &3)],
+        );
+        assert_eq!(
+            n.difference_iter(&m).collect::<Vec<(&u32, &u32)>>(),
+            vec![(&1, &1), (&2, &2)],
+        );
+        assert_eq!(
+            n.symmetric_difference_iter(&m)
+                .collect::<Vec<(&u32, &u32)>>(),
+            vec![(&1, &1), (&2, &2), (&4, &4), (&5, &5)],
+        );
+        assert_eq!(n.len(), 3);
+        assert_eq!(m.len(), 3);
+    }
+
+    #[test]
+    fn test_cloned_set_ops() {
+        let mut n = TreapMap::new();
+        n.insert(1, 1);
+        n.insert(2, 2);
+        n.insert(3, 3);
+
+        let mut m = TreapMap::new();
+        m.insert(3, 5);
+        m.insert(4, 4);
+        m.insert(5, 5);
+
+        let union = n.union_cloned(&m);
+        assert_eq!(
+            union.iter().collect::<Vec<(&u32, &u32)>>(),
+            vec![(&1, &1), (&2, &2), (&3, &3), (&4, &4), (&5, &5)],
+        );
+        assert_eq!(union.len(), 5);
+        assert_eq!(
+            n.intersection_cloned(&m)
+                .into_iter()
+                .collect::<Vec<(u32, u32)>>(),
+            vec![(3, 3)],
+        );
+        assert_eq!(
+            n.difference_cloned(&m)
+                .into_iter()
+                .collect::<Vec<(u32, u32)>>(),
+            vec![(1, 1), (2, 2)],
+        );
+        assert_eq!(
+            n.symmetric_difference_cloned(&m)
+                .into_iter()
+                .collect::<Vec<(u32, u32)>>(),
+            vec![(1, 1), (2, 2), (4, 4), (5, 5)],
+        );
+        assert_eq!(n.len(), 3);
+        assert_eq!(m.len(), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_par_set_ops() {
+        let mut rng: XorShiftRng = SeedableRng::from_seed([1, 1, 1, 1]);
+        let mut n = TreapMap::new();
+        let mut m = TreapMap::new();
+        for _ in 0..20_000 {
+            n.insert(rng.gen::<u16>(), rng.gen::<u32>());
+            m.insert(rng.gen::<u16>(), rng.gen::<u32>());
+        }
+
+        assert_eq!(
+            TreapMap::par_union(n.clone(), m.clone()),
+            TreapMap::union(n.clone(), m.clone()),
+        );
+        assert_eq!(
+            TreapMap::par_intersection(n.clone(), m.clone()),
+            TreapMap::intersection(n.clone(), m.clone()),
+        );
+        assert_eq!(
+            TreapMap::par_difference(n.clone(), m.clone()),
+            TreapMap::difference(n.clone(), m.clone()),
+        );
+        let symmetric_difference = TreapMap::par_symmetric_difference(n.clone(), m.clone());
+        assert_eq!(symmetric_difference, TreapMap::symmetric_difference(n, m),);
+        let mut prev = None;
+        for (key, _) in &symmetric_difference {
+            assert!(prev < Some(key));
+            prev = Some(key);
+        }
+    }
+
     #[test]
     fn test_into_iter() {
         let mut map = TreapMap::new();
@@ -982,4 +2123,164 @@ mod tests {
             vec![(&1, &3), (&3, &5), (&5, &7)],
         );
     }
+
+    #[test]
+    fn test_with_rng() {
+        let mut map = TreapMap::with_rng(XorShiftRng::from_seed([1, 1, 1, 1]));
+        let mut other = TreapMap::with_rng(XorShiftRng::from_seed([1, 1, 1, 1]));
+        for key in 0..100 {
+            map.insert(key, key);
+            other.insert(key, key);
+        }
+
+        let root_priority = map.tree.as_ref().map(|node| node.priority);
+        let other_root_priority = other.tree.as_ref().map(|node| node.priority);
+        assert!(root_priority.is_some());
+        assert_eq!(root_priority, other_root_priority);
+    }
+
+    #[test]
+    fn test_with_seed() {
+        let mut map = TreapMap::with_seed(1);
+        let mut other = TreapMap::with_seed(1);
+        let mut different = TreapMap::with_seed(2);
+        for key in 0..100 {
+            map.insert(key, key);
+            other.insert(key, key);
+            different.insert(key, key);
+        }
+
+        let root_priority = map.tree.as_ref().map(|node| node.priority);
+        let other_root_priority = other.tree.as_ref().map(|node| node.priority);
+        let different_root_priority = different.tree.as_ref().map(|node| node.priority);
+        assert!(root_priority.is_some());
+        assert_eq!(root_priority, other_root_priority);
+        assert_ne!(root_priority, different_root_priority);
+    }
+
+    #[test]
+    fn test_clone_eq() {
+        let mut map = TreapMap::new();
+        map.insert(1, 2);
+        map.insert(5, 6);
+        map.insert(3, 4);
+
+        let mut cloned = map.clone();
+        assert_eq!(cloned, map);
+        cloned.insert(3, 5);
+        assert_ne!(cloned, map);
+        cloned.insert(3, 4);
+        cloned.insert(7, 8);
+        assert_ne!(cloned, map);
+        cloned.remove(&7);
+        assert_eq!(cloned, map);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut map = TreapMap::new();
+        map.insert(3, 4);
+        map.insert(1, 2);
+        assert_eq!(format!("{:?}", map), "{1: 2, 3: 4}");
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut map = TreapMap::new();
+        for key in 0..10_007u32 {
+            map.insert(key * 7_919 % 10_007, key);
+        }
+
+        let is_retained = |key: &u32| !key.is_multiple_of(3) && (*key < 2_000 || *key > 6_000);
+        map.retain(|key, value| {
+            *value += 1;
+            is_retained(key)
+        });
+        let expected: Vec<u32> = (0..10_007).filter(is_retained).collect();
+        assert_eq!(map.len(), expected.len());
+        assert_eq!(
+            map.iter().map(|(key, _)| *key).collect::<Vec<u32>>(),
+            expected,
+        );
+
+        for key in expected.iter().filter(|key| *key % 2 == 0) {
+            assert!(map.remove(key).is_some());
+        }
+        map.insert(4_000, 0);
+        assert_eq!(
+            map.len(),
+            expected.iter().filter(|key| *key % 2 == 1).count() + 1
+        );
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut map = TreapMap::new();
+        map.insert(1, 2);
+        map.insert(5, 6);
+        map.insert(3, 4);
+
+        assert_eq!(
+            map.drain().collect::<Vec<(u32, u32)>>(),
+            vec![(1, 2), (3, 4), (5, 6)],
+        );
+        assert!(map.is_empty());
+        map.insert(1, 2);
+        assert_eq!(map.get(&1), Some(&2));
+    }
+
+    #[test]
+    fn test_comparator() {
+        let mut map = TreapMap::with_comparator(|a: &String, b: &String| {
+            a.to_lowercase().cmp(&b.to_lowercase())
+        });
+        map.insert(String::from("b"), 1);
+        map.insert(String::from("C"), 2);
+        assert_eq!(
+            map.insert(String::from("B"), 3),
+            Some((String::from("b"), 1))
+        );
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&String::from("c")), Some(&2));
+        assert_eq!(map.floor(&String::from("bb")), Some(&String::from("B")));
+        assert_eq!(map.ceil(&String::from("a")), Some(&String::from("B")));
+        assert_eq!(map.remove(&String::from("c")), Some((String::from("C"), 2)));
+        assert_eq!(
+            map.iter().collect::<Vec<(&String, &u32)>>(),
+            vec![(&String::from("B"), &3)],
+        );
+    }
+
+    #[test]
+    fn test_comparator_set_ops() {
+        let compare = |a: &u32, b: &u32| b.cmp(a);
+        let mut n = TreapMap::with_comparator(compare);
+        n.insert(1, 1);
+        n.insert(2, 2);
+
+        let mut m = TreapMap::with_comparator(compare);
+        m.insert(2, 3);
+        m.insert(3, 3);
+
+        let union = TreapMap::union(n.clone(), m.clone());
+        assert_eq!(
+            union.iter().collect::<Vec<(&u32, &u32)>>(),
+            vec![(&3, &3), (&2, &2), (&1, &1)],
+        );
+
+        let mut map = union.clone();
+        let split = map.split_off(&2, true);
+        assert_eq!(
+            map.iter().collect::<Vec<(&u32, &u32)>>(),
+            vec![(&3, &3), (&2, &2)]
+        );
+        assert_eq!(split.iter().collect::<Vec<(&u32, &u32)>>(), vec![(&1, &1)]);
+
+        let difference = TreapMap::difference(n, m);
+        assert_eq!(
+            difference.iter().collect::<Vec<(&u32, &u32)>>(),
+            vec![(&1, &1)]
+        );
+    }
 }
@@ -1,12 +1,15 @@
 //! Probabilistic binary search tree where each node also maintains the heap invariant.
 
-mod implicit_tree;
+pub(crate) mod implicit_tree;
 mod list;
-mod map;
-mod node;
+pub(crate) mod map;
+pub(crate) mod node;
+mod persistent_map;
+mod persistent_tree;
 mod set;
 mod tree;
 
 pub use self::list::TreapList;
 pub use self::map::TreapMap;
+pub use self::persistent_map::PersistentTreapMap;
 pub use self::set::TreapSet;
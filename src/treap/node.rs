@@ -1,7 +1,8 @@
 use crate::entry::Entry;
-use crate::treap::{implicit_tree, tree};
+use crate::treap::{implicit_tree, persistent_tree, tree};
 
 /// A struct representing an internal node of a treap.
+#[derive(Clone)]
 pub struct Node<T, U> {
     pub entry: Entry<T, U>,
     pub priority: u32,
@@ -85,3 +86,37 @@ impl<T> ImplicitNode<T> {
         }
     }
 }
+
+/// A struct representing an internal node of a persistent treap.
+#[derive(Clone)]
+pub struct PersistentNode<T, U> {
+    pub entry: Entry<T, U>,
+    pub priority: u32,
+    pub len: usize,
+    pub left: persistent_tree::Tree<T, U>,
+    pub right: persistent_tree::Tree<T, U>,
+}
+
+impl<T, U> PersistentNode<T, U> {
+    pub fn new(
+        entry: Entry<T, U>,
+        priority: u32,
+        left: persistent_tree::Tree<T, U>,
+        right: persistent_tree::Tree<T, U>,
+    ) -> Self {
+        let mut len = 1;
+        if let Some(ref left_node) = left {
+            len += left_node.len;
+        }
+        if let Some(ref right_node) = right {
+            len += right_node.len;
+        }
+        PersistentNode {
+            entry,
+            priority,
+            len,
+            left,
+            right,
+        }
+    }
+}
@@ -0,0 +1,498 @@
+use crate::entry::Entry;
+use crate::seed;
+use crate::treap::node::PersistentNode;
+use crate::treap::persistent_tree;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::ops::Index;
+use rand::Rng;
+use rand::XorShiftRng;
+
+/// A persistent ordered map implemented using a treap.
+///
+/// Modifying the map returns a new map and leaves the original map unchanged. Only the nodes on
+/// the path from the root to the modified node are copied, and the remaining nodes are shared
+/// between the two maps using reference counting. Inserting or removing a key therefore takes
+/// expected logarithmic time and space, and cloning a map takes constant time. Keys and values
+/// are cloned when the node that contains them is copied.
+///
+/// # Examples
+///
+/// ```
+/// use extended_collections::treap::PersistentTreapMap;
+///
+/// let map = PersistentTreapMap::new();
+/// let map = map.insert(0, 1);
+/// let snapshot = map.insert(3, 4);
+/// let map = snapshot.remove(&0);
+///
+/// assert_eq!(snapshot[&0], 1);
+/// assert_eq!(snapshot.len(), 2);
+///
+/// assert_eq!(map.get(&0), None);
+/// assert_eq!(map.get(&3), Some(&4));
+/// assert_eq!(map.len(), 1);
+/// ```
+#[derive(Clone)]
+pub struct PersistentTreapMap<T, U> {
+    tree: persistent_tree::Tree<T, U>,
+    rng: XorShiftRng,
+}
+
+impl<T, U> PersistentTreapMap<T, U> {
+    /// Constructs a new, empty `PersistentTreapMap<T, U>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map: PersistentTreapMap<u32, u32> = PersistentTreapMap::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::with_rng(seed::new_rng())
+    }
+
+    /// Constructs a new, empty `PersistentTreapMap<T, U>` that uses `rng` to generate the
+    /// priorities of its nodes. Seeding `rng` makes the internal structure reproducible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    /// use rand::{SeedableRng, XorShiftRng};
+    ///
+    /// let map: PersistentTreapMap<u32, u32> =
+    ///     PersistentTreapMap::with_rng(XorShiftRng::from_seed([1, 1, 1, 1]));
+    /// ```
+    pub fn with_rng(rng: XorShiftRng) -> Self {
+        PersistentTreapMap { tree: None, rng }
+    }
+
+    /// Constructs a new, empty `PersistentTreapMap<T, U>` that generates the priorities of its
+    /// nodes with a random number generator seeded with `seed`. Maps constructed with the same seed
+    /// have the same internal structure after the same sequence of operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map: PersistentTreapMap<u32, u32> = PersistentTreapMap::with_seed(1);
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(seed::rng_from_seed(seed))
+    }
+
+    /// Returns a new map with a key-value pair inserted. If the key already exists in the map, its
+    /// value is replaced in the new map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new();
+    /// let new_map = map.insert(1, 1);
+    /// assert_eq!(map.get(&1), None);
+    /// assert_eq!(new_map.get(&1), Some(&1));
+    /// ```
+    pub fn insert(&self, key: T, value: U) -> Self
+    where
+        T: Ord + Clone,
+        U: Clone,
+    {
+        let mut rng = self.rng.clone();
+        let priority = rng.next_u32();
+        PersistentTreapMap {
+            tree: persistent_tree::insert(&self.tree, Entry { key, value }, priority),
+            rng,
+        }
+    }
+
+    /// Returns a new map with a key removed. If the key does not exist in the map, the new map
+    /// shares all of its nodes with the original map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new().insert(1, 1);
+    /// let new_map = map.remove(&1);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// assert_eq!(new_map.get(&1), None);
+    /// ```
+    pub fn remove<V>(&self, key: &V) -> Self
+    where
+        T: Borrow<V> + Clone,
+        U: Clone,
+        V: Ord + ?Sized,
+    {
+        PersistentTreapMap {
+            tree: persistent_tree::remove(&self.tree, key).unwrap_or_else(|| self.tree.clone()),
+            rng: self.rng.clone(),
+        }
+    }
+
+    /// Checks if a key exists in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new().insert(1, 1);
+    /// assert!(!map.contains_key(&0));
+    /// assert!(map.contains_key(&1));
+    /// ```
+    pub fn contains_key<V>(&self, key: &V) -> bool
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Returns an immutable reference to the value associated with a particular key. It will
+    /// return `None` if the key does not exist in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new().insert(1, 1);
+    /// assert_eq!(map.get(&0), None);
+    /// assert_eq!(map.get(&1), Some(&1));
+    /// ```
+    pub fn get<V>(&self, key: &V) -> Option<&U>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        persistent_tree::get(&self.tree, key).map(|entry| &entry.value)
+    }
+
+    /// Returns the number of elements in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new().insert(1, 1);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        persistent_tree::len(&self.tree)
+    }
+
+    /// Returns `true` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map: PersistentTreapMap<u32, u32> = PersistentTreapMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a key in the map that is less than or equal to a particular key. Returns `None` if
+    /// such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new().insert(1, 1);
+    /// assert_eq!(map.floor(&0), None);
+    /// assert_eq!(map.floor(&2), Some(&1));
+    /// ```
+    pub fn floor<V>(&self, key: &V) -> Option<&T>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        persistent_tree::floor(&self.tree, key, true).map(|entry| &entry.key)
+    }
+
+    /// Returns a key in the map that is greater than or equal to a particular key. Returns `None`
+    /// if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new().insert(1, 1);
+    /// assert_eq!(map.ceil(&0), Some(&1));
+    /// assert_eq!(map.ceil(&2), None);
+    /// ```
+    pub fn ceil<V>(&self, key: &V) -> Option<&T>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        persistent_tree::ceil(&self.tree, key, true).map(|entry| &entry.key)
+    }
+
+    /// Returns the key-value pair in the map with the greatest key that is less than or equal to a
+    /// particular key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new().insert(1, 1);
+    /// assert_eq!(map.floor_entry(&0), None);
+    /// assert_eq!(map.floor_entry(&2), Some((&1, &1)));
+    /// ```
+    pub fn floor_entry<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        persistent_tree::floor(&self.tree, key, true).map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Returns the key-value pair in the map with the least key that is greater than or equal to a
+    /// particular key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new().insert(1, 1);
+    /// assert_eq!(map.ceil_entry(&2), None);
+    /// assert_eq!(map.ceil_entry(&0), Some((&1, &1)));
+    /// ```
+    pub fn ceil_entry<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        persistent_tree::ceil(&self.tree, key, true).map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Returns the key-value pair in the map with the greatest key that is less than a particular
+    /// key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new().insert(1, 1).insert(2, 2);
+    /// assert_eq!(map.next_below(&1), None);
+    /// assert_eq!(map.next_below(&2), Some((&1, &1)));
+    /// ```
+    pub fn next_below<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        persistent_tree::floor(&self.tree, key, false).map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Returns the key-value pair in the map with the least key that is greater than a particular
+    /// key. Returns `None` if such a key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new().insert(1, 1).insert(2, 2);
+    /// assert_eq!(map.next_above(&2), None);
+    /// assert_eq!(map.next_above(&1), Some((&2, &2)));
+    /// ```
+    pub fn next_above<V>(&self, key: &V) -> Option<(&T, &U)>
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+    {
+        persistent_tree::ceil(&self.tree, key, false).map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Returns the minimum key of the map. Returns `None` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new().insert(1, 1).insert(3, 3);
+    /// assert_eq!(map.min(), Some(&1));
+    /// ```
+    pub fn min(&self) -> Option<&T> {
+        persistent_tree::min(&self.tree).map(|entry| &entry.key)
+    }
+
+    /// Returns the maximum key of the map. Returns `None` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new().insert(1, 1).insert(3, 3);
+    /// assert_eq!(map.max(), Some(&3));
+    /// ```
+    pub fn max(&self) -> Option<&T> {
+        persistent_tree::max(&self.tree).map(|entry| &entry.key)
+    }
+
+    /// Returns an iterator over the map. The iterator will yield key-value pairs using in-order
+    /// traversal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::PersistentTreapMap;
+    ///
+    /// let map = PersistentTreapMap::new().insert(1, 1).insert(2, 2);
+    ///
+    /// let mut iterator = map.iter();
+    /// assert_eq!(iterator.next(), Some((&1, &1)));
+    /// assert_eq!(iterator.next(), Some((&2, &2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> PersistentTreapMapIter<'_, T, U> {
+        PersistentTreapMapIter {
+            current: &self.tree,
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl<'a, T, U> IntoIterator for &'a PersistentTreapMap<T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type IntoIter = PersistentTreapMapIter<'a, T, U>;
+    type Item = (&'a T, &'a U);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator for `PersistentTreapMap<T, U>`.
+///
+/// This iterator traverses the elements of the map in-order and yields immutable references.
+pub struct PersistentTreapMapIter<'a, T, U> {
+    current: &'a persistent_tree::Tree<T, U>,
+    stack: Vec<&'a PersistentNode<T, U>>,
+}
+
+impl<'a, T, U> Iterator for PersistentTreapMapIter<'a, T, U>
+where
+    T: 'a,
+    U: 'a,
+{
+    type Item = (&'a T, &'a U);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(ref node) = self.current {
+            self.current = &node.left;
+            self.stack.push(node);
+        }
+        self.stack.pop().map(|node| {
+            let PersistentNode {
+                entry: Entry { ref key, ref value },
+                ref right,
+                ..
+            } = node;
+            self.current = right;
+            (key, value)
+        })
+    }
+}
+
+impl<T, U> Default for PersistentTreapMap<T, U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, U, V> Index<&V> for PersistentTreapMap<T, U>
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    type Output = U;
+
+    fn index(&self, key: &V) -> &Self::Output {
+        self.get(key).expect("Error: key does not exist.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PersistentTreapMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_len_empty() {
+        let map: PersistentTreapMap<u32, u32> = PersistentTreapMap::new();
+        assert_eq!(map.len(), 0);
+        assert_eq!(map.min(), None);
+        assert_eq!(map.max(), None);
+    }
+
+    #[test]
+    fn test_insert_replace() {
+        let map = PersistentTreapMap::new().insert(1, 1);
+        let new_map = map.insert(1, 3);
+        assert_eq!(map.get(&1), Some(&1));
+        assert_eq!(new_map.get(&1), Some(&3));
+        assert_eq!(new_map.len(), 1);
+    }
+
+    #[test]
+    fn test_remove_missing() {
+        let map = PersistentTreapMap::new().insert(1, 1);
+        let new_map = map.remove(&0);
+        assert!(Arc::ptr_eq(
+            map.tree.as_ref().unwrap(),
+            new_map.tree.as_ref().unwrap(),
+        ));
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let mut snapshots = vec![PersistentTreapMap::new()];
+        for key in 0..100u32 {
+            let map = snapshots[snapshots.len() - 1].insert((key * 37) % 100, key);
+            snapshots.push(map);
+        }
+        for key in 0..50u32 {
+            let map = snapshots[snapshots.len() - 1].remove(&key);
+            snapshots.push(map);
+        }
+
+        for (index, map) in snapshots.iter().enumerate() {
+            let mut expected: Vec<(u32, u32)> = (0..index.min(100) as u32)
+                .map(|key| ((key * 37) % 100, key))
+                .filter(|entry| index <= 100 || entry.0 >= (index - 100) as u32)
+                .collect();
+            expected.sort();
+            assert_eq!(map.len(), expected.len());
+            assert_eq!(
+                map.iter()
+                    .map(|(key, value)| (*key, *value))
+                    .collect::<Vec<(u32, u32)>>(),
+                expected,
+            );
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::entry::Entry;
+use crate::treap::node::PersistentNode;
+use alloc::sync::Arc;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+
+pub type Tree<T, U> = Option<Arc<PersistentNode<T, U>>>;
+
+fn new_tree<T, U>(
+    entry: Entry<T, U>,
+    priority: u32,
+    left: Tree<T, U>,
+    right: Tree<T, U>,
+) -> Tree<T, U> {
+    Some(Arc::new(PersistentNode::new(entry, priority, left, right)))
+}
+
+pub fn len<T, U>(tree: &Tree<T, U>) -> usize {
+    match tree {
+        Some(ref node) => node.len,
+        None => 0,
+    }
+}
+
+pub fn merge<T, U>(l_tree: &Tree<T, U>, r_tree: &Tree<T, U>) -> Tree<T, U>
+where
+    T: Clone,
+    U: Clone,
+{
+    match (l_tree, r_tree) {
+        (Some(l_node), Some(r_node)) => {
+            if l_node.priority > r_node.priority {
+                let right = merge(&l_node.right, r_tree);
+                new_tree(
+                    l_node.entry.clone(),
+                    l_node.priority,
+                    l_node.left.clone(),
+                    right,
+                )
+            } else {
+                let left = merge(l_tree, &r_node.left);
+                new_tree(
+                    r_node.entry.clone(),
+                    r_node.priority,
+                    left,
+                    r_node.right.clone(),
+                )
+            }
+        }
+        (tree, None) | (None, tree) => tree.clone(),
+    }
+}
+
+// Splits a tree into the trees of keys less than and greater than `key`. The node with a key equal
+// to `key` is excluded.
+pub fn split<T, U, V>(tree: &Tree<T, U>, key: &V) -> (Tree<T, U>, Tree<T, U>)
+where
+    T: Borrow<V> + Clone,
+    U: Clone,
+    V: Ord + ?Sized,
+{
+    match tree {
+        Some(node) => match key.cmp(node.entry.key.borrow()) {
+            Ordering::Less => {
+                let (left, right) = split(&node.left, key);
+                let right = new_tree(node.entry.clone(), node.priority, right, node.right.clone());
+                (left, right)
+            }
+            Ordering::Greater => {
+                let (left, right) = split(&node.right, key);
+                let left = new_tree(node.entry.clone(), node.priority, node.left.clone(), left);
+                (left, right)
+            }
+            Ordering::Equal => (node.left.clone(), node.right.clone()),
+        },
+        None => (None, None),
+    }
+}
+
+pub fn insert<T, U>(tree: &Tree<T, U>, entry: Entry<T, U>, priority: u32) -> Tree<T, U>
+where
+    T: Ord + Clone,
+    U: Clone,
+{
+    match tree {
+        Some(node) if priority <= node.priority => match entry.key.cmp(&node.entry.key) {
+            Ordering::Less => {
+                let left = insert(&node.left, entry, priority);
+                new_tree(node.entry.clone(), node.priority, left, node.right.clone())
+            }
+            Ordering::Greater => {
+                let right = insert(&node.right, entry, priority);
+                new_tree(node.entry.clone(), node.priority, node.left.clone(), right)
+            }
+            Ordering::Equal => {
+                new_tree(entry, node.priority, node.left.clone(), node.right.clone())
+            }
+        },
+        _ => {
+            let (left, right) = split(tree, &entry.key);
+            new_tree(entry, priority, left, right)
+        }
+    }
+}
+
+// Returns `None` if the key does not exist in the tree.
+pub fn remove<T, U, V>(tree: &Tree<T, U>, key: &V) -> Option<Tree<T, U>>
+where
+    T: Borrow<V> + Clone,
+    U: Clone,
+    V: Ord + ?Sized,
+{
+    tree.as_ref()
+        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
+            Ordering::Less => remove(&node.left, key)
+                .map(|left| new_tree(node.entry.clone(), node.priority, left, node.right.clone())),
+            Ordering::Greater => remove(&node.right, key)
+                .map(|right| new_tree(node.entry.clone(), node.priority, node.left.clone(), right)),
+            Ordering::Equal => Some(merge(&node.left, &node.right)),
+        })
+}
+
+pub fn get<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V) -> Option<&'a Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    tree.as_ref()
+        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
+            Ordering::Less => get(&node.left, key),
+            Ordering::Greater => get(&node.right, key),
+            Ordering::Equal => Some(&node.entry),
+        })
+}
+
+pub fn ceil<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V, inclusive: bool) -> Option<&'a Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    let mut curr = tree;
+    let mut ret = None;
+    while let Some(node) = curr {
+        match key.cmp(node.entry.key.borrow()) {
+            Ordering::Less => {
+                ret = Some(&node.entry);
+                curr = &node.left;
+            }
+            Ordering::Equal if inclusive => return Some(&node.entry),
+            _ => curr = &node.right,
+        }
+    }
+    ret
+}
+
+pub fn floor<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V, inclusive: bool) -> Option<&'a Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: Ord + ?Sized,
+{
+    let mut curr = tree;
+    let mut ret = None;
+    while let Some(node) = curr {
+        match key.cmp(node.entry.key.borrow()) {
+            Ordering::Greater => {
+                ret = Some(&node.entry);
+                curr = &node.right;
+            }
+            Ordering::Equal if inclusive => return Some(&node.entry),
+            _ => curr = &node.left,
+        }
+    }
+    ret
+}
+
+pub fn min<T, U>(tree: &Tree<T, U>) -> Option<&Entry<T, U>> {
+    tree.as_ref().map(|node| {
+        let mut curr = node;
+        while let Some(ref left_node) = curr.left {
+            curr = left_node;
+        }
+        &curr.entry
+    })
+}
+
+pub fn max<T, U>(tree: &Tree<T, U>) -> Option<&Entry<T, U>> {
+    tree.as_ref().map(|node| {
+        let mut curr = node;
+        while let Some(ref right_node) = curr.right {
+            curr = right_node;
+        }
+        &curr.entry
+    })
+}
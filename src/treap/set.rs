@@ -1,6 +1,11 @@
+use crate::ordered::OrderedSet;
+use crate::set_ops::{DifferenceIter, IntersectionIter, SymmetricDifferenceIter, UnionIter};
 use crate::treap::map::{TreapMap, TreapMapIntoIter, TreapMapIter};
-use std::borrow::Borrow;
-use std::ops::{Add, Sub};
+use core::borrow::Borrow;
+use core::fmt;
+use core::iter::FromIterator;
+use core::ops::{Add, RangeBounds, Sub};
+use rand::XorShiftRng;
 
 /// An ordered set implemented using a treap.
 ///
@@ -27,6 +32,7 @@ use std::ops::{Add, Sub};
 /// assert_eq!(set.remove(&0), Some(0));
 /// assert_eq!(set.remove(&1), None);
 /// ```
+#[derive(Clone)]
 pub struct TreapSet<T> {
     map: TreapMap<T, ()>,
 }
@@ -47,6 +53,40 @@ impl<T> TreapSet<T> {
         }
     }
 
+    /// Constructs a new, empty `TreapSet<T>` that uses `rng` to generate the priorities of its
+    /// nodes. Seeding `rng` makes the internal structure reproducible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    /// use rand::{SeedableRng, XorShiftRng};
+    ///
+    /// let set: TreapSet<u32> = TreapSet::with_rng(XorShiftRng::from_seed([1, 1, 1, 1]));
+    /// ```
+    pub fn with_rng(rng: XorShiftRng) -> Self {
+        TreapSet {
+            map: TreapMap::with_rng(rng),
+        }
+    }
+
+    /// Constructs a new, empty `TreapSet<T>` that generates the priorities of its nodes with a
+    /// random number generator seeded with `seed`. Sets constructed with the same seed have the
+    /// same internal structure after the same sequence of operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let set: TreapSet<u32> = TreapSet::with_seed(1);
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        TreapSet {
+            map: TreapMap::with_seed(seed),
+        }
+    }
+
     /// Inserts a key into the set. If the key already exists in the set, it will return and
     /// replace the key.
     ///
@@ -80,9 +120,10 @@ impl<T> TreapSet<T> {
     /// assert_eq!(set.remove(&1), Some(1));
     /// assert_eq!(set.remove(&1), None);
     /// ```
-    pub fn remove(&mut self, key: &T) -> Option<T>
+    pub fn remove<V>(&mut self, key: &V) -> Option<T>
     where
-        T: Ord,
+        T: Borrow<V>,
+        V: Ord + ?Sized,
     {
         self.map.remove(key).map(|pair| pair.0)
     }
@@ -153,6 +194,49 @@ impl<T> TreapSet<T> {
         self.map.clear();
     }
 
+    /// Retains only the keys that satisfy a predicate. The keys are visited in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let mut set = TreapSet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    /// set.insert(3);
+    ///
+    /// set.retain(|key| key % 2 == 1);
+    /// assert_eq!(set.iter().collect::<Vec<&u32>>(), vec![&1, &3]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.map.retain(|key, _| f(key));
+    }
+
+    /// Clears the set, returning all keys as an iterator. The iterator yields the keys in
+    /// ascending order, and the set can be reused afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let mut set = TreapSet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    ///
+    /// assert_eq!(set.drain().collect::<Vec<u32>>(), vec![1, 2]);
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> TreapSetIntoIter<T> {
+        TreapSetIntoIter {
+            map_iter: self.map.drain(),
+        }
+    }
+
     /// Returns a key in the set that is less than or equal to a particular key. Returns `None` if
     /// such a key does not exist.
     ///
@@ -252,15 +336,43 @@ impl<T> TreapSet<T> {
     /// assert!(set.contains(&2));
     /// assert!(split.contains(&3));
     /// ```
-    pub fn split_off(&mut self, key: &T, inclusive: bool) -> Self
+    pub fn split_off<V>(&mut self, key: &V, inclusive: bool) -> Self
     where
-        T: Ord,
+        T: Borrow<V>,
+        V: Ord + ?Sized,
     {
         TreapSet {
             map: self.map.split_off(key, inclusive),
         }
     }
 
+    /// Removes all keys that are in `range` from the set and returns them as a new set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let mut set = TreapSet::new();
+    /// for key in 0..5 {
+    ///     set.insert(key);
+    /// }
+    ///
+    /// let removed = set.remove_range(1..3);
+    /// assert_eq!(removed.iter().collect::<Vec<&u32>>(), vec![&1, &2]);
+    /// assert_eq!(set.iter().collect::<Vec<&u32>>(), vec![&0, &3, &4]);
+    /// ```
+    pub fn remove_range<V, R>(&mut self, range: R) -> Self
+    where
+        T: Borrow<V>,
+        V: Ord + ?Sized,
+        R: RangeBounds<V>,
+    {
+        TreapSet {
+            map: self.map.remove_range(range),
+        }
+    }
+
     /// Returns the union of two set. The `+` operator is implemented to take the union of two
     /// sets.
     ///
@@ -375,6 +487,330 @@ impl<T> TreapSet<T> {
         }
     }
 
+    /// Returns the union of two sets, splitting the work across threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let n: TreapSet<u32> = (0..100).collect();
+    /// let m: TreapSet<u32> = (50..150).collect();
+    ///
+    /// let union = TreapSet::par_union(n, m);
+    /// assert_eq!(union.len(), 150);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn par_union(left: Self, right: Self) -> Self
+    where
+        T: Ord + Send,
+    {
+        TreapSet {
+            map: TreapMap::par_union(left.map, right.map),
+        }
+    }
+
+    /// Returns the intersection of two sets, splitting the work across threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let n: TreapSet<u32> = (0..100).collect();
+    /// let m: TreapSet<u32> = (50..150).collect();
+    ///
+    /// let intersection = TreapSet::par_intersection(n, m);
+    /// assert_eq!(intersection.len(), 50);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn par_intersection(left: Self, right: Self) -> Self
+    where
+        T: Ord + Send,
+    {
+        TreapSet {
+            map: TreapMap::par_intersection(left.map, right.map),
+        }
+    }
+
+    /// Returns the difference of `left` and `right`, splitting the work across threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let n: TreapSet<u32> = (0..100).collect();
+    /// let m: TreapSet<u32> = (50..150).collect();
+    ///
+    /// let difference = TreapSet::par_difference(n, m);
+    /// assert_eq!(difference.len(), 50);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn par_difference(left: Self, right: Self) -> Self
+    where
+        T: Ord + Send,
+    {
+        TreapSet {
+            map: TreapMap::par_difference(left.map, right.map),
+        }
+    }
+
+    /// Returns the symmetric difference of `left` and `right`, splitting the work across threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let n: TreapSet<u32> = (0..100).collect();
+    /// let m: TreapSet<u32> = (50..150).collect();
+    ///
+    /// let symmetric_difference = TreapSet::par_symmetric_difference(n, m);
+    /// assert_eq!(symmetric_difference.len(), 100);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn par_symmetric_difference(left: Self, right: Self) -> Self
+    where
+        T: Ord + Send,
+    {
+        TreapSet {
+            map: TreapMap::par_symmetric_difference(left.map, right.map),
+        }
+    }
+
+    /// Returns a lazy iterator over the union of two sets without consuming them. The iterator
+    /// will yield keys in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let mut n = TreapSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = TreapSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// assert_eq!(
+    ///     n.union_iter(&m).collect::<Vec<&u32>>(),
+    ///     vec![&1, &2, &3],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn union_iter<'a>(&'a self, other: &'a Self) -> UnionIter<TreapSetIter<'a, T>>
+    where
+        T: Ord,
+    {
+        UnionIter::new(self.iter(), other.iter())
+    }
+
+    /// Returns a lazy iterator over the intersection of two sets without consuming them. The
+    /// iterator will yield keys in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let mut n = TreapSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = TreapSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// assert_eq!(
+    ///     n.intersection_iter(&m).collect::<Vec<&u32>>(),
+    ///     vec![&2],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn intersection_iter<'a>(&'a self, other: &'a Self) -> IntersectionIter<TreapSetIter<'a, T>>
+    where
+        T: Ord,
+    {
+        IntersectionIter::new(self.iter(), other.iter())
+    }
+
+    /// Returns a lazy iterator over the difference of two sets without consuming them. The
+    /// iterator will yield the keys of `self` that are not in `other` in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let mut n = TreapSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = TreapSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// assert_eq!(
+    ///     n.difference_iter(&m).collect::<Vec<&u32>>(),
+    ///     vec![&1],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn difference_iter<'a>(&'a self, other: &'a Self) -> DifferenceIter<TreapSetIter<'a, T>>
+    where
+        T: Ord,
+    {
+        DifferenceIter::new(self.iter(), other.iter())
+    }
+
+    /// Returns a lazy iterator over the symmetric difference of two sets without consuming
+    /// them. The iterator will yield the keys that exist in one set, but not both sets, in
+    /// ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let mut n = TreapSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = TreapSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// assert_eq!(
+    ///     n.symmetric_difference_iter(&m).collect::<Vec<&u32>>(),
+    ///     vec![&1, &3],
+    /// );
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn symmetric_difference_iter<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> SymmetricDifferenceIter<TreapSetIter<'a, T>>
+    where
+        T: Ord,
+    {
+        SymmetricDifferenceIter::new(self.iter(), other.iter())
+    }
+
+    /// Returns the union of two sets without consuming them. Only the keys of the result are cloned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let mut n = TreapSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = TreapSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// let union = n.union_cloned(&m);
+    /// assert_eq!(union.iter().collect::<Vec<&u32>>(), vec![&1, &2, &3]);
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn union_cloned(&self, other: &Self) -> Self
+    where
+        T: Ord + Clone,
+    {
+        TreapSet {
+            map: self.map.union_cloned(&other.map),
+        }
+    }
+
+    /// Returns the intersection of two sets without consuming them. Only the keys of the result are cloned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let mut n = TreapSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = TreapSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// let intersection = n.intersection_cloned(&m);
+    /// assert_eq!(intersection.iter().collect::<Vec<&u32>>(), vec![&2]);
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn intersection_cloned(&self, other: &Self) -> Self
+    where
+        T: Ord + Clone,
+    {
+        TreapSet {
+            map: self.map.intersection_cloned(&other.map),
+        }
+    }
+
+    /// Returns the difference of `self` and `other` without consuming them. Only the keys of the result are cloned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let mut n = TreapSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = TreapSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// let difference = n.difference_cloned(&m);
+    /// assert_eq!(difference.iter().collect::<Vec<&u32>>(), vec![&1]);
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn difference_cloned(&self, other: &Self) -> Self
+    where
+        T: Ord + Clone,
+    {
+        TreapSet {
+            map: self.map.difference_cloned(&other.map),
+        }
+    }
+
+    /// Returns the symmetric difference of `self` and `other` without consuming them. Only the keys of the result are cloned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use extended_collections::treap::TreapSet;
+    ///
+    /// let mut n = TreapSet::new();
+    /// n.insert(1);
+    /// n.insert(2);
+    ///
+    /// let mut m = TreapSet::new();
+    /// m.insert(2);
+    /// m.insert(3);
+    ///
+    /// let symmetric_difference = n.symmetric_difference_cloned(&m);
+    /// assert_eq!(symmetric_difference.iter().collect::<Vec<&u32>>(), vec![&1, &3]);
+    /// assert_eq!(n.len(), 2);
+    /// ```
+    pub fn symmetric_difference_cloned(&self, other: &Self) -> Self
+    where
+        T: Ord + Clone,
+    {
+        TreapSet {
+            map: self.map.symmetric_difference_cloned(&other.map),
+        }
+    }
+
     /// Returns an iterator over the set. The iterator will yield keys using in-order traversal.
     ///
     /// # Examples
@@ -454,12 +890,109 @@ where
     }
 }
 
+impl<T> OrderedSet<T> for TreapSet<T>
+where
+    T: Ord,
+{
+    type Iter<'a>
+        = TreapSetIter<'a, T>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn insert(&mut self, key: T) -> Option<T> {
+        TreapSet::insert(self, key)
+    }
+
+    fn remove(&mut self, key: &T) -> Option<T> {
+        TreapSet::remove(self, key)
+    }
+
+    fn contains(&self, key: &T) -> bool {
+        TreapSet::contains(self, key)
+    }
+
+    fn len(&self) -> usize {
+        TreapSet::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        TreapSet::is_empty(self)
+    }
+
+    fn floor(&self, key: &T) -> Option<&T> {
+        TreapSet::floor(self, key)
+    }
+
+    fn ceil(&self, key: &T) -> Option<&T> {
+        TreapSet::ceil(self, key)
+    }
+
+    fn min(&self) -> Option<&T> {
+        TreapSet::min(self)
+    }
+
+    fn max(&self) -> Option<&T> {
+        TreapSet::max(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        TreapSet::iter(self)
+    }
+}
+
+impl<T> fmt::Debug for TreapSet<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T> PartialEq for TreapSet<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T> Eq for TreapSet<T> where T: Eq {}
+
 impl<T> Default for TreapSet<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl<T> FromIterator<T> for TreapSet<T>
+where
+    T: Ord,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        TreapSet {
+            map: iter.into_iter().map(|key| (key, ())).collect(),
+        }
+    }
+}
+
+impl<T> Extend<T> for TreapSet<T>
+where
+    T: Ord,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.map.extend(iter.into_iter().map(|key| (key, ())));
+    }
+}
+
 impl<T> Add for TreapSet<T>
 where
     T: Ord,
@@ -658,6 +1191,90 @@ mod tests {
         assert_eq!(symmetric_difference.len(), 4);
     }
 
+    #[test]
+    fn test_set_op_iters() {
+        let mut n = TreapSet::new();
+        n.insert(1);
+        n.insert(2);
+        n.insert(3);
+
+        let mut m = TreapSet::new();
+        m.insert(3);
+        m.insert(4);
+        m.insert(5);
+
+        assert_eq!(
+            n.union_iter(&m).collect::<Vec<&u32>>(),
+            vec![&1, &2, &3, &4, &5],
+        );
+        assert_eq!(n.intersection_iter(&m).collect::<Vec<&u32>>(), vec![&3]);
+        assert_eq!(n.difference_iter(&m).collect::<Vec<&u32>>(), vec![&1, &2]);
+        assert_eq!(
+            n.symmetric_difference_iter(&m).collect::<Vec<&u32>>(),
+            vec![&1, &2, &4, &5],
+        );
+        assert_eq!(n.len(), 3);
+        assert_eq!(m.len(), 3);
+    }
+
+    #[test]
+    fn test_cloned_set_ops() {
+        let mut n = TreapSet::new();
+        n.insert(1);
+        n.insert(2);
+        n.insert(3);
+
+        let mut m = TreapSet::new();
+        m.insert(3);
+        m.insert(4);
+        m.insert(5);
+
+        assert_eq!(
+            n.union_cloned(&m).into_iter().collect::<Vec<u32>>(),
+            vec![1, 2, 3, 4, 5],
+        );
+        assert_eq!(
+            n.intersection_cloned(&m).into_iter().collect::<Vec<u32>>(),
+            vec![3]
+        );
+        assert_eq!(
+            n.difference_cloned(&m).into_iter().collect::<Vec<u32>>(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            n.symmetric_difference_cloned(&m)
+                .into_iter()
+                .collect::<Vec<u32>>(),
+            vec![1, 2, 4, 5],
+        );
+        assert_eq!(n.len(), 3);
+        assert_eq!(m.len(), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_par_set_ops() {
+        let n: TreapSet<u32> = (0..10_000).map(|key| key * 2).collect();
+        let m: TreapSet<u32> = (0..10_000).map(|key| key * 3).collect();
+
+        assert_eq!(
+            TreapSet::par_union(n.clone(), m.clone()),
+            TreapSet::union(n.clone(), m.clone()),
+        );
+        assert_eq!(
+            TreapSet::par_intersection(n.clone(), m.clone()),
+            TreapSet::intersection(n.clone(), m.clone()),
+        );
+        assert_eq!(
+            TreapSet::par_difference(n.clone(), m.clone()),
+            TreapSet::difference(n.clone(), m.clone()),
+        );
+        assert_eq!(
+            TreapSet::par_symmetric_difference(n.clone(), m.clone()),
+            TreapSet::symmetric_difference(n, m),
+        );
+    }
+
     #[test]
     fn test_into_iter() {
         let mut set = TreapSet::new();
@@ -668,6 +1285,13 @@ mod tests {
         assert_eq!(set.into_iter().collect::<Vec<u32>>(), vec![1, 3, 5]);
     }
 
+    #[test]
+    fn test_from_iter() {
+        let mut set: TreapSet<u32> = vec![3, 1, 2, 1].into_iter().collect();
+        set.extend(vec![5, 4]);
+        assert_eq!(set.into_iter().collect::<Vec<u32>>(), vec![1, 2, 3, 4, 5]);
+    }
+
     #[test]
     fn test_iter() {
         let mut set = TreapSet::new();
@@ -677,4 +1301,55 @@ mod tests {
 
         assert_eq!(set.iter().collect::<Vec<&u32>>(), vec![&1, &3, &5]);
     }
+
+    #[test]
+    fn test_clone_eq() {
+        let mut set = TreapSet::new();
+        set.insert(1);
+        set.insert(5);
+        set.insert(3);
+
+        let mut cloned = set.clone();
+        assert_eq!(cloned, set);
+        cloned.insert(7);
+        assert_ne!(cloned, set);
+        cloned.remove(&7);
+        assert_eq!(cloned, set);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut set = TreapSet::new();
+        set.insert(3);
+        set.insert(1);
+        assert_eq!(format!("{:?}", set), "{1, 3}");
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut set = TreapSet::new();
+        for key in 0..100 {
+            set.insert(key);
+        }
+
+        set.retain(|key| key % 3 == 0);
+        assert_eq!(set.len(), 34);
+        assert_eq!(
+            set.into_iter().collect::<Vec<u32>>(),
+            (0..100).filter(|key| key % 3 == 0).collect::<Vec<u32>>(),
+        );
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut set = TreapSet::new();
+        set.insert(1);
+        set.insert(5);
+        set.insert(3);
+
+        assert_eq!(set.drain().collect::<Vec<u32>>(), vec![1, 3, 5]);
+        assert!(set.is_empty());
+        set.insert(1);
+        assert!(set.contains(&1));
+    }
 }
@@ -1,11 +1,23 @@
+use crate::compare::Compare;
 use crate::entry::Entry;
 use crate::treap::node::Node;
-use std::borrow::Borrow;
-use std::cmp::Ordering;
-use std::mem;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::mem;
+#[cfg(feature = "std")]
+use std::panic;
+#[cfg(feature = "std")]
+use std::thread;
 
 pub type Tree<T, U> = Option<Box<Node<T, U>>>;
 
+// The minimum combined size of two trees for a parallel set operation to split its work across
+// threads. Smaller trees are combined sequentially.
+#[cfg(feature = "std")]
+const PARALLEL_THRESHOLD: usize = 1 << 12;
+
 pub fn merge<T, U>(l_tree: &mut Tree<T, U>, r_tree: Tree<T, U>) {
     match (l_tree.take(), r_tree) {
         (Some(mut l_node), Some(mut r_node)) => {
@@ -25,24 +37,25 @@ pub fn merge<T, U>(l_tree: &mut Tree<T, U>, r_tree: Tree<T, U>) {
     }
 }
 
-pub fn split<T, U, V>(tree: &mut Tree<T, U>, key: &V) -> (Tree<T, U>, Tree<T, U>)
+pub fn split<T, U, V, C>(tree: &mut Tree<T, U>, key: &V, compare: &C) -> (Tree<T, U>, Tree<T, U>)
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
     match tree.take() {
         Some(mut node) => {
             let ret;
-            match key.cmp(node.entry.key.borrow()) {
+            match compare.compare(key, node.entry.key.borrow()) {
                 Ordering::Less => {
-                    let res = split(&mut node.left, key);
+                    let res = split(&mut node.left, key, compare);
                     *tree = node.left.take();
                     node.left = res.1;
                     node.update();
                     ret = (res.0, Some(node));
                 }
                 Ordering::Greater => {
-                    ret = split(&mut node.right, key);
+                    ret = split(&mut node.right, key, compare);
                     node.update();
                     *tree = Some(node);
                 }
@@ -59,21 +72,87 @@ where
     }
 }
 
-pub fn insert<T, U>(tree: &mut Tree<T, U>, mut new_node: Node<T, U>) -> Option<Entry<T, U>>
+// Builds a treap from nodes with strictly ascending keys in linear time by maintaining the right
+// spine of the tree on a stack.
+pub fn build<T, U, I>(nodes: I) -> Tree<T, U>
+where
+    I: IntoIterator<Item = Node<T, U>>,
+{
+    let mut spine: Vec<Box<Node<T, U>>> = Vec::new();
+    for node in nodes {
+        let mut new_node = Box::new(node);
+        let mut left = None;
+        while let Some(mut top) = spine.pop() {
+            if top.priority >= new_node.priority {
+                spine.push(top);
+                break;
+            }
+            top.right = left;
+            top.update();
+            left = Some(top);
+        }
+        new_node.left = left;
+        spine.push(new_node);
+    }
+
+    let mut ret = None;
+    while let Some(mut top) = spine.pop() {
+        top.right = ret;
+        top.update();
+        ret = Some(top);
+    }
+    ret
+}
+
+// Removes all entries whose key-value pairs do not satisfy `f` by merging the children of each
+// removed node. The entries are visited in ascending order.
+pub fn retain<T, U, F>(tree: &mut Tree<T, U>, f: &mut F)
 where
-    T: Ord,
+    F: FnMut(&T, &mut U) -> bool,
+{
+    let keep = match tree {
+        Some(ref mut node) => {
+            retain(&mut node.left, f);
+            let keep = {
+                let Entry {
+                    ref key,
+                    ref mut value,
+                } = node.entry;
+                f(key, value)
+            };
+            retain(&mut node.right, f);
+            node.update();
+            keep
+        }
+        None => return,
+    };
+
+    if !keep {
+        let mut node = tree.take().expect("Expected a non-empty tree.");
+        *tree = node.left.take();
+        merge(tree, node.right.take());
+    }
+}
+
+pub fn insert<T, U, C>(
+    tree: &mut Tree<T, U>,
+    mut new_node: Node<T, U>,
+    compare: &C,
+) -> Option<Entry<T, U>>
+where
+    C: Compare<T>,
 {
     match tree {
         Some(ref mut node) => {
             if new_node.priority <= node.priority {
-                match new_node.entry.key.cmp(&node.entry.key) {
+                match compare.compare(&new_node.entry.key, &node.entry.key) {
                     Ordering::Less => {
-                        let ret = insert(&mut node.left, new_node);
+                        let ret = insert(&mut node.left, new_node, compare);
                         node.update();
                         return ret;
                     }
                     Ordering::Greater => {
-                        let ret = insert(&mut node.right, new_node);
+                        let ret = insert(&mut node.right, new_node, compare);
                         node.update();
                         return ret;
                     }
@@ -90,28 +169,29 @@ where
         }
     }
     new_node.left = tree.take();
-    let (dup_opt, right) = split(&mut new_node.left, &new_node.entry.key);
+    let (dup_opt, right) = split(&mut new_node.left, &new_node.entry.key, compare);
     new_node.right = right;
     new_node.update();
     *tree = Some(Box::new(new_node));
     dup_opt.map(|node| node.entry)
 }
 
-pub fn remove<T, U, V>(tree: &mut Tree<T, U>, key: &V) -> Option<Entry<T, U>>
+pub fn remove<T, U, V, C>(tree: &mut Tree<T, U>, key: &V, compare: &C) -> Option<Entry<T, U>>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
     let mut new_tree;
     match tree {
-        Some(ref mut node) => match key.cmp(node.entry.key.borrow()) {
+        Some(ref mut node) => match compare.compare(key, node.entry.key.borrow()) {
             Ordering::Less => {
-                let ret = remove(&mut node.left, key);
+                let ret = remove(&mut node.left, key, compare);
                 node.update();
                 return ret;
             }
             Ordering::Greater => {
-                let ret = remove(&mut node.right, key);
+                let ret = remove(&mut node.right, key, compare);
                 node.update();
                 return ret;
             }
@@ -125,68 +205,173 @@ where
     mem::replace(tree, new_tree).map(|node| node.entry)
 }
 
-pub fn get<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V) -> Option<&'a Entry<T, U>>
+pub fn pop_min<T, U>(tree: &mut Tree<T, U>) -> Option<Entry<T, U>> {
+    let new_tree = match tree {
+        Some(ref mut node) => {
+            if node.left.is_some() {
+                let ret = pop_min(&mut node.left);
+                node.update();
+                return ret;
+            }
+            node.right.take()
+        }
+        None => return None,
+    };
+    mem::replace(tree, new_tree).map(|node| node.entry)
+}
+
+pub fn pop_max<T, U>(tree: &mut Tree<T, U>) -> Option<Entry<T, U>> {
+    let new_tree = match tree {
+        Some(ref mut node) => {
+            if node.right.is_some() {
+                let ret = pop_max(&mut node.right);
+                node.update();
+                return ret;
+            }
+            node.left.take()
+        }
+        None => return None,
+    };
+    mem::replace(tree, new_tree).map(|node| node.entry)
+}
+
+pub fn get<'a, T, U, V, C>(tree: &'a Tree<T, U>, key: &V, compare: &C) -> Option<&'a Entry<T, U>>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
     tree.as_ref()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Less => get(&node.left, key),
-            Ordering::Greater => get(&node.right, key),
+        .and_then(|node| match compare.compare(key, node.entry.key.borrow()) {
+            Ordering::Less => get(&node.left, key, compare),
+            Ordering::Greater => get(&node.right, key, compare),
             Ordering::Equal => Some(&node.entry),
         })
 }
 
-pub fn get_mut<'a, T, U, V>(tree: &'a mut Tree<T, U>, key: &V) -> Option<&'a mut Entry<T, U>>
+pub fn get_mut<'a, T, U, V, C>(
+    tree: &'a mut Tree<T, U>,
+    key: &V,
+    compare: &C,
+) -> Option<&'a mut Entry<T, U>>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
     tree.as_mut()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Less => get_mut(&mut node.left, key),
-            Ordering::Greater => get_mut(&mut node.right, key),
+        .and_then(|node| match compare.compare(key, node.entry.key.borrow()) {
+            Ordering::Less => get_mut(&mut node.left, key, compare),
+            Ordering::Greater => get_mut(&mut node.right, key, compare),
             Ordering::Equal => Some(&mut node.entry),
         })
 }
 
-pub fn ceil<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V) -> Option<&'a Entry<T, U>>
+pub fn ceil<'a, T, U, V, C>(
+    tree: &'a Tree<T, U>,
+    key: &V,
+    inclusive: bool,
+    compare: &C,
+) -> Option<&'a Entry<T, U>>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
-    tree.as_ref()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Greater => ceil(&node.right, key),
-            Ordering::Less => match ceil(&node.left, key) {
-                None => Some(&node.entry),
-                res => res,
-            },
-            Ordering::Equal => Some(&node.entry),
-        })
+    let mut curr = tree;
+    let mut ret = None;
+    while let Some(node) = curr {
+        match compare.compare(key, node.entry.key.borrow()) {
+            Ordering::Less => {
+                ret = Some(&node.entry);
+                curr = &node.left;
+            }
+            Ordering::Equal if inclusive => return Some(&node.entry),
+            _ => curr = &node.right,
+        }
+    }
+    ret
 }
 
-pub fn floor<'a, T, U, V>(tree: &'a Tree<T, U>, key: &V) -> Option<&'a Entry<T, U>>
+pub fn ceil_mut<'a, T, U, V, C>(
+    tree: &'a mut Tree<T, U>,
+    key: &V,
+    inclusive: bool,
+    compare: &C,
+) -> Option<&'a mut Entry<T, U>>
 where
     T: Borrow<V>,
-    V: Ord + ?Sized,
+    V: ?Sized,
+    C: Compare<V>,
 {
-    tree.as_ref()
-        .and_then(|node| match key.cmp(node.entry.key.borrow()) {
-            Ordering::Less => floor(&node.left, key),
-            Ordering::Greater => match floor(&node.right, key) {
-                None => Some(&node.entry),
-                res => res,
-            },
-            Ordering::Equal => Some(&node.entry),
-        })
+    let mut curr = tree;
+    let mut ret = None;
+    while let Some(node) = curr {
+        match compare.compare(key, node.entry.key.borrow()) {
+            Ordering::Less => {
+                ret = Some(&mut node.entry);
+                curr = &mut node.left;
+            }
+            Ordering::Equal if inclusive => return Some(&mut node.entry),
+            _ => curr = &mut node.right,
+        }
+    }
+    ret
 }
 
-pub fn min<T, U>(tree: &Tree<T, U>) -> Option<&Entry<T, U>>
+pub fn floor<'a, T, U, V, C>(
+    tree: &'a Tree<T, U>,
+    key: &V,
+    inclusive: bool,
+    compare: &C,
+) -> Option<&'a Entry<T, U>>
 where
-    T: Ord,
+    T: Borrow<V>,
+    V: ?Sized,
+    C: Compare<V>,
+{
+    let mut curr = tree;
+    let mut ret = None;
+    while let Some(node) = curr {
+        match compare.compare(key, node.entry.key.borrow()) {
+            Ordering::Greater => {
+                ret = Some(&node.entry);
+                curr = &node.right;
+            }
+            Ordering::Equal if inclusive => return Some(&node.entry),
+            _ => curr = &node.left,
+        }
+    }
+    ret
+}
+
+pub fn floor_mut<'a, T, U, V, C>(
+    tree: &'a mut Tree<T, U>,
+    key: &V,
+    inclusive: bool,
+    compare: &C,
+) -> Option<&'a mut Entry<T, U>>
+where
+    T: Borrow<V>,
+    V: ?Sized,
+    C: Compare<V>,
 {
+    let mut curr = tree;
+    let mut ret = None;
+    while let Some(node) = curr {
+        match compare.compare(key, node.entry.key.borrow()) {
+            Ordering::Greater => {
+                ret = Some(&mut node.entry);
+                curr = &mut node.right;
+            }
+            Ordering::Equal if inclusive => return Some(&mut node.entry),
+            _ => curr = &mut node.left,
+        }
+    }
+    ret
+}
+
+pub fn min<T, U>(tree: &Tree<T, U>) -> Option<&Entry<T, U>> {
     tree.as_ref().and_then(|node| {
         let mut curr = node;
         while let Some(ref left_node) = curr.left {
@@ -196,10 +381,7 @@ where
     })
 }
 
-pub fn max<T, U>(tree: &Tree<T, U>) -> Option<&Entry<T, U>>
-where
-    T: Ord,
-{
+pub fn max<T, U>(tree: &Tree<T, U>) -> Option<&Entry<T, U>> {
     tree.as_ref().and_then(|node| {
         let mut curr = node;
         while let Some(ref right_node) = curr.right {
@@ -209,9 +391,14 @@ where
     })
 }
 
-pub fn union<T, U>(left_tree: Tree<T, U>, right_tree: Tree<T, U>, mut swapped: bool) -> Tree<T, U>
+pub fn union<T, U, C>(
+    left_tree: Tree<T, U>,
+    right_tree: Tree<T, U>,
+    mut swapped: bool,
+    compare: &C,
+) -> Tree<T, U>
 where
-    T: Ord,
+    C: Compare<T>,
 {
     match (left_tree, right_tree) {
         (Some(mut left_node), Some(mut right_node)) => {
@@ -227,9 +414,10 @@ where
                     ..
                 } = &mut *left_node;
                 let mut right_left_subtree = Some(right_node);
-                let (dup_opt, right_right_subtree) = split(&mut right_left_subtree, &entry.key);
-                *left_subtree = union(left_subtree.take(), right_left_subtree, swapped);
-                *right_subtree = union(right_subtree.take(), right_right_subtree, swapped);
+                let (dup_opt, right_right_subtree) =
+                    split(&mut right_left_subtree, &entry.key, compare);
+                *left_subtree = union(left_subtree.take(), right_left_subtree, swapped, compare);
+                *right_subtree = union(right_subtree.take(), right_right_subtree, swapped, compare);
                 if let Some(dup_node) = dup_opt {
                     if swapped {
                         *entry = dup_node.entry;
@@ -244,13 +432,14 @@ where
     }
 }
 
-pub fn intersection<T, U>(
+pub fn intersection<T, U, C>(
     left_tree: Tree<T, U>,
     right_tree: Tree<T, U>,
     mut swapped: bool,
+    compare: &C,
 ) -> Tree<T, U>
 where
-    T: Ord,
+    C: Compare<T>,
 {
     match (left_tree, right_tree) {
         (Some(mut left_node), Some(mut right_node)) => {
@@ -266,9 +455,12 @@ where
                     ..
                 } = &mut *left_node;
                 let mut right_left_subtree = Some(right_node);
-                let (dup_opt, right_right_subtree) = split(&mut right_left_subtree, &entry.key);
-                *left_subtree = intersection(left_subtree.take(), right_left_subtree, swapped);
-                *right_subtree = intersection(right_subtree.take(), right_right_subtree, swapped);
+                let (dup_opt, right_right_subtree) =
+                    split(&mut right_left_subtree, &entry.key, compare);
+                *left_subtree =
+                    intersection(left_subtree.take(), right_left_subtree, swapped, compare);
+                *right_subtree =
+                    intersection(right_subtree.take(), right_right_subtree, swapped, compare);
                 match dup_opt {
                     Some(dup_node) => {
                         if swapped {
@@ -288,14 +480,15 @@ where
     }
 }
 
-pub fn difference<T, U>(
+pub fn difference<T, U, C>(
     left_tree: Tree<T, U>,
     right_tree: Tree<T, U>,
     mut swapped: bool,
     symmetric: bool,
+    compare: &C,
 ) -> Tree<T, U>
 where
-    T: Ord,
+    C: Compare<T>,
 {
     match (left_tree, right_tree) {
         (Some(mut left_node), Some(mut right_node)) => {
@@ -311,14 +504,21 @@ where
                     ..
                 } = &mut *left_node;
                 let mut right_left_subtree = Some(right_node);
-                let (dup_opt, right_right_subtree) = split(&mut right_left_subtree, &entry.key);
-                *left_subtree =
-                    difference(left_subtree.take(), right_left_subtree, swapped, symmetric);
+                let (dup_opt, right_right_subtree) =
+                    split(&mut right_left_subtree, &entry.key, compare);
+                *left_subtree = difference(
+                    left_subtree.take(),
+                    right_left_subtree,
+                    swapped,
+                    symmetric,
+                    compare,
+                );
                 *right_subtree = difference(
                     right_subtree.take(),
                     right_right_subtree,
                     swapped,
                     symmetric,
+                    compare,
                 );
                 if dup_opt.is_some() || (swapped && !symmetric) {
                     merge(left_subtree, right_subtree.take());
@@ -340,3 +540,233 @@ where
         }
     }
 }
+
+// Returns the number of levels of recursion that a parallel set operation should fork at. The
+// depth is chosen so that there are a few more tasks than available threads to balance the load.
+#[cfg(feature = "std")]
+pub fn parallel_depth() -> usize {
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    threads.next_power_of_two().trailing_zeros() as usize + 2
+}
+
+// Runs `left` on the current thread and `right` on a scoped thread, returning both results.
+// Panics in either closure are propagated to the caller.
+#[cfg(feature = "std")]
+fn join<A, B, RA, RB>(left: A, right: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA,
+    B: FnOnce() -> RB + Send,
+    RB: Send,
+{
+    thread::scope(|scope| {
+        let handle = scope.spawn(right);
+        let left_ret = left();
+        let right_ret = handle
+            .join()
+            .unwrap_or_else(|err| panic::resume_unwind(err));
+        (left_ret, right_ret)
+    })
+}
+
+#[cfg(feature = "std")]
+fn is_parallel<T, U>(left_node: &Node<T, U>, right_node: &Node<T, U>, depth: usize) -> bool {
+    depth > 0 && left_node.len + right_node.len >= PARALLEL_THRESHOLD
+}
+
+#[cfg(feature = "std")]
+pub fn par_union<T, U, C>(
+    left_tree: Tree<T, U>,
+    right_tree: Tree<T, U>,
+    mut swapped: bool,
+    depth: usize,
+    compare: &C,
+) -> Tree<T, U>
+where
+    T: Send,
+    U: Send,
+    C: Compare<T> + Sync,
+{
+    match (left_tree, right_tree) {
+        (Some(mut left_node), Some(mut right_node)) => {
+            if !is_parallel(&left_node, &right_node, depth) {
+                return union(Some(left_node), Some(right_node), swapped, compare);
+            }
+            if left_node.priority < right_node.priority {
+                mem::swap(&mut left_node, &mut right_node);
+                swapped = !swapped;
+            }
+            let mut right_left_subtree = Some(right_node);
+            let (dup_opt, right_right_subtree) =
+                split(&mut right_left_subtree, &left_node.entry.key, compare);
+            let left_subtree = left_node.left.take();
+            let right_subtree = left_node.right.take();
+            let (left_subtree, right_subtree) = join(
+                move || {
+                    par_union(
+                        left_subtree,
+                        right_left_subtree,
+                        swapped,
+                        depth - 1,
+                        compare,
+                    )
+                },
+                move || {
+                    par_union(
+                        right_subtree,
+                        right_right_subtree,
+                        swapped,
+                        depth - 1,
+                        compare,
+                    )
+                },
+            );
+            left_node.left = left_subtree;
+            left_node.right = right_subtree;
+            if let Some(dup_node) = dup_opt {
+                if swapped {
+                    left_node.entry = dup_node.entry;
+                }
+            }
+            left_node.update();
+            Some(left_node)
+        }
+        (None, right_tree) => right_tree,
+        (left_tree, None) => left_tree,
+    }
+}
+
+#[cfg(feature = "std")]
+pub fn par_intersection<T, U, C>(
+    left_tree: Tree<T, U>,
+    right_tree: Tree<T, U>,
+    mut swapped: bool,
+    depth: usize,
+    compare: &C,
+) -> Tree<T, U>
+where
+    T: Send,
+    U: Send,
+    C: Compare<T> + Sync,
+{
+    match (left_tree, right_tree) {
+        (Some(mut left_node), Some(mut right_node)) => {
+            if !is_parallel(&left_node, &right_node, depth) {
+                return intersection(Some(left_node), Some(right_node), swapped, compare);
+            }
+            if left_node.priority < right_node.priority {
+                mem::swap(&mut left_node, &mut right_node);
+                swapped = !swapped;
+            }
+            let mut right_left_subtree = Some(right_node);
+            let (dup_opt, right_right_subtree) =
+                split(&mut right_left_subtree, &left_node.entry.key, compare);
+            let left_subtree = left_node.left.take();
+            let right_subtree = left_node.right.take();
+            let (mut left_subtree, right_subtree) = join(
+                move || {
+                    par_intersection(
+                        left_subtree,
+                        right_left_subtree,
+                        swapped,
+                        depth - 1,
+                        compare,
+                    )
+                },
+                move || {
+                    par_intersection(
+                        right_subtree,
+                        right_right_subtree,
+                        swapped,
+                        depth - 1,
+                        compare,
+                    )
+                },
+            );
+            match dup_opt {
+                Some(dup_node) => {
+                    if swapped {
+                        left_node.entry = dup_node.entry;
+                    }
+                }
+                None => {
+                    merge(&mut left_subtree, right_subtree);
+                    return left_subtree;
+                }
+            }
+            left_node.left = left_subtree;
+            left_node.right = right_subtree;
+            left_node.update();
+            Some(left_node)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(feature = "std")]
+pub fn par_difference<T, U, C>(
+    left_tree: Tree<T, U>,
+    right_tree: Tree<T, U>,
+    mut swapped: bool,
+    symmetric: bool,
+    depth: usize,
+    compare: &C,
+) -> Tree<T, U>
+where
+    T: Send,
+    U: Send,
+    C: Compare<T> + Sync,
+{
+    match (left_tree, right_tree) {
+        (Some(mut left_node), Some(mut right_node)) => {
+            if !is_parallel(&left_node, &right_node, depth) {
+                return difference(
+                    Some(left_node),
+                    Some(right_node),
+                    swapped,
+                    symmetric,
+                    compare,
+                );
+            }
+            if left_node.priority < right_node.priority {
+                mem::swap(&mut left_node, &mut right_node);
+                swapped = !swapped;
+            }
+            let mut right_left_subtree = Some(right_node);
+            let (dup_opt, right_right_subtree) =
+                split(&mut right_left_subtree, &left_node.entry.key, compare);
+            let left_subtree = left_node.left.take();
+            let right_subtree = left_node.right.take();
+            let (mut left_subtree, right_subtree) = join(
+                move || {
+                    par_difference(
+                        left_subtree,
+                        right_left_subtree,
+                        swapped,
+                        symmetric,
+                        depth - 1,
+                        compare,
+                    )
+                },
+                move || {
+                    par_difference(
+                        right_subtree,
+                        right_right_subtree,
+                        swapped,
+                        symmetric,
+                        depth - 1,
+                        compare,
+                    )
+                },
+            );
+            if dup_opt.is_some() || (swapped && !symmetric) {
+                merge(&mut left_subtree, right_subtree);
+                return left_subtree;
+            }
+            left_node.left = left_subtree;
+            left_node.right = right_subtree;
+            left_node.update();
+            Some(left_node)
+        }
+        (left_tree, right_tree) => difference(left_tree, right_tree, swapped, symmetric, compare),
+    }
+}
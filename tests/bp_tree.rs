@@ -1,3 +1,5 @@
+#![cfg(feature = "bp_tree")]
+
 use extended_collections::bp_tree::{BpMap, Result};
 use rand::{thread_rng, Rng};
 use std::fs;
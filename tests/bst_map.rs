@@ -5,7 +5,7 @@ macro_rules! bst_map_tests {
         $(
             mod $module_name {
                 use extended_collections::$module_name::$type_name;
-                use rand::{thread_rng, Rng};
+                use rand::Rng;
                 use super::NUM_OF_OPERATIONS;
 
                 #[test]
@@ -51,7 +51,7 @@ macro_rules! bst_map_tests {
                         assert_eq!(map.get(&entry.0), Some(&val_2));
                     }
 
-                    thread_rng().shuffle(&mut expected);
+                    rng.shuffle(&mut expected);
 
                     let mut expected_len = expected.len();
                     for entry in expected {
@@ -61,6 +61,61 @@ macro_rules! bst_map_tests {
                         assert_eq!(map.len(), expected_len);
                     }
                 }
+
+                #[test]
+                fn int_test_map_from_iter() {
+                    let mut rng: rand::XorShiftRng = rand::SeedableRng::from_seed([1, 1, 1, 1]);
+                    let mut expected: Vec<(u32, u32)> = (0..NUM_OF_OPERATIONS)
+                        .map(|_| (rng.gen(), rng.gen()))
+                        .collect();
+                    let map: $type_name<u32, u32> = expected.iter().cloned().collect();
+
+                    expected.reverse();
+                    expected.sort_by(|l, r| l.0.cmp(&r.0));
+                    expected.dedup_by_key(|pair| pair.0);
+
+                    assert_eq!(map.len(), expected.len());
+                    assert_eq!(map.into_iter().collect::<Vec<(u32, u32)>>(), expected);
+
+                    let mut sorted_map: $type_name<u32, u32> = expected.iter().cloned().collect();
+                    assert_eq!(sorted_map.len(), expected.len());
+                    assert_eq!(
+                        sorted_map.iter().map(|(key, value)| (*key, *value)).collect::<Vec<(u32, u32)>>(),
+                        expected,
+                    );
+                    for entry in &expected {
+                        assert_eq!(sorted_map.remove(&entry.0), Some((entry.0, entry.1)));
+                    }
+                    assert!(sorted_map.is_empty());
+
+                    let (left, right) = expected.split_at(expected.len() / 2);
+                    let mut extended_map: $type_name<u32, u32> = right.iter().cloned().collect();
+                    extended_map.extend(left.iter().cloned());
+                    assert_eq!(extended_map.into_iter().collect::<Vec<(u32, u32)>>(), expected);
+                }
+
+                #[test]
+                fn int_test_map_borrowed_keys() {
+                    let mut map = $type_name::new();
+                    for key in &["a", "c", "e"] {
+                        map.insert(key.to_string(), key.len());
+                    }
+
+                    assert!(map.contains_key("a"));
+                    assert!(!map.contains_key("b"));
+                    assert_eq!(map.get("c"), Some(&1));
+                    assert_eq!(map["e"], 1);
+                    *map.get_mut("c").expect("Expected value.") = 2;
+                    map["e"] = 3;
+                    assert_eq!(map.floor("d"), Some(&String::from("c")));
+                    assert_eq!(map.ceil("d"), Some(&String::from("e")));
+                    assert_eq!(map.remove("c"), Some((String::from("c"), 2)));
+                    assert_eq!(map.remove("c"), None);
+                    assert_eq!(
+                        map.into_iter().collect::<Vec<(String, usize)>>(),
+                        vec![(String::from("a"), 1), (String::from("e"), 3)],
+                    );
+                }
             }
         )*
     }
@@ -1,8 +1,21 @@
-use extended_collections::lsm_tree::compaction::{LeveledStrategy, SizeTieredStrategy};
-use extended_collections::lsm_tree::{LsmMap, Result};
+#![cfg(feature = "lsm")]
+
+use extended_collections::durability::SyncMode;
+use extended_collections::lsm_tree::compaction::{
+    CompactionInfo, CompactionStrategy, EventListener, FlushInfo, LeveledStrategy,
+    SizeTieredStrategy,
+};
+use extended_collections::lsm_tree::{
+    Error, IoOptions, LsmDatabase, LsmMap, Result, SSTableBuilder,
+};
 use rand::{thread_rng, Rng};
 use std::fs;
+use std::mem;
 use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
 use std::vec::Vec;
 
 fn teardown(test_name: &str) {
@@ -177,3 +190,1185 @@ fn int_test_lsm_map_leveled_strategy() -> Result<()> {
         test_name,
     )
 }
+
+#[test]
+fn int_test_lsm_map_size_tiered_strategy_close() -> Result<()> {
+    let test_name = "int_test_lsm_map_size_tiered_strategy_close";
+    run_test(
+        || {
+            let mut sts: SizeTieredStrategy<u32, u64> =
+                SizeTieredStrategy::new(test_name, 1000, 4, 4000, 0.5, 1.5)?;
+            sts.close()?;
+            match sts.get(&0u32) {
+                Err(Error::Closed) => {}
+                _ => panic!("Expected a closed error."),
+            }
+
+            let mut map: LsmMap<u32, u64, _> = LsmMap::new(SizeTieredStrategy::open(test_name)?);
+            for key in 0..1000u32 {
+                map.insert(key, u64::from(key))?;
+            }
+            map.close()?;
+
+            let mut map: LsmMap<u32, u64, _> = LsmMap::new(SizeTieredStrategy::open(test_name)?);
+            assert_eq!(map.len()?, 1000);
+            for key in 0..1000u32 {
+                assert_eq!(map.get(&key)?, Some(u64::from(key)));
+            }
+            Ok(())
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_open_errors() -> Result<()> {
+    let test_name = "int_test_lsm_map_open_errors";
+    run_test(
+        || {
+            let metadata_path = Path::new(test_name).join("metadata.dat");
+            match SizeTieredStrategy::<u32, u64>::open(test_name) {
+                Err(Error::FileIOError {
+                    path, operation, ..
+                }) => {
+                    assert_eq!(path, metadata_path);
+                    assert_eq!(operation, "open");
+                }
+                _ => panic!("Expected a file error."),
+            }
+
+            let mut sts: SizeTieredStrategy<u32, u64> =
+                SizeTieredStrategy::new(test_name, 1000, 4, 4000, 0.5, 1.5)?;
+            sts.close()?;
+            fs::write(&metadata_path, [255; 4])?;
+            match SizeTieredStrategy::<u32, u64>::open(test_name) {
+                Err(Error::Corruption { path, offset }) => {
+                    assert_eq!(path, metadata_path);
+                    assert_eq!(offset, 0);
+                }
+                _ => panic!("Expected a corruption error."),
+            }
+            Ok(())
+        },
+        test_name,
+    )
+}
+
+fn check_metadata_fallback<S, F>(test_name: &str, open: F) -> Result<()>
+where
+    S: CompactionStrategy<u32, u64>,
+    F: Fn() -> Result<S>,
+{
+    let metadata_path = Path::new(test_name).join("metadata.dat");
+    let prev_metadata_path = Path::new(test_name).join("metadata.dat.prev");
+    let mut map = LsmMap::new(open()?);
+    for key in 0..1000u32 {
+        map.insert(key, u64::from(key))?;
+    }
+    map.close()?;
+    assert!(prev_metadata_path.exists());
+
+    // a crash between the renames leaves only the previous version
+    let metadata = fs::read(&metadata_path)?;
+    fs::remove_file(&metadata_path)?;
+    let mut strategy = open()?;
+    for key in 0..1000u32 {
+        if let Some(value) = strategy.get(&key)? {
+            assert_eq!(value.data, Some(u64::from(key)));
+        }
+    }
+    mem::drop(strategy);
+
+    // a torn write of the current version falls back to the previous version
+    fs::write(&metadata_path, &metadata[..metadata.len() / 2])?;
+    let mut map = LsmMap::new(open()?);
+    assert!(map.len()? <= 1000);
+    for key in 0..1000u32 {
+        if let Some(value) = map.get(&key)? {
+            assert_eq!(value, u64::from(key));
+        }
+    }
+    map.close()?;
+
+    fs::remove_file(&metadata_path)?;
+    fs::write(&prev_metadata_path, [255; 4])?;
+    match open() {
+        Err(Error::FileIOError {
+            path, operation, ..
+        }) => {
+            assert_eq!(path, metadata_path);
+            assert_eq!(operation, "open");
+        }
+        _ => panic!("Expected a file error."),
+    }
+    Ok(())
+}
+
+#[test]
+fn int_test_lsm_map_size_tiered_strategy_metadata_fallback() -> Result<()> {
+    let test_name = "int_test_lsm_map_size_tiered_strategy_metadata_fallback";
+    run_test(
+        || {
+            SizeTieredStrategy::<u32, u64>::new(test_name, 1000, 4, 4000, 0.5, 1.5)?.close()?;
+            check_metadata_fallback(test_name, || SizeTieredStrategy::open(test_name))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_leveled_strategy_metadata_fallback() -> Result<()> {
+    let test_name = "int_test_lsm_map_leveled_strategy_metadata_fallback";
+    run_test(
+        || {
+            LeveledStrategy::<u32, u64>::new(test_name, 1000, 4, 4000, 10, 10)?.close()?;
+            check_metadata_fallback(test_name, || LeveledStrategy::open(test_name))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_collect_garbage() -> Result<()> {
+    let test_name = "int_test_lsm_map_collect_garbage";
+    run_test(
+        || {
+            let sts = SizeTieredStrategy::new(test_name, 1000, 4, 4000, 0.5, 1.5)?;
+            let mut map: LsmMap<u32, u64, _> = LsmMap::new(sts);
+            for key in 0..1000u32 {
+                map.insert(key, u64::from(key))?;
+            }
+            map.close()?;
+
+            let orphan_path = Path::new(test_name).join("orphan");
+            let temp_metadata_path = Path::new(test_name).join("metadata.dat.tmp");
+            fs::create_dir(&orphan_path)?;
+            fs::write(orphan_path.join("data.dat"), [0; 4])?;
+            fs::write(&temp_metadata_path, [0; 4])?;
+
+            let expected = vec![temp_metadata_path.clone(), orphan_path.clone()];
+            assert_eq!(
+                SizeTieredStrategy::<u32, u64>::collect_garbage(test_name, true)?,
+                expected,
+            );
+            assert!(orphan_path.exists());
+            assert!(temp_metadata_path.exists());
+
+            let mut map: LsmMap<u32, u64, _> = LsmMap::new(SizeTieredStrategy::open(test_name)?);
+            assert!(!orphan_path.exists());
+            assert!(!temp_metadata_path.exists());
+            assert_eq!(map.len()?, 1000);
+            for key in 0..1000u32 {
+                assert_eq!(map.get(&key)?, Some(u64::from(key)));
+            }
+            map.close()?;
+
+            assert!(SizeTieredStrategy::<u32, u64>::collect_garbage(test_name, false)?.is_empty());
+            Ok(())
+        },
+        test_name,
+    )
+}
+
+fn check_ingest_sstable<S, F>(test_name: &str, open: F) -> Result<()>
+where
+    S: CompactionStrategy<u32, u64>,
+    F: Fn() -> Result<S>,
+{
+    let data_path = format!("{}_data", test_name);
+    fs::create_dir(&data_path)?;
+    let mut sstable_builder = SSTableBuilder::new(&data_path, 1000)?;
+    for key in 0..1000u32 {
+        sstable_builder.insert(key * 2, u64::from(key))?;
+    }
+    let sstable_path = sstable_builder.finish()?;
+
+    let mut map = LsmMap::new(open()?);
+    for key in 0..500u32 {
+        map.insert(key * 4 + 1, 0)?;
+        map.insert(key * 4, 0)?;
+    }
+    map.remove(2)?;
+    map.ingest_sstable(&sstable_path)?;
+    map.insert(4, 0)?;
+    map.ingest_sstable(&sstable_path)?;
+    map.insert(6, 0)?;
+
+    let check = |map: &mut LsmMap<u32, u64, S>| -> Result<()> {
+        assert_eq!(map.len()?, 1500);
+        for key in 0..1000u32 {
+            let expected = if key == 3 { 0 } else { u64::from(key) };
+            assert_eq!(map.get(&(key * 2))?, Some(expected));
+        }
+        for key in 0..500u32 {
+            assert_eq!(map.get(&(key * 4 + 1))?, Some(0));
+        }
+        Ok(())
+    };
+    check(&mut map)?;
+    map.close()?;
+    let mut map = LsmMap::new(open()?);
+    check(&mut map)?;
+    map.close()?;
+
+    fs::remove_dir_all(&data_path)?;
+    Ok(())
+}
+
+#[test]
+fn int_test_lsm_map_size_tiered_strategy_ingest_sstable() -> Result<()> {
+    let test_name = "int_test_lsm_map_size_tiered_strategy_ingest_sstable";
+    run_test(
+        || {
+            SizeTieredStrategy::<u32, u64>::new(test_name, 1000, 4, 4000, 0.5, 1.5)?.close()?;
+            check_ingest_sstable(test_name, || SizeTieredStrategy::open(test_name))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_leveled_strategy_ingest_sstable() -> Result<()> {
+    let test_name = "int_test_lsm_map_leveled_strategy_ingest_sstable";
+    run_test(
+        || {
+            LeveledStrategy::<u32, u64>::new(test_name, 1000, 4, 4000, 10, 10)?.close()?;
+            check_ingest_sstable(test_name, || LeveledStrategy::open(test_name))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_leveled_strategy_close() -> Result<()> {
+    let test_name = "int_test_lsm_map_leveled_strategy_close";
+    run_test(
+        || {
+            let mut ls: LeveledStrategy<u32, u64> =
+                LeveledStrategy::new(test_name, 1000, 4, 4000, 10, 10)?;
+            ls.close()?;
+            match ls.get(&0u32) {
+                Err(Error::Closed) => {}
+                _ => panic!("Expected a closed error."),
+            }
+
+            let mut map: LsmMap<u32, u64, _> = LsmMap::new(LeveledStrategy::open(test_name)?);
+            for key in 0..1000u32 {
+                map.insert(key, u64::from(key))?;
+            }
+            map.close()?;
+
+            let mut map: LsmMap<u32, u64, _> = LsmMap::new(LeveledStrategy::open(test_name)?);
+            assert_eq!(map.len()?, 1000);
+            for key in 0..1000u32 {
+                assert_eq!(map.get(&key)?, Some(u64::from(key)));
+            }
+            Ok(())
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_filter_stats() -> Result<()> {
+    let test_name = "int_test_lsm_map_filter_stats";
+    run_test(
+        || {
+            let mut sts: SizeTieredStrategy<u32, u64> =
+                SizeTieredStrategy::new(test_name, 1000, 1000, 4000, 0.5, 1.5)?;
+            sts.set_filter_auto_tuning(Some(0.01));
+            let mut map = LsmMap::new(sts);
+            for key in 0..1000u32 {
+                map.insert(key * 2, u64::from(key))?;
+            }
+            map.flush()?;
+
+            for key in 0..2000u32 {
+                assert_eq!(map.get(&key)?.is_some(), key % 2 == 0);
+            }
+
+            let filter_stats = map.filter_stats()?;
+            assert!(!filter_stats.is_empty());
+            let true_positive_count: usize = filter_stats
+                .iter()
+                .map(|filter_stats| filter_stats.true_positive_count)
+                .sum();
+            assert_eq!(true_positive_count, 1000);
+            for filter_stats in &filter_stats {
+                assert!(
+                    filter_stats.true_positive_count + filter_stats.false_positive_count
+                        <= filter_stats.query_count
+                );
+            }
+            Ok(())
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_partitioned_filters() -> Result<()> {
+    let test_name = "int_test_lsm_map_partitioned_filters";
+    run_test(
+        || {
+            let mut ls: LeveledStrategy<u32, u64> =
+                LeveledStrategy::new(test_name, 1000, 4, 4000, 2, 4)?;
+            ls.set_filter_bits_per_key(10.0);
+            ls.set_filter_partition_threshold(Some(100));
+            let mut map = LsmMap::new(ls);
+            for key in 0..5000u32 {
+                map.insert(key * 2, u64::from(key))?;
+            }
+            map.flush()?;
+
+            for key in 0..10000u32 {
+                assert_eq!(
+                    map.get(&key)?,
+                    Some(u64::from(key / 2)).filter(|_| key % 2 == 0)
+                );
+            }
+
+            let true_positive_count: usize = map
+                .filter_stats()?
+                .iter()
+                .map(|filter_stats| filter_stats.true_positive_count)
+                .sum();
+            assert_eq!(true_positive_count, 5000);
+            map.close()
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_stats() -> Result<()> {
+    let test_name = "int_test_lsm_map_stats";
+    run_test(
+        || {
+            let mut rng: rand::XorShiftRng = rand::SeedableRng::from_seed([1, 1, 1, 1]);
+            let lts = LeveledStrategy::new(test_name, 1000, 4, 4000, 10, 10)?;
+            let mut map = LsmMap::new(lts);
+            for _ in 0..10000 {
+                let key = rng.gen_range(0, 1000u32);
+                if rng.gen::<bool>() {
+                    map.insert(key, rng.gen::<u64>())?;
+                } else {
+                    map.remove(key)?;
+                }
+            }
+            map.flush()?;
+
+            let stats = map.stats()?;
+            assert!(stats.levels.len() > 1);
+            assert!(stats.sstable_count() > 0);
+            assert_eq!(
+                stats.disk_size(),
+                stats
+                    .levels
+                    .iter()
+                    .map(|level| level.disk_size)
+                    .sum::<u64>(),
+            );
+            for level in &stats.levels {
+                assert!(level.tombstone_count <= level.entry_count);
+                assert!(level.sstable_count == 0 || level.disk_size > 0);
+            }
+            assert!(stats.flushed_size > 0);
+            assert!(stats.compacted_size > 0);
+            assert!(stats.write_amplification() > 1.0);
+            map.close()
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_secondary_index() -> Result<()> {
+    let test_name = "int_test_lsm_map_secondary_index";
+    let index_name = "int_test_lsm_map_secondary_index_bucket";
+    let result = run_test(
+        || {
+            let mut rng: rand::XorShiftRng = rand::SeedableRng::from_seed([1, 1, 1, 1]);
+            let sts = SizeTieredStrategy::new(test_name, 1000, 4, 4000, 0.5, 1.5)?;
+            let mut map = LsmMap::new(sts);
+            let mut expected = vec![None; 500];
+
+            for key in 0..250u32 {
+                let val = rng.gen::<u64>();
+                map.insert(key, val)?;
+                expected[key as usize] = Some(val);
+            }
+
+            let sts = SizeTieredStrategy::new(index_name, 1000, 4, 4000, 0.5, 1.5)?;
+            map.create_index("bucket", sts, |value: &u64| value % 10)?;
+
+            for _ in 0..5000 {
+                let key = rng.gen_range(0, 500u32);
+                if rng.gen::<bool>() {
+                    let val = rng.gen::<u64>();
+                    map.insert(key, val)?;
+                    expected[key as usize] = Some(val);
+                } else {
+                    map.remove(key)?;
+                    expected[key as usize] = None;
+                }
+            }
+
+            for bucket in 0..10u64 {
+                let expected_keys: Vec<u32> = (0..500u32)
+                    .filter(|key| expected[*key as usize].map(|val| val % 10) == Some(bucket))
+                    .collect();
+                assert_eq!(map.get_by_index("bucket", &bucket)?, expected_keys);
+            }
+
+            map.clear()?;
+            assert_eq!(map.get_by_index("bucket", &0u64)?, Vec::<u32>::new());
+            map.close()
+        },
+        test_name,
+    );
+    teardown(index_name);
+    result
+}
+
+fn test_compaction_scheduling<C>(
+    mut map: LsmMap<u32, u64, C>,
+    max_first_level_len: Option<usize>,
+) -> Result<()>
+where
+    C: CompactionStrategy<u32, u64>,
+{
+    let mut rng: rand::XorShiftRng = rand::SeedableRng::from_seed([1, 1, 1, 1]);
+    let mut expected = vec![None; 2000];
+
+    for index in 0..20_000 {
+        let key = rng.gen_range(0, 2000u32);
+        if rng.gen_range(0, 4) == 0 {
+            map.remove(key)?;
+            expected[key as usize] = None;
+        } else {
+            let val = rng.gen::<u64>();
+            map.insert(key, val)?;
+            expected[key as usize] = Some(val);
+        }
+
+        if let Some(max_first_level_len) = max_first_level_len {
+            if index % 100 == 0 {
+                assert!(map.stats()?.levels[0].sstable_count <= max_first_level_len);
+            }
+        }
+    }
+
+    map.flush()?;
+    for key in 0..2000u32 {
+        assert_eq!(map.get(&key)?, expected[key as usize]);
+    }
+    assert_eq!(
+        map.len()?,
+        expected.iter().filter(|val| val.is_some()).count(),
+    );
+    map.close()
+}
+
+#[test]
+fn int_test_lsm_map_leveled_strategy_scheduling() -> Result<()> {
+    let test_name = "int_test_lsm_map_leveled_strategy_scheduling";
+    run_test(
+        || {
+            let mut ls = LeveledStrategy::new(test_name, 1000, 4, 4000, 2, 4)?;
+            ls.set_max_concurrent_compactions(4);
+            ls.set_compaction_rate_limit(Some(64 << 20));
+            ls.set_write_stall_threshold(Some(6));
+            test_compaction_scheduling(LsmMap::new(ls), Some(6))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_size_tiered_strategy_scheduling() -> Result<()> {
+    let test_name = "int_test_lsm_map_size_tiered_strategy_scheduling";
+    run_test(
+        || {
+            let mut sts = SizeTieredStrategy::new(test_name, 1000, 4, 4000, 0.5, 1.5)?;
+            sts.set_compaction_rate_limit(Some(64 << 20));
+            sts.set_write_stall_threshold(Some(8));
+            test_compaction_scheduling(LsmMap::new(sts), None)
+        },
+        test_name,
+    )
+}
+
+fn test_remove_range<C, F>(test_name: &str, mut map: LsmMap<u32, u64, C>, open: F) -> Result<()>
+where
+    C: CompactionStrategy<u32, u64>,
+    F: Fn(&str) -> Result<C>,
+{
+    let mut rng: rand::XorShiftRng = rand::SeedableRng::from_seed([1, 1, 1, 1]);
+    let mut expected = vec![None; 5000];
+
+    for _ in 0..20 {
+        for _ in 0..1000 {
+            let key = rng.gen_range(0, 5000u32);
+            let val = rng.gen::<u64>();
+            map.insert(key, val)?;
+            expected[key as usize] = Some(val);
+        }
+
+        let start = rng.gen_range(0, 5000u32);
+        let end = rng.gen_range(start, 5001u32);
+        map.remove_range(start, end)?;
+        for val in &mut expected[start as usize..end as usize] {
+            *val = None;
+        }
+
+        for _ in 0..100 {
+            let key = rng.gen_range(0, 5000u32);
+            assert_eq!(map.get(&key)?, expected[key as usize]);
+        }
+    }
+
+    map.close()?;
+    map = LsmMap::new(open(test_name)?);
+
+    for key in 0..5000u32 {
+        assert_eq!(map.get(&key)?, expected[key as usize]);
+    }
+    let entries = map.iter()?.collect::<Result<Vec<(u32, u64)>>>()?;
+    let expected_entries: Vec<(u32, u64)> = expected
+        .iter()
+        .enumerate()
+        .filter_map(|(key, val)| val.map(|val| (key as u32, val)))
+        .collect();
+    assert_eq!(entries, expected_entries);
+
+    map.remove_range(0, 5000)?;
+    assert_eq!(map.min()?, None);
+    assert_eq!(map.max()?, None);
+    assert_eq!(map.len()?, 0);
+    map.close()
+}
+
+#[test]
+fn int_test_lsm_map_size_tiered_strategy_remove_range() -> Result<()> {
+    let test_name = "int_test_lsm_map_size_tiered_strategy_remove_range";
+    run_test(
+        || {
+            let sts = SizeTieredStrategy::new(test_name, 1000, 4, 4000, 0.5, 1.5)?;
+            test_remove_range(test_name, LsmMap::new(sts), |path| {
+                SizeTieredStrategy::open(path)
+            })
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_leveled_strategy_remove_range() -> Result<()> {
+    let test_name = "int_test_lsm_map_leveled_strategy_remove_range";
+    run_test(
+        || {
+            let ls = LeveledStrategy::new(test_name, 1000, 4, 4000, 2, 4)?;
+            test_remove_range(test_name, LsmMap::new(ls), |path| {
+                LeveledStrategy::open(path)
+            })
+        },
+        test_name,
+    )
+}
+
+fn test_range<C>(mut map: LsmMap<u32, u64, C>) -> Result<()>
+where
+    C: CompactionStrategy<u32, u64>,
+{
+    let mut rng: rand::XorShiftRng = rand::SeedableRng::from_seed([1, 1, 1, 1]);
+    let mut expected = vec![None; 5000];
+
+    for _ in 0..10000 {
+        let key = rng.gen_range(0, 5000u32);
+        if rng.gen::<bool>() {
+            let val = rng.gen::<u64>();
+            map.insert(key, val)?;
+            expected[key as usize] = Some(val);
+        } else {
+            map.remove(key)?;
+            expected[key as usize] = None;
+        }
+    }
+    let expected_entries: Vec<(u32, u64)> = expected
+        .iter()
+        .enumerate()
+        .filter_map(|(key, val)| val.map(|val| (key as u32, val)))
+        .collect();
+
+    for _ in 0..100 {
+        let start = rng.gen_range(0, 5001u32);
+        let end = rng.gen_range(start, 5001u32);
+        let entries = map
+            .range(&start, &end)?
+            .collect::<Result<Vec<(u32, u64)>>>()?;
+        let expected_range: Vec<(u32, u64)> = expected_entries
+            .iter()
+            .cloned()
+            .filter(|entry| start <= entry.0 && entry.0 < end)
+            .collect();
+        assert_eq!(entries, expected_range);
+    }
+
+    let mut iter = map.iter_from(&0)?;
+    for _ in 0..100 {
+        let key = rng.gen_range(0, 5001u32);
+        iter.seek(&key)?;
+        let expected_entry = expected_entries.iter().find(|entry| entry.0 >= key);
+        assert_eq!(iter.next().transpose()?.as_ref(), expected_entry);
+    }
+    drop(iter);
+
+    let mut pages = Vec::new();
+    let mut start = 0;
+    loop {
+        let page = map
+            .iter_from(&start)?
+            .take(100)
+            .collect::<Result<Vec<(u32, u64)>>>()?;
+        match page.last() {
+            Some(entry) => start = entry.0 + 1,
+            None => break,
+        }
+        pages.extend(page);
+    }
+    assert_eq!(pages, expected_entries);
+    map.close()
+}
+
+#[test]
+fn int_test_lsm_map_size_tiered_strategy_range() -> Result<()> {
+    let test_name = "int_test_lsm_map_size_tiered_strategy_range";
+    run_test(
+        || {
+            let sts = SizeTieredStrategy::new(test_name, 1000, 4, 4000, 0.5, 1.5)?;
+            test_range(LsmMap::new(sts))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_leveled_strategy_range() -> Result<()> {
+    let test_name = "int_test_lsm_map_leveled_strategy_range";
+    run_test(
+        || {
+            let ls = LeveledStrategy::new(test_name, 1000, 4, 4000, 2, 4)?;
+            test_range(LsmMap::new(ls))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_size_tiered_strategy_io_options() -> Result<()> {
+    let test_name = "int_test_lsm_map_size_tiered_strategy_io_options";
+    run_test(
+        || {
+            let mut sts = SizeTieredStrategy::new(test_name, 1000, 4, 4000, 0.5, 1.5)?;
+            sts.set_io_options(IoOptions {
+                write_buffer_size: 100,
+                sync_on_seal: true,
+                direct_io: true,
+            });
+            test_range(LsmMap::new(sts))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_leveled_strategy_io_options() -> Result<()> {
+    let test_name = "int_test_lsm_map_leveled_strategy_io_options";
+    run_test(
+        || {
+            let mut ls = LeveledStrategy::new(test_name, 1000, 4, 4000, 2, 4)?;
+            ls.set_io_options(IoOptions {
+                write_buffer_size: 100,
+                sync_on_seal: true,
+                direct_io: true,
+            });
+            test_range(LsmMap::new(ls))
+        },
+        test_name,
+    )
+}
+
+#[derive(Default)]
+struct EventCounter {
+    flushes: AtomicUsize,
+    compaction_starts: AtomicUsize,
+    compaction_ends: AtomicUsize,
+}
+
+impl EventListener for EventCounter {
+    fn on_flush(&self, info: &FlushInfo) {
+        assert!(info.path.exists());
+        assert!(info.entry_count > 0);
+        self.flushes.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_compaction_start(&self, info: &CompactionInfo) {
+        assert!(info.sstable_count > 0);
+        self.compaction_starts.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_compaction_end(&self, _info: &CompactionInfo, result: std::result::Result<u64, &Error>) {
+        assert!(result.is_ok());
+        self.compaction_ends.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn check_event_counter(event_counter: &EventCounter) {
+    let compaction_starts = event_counter.compaction_starts.load(Ordering::SeqCst);
+    assert!(event_counter.flushes.load(Ordering::SeqCst) > 0);
+    assert!(compaction_starts > 0);
+    assert_eq!(
+        event_counter.compaction_ends.load(Ordering::SeqCst),
+        compaction_starts,
+    );
+}
+
+#[test]
+fn int_test_lsm_map_size_tiered_strategy_event_listener() -> Result<()> {
+    let test_name = "int_test_lsm_map_size_tiered_strategy_event_listener";
+    run_test(
+        || {
+            let event_counter = Arc::new(EventCounter::default());
+            let mut sts = SizeTieredStrategy::new(test_name, 1000, 4, 4000, 0.5, 1.5)?;
+            sts.set_event_listener(Some(event_counter.clone()));
+            test_compaction_scheduling(LsmMap::new(sts), None)?;
+            check_event_counter(&event_counter);
+            Ok(())
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_leveled_strategy_event_listener() -> Result<()> {
+    let test_name = "int_test_lsm_map_leveled_strategy_event_listener";
+    run_test(
+        || {
+            let event_counter = Arc::new(EventCounter::default());
+            let mut ls = LeveledStrategy::new(test_name, 1000, 4, 4000, 2, 4)?;
+            ls.set_event_listener(Some(event_counter.clone()));
+            test_compaction_scheduling(LsmMap::new(ls), None)?;
+            check_event_counter(&event_counter);
+            Ok(())
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_size_tiered_strategy_index_block_size() -> Result<()> {
+    let test_name = "int_test_lsm_map_size_tiered_strategy_index_block_size";
+    run_test(
+        || {
+            SizeTieredStrategy::<u32, u64>::with_index_block_size(
+                test_name, 1000, 4, 4000, 0.5, 1.5, 4,
+            )?
+            .close()?;
+            let sts = SizeTieredStrategy::open(test_name)?;
+            assert_eq!(sts.get_index_block_size(), Some(4));
+            test_range(LsmMap::new(sts))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_leveled_strategy_index_block_size() -> Result<()> {
+    let test_name = "int_test_lsm_map_leveled_strategy_index_block_size";
+    run_test(
+        || {
+            LeveledStrategy::<u32, u64>::with_index_block_size(test_name, 1000, 4, 4000, 2, 4, 4)?
+                .close()?;
+            let ls = LeveledStrategy::open(test_name)?;
+            assert_eq!(ls.get_index_block_size(), Some(4));
+            test_range(LsmMap::new(ls))
+        },
+        test_name,
+    )
+}
+
+// Lists the SSTable folders of the compaction strategy at `path`.
+fn sstable_dirs(path: &str) -> Result<Vec<PathBuf>> {
+    let mut sstable_dirs = Vec::new();
+    for dir_entry in fs::read_dir(path)? {
+        let dir_path = dir_entry?.path();
+        if dir_path.is_dir() {
+            sstable_dirs.push(dir_path);
+        }
+    }
+    Ok(sstable_dirs)
+}
+
+fn test_range_pruning<C>(test_name: &str, mut map: LsmMap<u32, u64, C>) -> Result<()>
+where
+    C: CompactionStrategy<u32, u64>,
+{
+    for key in 0..100 {
+        map.insert(key, u64::from(key))?;
+    }
+    map.flush()?;
+    let lower_sstable_dirs = sstable_dirs(test_name)?;
+    for key in 100..200 {
+        map.insert(key, u64::from(key))?;
+    }
+    map.flush()?;
+
+    // the SSTable with the keys in `[100, 200)` can no longer be read, so only iterators that skip
+    // it succeed.
+    for sstable_dir in sstable_dirs(test_name)? {
+        if !lower_sstable_dirs.contains(&sstable_dir) {
+            fs::remove_file(sstable_dir.join("data.dat"))?;
+        }
+    }
+
+    let entries = map.range(&10, &100)?.collect::<Result<Vec<(u32, u64)>>>()?;
+    let expected_entries: Vec<(u32, u64)> = (10..100).map(|key| (key, u64::from(key))).collect();
+    assert_eq!(entries, expected_entries);
+
+    let mut iter = map.range(&0, &50)?;
+    iter.seek(&40)?;
+    assert_eq!(iter.next().transpose()?, Some((40, 40)));
+    drop(iter);
+
+    let entries = map
+        .range(&50, &150)
+        .and_then(|iter| iter.collect::<Result<Vec<(u32, u64)>>>());
+    assert!(entries.is_err());
+    let entries = map
+        .iter()
+        .and_then(|iter| iter.collect::<Result<Vec<(u32, u64)>>>());
+    assert!(entries.is_err());
+    Ok(())
+}
+
+#[test]
+fn int_test_lsm_map_size_tiered_strategy_range_pruning() -> Result<()> {
+    let test_name = "int_test_lsm_map_size_tiered_strategy_range_pruning";
+    run_test(
+        || {
+            let sts = SizeTieredStrategy::new(test_name, 100_000, 4, 400_000, 0.5, 1.5)?;
+            test_range_pruning(test_name, LsmMap::new(sts))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_leveled_strategy_range_pruning() -> Result<()> {
+    let test_name = "int_test_lsm_map_leveled_strategy_range_pruning";
+    run_test(
+        || {
+            let ls = LeveledStrategy::new(test_name, 100_000, 4, 400_000, 2, 4)?;
+            test_range_pruning(test_name, LsmMap::new(ls))
+        },
+        test_name,
+    )
+}
+
+fn test_range_prefix_pruning<C>(test_name: &str, mut map: LsmMap<u32, u64, C>) -> Result<()>
+where
+    C: CompactionStrategy<u32, u64>,
+{
+    for key in (0..50).chain(1000..1050) {
+        map.insert(key, u64::from(key))?;
+    }
+    map.flush()?;
+    let outer_sstable_dirs = sstable_dirs(test_name)?;
+    for key in 500..550 {
+        map.insert(key, u64::from(key))?;
+    }
+    map.flush()?;
+
+    // the SSTable with the keys in `[0, 50)` and `[1000, 1050)` can no longer be read, but it
+    // does not contain any keys with the prefix of the keys in `[500, 600)`.
+    for sstable_dir in outer_sstable_dirs {
+        fs::remove_file(sstable_dir.join("data.dat"))?;
+    }
+
+    let entries = map
+        .range(&500, &599)?
+        .collect::<Result<Vec<(u32, u64)>>>()?;
+    let expected_entries: Vec<(u32, u64)> = (500..550).map(|key| (key, u64::from(key))).collect();
+    assert_eq!(entries, expected_entries);
+
+    let entries = map
+        .range(&500, &600)
+        .and_then(|iter| iter.collect::<Result<Vec<(u32, u64)>>>());
+    assert!(entries.is_err());
+    Ok(())
+}
+
+#[test]
+fn int_test_lsm_map_size_tiered_strategy_range_prefix_pruning() -> Result<()> {
+    let test_name = "int_test_lsm_map_size_tiered_strategy_range_prefix_pruning";
+    run_test(
+        || {
+            let mut sts = SizeTieredStrategy::new(test_name, 100_000, 4, 400_000, 0.5, 1.5)?;
+            sts.set_filter_false_positive_rate(0.0001);
+            sts.set_prefix_extractor(Some(Arc::new(|key: &u32| vec![(key / 100) as u8])));
+            test_range_prefix_pruning(test_name, LsmMap::new(sts))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_leveled_strategy_range_prefix_pruning() -> Result<()> {
+    let test_name = "int_test_lsm_map_leveled_strategy_range_prefix_pruning";
+    run_test(
+        || {
+            let mut ls = LeveledStrategy::new(test_name, 100_000, 4, 400_000, 2, 4)?;
+            ls.set_filter_false_positive_rate(0.0001);
+            ls.set_prefix_extractor(Some(Arc::new(|key: &u32| vec![(key / 100) as u8])));
+            test_range_prefix_pruning(test_name, LsmMap::new(ls))
+        },
+        test_name,
+    )
+}
+
+fn test_checkpoint_restore<C>(test_name: &str, mut map: LsmMap<u32, u64, C>) -> Result<()>
+where
+    C: CompactionStrategy<u32, u64>,
+{
+    let checkpoint_name = format!("{}_checkpoint", test_name);
+    let restored_name = format!("{}_restored", test_name);
+    let result = (|| {
+        for key in 0..5000 {
+            map.insert(key, u64::from(key))?;
+        }
+        map.checkpoint(&checkpoint_name)?;
+        for key in 0..5000 {
+            map.insert(key, 0)?;
+        }
+        map.flush()?;
+
+        let mut restored: LsmMap<u32, u64, C> = LsmMap::restore(&checkpoint_name, &restored_name)?;
+        assert_eq!(restored.len()?, 5000);
+        for key in 0..5000 {
+            assert_eq!(restored.get(&key)?, Some(u64::from(key)));
+            assert_eq!(map.get(&key)?, Some(0));
+        }
+        restored.close()?;
+        map.close()
+    })();
+    teardown(&checkpoint_name);
+    teardown(&restored_name);
+    result
+}
+
+#[test]
+fn int_test_lsm_map_size_tiered_strategy_checkpoint() -> Result<()> {
+    let test_name = "int_test_lsm_map_size_tiered_strategy_checkpoint";
+    run_test(
+        || {
+            let sts = SizeTieredStrategy::new(test_name, 1000, 4, 4000, 0.5, 1.5)?;
+            test_checkpoint_restore(test_name, LsmMap::new(sts))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_leveled_strategy_checkpoint() -> Result<()> {
+    let test_name = "int_test_lsm_map_leveled_strategy_checkpoint";
+    run_test(
+        || {
+            let ls = LeveledStrategy::new(test_name, 1000, 4, 4000, 10, 10)?;
+            test_checkpoint_restore(test_name, LsmMap::new(ls))
+        },
+        test_name,
+    )
+}
+
+fn test_concurrent_readers<C>(mut map: LsmMap<u32, u64, C>) -> Result<()>
+where
+    C: CompactionStrategy<u32, u64>,
+{
+    let reader = map.reader()?;
+    let is_done = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|offset| {
+                let reader = reader.clone();
+                let is_done = &is_done;
+                scope.spawn(move || -> Result<()> {
+                    while !is_done.load(Ordering::Acquire) {
+                        for key in (offset..5000).step_by(4) {
+                            if let Some(value) = reader.get(&key)? {
+                                assert_eq!(value, u64::from(key) * 2);
+                            }
+                        }
+                    }
+                    Ok(())
+                })
+            })
+            .collect();
+
+        let result = (|| {
+            for key in 0..5000 {
+                map.insert(key, u64::from(key) * 2)?;
+            }
+            map.flush()
+        })();
+        is_done.store(true, Ordering::Release);
+
+        for handle in handles {
+            handle.join().expect("Expected thread to succeed.")?;
+        }
+        result
+    })?;
+
+    for key in 0..5000 {
+        assert_eq!(reader.get(&key)?, Some(u64::from(key) * 2));
+    }
+    map.remove_range(0, 2500)?;
+    map.remove(2500)?;
+    for key in 0..5000 {
+        let expected = if key <= 2500 {
+            None
+        } else {
+            Some(u64::from(key) * 2)
+        };
+        assert_eq!(reader.clone().get(&key)?, expected);
+    }
+
+    map.clear()?;
+    assert!(!reader.contains_key(&4999)?);
+    map.close()
+}
+
+#[test]
+fn int_test_lsm_map_size_tiered_strategy_concurrent_readers() -> Result<()> {
+    let test_name = "int_test_lsm_map_size_tiered_strategy_concurrent_readers";
+    run_test(
+        || {
+            let sts = SizeTieredStrategy::new(test_name, 1000, 4, 4000, 0.5, 1.5)?;
+            test_concurrent_readers(LsmMap::new(sts))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_leveled_strategy_concurrent_readers() -> Result<()> {
+    let test_name = "int_test_lsm_map_leveled_strategy_concurrent_readers";
+    run_test(
+        || {
+            let ls = LeveledStrategy::new(test_name, 1000, 4, 4000, 2, 4)?;
+            test_concurrent_readers(LsmMap::new(ls))
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_database() -> Result<()> {
+    let test_name = "int_test_lsm_database";
+    run_test(
+        || {
+            let mut db = LsmDatabase::new(test_name)?;
+            db.add_cf("sts", |path| {
+                SizeTieredStrategy::<u32, u64>::new(path, 1000, 4, 4000, 0.5, 1.5)
+            })?;
+            db.add_cf("ls", |path| {
+                LeveledStrategy::<String, u32>::new(path, 1000, 4, 4000, 2, 4)
+            })?;
+
+            let mut expected = Vec::new();
+            for key in 0..1000u32 {
+                let sts: &mut LsmMap<u32, u64, SizeTieredStrategy<u32, u64>> = db.cf("sts");
+                sts.insert(key, u64::from(key))?;
+                let ls: &mut LsmMap<String, u32, LeveledStrategy<String, u32>> = db.cf("ls");
+                ls.insert(key.to_string(), key)?;
+                expected.push((key.to_string(), key));
+            }
+            expected.sort();
+            db.close()?;
+
+            // the writes to both column families are ordered by the shared logical clock.
+            let sts_path = Path::new(test_name).join("sts");
+            let sts: SizeTieredStrategy<u32, u64> = SizeTieredStrategy::open(&sts_path)?;
+            assert_eq!(sts.get_logical_time(), 1999);
+            LsmMap::new(sts).close()?;
+
+            let mut db = LsmDatabase::open(test_name)?;
+            let ls = db.add_cf("ls", LeveledStrategy::<String, u32>::open)?;
+            assert_eq!(ls.iter()?.collect::<Result<Vec<_>>>()?, expected);
+            let sts = db.add_cf("sts", SizeTieredStrategy::<u32, u64>::open)?;
+            sts.insert(0, 1)?;
+            db.remove_cf("ls")?;
+            assert!(!db.contains_cf("ls"));
+            assert!(!Path::new(test_name).join("ls").exists());
+            db.close()?;
+
+            let sts: SizeTieredStrategy<u32, u64> = SizeTieredStrategy::open(&sts_path)?;
+            assert_eq!(sts.get_logical_time(), 2001);
+            LsmMap::new(sts).close()?;
+            Ok(())
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_drop() -> Result<()> {
+    let test_name = "int_test_lsm_map_drop";
+    run_test(
+        || {
+            let sts = SizeTieredStrategy::new(test_name, 100_000, 4, 4000, 0.5, 1.5)?;
+            let mut map = LsmMap::new(sts);
+            for key in 0..100u32 {
+                map.insert(key, u64::from(key))?;
+            }
+            drop(map);
+
+            let mut map: LsmMap<u32, u64, _> = LsmMap::new(SizeTieredStrategy::open(test_name)?);
+            assert_eq!(map.len()?, 100);
+            for key in 0..100u32 {
+                assert_eq!(map.get(&key)?, Some(u64::from(key)));
+            }
+            map.close()
+        },
+        test_name,
+    )
+}
+
+#[test]
+fn int_test_lsm_map_sync_mode() -> Result<()> {
+    let test_name = "int_test_lsm_map_sync_mode";
+    run_test(
+        || {
+            let ls = LeveledStrategy::new(test_name, 100_000, 4, 4000, 2, 4)?;
+            let mut map = LsmMap::new(ls);
+            map.set_sync_mode(SyncMode::Always);
+            assert_eq!(map.sync_mode(), SyncMode::Always);
+            for key in 0..10u32 {
+                map.insert(key, u64::from(key))?;
+            }
+            map.remove(0)?;
+            map.remove_range(8, 10)?;
+            // the map is not dropped, so only the synced writes are persisted.
+            mem::forget(map);
+
+            let mut map: LsmMap<u32, u64, _> = LsmMap::new(LeveledStrategy::open(test_name)?);
+            assert_eq!(
+                map.iter()?.collect::<Result<Vec<(u32, u64)>>>()?,
+                (1..8).map(|key| (key, u64::from(key))).collect::<Vec<_>>(),
+            );
+            map.close()
+        },
+        test_name,
+    )
+}
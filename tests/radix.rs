@@ -1,5 +1,5 @@
 use extended_collections::radix::RadixMap;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 use std::iter;
 use std::vec::Vec;
 
@@ -51,7 +51,7 @@ fn int_test_radix_map() {
         assert_eq!(map.get(&entry.0), Some(&val_2));
     }
 
-    thread_rng().shuffle(&mut expected);
+    rng.shuffle(&mut expected);
 
     let mut expected_len = expected.len();
     for entry in expected {
@@ -0,0 +1,111 @@
+use extended_collections::avl_tree::AvlMap;
+use extended_collections::red_black_tree::RedBlackMap;
+#[cfg(feature = "std")]
+use extended_collections::rope::Rope;
+use extended_collections::skiplist::{SkipList, SkipMap, SkipSet};
+use extended_collections::splay_tree::SplayMap;
+#[cfg(feature = "sync")]
+use extended_collections::sync;
+use extended_collections::treap::{TreapList, TreapMap};
+use std::sync::Arc;
+use std::thread;
+
+fn assert_send<T: Send>(_: &T) {}
+
+fn assert_sync<T: Sync>(_: &T) {}
+
+fn assert_send_sync<T: Send + Sync>(value: &T) {
+    assert_send(value);
+    assert_sync(value);
+}
+
+#[test]
+fn int_test_send_sync() {
+    let mut list: SkipList<u32> = SkipList::new();
+    assert_send_sync(&list);
+    assert_send_sync(&list.iter());
+    assert_send_sync(&list.iter_mut());
+    assert_send_sync(&SkipList::<u32>::new().into_iter());
+
+    let mut map: SkipMap<u32, String> = SkipMap::new();
+    assert_send_sync(&map);
+    assert_send_sync(&map.iter());
+    assert_send_sync(&map.iter_mut());
+    assert_send_sync(&SkipMap::<u32, String>::new().into_iter());
+
+    let set: SkipSet<u32> = SkipSet::new();
+    assert_send_sync(&set);
+    assert_send_sync(&set.iter());
+
+    assert_send_sync(&TreapMap::<u32, String>::new());
+    assert_send_sync(&TreapList::<u32>::new());
+    assert_send_sync(&AvlMap::<u32, String>::new());
+    assert_send_sync(&RedBlackMap::<u32, String>::new());
+    assert_send_sync(&SplayMap::<u32, String>::new());
+    #[cfg(feature = "std")]
+    assert_send_sync(&Rope::from(""));
+}
+
+#[cfg(feature = "sync")]
+#[test]
+fn int_test_send_sync_concurrent() {
+    let sync_map: sync::HashMap<u32, u32> = sync::HashMap::new();
+    sync_map.insert(1, 1);
+    assert_send_sync(&sync_map);
+    assert_sync(&sync_map.get(&1).expect("Expected value."));
+    assert_sync(&sync_map.get_mut(&1).expect("Expected value."));
+}
+
+#[test]
+fn int_test_move_across_threads() {
+    let mut list = SkipList::new();
+    let mut map = SkipMap::new();
+    for i in 0..1000u32 {
+        list.push_back(i);
+        map.insert(i, i.to_string());
+    }
+
+    let (list, map) = thread::spawn(move || {
+        for value in list.iter_mut() {
+            *value += 1;
+        }
+        map.remove(&0);
+        (list, map)
+    })
+    .join()
+    .expect("Expected thread to succeed.");
+
+    assert_eq!(
+        list.iter().cloned().collect::<Vec<u32>>(),
+        (1..1001).collect::<Vec<u32>>()
+    );
+    assert_eq!(map.len(), 999);
+    assert_eq!(map.get(&1), Some(&String::from("1")));
+}
+
+#[test]
+fn int_test_share_across_threads() {
+    let mut map = SkipMap::new();
+    for i in 0..1000u32 {
+        map.insert(i, i * 2);
+    }
+    let map = Arc::new(map);
+
+    let handles: Vec<_> = (0..4)
+        .map(|offset| {
+            let map = Arc::clone(&map);
+            thread::spawn(move || {
+                (offset..1000)
+                    .step_by(4)
+                    .map(|key| *map.get(&key).expect("Expected value."))
+                    .sum::<u32>()
+            })
+        })
+        .collect();
+
+    let sum: u32 = handles
+        .into_iter()
+        .map(|handle| handle.join().expect("Expected thread to succeed."))
+        .sum();
+    assert_eq!(sum, map.iter().map(|pair| *pair.1).sum());
+}